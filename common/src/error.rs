@@ -0,0 +1,37 @@
+//! The error trait implemented by Clack's error types.
+//!
+//! When the `std` feature is enabled (which is the default), [`Error`] is simply a re-export of
+//! [`std::error::Error`].
+//!
+//! Otherwise, it is a minimal stand-in for it, with the same [`source`](Error::source) method, as
+//! `core::error::Error` is not available on Clack's minimum supported Rust version. Errors that
+//! are meant to be converted into Clack's own error types (e.g. with the `?` operator) have to
+//! implement this trait.
+
+#[cfg(feature = "std")]
+pub use std::error::Error;
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use core::fmt::{Debug, Display};
+
+    /// A stand-in for `std::error::Error`, for environments without the standard library.
+    ///
+    /// See the [module documentation](super) for more information.
+    pub trait Error: Debug + Display {
+        /// Returns the lower-level source of this error, if any.
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            None
+        }
+    }
+
+    impl Error for core::fmt::Error {}
+    impl Error for core::str::Utf8Error {}
+    impl Error for alloc::ffi::NulError {}
+    impl Error for core::ffi::FromBytesWithNulError {}
+    impl Error for core::num::ParseIntError {}
+    impl Error for core::num::ParseFloatError {}
+}
+
+#[cfg(not(feature = "std"))]
+pub use no_std::Error;
@@ -9,13 +9,15 @@
 //! [`OutputEventBuffer`](io::OutputEventBuffer)s
 //! (see the plugin's `process` method).
 
+use crate::error::Error;
 use crate::events::spaces::*;
 use clap_sys::events::clap_event_header;
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 
 pub mod event_types;
 pub mod io;
 pub mod spaces;
+pub mod velocity;
 
 mod header;
 mod helpers;
@@ -88,8 +90,11 @@ pub unsafe trait Event: AsRef<UnknownEvent> + Sized + 'static {
 
     #[inline]
     fn as_unknown(&self) -> &UnknownEvent {
-        // SAFETY: this trait guarantees the raw_header points to an initialized and valid event.
-        unsafe { UnknownEvent::from_raw(self.raw_header()) }
+        // The pointer is derived from the whole event, and not from raw_header(), as a reference to
+        // the header alone would not allow reading the rest of the event through it.
+        let ptr = self as *const Self as *const clap_event_header;
+        // SAFETY: this trait guarantees self points to an initialized and valid event.
+        unsafe { UnknownEvent::from_raw(ptr) }
     }
 
     #[inline]
@@ -210,15 +215,11 @@ impl UnknownEvent {
         space_id: EventSpaceId<E::EventSpace<'_>>,
     ) -> Option<&E> {
         let raw = self.header().as_raw();
-        if raw.space_id != space_id.id()
-            || raw.type_ != E::TYPE_ID
-            || raw.size != core::mem::size_of::<E>() as u32
-        {
+        if raw.space_id != space_id.id() || raw.type_ != E::TYPE_ID {
             return None;
         }
 
-        // SAFETY: this type guarantees the header is followed by event data, and we just checked the space_id, type and size fields
-        Some(unsafe { self.as_event_unchecked() })
+        self.as_event_checked_size()
     }
 
     /// Attempts to downcast this event to the given [event space](EventSpace), allowing it to be
@@ -236,6 +237,33 @@ impl UnknownEvent {
         unsafe { S::from_unknown(self) }
     }
 
+    /// Casts this event as an event of a given type, only checking that the event's size matches
+    /// the size of the type `E`.
+    ///
+    /// Both the size in the event header and the size of the underlying data are checked, as
+    /// hosts and plugins can provide events with an arbitrary (and possibly invalid) size.
+    ///
+    /// The event's alignment is also checked against the alignment of the type `E`, as events
+    /// provided through raw pointers may not be properly aligned.
+    ///
+    /// This does not check the event's type or space ID: it is up to the caller to ensure the
+    /// event is of the given type, as the cast may otherwise be incorrect.
+    #[inline]
+    pub(crate) fn as_event_checked_size<E: Event>(&self) -> Option<&E> {
+        let size = core::mem::size_of::<E>();
+        if self.header().size() as usize != size || self.data.len() < size {
+            return None;
+        }
+
+        if !is_aligned_for::<E>(self.as_raw()) {
+            return None;
+        }
+
+        // SAFETY: this type guarantees the header is followed by event data, and we just checked
+        // there is enough of it.
+        Some(unsafe { self.as_event_unchecked() })
+    }
+
     /// Casts this event as an event of a given type, without performing any checks.
     ///
     /// # Safety
@@ -254,8 +282,16 @@ impl UnknownEvent {
     /// in the header.
     #[inline]
     pub const unsafe fn from_raw<'e>(header: *const clap_event_header) -> &'e Self {
+        // The header is always valid, even if the size it advertises is too small to contain it.
+        // In that case, we still include the whole header in the data slice, so that it can be
+        // safely read by the header() method.
+        let mut size = (*header).size as usize;
+        if size < core::mem::size_of::<clap_event_header>() {
+            size = core::mem::size_of::<clap_event_header>();
+        }
+
         // SAFETY: no need to check for len > 0, since the data slice includes the header itself.
-        let data = core::slice::from_raw_parts(header as *const _, (*header).size as usize);
+        let data = core::slice::from_raw_parts(header as *const _, size);
         // SAFETY: The caller guarantees the right number of bytes is available after the given pointer in the size field.
         Self::from_bytes_unchecked(data)
     }
@@ -267,6 +303,10 @@ impl UnknownEvent {
     }
 
     /// Returns the event as a raw byte buffer. This includes the event's (header)[EventHeader].
+    ///
+    /// Event types may contain padding bytes, which are left uninitialized. These bytes are meant
+    /// to be copied around as a whole (e.g. with [`core::ptr::copy_nonoverlapping`]), but not to
+    /// be inspected individually.
     #[inline]
     pub fn as_bytes(&self) -> &[u8] {
         &self.data
@@ -286,6 +326,12 @@ impl UnknownEvent {
     }
 }
 
+/// Returns `true` if the given event pointer is suitably aligned to be read as a `T`.
+#[inline]
+pub(crate) fn is_aligned_for<T>(ptr: *const clap_event_header) -> bool {
+    (ptr as usize) % core::mem::align_of::<T>() == 0
+}
+
 impl<E: Event> PartialEq<E> for UnknownEvent
 where
     E: PartialEq,
@@ -299,7 +345,7 @@ where
 }
 
 impl Debug for UnknownEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self.as_core_event() {
             Some(e) => Debug::fmt(&e, f),
             None => f
@@ -317,51 +363,115 @@ impl AsRef<UnknownEvent> for UnknownEvent {
     }
 }
 
+/// An error returned when a raw, C-FFI compatible event struct does not match the event type it
+/// is being converted into.
+///
+/// This is returned by the `try_from_raw` family of methods of all standard event types, as well
+/// as their [`TryFrom`] implementations.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum RawEventError {
+    /// The event does not belong to the core event space.
+    SpaceMismatch {
+        /// The event space ID found in the event's header.
+        actual: u16,
+    },
+    /// The event's type ID does not match the expected event type.
+    TypeMismatch {
+        /// The type ID of the expected event type.
+        expected: u16,
+        /// The type ID found in the event's header.
+        actual: u16,
+    },
+    /// The size advertised in the event's header does not match the size of the expected event
+    /// type.
+    SizeMismatch {
+        /// The size of the expected event type, in bytes.
+        expected: u32,
+        /// The size found in the event's header, in bytes.
+        actual: u32,
+    },
+}
+
+impl Display for RawEventError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RawEventError::SpaceMismatch { actual } => write!(
+                f,
+                "CLAP Event mismatch: expected core event space (ID 0), got event space ID {actual} instead."
+            ),
+            RawEventError::TypeMismatch { expected, actual } => write!(
+                f,
+                "CLAP Event mismatch: expected event ID {expected}, got event ID {actual} instead."
+            ),
+            RawEventError::SizeMismatch { expected, actual } => write!(
+                f,
+                "CLAP Event mismatch: expected event size {expected}, got event size {actual} instead."
+            ),
+        }
+    }
+}
+
+impl Error for RawEventError {}
+
+/// Checks that the given raw header matches the core event type `E`: its event space, type ID,
+/// and advertised size must all match.
 #[inline]
-pub(crate) const fn ensure_event_matches_const<
-    's,
-    E: Event<EventSpace<'s> = CoreEventSpace<'s>>,
->(
+pub(crate) const fn check_event_matches<'s, E: Event<EventSpace<'s> = CoreEventSpace<'s>>>(
     header: &clap_event_header,
-) {
+) -> Result<(), RawEventError> {
     if header.space_id != 0 {
-        panic_event_space_mismatch_const()
+        return Err(RawEventError::SpaceMismatch {
+            actual: header.space_id,
+        });
     }
 
     if header.type_ != E::TYPE_ID {
-        panic_event_type_mismatch_const()
+        return Err(RawEventError::TypeMismatch {
+            expected: E::TYPE_ID,
+            actual: header.type_,
+        });
     }
+
+    let expected_size = core::mem::size_of::<E>() as u32;
+    if header.size != expected_size {
+        return Err(RawEventError::SizeMismatch {
+            expected: expected_size,
+            actual: header.size,
+        });
+    }
+
+    Ok(())
 }
 
 #[inline]
-pub(crate) fn ensure_event_matches<'s, E: Event<EventSpace<'s> = CoreEventSpace<'s>>>(
+pub(crate) const fn ensure_event_matches_const<
+    's,
+    E: Event<EventSpace<'s> = CoreEventSpace<'s>>,
+>(
     header: &clap_event_header,
 ) {
-    if header.space_id != 0 {
-        panic_event_space_mismatch(header.space_id)
-    }
-
-    if header.type_ != E::TYPE_ID {
-        panic_event_type_mismatch(E::TYPE_ID, header.type_)
+    match check_event_matches::<E>(header) {
+        Ok(()) => {}
+        Err(RawEventError::SpaceMismatch { .. }) => panic_event_space_mismatch_const(),
+        Err(RawEventError::TypeMismatch { .. }) => panic_event_type_mismatch_const(),
+        Err(RawEventError::SizeMismatch { .. }) => panic_event_size_mismatch_const(),
     }
 }
 
-#[inline(never)]
-#[cold]
-fn panic_event_space_mismatch(actual_id: u16) {
-    panic!(
-        "CLAP Event mismatch: expected core event space (ID 0), got event space ID {} instead.",
-        actual_id
-    )
+#[inline]
+pub(crate) fn ensure_event_matches<'s, E: Event<EventSpace<'s> = CoreEventSpace<'s>>>(
+    header: &clap_event_header,
+) {
+    if let Err(e) = check_event_matches::<E>(header) {
+        panic_event_mismatch(e)
+    }
 }
 
 #[inline(never)]
 #[cold]
-fn panic_event_type_mismatch(expected_id: u16, actual_id: u16) {
-    panic!(
-        "CLAP Event mismatch: expected event ID {}, got event ID {} instead.",
-        expected_id, actual_id
-    )
+fn panic_event_mismatch(error: RawEventError) {
+    panic!("{error}")
 }
 
 #[inline(never)]
@@ -377,3 +487,260 @@ const fn panic_event_space_mismatch_const() {
 const fn panic_event_type_mismatch_const() {
     panic!("CLAP Event mismatch: got a different event ID from expected")
 }
+
+#[inline(never)]
+#[cold]
+const fn panic_event_size_mismatch_const() {
+    panic!("CLAP Event mismatch: got a different event size from expected")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::event_types::*;
+    use crate::utils::ClapId;
+    use core::mem::MaybeUninit;
+
+    /// An aligned buffer to copy events into.
+    ///
+    /// Events may contain padding, which is uninitialized, so the bytes aren't either.
+    #[repr(C, align(8))]
+    struct AlignedBytes([MaybeUninit<u8>; 64]);
+
+    impl AlignedBytes {
+        fn as_ptr(&self) -> *const clap_event_header {
+            self.0.as_ptr().cast()
+        }
+    }
+
+    fn copy_event<E: Event>(event: &E) -> AlignedBytes {
+        let mut buf = AlignedBytes([MaybeUninit::zeroed(); 64]);
+        let bytes = event.as_unknown().as_bytes();
+
+        // SAFETY: the buffer is big enough for all the events used in these tests.
+        unsafe {
+            core::ptr::copy_nonoverlapping(bytes.as_ptr(), buf.0.as_mut_ptr().cast(), bytes.len())
+        };
+        buf
+    }
+
+    fn set_size(buf: &mut AlignedBytes, size: u32) {
+        // The size field is the first field of the header.
+        for (byte, value) in buf.0.iter_mut().zip(size.to_ne_bytes()) {
+            *byte = MaybeUninit::new(value);
+        }
+    }
+
+    #[test]
+    fn undersized_sysex_is_rejected() {
+        let data = [0xF0, 0x7E, 0xF7];
+        let event = MidiSysExEvent::new(0, 0, &data);
+
+        let mut buf = copy_event(&event);
+        set_size(&mut buf, core::mem::size_of::<clap_event_header>() as u32);
+
+        // SAFETY: the buffer is aligned and contains a valid header.
+        let unknown = unsafe { UnknownEvent::from_raw(buf.as_ptr()) };
+
+        assert!(unknown.as_event::<MidiSysExEvent>().is_none());
+        assert!(unknown.as_core_event().is_none());
+    }
+
+    #[test]
+    fn truncated_data_is_rejected_even_with_valid_header_size() {
+        let event = MidiEvent::new(0, 0, [0x90, 60, 127]);
+        let buf = copy_event(&event);
+
+        // SAFETY: the buffer is aligned and contains a valid header.
+        let unknown = unsafe {
+            let truncated = core::slice::from_raw_parts(
+                buf.as_ptr().cast(),
+                core::mem::size_of::<MidiEvent>() - 1,
+            );
+            UnknownEvent::from_bytes_unchecked(truncated)
+        };
+
+        assert!(unknown.as_event::<MidiEvent>().is_none());
+        assert!(unknown.as_core_event().is_none());
+    }
+
+    #[test]
+    fn size_smaller_than_header_still_includes_header() {
+        let event = MidiEvent::new(0, 0, [0x90, 60, 127]);
+        let mut buf = copy_event(&event);
+        set_size(&mut buf, 2);
+
+        // SAFETY: the buffer is aligned and contains a valid header.
+        let unknown = unsafe { UnknownEvent::from_raw(buf.as_ptr()) };
+
+        assert_eq!(
+            unknown.as_bytes().len(),
+            core::mem::size_of::<clap_event_header>()
+        );
+        assert_eq!(unknown.header().size(), 2);
+        assert!(unknown.as_event::<MidiEvent>().is_none());
+        assert!(unknown.as_core_event().is_none());
+    }
+
+    #[test]
+    fn oversized_event_is_rejected() {
+        let event = MidiEvent::new(0, 0, [0x90, 60, 127]);
+        let mut buf = copy_event(&event);
+        set_size(&mut buf, 64);
+
+        // SAFETY: the buffer is aligned and contains a valid header.
+        let unknown = unsafe { UnknownEvent::from_raw(buf.as_ptr()) };
+
+        assert!(unknown.as_event::<MidiEvent>().is_none());
+        assert!(unknown.as_core_event().is_none());
+    }
+
+    #[test]
+    fn valid_events_are_accepted() {
+        let event = MidiEvent::new(0, 0, [0x90, 60, 127]);
+        assert_eq!(event.as_unknown().as_event::<MidiEvent>(), Some(&event));
+        assert!(matches!(
+            event.as_unknown().as_core_event(),
+            Some(CoreEventSpace::Midi(_))
+        ));
+
+        let begin = ParamGestureBeginEvent::new(0, ClapId::new(1));
+        assert!(matches!(
+            begin.as_unknown().as_core_event(),
+            Some(CoreEventSpace::ParamGestureBegin(_))
+        ));
+
+        let end = ParamGestureEndEvent::new(0, ClapId::new(1));
+        assert!(matches!(
+            end.as_unknown().as_core_event(),
+            Some(CoreEventSpace::ParamGestureEnd(_))
+        ));
+    }
+
+    fn raw_midi(header: clap_event_header) -> clap_sys::events::clap_event_midi {
+        clap_sys::events::clap_event_midi {
+            header,
+            port_index: 1,
+            data: [0x90, 60, 127],
+        }
+    }
+
+    fn midi_header() -> clap_event_header {
+        clap_event_header {
+            size: core::mem::size_of::<clap_sys::events::clap_event_midi>() as u32,
+            time: 42,
+            space_id: 0,
+            type_: clap_sys::events::CLAP_EVENT_MIDI,
+            flags: 0,
+        }
+    }
+
+    #[test]
+    fn raw_events_are_converted() {
+        let raw = raw_midi(midi_header());
+
+        let event = MidiEvent::try_from_raw_ref(&raw).unwrap();
+        assert_eq!(event.time(), 42);
+        assert_eq!(event.port_index(), 1);
+        assert_eq!(event.data(), [0x90, 60, 127]);
+        assert!(core::ptr::eq(event.as_raw(), &raw));
+
+        let event = MidiEvent::try_from(raw).unwrap();
+        let round_trip: clap_sys::events::clap_event_midi = event.into();
+        assert_eq!(round_trip.data, raw.data);
+        assert_eq!(round_trip.header.time, raw.header.time);
+
+        let mut raw = raw;
+        let event: &mut MidiEvent = (&mut raw).try_into().unwrap();
+        event.set_time(12);
+        assert_eq!(raw.header.time, 12);
+    }
+
+    #[test]
+    fn raw_events_with_mismatched_headers_are_rejected() {
+        let raw = raw_midi(clap_event_header {
+            size: core::mem::size_of::<clap_event_header>() as u32,
+            ..midi_header()
+        });
+        assert_eq!(
+            MidiEvent::try_from_raw_ref(&raw).err(),
+            Some(RawEventError::SizeMismatch {
+                expected: core::mem::size_of::<MidiEvent>() as u32,
+                actual: core::mem::size_of::<clap_event_header>() as u32,
+            })
+        );
+
+        let raw = raw_midi(clap_event_header {
+            type_: clap_sys::events::CLAP_EVENT_MIDI2,
+            ..midi_header()
+        });
+        assert_eq!(
+            MidiEvent::try_from_raw(&raw).err(),
+            Some(RawEventError::TypeMismatch {
+                expected: clap_sys::events::CLAP_EVENT_MIDI,
+                actual: clap_sys::events::CLAP_EVENT_MIDI2,
+            })
+        );
+
+        let raw = raw_midi(clap_event_header {
+            space_id: 42,
+            ..midi_header()
+        });
+        assert_eq!(
+            <&MidiEvent>::try_from(&raw).err(),
+            Some(RawEventError::SpaceMismatch { actual: 42 })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CLAP Event mismatch: got a different event size from expected")]
+    fn from_raw_panics_on_size_mismatch() {
+        let raw = raw_midi(clap_event_header {
+            size: 2,
+            ..midi_header()
+        });
+
+        let _ = MidiEvent::from_raw(&raw);
+    }
+
+    #[test]
+    fn note_and_transport_events_are_converted() {
+        let note_on = NoteOnEvent::new(5, Pckn::new(0u16, 1u16, 60u16, Match::All), 1.0);
+        let raw = note_on.into_raw();
+
+        assert_eq!(NoteOnEvent::try_from_raw(&raw), Ok(note_on));
+        assert_eq!(
+            NoteOffEvent::try_from_raw_ref(&raw).err(),
+            Some(RawEventError::TypeMismatch {
+                expected: clap_sys::events::CLAP_EVENT_NOTE_OFF,
+                actual: clap_sys::events::CLAP_EVENT_NOTE_ON,
+            })
+        );
+
+        let transport = TransportEvent {
+            header: EventHeader::new_core(0, EventFlags::empty()),
+            flags: TransportFlags::IS_PLAYING,
+            song_pos_beats: Default::default(),
+            song_pos_seconds: Default::default(),
+            tempo: 120.0,
+            tempo_inc: 0.0,
+            loop_start_beats: Default::default(),
+            loop_end_beats: Default::default(),
+            loop_start_seconds: Default::default(),
+            loop_end_seconds: Default::default(),
+            bar_start: Default::default(),
+            bar_number: 0,
+            time_signature_numerator: 4,
+            time_signature_denominator: 4,
+        };
+
+        let mut raw: clap_sys::events::clap_event_transport = transport.into();
+        assert_eq!(TransportEvent::try_from(raw), Ok(transport));
+
+        raw.header.size += 1;
+        assert!(matches!(
+            TransportEvent::try_from_raw_ref(&raw),
+            Err(RawEventError::SizeMismatch { .. })
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::events::helpers::impl_event_helpers;
+use crate::events::helpers::{impl_event_helpers, impl_raw_event_conversions};
 use crate::events::spaces::CoreEventSpace;
 use crate::events::{Event, EventFlags, EventHeader, UnknownEvent};
 use crate::utils::slice_from_external_parts;
@@ -6,7 +6,7 @@ use clap_sys::events::{
     clap_event_midi, clap_event_midi2, clap_event_midi_sysex, CLAP_EVENT_MIDI, CLAP_EVENT_MIDI2,
     CLAP_EVENT_MIDI_SYSEX,
 };
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Formatter};
 
 #[derive(Copy, Clone)]
 pub struct MidiEvent {
@@ -73,6 +73,8 @@ impl MidiEvent {
     impl_event_helpers!(clap_event_midi);
 }
 
+impl_raw_event_conversions!(MidiEvent, clap_event_midi);
+
 impl PartialEq for MidiEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -83,7 +85,7 @@ impl PartialEq for MidiEvent {
 impl Eq for MidiEvent {}
 
 impl Debug for MidiEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("MidiEvent")
             .field("header", &self.header())
             .field("port_index", &self.inner.port_index)
@@ -167,6 +169,8 @@ impl MidiSysExEvent {
     impl_event_helpers!(clap_event_midi_sysex);
 }
 
+impl_raw_event_conversions!(MidiSysExEvent, clap_event_midi_sysex);
+
 impl PartialEq for MidiSysExEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -179,7 +183,7 @@ impl PartialEq for MidiSysExEvent {
 impl Eq for MidiSysExEvent {}
 
 impl Debug for MidiSysExEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("MidiSysexEvent")
             .field("header", &self.header())
             .field("port_index", &self.inner.port_index)
@@ -253,6 +257,8 @@ impl Midi2Event {
     impl_event_helpers!(clap_event_midi2);
 }
 
+impl_raw_event_conversions!(Midi2Event, clap_event_midi2);
+
 impl PartialEq for Midi2Event {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -263,7 +269,7 @@ impl PartialEq for Midi2Event {
 impl Eq for Midi2Event {}
 
 impl Debug for Midi2Event {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Midi2Event")
             .field("header", &self.header())
             .field("port_index", &self.inner.port_index)
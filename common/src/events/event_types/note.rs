@@ -1,4 +1,5 @@
 use crate::events::spaces::CoreEventSpace;
+use crate::events::velocity::Velocity;
 use crate::events::{Event, Match, Pckn, UnknownEvent};
 use clap_sys::events::*;
 
@@ -36,6 +37,12 @@ impl NoteOnEvent {
         }
     }
 
+    /// Creates a new Note On event, with the given MIDI velocity (from `0` to `127`).
+    #[inline]
+    pub fn from_midi(time: u32, pckn: Pckn, velocity: u8) -> Self {
+        Self::new(time, pckn, Velocity::from_midi(velocity).get())
+    }
+
     #[inline]
     pub const fn velocity(&self) -> f64 {
         self.inner.inner.velocity
@@ -52,6 +59,23 @@ impl NoteOnEvent {
         self
     }
 
+    /// Returns this event's velocity, clamped to the `0.0..=1.0` range.
+    ///
+    /// See [`Velocity::new`].
+    #[inline]
+    pub fn clamped_velocity(&self) -> Velocity {
+        Velocity::new(self.velocity())
+    }
+
+    /// Returns this event's velocity as the velocity of a MIDI Note On message, from `1` to
+    /// `127`.
+    ///
+    /// See [`Velocity::to_midi_note_on`].
+    #[inline]
+    pub fn midi_velocity(&self) -> u8 {
+        self.clamped_velocity().to_midi_note_on()
+    }
+
     self::impl_note_helpers!();
 }
 
@@ -63,6 +87,12 @@ impl NoteOffEvent {
         }
     }
 
+    /// Creates a new Note Off event, with the given MIDI velocity (from `0` to `127`).
+    #[inline]
+    pub fn from_midi(time: u32, pckn: Pckn, velocity: u8) -> Self {
+        Self::new(time, pckn, Velocity::from_midi(velocity).get())
+    }
+
     #[inline]
     pub const fn velocity(&self) -> f64 {
         self.inner.inner.velocity
@@ -79,6 +109,20 @@ impl NoteOffEvent {
         self
     }
 
+    /// Returns this event's velocity, clamped to the `0.0..=1.0` range.
+    ///
+    /// See [`Velocity::new`].
+    #[inline]
+    pub fn clamped_velocity(&self) -> Velocity {
+        Velocity::new(self.velocity())
+    }
+
+    /// Returns this event's velocity as a MIDI velocity, from `0` to `127`.
+    #[inline]
+    pub fn midi_velocity(&self) -> u8 {
+        self.clamped_velocity().to_midi()
+    }
+
     self::impl_note_helpers!();
 }
 
@@ -1,9 +1,10 @@
 #![deny(missing_docs)]
 
 use crate::events::{Event, EventFlags, EventHeader, Pckn};
+use crate::utils::KeyDebug;
 use clap_sys::events::clap_event_note;
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use core::fmt::Formatter;
+use core::marker::PhantomData;
 
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -65,7 +66,7 @@ impl<E: for<'a> Event<EventSpace<'a> = CoreEventSpace<'a>>> NoteEvent<E> {
             .field("header", self.header())
             .field("port_index", &self.inner.port_index)
             .field("channel", &self.inner.channel)
-            .field("key", &self.inner.key)
+            .field("key", &KeyDebug(self.inner.key))
             .field("velocity", &self.inner.velocity)
             .field("note_id", &self.inner.note_id)
             .finish()
@@ -232,6 +233,12 @@ macro_rules! impl_note_helpers {
             &mut self.inner.inner
         }
 
+        /// Consumes this event, returning the underlying raw, C-FFI compatible event struct.
+        #[inline]
+        pub const fn into_raw(self) -> clap_event_note {
+            self.inner.inner
+        }
+
         /// Creates a new note event of this type from a reference to a raw, C-FFI compatible event
         /// struct.
         ///
@@ -277,6 +284,59 @@ macro_rules! impl_note_helpers {
             // SAFETY: This type is #[repr(C)]-compatible with clap_event_note
             unsafe { &mut *(raw as *mut clap_event_note as *mut Self) }
         }
+
+        /// Creates a new note event of this type from a raw, C-FFI compatible event struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`RawEventError`](crate::events::RawEventError) if the event space, type or
+        /// size in the given struct's header doesn't match this note event type.
+        #[inline]
+        pub const fn try_from_raw(
+            raw: &clap_event_note,
+        ) -> Result<Self, crate::events::RawEventError> {
+            match crate::events::check_event_matches::<Self>(&raw.header) {
+                Ok(()) => Ok(Self {
+                    inner: NoteEvent::from_raw(raw),
+                }),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Creates a reference to a note event of this type from a raw, C-FFI compatible event
+        /// struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`RawEventError`](crate::events::RawEventError) if the event space, type or
+        /// size in the given struct's header doesn't match this note event type.
+        #[inline]
+        pub const fn try_from_raw_ref(
+            raw: &clap_event_note,
+        ) -> Result<&Self, crate::events::RawEventError> {
+            match crate::events::check_event_matches::<Self>(&raw.header) {
+                // SAFETY: This type is #[repr(C)]-compatible with clap_event_note
+                Ok(()) => Ok(unsafe { &*(raw as *const clap_event_note as *const Self) }),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Creates a mutable reference to a note event of this type from a raw, C-FFI compatible
+        /// event struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`RawEventError`](crate::events::RawEventError) if the event space, type or
+        /// size in the given struct's header doesn't match this note event type.
+        #[inline]
+        pub fn try_from_raw_mut(
+            raw: &mut clap_event_note,
+        ) -> Result<&mut Self, crate::events::RawEventError> {
+            crate::events::check_event_matches::<Self>(&raw.header)?;
+
+            // SAFETY: This type is #[repr(C)]-compatible with clap_event_note
+            Ok(unsafe { &mut *(raw as *mut clap_event_note as *mut Self) })
+        }
     };
 }
 
@@ -290,13 +350,15 @@ macro_rules! impl_note_traits {
                 }
             }
 
-            impl std::fmt::Debug for $type {
+            impl core::fmt::Debug for $type {
                 #[inline]
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                     self.inner.fmt(f, stringify!($type))
                 }
             }
         };
+
+        crate::events::helpers::impl_raw_event_conversions!($type, clap_event_note);
     };
 }
 
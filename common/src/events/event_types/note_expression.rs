@@ -1,8 +1,10 @@
-use crate::events::helpers::impl_event_helpers;
+use crate::events::helpers::{impl_event_helpers, impl_raw_event_conversions};
 use crate::events::spaces::CoreEventSpace;
+use crate::events::velocity::clamp_for;
 use crate::events::{impl_event_pckn, Event, EventFlags, EventHeader, Match, Pckn, UnknownEvent};
+use crate::utils::KeyDebug;
 use clap_sys::events::*;
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Formatter};
 
 #[non_exhaustive]
 #[repr(i32)]
@@ -26,6 +28,7 @@ impl NoteExpressionType {
             CLAP_NOTE_EXPRESSION_PAN => Some(Pan),
             CLAP_NOTE_EXPRESSION_TUNING => Some(Tuning),
             CLAP_NOTE_EXPRESSION_VIBRATO => Some(Vibrato),
+            CLAP_NOTE_EXPRESSION_EXPRESSION => Some(Expression),
             CLAP_NOTE_EXPRESSION_BRIGHTNESS => Some(Brightness),
             CLAP_NOTE_EXPRESSION_PRESSURE => Some(Pressure),
             _ => None,
@@ -109,10 +112,24 @@ impl NoteExpressionEvent {
         self
     }
 
+    /// Returns this event's value, clamped to the range of its expression type.
+    ///
+    /// Values of unknown expression types are returned as-is. See
+    /// [`clamp_for`](crate::events::velocity::clamp_for).
+    #[inline]
+    pub fn clamped_value(&self) -> f64 {
+        match self.expression_type() {
+            Some(expression_type) => clamp_for(expression_type, self.inner.value),
+            None => self.inner.value,
+        }
+    }
+
     impl_event_helpers!(clap_event_note_expression);
     impl_event_pckn!();
 }
 
+impl_raw_event_conversions!(NoteExpressionEvent, clap_event_note_expression);
+
 impl PartialEq for NoteExpressionEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -126,11 +143,11 @@ impl PartialEq for NoteExpressionEvent {
 }
 
 impl Debug for NoteExpressionEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("NoteExpressionEvent")
             .field("port_index", &self.inner.port_index)
             .field("channel", &self.inner.channel)
-            .field("key", &self.inner.key)
+            .field("key", &KeyDebug(self.inner.key))
             .field("expression_id", &self.inner.expression_id)
             .field("note_id", &self.inner.note_id)
             .field("value", &self.inner.value)
@@ -1,4 +1,4 @@
-use crate::events::helpers::impl_event_helpers;
+use crate::events::helpers::{impl_event_helpers, impl_raw_event_conversions};
 use crate::events::spaces::CoreEventSpace;
 use crate::events::{impl_event_pckn, Event, EventFlags, EventHeader, Match, Pckn, UnknownEvent};
 use crate::utils::{ClapId, Cookie};
@@ -7,7 +7,7 @@ use clap_sys::events::{
     CLAP_EVENT_PARAM_GESTURE_BEGIN, CLAP_EVENT_PARAM_GESTURE_END, CLAP_EVENT_PARAM_MOD,
     CLAP_EVENT_PARAM_VALUE,
 };
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Formatter};
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -97,6 +97,8 @@ impl ParamValueEvent {
     }
 }
 
+impl_raw_event_conversions!(ParamValueEvent, clap_event_param_value);
+
 impl PartialEq for ParamValueEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -111,7 +113,7 @@ impl PartialEq for ParamValueEvent {
 }
 
 impl Debug for ParamValueEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("ParamValueEvent")
             .field("header", &self.header())
             .field("port_index", &self.inner.port_index)
@@ -212,6 +214,8 @@ impl ParamModEvent {
     }
 }
 
+impl_raw_event_conversions!(ParamModEvent, clap_event_param_mod);
+
 impl PartialEq for ParamModEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -226,7 +230,7 @@ impl PartialEq for ParamModEvent {
 }
 
 impl Debug for ParamModEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("ParamModEvent")
             .field("header", &self.header())
             .field("port_index", &self.inner.port_index)
@@ -284,10 +288,12 @@ impl ParamGestureBeginEvent {
         self.inner.param_id = param_id.get();
         self
     }
+
+    impl_event_helpers!(clap_event_param_gesture);
 }
 
 impl Debug for ParamGestureBeginEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("ParamGestureBeginEvent")
             .field("header", &self.header())
             .field("header", &self.header())
@@ -296,6 +302,8 @@ impl Debug for ParamGestureBeginEvent {
     }
 }
 
+impl_raw_event_conversions!(ParamGestureBeginEvent, clap_event_param_gesture);
+
 impl PartialEq for ParamGestureBeginEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -349,10 +357,12 @@ impl ParamGestureEndEvent {
         self.inner.param_id = param_id.get();
         self
     }
+
+    impl_event_helpers!(clap_event_param_gesture);
 }
 
 impl Debug for ParamGestureEndEvent {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("ParamGestureEndEvent")
             .field("header", &self.header())
             .field("header", self.header())
@@ -361,6 +371,8 @@ impl Debug for ParamGestureEndEvent {
     }
 }
 
+impl_raw_event_conversions!(ParamGestureEndEvent, clap_event_param_gesture);
+
 impl PartialEq for ParamGestureEndEvent {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -1,5 +1,6 @@
+use crate::events::helpers::impl_raw_event_conversions;
 use crate::events::spaces::CoreEventSpace;
-use crate::events::{Event, EventHeader, UnknownEvent};
+use crate::events::{Event, EventHeader, RawEventError, UnknownEvent};
 use crate::utils::{BeatTime, SecondsTime};
 use bitflags::bitflags;
 use clap_sys::events::{
@@ -63,6 +64,53 @@ impl AsRef<UnknownEvent> for TransportEvent {
 }
 
 impl TransportEvent {
+    /// Returns the tempo at the time of this event, in beats per minute, if the host provided
+    /// one.
+    ///
+    /// This returns `None` if the [`HAS_TEMPO`](TransportFlags::HAS_TEMPO) flag isn't set, or if
+    /// the tempo isn't strictly positive.
+    #[inline]
+    pub fn tempo_bpm(&self) -> Option<f64> {
+        if self.flags.contains(TransportFlags::HAS_TEMPO) && self.tempo > 0.0 {
+            Some(self.tempo)
+        } else {
+            None
+        }
+    }
+
+    /// Returns by how much the tempo changes on every sample, in beats per minute, until the
+    /// next transport event, if the host provided a tempo.
+    ///
+    /// This is `0.0` if the tempo is constant.
+    #[inline]
+    pub fn tempo_increment(&self) -> Option<f64> {
+        self.tempo_bpm()?;
+
+        if self.tempo_inc.is_finite() {
+            Some(self.tempo_inc)
+        } else {
+            Some(0.0)
+        }
+    }
+
+    /// Returns the tempo reached the given number of samples after this event, in beats per
+    /// minute, if the host provided a tempo.
+    ///
+    /// The tempo never goes below zero, even if the tempo increment is negative.
+    #[inline]
+    pub fn tempo_bpm_after(&self, samples: u32) -> Option<f64> {
+        let tempo = self.tempo_bpm()?;
+        let tempo_inc = self.tempo_increment()?;
+
+        Some((tempo + tempo_inc * samples as f64).max(0.0))
+    }
+
+    /// Returns `true` if the host provided a tempo, and that tempo is changing.
+    #[inline]
+    pub fn is_tempo_ramping(&self) -> bool {
+        self.tempo_increment().is_some_and(|inc| inc != 0.0)
+    }
+
     #[inline]
     pub const fn as_raw(&self) -> &clap_event_transport {
         // SAFETY: This type is #[repr(C)]-compatible with clap_event_transport
@@ -75,6 +123,12 @@ impl TransportEvent {
         unsafe { &mut *(self as *mut Self as *mut clap_event_transport) }
     }
 
+    /// Consumes this event, returning the equivalent raw, C-FFI compatible event struct.
+    #[inline]
+    pub const fn into_raw(self) -> clap_event_transport {
+        *self.as_raw()
+    }
+
     #[inline]
     pub const fn from_raw(raw: &clap_event_transport) -> Self {
         crate::events::ensure_event_matches_const::<Self>(&raw.header);
@@ -99,4 +153,50 @@ impl TransportEvent {
         // SAFETY: This type is #[repr(C)]-compatible with clap_event_transport
         unsafe { &mut *(raw as *mut clap_event_transport as *mut Self) }
     }
+
+    /// Creates a new transport event from a raw, C-FFI compatible event struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RawEventError`] if the event space, type or size in the given struct's header
+    /// doesn't match the transport event type.
+    #[inline]
+    pub const fn try_from_raw(raw: &clap_event_transport) -> Result<Self, RawEventError> {
+        match Self::try_from_raw_ref(raw) {
+            Ok(this) => Ok(*this),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a reference to a transport event from a raw, C-FFI compatible event struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RawEventError`] if the event space, type or size in the given struct's header
+    /// doesn't match the transport event type.
+    #[inline]
+    pub const fn try_from_raw_ref(raw: &clap_event_transport) -> Result<&Self, RawEventError> {
+        match crate::events::check_event_matches::<Self>(&raw.header) {
+            // SAFETY: This type is #[repr(C)]-compatible with clap_event_transport
+            Ok(()) => Ok(unsafe { &*(raw as *const clap_event_transport as *const Self) }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a mutable reference to a transport event from a raw, C-FFI compatible event
+    /// struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RawEventError`] if the event space, type or size in the given struct's header
+    /// doesn't match the transport event type.
+    #[inline]
+    pub fn try_from_raw_mut(raw: &mut clap_event_transport) -> Result<&mut Self, RawEventError> {
+        crate::events::check_event_matches::<Self>(&raw.header)?;
+
+        // SAFETY: This type is #[repr(C)]-compatible with clap_event_transport
+        Ok(unsafe { &mut *(raw as *mut clap_event_transport as *mut Self) })
+    }
 }
+
+impl_raw_event_conversions!(TransportEvent, clap_event_transport);
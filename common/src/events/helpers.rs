@@ -10,6 +10,12 @@ macro_rules! impl_event_helpers {
             &mut self.inner
         }
 
+        /// Consumes this event, returning the underlying raw, C-FFI compatible event struct.
+        #[inline]
+        pub const fn into_raw(self) -> $raw_type {
+            self.inner
+        }
+
         #[inline]
         pub const fn from_raw(raw: &$raw_type) -> Self {
             crate::events::ensure_event_matches_const::<Self>(&raw.header);
@@ -32,7 +38,98 @@ macro_rules! impl_event_helpers {
             // SAFETY: This type is #[repr(C)]-compatible with $raw_type
             unsafe { &mut *(raw as *mut $raw_type as *mut Self) }
         }
+
+        /// Creates a new event of this type from a raw, C-FFI compatible event struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`RawEventError`](crate::events::RawEventError) if the event space, type or
+        /// size in the given struct's header doesn't match this event type.
+        #[inline]
+        pub const fn try_from_raw(raw: &$raw_type) -> Result<Self, crate::events::RawEventError> {
+            match crate::events::check_event_matches::<Self>(&raw.header) {
+                Ok(()) => Ok(Self { inner: *raw }),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Creates a reference to an event of this type from a raw, C-FFI compatible event struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`RawEventError`](crate::events::RawEventError) if the event space, type or
+        /// size in the given struct's header doesn't match this event type.
+        #[inline]
+        pub const fn try_from_raw_ref(
+            raw: &$raw_type,
+        ) -> Result<&Self, crate::events::RawEventError> {
+            match crate::events::check_event_matches::<Self>(&raw.header) {
+                // SAFETY: This type is #[repr(C)]-compatible with $raw_type
+                Ok(()) => Ok(unsafe { &*(raw as *const $raw_type as *const Self) }),
+                Err(e) => Err(e),
+            }
+        }
+
+        /// Creates a mutable reference to an event of this type from a raw, C-FFI compatible
+        /// event struct.
+        ///
+        /// # Errors
+        ///
+        /// Returns a [`RawEventError`](crate::events::RawEventError) if the event space, type or
+        /// size in the given struct's header doesn't match this event type.
+        #[inline]
+        pub fn try_from_raw_mut(
+            raw: &mut $raw_type,
+        ) -> Result<&mut Self, crate::events::RawEventError> {
+            crate::events::check_event_matches::<Self>(&raw.header)?;
+
+            // SAFETY: This type is #[repr(C)]-compatible with $raw_type
+            Ok(unsafe { &mut *(raw as *mut $raw_type as *mut Self) })
+        }
+    };
+}
+
+/// Implements the standard conversion traits between an event type and its raw, C-FFI compatible
+/// event struct. The event type must also use [`impl_event_helpers`] (or provide equivalent
+/// methods).
+macro_rules! impl_raw_event_conversions {
+    ($type:ty, $raw_type:ty) => {
+        const _: () = {
+            impl From<$type> for $raw_type {
+                #[inline]
+                fn from(event: $type) -> Self {
+                    event.into_raw()
+                }
+            }
+
+            impl TryFrom<$raw_type> for $type {
+                type Error = crate::events::RawEventError;
+
+                #[inline]
+                fn try_from(raw: $raw_type) -> Result<Self, Self::Error> {
+                    <$type>::try_from_raw(&raw)
+                }
+            }
+
+            impl<'a> TryFrom<&'a $raw_type> for &'a $type {
+                type Error = crate::events::RawEventError;
+
+                #[inline]
+                fn try_from(raw: &'a $raw_type) -> Result<Self, Self::Error> {
+                    <$type>::try_from_raw_ref(raw)
+                }
+            }
+
+            impl<'a> TryFrom<&'a mut $raw_type> for &'a mut $type {
+                type Error = crate::events::RawEventError;
+
+                #[inline]
+                fn try_from(raw: &'a mut $raw_type) -> Result<Self, Self::Error> {
+                    <$type>::try_from_raw_mut(raw)
+                }
+            }
+        };
     };
 }
 
-pub(crate) use impl_event_helpers;
+pub(crate) use {impl_event_helpers, impl_raw_event_conversions};
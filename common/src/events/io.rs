@@ -5,14 +5,18 @@
 
 mod batcher;
 mod buffer;
+mod event_io;
 mod implementation;
 mod input;
 mod merger;
 mod output;
+mod stats;
 
 pub use batcher::*;
 pub use buffer::*;
+pub use event_io::*;
 pub use implementation::*;
 pub use input::*;
 pub use merger::*;
 pub use output::*;
+pub use stats::*;
@@ -1,5 +1,5 @@
 use crate::events::io::{InputEvents, InputEventsIter};
-use std::ops::Bound;
+use core::ops::{Bound, Range};
 
 #[derive(Copy, Clone, Debug)]
 enum State {
@@ -20,25 +20,66 @@ enum State {
 #[must_use = "iterators are lazy and do nothing unless consumed"]
 pub struct EventBatcher<'a> {
     events: &'a InputEvents<'a>,
-    events_len: u32,
+    start_index: u32,
+    end_index: u32,
+    frames_offset: u32,
     state: State,
 }
 
 impl<'a> EventBatcher<'a> {
     pub(crate) fn new(events: &'a InputEvents<'a>) -> Self {
-        let events_len = events.len();
+        Self::with_indexes(events, 0, events.len(), 0)
+    }
+
+    /// Creates a batcher over the events happening in the given range of frames only.
+    ///
+    /// Event times are assumed to be sorted, as required by the CLAP specification.
+    pub(crate) fn in_frames(events: &'a InputEvents<'a>, frames: Range<u32>) -> Self {
+        let len = events.len();
+        let time = |index: u32| events.get(index).map(|e| e.header().time());
+
+        let start_index = (0..len)
+            .find(|i| time(*i).is_some_and(|t| t >= frames.start))
+            .unwrap_or(len);
+        let end_index = (start_index..len)
+            .find(|i| time(*i).is_some_and(|t| t >= frames.end))
+            .unwrap_or(len);
+
+        Self::with_indexes(events, start_index, end_index, frames.start)
+    }
 
-        Self {
+    fn with_indexes(
+        events: &'a InputEvents<'a>,
+        start_index: u32,
+        end_index: u32,
+        frames_offset: u32,
+    ) -> Self {
+        let mut batcher = Self {
             events,
-            events_len,
-            state: State::Started {
-                first_event_sample_time: if events_len == 0 {
-                    None
-                } else {
-                    events.get(0).map(|e| e.header().time())
-                },
+            start_index,
+            end_index,
+            frames_offset,
+            state: State::Ended,
+        };
+
+        batcher.state = State::Started {
+            first_event_sample_time: if start_index >= end_index {
+                None
+            } else {
+                batcher.event_time(start_index)
             },
-        }
+        };
+
+        batcher
+    }
+
+    /// Returns the time of the event at the given index, relative to the start of the batched
+    /// frames.
+    #[inline]
+    fn event_time(&self, index: u32) -> Option<u32> {
+        self.events
+            .get(index)
+            .map(|e| e.header().time().saturating_sub(self.frames_offset))
     }
 
     fn next_non_matching(
@@ -46,12 +87,11 @@ impl<'a> EventBatcher<'a> {
         current_event_index: u32,
         current_sample: u32,
     ) -> Option<(u32, u32)> {
-        for next_index in (current_event_index + 1)..self.events_len {
-            let Some(next_event) = self.events.get(next_index) else {
+        for next_index in (current_event_index + 1)..self.end_index {
+            let Some(next_event_sample_time) = self.event_time(next_index) else {
                 continue;
             };
 
-            let next_event_sample_time = next_event.header().time();
             if next_event_sample_time != current_sample {
                 return Some((next_index, next_event_sample_time));
             }
@@ -79,13 +119,21 @@ impl<'a> Iterator for EventBatcher<'a> {
             ),
             Started {
                 first_event_sample_time: Some(0),
-            } => (0, 0, self.next_non_matching(0, 0)),
+            } => (
+                self.start_index,
+                0,
+                self.next_non_matching(self.start_index, 0),
+            ),
             Started {
                 first_event_sample_time: None,
-            } => (0, 0, None),
+            } => (self.start_index, 0, None),
             Started {
                 first_event_sample_time: Some(first_event_sample_time),
-            } => (0, 0, Some((0, first_event_sample_time))),
+            } => (
+                self.start_index,
+                0,
+                Some((self.start_index, first_event_sample_time)),
+            ),
         };
 
         match next_non_matching_event {
@@ -93,7 +141,7 @@ impl<'a> Iterator for EventBatcher<'a> {
                 self.state = Ended;
 
                 Some(EventBatch {
-                    events: InputEventsIter::new(self.events, current_event_index..self.events_len),
+                    events: InputEventsIter::new(self.events, current_event_index..self.end_index),
                     first_sample: current_sample as usize,
                     next_batch_first_sample: None,
                 })
@@ -388,4 +436,55 @@ mod tests {
 
         assert!(events.next().is_none())
     }
+
+    #[test]
+    pub fn frame_ranges_only_batch_their_events() {
+        let buf = [
+            ParamGestureBeginEvent::new(2, PARAM),
+            ParamGestureBeginEvent::new(4, PARAM),
+            ParamGestureBeginEvent::new(6, PARAM),
+            ParamGestureBeginEvent::new(9, PARAM),
+        ];
+
+        let events = InputEvents::from_buffer(&buf);
+        let mut events = events.batch_frames(4..8);
+
+        {
+            let batch = events.next().unwrap();
+            assert_eq!(batch.first_sample(), 0);
+            assert_eq!(batch.next_batch_first_sample(), Some(2));
+
+            let mut batch_events = batch.events();
+            assert_eq!(&buf[1], batch_events.next().unwrap().as_event().unwrap());
+            assert!(batch_events.next().is_none());
+        }
+        {
+            let batch = events.next().unwrap();
+            assert_eq!(batch.first_sample(), 2);
+            assert_eq!(batch.next_batch_first_sample(), None);
+
+            let mut batch_events = batch.events();
+            assert_eq!(&buf[2], batch_events.next().unwrap().as_event().unwrap());
+            assert!(batch_events.next().is_none());
+        }
+
+        assert!(events.next().is_none())
+    }
+
+    #[test]
+    pub fn empty_frame_ranges_yield_a_single_empty_batch() {
+        let buf = [ParamGestureBeginEvent::new(9, PARAM)];
+
+        let events = InputEvents::from_buffer(&buf);
+        let mut events = events.batch_frames(0..8);
+
+        {
+            let batch = events.next().unwrap();
+            assert_eq!(batch.first_sample(), 0);
+            assert_eq!(batch.next_batch_first_sample(), None);
+            assert!(batch.events().next().is_none());
+        }
+
+        assert!(events.next().is_none())
+    }
 }
@@ -2,10 +2,11 @@ use crate::events::event_types::TransportEvent;
 use crate::events::io::implementation::{InputEventBuffer, OutputEventBuffer};
 use crate::events::io::{InputEvents, OutputEvents, TryPushError};
 use crate::events::UnknownEvent;
+use alloc::vec::Vec;
 use clap_sys::events::clap_event_header;
-use core::mem::{size_of_val, MaybeUninit};
-use std::fmt::{Debug, Formatter};
-use std::ops::{Index, Range};
+use core::fmt::{Debug, Formatter};
+use core::mem::{size_of, size_of_val, MaybeUninit};
+use core::ops::{Index, Range};
 
 #[repr(C, align(8))]
 #[derive(Copy, Clone)]
@@ -38,6 +39,12 @@ struct AlignedEventHeader(clap_event_header);
 /// assert_eq!(&buffer[0], &some_event);
 /// ```
 ///
+/// # Alignment
+///
+/// Every event stored in this buffer is guaranteed to be aligned to 8 bytes, which is enough for
+/// all standard CLAP events. This holds even when events of arbitrary (e.g. odd) sizes are pushed
+/// in between, as each event's storage is padded to the next 8-byte boundary.
+///
 /// # Realtime Safety
 ///
 /// This type is backed by `Vec` internally, and therefore holds similar realtime properties.
@@ -118,7 +125,8 @@ impl EventBuffer {
         // SAFETY: Registered indexes always have actual event headers written by append_header_data
         // PANIC: We used registered indexes, this should never panic
         let event = unsafe { self.headers[header_index].assume_init_ref() };
-        let event_size = event.0.size as usize;
+        // Stored events always include their full header, even if they advertise a smaller size.
+        let event_size = (event.0.size as usize).max(size_of::<clap_event_header>());
         let header_end_index =
             byte_index_to_value_index::<AlignedEventHeader>(event_size) + header_index;
 
@@ -153,6 +161,89 @@ impl EventBuffer {
         })
     }
 
+    /// Fixes up events pushed by a plugin that do not follow the CLAP specification, so that they
+    /// can be safely forwarded somewhere else.
+    ///
+    /// This is meant to be used by defensive hosts on the buffer backing a plugin's
+    /// [`OutputEvents`], after the plugin's `process` call. It performs two operations:
+    ///
+    /// * Events whose time is beyond the processed block (i.e. greater than or equal to
+    ///   `frames_count`) are clamped to the block's last frame;
+    /// * Events are then sorted by time, while preserving the order of events with the same time.
+    ///
+    /// The returned [`EventSanitizeReport`] tells how many events had to be fixed, if any.
+    ///
+    /// # Realtime Safety
+    ///
+    /// This method never allocates, and is realtime-safe. It is most efficient on lists that are
+    /// already (or nearly) sorted, as sorting is done using an in-place insertion sort.
+    pub fn sanitize(&mut self, frames_count: u32) -> EventSanitizeReport {
+        let last_frame = frames_count.saturating_sub(1);
+        let mut report = EventSanitizeReport::default();
+        let mut previous_time = 0;
+
+        for &index in &self.indexes {
+            // SAFETY: Registered indexes always have actual event headers written by append_header_data
+            // PANIC: We used registered indexes, this should never panic
+            let header = unsafe { &mut self.headers[index as usize].assume_init_mut().0 };
+
+            if header.time < previous_time {
+                report.out_of_order += 1;
+            }
+            previous_time = header.time;
+
+            if header.time > last_frame {
+                header.time = last_frame;
+                report.out_of_block += 1;
+            }
+        }
+
+        if report.out_of_order > 0 {
+            self.insertion_sort();
+        }
+
+        report
+    }
+
+    /// Sets the time of all the events contained in this buffer to the given `time`.
+    ///
+    /// This is useful to carry events over to the start of the next processing block. As all
+    /// events then share the same time, their order is preserved and the buffer stays sorted.
+    ///
+    /// # Realtime Safety
+    ///
+    /// This method never allocates, and is realtime-safe.
+    pub fn retime(&mut self, time: u32) {
+        for &index in &self.indexes {
+            // SAFETY: Registered indexes always have actual event headers written by append_header_data
+            // PANIC: We used registered indexes, this should never panic
+            let header = unsafe { &mut self.headers[index as usize].assume_init_mut().0 };
+            header.time = time;
+        }
+    }
+
+    /// A stable, allocation-free sort of the event indexes by event time.
+    fn insertion_sort(&mut self) {
+        let time = |headers: &[MaybeUninit<AlignedEventHeader>], index: u32| {
+            // SAFETY: Registered indexes always have actual event headers written by append_header_data
+            // PANIC: We used registered indexes, this should never panic
+            unsafe { headers[index as usize].assume_init_ref().0.time }
+        };
+
+        for i in 1..self.indexes.len() {
+            let current = self.indexes[i];
+            let current_time = time(&self.headers, current);
+
+            let mut j = i;
+            while j > 0 && time(&self.headers, self.indexes[j - 1]) > current_time {
+                self.indexes[j] = self.indexes[j - 1];
+                j -= 1;
+            }
+
+            self.indexes[j] = current;
+        }
+    }
+
     /// Inserts a given `event` at the given `position`, shifting all events after it to the right.
     ///
     /// # Panics
@@ -229,6 +320,23 @@ impl EventBuffer {
     }
 }
 
+/// A report of the fixes [`EventBuffer::sanitize`] had to make to an event list.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct EventSanitizeReport {
+    /// The number of events that had a smaller time than the event preceding them.
+    pub out_of_order: u32,
+    /// The number of events whose time was beyond the end of the block, and had to be clamped.
+    pub out_of_block: u32,
+}
+
+impl EventSanitizeReport {
+    /// Returns `true` if no event had to be fixed.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.out_of_order == 0 && self.out_of_block == 0
+    }
+}
+
 impl<'a> IntoIterator for &'a EventBuffer {
     type Item = &'a UnknownEvent;
     type IntoIter = EventBufferIter<'a>;
@@ -267,7 +375,7 @@ impl Default for EventBuffer {
 }
 
 impl Debug for EventBuffer {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let mut list = f.debug_list();
         for event in self {
             if let Some(event) = event.as_core_event() {
@@ -309,9 +417,10 @@ impl<'a> Iterator for EventBufferIter<'a> {
 
 #[cfg(test)]
 mod test {
-    use crate::events::event_types::MidiEvent;
-    use crate::events::io::EventBuffer;
+    use super::*;
+    use crate::events::event_types::{MidiEvent, NoteOnEvent};
     use crate::events::Event;
+    use crate::events::Pckn;
 
     #[test]
     fn it_works() {
@@ -338,4 +447,115 @@ mod test {
         assert_eq!(Some(&event_2), buffer.get(2).unwrap().as_event());
         assert_eq!(Some(&event_3), buffer.get(3).unwrap().as_event());
     }
+
+    #[test]
+    fn sanitize_sorts_and_clamps_bad_events() {
+        let events = [
+            MidiEvent::new(5, 0, [0; 3]),
+            MidiEvent::new(2, 0, [1; 3]),
+            MidiEvent::new(40, 0, [2; 3]),
+            MidiEvent::new(2, 0, [3; 3]),
+            MidiEvent::new(0, 0, [4; 3]),
+        ];
+
+        let mut buffer = EventBuffer::new();
+        buffer.push_all(events.iter().map(|e| e.as_unknown()));
+
+        let report = buffer.sanitize(32);
+        assert_eq!(
+            report,
+            EventSanitizeReport {
+                out_of_order: 3,
+                out_of_block: 1
+            }
+        );
+        assert!(!report.is_clean());
+
+        let sanitized: Vec<_> = buffer
+            .iter()
+            .map(|e| {
+                let e = e.as_event::<MidiEvent>().unwrap();
+                (e.header().time(), e.data()[0])
+            })
+            .collect();
+
+        // Events with the same time keep their relative order.
+        assert_eq!(sanitized, [(0, 4), (2, 1), (2, 3), (5, 0), (31, 2)]);
+    }
+
+    #[test]
+    fn sanitize_leaves_valid_events_untouched() {
+        let events = [
+            MidiEvent::new(0, 0, [0; 3]),
+            MidiEvent::new(0, 0, [1; 3]),
+            MidiEvent::new(31, 0, [2; 3]),
+        ];
+
+        let mut buffer = EventBuffer::new();
+        buffer.push_all(events.iter().map(|e| e.as_unknown()));
+
+        assert!(buffer.sanitize(32).is_clean());
+
+        for (event, expected) in buffer.iter().zip(&events) {
+            assert_eq!(event.as_event(), Some(expected));
+        }
+    }
+
+    #[repr(C, align(8))]
+    struct AlignedBytes([u8; 64]);
+
+    /// Creates an event of a non-standard space with the given total size.
+    fn odd_sized_event(size: u32, fill: u8) -> AlignedBytes {
+        let mut buf = AlignedBytes([fill; 64]);
+        let header = clap_event_header {
+            size,
+            time: 0,
+            space_id: 42,
+            type_: 0,
+            flags: 0,
+        };
+
+        // SAFETY: the buffer is large enough and properly aligned for a header.
+        unsafe { core::ptr::write(buf.0.as_mut_ptr().cast(), header) };
+        buf
+    }
+
+    #[test]
+    fn events_are_always_aligned() {
+        let header_size = size_of::<clap_event_header>() as u32;
+        let sizes = [header_size + 1, header_size + 3, header_size + 7, 41];
+        let odd_events: Vec<_> = sizes
+            .iter()
+            .enumerate()
+            .map(|(i, size)| odd_sized_event(*size, i as u8))
+            .collect();
+
+        let mut buffer = EventBuffer::new();
+
+        for (i, odd_event) in odd_events.iter().enumerate() {
+            // SAFETY: the buffer is aligned and contains a valid header, followed by enough data.
+            let odd_event = unsafe { UnknownEvent::from_raw(odd_event.0.as_ptr().cast()) };
+            buffer.push(odd_event);
+            buffer.push(&NoteOnEvent::new(
+                i as u32,
+                Pckn::new(0u8, 0u8, 60u8, 0u32),
+                1.0,
+            ));
+        }
+
+        assert_eq!(buffer.len(), sizes.len() * 2);
+
+        for (i, event) in buffer.iter().enumerate() {
+            assert_eq!(event.as_raw() as usize % 8, 0);
+
+            if i % 2 == 0 {
+                let size = sizes[i / 2] as usize;
+                assert_eq!(event.as_bytes().len(), size);
+                assert_eq!(event.as_bytes(), &odd_events[i / 2].0[..size]);
+            } else {
+                let note = event.as_event::<NoteOnEvent>().unwrap();
+                assert_eq!(note.header().time(), (i / 2) as u32);
+            }
+        }
+    }
 }
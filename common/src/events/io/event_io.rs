@@ -0,0 +1,312 @@
+use crate::events::event_types::*;
+use crate::events::io::{EventBuffer, InputEvents, OutputEvents};
+use crate::events::spaces::CoreEventSpace;
+use crate::events::{Event, EventHeader, UnknownEvent};
+use core::ops::Range;
+
+/// A pair of [`EventBuffer`]s, holding the input and output events of a `process` or `flush`
+/// call.
+///
+/// This saves the ceremony of creating and wrapping two separate buffers for every call: events
+/// are pushed with [`push_input`](Self::push_input), the borrowed [`InputEvents`] and
+/// [`OutputEvents`] views are produced by [`split`](Self::split) (or individually by
+/// [`input`](Self::input) and [`output`](Self::output)), and the plugin's output events are then
+/// read back using [`drain_output`](Self::drain_output).
+///
+/// # Example
+///
+/// ```
+/// use clack_common::events::event_types::{NoteOnEvent, ParamGestureBeginEvent};
+/// use clack_common::events::io::{EventIo, OwnedEvent};
+/// use clack_common::events::Pckn;
+/// use clack_common::utils::ClapId;
+///
+/// let mut events = EventIo::with_capacity(16);
+/// events.push_input(&NoteOnEvent::new(0, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0));
+///
+/// let (input, mut output) = events.split();
+/// assert_eq!(input.len(), 1);
+/// // In a real host, these would be given to the plugin's process or flush call.
+/// output.try_push(ParamGestureBeginEvent::new(4, ClapId::new(2))).unwrap();
+///
+/// let output: Vec<OwnedEvent> = events.drain_output().collect();
+/// assert!(matches!(output[..], [OwnedEvent::ParamGestureBegin(_)]));
+///
+/// // Get ready for the next block.
+/// events.reset();
+/// assert!(events.input_buffer().is_empty());
+/// ```
+///
+/// # Realtime Safety
+///
+/// Like [`EventBuffer`], pushing input events or receiving output events may allocate if the
+/// buffers grow beyond their capacity. However, [`reset`](Self::reset) and
+/// [`drain_output`](Self::drain_output) never release the allocated memory: once the buffers
+/// have grown to fit the busiest block (or have been pre-allocated using
+/// [`with_capacity`](Self::with_capacity)), an [`EventIo`] can be reused on the audio thread
+/// without allocating.
+#[derive(Debug, Default)]
+pub struct EventIo {
+    input: EventBuffer,
+    output: EventBuffer,
+}
+
+impl EventIo {
+    /// Creates a new [`EventIo`], with empty input and output buffers.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            input: EventBuffer::new(),
+            output: EventBuffer::new(),
+        }
+    }
+
+    /// Creates a new [`EventIo`], with enough pre-allocated capacity for the given number of
+    /// standard events in both its input and output buffers.
+    ///
+    /// See [`EventBuffer::with_capacity`].
+    #[inline]
+    pub fn with_capacity(events: usize) -> Self {
+        Self {
+            input: EventBuffer::with_capacity(events),
+            output: EventBuffer::with_capacity(events),
+        }
+    }
+
+    /// Pushes the given event at the end of the input events.
+    ///
+    /// Input events must be sorted by time before being sent to a plugin: either push them in
+    /// order, or sort them using [`EventBuffer::sort`] on the
+    /// [`input_buffer_mut`](Self::input_buffer_mut).
+    #[inline]
+    pub fn push_input<E: AsRef<UnknownEvent> + ?Sized>(&mut self, event: &E) {
+        self.input.push(event)
+    }
+
+    /// Produces an [`InputEvents`] reading the input events.
+    #[inline]
+    pub fn input(&self) -> InputEvents<'_> {
+        self.input.as_input()
+    }
+
+    /// Produces an [`OutputEvents`] appending to the output events.
+    #[inline]
+    pub fn output(&mut self) -> OutputEvents<'_> {
+        self.output.as_output()
+    }
+
+    /// Produces both the [`InputEvents`] and [`OutputEvents`] views at once, as needed by
+    /// `process` and `flush` calls.
+    #[inline]
+    pub fn split(&mut self) -> (InputEvents<'_>, OutputEvents<'_>) {
+        (self.input.as_input(), self.output.as_output())
+    }
+
+    /// Returns the buffer holding the input events.
+    #[inline]
+    pub fn input_buffer(&self) -> &EventBuffer {
+        &self.input
+    }
+
+    /// Returns the buffer holding the input events, mutably.
+    #[inline]
+    pub fn input_buffer_mut(&mut self) -> &mut EventBuffer {
+        &mut self.input
+    }
+
+    /// Returns the buffer holding the output events.
+    #[inline]
+    pub fn output_buffer(&self) -> &EventBuffer {
+        &self.output
+    }
+
+    /// Returns an iterator over copies of the output events, which empties the output buffer.
+    ///
+    /// The output buffer is emptied when the returned iterator is dropped, even if it wasn't
+    /// fully consumed. Its capacity is left untouched.
+    #[inline]
+    pub fn drain_output(&mut self) -> EventIoDrain<'_> {
+        EventIoDrain {
+            range: 0..self.output.len(),
+            buffer: &mut self.output,
+        }
+    }
+
+    /// Clears both the input and output events, for reuse in the next block.
+    ///
+    /// Note that this has no effect on the allocated capacity of the buffers.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.input.clear();
+        self.output.clear();
+    }
+}
+
+/// A by-value copy of an event, as returned by [`EventIo::drain_output`].
+///
+/// All core events are copied in full, except for [`MidiSysExEvent`]s, whose data is only
+/// borrowed from the plugin for the duration of the call. Those, as well as events from other
+/// event spaces, are reduced to their [`EventHeader`], in the [`Other`](OwnedEvent::Other)
+/// variant.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OwnedEvent {
+    /// A [`NoteOnEvent`].
+    NoteOn(NoteOnEvent),
+    /// A [`NoteOffEvent`].
+    NoteOff(NoteOffEvent),
+    /// A [`NoteChokeEvent`].
+    NoteChoke(NoteChokeEvent),
+    /// A [`NoteEndEvent`].
+    NoteEnd(NoteEndEvent),
+    /// A [`NoteExpressionEvent`].
+    NoteExpression(NoteExpressionEvent),
+    /// A [`ParamValueEvent`].
+    ParamValue(ParamValueEvent),
+    /// A [`ParamModEvent`].
+    ParamMod(ParamModEvent),
+    /// A [`ParamGestureBeginEvent`].
+    ParamGestureBegin(ParamGestureBeginEvent),
+    /// A [`ParamGestureEndEvent`].
+    ParamGestureEnd(ParamGestureEndEvent),
+    /// A [`TransportEvent`].
+    Transport(TransportEvent),
+    /// A [`MidiEvent`].
+    Midi(MidiEvent),
+    /// A [`Midi2Event`].
+    Midi2(Midi2Event),
+    /// The header of any other event.
+    Other(EventHeader),
+}
+
+impl OwnedEvent {
+    /// Copies the given event.
+    pub fn from_unknown(event: &UnknownEvent) -> Self {
+        match event.as_core_event() {
+            Some(CoreEventSpace::NoteOn(e)) => Self::NoteOn(*e),
+            Some(CoreEventSpace::NoteOff(e)) => Self::NoteOff(*e),
+            Some(CoreEventSpace::NoteChoke(e)) => Self::NoteChoke(*e),
+            Some(CoreEventSpace::NoteEnd(e)) => Self::NoteEnd(*e),
+            Some(CoreEventSpace::NoteExpression(e)) => Self::NoteExpression(*e),
+            Some(CoreEventSpace::ParamValue(e)) => Self::ParamValue(*e),
+            Some(CoreEventSpace::ParamMod(e)) => Self::ParamMod(*e),
+            Some(CoreEventSpace::ParamGestureBegin(e)) => Self::ParamGestureBegin(*e),
+            Some(CoreEventSpace::ParamGestureEnd(e)) => Self::ParamGestureEnd(*e),
+            Some(CoreEventSpace::Transport(e)) => Self::Transport(*e),
+            Some(CoreEventSpace::Midi(e)) => Self::Midi(*e),
+            Some(CoreEventSpace::Midi2(e)) => Self::Midi2(*e),
+            Some(CoreEventSpace::MidiSysEx(_)) | None => Self::Other(*event.header()),
+        }
+    }
+
+    /// Returns this event as an [`UnknownEvent`], or [`None`] if only its header was kept.
+    pub fn as_unknown(&self) -> Option<&UnknownEvent> {
+        match self {
+            Self::NoteOn(e) => Some(e.as_unknown()),
+            Self::NoteOff(e) => Some(e.as_unknown()),
+            Self::NoteChoke(e) => Some(e.as_unknown()),
+            Self::NoteEnd(e) => Some(e.as_unknown()),
+            Self::NoteExpression(e) => Some(e.as_unknown()),
+            Self::ParamValue(e) => Some(e.as_unknown()),
+            Self::ParamMod(e) => Some(e.as_unknown()),
+            Self::ParamGestureBegin(e) => Some(e.as_unknown()),
+            Self::ParamGestureEnd(e) => Some(e.as_unknown()),
+            Self::Transport(e) => Some(e.as_unknown()),
+            Self::Midi(e) => Some(e.as_unknown()),
+            Self::Midi2(e) => Some(e.as_unknown()),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+/// A draining iterator over the output events of an [`EventIo`].
+///
+/// See [`EventIo::drain_output`].
+pub struct EventIoDrain<'a> {
+    buffer: &'a mut EventBuffer,
+    range: Range<usize>,
+}
+
+impl Iterator for EventIoDrain<'_> {
+    type Item = OwnedEvent;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.range.next()?;
+        self.buffer.get(index as u32).map(OwnedEvent::from_unknown)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl Drop for EventIoDrain<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.buffer.clear()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::Pckn;
+    use crate::utils::{ClapId, Cookie};
+
+    fn param_value(time: u32, value: f64) -> ParamValueEvent {
+        ParamValueEvent::new(
+            time,
+            ClapId::new(1),
+            Pckn::match_all(),
+            value,
+            Cookie::empty(),
+        )
+    }
+
+    #[test]
+    fn drained_events_are_copied_and_removed() {
+        let mut events = EventIo::new();
+        events.push_input(&param_value(0, 0.5));
+
+        let (input, mut output) = events.split();
+        for event in &input {
+            output.try_push(event).unwrap();
+        }
+        output.try_push(param_value(1, 0.25)).unwrap();
+
+        let mut drain = events.drain_output();
+        assert_eq!(
+            drain.next(),
+            Some(OwnedEvent::ParamValue(param_value(0, 0.5)))
+        );
+        drop(drain);
+
+        assert!(events.output_buffer().is_empty());
+        assert_eq!(events.input_buffer().len(), 1);
+        assert!(events.drain_output().next().is_none());
+    }
+
+    #[test]
+    fn non_copyable_events_keep_their_header() {
+        let mut events = EventIo::new();
+        let data = [0xF0, 0xF7];
+        let sysex = MidiSysExEvent::new(3, 0, &data);
+        events.output().try_push(sysex).unwrap();
+
+        let drained: alloc::vec::Vec<_> = events.drain_output().collect();
+        assert_eq!(drained, [OwnedEvent::Other(*sysex.as_unknown().header())]);
+        assert!(drained[0].as_unknown().is_none());
+    }
+
+    #[test]
+    fn reset_clears_both_buffers() {
+        let mut events = EventIo::with_capacity(4);
+        events.push_input(&param_value(0, 0.5));
+        events.output().try_push(param_value(0, 0.5)).unwrap();
+
+        events.reset();
+        assert!(events.input_buffer().is_empty());
+        assert!(events.output_buffer().is_empty());
+    }
+}
@@ -1,8 +1,11 @@
 use crate::events::io::TryPushError;
 use crate::events::spaces::CoreEventSpace;
-use crate::events::{Event, UnknownEvent};
+use crate::events::{is_aligned_for, Event, UnknownEvent};
+use crate::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
 use crate::utils::handle_panic;
+use alloc::vec::Vec;
 use clap_sys::events::{clap_event_header, clap_input_events, clap_output_events};
+use clap_sys::ext::log::CLAP_LOG_ERROR;
 
 /// A trait for all types which can act as an ordered, indexed list of [`UnknownEvent`]s.
 ///
@@ -84,15 +87,64 @@ unsafe extern "C" fn try_push<O: OutputEventBuffer>(
     event: *const clap_event_header,
 ) -> bool {
     handle_panic(|| {
-        O::try_push(
-            &mut *((*list).ctx as *const _ as *mut O),
-            UnknownEvent::from_raw(event),
-        )
-        .is_ok()
+        let Some(event) = checked_event_from_raw(event, "clap_output_events.try_push") else {
+            return false;
+        };
+
+        O::try_push(&mut *((*list).ctx as *const _ as *mut O), event).is_ok()
     })
     .unwrap_or(false)
 }
 
+/// Gets an unknown event from a raw event header pointer provided by the other side of the CLAP
+/// API, checking that it can be safely read.
+///
+/// Null pointers are rejected silently. Misaligned or undersized events are logged to the
+/// [fallback logger](crate::utils::fallback_log) and rejected, as reading them would otherwise be
+/// Undefined Behavior.
+///
+/// # Safety
+///
+/// If non-null and properly aligned, the pointer must point to a readable event header,
+/// immediately followed by the number of bytes the header advertises.
+pub(crate) unsafe fn checked_event_from_raw<'e>(
+    event: *const clap_event_header,
+    callback: &'static str,
+) -> Option<&'e UnknownEvent> {
+    if event.is_null() {
+        return None;
+    }
+
+    if !is_aligned_for::<clap_event_header>(event) {
+        log_rejected_event(
+            callback,
+            format_args!("Ignored misaligned event at address {event:p}"),
+        );
+        return None;
+    }
+
+    let size = (*event).size as usize;
+    if size < core::mem::size_of::<clap_event_header>() {
+        log_rejected_event(
+            callback,
+            format_args!("Ignored event with invalid size {size}, smaller than its header"),
+        );
+        return None;
+    }
+
+    Some(UnknownEvent::from_raw(event))
+}
+
+fn log_rejected_event(callback: &'static str, message: core::fmt::Arguments) {
+    fallback_log(&LogRecord {
+        origin: LogOrigin::Events,
+        severity: CLAP_LOG_ERROR,
+        plugin_id: None,
+        callback,
+        message: &message,
+    });
+}
+
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn void_push(
     _list: *const clap_output_events,
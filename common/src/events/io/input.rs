@@ -1,10 +1,12 @@
-use crate::events::io::implementation::{raw_input_events, InputEventBuffer};
-use crate::events::io::EventBatcher;
+use crate::events::io::implementation::{
+    checked_event_from_raw, raw_input_events, InputEventBuffer,
+};
+use crate::events::io::{EventBatcher, InputEventStats};
 use crate::events::UnknownEvent;
 use clap_sys::events::clap_input_events;
-use std::fmt::{Debug, Formatter};
-use std::marker::PhantomData;
-use std::ops::{Index, Range};
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::ops::{Index, Range};
 
 /// An input list of timestamped events.
 ///
@@ -128,6 +130,13 @@ impl<'a> InputEvents<'a> {
     /// [`len`](InputEvents::len) before attempting to call this method, to ensure that `index` is
     /// always in bounds.
     ///
+    /// # Misaligned events
+    ///
+    /// Events that are not properly aligned, or whose size is too small to even contain their
+    /// header, cannot be safely read. If such an event is returned by the underlying event list,
+    /// it is skipped (i.e. `None` is returned), and an error is logged to the
+    /// [fallback logger](crate::utils::fallback_log).
+    ///
     /// # See also
     ///
     /// Use the [`iter`](InputEvents::iter) method to iterate on all input events, which handles
@@ -136,12 +145,10 @@ impl<'a> InputEvents<'a> {
     pub fn get(&self, index: u32) -> Option<&UnknownEvent> {
         // SAFETY: this function pointer is safely initialized by from_raw or from_buffer
         let event = unsafe { self.inner.get?(&self.inner, index) };
-        if event.is_null() {
-            return None;
-        };
 
-        // SAFETY: the returned event pointer is guaranteed to be valid by from_raw or from_buffer
-        unsafe { Some(UnknownEvent::from_raw(event)) }
+        // SAFETY: the returned event pointer is guaranteed to be valid by from_raw or from_buffer,
+        // as long as it is properly aligned.
+        unsafe { checked_event_from_raw(event, "clap_input_events.get") }
     }
 
     /// Returns an iterator over all the events in this [`InputEvents`].
@@ -243,9 +250,51 @@ impl<'a> InputEvents<'a> {
     pub fn batch(&self) -> EventBatcher {
         EventBatcher::new(self)
     }
+
+    /// Returns an iterator that batches the events happening in the given range of frames only.
+    ///
+    /// This works like [`batch`](Self::batch), except that events outside of the given range are
+    /// skipped, and that the sample indexes of all batches are relative to the start of the range.
+    /// This allows to process the events alongside sub-ranges of the sample buffers, e.g. when
+    /// processing audio in fixed-size chunks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_common::events::event_types::ParamGestureBeginEvent;
+    /// use clack_common::events::io::InputEvents;
+    /// use clack_common::utils::ClapId;
+    ///
+    /// let buf = [ParamGestureBeginEvent::new(5, ClapId::new(0))];
+    /// let events = InputEvents::from_buffer(&buf);
+    ///
+    /// // The event happens on the second sample of the 4..8 range.
+    /// let batches: Vec<_> = events.batch_frames(4..8).map(|b| b.first_sample()).collect();
+    /// assert_eq!(batches, [0, 1]);
+    ///
+    /// // The event is outside the 0..4 range.
+    /// assert_eq!(events.batch_frames(0..4).count(), 1);
+    /// ```
+    #[inline]
+    pub fn batch_frames(&self, frames: Range<u32>) -> EventBatcher<'_> {
+        EventBatcher::in_frames(self, frames)
+    }
+
+    /// Computes statistics about the events in this list, for a block of `frames_count` frames.
+    ///
+    /// This reads through the whole list once, counting events per type, as well as events that
+    /// are out of order, out of the block's time range, or that could not be read at all.
+    /// Nothing is counted unless this method is called, so it can be used in debug builds only
+    /// (e.g. behind `cfg!(debug_assertions)`) to diagnose misbehaving hosts.
+    ///
+    /// See [`InputEventStats`] for more information.
+    #[inline]
+    pub fn stats(&self, frames_count: u32) -> InputEventStats {
+        InputEventStats::collect(self, frames_count)
+    }
 }
 
-impl<'a> IntoIterator for &'a InputEvents<'a> {
+impl<'a> IntoIterator for &'a InputEvents<'_> {
     type Item = &'a UnknownEvent;
     type IntoIter = InputEventsIter<'a>;
 
@@ -286,7 +335,7 @@ impl Index<usize> for InputEvents<'_> {
 }
 
 impl Debug for InputEvents<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let mut list = f.debug_list();
         for event in self {
             if let Some(event) = event.as_core_event() {
@@ -328,7 +377,9 @@ impl<'a> Iterator for InputEventsIter<'a> {
     type Item = &'a UnknownEvent;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.range.next().and_then(|i| self.list.get(i))
+        // Skip over any events the list fails to return, instead of stopping early.
+        let list = self.list;
+        self.range.find_map(|i| list.get(i))
     }
 
     #[inline]
@@ -347,7 +398,8 @@ impl ExactSizeIterator for InputEventsIter<'_> {
 impl DoubleEndedIterator for InputEventsIter<'_> {
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.range.next_back().and_then(|i| self.list.get(i))
+        let list = self.list;
+        self.range.by_ref().rev().find_map(|i| list.get(i))
     }
 }
 
@@ -357,4 +409,66 @@ mod test {
     use super::*;
 
     sa::assert_not_impl_any!(InputEvents<'static>: Send, Sync);
+
+    use crate::events::event_types::NoteOnEvent;
+    use crate::events::{Event, Pckn};
+    use clap_sys::events::clap_event_header;
+
+    #[repr(C, align(8))]
+    struct Storage([NoteOnEvent; 2], [u8; 64]);
+
+    #[allow(clippy::missing_safety_doc)]
+    unsafe extern "C" fn size(_list: *const clap_input_events) -> u32 {
+        3
+    }
+
+    #[allow(clippy::missing_safety_doc)]
+    unsafe extern "C" fn get(
+        list: *const clap_input_events,
+        index: u32,
+    ) -> *const clap_event_header {
+        let storage = &*((*list).ctx as *const Storage);
+
+        match index {
+            0 => storage.0[0].as_unknown().as_raw(),
+            // Deliberately misaligned
+            1 => storage.1.as_ptr().add(1).cast(),
+            _ => storage.0[1].as_unknown().as_raw(),
+        }
+    }
+
+    #[test]
+    fn skips_misaligned_events() {
+        let storage = Storage(
+            [
+                NoteOnEvent::new(0, Pckn::new(0u8, 0u8, 60u8, 0u32), 1.0),
+                NoteOnEvent::new(1, Pckn::new(0u8, 0u8, 64u8, 0u32), 1.0),
+            ],
+            [0; 64],
+        );
+
+        let raw = clap_input_events {
+            ctx: &storage as *const Storage as *mut _,
+            size: Some(size),
+            get: Some(get),
+        };
+
+        // SAFETY: the list is valid for the duration of the test.
+        let events = unsafe { InputEvents::from_raw(&raw) };
+
+        assert!(events.get(0).is_some());
+        assert!(events.get(1).is_none());
+        assert!(events.get(2).is_some());
+
+        let stats = events.stats(4);
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.unreadable, 1);
+        assert_eq!(stats.count_of::<NoteOnEvent>(), 2);
+
+        let times: Vec<_> = events.iter().map(|e| e.header().time()).collect();
+        assert_eq!(times, [0, 1]);
+
+        let times: Vec<_> = events.iter().rev().map(|e| e.header().time()).collect();
+        assert_eq!(times, [1, 0]);
+    }
 }
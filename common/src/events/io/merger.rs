@@ -1,5 +1,5 @@
 use crate::events::UnknownEvent;
-use std::mem::replace;
+use core::mem::replace;
 
 /// An iterator that merges two ordered streams of events together.
 ///
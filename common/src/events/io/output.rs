@@ -1,10 +1,10 @@
+use crate::error::Error;
 use crate::events::io::implementation::{raw_output_events, OutputEventBuffer};
 use crate::events::io::void_output_events;
 use crate::events::UnknownEvent;
 use clap_sys::events::clap_output_events;
-use std::error::Error;
-use std::fmt::{Display, Formatter};
-use std::marker::PhantomData;
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
 
 /// An ordered list of timestamped events.
 ///
@@ -53,11 +53,12 @@ impl<'a> OutputEvents<'a> {
 
     /// Returns a C FFI-compatible mutable pointer to this event list.
     ///
-    /// This pointer is only valid until the list is dropped.
+    /// This pointer is only valid until the list is dropped, and allows it to be pushed to. As such,
+    /// it must not be used anymore once this list is used again through any other means.
     #[inline]
-    pub fn as_raw_mut(&mut self) -> &mut clap_output_events {
-        // SAFETY: OutputEvents list has the same layout and is repr(C)
-        unsafe { &mut *(self as *mut _ as *mut _) }
+    pub fn as_raw_mut(&mut self) -> *mut clap_output_events {
+        // OutputEvents list has the same layout and is repr(C)
+        self as *mut Self as *mut _
     }
 
     /// Create a new event list by wrapping an event buffer implementation.
@@ -153,7 +154,7 @@ impl TryPushError {
 }
 
 impl Display for TryPushError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str("Failed to push event into output event buffer")
     }
 }
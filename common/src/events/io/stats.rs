@@ -0,0 +1,249 @@
+use crate::events::io::{InputEvents, OutputEventBuffer, TryPushError};
+use crate::events::spaces::{CoreEventSpace, EventSpaceId};
+use crate::events::{Event, UnknownEvent};
+
+/// The number of core event types that are counted separately by [`InputEventStats`].
+const CORE_EVENT_TYPE_COUNT: usize = 16;
+
+/// Statistics about the contents of an [`InputEvents`] list.
+///
+/// These are meant to help diagnose hosts that send malformed or unexpected event streams (e.g.
+/// notes that never end). They are computed on demand by [`InputEvents::stats`], and can be
+/// accumulated across multiple blocks using [`merge`](Self::merge).
+///
+/// # Example
+///
+/// ```
+/// use clack_common::events::event_types::{NoteOffEvent, NoteOnEvent};
+/// use clack_common::events::io::{EventBuffer, InputEvents};
+/// use clack_common::events::Pckn;
+///
+/// let mut buffer = EventBuffer::new();
+/// buffer.push(&NoteOnEvent::new(8, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0));
+/// buffer.push(&NoteOffEvent::new(4, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0));
+/// buffer.push(&NoteOnEvent::new(64, Pckn::new(0u16, 0u16, 62u16, 1u32), 1.0));
+///
+/// let stats = buffer.as_input().stats(32);
+///
+/// assert_eq!(stats.total, 3);
+/// assert_eq!(stats.count_of::<NoteOnEvent>(), 2);
+/// assert_eq!(stats.count_of::<NoteOffEvent>(), 1);
+/// assert_eq!(stats.out_of_order, 1);
+/// assert_eq!(stats.out_of_range_time, 1);
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct InputEventStats {
+    /// The number of events the list reported, including the unreadable ones.
+    pub total: u32,
+    /// The number of events the list failed to return, e.g. because they were misaligned.
+    pub unreadable: u32,
+    /// The number of events whose time is before the time of the event preceding them.
+    pub out_of_order: u32,
+    /// The number of events whose time is not within the processed block.
+    pub out_of_range_time: u32,
+    /// The number of events that are not from the core event space.
+    pub non_core: u32,
+    core: [u32; CORE_EVENT_TYPE_COUNT],
+}
+
+impl InputEventStats {
+    /// Returns the number of core events of the given raw type ID.
+    ///
+    /// Events from other event spaces are not counted here, see [`non_core`](Self::non_core).
+    #[inline]
+    pub fn count(&self, core_type_id: u16) -> u32 {
+        self.core.get(core_type_id as usize).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of events of the given core event type.
+    #[inline]
+    pub fn count_of<E: for<'a> Event<EventSpace<'a> = CoreEventSpace<'a>>>(&self) -> u32 {
+        self.count(E::TYPE_ID)
+    }
+
+    /// Adds the statistics of another list to these.
+    pub fn merge(&mut self, other: &InputEventStats) {
+        self.total = self.total.saturating_add(other.total);
+        self.unreadable = self.unreadable.saturating_add(other.unreadable);
+        self.out_of_order = self.out_of_order.saturating_add(other.out_of_order);
+        self.out_of_range_time = self
+            .out_of_range_time
+            .saturating_add(other.out_of_range_time);
+        self.non_core = self.non_core.saturating_add(other.non_core);
+
+        for (count, other) in self.core.iter_mut().zip(other.core) {
+            *count = count.saturating_add(other);
+        }
+    }
+
+    pub(crate) fn collect(events: &InputEvents, frames_count: u32) -> Self {
+        let mut stats = Self {
+            total: events.len(),
+            ..Self::default()
+        };
+
+        let mut previous_time = 0;
+        let mut read = 0;
+
+        for event in events {
+            read += 1;
+
+            let time = event.header().time();
+            stats.out_of_order += (time < previous_time) as u32;
+            stats.out_of_range_time += (time >= frames_count) as u32;
+            previous_time = time;
+
+            stats.record_type(event);
+        }
+
+        stats.unreadable = stats.total - read;
+        stats
+    }
+
+    #[inline]
+    fn record_type(&mut self, event: &UnknownEvent) {
+        let header = event.header();
+        let is_core = header.space_id().map(|id| id.id()) == Some(EventSpaceId::core().id());
+
+        match self.core.get_mut(header.type_id() as usize) {
+            Some(count) if is_core => *count += 1,
+            _ => self.non_core += 1,
+        }
+    }
+}
+
+/// Statistics about the events pushed into an [`EventDropCounter`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OutputEventStats {
+    /// The number of events that were successfully pushed.
+    pub pushed: u64,
+    /// The number of events the underlying buffer refused, e.g. because it was full, or because
+    /// it could not represent them.
+    pub dropped: u64,
+}
+
+/// An [`OutputEventBuffer`] wrapper that counts the events the wrapped buffer dropped.
+///
+/// This is useful alongside fixed-capacity or type-restricted buffers, which may refuse some of
+/// the events pushed into them. Counting is always enabled, and does not add any branch to the
+/// push path.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::events::event_types::{NoteOffEvent, NoteOnEvent};
+/// use clack_common::events::io::{EventDropCounter, OutputEvents};
+/// use clack_common::events::Pckn;
+///
+/// // This buffer only accepts note on events.
+/// let mut counter = EventDropCounter::new(Vec::<NoteOnEvent>::new());
+/// let mut output = OutputEvents::from_buffer(&mut counter);
+///
+/// let pckn = Pckn::new(0u16, 0u16, 60u16, 0u32);
+/// let _ = output.try_push(NoteOnEvent::new(0, pckn, 1.0));
+/// let _ = output.try_push(NoteOffEvent::new(4, pckn, 1.0));
+///
+/// assert_eq!(counter.stats().pushed, 1);
+/// assert_eq!(counter.stats().dropped, 1);
+/// assert_eq!(counter.inner().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EventDropCounter<O> {
+    inner: O,
+    stats: OutputEventStats,
+}
+
+impl<O: OutputEventBuffer> EventDropCounter<O> {
+    /// Wraps the given buffer.
+    #[inline]
+    pub const fn new(inner: O) -> Self {
+        Self {
+            inner,
+            stats: OutputEventStats {
+                pushed: 0,
+                dropped: 0,
+            },
+        }
+    }
+
+    /// Returns the statistics gathered since this counter was created or last reset.
+    #[inline]
+    pub fn stats(&self) -> OutputEventStats {
+        self.stats
+    }
+
+    /// Resets the statistics to zero.
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.stats = OutputEventStats::default();
+    }
+
+    /// Returns a shared reference to the wrapped buffer.
+    #[inline]
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped buffer.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped buffer.
+    #[inline]
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+
+impl<O: OutputEventBuffer> OutputEventBuffer for EventDropCounter<O> {
+    #[inline]
+    fn try_push(&mut self, event: &UnknownEvent) -> Result<(), TryPushError> {
+        let result = self.inner.try_push(event);
+        let pushed = result.is_ok() as u64;
+
+        self.stats.pushed = self.stats.pushed.wrapping_add(pushed);
+        self.stats.dropped = self.stats.dropped.wrapping_add(pushed ^ 1);
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::event_types::{NoteOnEvent, ParamValueEvent};
+    use crate::events::io::EventBuffer;
+    use crate::events::Pckn;
+    use crate::utils::{ClapId, Cookie};
+
+    #[test]
+    fn merges_stats_across_blocks() {
+        let mut buffer = EventBuffer::new();
+        buffer.push(&NoteOnEvent::new(
+            0,
+            Pckn::new(0u16, 0u16, 60u16, 0u32),
+            1.0,
+        ));
+        buffer.push(&ParamValueEvent::new(
+            2,
+            ClapId::new(1),
+            Pckn::match_all(),
+            0.5,
+            Cookie::empty(),
+        ));
+
+        let mut total = InputEventStats::default();
+        total.merge(&buffer.as_input().stats(4));
+        total.merge(&buffer.as_input().stats(1));
+
+        assert_eq!(total.total, 4);
+        assert_eq!(total.count_of::<NoteOnEvent>(), 2);
+        assert_eq!(total.count_of::<ParamValueEvent>(), 2);
+        assert_eq!(total.out_of_order, 0);
+        assert_eq!(total.out_of_range_time, 1);
+        assert_eq!(total.unreadable, 0);
+        assert_eq!(total.non_core, 0);
+    }
+}
@@ -5,7 +5,7 @@ pub use self::core::*;
 pub use id::*;
 
 use crate::events::UnknownEvent;
-use std::ffi::CStr;
+use ::core::ffi::CStr;
 
 /// Holds all the possible event types included in a given event space.  
 ///
@@ -1,7 +1,7 @@
 use crate::events::event_types::*;
 use crate::events::{Event, EventSpace, UnknownEvent};
-use std::ffi::CStr;
-use std::fmt::{Debug, Formatter};
+use core::ffi::CStr;
+use core::fmt::{Debug, Formatter};
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum CoreEventSpace<'a> {
@@ -30,17 +30,21 @@ unsafe impl<'a> EventSpace<'a> for CoreEventSpace<'a> {
         use CoreEventSpace::*;
 
         match event.header().type_id() {
-            NoteOnEvent::TYPE_ID => Some(NoteOn(event.as_event_unchecked())),
-            NoteOffEvent::TYPE_ID => Some(NoteOff(event.as_event_unchecked())),
-            NoteChokeEvent::TYPE_ID => Some(NoteChoke(event.as_event_unchecked())),
-            NoteEndEvent::TYPE_ID => Some(NoteEnd(event.as_event_unchecked())),
-            NoteExpressionEvent::TYPE_ID => Some(NoteExpression(event.as_event_unchecked())),
-            ParamValueEvent::TYPE_ID => Some(ParamValue(event.as_event_unchecked())),
-            ParamModEvent::TYPE_ID => Some(ParamMod(event.as_event_unchecked())),
-            TransportEvent::TYPE_ID => Some(Transport(event.as_event_unchecked())),
-            MidiEvent::TYPE_ID => Some(Midi(event.as_event_unchecked())),
-            Midi2Event::TYPE_ID => Some(Midi2(event.as_event_unchecked())),
-            MidiSysExEvent::TYPE_ID => Some(MidiSysEx(event.as_event_unchecked())),
+            NoteOnEvent::TYPE_ID => Some(NoteOn(event.as_event_checked_size()?)),
+            NoteOffEvent::TYPE_ID => Some(NoteOff(event.as_event_checked_size()?)),
+            NoteChokeEvent::TYPE_ID => Some(NoteChoke(event.as_event_checked_size()?)),
+            NoteEndEvent::TYPE_ID => Some(NoteEnd(event.as_event_checked_size()?)),
+            NoteExpressionEvent::TYPE_ID => Some(NoteExpression(event.as_event_checked_size()?)),
+            ParamValueEvent::TYPE_ID => Some(ParamValue(event.as_event_checked_size()?)),
+            ParamModEvent::TYPE_ID => Some(ParamMod(event.as_event_checked_size()?)),
+            ParamGestureBeginEvent::TYPE_ID => {
+                Some(ParamGestureBegin(event.as_event_checked_size()?))
+            }
+            ParamGestureEndEvent::TYPE_ID => Some(ParamGestureEnd(event.as_event_checked_size()?)),
+            TransportEvent::TYPE_ID => Some(Transport(event.as_event_checked_size()?)),
+            MidiEvent::TYPE_ID => Some(Midi(event.as_event_checked_size()?)),
+            Midi2Event::TYPE_ID => Some(Midi2(event.as_event_checked_size()?)),
+            MidiSysExEvent::TYPE_ID => Some(MidiSysEx(event.as_event_checked_size()?)),
             _ => None,
         }
     }
@@ -67,7 +71,7 @@ unsafe impl<'a> EventSpace<'a> for CoreEventSpace<'a> {
 
 impl Debug for CoreEventSpace<'_> {
     #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             CoreEventSpace::NoteOn(e) => Debug::fmt(e, f),
             CoreEventSpace::NoteOff(e) => Debug::fmt(e, f),
@@ -1,7 +1,7 @@
 use crate::events::spaces::core::CoreEventSpace;
 use crate::events::EventSpace;
 use clap_sys::events::CLAP_CORE_EVENT_SPACE_ID;
-use std::marker::PhantomData;
+use core::marker::PhantomData;
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub struct EventSpaceId<S = ()> {
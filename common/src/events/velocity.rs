@@ -0,0 +1,293 @@
+//! Validation and conversion of note velocities and note expression values.
+//!
+//! CLAP note velocities are `f64` values from `0.0` to `1.0`, and note expression values are
+//! `f64` values within a range that depends on the [`NoteExpressionType`]. This module provides
+//! the [`Velocity`] type, which converts velocities to and from their MIDI `0..=127`
+//! representation and to and from decibels, and the [`ExpressionRange`] type, which holds the
+//! range of each note expression type.
+//!
+//! The clamping constructors ([`Velocity::new`] and [`clamp_for`]) report out-of-range values to
+//! the [fallback logger](crate::utils::fallback_log) in debug builds, as those usually point to a
+//! conversion bug on the other side of the CLAP API.
+//!
+//! # Example
+//!
+//! ```
+//! use clack_common::events::event_types::NoteExpressionType;
+//! use clack_common::events::velocity::*;
+//!
+//! assert_eq!(Velocity::from_midi(127), Velocity::MAX);
+//! assert_eq!(Velocity::new(0.5).to_midi(), 64);
+//! // A Note On with a zero MIDI velocity is a Note Off.
+//! assert_eq!(Velocity::MIN.to_midi_note_on(), 1);
+//!
+//! assert_eq!(clamp_for(NoteExpressionType::Tuning, 12.0), 12.0);
+//! assert_eq!(ExpressionRange::TUNING.max(), 120.0);
+//! ```
+
+use crate::events::event_types::NoteExpressionType;
+#[cfg(debug_assertions)]
+use crate::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
+#[cfg(debug_assertions)]
+use clap_sys::ext::log::CLAP_LOG_WARNING;
+use core::fmt::{Display, Formatter};
+
+/// The highest MIDI velocity.
+const MAX_MIDI_VELOCITY: u8 = 127;
+
+/// A note velocity, from `0.0` to `1.0`.
+///
+/// This is the velocity carried by [`NoteOnEvent`](crate::events::event_types::NoteOnEvent)s
+/// and [`NoteOffEvent`](crate::events::event_types::NoteOffEvent)s. Values of this type are
+/// always within range.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Velocity(f64);
+
+impl Velocity {
+    /// The lowest velocity, `0.0`.
+    pub const MIN: Self = Self(0.0);
+
+    /// The highest velocity, `1.0`.
+    pub const MAX: Self = Self(1.0);
+
+    /// Creates a velocity from the given value, clamping it to the `0.0..=1.0` range.
+    ///
+    /// `NaN` values are turned into [`MIN`](Self::MIN). In debug builds, out-of-range values are
+    /// reported to the [fallback logger](crate::utils::fallback_log).
+    #[inline]
+    pub fn new(value: f64) -> Self {
+        match Self::try_new(value) {
+            Some(velocity) => velocity,
+            None => {
+                #[cfg(debug_assertions)]
+                log_out_of_range("velocity", value, Self::MIN.0, Self::MAX.0);
+
+                if value.is_nan() {
+                    Self::MIN
+                } else {
+                    Self(value.clamp(Self::MIN.0, Self::MAX.0))
+                }
+            }
+        }
+    }
+
+    /// Creates a velocity from the given value, or returns `None` if it is not within the
+    /// `0.0..=1.0` range.
+    #[inline]
+    pub fn try_new(value: f64) -> Option<Self> {
+        if (Self::MIN.0..=Self::MAX.0).contains(&value) {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a velocity from a MIDI velocity, from `0` to `127`.
+    ///
+    /// The highest bit of the given byte is ignored, as MIDI data bytes only have 7 bits.
+    #[inline]
+    pub fn from_midi(velocity: u8) -> Self {
+        Self(f64::from(velocity & 0x7F) / f64::from(MAX_MIDI_VELOCITY))
+    }
+
+    /// Returns this velocity as a MIDI velocity, from `0` to `127`, rounded to the nearest value.
+    #[inline]
+    pub fn to_midi(self) -> u8 {
+        // Velocities are always positive, so this rounds to the nearest value.
+        (self.0 * f64::from(MAX_MIDI_VELOCITY) + 0.5) as u8
+    }
+
+    /// Returns this velocity as the velocity of a MIDI Note On message, from `1` to `127`.
+    ///
+    /// Unlike [`to_midi`](Self::to_midi), this never returns `0`, as a MIDI Note On message with
+    /// a zero velocity is a Note Off message.
+    #[inline]
+    pub fn to_midi_note_on(self) -> u8 {
+        self.to_midi().max(1)
+    }
+
+    /// Creates a velocity from a gain in decibels, clamping it to the `0.0..=1.0` range.
+    ///
+    /// Velocities are treated as linear gains: `0.0` dB is [`MAX`](Self::MAX), and negative
+    /// infinity is [`MIN`](Self::MIN).
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn from_db(db: f64) -> Self {
+        Self::new(10f64.powf(db / 20.0))
+    }
+
+    /// Returns this velocity as a gain in decibels, from negative infinity to `0.0`.
+    ///
+    /// See [`from_db`](Self::from_db).
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn to_db(self) -> f64 {
+        20.0 * self.0.log10()
+    }
+
+    /// Returns the value of this velocity, from `0.0` to `1.0`.
+    #[inline]
+    pub const fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl From<Velocity> for f64 {
+    #[inline]
+    fn from(velocity: Velocity) -> Self {
+        velocity.0
+    }
+}
+
+impl Display for Velocity {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// The range of values of a note expression type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExpressionRange {
+    min: f64,
+    max: f64,
+}
+
+impl ExpressionRange {
+    /// The range of [`NoteExpressionType::Volume`]: a linear gain from `0.0` to `4.0` (about
+    /// +12 dB).
+    pub const VOLUME: Self = Self::new(0.0, 4.0);
+
+    /// The range of [`NoteExpressionType::Pan`]: from `0.0` (left) to `1.0` (right), `0.5` being
+    /// the center.
+    pub const PAN: Self = Self::new(0.0, 1.0);
+
+    /// The range of [`NoteExpressionType::Tuning`]: a relative tuning, from `-120.0` to `120.0`
+    /// semitones.
+    pub const TUNING: Self = Self::new(-120.0, 120.0);
+
+    /// The range of all the other note expression types: from `0.0` to `1.0`.
+    pub const NORMALIZED: Self = Self::new(0.0, 1.0);
+
+    /// Creates a new range, from `min` to `max` (inclusive).
+    #[inline]
+    pub const fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns the range of the given note expression type.
+    #[inline]
+    pub const fn for_type(expression_type: NoteExpressionType) -> Self {
+        match expression_type {
+            NoteExpressionType::Volume => Self::VOLUME,
+            NoteExpressionType::Pan => Self::PAN,
+            NoteExpressionType::Tuning => Self::TUNING,
+            NoteExpressionType::Vibrato
+            | NoteExpressionType::Expression
+            | NoteExpressionType::Brightness
+            | NoteExpressionType::Pressure => Self::NORMALIZED,
+        }
+    }
+
+    /// Returns the lowest value of this range.
+    #[inline]
+    pub const fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Returns the highest value of this range.
+    #[inline]
+    pub const fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Returns `true` if the given value is within this range.
+    #[inline]
+    pub fn contains(&self, value: f64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// Clamps the given value to this range. `NaN` values are turned into the lowest value.
+    #[inline]
+    pub fn clamp(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            self.min
+        } else {
+            value.clamp(self.min, self.max)
+        }
+    }
+}
+
+/// Clamps the given value to the range of the given note expression type.
+///
+/// `NaN` values are turned into the lowest value of the range. In debug builds, out-of-range
+/// values are reported to the [fallback logger](crate::utils::fallback_log).
+#[inline]
+pub fn clamp_for(expression_type: NoteExpressionType, value: f64) -> f64 {
+    let range = ExpressionRange::for_type(expression_type);
+
+    if range.contains(value) {
+        return value;
+    }
+
+    #[cfg(debug_assertions)]
+    log_out_of_range("note expression value", value, range.min, range.max);
+
+    range.clamp(value)
+}
+
+#[cfg(debug_assertions)]
+fn log_out_of_range(name: &str, value: f64, min: f64, max: f64) {
+    fallback_log(&LogRecord {
+        origin: LogOrigin::Events,
+        severity: CLAP_LOG_WARNING,
+        plugin_id: None,
+        callback: "",
+        message: &format_args!("Clamped {name} {value}, outside of the {min}..={max} range"),
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn midi_velocities_round_trip() {
+        for midi in 0..=127 {
+            assert_eq!(Velocity::from_midi(midi).to_midi(), midi);
+        }
+
+        assert_eq!(Velocity::from_midi(0xFF), Velocity::MAX);
+        assert_eq!(Velocity::new(0.001).to_midi(), 0);
+        assert_eq!(Velocity::new(0.001).to_midi_note_on(), 1);
+    }
+
+    #[test]
+    fn out_of_range_velocities_are_clamped() {
+        assert_eq!(Velocity::new(2.0), Velocity::MAX);
+        assert_eq!(Velocity::new(-1.0), Velocity::MIN);
+        assert_eq!(Velocity::new(f64::NAN), Velocity::MIN);
+
+        assert_eq!(Velocity::try_new(2.0), None);
+        assert_eq!(Velocity::try_new(0.5), Some(Velocity::new(0.5)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn db_conversions() {
+        assert_eq!(Velocity::from_db(0.0), Velocity::MAX);
+        assert_eq!(Velocity::from_db(6.0), Velocity::MAX);
+        assert_eq!(Velocity::from_db(f64::NEG_INFINITY), Velocity::MIN);
+        assert_eq!(Velocity::MIN.to_db(), f64::NEG_INFINITY);
+        assert!((Velocity::new(0.5).to_db() + 6.0206).abs() < 1e-4);
+    }
+
+    #[test]
+    fn expression_values_are_clamped_to_their_range() {
+        assert_eq!(clamp_for(NoteExpressionType::Volume, 5.0), 4.0);
+        assert_eq!(clamp_for(NoteExpressionType::Tuning, -200.0), -120.0);
+        assert_eq!(clamp_for(NoteExpressionType::Tuning, -12.0), -12.0);
+        assert_eq!(clamp_for(NoteExpressionType::Pressure, 1.5), 1.0);
+        assert_eq!(clamp_for(NoteExpressionType::Pan, f64::NAN), 0.0);
+    }
+}
@@ -3,11 +3,14 @@
 //! See the documentation of the `extensions` module in the `clack-plugin` and `clack-host` crates
 //! for implementation examples.
 
-use std::ffi::CStr;
+use core::ffi::CStr;
 
 mod raw;
 pub use raw::{RawExtension, RawExtensionImplementation};
 
+#[doc(hidden)]
+pub mod restart_generation;
+
 /// A marker struct that represents extensions to be implemented by the plugin side.
 ///
 /// See [`Extension::ExtensionSide`].
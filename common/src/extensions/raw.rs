@@ -2,10 +2,10 @@
 use crate::extensions::{ExtensionSide, HostExtensionSide, PluginExtensionSide};
 use clap_sys::host::clap_host;
 use clap_sys::plugin::clap_plugin;
-use std::ffi::c_void;
-use std::fmt::{Debug, Formatter};
-use std::marker::PhantomData;
-use std::ptr::NonNull;
+use core::ffi::c_void;
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
 
 /// A raw extension pointer.
 ///
@@ -185,7 +185,7 @@ impl RawExtensionImplementation {
 
 impl Debug for RawExtensionImplementation {
     #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "RawExtensionImplementation({:p})", self.inner)
     }
 }
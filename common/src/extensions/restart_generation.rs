@@ -0,0 +1,32 @@
+//! A small private extension Clack hosts use to tell Clack plugins which restart sequence their
+//! activation and deactivation belong to.
+//!
+//! Hosts that restart a plugin (i.e. deactivate it, then activate it again right away) can mark
+//! both calls with the same non-zero generation number. This allows the plugin to carry some
+//! audio processing state over from the deactivated audio processor to the re-activated one.
+//!
+//! This is not part of the CLAP specification: it is only implemented by `clack-host` and
+//! `clack-plugin`, which handle it transparently. Plugins and hosts that do not use Clack simply
+//! never see it.
+
+#![allow(non_camel_case_types)]
+
+use clap_sys::host::clap_host;
+use core::ffi::CStr;
+
+/// The identifier of the restart generation extension.
+// SAFETY: the byte string is NUL-terminated and has no interior NUL bytes.
+pub const CLACK_EXT_RESTART_GENERATION: &CStr = unsafe {
+    CStr::from_bytes_with_nul_unchecked(b"org.rust-audio.clack.restart-generation.draft/0\0")
+};
+
+/// The host side of the restart generation extension.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct clack_host_restart_generation {
+    /// Returns the generation of the restart sequence the host is currently performing, or `0`
+    /// if it isn't restarting the plugin.
+    ///
+    /// \[main-thread\]
+    pub get: Option<unsafe extern "C" fn(host: *const clap_host) -> u64>,
+}
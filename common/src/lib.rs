@@ -1,13 +1,24 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
 #![deny(clippy::undocumented_unsafe_blocks)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 //! A small crate containing various CLAP utilities and definitions that are common to both
 //! plugins and hosts.
 //!
 //! All modules of this crate are re-exported in the `clack-host` and `clack-plugin` crates. Most users
 //! should not have to use `clack-common` directly.
+//!
+//! # Features
+//!
+//! * `std` (enabled by default): implements the standard library's traits (such as
+//!   [`std::error::Error`] and [`std::io::Read`]) for Clack's types, and enables the default
+//!   [fallback logger](utils::fallback_log). Without it, this crate only depends on `core` and
+//!   `alloc`.
+
+extern crate alloc;
 
 pub mod entry;
+pub mod error;
 pub mod events;
 pub mod extensions;
 pub mod plugin;
@@ -0,0 +1,39 @@
+//! A small private factory Clack plugins use to declare which other plugin IDs they are
+//! compatible with.
+//!
+//! A plugin can declare compatibility with another plugin ID to replace a deprecated ID, or to
+//! load sessions that were saved with another plugin. Hosts can then use one of the compatible
+//! plugins when a session references a plugin ID that no installed plugin provides directly.
+//!
+//! This is not part of the CLAP specification: it is only implemented by `clack-plugin` and
+//! understood by `clack-host`.
+
+#![allow(non_camel_case_types)]
+
+use core::ffi::{c_char, CStr};
+
+/// The identifier of the plugin compatibility factory.
+// SAFETY: the byte string is NUL-terminated and has no interior NUL bytes.
+pub const CLACK_PLUGIN_COMPATIBILITY_FACTORY_ID: &CStr = unsafe {
+    CStr::from_bytes_with_nul_unchecked(
+        b"org.rust-audio.clack.plugin-compatibility-factory.draft/0\0",
+    )
+};
+
+/// The plugin compatibility factory.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct clack_plugin_compatibility_factory {
+    /// Returns a NULL-terminated array of the plugin IDs the plugin with the given ID is
+    /// compatible with, or NULL if it isn't compatible with any other plugin ID.
+    ///
+    /// The returned array and strings remain valid for as long as the factory is.
+    ///
+    /// \[thread-safe\]
+    pub get_compatible_ids: Option<
+        unsafe extern "C" fn(
+            factory: *const clack_plugin_compatibility_factory,
+            plugin_id: *const c_char,
+        ) -> *const *const c_char,
+    >,
+}
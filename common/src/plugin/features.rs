@@ -0,0 +1,457 @@
+//! A set of standard plugin features meant to be used for a plugin descriptor's features.
+//!
+//! Those are available both as raw C string constants (e.g. [`AUDIO_EFFECT`]), and as the
+//! [`PluginFeature`] enum.
+//!
+//! Non-standard features should be formatted as: "$namespace:$feature"
+
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use clap_sys::plugin_features::*;
+use core::ffi::CStr;
+use core::fmt::{Display, Formatter};
+
+/// `"instrument"`: The plugin can process note events and then produce audio
+pub const INSTRUMENT: &CStr = CLAP_PLUGIN_FEATURE_INSTRUMENT;
+/// `"audio-effect"`: The plugin is an audio effect
+pub const AUDIO_EFFECT: &CStr = CLAP_PLUGIN_FEATURE_AUDIO_EFFECT;
+/// `"note-effect"`: The plugin is a note effect or a note generator/sequencer
+pub const NOTE_EFFECT: &CStr = CLAP_PLUGIN_FEATURE_NOTE_EFFECT;
+/// `"note-detector"`: The plugin is a note detector, which analyzes audio to produce note events
+pub const NOTE_DETECTOR: &CStr = CLAP_PLUGIN_FEATURE_NOTE_DETECTOR;
+/// `"analyzer"`: The plugin is an analyzer
+pub const ANALYZER: &CStr = CLAP_PLUGIN_FEATURE_ANALYZER;
+
+/// `"synthesizer"`
+pub const SYNTHESIZER: &CStr = CLAP_PLUGIN_FEATURE_SYNTHESIZER;
+/// `"sampler"`
+pub const SAMPLER: &CStr = CLAP_PLUGIN_FEATURE_SAMPLER;
+/// `"drum"`
+pub const DRUM: &CStr = CLAP_PLUGIN_FEATURE_DRUM;
+/// `"drum-machine"`
+pub const DRUM_MACHINE: &CStr = CLAP_PLUGIN_FEATURE_DRUM_MACHINE;
+
+/// `"filter"`
+pub const FILTER: &CStr = CLAP_PLUGIN_FEATURE_FILTER;
+/// `"phaser"`
+pub const PHASER: &CStr = CLAP_PLUGIN_FEATURE_PHASER;
+/// `"equalizer"`
+pub const EQUALIZER: &CStr = CLAP_PLUGIN_FEATURE_EQUALIZER;
+/// `"de-esser"`
+pub const DEESSER: &CStr = CLAP_PLUGIN_FEATURE_DEESSER;
+/// `"phase-vocoder"`
+pub const PHASE_VOCODER: &CStr = CLAP_PLUGIN_FEATURE_PHASE_VOCODER;
+/// `"granular"`
+pub const GRANULAR: &CStr = CLAP_PLUGIN_FEATURE_GRANULAR;
+/// `"frequency-shifter"`
+pub const FREQUENCY_SHIFTER: &CStr = CLAP_PLUGIN_FEATURE_FREQUENCY_SHIFTER;
+/// `"pitch-shifter"`
+pub const PITCH_SHIFTER: &CStr = CLAP_PLUGIN_FEATURE_PITCH_SHIFTER;
+
+/// `"distortion"`
+pub const DISTORTION: &CStr = CLAP_PLUGIN_FEATURE_DISTORTION;
+/// `"transient-shaper"`
+pub const TRANSIENT_SHAPER: &CStr = CLAP_PLUGIN_FEATURE_TRANSIENT_SHAPER;
+/// `"compressor"`
+pub const COMPRESSOR: &CStr = CLAP_PLUGIN_FEATURE_COMPRESSOR;
+/// `"expander"`
+pub const EXPANDER: &CStr = CLAP_PLUGIN_FEATURE_EXPANDER;
+/// `"gate"`
+pub const GATE: &CStr = CLAP_PLUGIN_FEATURE_GATE;
+/// `"limiter"`
+pub const LIMITER: &CStr = CLAP_PLUGIN_FEATURE_LIMITER;
+
+/// `"flanger"`
+pub const FLANGER: &CStr = CLAP_PLUGIN_FEATURE_FLANGER;
+/// `"chorus"`
+pub const CHORUS: &CStr = CLAP_PLUGIN_FEATURE_CHORUS;
+/// `"delay"`
+pub const DELAY: &CStr = CLAP_PLUGIN_FEATURE_DELAY;
+/// `"reverb"`
+pub const REVERB: &CStr = CLAP_PLUGIN_FEATURE_REVERB;
+
+/// `"tremolo"`
+pub const TREMOLO: &CStr = CLAP_PLUGIN_FEATURE_TREMOLO;
+/// `"glitch"`
+pub const GLITCH: &CStr = CLAP_PLUGIN_FEATURE_GLITCH;
+
+/// `"utility"`
+pub const UTILITY: &CStr = CLAP_PLUGIN_FEATURE_UTILITY;
+/// `"pitch-correction"`
+pub const PITCH_CORRECTION: &CStr = CLAP_PLUGIN_FEATURE_PITCH_CORRECTION;
+/// `"restoration"`
+pub const RESTORATION: &CStr = CLAP_PLUGIN_FEATURE_RESTORATION;
+
+/// `"multi-effects"`
+pub const MULTI_EFFECTS: &CStr = CLAP_PLUGIN_FEATURE_MULTI_EFFECTS;
+
+/// `"mixing"`
+pub const MIXING: &CStr = CLAP_PLUGIN_FEATURE_MIXING;
+/// `"mastering"`
+pub const MASTERING: &CStr = CLAP_PLUGIN_FEATURE_MASTERING;
+
+/// `"mono"`
+pub const MONO: &CStr = CLAP_PLUGIN_FEATURE_MONO;
+/// `"stereo"`
+pub const STEREO: &CStr = CLAP_PLUGIN_FEATURE_STEREO;
+/// `"surround"`
+pub const SURROUND: &CStr = CLAP_PLUGIN_FEATURE_SURROUND;
+/// `"ambisonic"`
+pub const AMBISONIC: &CStr = CLAP_PLUGIN_FEATURE_AMBISONIC;
+
+/// A standard CLAP plugin feature.
+///
+/// This is a typed equivalent of the raw feature constants of this module. It can be converted
+/// from and to those using [`from_cstr`](PluginFeature::from_cstr) and
+/// [`as_cstr`](PluginFeature::as_cstr).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PluginFeature {
+    /// `"instrument"`: The plugin can process note events and then produce audio
+    Instrument,
+    /// `"audio-effect"`: The plugin is an audio effect
+    AudioEffect,
+    /// `"note-effect"`: The plugin is a note effect or a note generator/sequencer
+    NoteEffect,
+    /// `"note-detector"`: The plugin is a note detector, which analyzes audio to produce note events
+    NoteDetector,
+    /// `"analyzer"`: The plugin is an analyzer
+    Analyzer,
+
+    /// `"synthesizer"`
+    Synthesizer,
+    /// `"sampler"`
+    Sampler,
+    /// `"drum"`
+    Drum,
+    /// `"drum-machine"`
+    DrumMachine,
+
+    /// `"filter"`
+    Filter,
+    /// `"phaser"`
+    Phaser,
+    /// `"equalizer"`
+    Equalizer,
+    /// `"de-esser"`
+    Deesser,
+    /// `"phase-vocoder"`
+    PhaseVocoder,
+    /// `"granular"`
+    Granular,
+    /// `"frequency-shifter"`
+    FrequencyShifter,
+    /// `"pitch-shifter"`
+    PitchShifter,
+
+    /// `"distortion"`
+    Distortion,
+    /// `"transient-shaper"`
+    TransientShaper,
+    /// `"compressor"`
+    Compressor,
+    /// `"expander"`
+    Expander,
+    /// `"gate"`
+    Gate,
+    /// `"limiter"`
+    Limiter,
+
+    /// `"flanger"`
+    Flanger,
+    /// `"chorus"`
+    Chorus,
+    /// `"delay"`
+    Delay,
+    /// `"reverb"`
+    Reverb,
+
+    /// `"tremolo"`
+    Tremolo,
+    /// `"glitch"`
+    Glitch,
+
+    /// `"utility"`
+    Utility,
+    /// `"pitch-correction"`
+    PitchCorrection,
+    /// `"restoration"`
+    Restoration,
+
+    /// `"multi-effects"`
+    MultiEffects,
+
+    /// `"mixing"`
+    Mixing,
+    /// `"mastering"`
+    Mastering,
+
+    /// `"mono"`
+    Mono,
+    /// `"stereo"`
+    Stereo,
+    /// `"surround"`
+    Surround,
+    /// `"ambisonic"`
+    Ambisonic,
+}
+
+impl PluginFeature {
+    /// All the standard plugin features.
+    pub const ALL: &'static [PluginFeature] = &[
+        PluginFeature::Instrument,
+        PluginFeature::AudioEffect,
+        PluginFeature::NoteEffect,
+        PluginFeature::NoteDetector,
+        PluginFeature::Analyzer,
+        PluginFeature::Synthesizer,
+        PluginFeature::Sampler,
+        PluginFeature::Drum,
+        PluginFeature::DrumMachine,
+        PluginFeature::Filter,
+        PluginFeature::Phaser,
+        PluginFeature::Equalizer,
+        PluginFeature::Deesser,
+        PluginFeature::PhaseVocoder,
+        PluginFeature::Granular,
+        PluginFeature::FrequencyShifter,
+        PluginFeature::PitchShifter,
+        PluginFeature::Distortion,
+        PluginFeature::TransientShaper,
+        PluginFeature::Compressor,
+        PluginFeature::Expander,
+        PluginFeature::Gate,
+        PluginFeature::Limiter,
+        PluginFeature::Flanger,
+        PluginFeature::Chorus,
+        PluginFeature::Delay,
+        PluginFeature::Reverb,
+        PluginFeature::Tremolo,
+        PluginFeature::Glitch,
+        PluginFeature::Utility,
+        PluginFeature::PitchCorrection,
+        PluginFeature::Restoration,
+        PluginFeature::MultiEffects,
+        PluginFeature::Mixing,
+        PluginFeature::Mastering,
+        PluginFeature::Mono,
+        PluginFeature::Stereo,
+        PluginFeature::Surround,
+        PluginFeature::Ambisonic,
+    ];
+
+    /// Returns the raw C string identifier of this feature (e.g. `"audio-effect"`).
+    pub const fn as_cstr(&self) -> &'static CStr {
+        match self {
+            PluginFeature::Instrument => INSTRUMENT,
+            PluginFeature::AudioEffect => AUDIO_EFFECT,
+            PluginFeature::NoteEffect => NOTE_EFFECT,
+            PluginFeature::NoteDetector => NOTE_DETECTOR,
+            PluginFeature::Analyzer => ANALYZER,
+            PluginFeature::Synthesizer => SYNTHESIZER,
+            PluginFeature::Sampler => SAMPLER,
+            PluginFeature::Drum => DRUM,
+            PluginFeature::DrumMachine => DRUM_MACHINE,
+            PluginFeature::Filter => FILTER,
+            PluginFeature::Phaser => PHASER,
+            PluginFeature::Equalizer => EQUALIZER,
+            PluginFeature::Deesser => DEESSER,
+            PluginFeature::PhaseVocoder => PHASE_VOCODER,
+            PluginFeature::Granular => GRANULAR,
+            PluginFeature::FrequencyShifter => FREQUENCY_SHIFTER,
+            PluginFeature::PitchShifter => PITCH_SHIFTER,
+            PluginFeature::Distortion => DISTORTION,
+            PluginFeature::TransientShaper => TRANSIENT_SHAPER,
+            PluginFeature::Compressor => COMPRESSOR,
+            PluginFeature::Expander => EXPANDER,
+            PluginFeature::Gate => GATE,
+            PluginFeature::Limiter => LIMITER,
+            PluginFeature::Flanger => FLANGER,
+            PluginFeature::Chorus => CHORUS,
+            PluginFeature::Delay => DELAY,
+            PluginFeature::Reverb => REVERB,
+            PluginFeature::Tremolo => TREMOLO,
+            PluginFeature::Glitch => GLITCH,
+            PluginFeature::Utility => UTILITY,
+            PluginFeature::PitchCorrection => PITCH_CORRECTION,
+            PluginFeature::Restoration => RESTORATION,
+            PluginFeature::MultiEffects => MULTI_EFFECTS,
+            PluginFeature::Mixing => MIXING,
+            PluginFeature::Mastering => MASTERING,
+            PluginFeature::Mono => MONO,
+            PluginFeature::Stereo => STEREO,
+            PluginFeature::Surround => SURROUND,
+            PluginFeature::Ambisonic => AMBISONIC,
+        }
+    }
+
+    /// Returns the standard feature matching the given raw C string identifier, or `None` if it
+    /// doesn't match any standard feature.
+    pub fn from_cstr(feature: &CStr) -> Option<Self> {
+        Self::ALL.iter().copied().find(|f| f.as_cstr() == feature)
+    }
+
+    /// Returns `true` if this feature is one of the main plugin categories, i.e.
+    /// [`Instrument`](Self::Instrument), [`AudioEffect`](Self::AudioEffect),
+    /// [`NoteEffect`](Self::NoteEffect), [`NoteDetector`](Self::NoteDetector), or
+    /// [`Analyzer`](Self::Analyzer).
+    pub const fn is_main_category(&self) -> bool {
+        matches!(
+            self,
+            PluginFeature::Instrument
+                | PluginFeature::AudioEffect
+                | PluginFeature::NoteEffect
+                | PluginFeature::NoteDetector
+                | PluginFeature::Analyzer
+        )
+    }
+}
+
+impl AsRef<CStr> for PluginFeature {
+    #[inline]
+    fn as_ref(&self) -> &CStr {
+        self.as_cstr()
+    }
+}
+
+impl Display for PluginFeature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.as_cstr().to_string_lossy())
+    }
+}
+
+/// A potential issue found in a plugin's feature list by [`validate_features`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeatureWarning {
+    /// The feature is neither a standard feature, nor a namespaced non-standard feature (i.e.
+    /// formatted as `"$namespace:$feature"`).
+    ///
+    /// This is often caused by a typo.
+    Unknown(CString),
+    /// The feature was declared more than once.
+    Duplicate(CString),
+    /// None of the main plugin categories (see [`PluginFeature::is_main_category`]) were declared.
+    MissingMainCategory,
+    /// The plugin declares being both an instrument and an audio effect, without being a note
+    /// effect.
+    ///
+    /// Hosts usually can't categorize such plugins consistently.
+    InstrumentAndAudioEffect,
+}
+
+impl Display for FeatureWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FeatureWarning::Unknown(feature) => write!(
+                f,
+                "Unknown plugin feature {feature:?}. Non-standard features should be formatted as \"$namespace:$feature\""
+            ),
+            FeatureWarning::Duplicate(feature) => {
+                write!(f, "Plugin feature {feature:?} is declared more than once")
+            }
+            FeatureWarning::MissingMainCategory => f.write_str(
+                "No main category (instrument, audio-effect, note-effect, note-detector, analyzer) is declared",
+            ),
+            FeatureWarning::InstrumentAndAudioEffect => f.write_str(
+                "Plugin is declared as both an instrument and an audio-effect, but not as a note-effect",
+            ),
+        }
+    }
+}
+
+impl crate::error::Error for FeatureWarning {}
+
+/// Checks the given plugin feature list for unknown features or inconsistent combinations.
+///
+/// An empty list is returned if no issues were found.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::plugin::features::*;
+///
+/// let warnings = validate_features([AUDIO_EFFECT, STEREO]);
+/// assert!(warnings.is_empty());
+///
+/// let typo = std::ffi::CStr::from_bytes_with_nul(b"steero\0").unwrap();
+/// let warnings = validate_features([AUDIO_EFFECT, typo]);
+/// assert_eq!(warnings, [FeatureWarning::Unknown(typo.into())]);
+/// ```
+pub fn validate_features<'a>(features: impl IntoIterator<Item = &'a CStr>) -> Vec<FeatureWarning> {
+    let mut warnings = Vec::new();
+    let mut seen: Vec<&CStr> = Vec::new();
+    let mut known: Vec<PluginFeature> = Vec::new();
+
+    for feature in features {
+        if seen.contains(&feature) {
+            warnings.push(FeatureWarning::Duplicate(feature.into()));
+            continue;
+        }
+        seen.push(feature);
+
+        match PluginFeature::from_cstr(feature) {
+            Some(f) => known.push(f),
+            None if feature.to_bytes().contains(&b':') => {}
+            None => warnings.push(FeatureWarning::Unknown(feature.into())),
+        }
+    }
+
+    if !known.iter().any(PluginFeature::is_main_category) {
+        warnings.push(FeatureWarning::MissingMainCategory);
+    }
+
+    if known.contains(&PluginFeature::Instrument)
+        && known.contains(&PluginFeature::AudioEffect)
+        && !known.contains(&PluginFeature::NoteEffect)
+    {
+        warnings.push(FeatureWarning::InstrumentAndAudioEffect);
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn features_round_trip() {
+        for feature in PluginFeature::ALL {
+            assert_eq!(PluginFeature::from_cstr(feature.as_cstr()), Some(*feature));
+        }
+
+        assert_eq!(
+            PluginFeature::from_cstr(STEREO),
+            Some(PluginFeature::Stereo)
+        );
+        assert_eq!(PluginFeature::AudioEffect.to_string(), "audio-effect");
+    }
+
+    #[test]
+    pub fn valid_features_have_no_warnings() {
+        assert!(validate_features([INSTRUMENT, SYNTHESIZER, STEREO]).is_empty());
+        assert!(validate_features([INSTRUMENT, AUDIO_EFFECT, NOTE_EFFECT]).is_empty());
+
+        let custom = CStr::from_bytes_with_nul(b"my-company:vintage\0").unwrap();
+        assert!(validate_features([AUDIO_EFFECT, custom]).is_empty());
+    }
+
+    #[test]
+    pub fn invalid_features_have_warnings() {
+        let unknown = CStr::from_bytes_with_nul(b"vintage\0").unwrap();
+
+        assert_eq!(
+            validate_features([INSTRUMENT, AUDIO_EFFECT, unknown, STEREO, STEREO]),
+            [
+                FeatureWarning::Unknown(unknown.into()),
+                FeatureWarning::Duplicate(STEREO.into()),
+                FeatureWarning::InstrumentAndAudioEffect
+            ]
+        );
+
+        assert_eq!(
+            validate_features([STEREO]),
+            [FeatureWarning::MissingMainCategory]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use clap_sys::process::*;
-use std::fmt::Debug;
+use core::fmt::Debug;
 
 mod constant_mask;
 pub use constant_mask::*;
@@ -26,13 +26,22 @@ pub enum ProcessStatus {
 }
 
 impl ProcessStatus {
+    /// All the process statuses defined by CLAP, except for `CLAP_PROCESS_ERROR`.
+    pub const ALL: [ProcessStatus; 4] = [
+        ProcessStatus::Continue,
+        ProcessStatus::ContinueIfNotQuiet,
+        ProcessStatus::Tail,
+        ProcessStatus::Sleep,
+    ];
+
     /// Gets a [`ProcessStatus`] from the raw, C-FFI compatible value.
     ///
     /// In order to match Clack's APIs, this returns `Some(Err(()))` if the value is
     /// `CLAP_PROCESS_ERROR`.
     ///
     /// If the given integer does not match any known CLAP Processing status codes, [`None`] is
-    /// returned.
+    /// returned. Note that future CLAP versions may define new status codes: hosts may want to
+    /// handle those permissively, rather than treating them as errors.
     #[inline]
     pub fn from_raw(raw: clap_process_status) -> Option<Result<Self, ()>> {
         use ProcessStatus::*;
@@ -47,6 +56,14 @@ impl ProcessStatus {
         }
     }
 
+    /// Returns the raw, C-FFI compatible value of this status.
+    ///
+    /// This is the inverse of [`from_raw`](Self::from_raw).
+    #[inline]
+    pub const fn as_raw(self) -> clap_process_status {
+        self as clap_process_status
+    }
+
     pub fn combined_with(self, other: ProcessStatus) -> ProcessStatus {
         use ProcessStatus::*;
 
@@ -115,3 +132,36 @@ impl AudioPortProcessingInfo {
         self.constant_mask
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn process_statuses_round_trip() {
+        for status in ProcessStatus::ALL {
+            assert_eq!(ProcessStatus::from_raw(status.as_raw()), Some(Ok(status)));
+        }
+
+        assert_eq!(ProcessStatus::Continue.as_raw(), CLAP_PROCESS_CONTINUE);
+        assert_eq!(
+            ProcessStatus::ContinueIfNotQuiet.as_raw(),
+            CLAP_PROCESS_CONTINUE_IF_NOT_QUIET
+        );
+        assert_eq!(ProcessStatus::Tail.as_raw(), CLAP_PROCESS_TAIL);
+        assert_eq!(ProcessStatus::Sleep.as_raw(), CLAP_PROCESS_SLEEP);
+    }
+
+    #[test]
+    fn errors_and_unknown_statuses_are_not_statuses() {
+        assert_eq!(ProcessStatus::from_raw(CLAP_PROCESS_ERROR), Some(Err(())));
+
+        for status in ProcessStatus::ALL {
+            assert_ne!(status.as_raw(), CLAP_PROCESS_ERROR);
+        }
+
+        for raw in [-1, 5, 42, i32::MIN, i32::MAX] {
+            assert_eq!(ProcessStatus::from_raw(raw), None);
+        }
+    }
+}
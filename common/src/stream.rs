@@ -1,12 +1,16 @@
 //! Stream utilities.
 
-use crate::utils::{slice_from_external_parts, slice_from_external_parts_mut};
+use crate::error::Error;
 use clap_sys::stream::{clap_istream, clap_ostream};
-use std::error::Error;
-use std::ffi::c_void;
-use std::fmt::{Display, Formatter};
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
+
+#[cfg(feature = "std")]
+use crate::utils::{slice_from_external_parts, slice_from_external_parts_mut};
+#[cfg(feature = "std")]
+use core::ffi::c_void;
+#[cfg(feature = "std")]
 use std::io::{ErrorKind, Read, Write};
-use std::marker::PhantomData;
 
 /// An error code that can be raised by CLAP stream methods.
 #[derive(Copy, Clone, Debug)]
@@ -23,7 +27,7 @@ impl StreamError {
 
 impl Display for StreamError {
     #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "CLAP stream error (code: {})", self.code)
     }
 }
@@ -32,12 +36,14 @@ impl Error for StreamError {}
 
 /// A CLAP data stream that can be read from.
 ///
-/// This helper struct is designed to work with the standard [`Read`] trait.
+/// This helper struct is designed to work with the standard [`Read`](std::io::Read) trait, which
+/// requires the `std` feature.
 #[repr(C)]
 pub struct InputStream<'a>(clap_istream, PhantomData<(&'a mut clap_istream, *const ())>);
 
 impl<'a> InputStream<'a> {
     /// Creates a new input stream for an existing [reader](Read) implementation.
+    #[cfg(feature = "std")]
     pub fn from_reader<R: Read + Sized + 'a>(reader: &'a mut R) -> Self {
         Self(
             clap_istream {
@@ -59,12 +65,16 @@ impl<'a> InputStream<'a> {
     }
 
     /// Returns this input stream as a C FFI-compatible pointer.
+    ///
+    /// The returned pointer allows the stream to be written to, and is only valid for as long as
+    /// this stream is not moved, or used otherwise.
     #[inline]
-    pub fn as_raw_mut(&mut self) -> &mut clap_istream {
-        &mut self.0
+    pub fn as_raw_mut(&mut self) -> *mut clap_istream {
+        core::ptr::addr_of_mut!(self.0)
     }
 }
 
+#[cfg(feature = "std")]
 impl Read for InputStream<'_> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let ret = if let Some(read) = self.0.read {
@@ -82,12 +92,14 @@ impl Read for InputStream<'_> {
 
 /// A CLAP data stream that can be written to.
 ///
-/// This helper struct is designed to work with the standard [`Write`] trait.
+/// This helper struct is designed to work with the standard [`Write`](std::io::Write) trait, which
+/// requires the `std` feature.
 #[repr(C)]
 pub struct OutputStream<'a>(clap_ostream, PhantomData<(&'a mut clap_ostream, *const ())>);
 
 impl<'a> OutputStream<'a> {
     /// Creates a new output stream for an existing [write](Write) implementation.
+    #[cfg(feature = "std")]
     pub fn from_writer<W: Write + Sized + 'a>(writer: &'a mut W) -> Self {
         Self(
             clap_ostream {
@@ -109,12 +121,16 @@ impl<'a> OutputStream<'a> {
     }
 
     /// Returns this output stream as a C FFI-compatible pointer.
+    ///
+    /// The returned pointer allows the stream to be written to, and is only valid for as long as
+    /// this stream is not moved, or used otherwise.
     #[inline]
-    pub fn as_raw_mut(&mut self) -> &mut clap_ostream {
-        &mut self.0
+    pub fn as_raw_mut(&mut self) -> *mut clap_ostream {
+        core::ptr::addr_of_mut!(self.0)
     }
 }
 
+#[cfg(feature = "std")]
 impl Write for OutputStream<'_> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         let ret = if let Some(write) = self.0.write {
@@ -136,6 +152,7 @@ impl Write for OutputStream<'_> {
     }
 }
 
+#[cfg(feature = "std")]
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn read<R: Read + Sized>(
     istream: *const clap_istream,
@@ -151,6 +168,7 @@ unsafe extern "C" fn read<R: Read + Sized>(
     }
 }
 
+#[cfg(feature = "std")]
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn write<W: Write + Sized>(
     ostream: *const clap_ostream,
@@ -166,6 +184,7 @@ unsafe extern "C" fn write<W: Write + Sized>(
     }
 }
 
+#[cfg(feature = "std")]
 fn handle_interrupted<F: FnMut() -> std::io::Result<usize>>(
     mut handler: F,
 ) -> std::io::Result<usize> {
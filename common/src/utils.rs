@@ -1,25 +1,36 @@
 //! Various CLAP-related utilities.
 
-#[cfg(not(test))]
+#[cfg(all(feature = "std", not(test)))]
 #[allow(unused)]
 pub(crate) use std::panic::catch_unwind as handle_panic;
 
-#[cfg(test)]
+/// Without the standard library, panics can't be caught: they are expected to abort.
+#[cfg(any(not(feature = "std"), test))]
 #[inline]
 #[allow(unused)]
-pub(crate) fn handle_panic<F: FnOnce() -> R, R>(f: F) -> std::thread::Result<R> {
+pub(crate) fn handle_panic<F: FnOnce() -> R, R>(
+    f: F,
+) -> Result<R, alloc::boxed::Box<dyn core::any::Any + Send>> {
     Ok(f())
 }
 
+pub mod compatibility;
+pub mod fallback_log;
 mod fixed_point;
 mod id;
+mod note;
+mod tempo;
+mod timebase;
 mod version;
 
 pub use fixed_point::*;
 pub use id::ClapId;
+pub use note::*;
+pub use tempo::*;
+pub use timebase::*;
 pub use version::ClapVersion;
 
-use std::ffi::c_void;
+use core::ffi::c_void;
 
 /// An opaque pointer for use in e.g. parameter definitions and parameter-related events.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -81,6 +92,7 @@ pub(crate) unsafe fn slice_from_external_parts<'a, T>(data: *const T, len: usize
 ///
 /// Same as [`core::slice::from_raw_parts_mut`], except the provided pointer *can* be null or
 /// dangling for zero-length slices.
+#[cfg(feature = "std")]
 #[inline]
 pub(crate) unsafe fn slice_from_external_parts_mut<'a, T>(data: *mut T, len: usize) -> &'a mut [T] {
     if len == 0 {
@@ -0,0 +1,345 @@
+//! Configurable strictness of the checks Clack performs on the other side of the CLAP API.
+//!
+//! Both plugins and hosts are known to violate the CLAP specification in small ways, e.g. hosts
+//! querying extensions before initializing the plugin, or plugins returning process statuses that
+//! don't exist. Clack checks for a number of those violations, listed in [`CompatibilityCheck`].
+//! How each of them is handled is decided by a [`CompatibilityPolicy`], which holds a
+//! [`CheckLevel`] for every check:
+//!
+//! * [`Deny`](CheckLevel::Deny) rejects the offending call, and reports it;
+//! * [`Warn`](CheckLevel::Warn) reports it, but works around it where possible;
+//! * [`Allow`](CheckLevel::Allow) silently works around it.
+//!
+//! Two presets are provided: [`CompatibilityPolicy::STRICT`], which denies everything and is
+//! meant for validation tools and CI, and [`CompatibilityPolicy::COMPATIBLE`], which is meant for
+//! shipping hosts and plugins that have to cope with the ecosystem as it is.
+//!
+//! Plugins select their policy through the `Plugin::COMPATIBILITY` constant, and hosts when
+//! creating each plugin instance. Each check is only performed on the side it applies to, which is
+//! documented on every [`CompatibilityCheck`] variant.
+//!
+//! # Diagnostics
+//!
+//! Every violation that isn't allowed is reported as a [`Diagnostic`]. If the policy has a
+//! [diagnostics handler](CompatibilityPolicy::with_diagnostics_handler), all diagnostics are sent
+//! to it. Otherwise, plugins report them to the host's `log` extension, and hosts report them to
+//! their own `log` extension implementation, both falling back to the
+//! [fallback logger](crate::utils::fallback_log).
+//!
+//! # Example
+//!
+//! ```
+//! use clack_common::utils::compatibility::*;
+//!
+//! fn collect(diagnostic: &Diagnostic) {
+//!     eprintln!("{:?}: {diagnostic}", diagnostic.check);
+//! }
+//!
+//! const POLICY: CompatibilityPolicy = CompatibilityPolicy::STRICT
+//!     .with(CompatibilityCheck::EarlyGetExtension, CheckLevel::Warn)
+//!     .with_diagnostics_handler(collect);
+//!
+//! assert_eq!(POLICY.level(CompatibilityCheck::EarlyGetExtension), CheckLevel::Warn);
+//! assert_eq!(POLICY.level(CompatibilityCheck::UnsortedEvents), CheckLevel::Deny);
+//! ```
+
+use clap_sys::ext::log::clap_log_severity;
+use clap_sys::plugin::clap_plugin;
+use core::ffi::CStr;
+use core::fmt::{Display, Formatter};
+
+/// How a [`CompatibilityCheck`] handles the violations it detects.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CheckLevel {
+    /// The offending call is rejected, and the violation is reported.
+    Deny,
+    /// The violation is reported, but worked around where possible.
+    Warn,
+    /// The violation is silently worked around.
+    Allow,
+}
+
+/// A violation of the CLAP specification that Clack can detect.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CompatibilityCheck {
+    /// Plugin side: the plugin pushed output events out of order, or outside the current block.
+    ///
+    /// If denied, the offending events are not forwarded to the host. This check adds a small
+    /// overhead to every event push: it is only performed in debug builds, and not at all if
+    /// allowed.
+    UnsortedEvents,
+    /// Host side: the plugin called a main-thread host function from another thread.
+    ///
+    /// If denied, the call is ignored, and returns a default value to the plugin.
+    WrongThreadCallbacks,
+    /// Plugin side: the host called `process` with a number of frames outside of the range given
+    /// when the plugin was activated.
+    ///
+    /// If denied, the `process` call fails. Otherwise, blocks shorter than the minimum are
+    /// processed normally, but blocks longer than the maximum still fail, as the plugin may only
+    /// have allocated enough memory for the maximum. Empty blocks are never checked. This is
+    /// reported only once per activation.
+    FramesOutOfRange,
+    /// Plugin side: the host provided a different number of audio ports in a `process` call than
+    /// the plugin declared through its audio ports extension.
+    ///
+    /// If denied, the `process` call fails, without calling the plugin's audio processor.
+    /// Otherwise, ports the host provided in excess are exposed, but flagged as undeclared, while
+    /// ports the host didn't provide are simply missing. This is only checked if the plugin
+    /// implements the audio ports extension, and reported only once per activation. Mismatches
+    /// that are worked around are only reported in debug builds.
+    PortCountMismatch,
+    /// Host side: the plugin returned a process status that is unknown to this version of Clack.
+    ///
+    /// If denied, the `process` call returns an error. Otherwise, the status is treated as
+    /// `Continue`. This is reported only once per plugin instance.
+    UnknownProcessStatus,
+    /// Plugin side: the host called the plugin's `get_extension` function before `init`.
+    ///
+    /// If denied, a null pointer is returned. Otherwise, the query is answered without the
+    /// plugin's shared state. Either way, the functions of the returned extensions still reject
+    /// any call made before the plugin is initialized.
+    EarlyGetExtension,
+}
+
+impl CompatibilityCheck {
+    /// All the checks, in declaration order.
+    pub const ALL: [Self; 6] = [
+        Self::UnsortedEvents,
+        Self::WrongThreadCallbacks,
+        Self::FramesOutOfRange,
+        Self::PortCountMismatch,
+        Self::UnknownProcessStatus,
+        Self::EarlyGetExtension,
+    ];
+
+    /// Returns a short, stable identifier for this check, e.g. for filtering diagnostics.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::UnsortedEvents => "unsorted-events",
+            Self::WrongThreadCallbacks => "wrong-thread-callbacks",
+            Self::FramesOutOfRange => "frames-out-of-range",
+            Self::PortCountMismatch => "port-count-mismatch",
+            Self::UnknownProcessStatus => "unknown-process-status",
+            Self::EarlyGetExtension => "early-get-extension",
+        }
+    }
+}
+
+impl Display for CompatibilityCheck {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A violation detected by a [`CompatibilityCheck`], as received by a [`DiagnosticsHandler`].
+#[derive(Copy, Clone)]
+pub struct Diagnostic<'a> {
+    /// The check that detected the violation.
+    pub check: CompatibilityCheck,
+    /// The level of the check. This is never [`Allow`](CheckLevel::Allow).
+    pub level: CheckLevel,
+    /// The raw CLAP severity of the violation, e.g. `CLAP_LOG_HOST_MISBEHAVING`.
+    pub severity: clap_log_severity,
+    /// The plugin instance the violation relates to, or null if unknown.
+    ///
+    /// This is only meant to identify the instance, e.g. by comparing it to the pointer a host
+    /// created it with. It must not be dereferenced.
+    pub instance: *const clap_plugin,
+    /// The ID of the plugin the violation relates to, if known.
+    pub plugin_id: Option<&'a CStr>,
+    /// The name of the CLAP function the violation was detected in (e.g. `clap_plugin.process`),
+    /// or an empty string if unknown.
+    pub callback: &'static str,
+    /// A description of the violation.
+    pub message: &'a dyn Display,
+}
+
+impl Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let action = match self.level {
+            CheckLevel::Deny => "denied",
+            CheckLevel::Warn => "worked around",
+            CheckLevel::Allow => "allowed",
+        };
+
+        write!(f, "{} ({}, {action})", self.message, self.check)
+    }
+}
+
+/// A function that receives all the [`Diagnostic`]s reported under a [`CompatibilityPolicy`].
+///
+/// This may be called from any thread, including the audio thread.
+pub type DiagnosticsHandler = fn(&Diagnostic);
+
+/// How each [`CompatibilityCheck`] handles the violations it detects, and where they are
+/// reported.
+///
+/// See the [module docs](self) for more information.
+#[derive(Copy, Clone, Debug)]
+pub struct CompatibilityPolicy {
+    unsorted_events: CheckLevel,
+    wrong_thread_callbacks: CheckLevel,
+    frames_out_of_range: CheckLevel,
+    port_count_mismatch: CheckLevel,
+    unknown_process_status: CheckLevel,
+    early_get_extension: CheckLevel,
+    handler: Option<DiagnosticsHandler>,
+}
+
+impl CompatibilityPolicy {
+    /// A policy which denies every violation.
+    ///
+    /// This is meant for validation hosts and CI, where violations should be caught as early as
+    /// possible.
+    pub const STRICT: Self = Self::all(CheckLevel::Deny);
+
+    /// A policy which works around every violation it can, and reports those that are likely to
+    /// cause audible or functional issues.
+    ///
+    /// This is meant for shipping hosts and plugins. Output event ordering isn't checked, as the
+    /// check has a cost on every event push.
+    ///
+    /// Some violations are still denied, as there is no safe way to work around them:
+    ///
+    /// * Extension queries made before `init`: answering them without the plugin's shared state
+    ///   may expose a different set of extensions than the plugin does once initialized;
+    /// * Blocks with a number of frames outside of the activation range;
+    /// * Unknown process statuses, which are returned as
+    ///   `PluginInstanceError::InvalidProcessStatus` errors: treating them as `Continue` would
+    ///   hide whatever the plugin meant to signal.
+    pub const COMPATIBLE: Self = Self::all(CheckLevel::Warn)
+        .with(CompatibilityCheck::UnsortedEvents, CheckLevel::Allow)
+        .with(CompatibilityCheck::FramesOutOfRange, CheckLevel::Deny)
+        .with(CompatibilityCheck::UnknownProcessStatus, CheckLevel::Deny)
+        .with(CompatibilityCheck::EarlyGetExtension, CheckLevel::Deny);
+
+    /// Returns a policy with the given level for every check, and no diagnostics handler.
+    #[inline]
+    pub const fn all(level: CheckLevel) -> Self {
+        Self {
+            unsorted_events: level,
+            wrong_thread_callbacks: level,
+            frames_out_of_range: level,
+            port_count_mismatch: level,
+            unknown_process_status: level,
+            early_get_extension: level,
+            handler: None,
+        }
+    }
+
+    /// Returns this policy, with the given level for the given check.
+    #[inline]
+    pub const fn with(mut self, check: CompatibilityCheck, level: CheckLevel) -> Self {
+        match check {
+            CompatibilityCheck::UnsortedEvents => self.unsorted_events = level,
+            CompatibilityCheck::WrongThreadCallbacks => self.wrong_thread_callbacks = level,
+            CompatibilityCheck::FramesOutOfRange => self.frames_out_of_range = level,
+            CompatibilityCheck::PortCountMismatch => self.port_count_mismatch = level,
+            CompatibilityCheck::UnknownProcessStatus => self.unknown_process_status = level,
+            CompatibilityCheck::EarlyGetExtension => self.early_get_extension = level,
+        }
+
+        self
+    }
+
+    /// Returns this policy, sending all diagnostics to the given handler instead of the `log`
+    /// extension.
+    #[inline]
+    pub const fn with_diagnostics_handler(mut self, handler: DiagnosticsHandler) -> Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// Returns the level of the given check.
+    #[inline]
+    pub const fn level(&self, check: CompatibilityCheck) -> CheckLevel {
+        match check {
+            CompatibilityCheck::UnsortedEvents => self.unsorted_events,
+            CompatibilityCheck::WrongThreadCallbacks => self.wrong_thread_callbacks,
+            CompatibilityCheck::FramesOutOfRange => self.frames_out_of_range,
+            CompatibilityCheck::PortCountMismatch => self.port_count_mismatch,
+            CompatibilityCheck::UnknownProcessStatus => self.unknown_process_status,
+            CompatibilityCheck::EarlyGetExtension => self.early_get_extension,
+        }
+    }
+
+    /// Returns the diagnostics handler of this policy, if any.
+    #[inline]
+    pub const fn diagnostics_handler(&self) -> Option<DiagnosticsHandler> {
+        self.handler
+    }
+
+    /// Sends the given diagnostic to this policy's diagnostics handler, or to the given default
+    /// reporter if it has none.
+    ///
+    /// This is meant to be used by Clack's plugin and host implementations.
+    #[inline]
+    pub fn report(&self, diagnostic: &Diagnostic, default: impl FnOnce(&Diagnostic)) {
+        match self.handler {
+            Some(handler) => handler(diagnostic),
+            None => default(diagnostic),
+        }
+    }
+}
+
+impl Default for CompatibilityPolicy {
+    /// Returns the [`COMPATIBLE`](Self::COMPATIBLE) policy.
+    #[inline]
+    fn default() -> Self {
+        Self::COMPATIBLE
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn presets_have_the_expected_levels() {
+        for check in CompatibilityCheck::ALL {
+            assert_eq!(CompatibilityPolicy::STRICT.level(check), CheckLevel::Deny);
+        }
+
+        let compatible = CompatibilityPolicy::default();
+        assert_eq!(
+            compatible.level(CompatibilityCheck::UnknownProcessStatus),
+            CheckLevel::Deny
+        );
+        assert_eq!(
+            compatible.level(CompatibilityCheck::FramesOutOfRange),
+            CheckLevel::Deny
+        );
+        assert_eq!(
+            compatible.level(CompatibilityCheck::PortCountMismatch),
+            CheckLevel::Warn
+        );
+        assert_eq!(
+            compatible.level(CompatibilityCheck::UnsortedEvents),
+            CheckLevel::Allow
+        );
+        assert_eq!(
+            compatible.level(CompatibilityCheck::EarlyGetExtension),
+            CheckLevel::Deny
+        );
+        assert!(compatible.diagnostics_handler().is_none());
+    }
+
+    #[test]
+    fn levels_are_set_per_check() {
+        let policy = CompatibilityPolicy::all(CheckLevel::Allow)
+            .with(CompatibilityCheck::FramesOutOfRange, CheckLevel::Deny);
+
+        for check in CompatibilityCheck::ALL {
+            let expected = if check == CompatibilityCheck::FramesOutOfRange {
+                CheckLevel::Deny
+            } else {
+                CheckLevel::Allow
+            };
+
+            assert_eq!(policy.level(check), expected);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! A fallback for errors that could not be reported through the `log` extension.
+//!
+//! When a plugin or host callback fails, Clack tries to report the error through the other side's
+//! `log` extension. If it is unavailable (or if the message cannot be sent through it), the error
+//! is instead reported as a [`LogRecord`] to the fallback logger, which writes it to the standard
+//! error output by default.
+//!
+//! Hosts and plugins can redirect those records elsewhere using [`set_fallback_logger`].
+//!
+//! The fallback logger is a static of this crate: there is one per compiled copy of Clack, i.e.
+//! per binary or dynamic library linking it. A host and the plugins it loads from separate
+//! bundles therefore each have their own fallback logger, and rate limiter.
+//!
+//! Because a misbehaving plugin or host may fail on every single audio block, the fallback logger
+//! is rate-limited: at most [`MAX_RECORDS_PER_WINDOW`] records are emitted every
+//! [`RATE_LIMIT_WINDOW`], and the number of records that were suppressed in between is reported
+//! once the next window starts.
+//!
+//! Without the `std` feature, there is neither a standard error output nor a clock to rate-limit
+//! with: records are discarded unless a fallback logger is set, which then receives all of them.
+
+use clap_sys::ext::log::clap_log_severity;
+use core::ffi::CStr;
+use core::fmt::{Display, Formatter};
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicPtr, Ordering};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{
+    sync::{Mutex, RwLock},
+    time::Instant,
+};
+
+/// The maximum number of records the fallback logger emits during a single [`RATE_LIMIT_WINDOW`].
+pub const MAX_RECORDS_PER_WINDOW: u32 = 20;
+
+/// The duration of a rate-limiting window of the fallback logger.
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+/// The side of the CLAP API a [`LogRecord`] originates from.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LogOrigin {
+    /// The record was produced by a plugin instance.
+    Plugin,
+    /// The record was produced by a plugin factory.
+    PluginFactory,
+    /// The record was produced by a host.
+    Host,
+    /// The record was produced while reading an event list provided by the other side of the
+    /// CLAP API.
+    Events,
+}
+
+impl LogOrigin {
+    /// Returns the prefix used when writing records from this origin to the standard error output.
+    #[inline]
+    pub const fn prefix(&self) -> &'static str {
+        match self {
+            LogOrigin::Plugin => "CLAP_PLUGIN_ERROR",
+            LogOrigin::PluginFactory => "CLAP_PLUGIN_FACTORY_ERROR",
+            LogOrigin::Host => "CLAP_HOST_ERROR",
+            LogOrigin::Events => "CLAP_EVENTS_ERROR",
+        }
+    }
+}
+
+/// A message that could not be reported through the `log` extension.
+///
+/// This is what the fallback logger receives. See the [module docs](self) for more information.
+#[derive(Copy, Clone)]
+pub struct LogRecord<'a> {
+    /// Which side of the CLAP API produced this record.
+    pub origin: LogOrigin,
+    /// The raw CLAP severity of this record.
+    pub severity: clap_log_severity,
+    /// The ID of the plugin this record relates to, if known.
+    pub plugin_id: Option<&'a CStr>,
+    /// The name of the CLAP callback that produced this record (e.g. `clap_plugin.activate`), or
+    /// an empty string if unknown.
+    pub callback: &'static str,
+    /// The message itself.
+    pub message: &'a dyn Display,
+}
+
+impl Display for LogRecord<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if let Some(plugin_id) = self.plugin_id {
+            write!(f, "[{}] ", plugin_id.to_string_lossy())?;
+        }
+
+        if !self.callback.is_empty() {
+            write!(f, "[{}] ", self.callback)?;
+        }
+
+        Display::fmt(self.message, f)
+    }
+}
+
+/// A function that receives all [`LogRecord`]s the fallback logger emits.
+pub type FallbackLogger = fn(&LogRecord);
+
+#[cfg(feature = "std")]
+static FALLBACK_LOGGER: RwLock<Option<FallbackLogger>> = RwLock::new(None);
+#[cfg(feature = "std")]
+static RATE_LIMITER: Mutex<RateLimiter> = Mutex::new(RateLimiter::new());
+
+#[cfg(not(feature = "std"))]
+static FALLBACK_LOGGER: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Sets the fallback logger, replacing the default one which writes to the standard error output.
+///
+/// Passing `None` restores the default logger.
+///
+/// Note this applies to every Clack plugin and host in the current binary or dynamic library,
+/// but not to those loaded from other ones, e.g. plugin bundles loaded by a host.
+pub fn set_fallback_logger(logger: Option<FallbackLogger>) {
+    #[cfg(feature = "std")]
+    {
+        *FALLBACK_LOGGER.write().unwrap_or_else(|e| e.into_inner()) = logger;
+    }
+
+    #[cfg(not(feature = "std"))]
+    FALLBACK_LOGGER.store(
+        logger.map_or(core::ptr::null_mut(), |l| l as *mut ()),
+        Ordering::Release,
+    );
+}
+
+#[cfg(feature = "std")]
+fn current_logger() -> Option<FallbackLogger> {
+    *FALLBACK_LOGGER.read().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(not(feature = "std"))]
+fn current_logger() -> Option<FallbackLogger> {
+    let logger = FALLBACK_LOGGER.load(Ordering::Acquire);
+
+    if logger.is_null() {
+        return None;
+    }
+
+    // SAFETY: non-null pointers are only ever stored from a FallbackLogger in set_fallback_logger.
+    Some(unsafe { core::mem::transmute::<*mut (), FallbackLogger>(logger) })
+}
+
+#[cfg(feature = "std")]
+fn admit() -> Admission {
+    RATE_LIMITER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .admit(Instant::now())
+}
+
+/// Emits the given record to the current fallback logger, subject to rate limiting.
+///
+/// This is used by Clack plugins and hosts when the `log` extension is unavailable, but it can
+/// also be used by custom extension implementations.
+pub fn fallback_log(record: &LogRecord) {
+    #[cfg(feature = "std")]
+    let suppressed = match admit() {
+        Admission::Suppressed => return,
+        Admission::Allowed { suppressed } => suppressed,
+    };
+    #[cfg(not(feature = "std"))]
+    let suppressed = 0;
+
+    let logger = current_logger().unwrap_or(default_logger);
+
+    if suppressed > 0 {
+        logger(&LogRecord {
+            origin: record.origin,
+            severity: record.severity,
+            plugin_id: None,
+            callback: "",
+            message: &format_args!("{suppressed} messages were suppressed by rate limiting"),
+        });
+    }
+
+    logger(record)
+}
+
+#[cfg(feature = "std")]
+fn default_logger(record: &LogRecord) {
+    std::eprintln!("[{}] {record}", record.origin.prefix())
+}
+
+#[cfg(not(feature = "std"))]
+fn default_logger(_record: &LogRecord) {}
+
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Admission {
+    Allowed { suppressed: u32 },
+    Suppressed,
+}
+
+#[cfg(feature = "std")]
+struct RateLimiter {
+    window_start: Option<Instant>,
+    count: u32,
+    suppressed: u32,
+}
+
+#[cfg(feature = "std")]
+impl RateLimiter {
+    const fn new() -> Self {
+        Self {
+            window_start: None,
+            count: 0,
+            suppressed: 0,
+        }
+    }
+
+    fn admit(&mut self, now: Instant) -> Admission {
+        let window_expired = match self.window_start {
+            None => true,
+            Some(start) => now.saturating_duration_since(start) >= RATE_LIMIT_WINDOW,
+        };
+
+        if window_expired {
+            let suppressed = self.suppressed;
+            self.window_start = Some(now);
+            self.count = 1;
+            self.suppressed = 0;
+
+            return Admission::Allowed { suppressed };
+        }
+
+        if self.count < MAX_RECORDS_PER_WINDOW {
+            self.count += 1;
+            Admission::Allowed { suppressed: 0 }
+        } else {
+            self.suppressed = self.suppressed.saturating_add(1);
+            Admission::Suppressed
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap_sys::ext::log::CLAP_LOG_ERROR;
+
+    #[test]
+    fn rate_limiter_suppresses_floods() {
+        let mut limiter = RateLimiter::new();
+        let start = Instant::now();
+
+        for _ in 0..MAX_RECORDS_PER_WINDOW {
+            assert_eq!(limiter.admit(start), Admission::Allowed { suppressed: 0 });
+        }
+
+        for _ in 0..5 {
+            assert_eq!(limiter.admit(start), Admission::Suppressed);
+        }
+
+        assert_eq!(
+            limiter.admit(start + RATE_LIMIT_WINDOW),
+            Admission::Allowed { suppressed: 5 }
+        );
+        assert_eq!(
+            limiter.admit(start + RATE_LIMIT_WINDOW),
+            Admission::Allowed { suppressed: 0 }
+        );
+    }
+
+    #[test]
+    fn record_display_includes_context() {
+        let message = "Something went wrong";
+        let record = LogRecord {
+            origin: LogOrigin::Plugin,
+            severity: CLAP_LOG_ERROR,
+            plugin_id: Some(CStr::from_bytes_with_nul(b"com.example.plugin\0").unwrap()),
+            callback: "clap_plugin.activate",
+            message: &message,
+        };
+
+        assert_eq!(
+            record.to_string(),
+            "[com.example.plugin] [clap_plugin.activate] Something went wrong"
+        );
+
+        let record = LogRecord {
+            plugin_id: None,
+            callback: "",
+            ..record
+        };
+
+        assert_eq!(record.to_string(), "Something went wrong");
+    }
+}
@@ -1,4 +1,4 @@
-use std::ops::Add;
+use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
 pub type BeatTime = FixedPoint;
 pub type SecondsTime = FixedPoint;
@@ -39,6 +39,101 @@ impl FixedPoint {
     pub fn from_float(val: f64) -> Self {
         Self((Self::FACTOR as f64 * val).round() as i64)
     }
+
+    /// Returns the value closest to `numerator / denominator`.
+    ///
+    /// This is computed exactly using integer math, e.g. `FixedPoint::from_ratio(512, 48_000)`
+    /// is the duration of 512 samples at 48kHz, in seconds. Computing positions this way from an
+    /// absolute sample count, rather than adding up floating-point durations, avoids any error
+    /// accumulating over long sessions.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `denominator` is `0`.
+    #[inline]
+    pub const fn from_ratio(numerator: i64, denominator: i64) -> Self {
+        Self::from_int(1).mul_ratio(numerator, denominator)
+    }
+
+    /// Returns this value multiplied by `numerator / denominator`, rounded to the closest value.
+    ///
+    /// The intermediate product is computed on 128 bits, so this only loses precision in the
+    /// final rounding. Results that do not fit are saturated.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `denominator` is `0`.
+    #[inline]
+    pub const fn mul_ratio(&self, numerator: i64, denominator: i64) -> Self {
+        let mut product = self.0 as i128 * numerator as i128;
+        let mut denominator = denominator as i128;
+
+        if denominator < 0 {
+            product = -product;
+            denominator = -denominator;
+        }
+
+        // Round half away from zero. Integer division truncates towards zero.
+        let half = denominator / 2;
+        let result = if product >= 0 {
+            (product + half) / denominator
+        } else {
+            (product - half) / denominator
+        };
+
+        Self(saturate(result))
+    }
+
+    /// Returns this value multiplied by an integer, or `None` if it overflows.
+    #[inline]
+    pub const fn checked_mul_int(&self, rhs: i64) -> Option<Self> {
+        match self.0.checked_mul(rhs) {
+            Some(bits) => Some(Self(bits)),
+            None => None,
+        }
+    }
+
+    /// Returns the sum of the two values, or `None` if it overflows.
+    #[inline]
+    pub const fn checked_add(&self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(bits) => Some(Self(bits)),
+            None => None,
+        }
+    }
+
+    /// Returns the difference of the two values, or `None` if it overflows.
+    #[inline]
+    pub const fn checked_sub(&self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(bits) => Some(Self(bits)),
+            None => None,
+        }
+    }
+
+    /// Returns the largest integer value less than or equal to this value.
+    #[inline]
+    pub const fn floor(&self) -> Self {
+        Self(self.0.div_euclid(Self::FACTOR) * Self::FACTOR)
+    }
+
+    /// Returns the fractional part of this value, which is always positive (e.g. `0.75` for
+    /// `-1.25`), such that `x.floor() + x.fract() == x`.
+    #[inline]
+    pub const fn fract(&self) -> Self {
+        Self(self.0.rem_euclid(Self::FACTOR))
+    }
+}
+
+#[inline]
+const fn saturate(value: i128) -> i64 {
+    if value > i64::MAX as i128 {
+        i64::MAX
+    } else if value < i64::MIN as i128 {
+        i64::MIN
+    } else {
+        value as i64
+    }
 }
 
 impl From<f64> for FixedPoint {
@@ -63,3 +158,35 @@ impl Add for FixedPoint {
         Self::from_bits(self.0 + rhs.0)
     }
 }
+
+impl AddAssign for FixedPoint {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0
+    }
+}
+
+impl Sub for FixedPoint {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::from_bits(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for FixedPoint {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.0 -= rhs.0
+    }
+}
+
+impl Neg for FixedPoint {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self::from_bits(-self.0)
+    }
+}
@@ -1,6 +1,6 @@
+use core::cmp::Ordering;
+use core::fmt::{Debug, Display, Formatter};
 use core::num::NonZeroU32;
-use std::cmp::Ordering;
-use std::fmt::{Debug, Display, Formatter};
 
 /// A standardized CLAP identifier.
 ///
@@ -73,14 +73,14 @@ impl ClapId {
 
 impl Debug for ClapId {
     #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_tuple("ClapId").field(&self.get()).finish()
     }
 }
 
 impl Display for ClapId {
     #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         Display::fmt(&self.get(), f)
     }
 }
@@ -146,3 +146,21 @@ impl PartialEq<ClapId> for Option<ClapId> {
         }
     }
 }
+
+/// Serializes as the raw numeric value of the ID.
+#[cfg(feature = "serde")]
+impl serde::Serialize for ClapId {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.get())
+    }
+}
+
+/// Deserializes from the raw numeric value of the ID, which must not be [`u32::MAX`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ClapId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = <u32 as serde::Deserialize>::deserialize(deserializer)?;
+        Self::from_raw(raw).ok_or_else(|| serde::de::Error::custom("invalid CLAP ID"))
+    }
+}
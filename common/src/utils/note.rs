@@ -0,0 +1,405 @@
+use crate::error::Error;
+use core::fmt::{Debug, Display, Formatter};
+
+/// The highest valid key number, as used by CLAP note events.
+pub const MAX_KEY: u16 = 127;
+
+/// The key number of the A4 note (in scientific pitch notation), i.e. the note tuned to the
+/// reference pitch.
+pub const A4_KEY: u16 = 69;
+
+/// The default reference pitch of the A4 note, in Hertz.
+pub const DEFAULT_A4_FREQUENCY: f64 = 440.0;
+
+const SHARP_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+const FLAT_NAMES: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+];
+
+/// A convention for naming CLAP keys, e.g. `C#4`.
+///
+/// Conventions differ in which octave number is given to middle C (key `60`): scientific pitch
+/// notation (the default) names it `C4`, while some manufacturers name it `C3` or `C5`.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::NoteNaming;
+///
+/// assert_eq!(NoteNaming::SCIENTIFIC.name(61).unwrap().to_string(), "C#4");
+/// assert_eq!(NoteNaming::new(3).with_flats(true).name(61).unwrap().to_string(), "Db3");
+///
+/// assert_eq!(NoteNaming::SCIENTIFIC.parse("c#-1"), Ok(1));
+/// assert_eq!(NoteNaming::SCIENTIFIC.parse("Db5"), Ok(73));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NoteNaming {
+    middle_c_octave: i8,
+    use_flats: bool,
+}
+
+impl NoteNaming {
+    /// Scientific pitch notation, where middle C (key `60`) is `C4`.
+    pub const SCIENTIFIC: Self = Self::new(4);
+
+    /// Creates a new naming convention, where middle C (key `60`) is in the given octave.
+    ///
+    /// Accidentals are named using sharps by default.
+    #[inline]
+    pub const fn new(middle_c_octave: i8) -> Self {
+        Self {
+            middle_c_octave,
+            use_flats: false,
+        }
+    }
+
+    /// Returns this convention, naming accidentals using flats (e.g. `Db`) instead of sharps
+    /// (e.g. `C#`) if `use_flats` is `true`.
+    #[inline]
+    pub const fn with_flats(mut self, use_flats: bool) -> Self {
+        self.use_flats = use_flats;
+        self
+    }
+
+    /// Returns the octave number given to middle C (key `60`) by this convention.
+    #[inline]
+    pub const fn middle_c_octave(&self) -> i8 {
+        self.middle_c_octave
+    }
+
+    /// Returns `true` if accidentals are named using flats rather than sharps.
+    #[inline]
+    pub const fn uses_flats(&self) -> bool {
+        self.use_flats
+    }
+
+    /// Returns the name of the given key, or `None` if it is greater than [`MAX_KEY`].
+    ///
+    /// The returned [`KeyName`] implements [`Display`], and doesn't allocate.
+    #[inline]
+    pub const fn name(&self, key: u16) -> Option<KeyName> {
+        if key > MAX_KEY {
+            return None;
+        }
+
+        Some(KeyName { key, naming: *self })
+    }
+
+    /// Returns the octave number of the given key in this convention.
+    #[inline]
+    pub const fn octave(&self, key: u16) -> i16 {
+        (key / 12) as i16 - 5 + self.middle_c_octave as i16
+    }
+
+    /// Parses a note name into a key number.
+    ///
+    /// Note names are made of a case-insensitive note letter (`A` to `G`), any number of sharp
+    /// (`#`) or flat (`b`) accidentals, and an octave number, which can be negative. Surrounding
+    /// whitespace is ignored.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`NoteNameParseError`] if the name is malformed, or if it designates a key
+    /// outside of the `0..=127` range.
+    pub fn parse(&self, name: &str) -> Result<u16, NoteNameParseError> {
+        let name = name.trim();
+        let mut chars = name.chars();
+
+        let pitch_class: i32 = match chars.next() {
+            None => return Err(NoteNameParseError::Empty),
+            Some(c) => match c.to_ascii_uppercase() {
+                'C' => 0,
+                'D' => 2,
+                'E' => 4,
+                'F' => 5,
+                'G' => 7,
+                'A' => 9,
+                'B' => 11,
+                _ => return Err(NoteNameParseError::InvalidLetter(c)),
+            },
+        };
+
+        let rest = chars.as_str();
+        let octave_start = rest
+            .find(|c: char| c != '#' && c != 'b')
+            .unwrap_or(rest.len());
+        let (accidentals, octave) = rest.split_at(octave_start);
+
+        let offset: i32 = accidentals
+            .chars()
+            .map(|c| if c == '#' { 1 } else { -1 })
+            .sum();
+
+        if octave.is_empty() {
+            return Err(NoteNameParseError::MissingOctave);
+        }
+
+        let octave: i32 = octave
+            .parse()
+            .map_err(|_| NoteNameParseError::InvalidOctave)?;
+
+        let key = octave
+            .checked_sub(self.middle_c_octave as i32)
+            .and_then(|o| o.checked_add(5))
+            .and_then(|o| o.checked_mul(12))
+            .and_then(|k| k.checked_add(pitch_class + offset))
+            .ok_or(NoteNameParseError::OutOfRange)?;
+
+        if !(0..=MAX_KEY as i32).contains(&key) {
+            return Err(NoteNameParseError::OutOfRange);
+        }
+
+        Ok(key as u16)
+    }
+}
+
+impl Default for NoteNaming {
+    #[inline]
+    fn default() -> Self {
+        Self::SCIENTIFIC
+    }
+}
+
+/// The displayable name of a key, in a given [`NoteNaming`] convention.
+///
+/// This is returned by [`NoteNaming::name`], or [`key_name`] for scientific pitch notation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct KeyName {
+    key: u16,
+    naming: NoteNaming,
+}
+
+impl KeyName {
+    /// Returns the key number being named.
+    #[inline]
+    pub const fn key(&self) -> u16 {
+        self.key
+    }
+
+    /// Returns the octave number of the key.
+    #[inline]
+    pub const fn octave(&self) -> i16 {
+        self.naming.octave(self.key)
+    }
+
+    /// Returns the name of the key's pitch class, without its octave (e.g. `C#`).
+    #[inline]
+    pub const fn pitch_class(&self) -> &'static str {
+        let names = if self.naming.use_flats {
+            &FLAT_NAMES
+        } else {
+            &SHARP_NAMES
+        };
+
+        names[(self.key % 12) as usize]
+    }
+}
+
+impl Display for KeyName {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", self.pitch_class(), self.octave())
+    }
+}
+
+/// Returns the name of the given key in scientific pitch notation (e.g. `C4` for key `60`), or
+/// `None` if it is greater than [`MAX_KEY`].
+///
+/// See [`NoteNaming`] for other naming conventions.
+#[inline]
+pub const fn key_name(key: u16) -> Option<KeyName> {
+    NoteNaming::SCIENTIFIC.name(key)
+}
+
+/// Debug-formats a raw CLAP key number along with its name, e.g. `60 (C4)`.
+pub(crate) struct KeyDebug(pub i16);
+
+impl Debug for KeyDebug {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match u16::try_from(self.0).ok().and_then(key_name) {
+            Some(name) => write!(f, "{} ({name})", self.0),
+            None => Debug::fmt(&self.0, f),
+        }
+    }
+}
+
+/// Errors that can occur while parsing a note name. See [`NoteNaming::parse`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NoteNameParseError {
+    /// The note name is empty.
+    Empty,
+    /// The note name doesn't start with a note letter (`A` to `G`).
+    InvalidLetter(char),
+    /// The note name has no octave number.
+    MissingOctave,
+    /// The octave number is not a valid integer.
+    InvalidOctave,
+    /// The note name designates a key outside of the `0..=127` range.
+    OutOfRange,
+}
+
+impl Display for NoteNameParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("Note name is empty"),
+            Self::InvalidLetter(c) => write!(f, "Invalid note letter '{c}'"),
+            Self::MissingOctave => f.write_str("Note name is missing an octave number"),
+            Self::InvalidOctave => f.write_str("Invalid octave number in note name"),
+            Self::OutOfRange => f.write_str("Note is outside of the valid key range (0-127)"),
+        }
+    }
+}
+
+impl Error for NoteNameParseError {}
+
+/// A source of microtuning information, such as the host's `tuning` extension.
+///
+/// Tunings are expressed relative to 12-tone equal temperament, in semitones.
+pub trait TuningSource {
+    /// Returns the tuning of the given key on the given channel, relative to equal temperament,
+    /// in semitones.
+    fn relative_tuning(&self, channel: u16, key: u16) -> f64;
+}
+
+/// Returns the frequency of the given key on the given channel, in Hertz.
+///
+/// The key is tuned to 12-tone equal temperament, with A4 (key `69`) tuned to `a4_frequency`
+/// (usually [`DEFAULT_A4_FREQUENCY`]). If a `tuning` is given, its relative tuning for this key
+/// and channel is applied on top.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::{key_to_frequency, DEFAULT_A4_FREQUENCY};
+///
+/// assert_eq!(key_to_frequency(69, 0, DEFAULT_A4_FREQUENCY, None), 440.0);
+/// assert_eq!(key_to_frequency(81, 0, DEFAULT_A4_FREQUENCY, None), 880.0);
+/// ```
+#[inline]
+pub fn key_to_frequency(
+    key: u16,
+    channel: u16,
+    a4_frequency: f64,
+    tuning: Option<&dyn TuningSource>,
+) -> f64 {
+    let offset = tuning.map_or(0.0, |t| t.relative_tuning(channel, key));
+    let semitones = key as f64 - A4_KEY as f64 + offset;
+
+    a4_frequency * (semitones / 12.0).exp2()
+}
+
+/// Returns the (fractional) key number matching the given frequency in Hertz, in 12-tone equal
+/// temperament with A4 (key `69`) tuned to `a4_frequency`.
+///
+/// This is the inverse of [`key_to_frequency`] without a tuning. The result may be outside of
+/// the valid key range, and is not rounded.
+#[inline]
+pub fn frequency_to_key(frequency: f64, a4_frequency: f64) -> f64 {
+    A4_KEY as f64 + 12.0 * (frequency / a4_frequency).log2()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_round_trip_across_key_range() {
+        for naming in [
+            NoteNaming::SCIENTIFIC,
+            NoteNaming::new(3),
+            NoteNaming::new(5).with_flats(true),
+        ] {
+            for key in 0..=MAX_KEY {
+                let name = naming.name(key).unwrap().to_string();
+                assert_eq!(naming.parse(&name), Ok(key), "{name}");
+            }
+
+            assert_eq!(naming.name(MAX_KEY + 1), None);
+        }
+    }
+
+    #[test]
+    fn names_follow_conventions() {
+        assert_eq!(key_name(0).unwrap().to_string(), "C-1");
+        assert_eq!(key_name(60).unwrap().to_string(), "C4");
+        assert_eq!(key_name(127).unwrap().to_string(), "G9");
+        assert_eq!(NoteNaming::new(3).name(60).unwrap().to_string(), "C3");
+        assert_eq!(NoteNaming::new(3).name(0).unwrap().to_string(), "C-2");
+    }
+
+    #[test]
+    fn parses_note_names() {
+        let naming = NoteNaming::SCIENTIFIC;
+
+        assert_eq!(naming.parse("c#-1"), Ok(1));
+        assert_eq!(naming.parse("Db5"), Ok(73));
+        assert_eq!(naming.parse(" A4 "), Ok(69));
+        assert_eq!(naming.parse("B#3"), Ok(60));
+        assert_eq!(naming.parse("Cb4"), Ok(59));
+        assert_eq!(naming.parse("bb4"), Ok(70));
+        assert_eq!(naming.parse("G9"), Ok(127));
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let naming = NoteNaming::SCIENTIFIC;
+
+        assert_eq!(naming.parse(""), Err(NoteNameParseError::Empty));
+        assert_eq!(
+            naming.parse("H4"),
+            Err(NoteNameParseError::InvalidLetter('H'))
+        );
+        assert_eq!(naming.parse("C#"), Err(NoteNameParseError::MissingOctave));
+        assert_eq!(naming.parse("Cx4"), Err(NoteNameParseError::InvalidOctave));
+        assert_eq!(naming.parse("G#9"), Err(NoteNameParseError::OutOfRange));
+        assert_eq!(naming.parse("Cb-1"), Err(NoteNameParseError::OutOfRange));
+        assert_eq!(
+            naming.parse("C99999999999"),
+            Err(NoteNameParseError::InvalidOctave)
+        );
+    }
+
+    #[test]
+    fn note_events_debug_key_names() {
+        use crate::events::event_types::NoteOnEvent;
+        use crate::events::Pckn;
+
+        let event = NoteOnEvent::new(0, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0);
+        assert!(format!("{event:?}").contains("key: 60 (C4)"));
+
+        let event = NoteOnEvent::new(0, Pckn::match_all(), 1.0);
+        assert!(format!("{event:?}").contains("key: -1,"));
+    }
+
+    struct QuarterToneUp;
+
+    impl TuningSource for QuarterToneUp {
+        fn relative_tuning(&self, channel: u16, _key: u16) -> f64 {
+            if channel == 1 {
+                0.5
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn converts_frequencies() {
+        assert_eq!(key_to_frequency(69, 0, 440.0, None), 440.0);
+        assert_eq!(key_to_frequency(57, 0, 440.0, None), 220.0);
+        assert_eq!(key_to_frequency(69, 0, 432.0, None), 432.0);
+        assert!((key_to_frequency(60, 0, 440.0, None) - 261.625_565).abs() < 1e-5);
+
+        let tuning = QuarterToneUp;
+        assert_eq!(key_to_frequency(69, 0, 440.0, Some(&tuning)), 440.0);
+        let tuned = key_to_frequency(69, 1, 440.0, Some(&tuning));
+        assert!((frequency_to_key(tuned, 440.0) - 69.5).abs() < 1e-9);
+
+        for key in 0..=MAX_KEY {
+            let frequency = key_to_frequency(key, 0, 440.0, None);
+            assert!((frequency_to_key(frequency, 440.0) - key as f64).abs() < 1e-9);
+        }
+    }
+}
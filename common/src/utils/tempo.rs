@@ -0,0 +1,290 @@
+use crate::events::event_types::TransportEvent;
+
+/// How the tempo evolves between two transport events, as interpreted by a [`TempoIntegrator`].
+///
+/// CLAP transports carry a tempo and a per-sample tempo increment, which leaves two ways to
+/// turn them into beats. They only differ while the tempo is ramping, by half of the tempo
+/// increment for every sample, and hosts don't agree on which one to use. Plugins that must stay
+/// in sync with the host's timeline over long ramps should pick the one their host uses.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TempoCurve {
+    /// The tempo changes continuously, following a linear ramp that gains the tempo increment
+    /// over every sample.
+    ///
+    /// This is how Clack's host-side `TransportSynthesizer` ramps the tempo.
+    #[default]
+    Ramped,
+    /// The tempo is constant during each sample, and steps by the tempo increment from one sample
+    /// to the next.
+    ///
+    /// This is how [`Timebase`](super::Timebase) converts between samples and beats.
+    PiecewiseConstant,
+}
+
+/// Counts the beats elapsed over consecutive blocks, following the tempo and tempo ramps of the
+/// host's transport.
+///
+/// Unlike the song position, the beats counted by an integrator are never affected by the host's
+/// loops and jumps: this makes it suitable to drive tempo-synced effects, such as delays or
+/// LFOs, that must keep running smoothly whatever the transport does.
+///
+/// The integrator must be [updated](Self::update) with every transport event the plugin
+/// receives, at the time it happens, and [advanced](Self::advance) by all the frames in between.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::events::event_types::{TransportEvent, TransportFlags};
+/// use clack_common::events::{EventFlags, EventHeader};
+/// use clack_common::utils::{BeatTime, SecondsTime, TempoCurve, TempoIntegrator};
+///
+/// let transport = TransportEvent {
+///     header: EventHeader::new_core(0, EventFlags::empty()),
+///     flags: TransportFlags::HAS_TEMPO,
+///     song_pos_beats: BeatTime::from_int(0),
+///     song_pos_seconds: SecondsTime::from_int(0),
+///     // The tempo ramps from 60 to 120 BPM over 48000 samples.
+///     tempo: 60.0,
+///     tempo_inc: 60.0 / 48_000.0,
+///     loop_start_beats: BeatTime::from_int(0),
+///     loop_end_beats: BeatTime::from_int(0),
+///     loop_start_seconds: SecondsTime::from_int(0),
+///     loop_end_seconds: SecondsTime::from_int(0),
+///     bar_start: BeatTime::from_int(0),
+///     bar_number: 0,
+///     time_signature_numerator: 4,
+///     time_signature_denominator: 4,
+/// };
+///
+/// let mut integrator = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+/// integrator.update(&transport);
+///
+/// // Over the ramp, the average tempo is 90 BPM.
+/// assert_eq!(integrator.advance(48_000), 1.5);
+/// assert_eq!(integrator.tempo(), Some(120.0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TempoIntegrator {
+    sample_rate: f64,
+    curve: TempoCurve,
+    tempo: Option<f64>,
+    tempo_inc: f64,
+}
+
+impl TempoIntegrator {
+    /// Creates a new integrator for the given sample rate, in Hertz, interpreting tempo ramps
+    /// with the given curve.
+    ///
+    /// The integrator has no tempo until it is [updated](Self::update) with a transport event
+    /// that has one, or until one is [set](Self::set_tempo).
+    #[inline]
+    pub fn new(sample_rate: f64, curve: TempoCurve) -> Self {
+        Self {
+            sample_rate,
+            curve,
+            tempo: None,
+            tempo_inc: 0.0,
+        }
+    }
+
+    /// Returns the curve this integrator uses to interpret tempo ramps.
+    #[inline]
+    pub fn curve(&self) -> TempoCurve {
+        self.curve
+    }
+
+    /// Returns the current tempo, in beats per minute, if any.
+    #[inline]
+    pub fn tempo(&self) -> Option<f64> {
+        self.tempo
+    }
+
+    /// Returns the current tempo increment, per sample, in beats per minute.
+    #[inline]
+    pub fn tempo_increment(&self) -> f64 {
+        self.tempo_inc
+    }
+
+    /// Follows the tempo and tempo ramp of the given transport event, from now on.
+    ///
+    /// If the transport has no tempo, the integrator stops counting beats until it gets one.
+    #[inline]
+    pub fn update(&mut self, transport: &TransportEvent) {
+        self.tempo = transport.tempo_bpm();
+        self.tempo_inc = transport.tempo_increment().unwrap_or(0.0);
+    }
+
+    /// Sets a constant tempo, in beats per minute, cancelling any tempo ramp.
+    ///
+    /// This is useful to keep counting beats at a fallback tempo, when the host doesn't provide
+    /// one.
+    #[inline]
+    pub fn set_tempo(&mut self, tempo: f64) {
+        self.tempo = Some(tempo).filter(|t| *t > 0.0);
+        self.tempo_inc = 0.0;
+    }
+
+    /// Returns how many beats elapse over the given number of frames, starting from now, without
+    /// advancing.
+    ///
+    /// This is `0.0` if the integrator has no tempo.
+    pub fn beats_in(&self, frames: u32) -> f64 {
+        let Some(tempo) = self.tempo else {
+            return 0.0;
+        };
+
+        let frames = frames as f64;
+        let tempo_inc = self.tempo_inc;
+
+        // The tempo never goes below zero: a decreasing ramp stops once it reaches it.
+        let ramp_frames = if tempo_inc < 0.0 {
+            frames.min(self.frames_to_zero(tempo))
+        } else {
+            frames
+        };
+
+        let beat_minutes_frames = match self.curve {
+            TempoCurve::Ramped => tempo * ramp_frames + tempo_inc * ramp_frames * ramp_frames / 2.0,
+            TempoCurve::PiecewiseConstant => {
+                tempo * ramp_frames + tempo_inc * ramp_frames * (ramp_frames - 1.0) / 2.0
+            }
+        };
+
+        beat_minutes_frames / (60.0 * self.sample_rate)
+    }
+
+    /// Advances by the given number of frames, and returns how many beats elapsed over them.
+    ///
+    /// This is `0.0` if the integrator has no tempo.
+    pub fn advance(&mut self, frames: u32) -> f64 {
+        let beats = self.beats_in(frames);
+
+        if let Some(tempo) = &mut self.tempo {
+            *tempo = (*tempo + self.tempo_inc * frames as f64).max(0.0);
+
+            if *tempo == 0.0 {
+                self.tempo_inc = self.tempo_inc.max(0.0);
+            }
+        }
+
+        beats
+    }
+
+    /// Returns after how many frames a decreasing tempo ramp starting at `tempo` reaches zero.
+    #[inline]
+    fn frames_to_zero(&self, tempo: f64) -> f64 {
+        match self.curve {
+            TempoCurve::Ramped => tempo / -self.tempo_inc,
+            // The last sample with a positive tempo is still counted.
+            TempoCurve::PiecewiseConstant => (tempo / -self.tempo_inc).ceil(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::event_types::TransportFlags;
+    use crate::events::{EventFlags, EventHeader};
+    use crate::utils::{BeatTime, SecondsTime, Timebase};
+
+    fn transport(tempo: f64, tempo_inc: f64) -> TransportEvent {
+        TransportEvent {
+            header: EventHeader::new_core(0, EventFlags::empty()),
+            flags: TransportFlags::HAS_TEMPO,
+            song_pos_beats: BeatTime::from_int(0),
+            song_pos_seconds: SecondsTime::from_int(0),
+            tempo,
+            tempo_inc,
+            loop_start_beats: BeatTime::from_int(0),
+            loop_end_beats: BeatTime::from_int(0),
+            loop_start_seconds: SecondsTime::from_int(0),
+            loop_end_seconds: SecondsTime::from_int(0),
+            bar_start: BeatTime::from_int(0),
+            bar_number: 0,
+            time_signature_numerator: 4,
+            time_signature_denominator: 4,
+        }
+    }
+
+    #[test]
+    fn counts_nothing_without_tempo() {
+        let mut integrator = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+        assert_eq!(integrator.advance(48_000), 0.0);
+
+        let mut no_tempo = transport(120.0, 0.0);
+        no_tempo.flags = TransportFlags::empty();
+        integrator.update(&no_tempo);
+        assert_eq!(integrator.tempo(), None);
+        assert_eq!(integrator.advance(48_000), 0.0);
+
+        integrator.set_tempo(120.0);
+        assert_eq!(integrator.advance(48_000), 2.0);
+    }
+
+    #[test]
+    fn constant_tempo_is_the_same_for_both_curves() {
+        for curve in [TempoCurve::Ramped, TempoCurve::PiecewiseConstant] {
+            let mut integrator = TempoIntegrator::new(48_000.0, curve);
+            integrator.update(&transport(90.0, 0.0));
+
+            assert_eq!(integrator.advance(32_000), 1.0);
+            assert_eq!(integrator.tempo(), Some(90.0));
+        }
+    }
+
+    #[test]
+    fn curves_differ_by_half_an_increment_per_sample() {
+        let ramp = transport(60.0, 0.01);
+
+        let mut ramped = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+        let mut stepped = TempoIntegrator::new(48_000.0, TempoCurve::PiecewiseConstant);
+        ramped.update(&ramp);
+        stepped.update(&ramp);
+
+        let difference = ramped.advance(6000) - stepped.advance(6000);
+        assert!((difference - 0.01 * 6000.0 / 2.0 / (60.0 * 48_000.0)).abs() < 1e-12);
+
+        // Both reach the same tempo.
+        assert_eq!(ramped.tempo(), stepped.tempo());
+    }
+
+    #[test]
+    fn piecewise_constant_matches_timebase() {
+        let ramp = transport(100.0, -0.002);
+        let timebase = Timebase::new(44_100.0, Some(&ramp));
+
+        let mut integrator = TempoIntegrator::new(44_100.0, TempoCurve::PiecewiseConstant);
+        integrator.update(&ramp);
+
+        let expected = timebase.samples_to_beats(10_000.0).unwrap();
+        assert!((integrator.beats_in(10_000) - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn splitting_blocks_does_not_change_the_count() {
+        let ramp = transport(140.0, -0.0005);
+
+        let mut whole = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+        let mut split = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+        whole.update(&ramp);
+        split.update(&ramp);
+
+        let expected = whole.advance(100_000);
+        let beats: f64 = (0..100).map(|_| split.advance(1000)).sum();
+
+        assert!((beats - expected).abs() < 1e-9);
+        assert!((split.tempo().unwrap() - whole.tempo().unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn decreasing_ramps_stop_at_zero() {
+        let mut integrator = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+        integrator.update(&transport(60.0, -0.01));
+
+        // The tempo reaches zero after 6000 samples, at an average of 30 BPM.
+        assert_eq!(integrator.advance(48_000), 6000.0 * 30.0 / 60.0 / 48_000.0);
+        assert_eq!(integrator.tempo(), Some(0.0));
+        assert_eq!(integrator.advance(48_000), 0.0);
+    }
+}
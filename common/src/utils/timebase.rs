@@ -0,0 +1,517 @@
+use crate::events::event_types::{TransportEvent, TransportFlags};
+use crate::utils::{BeatTime, FixedPoint, SecondsTime};
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+
+/// Converts between sample offsets, seconds, and the transport's beat time.
+///
+/// A timebase is built from the current sample rate, and the transport information of the
+/// current block, if any. Sample offsets are relative to the start of that block, i.e. the
+/// position described by the transport.
+///
+/// Beat times are in quarter notes, as in CLAP. Conversions involving beats take the transport's
+/// tempo and tempo increment into account, and return `None` if the transport has no tempo
+/// information.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::Timebase;
+///
+/// // Without transport, only conversions between samples and seconds are available.
+/// let timebase = Timebase::new(48_000.0, None);
+///
+/// assert_eq!(timebase.samples_to_seconds(24_000.0), 0.5);
+/// assert_eq!(timebase.samples_to_beats(24_000.0), None);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Timebase {
+    sample_rate: f64,
+    transport: Option<TransportEvent>,
+}
+
+impl Timebase {
+    /// Creates a new timebase from the given sample rate, in Hertz, and the transport
+    /// information of the current block.
+    #[inline]
+    pub fn new(sample_rate: f64, transport: Option<&TransportEvent>) -> Self {
+        Self {
+            sample_rate,
+            transport: transport.copied(),
+        }
+    }
+
+    /// Returns the sample rate of this timebase, in Hertz.
+    #[inline]
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Returns the transport information of this timebase, if any.
+    #[inline]
+    pub fn transport(&self) -> Option<&TransportEvent> {
+        self.transport.as_ref()
+    }
+
+    /// Returns the tempo at the start of the block, in beats per minute, if the transport has
+    /// tempo information.
+    #[inline]
+    pub fn tempo(&self) -> Option<f64> {
+        self.transport?.tempo_bpm()
+    }
+
+    /// Converts a number of samples to a duration in seconds.
+    #[inline]
+    pub fn samples_to_seconds(&self, samples: f64) -> f64 {
+        samples / self.sample_rate
+    }
+
+    /// Converts a duration in seconds to a number of samples.
+    #[inline]
+    pub fn seconds_to_samples(&self, seconds: f64) -> f64 {
+        seconds * self.sample_rate
+    }
+
+    /// Converts an absolute number of samples to a duration in seconds.
+    ///
+    /// If the sample rate is an integer, this is computed exactly, without accumulating any
+    /// error, however large `samples` is.
+    #[inline]
+    pub fn samples_to_seconds_time(&self, samples: i64) -> SecondsTime {
+        if self.sample_rate.fract() == 0.0 && self.sample_rate >= 1.0 {
+            FixedPoint::from_ratio(samples, self.sample_rate as i64)
+        } else {
+            FixedPoint::from_float(samples as f64 / self.sample_rate)
+        }
+    }
+
+    /// Returns how many beats elapse during the given number of samples, starting from the
+    /// beginning of the block.
+    ///
+    /// This takes the transport's tempo increment into account.
+    pub fn samples_to_beats(&self, samples: f64) -> Option<f64> {
+        let tempo = self.tempo()?;
+        let tempo_inc = self.tempo_inc();
+
+        // The tempo increases by tempo_inc for every sample: sum it over all samples.
+        let beats_per_minute_samples =
+            samples * tempo + tempo_inc * samples * (samples - 1.0) / 2.0;
+
+        Some(beats_per_minute_samples / (60.0 * self.sample_rate))
+    }
+
+    /// Returns how many samples it takes for the given number of beats to elapse, starting from
+    /// the beginning of the block.
+    ///
+    /// This is the inverse of [`samples_to_beats`](Self::samples_to_beats). It returns `None` if
+    /// the transport has no tempo, or if the given beats can never be reached because the tempo
+    /// decreases too quickly.
+    pub fn beats_to_samples(&self, beats: f64) -> Option<f64> {
+        let tempo = self.tempo()?;
+        let tempo_inc = self.tempo_inc();
+
+        // Solves (tempo_inc / 2) * n² + (tempo - tempo_inc / 2) * n - c = 0 for n, in a form that
+        // stays numerically stable when tempo_inc is (close to) zero.
+        let b = tempo - tempo_inc / 2.0;
+        let c = beats * 60.0 * self.sample_rate;
+        let discriminant = b * b + 2.0 * tempo_inc * c;
+
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let denominator = b + discriminant.sqrt();
+        if denominator == 0.0 {
+            return if c == 0.0 { Some(0.0) } else { None };
+        }
+
+        Some(2.0 * c / denominator)
+    }
+
+    /// Converts a duration in seconds, starting from the beginning of the block, to a beat
+    /// duration.
+    #[inline]
+    pub fn seconds_to_beattime(&self, seconds: f64) -> Option<BeatTime> {
+        self.samples_to_beats(self.seconds_to_samples(seconds))
+            .map(BeatTime::from_float)
+    }
+
+    /// Converts a beat duration, starting from the beginning of the block, to a duration in
+    /// seconds.
+    #[inline]
+    pub fn beattime_to_seconds(&self, beats: BeatTime) -> Option<SecondsTime> {
+        self.beats_to_samples(beats.to_float())
+            .map(|samples| SecondsTime::from_float(self.samples_to_seconds(samples)))
+    }
+
+    /// Returns the song position in beats at the given sample offset in the block, if the
+    /// transport has a beat timeline.
+    pub fn beat_position_at(&self, sample_offset: u32) -> Option<BeatTime> {
+        let transport = self.transport_with(TransportFlags::HAS_BEATS_TIMELINE)?;
+        let elapsed = self.samples_to_beats(sample_offset as f64)?;
+
+        Some(transport.song_pos_beats + BeatTime::from_float(elapsed))
+    }
+
+    /// Returns the bar, beat and tick of the given song position, honoring the transport's time
+    /// signature and bar start.
+    ///
+    /// Beats are counted in units of the time signature's denominator (e.g. eighth notes in 6/8),
+    /// and each of them is divided in `ticks_per_beat` ticks. This is computed exactly, using
+    /// integer math.
+    ///
+    /// This returns `None` if the transport has no time signature, if it is invalid, or if
+    /// `ticks_per_beat` is `0`.
+    pub fn bar_beat_tick(&self, position: BeatTime, ticks_per_beat: u32) -> Option<BarBeatTick> {
+        let transport = self.transport_with(TransportFlags::HAS_TIME_SIGNATURE)?;
+
+        let numerator = transport.time_signature_numerator;
+        let denominator = transport.time_signature_denominator;
+        if numerator <= 0 || denominator <= 0 || ticks_per_beat == 0 {
+            return None;
+        }
+
+        // All values are scaled by the denominator, so that beat lengths are integers.
+        // In quarter notes, a beat lasts 4 / denominator, and a bar lasts numerator beats.
+        let beat_length = FixedPoint::FACTOR as i128 * 4;
+        let bar_length = beat_length * numerator as i128;
+        let relative = (position.to_bits() as i128 - transport.bar_start.to_bits() as i128)
+            * denominator as i128;
+
+        let bars = relative.div_euclid(bar_length);
+        let in_bar = relative.rem_euclid(bar_length);
+
+        Some(BarBeatTick {
+            bar: transport.bar_number as i64 + bars as i64,
+            beat: (in_bar / beat_length) as u32,
+            tick: ((in_bar % beat_length) * ticks_per_beat as i128 / beat_length) as u32,
+        })
+    }
+
+    /// Returns the active loop region, in beats, if any.
+    #[inline]
+    pub fn loop_region_beats(&self) -> Option<Range<BeatTime>> {
+        let transport = self.transport_with(
+            TransportFlags::IS_LOOP_ACTIVE.union(TransportFlags::HAS_BEATS_TIMELINE),
+        )?;
+
+        Some(transport.loop_start_beats..transport.loop_end_beats)
+    }
+
+    /// Returns the active loop region, in seconds, if any.
+    #[inline]
+    pub fn loop_region_seconds(&self) -> Option<Range<SecondsTime>> {
+        let transport = self.transport_with(
+            TransportFlags::IS_LOOP_ACTIVE.union(TransportFlags::HAS_SECONDS_TIMELINE),
+        )?;
+
+        Some(transport.loop_start_seconds..transport.loop_end_seconds)
+    }
+
+    /// Returns `true` if a loop is active and the given song position, in beats, is within it.
+    #[inline]
+    pub fn loop_contains_beats(&self, position: BeatTime) -> bool {
+        self.loop_region_beats()
+            .is_some_and(|region| region.contains(&position))
+    }
+
+    /// Returns `true` if a loop is active and the given song position, in seconds, is within it.
+    #[inline]
+    pub fn loop_contains_seconds(&self, position: SecondsTime) -> bool {
+        self.loop_region_seconds()
+            .is_some_and(|region| region.contains(&position))
+    }
+
+    #[inline]
+    fn tempo_inc(&self) -> f64 {
+        self.transport
+            .and_then(|t| t.tempo_increment())
+            .unwrap_or(0.0)
+    }
+
+    #[inline]
+    fn transport_with(&self, flags: TransportFlags) -> Option<&TransportEvent> {
+        self.transport
+            .as_ref()
+            .filter(|transport| transport.flags.contains(flags))
+    }
+}
+
+/// A musical position, expressed in bars, beats and ticks.
+///
+/// Bars are numbered as in CLAP, i.e. the bar at song position `0` is bar `0`. Beats and ticks
+/// are 0-based. However, the [`Display`] implementation shows all of them 1-based, as is usual in
+/// DAWs (e.g. `1.1.1` for the very start of the song).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct BarBeatTick {
+    /// The bar number.
+    pub bar: i64,
+    /// The beat within the bar.
+    pub beat: u32,
+    /// The tick within the beat.
+    pub tick: u32,
+}
+
+impl Display for BarBeatTick {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}.{}", self.bar + 1, self.beat + 1, self.tick + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::events::{EventFlags, EventHeader};
+
+    /// A tiny xorshift generator, to drive property tests deterministically.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn range(&mut self, range: Range<i64>) -> i64 {
+            range.start + (self.next() % (range.end - range.start) as u64) as i64
+        }
+    }
+
+    fn transport(tempo: f64, tempo_inc: f64) -> TransportEvent {
+        TransportEvent {
+            header: EventHeader::new_core(0, EventFlags::empty()),
+            flags: TransportFlags::HAS_TEMPO
+                | TransportFlags::HAS_BEATS_TIMELINE
+                | TransportFlags::HAS_SECONDS_TIMELINE
+                | TransportFlags::HAS_TIME_SIGNATURE,
+            song_pos_beats: BeatTime::from_int(0),
+            song_pos_seconds: SecondsTime::from_int(0),
+            tempo,
+            tempo_inc,
+            loop_start_beats: BeatTime::from_int(0),
+            loop_end_beats: BeatTime::from_int(0),
+            loop_start_seconds: SecondsTime::from_int(0),
+            loop_end_seconds: SecondsTime::from_int(0),
+            bar_start: BeatTime::from_int(0),
+            bar_number: 0,
+            time_signature_numerator: 4,
+            time_signature_denominator: 4,
+        }
+    }
+
+    #[test]
+    fn fixed_point_integer_math_round_trips() {
+        let mut rng = Rng(0x1234_5678_9abc_def0);
+
+        for _ in 0..10_000 {
+            let x = FixedPoint::from_bits(rng.range(-(1 << 50)..(1 << 50)));
+            let y = FixedPoint::from_bits(rng.range(-(1 << 50)..(1 << 50)));
+            let k = rng.range(1..1000);
+
+            assert_eq!(x + y - y, x);
+            assert_eq!(x.floor() + x.fract(), x);
+            assert_eq!(x.checked_mul_int(k).unwrap().mul_ratio(1, k), x);
+            assert_eq!(FixedPoint::from_float(x.to_float()), x);
+            assert_eq!(FixedPoint::from_ratio(k * 7, k), FixedPoint::from_int(7));
+        }
+    }
+
+    #[test]
+    fn fixed_point_ratios_do_not_accumulate_error() {
+        // One hour of 512-sample blocks at 44.1kHz.
+        let blocks = 3600 * 44_100 / 512;
+
+        let mut accumulated = 0.0f64;
+        for _ in 0..blocks {
+            accumulated += 512.0 / 44_100.0;
+        }
+
+        let exact = FixedPoint::from_ratio(blocks * 512, 44_100);
+        assert_eq!(
+            exact,
+            Timebase::new(44_100.0, None).samples_to_seconds_time(blocks * 512)
+        );
+        assert!((exact.to_float() - blocks as f64 * 512.0 / 44_100.0).abs() < 1e-9);
+        assert_ne!(FixedPoint::from_float(accumulated), exact);
+    }
+
+    #[test]
+    fn fixed_point_ratios_round_to_nearest() {
+        assert_eq!(FixedPoint::from_ratio(1, 2).to_float(), 0.5);
+        assert_eq!(FixedPoint::from_ratio(-1, 2).to_float(), -0.5);
+        assert_eq!(FixedPoint::from_ratio(1, -2).to_float(), -0.5);
+        assert_eq!(FixedPoint::from_bits(3).mul_ratio(1, 2).to_bits(), 2);
+        assert_eq!(FixedPoint::from_bits(-3).mul_ratio(1, 2).to_bits(), -2);
+        assert_eq!(
+            FixedPoint::from_bits(i64::MAX / 2)
+                .mul_ratio(4, 1)
+                .to_bits(),
+            i64::MAX
+        );
+    }
+
+    #[test]
+    fn beats_and_samples_round_trip() {
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+
+        for _ in 0..1000 {
+            let tempo = rng.range(20..300) as f64;
+            let tempo_inc = rng.range(-100..100) as f64 / 1e6;
+            let samples = rng.range(0..1_000_000) as f64;
+
+            // Past the point where the tempo reaches zero, beats go backwards and can't be
+            // converted back unambiguously.
+            if tempo + tempo_inc * samples <= 0.0 {
+                continue;
+            }
+
+            let timebase = Timebase::new(48_000.0, Some(&transport(tempo, tempo_inc)));
+            let beats = timebase.samples_to_beats(samples).unwrap();
+            let back = timebase.beats_to_samples(beats).unwrap();
+
+            assert!(
+                (back - samples).abs() < 1e-3,
+                "{samples} -> {beats} -> {back}"
+            );
+        }
+    }
+
+    #[test]
+    fn converts_at_constant_tempo() {
+        let timebase = Timebase::new(48_000.0, Some(&transport(120.0, 0.0)));
+
+        assert_eq!(timebase.samples_to_beats(24_000.0), Some(1.0));
+        assert_eq!(timebase.beats_to_samples(4.0), Some(96_000.0));
+        assert_eq!(
+            timebase.seconds_to_beattime(1.5),
+            Some(BeatTime::from_int(3))
+        );
+        assert_eq!(
+            timebase.beattime_to_seconds(BeatTime::from_int(3)),
+            Some(SecondsTime::from_float(1.5))
+        );
+
+        let mut no_tempo = transport(120.0, 0.0);
+        no_tempo.flags.remove(TransportFlags::HAS_TEMPO);
+        let timebase = Timebase::new(48_000.0, Some(&no_tempo));
+        assert_eq!(timebase.samples_to_beats(1.0), None);
+        assert_eq!(timebase.beat_position_at(0), None);
+    }
+
+    #[test]
+    fn follows_tempo_increments() {
+        // The tempo increases by 1 BPM every 480 samples.
+        let timebase = Timebase::new(48_000.0, Some(&transport(60.0, 1.0 / 480.0)));
+
+        // Slightly more than a beat elapses in a second, as the tempo goes up.
+        let beats = timebase.samples_to_beats(48_000.0).unwrap();
+        assert!(beats > 1.8 && beats < 1.9, "{beats}");
+    }
+
+    #[test]
+    fn formats_bar_beat_tick() {
+        let mut transport = transport(120.0, 0.0);
+        transport.bar_start = BeatTime::from_int(8);
+        transport.bar_number = 2;
+        transport.song_pos_beats = BeatTime::from_float(9.5);
+
+        let timebase = Timebase::new(48_000.0, Some(&transport));
+        let position = timebase.beat_position_at(0).unwrap();
+
+        let bbt = timebase.bar_beat_tick(position, 960).unwrap();
+        assert_eq!(
+            bbt,
+            BarBeatTick {
+                bar: 2,
+                beat: 1,
+                tick: 480
+            }
+        );
+        assert_eq!(bbt.to_string(), "3.2.481");
+
+        // Before the bar start.
+        let bbt = timebase.bar_beat_tick(BeatTime::from_int(7), 960).unwrap();
+        assert_eq!(
+            bbt,
+            BarBeatTick {
+                bar: 1,
+                beat: 3,
+                tick: 0
+            }
+        );
+
+        // In 6/8, beats are eighth notes and bars last 3 quarter notes.
+        transport.time_signature_numerator = 6;
+        transport.time_signature_denominator = 8;
+        let timebase = Timebase::new(48_000.0, Some(&transport));
+
+        let bbt = timebase
+            .bar_beat_tick(BeatTime::from_float(12.25), 4)
+            .unwrap();
+        assert_eq!(
+            bbt,
+            BarBeatTick {
+                bar: 3,
+                beat: 2,
+                tick: 2
+            }
+        );
+    }
+
+    #[test]
+    fn bar_beat_tick_round_trips() {
+        let mut rng = Rng(0x0bad_5eed_0bad_5eed);
+        let mut transport = transport(120.0, 0.0);
+
+        for _ in 0..1000 {
+            let numerator = rng.range(1..16);
+            let denominator = 1 << rng.range(0..5);
+            transport.time_signature_numerator = numerator as i16;
+            transport.time_signature_denominator = denominator as i16;
+
+            let timebase = Timebase::new(48_000.0, Some(&transport));
+
+            // With a power of two ticks per beat, all tick positions are exactly representable.
+            let bar = rng.range(0..10_000);
+            let beat = rng.range(0..numerator);
+            let tick = rng.range(0..1024);
+
+            let position = BeatTime::from_ratio(
+                ((bar * numerator + beat) * 1024 + tick) * 4,
+                denominator * 1024,
+            );
+
+            assert_eq!(
+                timebase.bar_beat_tick(position, 1024),
+                Some(BarBeatTick {
+                    bar,
+                    beat: beat as u32,
+                    tick: tick as u32
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn checks_loop_containment() {
+        let mut transport = transport(120.0, 0.0);
+        transport.loop_start_beats = BeatTime::from_int(4);
+        transport.loop_end_beats = BeatTime::from_int(8);
+        transport.loop_start_seconds = SecondsTime::from_int(2);
+        transport.loop_end_seconds = SecondsTime::from_int(4);
+
+        let timebase = Timebase::new(48_000.0, Some(&transport));
+        assert!(!timebase.loop_contains_beats(BeatTime::from_int(5)));
+
+        transport.flags.insert(TransportFlags::IS_LOOP_ACTIVE);
+        let timebase = Timebase::new(48_000.0, Some(&transport));
+
+        assert!(timebase.loop_contains_beats(BeatTime::from_int(4)));
+        assert!(timebase.loop_contains_beats(BeatTime::from_float(7.9)));
+        assert!(!timebase.loop_contains_beats(BeatTime::from_int(8)));
+        assert!(timebase.loop_contains_seconds(SecondsTime::from_int(3)));
+        assert!(!timebase.loop_contains_seconds(SecondsTime::from_int(1)));
+    }
+}
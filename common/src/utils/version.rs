@@ -1,6 +1,6 @@
 use clap_sys::version::clap_version;
-use std::cmp::Ordering;
-use std::fmt::{Display, Formatter};
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter};
 
 /// A CLAP version identifier.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -32,9 +32,43 @@ impl ClapVersion {
         }
     }
 
+    /// Returns `true` if this version is compatible with the CLAP version implemented by Clack
+    /// (i.e. [`ClapVersion::CURRENT`]).
+    ///
+    /// As per the CLAP specification, all `1.x.y` versions (and above) are compatible with each
+    /// other. Versions `0.x.y` were only used during CLAP's development stage, and are never
+    /// considered compatible with a stable version.
     #[inline]
     pub const fn is_compatible(&self) -> bool {
-        clap_sys::version::clap_version_is_compatible(self.to_raw())
+        self.is_compatible_with(&Self::CURRENT)
+    }
+
+    /// Returns `true` if a plugin or host using this version can be used with another one using
+    /// the `other` version.
+    ///
+    /// Stable versions (`1.x.y` and above) are all compatible with each other. Development-stage
+    /// versions (`0.x.y`) have no ABI stability guarantees: they are only compatible with the
+    /// exact same `0.x` minor version, and never with a stable version.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_common::utils::ClapVersion;
+    ///
+    /// let v = |major, minor, revision| ClapVersion { major, minor, revision };
+    ///
+    /// assert!(v(1, 0, 0).is_compatible_with(&v(1, 2, 1)));
+    /// assert!(v(0, 3, 1).is_compatible_with(&v(0, 3, 0)));
+    /// assert!(!v(0, 3, 1).is_compatible_with(&v(0, 2, 0)));
+    /// assert!(!v(0, 3, 1).is_compatible_with(&v(1, 0, 0)));
+    /// ```
+    #[inline]
+    pub const fn is_compatible_with(&self, other: &ClapVersion) -> bool {
+        match (self.major, other.major) {
+            (0, 0) => self.minor == other.minor,
+            (0, _) | (_, 0) => false,
+            _ => true,
+        }
     }
 }
 
@@ -58,7 +92,7 @@ impl Ord for ClapVersion {
 }
 
 impl Display for ClapVersion {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}.{}.{}", self.major, self.minor, self.revision)
     }
 }
@@ -79,6 +113,57 @@ mod test {
         assert_eq!(&"1.5.3", &display);
     }
 
+    #[test]
+    pub fn current_is_compatible() {
+        assert!(ClapVersion::CURRENT.is_compatible());
+        assert!(ClapVersion::CURRENT.is_compatible_with(&ClapVersion::CURRENT));
+    }
+
+    #[test]
+    pub fn stable_versions_are_compatible() {
+        let old = ClapVersion {
+            major: 1,
+            minor: 0,
+            revision: 0,
+        };
+        let new = ClapVersion {
+            major: 1,
+            minor: 2,
+            revision: 2,
+        };
+
+        assert!(old.is_compatible());
+        assert!(new.is_compatible());
+        assert!(old.is_compatible_with(&new));
+        assert!(new.is_compatible_with(&old));
+    }
+
+    #[test]
+    pub fn development_versions_are_only_compatible_with_same_minor() {
+        let dev = ClapVersion {
+            major: 0,
+            minor: 26,
+            revision: 0,
+        };
+        let dev_patched = ClapVersion {
+            major: 0,
+            minor: 26,
+            revision: 3,
+        };
+        let other_dev = ClapVersion {
+            major: 0,
+            minor: 25,
+            revision: 0,
+        };
+
+        assert!(!dev.is_compatible());
+        assert!(dev.is_compatible_with(&dev_patched));
+        assert!(dev_patched.is_compatible_with(&dev));
+        assert!(!dev.is_compatible_with(&other_dev));
+        assert!(!dev.is_compatible_with(&ClapVersion::CURRENT));
+        assert!(!ClapVersion::CURRENT.is_compatible_with(&dev));
+    }
+
     #[test]
     pub fn version_ordering() {
         let version = ClapVersion {
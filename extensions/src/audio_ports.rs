@@ -4,6 +4,12 @@ use clack_common::utils::ClapId;
 use clap_sys::ext::audio_ports::*;
 use std::ffi::CStr;
 use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+pub mod channels;
+pub mod labels;
+pub mod port_types;
+pub mod snapshot;
 
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
@@ -17,8 +23,20 @@ pub struct HostAudioPorts(RawExtension<HostExtensionSide, clap_host_audio_ports>
 pub struct AudioPortType<'a>(pub &'a CStr);
 
 impl AudioPortType<'_> {
-    pub const MONO: AudioPortType<'static> = AudioPortType(CLAP_PORT_MONO);
-    pub const STEREO: AudioPortType<'static> = AudioPortType(CLAP_PORT_STEREO);
+    pub const MONO: AudioPortType<'static> = AudioPortType(port_types::MONO);
+    pub const STEREO: AudioPortType<'static> = AudioPortType(port_types::STEREO);
+    pub const SURROUND: AudioPortType<'static> = AudioPortType(port_types::SURROUND);
+    pub const AMBISONIC: AudioPortType<'static> = AudioPortType(port_types::AMBISONIC);
+    /// A control-voltage port, carrying modulation signals rather than audio.
+    ///
+    /// See [`AudioPortInfo::cv`] to declare such ports.
+    pub const CV: AudioPortType<'static> = AudioPortType(port_types::CV);
+
+    /// Returns `true` if this is the [`CV`](Self::CV) port type.
+    #[inline]
+    pub fn is_cv(&self) -> bool {
+        *self == Self::CV
+    }
 
     #[inline]
     pub const fn from_channel_count(channel_count: u32) -> Option<Self> {
@@ -65,6 +83,8 @@ impl RescanType {
 bitflags! {
     #[repr(C)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct AudioPortFlags: u32 {
         const IS_MAIN = CLAP_AUDIO_PORT_IS_MAIN;
         const SUPPORTS_64BITS = CLAP_AUDIO_PORT_SUPPORTS_64BITS;
@@ -106,6 +126,64 @@ pub struct AudioPortInfo<'a> {
 }
 
 impl<'a> AudioPortInfo<'a> {
+    /// Returns the information of a mono audio port, with the given ID and name, and no flags.
+    #[inline]
+    pub const fn mono(id: ClapId, name: &'a [u8]) -> Self {
+        Self::with_type(id, name, 1, AudioPortType::MONO)
+    }
+
+    /// Returns the information of a stereo audio port, with the given ID and name, and no flags.
+    #[inline]
+    pub const fn stereo(id: ClapId, name: &'a [u8]) -> Self {
+        Self::with_type(id, name, 2, AudioPortType::STEREO)
+    }
+
+    /// Returns the information of a mono control-voltage port, with the given ID and name.
+    ///
+    /// CV ports carry modulation signals rather than audio, and hosts may present them
+    /// differently, e.g. as modulation jacks. They cannot be main ports.
+    #[inline]
+    pub const fn cv(id: ClapId, name: &'a [u8]) -> Self {
+        Self::with_type(id, name, 1, AudioPortType::CV)
+    }
+
+    #[inline]
+    const fn with_type(
+        id: ClapId,
+        name: &'a [u8],
+        channel_count: u32,
+        port_type: AudioPortType<'static>,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            channel_count,
+            flags: AudioPortFlags::empty(),
+            port_type: Some(port_type),
+            in_place_pair: None,
+        }
+    }
+
+    /// Returns this port information with the [`IS_MAIN`](AudioPortFlags::IS_MAIN) flag set.
+    #[inline]
+    pub const fn main(mut self) -> Self {
+        self.flags = self.flags.union(AudioPortFlags::IS_MAIN);
+        self
+    }
+
+    /// Returns this port information with the given in-place pair.
+    #[inline]
+    pub const fn with_in_place_pair(mut self, in_place_pair: ClapId) -> Self {
+        self.in_place_pair = Some(in_place_pair);
+        self
+    }
+
+    /// Returns `true` if this port is a control-voltage port.
+    #[inline]
+    pub fn is_cv(&self) -> bool {
+        self.port_type.is_some_and(|t| t.is_cv())
+    }
+
     /// # Safety
     /// The raw port_type pointer must be a valid C string for the 'a lifetime.
     pub unsafe fn from_raw(raw: &'a clap_audio_port_info) -> Option<Self> {
@@ -139,6 +217,258 @@ impl Debug for AudioPortInfo<'_> {
     }
 }
 
+/// A fixed set of input and output audio ports, that can be declared as a constant.
+///
+/// This is used with [`StaticAudioPorts`] to implement the `audio-ports` extension for plugins
+/// that have a static port layout, instead of implementing
+/// `PluginAudioPortsImpl` by hand.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::ClapId;
+/// use clack_extensions::audio_ports::*;
+///
+/// const MAIN_PORT: AudioPortInfo<'static> = AudioPortInfo {
+///     id: ClapId::new(0),
+///     name: b"main",
+///     channel_count: 2,
+///     flags: AudioPortFlags::IS_MAIN,
+///     port_type: Some(AudioPortType::STEREO),
+///     in_place_pair: None,
+/// };
+///
+/// const STEREO_IN_OUT: AudioPortLayout = AudioPortLayout::new(&[MAIN_PORT], &[MAIN_PORT]);
+///
+/// assert_eq!(STEREO_IN_OUT.count(true), 1);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct AudioPortLayout {
+    inputs: &'static [AudioPortInfo<'static>],
+    outputs: &'static [AudioPortInfo<'static>],
+    channel_labels: &'static [labels::CustomChannelLabels],
+}
+
+impl AudioPortLayout {
+    /// Creates a new layout from the given input and output ports.
+    ///
+    /// # Panics
+    ///
+    /// This panics if any port name contains a NUL byte or is too long, or if two ports of the
+    /// same direction share the same ID. When used to declare a constant, this results in a
+    /// compile-time error instead.
+    pub const fn new(
+        inputs: &'static [AudioPortInfo<'static>],
+        outputs: &'static [AudioPortInfo<'static>],
+    ) -> Self {
+        validate_ports(inputs);
+        validate_ports(outputs);
+
+        Self {
+            inputs,
+            outputs,
+            channel_labels: &[],
+        }
+    }
+
+    /// Declares custom labels for the channels of some of this layout's ports.
+    ///
+    /// The labels are appended to the port names exposed to the host, following the convention
+    /// described in the [`labels`] module.
+    ///
+    /// # Panics
+    ///
+    /// This panics if any of the labelled ports doesn't exist or is labelled twice, if the number
+    /// of labels doesn't match the port's channel count, if any label is empty or contains a
+    /// reserved character, or if the resulting port name is too long. When used to declare a
+    /// constant, this results in a compile-time error instead.
+    pub const fn with_channel_labels(
+        self,
+        channel_labels: &'static [labels::CustomChannelLabels],
+    ) -> Self {
+        let mut i = 0;
+        while i < channel_labels.len() {
+            let custom = &channel_labels[i];
+            let Some(port) = find_port(self.ports(custom.is_input), custom.port_id) else {
+                panic!("Channel labels must refer to an existing port");
+            };
+
+            assert!(
+                custom.labels.len() == port.channel_count as usize,
+                "There must be exactly one channel label per channel"
+            );
+
+            let mut j = 0;
+            while j < custom.labels.len() {
+                labels::validate_label(custom.labels[j]);
+                j += 1;
+            }
+
+            assert!(
+                labels::encoded_len(port.name, custom.labels)
+                    < clap_sys::string_sizes::CLAP_NAME_SIZE,
+                "Port name with channel labels is too long"
+            );
+
+            let mut j = i + 1;
+            while j < channel_labels.len() {
+                assert!(
+                    channel_labels[j].is_input != custom.is_input
+                        || channel_labels[j].port_id.get() != custom.port_id.get(),
+                    "Each port can only be labelled once"
+                );
+                j += 1;
+            }
+
+            i += 1;
+        }
+
+        Self {
+            channel_labels,
+            ..self
+        }
+    }
+
+    /// Returns the custom channel labels of the port with the given ID and direction, if any.
+    pub const fn channel_labels(
+        &self,
+        is_input: bool,
+        port_id: ClapId,
+    ) -> Option<&'static [&'static [u8]]> {
+        let mut i = 0;
+        while i < self.channel_labels.len() {
+            let custom = &self.channel_labels[i];
+            if custom.is_input == is_input && custom.port_id.get() == port_id.get() {
+                return Some(custom.labels);
+            }
+            i += 1;
+        }
+
+        None
+    }
+
+    /// Returns the ports of this layout, for the given direction.
+    #[inline]
+    pub const fn ports(&self, is_input: bool) -> &'static [AudioPortInfo<'static>] {
+        if is_input {
+            self.inputs
+        } else {
+            self.outputs
+        }
+    }
+
+    /// Returns the number of ports of this layout, for the given direction.
+    #[inline]
+    pub const fn count(&self, is_input: bool) -> u32 {
+        self.ports(is_input).len() as u32
+    }
+
+    /// Returns the port at the given index, for the given direction.
+    #[inline]
+    pub const fn get(&self, index: u32, is_input: bool) -> Option<&'static AudioPortInfo<'static>> {
+        let ports = self.ports(is_input);
+
+        if (index as usize) < ports.len() {
+            Some(&ports[index as usize])
+        } else {
+            None
+        }
+    }
+}
+
+const fn find_port(
+    ports: &'static [AudioPortInfo<'static>],
+    id: ClapId,
+) -> Option<&'static AudioPortInfo<'static>> {
+    let mut i = 0;
+    while i < ports.len() {
+        if ports[i].id.get() == id.get() {
+            return Some(&ports[i]);
+        }
+        i += 1;
+    }
+
+    None
+}
+
+const fn validate_ports(ports: &[AudioPortInfo]) {
+    let mut i = 0;
+    while i < ports.len() {
+        crate::utils::validate_name(ports[i].name);
+
+        if let Some(port_type) = ports[i].port_type {
+            assert!(
+                !is_cv_type(port_type) || !ports[i].flags.contains(AudioPortFlags::IS_MAIN),
+                "CV ports cannot be main ports"
+            );
+        }
+
+        let mut j = i + 1;
+        while j < ports.len() {
+            assert!(
+                ports[i].id.get() != ports[j].id.get(),
+                "Audio port IDs must be unique"
+            );
+            j += 1;
+        }
+
+        i += 1;
+    }
+}
+
+/// A `const` equivalent of [`AudioPortType::is_cv`].
+const fn is_cv_type(port_type: AudioPortType) -> bool {
+    let (a, b) = (port_type.0.to_bytes(), port_types::CV.to_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Provides a constant [`AudioPortLayout`], to be used with [`StaticAudioPorts`].
+///
+/// This is typically implemented on the plugin type itself.
+pub trait AudioPortLayoutProvider: 'static {
+    /// The plugin's audio port layout.
+    const AUDIO_PORTS: AudioPortLayout;
+}
+
+/// An implementation of the plugin side of the `audio-ports` extension, that always exposes the
+/// constant layout declared by `L`.
+///
+/// This can be registered in place of [`PluginAudioPorts`] in `Plugin::declare_extensions`,
+/// without having to implement `PluginAudioPortsImpl`.
+pub struct StaticAudioPorts<L>(PhantomData<fn() -> L>);
+
+impl<L> Clone for StaticAudioPorts<L> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L> Copy for StaticAudioPorts<L> {}
+
+// SAFETY: This type carries no extension data. Its implementation is the same as PluginAudioPorts'.
+unsafe impl<L: AudioPortLayoutProvider> Extension for StaticAudioPorts<L> {
+    const IDENTIFIER: &'static CStr = CLAP_EXT_AUDIO_PORTS;
+    type ExtensionSide = PluginExtensionSide;
+
+    #[inline]
+    unsafe fn from_raw(_raw: RawExtension<Self::ExtensionSide>) -> Self {
+        Self(PhantomData)
+    }
+}
+
 #[cfg(feature = "clack-host")]
 mod host;
 #[cfg(feature = "clack-host")]
@@ -148,3 +478,69 @@ pub use host::*;
 mod plugin;
 #[cfg(feature = "clack-plugin")]
 pub use plugin::*;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const fn port(id: u32, name: &'static [u8]) -> AudioPortInfo<'static> {
+        AudioPortInfo {
+            id: ClapId::new(id),
+            name,
+            channel_count: 2,
+            flags: AudioPortFlags::IS_MAIN,
+            port_type: Some(AudioPortType::STEREO),
+            in_place_pair: None,
+        }
+    }
+
+    #[test]
+    fn layout_exposes_ports() {
+        const LAYOUT: AudioPortLayout =
+            AudioPortLayout::new(&[port(0, b"main"), port(1, b"aux")], &[]);
+
+        assert_eq!(LAYOUT.count(true), 2);
+        assert_eq!(LAYOUT.count(false), 0);
+        assert_eq!(LAYOUT.get(1, true).unwrap().name, b"aux");
+        assert!(LAYOUT.get(2, true).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Name must not contain NUL bytes")]
+    fn layout_rejects_nul_in_names() {
+        static PORTS: [AudioPortInfo; 1] = [port(0, b"ma\0in")];
+        AudioPortLayout::new(&PORTS, &[]);
+    }
+
+    #[test]
+    fn labeled_ports_have_expected_info() {
+        const CV: AudioPortInfo<'static> = AudioPortInfo::cv(ClapId::new(3), b"mod");
+
+        assert_eq!(CV.channel_count, 1);
+        assert!(CV.flags.is_empty());
+        assert!(CV.is_cv());
+
+        let main = AudioPortInfo::stereo(ClapId::new(0), b"main").main();
+        assert_eq!(main, port(0, b"main"));
+        assert!(!main.is_cv());
+
+        assert_eq!(
+            AudioPortInfo::mono(ClapId::new(1), b"side").port_type,
+            Some(AudioPortType::MONO)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "CV ports cannot be main ports")]
+    fn layout_rejects_main_cv_ports() {
+        static PORTS: [AudioPortInfo; 1] = [AudioPortInfo::cv(ClapId::new(0), b"cv").main()];
+        AudioPortLayout::new(&PORTS, &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Audio port IDs must be unique")]
+    fn layout_rejects_duplicate_ids() {
+        static PORTS: [AudioPortInfo; 2] = [port(0, b"main"), port(0, b"aux")];
+        AudioPortLayout::new(&PORTS, &[]);
+    }
+}
@@ -0,0 +1,537 @@
+//! Validation of the channel counts of audio ports, including surround and ambisonic ports.
+//!
+//! The channel count of a port must be consistent with its type: mono ports have a single
+//! channel, stereo ports have two, surround ports have one channel per entry in their channel map,
+//! and ambisonic ports have `(n + 1)²` channels for an order `n`. Buffers given to the plugin in
+//! `process` must then have the exact channel count of their port.
+//!
+//! Mismatches there are not caught by the plugin, which will happily read or write the wrong
+//! channels. This module provides [`PortChannelCounts`], which computes and validates the expected
+//! channel counts of every port once (e.g. when activating the plugin), and can then check the
+//! actual process buffers against them, reporting a typed [`ChannelLayoutError`] on mismatches.
+
+use crate::audio_ports::{AudioPortInfo, AudioPortType};
+use clack_common::process::AudioPortProcessingInfo;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Spatial information about a surround or ambisonic port, as provided by the `surround` and
+/// `ambisonic` extensions respectively.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SpatialLayout<'a> {
+    /// The channel map of a surround port, with one speaker position per channel.
+    Surround {
+        /// The speaker position of each channel.
+        channel_map: &'a [u8],
+    },
+    /// The order of an ambisonic port.
+    Ambisonic {
+        /// The ambisonic order.
+        order: u32,
+    },
+}
+
+/// Returns the number of channels of an ambisonic port of the given order, i.e. `(order + 1)²`.
+///
+/// This returns `None` if the channel count would overflow.
+#[inline]
+pub const fn ambisonic_channel_count(order: u32) -> Option<u32> {
+    match order.checked_add(1) {
+        Some(n) => n.checked_mul(n),
+        None => None,
+    }
+}
+
+/// Returns the ambisonic order matching the given channel count, or `None` if no order has that
+/// many channels (i.e. if it isn't a non-zero perfect square).
+pub const fn ambisonic_order(channel_count: u32) -> Option<u32> {
+    let mut n: u32 = 1;
+
+    while n <= channel_count / n {
+        if n * n == channel_count {
+            return Some(n - 1);
+        }
+        n += 1;
+    }
+
+    None
+}
+
+/// The reason why a port's channel count is invalid.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ChannelMismatch {
+    /// The port's channel count doesn't match its mono or stereo port type.
+    PortType {
+        /// The channel count implied by the port type.
+        expected: u32,
+        /// The port's declared channel count.
+        channel_count: u32,
+    },
+    /// The length of a surround port's channel map doesn't match its channel count.
+    ChannelMap {
+        /// The length of the channel map.
+        map_len: usize,
+        /// The port's declared channel count.
+        channel_count: u32,
+    },
+    /// The channel count of an ambisonic port doesn't match any ambisonic order.
+    InvalidAmbisonicChannelCount {
+        /// The port's declared channel count.
+        channel_count: u32,
+    },
+    /// The channel count of an ambisonic port doesn't match its ambisonic order.
+    AmbisonicOrder {
+        /// The ambisonic order of the port.
+        order: u32,
+        /// The port's declared channel count.
+        channel_count: u32,
+    },
+    /// Spatial information was given for a port whose type doesn't support it, e.g. a channel
+    /// map for an ambisonic port.
+    UnexpectedSpatialLayout,
+    /// The plugin failed to provide information about the port.
+    UnavailablePortInfo,
+    /// The number of ports of the buffers doesn't match the number of declared ports.
+    PortCount {
+        /// The number of declared ports.
+        expected: usize,
+        /// The number of ports of the buffers.
+        actual: usize,
+    },
+    /// The channel count of a port's buffer doesn't match the port's channel count.
+    Buffer {
+        /// The port's channel count.
+        expected: u32,
+        /// The channel count of the buffer.
+        actual: u32,
+    },
+}
+
+impl Display for ChannelMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PortType {
+                expected,
+                channel_count,
+            } => write!(
+                f,
+                "port type requires {expected} channels, but port has {channel_count}"
+            ),
+            Self::ChannelMap {
+                map_len,
+                channel_count,
+            } => write!(
+                f,
+                "channel map has {map_len} entries, but port has {channel_count} channels"
+            ),
+            Self::InvalidAmbisonicChannelCount { channel_count } => write!(
+                f,
+                "{channel_count} channels doesn't match any ambisonic order"
+            ),
+            Self::AmbisonicOrder {
+                order,
+                channel_count,
+            } => write!(
+                f,
+                "ambisonic order {order} doesn't match the port's {channel_count} channels"
+            ),
+            Self::UnexpectedSpatialLayout => {
+                f.write_str("spatial layout doesn't match the port type")
+            }
+            Self::UnavailablePortInfo => f.write_str("port information is unavailable"),
+            Self::PortCount { expected, actual } => {
+                write!(f, "expected {expected} ports, got {actual}")
+            }
+            Self::Buffer { expected, actual } => {
+                write!(f, "expected {expected} channels, buffer has {actual}")
+            }
+        }
+    }
+}
+
+/// An invalid channel count on an audio port, or on its buffer.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ChannelLayoutError {
+    /// Whether the invalid port is an input port or an output port.
+    pub is_input: bool,
+    /// The index of the invalid port.
+    ///
+    /// For [`ChannelMismatch::PortCount`] errors, this is the index of the first missing or extra
+    /// port.
+    pub port_index: u32,
+    /// What is wrong with the port's channel count.
+    pub mismatch: ChannelMismatch,
+}
+
+impl Display for ChannelLayoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let direction = if self.is_input { "input" } else { "output" };
+
+        write!(
+            f,
+            "Invalid channel layout on {direction} port #{}: {}",
+            self.port_index, self.mismatch
+        )
+    }
+}
+
+impl Error for ChannelLayoutError {}
+
+/// Returns the channel count of the given port, after checking it is consistent with the port's
+/// type and the given spatial information.
+///
+/// Ports that are neither mono, stereo, surround nor ambisonic are not checked.
+///
+/// # Errors
+///
+/// This returns a [`ChannelMismatch`] if the port's channel count is inconsistent.
+pub fn expected_channel_count(
+    port: &AudioPortInfo,
+    spatial: Option<SpatialLayout>,
+) -> Result<u32, ChannelMismatch> {
+    let channel_count = port.channel_count;
+    let port_type = port.port_type;
+
+    let fixed_count = if port_type == Some(AudioPortType::MONO) {
+        Some(1)
+    } else if port_type == Some(AudioPortType::STEREO) {
+        Some(2)
+    } else {
+        None
+    };
+
+    if let Some(expected) = fixed_count {
+        if spatial.is_some() {
+            return Err(ChannelMismatch::UnexpectedSpatialLayout);
+        }
+
+        if channel_count != expected {
+            return Err(ChannelMismatch::PortType {
+                expected,
+                channel_count,
+            });
+        }
+
+        return Ok(channel_count);
+    }
+
+    if port_type == Some(AudioPortType::AMBISONIC) {
+        if ambisonic_order(channel_count).is_none() {
+            return Err(ChannelMismatch::InvalidAmbisonicChannelCount { channel_count });
+        }
+
+        return match spatial {
+            None => Ok(channel_count),
+            Some(SpatialLayout::Ambisonic { order })
+                if ambisonic_channel_count(order) == Some(channel_count) =>
+            {
+                Ok(channel_count)
+            }
+            Some(SpatialLayout::Ambisonic { order }) => Err(ChannelMismatch::AmbisonicOrder {
+                order,
+                channel_count,
+            }),
+            Some(SpatialLayout::Surround { .. }) => Err(ChannelMismatch::UnexpectedSpatialLayout),
+        };
+    }
+
+    match spatial {
+        None => Ok(channel_count),
+        Some(SpatialLayout::Surround { channel_map })
+            if port_type == Some(AudioPortType::SURROUND) =>
+        {
+            if channel_map.len() == channel_count as usize {
+                Ok(channel_count)
+            } else {
+                Err(ChannelMismatch::ChannelMap {
+                    map_len: channel_map.len(),
+                    channel_count,
+                })
+            }
+        }
+        Some(_) => Err(ChannelMismatch::UnexpectedSpatialLayout),
+    }
+}
+
+/// The validated channel counts of all the input and output ports of a plugin.
+///
+/// Hosts typically compute this right before activating a plugin (using `PortChannelCounts::scan`
+/// with the `clack-host` feature), and then use it to check the buffers passed to the plugin
+/// before each `process` call. Plugins can also use it to check the buffers they are given (using
+/// `PortChannelCounts::check_audio` with the `clack-plugin` feature).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PortChannelCounts {
+    inputs: Vec<u32>,
+    outputs: Vec<u32>,
+}
+
+impl PortChannelCounts {
+    /// Validates the given input and output ports, and collects their channel counts.
+    ///
+    /// The `spatial` closure is called with the direction (`true` for inputs) and the index of each
+    /// port, and returns its spatial information, if any.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ChannelLayoutError`] for the first port whose channel count is
+    /// inconsistent with its type or spatial information.
+    pub fn from_ports<'s>(
+        inputs: &[AudioPortInfo],
+        outputs: &[AudioPortInfo],
+        mut spatial: impl FnMut(bool, u32) -> Option<SpatialLayout<'s>>,
+    ) -> Result<Self, ChannelLayoutError> {
+        let mut collect = |ports: &[AudioPortInfo], is_input: bool| {
+            ports
+                .iter()
+                .enumerate()
+                .map(|(index, port)| {
+                    let port_index = index as u32;
+                    expected_channel_count(port, spatial(is_input, port_index)).map_err(
+                        |mismatch| ChannelLayoutError {
+                            is_input,
+                            port_index,
+                            mismatch,
+                        },
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(Self {
+            inputs: collect(inputs, true)?,
+            outputs: collect(outputs, false)?,
+        })
+    }
+
+    #[cfg(feature = "clack-host")]
+    #[inline]
+    pub(crate) fn from_channel_counts(inputs: Vec<u32>, outputs: Vec<u32>) -> Self {
+        Self { inputs, outputs }
+    }
+
+    /// Returns the channel count of each port, for the given direction.
+    #[inline]
+    pub fn channel_counts(&self, is_input: bool) -> &[u32] {
+        if is_input {
+            &self.inputs
+        } else {
+            &self.outputs
+        }
+    }
+
+    /// Returns the total number of channels of all ports, for the given direction.
+    ///
+    /// This is useful to pre-allocate the right amount of buffers.
+    #[inline]
+    pub fn total_channel_count(&self, is_input: bool) -> u32 {
+        self.channel_counts(is_input).iter().sum()
+    }
+
+    /// Checks that the given port buffers match these channel counts, for the given direction.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ChannelLayoutError`] if the number of ports differ, or if any port buffer
+    /// doesn't have the expected channel count.
+    pub fn check_buffers(
+        &self,
+        is_input: bool,
+        buffers: impl IntoIterator<Item = AudioPortProcessingInfo>,
+    ) -> Result<(), ChannelLayoutError> {
+        let expected_counts = self.channel_counts(is_input);
+        let error = |port_index: usize, mismatch| ChannelLayoutError {
+            is_input,
+            port_index: port_index as u32,
+            mismatch,
+        };
+
+        let mut actual_port_count = 0;
+        for (port_index, buffer) in buffers.into_iter().enumerate() {
+            actual_port_count += 1;
+
+            let Some(&expected) = expected_counts.get(port_index) else {
+                continue;
+            };
+
+            if buffer.channel_count() != expected {
+                return Err(error(
+                    port_index,
+                    ChannelMismatch::Buffer {
+                        expected,
+                        actual: buffer.channel_count(),
+                    },
+                ));
+            }
+        }
+
+        if actual_port_count != expected_counts.len() {
+            return Err(error(
+                actual_port_count.min(expected_counts.len()),
+                ChannelMismatch::PortCount {
+                    expected: expected_counts.len(),
+                    actual: actual_port_count,
+                },
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::utils::ClapId;
+    use clap_sys::audio_buffer::clap_audio_buffer;
+    use core::ptr::null_mut;
+
+    fn port(channel_count: u32, port_type: AudioPortType<'static>) -> AudioPortInfo<'static> {
+        AudioPortInfo {
+            channel_count,
+            port_type: Some(port_type),
+            ..AudioPortInfo::mono(ClapId::new(0), b"port")
+        }
+    }
+
+    fn buffer(channel_count: u32) -> AudioPortProcessingInfo {
+        AudioPortProcessingInfo::from_raw(&clap_audio_buffer {
+            data32: null_mut(),
+            data64: null_mut(),
+            channel_count,
+            latency: 0,
+            constant_mask: 0,
+        })
+    }
+
+    #[test]
+    fn ambisonic_orders_match_channel_counts() {
+        assert_eq!(ambisonic_channel_count(0), Some(1));
+        assert_eq!(ambisonic_channel_count(1), Some(4));
+        assert_eq!(ambisonic_channel_count(3), Some(16));
+        assert_eq!(ambisonic_channel_count(u32::MAX), None);
+
+        assert_eq!(ambisonic_order(16), Some(3));
+        assert_eq!(ambisonic_order(9), Some(2));
+        assert_eq!(ambisonic_order(8), None);
+        assert_eq!(ambisonic_order(0), None);
+        assert_eq!(ambisonic_order(u32::MAX), None);
+        assert_eq!(ambisonic_order(65535 * 65535), Some(65534));
+    }
+
+    #[test]
+    fn port_types_are_checked() {
+        use ChannelMismatch::*;
+
+        assert_eq!(
+            expected_channel_count(&port(1, AudioPortType::MONO), None),
+            Ok(1)
+        );
+        assert_eq!(
+            expected_channel_count(&port(1, AudioPortType::STEREO), None),
+            Err(PortType {
+                expected: 2,
+                channel_count: 1
+            })
+        );
+
+        let map = [0, 1, 2, 3, 4, 5];
+        let surround = SpatialLayout::Surround { channel_map: &map };
+        assert_eq!(
+            expected_channel_count(&port(6, AudioPortType::SURROUND), Some(surround)),
+            Ok(6)
+        );
+        assert_eq!(
+            expected_channel_count(&port(8, AudioPortType::SURROUND), Some(surround)),
+            Err(ChannelMap {
+                map_len: 6,
+                channel_count: 8
+            })
+        );
+
+        let first_order = SpatialLayout::Ambisonic { order: 1 };
+        assert_eq!(
+            expected_channel_count(&port(4, AudioPortType::AMBISONIC), Some(first_order)),
+            Ok(4)
+        );
+        assert_eq!(
+            expected_channel_count(&port(9, AudioPortType::AMBISONIC), Some(first_order)),
+            Err(AmbisonicOrder {
+                order: 1,
+                channel_count: 9
+            })
+        );
+        assert_eq!(
+            expected_channel_count(&port(5, AudioPortType::AMBISONIC), None),
+            Err(InvalidAmbisonicChannelCount { channel_count: 5 })
+        );
+        assert_eq!(
+            expected_channel_count(&port(4, AudioPortType::AMBISONIC), Some(surround)),
+            Err(UnexpectedSpatialLayout)
+        );
+    }
+
+    #[test]
+    fn buffers_are_checked_against_ports() {
+        let inputs = [
+            port(2, AudioPortType::STEREO),
+            port(4, AudioPortType::AMBISONIC),
+        ];
+        let outputs = [port(1, AudioPortType::MONO)];
+
+        let counts = PortChannelCounts::from_ports(&inputs, &outputs, |is_input, index| {
+            (is_input && index == 1).then_some(SpatialLayout::Ambisonic { order: 1 })
+        })
+        .unwrap();
+
+        assert_eq!(counts.channel_counts(true), [2, 4]);
+        assert_eq!(counts.total_channel_count(true), 6);
+
+        assert_eq!(counts.check_buffers(true, [buffer(2), buffer(4)]), Ok(()));
+        assert_eq!(counts.check_buffers(false, [buffer(1)]), Ok(()));
+
+        assert_eq!(
+            counts.check_buffers(true, [buffer(2), buffer(2)]),
+            Err(ChannelLayoutError {
+                is_input: true,
+                port_index: 1,
+                mismatch: ChannelMismatch::Buffer {
+                    expected: 4,
+                    actual: 2
+                }
+            })
+        );
+        assert_eq!(
+            counts.check_buffers(false, []),
+            Err(ChannelLayoutError {
+                is_input: false,
+                port_index: 0,
+                mismatch: ChannelMismatch::PortCount {
+                    expected: 1,
+                    actual: 0
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_ports_are_reported() {
+        let outputs = [
+            port(2, AudioPortType::STEREO),
+            port(6, AudioPortType::SURROUND),
+        ];
+        let map = [0, 1, 2, 3];
+
+        let error = PortChannelCounts::from_ports(&[], &outputs, |_, index| {
+            (index == 1).then_some(SpatialLayout::Surround { channel_map: &map })
+        })
+        .unwrap_err();
+
+        assert!(!error.is_input);
+        assert_eq!(error.port_index, 1);
+        assert_eq!(
+            error.to_string(),
+            "Invalid channel layout on output port #1: channel map has 4 entries, but port has 6 channels"
+        );
+    }
+}
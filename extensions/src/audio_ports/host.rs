@@ -1,5 +1,7 @@
+use super::channels::*;
 use super::*;
 use clack_host::extensions::prelude::*;
+use clack_host::process::audio_buffers::{InputAudioBuffers, OutputAudioBuffers};
 use core::mem::MaybeUninit;
 
 #[derive(Clone)]
@@ -58,6 +60,66 @@ impl PluginAudioPorts {
     }
 }
 
+impl PortChannelCounts {
+    /// Queries all the audio ports of the given plugin, validates them, and collects their
+    /// channel counts.
+    ///
+    /// This is meant to be called by hosts right before activating the plugin, so that invalid
+    /// port layouts are caught before the plugin is ever given any buffer. The `spatial` closure
+    /// is called with the direction (`true` for inputs) and the index of each port, and returns
+    /// its spatial information, as retrieved from the `surround` or `ambisonic` extensions.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ChannelLayoutError`] for the first port whose information could not be
+    /// retrieved, or whose channel count is inconsistent with its type or spatial information.
+    pub fn scan<'s>(
+        plugin: &mut PluginMainThreadHandle,
+        audio_ports: &PluginAudioPorts,
+        mut spatial: impl FnMut(bool, u32) -> Option<SpatialLayout<'s>>,
+    ) -> Result<Self, ChannelLayoutError> {
+        let mut buffer = AudioPortInfoBuffer::new();
+        let mut scan_ports = |is_input: bool| {
+            (0..audio_ports.count(plugin, is_input))
+                .map(|port_index| {
+                    let error = |mismatch| ChannelLayoutError {
+                        is_input,
+                        port_index,
+                        mismatch,
+                    };
+
+                    let info = audio_ports
+                        .get(plugin, port_index, is_input, &mut buffer)
+                        .ok_or(error(ChannelMismatch::UnavailablePortInfo))?;
+
+                    expected_channel_count(&info, spatial(is_input, port_index)).map_err(error)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let inputs = scan_ports(true)?;
+        let outputs = scan_ports(false)?;
+
+        Ok(Self::from_channel_counts(inputs, outputs))
+    }
+
+    /// Checks that the given input and output buffers match these channel counts.
+    ///
+    /// This should be called before passing the buffers to the plugin's `process` method.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ChannelLayoutError`] for the first port buffer that doesn't match.
+    pub fn check_audio_buffers(
+        &self,
+        inputs: &InputAudioBuffers,
+        outputs: &OutputAudioBuffers,
+    ) -> Result<(), ChannelLayoutError> {
+        self.check_buffers(true, inputs.port_infos())?;
+        self.check_buffers(false, outputs.port_infos())
+    }
+}
+
 pub trait HostAudioPortsImpl {
     fn is_rescan_flag_supported(&self, flag: RescanType) -> bool;
     fn rescan(&mut self, flag: RescanType);
@@ -83,12 +145,16 @@ unsafe extern "C" fn is_rescan_flag_supported<H: HostHandlers>(
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostAudioPortsImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
-        Ok(host
-            .main_thread()
-            .as_ref()
-            .is_rescan_flag_supported(RescanType::from_bits_truncate(flag)))
-    })
+    HostWrapper::<H>::handle_main_thread(
+        host,
+        "clap_host_audio_ports.is_rescan_flag_supported",
+        |host| {
+            Ok(host
+                .main_thread()
+                .as_ref()
+                .is_rescan_flag_supported(RescanType::from_bits_truncate(flag)))
+        },
+    )
     .unwrap_or(false)
 }
 
@@ -97,7 +163,7 @@ unsafe extern "C" fn rescan<H: HostHandlers>(host: *const clap_host, flag: u32)
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostAudioPortsImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_audio_ports.rescan", |host| {
         host.main_thread()
             .as_mut()
             .rescan(RescanType::from_bits_truncate(flag));
@@ -0,0 +1,477 @@
+//! Labels for the individual channels of audio ports, e.g. to display routing matrices.
+//!
+//! CLAP only gives a name to each audio port as a whole. [`ChannelLabels`] derives a label for
+//! each of its channels, from the following sources, in order of priority:
+//!
+//! 1. Custom labels declared by the plugin (see below);
+//! 2. The channel map of surround ports, e.g. `L`, `R`, `C`, `LFE`;
+//! 3. The ambisonic channel number (ACN) of ambisonic ports;
+//! 4. The port type, for mono and stereo ports;
+//! 5. The channel number, e.g. `Ch 1`, as a fallback.
+//!
+//! Every label has a short and a long form (e.g. `L` and `Front Left`). Both forms only use
+//! standard abbreviations and English terms, which hosts may translate if needed.
+//!
+//! # Custom channel labels
+//!
+//! There is no CLAP extension for plugins to name individual channels. Instead, plugins can use
+//! the following convention: the labels of all the port's channels are appended to the port's
+//! name, between square brackets, and separated by `|` characters. For instance, a 3-channel port
+//! named `Drums [Kick|Snare|Hat]` is displayed as `Drums`, with channels labeled `Kick`, `Snare`
+//! and `Hat`.
+//!
+//! The suffix is only understood as channel labels if it has exactly as many labels as the port
+//! has channels. Hosts that don't know about this convention simply display the full name, which
+//! is still meaningful to users.
+//!
+//! Plugins using a static port layout can declare custom labels with
+//! [`AudioPortLayout::with_channel_labels`](super::AudioPortLayout::with_channel_labels), which
+//! follows this convention.
+
+use super::channels::SpatialLayout;
+use super::{AudioPortInfo, AudioPortType};
+use clack_common::utils::ClapId;
+use clap_sys::ext::draft::surround::*;
+
+/// The label of a single channel, in both short and long forms.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelLabel {
+    /// A short form of the label, e.g. `L` or `Ch 3`, for narrow displays.
+    pub short: String,
+    /// A long form of the label, e.g. `Front Left` or `Channel 3`.
+    pub long: String,
+}
+
+impl ChannelLabel {
+    #[inline]
+    fn new(short: impl Into<String>, long: impl Into<String>) -> Self {
+        Self {
+            short: short.into(),
+            long: long.into(),
+        }
+    }
+
+    fn fallback(channel_index: usize) -> Self {
+        let number = channel_index + 1;
+        Self::new(format!("Ch {number}"), format!("Channel {number}"))
+    }
+
+    fn surround(position: u8) -> Option<Self> {
+        let (short, long) = match position as u32 {
+            CLAP_SURROUND_FL => ("L", "Front Left"),
+            CLAP_SURROUND_FR => ("R", "Front Right"),
+            CLAP_SURROUND_FC => ("C", "Front Center"),
+            CLAP_SURROUND_LFE => ("LFE", "Low Frequency"),
+            CLAP_SURROUND_BL => ("BL", "Back Left"),
+            CLAP_SURROUND_BR => ("BR", "Back Right"),
+            CLAP_SURROUND_FLC => ("FLC", "Front Left of Center"),
+            CLAP_SURROUND_FRC => ("FRC", "Front Right of Center"),
+            CLAP_SURROUND_BC => ("BC", "Back Center"),
+            CLAP_SURROUND_SL => ("SL", "Side Left"),
+            CLAP_SURROUND_SR => ("SR", "Side Right"),
+            CLAP_SURROUND_TC => ("TC", "Top Center"),
+            CLAP_SURROUND_TFL => ("TFL", "Top Front Left"),
+            CLAP_SURROUND_TFC => ("TFC", "Top Front Center"),
+            CLAP_SURROUND_TFR => ("TFR", "Top Front Right"),
+            CLAP_SURROUND_TBL => ("TBL", "Top Back Left"),
+            CLAP_SURROUND_TBC => ("TBC", "Top Back Center"),
+            CLAP_SURROUND_TBR => ("TBR", "Top Back Right"),
+            _ => return None,
+        };
+
+        Some(Self::new(short, long))
+    }
+
+    fn ambisonic(acn: usize) -> Self {
+        // ACN = l * (l + 1) + m, with -l <= m <= l.
+        let mut order = 0;
+        while (order + 1) * (order + 1) <= acn {
+            order += 1;
+        }
+
+        let degree = acn as isize - (order * (order + 1)) as isize;
+
+        Self::new(
+            format!("ACN {acn}"),
+            format!("Ambisonic {acn} (order {order}, degree {degree})"),
+        )
+    }
+}
+
+/// The labels of all the channels of an audio port.
+///
+/// See the [module docs](self) for how labels are derived.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::ClapId;
+/// use clack_extensions::audio_ports::{labels::ChannelLabels, AudioPortInfo, AudioPortType};
+///
+/// let port = AudioPortInfo::stereo(ClapId::new(0), b"Main");
+/// let labels = ChannelLabels::new(&port, None);
+///
+/// assert_eq!(labels.port_name(), "Main");
+/// assert_eq!(labels.short_labels().collect::<Vec<_>>(), ["L", "R"]);
+///
+/// let port = AudioPortInfo {
+///     channel_count: 3,
+///     port_type: None,
+///     ..AudioPortInfo::mono(ClapId::new(1), b"Drums [Kick|Snare|Hat]")
+/// };
+/// let labels = ChannelLabels::new(&port, None);
+///
+/// assert_eq!(labels.port_name(), "Drums");
+/// assert_eq!(labels.get(1).unwrap().long, "Snare");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelLabels {
+    port_name: String,
+    labels: Vec<ChannelLabel>,
+}
+
+impl ChannelLabels {
+    /// Derives the channel labels of the given port, using the given spatial information if the
+    /// port is a surround or ambisonic port.
+    ///
+    /// This always returns exactly one label per channel of the port, in channel order. Spatial
+    /// information that doesn't match the port's type or channel count is ignored.
+    pub fn new(port: &AudioPortInfo, spatial: Option<SpatialLayout>) -> Self {
+        let channel_count = port.channel_count as usize;
+
+        if let Some((port_name, custom)) = split_custom_labels(port.name, channel_count) {
+            return Self {
+                port_name: String::from_utf8_lossy(port_name).into_owned(),
+                labels: custom
+                    .map(|label| {
+                        let label = String::from_utf8_lossy(label);
+                        ChannelLabel::new(label.clone(), label)
+                    })
+                    .collect(),
+            };
+        }
+
+        let labels = (0..channel_count)
+            .map(|index| {
+                Self::derived_label(port, spatial, index)
+                    .unwrap_or_else(|| ChannelLabel::fallback(index))
+            })
+            .collect();
+
+        Self {
+            port_name: String::from_utf8_lossy(port.name).into_owned(),
+            labels,
+        }
+    }
+
+    fn derived_label(
+        port: &AudioPortInfo,
+        spatial: Option<SpatialLayout>,
+        index: usize,
+    ) -> Option<ChannelLabel> {
+        let port_type = port.port_type?;
+
+        match spatial {
+            Some(SpatialLayout::Surround { channel_map })
+                if port_type == AudioPortType::SURROUND
+                    && channel_map.len() == port.channel_count as usize =>
+            {
+                return ChannelLabel::surround(channel_map[index]);
+            }
+            _ => {}
+        }
+
+        if port_type == AudioPortType::AMBISONIC {
+            return Some(ChannelLabel::ambisonic(index));
+        }
+
+        if port_type == AudioPortType::MONO && port.channel_count == 1 {
+            return Some(ChannelLabel::new("M", "Mono"));
+        }
+
+        if port_type == AudioPortType::STEREO && port.channel_count == 2 {
+            return Some(if index == 0 {
+                ChannelLabel::new("L", "Left")
+            } else {
+                ChannelLabel::new("R", "Right")
+            });
+        }
+
+        None
+    }
+
+    /// Returns the name of the port, without the custom channel labels it may have carried.
+    #[inline]
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    /// Returns the label of the channel at the given index, if it exists.
+    #[inline]
+    pub fn get(&self, channel_index: u32) -> Option<&ChannelLabel> {
+        self.labels.get(channel_index as usize)
+    }
+
+    /// Returns the number of channels of the port.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns `true` if the port has no channels.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Returns an iterator over the labels of all the channels, in channel order.
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, ChannelLabel> {
+        self.labels.iter()
+    }
+
+    /// Returns an iterator over the short labels of all the channels, in channel order.
+    #[inline]
+    pub fn short_labels(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.labels.iter().map(|l| l.short.as_str())
+    }
+
+    /// Returns an iterator over the long labels of all the channels, in channel order.
+    #[inline]
+    pub fn long_labels(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.labels.iter().map(|l| l.long.as_str())
+    }
+}
+
+impl<'a> IntoIterator for &'a ChannelLabels {
+    type Item = &'a ChannelLabel;
+    type IntoIter = core::slice::Iter<'a, ChannelLabel>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Custom labels for the channels of a port of an
+/// [`AudioPortLayout`](super::AudioPortLayout).
+///
+/// See [`AudioPortLayout::with_channel_labels`](super::AudioPortLayout::with_channel_labels).
+#[derive(Copy, Clone, Debug)]
+pub struct CustomChannelLabels {
+    /// Whether the labelled port is an input port.
+    pub is_input: bool,
+    /// The ID of the labelled port.
+    pub port_id: ClapId,
+    /// The labels of each of the port's channels, in channel order.
+    pub labels: &'static [&'static [u8]],
+}
+
+/// The separator between the port name and its custom channel labels.
+const LABELS_START: &[u8] = b" [";
+/// The separator between two custom channel labels.
+const LABELS_SEPARATOR: u8 = b'|';
+/// The end of the custom channel labels.
+const LABELS_END: u8 = b']';
+
+/// Splits the given port name into the actual name and its custom channel labels, if it has
+/// exactly the given number of them.
+fn split_custom_labels(
+    name: &[u8],
+    channel_count: usize,
+) -> Option<(&[u8], impl Iterator<Item = &[u8]>)> {
+    let inner = name.strip_suffix(&[LABELS_END])?;
+    let start = inner
+        .windows(LABELS_START.len())
+        .rposition(|w| w == LABELS_START)?;
+
+    let (port_name, labels) = (&inner[..start], &inner[start + LABELS_START.len()..]);
+
+    if channel_count == 0
+        || labels.contains(&b'[')
+        || labels.split(|b| *b == LABELS_SEPARATOR).count() != channel_count
+        || labels
+            .split(|b| *b == LABELS_SEPARATOR)
+            .any(<[u8]>::is_empty)
+    {
+        return None;
+    }
+
+    Some((port_name, labels.split(|b| *b == LABELS_SEPARATOR)))
+}
+
+/// Appends the given custom channel labels to the given port name.
+#[cfg(feature = "clack-plugin")]
+pub(crate) fn append_custom_labels(name: &[u8], labels: &[&[u8]]) -> Vec<u8> {
+    let mut full_name = Vec::with_capacity(encoded_len(name, labels));
+    full_name.extend_from_slice(name);
+    full_name.extend_from_slice(LABELS_START);
+
+    for (index, label) in labels.iter().enumerate() {
+        if index > 0 {
+            full_name.push(LABELS_SEPARATOR);
+        }
+        full_name.extend_from_slice(label);
+    }
+
+    full_name.push(LABELS_END);
+    full_name
+}
+
+/// Returns the length of the given port name, once the given custom channel labels are appended
+/// to it.
+pub(crate) const fn encoded_len(name: &[u8], labels: &[&[u8]]) -> usize {
+    let mut len = name.len() + LABELS_START.len() + labels.len();
+
+    let mut i = 0;
+    while i < labels.len() {
+        len += labels[i].len();
+        i += 1;
+    }
+
+    len
+}
+
+/// Checks the given custom channel label can be appended to a port name.
+///
+/// # Panics
+///
+/// This panics if the label is empty, or contains a NUL byte or any of the characters used by the
+/// convention. When used in a const context, this results in a compile-time error instead.
+pub(crate) const fn validate_label(label: &[u8]) {
+    assert!(!label.is_empty(), "Channel labels must not be empty");
+
+    let mut i = 0;
+    while i < label.len() {
+        let b = label[i];
+        assert!(
+            b != 0 && b != LABELS_SEPARATOR && b != LABELS_END && b != b'[',
+            "Channel labels must not contain NUL bytes, '|', '[' or ']'"
+        );
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn port(
+        name: &'static [u8],
+        channel_count: u32,
+        port_type: Option<AudioPortType<'static>>,
+    ) -> AudioPortInfo<'static> {
+        AudioPortInfo {
+            channel_count,
+            port_type,
+            ..AudioPortInfo::mono(ClapId::new(0), name)
+        }
+    }
+
+    #[test]
+    fn labels_are_derived_from_port_types() {
+        let mono = ChannelLabels::new(&port(b"In", 1, Some(AudioPortType::MONO)), None);
+        assert_eq!(mono.long_labels().collect::<Vec<_>>(), ["Mono"]);
+
+        let stereo = ChannelLabels::new(&port(b"In", 2, Some(AudioPortType::STEREO)), None);
+        assert_eq!(stereo.short_labels().collect::<Vec<_>>(), ["L", "R"]);
+
+        // Mismatched and unknown port types fall back to channel numbers.
+        let broken = ChannelLabels::new(&port(b"In", 3, Some(AudioPortType::STEREO)), None);
+        assert_eq!(
+            broken.short_labels().collect::<Vec<_>>(),
+            ["Ch 1", "Ch 2", "Ch 3"]
+        );
+
+        let untyped = ChannelLabels::new(&port(b"In", 2, None), None);
+        assert_eq!(
+            untyped.long_labels().collect::<Vec<_>>(),
+            ["Channel 1", "Channel 2"]
+        );
+    }
+
+    #[test]
+    fn labels_are_derived_from_spatial_layouts() {
+        let map = [
+            CLAP_SURROUND_FL as u8,
+            CLAP_SURROUND_FR as u8,
+            CLAP_SURROUND_FC as u8,
+            CLAP_SURROUND_LFE as u8,
+            CLAP_SURROUND_SL as u8,
+            200,
+        ];
+        let surround = SpatialLayout::Surround { channel_map: &map };
+
+        let labels = ChannelLabels::new(
+            &port(b"5.1", 6, Some(AudioPortType::SURROUND)),
+            Some(surround),
+        );
+        assert_eq!(
+            labels.short_labels().collect::<Vec<_>>(),
+            ["L", "R", "C", "LFE", "SL", "Ch 6"]
+        );
+
+        // A channel map that doesn't match the channel count is ignored.
+        let labels = ChannelLabels::new(
+            &port(b"Quad", 4, Some(AudioPortType::SURROUND)),
+            Some(surround),
+        );
+        assert_eq!(labels.get(0).unwrap().short, "Ch 1");
+
+        let labels = ChannelLabels::new(&port(b"FOA", 4, Some(AudioPortType::AMBISONIC)), None);
+        assert_eq!(
+            labels.short_labels().collect::<Vec<_>>(),
+            ["ACN 0", "ACN 1", "ACN 2", "ACN 3"]
+        );
+        assert_eq!(
+            labels.get(1).unwrap().long,
+            "Ambisonic 1 (order 1, degree -1)"
+        );
+        assert_eq!(
+            labels.get(3).unwrap().long,
+            "Ambisonic 3 (order 1, degree 1)"
+        );
+    }
+
+    #[test]
+    fn custom_labels_are_parsed_from_port_names() {
+        let labels = ChannelLabels::new(
+            &port(b"Drums [Kick|Snare|Hat]", 3, Some(AudioPortType::SURROUND)),
+            None,
+        );
+        assert_eq!(labels.port_name(), "Drums");
+        assert_eq!(
+            labels.short_labels().collect::<Vec<_>>(),
+            ["Kick", "Snare", "Hat"]
+        );
+        assert_eq!(labels.len(), 3);
+
+        // Suffixes that don't match the channel count are part of the name.
+        for name in [
+            &b"Drums [Kick|Snare]"[..],
+            b"Drums [Kick||Hat]",
+            b"Drums [Kick|Snare|Hat",
+            b"[Kick|Snare|Hat]",
+        ] {
+            let labels = ChannelLabels::new(&port(name, 3, None), None);
+            assert_eq!(labels.port_name().as_bytes(), name);
+            assert_eq!(labels.get(0).unwrap().short, "Ch 1");
+        }
+    }
+
+    #[cfg(feature = "clack-plugin")]
+    #[test]
+    fn custom_labels_round_trip() {
+        let labels: [&[u8]; 2] = [b"Dry", b"Wet"];
+        let name = append_custom_labels(b"Out", &labels);
+
+        assert_eq!(name, b"Out [Dry|Wet]");
+        assert_eq!(name.len(), encoded_len(b"Out", &labels));
+
+        let (port_name, labels) = split_custom_labels(&name, 2).unwrap();
+        assert_eq!(port_name, b"Out");
+        assert_eq!(labels.collect::<Vec<_>>(), [&b"Dry"[..], b"Wet"]);
+    }
+}
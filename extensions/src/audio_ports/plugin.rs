@@ -1,6 +1,11 @@
-use crate::audio_ports::{AudioPortInfo, HostAudioPorts, PluginAudioPorts, RescanType};
+use crate::audio_ports::channels::{ChannelLayoutError, PortChannelCounts, SpatialLayout};
+use crate::audio_ports::{
+    AudioPortInfo, AudioPortLayout, AudioPortLayoutProvider, HostAudioPorts, PluginAudioPorts,
+    RescanType, StaticAudioPorts,
+};
 use crate::utils::write_to_array_buf;
 use clack_plugin::extensions::prelude::*;
+use clack_plugin::process::{Audio, AudioPortProcessingInfo};
 use clap_sys::ext::audio_ports::{clap_audio_port_info, clap_plugin_audio_ports};
 use std::mem::MaybeUninit;
 use std::ptr::addr_of_mut;
@@ -16,7 +21,7 @@ impl AudioPortInfoWriter<'_> {
     /// The user must ensure the provided pointer is aligned and points to a valid allocation.
     /// However, it doesn't have to be initialized.
     #[inline]
-    unsafe fn from_raw(raw: *mut clap_audio_port_info) -> Self {
+    pub(crate) unsafe fn from_raw(raw: *mut clap_audio_port_info) -> Self {
         Self {
             buf: &mut *raw.cast(),
             is_set: false,
@@ -52,6 +57,19 @@ impl AudioPortInfoWriter<'_> {
 
         self.is_set = true;
     }
+
+    /// Returns the ID of the port that was written, or [`None`] if nothing was written yet.
+    #[inline]
+    #[cfg(feature = "extensible-audio-ports")]
+    pub(crate) fn written_id(&self) -> Option<ClapId> {
+        if !self.is_set {
+            return None;
+        }
+
+        // SAFETY: the ID field was initialized by `set`.
+        let id = unsafe { core::ptr::addr_of!((*self.buf.as_ptr()).id).read() };
+        ClapId::from_raw(id)
+    }
 }
 
 pub trait PluginAudioPortsImpl {
@@ -76,8 +94,10 @@ unsafe extern "C" fn count<P: Plugin>(plugin: *const clap_plugin, is_input: bool
 where
     for<'a> P::MainThread<'a>: PluginAudioPortsImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| Ok(p.main_thread().as_mut().count(is_input)))
-        .unwrap_or(0)
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports.count", |p| {
+        Ok(p.main_thread().as_mut().count(is_input))
+    })
+    .unwrap_or(0)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -90,7 +110,7 @@ unsafe extern "C" fn get<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginAudioPortsImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports.get", |p| {
         if info.is_null() {
             return Err(PluginWrapperError::NulPtr("clap_audio_port_info"));
         };
@@ -102,6 +122,105 @@ where
     .unwrap_or(false)
 }
 
+// SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+unsafe impl<P: Plugin, L: AudioPortLayoutProvider> ExtensionImplementation<P>
+    for StaticAudioPorts<L>
+{
+    const IMPLEMENTATION: RawExtensionImplementation =
+        RawExtensionImplementation::new(&clap_plugin_audio_ports {
+            count: Some(static_count::<P, L>),
+            get: Some(static_get::<P, L>),
+        });
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn static_count<P: Plugin, L: AudioPortLayoutProvider>(
+    plugin: *const clap_plugin,
+    is_input: bool,
+) -> u32 {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports.count", |_| {
+        Ok(L::AUDIO_PORTS.count(is_input))
+    })
+    .unwrap_or(0)
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn static_get<P: Plugin, L: AudioPortLayoutProvider>(
+    plugin: *const clap_plugin,
+    index: u32,
+    is_input: bool,
+    info: *mut clap_audio_port_info,
+) -> bool {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports.get", |_| {
+        if info.is_null() {
+            return Err(PluginWrapperError::NulPtr("clap_audio_port_info"));
+        };
+
+        let Some(port) = L::AUDIO_PORTS.get(index, is_input) else {
+            return Ok(false);
+        };
+
+        match L::AUDIO_PORTS.channel_labels(is_input, port.id) {
+            Some(labels) => {
+                let name = super::labels::append_custom_labels(port.name, labels);
+                AudioPortInfoWriter::from_raw(info).set(&AudioPortInfo {
+                    name: &name,
+                    ..*port
+                });
+            }
+            None => AudioPortInfoWriter::from_raw(info).set(port),
+        }
+
+        Ok(true)
+    })
+    .unwrap_or(false)
+}
+
+impl PortChannelCounts {
+    /// Checks that the given audio buffers match these channel counts.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ChannelLayoutError`] for the first port buffer that doesn't match.
+    pub fn check_audio(&self, audio: &Audio) -> Result<(), ChannelLayoutError> {
+        self.check_buffers(true, audio.input_ports_infos())?;
+        self.check_buffers(false, audio.output_ports_infos())
+    }
+}
+
+impl AudioPortLayout {
+    /// Validates the channel counts of all the ports of this layout, with the given spatial
+    /// information.
+    ///
+    /// See [`PortChannelCounts::from_ports`].
+    pub fn channel_counts<'s>(
+        &self,
+        spatial: impl FnMut(bool, u32) -> Option<SpatialLayout<'s>>,
+    ) -> Result<PortChannelCounts, ChannelLayoutError> {
+        PortChannelCounts::from_ports(self.ports(true), self.ports(false), spatial)
+    }
+
+    /// Returns `true` if the given audio buffers match this layout, i.e. if they have the same
+    /// number of input and output ports, with the same channel counts.
+    ///
+    /// This is useful to check the host actually follows the declared layout in `process`.
+    pub fn matches(&self, audio: &Audio) -> bool {
+        fn matches_ports(
+            ports: &[AudioPortInfo],
+            infos: impl ExactSizeIterator<Item = AudioPortProcessingInfo>,
+        ) -> bool {
+            ports.len() == infos.len()
+                && ports
+                    .iter()
+                    .zip(infos)
+                    .all(|(port, info)| port.channel_count == info.channel_count())
+        }
+
+        matches_ports(self.ports(true), audio.input_ports_infos())
+            && matches_ports(self.ports(false), audio.output_ports_infos())
+    }
+}
+
 impl HostAudioPorts {
     #[inline]
     pub fn is_rescan_flag_supported(&self, host: &HostMainThreadHandle, flag: RescanType) -> bool {
@@ -120,3 +239,23 @@ impl HostAudioPorts {
         }
     }
 }
+
+impl DynInterface for dyn PluginAudioPortsImpl {
+    type Target<'a> = dyn PluginAudioPortsImpl + 'a;
+}
+
+/// Forwards to the [`PluginAudioPortsImpl`] implementation provided by the dynamic plugin, if any.
+impl PluginAudioPortsImpl for DynPluginMainThread<'_> {
+    #[inline]
+    fn count(&mut self, is_input: bool) -> u32 {
+        self.interface::<dyn PluginAudioPortsImpl>()
+            .map_or(0, |ports| ports.count(is_input))
+    }
+
+    #[inline]
+    fn get(&mut self, index: u32, is_input: bool, writer: &mut AudioPortInfoWriter) {
+        if let Some(ports) = self.interface::<dyn PluginAudioPortsImpl>() {
+            ports.get(index, is_input, writer)
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! The raw port type identifiers defined by CLAP.
+//!
+//! These are the strings found in the `port_type` field of audio port information, used by both
+//! plugins when declaring ports and hosts when interpreting them. Most of the time, the
+//! [`AudioPortType`](super::AudioPortType) constants should be used instead.
+
+use std::ffi::CStr;
+
+/// A single channel of audio.
+pub const MONO: &CStr = clap_sys::ext::audio_ports::CLAP_PORT_MONO;
+
+/// Two channels of audio, left then right.
+pub const STEREO: &CStr = clap_sys::ext::audio_ports::CLAP_PORT_STEREO;
+
+/// Any number of channels, each mapped to a speaker position by the surround extension.
+pub const SURROUND: &CStr = clap_sys::ext::draft::surround::CLAP_PORT_SURROUND;
+
+/// Ambisonic channels, whose ordering and normalization are described by the ambisonic
+/// extension.
+pub const AMBISONIC: &CStr = clap_sys::ext::draft::ambisonic::CLAP_PORT_AMBISONIC;
+
+/// Control-voltage signals, such as modulation sources or gates, rather than audio.
+///
+/// CV ports are usually mono, and are never main ports.
+pub const CV: &CStr = clap_sys::ext::draft::cv::CLAP_PORT_CV;
@@ -0,0 +1,387 @@
+//! Owned snapshots of a plugin's audio port layout, to compare them across rescans.
+//!
+//! See the [`AudioPortLayoutSnapshot`] type for more information.
+
+use super::*;
+use crate::utils::diff_by_id;
+
+/// The information of a single audio port in an [`AudioPortLayoutSnapshot`].
+///
+/// Unlike [`AudioPortInfo`], this owns all of its data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioPortEntry {
+    /// The port's ID.
+    pub id: ClapId,
+    /// The port's name.
+    pub name: String,
+    /// The number of channels of the port.
+    pub channel_count: u32,
+    /// The port's flags.
+    pub flags: AudioPortFlags,
+    /// The port's type, if it has one.
+    pub port_type: Option<String>,
+    /// The ID of the port this port is paired with for in-place processing, if any.
+    pub in_place_pair: Option<ClapId>,
+}
+
+impl AudioPortEntry {
+    /// Copies the given port information.
+    pub fn from_info(info: &AudioPortInfo) -> Self {
+        Self {
+            id: info.id,
+            name: String::from_utf8_lossy(info.name).into_owned(),
+            channel_count: info.channel_count,
+            flags: info.flags,
+            port_type: info.port_type.map(|t| t.0.to_string_lossy().into_owned()),
+            in_place_pair: info.in_place_pair,
+        }
+    }
+}
+
+/// A change in a plugin's audio ports between two [`AudioPortLayoutSnapshot`]s.
+///
+/// All variants carry whether the port is an input port, and the port's ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AudioPortChange {
+    /// A new port was added.
+    Added {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the new port.
+        id: ClapId,
+    },
+    /// A port was removed.
+    Removed {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the removed port.
+        id: ClapId,
+    },
+    /// A port's index changed.
+    Moved {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous index.
+        old: usize,
+        /// The new index.
+        new: usize,
+    },
+    /// A port's name changed.
+    Renamed {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous name.
+        old: String,
+        /// The new name.
+        new: String,
+    },
+    /// A port's flags changed.
+    FlagsChanged {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous flags.
+        old: AudioPortFlags,
+        /// The new flags.
+        new: AudioPortFlags,
+    },
+    /// A port's channel count changed.
+    ChannelCountChanged {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous channel count.
+        old: u32,
+        /// The new channel count.
+        new: u32,
+    },
+    /// A port's type changed.
+    PortTypeChanged {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous port type.
+        old: Option<String>,
+        /// The new port type.
+        new: Option<String>,
+    },
+    /// The port a port is paired with for in-place processing changed.
+    InPlacePairChanged {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous paired port.
+        old: Option<ClapId>,
+        /// The new paired port.
+        new: Option<ClapId>,
+    },
+}
+
+impl AudioPortChange {
+    /// Returns whether the port this change is about is an input port.
+    pub fn is_input(&self) -> bool {
+        match self {
+            Self::Added { is_input, .. }
+            | Self::Removed { is_input, .. }
+            | Self::Moved { is_input, .. }
+            | Self::Renamed { is_input, .. }
+            | Self::FlagsChanged { is_input, .. }
+            | Self::ChannelCountChanged { is_input, .. }
+            | Self::PortTypeChanged { is_input, .. }
+            | Self::InPlacePairChanged { is_input, .. } => *is_input,
+        }
+    }
+
+    /// Returns the ID of the port this change is about.
+    pub fn id(&self) -> ClapId {
+        match self {
+            Self::Added { id, .. }
+            | Self::Removed { id, .. }
+            | Self::Moved { id, .. }
+            | Self::Renamed { id, .. }
+            | Self::FlagsChanged { id, .. }
+            | Self::ChannelCountChanged { id, .. }
+            | Self::PortTypeChanged { id, .. }
+            | Self::InPlacePairChanged { id, .. } => *id,
+        }
+    }
+}
+
+/// An owned snapshot of a plugin's audio port layout.
+///
+/// Snapshots can be [captured](Self::capture) before and after an audio ports rescan, and then
+/// [compared](Self::diff) to list what actually changed, e.g. to only re-route the affected ports.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AudioPortLayoutSnapshot {
+    inputs: Vec<AudioPortEntry>,
+    outputs: Vec<AudioPortEntry>,
+}
+
+impl AudioPortLayoutSnapshot {
+    /// Creates a snapshot from the given input and output port entries, in the order the plugin
+    /// reports them.
+    #[inline]
+    pub fn from_entries(inputs: Vec<AudioPortEntry>, outputs: Vec<AudioPortEntry>) -> Self {
+        Self { inputs, outputs }
+    }
+
+    /// Queries and copies the information of all the audio ports of the given plugin.
+    ///
+    /// Ports whose information could not be retrieved are skipped.
+    #[cfg(feature = "clack-host")]
+    pub fn capture(
+        ports: &PluginAudioPorts,
+        plugin: &mut clack_host::prelude::PluginMainThreadHandle,
+    ) -> Self {
+        let mut buffer = AudioPortInfoBuffer::new();
+        let mut snapshot = Self::default();
+
+        for is_input in [true, false] {
+            let entries = if is_input {
+                &mut snapshot.inputs
+            } else {
+                &mut snapshot.outputs
+            };
+
+            for index in 0..ports.count(plugin, is_input) {
+                if let Some(info) = ports.get(plugin, index, is_input, &mut buffer) {
+                    entries.push(AudioPortEntry::from_info(&info));
+                }
+            }
+        }
+
+        snapshot
+    }
+
+    /// Returns the input ports of this snapshot.
+    #[inline]
+    pub fn inputs(&self) -> &[AudioPortEntry] {
+        &self.inputs
+    }
+
+    /// Returns the output ports of this snapshot.
+    #[inline]
+    pub fn outputs(&self) -> &[AudioPortEntry] {
+        &self.outputs
+    }
+
+    /// Lists all the changes between this snapshot and a more recent one.
+    ///
+    /// Changes to input ports are listed first. For each direction, removed ports are listed
+    /// first, then added ones, then the changes to the remaining ports, in their new order.
+    pub fn diff(&self, after: &Self) -> Vec<AudioPortChange> {
+        let mut changes = Vec::new();
+        diff_ports(&self.inputs, &after.inputs, true, &mut changes);
+        diff_ports(&self.outputs, &after.outputs, false, &mut changes);
+        changes
+    }
+}
+
+fn diff_ports(
+    old: &[AudioPortEntry],
+    new: &[AudioPortEntry],
+    is_input: bool,
+    changes: &mut Vec<AudioPortChange>,
+) {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+
+    diff_by_id(
+        old,
+        new,
+        |p| p.id,
+        |_, p| removed.push(AudioPortChange::Removed { is_input, id: p.id }),
+        |_, p| added.push(AudioPortChange::Added { is_input, id: p.id }),
+        |(old_index, old), (new_index, new)| {
+            let id = new.id;
+
+            if old_index != new_index {
+                changed.push(AudioPortChange::Moved {
+                    is_input,
+                    id,
+                    old: old_index,
+                    new: new_index,
+                });
+            }
+
+            if old.name != new.name {
+                changed.push(AudioPortChange::Renamed {
+                    is_input,
+                    id,
+                    old: old.name.clone(),
+                    new: new.name.clone(),
+                });
+            }
+
+            if old.flags != new.flags {
+                changed.push(AudioPortChange::FlagsChanged {
+                    is_input,
+                    id,
+                    old: old.flags,
+                    new: new.flags,
+                });
+            }
+
+            if old.channel_count != new.channel_count {
+                changed.push(AudioPortChange::ChannelCountChanged {
+                    is_input,
+                    id,
+                    old: old.channel_count,
+                    new: new.channel_count,
+                });
+            }
+
+            if old.port_type != new.port_type {
+                changed.push(AudioPortChange::PortTypeChanged {
+                    is_input,
+                    id,
+                    old: old.port_type.clone(),
+                    new: new.port_type.clone(),
+                });
+            }
+
+            if old.in_place_pair != new.in_place_pair {
+                changed.push(AudioPortChange::InPlacePairChanged {
+                    is_input,
+                    id,
+                    old: old.in_place_pair,
+                    new: new.in_place_pair,
+                });
+            }
+        },
+    );
+
+    changes.extend(removed);
+    changes.extend(added);
+    changes.extend(changed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn port(id: u32, name: &str) -> AudioPortEntry {
+        AudioPortEntry {
+            id: ClapId::new(id),
+            name: name.into(),
+            channel_count: 2,
+            flags: AudioPortFlags::empty(),
+            port_type: Some("stereo".into()),
+            in_place_pair: None,
+        }
+    }
+
+    #[test]
+    fn lists_all_changes() {
+        let before = AudioPortLayoutSnapshot::from_entries(
+            vec![port(1, "Main In"), port(2, "Sidechain")],
+            vec![port(1, "Main Out")],
+        );
+
+        let after = AudioPortLayoutSnapshot::from_entries(
+            vec![port(2, "Sidechain"), port(1, "Main In")],
+            vec![
+                AudioPortEntry {
+                    channel_count: 1,
+                    port_type: Some("mono".into()),
+                    ..port(1, "Out")
+                },
+                port(3, "Aux Out"),
+            ],
+        );
+
+        let id = ClapId::new;
+        assert_eq!(
+            before.diff(&after),
+            [
+                AudioPortChange::Moved {
+                    is_input: true,
+                    id: id(2),
+                    old: 1,
+                    new: 0
+                },
+                AudioPortChange::Moved {
+                    is_input: true,
+                    id: id(1),
+                    old: 0,
+                    new: 1
+                },
+                AudioPortChange::Added {
+                    is_input: false,
+                    id: id(3)
+                },
+                AudioPortChange::Renamed {
+                    is_input: false,
+                    id: id(1),
+                    old: "Main Out".into(),
+                    new: "Out".into()
+                },
+                AudioPortChange::ChannelCountChanged {
+                    is_input: false,
+                    id: id(1),
+                    old: 2,
+                    new: 1
+                },
+                AudioPortChange::PortTypeChanged {
+                    is_input: false,
+                    id: id(1),
+                    old: Some("stereo".into()),
+                    new: Some("mono".into())
+                },
+            ]
+        );
+
+        assert!(before.diff(&before).is_empty());
+    }
+}
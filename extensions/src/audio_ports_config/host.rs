@@ -118,7 +118,7 @@ unsafe extern "C" fn rescan<H: HostHandlers>(host: *const clap_host)
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostAudioPortsConfigImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_audio_ports_config.rescan", |host| {
         host.main_thread().as_mut().rescan();
 
         Ok(())
@@ -45,7 +45,10 @@ unsafe extern "C" fn count<P: Plugin>(plugin: *const clap_plugin) -> u32
 where
     for<'a> P::MainThread<'a>: PluginAudioPortsConfigImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| Ok(p.main_thread().as_mut().count())).unwrap_or(0)
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports_config.count", |p| {
+        Ok(p.main_thread().as_mut().count())
+    })
+    .unwrap_or(0)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -57,7 +60,7 @@ unsafe extern "C" fn get<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginAudioPortsConfigImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports_config.get", |p| {
         if config.is_null() {
             return Err(PluginWrapperError::NulPtr("clap_audio_ports_config"));
         };
@@ -74,7 +77,7 @@ unsafe extern "C" fn select<P: Plugin>(plugin: *const clap_plugin, config_id: u3
 where
     for<'a> P::MainThread<'a>: PluginAudioPortsConfigImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_audio_ports_config.select", |p| {
         if p.is_active() {
             return Err(PluginWrapperError::DeactivationRequiredForFunction(
                 "clap_plugin_audio_ports_config.select",
@@ -0,0 +1,137 @@
+//! A snapshot of the host extensions a plugin can rely on.
+//!
+//! Plugins often want to adapt to what the host supports: for instance, render voices on the
+//! host's thread pool if there is one and serially otherwise, or drive UI updates from a host
+//! timer if available and from the GUI's own idle loop otherwise.
+//!
+//! Instead of querying each extension everywhere it's needed, a plugin can capture a
+//! [`HostCapabilities`] snapshot once, when it's created, and keep it in its `Shared` type. Since
+//! the snapshot is [`Copy`] and thread-safe, it is then available to the main thread and the audio
+//! processor alike, and branching on it never calls back into the host.
+//!
+//! Every enabled extension has an accessor returning an [`Option`] of its host-side handle, which
+//! makes degradation paths read like regular [`Option`] combinators:
+//!
+//! ```
+//! use clack_extensions::capabilities::HostCapabilities;
+//! use clack_plugin::prelude::*;
+//!
+//! pub struct MySynthShared {
+//!     capabilities: HostCapabilities,
+//! }
+//!
+//! impl MySynthShared {
+//!     pub fn new(host: HostSharedHandle) -> Self {
+//!         Self {
+//!             capabilities: HostCapabilities::query(&host),
+//!         }
+//!     }
+//! }
+//!
+//! # fn render_serially(_voices: u32) {}
+//! # fn on_gui_idle(_callback: fn()) {}
+//! # fn refresh_meters() {}
+//! /// On the audio thread: use the host's thread pool if possible, or render serially.
+//! fn render_voices(shared: &MySynthShared, host: &mut HostAudioProcessorHandle, voices: u32) {
+//!     shared.capabilities.thread_pool().map_or_else(
+//!         || render_serially(voices),
+//!         |pool| {
+//!             if pool.request_exec(host, voices).is_err() {
+//!                 render_serially(voices)
+//!             }
+//!         },
+//!     )
+//! }
+//!
+//! /// On the main thread: use a host timer if possible, or rely on the GUI's idle callback.
+//! fn start_meter_refresh(shared: &MySynthShared, host: &mut HostMainThreadHandle) {
+//!     let timer = shared
+//!         .capabilities
+//!         .timer()
+//!         .and_then(|timer| timer.register_timer(host, 30).ok());
+//!
+//!     if timer.is_none() {
+//!         on_gui_idle(refresh_meters);
+//!     }
+//! }
+//! ```
+//!
+//! Extensions that aren't enabled through their feature flag aren't part of the snapshot.
+//!
+//! Note that a snapshot doesn't track extensions the host could start supporting after it was
+//! taken. As hosts have to expose all of their extensions by the time the plugin is initialized,
+//! this is only a concern if the snapshot is taken before that.
+
+#![deny(missing_docs)]
+
+use clack_plugin::host::HostInfo;
+
+macro_rules! host_capabilities {
+    ($($(#[$cfg:meta])* $name:ident: $extension:ty,)*) => {
+        /// A snapshot of the host extensions supported by the host.
+        ///
+        /// See the [module documentation](self) for more information.
+        #[derive(Copy, Clone, Default)]
+        pub struct HostCapabilities {
+            $($(#[$cfg])* $name: Option<$extension>,)*
+        }
+
+        impl HostCapabilities {
+            /// Queries all the known host extensions from the given host, and captures the result.
+            ///
+            /// This calls into the host once per extension, and should therefore only be done
+            /// once, when the plugin is created.
+            pub fn query(host: &HostInfo) -> Self {
+                Self {
+                    $($(#[$cfg])* $name: host.get_extension(),)*
+                }
+            }
+
+            $(
+                $(#[$cfg])*
+                #[doc = concat!("Returns the [`", stringify!($extension), "`] extension, if the host supports it.")]
+                #[inline]
+                pub fn $name(&self) -> Option<$extension> {
+                    self.$name
+                }
+            )*
+        }
+    };
+}
+
+host_capabilities! {
+    #[cfg(feature = "audio-ports")]
+    audio_ports: crate::audio_ports::HostAudioPorts,
+    #[cfg(feature = "audio-ports-config")]
+    audio_ports_config: crate::audio_ports_config::HostAudioPortsConfig,
+    #[cfg(feature = "context-menu")]
+    context_menu: crate::context_menu::HostContextMenu,
+    #[cfg(feature = "event-registry")]
+    event_registry: crate::event_registry::HostEventRegistry,
+    #[cfg(feature = "gui")]
+    gui: crate::gui::HostGui,
+    #[cfg(feature = "latency")]
+    latency: crate::latency::HostLatency,
+    #[cfg(feature = "log")]
+    log: crate::log::HostLog,
+    #[cfg(feature = "note-name")]
+    note_name: crate::note_name::HostNoteName,
+    #[cfg(feature = "note-ports")]
+    note_ports: crate::note_ports::HostNotePorts,
+    #[cfg(feature = "params")]
+    params: crate::params::HostParams,
+    #[cfg(all(unix, feature = "posix-fd"))]
+    posix_fd: crate::posix_fd::HostPosixFd,
+    #[cfg(feature = "state")]
+    state: crate::state::HostState,
+    #[cfg(feature = "tail")]
+    tail: crate::tail::HostTail,
+    #[cfg(feature = "thread-check")]
+    thread_check: crate::thread_check::HostThreadCheck,
+    #[cfg(feature = "thread-pool")]
+    thread_pool: crate::thread_pool::HostThreadPool,
+    #[cfg(feature = "timer")]
+    timer: crate::timer::HostTimer,
+    #[cfg(feature = "voice-info")]
+    voice_info: crate::voice_info::HostVoiceInfo,
+}
@@ -0,0 +1,710 @@
+#![deny(missing_docs)]
+
+//! Context menus, which can be populated and performed by both the plugin and the host.
+//!
+//! This extension allows a plugin to insert its own entries in the host's context menus (e.g.
+//! when right-clicking a parameter in a generic editor), and the host to insert its own entries in
+//! the plugin's menus. Plugins with an embedded GUI can also ask the host to show its context menu
+//! at a given position, using [`HostContextMenu::popup`].
+//!
+//! Menus are built one item at a time, using a [`ContextMenuBuilder`] provided by the side that
+//! displays the menu. Hosts and plugins can also capture the items of the other side into an owned
+//! [`MenuModel`], which can then be displayed using any UI toolkit.
+
+use clack_common::extensions::{Extension, HostExtensionSide, PluginExtensionSide, RawExtension};
+use clack_common::utils::ClapId;
+use clap_sys::ext::draft::context_menu::*;
+use std::ffi::{c_void, CStr, CString};
+
+/// The Plugin-side of the Context Menu extension.
+#[derive(Copy, Clone)]
+#[allow(dead_code)]
+pub struct PluginContextMenu(RawExtension<PluginExtensionSide, clap_plugin_context_menu>);
+
+// SAFETY: This type is repr(C) and ABI-compatible with the matching extension type.
+unsafe impl Extension for PluginContextMenu {
+    const IDENTIFIER: &'static CStr = CLAP_EXT_CONTEXT_MENU;
+    type ExtensionSide = PluginExtensionSide;
+
+    #[inline]
+    unsafe fn from_raw(raw: RawExtension<Self::ExtensionSide>) -> Self {
+        Self(raw.cast())
+    }
+}
+
+/// The Host-side of the Context Menu extension.
+#[derive(Copy, Clone)]
+#[allow(dead_code)]
+pub struct HostContextMenu(RawExtension<HostExtensionSide, clap_host_context_menu>);
+
+// SAFETY: This type is repr(C) and ABI-compatible with the matching extension type.
+unsafe impl Extension for HostContextMenu {
+    const IDENTIFIER: &'static CStr = CLAP_EXT_CONTEXT_MENU;
+    type ExtensionSide = HostExtensionSide;
+
+    #[inline]
+    unsafe fn from_raw(raw: RawExtension<Self::ExtensionSide>) -> Self {
+        Self(raw.cast())
+    }
+}
+
+/// The element of the plugin a context menu applies to.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ContextMenuTarget {
+    /// The menu applies to the plugin as a whole.
+    Global,
+    /// The menu applies to the parameter with the given ID.
+    Param(ClapId),
+}
+
+impl ContextMenuTarget {
+    /// Reads a target from the given raw, C-FFI compatible pointer.
+    ///
+    /// A NULL pointer is the [`Global`](Self::Global) target. This returns `None` if the target
+    /// is of an unknown kind, or has an invalid ID.
+    ///
+    /// # Safety
+    ///
+    /// The given pointer must either be NULL or valid for reads.
+    pub unsafe fn from_raw(raw: *const clap_context_menu_target) -> Option<Self> {
+        let Some(raw) = raw.as_ref() else {
+            return Some(Self::Global);
+        };
+
+        match raw.kind {
+            CLAP_CONTEXT_MENU_TARGET_KIND_GLOBAL => Some(Self::Global),
+            CLAP_CONTEXT_MENU_TARGET_KIND_PARAM => Some(Self::Param(ClapId::from_raw(raw.id)?)),
+            _ => None,
+        }
+    }
+
+    /// Returns this target as a raw, C-FFI compatible struct.
+    pub fn to_raw(&self) -> clap_context_menu_target {
+        match self {
+            Self::Global => clap_context_menu_target {
+                kind: CLAP_CONTEXT_MENU_TARGET_KIND_GLOBAL,
+                id: 0,
+            },
+            Self::Param(id) => clap_context_menu_target {
+                kind: CLAP_CONTEXT_MENU_TARGET_KIND_PARAM,
+                id: id.get(),
+            },
+        }
+    }
+}
+
+/// The kind of a [`ContextMenuItem`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ContextMenuItemKind {
+    /// A clickable entry.
+    Entry,
+    /// A clickable entry with a check mark.
+    CheckEntry,
+    /// A separator line.
+    Separator,
+    /// The start of a submenu.
+    BeginSubmenu,
+    /// The end of the current submenu.
+    EndSubmenu,
+    /// A title, which cannot be clicked.
+    Title,
+}
+
+impl ContextMenuItemKind {
+    /// Gets the item kind matching the given raw C-FFI compatible value, if it is known.
+    pub fn from_raw(raw: clap_context_menu_item_kind) -> Option<Self> {
+        match raw {
+            CLAP_CONTEXT_MENU_ITEM_ENTRY => Some(Self::Entry),
+            CLAP_CONTEXT_MENU_ITEM_CHECK_ENTRY => Some(Self::CheckEntry),
+            CLAP_CONTEXT_MENU_ITEM_SEPARATOR => Some(Self::Separator),
+            CLAP_CONTEXT_MENU_ITEM_BEGIN_SUBMENU => Some(Self::BeginSubmenu),
+            CLAP_CONTEXT_MENU_ITEM_END_SUBMENU => Some(Self::EndSubmenu),
+            CLAP_CONTEXT_MENU_ITEM_TITLE => Some(Self::Title),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw C-FFI compatible value of this item kind.
+    pub fn to_raw(&self) -> clap_context_menu_item_kind {
+        match self {
+            Self::Entry => CLAP_CONTEXT_MENU_ITEM_ENTRY,
+            Self::CheckEntry => CLAP_CONTEXT_MENU_ITEM_CHECK_ENTRY,
+            Self::Separator => CLAP_CONTEXT_MENU_ITEM_SEPARATOR,
+            Self::BeginSubmenu => CLAP_CONTEXT_MENU_ITEM_BEGIN_SUBMENU,
+            Self::EndSubmenu => CLAP_CONTEXT_MENU_ITEM_END_SUBMENU,
+            Self::Title => CLAP_CONTEXT_MENU_ITEM_TITLE,
+        }
+    }
+}
+
+/// A single item of a context menu, as added to a [`ContextMenuBuilder`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ContextMenuItem<'a> {
+    /// A clickable entry.
+    Entry {
+        /// The displayed label.
+        label: &'a CStr,
+        /// Whether the entry can be clicked.
+        is_enabled: bool,
+        /// The action ID passed to `perform` when this entry is clicked.
+        action_id: ClapId,
+    },
+    /// A clickable entry with a check mark.
+    CheckEntry {
+        /// The displayed label.
+        label: &'a CStr,
+        /// Whether the entry can be clicked.
+        is_enabled: bool,
+        /// Whether the check mark is displayed.
+        is_checked: bool,
+        /// The action ID passed to `perform` when this entry is clicked.
+        action_id: ClapId,
+    },
+    /// A separator line.
+    Separator,
+    /// The start of a submenu. All following items go in the submenu, until the matching
+    /// [`EndSubmenu`](Self::EndSubmenu).
+    BeginSubmenu {
+        /// The displayed label.
+        label: &'a CStr,
+        /// Whether the submenu can be opened.
+        is_enabled: bool,
+    },
+    /// The end of the current submenu.
+    EndSubmenu,
+    /// A title, which cannot be clicked.
+    Title {
+        /// The displayed title.
+        title: &'a CStr,
+        /// Whether the title is displayed as enabled.
+        is_enabled: bool,
+    },
+}
+
+impl<'a> ContextMenuItem<'a> {
+    /// Returns the kind of this item.
+    pub fn kind(&self) -> ContextMenuItemKind {
+        match self {
+            Self::Entry { .. } => ContextMenuItemKind::Entry,
+            Self::CheckEntry { .. } => ContextMenuItemKind::CheckEntry,
+            Self::Separator => ContextMenuItemKind::Separator,
+            Self::BeginSubmenu { .. } => ContextMenuItemKind::BeginSubmenu,
+            Self::EndSubmenu => ContextMenuItemKind::EndSubmenu,
+            Self::Title { .. } => ContextMenuItemKind::Title,
+        }
+    }
+
+    /// Reads an item from its raw, C-FFI compatible kind and data.
+    ///
+    /// This returns `None` if the kind is unknown, or if its data or label is NULL or has an
+    /// invalid ID.
+    ///
+    /// # Safety
+    ///
+    /// The given data pointer must either be NULL or point to the C-FFI struct matching the given
+    /// kind, and the strings it references must be valid for `'a`.
+    pub unsafe fn from_raw(kind: clap_context_menu_item_kind, data: *const c_void) -> Option<Self> {
+        match ContextMenuItemKind::from_raw(kind)? {
+            ContextMenuItemKind::Separator => Some(Self::Separator),
+            ContextMenuItemKind::EndSubmenu => Some(Self::EndSubmenu),
+            ContextMenuItemKind::Entry => {
+                let data = data.cast::<clap_context_menu_entry>().as_ref()?;
+                Some(Self::Entry {
+                    label: cstr_from_raw(data.label)?,
+                    is_enabled: data.is_enabled,
+                    action_id: ClapId::from_raw(data.action_id)?,
+                })
+            }
+            ContextMenuItemKind::CheckEntry => {
+                let data = data.cast::<clap_context_menu_check_entry>().as_ref()?;
+                Some(Self::CheckEntry {
+                    label: cstr_from_raw(data.label)?,
+                    is_enabled: data.is_enabled,
+                    is_checked: data.is_checked,
+                    action_id: ClapId::from_raw(data.action_id)?,
+                })
+            }
+            ContextMenuItemKind::BeginSubmenu => {
+                let data = data.cast::<clap_context_menu_submenu>().as_ref()?;
+                Some(Self::BeginSubmenu {
+                    label: cstr_from_raw(data.label)?,
+                    is_enabled: data.is_enabled,
+                })
+            }
+            ContextMenuItemKind::Title => {
+                let data = data.cast::<clap_context_menu_item_title>().as_ref()?;
+                Some(Self::Title {
+                    title: cstr_from_raw(data.title)?,
+                    is_enabled: data.is_enabled,
+                })
+            }
+        }
+    }
+}
+
+/// # Safety
+///
+/// The pointer must either be NULL or point to a NULL-terminated string valid for `'a`.
+#[inline]
+unsafe fn cstr_from_raw<'a>(ptr: *const std::os::raw::c_char) -> Option<&'a CStr> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr))
+    }
+}
+
+/// A menu builder provided by the side that displays a context menu, which the other side uses to
+/// add its own items.
+///
+/// This builder is only valid for the duration of the `populate` call it was given to.
+pub struct ContextMenuBuilder<'a> {
+    raw: &'a clap_context_menu_builder,
+}
+
+impl<'a> ContextMenuBuilder<'a> {
+    /// Wraps the given raw, C-FFI compatible builder.
+    ///
+    /// # Safety
+    ///
+    /// The builder's function pointers and context must be valid for `'a`.
+    #[inline]
+    pub unsafe fn from_raw(raw: &'a clap_context_menu_builder) -> Self {
+        Self { raw }
+    }
+
+    /// Returns whether the menu can display items of the given kind.
+    pub fn supports(&self, kind: ContextMenuItemKind) -> bool {
+        match self.raw.supports {
+            // SAFETY: This type ensures the function pointer is valid.
+            Some(supports) => unsafe { supports(self.raw, kind.to_raw()) },
+            None => false,
+        }
+    }
+
+    /// Adds an item to the menu. Returns `false` if the menu refused it.
+    pub fn add(&mut self, item: ContextMenuItem) -> bool {
+        let Some(add_item) = self.raw.add_item else {
+            return false;
+        };
+
+        let kind = item.kind().to_raw();
+
+        // SAFETY: This type ensures the function pointer is valid. All data pointers are valid for
+        // the duration of the call.
+        unsafe {
+            match item {
+                ContextMenuItem::Separator | ContextMenuItem::EndSubmenu => {
+                    add_item(self.raw, kind, core::ptr::null())
+                }
+                ContextMenuItem::Entry {
+                    label,
+                    is_enabled,
+                    action_id,
+                } => {
+                    let data = clap_context_menu_entry {
+                        label: label.as_ptr(),
+                        is_enabled,
+                        action_id: action_id.get(),
+                    };
+                    add_item(self.raw, kind, &data as *const _ as *const c_void)
+                }
+                ContextMenuItem::CheckEntry {
+                    label,
+                    is_enabled,
+                    is_checked,
+                    action_id,
+                } => {
+                    let data = clap_context_menu_check_entry {
+                        label: label.as_ptr(),
+                        is_enabled,
+                        is_checked,
+                        action_id: action_id.get(),
+                    };
+                    add_item(self.raw, kind, &data as *const _ as *const c_void)
+                }
+                ContextMenuItem::BeginSubmenu { label, is_enabled } => {
+                    let data = clap_context_menu_submenu {
+                        label: label.as_ptr(),
+                        is_enabled,
+                    };
+                    add_item(self.raw, kind, &data as *const _ as *const c_void)
+                }
+                ContextMenuItem::Title { title, is_enabled } => {
+                    let data = clap_context_menu_item_title {
+                        title: title.as_ptr(),
+                        is_enabled,
+                    };
+                    add_item(self.raw, kind, &data as *const _ as *const c_void)
+                }
+            }
+        }
+    }
+}
+
+/// An owned item of a [`MenuModel`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MenuItem {
+    /// A clickable entry.
+    Entry {
+        /// The displayed label.
+        label: CString,
+        /// Whether the entry can be clicked.
+        is_enabled: bool,
+        /// The action ID to perform when this entry is clicked.
+        action_id: ClapId,
+    },
+    /// A clickable entry with a check mark.
+    CheckEntry {
+        /// The displayed label.
+        label: CString,
+        /// Whether the entry can be clicked.
+        is_enabled: bool,
+        /// Whether the check mark is displayed.
+        is_checked: bool,
+        /// The action ID to perform when this entry is clicked.
+        action_id: ClapId,
+    },
+    /// A separator line.
+    Separator,
+    /// A submenu, and all of its items.
+    Submenu {
+        /// The displayed label.
+        label: CString,
+        /// Whether the submenu can be opened.
+        is_enabled: bool,
+        /// The items of the submenu.
+        items: Vec<MenuItem>,
+    },
+    /// A title, which cannot be clicked.
+    Title {
+        /// The displayed title.
+        title: CString,
+        /// Whether the title is displayed as enabled.
+        is_enabled: bool,
+    },
+}
+
+/// A submenu that has been started, but not ended yet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct OpenSubmenu {
+    label: CString,
+    is_enabled: bool,
+    items: Vec<MenuItem>,
+}
+
+/// An owned tree of context menu items, which can be displayed using any UI toolkit.
+///
+/// Items can either be added directly using [`push`](Self::push), or collected from the other
+/// side's `populate` callback (e.g. using `PluginContextMenu::populate` on the host side).
+/// Submenus that are still open at the end of a `populate` call are closed automatically.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MenuModel {
+    items: Vec<MenuItem>,
+    open_submenus: Vec<OpenSubmenu>,
+}
+
+impl MenuModel {
+    /// Creates a new, empty menu.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            open_submenus: Vec::new(),
+        }
+    }
+
+    /// Returns the top-level items of this menu.
+    #[inline]
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    /// Returns `true` if this menu has no items.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty() && self.open_submenus.is_empty()
+    }
+
+    /// Removes all items from this menu.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.open_submenus.clear();
+    }
+
+    /// Adds an item to the menu, or to the currently open submenu.
+    ///
+    /// This returns `false` if the item is an [`EndSubmenu`](ContextMenuItem::EndSubmenu) that
+    /// does not match any open submenu.
+    pub fn push(&mut self, item: ContextMenuItem) -> bool {
+        let item = match item {
+            ContextMenuItem::Entry {
+                label,
+                is_enabled,
+                action_id,
+            } => MenuItem::Entry {
+                label: label.into(),
+                is_enabled,
+                action_id,
+            },
+            ContextMenuItem::CheckEntry {
+                label,
+                is_enabled,
+                is_checked,
+                action_id,
+            } => MenuItem::CheckEntry {
+                label: label.into(),
+                is_enabled,
+                is_checked,
+                action_id,
+            },
+            ContextMenuItem::Separator => MenuItem::Separator,
+            ContextMenuItem::Title { title, is_enabled } => MenuItem::Title {
+                title: title.into(),
+                is_enabled,
+            },
+            ContextMenuItem::BeginSubmenu { label, is_enabled } => {
+                self.open_submenus.push(OpenSubmenu {
+                    label: label.into(),
+                    is_enabled,
+                    items: Vec::new(),
+                });
+                return true;
+            }
+            ContextMenuItem::EndSubmenu => return self.end_submenu(),
+        };
+
+        self.current_items().push(item);
+        true
+    }
+
+    /// Returns the item performing the given action, if any.
+    pub fn action(&self, action_id: ClapId) -> Option<&MenuItem> {
+        find_action(&self.items, action_id, true).map(|(item, _)| item)
+    }
+
+    /// Returns `true` if the item performing the given action can be clicked, i.e. if it and all
+    /// of the submenus containing it are enabled.
+    pub fn is_action_enabled(&self, action_id: ClapId) -> bool {
+        find_action(&self.items, action_id, true).is_some_and(|(_, enabled)| enabled)
+    }
+
+    /// Adds all the items of this menu to the given builder, e.g. to forward them to the other
+    /// side's `populate` callback.
+    ///
+    /// This returns `false` if the builder refused any of the items.
+    pub fn write_to(&self, builder: &mut ContextMenuBuilder) -> bool {
+        write_items(&self.items, builder)
+    }
+
+    /// Closes all the submenus that are still open.
+    pub fn close_submenus(&mut self) {
+        while self.end_submenu() {}
+    }
+
+    /// Calls the given closure with a raw, C-FFI compatible builder that adds items to this menu.
+    ///
+    /// The raw builder must not be used after the closure returns.
+    pub(crate) fn with_raw_builder<R>(
+        &mut self,
+        populate: impl FnOnce(&clap_context_menu_builder) -> R,
+    ) -> R {
+        let builder = clap_context_menu_builder {
+            ctx: (self as *mut Self).cast(),
+            add_item: Some(add_item),
+            supports: Some(supports),
+        };
+
+        let result = populate(&builder);
+        self.close_submenus();
+        result
+    }
+
+    fn current_items(&mut self) -> &mut Vec<MenuItem> {
+        match self.open_submenus.last_mut() {
+            Some(submenu) => &mut submenu.items,
+            None => &mut self.items,
+        }
+    }
+
+    fn end_submenu(&mut self) -> bool {
+        let Some(submenu) = self.open_submenus.pop() else {
+            return false;
+        };
+
+        self.current_items().push(MenuItem::Submenu {
+            label: submenu.label,
+            is_enabled: submenu.is_enabled,
+            items: submenu.items,
+        });
+
+        true
+    }
+}
+
+fn find_action(items: &[MenuItem], action_id: ClapId, enabled: bool) -> Option<(&MenuItem, bool)> {
+    items.iter().find_map(|item| match item {
+        MenuItem::Entry {
+            action_id: id,
+            is_enabled,
+            ..
+        }
+        | MenuItem::CheckEntry {
+            action_id: id,
+            is_enabled,
+            ..
+        } if *id == action_id => Some((item, enabled && *is_enabled)),
+        MenuItem::Submenu {
+            is_enabled, items, ..
+        } => find_action(items, action_id, enabled && *is_enabled),
+        _ => None,
+    })
+}
+
+fn write_items(items: &[MenuItem], builder: &mut ContextMenuBuilder) -> bool {
+    items.iter().all(|item| match item {
+        MenuItem::Entry {
+            label,
+            is_enabled,
+            action_id,
+        } => builder.add(ContextMenuItem::Entry {
+            label,
+            is_enabled: *is_enabled,
+            action_id: *action_id,
+        }),
+        MenuItem::CheckEntry {
+            label,
+            is_enabled,
+            is_checked,
+            action_id,
+        } => builder.add(ContextMenuItem::CheckEntry {
+            label,
+            is_enabled: *is_enabled,
+            is_checked: *is_checked,
+            action_id: *action_id,
+        }),
+        MenuItem::Separator => builder.add(ContextMenuItem::Separator),
+        MenuItem::Title { title, is_enabled } => builder.add(ContextMenuItem::Title {
+            title,
+            is_enabled: *is_enabled,
+        }),
+        MenuItem::Submenu {
+            label,
+            is_enabled,
+            items,
+        } => {
+            builder.add(ContextMenuItem::BeginSubmenu {
+                label,
+                is_enabled: *is_enabled,
+            }) && write_items(items, builder)
+                && builder.add(ContextMenuItem::EndSubmenu)
+        }
+    })
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn add_item(
+    builder: *const clap_context_menu_builder,
+    item_kind: clap_context_menu_item_kind,
+    item_data: *const c_void,
+) -> bool {
+    let Some(builder) = builder.as_ref() else {
+        return false;
+    };
+
+    // SAFETY: the context is set by with_raw_builder, and is valid for as long as the builder is.
+    let Some(menu) = builder.ctx.cast::<MenuModel>().as_mut() else {
+        return false;
+    };
+
+    match ContextMenuItem::from_raw(item_kind, item_data) {
+        Some(item) => menu.push(item),
+        None => false,
+    }
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn supports(
+    _builder: *const clap_context_menu_builder,
+    item_kind: clap_context_menu_item_kind,
+) -> bool {
+    ContextMenuItemKind::from_raw(item_kind).is_some()
+}
+
+#[cfg(feature = "clack-host")]
+mod host;
+
+#[cfg(feature = "clack-host")]
+pub use host::*;
+
+#[cfg(feature = "clack-plugin")]
+mod plugin;
+
+#[cfg(feature = "clack-plugin")]
+pub use plugin::*;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cstr(bytes: &[u8]) -> &CStr {
+        CStr::from_bytes_with_nul(bytes).unwrap()
+    }
+
+    #[test]
+    fn builds_nested_menus_through_raw_builder() {
+        let mut menu = MenuModel::new();
+
+        let accepted = menu.with_raw_builder(|raw| {
+            // SAFETY: the raw builder is valid for the duration of this closure.
+            let mut builder = unsafe { ContextMenuBuilder::from_raw(raw) };
+
+            assert!(builder.supports(ContextMenuItemKind::CheckEntry));
+            builder.add(ContextMenuItem::Title {
+                title: cstr(b"Gain\0"),
+                is_enabled: true,
+            }) && builder.add(ContextMenuItem::BeginSubmenu {
+                label: cstr(b"More\0"),
+                is_enabled: false,
+            }) && builder.add(ContextMenuItem::Entry {
+                label: cstr(b"Reset\0"),
+                is_enabled: true,
+                action_id: ClapId::new(3),
+            })
+            // The submenu is left open on purpose.
+        });
+
+        assert!(accepted);
+        assert_eq!(
+            menu.items(),
+            [
+                MenuItem::Title {
+                    title: cstr(b"Gain\0").into(),
+                    is_enabled: true
+                },
+                MenuItem::Submenu {
+                    label: cstr(b"More\0").into(),
+                    is_enabled: false,
+                    items: vec![MenuItem::Entry {
+                        label: cstr(b"Reset\0").into(),
+                        is_enabled: true,
+                        action_id: ClapId::new(3)
+                    }]
+                }
+            ]
+        );
+
+        // The entry itself is enabled, but its submenu is not.
+        assert!(menu.action(ClapId::new(3)).is_some());
+        assert!(!menu.is_action_enabled(ClapId::new(3)));
+        assert!(!menu.push(ContextMenuItem::EndSubmenu));
+
+        let mut copy = MenuModel::new();
+        copy.with_raw_builder(|raw| {
+            // SAFETY: the raw builder is valid for the duration of this closure.
+            assert!(menu.write_to(&mut unsafe { ContextMenuBuilder::from_raw(raw) }));
+        });
+        assert_eq!(copy, menu);
+    }
+}
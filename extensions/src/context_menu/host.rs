@@ -0,0 +1,408 @@
+use super::*;
+use clack_host::extensions::prelude::*;
+
+impl PluginContextMenu {
+    /// Asks the plugin to add its items for the given target to the given menu.
+    ///
+    /// Returns `false` if the plugin failed to populate the menu. Items it added before failing
+    /// are kept.
+    pub fn populate(
+        &self,
+        plugin: &mut PluginMainThreadHandle,
+        target: ContextMenuTarget,
+        menu: &mut MenuModel,
+    ) -> bool {
+        let Some(populate) = plugin.use_extension(&self.0).populate else {
+            return false;
+        };
+
+        let target = target.to_raw();
+
+        menu.with_raw_builder(|builder| {
+            // SAFETY: This type ensures the function pointer is valid. The target and builder
+            // are valid for the duration of the call.
+            unsafe { populate(plugin.as_raw(), &target, builder) }
+        })
+    }
+
+    /// Asks the plugin to perform the action of the menu item with the given action ID, which was
+    /// added by the plugin for the given target.
+    ///
+    /// Returns `false` if the plugin failed to perform the action.
+    pub fn perform(
+        &self,
+        plugin: &mut PluginMainThreadHandle,
+        target: ContextMenuTarget,
+        action_id: ClapId,
+    ) -> bool {
+        let Some(perform) = plugin.use_extension(&self.0).perform else {
+            return false;
+        };
+
+        let target = target.to_raw();
+
+        // SAFETY: This type ensures the function pointer is valid. The target is valid for the
+        // duration of the call.
+        unsafe { perform(plugin.as_raw(), &target, action_id.get()) }
+    }
+}
+
+/// Implementation of the Host-side of the Context Menu extension.
+///
+/// See [`ContextMenuPopups`] for a helper implementing the popup-related methods.
+pub trait HostContextMenuImpl {
+    /// Adds the host's items for the given target to the plugin's menu.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host failed to populate the menu.
+    fn populate(
+        &mut self,
+        target: ContextMenuTarget,
+        builder: &mut ContextMenuBuilder,
+    ) -> Result<(), HostError>;
+
+    /// Performs the action of the menu item with the given action ID, which was added by the host
+    /// for the given target.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host failed to perform the action.
+    fn perform(&mut self, target: ContextMenuTarget, action_id: ClapId) -> Result<(), HostError>;
+
+    /// Returns `true` if the host can display its own context menus for the plugin.
+    fn can_popup(&mut self) -> bool;
+
+    /// Shows the host's context menu for the given target.
+    ///
+    /// If the plugin's GUI is embedded, the `x` and `y` coordinates are relative to the plugin's
+    /// window. Otherwise, they are absolute coordinates on the screen with the given index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host cannot show the menu.
+    fn popup(
+        &mut self,
+        target: ContextMenuTarget,
+        screen_index: i32,
+        x: i32,
+        y: i32,
+    ) -> Result<(), HostError>;
+}
+
+// SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+unsafe impl<H: HostHandlers> ExtensionImplementation<H> for HostContextMenu
+where
+    for<'a> <H as HostHandlers>::MainThread<'a>: HostContextMenuImpl,
+{
+    #[doc(hidden)]
+    const IMPLEMENTATION: RawExtensionImplementation =
+        RawExtensionImplementation::new(&clap_host_context_menu {
+            populate: Some(populate::<H>),
+            perform: Some(perform::<H>),
+            can_popup: Some(can_popup::<H>),
+            popup: Some(popup::<H>),
+        });
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn populate<H: HostHandlers>(
+    host: *const clap_host,
+    target: *const clap_context_menu_target,
+    builder: *const clap_context_menu_builder,
+) -> bool
+where
+    for<'a> <H as HostHandlers>::MainThread<'a>: HostContextMenuImpl,
+{
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_context_menu.populate", |host| {
+        let target = ContextMenuTarget::from_raw(target).ok_or(
+            HostWrapperError::InvalidParameter("Invalid context menu target"),
+        )?;
+        let builder = builder.as_ref().ok_or(HostWrapperError::InvalidParameter(
+            "Context menu builder is NULL",
+        ))?;
+
+        let mut builder = ContextMenuBuilder::from_raw(builder);
+
+        Ok(host
+            .main_thread()
+            .as_mut()
+            .populate(target, &mut builder)
+            .is_ok())
+    })
+    .unwrap_or(false)
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn perform<H: HostHandlers>(
+    host: *const clap_host,
+    target: *const clap_context_menu_target,
+    action_id: u32,
+) -> bool
+where
+    for<'a> <H as HostHandlers>::MainThread<'a>: HostContextMenuImpl,
+{
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_context_menu.perform", |host| {
+        let target = ContextMenuTarget::from_raw(target).ok_or(
+            HostWrapperError::InvalidParameter("Invalid context menu target"),
+        )?;
+        let action_id = ClapId::from_raw(action_id).ok_or(HostWrapperError::InvalidParameter(
+            "Invalid context menu action ID",
+        ))?;
+
+        Ok(host
+            .main_thread()
+            .as_mut()
+            .perform(target, action_id)
+            .is_ok())
+    })
+    .unwrap_or(false)
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn can_popup<H: HostHandlers>(host: *const clap_host) -> bool
+where
+    for<'a> <H as HostHandlers>::MainThread<'a>: HostContextMenuImpl,
+{
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_context_menu.can_popup", |host| {
+        Ok(host.main_thread().as_mut().can_popup())
+    })
+    .unwrap_or(false)
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn popup<H: HostHandlers>(
+    host: *const clap_host,
+    target: *const clap_context_menu_target,
+    screen_index: i32,
+    x: i32,
+    y: i32,
+) -> bool
+where
+    for<'a> <H as HostHandlers>::MainThread<'a>: HostContextMenuImpl,
+{
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_context_menu.popup", |host| {
+        let target = ContextMenuTarget::from_raw(target).ok_or(
+            HostWrapperError::InvalidParameter("Invalid context menu target"),
+        )?;
+
+        Ok(host
+            .main_thread()
+            .as_mut()
+            .popup(target, screen_index, x, y)
+            .is_ok())
+    })
+    .unwrap_or(false)
+}
+
+/// A plugin's request to show the host's context menu, as received by
+/// [`HostContextMenuImpl::popup`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PopupRequest {
+    /// The element of the plugin the menu applies to.
+    pub target: ContextMenuTarget,
+    /// The index of the screen the coordinates are on, if the plugin's GUI is floating.
+    pub screen_index: i32,
+    /// The horizontal position of the menu.
+    pub x: i32,
+    /// The vertical position of the menu.
+    pub y: i32,
+}
+
+impl PopupRequest {
+    /// Collects the plugin's items for this request's target into a new [`MenuModel`], then hands
+    /// the resulting [`ContextMenuPopup`] to the given callback, which is expected to show it
+    /// using the host's UI toolkit.
+    ///
+    /// This must be called after the plugin's `popup` call has returned, e.g. from the host's
+    /// main loop. The host can add its own items to the menu before showing it.
+    ///
+    /// This returns `false`, and the callback is not called, if the plugin does not support the
+    /// Context Menu extension, or if it failed to populate the menu.
+    pub fn show(
+        self,
+        plugin: &mut PluginMainThreadHandle,
+        show: impl FnOnce(ContextMenuPopup),
+    ) -> bool {
+        let Some(extension) = plugin.get_extension::<PluginContextMenu>() else {
+            return false;
+        };
+
+        let mut menu = MenuModel::new();
+        if !extension.populate(plugin, self.target, &mut menu) {
+            return false;
+        }
+
+        show(ContextMenuPopup {
+            request: self,
+            menu,
+            extension,
+        });
+
+        true
+    }
+}
+
+/// A context menu requested by the plugin, populated with the plugin's items, and ready to be
+/// shown by the host.
+///
+/// Once the user picks an item, its action must be sent back to the plugin using
+/// [`perform`](Self::perform), which can only happen once.
+pub struct ContextMenuPopup {
+    request: PopupRequest,
+    menu: MenuModel,
+    extension: PluginContextMenu,
+}
+
+impl ContextMenuPopup {
+    /// Returns the request this menu was created from.
+    #[inline]
+    pub fn request(&self) -> &PopupRequest {
+        &self.request
+    }
+
+    /// Returns the items of this menu.
+    #[inline]
+    pub fn menu(&self) -> &MenuModel {
+        &self.menu
+    }
+
+    /// Returns a mutable reference to the items of this menu, e.g. for the host to add its own.
+    ///
+    /// Only the actions of the items added by the plugin are sent to it by
+    /// [`perform`](Self::perform).
+    #[inline]
+    pub fn menu_mut(&mut self) -> &mut MenuModel {
+        &mut self.menu
+    }
+
+    /// Asks the plugin to perform the action of the item the user picked, for the original target
+    /// of this menu.
+    ///
+    /// This returns `false` without calling the plugin if the action is not part of the menu, or
+    /// if it is disabled. Otherwise, this returns whether the plugin performed the action.
+    pub fn perform(self, plugin: &mut PluginMainThreadHandle, action_id: ClapId) -> bool {
+        if !self.menu.is_action_enabled(action_id) {
+            return false;
+        }
+
+        self.extension
+            .perform(plugin, self.request.target, action_id)
+    }
+}
+
+/// A helper to implement the popup-related methods of [`HostContextMenuImpl`].
+///
+/// A plugin's request to show a menu cannot be fulfilled during the `popup` call itself, as the
+/// plugin's items need to be collected by calling back into it. This helper stores the request
+/// instead, so that the host can show it later from its main loop, using
+/// [`take_pending`](Self::take_pending) and [`PopupRequest::show`].
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::context_menu::*;
+/// use clack_host::prelude::*;
+///
+/// struct MyHostMainThread {
+///     popups: ContextMenuPopups,
+/// }
+///
+/// impl HostContextMenuImpl for MyHostMainThread {
+///     fn populate(&mut self, _: ContextMenuTarget, _: &mut ContextMenuBuilder) -> Result<(), HostError> {
+///         Ok(())
+///     }
+///
+///     fn perform(&mut self, _: ContextMenuTarget, _: ClapId) -> Result<(), HostError> {
+///         Err(HostError::Message("Unknown action"))
+///     }
+///
+///     fn can_popup(&mut self) -> bool {
+///         self.popups.can_popup()
+///     }
+///
+///     fn popup(&mut self, target: ContextMenuTarget, screen_index: i32, x: i32, y: i32) -> Result<(), HostError> {
+///         self.popups.request(target, screen_index, x, y)
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContextMenuPopups {
+    can_popup: bool,
+    pending: Option<PopupRequest>,
+}
+
+impl Default for ContextMenuPopups {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContextMenuPopups {
+    /// Creates a new helper, which accepts popup requests.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            can_popup: true,
+            pending: None,
+        }
+    }
+
+    /// Returns `true` if popup requests are currently accepted.
+    #[inline]
+    pub fn can_popup(&self) -> bool {
+        self.can_popup
+    }
+
+    /// Sets whether popup requests are accepted, e.g. depending on whether the host's UI is
+    /// currently able to show menus.
+    ///
+    /// Disabling popups also discards any pending request.
+    #[inline]
+    pub fn set_can_popup(&mut self, can_popup: bool) {
+        self.can_popup = can_popup;
+
+        if !can_popup {
+            self.pending = None;
+        }
+    }
+
+    /// Stores a popup request from the plugin, replacing any request that was not shown yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if popups are not currently accepted.
+    pub fn request(
+        &mut self,
+        target: ContextMenuTarget,
+        screen_index: i32,
+        x: i32,
+        y: i32,
+    ) -> Result<(), HostError> {
+        if !self.can_popup {
+            return Err(HostError::Message("Context menu popups are not available"));
+        }
+
+        self.pending = Some(PopupRequest {
+            target,
+            screen_index,
+            x,
+            y,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the request that was not shown yet, if any.
+    #[inline]
+    pub fn pending(&self) -> Option<&PopupRequest> {
+        self.pending.as_ref()
+    }
+
+    /// Takes the request that was not shown yet, if any.
+    #[inline]
+    pub fn take_pending(&mut self) -> Option<PopupRequest> {
+        self.pending.take()
+    }
+}
@@ -0,0 +1,163 @@
+use super::*;
+use clack_plugin::extensions::prelude::*;
+
+impl HostContextMenu {
+    /// Asks the host to add its items for the given target to the given menu.
+    ///
+    /// Returns `false` if the host failed to populate the menu. Items it added before failing
+    /// are kept.
+    pub fn populate(
+        &self,
+        host: &mut HostMainThreadHandle,
+        target: ContextMenuTarget,
+        menu: &mut MenuModel,
+    ) -> bool {
+        let Some(populate) = host.use_extension(&self.0).populate else {
+            return false;
+        };
+
+        let target = target.to_raw();
+
+        menu.with_raw_builder(|builder| {
+            // SAFETY: This type ensures the function pointer is valid. The target and builder
+            // are valid for the duration of the call.
+            unsafe { populate(host.as_raw(), &target, builder) }
+        })
+    }
+
+    /// Asks the host to perform the action of the menu item with the given action ID, which was
+    /// added by the host for the given target.
+    ///
+    /// Returns `false` if the host failed to perform the action.
+    pub fn perform(
+        &self,
+        host: &mut HostMainThreadHandle,
+        target: ContextMenuTarget,
+        action_id: ClapId,
+    ) -> bool {
+        let Some(perform) = host.use_extension(&self.0).perform else {
+            return false;
+        };
+
+        let target = target.to_raw();
+
+        // SAFETY: This type ensures the function pointer is valid. The target is valid for the
+        // duration of the call.
+        unsafe { perform(host.as_raw(), &target, action_id.get()) }
+    }
+
+    /// Returns `true` if the host can display its own context menus for the plugin.
+    pub fn can_popup(&self, host: &mut HostMainThreadHandle) -> bool {
+        match host.use_extension(&self.0).can_popup {
+            // SAFETY: This type ensures the function pointer is valid.
+            Some(can_popup) => unsafe { can_popup(host.as_raw()) },
+            None => false,
+        }
+    }
+
+    /// Asks the host to show its context menu for the given target.
+    ///
+    /// If the plugin's GUI is embedded, the `x` and `y` coordinates are relative to the plugin's
+    /// window. Otherwise, they are absolute coordinates on the screen with the given index.
+    ///
+    /// Returns `false` if the host cannot show the menu.
+    pub fn popup(
+        &self,
+        host: &mut HostMainThreadHandle,
+        target: ContextMenuTarget,
+        screen_index: i32,
+        x: i32,
+        y: i32,
+    ) -> bool {
+        let Some(popup) = host.use_extension(&self.0).popup else {
+            return false;
+        };
+
+        let target = target.to_raw();
+
+        // SAFETY: This type ensures the function pointer is valid. The target is valid for the
+        // duration of the call.
+        unsafe { popup(host.as_raw(), &target, screen_index, x, y) }
+    }
+}
+
+/// Implementation of the Plugin-side of the Context Menu extension.
+pub trait PluginContextMenuImpl {
+    /// Adds the plugin's items for the given target to the host's menu.
+    fn populate(
+        &mut self,
+        target: ContextMenuTarget,
+        builder: &mut ContextMenuBuilder,
+    ) -> Result<(), PluginError>;
+
+    /// Performs the action of the menu item with the given action ID, which was added by the
+    /// plugin for the given target.
+    fn perform(&mut self, target: ContextMenuTarget, action_id: ClapId) -> Result<(), PluginError>;
+}
+
+// SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+unsafe impl<P: Plugin> ExtensionImplementation<P> for PluginContextMenu
+where
+    for<'a> P::MainThread<'a>: PluginContextMenuImpl,
+{
+    #[doc(hidden)]
+    const IMPLEMENTATION: RawExtensionImplementation =
+        RawExtensionImplementation::new(&clap_plugin_context_menu {
+            populate: Some(populate::<P>),
+            perform: Some(perform::<P>),
+        });
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn populate<P: Plugin>(
+    plugin: *const clap_plugin,
+    target: *const clap_context_menu_target,
+    builder: *const clap_context_menu_builder,
+) -> bool
+where
+    for<'a> P::MainThread<'a>: PluginContextMenuImpl,
+{
+    PluginWrapper::<P>::handle_main_thread(
+        plugin,
+        "clap_plugin_context_menu.populate",
+        |main_thread, _, _| {
+            let target = ContextMenuTarget::from_raw(target).ok_or(
+                PluginWrapperError::InvalidParameter("Invalid context menu target"),
+            )?;
+            let builder = builder
+                .as_ref()
+                .ok_or(PluginWrapperError::NulPtr("Context menu builder"))?;
+
+            main_thread.populate(target, &mut ContextMenuBuilder::from_raw(builder))?;
+            Ok(true)
+        },
+    )
+    .unwrap_or(false)
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn perform<P: Plugin>(
+    plugin: *const clap_plugin,
+    target: *const clap_context_menu_target,
+    action_id: u32,
+) -> bool
+where
+    for<'a> P::MainThread<'a>: PluginContextMenuImpl,
+{
+    PluginWrapper::<P>::handle_main_thread(
+        plugin,
+        "clap_plugin_context_menu.perform",
+        |main_thread, _, _| {
+            let target = ContextMenuTarget::from_raw(target).ok_or(
+                PluginWrapperError::InvalidParameter("Invalid context menu target"),
+            )?;
+            let action_id = ClapId::from_raw(action_id).ok_or(
+                PluginWrapperError::InvalidParameter("Invalid context menu action ID"),
+            )?;
+
+            main_thread.perform(target, action_id)?;
+            Ok(true)
+        },
+    )
+    .unwrap_or(false)
+}
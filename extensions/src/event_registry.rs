@@ -85,15 +85,16 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostEventRegistryImpl,
     {
-        let result = HostWrapper::<H>::handle(host, |host| {
-            let space_name = CStr::from_ptr(space_name);
+        let result =
+            HostWrapper::<H>::handle_main_thread(host, "clap_host_event_registry.query", |host| {
+                let space_name = CStr::from_ptr(space_name);
 
-            let result = host.main_thread().as_ref().query(space_name);
-            *space_id = EventSpaceId::optional_id(&result);
+                let result = host.main_thread().as_ref().query(space_name);
+                *space_id = EventSpaceId::optional_id(&result);
 
-            Ok(result.is_some())
-        })
-        .unwrap_or(false);
+                Ok(result.is_some())
+            })
+            .unwrap_or(false);
 
         if !result {
             *space_id = u16::MAX;
@@ -0,0 +1,384 @@
+//! Allows the host to add and remove audio ports of a plugin, e.g. to give it extra sidechain
+//! inputs.
+//!
+//! Ports can only be added or removed while the plugin is deactivated, and from the main thread.
+//! The host then has to rescan the plugin's audio ports (see the
+//! [`audio_ports`](crate::audio_ports) extension) to discover the new layout: the host-side
+//! helpers of this module do this automatically, and report the changes as an
+//! [`AudioPortLayoutSnapshot`] diff.
+//!
+//! This is a draft extension: its interface may change in future CLAP versions.
+
+#![deny(missing_docs)]
+
+use crate::audio_ports::snapshot::{AudioPortChange, AudioPortLayoutSnapshot};
+use crate::audio_ports::AudioPortType;
+use clack_common::extensions::*;
+use clack_common::utils::ClapId;
+use clap_sys::ext::draft::extensible_audio_ports::*;
+use std::error::Error;
+use std::ffi::{c_void, CStr};
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+
+/// Plugin-side of the Extensible Audio Ports extension.
+#[derive(Copy, Clone)]
+#[allow(dead_code)]
+pub struct PluginExtensibleAudioPorts(
+    RawExtension<PluginExtensionSide, clap_plugin_extensible_audio_ports>,
+);
+
+// SAFETY: This type is repr(C) and ABI-compatible with the matching extension type.
+unsafe impl Extension for PluginExtensibleAudioPorts {
+    const IDENTIFIER: &'static CStr = CLAP_EXT_EXTENSIBLE_AUDIO_PORTS;
+    type ExtensionSide = PluginExtensionSide;
+
+    #[inline]
+    unsafe fn from_raw(raw: RawExtension<Self::ExtensionSide>) -> Self {
+        Self(raw.cast())
+    }
+}
+
+/// Additional, port-type-specific details about a port to add.
+///
+/// The contents of these details depend on the port's type, and are not defined by CLAP yet.
+/// The helpers of this module never send any details.
+#[derive(Copy, Clone, Debug)]
+pub struct PortDetails<'a> {
+    raw: *const c_void,
+    _lifetime: PhantomData<&'a c_void>,
+}
+
+impl PortDetails<'_> {
+    /// Returns `true` if no details were provided.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_null()
+    }
+
+    /// Returns the raw pointer to the details, which may be null.
+    #[inline]
+    pub fn as_raw(&self) -> *const c_void {
+        self.raw
+    }
+}
+
+/// The result of a successful port addition or removal, made through the host-side helpers of
+/// this module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioPortsUpdate {
+    /// The ID of the port that was added or removed.
+    pub port_id: ClapId,
+    /// All the changes in the plugin's audio port layout, as listed by
+    /// [`AudioPortLayoutSnapshot::diff`].
+    pub changes: Vec<AudioPortChange>,
+}
+
+/// Errors that can occur when adding or removing audio ports.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExtensibleAudioPortsError {
+    /// The plugin is active: ports can only be added or removed while it is deactivated.
+    PluginActive,
+    /// The plugin doesn't implement the audio ports extension, so its new layout can't be
+    /// retrieved.
+    AudioPortsUnsupported,
+    /// The plugin rejected the request.
+    Rejected,
+    /// The port to remove isn't part of the plugin's current layout.
+    UnknownPort(ClapId),
+    /// The plugin accepted to add a port, but no new port was found in its layout.
+    MissingAddedPort,
+}
+
+impl Display for ExtensibleAudioPortsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PluginActive => {
+                f.write_str("Audio ports can only be changed while the plugin is deactivated")
+            }
+            Self::AudioPortsUnsupported => {
+                f.write_str("Plugin does not implement the audio ports extension")
+            }
+            Self::Rejected => f.write_str("Plugin rejected the audio port change"),
+            Self::UnknownPort(id) => write!(f, "Unknown audio port ID: {id}"),
+            Self::MissingAddedPort => {
+                f.write_str("Plugin accepted to add an audio port, but no new port was found")
+            }
+        }
+    }
+}
+
+impl Error for ExtensibleAudioPortsError {}
+
+#[cfg(feature = "clack-host")]
+mod host {
+    use super::*;
+    use crate::audio_ports::PluginAudioPorts;
+    use clack_host::prelude::*;
+    use std::ptr::null;
+
+    impl PluginExtensibleAudioPorts {
+        /// Asks the plugin to add a new audio port, with the given number of channels and port
+        /// type.
+        ///
+        /// On success, the plugin's audio ports are rescanned, the given `layout` is updated
+        /// to the new one, and the ID of the new port is returned along with all the changes in
+        /// the layout. `layout` is expected to be the plugin's current layout, e.g. as
+        /// [captured](AudioPortLayoutSnapshot::capture) after instantiation.
+        ///
+        /// # Errors
+        ///
+        /// This returns [`ExtensibleAudioPortsError::PluginActive`] if the plugin instance is
+        /// active, in which case nothing is sent to the plugin.
+        ///
+        /// # Panics
+        ///
+        /// In debug builds, this panics if called from a different thread than the one the
+        /// `main_thread` token was acquired on.
+        #[track_caller]
+        pub fn add_port<H: HostHandlers>(
+            &self,
+            instance: &mut PluginInstance<H>,
+            main_thread: &MainThreadToken,
+            layout: &mut AudioPortLayoutSnapshot,
+            is_input: bool,
+            channel_count: u32,
+            port_type: Option<AudioPortType>,
+        ) -> Result<AudioPortsUpdate, ExtensibleAudioPortsError> {
+            if instance.is_active() {
+                return Err(ExtensibleAudioPortsError::PluginActive);
+            }
+
+            let mut plugin = instance.plugin_handle_checked(main_thread);
+            let audio_ports = plugin
+                .get_extension::<PluginAudioPorts>()
+                .ok_or(ExtensibleAudioPortsError::AudioPortsUnsupported)?;
+
+            let Some(add_port) = plugin.use_extension(&self.0).add_port else {
+                return Err(ExtensibleAudioPortsError::Rejected);
+            };
+
+            // SAFETY: This type ensures the function pointer is valid. The port type string
+            // outlives the call.
+            let added = unsafe {
+                add_port(
+                    plugin.as_raw(),
+                    is_input,
+                    channel_count,
+                    port_type.map(|t| t.0.as_ptr()).unwrap_or(null()),
+                    null(),
+                )
+            };
+
+            if !added {
+                return Err(ExtensibleAudioPortsError::Rejected);
+            }
+
+            let changes = rescan(&audio_ports, &mut plugin, layout);
+            let port_id = changes
+                .iter()
+                .find_map(|change| match change {
+                    AudioPortChange::Added { is_input: i, id } if *i == is_input => Some(*id),
+                    _ => None,
+                })
+                .ok_or(ExtensibleAudioPortsError::MissingAddedPort)?;
+
+            Ok(AudioPortsUpdate { port_id, changes })
+        }
+
+        /// Asks the plugin to remove the audio port with the given ID.
+        ///
+        /// The port is looked up in the given `layout`, which is expected to be the plugin's
+        /// current layout. On success, the plugin's audio ports are rescanned, `layout` is
+        /// updated to the new one, and all the changes in the layout are returned.
+        ///
+        /// # Errors
+        ///
+        /// This returns [`ExtensibleAudioPortsError::PluginActive`] if the plugin instance is
+        /// active, and [`ExtensibleAudioPortsError::UnknownPort`] if the port isn't in `layout`.
+        /// In both cases, nothing is sent to the plugin.
+        ///
+        /// # Panics
+        ///
+        /// In debug builds, this panics if called from a different thread than the one the
+        /// `main_thread` token was acquired on.
+        #[track_caller]
+        pub fn remove_port<H: HostHandlers>(
+            &self,
+            instance: &mut PluginInstance<H>,
+            main_thread: &MainThreadToken,
+            layout: &mut AudioPortLayoutSnapshot,
+            is_input: bool,
+            port_id: ClapId,
+        ) -> Result<AudioPortsUpdate, ExtensibleAudioPortsError> {
+            if instance.is_active() {
+                return Err(ExtensibleAudioPortsError::PluginActive);
+            }
+
+            let ports = if is_input {
+                layout.inputs()
+            } else {
+                layout.outputs()
+            };
+
+            let index = ports
+                .iter()
+                .position(|p| p.id == port_id)
+                .ok_or(ExtensibleAudioPortsError::UnknownPort(port_id))?;
+
+            let mut plugin = instance.plugin_handle_checked(main_thread);
+            let audio_ports = plugin
+                .get_extension::<PluginAudioPorts>()
+                .ok_or(ExtensibleAudioPortsError::AudioPortsUnsupported)?;
+
+            let Some(remove_port) = plugin.use_extension(&self.0).remove_port else {
+                return Err(ExtensibleAudioPortsError::Rejected);
+            };
+
+            // SAFETY: This type ensures the function pointer is valid.
+            let removed = unsafe { remove_port(plugin.as_raw(), is_input, index as u32) };
+
+            if !removed {
+                return Err(ExtensibleAudioPortsError::Rejected);
+            }
+
+            let changes = rescan(&audio_ports, &mut plugin, layout);
+            Ok(AudioPortsUpdate { port_id, changes })
+        }
+    }
+
+    fn rescan(
+        audio_ports: &PluginAudioPorts,
+        plugin: &mut PluginMainThreadHandle,
+        layout: &mut AudioPortLayoutSnapshot,
+    ) -> Vec<AudioPortChange> {
+        let new_layout = AudioPortLayoutSnapshot::capture(audio_ports, plugin);
+        let changes = layout.diff(&new_layout);
+        *layout = new_layout;
+        changes
+    }
+}
+
+#[cfg(feature = "clack-plugin")]
+mod plugin {
+    use super::*;
+    use crate::audio_ports::{AudioPortInfoWriter, PluginAudioPortsImpl};
+    use clack_plugin::extensions::prelude::*;
+    use clap_sys::ext::audio_ports::clap_audio_port_info;
+    use std::ffi::c_char;
+    use std::mem::MaybeUninit;
+
+    /// Implementation of the Plugin-side of the Extensible Audio Ports extension.
+    ///
+    /// Both methods are only ever called on the main thread, while the plugin is deactivated.
+    /// The host rescans the plugin's audio ports after every successful call.
+    pub trait PluginExtensibleAudioPortsImpl {
+        /// Adds a new audio port, with the given number of channels and port type.
+        ///
+        /// Returns the ID of the new port, or an error if it couldn't be added.
+        fn add_port(
+            &mut self,
+            is_input: bool,
+            channel_count: u32,
+            port_type: Option<AudioPortType>,
+            details: PortDetails,
+        ) -> Result<ClapId, PluginError>;
+
+        /// Removes the audio port with the given ID.
+        ///
+        /// Returns an error if the port couldn't be removed, e.g. if it isn't a port that was
+        /// previously added.
+        fn remove_port(&mut self, is_input: bool, port_id: ClapId) -> Result<(), PluginError>;
+    }
+
+    // SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+    unsafe impl<P: Plugin> ExtensionImplementation<P> for PluginExtensibleAudioPorts
+    where
+        for<'a> P::MainThread<'a>: PluginExtensibleAudioPortsImpl + PluginAudioPortsImpl,
+    {
+        const IMPLEMENTATION: RawExtensionImplementation =
+            RawExtensionImplementation::new(&clap_plugin_extensible_audio_ports {
+                add_port: Some(add_port::<P>),
+                remove_port: Some(remove_port::<P>),
+            });
+    }
+
+    #[allow(clippy::missing_safety_doc)]
+    unsafe extern "C" fn add_port<P: Plugin>(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        channel_count: u32,
+        port_type: *const c_char,
+        port_details: *const c_void,
+    ) -> bool
+    where
+        for<'a> P::MainThread<'a>: PluginExtensibleAudioPortsImpl + PluginAudioPortsImpl,
+    {
+        PluginWrapper::<P>::handle_named(
+            plugin,
+            "clap_plugin_extensible_audio_ports.add_port",
+            |p| {
+                if p.is_active() {
+                    return Err(PluginWrapperError::DeactivationRequiredForFunction(
+                        "clap_plugin_extensible_audio_ports.add_port",
+                    ));
+                }
+
+                let port_type = if port_type.is_null() {
+                    None
+                } else {
+                    Some(AudioPortType(CStr::from_ptr(port_type)))
+                };
+
+                let details = PortDetails {
+                    raw: port_details,
+                    _lifetime: PhantomData,
+                };
+
+                Ok(p.main_thread()
+                    .as_mut()
+                    .add_port(is_input, channel_count, port_type, details)
+                    .is_ok())
+            },
+        )
+        .unwrap_or(false)
+    }
+
+    #[allow(clippy::missing_safety_doc)]
+    unsafe extern "C" fn remove_port<P: Plugin>(
+        plugin: *const clap_plugin,
+        is_input: bool,
+        index: u32,
+    ) -> bool
+    where
+        for<'a> P::MainThread<'a>: PluginExtensibleAudioPortsImpl + PluginAudioPortsImpl,
+    {
+        PluginWrapper::<P>::handle_named(
+            plugin,
+            "clap_plugin_extensible_audio_ports.remove_port",
+            |p| {
+                if p.is_active() {
+                    return Err(PluginWrapperError::DeactivationRequiredForFunction(
+                        "clap_plugin_extensible_audio_ports.remove_port",
+                    ));
+                }
+
+                let main_thread = p.main_thread().as_mut();
+
+                let mut info = MaybeUninit::<clap_audio_port_info>::uninit();
+                let mut writer = AudioPortInfoWriter::from_raw(info.as_mut_ptr());
+                main_thread.get(index, is_input, &mut writer);
+
+                let port_id = writer
+                    .written_id()
+                    .ok_or(PluginWrapperError::InvalidParameter("Invalid port index"))?;
+
+                Ok(main_thread.remove_port(is_input, port_id).is_ok())
+            },
+        )
+        .unwrap_or(false)
+    }
+}
+
+#[cfg(feature = "clack-plugin")]
+pub use plugin::*;
@@ -55,6 +55,8 @@
 //! * Adjust it to something acceptable by the plugin by calling `adjust_size(new_size)` to get
 //!   new `working_size`.
 //! * Once negotiated, call `set_size(working_size)` to let the plugin redraw and resize its UI.
+//!
+//! The [`resize`] module provides helpers to implement this negotiation on both sides.
 
 #![deny(missing_docs)]
 
@@ -65,6 +67,7 @@ use std::error::Error;
 use std::ffi::CStr;
 use std::fmt::{Debug, Display, Formatter};
 
+pub mod resize;
 mod window;
 pub use window::*;
 
@@ -1,3 +1,4 @@
+use super::resize::GuiResizeTarget;
 use super::*;
 use clack_host::extensions::prelude::*;
 
@@ -202,6 +203,16 @@ impl PluginGui {
         success.then_some(()).ok_or(GuiError::SetScaleError)
     }
 
+    /// Returns a [`GuiResizeTarget`] for this plugin's GUI, to be used with a
+    /// [`GuiResizeNegotiator`](super::resize::GuiResizeNegotiator).
+    #[inline]
+    pub fn resize_target<'a, 'p>(
+        &'a self,
+        plugin: &'a mut PluginMainThreadHandle<'p>,
+    ) -> PluginGuiResizeTarget<'a, 'p> {
+        PluginGuiResizeTarget { gui: self, plugin }
+    }
+
     /// Embeds the plugin's GUI into the given parent window.
     ///
     /// # Safety
@@ -295,6 +306,36 @@ impl PluginGui {
     }
 }
 
+/// A [`GuiResizeTarget`] that calls into a plugin's GUI extension.
+///
+/// See [`PluginGui::resize_target`].
+pub struct PluginGuiResizeTarget<'a, 'p> {
+    gui: &'a PluginGui,
+    plugin: &'a mut PluginMainThreadHandle<'p>,
+}
+
+impl GuiResizeTarget for PluginGuiResizeTarget<'_, '_> {
+    #[inline]
+    fn can_resize(&mut self) -> bool {
+        self.gui.can_resize(self.plugin)
+    }
+
+    #[inline]
+    fn adjust_size(&mut self, size: GuiSize) -> Option<GuiSize> {
+        self.gui.adjust_size(self.plugin, size)
+    }
+
+    #[inline]
+    fn set_size(&mut self, size: GuiSize) -> bool {
+        self.gui.set_size(self.plugin, size).is_ok()
+    }
+
+    #[inline]
+    fn get_size(&mut self) -> Option<GuiSize> {
+        self.gui.get_size(self.plugin)
+    }
+}
+
 /// Implementation of the Host-side of the GUI extension.
 pub trait HostGuiImpl {
     /// Notify the host that the plugin window's [`GuiResizeHints`] have changed, and
@@ -360,7 +401,7 @@ unsafe extern "C" fn resize_hints_changed<H: HostHandlers>(host: *const clap_hos
 where
     for<'a> <H as HostHandlers>::Shared<'a>: HostGuiImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_named(host, "clap_host_gui.resize_hints_changed", |host| {
         host.shared().resize_hints_changed();
         Ok(())
     });
@@ -375,7 +416,7 @@ unsafe extern "C" fn request_resize<H: HostHandlers>(
 where
     for<'a> <H as HostHandlers>::Shared<'a>: HostGuiImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_named(host, "clap_host_gui.request_resize", |host| {
         Ok(host
             .shared()
             .request_resize(GuiSize { width, height })
@@ -389,7 +430,10 @@ unsafe extern "C" fn request_show<H: HostHandlers>(host: *const clap_host) -> bo
 where
     for<'a> <H as HostHandlers>::Shared<'a>: HostGuiImpl,
 {
-    HostWrapper::<H>::handle(host, |host| Ok(host.shared().request_show().is_ok())).unwrap_or(false)
+    HostWrapper::<H>::handle_named(host, "clap_host_gui.request_show", |host| {
+        Ok(host.shared().request_show().is_ok())
+    })
+    .unwrap_or(false)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -397,7 +441,10 @@ unsafe extern "C" fn request_hide<H: HostHandlers>(host: *const clap_host) -> bo
 where
     for<'a> <H as HostHandlers>::Shared<'a>: HostGuiImpl,
 {
-    HostWrapper::<H>::handle(host, |host| Ok(host.shared().request_hide().is_ok())).unwrap_or(false)
+    HostWrapper::<H>::handle_named(host, "clap_host_gui.request_hide", |host| {
+        Ok(host.shared().request_hide().is_ok())
+    })
+    .unwrap_or(false)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -405,7 +452,7 @@ unsafe extern "C" fn closed<H: HostHandlers>(host: *const clap_host, was_destroy
 where
     for<'a> <H as HostHandlers>::Shared<'a>: HostGuiImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_named(host, "clap_host_gui.closed", |host| {
         host.shared().closed(was_destroyed);
         Ok(())
     });
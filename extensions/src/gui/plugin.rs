@@ -219,7 +219,7 @@ unsafe extern "C" fn is_api_supported<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.is_api_supported", |plugin| {
         Ok(plugin
             .main_thread()
             .as_mut()
@@ -240,7 +240,7 @@ unsafe extern "C" fn get_preferred_api<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.get_preferred_api", |plugin| {
         if api.is_null() || floating.is_null() {
             return Err(PluginWrapperError::NulPtr("get_preferred_api output"));
         }
@@ -270,7 +270,7 @@ unsafe extern "C" fn create<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.create", |plugin| {
         Ok(plugin
             .main_thread()
             .as_mut()
@@ -288,7 +288,7 @@ unsafe extern "C" fn destroy<P: Plugin>(plugin: *const clap_plugin)
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.destroy", |plugin| {
         plugin.main_thread().as_mut().destroy();
         Ok(())
     });
@@ -299,7 +299,7 @@ unsafe extern "C" fn set_scale<P: Plugin>(plugin: *const clap_plugin, scale: f64
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.set_scale", |plugin| {
         Ok(plugin.main_thread().as_mut().set_scale(scale).is_ok())
     })
     .unwrap_or(false)
@@ -314,7 +314,7 @@ unsafe extern "C" fn get_size<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.get_size", |plugin| {
         if let Some(size) = plugin.main_thread().as_mut().get_size() {
             *width = size.width;
             *height = size.height;
@@ -333,7 +333,7 @@ unsafe extern "C" fn can_resize<P: Plugin>(plugin: *const clap_plugin) -> bool
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.can_resize", |plugin| {
         Ok(plugin.main_thread().as_mut().can_resize())
     })
     .unwrap_or(false)
@@ -347,7 +347,7 @@ unsafe extern "C" fn get_resize_hints<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.get_resize_hints", |plugin| {
         if let Some(plugin_hints) = plugin.main_thread().as_mut().get_resize_hints() {
             *hints = plugin_hints.to_raw();
             Ok(true)
@@ -379,7 +379,7 @@ where
         return false;
     }
 
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.adjust_size", |plugin| {
         if width_adj.is_null() || height_adj.is_null() {
             return Err(PluginWrapperError::NulPtr("adjust_size output"));
         }
@@ -409,7 +409,7 @@ unsafe extern "C" fn set_size<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.set_size", |plugin| {
         let size = GuiSize { width, height };
         Ok(plugin.main_thread().as_mut().set_size(size))
     })
@@ -424,7 +424,7 @@ unsafe extern "C" fn set_parent<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.set_parent", |plugin| {
         let window = window
             .as_ref()
             .ok_or(PluginWrapperError::NulPtr("clap_window"))?;
@@ -446,7 +446,7 @@ unsafe extern "C" fn set_transient<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.set_transient", |plugin| {
         let window = window
             .as_ref()
             .ok_or(PluginWrapperError::NulPtr("clap_window"))?;
@@ -465,7 +465,7 @@ unsafe extern "C" fn suggest_title<P: Plugin>(plugin: *const clap_plugin, title:
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.suggest_title", |plugin| {
         let title = CStr::from_ptr(title)
             .to_str()
             .map_err(PluginWrapperError::StringEncoding)?;
@@ -481,7 +481,7 @@ unsafe extern "C" fn show<P: Plugin>(plugin: *const clap_plugin) -> bool
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.show", |plugin| {
         Ok(plugin.main_thread().as_mut().show().is_ok())
     })
     .unwrap_or(false)
@@ -492,7 +492,7 @@ unsafe extern "C" fn hide<P: Plugin>(plugin: *const clap_plugin) -> bool
 where
     for<'a> P::MainThread<'a>: PluginGuiImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |plugin| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_gui.hide", |plugin| {
         Ok(plugin.main_thread().as_mut().hide().is_ok())
     })
     .unwrap_or(false)
@@ -0,0 +1,735 @@
+//! Helpers to implement the resizing of embedded plugin windows correctly.
+//!
+//! Resizing an embedded window is a negotiation between the host and the plugin (see the
+//! [module-level documentation](super) for the full sequence). This module provides two
+//! independent, windowing-agnostic state machines to implement each side of it:
+//!
+//! * [`GuiResizer`], for plugins, which derives consistent answers to `can_resize`,
+//!   `get_resize_hints`, `adjust_size` and `set_size` from a single declaration of the window's
+//!   size constraints;
+//! * [`GuiResizeNegotiator`], for hosts, which drives the calls to the plugin in the right order,
+//!   and keeps track of the plugin's actual size when it refuses a new size.
+
+use super::{AspectRatioStrategy, GuiResizeHints, GuiSize};
+
+/// The size constraints of a plugin's embedded window, and its current size.
+///
+/// This is meant to be used by plugins to implement the resize-related methods of their GUI
+/// consistently: [`can_resize`](Self::can_resize), [`resize_hints`](Self::resize_hints),
+/// [`adjust_size`](Self::adjust_size) and [`set_size`](Self::set_size) all derive their results
+/// from the constraints given when building the resizer.
+///
+/// By default, the window is not resizable. Constraints are applied by
+/// [`adjust_size`](Self::adjust_size) in the following order: the aspect ratio (if any), the
+/// minimum and maximum sizes, and finally the size steps.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::gui::GuiSize;
+/// use clack_extensions::gui::resize::GuiResizer;
+///
+/// let resizer = GuiResizer::new(GuiSize { width: 400, height: 300 })
+///     .with_min_size(GuiSize { width: 200, height: 150 })
+///     .unbounded()
+///     .with_aspect_ratio(4, 3);
+///
+/// assert!(resizer.can_resize());
+/// assert_eq!(
+///     resizer.adjust_size(GuiSize { width: 1000, height: 600 }),
+///     GuiSize { width: 800, height: 600 }
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct GuiResizer {
+    size: GuiSize,
+    min_size: GuiSize,
+    max_size: GuiSize,
+    step: GuiSize,
+    aspect_ratio: Option<(u32, u32)>,
+    can_resize_horizontally: bool,
+    can_resize_vertically: bool,
+    pending_request: Option<GuiSize>,
+}
+
+impl GuiResizer {
+    /// Creates a new resizer for a window of the given initial size, which is not resizable.
+    ///
+    /// Use [`with_min_size`](Self::with_min_size) or [`with_max_size`](Self::with_max_size) to
+    /// make it resizable.
+    #[inline]
+    pub const fn new(initial_size: GuiSize) -> Self {
+        Self {
+            size: initial_size,
+            min_size: initial_size,
+            max_size: initial_size,
+            step: GuiSize {
+                width: 1,
+                height: 1,
+            },
+            aspect_ratio: None,
+            can_resize_horizontally: true,
+            can_resize_vertically: true,
+            pending_request: None,
+        }
+    }
+
+    /// Sets the minimum size of the window.
+    ///
+    /// By default, the minimum size is the initial size.
+    #[inline]
+    pub const fn with_min_size(mut self, min_size: GuiSize) -> Self {
+        self.min_size = min_size;
+        if self.max_size.width < min_size.width {
+            self.max_size.width = min_size.width;
+        }
+        if self.max_size.height < min_size.height {
+            self.max_size.height = min_size.height;
+        }
+        self
+    }
+
+    /// Sets the maximum size of the window.
+    ///
+    /// By default, the maximum size is the initial size. See also [`unbounded`](Self::unbounded).
+    #[inline]
+    pub const fn with_max_size(mut self, max_size: GuiSize) -> Self {
+        self.max_size = max_size;
+        if self.min_size.width > max_size.width {
+            self.min_size.width = max_size.width;
+        }
+        if self.min_size.height > max_size.height {
+            self.min_size.height = max_size.height;
+        }
+        self
+    }
+
+    /// Removes the upper bound of the window's size.
+    #[inline]
+    pub const fn unbounded(mut self) -> Self {
+        self.max_size = GuiSize {
+            width: u32::MAX,
+            height: u32::MAX,
+        };
+        self
+    }
+
+    /// Sets the size steps of the window: its width and height can only be the minimum size plus
+    /// a multiple of the given steps.
+    ///
+    /// Steps of `0` are treated as `1`.
+    #[inline]
+    pub const fn with_step(mut self, width: u32, height: u32) -> Self {
+        self.step = GuiSize {
+            width: if width == 0 { 1 } else { width },
+            height: if height == 0 { 1 } else { height },
+        };
+        self
+    }
+
+    /// Makes the window preserve the given aspect ratio when resized.
+    ///
+    /// This has no effect if a component of the ratio is `0`, or if the window can only be
+    /// resized along one axis.
+    #[inline]
+    pub const fn with_aspect_ratio(mut self, width: u32, height: u32) -> Self {
+        self.aspect_ratio = if width == 0 || height == 0 {
+            None
+        } else {
+            Some((width, height))
+        };
+        self
+    }
+
+    /// Restricts the axes the window can be resized along.
+    #[inline]
+    pub const fn with_resizable_axes(mut self, horizontally: bool, vertically: bool) -> Self {
+        self.can_resize_horizontally = horizontally;
+        self.can_resize_vertically = vertically;
+        self
+    }
+
+    /// Returns the current size of the window.
+    #[inline]
+    pub const fn size(&self) -> GuiSize {
+        self.size
+    }
+
+    /// Returns `true` if the window can be resized at all.
+    ///
+    /// This is the answer to the host's `can_resize` call.
+    #[inline]
+    pub const fn can_resize(&self) -> bool {
+        self.is_horizontally_resizable() || self.is_vertically_resizable()
+    }
+
+    /// Returns the resize hints of the window.
+    ///
+    /// This is the answer to the host's `get_resize_hints` call.
+    pub const fn resize_hints(&self) -> GuiResizeHints {
+        let can_resize_horizontally = self.is_horizontally_resizable();
+        let can_resize_vertically = self.is_vertically_resizable();
+
+        let strategy = match self.aspect_ratio {
+            Some((width, height)) if can_resize_horizontally && can_resize_vertically => {
+                AspectRatioStrategy::Preserve { width, height }
+            }
+            _ => AspectRatioStrategy::Disregard,
+        };
+
+        GuiResizeHints {
+            can_resize_horizontally,
+            can_resize_vertically,
+            strategy,
+        }
+    }
+
+    /// Returns the size closest to the given one that satisfies all of the window's constraints.
+    ///
+    /// This is the answer to the host's `adjust_size` call. Whenever possible, the returned size
+    /// fits within the requested one.
+    pub fn adjust_size(&self, requested: GuiSize) -> GuiSize {
+        let mut width = if self.is_horizontally_resizable() {
+            requested.width
+        } else {
+            self.size.width
+        };
+        let mut height = if self.is_vertically_resizable() {
+            requested.height
+        } else {
+            self.size.height
+        };
+
+        if let AspectRatioStrategy::Preserve {
+            width: ratio_width,
+            height: ratio_height,
+        } = self.resize_hints().strategy
+        {
+            // Take the largest size with the right aspect ratio that fits in the requested one,
+            // then enforce the bounds on the width, and derive the height from it.
+            let width_from_height = scale(height, ratio_width, ratio_height);
+            width = width.min(width_from_height);
+            width = self.snap(
+                width,
+                self.min_size.width,
+                self.max_size.width,
+                self.step.width,
+            );
+
+            height = scale(width, ratio_height, ratio_width);
+            if height < self.min_size.height || height > self.max_size.height {
+                height = height.clamp(self.min_size.height, self.max_size.height);
+                width = self.snap(
+                    scale(height, ratio_width, ratio_height),
+                    self.min_size.width,
+                    self.max_size.width,
+                    self.step.width,
+                );
+            }
+        } else {
+            width = self.snap(
+                width,
+                self.min_size.width,
+                self.max_size.width,
+                self.step.width,
+            );
+            height = self.snap(
+                height,
+                self.min_size.height,
+                self.max_size.height,
+                self.step.height,
+            );
+        }
+
+        GuiSize { width, height }
+    }
+
+    /// Returns `true` if the given size satisfies all of the window's constraints, i.e. if
+    /// [`adjust_size`](Self::adjust_size) would leave it untouched.
+    #[inline]
+    pub fn is_valid_size(&self, size: GuiSize) -> bool {
+        self.adjust_size(size) == size
+    }
+
+    /// Sets the current size of the window, if it is valid.
+    ///
+    /// This is the answer to the host's `set_size` call: it returns `false`, leaving the current
+    /// size untouched, if the given size doesn't satisfy the window's constraints. Hosts should
+    /// always call `adjust_size` first, but may not.
+    ///
+    /// This also completes any [pending resize request](Self::request_resize).
+    pub fn set_size(&mut self, size: GuiSize) -> bool {
+        if !self.is_valid_size(size) {
+            return false;
+        }
+
+        self.size = size;
+        self.pending_request = None;
+        true
+    }
+
+    /// Prepares a plugin-initiated resize request to the given size.
+    ///
+    /// This returns the adjusted size to be sent to the host with `request_resize`, or `None` if
+    /// the window is not resizable or is already of that size. The request is then pending until
+    /// either the host confirms it with a `set_size` call, the host accepts it (see
+    /// [`confirm_request`](Self::confirm_request)), or it is [cancelled](Self::cancel_request).
+    pub fn request_resize(&mut self, size: GuiSize) -> Option<GuiSize> {
+        if !self.can_resize() {
+            return None;
+        }
+
+        let adjusted = self.adjust_size(size);
+        if adjusted == self.size {
+            return None;
+        }
+
+        self.pending_request = Some(adjusted);
+        Some(adjusted)
+    }
+
+    /// Returns the size of the pending resize request, if any.
+    #[inline]
+    pub const fn pending_request(&self) -> Option<GuiSize> {
+        self.pending_request
+    }
+
+    /// Applies the pending resize request, after the host accepted it.
+    ///
+    /// Hosts are not required to call `set_size` after accepting a `request_resize` call, so the
+    /// plugin should call this when `request_resize` succeeds. This returns the new size, or
+    /// `None` if no request was pending.
+    pub fn confirm_request(&mut self) -> Option<GuiSize> {
+        let size = self.pending_request.take()?;
+        self.size = size;
+        Some(size)
+    }
+
+    /// Cancels the pending resize request, e.g. after the host refused it.
+    #[inline]
+    pub fn cancel_request(&mut self) {
+        self.pending_request = None;
+    }
+
+    #[inline]
+    const fn is_horizontally_resizable(&self) -> bool {
+        self.can_resize_horizontally && self.min_size.width < self.max_size.width
+    }
+
+    #[inline]
+    const fn is_vertically_resizable(&self) -> bool {
+        self.can_resize_vertically && self.min_size.height < self.max_size.height
+    }
+
+    /// Clamps the given dimension to the given bounds, and rounds it down to the closest step.
+    fn snap(&self, value: u32, min: u32, max: u32, step: u32) -> u32 {
+        let value = value.clamp(min, max);
+        min + (value - min) / step * step
+    }
+}
+
+/// Returns `value * numerator / denominator`, saturating on overflow.
+#[inline]
+fn scale(value: u32, numerator: u32, denominator: u32) -> u32 {
+    let scaled = value as u64 * numerator as u64 / denominator as u64;
+    scaled.min(u32::MAX as u64) as u32
+}
+
+/// The plugin-side operations the [`GuiResizeNegotiator`] needs to perform.
+///
+/// With the `clack-host` feature, this is implemented by `PluginGuiResizeTarget`, which calls the
+/// plugin's GUI extension.
+pub trait GuiResizeTarget {
+    /// Returns `true` if the plugin's window can be resized.
+    fn can_resize(&mut self) -> bool;
+    /// Asks the plugin for the closest size it supports, or returns `None` if it didn't answer.
+    fn adjust_size(&mut self, size: GuiSize) -> Option<GuiSize>;
+    /// Sets the size of the plugin's window, returning `false` if the plugin refused it.
+    fn set_size(&mut self, size: GuiSize) -> bool;
+    /// Returns the current size of the plugin's window, if the plugin provided it.
+    fn get_size(&mut self) -> Option<GuiSize>;
+}
+
+/// The result of a resize negotiation, as performed by a [`GuiResizeNegotiator`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GuiResizeOutcome {
+    /// The plugin's window was resized to the given size. The host should resize its parent
+    /// window's client area to match.
+    Resized(GuiSize),
+    /// The plugin refused the new size. The host should restore its parent window's client area
+    /// to the given size, which is the plugin's actual current size, if known.
+    Refused(Option<GuiSize>),
+    /// The plugin's window is not resizable. The host should prevent the user from resizing it.
+    NotResizable(Option<GuiSize>),
+}
+
+impl GuiResizeOutcome {
+    /// Returns the size the host's parent window should have after this negotiation, if known.
+    #[inline]
+    pub fn size(&self) -> Option<GuiSize> {
+        match *self {
+            Self::Resized(size) => Some(size),
+            Self::Refused(size) | Self::NotResizable(size) => size,
+        }
+    }
+}
+
+/// Host-side state to negotiate the size of a plugin's embedded window.
+///
+/// This drives the plugin calls in the order required by the CLAP specification (`can_resize`,
+/// then `adjust_size`, then `set_size`), and keeps track of the plugin's actual size, including
+/// when it refuses a size it was given.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct GuiResizeNegotiator {
+    size: Option<GuiSize>,
+    can_resize: Option<bool>,
+}
+
+impl GuiResizeNegotiator {
+    /// Creates a new negotiator, which doesn't know the plugin's window size yet.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            size: None,
+            can_resize: None,
+        }
+    }
+
+    /// Returns the last known size of the plugin's window.
+    #[inline]
+    pub const fn size(&self) -> Option<GuiSize> {
+        self.size
+    }
+
+    /// Queries the plugin's current window size, e.g. after creating its GUI.
+    pub fn sync(&mut self, target: &mut impl GuiResizeTarget) -> Option<GuiSize> {
+        self.size = target.get_size();
+        self.size
+    }
+
+    /// Forgets whether the plugin's window is resizable, so that it is queried again on the next
+    /// negotiation.
+    ///
+    /// This should be called when the plugin notifies the host its resize hints changed.
+    #[inline]
+    pub fn resize_hints_changed(&mut self) {
+        self.can_resize = None;
+    }
+
+    /// Negotiates a new size for the plugin's window, e.g. after the user resized the host's
+    /// parent window.
+    ///
+    /// The requested size is first adjusted by the plugin, then applied with `set_size`. If the
+    /// plugin refuses the adjusted size, its actual size is queried again.
+    pub fn resize(
+        &mut self,
+        target: &mut impl GuiResizeTarget,
+        requested: GuiSize,
+    ) -> GuiResizeOutcome {
+        if !self.can_resize(target) {
+            return GuiResizeOutcome::NotResizable(self.size);
+        }
+
+        let adjusted = target.adjust_size(requested).unwrap_or(requested);
+        self.apply(target, adjusted)
+    }
+
+    /// Handles a resize request from the plugin (i.e. a `request_resize` call), given the space
+    /// available to the plugin window, if limited.
+    ///
+    /// If the requested size fits, it is confirmed to the plugin with `set_size`. Otherwise, a
+    /// size that fits in the available space is negotiated instead, like
+    /// [`resize`](Self::resize) does.
+    ///
+    /// Hosts should only report the request as accepted to the plugin if the outcome is
+    /// [`GuiResizeOutcome::Resized`] with the requested size (see
+    /// [`accepts_request`](Self::accepts_request)).
+    pub fn handle_request(
+        &mut self,
+        target: &mut impl GuiResizeTarget,
+        requested: GuiSize,
+        available: Option<GuiSize>,
+    ) -> GuiResizeOutcome {
+        match available {
+            Some(available)
+                if requested.width > available.width || requested.height > available.height =>
+            {
+                let bounded = GuiSize {
+                    width: requested.width.min(available.width),
+                    height: requested.height.min(available.height),
+                };
+
+                self.resize(target, bounded)
+            }
+            _ => self.apply(target, requested),
+        }
+    }
+
+    /// Returns `true` if the given outcome of [`handle_request`](Self::handle_request) means the
+    /// plugin's request for the given size was accepted as-is.
+    #[inline]
+    pub fn accepts_request(outcome: GuiResizeOutcome, requested: GuiSize) -> bool {
+        outcome == GuiResizeOutcome::Resized(requested)
+    }
+
+    fn can_resize(&mut self, target: &mut impl GuiResizeTarget) -> bool {
+        *self.can_resize.get_or_insert_with(|| target.can_resize())
+    }
+
+    fn apply(&mut self, target: &mut impl GuiResizeTarget, size: GuiSize) -> GuiResizeOutcome {
+        if Some(size) == self.size {
+            return GuiResizeOutcome::Resized(size);
+        }
+
+        if target.set_size(size) {
+            self.size = Some(size);
+            GuiResizeOutcome::Resized(size)
+        } else {
+            // The plugin may have ended up with any size: ask it again.
+            if let Some(actual) = target.get_size() {
+                self.size = Some(actual);
+            }
+
+            GuiResizeOutcome::Refused(self.size)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const fn size(width: u32, height: u32) -> GuiSize {
+        GuiSize { width, height }
+    }
+
+    #[test]
+    fn fixed_size_windows_are_not_resizable() {
+        let mut resizer = GuiResizer::new(size(400, 300));
+
+        assert!(!resizer.can_resize());
+        assert_eq!(
+            resizer.resize_hints(),
+            GuiResizeHints {
+                can_resize_horizontally: false,
+                can_resize_vertically: false,
+                strategy: AspectRatioStrategy::Disregard,
+            }
+        );
+        assert_eq!(resizer.adjust_size(size(800, 600)), size(400, 300));
+        assert!(!resizer.set_size(size(800, 600)));
+        assert!(resizer.set_size(size(400, 300)));
+        assert_eq!(resizer.request_resize(size(800, 600)), None);
+    }
+
+    #[test]
+    fn sizes_are_clamped_and_stepped() {
+        let resizer = GuiResizer::new(size(400, 300))
+            .with_min_size(size(200, 100))
+            .with_max_size(size(1000, 800))
+            .with_step(10, 25);
+
+        assert!(resizer.can_resize());
+        assert_eq!(resizer.adjust_size(size(10, 10)), size(200, 100));
+        assert_eq!(resizer.adjust_size(size(2000, 2000)), size(1000, 800));
+        assert_eq!(resizer.adjust_size(size(437, 437)), size(430, 425));
+        assert!(resizer.is_valid_size(size(430, 425)));
+        assert!(!resizer.is_valid_size(size(431, 425)));
+    }
+
+    #[test]
+    fn aspect_ratio_is_preserved() {
+        let resizer = GuiResizer::new(size(400, 300))
+            .with_min_size(size(200, 150))
+            .with_max_size(size(1600, 1200))
+            .with_aspect_ratio(4, 3);
+
+        assert_eq!(
+            resizer.resize_hints().strategy,
+            AspectRatioStrategy::Preserve {
+                width: 4,
+                height: 3
+            }
+        );
+
+        // The result always fits in the requested size.
+        assert_eq!(resizer.adjust_size(size(1000, 600)), size(800, 600));
+        assert_eq!(resizer.adjust_size(size(800, 1000)), size(800, 600));
+
+        // ... unless it is too small.
+        assert_eq!(resizer.adjust_size(size(100, 100)), size(200, 150));
+        assert_eq!(resizer.adjust_size(size(5000, 5000)), size(1600, 1200));
+
+        for requested in [size(123, 456), size(999, 333), size(640, 480)] {
+            let adjusted = resizer.adjust_size(requested);
+            assert_eq!(adjusted.width * 3, adjusted.height * 4);
+            assert!(resizer.is_valid_size(adjusted));
+        }
+    }
+
+    #[test]
+    fn single_axis_resizing_ignores_the_other_axis() {
+        let resizer = GuiResizer::new(size(400, 300))
+            .unbounded()
+            .with_aspect_ratio(4, 3)
+            .with_resizable_axes(true, false);
+
+        let hints = resizer.resize_hints();
+        assert!(hints.can_resize_horizontally);
+        assert!(!hints.can_resize_vertically);
+        assert_eq!(hints.strategy, AspectRatioStrategy::Disregard);
+
+        assert_eq!(resizer.adjust_size(size(900, 900)), size(900, 300));
+    }
+
+    #[test]
+    fn plugin_requests_are_tracked() {
+        let mut resizer = GuiResizer::new(size(400, 300))
+            .unbounded()
+            .with_step(100, 100);
+
+        assert_eq!(resizer.request_resize(size(450, 300)), None);
+        assert_eq!(resizer.request_resize(size(650, 420)), Some(size(600, 400)));
+        assert_eq!(resizer.pending_request(), Some(size(600, 400)));
+
+        resizer.cancel_request();
+        assert_eq!(resizer.pending_request(), None);
+        assert_eq!(resizer.confirm_request(), None);
+        assert_eq!(resizer.size(), size(400, 300));
+
+        resizer.request_resize(size(600, 400));
+        assert_eq!(resizer.confirm_request(), Some(size(600, 400)));
+        assert_eq!(resizer.size(), size(600, 400));
+
+        // The host may also confirm the request by calling set_size.
+        resizer.request_resize(size(800, 800));
+        assert!(resizer.set_size(size(800, 800)));
+        assert_eq!(resizer.pending_request(), None);
+    }
+
+    /// A fake plugin, recording the calls made to it.
+    struct FakePlugin {
+        resizer: GuiResizer,
+        refuses_set_size: bool,
+        calls: Vec<&'static str>,
+    }
+
+    impl FakePlugin {
+        fn new(resizer: GuiResizer) -> Self {
+            Self {
+                resizer,
+                refuses_set_size: false,
+                calls: Vec::new(),
+            }
+        }
+    }
+
+    impl GuiResizeTarget for FakePlugin {
+        fn can_resize(&mut self) -> bool {
+            self.calls.push("can_resize");
+            self.resizer.can_resize()
+        }
+
+        fn adjust_size(&mut self, size: GuiSize) -> Option<GuiSize> {
+            self.calls.push("adjust_size");
+            Some(self.resizer.adjust_size(size))
+        }
+
+        fn set_size(&mut self, size: GuiSize) -> bool {
+            self.calls.push("set_size");
+            !self.refuses_set_size && self.resizer.set_size(size)
+        }
+
+        fn get_size(&mut self) -> Option<GuiSize> {
+            self.calls.push("get_size");
+            Some(self.resizer.size())
+        }
+    }
+
+    fn resizable_plugin() -> FakePlugin {
+        FakePlugin::new(
+            GuiResizer::new(size(400, 300))
+                .with_max_size(size(800, 600))
+                .with_step(50, 50),
+        )
+    }
+
+    #[test]
+    fn negotiator_follows_the_call_sequence() {
+        let mut plugin = resizable_plugin();
+        let mut negotiator = GuiResizeNegotiator::new();
+
+        assert_eq!(negotiator.sync(&mut plugin), Some(size(400, 300)));
+        assert_eq!(
+            negotiator.resize(&mut plugin, size(520, 380)),
+            GuiResizeOutcome::Resized(size(500, 350))
+        );
+        assert_eq!(
+            plugin.calls,
+            ["get_size", "can_resize", "adjust_size", "set_size"]
+        );
+
+        // can_resize is only queried again after the resize hints changed.
+        plugin.calls.clear();
+        negotiator.resize(&mut plugin, size(600, 400));
+        negotiator.resize_hints_changed();
+        negotiator.resize(&mut plugin, size(700, 400));
+        assert_eq!(
+            plugin.calls,
+            [
+                "adjust_size",
+                "set_size",
+                "can_resize",
+                "adjust_size",
+                "set_size"
+            ]
+        );
+        assert_eq!(negotiator.size(), Some(size(700, 400)));
+    }
+
+    #[test]
+    fn negotiator_handles_refused_sizes() {
+        let mut plugin = resizable_plugin();
+        let mut negotiator = GuiResizeNegotiator::new();
+        negotiator.sync(&mut plugin);
+
+        plugin.refuses_set_size = true;
+        assert_eq!(
+            negotiator.resize(&mut plugin, size(600, 400)),
+            GuiResizeOutcome::Refused(Some(size(400, 300)))
+        );
+        assert_eq!(negotiator.size(), Some(size(400, 300)));
+
+        let mut fixed = FakePlugin::new(GuiResizer::new(size(400, 300)));
+        let mut negotiator = GuiResizeNegotiator::new();
+        negotiator.sync(&mut fixed);
+
+        let outcome = negotiator.resize(&mut fixed, size(600, 400));
+        assert_eq!(
+            outcome,
+            GuiResizeOutcome::NotResizable(Some(size(400, 300)))
+        );
+        assert_eq!(outcome.size(), Some(size(400, 300)));
+        assert!(!fixed.calls.contains(&"set_size"));
+    }
+
+    #[test]
+    fn negotiator_handles_plugin_requests() {
+        let mut plugin = resizable_plugin();
+        let mut negotiator = GuiResizeNegotiator::new();
+        negotiator.sync(&mut plugin);
+
+        let requested = plugin.resizer.request_resize(size(600, 450)).unwrap();
+        let outcome = negotiator.handle_request(&mut plugin, requested, None);
+        assert!(GuiResizeNegotiator::accepts_request(outcome, requested));
+        assert_eq!(plugin.resizer.size(), requested);
+        assert_eq!(plugin.resizer.pending_request(), None);
+
+        // Requests that don't fit are negotiated down to the available space.
+        let requested = plugin.resizer.request_resize(size(800, 600)).unwrap();
+        let outcome = negotiator.handle_request(&mut plugin, requested, Some(size(720, 720)));
+        assert!(!GuiResizeNegotiator::accepts_request(outcome, requested));
+        assert_eq!(outcome, GuiResizeOutcome::Resized(size(700, 600)));
+        assert_eq!(plugin.resizer.size(), size(700, 600));
+    }
+}
@@ -5,8 +5,14 @@
 pub mod audio_ports;
 #[cfg(feature = "audio-ports-config")]
 pub mod audio_ports_config;
+#[cfg(feature = "clack-plugin")]
+pub mod capabilities;
+#[cfg(feature = "context-menu")]
+pub mod context_menu;
 #[cfg(feature = "event-registry")]
 pub mod event_registry;
+#[cfg(feature = "extensible-audio-ports")]
+pub mod extensible_audio_ports;
 #[cfg(feature = "gui")]
 pub mod gui;
 #[cfg(feature = "latency")]
@@ -36,8 +42,21 @@ pub mod timer;
 #[cfg(feature = "voice-info")]
 pub mod voice_info;
 
+pub mod prelude;
+
+mod macros;
 pub(crate) mod utils;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use clack_common;
+    #[cfg(feature = "clack-host")]
+    pub use clack_host;
+    #[cfg(feature = "clack-plugin")]
+    pub use clack_plugin;
+    pub use clap_sys;
+}
+
 #[cfg(test)]
 #[doc(hidden)]
 pub mod __doc_utils;
@@ -2,6 +2,7 @@ use clack_common::extensions::{Extension, HostExtensionSide, RawExtension};
 use clap_sys::ext::log::{clap_host_log, clap_log_severity, CLAP_EXT_LOG};
 use std::ffi::CStr;
 use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicI32, Ordering};
 
 mod error;
 pub use error::LogError;
@@ -46,6 +47,34 @@ impl LogSeverity {
     pub fn to_raw(self) -> clap_log_severity {
         self as _
     }
+
+    /// Returns `true` if this severity reports the host or the plugin misbehaving.
+    #[inline]
+    pub const fn is_misbehaving(self) -> bool {
+        matches!(
+            self,
+            LogSeverity::HostMisbehaving | LogSeverity::PluginMisbehaving
+        )
+    }
+
+    /// Returns `true` if a message of this severity should be kept when only messages of the
+    /// given `minimum` severity or above are wanted.
+    ///
+    /// Misbehaving severities are always kept, as they report bugs rather than regular messages.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_extensions::log::LogSeverity;
+    ///
+    /// assert!(LogSeverity::Error.passes(LogSeverity::Warning));
+    /// assert!(!LogSeverity::Debug.passes(LogSeverity::Warning));
+    /// assert!(LogSeverity::HostMisbehaving.passes(LogSeverity::PluginMisbehaving));
+    /// ```
+    #[inline]
+    pub fn passes(self, minimum: LogSeverity) -> bool {
+        self.is_misbehaving() || self >= minimum
+    }
 }
 
 impl Display for LogSeverity {
@@ -64,6 +93,124 @@ impl Display for LogSeverity {
     }
 }
 
+/// A minimum log severity, which can be changed at any time from any thread.
+///
+/// Hosts can keep one of these per plugin instance (e.g. in their `HostLogImpl` implementation)
+/// to filter out each instance's messages below a given severity.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::log::{LogFilter, LogSeverity};
+///
+/// let filter = LogFilter::new(LogSeverity::Info);
+/// assert!(!filter.allows(LogSeverity::Debug));
+///
+/// filter.set_minimum(LogSeverity::Debug);
+/// assert!(filter.allows(LogSeverity::Debug));
+/// ```
+#[derive(Debug)]
+pub struct LogFilter {
+    minimum: AtomicI32,
+}
+
+impl LogFilter {
+    /// Creates a new filter, which only allows messages of the given `minimum` severity or above.
+    #[inline]
+    pub const fn new(minimum: LogSeverity) -> Self {
+        Self {
+            minimum: AtomicI32::new(minimum as i32),
+        }
+    }
+
+    /// Returns the current minimum severity.
+    #[inline]
+    pub fn minimum(&self) -> LogSeverity {
+        LogSeverity::from_raw(self.minimum.load(Ordering::Relaxed)).unwrap_or(LogSeverity::Debug)
+    }
+
+    /// Sets the minimum severity.
+    #[inline]
+    pub fn set_minimum(&self, minimum: LogSeverity) {
+        self.minimum.store(minimum.to_raw(), Ordering::Relaxed)
+    }
+
+    /// Returns `true` if a message of the given severity passes this filter.
+    ///
+    /// See [`LogSeverity::passes`].
+    #[inline]
+    pub fn allows(&self, severity: LogSeverity) -> bool {
+        severity.passes(self.minimum())
+    }
+}
+
+impl Default for LogFilter {
+    /// Returns a filter which allows all messages.
+    #[inline]
+    fn default() -> Self {
+        Self::new(LogSeverity::Debug)
+    }
+}
+
+/// Whether the [`clap_log_debug`](crate::clap_log_debug) macro sends its messages to the host.
+///
+/// This is `true` in debug builds, or if the `debug-logs-in-release` feature is enabled.
+pub const DEBUG_LOGS_ENABLED: bool = cfg!(any(debug_assertions, feature = "debug-logs-in-release"));
+
+/// Formats a message and sends it to the host's `log` extension.
+///
+/// This takes the [`HostLog`] extension, the [`HostSharedHandle`](clack_plugin::host::HostSharedHandle)
+/// it was retrieved from, the message's [`LogSeverity`], and then formatting arguments, like
+/// [`format!`]. It evaluates to a `Result<(), LogError>`.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::clap_log;
+/// use clack_extensions::log::{HostLog, LogSeverity};
+/// use clack_plugin::host::HostSharedHandle;
+///
+/// fn report(log: &HostLog, host: &HostSharedHandle, voices: usize) {
+///     let _ = clap_log!(log, host, LogSeverity::Info, "Now using {voices} voices");
+/// }
+/// ```
+#[cfg(feature = "clack-plugin")]
+#[macro_export]
+macro_rules! clap_log {
+    ($log:expr, $host:expr, $severity:expr, $($arg:tt)+) => {
+        $log.log_fmt($host, $severity, ::core::format_args!($($arg)+))
+    };
+}
+
+/// Formats a message and sends it to the host's `log` extension with the
+/// [`Debug`](LogSeverity::Debug) severity, only if [`DEBUG_LOGS_ENABLED`] is `true`.
+///
+/// Otherwise, the message is neither formatted nor sent, and the whole call is compiled out.
+/// This takes the same arguments as [`clap_log`](crate::clap_log), minus the severity.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::clap_log_debug;
+/// use clack_extensions::log::HostLog;
+/// use clack_plugin::host::HostSharedHandle;
+///
+/// fn trace(log: &HostLog, host: &HostSharedHandle, block: u64) {
+///     let _ = clap_log_debug!(log, host, "Processing block {block}");
+/// }
+/// ```
+#[cfg(feature = "clack-plugin")]
+#[macro_export]
+macro_rules! clap_log_debug {
+    ($log:expr, $host:expr, $($arg:tt)+) => {
+        if $crate::log::DEBUG_LOGS_ENABLED {
+            $crate::clap_log!($log, $host, $crate::log::LogSeverity::Debug, $($arg)+)
+        } else {
+            ::core::result::Result::<(), $crate::log::LogError>::Ok(())
+        }
+    };
+}
+
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
 pub struct HostLog(RawExtension<HostExtensionSide, clap_host_log>);
@@ -83,6 +230,8 @@ unsafe impl Extension for HostLog {
 mod plugin {
     use super::*;
     use clack_plugin::host::HostSharedHandle;
+    use std::ffi::CString;
+    use std::fmt::{Arguments, Write};
 
     impl HostLog {
         #[inline]
@@ -92,5 +241,22 @@ mod plugin {
                 unsafe { log(host.as_raw(), log_severity.to_raw(), message.as_ptr()) }
             }
         }
+
+        /// Formats the given message and sends it to the host.
+        ///
+        /// This allocates. See also the [`clap_log`](crate::clap_log) and
+        /// [`clap_log_debug`](crate::clap_log_debug) macros.
+        pub fn log_fmt(
+            &self,
+            host: &HostSharedHandle,
+            log_severity: LogSeverity,
+            message: Arguments,
+        ) -> Result<(), LogError> {
+            let mut buf = String::new();
+            buf.write_fmt(message)?;
+
+            self.log(host, log_severity, &CString::new(buf)?);
+            Ok(())
+        }
     }
 }
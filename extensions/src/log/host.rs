@@ -1,4 +1,5 @@
 use super::{HostLog, LogSeverity};
+use clack_common::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
 use clack_host::extensions::prelude::*;
 use clap_sys::ext::log::{clap_host_log, clap_log_severity};
 use std::borrow::Cow::Owned;
@@ -29,7 +30,7 @@ unsafe extern "C" fn log<H: HostHandlers>(
     for<'a> <H as HostHandlers>::Shared<'a>: HostLogImpl,
 {
     let msg = CStr::from_ptr(msg).to_string_lossy();
-    let res = HostWrapper::<H>::handle(host, |host| {
+    let res = HostWrapper::<H>::handle_named(host, "clap_host_log.log", |host| {
         let host = host.shared();
         let log_severity = LogSeverity::from_raw(severity);
 
@@ -53,6 +54,12 @@ unsafe extern "C" fn log<H: HostHandlers>(
     });
 
     if res.is_none() {
-        eprintln!("[ERROR] Log handler failed when writing message: {msg}")
+        fallback_log(&LogRecord {
+            origin: LogOrigin::Host,
+            severity,
+            plugin_id: None,
+            callback: "clap_host_log.log",
+            message: &format_args!("Log handler failed when writing message: {msg}"),
+        })
     }
 }
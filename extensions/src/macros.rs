@@ -0,0 +1,459 @@
+//! The [`declare_extension`](crate::declare_extension) macro, and its side-specific helpers.
+//!
+//! The helpers are defined twice: once generating the glue code when the matching side's feature
+//! (`clack-plugin` or `clack-host`) is enabled, and once generating nothing otherwise.
+
+/// Generates the Clack bindings of a CLAP extension from its C struct definition.
+///
+/// This is meant for vendor-specific extensions, which are shipped as C headers and aren't part
+/// of this crate. Given the extension's raw struct, its identifier, and the thread each of its
+/// functions may be called on, this macro generates:
+///
+/// * the extension type itself, and its [`Extension`](clack_common::extensions::Extension)
+///   implementation;
+/// * on the side implementing the extension, one trait per thread, and the
+///   [`ExtensionImplementation`](clack_common::extensions::ExtensionImplementation) glue calling
+///   into it through the plugin or host wrapper. Panics and errors are logged, and the declared
+///   default value is returned to the caller instead;
+/// * on the side using the extension, methods on the extension type calling the raw function
+///   pointers. If the other side left a function pointer null, the declared default value is
+///   returned instead.
+///
+/// The side-specific items are only generated if this crate's matching `clack-plugin` or
+/// `clack-host` feature is enabled.
+///
+/// # Syntax
+///
+/// The extension type is declared as a tuple struct, followed by the side that implements it
+/// (`plugin` or `host`), the raw struct's path, and the extension's identifier. The raw struct has
+/// to be `Copy`, which is the case of the ones generated by `bindgen`.
+///
+/// It is then followed by one or more traits, each declaring the thread its functions are called
+/// on:
+///
+/// * `main_thread`: functions marked `[main-thread]`. The trait has to be implemented on the
+///   plugin's (or host's) `MainThread` type, and receives `&mut self`;
+/// * `audio_thread`: functions marked `[audio-thread]`. The trait has to be implemented on the
+///   plugin's (or host's) `AudioProcessor` type, and receives `&mut self`;
+/// * `any_thread`: functions marked `[thread-safe]`. The trait has to be implemented on the
+///   plugin's (or host's) `Shared` type, and receives `&self`.
+///
+/// Each function is declared with the same name and arguments as the matching field of the raw
+/// struct, minus the leading plugin or host pointer. Functions returning a value also declare the
+/// default value to return when the call can't be performed.
+///
+/// Arguments and return values are passed through as-is: any pointer the extension uses is
+/// handed to the trait implementation unchanged, and it is up to it to uphold the extension's
+/// safety requirements.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::declare_extension;
+/// use clap_sys::plugin::clap_plugin;
+/// use std::ffi::CStr;
+///
+/// // Those would usually come from the vendor's header bindings.
+/// pub const EXT_VENDOR_GAIN: &CStr =
+///     match CStr::from_bytes_with_nul(b"com.example.vendor-gain\0") {
+///         Ok(id) => id,
+///         Err(_) => panic!(),
+///     };
+///
+/// #[repr(C)]
+/// #[derive(Copy, Clone)]
+/// pub struct clap_plugin_vendor_gain {
+///     pub get_gain: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> f64>,
+///     pub set_gain: Option<unsafe extern "C" fn(plugin: *const clap_plugin, gain: f64)>,
+///     pub is_clipping: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> bool>,
+/// }
+///
+/// declare_extension! {
+///     /// The plugin side of the vendor's gain extension.
+///     pub struct PluginVendorGain: plugin(clap_plugin_vendor_gain) = EXT_VENDOR_GAIN;
+///
+///     /// Main-thread functions of the vendor's gain extension.
+///     pub trait PluginVendorGainImpl: main_thread {
+///         /// Returns the current gain.
+///         fn get_gain() -> f64 = 1.0;
+///         /// Sets the current gain.
+///         fn set_gain(gain: f64);
+///     }
+///
+///     /// Audio-thread functions of the vendor's gain extension.
+///     pub trait PluginVendorGainAudioProcessor: audio_thread {
+///         /// Returns whether the output clipped during the last process call.
+///         fn is_clipping() -> bool = false;
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_extension {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: plugin($raw:path) = $id:expr;
+        $($traits:tt)+
+    ) => {
+        $crate::declare_extension!(@type $(#[$meta])* $vis $name, PluginExtensionSide, $raw, $id);
+        $crate::__declare_extension_plugin_side!(implement $name, $raw; $($traits)+);
+        $crate::__declare_extension_host_side!(call $name; $($traits)+);
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: host($raw:path) = $id:expr;
+        $($traits:tt)+
+    ) => {
+        $crate::declare_extension!(@type $(#[$meta])* $vis $name, HostExtensionSide, $raw, $id);
+        $crate::__declare_extension_host_side!(implement $name, $raw; $($traits)+);
+        $crate::__declare_extension_plugin_side!(call $name; $($traits)+);
+    };
+    (@type $(#[$meta:meta])* $vis:vis $name:ident, $side:ident, $raw:path, $id:expr) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone)]
+        #[allow(dead_code)]
+        $vis struct $name(
+            $crate::__private::clack_common::extensions::RawExtension<
+                $crate::__private::clack_common::extensions::$side,
+                $raw,
+            >,
+        );
+
+        // SAFETY: This type is repr(C) and ABI-compatible with the matching extension type.
+        unsafe impl $crate::__private::clack_common::extensions::Extension for $name {
+            const IDENTIFIER: &'static ::core::ffi::CStr = $id;
+            type ExtensionSide = $crate::__private::clack_common::extensions::$side;
+
+            #[inline]
+            unsafe fn from_raw(
+                raw: $crate::__private::clack_common::extensions::RawExtension<Self::ExtensionSide>,
+            ) -> Self {
+                Self(raw.cast())
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_extension_default {
+    () => {
+        ()
+    };
+    ($default:expr) => {
+        $default
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_extension_trait {
+    (
+        mut $(#[$tmeta:meta])* $tvis:vis $trait:ident {
+            $( $(#[$fmeta:meta])* fn $fn:ident($($arg:ident: $argty:ty),*) $(-> $ret:ty)?; )*
+        }
+    ) => {
+        $(#[$tmeta])*
+        $tvis trait $trait {
+            $( $(#[$fmeta])* fn $fn(&mut self, $($arg: $argty),*) $(-> $ret)?; )*
+        }
+    };
+    (
+        ref $(#[$tmeta:meta])* $tvis:vis $trait:ident {
+            $( $(#[$fmeta:meta])* fn $fn:ident($($arg:ident: $argty:ty),*) $(-> $ret:ty)?; )*
+        }
+    ) => {
+        $(#[$tmeta])*
+        $tvis trait $trait {
+            $( $(#[$fmeta])* fn $fn(&self, $($arg: $argty),*) $(-> $ret)?; )*
+        }
+    };
+}
+
+#[cfg(feature = "clack-plugin")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_extension_plugin_side {
+    (
+        implement $name:ident, $raw:path;
+        $(
+            $(#[$tmeta:meta])*
+            $tvis:vis trait $trait:ident: $thread:ident {
+                $(
+                    $(#[$fmeta:meta])*
+                    fn $fn:ident($($arg:ident: $argty:ty),* $(,)?) $(-> $ret:ty = $default:expr)?;
+                )*
+            }
+        )+
+    ) => {
+        $(
+            $crate::__declare_extension_plugin_side!(
+                @trait $thread $(#[$tmeta])* $tvis $trait {
+                    $( $(#[$fmeta])* fn $fn($($arg: $argty),*) $(-> $ret)?; )*
+                }
+            );
+        )+
+
+        // SAFETY: The given struct is the CLAP extension struct for the matching side of this
+        // extension.
+        unsafe impl<P: $crate::__private::clack_plugin::plugin::Plugin>
+            $crate::__private::clack_plugin::extensions::ExtensionImplementation<P> for $name
+        where
+            $(for<'a> $crate::__declare_extension_plugin_side!(@target $thread P 'a): $trait,)+
+        {
+            const IMPLEMENTATION: $crate::__private::clack_plugin::extensions::RawExtensionImplementation = {
+                $($(
+                    #[allow(clippy::missing_safety_doc)]
+                    unsafe extern "C" fn $fn<P: $crate::__private::clack_plugin::plugin::Plugin>(
+                        plugin: *const $crate::__private::clap_sys::plugin::clap_plugin,
+                        $($arg: $argty),*
+                    ) $(-> $ret)?
+                    where
+                        for<'a> $crate::__declare_extension_plugin_side!(@target $thread P 'a): $trait,
+                    {
+                        let result = $crate::__declare_extension_plugin_side!(
+                            @handle $thread P, plugin,
+                            ::core::concat!(::core::stringify!($raw), ".", ::core::stringify!($fn)),
+                            |target| target.$fn($($arg),*)
+                        );
+
+                        result.unwrap_or($crate::__declare_extension_default!($($default)?))
+                    }
+                )*)+
+
+                type Raw = $raw;
+
+                $crate::__private::clack_plugin::extensions::RawExtensionImplementation::new(&Raw {
+                    $($($fn: Some($fn::<P>),)*)+
+                })
+            };
+        }
+    };
+    (
+        call $name:ident;
+        $(
+            $(#[$tmeta:meta])*
+            $tvis:vis trait $trait:ident: $thread:ident {
+                $(
+                    $(#[$fmeta:meta])*
+                    fn $fn:ident($($arg:ident: $argty:ty),* $(,)?) $(-> $ret:ty = $default:expr)?;
+                )*
+            }
+        )+
+    ) => {
+        impl $name {
+            $($(
+                $(#[$fmeta])*
+                #[inline]
+                pub fn $fn(
+                    &self,
+                    host: $crate::__declare_extension_plugin_side!(@handle_type $thread),
+                    $($arg: $argty),*
+                ) $(-> $ret)? {
+                    match host.use_extension(&self.0).$fn {
+                        // SAFETY: This type ensures the function pointer is valid.
+                        Some(function) => unsafe { function(host.as_raw(), $($arg),*) },
+                        None => $crate::__declare_extension_default!($($default)?),
+                    }
+                }
+            )*)+
+        }
+    };
+    (@trait main_thread $(#[$tmeta:meta])* $tvis:vis $trait:ident $fns:tt) => {
+        $crate::__declare_extension_trait!(mut $(#[$tmeta])* $tvis $trait $fns);
+    };
+    (@trait audio_thread $(#[$tmeta:meta])* $tvis:vis $trait:ident $fns:tt) => {
+        $crate::__declare_extension_trait!(mut $(#[$tmeta])* $tvis $trait $fns);
+    };
+    (@trait any_thread $(#[$tmeta:meta])* $tvis:vis $trait:ident $fns:tt) => {
+        $crate::__declare_extension_trait!(ref $(#[$tmeta])* $tvis $trait $fns);
+    };
+    (@target main_thread $P:ident $lt:lifetime) => {
+        <$P as $crate::__private::clack_plugin::plugin::Plugin>::MainThread<$lt>
+    };
+    (@target audio_thread $P:ident $lt:lifetime) => {
+        <$P as $crate::__private::clack_plugin::plugin::Plugin>::AudioProcessor<$lt>
+    };
+    (@target any_thread $P:ident $lt:lifetime) => {
+        <$P as $crate::__private::clack_plugin::plugin::Plugin>::Shared<$lt>
+    };
+    (@handle main_thread $P:ident, $plugin:ident, $callback:expr, |$target:ident| $call:expr) => {
+        $crate::__private::clack_plugin::extensions::wrapper::PluginWrapper::<$P>::handle_main_thread(
+            $plugin,
+            $callback,
+            |$target, _, _| Ok($call),
+        )
+    };
+    (@handle audio_thread $P:ident, $plugin:ident, $callback:expr, |$target:ident| $call:expr) => {
+        $crate::__private::clack_plugin::extensions::wrapper::PluginWrapper::<$P>::handle_audio_processor(
+            $plugin,
+            $callback,
+            |$target, _, _| Ok($call),
+        )
+    };
+    (@handle any_thread $P:ident, $plugin:ident, $callback:expr, |$target:ident| $call:expr) => {
+        $crate::__private::clack_plugin::extensions::wrapper::PluginWrapper::<$P>::handle_shared(
+            $plugin,
+            $callback,
+            |$target, _| Ok($call),
+        )
+    };
+    (@handle_type main_thread) => {
+        &mut $crate::__private::clack_plugin::host::HostMainThreadHandle<'_>
+    };
+    (@handle_type audio_thread) => {
+        &mut $crate::__private::clack_plugin::host::HostAudioProcessorHandle<'_>
+    };
+    (@handle_type any_thread) => {
+        &$crate::__private::clack_plugin::host::HostSharedHandle<'_>
+    };
+}
+
+#[cfg(not(feature = "clack-plugin"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_extension_plugin_side {
+    ($($tokens:tt)*) => {};
+}
+
+#[cfg(feature = "clack-host")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_extension_host_side {
+    (
+        implement $name:ident, $raw:path;
+        $(
+            $(#[$tmeta:meta])*
+            $tvis:vis trait $trait:ident: $thread:ident {
+                $(
+                    $(#[$fmeta:meta])*
+                    fn $fn:ident($($arg:ident: $argty:ty),* $(,)?) $(-> $ret:ty = $default:expr)?;
+                )*
+            }
+        )+
+    ) => {
+        $(
+            $crate::__declare_extension_host_side!(
+                @trait $thread $(#[$tmeta])* $tvis $trait {
+                    $( $(#[$fmeta])* fn $fn($($arg: $argty),*) $(-> $ret)?; )*
+                }
+            );
+        )+
+
+        // SAFETY: The given struct is the CLAP extension struct for the matching side of this
+        // extension.
+        unsafe impl<H: $crate::__private::clack_host::host::HostHandlers>
+            $crate::__private::clack_host::extensions::ExtensionImplementation<H> for $name
+        where
+            $(for<'a> $crate::__declare_extension_host_side!(@target $thread H 'a): $trait,)+
+        {
+            const IMPLEMENTATION: $crate::__private::clack_host::extensions::RawExtensionImplementation = {
+                $($(
+                    #[allow(clippy::missing_safety_doc)]
+                    unsafe extern "C" fn $fn<H: $crate::__private::clack_host::host::HostHandlers>(
+                        host: *const $crate::__private::clap_sys::host::clap_host,
+                        $($arg: $argty),*
+                    ) $(-> $ret)?
+                    where
+                        for<'a> $crate::__declare_extension_host_side!(@target $thread H 'a): $trait,
+                    {
+                        let result = $crate::__declare_extension_host_side!(@handle $thread H)(
+                            host,
+                            ::core::concat!(::core::stringify!($raw), ".", ::core::stringify!($fn)),
+                            |wrapper| {
+                                let target = $crate::__declare_extension_host_side!(@access $thread wrapper);
+                                Ok(target.$fn($($arg),*))
+                            },
+                        );
+
+                        result.unwrap_or($crate::__declare_extension_default!($($default)?))
+                    }
+                )*)+
+
+                type Raw = $raw;
+
+                $crate::__private::clack_host::extensions::RawExtensionImplementation::new(&Raw {
+                    $($($fn: Some($fn::<H>),)*)+
+                })
+            };
+        }
+    };
+    (
+        call $name:ident;
+        $(
+            $(#[$tmeta:meta])*
+            $tvis:vis trait $trait:ident: $thread:ident {
+                $(
+                    $(#[$fmeta:meta])*
+                    fn $fn:ident($($arg:ident: $argty:ty),* $(,)?) $(-> $ret:ty = $default:expr)?;
+                )*
+            }
+        )+
+    ) => {
+        impl $name {
+            $($(
+                $(#[$fmeta])*
+                #[inline]
+                pub fn $fn(
+                    &self,
+                    plugin: $crate::__declare_extension_host_side!(@handle_type $thread),
+                    $($arg: $argty),*
+                ) $(-> $ret)? {
+                    match plugin.use_extension(&self.0).$fn {
+                        // SAFETY: This type ensures the function pointer is valid.
+                        Some(function) => unsafe { function(plugin.as_raw(), $($arg),*) },
+                        None => $crate::__declare_extension_default!($($default)?),
+                    }
+                }
+            )*)+
+        }
+    };
+    (@trait main_thread $(#[$tmeta:meta])* $tvis:vis $trait:ident $fns:tt) => {
+        $crate::__declare_extension_trait!(mut $(#[$tmeta])* $tvis $trait $fns);
+    };
+    (@trait audio_thread $(#[$tmeta:meta])* $tvis:vis $trait:ident $fns:tt) => {
+        $crate::__declare_extension_trait!(mut $(#[$tmeta])* $tvis $trait $fns);
+    };
+    (@trait any_thread $(#[$tmeta:meta])* $tvis:vis $trait:ident $fns:tt) => {
+        $crate::__declare_extension_trait!(ref $(#[$tmeta])* $tvis $trait $fns);
+    };
+    (@target main_thread $H:ident $lt:lifetime) => {
+        <$H as $crate::__private::clack_host::host::HostHandlers>::MainThread<$lt>
+    };
+    (@target audio_thread $H:ident $lt:lifetime) => {
+        <$H as $crate::__private::clack_host::host::HostHandlers>::AudioProcessor<$lt>
+    };
+    (@target any_thread $H:ident $lt:lifetime) => {
+        <$H as $crate::__private::clack_host::host::HostHandlers>::Shared<$lt>
+    };
+    (@handle main_thread $H:ident) => {
+        $crate::__private::clack_host::extensions::wrapper::HostWrapper::<$H>::handle_main_thread
+    };
+    (@handle $thread:ident $H:ident) => {
+        $crate::__private::clack_host::extensions::wrapper::HostWrapper::<$H>::handle
+    };
+    (@access main_thread $wrapper:ident) => {
+        $wrapper.main_thread().as_mut()
+    };
+    (@access audio_thread $wrapper:ident) => {
+        $wrapper.audio_processor()?.as_mut()
+    };
+    (@access any_thread $wrapper:ident) => {
+        $wrapper.shared()
+    };
+    (@handle_type main_thread) => {
+        &mut $crate::__private::clack_host::plugin::PluginMainThreadHandle<'_>
+    };
+    (@handle_type audio_thread) => {
+        &mut $crate::__private::clack_host::plugin::PluginAudioProcessorHandle<'_>
+    };
+    (@handle_type any_thread) => {
+        &$crate::__private::clack_host::plugin::PluginSharedHandle<'_>
+    };
+}
+
+#[cfg(not(feature = "clack-host"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_extension_host_side {
+    ($($tokens:tt)*) => {};
+}
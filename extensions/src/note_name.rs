@@ -4,6 +4,7 @@
 
 use clack_common::events::Match;
 use clack_common::extensions::{Extension, HostExtensionSide, PluginExtensionSide, RawExtension};
+use clack_common::utils::{KeyName, NoteNaming};
 use clap_sys::ext::note_name::*;
 use clap_sys::string_sizes::CLAP_NAME_SIZE;
 use std::ffi::CStr;
@@ -81,6 +82,17 @@ impl<'a> NoteName<'a> {
             key: self.key.to_raw(),
         }
     }
+
+    /// Returns the standard name of the key this note name applies to, in the given naming
+    /// convention.
+    ///
+    /// This can be used by hosts to display a fallback name alongside (or instead of) the
+    /// plugin-provided one. This returns `None` if this note name applies to every key, or if its
+    /// key is out of range.
+    #[inline]
+    pub fn standard_key_name(&self, naming: NoteNaming) -> Option<KeyName> {
+        naming.name(self.key.into_specific()?)
+    }
 }
 
 #[cfg(feature = "clack-host")]
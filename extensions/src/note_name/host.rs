@@ -82,7 +82,7 @@ unsafe extern "C" fn changed<H: HostHandlers>(host: *const clap_host)
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostNoteNameImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_note_name.changed", |host| {
         host.main_thread().as_mut().changed();
 
         Ok(())
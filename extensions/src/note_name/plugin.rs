@@ -34,7 +34,10 @@ unsafe extern "C" fn count<P: Plugin>(plugin: *const clap_plugin) -> u32
 where
     for<'a> P::MainThread<'a>: PluginNoteNameImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| Ok(p.main_thread().as_mut().count() as u32)).unwrap_or(0)
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_note_name.count", |p| {
+        Ok(p.main_thread().as_mut().count() as u32)
+    })
+    .unwrap_or(0)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -46,7 +49,7 @@ unsafe extern "C" fn get<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginNoteNameImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_note_name.get", |p| {
         if config.is_null() {
             return Err(PluginWrapperError::NulPtr("clap_note_name output"));
         };
@@ -3,6 +3,7 @@ use clack_common::extensions::{Extension, HostExtensionSide, PluginExtensionSide
 use clack_common::utils::ClapId;
 use clap_sys::ext::note_ports::*;
 use std::ffi::CStr;
+use std::marker::PhantomData;
 
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
@@ -24,6 +25,8 @@ bitflags! {
 bitflags! {
     #[repr(C)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct NoteDialects: u32 {
         const CLAP = CLAP_NOTE_DIALECT_CLAP;
         const MIDI = CLAP_NOTE_DIALECT_MIDI;
@@ -41,6 +44,7 @@ impl NoteDialects {
 
 #[repr(u32)]
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NoteDialect {
     Clap = CLAP_NOTE_DIALECT_CLAP,
     Midi = CLAP_NOTE_DIALECT_MIDI,
@@ -107,6 +111,157 @@ impl<'a> NotePortInfo<'a> {
     }
 }
 
+impl std::fmt::Debug for NotePortInfo<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotePortInfo")
+            .field("id", &self.id)
+            .field("name", &String::from_utf8_lossy(self.name))
+            .field("supported_dialects", &self.supported_dialects)
+            .field("preferred_dialect", &self.preferred_dialect)
+            .finish()
+    }
+}
+
+/// A fixed set of input and output note ports, that can be declared as a constant.
+///
+/// This is used with [`StaticNotePorts`] to implement the `note-ports` extension for plugins
+/// that have a static port layout, instead of implementing `PluginNotePortsImpl` by hand.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::ClapId;
+/// use clack_extensions::note_ports::*;
+///
+/// const MAIN_PORT: NotePortInfo<'static> = NotePortInfo {
+///     id: ClapId::new(0),
+///     name: b"main",
+///     supported_dialects: NoteDialects::CLAP,
+///     preferred_dialect: Some(NoteDialect::Clap),
+/// };
+///
+/// const NOTE_INPUT: NotePortLayout = NotePortLayout::new(&[MAIN_PORT], &[]);
+///
+/// assert_eq!(NOTE_INPUT.count(true), 1);
+/// assert_eq!(NOTE_INPUT.count(false), 0);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct NotePortLayout {
+    inputs: &'static [NotePortInfo<'static>],
+    outputs: &'static [NotePortInfo<'static>],
+}
+
+impl NotePortLayout {
+    /// Creates a new layout from the given input and output ports.
+    ///
+    /// # Panics
+    ///
+    /// This panics if any port name contains a NUL byte or is too long, if a port's preferred
+    /// dialect isn't part of its supported dialects, or if two ports of the same direction share
+    /// the same ID. When used to declare a constant, this results in a compile-time error instead.
+    pub const fn new(
+        inputs: &'static [NotePortInfo<'static>],
+        outputs: &'static [NotePortInfo<'static>],
+    ) -> Self {
+        validate_ports(inputs);
+        validate_ports(outputs);
+
+        Self { inputs, outputs }
+    }
+
+    /// Returns the ports of this layout, for the given direction.
+    #[inline]
+    pub const fn ports(&self, is_input: bool) -> &'static [NotePortInfo<'static>] {
+        if is_input {
+            self.inputs
+        } else {
+            self.outputs
+        }
+    }
+
+    /// Returns the number of ports of this layout, for the given direction.
+    #[inline]
+    pub const fn count(&self, is_input: bool) -> u32 {
+        self.ports(is_input).len() as u32
+    }
+
+    /// Returns the port at the given index, for the given direction.
+    #[inline]
+    pub const fn get(&self, index: u32, is_input: bool) -> Option<&'static NotePortInfo<'static>> {
+        let ports = self.ports(is_input);
+
+        if (index as usize) < ports.len() {
+            Some(&ports[index as usize])
+        } else {
+            None
+        }
+    }
+}
+
+const fn validate_ports(ports: &[NotePortInfo]) {
+    let mut i = 0;
+    while i < ports.len() {
+        crate::utils::validate_name(ports[i].name);
+
+        if let Some(preferred) = ports[i].preferred_dialect {
+            assert!(
+                ports[i].supported_dialects.bits() & preferred as u32 != 0,
+                "Preferred note dialect must be supported"
+            );
+        }
+
+        let mut j = i + 1;
+        while j < ports.len() {
+            assert!(
+                ports[i].id.get() != ports[j].id.get(),
+                "Note port IDs must be unique"
+            );
+            j += 1;
+        }
+
+        i += 1;
+    }
+}
+
+/// Provides a constant [`NotePortLayout`], to be used with [`StaticNotePorts`].
+///
+/// This is typically implemented on the plugin type itself.
+pub trait NotePortLayoutProvider: 'static {
+    /// The plugin's note port layout.
+    const NOTE_PORTS: NotePortLayout;
+}
+
+/// An implementation of the plugin side of the `note-ports` extension, that always exposes the
+/// constant layout declared by `L`.
+///
+/// This can be registered in place of [`PluginNotePorts`] in `Plugin::declare_extensions`,
+/// without having to implement `PluginNotePortsImpl`.
+pub struct StaticNotePorts<L>(PhantomData<fn() -> L>);
+
+impl<L> Clone for StaticNotePorts<L> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<L> Copy for StaticNotePorts<L> {}
+
+// SAFETY: This type carries no extension data. Its implementation is the same as PluginNotePorts'.
+unsafe impl<L: NotePortLayoutProvider> Extension for StaticNotePorts<L> {
+    const IDENTIFIER: &'static CStr = CLAP_EXT_NOTE_PORTS;
+    type ExtensionSide = PluginExtensionSide;
+
+    #[inline]
+    unsafe fn from_raw(_raw: RawExtension<Self::ExtensionSide>) -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub mod snapshot;
+
+#[cfg(feature = "clack-host")]
+pub mod dialect;
 #[cfg(feature = "clack-host")]
 mod host;
 #[cfg(feature = "clack-host")]
@@ -0,0 +1,559 @@
+//! A host-side helper to exchange note events with plugins that only understand MIDI.
+//!
+//! Hosts typically work with CLAP note events internally, but some plugins only support the MIDI
+//! dialect on their note ports. The [`DialectTranslator`] sits between the host's event queue and
+//! the plugin: it converts the CLAP note events sent to the plugin into MIDI messages, and the
+//! MIDI messages output by the plugin back into CLAP note events.
+//!
+//! See the [`DialectTranslator`] type for more information.
+
+use super::*;
+use clack_host::events::event_types::{MidiEvent, NoteOffEvent, NoteOnEvent};
+use clack_host::events::io::{InputEvents, OutputEvents, TryPushError};
+use clack_host::events::spaces::CoreEventSpace;
+use clack_host::events::{Event, Match, Pckn};
+
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+
+/// Translates note events between the CLAP and MIDI dialects, for a single note port.
+///
+/// The translation mode is picked from the plugin's note port information, using
+/// [`for_port`](Self::for_port): ports that prefer (or only support) MIDI get their note events
+/// translated, while ports that can handle CLAP note events directly get all events forwarded
+/// as-is.
+///
+/// Towards the plugin, [`translate_input`](Self::translate_input) turns CLAP Note On and Note Off
+/// events into MIDI Note On and Note Off messages. Velocities are scaled from the `0.0..=1.0`
+/// range to `0..=127` (`1..=127` for Note On, as a zero velocity would turn it into a Note Off)
+/// using [`Velocity`](clack_host::events::velocity::Velocity),
+/// and the MIDI channel is taken from the event's [`Pckn`]. MIDI has no concept of note IDs: the
+/// note IDs carried by the translated events are lost, which is reported in the returned
+/// [`DialectTranslationReport`].
+///
+/// From the plugin, [`translate_output`](Self::translate_output) turns MIDI Note On and Note Off
+/// messages back into CLAP events, with no note ID. MIDI running status is supported: messages
+/// starting with a data byte reuse the status byte of the last channel message the plugin sent.
+///
+/// All other events are forwarded untouched, except for the note events MIDI cannot represent,
+/// which are dropped and counted in the report. The translator also keeps running
+/// [`totals`](Self::totals) of all its reports, which hosts can poll to diagnose lost notes.
+///
+/// # Realtime Safety
+///
+/// This type never allocates, and both translation methods are realtime-safe, provided the given
+/// [`OutputEvents`] don't allocate either (e.g. an `EventBuffer` with enough capacity).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct DialectTranslator {
+    translates: bool,
+    running_status: Option<u8>,
+    totals: DialectTranslationReport,
+}
+
+impl DialectTranslator {
+    /// Creates a translator that forwards all events as-is, for ports that support CLAP events.
+    #[inline]
+    pub const fn passthrough() -> Self {
+        Self {
+            translates: false,
+            running_status: None,
+            totals: DialectTranslationReport::new(),
+        }
+    }
+
+    /// Creates a translator that translates note events to and from MIDI.
+    #[inline]
+    pub const fn midi() -> Self {
+        Self {
+            translates: true,
+            running_status: None,
+            totals: DialectTranslationReport::new(),
+        }
+    }
+
+    /// Creates a translator for a port with the given supported and preferred dialects.
+    ///
+    /// The port's preferred dialect is used if it is either CLAP or MIDI (including MPE).
+    /// Otherwise, CLAP is picked if supported, then MIDI.
+    ///
+    /// Returns `None` if the port supports neither CLAP nor MIDI (e.g. only MIDI 2.0).
+    pub fn new(supported: NoteDialects, preferred: Option<NoteDialect>) -> Option<Self> {
+        match preferred {
+            Some(NoteDialect::Clap) => return Some(Self::passthrough()),
+            Some(NoteDialect::Midi | NoteDialect::MidiMpe) => return Some(Self::midi()),
+            Some(NoteDialect::Midi2) | None => {}
+        }
+
+        if supported.contains(NoteDialects::CLAP) {
+            Some(Self::passthrough())
+        } else if supported.intersects(NoteDialects::MIDI | NoteDialects::MIDI_MPE) {
+            Some(Self::midi())
+        } else {
+            None
+        }
+    }
+
+    /// Creates a translator for the given note port.
+    ///
+    /// See [`new`](Self::new) for how the translation mode is picked.
+    #[inline]
+    pub fn for_port(port: &NotePortInfo) -> Option<Self> {
+        Self::new(port.supported_dialects, port.preferred_dialect)
+    }
+
+    /// Returns `true` if this translator converts note events to and from MIDI, or `false` if it
+    /// forwards them as-is.
+    #[inline]
+    pub const fn translates(&self) -> bool {
+        self.translates
+    }
+
+    /// Forgets the MIDI running status of the plugin's output.
+    ///
+    /// This should be called whenever the plugin's output stream is interrupted, e.g. when it is
+    /// deactivated.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.running_status = None;
+    }
+
+    /// Returns the sum of all the reports of this translator, since it was created or since
+    /// [`reset_totals`](Self::reset_totals) was last called.
+    ///
+    /// Events counted in a report are included here even if the translation call that produced
+    /// it returned an error.
+    #[inline]
+    pub const fn totals(&self) -> DialectTranslationReport {
+        self.totals
+    }
+
+    /// Resets the [`totals`](Self::totals) of this translator to zero.
+    #[inline]
+    pub fn reset_totals(&mut self) {
+        self.totals = DialectTranslationReport::new();
+    }
+
+    /// Translates the host's events, to be sent to the plugin.
+    ///
+    /// All events from `input` are pushed to `output`, with CLAP note events converted to MIDI if
+    /// needed.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if `output` refused one of the events. Events processed
+    /// before that point have already been pushed.
+    pub fn translate_input(
+        &mut self,
+        input: &InputEvents,
+        output: &mut OutputEvents,
+    ) -> Result<DialectTranslationReport, TryPushError> {
+        let mut report = DialectTranslationReport::new();
+        let result = self.translate_input_into(input, output, &mut report);
+        self.totals.merge(&report);
+
+        result.map(|()| report)
+    }
+
+    fn translate_input_into(
+        &mut self,
+        input: &InputEvents,
+        output: &mut OutputEvents,
+        report: &mut DialectTranslationReport,
+    ) -> Result<(), TryPushError> {
+        for event in input {
+            if !self.translates {
+                output.try_push(event)?;
+                continue;
+            }
+
+            match event.as_core_event() {
+                Some(CoreEventSpace::NoteOn(e)) => {
+                    push_midi_note(
+                        e.pckn(),
+                        e.header().time(),
+                        NOTE_ON,
+                        e.midi_velocity(),
+                        output,
+                        report,
+                    )?;
+                }
+                Some(CoreEventSpace::NoteOff(e)) => {
+                    push_midi_note(
+                        e.pckn(),
+                        e.header().time(),
+                        NOTE_OFF,
+                        e.midi_velocity(),
+                        output,
+                        report,
+                    )?;
+                }
+                Some(
+                    CoreEventSpace::NoteChoke(_)
+                    | CoreEventSpace::NoteEnd(_)
+                    | CoreEventSpace::NoteExpression(_),
+                ) => report.dropped += 1,
+                _ => output.try_push(event)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Translates the plugin's output events, to be handled by the host.
+    ///
+    /// All events from `input` are pushed to `output`, with MIDI Note On and Note Off messages
+    /// converted to CLAP note events if needed. Other MIDI messages are forwarded with their
+    /// running status resolved, so that they always start with a status byte.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if `output` refused one of the events. Events processed
+    /// before that point have already been pushed.
+    pub fn translate_output(
+        &mut self,
+        input: &InputEvents,
+        output: &mut OutputEvents,
+    ) -> Result<DialectTranslationReport, TryPushError> {
+        let mut report = DialectTranslationReport::new();
+        let result = self.translate_output_into(input, output, &mut report);
+        self.totals.merge(&report);
+
+        result.map(|()| report)
+    }
+
+    fn translate_output_into(
+        &mut self,
+        input: &InputEvents,
+        output: &mut OutputEvents,
+        report: &mut DialectTranslationReport,
+    ) -> Result<(), TryPushError> {
+        for event in input {
+            match event.as_event::<MidiEvent>() {
+                Some(midi) if self.translates => self.translate_midi(midi, output, report)?,
+                _ => output.try_push(event)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn translate_midi(
+        &mut self,
+        event: &MidiEvent,
+        output: &mut OutputEvents,
+        report: &mut DialectTranslationReport,
+    ) -> Result<(), TryPushError> {
+        let [first, second, third] = event.data();
+
+        let (status, data1, data2) = if first & 0x80 != 0 {
+            match first {
+                // Channel messages set the running status, System Common messages clear it, and
+                // System Real-Time messages leave it untouched.
+                0x80..=0xEF => self.running_status = Some(first),
+                0xF0..=0xF7 => self.running_status = None,
+                _ => {}
+            }
+
+            (first, second, third)
+        } else if let Some(status) = self.running_status {
+            (status, first, second)
+        } else {
+            // A data byte without any status byte to attach it to.
+            report.dropped += 1;
+            return Ok(());
+        };
+
+        let time = event.header().time();
+        let pckn = Pckn::new(event.port_index(), status & 0x0F, data1 & 0x7F, Match::All);
+
+        match status & 0xF0 {
+            NOTE_ON if data2 != 0 => output.try_push(NoteOnEvent::from_midi(time, pckn, data2))?,
+            NOTE_ON | NOTE_OFF => output.try_push(NoteOffEvent::from_midi(time, pckn, data2))?,
+            // Other messages are forwarded as MIDI.
+            _ if first & 0x80 != 0 => return output.try_push(event),
+            _ => {
+                let data = [status, data1, data2];
+                return output.try_push(MidiEvent::new(time, event.port_index(), data));
+            }
+        }
+
+        report.translated += 1;
+        Ok(())
+    }
+}
+
+fn push_midi_note(
+    pckn: Pckn,
+    time: u32,
+    status: u8,
+    velocity: u8,
+    output: &mut OutputEvents,
+    report: &mut DialectTranslationReport,
+) -> Result<(), TryPushError> {
+    let (Some(channel @ 0..=15), Some(key @ 0..=127)) =
+        (pckn.channel.into_specific(), pckn.key.into_specific())
+    else {
+        // MIDI can't target multiple keys or channels at once, nor go beyond 16 channels.
+        report.dropped += 1;
+        return Ok(());
+    };
+
+    if pckn.note_id.as_specific().is_some() {
+        report.note_ids_lost += 1;
+    }
+
+    let port_index = pckn.port_index.into_specific().unwrap_or(0);
+    let data = [status | channel as u8, key as u8, velocity];
+
+    output.try_push(MidiEvent::new(time, port_index, data))?;
+    report.translated += 1;
+
+    Ok(())
+}
+
+/// A report of the conversions a [`DialectTranslator`] performed on an event list.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DialectTranslationReport {
+    /// The number of note events that were converted from one dialect to the other.
+    pub translated: u32,
+    /// The number of translated note events that targeted a specific note ID, which MIDI cannot
+    /// carry.
+    ///
+    /// Plugins receiving these events cannot report which note ID a voice belongs to, e.g. in
+    /// their Note End events.
+    pub note_ids_lost: u32,
+    /// The number of events that could not be represented in the target dialect, and were
+    /// dropped.
+    pub dropped: u32,
+}
+
+impl DialectTranslationReport {
+    /// Returns an empty report.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            translated: 0,
+            note_ids_lost: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Adds the counts of another report to this one.
+    #[inline]
+    pub fn merge(&mut self, other: &DialectTranslationReport) {
+        self.translated = self.translated.saturating_add(other.translated);
+        self.note_ids_lost = self.note_ids_lost.saturating_add(other.note_ids_lost);
+        self.dropped = self.dropped.saturating_add(other.dropped);
+    }
+
+    /// Returns `true` if all events were translated without losing any information.
+    #[inline]
+    pub fn is_lossless(&self) -> bool {
+        self.note_ids_lost == 0 && self.dropped == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_host::events::io::EventBuffer;
+
+    fn note_on(
+        time: u32,
+        channel: u16,
+        key: u16,
+        note_id: Match<u32>,
+        velocity: f64,
+    ) -> NoteOnEvent {
+        NoteOnEvent::new(time, Pckn::new(0u16, channel, key, note_id), velocity)
+    }
+
+    fn translate_input(
+        translator: &mut DialectTranslator,
+        events: &EventBuffer,
+    ) -> (EventBuffer, DialectTranslationReport) {
+        let mut output = EventBuffer::with_capacity(16);
+        let report = translator
+            .translate_input(&events.as_input(), &mut output.as_output())
+            .unwrap();
+        (output, report)
+    }
+
+    fn translate_output(
+        translator: &mut DialectTranslator,
+        events: &EventBuffer,
+    ) -> (EventBuffer, DialectTranslationReport) {
+        let mut output = EventBuffer::with_capacity(16);
+        let report = translator
+            .translate_output(&events.as_input(), &mut output.as_output())
+            .unwrap();
+        (output, report)
+    }
+
+    fn midi_data(events: &EventBuffer) -> Vec<[u8; 3]> {
+        events
+            .iter()
+            .map(|e| e.as_event::<MidiEvent>().unwrap().data())
+            .collect()
+    }
+
+    #[test]
+    fn mode_is_picked_from_port_dialects() {
+        assert!(!DialectTranslator::new(
+            NoteDialects::CLAP | NoteDialects::MIDI,
+            Some(NoteDialect::Clap)
+        )
+        .unwrap()
+        .translates());
+        assert!(DialectTranslator::new(
+            NoteDialects::CLAP | NoteDialects::MIDI,
+            Some(NoteDialect::Midi)
+        )
+        .unwrap()
+        .translates());
+        assert!(!DialectTranslator::new(
+            NoteDialects::CLAP | NoteDialects::MIDI2,
+            Some(NoteDialect::Midi2)
+        )
+        .unwrap()
+        .translates());
+        assert!(DialectTranslator::new(NoteDialects::MIDI_MPE, None)
+            .unwrap()
+            .translates());
+        assert!(DialectTranslator::new(NoteDialects::MIDI2, None).is_none());
+    }
+
+    #[test]
+    fn clap_notes_round_trip_through_midi() {
+        let mut translator = DialectTranslator::midi();
+
+        let mut events = EventBuffer::new();
+        events.push(&note_on(3, 2, 60, Match::All, 1.0));
+        events.push(&NoteOffEvent::new(
+            10,
+            Pckn::new(0u16, 2u16, 60u16, Match::All),
+            0.5,
+        ));
+
+        let (midi, report) = translate_input(&mut translator, &events);
+        assert_eq!(midi_data(&midi), [[0x92, 60, 127], [0x82, 60, 64]]);
+        assert_eq!(midi.get(1).unwrap().header().time(), 10);
+        assert_eq!(report.translated, 2);
+        assert!(report.is_lossless());
+
+        let (clap, report) = translate_output(&mut translator, &midi);
+        assert_eq!(report.translated, 2);
+
+        let on = clap.get(0).unwrap().as_event::<NoteOnEvent>().unwrap();
+        assert_eq!(on.header().time(), 3);
+        assert_eq!(on.pckn(), Pckn::new(0u16, 2u16, 60u16, Match::All));
+        assert_eq!(on.velocity(), 1.0);
+
+        let off = clap.get(1).unwrap().as_event::<NoteOffEvent>().unwrap();
+        assert_eq!(off.header().time(), 10);
+        assert_eq!(off.velocity(), 64.0 / 127.0);
+
+        assert_eq!(translator.totals().translated, 4);
+        translator.reset_totals();
+        assert_eq!(translator.totals(), DialectTranslationReport::new());
+    }
+
+    #[test]
+    fn plugin_midi_round_trips_through_clap() {
+        let mut translator = DialectTranslator::midi();
+
+        let mut events = EventBuffer::new();
+        events.push(&MidiEvent::new(0, 0, [0x91, 64, 100]));
+        // Note On with a zero velocity is a Note Off.
+        events.push(&MidiEvent::new(5, 0, [0x91, 64, 0]));
+        // Control changes are forwarded untouched.
+        events.push(&MidiEvent::new(6, 0, [0xB1, 7, 90]));
+
+        let (clap, report) = translate_output(&mut translator, &events);
+        assert_eq!(report.translated, 2);
+        assert!(clap.get(0).unwrap().as_event::<NoteOnEvent>().is_some());
+        assert!(clap.get(1).unwrap().as_event::<NoteOffEvent>().is_some());
+        assert_eq!(
+            clap.get(2).unwrap().as_event::<MidiEvent>().unwrap().data(),
+            [0xB1, 7, 90]
+        );
+
+        let (midi, report) = translate_input(&mut translator, &clap);
+        assert_eq!(report.translated, 2);
+        assert_eq!(
+            midi_data(&midi),
+            [[0x91, 64, 100], [0x81, 64, 0], [0xB1, 7, 90]]
+        );
+    }
+
+    #[test]
+    fn running_status_is_resolved() {
+        let mut translator = DialectTranslator::midi();
+
+        let mut events = EventBuffer::new();
+        // A data byte with no prior status byte can't be interpreted.
+        events.push(&MidiEvent::new(0, 0, [60, 100, 0]));
+        events.push(&MidiEvent::new(0, 0, [0x90, 60, 100]));
+        events.push(&MidiEvent::new(1, 0, [62, 90, 0]));
+        events.push(&MidiEvent::new(2, 0, [60, 0, 0]));
+        // Real-Time messages don't cancel running status.
+        events.push(&MidiEvent::new(3, 0, [0xF8, 0, 0]));
+        events.push(&MidiEvent::new(4, 0, [62, 0, 0]));
+
+        let (clap, report) = translate_output(&mut translator, &events);
+        assert_eq!(report.dropped, 1);
+        assert_eq!(clap.len(), 5);
+
+        let second = clap.get(1).unwrap().as_event::<NoteOnEvent>().unwrap();
+        assert_eq!(second.key(), Match::Specific(62));
+        assert_eq!(second.velocity(), 90.0 / 127.0);
+        assert!(clap.get(2).unwrap().as_event::<NoteOffEvent>().is_some());
+        assert!(clap.get(4).unwrap().as_event::<NoteOffEvent>().is_some());
+
+        // Running status persists across blocks, until reset.
+        let mut next_block = EventBuffer::new();
+        next_block.push(&MidiEvent::new(0, 0, [64, 80, 0]));
+        assert_eq!(translate_output(&mut translator, &next_block).0.len(), 1);
+
+        translator.reset();
+        assert_eq!(translate_output(&mut translator, &next_block).1.dropped, 1);
+    }
+
+    #[test]
+    fn lost_note_ids_and_unrepresentable_events_are_reported() {
+        let mut translator = DialectTranslator::midi();
+
+        let mut events = EventBuffer::new();
+        events.push(&note_on(0, 0, 60, Match::Specific(42), 0.0));
+        events.push(&note_on(0, 16, 60, Match::All, 0.5));
+        events.push(&NoteOffEvent::new(
+            0,
+            Pckn::new(0u16, 0u16, Match::All, Match::All),
+            0.0,
+        ));
+
+        let (midi, report) = translate_input(&mut translator, &events);
+        // A zero velocity Note On is still sent as a Note On.
+        assert_eq!(midi_data(&midi), [[0x90, 60, 1]]);
+        assert_eq!(report.note_ids_lost, 1);
+        assert_eq!(report.dropped, 2);
+        assert!(!report.is_lossless());
+    }
+
+    #[test]
+    fn passthrough_forwards_everything() {
+        let mut translator = DialectTranslator::passthrough();
+
+        let mut events = EventBuffer::new();
+        events.push(&note_on(0, 0, 60, Match::Specific(42), 1.0));
+        events.push(&MidiEvent::new(0, 0, [62, 90, 0]));
+
+        let (forwarded, report) = translate_input(&mut translator, &events);
+        assert_eq!(forwarded.len(), 2);
+        assert_eq!(report, DialectTranslationReport::default());
+
+        let (forwarded, report) = translate_output(&mut translator, &events);
+        assert_eq!(forwarded.len(), 2);
+        assert_eq!(report, DialectTranslationReport::default());
+    }
+}
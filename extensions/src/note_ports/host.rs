@@ -63,7 +63,7 @@ where
     for<'h> <H as HostHandlers>::MainThread<'h>: HostNotePortsImpl,
 {
     const IMPLEMENTATION: RawExtensionImplementation =
-        RawExtensionImplementation::new(&&clap_host_note_ports {
+        RawExtensionImplementation::new(&clap_host_note_ports {
             supported_dialects: Some(supported_dialects::<H>),
             rescan: Some(rescan::<H>),
         });
@@ -74,7 +74,7 @@ unsafe extern "C" fn supported_dialects<H: HostHandlers>(host: *const clap_host)
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostNotePortsImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_note_ports.supported_dialects", |host| {
         Ok(host.main_thread().as_ref().supported_dialects().bits())
     })
     .unwrap_or(0)
@@ -85,7 +85,7 @@ unsafe extern "C" fn rescan<H: HostHandlers>(host: *const clap_host, flag: u32)
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostNotePortsImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_note_ports.rescan", |host| {
         host.main_thread()
             .as_mut()
             .rescan(NotePortRescanFlags::from_bits_truncate(flag));
@@ -69,8 +69,10 @@ unsafe extern "C" fn count<P: Plugin>(plugin: *const clap_plugin, is_input: bool
 where
     for<'a> P::MainThread<'a>: PluginNotePortsImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| Ok(p.main_thread().as_mut().count(is_input)))
-        .unwrap_or(0)
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_note_ports.count", |p| {
+        Ok(p.main_thread().as_mut().count(is_input))
+    })
+    .unwrap_or(0)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -83,7 +85,7 @@ unsafe extern "C" fn get<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginNotePortsImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_note_ports.get", |p| {
         if info.is_null() {
             return Err(PluginWrapperError::NulPtr("clap_note_port_info"));
         };
@@ -95,6 +97,50 @@ where
     .unwrap_or(false)
 }
 
+// SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+unsafe impl<P: Plugin, L: NotePortLayoutProvider> ExtensionImplementation<P>
+    for StaticNotePorts<L>
+{
+    const IMPLEMENTATION: RawExtensionImplementation =
+        RawExtensionImplementation::new(&clap_plugin_note_ports {
+            count: Some(static_count::<P, L>),
+            get: Some(static_get::<P, L>),
+        });
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn static_count<P: Plugin, L: NotePortLayoutProvider>(
+    plugin: *const clap_plugin,
+    is_input: bool,
+) -> u32 {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_note_ports.count", |_| {
+        Ok(L::NOTE_PORTS.count(is_input))
+    })
+    .unwrap_or(0)
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn static_get<P: Plugin, L: NotePortLayoutProvider>(
+    plugin: *const clap_plugin,
+    index: u32,
+    is_input: bool,
+    info: *mut clap_note_port_info,
+) -> bool {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_note_ports.get", |_| {
+        if info.is_null() {
+            return Err(PluginWrapperError::NulPtr("clap_note_port_info"));
+        };
+
+        let Some(port) = L::NOTE_PORTS.get(index, is_input) else {
+            return Ok(false);
+        };
+
+        NotePortInfoWriter::from_raw(info).set(port);
+        Ok(true)
+    })
+    .unwrap_or(false)
+}
+
 impl HostNotePorts {
     #[inline]
     pub fn supported_dialects(&self, host: &HostMainThreadHandle) -> NoteDialects {
@@ -115,3 +161,23 @@ impl HostNotePorts {
         }
     }
 }
+
+impl DynInterface for dyn PluginNotePortsImpl {
+    type Target<'a> = dyn PluginNotePortsImpl + 'a;
+}
+
+/// Forwards to the [`PluginNotePortsImpl`] implementation provided by the dynamic plugin, if any.
+impl PluginNotePortsImpl for DynPluginMainThread<'_> {
+    #[inline]
+    fn count(&mut self, is_input: bool) -> u32 {
+        self.interface::<dyn PluginNotePortsImpl>()
+            .map_or(0, |ports| ports.count(is_input))
+    }
+
+    #[inline]
+    fn get(&mut self, index: u32, is_input: bool, writer: &mut NotePortInfoWriter) {
+        if let Some(ports) = self.interface::<dyn PluginNotePortsImpl>() {
+            ports.get(index, is_input, writer)
+        }
+    }
+}
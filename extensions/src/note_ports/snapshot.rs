@@ -0,0 +1,304 @@
+//! Owned snapshots of a plugin's note port layout, to compare them across rescans.
+//!
+//! See the [`NotePortLayoutSnapshot`] type for more information.
+
+use super::*;
+use crate::utils::diff_by_id;
+
+/// The information of a single note port in a [`NotePortLayoutSnapshot`].
+///
+/// Unlike [`NotePortInfo`], this owns all of its data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotePortEntry {
+    /// The port's ID.
+    pub id: ClapId,
+    /// The port's name.
+    pub name: String,
+    /// The note dialects the port supports.
+    pub supported_dialects: NoteDialects,
+    /// The note dialect the port prefers, if any.
+    pub preferred_dialect: Option<NoteDialect>,
+}
+
+impl NotePortEntry {
+    /// Copies the given port information.
+    pub fn from_info(info: &NotePortInfo) -> Self {
+        Self {
+            id: info.id,
+            name: String::from_utf8_lossy(info.name).into_owned(),
+            supported_dialects: info.supported_dialects,
+            preferred_dialect: info.preferred_dialect,
+        }
+    }
+}
+
+/// A change in a plugin's note ports between two [`NotePortLayoutSnapshot`]s.
+///
+/// All variants carry whether the port is an input port, and the port's ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotePortChange {
+    /// A new port was added.
+    Added {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the new port.
+        id: ClapId,
+    },
+    /// A port was removed.
+    Removed {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the removed port.
+        id: ClapId,
+    },
+    /// A port's index changed.
+    Moved {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous index.
+        old: usize,
+        /// The new index.
+        new: usize,
+    },
+    /// A port's name changed.
+    Renamed {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous name.
+        old: String,
+        /// The new name.
+        new: String,
+    },
+    /// A port's supported or preferred note dialects changed.
+    DialectsChanged {
+        /// Whether this is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        id: ClapId,
+        /// The previous supported and preferred dialects.
+        old: (NoteDialects, Option<NoteDialect>),
+        /// The new supported and preferred dialects.
+        new: (NoteDialects, Option<NoteDialect>),
+    },
+}
+
+impl NotePortChange {
+    /// Returns whether the port this change is about is an input port.
+    pub fn is_input(&self) -> bool {
+        match self {
+            Self::Added { is_input, .. }
+            | Self::Removed { is_input, .. }
+            | Self::Moved { is_input, .. }
+            | Self::Renamed { is_input, .. }
+            | Self::DialectsChanged { is_input, .. } => *is_input,
+        }
+    }
+
+    /// Returns the ID of the port this change is about.
+    pub fn id(&self) -> ClapId {
+        match self {
+            Self::Added { id, .. }
+            | Self::Removed { id, .. }
+            | Self::Moved { id, .. }
+            | Self::Renamed { id, .. }
+            | Self::DialectsChanged { id, .. } => *id,
+        }
+    }
+
+    /// Returns the rescan flags a plugin must send to the host for this change to be allowed.
+    ///
+    /// Only renaming a port can be done with a [`NAMES`](NotePortRescanFlags::NAMES) rescan.
+    pub fn required_rescan(&self) -> NotePortRescanFlags {
+        match self {
+            Self::Renamed { .. } => NotePortRescanFlags::NAMES,
+            _ => NotePortRescanFlags::ALL,
+        }
+    }
+}
+
+/// An owned snapshot of a plugin's note port layout.
+///
+/// Snapshots can be [captured](Self::capture) before and after a note ports rescan, and then
+/// [compared](Self::diff) to list what actually changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotePortLayoutSnapshot {
+    inputs: Vec<NotePortEntry>,
+    outputs: Vec<NotePortEntry>,
+}
+
+impl NotePortLayoutSnapshot {
+    /// Creates a snapshot from the given input and output port entries, in the order the plugin
+    /// reports them.
+    #[inline]
+    pub fn from_entries(inputs: Vec<NotePortEntry>, outputs: Vec<NotePortEntry>) -> Self {
+        Self { inputs, outputs }
+    }
+
+    /// Queries and copies the information of all the note ports of the given plugin.
+    ///
+    /// Ports whose information could not be retrieved are skipped.
+    #[cfg(feature = "clack-host")]
+    pub fn capture(
+        ports: &PluginNotePorts,
+        plugin: &mut clack_host::prelude::PluginMainThreadHandle,
+    ) -> Self {
+        let mut buffer = NotePortInfoBuffer::new();
+        let mut snapshot = Self::default();
+
+        for is_input in [true, false] {
+            let entries = if is_input {
+                &mut snapshot.inputs
+            } else {
+                &mut snapshot.outputs
+            };
+
+            for index in 0..ports.count(plugin, is_input) {
+                if let Some(info) = ports.get(plugin, index, is_input, &mut buffer) {
+                    entries.push(NotePortEntry::from_info(&info));
+                }
+            }
+        }
+
+        snapshot
+    }
+
+    /// Returns the input ports of this snapshot.
+    #[inline]
+    pub fn inputs(&self) -> &[NotePortEntry] {
+        &self.inputs
+    }
+
+    /// Returns the output ports of this snapshot.
+    #[inline]
+    pub fn outputs(&self) -> &[NotePortEntry] {
+        &self.outputs
+    }
+
+    /// Lists all the changes between this snapshot and a more recent one.
+    ///
+    /// Changes to input ports are listed first. For each direction, removed ports are listed
+    /// first, then added ones, then the changes to the remaining ports, in their new order.
+    pub fn diff(&self, after: &Self) -> Vec<NotePortChange> {
+        let mut changes = Vec::new();
+        diff_ports(&self.inputs, &after.inputs, true, &mut changes);
+        diff_ports(&self.outputs, &after.outputs, false, &mut changes);
+        changes
+    }
+}
+
+fn diff_ports(
+    old: &[NotePortEntry],
+    new: &[NotePortEntry],
+    is_input: bool,
+    changes: &mut Vec<NotePortChange>,
+) {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+
+    diff_by_id(
+        old,
+        new,
+        |p| p.id,
+        |_, p| removed.push(NotePortChange::Removed { is_input, id: p.id }),
+        |_, p| added.push(NotePortChange::Added { is_input, id: p.id }),
+        |(old_index, old), (new_index, new)| {
+            let id = new.id;
+
+            if old_index != new_index {
+                changed.push(NotePortChange::Moved {
+                    is_input,
+                    id,
+                    old: old_index,
+                    new: new_index,
+                });
+            }
+
+            if old.name != new.name {
+                changed.push(NotePortChange::Renamed {
+                    is_input,
+                    id,
+                    old: old.name.clone(),
+                    new: new.name.clone(),
+                });
+            }
+
+            if old.supported_dialects != new.supported_dialects
+                || old.preferred_dialect != new.preferred_dialect
+            {
+                changed.push(NotePortChange::DialectsChanged {
+                    is_input,
+                    id,
+                    old: (old.supported_dialects, old.preferred_dialect),
+                    new: (new.supported_dialects, new.preferred_dialect),
+                });
+            }
+        },
+    );
+
+    changes.extend(removed);
+    changes.extend(added);
+    changes.extend(changed);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn port(id: u32, name: &str) -> NotePortEntry {
+        NotePortEntry {
+            id: ClapId::new(id),
+            name: name.into(),
+            supported_dialects: NoteDialects::CLAP,
+            preferred_dialect: Some(NoteDialect::Clap),
+        }
+    }
+
+    #[test]
+    fn lists_all_changes() {
+        let before = NotePortLayoutSnapshot::from_entries(vec![port(1, "Notes")], vec![]);
+        let after = NotePortLayoutSnapshot::from_entries(
+            vec![NotePortEntry {
+                supported_dialects: NoteDialects::CLAP | NoteDialects::MIDI,
+                ..port(1, "Keys")
+            }],
+            vec![port(2, "Out")],
+        );
+
+        let changes = before.diff(&after);
+        let id = ClapId::new;
+        assert_eq!(
+            changes,
+            [
+                NotePortChange::Renamed {
+                    is_input: true,
+                    id: id(1),
+                    old: "Notes".into(),
+                    new: "Keys".into()
+                },
+                NotePortChange::DialectsChanged {
+                    is_input: true,
+                    id: id(1),
+                    old: (NoteDialects::CLAP, Some(NoteDialect::Clap)),
+                    new: (
+                        NoteDialects::CLAP | NoteDialects::MIDI,
+                        Some(NoteDialect::Clap)
+                    )
+                },
+                NotePortChange::Added {
+                    is_input: false,
+                    id: id(2)
+                },
+            ]
+        );
+
+        assert_eq!(changes[0].required_rescan(), NotePortRescanFlags::NAMES);
+        assert_eq!(changes[1].required_rescan(), NotePortRescanFlags::ALL);
+    }
+}
@@ -2,7 +2,9 @@ use bitflags::bitflags;
 use clack_common::extensions::{Extension, HostExtensionSide, PluginExtensionSide, RawExtension};
 use clack_common::utils::{ClapId, Cookie};
 use clap_sys::ext::params::*;
+use std::error::Error;
 use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
 
 bitflags! {
     #[repr(C)]
@@ -37,6 +39,8 @@ bitflags! {
 bitflags! {
     #[repr(C)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct ParamInfoFlags: u32 {
         const IS_AUTOMATABLE = CLAP_PARAM_IS_AUTOMATABLE;
         const IS_AUTOMATABLE_PER_CHANNEL = CLAP_PARAM_IS_AUTOMATABLE_PER_CHANNEL;
@@ -54,6 +58,11 @@ bitflags! {
         const IS_READONLY = CLAP_PARAM_IS_READONLY;
         const IS_STEPPED = CLAP_PARAM_IS_STEPPED;
         const REQUIRES_PROCESS = CLAP_PARAM_REQUIRES_PROCESS;
+        /// The parameter is an enumeration: it is stepped, and each of its integer values has its
+        /// own label, which hosts can use to display it as a dropdown.
+        ///
+        /// This flag was added in CLAP 1.2.
+        const IS_ENUM = 1 << 16;
     }
 }
 
@@ -73,10 +82,83 @@ impl ParamInfoFlags {
             | Self::IS_MODULATABLE_PER_PORT.bits()
             | Self::IS_READONLY.bits()
             | Self::IS_BYPASS.bits()
-            | Self::IS_STEPPED.bits(),
+            | Self::IS_STEPPED.bits()
+            | Self::IS_ENUM.bits(),
     );
+
+    /// All the per-port, per-channel, per-key and per-note ID automation flags.
+    pub const AUTOMATABLE_PER_ANY: Self = Self::from_bits_truncate(
+        Self::IS_AUTOMATABLE_PER_PORT.bits()
+            | Self::IS_AUTOMATABLE_PER_CHANNEL.bits()
+            | Self::IS_AUTOMATABLE_PER_KEY.bits()
+            | Self::IS_AUTOMATABLE_PER_NOTE_ID.bits(),
+    );
+
+    /// All the per-port, per-channel, per-key and per-note ID modulation flags.
+    pub const MODULATABLE_PER_ANY: Self = Self::from_bits_truncate(
+        Self::IS_MODULATABLE_PER_PORT.bits()
+            | Self::IS_MODULATABLE_PER_CHANNEL.bits()
+            | Self::IS_MODULATABLE_PER_KEY.bits()
+            | Self::IS_MODULATABLE_PER_NOTE_ID.bits(),
+    );
+}
+
+/// An inconsistency found in a [`ParamInfo`] by [`ParamInfo::validate`].
+///
+/// Hosts may handle parameters with those inconsistencies in unexpected ways (or not at all).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParamInfoWarning {
+    /// The minimum, maximum or default value isn't finite, or the minimum value is greater than
+    /// the maximum value.
+    InvalidRange,
+    /// The default value is outside the parameter's range.
+    DefaultOutOfRange,
+    /// The parameter is stepped, but its range bounds aren't integers.
+    NonIntegerSteppedRange,
+    /// The parameter is a bypass parameter, but isn't stepped.
+    BypassNotStepped,
+    /// The parameter is a bypass parameter, but its range isn't `0..=1`.
+    BypassRange,
+    /// The parameter is an enumeration, but isn't stepped.
+    EnumNotStepped,
+    /// Some of the given per-port, per-channel, per-key or per-note ID automation flags are set,
+    /// but [`ParamInfoFlags::IS_AUTOMATABLE`] isn't.
+    PerAutomatableWithoutAutomatable(ParamInfoFlags),
+    /// Some of the given per-port, per-channel, per-key or per-note ID modulation flags are set,
+    /// but [`ParamInfoFlags::IS_MODULATABLE`] isn't.
+    PerModulatableWithoutModulatable(ParamInfoFlags),
+    /// The parameter is read-only, but is also automatable or modulatable by the host.
+    ReadOnlyAutomatable,
 }
 
+impl Display for ParamInfoWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidRange => f.write_str("Parameter has an invalid value range"),
+            Self::DefaultOutOfRange => {
+                f.write_str("Parameter's default value is outside of its range")
+            }
+            Self::NonIntegerSteppedRange => {
+                f.write_str("Stepped parameter has non-integer range bounds")
+            }
+            Self::BypassNotStepped => f.write_str("Bypass parameter is not stepped"),
+            Self::BypassRange => f.write_str("Bypass parameter's range is not 0 to 1"),
+            Self::EnumNotStepped => f.write_str("Enum parameter is not stepped"),
+            Self::PerAutomatableWithoutAutomatable(flags) => {
+                write!(f, "Parameter has flags {flags:?} but is not IS_AUTOMATABLE")
+            }
+            Self::PerModulatableWithoutModulatable(flags) => {
+                write!(f, "Parameter has flags {flags:?} but is not IS_MODULATABLE")
+            }
+            Self::ReadOnlyAutomatable => {
+                f.write_str("Read-only parameter is automatable or modulatable")
+            }
+        }
+    }
+}
+
+impl Error for ParamInfoWarning {}
+
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
 pub struct PluginParams(RawExtension<PluginExtensionSide, clap_plugin_params>);
@@ -131,6 +213,124 @@ impl<'a> ParamInfo<'a> {
         })
     }
 
+    /// Returns `true` if this parameter only takes integer values.
+    #[inline]
+    pub fn is_stepped(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_STEPPED)
+    }
+
+    /// Returns `true` if this parameter wraps around, e.g. a phase or an angle.
+    #[inline]
+    pub fn is_periodic(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_PERIODIC)
+    }
+
+    /// Returns `true` if this parameter should not be shown to the user.
+    #[inline]
+    pub fn is_hidden(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_HIDDEN)
+    }
+
+    /// Returns `true` if this parameter can only be changed by the plugin.
+    #[inline]
+    pub fn is_readonly(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_READONLY)
+    }
+
+    /// Returns `true` if this parameter is the plugin's bypass parameter.
+    #[inline]
+    pub fn is_bypass(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_BYPASS)
+    }
+
+    /// Returns `true` if this parameter can be automated by the host.
+    #[inline]
+    pub fn is_automatable(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_AUTOMATABLE)
+    }
+
+    /// Returns `true` if this parameter can be modulated by the host.
+    #[inline]
+    pub fn is_modulatable(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_MODULATABLE)
+    }
+
+    /// Returns `true` if changing this parameter requires the plugin's audio processor to run.
+    #[inline]
+    pub fn requires_process(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::REQUIRES_PROCESS)
+    }
+
+    /// Returns `true` if this parameter is an enumeration, i.e. each of its values has a label.
+    #[inline]
+    pub fn is_enum(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_ENUM)
+    }
+
+    /// Returns `true` if this parameter should be shown in a host's generic parameter UI.
+    ///
+    /// This is the case for all parameters that are neither hidden nor read-only.
+    #[inline]
+    pub fn is_user_editable(&self) -> bool {
+        !self.is_hidden() && !self.is_readonly()
+    }
+
+    /// Checks this parameter's info for inconsistent flags or values, and returns all the
+    /// [`ParamInfoWarning`]s that were found.
+    ///
+    /// An empty list means this parameter's info is consistent.
+    pub fn validate(&self) -> Vec<ParamInfoWarning> {
+        let mut warnings = Vec::new();
+
+        let range_is_finite = self.min_value.is_finite() && self.max_value.is_finite();
+        if !range_is_finite || !self.default_value.is_finite() || self.min_value > self.max_value {
+            warnings.push(ParamInfoWarning::InvalidRange);
+        } else if self.default_value < self.min_value || self.default_value > self.max_value {
+            warnings.push(ParamInfoWarning::DefaultOutOfRange);
+        }
+
+        if self.is_stepped()
+            && range_is_finite
+            && (self.min_value.fract() != 0.0 || self.max_value.fract() != 0.0)
+        {
+            warnings.push(ParamInfoWarning::NonIntegerSteppedRange);
+        }
+
+        if self.is_bypass() {
+            if !self.is_stepped() {
+                warnings.push(ParamInfoWarning::BypassNotStepped);
+            }
+
+            if self.min_value != 0.0 || self.max_value != 1.0 {
+                warnings.push(ParamInfoWarning::BypassRange);
+            }
+        }
+
+        if self.is_enum() && !self.is_stepped() {
+            warnings.push(ParamInfoWarning::EnumNotStepped);
+        }
+
+        let per_automatable = self.flags & ParamInfoFlags::AUTOMATABLE_PER_ANY;
+        if !per_automatable.is_empty() && !self.is_automatable() {
+            warnings.push(ParamInfoWarning::PerAutomatableWithoutAutomatable(
+                per_automatable,
+            ));
+        }
+
+        let per_modulatable = self.flags & ParamInfoFlags::MODULATABLE_PER_ANY;
+        if !per_modulatable.is_empty() && !self.is_modulatable() {
+            warnings.push(ParamInfoWarning::PerModulatableWithoutModulatable(
+                per_modulatable,
+            ));
+        }
+
+        if self.is_readonly() && (self.is_automatable() || self.is_modulatable()) {
+            warnings.push(ParamInfoWarning::ReadOnlyAutomatable);
+        }
+
+        warnings
+    }
+
     pub fn diff_for_rescan(&self, other: &ParamInfo) -> ParamRescanFlags {
         #[inline]
         fn flags_differ(
@@ -170,12 +370,124 @@ impl<'a> ParamInfo<'a> {
     }
 }
 
+pub mod info_snapshot;
+pub mod module;
+
 #[cfg(feature = "clack-host")]
 mod host;
 #[cfg(feature = "clack-host")]
 pub use host::*;
+#[cfg(feature = "clack-host")]
+pub mod flush;
+#[cfg(feature = "clack-host")]
+pub mod generic_ui;
+#[cfg(feature = "clack-host")]
+pub mod modulation;
+#[cfg(feature = "clack-host")]
+pub mod snapshot;
 
 #[cfg(feature = "clack-plugin")]
 mod plugin;
 #[cfg(feature = "clack-plugin")]
 pub use plugin::*;
+#[cfg(feature = "clack-plugin")]
+pub mod set;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn info(flags: ParamInfoFlags, min_value: f64, max_value: f64) -> ParamInfo<'static> {
+        ParamInfo {
+            id: ClapId::new(0),
+            flags,
+            cookie: Cookie::empty(),
+            name: b"Test",
+            module: b"",
+            min_value,
+            max_value,
+            default_value: min_value,
+        }
+    }
+
+    #[test]
+    pub fn consistent_infos_have_no_warnings() {
+        let flags = ParamInfoFlags::IS_AUTOMATABLE
+            | ParamInfoFlags::IS_AUTOMATABLE_PER_NOTE_ID
+            | ParamInfoFlags::IS_MODULATABLE
+            | ParamInfoFlags::IS_MODULATABLE_PER_CHANNEL;
+        assert!(info(flags, -1.0, 1.0).validate().is_empty());
+
+        let flags = ParamInfoFlags::IS_STEPPED | ParamInfoFlags::IS_BYPASS;
+        assert!(info(flags, 0.0, 1.0).validate().is_empty());
+
+        let flags = ParamInfoFlags::IS_STEPPED | ParamInfoFlags::IS_ENUM;
+        assert!(info(flags, 0.0, 3.0).validate().is_empty());
+    }
+
+    #[test]
+    pub fn inconsistent_flags_have_warnings() {
+        assert_eq!(
+            info(ParamInfoFlags::IS_BYPASS, 0.0, 1.0).validate(),
+            [ParamInfoWarning::BypassNotStepped]
+        );
+
+        assert_eq!(
+            info(ParamInfoFlags::IS_ENUM, 0.0, 2.0).validate(),
+            [ParamInfoWarning::EnumNotStepped]
+        );
+
+        let flags = ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_MODULATABLE_PER_NOTE_ID;
+        assert_eq!(
+            info(flags, 0.0, 1.0).validate(),
+            [ParamInfoWarning::PerModulatableWithoutModulatable(
+                ParamInfoFlags::IS_MODULATABLE_PER_NOTE_ID
+            )]
+        );
+
+        let flags = ParamInfoFlags::IS_AUTOMATABLE_PER_KEY | ParamInfoFlags::IS_READONLY;
+        assert_eq!(
+            info(flags, 0.0, 1.0).validate(),
+            [ParamInfoWarning::PerAutomatableWithoutAutomatable(
+                ParamInfoFlags::IS_AUTOMATABLE_PER_KEY
+            )]
+        );
+
+        let flags = ParamInfoFlags::IS_READONLY | ParamInfoFlags::IS_MODULATABLE;
+        assert_eq!(
+            info(flags, 0.0, 1.0).validate(),
+            [ParamInfoWarning::ReadOnlyAutomatable]
+        );
+    }
+
+    #[test]
+    pub fn inconsistent_ranges_have_warnings() {
+        assert_eq!(
+            info(ParamInfoFlags::empty(), 1.0, 0.0).validate(),
+            [ParamInfoWarning::InvalidRange]
+        );
+
+        assert_eq!(
+            info(ParamInfoFlags::empty(), 0.0, f64::INFINITY).validate(),
+            [ParamInfoWarning::InvalidRange]
+        );
+
+        let mut out_of_range = info(ParamInfoFlags::empty(), 0.0, 1.0);
+        out_of_range.default_value = 2.0;
+        assert_eq!(
+            out_of_range.validate(),
+            [ParamInfoWarning::DefaultOutOfRange]
+        );
+
+        assert_eq!(
+            info(ParamInfoFlags::IS_STEPPED, 0.0, 2.5).validate(),
+            [ParamInfoWarning::NonIntegerSteppedRange]
+        );
+
+        let flags = ParamInfoFlags::IS_STEPPED | ParamInfoFlags::IS_BYPASS;
+        assert_eq!(
+            info(flags, 0.0, 2.0).validate(),
+            [ParamInfoWarning::BypassRange]
+        );
+    }
+}
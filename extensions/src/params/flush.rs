@@ -0,0 +1,281 @@
+//! A host-side helper to service the plugins' parameter flush requests.
+//!
+//! Plugins call `request_flush` when they have parameter changes to report (or want to receive
+//! pending ones) while they are not processing. Much like with the `request_callback` and
+//! [`call_on_main_thread_callback`](PluginInstance::call_on_main_thread_callback) pair, the host
+//! only has to mark the request, and perform the actual flush a bit later, from the right thread.
+//!
+//! This module provides a [`FlushRequestQueue`], meant to be stored in the host's
+//! [`Shared`](HostHandlers::Shared) handler and marked from its
+//! [`HostParamsImplShared::request_flush`] implementation, and the [`run_pending_flushes`]
+//! function, which performs the pending flushes of inactive plugins on the main thread.
+//!
+//! Active plugins must be flushed from the audio thread instead, when they are not processing,
+//! using [`FlushRequestQueue::flush_active`].
+//!
+//! It also provides [`set_inactive_values`], which sets a batch of parameter values on an
+//! inactive plugin in a single flush, e.g. when loading a project before activating its plugins.
+
+use super::*;
+use clack_common::events::event_types::ParamValueEvent;
+use clack_common::events::Pckn;
+use clack_host::events::io::{EventIo, InputEvents, OutputEvents};
+use clack_host::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A plugin instance's pending parameter flush request.
+///
+/// Requests are coalesced: no matter how many times the plugin called `request_flush`, only a
+/// single flush is performed.
+#[derive(Debug, Default)]
+pub struct FlushRequestQueue {
+    requested: AtomicBool,
+}
+
+impl FlushRequestQueue {
+    /// Creates a new queue, with no pending request.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Marks a flush as requested.
+    ///
+    /// This is meant to be called from the host's [`HostParamsImplShared::request_flush`]
+    /// implementation, and can be called from any thread.
+    #[inline]
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::Release)
+    }
+
+    /// Returns `true` if a flush has been requested, and hasn't been performed yet.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.requested.load(Ordering::Acquire)
+    }
+
+    /// Clears the pending request, returning `true` if there was one.
+    #[inline]
+    pub fn take(&self) -> bool {
+        self.requested.swap(false, Ordering::AcqRel)
+    }
+
+    /// Performs the pending flush of an active plugin, if any, from the audio thread.
+    ///
+    /// This must only be called while the plugin is active, but not processing. While processing,
+    /// parameter changes are exchanged through the `process` calls instead, which makes any
+    /// pending request moot: hosts can simply [`take`](Self::take) it.
+    ///
+    /// The plugin's output events are written to `output_parameter_changes`. It is then up to the
+    /// host to forward them to the main thread, e.g. to update a [`ParamValueCache`].
+    ///
+    /// Returns `true` if a flush was performed.
+    pub fn flush_active(
+        &self,
+        params: &PluginParams,
+        plugin: &mut PluginAudioProcessorHandle,
+        input_parameter_changes: &InputEvents,
+        output_parameter_changes: &mut OutputEvents,
+    ) -> bool {
+        if !self.take() {
+            return false;
+        }
+
+        params.flush_active(plugin, input_parameter_changes, output_parameter_changes);
+        true
+    }
+}
+
+/// Gives access to a host's [`FlushRequestQueue`].
+///
+/// This must be implemented by the host's [`Shared`](HostHandlers::Shared) handler for its
+/// instances to be usable with [`run_pending_flushes`].
+pub trait HostParamsFlushQueue {
+    /// Returns the flush request queue of this plugin instance.
+    fn flush_request_queue(&self) -> &FlushRequestQueue;
+}
+
+/// A plugin instance to perform the pending flushes of, using [`run_pending_flushes`].
+pub struct FlushTarget<'a, H: HostHandlers> {
+    instance: &'a mut PluginInstance<H>,
+    cache: &'a mut ParamValueCache,
+    input_events: Option<&'a InputEvents<'a>>,
+}
+
+impl<'a, H: HostHandlers> FlushTarget<'a, H> {
+    /// Creates a new flush target, for the given plugin instance.
+    ///
+    /// The plugin's output parameter changes are used to update the given parameter `cache`.
+    #[inline]
+    pub fn new(instance: &'a mut PluginInstance<H>, cache: &'a mut ParamValueCache) -> Self {
+        Self {
+            instance,
+            cache,
+            input_events: None,
+        }
+    }
+
+    /// Sets the parameter changes to send to the plugin when it is flushed.
+    ///
+    /// These are only sent if the plugin has requested a flush. By default, no events are sent.
+    #[inline]
+    pub fn with_input_events(mut self, input_events: &'a InputEvents<'a>) -> Self {
+        self.input_events = Some(input_events);
+        self
+    }
+}
+
+/// Performs the pending parameter flushes of the given inactive plugin instances, on the main
+/// thread.
+///
+/// Each instance that requested a flush is flushed exactly once, with its queued input events
+/// (if any), and its parameter cache is updated with the events it outputs. The output buffer of
+/// `events` is used as temporary storage for these output events.
+///
+/// Active instances are left untouched, and their requests stay pending: they have to be
+/// flushed from the audio thread, using [`FlushRequestQueue::flush_active`]. Requests of plugins
+/// that don't implement the params extension are discarded.
+///
+/// Returns the number of flushes that were performed.
+pub fn run_pending_flushes<H: HostHandlers>(
+    instances: &mut [FlushTarget<H>],
+    events: &mut EventIo,
+) -> usize
+where
+    for<'a> <H as HostHandlers>::Shared<'a>: HostParamsFlushQueue,
+{
+    let mut flushed = 0;
+
+    for target in instances {
+        if target.instance.is_active()
+            || !target
+                .instance
+                .access_shared_handler(|s| s.flush_request_queue().take())
+        {
+            continue;
+        }
+
+        let mut handle = target.instance.plugin_handle_unchecked();
+        let Some(params) = handle.get_extension::<PluginParams>() else {
+            continue;
+        };
+
+        events.reset();
+        params.flush(
+            &mut handle,
+            target.input_events.unwrap_or(&InputEvents::empty()),
+            &mut events.output(),
+        );
+
+        target
+            .cache
+            .update_from_events(&events.output_buffer().as_input());
+        flushed += 1;
+    }
+
+    flushed
+}
+
+/// The outcome of [`set_inactive_values`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InactiveValuesReport {
+    rejected: Vec<ClapId>,
+}
+
+impl InactiveValuesReport {
+    /// Returns the IDs of the parameters that weren't set to the requested value, in the order
+    /// they were given.
+    #[inline]
+    pub fn rejected(&self) -> &[ClapId] {
+        &self.rejected
+    }
+
+    /// Returns `true` if all the requested values were accepted by the plugin.
+    #[inline]
+    pub fn all_accepted(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// Sets the given parameter values on an inactive plugin instance, in a single flush on the
+/// main thread.
+///
+/// Values are only sent for parameters that are present in the given (already
+/// [`rescan`](ParamValueCache::rescan)ned) `cache`, and that aren't flagged with
+/// [`ParamInfoFlags::IS_READONLY`]. Each value is then read back from the plugin: it is only
+/// considered accepted if the plugin reports it unchanged, in which case the `cache` is updated
+/// accordingly. Note this means values that the plugin clamps or rounds are reported as rejected.
+///
+/// Any other parameter change the plugin outputs during the flush is applied to the `cache` as
+/// well. `events` is used as temporary storage for the sent and received events.
+///
+/// If the plugin doesn't implement the params extension, all values are rejected.
+///
+/// # Errors
+///
+/// This returns [`PluginInstanceError::AlreadyActivatedPlugin`] if the plugin instance is
+/// active, in which case nothing is sent.
+pub fn set_inactive_values<H: HostHandlers>(
+    instance: &mut PluginInstance<H>,
+    cache: &mut ParamValueCache,
+    values: &[(ClapId, f64)],
+    events: &mut EventIo,
+) -> Result<InactiveValuesReport, PluginInstanceError> {
+    if instance.is_active() {
+        return Err(PluginInstanceError::AlreadyActivatedPlugin);
+    }
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let Some(params) = handle.get_extension::<PluginParams>() else {
+        return Ok(InactiveValuesReport {
+            rejected: values.iter().map(|(id, _)| *id).collect(),
+        });
+    };
+
+    events.reset();
+
+    for &(param_id, value) in values {
+        let Some(param) = cache.get(param_id) else {
+            continue;
+        };
+
+        if param.flags.contains(ParamInfoFlags::IS_READONLY) {
+            continue;
+        }
+
+        events.push_input(&ParamValueEvent::new(
+            0,
+            param_id,
+            Pckn::match_all(),
+            value,
+            param.cookie,
+        ));
+    }
+
+    if !events.input_buffer().is_empty() {
+        let (input, mut output) = events.split();
+        params.flush(&mut handle, &input, &mut output);
+    }
+
+    cache.update_from_events(&events.output_buffer().as_input());
+
+    let mut rejected = Vec::new();
+    for &(param_id, value) in values {
+        let is_sent = cache
+            .get(param_id)
+            .is_some_and(|p| !p.flags.contains(ParamInfoFlags::IS_READONLY));
+
+        match params.get_value(&mut handle, param_id) {
+            Some(actual) if is_sent && actual == value => {
+                if let Some(param) = cache.get_mut(param_id) {
+                    param.value = Some(actual);
+                }
+            }
+            _ => rejected.push(param_id),
+        }
+    }
+
+    Ok(InactiveValuesReport { rejected })
+}
@@ -0,0 +1,565 @@
+//! A host-side data model for generic plugin editors.
+//!
+//! Hosts usually provide a generic editor for plugins that don't have a GUI of their own (or
+//! whose GUI isn't available), made of one control per parameter. This module provides the
+//! [`GenericUiModel`] type, which holds everything such an editor needs, independently of the
+//! widget toolkit used to draw it:
+//!
+//! * the visible parameters, organized by module, and classified into [`ControlKind`]s
+//!   (knobs, steppers, toggles and enumerations) from their flags and ranges;
+//! * their current values, kept in sync with a [`ParamValueCache`];
+//! * their display text, queried lazily from the plugin and cached.
+//!
+//! User interactions are turned into parameter events, pushed to the host's event queue for the
+//! plugin (e.g. its next `process` call, or a [`flush`](PluginParams::flush)), with the
+//! [`set_value`](GenericUiModel::set_value) family of methods.
+//!
+//! The model has to be notified whenever the plugin requests a parameter rescan, through
+//! [`on_rescan`](GenericUiModel::on_rescan). Its [`revision`](GenericUiModel::revision) is
+//! incremented every time it changes, so that editors know when to redraw.
+
+use super::module::{ParamModulePath, ParamModuleTree};
+use super::*;
+use clack_common::events::event_types::{
+    ParamGestureBeginEvent, ParamGestureEndEvent, ParamValueEvent,
+};
+use clack_common::events::io::{OutputEvents, TryPushError};
+use clack_common::events::Pckn;
+use clack_host::extensions::prelude::*;
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+
+/// The kind of control a parameter is displayed with, in a generic editor.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ControlKind {
+    /// A continuous parameter, typically displayed as a knob or a slider.
+    Knob,
+    /// A stepped parameter, typically displayed as a spin box or a stepped slider.
+    Stepper,
+    /// A stepped parameter with only two values (e.g. a bypass parameter), typically displayed
+    /// as a checkbox or a toggle button.
+    Toggle,
+    /// An enumeration parameter, typically displayed as a dropdown.
+    ///
+    /// The labels of its values are available through [`GenericUiModel::enum_labels`].
+    Enum,
+}
+
+impl ControlKind {
+    /// Classifies the given parameter from its flags and range.
+    pub fn classify(param: &CachedParam) -> Self {
+        let flags = param.flags;
+
+        if flags.contains(ParamInfoFlags::IS_BYPASS) {
+            return ControlKind::Toggle;
+        }
+
+        if !flags.contains(ParamInfoFlags::IS_STEPPED) {
+            return ControlKind::Knob;
+        }
+
+        if param.min_value.ceil() + 1.0 == param.max_value.floor() {
+            ControlKind::Toggle
+        } else if flags.contains(ParamInfoFlags::IS_ENUM) {
+            ControlKind::Enum
+        } else {
+            ControlKind::Stepper
+        }
+    }
+
+    /// Returns `true` if this kind of control only takes integer values.
+    #[inline]
+    pub fn is_stepped(&self) -> bool {
+        !matches!(self, ControlKind::Knob)
+    }
+}
+
+/// A single parameter control in a [`GenericUiModel`].
+#[derive(Clone, Debug)]
+pub struct GenericControl {
+    id: ClapId,
+    kind: ControlKind,
+    flags: ParamInfoFlags,
+    cookie: Cookie,
+    name: String,
+    display_name: String,
+    module: ParamModulePath,
+    min_value: f64,
+    max_value: f64,
+    default_value: f64,
+    value: Option<f64>,
+}
+
+impl GenericControl {
+    fn new(param: &CachedParam, module: ParamModulePath, display_name: String) -> Self {
+        Self {
+            id: param.id,
+            kind: ControlKind::classify(param),
+            flags: param.flags,
+            cookie: param.cookie,
+            name: param.name.clone(),
+            display_name,
+            module,
+            min_value: param.min_value,
+            max_value: param.max_value,
+            default_value: param.default_value,
+            value: param.value,
+        }
+    }
+
+    /// Returns the ID of this control's parameter.
+    #[inline]
+    pub fn id(&self) -> ClapId {
+        self.id
+    }
+
+    /// Returns the kind of this control.
+    #[inline]
+    pub fn kind(&self) -> ControlKind {
+        self.kind
+    }
+
+    /// Returns the flags of this control's parameter.
+    #[inline]
+    pub fn flags(&self) -> ParamInfoFlags {
+        self.flags
+    }
+
+    /// Returns the name of this control's parameter, as given by the plugin.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the name to display for this control.
+    ///
+    /// This is the parameter's name, unless another visible parameter in a different module has
+    /// the same name, in which case it is qualified with the module path (see
+    /// [`ParamModuleTree::display_names`]).
+    #[inline]
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Returns the module path of this control's parameter.
+    #[inline]
+    pub fn module(&self) -> &ParamModulePath {
+        &self.module
+    }
+
+    /// Returns the minimum value of this control's parameter.
+    #[inline]
+    pub fn min_value(&self) -> f64 {
+        self.min_value
+    }
+
+    /// Returns the maximum value of this control's parameter.
+    #[inline]
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    /// Returns the default value of this control's parameter.
+    #[inline]
+    pub fn default_value(&self) -> f64 {
+        self.default_value
+    }
+
+    /// Returns the current value of this control's parameter, or `None` if the plugin didn't
+    /// provide it.
+    #[inline]
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    /// Returns the current value of this control's parameter, mapped to the `0..=1` range.
+    pub fn normalized_value(&self) -> Option<f64> {
+        let range = self.max_value - self.min_value;
+        if range <= 0.0 {
+            return Some(0.0);
+        }
+
+        Some(((self.value? - self.min_value) / range).clamp(0.0, 1.0))
+    }
+
+    /// Returns `true` if the user can't change this control, i.e. its parameter is read-only.
+    #[inline]
+    pub fn is_readonly(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_READONLY)
+    }
+
+    /// Returns `true` if this control's parameter is periodic, e.g. a phase that wraps around.
+    #[inline]
+    pub fn is_periodic(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_PERIODIC)
+    }
+
+    /// Clamps the given value to this control's range, rounding it if the control is stepped.
+    ///
+    /// NaN values are replaced with the default value.
+    pub fn constrain(&self, value: f64) -> f64 {
+        if value.is_nan() {
+            return self.default_value;
+        }
+
+        let value = value.clamp(self.min_value, self.max_value);
+
+        if self.kind.is_stepped() {
+            value.round().clamp(self.min_value, self.max_value)
+        } else {
+            value
+        }
+    }
+
+    fn info(&self) -> ParamInfo<'_> {
+        ParamInfo {
+            id: self.id,
+            flags: self.flags,
+            cookie: self.cookie,
+            name: self.name.as_bytes(),
+            module: self.module.as_str().as_bytes(),
+            min_value: self.min_value,
+            max_value: self.max_value,
+            default_value: self.default_value,
+        }
+    }
+
+    /// Formats the given value, for when the plugin can't.
+    fn fallback_text(&self, value: f64) -> String {
+        if self.kind.is_stepped() {
+            format!("{}", value.round())
+        } else {
+            format!("{value:.2}")
+        }
+    }
+}
+
+/// The data model of a generic plugin editor.
+///
+/// This is built from a [`ParamValueCache`], which must be kept up to date by the host. See the
+/// [module documentation](self) for more information.
+///
+/// Hidden parameters are not part of the model.
+#[derive(Default)]
+pub struct GenericUiModel {
+    controls: Vec<GenericControl>,
+    modules: ParamModuleTree<usize>,
+    texts: HashMap<ClapId, (u64, String)>,
+    enum_labels: EnumLabelCache,
+    revision: u64,
+}
+
+impl GenericUiModel {
+    /// Creates a new, empty model.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new model holding all the visible parameters in the given cache.
+    pub fn from_cache(cache: &ParamValueCache) -> Self {
+        let mut model = Self::new();
+        model.rebuild(cache);
+        model
+    }
+
+    /// Rebuilds this model from all the visible parameters in the given cache.
+    ///
+    /// All the cached display texts and enumeration labels are discarded.
+    pub fn rebuild(&mut self, cache: &ParamValueCache) {
+        let visible: Vec<_> = cache
+            .params()
+            .iter()
+            .filter(|p| !p.flags.contains(ParamInfoFlags::IS_HIDDEN))
+            .collect();
+
+        let modules: Vec<_> = visible
+            .iter()
+            .map(|p| ParamModulePath::from_bytes_lossy(p.module.as_bytes()))
+            .collect();
+
+        let named: ParamModuleTree<(usize, &CachedParam)> = modules
+            .iter()
+            .cloned()
+            .zip(visible.iter().copied().enumerate())
+            .collect();
+
+        let mut display_names: HashMap<usize, String> = named
+            .display_names(|(_, param)| param.name.as_str())
+            .into_iter()
+            .map(|(&(i, _), name)| (i, name))
+            .collect();
+
+        self.controls = visible
+            .iter()
+            .zip(modules.iter().cloned())
+            .enumerate()
+            .map(|(i, (param, module))| {
+                let display_name = display_names.remove(&i).unwrap_or_default();
+                GenericControl::new(param, module, display_name)
+            })
+            .collect();
+
+        self.modules = modules.iter().cloned().zip(0..).collect();
+        self.texts.clear();
+        self.enum_labels = EnumLabelCache::new();
+        self.revision += 1;
+    }
+
+    /// Updates this model following a `rescan` request from the plugin with the given flags.
+    ///
+    /// The given cache must have been [`rescan`](ParamValueCache::rescan)ned beforehand.
+    ///
+    /// This returns the IDs of the parameters whose values changed. If the parameter infos
+    /// changed, the whole model is rebuilt, and all the parameters are returned, followed by the
+    /// IDs of the parameters that were removed by the rescan (if any), so that editors can drop
+    /// their controls.
+    pub fn on_rescan(&mut self, flags: ParamRescanFlags, cache: &ParamValueCache) -> Vec<ClapId> {
+        if flags.intersects(ParamRescanFlags::INFO | ParamRescanFlags::ALL) {
+            let previous: Vec<_> = self.controls.iter().map(|c| c.id).collect();
+            self.rebuild(cache);
+
+            let mut changed: Vec<_> = self.controls.iter().map(|c| c.id).collect();
+            let removed: Vec<_> = previous
+                .into_iter()
+                .filter(|id| self.control(*id).is_none())
+                .collect();
+
+            changed.extend(removed);
+            return changed;
+        }
+
+        if flags.contains(ParamRescanFlags::TEXT) {
+            self.texts.clear();
+            self.enum_labels.invalidate(flags);
+            self.revision += 1;
+        }
+
+        if flags.contains(ParamRescanFlags::VALUES) {
+            self.sync_values(cache)
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Updates the values of this model from the given cache, e.g. after it was updated with the
+    /// parameter events coming from the plugin.
+    ///
+    /// This returns the IDs of the parameters whose values changed.
+    pub fn sync_values(&mut self, cache: &ParamValueCache) -> Vec<ClapId> {
+        let mut changed = Vec::new();
+
+        for control in &mut self.controls {
+            let value = cache.value(control.id);
+
+            if value != control.value {
+                control.value = value;
+                changed.push(control.id);
+            }
+        }
+
+        if !changed.is_empty() {
+            self.revision += 1;
+        }
+
+        changed
+    }
+
+    /// Returns a number that is incremented every time this model changes.
+    ///
+    /// Editors can compare it against the revision they last drew to know if they need to be
+    /// redrawn.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns all the controls of this model, in the order the plugin declared their
+    /// parameters in.
+    #[inline]
+    pub fn controls(&self) -> &[GenericControl] {
+        &self.controls
+    }
+
+    /// Returns the control of the parameter with the given ID, if it is part of this model.
+    #[inline]
+    pub fn control(&self, param_id: ClapId) -> Option<&GenericControl> {
+        self.controls.iter().find(|c| c.id == param_id)
+    }
+
+    /// Returns the controls organized by module.
+    ///
+    /// The items of this tree are indexes into [`controls`](Self::controls).
+    #[inline]
+    pub fn modules(&self) -> &ParamModuleTree<usize> {
+        &self.modules
+    }
+
+    /// Returns the text to display for the current value of the given parameter.
+    ///
+    /// The text is queried from the plugin's `value_to_text` implementation the first time, and
+    /// cached until the value changes, or until the plugin requests a text rescan. If the plugin
+    /// can't provide a text, the value is formatted by the host instead.
+    ///
+    /// This returns `None` if the parameter isn't part of this model, or if its value is unknown.
+    pub fn value_text(
+        &mut self,
+        param_id: ClapId,
+        params: &PluginParams,
+        plugin: &mut PluginMainThreadHandle,
+    ) -> Option<&str> {
+        let control = self.controls.iter().find(|c| c.id == param_id)?;
+        let value = control.value?;
+        let bits = value.to_bits();
+
+        if !matches!(self.texts.get(&param_id), Some((cached, _)) if *cached == bits) {
+            let mut buffer = [MaybeUninit::uninit(); 256];
+            let text = match params.value_to_text(plugin, param_id, value, &mut buffer) {
+                Ok(text) => String::from_utf8_lossy(text).into_owned(),
+                Err(_) => control.fallback_text(value),
+            };
+
+            self.texts.insert(param_id, (bits, text));
+        }
+
+        self.texts.get(&param_id).map(|(_, text)| text.as_str())
+    }
+
+    /// Returns the labels of each value of the given enumeration parameter.
+    ///
+    /// The labels are queried from the plugin the first time, and cached until the plugin
+    /// requests a text rescan. See [`ParamInfo::enum_labels`] for more information.
+    pub fn enum_labels(
+        &mut self,
+        param_id: ClapId,
+        params: &PluginParams,
+        plugin: &mut PluginMainThreadHandle,
+    ) -> Option<&[String]> {
+        let control = self.controls.iter().find(|c| c.id == param_id)?;
+        self.enum_labels.get(&control.info(), params, plugin)
+    }
+
+    /// Sets the value of the given parameter, on behalf of the user.
+    ///
+    /// The value is [constrained](GenericControl::constrain) to the parameter's range, and a
+    /// parameter value event is pushed to `events`, which are meant to be sent to the plugin. The
+    /// value is also updated in both this model and the given cache.
+    ///
+    /// This returns `Ok(false)` if the parameter isn't part of this model, or if it is
+    /// read-only, in which case nothing is pushed.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if the event could not be pushed.
+    pub fn set_value(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        cache: &mut ParamValueCache,
+        events: &mut OutputEvents,
+    ) -> Result<bool, TryPushError> {
+        let Some(control) = self.controls.iter_mut().find(|c| c.id == param_id) else {
+            return Ok(false);
+        };
+
+        if control.is_readonly() {
+            return Ok(false);
+        }
+
+        let value = control.constrain(value);
+
+        events.try_push(ParamValueEvent::new(
+            0,
+            param_id,
+            Pckn::match_all(),
+            value,
+            control.cookie,
+        ))?;
+
+        control.value = Some(value);
+
+        if let Some(cached) = cache.get_mut(param_id) {
+            cached.value = Some(value);
+        }
+
+        self.revision += 1;
+        Ok(true)
+    }
+
+    /// Sets the value of the given parameter from a value in the `0..=1` range.
+    ///
+    /// See [`set_value`](Self::set_value).
+    pub fn set_normalized_value(
+        &mut self,
+        param_id: ClapId,
+        normalized: f64,
+        cache: &mut ParamValueCache,
+        events: &mut OutputEvents,
+    ) -> Result<bool, TryPushError> {
+        let Some(control) = self.control(param_id) else {
+            return Ok(false);
+        };
+
+        let value = control.min_value
+            + normalized.clamp(0.0, 1.0) * (control.max_value - control.min_value);
+
+        self.set_value(param_id, value, cache, events)
+    }
+
+    /// Resets the given parameter to its default value.
+    ///
+    /// See [`set_value`](Self::set_value).
+    pub fn reset(
+        &mut self,
+        param_id: ClapId,
+        cache: &mut ParamValueCache,
+        events: &mut OutputEvents,
+    ) -> Result<bool, TryPushError> {
+        let Some(control) = self.control(param_id) else {
+            return Ok(false);
+        };
+
+        self.set_value(param_id, control.default_value, cache, events)
+    }
+
+    /// Pushes a gesture begin event for the given parameter to `events`, e.g. when the user
+    /// starts dragging its control.
+    ///
+    /// This returns `Ok(false)` if the parameter isn't part of this model, or if it is
+    /// read-only, in which case nothing is pushed.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if the event could not be pushed.
+    pub fn begin_gesture(
+        &self,
+        param_id: ClapId,
+        events: &mut OutputEvents,
+    ) -> Result<bool, TryPushError> {
+        match self.control(param_id) {
+            Some(control) if !control.is_readonly() => {
+                events.try_push(ParamGestureBeginEvent::new(0, param_id))?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Pushes a gesture end event for the given parameter to `events`, e.g. when the user
+    /// stops dragging its control.
+    ///
+    /// See [`begin_gesture`](Self::begin_gesture).
+    pub fn end_gesture(
+        &self,
+        param_id: ClapId,
+        events: &mut OutputEvents,
+    ) -> Result<bool, TryPushError> {
+        match self.control(param_id) {
+            Some(control) if !control.is_readonly() => {
+                events.try_push(ParamGestureEndEvent::new(0, param_id))?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
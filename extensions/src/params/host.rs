@@ -1,8 +1,15 @@
 use super::*;
-use clack_common::events::io::{InputEvents, OutputEvents};
+use clack_common::events::event_types::ParamValueEvent;
+use clack_common::events::io::{InputEvents, OutputEvents, TryPushError};
+use clack_common::events::spaces::CoreEventSpace;
+use clack_common::events::Pckn;
 use clack_host::extensions::prelude::*;
+use std::collections::HashMap;
 use std::mem::MaybeUninit;
 
+/// The maximum number of values [`ParamInfo::enum_labels`] queries labels for.
+pub const MAX_ENUM_LABELS: usize = 1024;
+
 #[derive(Clone)]
 pub struct ParamInfoBuffer {
     inner: MaybeUninit<clap_param_info>,
@@ -128,6 +135,11 @@ impl PluginParams {
         }
     }
 
+    /// Flushes the given parameter changes to the plugin, and receives its own parameter changes,
+    /// without processing any audio.
+    ///
+    /// This must only be called on the main thread, while the plugin is inactive. When the plugin
+    /// is active, use [`flush_active`](Self::flush_active) from the audio thread instead.
     pub fn flush(
         &self,
         plugin: &mut PluginMainThreadHandle,
@@ -146,6 +158,11 @@ impl PluginParams {
         }
     }
 
+    /// Flushes the given parameter changes to the plugin, and receives its own parameter changes,
+    /// without processing any audio.
+    ///
+    /// This must be called on the audio thread, while the plugin is active but not processing. When
+    /// the plugin is inactive, use [`flush`](Self::flush) from the main thread instead.
     pub fn flush_active(
         &self,
         plugin: &mut PluginAudioProcessorHandle,
@@ -165,6 +182,274 @@ impl PluginParams {
     }
 }
 
+impl ParamInfo<'_> {
+    /// Queries the labels of each value of this parameter, using the plugin's `value_to_text`
+    /// implementation.
+    ///
+    /// This is meant to build e.g. a dropdown model for enumeration parameters. It returns `None`
+    /// if this parameter isn't an enumeration (see [`ParamInfo::is_enum`]), if its range is
+    /// invalid or contains more than [`MAX_ENUM_LABELS`] values, or if the plugin failed to
+    /// provide a label for any of its values.
+    ///
+    /// As this calls into the plugin once per value, consider using an [`EnumLabelCache`] instead.
+    pub fn enum_labels(
+        &self,
+        params: &PluginParams,
+        plugin: &mut PluginMainThreadHandle,
+    ) -> Option<Vec<String>> {
+        if !self.is_enum() || !self.is_stepped() {
+            return None;
+        }
+
+        let min = self.min_value.ceil();
+        let max = self.max_value.floor();
+        if !min.is_finite() || !max.is_finite() || min > max {
+            return None;
+        }
+
+        let count = (max - min) as usize + 1;
+        if count > MAX_ENUM_LABELS {
+            return None;
+        }
+
+        let mut buffer = [MaybeUninit::uninit(); 256];
+
+        (0..count)
+            .map(|i| {
+                let text = params
+                    .value_to_text(plugin, self.id, min + i as f64, &mut buffer)
+                    .ok()?;
+
+                Some(String::from_utf8_lossy(text).into_owned())
+            })
+            .collect()
+    }
+}
+
+/// A cache for the labels of enumeration parameters.
+///
+/// See [`ParamInfo::enum_labels`].
+#[derive(Default)]
+pub struct EnumLabelCache {
+    labels: HashMap<ClapId, Option<Vec<String>>>,
+}
+
+impl EnumLabelCache {
+    /// Creates a new, empty cache.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the labels of the given parameter, querying them from the plugin if they weren't
+    /// already cached.
+    ///
+    /// See [`ParamInfo::enum_labels`] for more information.
+    pub fn get(
+        &mut self,
+        info: &ParamInfo,
+        params: &PluginParams,
+        plugin: &mut PluginMainThreadHandle,
+    ) -> Option<&[String]> {
+        self.labels
+            .entry(info.id)
+            .or_insert_with(|| info.enum_labels(params, plugin))
+            .as_deref()
+    }
+
+    /// Invalidates the cached labels, following a `rescan` request from the plugin with the given
+    /// flags.
+    ///
+    /// All cached labels are cleared if the text or info of the parameters changed.
+    pub fn invalidate(&mut self, flags: ParamRescanFlags) {
+        if flags.intersects(ParamRescanFlags::TEXT | ParamRescanFlags::INFO | ParamRescanFlags::ALL)
+        {
+            self.labels.clear();
+        }
+    }
+}
+
+/// The info and last known value of a parameter, as stored in a [`ParamValueCache`].
+#[derive(Clone, Debug)]
+pub struct CachedParam {
+    /// The parameter's ID.
+    pub id: ClapId,
+    /// The parameter's flags.
+    pub flags: ParamInfoFlags,
+    /// The parameter's cookie, to be sent back in parameter events.
+    pub cookie: Cookie,
+    /// The parameter's name.
+    pub name: String,
+    /// The parameter's module path.
+    pub module: String,
+    /// The parameter's minimum value.
+    pub min_value: f64,
+    /// The parameter's maximum value.
+    pub max_value: f64,
+    /// The parameter's default value.
+    pub default_value: f64,
+    /// The parameter's last known value, or `None` if the plugin didn't provide it.
+    pub value: Option<f64>,
+}
+
+impl CachedParam {
+    fn from_info(info: &ParamInfo, value: Option<f64>) -> Self {
+        Self {
+            id: info.id,
+            flags: info.flags,
+            cookie: info.cookie,
+            name: String::from_utf8_lossy(info.name).into_owned(),
+            module: String::from_utf8_lossy(info.module).into_owned(),
+            min_value: info.min_value,
+            max_value: info.max_value,
+            default_value: info.default_value,
+            value,
+        }
+    }
+}
+
+/// A main-thread cache of a plugin's parameter infos and values.
+///
+/// This is meant for hosts to e.g. build generic parameter UIs, without having to query the
+/// plugin every time. The cache has to be [`rescan`](Self::rescan)ned when the plugin requests
+/// it, and kept up to date with the parameter events going to and coming from the plugin
+/// through [`update_from_events`](Self::update_from_events).
+#[derive(Default)]
+pub struct ParamValueCache {
+    params: Vec<CachedParam>,
+}
+
+impl ParamValueCache {
+    /// Creates a new, empty cache.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queries all the parameter infos and values from the plugin again, replacing the contents
+    /// of this cache.
+    pub fn rescan(&mut self, params: &PluginParams, plugin: &mut PluginMainThreadHandle) {
+        self.params.clear();
+
+        let mut buffer = ParamInfoBuffer::new();
+        for index in 0..params.count(plugin) {
+            let Some(info) = params.get_info(plugin, index, &mut buffer) else {
+                continue;
+            };
+
+            let id = info.id;
+            let param = CachedParam::from_info(&info, None);
+            let value = params.get_value(plugin, id);
+
+            self.params.push(CachedParam { value, ..param });
+        }
+    }
+
+    /// Returns all the cached parameters, in the order the plugin reported them.
+    #[inline]
+    pub fn params(&self) -> &[CachedParam] {
+        &self.params
+    }
+
+    /// Returns the cached parameter with the given ID, if it exists.
+    #[inline]
+    pub fn get(&self, param_id: ClapId) -> Option<&CachedParam> {
+        self.params.iter().find(|p| p.id == param_id)
+    }
+
+    #[inline]
+    pub(super) fn get_mut(&mut self, param_id: ClapId) -> Option<&mut CachedParam> {
+        self.params.iter_mut().find(|p| p.id == param_id)
+    }
+
+    /// Returns the last known value of the parameter with the given ID, if any.
+    #[inline]
+    pub fn value(&self, param_id: ClapId) -> Option<f64> {
+        self.get(param_id)?.value
+    }
+
+    /// Updates the cached values from the given parameter events.
+    ///
+    /// Only global parameter value events are taken into account, i.e. those targeting all
+    /// ports, channels, keys and note IDs.
+    pub fn update_from_events(&mut self, events: &InputEvents) {
+        for event in events {
+            let Some(CoreEventSpace::ParamValue(event)) = event.as_core_event() else {
+                continue;
+            };
+
+            if event.pckn() != Pckn::match_all() {
+                continue;
+            }
+
+            let Some(param_id) = event.param_id() else {
+                continue;
+            };
+
+            if let Some(param) = self.params.iter_mut().find(|p| p.id == param_id) {
+                param.value = Some(event.value());
+            }
+        }
+    }
+
+    /// Returns the ID of the plugin's bypass parameter, i.e. the first parameter flagged with
+    /// [`ParamInfoFlags::IS_BYPASS`], if any.
+    ///
+    /// Hosts should wire this parameter to their bypass button when it exists.
+    #[inline]
+    pub fn bypass_param_id(&self) -> Option<ClapId> {
+        self.params
+            .iter()
+            .find(|p| p.flags.contains(ParamInfoFlags::IS_BYPASS))
+            .map(|p| p.id)
+    }
+
+    /// Returns `true` if the plugin has a bypass parameter, and its last known value is enabled.
+    #[inline]
+    pub fn is_bypassed(&self) -> bool {
+        self.bypass_param_id()
+            .and_then(|id| self.value(id))
+            .is_some_and(|v| v >= 0.5)
+    }
+
+    /// Pushes a parameter value event that sets the plugin's bypass parameter to the given state
+    /// to `events`, and updates the cached value accordingly.
+    ///
+    /// These events are meant to be sent to the plugin, e.g. in its next `process` call.
+    ///
+    /// Returns `Ok(false)` if the plugin has no bypass parameter, in which case nothing is
+    /// pushed.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if the event could not be pushed.
+    pub fn set_bypassed(
+        &mut self,
+        bypassed: bool,
+        events: &mut OutputEvents,
+    ) -> Result<bool, TryPushError> {
+        let Some(param) = self
+            .params
+            .iter_mut()
+            .find(|p| p.flags.contains(ParamInfoFlags::IS_BYPASS))
+        else {
+            return Ok(false);
+        };
+
+        let value = if bypassed { 1.0 } else { 0.0 };
+        events.try_push(ParamValueEvent::new(
+            0,
+            param.id,
+            Pckn::match_all(),
+            value,
+            param.cookie,
+        ))?;
+
+        param.value = Some(value);
+        Ok(true)
+    }
+}
+
 #[allow(clippy::missing_safety_doc)]
 #[inline]
 unsafe fn assume_init_slice<T>(slice: &mut [MaybeUninit<T>]) -> &mut [T] {
@@ -199,7 +484,7 @@ unsafe extern "C" fn rescan<H: HostHandlers>(host: *const clap_host, flags: clap
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostParamsImplMainThread,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_params.rescan", |host| {
         host.main_thread()
             .as_mut()
             .rescan(ParamRescanFlags::from_bits_truncate(flags));
@@ -216,7 +501,7 @@ unsafe extern "C" fn clear<H: HostHandlers>(
 ) where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostParamsImplMainThread,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_params.clear", |host| {
         let param_id = ClapId::from_raw(param_id)
             .ok_or(HostWrapperError::InvalidParameter("Invalid param_id"))?;
         host.main_thread()
@@ -232,7 +517,7 @@ unsafe extern "C" fn request_flush<H: HostHandlers>(host: *const clap_host)
 where
     for<'a> <H as HostHandlers>::Shared<'a>: HostParamsImplShared,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_named(host, "clap_host_params.request_flush", |host| {
         host.shared().request_flush();
 
         Ok(())
@@ -0,0 +1,415 @@
+//! Owned snapshots of a plugin's parameter information, to compare them across rescans.
+//!
+//! See the [`ParamInfoSnapshot`] type for more information.
+
+use super::*;
+use crate::utils::diff_by_id;
+
+/// The information of a single parameter in a [`ParamInfoSnapshot`].
+///
+/// Unlike [`ParamInfo`], this owns all of its data. The parameter's cookie is left out, as it is
+/// only meaningful to the plugin instance that provided it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParamInfoEntry {
+    /// The parameter's ID.
+    pub id: ClapId,
+    /// The parameter's flags.
+    pub flags: ParamInfoFlags,
+    /// The parameter's name.
+    pub name: String,
+    /// The parameter's module path.
+    pub module: String,
+    /// The parameter's minimum value.
+    pub min_value: f64,
+    /// The parameter's maximum value.
+    pub max_value: f64,
+    /// The parameter's default value.
+    pub default_value: f64,
+}
+
+impl ParamInfoEntry {
+    /// Copies the given parameter information.
+    pub fn from_info(info: &ParamInfo) -> Self {
+        Self {
+            id: info.id,
+            flags: info.flags,
+            name: String::from_utf8_lossy(info.name).into_owned(),
+            module: String::from_utf8_lossy(info.module).into_owned(),
+            min_value: info.min_value,
+            max_value: info.max_value,
+            default_value: info.default_value,
+        }
+    }
+}
+
+/// A change in a parameter's information between two [`ParamInfoSnapshot`]s.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamInfoChange {
+    /// A new parameter was added.
+    Added {
+        /// The ID of the new parameter.
+        id: ClapId,
+    },
+    /// A parameter was removed.
+    Removed {
+        /// The ID of the removed parameter.
+        id: ClapId,
+    },
+    /// A parameter's name changed.
+    Renamed {
+        /// The ID of the parameter.
+        id: ClapId,
+        /// The previous name.
+        old: String,
+        /// The new name.
+        new: String,
+    },
+    /// A parameter's module path changed.
+    ModuleChanged {
+        /// The ID of the parameter.
+        id: ClapId,
+        /// The previous module path.
+        old: String,
+        /// The new module path.
+        new: String,
+    },
+    /// A parameter's flags changed.
+    FlagsChanged {
+        /// The ID of the parameter.
+        id: ClapId,
+        /// The previous flags.
+        old: ParamInfoFlags,
+        /// The new flags.
+        new: ParamInfoFlags,
+    },
+    /// A parameter's range changed.
+    RangeChanged {
+        /// The ID of the parameter.
+        id: ClapId,
+        /// The previous minimum and maximum values.
+        old: (f64, f64),
+        /// The new minimum and maximum values.
+        new: (f64, f64),
+    },
+    /// A parameter's default value changed.
+    DefaultChanged {
+        /// The ID of the parameter.
+        id: ClapId,
+        /// The previous default value.
+        old: f64,
+        /// The new default value.
+        new: f64,
+    },
+}
+
+impl ParamInfoChange {
+    /// Returns the ID of the parameter this change is about.
+    pub fn id(&self) -> ClapId {
+        match self {
+            Self::Added { id }
+            | Self::Removed { id }
+            | Self::Renamed { id, .. }
+            | Self::ModuleChanged { id, .. }
+            | Self::FlagsChanged { id, .. }
+            | Self::RangeChanged { id, .. }
+            | Self::DefaultChanged { id, .. } => *id,
+        }
+    }
+
+    /// Returns the rescan flags a plugin must send to the host for this change to be allowed.
+    ///
+    /// This follows the same rules as [`ParamInfo::diff_for_rescan`]. Note that changes requiring
+    /// [`ParamRescanFlags::ALL`] can only be made while the plugin is deactivated.
+    pub fn required_rescan(&self) -> ParamRescanFlags {
+        match self {
+            Self::Added { .. } | Self::Removed { .. } | Self::RangeChanged { .. } => {
+                ParamRescanFlags::ALL
+            }
+            Self::FlagsChanged { old, new, .. } => {
+                let changed = *old ^ *new;
+
+                if changed.intersects(ParamInfoFlags::FLAGS_REQUIRING_FULL_RESCAN) {
+                    ParamRescanFlags::ALL
+                } else if changed.intersects(ParamInfoFlags::FLAGS_REQUIRING_INFO_RESCAN) {
+                    ParamRescanFlags::INFO
+                } else {
+                    ParamRescanFlags::empty()
+                }
+            }
+            Self::Renamed { .. } | Self::ModuleChanged { .. } | Self::DefaultChanged { .. } => {
+                ParamRescanFlags::INFO
+            }
+        }
+    }
+
+    /// Returns `true` if this change is allowed by a rescan with the given flags.
+    pub fn is_allowed_by(&self, flags: ParamRescanFlags) -> bool {
+        let required = self.required_rescan();
+
+        if required.contains(ParamRescanFlags::ALL) {
+            flags.contains(ParamRescanFlags::ALL)
+        } else {
+            flags.contains(required)
+        }
+    }
+}
+
+/// An owned snapshot of the information of all of a plugin's parameters.
+///
+/// Snapshots can be [captured](Self::capture) before and after a parameter rescan, and then
+/// [compared](Self::diff) to list what actually changed. This allows e.g. host UIs to only
+/// refresh what is needed, or validation tools to check that plugins announced their changes with
+/// the right rescan flags (see [`unannounced_changes`](Self::unannounced_changes)).
+///
+/// # Example
+///
+/// ```
+/// use clack_common::utils::ClapId;
+/// use clack_extensions::params::info_snapshot::*;
+/// use clack_extensions::params::{ParamInfoFlags, ParamRescanFlags};
+///
+/// let gain = ParamInfoEntry {
+///     id: ClapId::new(1),
+///     flags: ParamInfoFlags::IS_AUTOMATABLE,
+///     name: "Gain".into(),
+///     module: String::new(),
+///     min_value: 0.0,
+///     max_value: 1.0,
+///     default_value: 1.0,
+/// };
+///
+/// let before = ParamInfoSnapshot::from_entries(vec![gain.clone()]);
+/// let after = ParamInfoSnapshot::from_entries(vec![ParamInfoEntry {
+///     name: "Volume".into(),
+///     ..gain
+/// }]);
+///
+/// // Renaming a parameter requires an INFO rescan, not just a VALUES one.
+/// let changes = before.unannounced_changes(&after, ParamRescanFlags::VALUES);
+/// assert!(matches!(&changes[..], [ParamInfoChange::Renamed { .. }]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParamInfoSnapshot {
+    params: Vec<ParamInfoEntry>,
+}
+
+impl ParamInfoSnapshot {
+    /// Creates a snapshot from the given parameter entries, in the order the plugin reports them.
+    #[inline]
+    pub fn from_entries(params: Vec<ParamInfoEntry>) -> Self {
+        Self { params }
+    }
+
+    /// Queries and copies the information of all the parameters of the given plugin.
+    ///
+    /// Parameters whose information could not be retrieved are skipped.
+    #[cfg(feature = "clack-host")]
+    pub fn capture(
+        params: &PluginParams,
+        plugin: &mut clack_host::prelude::PluginMainThreadHandle,
+    ) -> Self {
+        let mut buffer = ParamInfoBuffer::new();
+
+        let count = params.count(plugin);
+        let mut entries = Vec::with_capacity(count as usize);
+
+        for index in 0..count {
+            if let Some(info) = params.get_info(plugin, index, &mut buffer) {
+                entries.push(ParamInfoEntry::from_info(&info));
+            }
+        }
+
+        Self { params: entries }
+    }
+
+    /// Returns all the parameters of this snapshot, in the order the plugin reported them.
+    #[inline]
+    pub fn params(&self) -> &[ParamInfoEntry] {
+        &self.params
+    }
+
+    /// Returns the information of the parameter with the given ID, if it is in this snapshot.
+    #[inline]
+    pub fn get(&self, param_id: ClapId) -> Option<&ParamInfoEntry> {
+        self.params.iter().find(|p| p.id == param_id)
+    }
+
+    /// Lists all the changes between this snapshot and a more recent one.
+    ///
+    /// Removed parameters are listed first, then added ones, then the changes to the remaining
+    /// parameters, in the order of the `after` snapshot.
+    pub fn diff(&self, after: &Self) -> Vec<ParamInfoChange> {
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+
+        diff_by_id(
+            &self.params,
+            &after.params,
+            |p| p.id,
+            |_, p| removed.push(ParamInfoChange::Removed { id: p.id }),
+            |_, p| added.push(ParamInfoChange::Added { id: p.id }),
+            |(_, old), (_, new)| {
+                let id = new.id;
+
+                if old.name != new.name {
+                    changed.push(ParamInfoChange::Renamed {
+                        id,
+                        old: old.name.clone(),
+                        new: new.name.clone(),
+                    });
+                }
+
+                if old.module != new.module {
+                    changed.push(ParamInfoChange::ModuleChanged {
+                        id,
+                        old: old.module.clone(),
+                        new: new.module.clone(),
+                    });
+                }
+
+                if old.flags != new.flags {
+                    changed.push(ParamInfoChange::FlagsChanged {
+                        id,
+                        old: old.flags,
+                        new: new.flags,
+                    });
+                }
+
+                if old.min_value != new.min_value || old.max_value != new.max_value {
+                    changed.push(ParamInfoChange::RangeChanged {
+                        id,
+                        old: (old.min_value, old.max_value),
+                        new: (new.min_value, new.max_value),
+                    });
+                }
+
+                if old.default_value != new.default_value {
+                    changed.push(ParamInfoChange::DefaultChanged {
+                        id,
+                        old: old.default_value,
+                        new: new.default_value,
+                    });
+                }
+            },
+        );
+
+        removed.extend(added);
+        removed.extend(changed);
+        removed
+    }
+
+    /// Lists the changes between this snapshot and a more recent one that are not allowed by a
+    /// rescan with the given flags.
+    ///
+    /// A plugin that only requested a [`VALUES`](ParamRescanFlags::VALUES) rescan, but whose
+    /// parameter information changed anyway, violates the CLAP specification: the returned list
+    /// is then non-empty.
+    pub fn unannounced_changes(
+        &self,
+        after: &Self,
+        flags: ParamRescanFlags,
+    ) -> Vec<ParamInfoChange> {
+        self.diff(after)
+            .into_iter()
+            .filter(|change| !change.is_allowed_by(flags))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(id: u32, name: &str) -> ParamInfoEntry {
+        ParamInfoEntry {
+            id: ClapId::new(id),
+            flags: ParamInfoFlags::IS_AUTOMATABLE,
+            name: name.into(),
+            module: String::new(),
+            min_value: 0.0,
+            max_value: 1.0,
+            default_value: 0.5,
+        }
+    }
+
+    #[test]
+    fn lists_all_changes() {
+        let before = ParamInfoSnapshot::from_entries(vec![
+            entry(1, "Gain"),
+            entry(2, "Pan"),
+            entry(3, "Mix"),
+        ]);
+
+        let after = ParamInfoSnapshot::from_entries(vec![
+            ParamInfoEntry {
+                name: "Volume".into(),
+                flags: ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_HIDDEN,
+                ..entry(1, "Gain")
+            },
+            ParamInfoEntry {
+                max_value: 2.0,
+                ..entry(3, "Mix")
+            },
+            entry(4, "Width"),
+        ]);
+
+        let id = ClapId::new;
+        assert_eq!(
+            before.diff(&after),
+            [
+                ParamInfoChange::Removed { id: id(2) },
+                ParamInfoChange::Added { id: id(4) },
+                ParamInfoChange::Renamed {
+                    id: id(1),
+                    old: "Gain".into(),
+                    new: "Volume".into()
+                },
+                ParamInfoChange::FlagsChanged {
+                    id: id(1),
+                    old: ParamInfoFlags::IS_AUTOMATABLE,
+                    new: ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_HIDDEN
+                },
+                ParamInfoChange::RangeChanged {
+                    id: id(3),
+                    old: (0.0, 1.0),
+                    new: (0.0, 2.0)
+                },
+            ]
+        );
+
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn flags_unannounced_changes() {
+        let before = ParamInfoSnapshot::from_entries(vec![entry(1, "Gain")]);
+        let renamed = ParamInfoSnapshot::from_entries(vec![entry(1, "Volume")]);
+        let stepped = ParamInfoSnapshot::from_entries(vec![ParamInfoEntry {
+            flags: ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_STEPPED,
+            ..entry(1, "Gain")
+        }]);
+
+        assert_eq!(
+            before
+                .unannounced_changes(&renamed, ParamRescanFlags::VALUES)
+                .len(),
+            1
+        );
+        assert!(before
+            .unannounced_changes(&renamed, ParamRescanFlags::VALUES | ParamRescanFlags::INFO)
+            .is_empty());
+
+        assert_eq!(
+            before
+                .unannounced_changes(&stepped, ParamRescanFlags::INFO)
+                .len(),
+            1
+        );
+        assert!(before
+            .unannounced_changes(&stepped, ParamRescanFlags::ALL)
+            .is_empty());
+    }
+}
@@ -0,0 +1,389 @@
+//! A host-side helper to route modulation sources to plugin parameters.
+
+use super::*;
+use clack_host::events::event_types::ParamModEvent;
+use clack_host::events::io::OutputEvents;
+use clack_host::events::Pckn;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A source of modulation, such as an LFO or a macro knob.
+///
+/// Sources are sampled once per processed block by the [`ModulationRouter`]. Their values are
+/// usually in the `-1.0..=1.0` range for bipolar sources, or `0.0..=1.0` for unipolar ones, and
+/// are scaled by each route's depth.
+///
+/// This is implemented for all `FnMut(u64, u32) -> f64` closures, which receive the steady time
+/// of the start of the block, and the number of frames in the block.
+pub trait ModulationSource: Send {
+    /// Returns the value of this source for the block starting at the given `steady_time`, and
+    /// lasting `frames_count` frames.
+    fn sample(&mut self, steady_time: u64, frames_count: u32) -> f64;
+}
+
+impl<F: FnMut(u64, u32) -> f64 + Send> ModulationSource for F {
+    #[inline]
+    fn sample(&mut self, steady_time: u64, frames_count: u32) -> f64 {
+        self(steady_time, frames_count)
+    }
+}
+
+/// A modulation source whose value can be set from any thread, e.g. by a knob in the host's UI.
+///
+/// This type can be cloned, all clones sharing the same value.
+#[derive(Clone, Debug, Default)]
+pub struct MacroKnob {
+    value: Arc<AtomicU64>,
+}
+
+impl MacroKnob {
+    /// Creates a new macro knob with the given initial value.
+    #[inline]
+    pub fn new(value: f64) -> Self {
+        Self {
+            value: Arc::new(AtomicU64::new(value.to_bits())),
+        }
+    }
+
+    /// Sets the value of this macro knob.
+    #[inline]
+    pub fn set(&self, value: f64) {
+        self.value.store(value.to_bits(), Ordering::Relaxed)
+    }
+
+    /// Returns the current value of this macro knob.
+    #[inline]
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::Relaxed))
+    }
+}
+
+impl ModulationSource for MacroKnob {
+    #[inline]
+    fn sample(&mut self, _steady_time: u64, _frames_count: u32) -> f64 {
+        self.get()
+    }
+}
+
+/// The identifier of a source added to a [`ModulationRouter`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ModulationSourceId(usize);
+
+/// Errors that can occur when routing a modulation source to a parameter.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ModulationRoutingError {
+    /// The given source does not belong to this router.
+    UnknownSource,
+    /// The parameter does not declare the given flags, which are required to modulate it with
+    /// the requested target.
+    ///
+    /// For instance, a parameter has to declare [`ParamInfoFlags::IS_MODULATABLE`] to be
+    /// modulated at all, and [`ParamInfoFlags::IS_MODULATABLE_PER_NOTE_ID`] to be modulated for
+    /// a specific note ID.
+    MissingFlags(ParamInfoFlags),
+}
+
+impl Display for ModulationRoutingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSource => f.write_str("Unknown modulation source"),
+            Self::MissingFlags(flags) => {
+                write!(f, "Parameter cannot be modulated, missing flags {flags:?}")
+            }
+        }
+    }
+}
+
+impl Error for ModulationRoutingError {}
+
+struct Route {
+    source: usize,
+    depth: f64,
+}
+
+struct Destination {
+    param_id: ClapId,
+    cookie: Cookie,
+    target: Pckn,
+    range: f64,
+    routes: Vec<Route>,
+    last_amount: Option<f64>,
+}
+
+/// Routes modulation sources to plugin parameters, by emitting parameter modulation events.
+///
+/// Sources (see [`ModulationSource`]) are added to the router using
+/// [`add_source`](Self::add_source), and can then be routed to any modulatable parameter using
+/// [`route`](Self::route). Each route can target either the parameter as a whole, or only some
+/// specific voices using a [`Pckn`], if the plugin supports it.
+///
+/// On each processed block, [`process`](Self::process) samples all the sources, and emits a
+/// [`ParamModEvent`] for every destination whose modulation amount changed. The amount of each
+/// destination is the sum of the values of all the sources routed to it, each scaled by its
+/// route's depth, and is clamped so that it never exceeds the span of the parameter's range.
+///
+/// Adding sources and changing routes may allocate, and should be done while the plugin isn't
+/// processing.
+#[derive(Default)]
+pub struct ModulationRouter {
+    sources: Vec<Box<dyn ModulationSource>>,
+    source_values: Vec<f64>,
+    destinations: Vec<Destination>,
+}
+
+impl ModulationRouter {
+    /// Creates a new, empty modulation router.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new modulation source to this router, and returns its identifier.
+    pub fn add_source(&mut self, source: impl ModulationSource + 'static) -> ModulationSourceId {
+        self.sources.push(Box::new(source));
+        self.source_values.push(0.0);
+
+        ModulationSourceId(self.sources.len() - 1)
+    }
+
+    /// Routes the given source to the parameter described by `info`, for the voices matching the
+    /// given `target`, with the given depth.
+    ///
+    /// If this source was already routed to this same destination, its depth is updated instead.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`ModulationRoutingError::MissingFlags`] if the parameter isn't modulatable,
+    /// or if it doesn't support being modulated for the specific port, channel, key or note ID
+    /// set in `target`.
+    pub fn route(
+        &mut self,
+        source: ModulationSourceId,
+        info: &ParamInfo,
+        target: Pckn,
+        depth: f64,
+    ) -> Result<(), ModulationRoutingError> {
+        if source.0 >= self.sources.len() {
+            return Err(ModulationRoutingError::UnknownSource);
+        }
+
+        let required_flags = Self::required_flags(&target);
+        if !info.flags.contains(required_flags) {
+            return Err(ModulationRoutingError::MissingFlags(
+                required_flags.difference(info.flags),
+            ));
+        }
+
+        let index = match self.destination_index(info.id, &target) {
+            Some(index) => index,
+            None => {
+                self.destinations.push(Destination {
+                    param_id: info.id,
+                    cookie: info.cookie,
+                    target,
+                    range: 0.0,
+                    routes: Vec::new(),
+                    last_amount: None,
+                });
+
+                self.destinations.len() - 1
+            }
+        };
+
+        let destination = &mut self.destinations[index];
+        destination.range = (info.max_value - info.min_value).abs();
+
+        match destination.routes.iter_mut().find(|r| r.source == source.0) {
+            Some(route) => route.depth = depth,
+            None => destination.routes.push(Route {
+                source: source.0,
+                depth,
+            }),
+        }
+
+        Ok(())
+    }
+
+    /// Removes the route between the given source and the given parameter destination.
+    ///
+    /// The modulation amount of the destination is updated on the next call to
+    /// [`process`](Self::process). Returns `false` if no such route existed.
+    pub fn unroute(&mut self, source: ModulationSourceId, param_id: ClapId, target: Pckn) -> bool {
+        let Some(index) = self.destination_index(param_id, &target) else {
+            return false;
+        };
+
+        let routes = &mut self.destinations[index].routes;
+        let Some(route_index) = routes.iter().position(|r| r.source == source.0) else {
+            return false;
+        };
+
+        routes.remove(route_index);
+        true
+    }
+
+    /// Samples all sources, and pushes a parameter modulation event to `output` for every
+    /// destination whose modulation amount changed since the last call.
+    ///
+    /// All events are emitted at the start of the block (i.e. at time `0`). Destinations that no
+    /// longer have any routes have their modulation amount reset to `0.0`, and are then removed.
+    ///
+    /// This method does not allocate, and can be called on the audio thread. The resulting events
+    /// are usually pushed into the same buffer as the plugin's input events for this block.
+    pub fn process(&mut self, steady_time: u64, frames_count: u32, output: &mut OutputEvents) {
+        for (source, value) in self.sources.iter_mut().zip(&mut self.source_values) {
+            *value = source.sample(steady_time, frames_count);
+        }
+
+        for destination in &mut self.destinations {
+            let amount: f64 = destination
+                .routes
+                .iter()
+                .map(|route| self.source_values[route.source] * route.depth)
+                .sum();
+
+            let amount = amount.clamp(-destination.range, destination.range);
+
+            if destination.last_amount == Some(amount) {
+                continue;
+            }
+
+            let event = ParamModEvent::new(
+                0,
+                destination.param_id,
+                destination.target,
+                amount,
+                destination.cookie,
+            );
+
+            if output.try_push(event).is_ok() {
+                destination.last_amount = Some(amount);
+            }
+        }
+
+        self.destinations
+            .retain(|d| !d.routes.is_empty() || d.last_amount != Some(0.0));
+    }
+
+    fn destination_index(&self, param_id: ClapId, target: &Pckn) -> Option<usize> {
+        self.destinations
+            .iter()
+            .position(|d| d.param_id == param_id && d.target == *target)
+    }
+
+    fn required_flags(target: &Pckn) -> ParamInfoFlags {
+        let mut flags = ParamInfoFlags::IS_MODULATABLE;
+
+        if target.port_index.is_specific() {
+            flags |= ParamInfoFlags::IS_MODULATABLE_PER_PORT;
+        }
+        if target.channel.is_specific() {
+            flags |= ParamInfoFlags::IS_MODULATABLE_PER_CHANNEL;
+        }
+        if target.key.is_specific() {
+            flags |= ParamInfoFlags::IS_MODULATABLE_PER_KEY;
+        }
+        if target.note_id.is_specific() {
+            flags |= ParamInfoFlags::IS_MODULATABLE_PER_NOTE_ID;
+        }
+
+        flags
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_host::events::io::EventBuffer;
+    use clack_host::events::Match;
+
+    fn info(flags: ParamInfoFlags) -> ParamInfo<'static> {
+        ParamInfo {
+            id: ClapId::new(7),
+            flags,
+            cookie: Cookie::empty(),
+            name: b"Cutoff",
+            module: b"",
+            min_value: -2.0,
+            max_value: 2.0,
+            default_value: 0.0,
+        }
+    }
+
+    fn amounts(buffer: &EventBuffer) -> Vec<(Pckn, f64)> {
+        buffer
+            .iter()
+            .map(|e| e.as_event::<ParamModEvent>().unwrap())
+            .map(|e| (e.pckn(), e.amount()))
+            .collect()
+    }
+
+    #[test]
+    fn refuses_non_modulatable_params() {
+        let mut router = ModulationRouter::new();
+        let source = router.add_source(MacroKnob::new(1.0));
+
+        assert_eq!(
+            router.route(
+                source,
+                &info(ParamInfoFlags::IS_AUTOMATABLE),
+                Pckn::match_all(),
+                1.0
+            ),
+            Err(ModulationRoutingError::MissingFlags(
+                ParamInfoFlags::IS_MODULATABLE
+            ))
+        );
+
+        let per_note = Pckn::new(Match::All, Match::All, Match::All, 42u32);
+        assert_eq!(
+            router.route(source, &info(ParamInfoFlags::IS_MODULATABLE), per_note, 1.0),
+            Err(ModulationRoutingError::MissingFlags(
+                ParamInfoFlags::IS_MODULATABLE_PER_NOTE_ID
+            ))
+        );
+
+        let flags = ParamInfoFlags::IS_MODULATABLE | ParamInfoFlags::IS_MODULATABLE_PER_NOTE_ID;
+        assert!(router.route(source, &info(flags), per_note, 1.0).is_ok());
+    }
+
+    #[test]
+    fn sums_and_clamps_amounts() {
+        let mut router = ModulationRouter::new();
+        let knob = MacroKnob::new(1.0);
+        let a = router.add_source(knob.clone());
+        let b = router.add_source(|_, _| 0.5);
+
+        let info = info(ParamInfoFlags::IS_MODULATABLE);
+        router.route(a, &info, Pckn::match_all(), 1.0).unwrap();
+        router.route(b, &info, Pckn::match_all(), 2.0).unwrap();
+
+        let mut buffer = EventBuffer::new();
+        router.process(0, 32, &mut buffer.as_output());
+        assert_eq!(amounts(&buffer), [(Pckn::match_all(), 2.0)]);
+
+        // Unchanged amounts aren't sent again.
+        buffer.clear();
+        router.process(32, 32, &mut buffer.as_output());
+        assert!(buffer.is_empty());
+
+        // Amounts can't exceed the parameter's range.
+        knob.set(10.0);
+        router.process(64, 32, &mut buffer.as_output());
+        assert_eq!(amounts(&buffer), [(Pckn::match_all(), 4.0)]);
+
+        // Removing all routes resets the modulation.
+        buffer.clear();
+        assert!(router.unroute(a, info.id, Pckn::match_all()));
+        assert!(router.unroute(b, info.id, Pckn::match_all()));
+        assert!(!router.unroute(b, info.id, Pckn::match_all()));
+        router.process(96, 32, &mut buffer.as_output());
+        assert_eq!(amounts(&buffer), [(Pckn::match_all(), 0.0)]);
+
+        buffer.clear();
+        router.process(128, 32, &mut buffer.as_output());
+        assert!(buffer.is_empty());
+    }
+}
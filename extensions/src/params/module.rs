@@ -0,0 +1,617 @@
+//! Utilities to work with the module paths of parameters.
+//!
+//! Each parameter can declare a `module` (see [`ParamInfo::module`]), a `/`-separated path such as
+//! `Oscillators/Osc 1`, that hosts can use to display the plugin's parameters as a tree.
+//!
+//! This module provides the [`ParamModulePath`] type to parse and validate those paths, the
+//! [`module!`](crate::module) macro for plugins to declare them, and, for hosts, the
+//! [`ParamModuleTree`] type to organize a list of parameters by module.
+
+use super::*;
+use clap_sys::string_sizes::CLAP_PATH_SIZE;
+
+/// The separator between the segments of a module path.
+pub const MODULE_SEPARATOR: char = '/';
+
+/// A validated parameter module path, such as `Filter/Envelope`.
+///
+/// A module path is made of non-empty segments, separated by [`MODULE_SEPARATOR`]. The empty path
+/// designates the root module, i.e. parameters that aren't part of any module.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct ParamModulePath {
+    path: String,
+}
+
+impl ParamModulePath {
+    /// The maximum length of a module path, in bytes.
+    ///
+    /// This is the size of the `module` buffer in `clap_param_info`, minus the NUL terminator.
+    pub const MAX_LEN: usize = CLAP_PATH_SIZE - 1;
+
+    /// The maximum depth of a module path, i.e. its maximum number of segments.
+    ///
+    /// The CLAP specification doesn't limit the depth of module paths, but it is implicitly bound
+    /// by [`MAX_LEN`](Self::MAX_LEN), as each segment needs at least one byte, plus a separator.
+    pub const MAX_DEPTH: usize = (Self::MAX_LEN + 1) / 2;
+
+    /// Returns the root module path, which is empty.
+    #[inline]
+    pub const fn root() -> Self {
+        Self {
+            path: String::new(),
+        }
+    }
+
+    /// Parses and validates the given module path.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ParamModulePathError`] if the path is longer than
+    /// [`MAX_LEN`](Self::MAX_LEN) bytes, if it contains a NUL byte, or if any of its segments
+    /// is empty (e.g. `Filter//Envelope`, or `Filter/`).
+    pub fn parse(path: &str) -> Result<Self, ParamModulePathError> {
+        if path.len() > Self::MAX_LEN {
+            return Err(ParamModulePathError::TooLong);
+        }
+
+        if path.contains('\0') {
+            return Err(ParamModulePathError::ContainsNul);
+        }
+
+        if !path.is_empty() && path.split(MODULE_SEPARATOR).any(str::is_empty) {
+            return Err(ParamModulePathError::EmptySegment);
+        }
+
+        Ok(Self { path: path.into() })
+    }
+
+    /// Reads a module path from the raw bytes of a [`ParamInfo::module`], as provided by a
+    /// plugin.
+    ///
+    /// Unlike [`parse`](Self::parse), this never fails: invalid UTF-8 sequences are replaced,
+    /// empty segments are skipped, and the path is truncated to [`MAX_LEN`](Self::MAX_LEN) bytes
+    /// (on a character boundary) if needed.
+    pub fn from_bytes_lossy(module: &[u8]) -> Self {
+        let module = String::from_utf8_lossy(module);
+        let mut path = String::with_capacity(module.len().min(Self::MAX_LEN));
+
+        for segment in module
+            .split(MODULE_SEPARATOR)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim_end_matches('\0'))
+        {
+            if !path.is_empty() {
+                path.push(MODULE_SEPARATOR);
+            }
+            path.push_str(segment);
+        }
+
+        if path.len() > Self::MAX_LEN {
+            let mut end = Self::MAX_LEN;
+            while !path.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            path.truncate(end);
+            path.truncate(path.trim_end_matches(MODULE_SEPARATOR).len());
+        }
+
+        Self { path }
+    }
+
+    /// Returns this module path as a string, e.g. `Filter/Envelope`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns `true` if this is the root module path.
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Returns the segments of this module path, e.g. `["Filter", "Envelope"]`.
+    ///
+    /// The root module path has no segments.
+    #[inline]
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &str> + '_ {
+        self.path
+            .split(MODULE_SEPARATOR)
+            .filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns the depth of this module path, i.e. its number of segments.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.segments().count()
+    }
+
+    /// Returns the parent of this module path, or `None` if this is the root module path.
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_root() {
+            return None;
+        }
+
+        let parent = match self.path.rfind(MODULE_SEPARATOR) {
+            Some(index) => &self.path[..index],
+            None => "",
+        };
+
+        Some(Self {
+            path: parent.into(),
+        })
+    }
+
+    /// Returns a sub-module path of this one, with the given segment appended.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ParamModulePathError`] if the segment is empty, contains a separator or a
+    /// NUL byte, or if the resulting path would be too long.
+    pub fn join(&self, segment: &str) -> Result<Self, ParamModulePathError> {
+        if segment.is_empty() || segment.contains(MODULE_SEPARATOR) {
+            return Err(ParamModulePathError::EmptySegment);
+        }
+
+        if self.is_root() {
+            return Self::parse(segment);
+        }
+
+        Self::parse(&format!("{}{MODULE_SEPARATOR}{segment}", self.path))
+    }
+
+    /// Returns the given parameter name, prefixed with this module path for display, e.g.
+    /// `Filter / Envelope / Attack`.
+    ///
+    /// This is useful to tell apart parameters that share the same name in different modules.
+    pub fn qualify(&self, name: &str) -> String {
+        let mut qualified = String::with_capacity(self.path.len() * 2 + name.len());
+
+        for segment in self.segments() {
+            qualified.push_str(segment);
+            qualified.push_str(" / ");
+        }
+
+        qualified.push_str(name);
+        qualified
+    }
+}
+
+impl Display for ParamModulePath {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl AsRef<str> for ParamModulePath {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Errors that can occur when parsing or building a [`ParamModulePath`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParamModulePathError {
+    /// The module path is longer than [`ParamModulePath::MAX_LEN`] bytes.
+    TooLong,
+    /// The module path contains a NUL byte.
+    ContainsNul,
+    /// One of the module path's segments is empty.
+    EmptySegment,
+}
+
+impl Display for ParamModulePathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLong => write!(
+                f,
+                "Module path is longer than {} bytes",
+                ParamModulePath::MAX_LEN
+            ),
+            Self::ContainsNul => f.write_str("Module path contains a NUL byte"),
+            Self::EmptySegment => f.write_str("Module path contains an empty segment"),
+        }
+    }
+}
+
+impl Error for ParamModulePathError {}
+
+#[doc(hidden)]
+pub const fn validate_module_segments(segments: &[&str]) {
+    let mut total_len = 0;
+    let mut i = 0;
+
+    while i < segments.len() {
+        let segment = segments[i].as_bytes();
+        assert!(
+            !segment.is_empty(),
+            "Module path segments must not be empty"
+        );
+
+        let mut j = 0;
+        while j < segment.len() {
+            assert!(
+                segment[j] != b'/',
+                "Module path segments must not contain '/'"
+            );
+            assert!(
+                segment[j] != 0,
+                "Module path segments must not contain NUL bytes"
+            );
+            j += 1;
+        }
+
+        total_len += segment.len() + if i > 0 { 1 } else { 0 };
+        i += 1;
+    }
+
+    assert!(
+        total_len <= ParamModulePath::MAX_LEN,
+        "Module path is too long"
+    );
+}
+
+/// Builds a parameter module path from the given segments, at compile time.
+///
+/// The segments must be string literals. They are validated at compile time: they must not be
+/// empty nor contain a `/` or a NUL byte, and the resulting path must fit in
+/// [`ParamModulePath::MAX_LEN`](crate::params::module::ParamModulePath::MAX_LEN) bytes.
+///
+/// This expands to a `&'static str`.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::module;
+///
+/// const ATTACK_MODULE: &str = module!("Filter", "Envelope");
+/// assert_eq!(ATTACK_MODULE, "Filter/Envelope");
+/// ```
+#[macro_export]
+macro_rules! module {
+    ($first:literal $(, $rest:literal)* $(,)?) => {{
+        const _: () = $crate::params::module::validate_module_segments(&[$first $(, $rest)*]);
+        concat!($first $(, "/", $rest)*)
+    }};
+}
+
+/// A tree of parameters, organized by module.
+///
+/// Each node of the tree is a module, holding the parameters that are directly in that module,
+/// as well as its sub-modules. Both parameters and sub-modules are kept in the order they were
+/// inserted in, which is typically the order the plugin declared its parameters in.
+///
+/// The parameters can be of any type `T`. For hosts, [`from_cache`](Self::from_cache) builds a
+/// tree of references to the parameters of a [`ParamValueCache`].
+#[derive(Clone, Debug)]
+pub struct ParamModuleTree<T> {
+    root: ParamModuleNode<T>,
+}
+
+impl<T> ParamModuleTree<T> {
+    /// Creates a new, empty tree.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            root: ParamModuleNode::new(String::new(), ParamModulePath::root()),
+        }
+    }
+
+    /// Inserts a parameter in the module with the given path, creating that module (and its
+    /// parents) if needed.
+    pub fn insert(&mut self, module: &ParamModulePath, param: T) {
+        let mut node = &mut self.root;
+        let mut path = String::new();
+
+        for segment in module.segments() {
+            if !path.is_empty() {
+                path.push(MODULE_SEPARATOR);
+            }
+            path.push_str(segment);
+
+            let index = match node.children.iter().position(|c| c.name == segment) {
+                Some(index) => index,
+                None => {
+                    let path = ParamModulePath { path: path.clone() };
+                    node.children
+                        .push(ParamModuleNode::new(segment.into(), path));
+                    node.children.len() - 1
+                }
+            };
+
+            node = &mut node.children[index];
+        }
+
+        node.params.push(param);
+    }
+
+    /// Returns the root module of this tree.
+    #[inline]
+    pub fn root(&self) -> &ParamModuleNode<T> {
+        &self.root
+    }
+
+    /// Returns the module with the given path, if it exists in this tree.
+    pub fn find(&self, module: &ParamModulePath) -> Option<&ParamModuleNode<T>> {
+        let mut node = &self.root;
+
+        for segment in module.segments() {
+            node = node.children.iter().find(|c| c.name == segment)?;
+        }
+
+        Some(node)
+    }
+
+    /// Returns all the parameters in this tree, along with the path of their module, in
+    /// depth-first order.
+    pub fn iter(&self) -> impl Iterator<Item = (&ParamModulePath, &T)> {
+        let mut stack = vec![&self.root];
+        let mut current: Option<(&ParamModuleNode<T>, usize)> = None;
+
+        core::iter::from_fn(move || loop {
+            if let Some((node, index)) = &mut current {
+                if let Some(param) = node.params.get(*index) {
+                    *index += 1;
+                    return Some((&node.path, param));
+                }
+            }
+
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            current = Some((node, 0));
+        })
+    }
+
+    /// Returns the display name of each parameter of this tree, in depth-first order.
+    ///
+    /// Parameters are displayed using their own name, as returned by `name`, unless another
+    /// parameter in a different module has the same name. In that case, both names are
+    /// [qualified](ParamModulePath::qualify) with their module path, so that the user can tell
+    /// them apart.
+    pub fn display_names(&self, name: impl Fn(&T) -> &str) -> Vec<(&T, String)> {
+        let params: Vec<_> = self.iter().collect();
+
+        params
+            .iter()
+            .map(|(module, param)| {
+                let param_name = name(param);
+                let is_ambiguous = params.iter().any(|(other_module, other)| {
+                    other_module != module && name(other) == param_name
+                });
+
+                let display_name = if is_ambiguous {
+                    module.qualify(param_name)
+                } else {
+                    param_name.into()
+                };
+
+                (*param, display_name)
+            })
+            .collect()
+    }
+}
+
+impl<T> Default for ParamModuleTree<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(ParamModulePath, T)> for ParamModuleTree<T> {
+    fn from_iter<I: IntoIterator<Item = (ParamModulePath, T)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+
+        for (module, param) in iter {
+            tree.insert(&module, param);
+        }
+
+        tree
+    }
+}
+
+#[cfg(feature = "clack-host")]
+impl<'a> ParamModuleTree<&'a CachedParam> {
+    /// Builds a tree of all the parameters in the given cache.
+    ///
+    /// Module paths are read leniently, using [`ParamModulePath::from_bytes_lossy`].
+    pub fn from_cache(cache: &'a ParamValueCache) -> Self {
+        cache
+            .params()
+            .iter()
+            .map(|p| (ParamModulePath::from_bytes_lossy(p.module.as_bytes()), p))
+            .collect()
+    }
+}
+
+/// A module in a [`ParamModuleTree`].
+#[derive(Clone, Debug)]
+pub struct ParamModuleNode<T> {
+    name: String,
+    path: ParamModulePath,
+    children: Vec<ParamModuleNode<T>>,
+    params: Vec<T>,
+}
+
+impl<T> ParamModuleNode<T> {
+    fn new(name: String, path: ParamModulePath) -> Self {
+        Self {
+            name,
+            path,
+            children: Vec::new(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Returns the name of this module, i.e. the last segment of its path.
+    ///
+    /// This is empty for the root module.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the full path of this module.
+    #[inline]
+    pub fn path(&self) -> &ParamModulePath {
+        &self.path
+    }
+
+    /// Returns the sub-modules of this module.
+    #[inline]
+    pub fn children(&self) -> &[ParamModuleNode<T>] {
+        &self.children
+    }
+
+    /// Returns the parameters that are directly in this module.
+    #[inline]
+    pub fn params(&self) -> &[T] {
+        &self.params
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_valid_paths() {
+        let path = ParamModulePath::parse("Filter/Envelope/Attack").unwrap();
+        assert_eq!(
+            path.segments().collect::<Vec<_>>(),
+            ["Filter", "Envelope", "Attack"]
+        );
+        assert_eq!(path.depth(), 3);
+        assert_eq!(path.parent().unwrap().as_str(), "Filter/Envelope");
+        assert_eq!(
+            path.join("Curve").unwrap().as_str(),
+            "Filter/Envelope/Attack/Curve"
+        );
+
+        let root = ParamModulePath::parse("").unwrap();
+        assert!(root.is_root());
+        assert_eq!(root.depth(), 0);
+        assert_eq!(root.parent(), None);
+        assert_eq!(root.join("Gain").unwrap().as_str(), "Gain");
+        assert_eq!(ParamModulePath::parse("Gain").unwrap().parent(), Some(root));
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        use ParamModulePathError::*;
+
+        assert_eq!(
+            ParamModulePath::parse("Filter//Envelope"),
+            Err(EmptySegment)
+        );
+        assert_eq!(ParamModulePath::parse("/Filter"), Err(EmptySegment));
+        assert_eq!(ParamModulePath::parse("Filter/"), Err(EmptySegment));
+        assert_eq!(ParamModulePath::parse("Fil\0ter"), Err(ContainsNul));
+        assert_eq!(ParamModulePath::root().join(""), Err(EmptySegment));
+        assert_eq!(ParamModulePath::root().join("a/b"), Err(EmptySegment));
+    }
+
+    #[test]
+    fn lossy_paths_skip_empty_segments() {
+        let path = ParamModulePath::from_bytes_lossy(b"/Filter//Envelope/");
+        assert_eq!(path.as_str(), "Filter/Envelope");
+        assert_eq!(path, ParamModulePath::parse("Filter/Envelope").unwrap());
+
+        assert!(ParamModulePath::from_bytes_lossy(b"//").is_root());
+    }
+
+    #[test]
+    fn length_limit_is_in_bytes() {
+        // 'é' is 2 bytes long in UTF-8.
+        let max = "é".repeat(ParamModulePath::MAX_LEN / 2) + "a";
+        assert_eq!(max.len(), ParamModulePath::MAX_LEN);
+        assert!(ParamModulePath::parse(&max).is_ok());
+
+        let too_long = "é".repeat(ParamModulePath::MAX_LEN / 2 + 1);
+        assert_eq!(too_long.chars().count(), 512);
+        assert_eq!(
+            ParamModulePath::parse(&too_long),
+            Err(ParamModulePathError::TooLong)
+        );
+
+        // Lossy parsing truncates to the last full character that fits.
+        let truncated = ParamModulePath::from_bytes_lossy(too_long.as_bytes());
+        assert_eq!(truncated.as_str().len(), ParamModulePath::MAX_LEN - 1);
+        assert!(truncated.as_str().chars().all(|c| c == 'é'));
+
+        let deepest = vec!["a"; ParamModulePath::MAX_DEPTH].join("/");
+        assert_eq!(
+            ParamModulePath::parse(&deepest).unwrap().depth(),
+            ParamModulePath::MAX_DEPTH
+        );
+    }
+
+    #[test]
+    fn module_macro_builds_paths() {
+        const PATH: &str = crate::module!("Filter", "Envelope", "Attack");
+        assert_eq!(PATH, "Filter/Envelope/Attack");
+        assert_eq!(crate::module!("Gain"), "Gain");
+        assert!(ParamModulePath::parse(PATH).is_ok());
+    }
+
+    fn tree() -> ParamModuleTree<(&'static str, u32)> {
+        [
+            ("", ("Gain", 0)),
+            ("Filter", ("Cutoff", 1)),
+            ("Filter/Envelope", ("Attack", 2)),
+            ("Amp/Envelope", ("Attack", 3)),
+            ("Filter", ("Resonance", 4)),
+        ]
+        .into_iter()
+        .map(|(module, param)| (ParamModulePath::parse(module).unwrap(), param))
+        .collect()
+    }
+
+    #[test]
+    fn params_are_grouped_by_module() {
+        let tree = tree();
+
+        let root = tree.root();
+        assert_eq!(root.params(), [("Gain", 0)]);
+        assert_eq!(
+            root.children().iter().map(|c| c.name()).collect::<Vec<_>>(),
+            ["Filter", "Amp"]
+        );
+
+        let filter = tree
+            .find(&ParamModulePath::parse("Filter").unwrap())
+            .unwrap();
+        assert_eq!(filter.params(), [("Cutoff", 1), ("Resonance", 4)]);
+        assert_eq!(filter.children()[0].path().as_str(), "Filter/Envelope");
+
+        assert!(tree.find(&ParamModulePath::parse("Osc").unwrap()).is_none());
+
+        let ids: Vec<_> = tree.iter().map(|(_, (_, id))| *id).collect();
+        assert_eq!(ids, [0, 1, 4, 2, 3]);
+    }
+
+    #[test]
+    fn shared_names_are_qualified() {
+        let tree = tree();
+
+        let names: Vec<_> = tree
+            .display_names(|(name, _)| name)
+            .into_iter()
+            .map(|((_, id), name)| (*id, name))
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                (0, "Gain".into()),
+                (1, "Cutoff".into()),
+                (4, "Resonance".into()),
+                (2, "Filter / Envelope / Attack".into()),
+                (3, "Amp / Envelope / Attack".into()),
+            ]
+        );
+    }
+}
@@ -1,10 +1,15 @@
 use super::*;
 use crate::utils::{slice_from_external_parts_mut, write_to_array_buf};
 use clack_common::events::io::{InputEvents, OutputEvents};
+#[cfg(debug_assertions)]
+use clack_common::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
 use clack_plugin::extensions::prelude::*;
 use clap_sys::events::{clap_input_events, clap_output_events};
 use clap_sys::ext::log::CLAP_LOG_ERROR;
+#[cfg(debug_assertions)]
+use clap_sys::ext::log::CLAP_LOG_WARNING;
 use clap_sys::id::clap_id;
+use std::fmt::Write;
 use std::mem::MaybeUninit;
 
 pub struct ParamInfoWriter<'a> {
@@ -25,8 +30,23 @@ impl ParamInfoWriter<'_> {
         }
     }
 
+    /// Writes the given parameter info.
+    ///
+    /// In debug builds, any inconsistency found by [`ParamInfo::validate`] is reported to the
+    /// [fallback logger](clack_common::utils::fallback_log).
     #[inline]
     pub fn set(&mut self, info: &ParamInfo) {
+        #[cfg(debug_assertions)]
+        for warning in info.validate() {
+            fallback_log(&LogRecord {
+                origin: LogOrigin::Plugin,
+                severity: CLAP_LOG_WARNING,
+                plugin_id: None,
+                callback: "clap_plugin_params.get_info",
+                message: &warning,
+            });
+        }
+
         let buf = self.buf.as_mut_ptr();
 
         // SAFETY: all pointers come from `inner`, which is valid for writes and well-aligned
@@ -52,7 +72,7 @@ pub struct ParamDisplayWriter<'a> {
 
 impl<'a> ParamDisplayWriter<'a> {
     #[inline]
-    fn new(buffer: &'a mut [u8]) -> Self {
+    pub(crate) fn new(buffer: &'a mut [u8]) -> Self {
         Self {
             cursor_position: 0,
             buffer,
@@ -70,7 +90,7 @@ impl<'a> ParamDisplayWriter<'a> {
         self.buffer.len().saturating_sub(self.cursor_position + 1)
     }
 
-    fn finish(self) -> bool {
+    pub(crate) fn finish(self) -> bool {
         if self.cursor_position > 0 {
             self.buffer[self.cursor_position] = 0;
         }
@@ -93,30 +113,80 @@ impl core::fmt::Write for ParamDisplayWriter<'_> {
     }
 }
 
+/// The main-thread side of the plugin's implementation of the `params` extension.
+///
+/// Only [`count`](Self::count), [`get_info`](Self::get_info) and [`get_value`](Self::get_value)
+/// must be implemented: the other methods have plain numeric defaults. Plugins using a
+/// [`ParamSet`](set::ParamSet) don't need to implement this trait at all, see
+/// [`ParamSetProvider`](set::ParamSetProvider).
 pub trait PluginMainThreadParams {
+    /// Returns the number of parameters.
     fn count(&mut self) -> u32;
+
+    /// Writes the info of the parameter at the given index into `info`.
     fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter);
+
+    /// Returns the current value of the given parameter, or `None` if it doesn't exist.
     fn get_value(&mut self, param_id: ClapId) -> Option<f64>;
+
+    /// Writes the text representation of the given value of the given parameter.
+    ///
+    /// By default, the value is written as a plain number.
     fn value_to_text(
         &mut self,
         param_id: ClapId,
         value: f64,
         writer: &mut ParamDisplayWriter,
-    ) -> core::fmt::Result;
-    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64>;
+    ) -> core::fmt::Result {
+        let _ = param_id;
+        write!(writer, "{value}")
+    }
+
+    /// Parses the given text into a value of the given parameter.
+    ///
+    /// By default, the text is parsed as a plain number, ignoring surrounding whitespace.
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        let _ = param_id;
+        text.to_str().ok()?.trim().parse().ok()
+    }
+
+    /// Handles the given parameter changes while the plugin is inactive, and writes any
+    /// parameter changes it produces into `output_parameter_changes`.
+    ///
+    /// **The default implementation drops all the given parameter changes.** Plugins whose
+    /// parameters can be changed by the host must override it, otherwise any change the host
+    /// makes while the plugin is inactive is lost. In debug builds, dropped changes are reported
+    /// to the [fallback logger](clack_common::utils::fallback_log).
     fn flush(
         &mut self,
         input_parameter_changes: &InputEvents,
         output_parameter_changes: &mut OutputEvents,
-    );
+    ) {
+        let _ = output_parameter_changes;
+        report_dropped_changes(input_parameter_changes, "PluginMainThreadParams::flush");
+    }
 }
 
+/// The audio-thread side of the plugin's implementation of the `params` extension.
+///
+/// Plugins using a [`ParamSet`](set::ParamSet) don't need to implement this trait, see
+/// [`ParamSetProvider`](set::ParamSetProvider).
 pub trait PluginAudioProcessorParams {
+    /// Handles the given parameter changes while the plugin is active but not processing, and
+    /// writes any parameter changes it produces into `output_parameter_changes`.
+    ///
+    /// **The default implementation drops all the given parameter changes.** Plugins whose
+    /// parameters can be changed by the host must override it, otherwise any change the host
+    /// makes while the plugin isn't processing is lost. In debug builds, dropped changes are
+    /// reported to the [fallback logger](clack_common::utils::fallback_log).
     fn flush(
         &mut self,
         input_parameter_changes: &InputEvents,
         output_parameter_changes: &mut OutputEvents,
-    );
+    ) {
+        let _ = output_parameter_changes;
+        report_dropped_changes(input_parameter_changes, "PluginAudioProcessorParams::flush");
+    }
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -124,7 +194,10 @@ unsafe extern "C" fn count<P: Plugin>(plugin: *const clap_plugin) -> u32
 where
     for<'a> P::MainThread<'a>: PluginMainThreadParams,
 {
-    PluginWrapper::<P>::handle(plugin, |p| Ok(p.main_thread().as_mut().count())).unwrap_or(0)
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_params.count", |p| {
+        Ok(p.main_thread().as_mut().count())
+    })
+    .unwrap_or(0)
 }
 
 #[allow(clippy::missing_safety_doc)]
@@ -137,7 +210,7 @@ where
     for<'a> P::MainThread<'a>: PluginMainThreadParams,
 {
     let mut info = ParamInfoWriter::new(value);
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_params.get_info", |p| {
         p.main_thread().as_mut().get_info(param_index, &mut info);
         Ok(())
     })
@@ -154,7 +227,7 @@ unsafe extern "C" fn get_value<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginMainThreadParams,
 {
-    let val = PluginWrapper::<P>::handle(plugin, |p| {
+    let val = PluginWrapper::<P>::handle_named(plugin, "clap_plugin_params.get_value", |p| {
         let param_id = ClapId::from_raw(param_id)
             .ok_or(PluginWrapperError::InvalidParameter("Invalid param_id"))?;
 
@@ -184,7 +257,7 @@ where
 {
     let buf = slice_from_external_parts_mut(display as *mut u8, size as usize);
     let mut writer = ParamDisplayWriter::new(buf);
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_params.value_to_text", |p| {
         let param_id = ClapId::from_raw(param_id)
             .ok_or(PluginWrapperError::InvalidParameter("Invalid param_id"))?;
 
@@ -207,13 +280,14 @@ unsafe extern "C" fn text_to_value<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginMainThreadParams,
 {
-    let result = PluginWrapper::<P>::handle(plugin, |p| {
-        let param_id = ClapId::from_raw(param_id)
-            .ok_or(PluginWrapperError::InvalidParameter("Invalid param_id"))?;
+    let result =
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin_params.text_to_value", |p| {
+            let param_id = ClapId::from_raw(param_id)
+                .ok_or(PluginWrapperError::InvalidParameter("Invalid param_id"))?;
 
-        let display = CStr::from_ptr(display);
-        Ok(p.main_thread().as_mut().text_to_value(param_id, display))
-    });
+            let display = CStr::from_ptr(display);
+            Ok(p.main_thread().as_mut().text_to_value(param_id, display))
+        });
 
     match result {
         Some(Some(val)) => {
@@ -224,6 +298,28 @@ where
     }
 }
 
+/// Reports the parameter changes dropped by a default `flush` implementation to the fallback
+/// logger. This does nothing in release builds.
+#[inline]
+fn report_dropped_changes(input_parameter_changes: &InputEvents, callback: &'static str) {
+    #[cfg(debug_assertions)]
+    if !input_parameter_changes.is_empty() {
+        fallback_log(&LogRecord {
+            origin: LogOrigin::Plugin,
+            severity: CLAP_LOG_WARNING,
+            plugin_id: None,
+            callback,
+            message: &format!(
+                "Dropped {} parameter event(s): the default flush implementation ignores them",
+                input_parameter_changes.len()
+            ),
+        });
+    }
+
+    #[cfg(not(debug_assertions))]
+    let _ = (input_parameter_changes, callback);
+}
+
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn flush<P: Plugin>(
     plugin: *const clap_plugin,
@@ -237,7 +333,9 @@ unsafe extern "C" fn flush<P: Plugin>(
     let output_parameter_changes =
         OutputEvents::from_raw_mut(&mut *(output_parameter_changes as *mut _));
 
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_params.flush", |p| {
+        // While the plugin is active, flush is called on the audio thread, and must reach the
+        // audio processor's state, even if the plugin is started and not currently processing.
         if let Ok(mut audio) = p.audio_processor() {
             audio
                 .as_mut()
@@ -293,3 +391,79 @@ impl HostParams {
         }
     }
 }
+
+impl DynInterface for dyn PluginMainThreadParams {
+    type Target<'a> = dyn PluginMainThreadParams + 'a;
+}
+
+impl DynInterface for dyn PluginAudioProcessorParams {
+    type Target<'a> = dyn PluginAudioProcessorParams + 'a;
+}
+
+/// Forwards to the [`PluginMainThreadParams`] implementation provided by the dynamic plugin, if
+/// any.
+impl PluginMainThreadParams for DynPluginMainThread<'_> {
+    #[inline]
+    fn count(&mut self) -> u32 {
+        self.interface::<dyn PluginMainThreadParams>()
+            .map_or(0, |p| p.count())
+    }
+
+    #[inline]
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        if let Some(params) = self.interface::<dyn PluginMainThreadParams>() {
+            params.get_info(param_index, info)
+        }
+    }
+
+    #[inline]
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.interface::<dyn PluginMainThreadParams>()?
+            .get_value(param_id)
+    }
+
+    #[inline]
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> core::fmt::Result {
+        match self.interface::<dyn PluginMainThreadParams>() {
+            Some(params) => params.value_to_text(param_id, value, writer),
+            None => Err(core::fmt::Error),
+        }
+    }
+
+    #[inline]
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.interface::<dyn PluginMainThreadParams>()?
+            .text_to_value(param_id, text)
+    }
+
+    #[inline]
+    fn flush(
+        &mut self,
+        input_parameter_changes: &InputEvents,
+        output_parameter_changes: &mut OutputEvents,
+    ) {
+        if let Some(params) = self.interface::<dyn PluginMainThreadParams>() {
+            params.flush(input_parameter_changes, output_parameter_changes)
+        }
+    }
+}
+
+/// Forwards to the [`PluginAudioProcessorParams`] implementation provided by the dynamic plugin,
+/// if any.
+impl PluginAudioProcessorParams for DynPluginAudioProcessor<'_> {
+    #[inline]
+    fn flush(
+        &mut self,
+        input_parameter_changes: &InputEvents,
+        output_parameter_changes: &mut OutputEvents,
+    ) {
+        if let Some(params) = self.interface::<dyn PluginAudioProcessorParams>() {
+            params.flush(input_parameter_changes, output_parameter_changes)
+        }
+    }
+}
@@ -0,0 +1,788 @@
+//! A simple, ready-to-use parameter set for plugins.
+//!
+//! See the [`ParamSet`] type for more information.
+
+use super::*;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use clack_common::events::spaces::CoreEventSpace;
+use clack_common::events::UnknownEvent;
+use clack_plugin::host::HostMainThreadHandle;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The definition of a single parameter in a [`ParamSet`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParamDef {
+    name: String,
+    module: String,
+    flags: ParamInfoFlags,
+    min_value: f64,
+    max_value: f64,
+    default_value: f64,
+    labels: Vec<String>,
+}
+
+impl ParamDef {
+    /// Defines a new, continuous and automatable parameter, with the given name, range and
+    /// default value.
+    pub fn new(name: &str, min_value: f64, max_value: f64, default_value: f64) -> Self {
+        Self {
+            name: name.into(),
+            module: String::new(),
+            flags: ParamInfoFlags::IS_AUTOMATABLE,
+            min_value,
+            max_value,
+            default_value,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Defines a new, automatable enumeration parameter, with the given name and one label per
+    /// value.
+    ///
+    /// The parameter is stepped, and ranges from `0` to the number of labels minus one. Its
+    /// default value is `0`, i.e. the first label.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_extensions::params::set::ParamDef;
+    ///
+    /// let filter_type = ParamDef::enumeration("Filter Type", &["LP", "HP", "BP"]);
+    ///
+    /// assert_eq!(filter_type.max_value(), 2.0);
+    /// assert_eq!(filter_type.labels(), ["LP", "HP", "BP"]);
+    /// ```
+    pub fn enumeration(name: &str, labels: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            module: String::new(),
+            flags: ParamInfoFlags::IS_AUTOMATABLE
+                | ParamInfoFlags::IS_STEPPED
+                | ParamInfoFlags::IS_ENUM,
+            min_value: 0.0,
+            max_value: labels.len().saturating_sub(1) as f64,
+            default_value: 0.0,
+            labels: labels.iter().map(|&l| l.into()).collect(),
+        }
+    }
+
+    /// Defines a new bypass parameter, with the given name.
+    ///
+    /// The parameter is stepped, ranges from `0` (not bypassed) to `1` (bypassed), and is
+    /// flagged with [`ParamInfoFlags::IS_BYPASS`], which allows hosts to wire it to their own
+    /// bypass button. Its default value is `0`.
+    pub fn bypass(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            module: String::new(),
+            flags: ParamInfoFlags::IS_AUTOMATABLE
+                | ParamInfoFlags::IS_STEPPED
+                | ParamInfoFlags::IS_BYPASS,
+            min_value: 0.0,
+            max_value: 1.0,
+            default_value: 0.0,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Sets the module path of this parameter, e.g. `Oscillators/Osc 1`.
+    #[inline]
+    pub fn with_module(mut self, module: &str) -> Self {
+        self.module = module.into();
+        self
+    }
+
+    /// Replaces the flags of this parameter with the given ones.
+    #[inline]
+    pub fn with_flags(mut self, flags: ParamInfoFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the default value of this parameter.
+    #[inline]
+    pub fn with_default_value(mut self, default_value: f64) -> Self {
+        self.default_value = default_value;
+        self
+    }
+
+    /// Returns the name of this parameter.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the flags of this parameter.
+    #[inline]
+    pub fn flags(&self) -> ParamInfoFlags {
+        self.flags
+    }
+
+    /// Returns the minimum value of this parameter.
+    #[inline]
+    pub fn min_value(&self) -> f64 {
+        self.min_value
+    }
+
+    /// Returns the maximum value of this parameter.
+    #[inline]
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    /// Returns the default value of this parameter.
+    #[inline]
+    pub fn default_value(&self) -> f64 {
+        self.default_value
+    }
+
+    /// Returns the labels of this parameter, if it is an enumeration. Otherwise, this returns an
+    /// empty slice.
+    #[inline]
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Clamps the given value to this parameter's range, and rounds it if it is stepped.
+    fn normalize(&self, value: f64) -> f64 {
+        let value = value.clamp(self.min_value, self.max_value);
+
+        if self.flags.contains(ParamInfoFlags::IS_STEPPED) {
+            value.round()
+        } else {
+            value
+        }
+    }
+
+    fn label(&self, value: f64) -> Option<&str> {
+        if value < 0.0 {
+            return None;
+        }
+
+        self.labels.get(value.round() as usize).map(|l| l.as_str())
+    }
+}
+
+struct Param {
+    id: ClapId,
+    def: ParamDef,
+    value: AtomicU64,
+}
+
+impl Param {
+    #[inline]
+    fn load_value(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::Relaxed))
+    }
+}
+
+/// Gives access to a [`ParamSet`], to implement the `params` extension entirely with it.
+///
+/// Implementing this trait for a plugin's main thread type implements
+/// [`PluginMainThreadParams`] for it, with every method forwarding to the given [`ParamSet`].
+/// Likewise, implementing it for the plugin's audio processor type implements
+/// [`PluginAudioProcessorParams`] for it, applying the flushed parameter changes to the set.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::params::set::{ParamDef, ParamSet, ParamSetProvider};
+/// use clack_common::utils::ClapId;
+///
+/// struct MyPluginMainThread<'a> {
+///     params: &'a ParamSet,
+/// }
+///
+/// // This is all that's needed for MyPluginMainThread to implement PluginMainThreadParams.
+/// impl ParamSetProvider for MyPluginMainThread<'_> {
+///     fn param_set(&self) -> &ParamSet {
+///         self.params
+///     }
+/// }
+/// ```
+pub trait ParamSetProvider {
+    /// Returns the parameter set.
+    fn param_set(&self) -> &ParamSet;
+}
+
+impl<T: ParamSetProvider> PluginMainThreadParams for T {
+    #[inline]
+    fn count(&mut self) -> u32 {
+        self.param_set().count()
+    }
+
+    #[inline]
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.param_set().get_info(param_index, info)
+    }
+
+    #[inline]
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.param_set().value(param_id)
+    }
+
+    #[inline]
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> core::fmt::Result {
+        self.param_set().value_to_text(param_id, value, writer)
+    }
+
+    #[inline]
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.param_set().text_to_value(param_id, text)
+    }
+
+    #[inline]
+    fn flush(&mut self, input_parameter_changes: &InputEvents, _output: &mut OutputEvents) {
+        self.param_set().flush(input_parameter_changes)
+    }
+}
+
+impl<T: ParamSetProvider> PluginAudioProcessorParams for T {
+    #[inline]
+    fn flush(&mut self, input_parameter_changes: &InputEvents, _output: &mut OutputEvents) {
+        self.param_set().flush(input_parameter_changes)
+    }
+}
+
+/// A set of parameters, and their current values.
+///
+/// This type implements all the logic of the `params` extension for simple plugins: plugins can
+/// implement [`ParamSetProvider`] to have their [`PluginMainThreadParams`] and
+/// [`PluginAudioProcessorParams`] implementations forwarded to it, and read the parameter values
+/// from any thread.
+///
+/// Values are always clamped to their parameter's range, and rounded if the parameter is stepped.
+///
+/// Enumeration parameters (see [`ParamDef::enumeration`]) are displayed using their labels, and
+/// parsed from either a label or the string representation of their integer value.
+///
+/// # Dynamic parameter lists
+///
+/// Some plugins, such as samplers, only know their parameters once e.g. a file is loaded. The
+/// parameter list can be changed at runtime using [`add_param`](Self::add_param),
+/// [`remove_param`](Self::remove_param) and [`replace_params`](Self::replace_params), which also
+/// request a [`ParamRescanFlags::ALL`] rescan from the host. As required by the CLAP
+/// specification for such rescans, they must only be called while the plugin is deactivated.
+///
+/// Parameters keep their ID and value for as long as they are part of the set, no matter how
+/// many other parameters are added or removed around them. Newly added parameters are reported
+/// after the existing ones.
+///
+/// The parameter list is protected by a lock, which is only ever written to by these methods.
+/// Since the plugin is deactivated when they are called, the audio thread never has to wait on
+/// it.
+///
+/// # Example
+///
+/// ```
+/// use clack_extensions::params::set::{ParamDef, ParamSet};
+/// use clack_common::utils::ClapId;
+/// use std::ffi::CStr;
+///
+/// const GAIN: ClapId = ClapId::new(0);
+/// const FILTER_TYPE: ClapId = ClapId::new(1);
+///
+/// let params = ParamSet::new()
+///     .with_param(GAIN, ParamDef::new("Gain", 0.0, 1.0, 1.0))
+///     .with_param(FILTER_TYPE, ParamDef::enumeration("Filter Type", &["LP", "HP", "BP"]));
+///
+/// assert_eq!(params.value(GAIN), Some(1.0));
+///
+/// params.set_value(FILTER_TYPE, 1.8);
+/// assert_eq!(params.value(FILTER_TYPE), Some(2.0));
+/// let label = CStr::from_bytes_with_nul(b"HP\0").unwrap();
+/// assert_eq!(params.text_to_value(FILTER_TYPE, label), Some(1.0));
+/// ```
+#[derive(Default)]
+pub struct ParamSet {
+    list: RwLock<ParamList>,
+}
+
+#[derive(Default)]
+struct ParamList {
+    params: Vec<Param>,
+    bypass_index: Option<usize>,
+}
+
+impl ParamList {
+    fn find(&self, id: ClapId) -> Option<&Param> {
+        self.params.iter().find(|p| p.id == id)
+    }
+
+    fn push(&mut self, id: ClapId, def: ParamDef, value: f64) -> Result<(), ParamSetError> {
+        if self.find(id).is_some() {
+            return Err(ParamSetError::DuplicateId(id));
+        }
+
+        if def.flags.contains(ParamInfoFlags::IS_BYPASS) {
+            if self.bypass_index.is_some() {
+                return Err(ParamSetError::SecondBypass);
+            }
+
+            self.bypass_index = Some(self.params.len());
+        }
+
+        let value = def.normalize(value);
+        self.params.push(Param {
+            id,
+            def,
+            value: AtomicU64::new(value.to_bits()),
+        });
+
+        Ok(())
+    }
+}
+
+/// Errors that can occur when adding parameters to a [`ParamSet`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ParamSetError {
+    /// A parameter with the given ID is already part of the set.
+    DuplicateId(ClapId),
+    /// The parameter is a bypass parameter, but the set already has one.
+    SecondBypass,
+}
+
+impl Display for ParamSetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateId(id) => write!(f, "Duplicate parameter ID: {}", id.get()),
+            Self::SecondBypass => f.write_str("A parameter set can only have one bypass parameter"),
+        }
+    }
+}
+
+impl Error for ParamSetError {}
+
+impl ParamSet {
+    /// Creates a new, empty parameter set.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            list: RwLock::new(ParamList::default()),
+        }
+    }
+
+    /// Adds a new parameter with the given ID and definition to this set. Its value is initialized
+    /// to its default value.
+    ///
+    /// # Panics
+    ///
+    /// This panics if a parameter with the same ID was already added to this set, or if the
+    /// given parameter is a second bypass parameter.
+    pub fn with_param(mut self, id: ClapId, def: ParamDef) -> Self {
+        let list = self.list.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let default_value = def.default_value;
+
+        if let Err(e) = list.push(id, def, default_value) {
+            panic!("{e}");
+        }
+
+        self
+    }
+
+    /// Adds a bypass parameter with the given ID to this set. See [`ParamDef::bypass`].
+    ///
+    /// The plugin's audio processor can then use [`is_bypassed`](Self::is_bypassed) to know if
+    /// it should pass its input through.
+    ///
+    /// # Panics
+    ///
+    /// This panics if a parameter with the same ID, or another bypass parameter, was already
+    /// added to this set.
+    #[inline]
+    pub fn with_bypass_param(self, id: ClapId) -> Self {
+        self.with_param(id, ParamDef::bypass("Bypass"))
+    }
+
+    /// Adds a new parameter with the given ID and definition to this set at runtime, and requests
+    /// the host to rescan all the parameters. Its value is initialized to its default value.
+    ///
+    /// This must only be called while the plugin is deactivated. See the
+    /// [type documentation](Self#dynamic-parameter-lists) for more information.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if a parameter with the same ID is already part of this set, or if
+    /// the given parameter is a second bypass parameter. The set is left unchanged, and no
+    /// rescan is requested.
+    pub fn add_param(
+        &self,
+        host: &mut HostMainThreadHandle,
+        id: ClapId,
+        def: ParamDef,
+    ) -> Result<(), ParamSetError> {
+        let default_value = def.default_value;
+        self.write().push(id, def, default_value)?;
+
+        request_full_rescan(host);
+        Ok(())
+    }
+
+    /// Removes the parameter with the given ID from this set at runtime, and requests the host to
+    /// rescan all the parameters.
+    ///
+    /// This must only be called while the plugin is deactivated. See the
+    /// [type documentation](Self#dynamic-parameter-lists) for more information.
+    ///
+    /// Returns the definition of the removed parameter, or [`None`] if no parameter with the given
+    /// ID exists, in which case no rescan is requested.
+    pub fn remove_param(&self, host: &mut HostMainThreadHandle, id: ClapId) -> Option<ParamDef> {
+        let removed = {
+            let mut list = self.write();
+            let index = list.params.iter().position(|p| p.id == id)?;
+            let removed = list.params.remove(index);
+
+            list.bypass_index = list
+                .params
+                .iter()
+                .position(|p| p.def.flags.contains(ParamInfoFlags::IS_BYPASS));
+
+            removed.def
+        };
+
+        request_full_rescan(host);
+        Some(removed)
+    }
+
+    /// Replaces all the parameters of this set at runtime, and requests the host to rescan all
+    /// the parameters.
+    ///
+    /// Parameters whose ID was already part of this set keep their current value, adjusted to
+    /// their new definition's range. The other ones are initialized to their default value.
+    ///
+    /// This must only be called while the plugin is deactivated. See the
+    /// [type documentation](Self#dynamic-parameter-lists) for more information.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the given parameters contain duplicate IDs, or more than one
+    /// bypass parameter. The set is left unchanged, and no rescan is requested.
+    pub fn replace_params(
+        &self,
+        host: &mut HostMainThreadHandle,
+        params: impl IntoIterator<Item = (ClapId, ParamDef)>,
+    ) -> Result<(), ParamSetError> {
+        {
+            let mut list = self.write();
+            let mut new_list = ParamList::default();
+
+            for (id, def) in params {
+                let value = list.find(id).map_or(def.default_value, |p| p.load_value());
+
+                new_list.push(id, def, value)?;
+            }
+
+            *list = new_list;
+        }
+
+        request_full_rescan(host);
+        Ok(())
+    }
+
+    /// Returns `true` if this set has a bypass parameter, and it is currently enabled.
+    ///
+    /// When bypassed, plugins should output their input unchanged, but still delayed by their
+    /// reported latency, so that hosts switching the bypass on and off stay aligned.
+    #[inline]
+    pub fn is_bypassed(&self) -> bool {
+        let list = self.read();
+
+        list.bypass_index
+            .and_then(|i| list.params.get(i))
+            .is_some_and(|p| p.load_value() >= 0.5)
+    }
+
+    /// Returns the number of parameters in this set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.read().params.len()
+    }
+
+    /// Returns `true` if this set contains no parameters.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.read().params.is_empty()
+    }
+
+    /// Returns the IDs of all the parameters in this set, in the order they are reported to the
+    /// host.
+    pub fn ids(&self) -> Vec<ClapId> {
+        self.read().params.iter().map(|p| p.id).collect()
+    }
+
+    /// Returns a copy of the definition of the parameter with the given ID, if it exists.
+    #[inline]
+    pub fn def(&self, id: ClapId) -> Option<ParamDef> {
+        self.read().find(id).map(|p| p.def.clone())
+    }
+
+    /// Returns the current value of the parameter with the given ID, if it exists.
+    #[inline]
+    pub fn value(&self, id: ClapId) -> Option<f64> {
+        self.read().find(id).map(Param::load_value)
+    }
+
+    /// Sets the value of the parameter with the given ID.
+    ///
+    /// The value is clamped to the parameter's range, and rounded if the parameter is stepped.
+    /// `NaN` values are ignored.
+    ///
+    /// Returns `false` if no parameter with the given ID exists, or if the value was ignored.
+    pub fn set_value(&self, id: ClapId, value: f64) -> bool {
+        let list = self.read();
+        let Some(param) = list.find(id) else {
+            return false;
+        };
+
+        if value.is_nan() {
+            return false;
+        }
+
+        let value = param.def.normalize(value);
+        param.value.store(value.to_bits(), Ordering::Relaxed);
+        true
+    }
+
+    /// Handles the given event. If it is a parameter value event targeting a parameter of this
+    /// set, its value is updated accordingly.
+    ///
+    /// Returns `true` if a parameter value was updated.
+    pub fn handle_event(&self, event: &UnknownEvent) -> bool {
+        match event.as_core_event() {
+            Some(CoreEventSpace::ParamValue(event)) => event
+                .param_id()
+                .is_some_and(|id| self.set_value(id, event.value())),
+            _ => false,
+        }
+    }
+
+    /// Handles all the given events. See [`handle_event`](Self::handle_event).
+    ///
+    /// This is meant to be called from the `flush` methods of the
+    /// [`PluginMainThreadParams`](super::PluginMainThreadParams) and
+    /// [`PluginAudioProcessorParams`](super::PluginAudioProcessorParams) traits, as well as
+    /// from the plugin's `process` method.
+    pub fn flush(&self, input_parameter_changes: &InputEvents) {
+        for event in input_parameter_changes {
+            self.handle_event(event);
+        }
+    }
+
+    /// Returns the number of parameters, for
+    /// [`PluginMainThreadParams::count`](super::PluginMainThreadParams::count).
+    #[inline]
+    pub fn count(&self) -> u32 {
+        self.read().params.len() as u32
+    }
+
+    /// Writes the info of the parameter at the given index, for
+    /// [`PluginMainThreadParams::get_info`](super::PluginMainThreadParams::get_info).
+    pub fn get_info(&self, param_index: u32, info: &mut ParamInfoWriter) {
+        let list = self.read();
+        let Some(param) = list.params.get(param_index as usize) else {
+            return;
+        };
+
+        info.set(&ParamInfo {
+            id: param.id,
+            flags: param.def.flags,
+            cookie: Cookie::empty(),
+            name: param.def.name.as_bytes(),
+            module: param.def.module.as_bytes(),
+            min_value: param.def.min_value,
+            max_value: param.def.max_value,
+            default_value: param.def.default_value,
+        })
+    }
+
+    /// Writes the text representation of the given value of the given parameter, for
+    /// [`PluginMainThreadParams::value_to_text`](super::PluginMainThreadParams::value_to_text).
+    ///
+    /// Enumeration parameters are displayed using their labels, other stepped parameters as
+    /// integers, and continuous parameters with two decimals.
+    pub fn value_to_text(
+        &self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> core::fmt::Result {
+        let list = self.read();
+        let def = &list.find(param_id).ok_or(core::fmt::Error)?.def;
+
+        if def.flags.contains(ParamInfoFlags::IS_ENUM) {
+            let label = def.label(value).ok_or(core::fmt::Error)?;
+            writer.write_str(label)
+        } else if def.flags.contains(ParamInfoFlags::IS_STEPPED) {
+            write!(writer, "{}", value.round())
+        } else {
+            write!(writer, "{value:.2}")
+        }
+    }
+
+    /// Parses the given text into a value of the given parameter, for
+    /// [`PluginMainThreadParams::text_to_value`](super::PluginMainThreadParams::text_to_value).
+    ///
+    /// For enumeration parameters, this accepts either one of the labels, or the integer value
+    /// itself.
+    pub fn text_to_value(&self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        let list = self.read();
+        let def = &list.find(param_id)?.def;
+        let text = text.to_str().ok()?.trim();
+
+        if def.flags.contains(ParamInfoFlags::IS_ENUM) {
+            if let Some(index) = def.labels.iter().position(|l| l == text) {
+                return Some(index as f64);
+            }
+        }
+
+        let value: f64 = text.parse().ok()?;
+        if value.is_nan() || value < def.min_value || value > def.max_value {
+            return None;
+        }
+
+        Some(def.normalize(value))
+    }
+
+    #[inline]
+    fn read(&self) -> RwLockReadGuard<'_, ParamList> {
+        self.list.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline]
+    fn write(&self) -> RwLockWriteGuard<'_, ParamList> {
+        self.list.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn request_full_rescan(host: &mut HostMainThreadHandle) {
+    if let Some(host_params) = host.shared().get_extension::<HostParams>() {
+        host_params.rescan(host, ParamRescanFlags::ALL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FILTER_TYPE: ClapId = ClapId::new(1);
+
+    fn params() -> ParamSet {
+        ParamSet::new().with_param(
+            FILTER_TYPE,
+            ParamDef::enumeration("Filter Type", &["LP", "HP", "BP"]),
+        )
+    }
+
+    fn value_to_text(params: &ParamSet, value: f64) -> Option<String> {
+        let mut buffer = [0u8; 16];
+        let mut writer = ParamDisplayWriter::new(&mut buffer);
+        params.value_to_text(FILTER_TYPE, value, &mut writer).ok()?;
+        writer.finish().then_some(())?;
+
+        let len = buffer.iter().position(|b| *b == 0)?;
+        Some(String::from_utf8(buffer[..len].to_vec()).unwrap())
+    }
+
+    fn text_to_value(params: &ParamSet, text: &str) -> Option<f64> {
+        let text = std::ffi::CString::new(text).unwrap();
+        params.text_to_value(FILTER_TYPE, &text)
+    }
+
+    #[test]
+    pub fn enumeration_has_consistent_info() {
+        let def = ParamDef::enumeration("Filter Type", &["LP", "HP", "BP"]);
+
+        assert_eq!(def.min_value(), 0.0);
+        assert_eq!(def.max_value(), 2.0);
+        assert_eq!(def.default_value(), 0.0);
+        assert!(def
+            .flags()
+            .contains(ParamInfoFlags::IS_STEPPED | ParamInfoFlags::IS_ENUM));
+
+        let info = ParamInfo {
+            id: FILTER_TYPE,
+            flags: def.flags(),
+            cookie: Cookie::empty(),
+            name: def.name().as_bytes(),
+            module: b"",
+            min_value: def.min_value(),
+            max_value: def.max_value(),
+            default_value: def.default_value(),
+        };
+
+        assert!(info.validate().is_empty());
+    }
+
+    #[test]
+    pub fn enumeration_text_round_trips() {
+        let params = params();
+
+        for (value, label) in ["LP", "HP", "BP"].into_iter().enumerate() {
+            let value = value as f64;
+
+            assert_eq!(value_to_text(&params, value).as_deref(), Some(label));
+            assert_eq!(text_to_value(&params, label), Some(value));
+            assert_eq!(text_to_value(&params, &value.to_string()), Some(value));
+        }
+
+        assert_eq!(value_to_text(&params, 3.0), None);
+        assert_eq!(text_to_value(&params, "Notch"), None);
+        assert_eq!(text_to_value(&params, "3"), None);
+    }
+
+    #[test]
+    pub fn bypass_param_is_reported() {
+        const BYPASS: ClapId = ClapId::new(2);
+
+        let params = params();
+        assert!(!params.is_bypassed());
+
+        let params = params.with_bypass_param(BYPASS);
+        assert!(!params.is_bypassed());
+        assert!(params
+            .def(BYPASS)
+            .unwrap()
+            .flags()
+            .contains(ParamInfoFlags::IS_BYPASS | ParamInfoFlags::IS_STEPPED));
+
+        assert!(params.set_value(BYPASS, 0.7));
+        assert!(params.is_bypassed());
+        assert_eq!(params.value(BYPASS), Some(1.0));
+
+        assert!(params.set_value(BYPASS, 0.0));
+        assert!(!params.is_bypassed());
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn only_one_bypass_param_is_allowed() {
+        let _ = ParamSet::new()
+            .with_bypass_param(ClapId::new(0))
+            .with_bypass_param(ClapId::new(1));
+    }
+
+    #[test]
+    pub fn values_are_clamped_and_rounded() {
+        let params = params();
+
+        assert_eq!(params.value(FILTER_TYPE), Some(0.0));
+
+        assert!(params.set_value(FILTER_TYPE, 1.4));
+        assert_eq!(params.value(FILTER_TYPE), Some(1.0));
+
+        assert!(params.set_value(FILTER_TYPE, 42.0));
+        assert_eq!(params.value(FILTER_TYPE), Some(2.0));
+
+        assert!(!params.set_value(FILTER_TYPE, f64::NAN));
+        assert!(!params.set_value(ClapId::new(42), 1.0));
+        assert_eq!(params.value(FILTER_TYPE), Some(2.0));
+    }
+}
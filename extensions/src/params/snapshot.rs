@@ -0,0 +1,283 @@
+//! Host-side snapshots of a plugin's parameter values, for randomization and preset morphing.
+//!
+//! See the [`ParamSnapshot`] type for more information.
+
+use super::*;
+use clack_common::events::event_types::ParamValueEvent;
+use clack_common::events::io::{OutputEvents, TryPushError};
+use clack_common::events::Pckn;
+
+/// A single parameter value in a [`ParamSnapshot`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SnapshotParam {
+    id: ClapId,
+    flags: ParamInfoFlags,
+    cookie: Cookie,
+    min_value: f64,
+    max_value: f64,
+    value: f64,
+}
+
+impl SnapshotParam {
+    /// Returns the parameter's ID.
+    #[inline]
+    pub fn id(&self) -> ClapId {
+        self.id
+    }
+
+    /// Returns the parameter's flags.
+    #[inline]
+    pub fn flags(&self) -> ParamInfoFlags {
+        self.flags
+    }
+
+    /// Returns the parameter's minimum value.
+    #[inline]
+    pub fn min_value(&self) -> f64 {
+        self.min_value
+    }
+
+    /// Returns the parameter's maximum value.
+    #[inline]
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    /// Returns the parameter's value in this snapshot.
+    #[inline]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Returns `true` if the parameter is stepped, in which case its value is always an integer.
+    #[inline]
+    pub fn is_stepped(&self) -> bool {
+        self.flags.contains(ParamInfoFlags::IS_STEPPED)
+    }
+
+    /// Clamps the given value to this parameter's range, and rounds it if the parameter is
+    /// stepped.
+    fn quantize(&self, value: f64) -> f64 {
+        let value = if value.is_nan() {
+            self.value
+        } else {
+            value.clamp(self.min_value, self.max_value)
+        };
+
+        if self.is_stepped() {
+            value.round()
+        } else {
+            value
+        }
+    }
+}
+
+/// A snapshot of the values of a plugin's parameters, as seen by the host.
+///
+/// Snapshots are [captured](Self::capture) from a [`ParamValueCache`], and can then be
+/// [randomized](Self::randomize), [interpolated](Self::interpolate) with other snapshots (e.g. to
+/// morph between presets), and [applied](Self::apply) back to the plugin.
+///
+/// Only parameters that can sensibly be changed in bulk are captured: read-only parameters, and
+/// the bypass parameter, are always left out. Values of stepped parameters are always integers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParamSnapshot {
+    params: Vec<SnapshotParam>,
+}
+
+impl ParamSnapshot {
+    /// Captures the last known values of the parameters in the given cache.
+    ///
+    /// Read-only parameters, the bypass parameter, and parameters whose value is unknown are
+    /// skipped.
+    pub fn capture(cache: &ParamValueCache) -> Self {
+        let params = cache
+            .params()
+            .iter()
+            .filter(|p| {
+                !p.flags
+                    .intersects(ParamInfoFlags::IS_READONLY | ParamInfoFlags::IS_BYPASS)
+            })
+            .filter(|p| p.min_value <= p.max_value)
+            .filter_map(|p| {
+                let mut param = SnapshotParam {
+                    id: p.id,
+                    flags: p.flags,
+                    cookie: p.cookie,
+                    min_value: p.min_value,
+                    max_value: p.max_value,
+                    value: p.value?,
+                };
+
+                param.value = param.quantize(param.value);
+                Some(param)
+            })
+            .collect();
+
+        Self { params }
+    }
+
+    /// Returns the number of parameters in this snapshot.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns `true` if this snapshot contains no parameters.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Returns all the parameters of this snapshot, in the order the plugin reported them.
+    #[inline]
+    pub fn params(&self) -> &[SnapshotParam] {
+        &self.params
+    }
+
+    /// Returns the value of the parameter with the given ID, if it is in this snapshot.
+    #[inline]
+    pub fn value(&self, param_id: ClapId) -> Option<f64> {
+        Some(self.get(param_id)?.value)
+    }
+
+    /// Sets the value of the parameter with the given ID.
+    ///
+    /// The value is clamped to the parameter's range, and rounded if the parameter is stepped.
+    /// This returns `false` if the parameter is not in this snapshot.
+    pub fn set_value(&mut self, param_id: ClapId, value: f64) -> bool {
+        let Some(param) = self.params.iter_mut().find(|p| p.id == param_id) else {
+            return false;
+        };
+
+        param.value = param.quantize(value);
+        true
+    }
+
+    /// Returns a randomized copy of this snapshot.
+    ///
+    /// Each parameter for which `filter` returns `true` is moved towards a random value within its
+    /// range, by the given `amount`: `0.0` leaves it untouched, while `1.0` sets it to the random
+    /// value. Other parameters are left untouched.
+    ///
+    /// Random values are drawn from `rng`, which must return uniformly distributed numbers in the
+    /// `[0.0, 1.0)` range. This lets hosts use their random number generator of choice.
+    pub fn randomize(
+        &self,
+        mut rng: impl FnMut() -> f64,
+        amount: f64,
+        mut filter: impl FnMut(&SnapshotParam) -> bool,
+    ) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+
+        let params = self
+            .params
+            .iter()
+            .map(|param| {
+                if !filter(param) {
+                    return *param;
+                }
+
+                let target = param.min_value + rng() * (param.max_value - param.min_value);
+                let value = param.value + (target - param.value) * amount;
+
+                SnapshotParam {
+                    value: param.quantize(value),
+                    ..*param
+                }
+            })
+            .collect();
+
+        Self { params }
+    }
+
+    /// Returns a snapshot morphed between `a` and `b`, by the given position `t`.
+    ///
+    /// At `0.0` the result is equal to `a`, and at `1.0` it matches `b`. Continuous parameters are
+    /// linearly interpolated, while stepped parameters (including enumerations) switch from `a`'s
+    /// value to `b`'s at `0.5`.
+    ///
+    /// The result contains the same parameters as `a`. Parameters that are missing from `b` keep
+    /// their value from `a`.
+    pub fn interpolate(a: &Self, b: &Self, t: f64) -> Self {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+
+        let params = a
+            .params
+            .iter()
+            .map(|param| {
+                let Some(to) = b.value(param.id) else {
+                    return *param;
+                };
+
+                let value = if param.is_stepped() {
+                    if t < 0.5 {
+                        param.value
+                    } else {
+                        to
+                    }
+                } else {
+                    param.value + (to - param.value) * t
+                };
+
+                SnapshotParam {
+                    value: param.quantize(value),
+                    ..*param
+                }
+            })
+            .collect();
+
+        Self { params }
+    }
+
+    /// Pushes the parameter value events needed to apply this snapshot to the plugin to `events`,
+    /// at the given sample time, and updates the cached values accordingly.
+    ///
+    /// Only the parameters whose value differs from their last known value in `cache` get an
+    /// event, which keeps the amount of events (and of undo steps, or automation points) minimal.
+    ///
+    /// These events are meant to be sent to the plugin, e.g. in its next `process` call, or through
+    /// [`PluginParams::flush`] if it is inactive. This returns the number of events that were
+    /// pushed.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if an event could not be pushed. The cache is kept
+    /// consistent with the events that were pushed until then.
+    pub fn apply(
+        &self,
+        cache: &mut ParamValueCache,
+        events: &mut OutputEvents,
+        time: u32,
+    ) -> Result<usize, TryPushError> {
+        let mut pushed = 0;
+
+        for param in &self.params {
+            let Some(cached) = cache.get_mut(param.id) else {
+                continue;
+            };
+
+            if cached.value == Some(param.value) {
+                continue;
+            }
+
+            events.try_push(ParamValueEvent::new(
+                time,
+                param.id,
+                Pckn::match_all(),
+                param.value,
+                param.cookie,
+            ))?;
+
+            cached.value = Some(param.value);
+            pushed += 1;
+        }
+
+        Ok(pushed)
+    }
+
+    #[inline]
+    fn get(&self, param_id: ClapId) -> Option<&SnapshotParam> {
+        self.params.iter().find(|p| p.id == param_id)
+    }
+}
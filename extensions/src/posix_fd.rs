@@ -139,13 +139,17 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostPosixFdImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
-            Ok(host
-                .main_thread()
-                .as_mut()
-                .register_fd(fd, FdFlags::from_bits_truncate(flags))
-                .is_ok())
-        })
+        HostWrapper::<H>::handle_main_thread(
+            host,
+            "clap_host_posix_fd_support.register_fd",
+            |host| {
+                Ok(host
+                    .main_thread()
+                    .as_mut()
+                    .register_fd(fd, FdFlags::from_bits_truncate(flags))
+                    .is_ok())
+            },
+        )
         .unwrap_or(false)
     }
 
@@ -158,7 +162,7 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostPosixFdImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
+        HostWrapper::<H>::handle_main_thread(host, "clap_host_posix_fd_support.modify_fd", |host| {
             Ok(host
                 .main_thread()
                 .as_mut()
@@ -172,9 +176,11 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostPosixFdImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
-            Ok(host.main_thread().as_mut().unregister_fd(fd).is_ok())
-        })
+        HostWrapper::<H>::handle_main_thread(
+            host,
+            "clap_host_posix_fd_support.unregister_fd",
+            |host| Ok(host.main_thread().as_mut().unregister_fd(fd).is_ok()),
+        )
         .unwrap_or(false)
     }
 }
@@ -283,14 +289,14 @@ mod plugin {
     ) where
         for<'a> P::MainThread<'a>: PluginPosixFdImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| {
-            plugin
-                .main_thread()
-                .as_mut()
-                .on_fd(fd, FdFlags::from_bits_truncate(flags));
-
-            Ok(())
-        });
+        PluginWrapper::<P>::handle_main_thread(
+            plugin,
+            "clap_plugin_posix_fd_support.on_fd",
+            |main_thread, _, _| {
+                main_thread.on_fd(fd, FdFlags::from_bits_truncate(flags));
+                Ok(())
+            },
+        );
     }
 }
 #[cfg(feature = "clack-plugin")]
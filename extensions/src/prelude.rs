@@ -0,0 +1,165 @@
+//! A helpful prelude re-exporting the most commonly used types of all enabled extensions.
+//!
+//! For each extension enabled through its feature flag, this re-exports:
+//!
+//! * its plugin-side and host-side extension types (e.g. [`PluginParams`] and [`HostParams`]);
+//! * the data types used in its API (e.g. [`ParamInfo`] or [`ParamInfoFlags`]);
+//! * if the `clack-plugin` feature is enabled, the traits plugins implement to support it, and the
+//!   types those traits use (e.g. [`PluginMainThreadParams`] and [`ParamInfoWriter`]);
+//! * if the `clack-host` feature is enabled, the traits hosts implement to support it, and the
+//!   types used to query it (e.g. [`HostParamsImplMainThread`] and [`ParamInfoBuffer`]).
+//!
+//! Raw FFI wrappers (such as the underlying `clap_sys` types), as well as more specialized helpers
+//! (e.g. [`params::snapshot`](crate::params::snapshot) or
+//! [`gui::resize`](crate::gui::resize)), are intentionally left out, and have to be imported from
+//! their own module.
+//!
+//! This prelude is meant to be used along with either `clack_plugin::prelude` or
+//! `clack_host::prelude`:
+//!
+//! ```
+//! use clack_extensions::prelude::*;
+//! use clack_plugin::prelude::*;
+//! ```
+
+#[cfg(feature = "audio-ports")]
+pub use crate::audio_ports::{
+    AudioPortFlags, AudioPortInfo, AudioPortLayout, AudioPortLayoutProvider, AudioPortType,
+    HostAudioPorts, PluginAudioPorts, RescanType, StaticAudioPorts,
+};
+#[cfg(all(feature = "audio-ports", feature = "clack-host"))]
+pub use crate::audio_ports::{AudioPortInfoBuffer, HostAudioPortsImpl};
+#[cfg(all(feature = "audio-ports", feature = "clack-plugin"))]
+pub use crate::audio_ports::{AudioPortInfoWriter, PluginAudioPortsImpl};
+
+#[cfg(feature = "audio-ports-config")]
+pub use crate::audio_ports_config::{
+    AudioPortConfigSelectError, AudioPortsConfiguration, HostAudioPortsConfig, MainPortInfo,
+    PluginAudioPortsConfig,
+};
+#[cfg(all(feature = "audio-ports-config", feature = "clack-plugin"))]
+pub use crate::audio_ports_config::{AudioPortConfigWriter, PluginAudioPortsConfigImpl};
+#[cfg(all(feature = "audio-ports-config", feature = "clack-host"))]
+pub use crate::audio_ports_config::{AudioPortsConfigBuffer, HostAudioPortsConfigImpl};
+
+#[cfg(feature = "clack-plugin")]
+pub use crate::capabilities::HostCapabilities;
+
+#[cfg(all(feature = "context-menu", feature = "clack-plugin"))]
+pub use crate::context_menu::PluginContextMenuImpl;
+#[cfg(feature = "context-menu")]
+pub use crate::context_menu::{
+    ContextMenuBuilder, ContextMenuItem, ContextMenuItemKind, ContextMenuTarget, HostContextMenu,
+    MenuItem, MenuModel, PluginContextMenu,
+};
+#[cfg(all(feature = "context-menu", feature = "clack-host"))]
+pub use crate::context_menu::{
+    ContextMenuPopup, ContextMenuPopups, HostContextMenuImpl, PopupRequest,
+};
+
+#[cfg(feature = "event-registry")]
+pub use crate::event_registry::HostEventRegistry;
+
+#[cfg(all(feature = "gui", feature = "clack-host"))]
+pub use crate::gui::HostGuiImpl;
+#[cfg(all(feature = "gui", feature = "clack-plugin"))]
+pub use crate::gui::PluginGuiImpl;
+#[cfg(feature = "gui")]
+pub use crate::gui::{
+    AspectRatioStrategy, GuiApiType, GuiConfiguration, GuiError, GuiResizeHints, GuiSize, HostGui,
+    PluginGui, Window,
+};
+
+#[cfg(all(feature = "latency", feature = "clack-host"))]
+pub use crate::latency::HostLatencyImpl;
+#[cfg(all(feature = "latency", feature = "clack-plugin"))]
+pub use crate::latency::PluginLatencyImpl;
+#[cfg(feature = "latency")]
+pub use crate::latency::{HostLatency, PluginLatency};
+
+#[cfg(all(feature = "log", feature = "clack-host"))]
+pub use crate::log::HostLogImpl;
+#[cfg(feature = "log")]
+pub use crate::log::{HostLog, LogError, LogSeverity};
+
+#[cfg(feature = "note-name")]
+pub use crate::note_name::{HostNoteName, NoteName, PluginNoteName};
+#[cfg(all(feature = "note-name", feature = "clack-host"))]
+pub use crate::note_name::{HostNoteNameImpl, NoteNameBuffer};
+#[cfg(all(feature = "note-name", feature = "clack-plugin"))]
+pub use crate::note_name::{NoteNameWriter, PluginNoteNameImpl};
+
+#[cfg(feature = "note-ports")]
+pub use crate::note_ports::{
+    HostNotePorts, NoteDialect, NoteDialects, NotePortInfo, NotePortLayout, NotePortLayoutProvider,
+    NotePortRescanFlags, PluginNotePorts, StaticNotePorts,
+};
+#[cfg(all(feature = "note-ports", feature = "clack-host"))]
+pub use crate::note_ports::{HostNotePortsImpl, NotePortInfoBuffer};
+#[cfg(all(feature = "note-ports", feature = "clack-plugin"))]
+pub use crate::note_ports::{NotePortInfoWriter, PluginNotePortsImpl};
+
+#[cfg(feature = "params")]
+pub use crate::params::{
+    HostParams, ParamClearFlags, ParamInfo, ParamInfoFlags, ParamRescanFlags, PluginParams,
+};
+#[cfg(all(feature = "params", feature = "clack-host"))]
+pub use crate::params::{
+    HostParamsImplMainThread, HostParamsImplShared, ParamInfoBuffer, ParamValueCache,
+};
+#[cfg(all(feature = "params", feature = "clack-plugin"))]
+pub use crate::params::{
+    ParamDisplayWriter, ParamInfoWriter, PluginAudioProcessorParams, PluginMainThreadParams,
+};
+
+#[cfg(all(unix, feature = "posix-fd", feature = "clack-host"))]
+pub use crate::posix_fd::HostPosixFdImpl;
+#[cfg(all(unix, feature = "posix-fd", feature = "clack-plugin"))]
+pub use crate::posix_fd::PluginPosixFdImpl;
+#[cfg(all(unix, feature = "posix-fd"))]
+pub use crate::posix_fd::{FdError, FdFlags, HostPosixFd, PluginPosixFd};
+
+#[cfg(all(feature = "render", feature = "clack-plugin"))]
+pub use crate::render::PluginRenderImpl;
+#[cfg(feature = "render")]
+pub use crate::render::{PluginRender, PluginRenderError, RenderMode};
+
+#[cfg(all(feature = "state", feature = "clack-host"))]
+pub use crate::state::HostStateImpl;
+#[cfg(all(feature = "state", feature = "clack-plugin"))]
+pub use crate::state::PluginStateImpl;
+#[cfg(feature = "state")]
+pub use crate::state::{HostState, PluginState, StateError};
+
+#[cfg(all(feature = "tail", feature = "clack-host"))]
+pub use crate::tail::HostTailImpl;
+#[cfg(all(feature = "tail", feature = "clack-plugin"))]
+pub use crate::tail::PluginTailImpl;
+#[cfg(feature = "tail")]
+pub use crate::tail::{HostTail, PluginTail, TailLength};
+
+#[cfg(feature = "thread-check")]
+pub use crate::thread_check::HostThreadCheck;
+#[cfg(all(feature = "thread-check", feature = "clack-host"))]
+pub use crate::thread_check::HostThreadCheckImpl;
+
+#[cfg(all(feature = "thread-pool", feature = "clack-host"))]
+pub use crate::thread_pool::HostThreadPoolImpl;
+#[cfg(all(feature = "thread-pool", feature = "clack-plugin"))]
+pub use crate::thread_pool::PluginThreadPoolImpl;
+#[cfg(feature = "thread-pool")]
+pub use crate::thread_pool::{HostThreadPool, PluginThreadPool, ThreadPoolRequestError};
+
+#[cfg(all(feature = "timer", feature = "clack-host"))]
+pub use crate::timer::HostTimerImpl;
+#[cfg(all(feature = "timer", feature = "clack-plugin"))]
+pub use crate::timer::PluginTimerImpl;
+#[cfg(feature = "timer")]
+pub use crate::timer::{HostTimer, PluginTimer, TimerError, TimerId};
+
+#[cfg(all(feature = "voice-info", feature = "clack-host"))]
+pub use crate::voice_info::HostVoiceInfoImpl;
+#[cfg(all(feature = "voice-info", feature = "clack-plugin"))]
+pub use crate::voice_info::PluginVoiceInfoImpl;
+#[cfg(feature = "voice-info")]
+pub use crate::voice_info::{HostVoiceInfo, PluginVoiceInfo, VoiceInfo, VoiceInfoFlags};
@@ -116,13 +116,17 @@ mod plugin {
     where
         for<'a> P::MainThread<'a>: PluginRenderImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| {
-            let mode = RenderMode::from_raw(mode).ok_or(PluginWrapperError::InvalidParameter(
-                "clap_plugin_render_mode",
-            ))?;
-
-            Ok(plugin.main_thread().as_mut().set(mode).is_ok())
-        })
+        PluginWrapper::<P>::handle_main_thread(
+            plugin,
+            "clap_plugin_render.set",
+            |main_thread, _, _| {
+                let mode = RenderMode::from_raw(mode).ok_or(
+                    PluginWrapperError::InvalidParameter("clap_plugin_render_mode"),
+                )?;
+
+                Ok(main_thread.set(mode).is_ok())
+            },
+        )
         .unwrap_or(false)
     }
 
@@ -133,12 +137,16 @@ mod plugin {
     where
         for<'a> P::MainThread<'a>: PluginRenderImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| {
-            Ok(plugin
-                .main_thread()
-                .as_ref()
-                .has_hard_realtime_requirement())
-        })
+        PluginWrapper::<P>::handle_named(
+            plugin,
+            "clap_plugin_render.has_hard_realtime_requirement",
+            |plugin| {
+                Ok(plugin
+                    .main_thread()
+                    .as_ref()
+                    .has_hard_realtime_requirement())
+            },
+        )
         .unwrap_or(false)
     }
 }
@@ -7,7 +7,8 @@
 //! [`OutputStream`](clack_common::stream::OutputStream)
 //!
 //! Plugins can also notify the host that their state has changed compared to the last time it was
-//! saved or loaded, using the `mark_dirty` call.
+//! saved or loaded, using the `mark_dirty` call. Hosts can track those calls, and only save the
+//! plugins that changed, using the helpers in the [`session`] module.
 //!
 //! # Host-Side Example
 //!
@@ -66,6 +67,7 @@
 //! # mod utils { include!("./__doc_utils.rs"); }
 //! let mut plugin_instance: PluginInstance<MyHost> = /* ... */
 //! # utils::get_working_instance(|_| MyHostShared { state_ext: OnceLock::new() }, |shared| MyHostMainThread { is_state_dirty: false, shared })?;
+//! let main_thread = MainThreadToken::acquire().expect("Not on the main thread");
 //!
 //! let state_ext = plugin_instance.access_shared_handler(|h| h.state_ext.get())
 //!     .expect("Plugin is not yet instantiated")
@@ -74,12 +76,12 @@
 //! // We just loaded our plugin, but we have a preset to initialize it to.
 //! let preset_data = b"I'm a totally legit preset.";
 //! let mut reader = Cursor::new(preset_data);
-//! state_ext.load(&mut plugin_instance.plugin_handle(), &mut reader)?;
+//! state_ext.load(&mut plugin_instance.plugin_handle_checked(&main_thread), &mut reader)?;
 //!
 //! // Some time passes, user interacts with the plugin, etc.
 //! // Now the user wants to save the state.
 //! let mut buffer = Vec::new();
-//! state_ext.save(&mut plugin_instance.plugin_handle(), &mut buffer)?;
+//! state_ext.save(&mut plugin_instance.plugin_handle_checked(&main_thread), &mut buffer)?;
 //! # Ok(()) }
 //! ```
 
@@ -161,3 +163,5 @@ pub use plugin::*;
 mod host;
 #[cfg(feature = "clack-host")]
 pub use host::*;
+#[cfg(feature = "clack-host")]
+pub mod session;
@@ -49,7 +49,11 @@ impl PluginState {
     }
 }
 
+/// Implementation of the host side of the State extension.
 pub trait HostStateImpl {
+    /// Called by the plugin when its state changed since it was last saved or loaded.
+    ///
+    /// Hosts can record this using a [`StateDirtiness`](super::session::StateDirtiness) tracker.
     fn mark_dirty(&mut self);
 }
 
@@ -70,7 +74,7 @@ unsafe extern "C" fn mark_dirty<H: HostHandlers>(host: *const clap_host)
 where
     for<'a> <H as HostHandlers>::MainThread<'a>: HostStateImpl,
 {
-    HostWrapper::<H>::handle(host, |host| {
+    HostWrapper::<H>::handle_main_thread(host, "clap_host_state.mark_dirty", |host| {
         host.main_thread().as_mut().mark_dirty();
 
         Ok(())
@@ -38,7 +38,7 @@ unsafe extern "C" fn load<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginStateImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_state.load", |p| {
         let input = InputStream::from_raw_mut(&mut *(stream as *mut _));
         p.main_thread().as_mut().load(input)?;
         Ok(())
@@ -54,10 +54,37 @@ unsafe extern "C" fn save<P: Plugin>(
 where
     for<'a> P::MainThread<'a>: PluginStateImpl,
 {
-    PluginWrapper::<P>::handle(plugin, |p| {
+    PluginWrapper::<P>::handle_named(plugin, "clap_plugin_state.save", |p| {
         let output = OutputStream::from_raw_mut(&mut *(stream as *mut _));
         p.main_thread().as_mut().save(output)?;
         Ok(())
     })
     .is_some()
 }
+
+impl DynInterface for dyn PluginStateImpl {
+    type Target<'a> = dyn PluginStateImpl + 'a;
+}
+
+/// Forwards to the [`PluginStateImpl`] implementation provided by the dynamic plugin, if any.
+impl PluginStateImpl for DynPluginMainThread<'_> {
+    #[inline]
+    fn save(&mut self, output: &mut OutputStream) -> Result<(), PluginError> {
+        match self.interface::<dyn PluginStateImpl>() {
+            Some(state) => state.save(output),
+            None => Err(PluginError::Message(
+                "Plugin does not provide the state extension",
+            )),
+        }
+    }
+
+    #[inline]
+    fn load(&mut self, input: &mut InputStream) -> Result<(), PluginError> {
+        match self.interface::<dyn PluginStateImpl>() {
+            Some(state) => state.load(input),
+            None => Err(PluginError::Message(
+                "Plugin does not provide the state extension",
+            )),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Host-side tracking of the plugins' unsaved state changes.
+//!
+//! Plugins call `mark_dirty` when their state changed compared to the last time it was saved or
+//! loaded, so that hosts can mark their project as modified, and enable its "save" action.
+//!
+//! This module provides a [`StateDirtiness`] tracker, meant to be stored in the host's
+//! [`MainThread`](HostHandlers::MainThread) handler and marked from its
+//! [`HostStateImpl::mark_dirty`] implementation, and the [`save_all_dirty`] function, which only
+//! saves the state of the plugins that changed since they were last saved.
+//!
+//! The CLAP API doesn't tell whether a plugin actually calls `mark_dirty`. A plugin that never
+//! calls it would never be saved if it was trusted blindly: by default, plugins are considered
+//! always dirty until they call `mark_dirty` for the first time. This can be overridden for each
+//! instance, using [`StateDirtiness::set_policy`].
+
+use super::*;
+use clack_host::prelude::*;
+
+/// How a [`StateDirtiness`] tracker decides if a plugin's state is dirty.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum DirtinessPolicy {
+    /// The plugin is always dirty until it calls `mark_dirty` for the first time, after which
+    /// only its `mark_dirty` calls are trusted.
+    #[default]
+    Auto,
+    /// The plugin is always dirty, regardless of its `mark_dirty` calls.
+    ///
+    /// This is meant for plugins that are known to call `mark_dirty` unreliably.
+    AlwaysDirty,
+    /// The plugin is only dirty if it called `mark_dirty` since it was last saved.
+    ///
+    /// This is meant for plugins that are known to call `mark_dirty` reliably, even if they
+    /// haven't called it yet.
+    Trusted,
+}
+
+/// Tracks the unsaved state changes of a single plugin instance.
+///
+/// Every call to [`mark_dirty`](Self::mark_dirty) advances a change counter, which is recorded
+/// when the state is saved. The plugin is dirty if the counter advanced since then, subject to
+/// the [`DirtinessPolicy`] of this tracker.
+#[derive(Clone, Debug, Default)]
+pub struct StateDirtiness {
+    changes: u64,
+    saved_changes: u64,
+    has_marked: bool,
+    policy: DirtinessPolicy,
+}
+
+impl StateDirtiness {
+    /// Creates a new tracker, with no changes, and the [`Auto`](DirtinessPolicy::Auto) policy.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            changes: 0,
+            saved_changes: 0,
+            has_marked: false,
+            policy: DirtinessPolicy::Auto,
+        }
+    }
+
+    /// Records a change of the plugin's state.
+    ///
+    /// This is meant to be called from the host's [`HostStateImpl::mark_dirty`] implementation.
+    #[inline]
+    pub fn mark_dirty(&mut self) {
+        self.changes = self.changes.wrapping_add(1);
+        self.has_marked = true;
+    }
+
+    /// Records that the plugin's state was saved or loaded, clearing the dirty flag.
+    ///
+    /// [`save_all_dirty`] calls this already for the states it saves successfully.
+    #[inline]
+    pub fn mark_saved(&mut self) {
+        self.saved_changes = self.changes;
+    }
+
+    /// Returns `true` if the plugin's state changed since it was last saved, according to the
+    /// [`policy`](Self::policy) of this tracker.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        match self.policy {
+            DirtinessPolicy::Auto if !self.has_marked => true,
+            DirtinessPolicy::AlwaysDirty => true,
+            _ => self.changes != self.saved_changes,
+        }
+    }
+
+    /// Returns the number of times the plugin called `mark_dirty` since this tracker was created.
+    #[inline]
+    pub fn change_count(&self) -> u64 {
+        self.changes
+    }
+
+    /// Returns `true` if the plugin called `mark_dirty` at least once.
+    #[inline]
+    pub fn has_marked_dirty(&self) -> bool {
+        self.has_marked
+    }
+
+    /// Returns the policy this tracker uses to decide if the plugin's state is dirty.
+    #[inline]
+    pub fn policy(&self) -> DirtinessPolicy {
+        self.policy
+    }
+
+    /// Sets the policy this tracker uses to decide if the plugin's state is dirty.
+    #[inline]
+    pub fn set_policy(&mut self, policy: DirtinessPolicy) {
+        self.policy = policy;
+    }
+}
+
+/// Gives access to a host's [`StateDirtiness`] tracker.
+///
+/// This must be implemented by the host's [`MainThread`](HostHandlers::MainThread) handler for
+/// its instances to be usable with [`save_all_dirty`].
+pub trait HostStateDirtiness {
+    /// Returns the dirtiness tracker of this plugin instance.
+    fn state_dirtiness(&self) -> &StateDirtiness;
+
+    /// Returns the dirtiness tracker of this plugin instance, mutably.
+    fn state_dirtiness_mut(&mut self) -> &mut StateDirtiness;
+}
+
+/// Saves the state of every given plugin instance that is dirty, on the main thread.
+///
+/// Each dirty instance's state is saved to a new buffer, which is returned alongside the index of
+/// the instance in `instances`, in order. Instances whose state was saved successfully are then
+/// marked as saved, with the change counter they had before being saved: a plugin that calls
+/// `mark_dirty` while saving stays dirty.
+///
+/// Instances that aren't dirty, or that don't implement the state extension, are skipped.
+pub fn save_all_dirty<H: HostHandlers>(
+    instances: &mut [&mut PluginInstance<H>],
+) -> Vec<(usize, Result<Vec<u8>, StateError>)>
+where
+    for<'a> <H as HostHandlers>::MainThread<'a>: HostStateDirtiness,
+{
+    let mut saved = Vec::new();
+
+    for (index, instance) in instances.iter_mut().enumerate() {
+        let (is_dirty, changes) = instance.access_handler_unchecked(|h| {
+            let dirtiness = h.state_dirtiness();
+            (dirtiness.is_dirty(), dirtiness.change_count())
+        });
+
+        if !is_dirty {
+            continue;
+        }
+
+        let mut handle = instance.plugin_handle_unchecked();
+        let Some(state) = handle.get_extension::<PluginState>() else {
+            continue;
+        };
+
+        let mut buffer = Vec::new();
+        let result = state.save(&mut handle, &mut buffer).map(|()| buffer);
+
+        if result.is_ok() {
+            instance.access_handler_mut_unchecked(|h| {
+                h.state_dirtiness_mut().saved_changes = changes;
+            });
+        }
+
+        saved.push((index, result));
+    }
+
+    saved
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plugins_are_dirty_until_they_mark_dirty() {
+        let mut dirtiness = StateDirtiness::new();
+        assert!(dirtiness.is_dirty());
+
+        dirtiness.mark_saved();
+        assert!(dirtiness.is_dirty());
+
+        dirtiness.mark_dirty();
+        assert!(dirtiness.is_dirty());
+        assert_eq!(dirtiness.change_count(), 1);
+
+        dirtiness.mark_saved();
+        assert!(!dirtiness.is_dirty());
+    }
+
+    #[test]
+    fn policy_overrides_the_marks() {
+        let mut dirtiness = StateDirtiness::new();
+
+        dirtiness.set_policy(DirtinessPolicy::Trusted);
+        assert!(!dirtiness.is_dirty());
+
+        dirtiness.mark_dirty();
+        dirtiness.mark_saved();
+        dirtiness.set_policy(DirtinessPolicy::AlwaysDirty);
+        assert!(dirtiness.is_dirty());
+    }
+}
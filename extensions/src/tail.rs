@@ -108,6 +108,8 @@ mod host {
 
     impl PluginTail {
         /// Returns the plugin's [`TailLength`].
+        ///
+        /// This must be called on the audio thread, while the plugin is active.
         #[inline]
         pub fn get(&self, plugin: &PluginAudioProcessorHandle) -> TailLength {
             match plugin.use_extension(&self.0).get {
@@ -141,7 +143,7 @@ mod host {
     where
         for<'a> <H as HostHandlers>::AudioProcessor<'a>: HostTailImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
+        HostWrapper::<H>::handle_named(host, "clap_host_tail.changed", |host| {
             host.audio_processor()?.as_mut().changed();
             Ok(())
         });
@@ -190,9 +192,11 @@ mod plugin {
     where
         for<'a> P::AudioProcessor<'a>: PluginTailImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| {
-            Ok(plugin.audio_processor()?.as_ref().get().to_raw())
-        })
+        PluginWrapper::<P>::handle_audio_processor(
+            plugin,
+            "clap_plugin_tail.get",
+            |audio_processor, _, _| Ok(audio_processor.get().to_raw()),
+        )
         .unwrap_or_else(|| TailLength::default().to_raw())
     }
 }
@@ -65,7 +65,10 @@ mod host {
     where
         for<'a> <H as HostHandlers>::Shared<'a>: HostThreadCheckImpl,
     {
-        HostWrapper::<H>::handle(host, |host| Ok(host.shared().is_main_thread())).unwrap_or(false)
+        HostWrapper::<H>::handle_named(host, "clap_host_thread_check.is_main_thread", |host| {
+            Ok(host.shared().is_main_thread())
+        })
+        .unwrap_or(false)
     }
 
     #[allow(clippy::missing_safety_doc)]
@@ -73,7 +76,10 @@ mod host {
     where
         for<'a> <H as HostHandlers>::Shared<'a>: HostThreadCheckImpl,
     {
-        HostWrapper::<H>::handle(host, |host| Ok(host.shared().is_audio_thread())).unwrap_or(false)
+        HostWrapper::<H>::handle_named(host, "clap_host_thread_check.is_audio_thread", |host| {
+            Ok(host.shared().is_audio_thread())
+        })
+        .unwrap_or(false)
     }
 }
 
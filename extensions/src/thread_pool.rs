@@ -83,8 +83,8 @@ mod plugin {
     where
         for<'a> P::Shared<'a>: PluginThreadPoolImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| {
-            plugin.shared().exec(task_index);
+        PluginWrapper::<P>::handle_shared(plugin, "clap_plugin_thread_pool.exec", |shared, _| {
+            shared.exec(task_index);
             Ok(())
         });
     }
@@ -166,7 +166,7 @@ mod host {
     where
         for<'a> <H as HostHandlers>::AudioProcessor<'a>: HostThreadPoolImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
+        HostWrapper::<H>::handle_named(host, "clap_host_thread_pool.request_exec", |host| {
             Ok(host
                 .audio_processor()?
                 .as_mut()
@@ -165,10 +165,14 @@ mod plugin {
     where
         for<'a> P::MainThread<'a>: PluginTimerImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| {
-            plugin.main_thread().as_mut().on_timer(TimerId(timer_id));
-            Ok(())
-        });
+        PluginWrapper::<P>::handle_main_thread(
+            plugin,
+            "clap_plugin_timer_support.on_timer",
+            |main_thread, _, _| {
+                main_thread.on_timer(TimerId(timer_id));
+                Ok(())
+            },
+        );
     }
 }
 #[cfg(feature = "clack-plugin")]
@@ -227,8 +231,10 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostTimerImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
-            match host.main_thread().as_mut().register_timer(period_ms) {
+        HostWrapper::<H>::handle_main_thread(
+            host,
+            "clap_host_timer_support.register_timer",
+            |host| match host.main_thread().as_mut().register_timer(period_ms) {
                 Ok(id) => {
                     *timer_id = id.0;
                     Ok(true)
@@ -237,8 +243,8 @@ mod host {
                     *timer_id = u32::MAX;
                     Ok(false)
                 }
-            }
-        })
+            },
+        )
         .unwrap_or(false)
     }
 
@@ -250,13 +256,17 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostTimerImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
-            Ok(host
-                .main_thread()
-                .as_mut()
-                .unregister_timer(TimerId(timer_id))
-                .is_ok())
-        })
+        HostWrapper::<H>::handle_main_thread(
+            host,
+            "clap_host_timer_support.unregister_timer",
+            |host| {
+                Ok(host
+                    .main_thread()
+                    .as_mut()
+                    .unregister_timer(TimerId(timer_id))
+                    .is_ok())
+            },
+        )
         .unwrap_or(false)
     }
 
@@ -12,6 +12,27 @@ pub(crate) fn data_from_array_buf<const N: usize>(data: &[c_char; N]) -> &[u8] {
         .unwrap_or(data)
 }
 
+/// Ensures the given name can be written into a CLAP name buffer, i.e. that it does not contain
+/// any NUL byte, and that it fits in [`CLAP_NAME_SIZE`](clap_sys::string_sizes::CLAP_NAME_SIZE)
+/// (including the NUL terminator).
+///
+/// # Panics
+///
+/// This function panics if the name is invalid. When used in a const context, this results in a
+/// compile-time error instead.
+pub(crate) const fn validate_name(name: &[u8]) {
+    assert!(
+        name.len() < clap_sys::string_sizes::CLAP_NAME_SIZE,
+        "Name is too long"
+    );
+
+    let mut i = 0;
+    while i < name.len() {
+        assert!(name[i] != 0, "Name must not contain NUL bytes");
+        i += 1;
+    }
+}
+
 /// # Safety
 ///
 /// The pointer must be non-null and well-aligned. However, the array doesn't need to be initialized.
@@ -46,3 +67,41 @@ pub(crate) unsafe fn slice_from_external_parts_mut<'a, T>(data: *mut T, len: usi
 
     core::slice::from_raw_parts_mut(data, len)
 }
+
+/// Matches the entries of two lists by their ID, e.g. to compare the ports or parameters a plugin
+/// declared before and after a rescan.
+///
+/// `removed` is called for each entry that is only in `old`, then `added` for each entry that is
+/// only in `new`, then `kept` for each entry that is in both, in the order of `new`. All closures
+/// are also given the index of the entries in their respective lists.
+pub(crate) fn diff_by_id<T>(
+    old: &[T],
+    new: &[T],
+    id: impl Fn(&T) -> clack_common::utils::ClapId,
+    mut removed: impl FnMut(usize, &T),
+    mut added: impl FnMut(usize, &T),
+    mut kept: impl FnMut((usize, &T), (usize, &T)),
+) {
+    use std::collections::HashMap;
+
+    let old_indexes: HashMap<_, _> = old.iter().enumerate().map(|(i, e)| (id(e), i)).collect();
+    let new_indexes: HashMap<_, _> = new.iter().enumerate().map(|(i, e)| (id(e), i)).collect();
+
+    for (index, entry) in old.iter().enumerate() {
+        if !new_indexes.contains_key(&id(entry)) {
+            removed(index, entry);
+        }
+    }
+
+    for (index, entry) in new.iter().enumerate() {
+        if !old_indexes.contains_key(&id(entry)) {
+            added(index, entry);
+        }
+    }
+
+    for (new_index, entry) in new.iter().enumerate() {
+        if let Some(&old_index) = old_indexes.get(&id(entry)) {
+            kept((old_index, &old[old_index]), (new_index, entry));
+        }
+    }
+}
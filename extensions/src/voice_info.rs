@@ -101,12 +101,11 @@ mod host {
         ///
         /// If the plugin failed to provide any Voice Information, this returns [`None`].
         pub fn get(&self, plugin: &mut PluginMainThreadHandle) -> Option<VoiceInfo> {
-            let info = MaybeUninit::zeroed();
+            let mut info = MaybeUninit::zeroed();
 
             // SAFETY: This type ensures the function pointer is valid.
-            let success = unsafe {
-                plugin.use_extension(&self.0).get?(plugin.as_raw(), info.as_ptr() as *mut _)
-            };
+            let success =
+                unsafe { plugin.use_extension(&self.0).get?(plugin.as_raw(), info.as_mut_ptr()) };
 
             // SAFETY: we only read the buffer if the plugin returned a successful state
             unsafe { success.then(|| VoiceInfo::from_raw(info.assume_init_ref())) }
@@ -136,7 +135,7 @@ mod host {
     where
         for<'a> <H as HostHandlers>::MainThread<'a>: HostVoiceInfoImpl,
     {
-        HostWrapper::<H>::handle(host, |host| {
+        HostWrapper::<H>::handle_main_thread(host, "clap_host_voice_info.changed", |host| {
             host.main_thread().as_mut().changed();
             Ok(())
         });
@@ -189,13 +188,17 @@ mod plugin {
     where
         for<'a> P::MainThread<'a>: PluginVoiceInfoImpl,
     {
-        PluginWrapper::<P>::handle(plugin, |plugin| match plugin.main_thread().as_mut().get() {
-            None => Ok(false),
-            Some(voice_info) => {
-                *info = voice_info.to_raw();
-                Ok(true)
-            }
-        })
+        PluginWrapper::<P>::handle_main_thread(
+            plugin,
+            "clap_plugin_voice_info.get",
+            |main_thread, _, _| match main_thread.get() {
+                None => Ok(false),
+                Some(voice_info) => {
+                    *info = voice_info.to_raw();
+                    Ok(true)
+                }
+            },
+        )
         .unwrap_or(false)
     }
 }
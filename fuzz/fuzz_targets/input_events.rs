@@ -0,0 +1,134 @@
+//! Feeds adversarial event lists through [`InputEvents`], and downcasts every event using all of the
+//! typed accessors.
+//!
+//! Events are built from arbitrary bytes, which means they can have any type or space ID, and any
+//! size (including sizes smaller than the header itself). The only guarantee this target upholds
+//! is that the memory backing each event is at least as large as both its header and its declared
+//! size, as a misbehaving host lying about pointers can't be defended against.
+
+#![no_main]
+
+use clack_common::events::event_types::*;
+use clack_common::events::io::{InputEventBuffer, InputEvents};
+use clack_common::events::spaces::CoreEventSpace;
+use clack_common::events::{Event, UnknownEvent};
+use clap_sys::events::clap_event_header;
+use libfuzzer_sys::fuzz_target;
+
+/// Backing storage for a single event, aligned to 8 bytes like real CLAP events.
+struct EventStorage(Vec<u64>);
+
+impl EventStorage {
+    fn new(bytes: &[u8]) -> Self {
+        let header_size = core::mem::size_of::<clap_event_header>();
+        let mut header_bytes = [0; 4];
+        let available = bytes.len().min(4);
+        header_bytes[..available].copy_from_slice(&bytes[..available]);
+        let declared_size = u32::from_ne_bytes(header_bytes) as usize;
+
+        // Cap the declared size to keep allocations reasonable.
+        let declared_size = declared_size.min(4096);
+        let total_size = bytes.len().max(header_size).max(declared_size);
+
+        let mut storage = vec![0u64; total_size.div_ceil(8)];
+        // SAFETY: the storage is at least total_size bytes long, and u8 has no alignment requirement.
+        let storage_bytes = unsafe {
+            core::slice::from_raw_parts_mut(storage.as_mut_ptr().cast::<u8>(), total_size)
+        };
+        storage_bytes[..bytes.len()].copy_from_slice(bytes);
+
+        // Write back the capped size if it had to be capped.
+        if u32::from_ne_bytes(header_bytes) as usize > declared_size {
+            storage_bytes[..4].copy_from_slice(&(declared_size as u32).to_ne_bytes());
+        }
+
+        Self(storage)
+    }
+
+    fn as_unknown(&self) -> &UnknownEvent {
+        // SAFETY: the storage is aligned, at least as big as a header, and at least as big as the
+        // size declared in the header.
+        unsafe { UnknownEvent::from_raw(self.0.as_ptr().cast()) }
+    }
+}
+
+struct AdversarialEvents(Vec<EventStorage>);
+
+impl InputEventBuffer for AdversarialEvents {
+    fn len(&self) -> u32 {
+        self.0.len() as u32
+    }
+
+    fn get(&self, index: u32) -> Option<&UnknownEvent> {
+        self.0.get(index as usize).map(|e| e.as_unknown())
+    }
+}
+
+fn check_downcast<E>(event: &UnknownEvent)
+where
+    E: for<'a> Event<EventSpace<'a> = CoreEventSpace<'a>> + core::fmt::Debug,
+{
+    if let Some(e) = event.as_event::<E>() {
+        assert_eq!(event.header().size() as usize, core::mem::size_of::<E>());
+        let _ = format!("{e:?}");
+    }
+}
+
+fn drive_core_event(event: CoreEventSpace) {
+    // Debug impls go through every accessor of the event types.
+    let _ = format!("{event:?}");
+
+    match event {
+        CoreEventSpace::NoteOn(e) => {
+            let _ = (e.pckn(), e.velocity());
+        }
+        CoreEventSpace::NoteExpression(e) => {
+            let _ = (e.expression_type(), e.value());
+        }
+        CoreEventSpace::ParamValue(e) => {
+            let _ = (e.param_id(), e.value(), e.cookie());
+        }
+        CoreEventSpace::Transport(e) => {
+            let _ = (e.flags, e.tempo, e.song_pos_beats);
+        }
+        CoreEventSpace::MidiSysEx(e) => {
+            // The buffer pointer comes from arbitrary bytes: only inspect it without dereferencing.
+            let _ = (e.buffer_ptr(), e.buffer_size(), e.port_index());
+        }
+        _ => {}
+    }
+}
+
+fuzz_target!(|data: Vec<Vec<u8>>| {
+    let events = AdversarialEvents(data.iter().map(|b| EventStorage::new(b)).collect());
+    let input_events = InputEvents::from_buffer(&events);
+
+    for event in &input_events {
+        let _ = event.header().flags();
+        let _ = event.header().space_id();
+        let _ = event.header().payload_size();
+
+        if let Some(core_event) = event.as_core_event() {
+            drive_core_event(core_event);
+        }
+
+        check_downcast::<NoteOnEvent>(event);
+        check_downcast::<NoteOffEvent>(event);
+        check_downcast::<NoteChokeEvent>(event);
+        check_downcast::<NoteEndEvent>(event);
+        check_downcast::<NoteExpressionEvent>(event);
+        check_downcast::<ParamValueEvent>(event);
+        check_downcast::<ParamModEvent>(event);
+        check_downcast::<ParamGestureBeginEvent>(event);
+        check_downcast::<ParamGestureEndEvent>(event);
+        check_downcast::<TransportEvent>(event);
+        check_downcast::<MidiEvent>(event);
+        check_downcast::<Midi2Event>(event);
+        check_downcast::<MidiSysExEvent>(event);
+    }
+
+    for batch in input_events.batch() {
+        let _ = batch.sample_bounds();
+        let _ = batch.events().count();
+    }
+});
@@ -0,0 +1,208 @@
+//! Round-trips arbitrary parameter information and display strings between a Clack plugin and a
+//! Clack host, through the `params` extension's [`ParamInfoWriter`] and [`ParamDisplayWriter`].
+//!
+//! This checks that any name, module or display text (including ones that are too long or contain
+//! null bytes) is always properly truncated and null-terminated, regardless of the size of the
+//! buffer the host provides.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use libfuzzer_sys::fuzz_target;
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::mem::MaybeUninit;
+use std::sync::Mutex;
+
+#[derive(Arbitrary, Debug, Clone)]
+struct ParamInput {
+    id: u32,
+    flags: u32,
+    name: Vec<u8>,
+    module: Vec<u8>,
+    min_value: f64,
+    max_value: f64,
+    default_value: f64,
+    display: String,
+    display_buffer_size: u16,
+}
+
+static CURRENT_INPUT: Mutex<Option<ParamInput>> = Mutex::new(None);
+
+fn current_input() -> ParamInput {
+    CURRENT_INPUT.lock().unwrap().clone().unwrap()
+}
+
+pub struct FuzzPlugin;
+pub struct FuzzPluginMainThread;
+pub struct FuzzPluginAudioProcessor;
+
+impl Plugin for FuzzPlugin {
+    type AudioProcessor<'a> = FuzzPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = FuzzPluginMainThread;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for FuzzPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.fuzz", "Fuzz")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(FuzzPluginMainThread)
+    }
+}
+
+impl PluginMainThread<'_, ()> for FuzzPluginMainThread {}
+
+impl<'a> PluginAudioProcessor<'a, (), FuzzPluginMainThread> for FuzzPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut FuzzPluginMainThread,
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Sleep)
+    }
+}
+
+impl PluginMainThreadParams for FuzzPluginMainThread {
+    fn count(&mut self) -> u32 {
+        1
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        let input = current_input();
+        let Some(id) = ClapId::from_raw(input.id) else {
+            return;
+        };
+
+        if param_index != 0 {
+            return;
+        }
+
+        info.set(&ParamInfo {
+            id,
+            flags: ParamInfoFlags::from_bits_truncate(input.flags),
+            cookie: Default::default(),
+            name: &input.name,
+            module: &input.module,
+            min_value: input.min_value,
+            max_value: input.max_value,
+            default_value: input.default_value,
+        })
+    }
+
+    fn get_value(&mut self, _param_id: ClapId) -> Option<f64> {
+        Some(current_input().default_value)
+    }
+
+    fn value_to_text(
+        &mut self,
+        _param_id: ClapId,
+        _value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        writer.write_str(&current_input().display)
+    }
+
+    fn text_to_value(&mut self, _param_id: ClapId, _text: &CStr) -> Option<f64> {
+        None
+    }
+
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+impl PluginAudioProcessorParams for FuzzPluginAudioProcessor {
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+static FUZZ_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<FuzzPlugin>);
+
+struct FuzzHost;
+struct FuzzHostShared;
+
+impl SharedHandler<'_> for FuzzHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostHandlers for FuzzHost {
+    type Shared<'a> = FuzzHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+/// Returns the bytes the host is expected to receive, after truncation and null-termination.
+fn expected_bytes(bytes: &[u8], max_len: usize) -> &[u8] {
+    let bytes = &bytes[..bytes.len().min(max_len)];
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    &bytes[..end]
+}
+
+fuzz_target!(|input: ParamInput| {
+    *CURRENT_INPUT.lock().unwrap() = Some(input.clone());
+
+    // SAFETY: the entry is a valid Clack entry.
+    let bundle = unsafe { PluginBundle::load_from_raw(&FUZZ_ENTRY, "") }.unwrap();
+    let host_info = HostInfo::new("Fuzz host", "", "", "").unwrap();
+
+    let mut instance = PluginInstance::<FuzzHost>::new(
+        |_| FuzzHostShared,
+        |_| (),
+        &bundle,
+        c"org.rust-audio.clack.fuzz",
+        &host_info,
+    )
+    .unwrap();
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    let mut info_buffer = ParamInfoBuffer::new();
+    match params.get_info(&mut handle, 0, &mut info_buffer) {
+        None => assert_eq!(input.id, u32::MAX),
+        Some(info) => {
+            assert_eq!(info.id.get(), input.id);
+            assert_eq!(info.flags.bits(), ParamInfoFlags::from_bits_truncate(input.flags).bits());
+            assert_eq!(info.name, expected_bytes(&input.name, 255));
+            assert_eq!(info.module, expected_bytes(&input.module, 1023));
+        }
+    }
+
+    let mut display_buffer = vec![MaybeUninit::uninit(); input.display_buffer_size as usize];
+    let display = params.value_to_text(&mut handle, ClapId::new(0), 0.0, &mut display_buffer);
+
+    let max_display_len = (input.display_buffer_size as usize).saturating_sub(1);
+    let expected_display = expected_bytes(input.display.as_bytes(), max_display_len);
+
+    match display {
+        Ok(display) => assert_eq!(display, expected_display),
+        Err(_) => assert!(input.display.is_empty() || max_display_len == 0),
+    }
+});
@@ -0,0 +1,113 @@
+//! Compares instantiating many plugins serially with [`ParallelInstantiator`], using a plugin
+//! that performs some expensive work (e.g. loading samples) when it is created.
+//!
+//! Run with `cargo bench -p clack-host --bench parallel_instantiation`.
+
+use clack_host::plugin::parallel::ParallelInstantiator;
+use clack_host::prelude::*;
+use clack_plugin::entry::{Entry, EntryFactories, EntryLoadError};
+use clack_plugin::factory::plugin::{PluginFactory, PluginFactoryWrapper};
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::time::{Duration, Instant};
+
+const INSTANCE_COUNT: usize = 60;
+const CREATION_TIME: Duration = Duration::from_millis(5);
+
+pub struct SlowPlugin;
+
+impl Plugin for SlowPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+pub struct SlowEntry {
+    plugin_factory: PluginFactoryWrapper<SlowFactory>,
+}
+
+impl Entry for SlowEntry {
+    fn new(_plugin_path: &CStr) -> Result<Self, EntryLoadError> {
+        Ok(Self {
+            plugin_factory: PluginFactoryWrapper::new(SlowFactory {
+                descriptor: PluginDescriptor::new("org.rust-audio.clack.slow", "Slow"),
+            }),
+        })
+    }
+
+    fn declare_factories<'a>(&'a self, builder: &mut EntryFactories<'a>) {
+        builder.register_factory(&self.plugin_factory);
+    }
+}
+
+struct SlowFactory {
+    descriptor: PluginDescriptor,
+}
+
+impl PluginFactory for SlowFactory {
+    fn plugin_count(&self) -> u32 {
+        1
+    }
+
+    fn plugin_descriptor(&self, index: u32) -> Option<&PluginDescriptor> {
+        (index == 0).then_some(&self.descriptor)
+    }
+
+    fn create_plugin<'a>(
+        &'a self,
+        host_info: clack_plugin::host::HostInfo<'a>,
+        plugin_id: &CStr,
+    ) -> Option<clack_plugin::plugin::PluginInstance<'a>> {
+        if plugin_id != self.descriptor.id() {
+            return None;
+        }
+
+        // Simulate some expensive, thread-safe work performed when the plugin is created.
+        std::thread::sleep(CREATION_TIME);
+
+        Some(clack_plugin::plugin::PluginInstance::new::<SlowPlugin>(
+            host_info,
+            &self.descriptor,
+            |_| Ok(()),
+            |_, _| Ok(()),
+        ))
+    }
+}
+
+static SLOW_ENTRY: EntryDescriptor = clack_plugin::clack_entry!(SlowEntry);
+
+fn main() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&SLOW_ENTRY, "/slow.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+    let plugin_id = CStr::from_bytes_with_nul(b"org.rust-audio.clack.slow\0").unwrap();
+    let main_thread = MainThreadToken::acquire().unwrap();
+
+    let start = Instant::now();
+    let serial: Vec<_> = (0..INSTANCE_COUNT)
+        .map(|_| PluginInstance::<()>::new(|_| (), |_| (), &bundle, plugin_id, &host_info))
+        .collect();
+    let serial_time = start.elapsed();
+    assert!(serial.iter().all(Result::is_ok));
+    drop(serial);
+
+    let mut instantiator = ParallelInstantiator::<()>::new(&host_info);
+    for _ in 0..INSTANCE_COUNT {
+        instantiator.push(&bundle, plugin_id);
+    }
+
+    let start = Instant::now();
+    let parallel = instantiator.instantiate(&main_thread, |_, _| (), |_, _| (), |_| {});
+    let parallel_time = start.elapsed();
+    assert!(parallel.iter().all(Result::is_ok));
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    println!("{INSTANCE_COUNT} instances, {threads} threads");
+    println!("serial:   {serial_time:>10.2?}");
+    println!("parallel: {parallel_time:>10.2?}");
+    println!(
+        "speed-up: {:>10.2}x",
+        serial_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+}
@@ -98,9 +98,13 @@ fn standard_clap_paths() -> Vec<PathBuf> {
 
 /// Returns `true` if the given entry could refer to a CLAP bundle.
 ///
-/// CLAP bundles are files that end with the `.clap` extension.
+/// CLAP bundles are files that end with the `.clap` extension. On macOS, they are directories
+/// instead.
 fn is_clap_bundle(dir_entry: &DirEntry) -> bool {
-    dir_entry.file_type().is_file() && dir_entry.file_name().to_string_lossy().ends_with(".clap")
+    let file_type = dir_entry.file_type();
+    let is_bundle_type = file_type.is_file() || (cfg!(target_os = "macos") && file_type.is_dir());
+
+    is_bundle_type && dir_entry.file_name().to_string_lossy().ends_with(".clap")
 }
 
 /// Search the given directories' contents, and returns a list of all the files that could be CLAP
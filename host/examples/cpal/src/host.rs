@@ -1,12 +1,6 @@
 use crate::discovery::FoundBundlePlugin;
 
-use clack_extensions::audio_ports::{HostAudioPortsImpl, PluginAudioPorts, RescanType};
-use clack_extensions::gui::{GuiSize, HostGui, PluginGui};
-use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
-use clack_extensions::params::{
-    HostParams, HostParamsImplMainThread, HostParamsImplShared, ParamClearFlags, ParamRescanFlags,
-};
-use clack_extensions::timer::{HostTimer, PluginTimer};
+use clack_extensions::prelude::*;
 use clack_host::prelude::*;
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use std::error::Error;
@@ -26,7 +20,6 @@ mod gui;
 mod timer;
 
 use audio::*;
-use clack_extensions::note_ports::{HostNotePortsImpl, NoteDialects, NotePortRescanFlags};
 use gui::*;
 use timer::*;
 
@@ -162,6 +155,7 @@ pub fn run(plugin: FoundBundlePlugin) -> Result<(), Box<dyn Error>> {
     let host_info = host_info();
     let plugin_id = CString::new(plugin.plugin.id.as_str())?;
     let (sender, receiver) = unbounded();
+    let main_thread = MainThreadToken::acquire().ok_or("Host must run on the main thread")?;
 
     let mut instance = PluginInstance::<CpalHost>::new(
         |_| CpalHostShared::new(sender.clone()),
@@ -171,22 +165,22 @@ pub fn run(plugin: FoundBundlePlugin) -> Result<(), Box<dyn Error>> {
         &host_info,
     )?;
 
-    let _stream = activate_to_stream(&mut instance)?;
+    let _stream = activate_to_stream(&mut instance, &main_thread)?;
 
     let gui = instance
-        .access_handler(|h| h.gui)
-        .map(|gui| Gui::new(gui, &mut instance.plugin_handle()));
+        .access_handler_checked(&main_thread, |h| h.gui)
+        .map(|gui| Gui::new(gui, &mut instance.plugin_handle_checked(&main_thread)));
 
     let gui = gui.and_then(|gui| Some((gui.needs_floating()?, gui)));
 
     let Some((needs_floating, gui)) = gui else {
-        return run_cli(instance, receiver);
+        return run_cli(instance, &main_thread, receiver);
     };
 
     if needs_floating {
-        run_gui_floating(instance, receiver, gui)
+        run_gui_floating(instance, &main_thread, receiver, gui)
     } else {
-        run_gui_embedded(instance, receiver, gui)
+        run_gui_embedded(instance, &main_thread, receiver, gui)
     }
 }
 
@@ -196,15 +190,18 @@ pub fn run(plugin: FoundBundlePlugin) -> Result<(), Box<dyn Error>> {
 // Note: not very-well tested
 fn run_gui_floating(
     mut instance: PluginInstance<CpalHost>,
+    main_thread: &MainThreadToken,
     receiver: Receiver<MainThreadMessage>,
     mut gui: Gui,
 ) -> Result<(), Box<dyn Error>> {
     println!("Opening GUI in floating mode");
-    gui.open_floating(&mut instance.plugin_handle())?;
+    gui.open_floating(&mut instance.plugin_handle_checked(main_thread))?;
 
     for message in receiver {
         match message {
-            MainThreadMessage::RunOnMainThread => instance.call_on_main_thread_callback(),
+            MainThreadMessage::RunOnMainThread => {
+                instance.call_on_main_thread_callback_checked(main_thread)
+            }
             MainThreadMessage::GuiClosed { .. } => {
                 println!("Window closed!");
                 break;
@@ -213,7 +210,7 @@ fn run_gui_floating(
         }
     }
 
-    gui.destroy(&mut instance.plugin_handle());
+    gui.destroy(&mut instance.plugin_handle_checked(main_thread));
 
     Ok(())
 }
@@ -223,6 +220,7 @@ fn run_gui_floating(
 /// This blocks until the window is closed.
 fn run_gui_embedded(
     mut instance: PluginInstance<CpalHost>,
+    main_thread: &MainThreadToken,
     receiver: Receiver<MainThreadMessage>,
     mut gui: Gui,
 ) -> Result<(), Box<dyn Error>> {
@@ -230,17 +228,26 @@ fn run_gui_embedded(
 
     let event_loop = EventLoop::new()?;
 
-    let mut window = Some(gui.open_embedded(&mut instance.plugin_handle(), &event_loop)?);
+    let mut window = Some(gui.open_embedded(
+        &mut instance.plugin_handle_checked(main_thread),
+        &event_loop,
+    )?);
 
     let uses_logical_pixels = gui.configuration.unwrap().api_type.uses_logical_size();
 
-    let timers = instance.access_handler(|h| h.timer_support.map(|ext| (h.timers.clone(), ext)));
+    let timers = instance.access_handler_checked(main_thread, |h| {
+        h.timer_support.map(|ext| (h.timers.clone(), ext))
+    });
+
+    let main_thread = *main_thread;
 
     #[allow(deprecated)]
     event_loop.run(move |event, target| {
         while let Ok(message) = receiver.try_recv() {
             match message {
-                MainThreadMessage::RunOnMainThread => instance.call_on_main_thread_callback(),
+                MainThreadMessage::RunOnMainThread => {
+                    instance.call_on_main_thread_callback_checked(&main_thread)
+                }
                 MainThreadMessage::GuiRequestResized { new_size } => {
                     let new_size: Size = if uses_logical_pixels {
                         LogicalSize {
@@ -266,7 +273,7 @@ fn run_gui_embedded(
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => {
                     println!("Plugin window closed, stopping.");
-                    gui.destroy(&mut instance.plugin_handle());
+                    gui.destroy(&mut instance.plugin_handle_checked(&main_thread));
                     window.take(); // Drop the window
                     return;
                 }
@@ -278,7 +285,11 @@ fn run_gui_embedded(
                     let window = window.as_ref().unwrap();
                     let scale_factor = window.scale_factor();
 
-                    let actual_size = gui.resize(&mut instance.plugin_handle(), size, scale_factor);
+                    let actual_size = gui.resize(
+                        &mut instance.plugin_handle_checked(&main_thread),
+                        size,
+                        scale_factor,
+                    );
 
                     if actual_size != size.into() {
                         let _ = window.request_inner_size(actual_size);
@@ -287,13 +298,13 @@ fn run_gui_embedded(
                 _ => {}
             },
             Event::LoopExiting => {
-                gui.destroy(&mut instance.plugin_handle());
+                gui.destroy(&mut instance.plugin_handle_checked(&main_thread));
             }
             _ => {}
         }
 
         let wait_duration = if let Some((timers, timer_ext)) = &timers {
-            timers.tick_timers(timer_ext, &mut instance.plugin_handle());
+            timers.tick_timers(timer_ext, &mut instance.plugin_handle_checked(&main_thread));
 
             timers
                 .smallest_duration()
@@ -316,13 +327,14 @@ fn run_gui_embedded(
 /// This blocks forever, until the process is killed.
 fn run_cli(
     mut instance: PluginInstance<CpalHost>,
+    main_thread: &MainThreadToken,
     receiver: Receiver<MainThreadMessage>,
 ) -> Result<(), Box<dyn Error>> {
     println!("Running headless. Press Ctrl+C to stop processing.");
 
     for message in receiver {
         if let MainThreadMessage::RunOnMainThread = message {
-            instance.call_on_main_thread_callback()
+            instance.call_on_main_thread_callback_checked(main_thread)
         }
     }
 
@@ -1,4 +1,5 @@
 use crate::host::CpalHost;
+use clack_host::audio_thread;
 use clack_host::prelude::*;
 use clack_host::process::StartedPluginAudioProcessor;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
@@ -21,19 +22,20 @@ use midi::*;
 /// Activates the given plugin instance, and outputs its processed audio to a new CPAL stream.
 pub fn activate_to_stream(
     instance: &mut PluginInstance<CpalHost>,
+    main_thread: &MainThreadToken,
 ) -> Result<Stream, Box<dyn Error>> {
     // Initialize CPAL
     let cpal_host = cpal::default_host();
 
     let output_device = cpal_host.default_output_device().unwrap();
 
-    let config = FullAudioConfig::find_best_from(&output_device, instance)?;
+    let config = FullAudioConfig::find_best_from(&output_device, instance, main_thread)?;
     println!("Using negociated audio output settings: {config}");
 
-    let midi = MidiReceiver::new(44_100, instance)?;
+    let midi = MidiReceiver::new(44_100, instance, main_thread)?;
 
     let plugin_audio_processor = instance
-        .activate(|_, _| (), config.as_clack_plugin_config())?
+        .activate_checked(main_thread, |_, _| (), config.as_clack_plugin_config())?
         .start_processing()?;
 
     let sample_format = config.sample_format;
@@ -96,10 +98,25 @@ fn build_output_stream_for_sample_format(
 }
 
 /// Creates a stream runner closure that processes the given sample type.
+///
+/// The audio thread is owned by CPAL, so it is elevated to real-time priority from within the
+/// first callback. Failing to do so only results in a warning.
 fn make_stream_runner<S: FromSample<f32>>(
     mut audio_processor: StreamAudioProcessor,
 ) -> impl FnMut(&mut [S], &OutputCallbackInfo) {
-    move |data, _info| audio_processor.process(data)
+    let mut promoted = false;
+
+    move |data, _info| {
+        if !promoted {
+            promoted = true;
+
+            if let Err(e) = audio_thread::promote_current_thread() {
+                eprintln!("Warning: {e}. Audio may glitch under load.");
+            }
+        }
+
+        audio_processor.process(data)
+    }
 }
 
 /// Holds all of the data, buffers and state that are going to live and get used on the audio thread.
@@ -110,6 +127,8 @@ struct StreamAudioProcessor {
     buffers: HostAudioBuffers,
     /// The MIDI event receiver.
     midi_receiver: Option<MidiReceiver>,
+    /// The buffers holding the events exchanged with the plugin.
+    events: EventIo,
     /// A steady frame counter, used by the plugin's process() method.
     steady_counter: u64,
 }
@@ -125,6 +144,7 @@ impl StreamAudioProcessor {
             audio_processor: plugin_instance,
             buffers: HostAudioBuffers::from_config(config),
             midi_receiver,
+            events: EventIo::with_capacity(128),
             steady_counter: 0,
         }
     }
@@ -143,17 +163,19 @@ impl StreamAudioProcessor {
 
         let (ins, mut outs) = self.buffers.prepare_plugin_buffers(data.len());
 
-        let events = if let Some(midi) = self.midi_receiver.as_mut() {
-            midi.receive_all_events(sample_count as u64)
-        } else {
-            InputEvents::empty()
-        };
+        // This example doesn't do anything with the plugin's output events: they are discarded.
+        self.events.reset();
+        if let Some(midi) = self.midi_receiver.as_mut() {
+            midi.receive_all_events(sample_count as u64, &mut self.events);
+        }
+
+        let (input_events, mut output_events) = self.events.split();
 
         match self.audio_processor.process(
             &ins,
             &mut outs,
-            &events,
-            &mut OutputEvents::void(),
+            &input_events,
+            &mut output_events,
             Some(self.steady_counter),
             None,
         ) {
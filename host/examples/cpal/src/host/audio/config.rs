@@ -3,7 +3,7 @@ use clack_extensions::audio_ports::{
     AudioPortFlags, AudioPortInfoBuffer, AudioPortType, PluginAudioPorts,
 };
 use clack_host::prelude::{
-    ClapId, PluginAudioConfiguration, PluginInstance, PluginMainThreadHandle,
+    ClapId, MainThreadToken, PluginAudioConfiguration, PluginInstance, PluginMainThreadHandle,
 };
 use cpal::traits::DeviceTrait;
 use cpal::{
@@ -42,11 +42,14 @@ impl FullAudioConfig {
     pub fn find_best_from(
         device: &Device,
         instance: &mut PluginInstance<CpalHost>,
+        main_thread: &MainThreadToken,
     ) -> Result<Self, Box<dyn Error>> {
         let best_cpal_configs = list_device_configs_ordered(device)?;
 
-        let input_ports = get_config_from_ports(&mut instance.plugin_handle(), true);
-        let output_ports = get_config_from_ports(&mut instance.plugin_handle(), false);
+        let input_ports =
+            get_config_from_ports(&mut instance.plugin_handle_checked(main_thread), true);
+        let output_ports =
+            get_config_from_ports(&mut instance.plugin_handle_checked(main_thread), false);
 
         Ok(find_matching_output_config(
             &best_cpal_configs,
@@ -155,6 +158,8 @@ pub enum AudioPortLayout {
     Mono,
     /// A pair of channels in stereo.
     Stereo,
+    /// A single control-voltage channel, carrying modulation signals rather than audio.
+    Cv,
     /// Another channel configuration with an arbitrary number of channels.
     Unsupported {
         /// The number of channels.
@@ -168,6 +173,7 @@ impl AudioPortLayout {
         match self {
             AudioPortLayout::Mono => 1,
             AudioPortLayout::Stereo => 2,
+            AudioPortLayout::Cv => 1,
             AudioPortLayout::Unsupported { channel_count } => *channel_count,
         }
     }
@@ -178,6 +184,7 @@ impl Display for AudioPortLayout {
         match self {
             AudioPortLayout::Mono => f.write_str("mono"),
             AudioPortLayout::Stereo => f.write_str("stereo"),
+            AudioPortLayout::Cv => f.write_str("CV"),
             AudioPortLayout::Unsupported { channel_count } => write!(f, "{channel_count}-channels"),
         }
     }
@@ -303,11 +310,22 @@ pub fn get_config_from_ports(
         let port_layout = match port_type {
             Some(l) if l == AudioPortType::MONO => AudioPortLayout::Mono,
             Some(l) if l == AudioPortType::STEREO => AudioPortLayout::Stereo,
+            Some(l) if l.is_cv() => AudioPortLayout::Cv,
             _ => AudioPortLayout::Unsupported {
                 channel_count: info.channel_count as u16,
             },
         };
 
+        // This example has no modulation routing: CV ports are still allocated (as silence for
+        // inputs), but we let the user know they are modulation jacks that aren't connected.
+        if port_layout == AudioPortLayout::Cv {
+            println!(
+                "Plugin {} port \"{}\" is a CV modulation jack. It will be left unconnected.",
+                if is_input { "input" } else { "output" },
+                String::from_utf8_lossy(info.name)
+            );
+        }
+
         // Store which port is the main one, and throw a warning if one already exists.
         if info.flags.contains(AudioPortFlags::IS_MAIN) && main_port_index.replace(i).is_some() {
             eprintln!("Warning: plugin defines multiple main ports. This shouldn't be allowed");
@@ -6,7 +6,7 @@ use clack_host::prelude::*;
 use midir::{Ignore, MidiInput, MidiInputConnection};
 use rtrb::{Consumer, RingBuffer};
 use std::error::Error;
-use wmidi::{MidiMessage, Velocity};
+use wmidi::MidiMessage;
 
 /// A MIDI message that was received at a given time.
 struct MidiEventMessage {
@@ -34,8 +34,6 @@ pub struct MidiReceiver {
     ///
     /// This is used to shut down all notes when a device is disconnected.
     abandoned: bool,
-    /// The buffer holding CLAP events to be fed to the plugin.
-    clap_events_buffer: EventBuffer,
     /// The audio sample rate. This is used to calculate the event's sample time from the device
     /// timestamp.
     sample_rate: u64,
@@ -53,8 +51,10 @@ impl MidiReceiver {
     pub fn new(
         sample_rate: u64,
         instance: &mut PluginInstance<CpalHost>,
+        main_thread: &MainThreadToken,
     ) -> Result<Option<Self>, Box<dyn Error>> {
-        let Some((main_plugin_note_port_index, prefers_midi)) = find_main_note_port_index(instance)
+        let Some((main_plugin_note_port_index, prefers_midi)) =
+            find_main_note_port_index(instance, main_thread)
         else {
             println!("Plugin does not have any Note inputs. It will not be fed any MIDI input.");
             return Ok(None);
@@ -108,7 +108,6 @@ impl MidiReceiver {
         )?;
 
         Ok(Some(Self {
-            clap_events_buffer: EventBuffer::with_capacity(128),
             _connection: connection,
             consumer,
             sample_rate,
@@ -122,13 +121,13 @@ impl MidiReceiver {
     ///
     /// Event's timestamps are interpolated to sample time between 0 and the given sample count.
     ///
-    /// This returns a Clack input event buffer handle, ready to feed to the plugin.
-    pub fn receive_all_events(&mut self, sample_count: u64) -> InputEvents {
-        self.clap_events_buffer.clear();
-
+    /// The received events are pushed to the input events of the given `events`, ready to feed to
+    /// the plugin.
+    pub fn receive_all_events(&mut self, sample_count: u64, events: &mut EventIo) {
         if !self.abandoned && self.consumer.is_abandoned() {
-            self.clap_events_buffer
-                .push(&NoteChokeEvent::new(0, Pckn::match_all()).with_flags(EventFlags::IS_LIVE));
+            events.push_input(
+                &NoteChokeEvent::new(0, Pckn::match_all()).with_flags(EventFlags::IS_LIVE),
+            );
             self.abandoned = true;
         } else {
             let mut first_event_timestamp = None;
@@ -146,14 +145,12 @@ impl MidiReceiver {
                 push_midi_to_buffer(
                     midi_event.midi_event,
                     sample_time as u32,
-                    &mut self.clap_events_buffer,
+                    events.input_buffer_mut(),
                     self.main_plugin_note_port_index,
                     self.prefers_midi,
                 );
             }
         }
-
-        self.clap_events_buffer.as_input()
     }
 }
 
@@ -185,18 +182,18 @@ fn push_midi_to_buffer(
 ) {
     match message {
         MidiMessage::NoteOff(channel, note, velocity) if !prefers_midi => buffer.push(
-            &NoteOffEvent::new(
+            &NoteOffEvent::from_midi(
                 sample_time,
                 Pckn::new(port_index, channel.index(), note as u16, Match::All),
-                u8::from(velocity) as f64 / (u8::from(Velocity::MAX) as f64),
+                u8::from(velocity),
             )
             .with_flags(EventFlags::IS_LIVE),
         ),
         MidiMessage::NoteOn(channel, note, velocity) if !prefers_midi => buffer.push(
-            &NoteOnEvent::new(
+            &NoteOnEvent::from_midi(
                 sample_time,
                 Pckn::new(port_index, channel.index(), note as u16, Match::All),
-                u8::from(velocity) as f64 / (u8::from(Velocity::MAX) as f64),
+                u8::from(velocity),
             )
             .with_flags(EventFlags::IS_LIVE),
         ),
@@ -215,8 +212,11 @@ fn push_midi_to_buffer(
 /// or not.
 ///
 /// This returns `None` if it couldn't find one.
-fn find_main_note_port_index(instance: &mut PluginInstance<CpalHost>) -> Option<(u16, bool)> {
-    let mut handle = instance.plugin_handle();
+fn find_main_note_port_index(
+    instance: &mut PluginInstance<CpalHost>,
+    main_thread: &MainThreadToken,
+) -> Option<(u16, bool)> {
+    let mut handle = instance.plugin_handle_checked(main_thread);
     let plugin_note_ports = handle.get_extension::<PluginNotePorts>()?;
 
     let mut buffer = NotePortInfoBuffer::new();
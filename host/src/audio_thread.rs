@@ -0,0 +1,398 @@
+//! Helpers to run audio processing on a real-time thread.
+//!
+//! Audio processing has to meet a deadline for every block: a thread that gets preempted by the
+//! OS scheduler, or that page-faults its way through a freshly mapped stack, can miss it and
+//! cause audible glitches. The [`spawn_realtime`] function spawns a thread that is set up to avoid
+//! both, on a best-effort basis:
+//!
+//! * on Linux, the thread is switched to the `SCHED_FIFO` scheduling policy;
+//! * on Windows, the thread joins the "Pro Audio" MMCSS task;
+//! * on macOS, the thread is given a `THREAD_TIME_CONSTRAINT_POLICY`;
+//! * on all platforms, the thread's stack is pre-faulted before the closure runs.
+//!
+//! Elevating a thread's priority often requires privileges the process doesn't have (e.g.
+//! `CAP_SYS_NICE` or an `RLIMIT_RTPRIO` on Linux). This is never treated as fatal: the thread
+//! still runs at its default priority, and the failure is reported through
+//! [`RealtimeThread::promotion`] so the host can warn the user about it.
+//!
+//! Hosts that don't own their audio thread, e.g. because it is created by an audio backend, can
+//! use [`promote_current_thread`] from within it instead.
+//!
+//! This module requires the `rt-thread` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use clack_host::audio_thread::spawn_realtime;
+//! # use clack_host::prelude::*;
+//! # fn test<H: HostHandlers>(processor: StoppedPluginAudioProcessor<H>) -> std::io::Result<()> {
+//!
+//! let thread = spawn_realtime("audio", 512 * 1024, move || {
+//!     let processor = processor.start_processing().unwrap();
+//!     // Process audio...
+//!     processor.stop_processing()
+//! })?;
+//!
+//! if let Err(e) = thread.promotion() {
+//!     eprintln!("Audio will run at normal priority: {e}");
+//! }
+//!
+//! let processor = thread.join().unwrap();
+//! # Ok(()) }
+//! ```
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hint::black_box;
+use std::io;
+use std::sync::mpsc;
+use std::thread::{Builder, JoinHandle, Thread};
+
+/// The smallest stack size [`spawn_realtime`] creates threads with, in bytes.
+///
+/// Smaller stack sizes are rounded up to this value.
+pub const MIN_STACK_SIZE: usize = 64 * 1024;
+
+/// The size of the stack chunks touched by each level of pre-faulting.
+const PREFAULT_CHUNK_SIZE: usize = 4 * 1024;
+
+/// Errors that can occur while elevating an audio thread to real-time priority.
+///
+/// These errors are not fatal: the thread keeps running at its default priority.
+#[derive(Debug)]
+pub enum PromotionError {
+    /// Real-time scheduling is not supported by this helper on the current platform.
+    Unsupported,
+    /// The OS refused to change the thread's scheduling, usually for lack of privileges.
+    Os(io::Error),
+}
+
+impl PromotionError {
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> PromotionErrorKind {
+        match self {
+            PromotionError::Unsupported => PromotionErrorKind::Unsupported,
+            PromotionError::Os(_) => PromotionErrorKind::Os,
+        }
+    }
+}
+
+/// The kind of a [`PromotionError`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PromotionErrorKind {
+    /// See [`PromotionError::Unsupported`].
+    Unsupported,
+    /// See [`PromotionError::Os`].
+    Os,
+}
+
+impl Display for PromotionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromotionError::Unsupported => {
+                f.write_str("Real-time thread priority is not supported on this platform")
+            }
+            PromotionError::Os(e) => {
+                write!(f, "Failed to elevate thread to real-time priority: {e}")
+            }
+        }
+    }
+}
+
+impl Error for PromotionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PromotionError::Unsupported => None,
+            PromotionError::Os(e) => Some(e),
+        }
+    }
+}
+
+/// A handle to a thread spawned by [`spawn_realtime`].
+///
+/// Dropping this handle detaches the thread, like [`JoinHandle`] does.
+pub struct RealtimeThread<T> {
+    handle: JoinHandle<T>,
+    promotion: Result<(), PromotionError>,
+}
+
+impl<T> RealtimeThread<T> {
+    /// Returns whether the thread was elevated to real-time priority.
+    ///
+    /// If it wasn't, the error explains why. The thread runs at its default priority in that case.
+    #[inline]
+    pub fn promotion(&self) -> Result<(), &PromotionError> {
+        self.promotion.as_ref().map(|_| ())
+    }
+
+    /// Returns the underlying [`Thread`].
+    #[inline]
+    pub fn thread(&self) -> &Thread {
+        self.handle.thread()
+    }
+
+    /// Returns whether the thread has finished running its closure.
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Waits for the thread to finish, and returns the value its closure returned.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the closure panicked, with the panic's payload.
+    #[inline]
+    pub fn join(self) -> std::thread::Result<T> {
+        self.handle.join()
+    }
+}
+
+/// Spawns a new thread with the given name and stack size, elevates it to real-time priority,
+/// and runs the given closure on it.
+///
+/// The first half of the thread's stack is pre-faulted before the closure is called.
+/// The `stack_size` is rounded up to [`MIN_STACK_SIZE`] if needed.
+///
+/// This function waits until the thread has attempted to elevate its priority, so that the
+/// outcome is available through [`RealtimeThread::promotion`] as soon as it returns. Failing to
+/// elevate the thread's priority is not an error: the closure is run at the default priority
+/// regardless.
+///
+/// The closure has to be `Send + 'static`, which makes it possible to move a
+/// [`StoppedPluginAudioProcessor`](crate::process::StoppedPluginAudioProcessor) into it, and start
+/// processing from the new thread.
+///
+/// # Errors
+///
+/// This returns an error if the OS failed to create the thread.
+pub fn spawn_realtime<F, T>(
+    name: impl Into<String>,
+    stack_size: usize,
+    f: F,
+) -> io::Result<RealtimeThread<T>>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let stack_size = stack_size.max(MIN_STACK_SIZE);
+    let (sender, receiver) = mpsc::sync_channel(1);
+
+    let handle = Builder::new()
+        .name(name.into())
+        .stack_size(stack_size)
+        .spawn(move || {
+            let _ = sender.send(promote_current_thread());
+            drop(sender);
+
+            // Leave some headroom for what the thread's runtime already uses, and for the
+            // closure's own frame, which may be inlined into this one.
+            prefault_stack(stack_size / 2);
+            f()
+        })?;
+
+    // The thread can only fail to report if it got killed before sending.
+    let promotion = receiver.recv().unwrap_or(Err(PromotionError::Unsupported));
+
+    Ok(RealtimeThread { handle, promotion })
+}
+
+/// Elevates the calling thread to real-time priority.
+///
+/// This is meant for audio threads that are not spawned by the host itself, e.g. in audio
+/// backend callbacks. It should only be called once per thread, before processing starts, as it
+/// performs system calls.
+///
+/// Unlike [`spawn_realtime`], this doesn't pre-fault the thread's stack, as its size is unknown.
+///
+/// # Errors
+///
+/// This returns an error if the thread's priority couldn't be elevated. The thread keeps its
+/// previous priority in that case.
+#[inline]
+pub fn promote_current_thread() -> Result<(), PromotionError> {
+    platform::promote_current_thread()
+}
+
+/// Touches `size` bytes of the current thread's stack, so that its pages are mapped before they
+/// are needed on the audio path.
+#[inline(never)]
+fn prefault_stack(size: usize) {
+    let mut chunk = [0u8; PREFAULT_CHUNK_SIZE];
+    black_box(&mut chunk);
+
+    if let Some(remaining) = size.checked_sub(PREFAULT_CHUNK_SIZE) {
+        prefault_stack(remaining);
+    }
+
+    // Using the chunk after the recursive call keeps it from being turned into a loop, which
+    // would reuse the same stack frame.
+    black_box(&chunk);
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::PromotionError;
+    use std::ffi::{c_int, c_ulong};
+    use std::io;
+
+    const SCHED_FIFO: c_int = 1;
+
+    #[repr(C)]
+    struct SchedParam {
+        sched_priority: c_int,
+    }
+
+    extern "C" {
+        fn pthread_self() -> c_ulong;
+        fn pthread_setschedparam(thread: c_ulong, policy: c_int, param: *const SchedParam)
+            -> c_int;
+        fn sched_get_priority_min(policy: c_int) -> c_int;
+        fn sched_get_priority_max(policy: c_int) -> c_int;
+    }
+
+    pub fn promote_current_thread() -> Result<(), PromotionError> {
+        // SAFETY: these functions have no preconditions.
+        let (min, max) = unsafe {
+            (
+                sched_get_priority_min(SCHED_FIFO),
+                sched_get_priority_max(SCHED_FIFO),
+            )
+        };
+
+        if min < 0 || max < 0 {
+            return Err(PromotionError::Os(io::Error::last_os_error()));
+        }
+
+        // Stay below the top of the range, which is best left to the audio server and the kernel.
+        let param = SchedParam {
+            sched_priority: min + (max - min) * 3 / 4,
+        };
+
+        // SAFETY: the thread handle is valid, and param is a valid pointer to a sched_param.
+        let result = unsafe { pthread_setschedparam(pthread_self(), SCHED_FIFO, &param) };
+
+        match result {
+            0 => Ok(()),
+            e => Err(PromotionError::Os(io::Error::from_raw_os_error(e))),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::PromotionError;
+    use std::ffi::{c_int, c_uint, c_void};
+    use std::io;
+
+    const THREAD_TIME_CONSTRAINT_POLICY: c_uint = 2;
+    const THREAD_TIME_CONSTRAINT_POLICY_COUNT: c_uint = 4;
+
+    /// The nominal period of the audio thread, in nanoseconds.
+    ///
+    /// The actual buffer duration isn't known here: this matches a 512-frame buffer at 48kHz.
+    const PERIOD_NS: u64 = 10_666_667;
+
+    #[repr(C)]
+    struct MachTimebaseInfo {
+        numer: u32,
+        denom: u32,
+    }
+
+    #[repr(C)]
+    struct ThreadTimeConstraintPolicy {
+        period: u32,
+        computation: u32,
+        constraint: u32,
+        preemptible: c_int,
+    }
+
+    extern "C" {
+        fn pthread_self() -> *mut c_void;
+        fn pthread_mach_thread_np(thread: *mut c_void) -> c_uint;
+        fn mach_timebase_info(info: *mut MachTimebaseInfo) -> c_int;
+        fn thread_policy_set(
+            thread: c_uint,
+            flavor: c_uint,
+            policy_info: *mut c_int,
+            count: c_uint,
+        ) -> c_int;
+    }
+
+    pub fn promote_current_thread() -> Result<(), PromotionError> {
+        let mut timebase = MachTimebaseInfo { numer: 0, denom: 0 };
+
+        // SAFETY: timebase is a valid pointer.
+        if unsafe { mach_timebase_info(&mut timebase) } != 0 || timebase.numer == 0 {
+            return Err(PromotionError::Unsupported);
+        }
+
+        let to_abs = |ns: u64| (ns * timebase.denom as u64 / timebase.numer as u64) as u32;
+
+        let mut policy = ThreadTimeConstraintPolicy {
+            period: to_abs(PERIOD_NS),
+            computation: to_abs(PERIOD_NS / 2),
+            constraint: to_abs(PERIOD_NS),
+            preemptible: 1,
+        };
+
+        // SAFETY: policy is a valid pointer to a time constraint policy, whose size matches the
+        // given count.
+        let result = unsafe {
+            thread_policy_set(
+                pthread_mach_thread_np(pthread_self()),
+                THREAD_TIME_CONSTRAINT_POLICY,
+                (&mut policy as *mut ThreadTimeConstraintPolicy).cast(),
+                THREAD_TIME_CONSTRAINT_POLICY_COUNT,
+            )
+        };
+
+        match result {
+            0 => Ok(()),
+            e => Err(PromotionError::Os(io::Error::new(
+                io::ErrorKind::Other,
+                format!("thread_policy_set failed with kern_return_t {e}"),
+            ))),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::PromotionError;
+    use std::ffi::c_void;
+    use std::io;
+
+    #[link(name = "avrt")]
+    extern "system" {
+        fn AvSetMmThreadCharacteristicsW(
+            task_name: *const u16,
+            task_index: *mut u32,
+        ) -> *mut c_void;
+    }
+
+    pub fn promote_current_thread() -> Result<(), PromotionError> {
+        let task_name: Vec<u16> = "Pro Audio\0".encode_utf16().collect();
+        let mut task_index = 0;
+
+        // SAFETY: task_name is a valid nul-terminated wide string, and task_index a valid pointer.
+        // The returned handle is not reverted: the thread stays in the task until it exits.
+        let handle = unsafe { AvSetMmThreadCharacteristicsW(task_name.as_ptr(), &mut task_index) };
+
+        if handle.is_null() {
+            Err(PromotionError::Os(io::Error::last_os_error()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::PromotionError;
+
+    pub fn promote_current_thread() -> Result<(), PromotionError> {
+        Err(PromotionError::Unsupported)
+    }
+}
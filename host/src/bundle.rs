@@ -61,11 +61,13 @@
 use std::error::Error;
 use std::ffi::NulError;
 use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
 
 use std::ptr::NonNull;
 
 mod cache;
 mod entry;
+pub mod index;
 
 #[cfg(feature = "libloading")]
 mod library;
@@ -75,6 +77,7 @@ mod library;
 pub mod diva_stub;
 
 use crate::bundle::cache::CachedEntry;
+pub(crate) use crate::bundle::cache::InstanceEntryGuard;
 use crate::factory::{FactoryPointer, PluginFactory};
 pub use clack_common::entry::*;
 use clack_common::utils::ClapVersion;
@@ -88,7 +91,10 @@ use clack_common::utils::ClapVersion;
 /// operation on this type only clones the handle.
 ///
 /// Plugin bundles are only unloaded when all handles are dropped and all associated instances
-/// are unloaded.
+/// are unloaded. The bundle's entry is always deinitialized after the last of its instances has
+/// been destroyed, even if all the handles to the bundle were dropped before it. In debug builds,
+/// dropping the last handle while instances are still alive is reported to the
+/// [fallback logger](clack_common::utils::fallback_log).
 ///
 /// A [`PluginBundle`] can also be loaded from a static [`EntryDescriptor`] instead of a file.
 /// See [`PluginBundle::load_from_raw`].
@@ -107,14 +113,25 @@ use clack_common::utils::ClapVersion;
 /// println!("Loaded bundle CLAP version: {}", bundle.version());
 /// # Ok(()) }
 /// ```
-#[derive(Clone)]
 pub struct PluginBundle {
     inner: CachedEntry,
 }
 
 impl PluginBundle {
+    #[inline]
+    fn from_cached(inner: CachedEntry) -> Self {
+        inner.acquire_handle();
+        Self { inner }
+    }
+
     /// Loads a CLAP bundle from a file located at the given path.
     ///
+    /// Relative paths are resolved from the current directory, and the resulting absolute path is
+    /// the one given to the bundle's entry.
+    ///
+    /// If the path points to a directory, it is loaded as a macOS bundle: the library file
+    /// actually loaded is the bundle's executable, as declared in its `Contents/Info.plist`.
+    ///
     /// # Safety
     ///
     /// This function loads an external library object file, which is inherently unsafe, as even
@@ -142,16 +159,18 @@ impl PluginBundle {
     /// ```
     #[cfg(feature = "libloading")]
     pub unsafe fn load<P: AsRef<std::ffi::OsStr>>(path: P) -> Result<Self, PluginBundleError> {
-        use crate::bundle::library::PluginEntryLibrary;
+        use crate::bundle::library::{resolve_path, PluginEntryLibrary};
 
-        let path = path.as_ref();
-        let path_str = path.to_str().ok_or(PluginBundleError::InvalidUtf8Path)?;
+        let path = resolve_path(Path::new(path.as_ref()));
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| PluginBundleError::InvalidUtf8Path { path: path.clone() })?;
 
-        let library = PluginEntryLibrary::load(path)?;
+        let library = PluginEntryLibrary::load(&path)?;
 
         let inner = cache::load_from_library(library, path_str)?;
 
-        Ok(Self { inner })
+        Ok(Self::from_cached(inner))
     }
 
     /// Loads a CLAP bundle from a given symbol in a given [`libloading::Library`].
@@ -197,16 +216,18 @@ impl PluginBundle {
         library: libloading::Library,
         symbol_name: &core::ffi::CStr,
     ) -> Result<Self, PluginBundleError> {
-        use crate::bundle::library::PluginEntryLibrary;
+        use crate::bundle::library::{resolve_path, PluginEntryLibrary};
 
-        let path = path.as_ref();
-        let path_str = path.to_str().ok_or(PluginBundleError::InvalidUtf8Path)?;
+        let path = resolve_path(Path::new(path.as_ref()));
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| PluginBundleError::InvalidUtf8Path { path: path.clone() })?;
 
-        let library = PluginEntryLibrary::load_from_symbol_in_library(library, symbol_name)?;
+        let library = PluginEntryLibrary::load_from_symbol_in_library(library, symbol_name, &path)?;
 
         let inner = cache::load_from_library(library, path_str)?;
 
-        Ok(Self { inner })
+        Ok(Self::from_cached(inner))
     }
 
     /// Loads a CLAP bundle from a `'static` [`EntryDescriptor`].
@@ -245,9 +266,7 @@ impl PluginBundle {
         inner: &'static EntryDescriptor,
         plugin_path: &str,
     ) -> Result<Self, PluginBundleError> {
-        Ok(Self {
-            inner: cache::load_from_raw(inner, plugin_path)?,
-        })
+        Ok(Self::from_cached(cache::load_from_raw(inner, plugin_path)?))
     }
 
     /// Gets the raw, C-FFI plugin entry descriptor exposed by this bundle.
@@ -309,46 +328,184 @@ impl PluginBundle {
     }
 
     /// Returns the CLAP version used by this bundle.
+    ///
+    /// Bundles using a version that is not [compatible](ClapVersion::is_compatible) with Clack's
+    /// are refused at load time, so this is always compatible with [`ClapVersion::CURRENT`].
+    /// However, plugins from this bundle may still be incompatible with the version a given
+    /// [`HostInfo`](crate::host::HostInfo) advertises, in which case they will fail to instantiate.
     #[inline]
-    pub fn version(&self) -> ClapVersion {
+    pub fn clap_version(&self) -> ClapVersion {
         ClapVersion::from_raw(self.raw_entry().clap_version)
     }
+
+    /// Returns the CLAP version used by this bundle.
+    ///
+    /// This is an alias of [`clap_version`](PluginBundle::clap_version).
+    #[inline]
+    pub fn version(&self) -> ClapVersion {
+        self.clap_version()
+    }
+
+    /// Returns the number of plugin instances created from this bundle that are still alive.
+    ///
+    /// This counts the instances created through any handle to this bundle. The bundle's entry is
+    /// only deinitialized once this drops to zero and all handles have been dropped.
+    #[inline]
+    pub fn instance_count(&self) -> usize {
+        self.inner.instance_count()
+    }
+
+    /// Returns a guard keeping this bundle's entry initialized while a plugin instance is alive.
+    #[inline]
+    pub(crate) fn instance_guard(&self) -> InstanceEntryGuard {
+        self.inner.instance_guard()
+    }
+}
+
+impl Clone for PluginBundle {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::from_cached(self.inner.clone())
+    }
+}
+
+impl Drop for PluginBundle {
+    fn drop(&mut self) {
+        let Some(live_instances) = self.inner.release_handle() else {
+            return;
+        };
+
+        #[cfg(debug_assertions)]
+        if live_instances > 0 {
+            use clack_common::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
+
+            fallback_log(&LogRecord {
+                origin: LogOrigin::Host,
+                severity: clap_sys::ext::log::CLAP_LOG_WARNING,
+                plugin_id: None,
+                callback: "",
+                message: &format_args!(
+                    "The last handle to a plugin bundle was dropped while {live_instances} of its \
+                     instances are still alive. Its entry will be deinitialized once they are \
+                     all destroyed."
+                ),
+            });
+        }
+
+        #[cfg(not(debug_assertions))]
+        let _ = live_instances;
+    }
 }
 
 /// Errors that can occur while loading a [`PluginBundle`].
 ///
+/// Every variant carries the path of the bundle that failed to load, which can be retrieved using
+/// [`path`](PluginBundleError::path). For bundles loaded from files, this is the resolved,
+/// absolute path of the bundle.
+///
 /// See [`PluginBundle::load`] and [`PluginBundle::load_from_raw`].
 #[derive(Debug)]
 pub enum PluginBundleError {
     /// The path given to [`PluginBundle::load`] is not valid UTF-8.
-    InvalidUtf8Path,
-    /// The dynamic library file could not be loaded.
+    InvalidUtf8Path {
+        /// The path of the bundle.
+        path: PathBuf,
+    },
+    /// The bundle file could not be accessed, e.g. because it does not exist, or because of
+    /// insufficient permissions.
+    FileAccess {
+        /// The path of the bundle.
+        path: PathBuf,
+        /// The error returned by the operating system.
+        error: std::io::Error,
+    },
+    /// The bundle is a directory, but its executable file could not be found.
+    ///
+    /// On macOS, CLAP bundles are directories, which contain the actual library file at
+    /// `Contents/MacOS/<executable>`, where `<executable>` is the `CFBundleExecutable` value of
+    /// the bundle's `Contents/Info.plist` file.
+    MissingBundleExecutable {
+        /// The path of the bundle.
+        path: PathBuf,
+        /// The expected path of the executable file.
+        executable_path: PathBuf,
+    },
+    /// The dynamic library file could not be loaded by the system's dynamic loader.
     ///
     /// This contains the error type from the underlying
-    /// [`libloading`](https://crates.io/crates/libloading) library.
+    /// [`libloading`](https://crates.io/crates/libloading) library, which includes the error
+    /// message from the loader itself (i.e. `dlerror` or `GetLastError`).
     #[cfg(feature = "libloading")]
-    LibraryLoadingError(libloading::Error),
+    LibraryLoadingError {
+        /// The path of the bundle.
+        path: PathBuf,
+        /// The error returned by the dynamic loader.
+        error: libloading::Error,
+    },
+    /// The dynamic library file does not expose the `clap_entry` symbol.
+    ///
+    /// This usually means the file is a valid library, but not a CLAP bundle.
+    #[cfg(feature = "libloading")]
+    MissingEntrySymbol {
+        /// The path of the bundle.
+        path: PathBuf,
+        /// The error returned by the dynamic loader.
+        error: libloading::Error,
+    },
     /// The entry pointer exposed by the dynamic library file is `null`.
-    NullEntryPointer,
+    NullEntryPointer {
+        /// The path of the bundle.
+        path: PathBuf,
+    },
     /// The exposed entry used an incompatible CLAP version.
     IncompatibleClapVersion {
+        /// The path of the bundle.
+        path: PathBuf,
         /// The CLAP version that the entry uses.
         ///
         /// See [`ClapVersion::CURRENT`] to get the current clap version.
         plugin_version: ClapVersion,
     },
     /// The given path is not a valid C string.
-    InvalidNulPath(NulError),
+    InvalidNulPath {
+        /// The path of the bundle.
+        path: PathBuf,
+        /// The underlying error.
+        error: NulError,
+    },
     /// The entry's `init` method failed.
-    EntryInitFailed,
+    EntryInitFailed {
+        /// The path of the bundle.
+        path: PathBuf,
+    },
+}
+
+impl PluginBundleError {
+    /// Returns the path of the bundle that failed to load.
+    pub fn path(&self) -> &Path {
+        match self {
+            PluginBundleError::InvalidUtf8Path { path }
+            | PluginBundleError::FileAccess { path, .. }
+            | PluginBundleError::MissingBundleExecutable { path, .. }
+            | PluginBundleError::NullEntryPointer { path }
+            | PluginBundleError::IncompatibleClapVersion { path, .. }
+            | PluginBundleError::InvalidNulPath { path, .. }
+            | PluginBundleError::EntryInitFailed { path } => path,
+            #[cfg(feature = "libloading")]
+            PluginBundleError::LibraryLoadingError { path, .. }
+            | PluginBundleError::MissingEntrySymbol { path, .. } => path,
+        }
+    }
 }
 
 impl Error for PluginBundleError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            PluginBundleError::InvalidNulPath(e) => Some(e),
+            PluginBundleError::InvalidNulPath { error, .. } => Some(error),
+            PluginBundleError::FileAccess { error, .. } => Some(error),
             #[cfg(feature = "libloading")]
-            PluginBundleError::LibraryLoadingError(e) => Some(e),
+            PluginBundleError::LibraryLoadingError { error, .. }
+            | PluginBundleError::MissingEntrySymbol { error, .. } => Some(error),
             _ => None,
         }
     }
@@ -357,21 +514,57 @@ impl Error for PluginBundleError {
 impl Display for PluginBundleError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            PluginBundleError::EntryInitFailed => f.write_str("Plugin entry initialization failed"),
-            PluginBundleError::InvalidNulPath(e) => {
-                write!(f, "Invalid plugin descriptor path: {e}")
+            PluginBundleError::EntryInitFailed { path } => {
+                write!(f, "Plugin entry initialization failed ({})", path.display())
             }
+            PluginBundleError::InvalidNulPath { path, error } => {
+                write!(f, "Invalid plugin bundle path {}: {error}", path.display())
+            }
+            PluginBundleError::FileAccess { path, error } => {
+                write!(
+                    f,
+                    "Failed to access plugin bundle {}: {error}",
+                    path.display()
+                )
+            }
+            PluginBundleError::MissingBundleExecutable {
+                path,
+                executable_path,
+            } => write!(
+                f,
+                "Plugin bundle {} has no executable (expected {})",
+                path.display(),
+                executable_path.display()
+            ),
             #[cfg(feature = "libloading")]
-            PluginBundleError::LibraryLoadingError(e) => {
-                write!(f, "Failed to load plugin descriptor library: {e}")
+            PluginBundleError::LibraryLoadingError { path, error } => {
+                write!(
+                    f,
+                    "Failed to load plugin bundle {}: {error}",
+                    path.display()
+                )
             }
-            PluginBundleError::InvalidUtf8Path => {
-                f.write_str("Plugin descriptor path contains invalid UTF-8")
+            #[cfg(feature = "libloading")]
+            PluginBundleError::MissingEntrySymbol { path, error } => write!(
+                f,
+                "Plugin bundle {} does not expose a CLAP entry: {error}",
+                path.display()
+            ),
+            PluginBundleError::InvalidUtf8Path { path } => write!(
+                f,
+                "Plugin bundle path {} contains invalid UTF-8",
+                path.display()
+            ),
+            PluginBundleError::NullEntryPointer { path } => {
+                write!(f, "Plugin entry pointer is null ({})", path.display())
             }
-            PluginBundleError::NullEntryPointer => f.write_str("Plugin entry pointer is null"),
-            PluginBundleError::IncompatibleClapVersion { plugin_version } => write!(
+            PluginBundleError::IncompatibleClapVersion {
+                path,
+                plugin_version,
+            } => write!(
                 f,
-                "Incompatible CLAP version: plugin is v{}, host is v{}",
+                "Incompatible CLAP version in {}: plugin is v{}, host is v{}",
+                path.display(),
                 plugin_version,
                 ClapVersion::CURRENT
             ),
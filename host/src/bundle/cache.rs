@@ -3,6 +3,7 @@ use crate::bundle::PluginBundleError;
 use clack_common::entry::EntryDescriptor;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 
 #[derive(Hash, Eq, PartialEq)]
@@ -14,7 +15,7 @@ unsafe impl Send for EntryPointer {}
 // SAFETY: we're treating those pointers as pure addresses, we never read from them
 unsafe impl Sync for EntryPointer {}
 
-static ENTRY_CACHE: OnceLock<Mutex<HashMap<EntryPointer, Arc<EntrySourceInner>>>> = OnceLock::new();
+static ENTRY_CACHE: OnceLock<Mutex<HashMap<EntryPointer, Arc<EntrySource>>>> = OnceLock::new();
 
 fn get_or_insert(
     entry_pointer: EntryPointer,
@@ -29,7 +30,11 @@ fn get_or_insert(
     let s = match cache.entry(entry_pointer) {
         Entry::Occupied(e) => Arc::clone(e.get()),
         Entry::Vacant(e) => {
-            let entry_source = Arc::new(load_entry()?);
+            let entry_source = Arc::new(EntrySource {
+                inner: load_entry()?,
+                handles: AtomicUsize::new(0),
+                instances: AtomicUsize::new(0),
+            });
             e.insert(Arc::clone(&entry_source));
             entry_source
         }
@@ -79,22 +84,86 @@ enum EntrySourceInner {
     },
 }
 
+/// A loaded entry, alongside the number of handles and instances that keep it alive.
+///
+/// The entry is only deinitialized once all [`CachedEntry`] references to it are dropped, which
+/// includes the [`InstanceEntryGuard`] held by every plugin instance.
+struct EntrySource {
+    inner: EntrySourceInner,
+    /// The number of [`PluginBundle`](crate::bundle::PluginBundle) handles to this entry.
+    handles: AtomicUsize,
+    /// The number of live plugin instances created from this entry.
+    instances: AtomicUsize,
+}
+
 #[derive(Clone)]
-pub(crate) struct CachedEntry(Option<Arc<EntrySourceInner>>);
+pub(crate) struct CachedEntry(Option<Arc<EntrySource>>);
 
 impl CachedEntry {
     #[inline]
-    pub(crate) fn raw_entry(&self) -> &EntryDescriptor {
+    fn source(&self) -> &EntrySource {
         let Some(entry) = &self.0 else {
             unreachable!("Unloaded state only exists during CachedEntry's Drop implementation")
         };
 
-        match entry.as_ref() {
+        entry
+    }
+
+    #[inline]
+    pub(crate) fn raw_entry(&self) -> &EntryDescriptor {
+        match &self.source().inner {
             EntrySourceInner::FromRaw(raw) => raw.entry(),
             #[cfg(feature = "libloading")]
             EntrySourceInner::FromLibrary { entry, .. } => entry.entry(),
         }
     }
+
+    /// Records a new bundle handle to this entry.
+    #[inline]
+    pub(crate) fn acquire_handle(&self) {
+        self.source().handles.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records that a bundle handle to this entry was dropped.
+    ///
+    /// Returns the number of live instances if it was the last handle, or `None` otherwise.
+    #[inline]
+    pub(crate) fn release_handle(&self) -> Option<usize> {
+        let source = self.source();
+
+        if source.handles.fetch_sub(1, Ordering::AcqRel) == 1 {
+            Some(source.instances.load(Ordering::Acquire))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of live plugin instances created from this entry.
+    #[inline]
+    pub(crate) fn instance_count(&self) -> usize {
+        self.source().instances.load(Ordering::Acquire)
+    }
+
+    /// Records a new plugin instance of this entry, which keeps the entry alive until the
+    /// returned guard is dropped.
+    #[inline]
+    pub(crate) fn instance_guard(&self) -> InstanceEntryGuard {
+        self.source().instances.fetch_add(1, Ordering::AcqRel);
+        InstanceEntryGuard(self.clone())
+    }
+}
+
+/// Keeps an entry initialized for as long as a plugin instance created from it is alive.
+///
+/// This must be dropped only after the instance has been destroyed: the entry may be
+/// deinitialized right after.
+pub(crate) struct InstanceEntryGuard(CachedEntry);
+
+impl Drop for InstanceEntryGuard {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.source().instances.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
 impl Drop for CachedEntry {
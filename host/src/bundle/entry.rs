@@ -16,14 +16,20 @@ impl LoadedEntry {
     pub unsafe fn load(entry: &EntryDescriptor, path: &str) -> Result<Self, PluginBundleError> {
         let plugin_version = ClapVersion::from_raw(entry.clap_version);
         if !plugin_version.is_compatible() {
-            return Err(PluginBundleError::IncompatibleClapVersion { plugin_version });
+            return Err(PluginBundleError::IncompatibleClapVersion {
+                path: path.into(),
+                plugin_version,
+            });
         }
 
-        let path = CString::new(path).map_err(PluginBundleError::InvalidNulPath)?;
+        let c_path = CString::new(path).map_err(|error| PluginBundleError::InvalidNulPath {
+            path: path.into(),
+            error,
+        })?;
 
         if let Some(init) = entry.init {
-            if !init(path.as_ptr()) {
-                return Err(PluginBundleError::EntryInitFailed);
+            if !init(c_path.as_ptr()) {
+                return Err(PluginBundleError::EntryInitFailed { path: path.into() });
             }
         }
 
@@ -0,0 +1,519 @@
+//! An index of the plugins found across multiple bundle files.
+//!
+//! See the [`PluginIndex`] type for more information.
+
+use crate::bundle::PluginBundle;
+use crate::factory::PluginCompatibilityFactory;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// A plugin found in a bundle file, as recorded by a [`PluginIndex`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexedPlugin {
+    /// The path of the bundle file the plugin was found in.
+    pub bundle_path: PathBuf,
+    /// The last modification time of the bundle file, if it could be retrieved.
+    pub modified: Option<SystemTime>,
+    /// The plugin's name, as found in its descriptor.
+    pub name: Option<String>,
+    /// The plugin's vendor, as found in its descriptor.
+    pub vendor: Option<String>,
+    /// The plugin's version, as found in its descriptor.
+    pub version: Option<String>,
+    /// The IDs of other plugins this plugin declared to be compatible with.
+    ///
+    /// See [`PluginIndex::resolve_for_session`].
+    pub compatible_ids: Vec<String>,
+}
+
+/// How a [`PluginIndex`] picks a bundle for plugins found in multiple bundle files.
+///
+/// Regardless of this preference, [pinned](PluginIndex::pin) bundles are always picked first.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ResolutionPreference {
+    /// Picks the bundle with the highest plugin version, as compared by [`compare_versions`].
+    /// Ties are broken by picking the most recently modified file.
+    #[default]
+    HighestVersion,
+    /// Picks the most recently modified bundle file. Ties are broken by picking the highest
+    /// plugin version.
+    NewestFile,
+}
+
+/// Why a [`Resolution`] picked its plugin.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ResolutionReason {
+    /// The plugin was only found in a single bundle file.
+    Unique,
+    /// The bundle was explicitly pinned by the host.
+    Pinned,
+    /// The bundle was picked following the index's [`ResolutionPreference`].
+    Preference(ResolutionPreference),
+}
+
+/// The result of resolving a plugin ID with [`PluginIndex::resolve`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Resolution<'a> {
+    /// The picked plugin.
+    pub plugin: &'a IndexedPlugin,
+    /// The other bundles the plugin was found in, which were not picked.
+    pub alternatives: Vec<&'a IndexedPlugin>,
+    /// Why this plugin was picked.
+    pub reason: ResolutionReason,
+}
+
+impl Resolution<'_> {
+    /// Returns `true` if the plugin was found in more than one bundle file.
+    #[inline]
+    pub fn is_conflict(&self) -> bool {
+        !self.alternatives.is_empty()
+    }
+}
+
+/// The result of resolving a plugin ID referenced by a saved session, with
+/// [`PluginIndex::resolve_for_session`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SessionResolution<'a> {
+    /// A plugin with the requested ID was found.
+    Direct(Resolution<'a>),
+    /// No plugin with the requested ID was found, but another plugin declared to be compatible
+    /// with it.
+    ///
+    /// Hosts should warn the user that a substitute is being used.
+    Compatible {
+        /// The plugin ID that was requested.
+        original: String,
+        /// The ID of the compatible plugin that was picked instead.
+        substitute: &'a str,
+        /// The resolution of the substitute plugin.
+        resolution: Resolution<'a>,
+    },
+}
+
+impl<'a> SessionResolution<'a> {
+    /// Returns the resolution of the picked plugin, regardless of whether it is a substitute.
+    #[inline]
+    pub fn resolution(&self) -> &Resolution<'a> {
+        match self {
+            SessionResolution::Direct(resolution) => resolution,
+            SessionResolution::Compatible { resolution, .. } => resolution,
+        }
+    }
+
+    /// Returns `true` if a compatible plugin is used in place of the requested one.
+    #[inline]
+    pub fn is_substitute(&self) -> bool {
+        matches!(self, SessionResolution::Compatible { .. })
+    }
+}
+
+/// An index of all the plugins found across multiple bundle files, by plugin ID.
+///
+/// The same plugin ID can be found in multiple bundle files, e.g. when an old and a new version of
+/// a plugin are installed side by side. This index records every bundle each plugin was found in,
+/// and [resolves](Self::resolve) which one to use following a configurable
+/// [`ResolutionPreference`], or an explicit [pin](Self::pin). Hosts can also list all the
+/// [conflicts](Self::conflicts), to surface them to the user.
+#[derive(Clone, Debug, Default)]
+pub struct PluginIndex {
+    plugins: HashMap<String, Vec<IndexedPlugin>>,
+    pins: HashMap<String, PathBuf>,
+    preference: ResolutionPreference,
+}
+
+impl PluginIndex {
+    /// Creates a new, empty index, using the default [`ResolutionPreference`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how this index picks a bundle for plugins found in multiple bundle files.
+    #[inline]
+    pub fn with_preference(mut self, preference: ResolutionPreference) -> Self {
+        self.preference = preference;
+        self
+    }
+
+    /// Records all the plugins exposed by the given bundle, which was loaded from the given path.
+    ///
+    /// The modification time of the bundle file is read from the filesystem. Returns the number of
+    /// plugins that were recorded.
+    pub fn add_bundle(&mut self, path: impl AsRef<Path>, bundle: &PluginBundle) -> usize {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        self.add_bundle_with_modified_time(path, modified, bundle)
+    }
+
+    /// Records all the plugins exposed by the given bundle, which was loaded from the given path
+    /// and was last modified at the given time.
+    ///
+    /// This is useful for hosts that already retrieved the file's metadata, e.g. from a scan
+    /// cache. Returns the number of plugins that were recorded.
+    pub fn add_bundle_with_modified_time(
+        &mut self,
+        path: impl AsRef<Path>,
+        modified: Option<SystemTime>,
+        bundle: &PluginBundle,
+    ) -> usize {
+        let Some(factory) = bundle.get_plugin_factory() else {
+            return 0;
+        };
+
+        let compatibility = bundle.get_factory::<PluginCompatibilityFactory>();
+        let to_string = |s: Option<&std::ffi::CStr>| s.map(|s| s.to_string_lossy().into_owned());
+
+        let mut count = 0;
+        for descriptor in factory.plugin_descriptors() {
+            let Some(id) = descriptor.id() else {
+                continue;
+            };
+
+            self.insert(
+                &id.to_string_lossy(),
+                IndexedPlugin {
+                    bundle_path: path.as_ref().to_path_buf(),
+                    modified,
+                    name: to_string(descriptor.name()),
+                    vendor: to_string(descriptor.vendor()),
+                    version: to_string(descriptor.version()),
+                    compatible_ids: compatibility
+                        .iter()
+                        .flat_map(|f| f.compatible_ids(id))
+                        .map(|id| id.to_string_lossy().into_owned())
+                        .collect(),
+                },
+            );
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Records a plugin with the given ID.
+    ///
+    /// If the plugin was already recorded for the same bundle path, the previous record is
+    /// replaced, e.g. when a bundle is scanned again.
+    pub fn insert(&mut self, id: &str, plugin: IndexedPlugin) {
+        let entries = self.plugins.entry(id.into()).or_default();
+
+        match entries
+            .iter_mut()
+            .find(|p| p.bundle_path == plugin.bundle_path)
+        {
+            Some(existing) => *existing = plugin,
+            None => entries.push(plugin),
+        }
+    }
+
+    /// Forgets all the plugins that were found in the bundle file at the given path.
+    pub fn remove_bundle(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+
+        self.plugins.retain(|_, entries| {
+            entries.retain(|p| p.bundle_path != path);
+            !entries.is_empty()
+        });
+    }
+
+    /// Pins the given plugin ID to the bundle file at the given path.
+    ///
+    /// Pinned bundles are always picked by [`resolve`](Self::resolve), as long as the plugin was
+    /// actually found in them.
+    pub fn pin(&mut self, id: &str, bundle_path: impl Into<PathBuf>) {
+        self.pins.insert(id.into(), bundle_path.into());
+    }
+
+    /// Removes the pin for the given plugin ID, if any.
+    #[inline]
+    pub fn unpin(&mut self, id: &str) {
+        self.pins.remove(id);
+    }
+
+    /// Returns the number of distinct plugin IDs in this index.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    /// Returns `true` if this index contains no plugins.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Returns all the bundles the plugin with the given ID was found in, in the order they were
+    /// recorded.
+    #[inline]
+    pub fn get(&self, id: &str) -> &[IndexedPlugin] {
+        self.plugins.get(id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns the IDs of all the plugins in this index, in no particular order.
+    #[inline]
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.plugins.keys().map(String::as_str)
+    }
+
+    /// Picks the bundle to use for the plugin with the given ID.
+    ///
+    /// Returns `None` if the plugin wasn't found in any bundle.
+    pub fn resolve(&self, id: &str) -> Option<Resolution<'_>> {
+        let entries = self.plugins.get(id)?;
+
+        let (index, reason) = if entries.len() == 1 {
+            (0, ResolutionReason::Unique)
+        } else if let Some(index) = self
+            .pins
+            .get(id)
+            .and_then(|pin| entries.iter().position(|p| &p.bundle_path == pin))
+        {
+            (index, ResolutionReason::Pinned)
+        } else {
+            let preference = self.preference;
+            let (index, _) = entries
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| compare_with_preference(preference, a, b))?;
+
+            (index, ResolutionReason::Preference(preference))
+        };
+
+        Some(Resolution {
+            plugin: &entries[index],
+            alternatives: entries
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .map(|(_, p)| p)
+                .collect(),
+            reason,
+        })
+    }
+
+    /// Picks the plugin to use for a plugin ID referenced by a saved session.
+    ///
+    /// If a plugin with the given ID was found, this returns its [`resolution`](Self::resolve)
+    /// directly. Otherwise, this looks for plugins that declared to be compatible with that ID
+    /// (e.g. because they replace a deprecated plugin), and picks one of those instead, following
+    /// this index's [`ResolutionPreference`]. In that case, hosts should warn the user that a
+    /// substitute is being used.
+    ///
+    /// Returns `None` if neither the plugin nor any compatible plugin was found.
+    pub fn resolve_for_session(&self, id: &str) -> Option<SessionResolution<'_>> {
+        if let Some(resolution) = self.resolve(id) {
+            return Some(SessionResolution::Direct(resolution));
+        }
+
+        let preference = self.preference;
+        let mut substitutes: Vec<_> = self
+            .plugins
+            .iter()
+            .filter(|(_, entries)| {
+                entries
+                    .iter()
+                    .any(|p| p.compatible_ids.iter().any(|c| c == id))
+            })
+            .filter_map(|(substitute, _)| Some((substitute.as_str(), self.resolve(substitute)?)))
+            .collect();
+
+        // Sort by ID first, so that ties are broken the same way every time.
+        substitutes.sort_by_key(|(substitute, _)| *substitute);
+        let (substitute, resolution) = substitutes.into_iter().reduce(|best, candidate| {
+            match compare_with_preference(preference, candidate.1.plugin, best.1.plugin) {
+                Ordering::Greater => candidate,
+                _ => best,
+            }
+        })?;
+
+        Some(SessionResolution::Compatible {
+            original: id.into(),
+            substitute,
+            resolution,
+        })
+    }
+
+    /// Returns the resolution of every plugin that was found in more than one bundle file.
+    ///
+    /// This is meant for hosts to report those conflicts to the user.
+    pub fn conflicts(&self) -> impl Iterator<Item = (&str, Resolution<'_>)> {
+        self.plugins
+            .iter()
+            .filter(|(_, entries)| entries.len() > 1)
+            .filter_map(|(id, _)| Some((id.as_str(), self.resolve(id)?)))
+    }
+}
+
+fn compare_with_preference(
+    preference: ResolutionPreference,
+    a: &IndexedPlugin,
+    b: &IndexedPlugin,
+) -> Ordering {
+    let by_version = || match (&a.version, &b.version) {
+        (Some(a), Some(b)) => compare_versions(a, b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    };
+    let by_time = || a.modified.cmp(&b.modified);
+
+    match preference {
+        ResolutionPreference::HighestVersion => by_version().then_with(by_time),
+        ResolutionPreference::NewestFile => by_time().then_with(by_version),
+    }
+}
+
+/// Compares two plugin version strings, tolerating most common versioning schemes.
+///
+/// Versions are compared component by component, splitting on `.`, with numeric components
+/// compared as numbers (so that `1.10` is greater than `1.9`), and missing components treated as
+/// `0` (so that `1.2` equals `1.2.0`). A leading `v` is ignored, as is any build metadata
+/// following a `+`.
+///
+/// Like with semantic versioning, a pre-release suffix following a `-` (e.g. `1.2.3-beta`) makes a
+/// version lower than the same version without that suffix. Pre-release suffixes are compared
+/// with the same rules as versions (so that `beta.2` is lower than `beta.10`).
+///
+/// Components that aren't numbers are compared lexicographically, and are lower than numbers.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::bundle::index::compare_versions;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(compare_versions("1.2.10", "1.2.9"), Ordering::Greater);
+/// assert_eq!(compare_versions("1.2.3-beta", "1.2.3"), Ordering::Less);
+/// assert_eq!(compare_versions("2021.1", "1.9.9"), Ordering::Greater);
+/// ```
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_release, a_pre) = split_version(a);
+    let (b_release, b_pre) = split_version(b);
+
+    compare_components(a_release, b_release).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_components(a, b),
+    })
+}
+
+/// Splits a version into its release and pre-release parts, ignoring build metadata.
+fn split_version(version: &str) -> (&str, Option<&str>) {
+    let version = version.trim();
+    let version = version
+        .strip_prefix(['v', 'V'])
+        .filter(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+        .unwrap_or(version);
+
+    let version = version.split('+').next().unwrap_or_default();
+
+    match version.split_once('-') {
+        Some((release, pre)) => (release, Some(pre)),
+        None => (version, None),
+    }
+}
+
+fn compare_components(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.').filter(|c| !c.is_empty());
+    let mut b = b.split('.').filter(|c| !c.is_empty());
+
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(a), None) => compare_component(a, "0"),
+            (None, Some(b)) => compare_component("0", b),
+            (Some(a), Some(b)) => compare_component(a, b),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn compare_component(a: &str, b: &str) -> Ordering {
+    fn split_number(component: &str) -> (Option<u64>, &str) {
+        let digits = component
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(component.len());
+
+        let (number, rest) = component.split_at(digits);
+        (number.parse().ok(), rest)
+    }
+
+    let (a_number, a_rest) = split_number(a);
+    let (b_number, b_rest) = split_number(b);
+
+    // None < Some(_): components without a leading number are lower than numeric ones.
+    a_number
+        .cmp(&b_number)
+        .then_with(|| match (a_rest, b_rest) {
+            // A suffix makes a component lower, e.g. 3b < 3.
+            ("", "") => Ordering::Equal,
+            ("", _) if a_number.is_some() => Ordering::Greater,
+            (_, "") if b_number.is_some() => Ordering::Less,
+            (a, b) => a.cmp(b),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cmp::Ordering::*;
+
+    #[test]
+    fn semver_versions_are_ordered() {
+        assert_eq!(compare_versions("1.2.3", "1.2.3"), Equal);
+        assert_eq!(compare_versions("1.2.3", "1.2.4"), Less);
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Greater);
+        assert_eq!(compare_versions("2.0.0", "1.99.99"), Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Equal);
+        assert_eq!(compare_versions("1.2.0.1", "1.2"), Greater);
+        assert_eq!(compare_versions("v1.2.3", "1.2.3"), Equal);
+        assert_eq!(compare_versions("1.2.3+build.5", "1.2.3"), Equal);
+    }
+
+    #[test]
+    fn pre_releases_are_lower() {
+        assert_eq!(compare_versions("1.2.3-beta", "1.2.3"), Less);
+        assert_eq!(compare_versions("1.2.3-alpha", "1.2.3-beta"), Less);
+        assert_eq!(compare_versions("1.2.3-beta.2", "1.2.3-beta.10"), Less);
+        assert_eq!(compare_versions("1.2.3-rc1", "1.2.2"), Greater);
+        assert_eq!(compare_versions("1.2.3-beta+exp", "1.2.3-beta"), Equal);
+    }
+
+    #[test]
+    fn calendar_and_odd_versions_are_ordered() {
+        assert_eq!(compare_versions("2021.1", "2020.12"), Greater);
+        assert_eq!(compare_versions("2021.1", "1.2.3"), Greater);
+        assert_eq!(compare_versions("2021.1.1", "2021.1"), Greater);
+        assert_eq!(compare_versions("1.2.3b", "1.2.3"), Less);
+        assert_eq!(compare_versions("1.2.3b", "1.2.3a"), Greater);
+        assert_eq!(compare_versions("unknown", "0.0.1"), Less);
+        assert_eq!(compare_versions("", "0"), Equal);
+        assert_eq!(compare_versions("version", "version"), Equal);
+    }
+
+    #[test]
+    fn version_comparison_is_antisymmetric() {
+        let versions = [
+            "1",
+            "1.0.1",
+            "1.2.3-beta",
+            "1.2.3",
+            "2021.1",
+            "v3",
+            "abc",
+            "1.2.3b",
+            "",
+        ];
+
+        for a in versions {
+            for b in versions {
+                assert_eq!(compare_versions(a, b), compare_versions(b, a).reverse());
+            }
+        }
+    }
+}
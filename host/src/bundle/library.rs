@@ -1,8 +1,9 @@
 use crate::bundle::PluginBundleError;
 use clack_common::entry::EntryDescriptor;
 use libloading::Library;
-use std::ffi::{CStr, OsStr};
+use std::ffi::CStr;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
 
 pub(crate) struct PluginEntryLibrary {
@@ -13,22 +14,40 @@ pub(crate) struct PluginEntryLibrary {
 // SAFETY: this has a null byte at the end
 const SYMBOL_NAME: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(b"clap_entry\0") };
 impl PluginEntryLibrary {
+    /// Loads the library of the bundle at the given path, which must be [resolved](resolve_path).
+    ///
+    /// If the path is a directory (i.e. a macOS bundle), its executable is loaded instead.
+    ///
     /// # Safety
     ///
     /// Loading an external library is inherently unsafe. Users must try their best to load only
     /// valid CLAP bundles.
-    pub unsafe fn load(path: &OsStr) -> Result<Self, PluginBundleError> {
-        let library = Library::new(path).map_err(PluginBundleError::LibraryLoadingError)?;
+    pub unsafe fn load(path: &Path) -> Result<Self, PluginBundleError> {
+        let metadata = std::fs::metadata(path).map_err(|error| PluginBundleError::FileAccess {
+            path: path.to_path_buf(),
+            error,
+        })?;
 
-        Self::load_from_library(library)
-    }
+        let library = if metadata.is_dir() {
+            let executable_path = bundle_executable_path(path);
+            if !executable_path.is_file() {
+                return Err(PluginBundleError::MissingBundleExecutable {
+                    path: path.to_path_buf(),
+                    executable_path,
+                });
+            }
 
-    /// # Safety
-    ///
-    /// Loading an external library is inherently unsafe. Users must try their best to load only
-    /// valid CLAP bundles.
-    pub unsafe fn load_from_library(library: Library) -> Result<Self, PluginBundleError> {
-        Self::load_from_symbol_in_library(library, SYMBOL_NAME)
+            Library::new(executable_path)
+        } else {
+            Library::new(path)
+        };
+
+        let library = library.map_err(|error| PluginBundleError::LibraryLoadingError {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        Self::load_from_symbol_in_library(library, SYMBOL_NAME, path)
     }
 
     /// # Safety
@@ -38,13 +57,20 @@ impl PluginEntryLibrary {
     pub unsafe fn load_from_symbol_in_library(
         library: Library,
         symbol_name: &CStr,
+        path: &Path,
     ) -> Result<Self, PluginBundleError> {
         let symbol = library
             .get::<*const EntryDescriptor>(symbol_name.to_bytes_with_nul())
-            .map_err(PluginBundleError::LibraryLoadingError)?;
+            .map_err(|error| PluginBundleError::MissingEntrySymbol {
+                path: path.to_path_buf(),
+                error,
+            })?;
 
-        let entry_ptr = NonNull::new(*symbol as *mut EntryDescriptor)
-            .ok_or(PluginBundleError::NullEntryPointer)?;
+        let entry_ptr = NonNull::new(*symbol as *mut EntryDescriptor).ok_or_else(|| {
+            PluginBundleError::NullEntryPointer {
+                path: path.to_path_buf(),
+            }
+        })?;
 
         Ok(Self {
             _library: library,
@@ -72,3 +98,51 @@ impl Deref for PluginEntryLibrary {
 unsafe impl Send for PluginEntryLibrary {}
 // SAFETY: Entries and factories are all thread-safe by the CLAP spec
 unsafe impl Sync for PluginEntryLibrary {}
+
+/// Returns the absolute version of the given path, relative to the current directory.
+///
+/// Symbolic links are not resolved, as plugins may rely on the path they were installed at.
+pub(crate) fn resolve_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    match std::env::current_dir() {
+        Ok(current_dir) => current_dir.join(path),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Returns the path of the executable of a bundle directory.
+///
+/// This is `Contents/MacOS/<executable>`, where `<executable>` is the `CFBundleExecutable` value
+/// of the bundle's `Contents/Info.plist`. If it can't be read, the bundle's file name without the
+/// `.clap` extension is used instead, as is conventional.
+fn bundle_executable_path(bundle_path: &Path) -> PathBuf {
+    let contents = bundle_path.join("Contents");
+
+    let from_plist = std::fs::read_to_string(contents.join("Info.plist"))
+        .ok()
+        .and_then(|plist| plist_executable_name(&plist).map(PathBuf::from));
+
+    let executable = match from_plist {
+        Some(executable) => executable,
+        None => bundle_path
+            .file_stem()
+            .map(PathBuf::from)
+            .unwrap_or_default(),
+    };
+
+    contents.join("MacOS").join(executable)
+}
+
+/// Extracts the `CFBundleExecutable` value from the contents of an XML `Info.plist` file.
+fn plist_executable_name(plist: &str) -> Option<&str> {
+    const KEY: &str = "<key>CFBundleExecutable</key>";
+
+    let after_key = plist[plist.find(KEY)? + KEY.len()..].trim_start();
+    let value = after_key.strip_prefix("<string>")?;
+    let value = value[..value.find("</string>")?].trim();
+
+    (!value.is_empty()).then_some(value)
+}
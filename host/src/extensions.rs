@@ -206,7 +206,7 @@
 //! unsafe extern "C" fn changed<H: HostHandlers>(host: *const clap_host)
 //!     where for<'a> <H as HostHandlers>::MainThread<'a>: HostLatencyImpl,
 //! {
-//!     HostWrapper::<H>::handle(host, |host| {
+//!     HostWrapper::<H>::handle_main_thread(host, "clap_host_latency.changed", |host| {
 //!         host.main_thread().as_mut().changed();
 //!         Ok(())
 //!     });
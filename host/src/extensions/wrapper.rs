@@ -3,16 +3,22 @@
 use crate::plugin::DestroyLock;
 use crate::prelude::*;
 use crate::util::UnsafeOptionCell;
+use clack_common::utils::compatibility::{
+    CheckLevel, CompatibilityCheck, CompatibilityPolicy, Diagnostic,
+};
+use clack_common::utils::fallback_log::{LogOrigin, LogRecord};
 use clap_sys::ext::log::{
     clap_log_severity, CLAP_LOG_HOST_MISBEHAVING, CLAP_LOG_PLUGIN_MISBEHAVING,
 };
 use clap_sys::host::clap_host;
 use clap_sys::plugin::clap_plugin;
+use std::ffi::CStr;
 use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::ptr::NonNull;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Once, OnceLock};
+use std::thread::ThreadId;
 
 #[cfg(not(test))]
 #[allow(unused)]
@@ -26,7 +32,7 @@ fn handle_panic<F: FnOnce() -> R, R>(f: F) -> std::thread::Result<R> {
 }
 
 pub(crate) mod descriptor;
-mod logging;
+pub(crate) mod logging;
 
 // Safety note: once this type is constructed, a pointer to it will be given to the plugin instance,
 // which means we can never
@@ -40,6 +46,14 @@ pub struct HostWrapper<H: HostHandlers> {
     init_started: AtomicBool,
     plugin_ptr: OnceLock<NonNull<clap_plugin>>,
 
+    // The generation of the restart sequence currently in progress, or 0.
+    restart_generation: AtomicU64,
+
+    // Compatibility stuff
+    policy: CompatibilityPolicy,
+    main_thread_id: OnceLock<ThreadId>,
+    unknown_status_reported: AtomicBool,
+
     // Drop stuff
     destroy_lock: Arc<DestroyLock>,
 }
@@ -52,6 +66,16 @@ unsafe impl<H: HostHandlers> Sync for HostWrapper<H> {}
 impl<H: HostHandlers> HostWrapper<H> {
     /// TODO: docs
     ///
+    /// Panics are caught and never unwind into the plugin's frames. If any error or panic occurs,
+    /// `None` is returned, and the error is logged using the host's own `log` extension
+    /// implementation, or sent to the [fallback logger](clack_common::utils::fallback_log) if it is
+    /// unavailable. Failures of the `log` callback itself (or failures that happen while reporting
+    /// another error through it) always go to the fallback logger, as reporting them through the
+    /// same callback would be circular.
+    ///
+    /// The given `callback` name (e.g. `"clap_host.request_restart"`) is only used to identify
+    /// which CLAP callback failed in those error messages.
+    ///
     /// # Safety
     ///
     /// The given host wrapper type `H` **must** be the correct type for the received pointer. Otherwise,
@@ -61,11 +85,16 @@ impl<H: HostHandlers> HostWrapper<H> {
     /// the CLAP Host. While this function does a couple of simple safety checks, only a few common
     /// cases are actually covered (i.e. null checks), and those **must not** be relied upon for safety: those
     /// checks only exist to help debugging.
-    pub unsafe fn handle<T, F>(host: *const clap_host, handler: F) -> Option<T>
+    pub unsafe fn handle_named<T, F>(
+        host: *const clap_host,
+        callback: &'static str,
+        handler: F,
+    ) -> Option<T>
     where
         F: FnOnce(&HostWrapper<H>) -> Result<T, HostWrapperError>,
     {
-        let result = Self::from_raw(host).and_then(|h| {
+        let wrapper = Self::from_raw(host);
+        let result = wrapper.and_then(|h| {
             Self::handle_panic(h, |h| {
                 h.ensure_initializing_called();
                 handler(h)
@@ -74,14 +103,120 @@ impl<H: HostHandlers> HostWrapper<H> {
 
         match result {
             Ok(value) => Some(value),
+            // Denied calls were already reported when they were checked.
+            Err(HostWrapperError::Denied(_)) => None,
             Err(e) => {
-                logging::host_log(host, &e);
+                let plugin_id = wrapper.ok().and_then(|w| w.plugin_id());
+                logging::host_log(host, plugin_id, callback, &e);
 
                 None
             }
         }
     }
 
+    /// Same as [`handle_named`](Self::handle_named), but without a callback name: error messages
+    /// don't tell which CLAP callback failed.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`handle_named`](Self::handle_named).
+    #[deprecated(note = "Use `handle_named`, which identifies the callback in error messages")]
+    #[inline]
+    pub unsafe fn handle<T, F>(host: *const clap_host, handler: F) -> Option<T>
+    where
+        F: FnOnce(&HostWrapper<H>) -> Result<T, HostWrapperError>,
+    {
+        Self::handle_named(host, "", handler)
+    }
+
+    /// Same as [`handle_named`](Self::handle_named), but for host functions that must only be
+    /// called on the main thread.
+    ///
+    /// If the plugin calls it from another thread, this is reported as a
+    /// [`WrongThreadCallbacks`](CompatibilityCheck::WrongThreadCallbacks) violation. If that
+    /// check is denied by the instance's [`CompatibilityPolicy`], the given handler isn't called,
+    /// and `None` is returned.
+    ///
+    /// Calls made before the plugin instance is initialized can't be checked.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`handle_named`](Self::handle_named).
+    pub unsafe fn handle_main_thread<T, F>(
+        host: *const clap_host,
+        callback: &'static str,
+        handler: F,
+    ) -> Option<T>
+    where
+        F: FnOnce(&HostWrapper<H>) -> Result<T, HostWrapperError>,
+    {
+        Self::handle_named(host, callback, |h| {
+            h.check_main_thread(host, callback)?;
+            handler(h)
+        })
+    }
+
+    /// Returns the [`CompatibilityPolicy`] of the plugin instance this host is attached to.
+    #[inline]
+    pub fn compatibility_policy(&self) -> &CompatibilityPolicy {
+        &self.policy
+    }
+
+    /// Reports a violation detected by the given check, following the instance's
+    /// [`CompatibilityPolicy`].
+    ///
+    /// Unless the policy has a diagnostics handler, the violation is logged using the host's own
+    /// `log` extension implementation, or sent to the
+    /// [fallback logger](clack_common::utils::fallback_log) if it is unavailable. Nothing is
+    /// reported if the check is allowed.
+    ///
+    /// This returns the level of the check, so that the caller can act on it.
+    ///
+    /// # Safety
+    ///
+    /// The `host` pointer must be the one given to the plugin instance this host is attached to.
+    pub unsafe fn report(
+        &self,
+        host: *const clap_host,
+        callback: &'static str,
+        check: CompatibilityCheck,
+        severity: clap_log_severity,
+        message: &dyn std::fmt::Display,
+    ) -> CheckLevel {
+        let level = self.policy.level(check);
+        if level == CheckLevel::Allow {
+            return level;
+        }
+
+        let diagnostic = Diagnostic {
+            check,
+            level,
+            severity,
+            instance: self
+                .plugin_ptr
+                .get()
+                .map_or(core::ptr::null(), |p| p.as_ptr()),
+            plugin_id: self.plugin_id(),
+            callback,
+            message,
+        };
+
+        self.policy.report(&diagnostic, |diagnostic| {
+            logging::host_log_record(
+                host,
+                &LogRecord {
+                    origin: LogOrigin::Host,
+                    severity,
+                    plugin_id: diagnostic.plugin_id,
+                    callback,
+                    message: diagnostic,
+                },
+            )
+        });
+
+        level
+    }
+
     /// Returns a raw, non-null pointer to the host's ([`MainThread`](HostHandlers::MainThread)) struct.
     ///
     /// # Safety
@@ -121,7 +256,11 @@ impl<H: HostHandlers> HostWrapper<H> {
         unsafe { shrink_shared_ref::<H>(&self.shared) }
     }
 
-    pub(crate) fn new<FS, FH>(shared: FS, main_thread: FH) -> Pin<Arc<Self>>
+    pub(crate) fn new<FS, FH>(
+        shared: FS,
+        main_thread: FH,
+        policy: CompatibilityPolicy,
+    ) -> Pin<Arc<Self>>
     where
         FS: for<'s> FnOnce(&'s ()) -> <H as HostHandlers>::Shared<'s>,
         FH: for<'s> FnOnce(
@@ -137,6 +276,10 @@ impl<H: HostHandlers> HostWrapper<H> {
             init_guard: Once::new(),
             init_started: AtomicBool::new(false),
             plugin_ptr: OnceLock::new(),
+            restart_generation: AtomicU64::new(0),
+            policy,
+            main_thread_id: OnceLock::new(),
+            unknown_status_reported: AtomicBool::new(false),
             destroy_lock: Arc::new(DestroyLock::new()),
         });
 
@@ -162,6 +305,18 @@ impl<H: HostHandlers> HostWrapper<H> {
         let _ = self.plugin_ptr.set(instance);
     }
 
+    /// Sets the thread the plugin instance is bound to, which main-thread host functions are
+    /// checked against.
+    pub(crate) fn set_main_thread(&self, id: ThreadId) {
+        let _ = self.main_thread_id.set(id);
+    }
+
+    /// Returns `true` if this is the first time the plugin returned an unknown process status.
+    #[inline]
+    pub(crate) fn first_unknown_process_status(&self) -> bool {
+        !self.unknown_status_reported.swap(true, Ordering::Relaxed)
+    }
+
     /// # Safety
     /// This must only be called on the main thread. User must ensure the provided instance pointer
     /// is valid.
@@ -234,6 +389,19 @@ impl<H: HostHandlers> HostWrapper<H> {
         }
     }
 
+    /// Returns the generation of the restart sequence currently in progress, or `0` if the plugin
+    /// isn't being restarted.
+    #[inline]
+    pub(crate) fn restart_generation(&self) -> u64 {
+        self.restart_generation.load(Ordering::Relaxed)
+    }
+
+    /// Sets the generation of the restart sequence currently in progress. `0` ends the sequence.
+    #[inline]
+    pub(crate) fn set_restart_generation(&self, generation: u64) {
+        self.restart_generation.store(generation, Ordering::Relaxed)
+    }
+
     /// # Safety
     /// the user must ensure this is not called concurrently
     /// to [`Self::setup_audio_processor`] or [`Self::teardown_audio_processor`]
@@ -260,6 +428,51 @@ impl<H: HostHandlers> HostWrapper<H> {
             .ok_or(HostWrapperError::NullHostData)
     }
 
+    /// Returns the ID of the plugin instance this host is attached to, if it was already created.
+    fn plugin_id(&self) -> Option<&CStr> {
+        let plugin = self.plugin_ptr.get()?;
+
+        // SAFETY: The pointer is guaranteed to be valid by the caller of created()
+        let descriptor = unsafe { plugin.as_ref().desc.as_ref()? };
+
+        if descriptor.id.is_null() {
+            return None;
+        }
+
+        // SAFETY: The plugin descriptor's ID is guaranteed to be a valid C string by the CLAP spec.
+        Some(unsafe { CStr::from_ptr(descriptor.id) })
+    }
+
+    /// # Safety
+    /// The `host` pointer must be the one given to the plugin instance this host is attached to.
+    unsafe fn check_main_thread(
+        &self,
+        host: *const clap_host,
+        callback: &'static str,
+    ) -> Result<(), HostWrapperError> {
+        let Some(main_thread) = self.main_thread_id.get() else {
+            return Ok(());
+        };
+
+        if *main_thread == std::thread::current().id() {
+            return Ok(());
+        }
+
+        let check = CompatibilityCheck::WrongThreadCallbacks;
+        let level = self.report(
+            host,
+            callback,
+            check,
+            CLAP_LOG_PLUGIN_MISBEHAVING,
+            &"Plugin called a main-thread host function from another thread",
+        );
+
+        match level {
+            CheckLevel::Deny => Err(HostWrapperError::Denied(check)),
+            _ => Ok(()),
+        }
+    }
+
     fn ensure_initializing_called(&self) {
         // This can only happen if the plugin tried to call a host method before init().
         let Some(ptr) = self.plugin_ptr.get().copied() else {
@@ -292,6 +505,8 @@ pub enum HostWrapperError {
     NullHostData,
     Panic,
     HostError(PluginInstanceError),
+    /// The call was denied by the instance's [`CompatibilityPolicy`], which already reported it.
+    Denied(CompatibilityCheck),
 }
 
 impl HostWrapperError {
@@ -302,6 +517,7 @@ impl HostWrapperError {
             HostWrapperError::InvalidParameter(s) => s,
             HostWrapperError::Panic => "Host callback panicked",
             HostWrapperError::HostError(e) => e.msg(),
+            HostWrapperError::Denied(_) => "Call denied by the compatibility policy",
         }
     }
 
@@ -311,6 +527,13 @@ impl HostWrapperError {
             HostWrapperError::InvalidParameter(_) => CLAP_LOG_PLUGIN_MISBEHAVING,
             HostWrapperError::NullHostData => CLAP_LOG_HOST_MISBEHAVING,
             HostWrapperError::Panic => CLAP_LOG_HOST_MISBEHAVING,
+            HostWrapperError::Denied(_) => CLAP_LOG_PLUGIN_MISBEHAVING,
+            // The plugin called a host method the current state of the instance doesn't allow.
+            HostWrapperError::HostError(
+                PluginInstanceError::DeactivatedPlugin
+                | PluginInstanceError::AlreadyActivatedPlugin
+                | PluginInstanceError::PluginDestroyed,
+            ) => CLAP_LOG_PLUGIN_MISBEHAVING,
             HostWrapperError::HostError(e) => e.severity(),
         }
     }
@@ -1,8 +1,9 @@
 use crate::extensions::wrapper::HostWrapper;
 use crate::host::{HostExtensions, HostHandlers, HostInfo, SharedHandler};
-use clack_common::utils::ClapVersion;
+use clack_common::extensions::restart_generation::*;
 use clap_sys::host::clap_host;
 use std::ffi::{c_void, CStr};
+use std::marker::PhantomData;
 
 pub(crate) struct RawHostDescriptor {
     raw: clap_host,
@@ -12,7 +13,7 @@ pub(crate) struct RawHostDescriptor {
 impl RawHostDescriptor {
     pub(crate) fn new<H: HostHandlers>(host_info: HostInfo) -> Self {
         let mut raw = clap_host {
-            clap_version: ClapVersion::CURRENT.to_raw(),
+            clap_version: host_info.clap_version().to_raw(),
             host_data: core::ptr::null_mut(),
             name: core::ptr::null_mut(),
             vendor: core::ptr::null_mut(),
@@ -48,19 +49,45 @@ unsafe extern "C" fn get_extension<H: HostHandlers>(
     host: *const clap_host,
     identifier: *const std::os::raw::c_char,
 ) -> *const c_void {
+    if identifier.is_null() {
+        return core::ptr::null();
+    }
+
     let identifier = CStr::from_ptr(identifier);
-    let mut builder = HostExtensions::new(identifier);
 
-    HostWrapper::<H>::handle(host, |h| {
+    HostWrapper::<H>::handle_named(host, "clap_host.get_extension", |h| {
+        if identifier == CLACK_EXT_RESTART_GENERATION {
+            let raw: *const clack_host_restart_generation = RestartGeneration::<H>::RAW;
+            return Ok(raw.cast());
+        }
+
+        let mut builder = HostExtensions::new(identifier);
         H::declare_extensions(&mut builder, h.shared());
-        Ok(())
-    });
-    builder.found()
+        Ok(builder.found())
+    })
+    .unwrap_or(core::ptr::null())
+}
+
+/// Clack's private restart generation extension, which every Clack host implements.
+struct RestartGeneration<H>(PhantomData<H>);
+
+impl<H: HostHandlers> RestartGeneration<H> {
+    const RAW: &'static clack_host_restart_generation = &clack_host_restart_generation {
+        get: Some(get_restart_generation::<H>),
+    };
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn get_restart_generation<H: HostHandlers>(host: *const clap_host) -> u64 {
+    HostWrapper::<H>::handle_named(host, "clack_host_restart_generation.get", |h| {
+        Ok(h.restart_generation())
+    })
+    .unwrap_or(0)
 }
 
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn request_restart<H: HostHandlers>(host: *const clap_host) {
-    HostWrapper::<H>::handle(host, |h| {
+    HostWrapper::<H>::handle_named(host, "clap_host.request_restart", |h| {
         h.shared().request_restart();
         Ok(())
     });
@@ -68,7 +95,7 @@ unsafe extern "C" fn request_restart<H: HostHandlers>(host: *const clap_host) {
 
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn request_process<H: HostHandlers>(host: *const clap_host) {
-    HostWrapper::<H>::handle(host, |h| {
+    HostWrapper::<H>::handle_named(host, "clap_host.request_process", |h| {
         h.shared().request_process();
         Ok(())
     });
@@ -76,7 +103,7 @@ unsafe extern "C" fn request_process<H: HostHandlers>(host: *const clap_host) {
 
 #[allow(clippy::missing_safety_doc)]
 unsafe extern "C" fn request_callback<H: HostHandlers>(host: *const clap_host) {
-    HostWrapper::<H>::handle(host, |h| {
+    HostWrapper::<H>::handle_named(host, "clap_host.request_callback", |h| {
         h.shared().request_callback();
         Ok(())
     });
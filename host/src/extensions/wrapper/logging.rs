@@ -1,8 +1,21 @@
 use crate::extensions::prelude::HostWrapperError;
+use clack_common::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
 use clap_sys::ext::log::{clap_host_log, clap_log_severity, CLAP_EXT_LOG};
 use clap_sys::host::clap_host;
+use std::cell::Cell;
 use std::os::raw::c_char;
-use std::{error::Error, ffi::CString, fmt::Write};
+use std::{error::Error, ffi::CStr, ffi::CString, fmt::Write};
+
+/// The name of the host's own `log` callback. Errors happening in it are never sent back to it.
+const LOG_CALLBACK: &str = "clap_host_log.log";
+
+thread_local! {
+    /// Whether the host's `log` callback is currently being used to report an error on this thread.
+    ///
+    /// If reporting that error fails as well, this prevents the failure from being reported through
+    /// the same callback again, which would recurse endlessly.
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+}
 
 pub type ClapLoggingFn =
     unsafe extern "C" fn(host: *const clap_host, severity: clap_log_severity, msg: *const c_char);
@@ -18,28 +31,81 @@ unsafe fn get_logger(host: *const clap_host) -> Option<ClapLoggingFn> {
     log.as_ref()?.log
 }
 
-fn log_display(error: &HostWrapperError) -> Result<CString, Box<dyn Error>> {
+fn log_display(record: &LogRecord) -> Result<CString, Box<dyn Error>> {
     let mut buf = String::new();
-    write!(buf, "{}", error.msg())?;
+    write!(buf, "{record}")?;
     Ok(CString::new(buf)?)
 }
 
 /// # Safety
 ///
-/// Plugin pointer must be non-dangling (but can be NULL).
-/// It *must* point to a plugin instance created by Clack.
-pub unsafe fn host_log(host: *const clap_host, e: &HostWrapperError) {
-    if let Some(logger) = get_logger(host) {
-        match log_display(e) {
-            Ok(cstr) => {
-                logger(host, e.severity(), cstr.as_ptr());
-                return;
-            }
-            Err(e) => {
-                eprintln!("[CLAP_HOST_ERROR] Failed to serialize error message for logging: {e}")
-            }
-        };
+/// Host pointer must be non-dangling (but can be NULL).
+/// It *must* point to a valid clap_host instance if not NULL.
+pub unsafe fn host_log(
+    host: *const clap_host,
+    plugin_id: Option<&CStr>,
+    callback: &'static str,
+    e: &HostWrapperError,
+) {
+    // The plugin ID and callback name are prepended to the message, the same way the fallback
+    // logger does, so that these errors can be found in the host's log files.
+    let record = LogRecord {
+        origin: LogOrigin::Host,
+        severity: e.severity(),
+        plugin_id,
+        callback,
+        message: &e.msg(),
+    };
+
+    host_log_record(host, &record);
+}
+
+/// Sends the given record to the host's own `log` extension, or to the fallback logger if it is
+/// unavailable.
+///
+/// # Safety
+///
+/// Same as [`host_log`].
+pub unsafe fn host_log_record(host: *const clap_host, record: &LogRecord) {
+    let circular = record.callback == LOG_CALLBACK || REPORTING.with(Cell::get);
+
+    if !circular {
+        // Both looking up and calling the logger go through host callbacks that may fail too.
+        REPORTING.with(|r| r.set(true));
+        let logged = log_through_host(host, record);
+        REPORTING.with(|r| r.set(false));
+
+        if logged {
+            return;
+        }
     }
 
-    eprintln!("[CLAP_HOST_ERROR] {}", e.msg());
+    fallback_log(record);
+}
+
+/// Returns `true` if the error was sent to the host's own `log` extension.
+///
+/// # Safety
+///
+/// Same as [`host_log`].
+unsafe fn log_through_host(host: *const clap_host, record: &LogRecord) -> bool {
+    let Some(logger) = get_logger(host) else {
+        return false;
+    };
+
+    match log_display(record) {
+        Ok(cstr) => {
+            logger(host, record.severity, cstr.as_ptr());
+            true
+        }
+        Err(serialization_error) => {
+            fallback_log(&LogRecord {
+                message: &format_args!(
+                    "Failed to serialize error message for logging: {serialization_error}"
+                ),
+                ..*record
+            });
+            false
+        }
+    }
 }
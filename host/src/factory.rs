@@ -28,7 +28,11 @@ use std::ffi::{c_void, CStr};
 use std::marker::PhantomData;
 use std::ptr::NonNull;
 
+mod category;
+mod compatibility;
 mod plugin_descriptor;
+pub use category::*;
+pub use compatibility::*;
 pub use plugin_descriptor::*;
 
 /// A custom factory pointer type.
@@ -0,0 +1,421 @@
+use crate::plugin::features::PluginFeature;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+
+/// The kind of an [instrument](PluginCategory::Instrument) plugin.
+///
+/// Variants are declared in decreasing order of priority: if a plugin declares the features of
+/// multiple kinds, the first matching one is picked.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum InstrumentKind {
+    /// `"drum"` or `"drum-machine"`.
+    Drums,
+    /// `"sampler"`.
+    Sampler,
+    /// `"synthesizer"`.
+    Synthesizer,
+    /// No specific instrument feature was declared.
+    Other,
+}
+
+impl InstrumentKind {
+    /// Returns the user-facing label of this kind, e.g. `"Synthesizers"`.
+    pub const fn label(&self) -> &'static str {
+        match self {
+            InstrumentKind::Drums => "Drums",
+            InstrumentKind::Sampler => "Samplers",
+            InstrumentKind::Synthesizer => "Synthesizers",
+            InstrumentKind::Other => "Other",
+        }
+    }
+
+    fn from_feature(feature: PluginFeature) -> Option<Self> {
+        match feature {
+            PluginFeature::Drum | PluginFeature::DrumMachine => Some(InstrumentKind::Drums),
+            PluginFeature::Sampler => Some(InstrumentKind::Sampler),
+            PluginFeature::Synthesizer => Some(InstrumentKind::Synthesizer),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of an [audio effect](PluginCategory::AudioEffect) plugin.
+///
+/// Variants are declared in decreasing order of priority: if a plugin declares the features of
+/// multiple kinds, the first matching one is picked.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum EffectKind {
+    /// `"mastering"`.
+    Mastering,
+    /// `"multi-effects"`.
+    MultiEffects,
+    /// `"reverb"`.
+    Reverb,
+    /// `"delay"`.
+    Delay,
+    /// `"equalizer"`.
+    Equalizer,
+    /// `"filter"`.
+    Filter,
+    /// `"distortion"`.
+    Distortion,
+    /// `"compressor"`, `"expander"`, `"gate"`, `"limiter"`, `"transient-shaper"` or `"de-esser"`.
+    Dynamics,
+    /// `"chorus"`, `"flanger"`, `"phaser"` or `"tremolo"`.
+    Modulation,
+    /// `"pitch-shifter"`, `"pitch-correction"`, `"frequency-shifter"` or `"phase-vocoder"`.
+    Pitch,
+    /// `"restoration"`.
+    Restoration,
+    /// `"utility"`.
+    Utility,
+    /// No specific effect feature was declared.
+    Other,
+}
+
+impl EffectKind {
+    /// Returns the user-facing label of this kind, e.g. `"EQ"`.
+    pub const fn label(&self) -> &'static str {
+        match self {
+            EffectKind::Mastering => "Mastering",
+            EffectKind::MultiEffects => "Multi-Effects",
+            EffectKind::Reverb => "Reverb",
+            EffectKind::Delay => "Delay",
+            EffectKind::Equalizer => "EQ",
+            EffectKind::Filter => "Filter",
+            EffectKind::Distortion => "Distortion",
+            EffectKind::Dynamics => "Dynamics",
+            EffectKind::Modulation => "Modulation",
+            EffectKind::Pitch => "Pitch",
+            EffectKind::Restoration => "Restoration",
+            EffectKind::Utility => "Utility",
+            EffectKind::Other => "Other",
+        }
+    }
+
+    fn from_feature(feature: PluginFeature) -> Option<Self> {
+        match feature {
+            PluginFeature::Mastering => Some(EffectKind::Mastering),
+            PluginFeature::MultiEffects => Some(EffectKind::MultiEffects),
+            PluginFeature::Reverb => Some(EffectKind::Reverb),
+            PluginFeature::Delay => Some(EffectKind::Delay),
+            PluginFeature::Equalizer => Some(EffectKind::Equalizer),
+            PluginFeature::Filter => Some(EffectKind::Filter),
+            PluginFeature::Distortion => Some(EffectKind::Distortion),
+            PluginFeature::Compressor
+            | PluginFeature::Expander
+            | PluginFeature::Gate
+            | PluginFeature::Limiter
+            | PluginFeature::TransientShaper
+            | PluginFeature::Deesser => Some(EffectKind::Dynamics),
+            PluginFeature::Chorus
+            | PluginFeature::Flanger
+            | PluginFeature::Phaser
+            | PluginFeature::Tremolo => Some(EffectKind::Modulation),
+            PluginFeature::PitchShifter
+            | PluginFeature::PitchCorrection
+            | PluginFeature::FrequencyShifter
+            | PluginFeature::PhaseVocoder => Some(EffectKind::Pitch),
+            PluginFeature::Restoration => Some(EffectKind::Restoration),
+            PluginFeature::Utility => Some(EffectKind::Utility),
+            _ => None,
+        }
+    }
+}
+
+/// The category of a plugin, as displayed in a host's plugin browser.
+///
+/// Categories form a two-level tree (e.g. `Effects > EQ`), and are derived from the standard
+/// features a plugin declares in its descriptor, using [`from_features`](Self::from_features) or
+/// [`PluginDescriptor::category`](super::PluginDescriptor::category).
+///
+/// Categories are ordered by their variant declaration order, which allows browsers to sort
+/// plugins by category consistently.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::factory::{EffectKind, PluginCategory};
+/// use clack_host::plugin::features::{AUDIO_EFFECT, EQUALIZER, STEREO};
+///
+/// let category = PluginCategory::from_features([AUDIO_EFFECT, EQUALIZER, STEREO]);
+///
+/// assert_eq!(category, PluginCategory::AudioEffect(EffectKind::Equalizer));
+/// assert_eq!(category.to_string(), "Effects > EQ");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PluginCategory {
+    /// `"instrument"`: the plugin produces audio from note events.
+    Instrument(InstrumentKind),
+    /// `"analyzer"` or `"note-detector"`: the plugin analyzes its audio input.
+    Analyzer,
+    /// `"audio-effect"`: the plugin processes its audio input.
+    AudioEffect(EffectKind),
+    /// `"note-effect"`: the plugin processes or generates note events.
+    NoteEffect,
+    /// The plugin declares no standard feature that could be used to categorize it.
+    Uncategorized,
+}
+
+impl PluginCategory {
+    /// Derives a category from the given plugin features.
+    ///
+    /// The main category is picked in the following order of priority, regardless of the order
+    /// in which the features are declared: instrument, analyzer (or note detector), audio
+    /// effect, then note effect. This allows plugins declaring contradictory features (e.g. both
+    /// `"instrument"` and `"audio-effect"`) to always end up in the same category.
+    ///
+    /// If no main category is declared, it is inferred from the sub-features instead: e.g. a
+    /// plugin only declaring `"reverb"` is considered to be an audio effect. Plugins that don't
+    /// declare any usable feature are [`Uncategorized`](Self::Uncategorized).
+    ///
+    /// Non-standard features are ignored.
+    pub fn from_features<'a>(features: impl IntoIterator<Item = &'a CStr>) -> Self {
+        let mut is_instrument = false;
+        let mut is_analyzer = false;
+        let mut is_audio_effect = false;
+        let mut is_note_effect = false;
+        let mut instrument_kind: Option<InstrumentKind> = None;
+        let mut effect_kind: Option<EffectKind> = None;
+
+        for feature in features.into_iter().filter_map(PluginFeature::from_cstr) {
+            match feature {
+                PluginFeature::Instrument => is_instrument = true,
+                PluginFeature::Analyzer | PluginFeature::NoteDetector => is_analyzer = true,
+                PluginFeature::AudioEffect => is_audio_effect = true,
+                PluginFeature::NoteEffect => is_note_effect = true,
+                _ => {}
+            }
+
+            if let Some(kind) = InstrumentKind::from_feature(feature) {
+                instrument_kind = Some(instrument_kind.map_or(kind, |k| k.min(kind)));
+            }
+
+            if let Some(kind) = EffectKind::from_feature(feature) {
+                effect_kind = Some(effect_kind.map_or(kind, |k| k.min(kind)));
+            }
+        }
+
+        if is_instrument {
+            PluginCategory::Instrument(instrument_kind.unwrap_or(InstrumentKind::Other))
+        } else if is_analyzer {
+            PluginCategory::Analyzer
+        } else if is_audio_effect {
+            PluginCategory::AudioEffect(effect_kind.unwrap_or(EffectKind::Other))
+        } else if is_note_effect {
+            PluginCategory::NoteEffect
+        } else if let Some(kind) = instrument_kind {
+            PluginCategory::Instrument(kind)
+        } else if let Some(kind) = effect_kind {
+            PluginCategory::AudioEffect(kind)
+        } else {
+            PluginCategory::Uncategorized
+        }
+    }
+
+    /// Returns the user-facing labels of this category, from the root of the tree: e.g.
+    /// `("Effects", Some("EQ"))`.
+    pub const fn path(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            PluginCategory::Instrument(kind) => ("Instruments", Some(kind.label())),
+            PluginCategory::Analyzer => ("Analyzers", None),
+            PluginCategory::AudioEffect(kind) => ("Effects", Some(kind.label())),
+            PluginCategory::NoteEffect => ("Note Effects", None),
+            PluginCategory::Uncategorized => ("Uncategorized", None),
+        }
+    }
+}
+
+impl Display for PluginCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.path() {
+            (main, Some(sub)) => write!(f, "{main} > {sub}"),
+            (main, None) => f.write_str(main),
+        }
+    }
+}
+
+/// Words that are too common to be useful when searching for a plugin.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "by", "for", "from", "in", "into", "is", "it", "its", "of", "on", "or",
+    "the", "this", "to", "with", "your",
+];
+
+/// Extracts the search-relevant keywords from the given plugin name and description.
+///
+/// Keywords are made of the lowercased alphanumeric words of both strings, in order of
+/// appearance, without duplicates. Single-letter words and common english words (e.g. "the",
+/// "for") are skipped.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::factory::search_keywords;
+///
+/// let keywords = search_keywords("Vintage Plate", "A plate reverb for vocals and drums.");
+/// assert_eq!(keywords, ["vintage", "plate", "reverb", "vocals", "drums"]);
+/// ```
+pub fn search_keywords(name: &str, description: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+
+    let words = name
+        .split(|c: char| !c.is_alphanumeric())
+        .chain(description.split(|c: char| !c.is_alphanumeric()));
+
+    for word in words {
+        if word.chars().count() < 2 {
+            continue;
+        }
+
+        let word = word.to_lowercase();
+        if STOP_WORDS.contains(&word.as_str()) || keywords.contains(&word) {
+            continue;
+        }
+
+        keywords.push(word);
+    }
+
+    keywords
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::factory::PluginDescriptor;
+    use crate::plugin::features::*;
+    use clap_sys::plugin::clap_plugin_descriptor;
+    use clap_sys::version::CLAP_VERSION;
+    use std::ffi::c_char;
+    use std::ptr::null;
+
+    fn descriptor_fixture(
+        name: &'static [u8],
+        description: &'static [u8],
+        features: &[*const c_char],
+    ) -> clap_plugin_descriptor {
+        clap_plugin_descriptor {
+            clap_version: CLAP_VERSION,
+            id: b"org.rust-audio.clack.fixture\0".as_ptr().cast(),
+            name: name.as_ptr().cast(),
+            vendor: null(),
+            url: null(),
+            manual_url: null(),
+            support_url: null(),
+            version: null(),
+            description: description.as_ptr().cast(),
+            features: features.as_ptr(),
+        }
+    }
+
+    #[test]
+    pub fn main_categories_are_derived() {
+        assert_eq!(
+            PluginCategory::from_features([INSTRUMENT, SYNTHESIZER, STEREO]),
+            PluginCategory::Instrument(InstrumentKind::Synthesizer)
+        );
+        assert_eq!(
+            PluginCategory::from_features([AUDIO_EFFECT, MASTERING, LIMITER]),
+            PluginCategory::AudioEffect(EffectKind::Mastering)
+        );
+        assert_eq!(
+            PluginCategory::from_features([NOTE_EFFECT]),
+            PluginCategory::NoteEffect
+        );
+        assert_eq!(
+            PluginCategory::from_features([NOTE_DETECTOR, AUDIO_EFFECT]),
+            PluginCategory::Analyzer
+        );
+        assert_eq!(
+            PluginCategory::from_features([AUDIO_EFFECT, MONO]),
+            PluginCategory::AudioEffect(EffectKind::Other)
+        );
+    }
+
+    #[test]
+    pub fn contradictory_features_are_resolved_deterministically() {
+        let expected = PluginCategory::Instrument(InstrumentKind::Drums);
+
+        assert_eq!(
+            PluginCategory::from_features([AUDIO_EFFECT, SAMPLER, INSTRUMENT, DRUM_MACHINE]),
+            expected
+        );
+        assert_eq!(
+            PluginCategory::from_features([DRUM_MACHINE, INSTRUMENT, SAMPLER, AUDIO_EFFECT]),
+            expected
+        );
+
+        assert_eq!(
+            PluginCategory::from_features([AUDIO_EFFECT, FILTER, EQUALIZER]),
+            PluginCategory::AudioEffect(EffectKind::Equalizer)
+        );
+        assert_eq!(
+            PluginCategory::from_features([AUDIO_EFFECT, EQUALIZER, FILTER]),
+            PluginCategory::AudioEffect(EffectKind::Equalizer)
+        );
+    }
+
+    #[test]
+    pub fn missing_main_categories_are_inferred() {
+        assert_eq!(
+            PluginCategory::from_features([REVERB, STEREO]),
+            PluginCategory::AudioEffect(EffectKind::Reverb)
+        );
+        assert_eq!(
+            PluginCategory::from_features([DISTORTION, SYNTHESIZER]),
+            PluginCategory::Instrument(InstrumentKind::Synthesizer)
+        );
+    }
+
+    #[test]
+    pub fn unknown_features_are_uncategorized() {
+        let custom = CStr::from_bytes_with_nul(b"my-company:vintage\0").unwrap();
+
+        assert_eq!(
+            PluginCategory::from_features([]),
+            PluginCategory::Uncategorized
+        );
+        assert_eq!(
+            PluginCategory::from_features([custom, STEREO]),
+            PluginCategory::Uncategorized
+        );
+        assert_eq!(PluginCategory::Uncategorized.to_string(), "Uncategorized");
+    }
+
+    #[test]
+    pub fn keywords_are_extracted() {
+        assert_eq!(
+            search_keywords(
+                "DE-ESSER 2",
+                "The de-esser, for your voice & the Voice of others."
+            ),
+            ["de", "esser", "voice", "others"]
+        );
+        assert!(search_keywords("", "").is_empty());
+    }
+
+    #[test]
+    pub fn descriptors_are_categorized() {
+        let features = [AUDIO_EFFECT.as_ptr(), COMPRESSOR.as_ptr(), null()];
+        let raw = descriptor_fixture(b"Squash\0", b"Bus compressor\0", &features);
+        // SAFETY: the fixture's pointers are all valid for the duration of this test.
+        let descriptor = unsafe { PluginDescriptor::from_raw(&raw) };
+
+        assert_eq!(
+            descriptor.category(),
+            PluginCategory::AudioEffect(EffectKind::Dynamics)
+        );
+        assert_eq!(descriptor.category().to_string(), "Effects > Dynamics");
+        assert_eq!(
+            descriptor.search_keywords(),
+            ["squash", "bus", "compressor"]
+        );
+
+        let features = [null()];
+        let raw = descriptor_fixture(b"Mystery\0", b"\0", &features);
+        // SAFETY: the fixture's pointers are all valid for the duration of this test.
+        let descriptor = unsafe { PluginDescriptor::from_raw(&raw) };
+
+        assert_eq!(descriptor.category(), PluginCategory::Uncategorized);
+        assert_eq!(descriptor.search_keywords(), ["mystery"]);
+    }
+}
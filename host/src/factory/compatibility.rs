@@ -0,0 +1,73 @@
+use super::plugin_descriptor::FeaturesIter;
+use super::FactoryPointer;
+use clack_common::plugin::compatibility::*;
+use std::ffi::{c_void, CStr};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+/// A factory pointer that exposes which other plugin IDs the plugins of a bundle are compatible
+/// with.
+///
+/// This factory is not part of the CLAP specification: it is only exposed by plugins made with
+/// Clack that declared some compatible IDs.
+///
+/// # Example
+///
+///```
+/// use clack_host::factory::PluginCompatibilityFactory;
+/// use clack_host::prelude::PluginBundle;
+/// use std::ffi::CStr;
+///
+/// # fn x(bundle: &PluginBundle) {
+/// let bundle: &PluginBundle = /* ... */
+/// # bundle;
+/// if let Some(factory) = bundle.get_factory::<PluginCompatibilityFactory>() {
+///     let plugin_id = CStr::from_bytes_with_nul(b"org.rust-audio.clack.reverb\0").unwrap();
+///
+///     for id in factory.compatible_ids(plugin_id) {
+///         println!("The reverb can replace {}", id.to_string_lossy());
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Copy, Clone)]
+pub struct PluginCompatibilityFactory<'a> {
+    inner: *const clack_plugin_compatibility_factory,
+    _lifetime: PhantomData<&'a clack_plugin_compatibility_factory>,
+}
+
+// SAFETY: This takes a clack_plugin_compatibility_factory pointer, which matches
+// CLACK_PLUGIN_COMPATIBILITY_FACTORY_ID
+unsafe impl<'a> FactoryPointer<'a> for PluginCompatibilityFactory<'a> {
+    const IDENTIFIER: &'static CStr = CLACK_PLUGIN_COMPATIBILITY_FACTORY_ID;
+
+    #[inline]
+    unsafe fn from_raw(raw: NonNull<c_void>) -> Self {
+        Self {
+            inner: raw.as_ptr() as *const _,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'a> PluginCompatibilityFactory<'a> {
+    /// Returns an iterator over the IDs the plugin with the given ID is compatible with.
+    ///
+    /// The host can use that plugin in place of any of those, e.g. when loading a session that
+    /// references one of them.
+    pub fn compatible_ids(&self, plugin_id: &CStr) -> impl Iterator<Item = &'a CStr> {
+        // SAFETY: this type ensures the factory pointer is valid
+        let current = match unsafe { (*self.inner).get_compatible_ids } {
+            None => core::ptr::null(),
+            // SAFETY: this type ensures the function pointer is valid
+            Some(get_compatible_ids) => unsafe {
+                get_compatible_ids(self.inner, plugin_id.as_ptr())
+            },
+        };
+
+        FeaturesIter {
+            current,
+            _lifetime: PhantomData,
+        }
+    }
+}
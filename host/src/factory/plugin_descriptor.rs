@@ -1,3 +1,5 @@
+use super::{search_keywords, PluginCategory};
+use crate::plugin::features::{validate_features, FeatureWarning, PluginFeature};
 use clap_sys::plugin::clap_plugin_descriptor;
 use std::ffi::CStr;
 use std::marker::PhantomData;
@@ -221,11 +223,54 @@ impl<'a> PluginDescriptor<'a> {
             _lifetime: PhantomData,
         }
     }
+
+    /// An iterator over all the standard features in this plugin's feature list.
+    ///
+    /// Non-standard features are skipped. Use [`features`](Self::features) to get all of them.
+    #[inline]
+    pub fn standard_features(&self) -> impl Iterator<Item = PluginFeature> + 'a {
+        FeaturesIter {
+            current: self.descriptor.features,
+            _lifetime: PhantomData,
+        }
+        .filter_map(PluginFeature::from_cstr)
+    }
+
+    /// Checks this plugin's feature list for unknown features or inconsistent combinations.
+    ///
+    /// See [`validate_features`] for more information.
+    #[inline]
+    pub fn validate_features(&self) -> Vec<FeatureWarning> {
+        validate_features(self.features())
+    }
+
+    /// Returns the category of this plugin, derived from its feature list.
+    ///
+    /// See [`PluginCategory::from_features`] for more information.
+    #[inline]
+    pub fn category(&self) -> PluginCategory {
+        PluginCategory::from_features(self.features())
+    }
+
+    /// Returns the search-relevant keywords of this plugin, extracted from its name and
+    /// description.
+    ///
+    /// Non-UTF-8 data is replaced lossily. See [`search_keywords`] for more information.
+    pub fn search_keywords(&self) -> Vec<String> {
+        let name = self.name().map(CStr::to_string_lossy).unwrap_or_default();
+        let description = self
+            .description()
+            .map(CStr::to_string_lossy)
+            .unwrap_or_default();
+
+        search_keywords(&name, &description)
+    }
 }
 
-struct FeaturesIter<'a> {
-    current: *const *const std::os::raw::c_char,
-    _lifetime: PhantomData<&'a CStr>,
+/// An iterator over a NULL-terminated array of C strings.
+pub(super) struct FeaturesIter<'a> {
+    pub(super) current: *const *const std::os::raw::c_char,
+    pub(super) _lifetime: PhantomData<&'a CStr>,
 }
 
 impl<'a> Iterator for FeaturesIter<'a> {
@@ -58,6 +58,13 @@
 //!   However, it should be noted that this type *can* be used by the plugin simultaneously from
 //!   threads that are neither the main thread nor the audio thread.
 //!
+//! On the host's side, main-thread-only operations on a
+//! [`PluginInstance`](crate::plugin::PluginInstance) take a [`MainThreadToken`], which can only be
+//! acquired once, by the host's main thread. Each of those operations also has an `_unchecked`
+//! variant that doesn't require a token. For the methods that existed before tokens were
+//! introduced (e.g. `activate`), the token-taking variants are suffixed with `_checked`, while
+//! the original, token-less methods are deprecated.
+//!
 //! # Example
 //!
 //! This example implements a basic host which is able to process callback requests from the plugin,
@@ -157,6 +164,7 @@
 //! # pub fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! // Information about our totally legit host.
 //! let host_info = HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2")?;
+//! let main_thread = MainThreadToken::acquire().expect("Not on the main thread");
 //!
 //! # mod diva { include!("./bundle/diva_stub.rs"); }
 //! # let bundle = unsafe { PluginBundle::load_from_raw(&diva::DIVA_STUB_ENTRY, "/home/user/.clap/u-he/libdiva.so")? };
@@ -181,7 +189,7 @@
 //!
 //! // This fetches the previous value and sets it to false in a single atomic operation.
 //! if callback_requested.fetch_and(false, Ordering::SeqCst) {
-//!     plugin_instance.call_on_main_thread_callback();
+//!     plugin_instance.call_on_main_thread_callback_checked(&main_thread);
 //! }
 //!
 //! // Do the same for the restart and process requests
@@ -191,12 +199,16 @@
 //! ```
 
 mod error;
+pub mod event_loop;
 mod extensions;
 mod info;
+mod main_thread;
+pub mod tick;
 
-pub use error::HostError;
+pub use error::{HostError, HostErrorKind};
 pub use extensions::HostExtensions;
 pub use info::HostInfo;
+pub use main_thread::MainThreadToken;
 
 use crate::plugin::{InitializedPluginHandle, InitializingPluginHandle};
 
@@ -37,8 +37,12 @@ use std::error::Error;
 /// # perform(true).unwrap()
 /// ```
 ///
+/// New variants may be added in the future: use [`kind`](Self::kind) to handle specific errors
+/// programmatically.
+///
 /// [`stderr`]: std::io::stderr
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum HostError {
     /// A generic, type-erased error.
     Error(Box<dyn Error + 'static>),
@@ -46,6 +50,41 @@ pub enum HostError {
     Message(&'static str),
 }
 
+impl HostError {
+    /// Returns the kind of this error, without any of its associated data.
+    #[inline]
+    pub fn kind(&self) -> HostErrorKind {
+        match self {
+            HostError::Error(_) => HostErrorKind::Error,
+            HostError::Message(_) => HostErrorKind::Message,
+        }
+    }
+
+    /// Returns the underlying error, if this wraps one.
+    ///
+    /// This type cannot implement [`Error`] itself (as it can be converted from any [`Error`]),
+    /// so this method allows to walk its error chain instead.
+    #[inline]
+    pub fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HostError::Error(e) => Some(e.as_ref()),
+            HostError::Message(_) => None,
+        }
+    }
+}
+
+/// The kind of a [`HostError`], without any of its associated data.
+///
+/// See [`HostError::kind`]. Each kind matches the [`HostError`] variant of the same name.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HostErrorKind {
+    /// A generic, type-erased error.
+    Error,
+    /// A constant string message.
+    Message,
+}
+
 impl Display for HostError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -0,0 +1,634 @@
+//! Integration of plugin main-thread requests into asynchronous event loops.
+//!
+//! Plugins request work from the host's main thread in three ways: by calling `request_callback`,
+//! by registering timers, and by registering file descriptors to be polled. Hosts whose main loop
+//! is built around an async runtime can use this module to turn those requests into futures and
+//! tasks, without Clack depending on any specific runtime:
+//!
+//! * [`CallbackRequests`] collects the `request_callback` calls of any number of plugin instances,
+//!   and resolves to the key of an instance whose `on_main_thread` callback must be called.
+//! * The [`HostEventLoop`] trait abstracts over the runtime itself, and is used to run timers and
+//!   file descriptor callbacks on the main thread.
+//! * [`LocalEventLoop`] is a minimal, dependency-free implementation of [`HostEventLoop`], which
+//!   can also drive futures using [`block_on`](LocalEventLoop::block_on).
+//!
+//! # Example
+//!
+//! ```
+//! use clack_host::host::event_loop::*;
+//! use std::sync::Arc;
+//!
+//! struct MyHostShared {
+//!     callback: Arc<CallbackSignal>,
+//! }
+//!
+//! // In SharedHandler::request_callback:
+//! # impl MyHostShared {
+//! fn request_callback(&self) {
+//!     self.callback.notify();
+//! }
+//! # }
+//!
+//! let mut requests = CallbackRequests::new();
+//! let shared = MyHostShared {
+//!     callback: requests.add("my-plugin"),
+//! };
+//!
+//! shared.request_callback();
+//!
+//! let event_loop = LocalEventLoop::new();
+//! let instance = event_loop.block_on(requests.next_request());
+//! assert_eq!(instance, "my-plugin");
+//! // Here, the host calls the instance's on_main_thread callback.
+//! ```
+
+use crate::host::HostError;
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
+use std::time::{Duration, Instant};
+
+/// The waker shared by all the signals of a [`CallbackRequests`] set.
+#[derive(Default)]
+struct SignalWaiter {
+    waker: Mutex<Option<Waker>>,
+}
+
+/// Signals that a single plugin instance requested its `on_main_thread` callback to be called.
+///
+/// This is obtained from [`CallbackRequests::add`], and is meant to be stored in the host's
+/// [`SharedHandler`](crate::host::SharedHandler), so that
+/// [`request_callback`](crate::host::SharedHandler::request_callback) can call
+/// [`notify`](Self::notify).
+pub struct CallbackSignal {
+    pending: AtomicBool,
+    waiter: Arc<SignalWaiter>,
+}
+
+impl CallbackSignal {
+    /// Marks the instance as needing its `on_main_thread` callback to be called, and wakes up the
+    /// task waiting on the matching [`CallbackRequests`].
+    ///
+    /// This is thread-safe and never blocks. However, it wakes up the waiting task using the
+    /// waker of the async runtime, which may not be realtime-safe.
+    pub fn notify(&self) {
+        self.pending.store(true, Ordering::Release);
+
+        // If the lock is taken, the waiting task is registering its waker right now, and will
+        // check the pending flags right after.
+        if let Ok(mut waker) = self.waiter.waker.try_lock() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Returns `true` if a callback was requested, but not serviced yet.
+    #[inline]
+    pub fn is_pending(&self) -> bool {
+        self.pending.load(Ordering::Acquire)
+    }
+}
+
+/// A set of plugin instances whose `request_callback` calls are awaited together.
+///
+/// Each instance is identified by a key of type `K`, and is given a [`CallbackSignal`] through
+/// [`add`](Self::add). The [`next_request`](Self::next_request) future then resolves to the key of an instance
+/// that requested a callback, for which the host must call
+/// [`call_on_main_thread_callback`](crate::plugin::PluginInstance::call_on_main_thread_callback).
+///
+/// Multiple requests from the same instance made before it is serviced are merged into a single
+/// one.
+pub struct CallbackRequests<K> {
+    waiter: Arc<SignalWaiter>,
+    signals: Vec<(K, Arc<CallbackSignal>)>,
+    next_index: usize,
+}
+
+impl<K: Clone> CallbackRequests<K> {
+    /// Creates a new, empty set.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            waiter: Arc::default(),
+            signals: Vec::new(),
+            next_index: 0,
+        }
+    }
+
+    /// Adds an instance with the given key to this set, returning the signal it must notify.
+    pub fn add(&mut self, key: K) -> Arc<CallbackSignal> {
+        let signal = Arc::new(CallbackSignal {
+            pending: AtomicBool::new(false),
+            waiter: self.waiter.clone(),
+        });
+
+        self.signals.push((key, signal.clone()));
+        signal
+    }
+
+    /// Removes the instance with the given key from this set.
+    ///
+    /// Its signal can still be notified, but it won't be reported anymore.
+    pub fn remove(&mut self, key: &K)
+    where
+        K: PartialEq,
+    {
+        self.signals.retain(|(k, _)| k != key);
+    }
+
+    /// Returns the number of instances in this set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.signals.len()
+    }
+
+    /// Returns `true` if this set contains no instances.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.signals.is_empty()
+    }
+
+    /// Returns the key of an instance that requested a callback, if any, and clears its request.
+    ///
+    /// Instances are checked in a round-robin fashion, so that a single instance requesting
+    /// callbacks continuously cannot starve the others.
+    pub fn try_next(&mut self) -> Option<K> {
+        let count = self.signals.len();
+
+        for offset in 0..count {
+            let index = (self.next_index + offset) % count;
+            let (key, signal) = &self.signals[index];
+
+            if signal.pending.swap(false, Ordering::AcqRel) {
+                self.next_index = (index + 1) % count;
+                return Some(key.clone());
+            }
+        }
+
+        None
+    }
+
+    /// Polls for the key of an instance that requested a callback.
+    ///
+    /// This is the stream-like counterpart to [`next_request`](Self::next_request), for use in custom futures or
+    /// stream adapters.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<K> {
+        if let Some(key) = self.try_next() {
+            return Poll::Ready(key);
+        }
+
+        *self.waiter.waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+
+        // A signal may have been notified while the waker was being registered.
+        match self.try_next() {
+            Some(key) => Poll::Ready(key),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Returns a future resolving to the key of the next instance that requested a callback.
+    #[inline]
+    pub fn next_request(&mut self) -> NextCallbackRequest<'_, K> {
+        NextCallbackRequest { requests: self }
+    }
+}
+
+impl<K: Clone> Default for CallbackRequests<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The future returned by [`CallbackRequests::next_request`].
+pub struct NextCallbackRequest<'a, K> {
+    requests: &'a mut CallbackRequests<K>,
+}
+
+impl<K: Clone> Future for NextCallbackRequest<'_, K> {
+    type Output = K;
+
+    #[inline]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<K> {
+        self.requests.poll_next(cx)
+    }
+}
+
+/// The readiness events a file descriptor can be polled for.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FdInterest {
+    /// The file descriptor is ready to be read from.
+    pub read: bool,
+    /// The file descriptor is ready to be written to.
+    pub write: bool,
+    /// An error occurred on the file descriptor.
+    pub error: bool,
+}
+
+/// An async runtime on which plugin timers and file descriptors can be serviced.
+///
+/// Implementations must run every callback they are given on the thread this event loop belongs
+/// to, which must be the host's main thread: plugins' timer and file descriptor callbacks are
+/// `[main-thread]` operations. For multithreaded runtimes, this usually means running them on a
+/// local task set, or a current-thread runtime.
+///
+/// See [`LocalEventLoop`] for a dependency-free implementation.
+pub trait HostEventLoop {
+    /// A handle to a timer registered in this event loop.
+    type Timer;
+
+    /// A handle to a file descriptor registered in this event loop.
+    type Fd;
+
+    /// Spawns a task on the main thread.
+    fn spawn_local(&self, task: Pin<Box<dyn Future<Output = ()>>>);
+
+    /// Registers a timer, calling `on_tick` on the main thread every `period`.
+    fn register_timer(&self, period: Duration, on_tick: Box<dyn FnMut()>) -> Self::Timer;
+
+    /// Unregisters a timer. Its callback is not called anymore once this returns.
+    fn unregister_timer(&self, timer: Self::Timer);
+
+    /// Registers a file descriptor, calling `on_ready` on the main thread every time it is ready
+    /// for any of the given `interest`s.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the file descriptor could not be registered, or if this event
+    /// loop does not support polling file descriptors.
+    #[cfg(unix)]
+    fn register_fd(
+        &self,
+        fd: std::os::fd::RawFd,
+        interest: FdInterest,
+        on_ready: Box<dyn FnMut(FdInterest)>,
+    ) -> Result<Self::Fd, HostError>;
+
+    /// Unregisters a file descriptor. Its callback is not called anymore once this returns.
+    fn unregister_fd(&self, fd: Self::Fd);
+}
+
+struct TaskWaker {
+    woken: AtomicBool,
+    thread: Thread,
+}
+
+impl TaskWaker {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            woken: AtomicBool::new(true),
+            thread: std::thread::current(),
+        })
+    }
+
+    #[inline]
+    fn take_woken(&self) -> bool {
+        self.woken.swap(false, Ordering::AcqRel)
+    }
+
+    #[inline]
+    fn is_woken(&self) -> bool {
+        self.woken.load(Ordering::Acquire)
+    }
+}
+
+impl Wake for TaskWaker {
+    #[inline]
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    #[inline]
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.thread.unpark();
+    }
+}
+
+struct LocalTask {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Arc<TaskWaker>,
+}
+
+struct LocalTimer {
+    id: u64,
+    period: Duration,
+    next_tick: Instant,
+    /// This is `None` while the callback is running.
+    on_tick: Option<Box<dyn FnMut()>>,
+}
+
+#[derive(Default)]
+struct LocalState {
+    tasks: Vec<LocalTask>,
+    timers: Vec<LocalTimer>,
+    next_timer_id: u64,
+}
+
+/// A handle to a timer registered in a [`LocalEventLoop`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LocalTimerHandle(u64);
+
+/// A minimal, single-threaded [`HostEventLoop`] implementation, which doesn't depend on any
+/// async runtime.
+///
+/// This event loop belongs to the thread it was created on, and is driven by calling
+/// [`block_on`](Self::block_on) or [`turn`](Self::turn) from that thread. Timers and spawned tasks
+/// only make progress while it is being driven.
+///
+/// This implementation does not support polling file descriptors.
+pub struct LocalEventLoop {
+    state: Rc<RefCell<LocalState>>,
+}
+
+impl LocalEventLoop {
+    /// Creates a new event loop, belonging to the current thread.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            state: Rc::default(),
+        }
+    }
+
+    /// Runs this event loop until the given future completes, and returns its output.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = TaskWaker::new();
+
+        loop {
+            if waker.take_woken() {
+                let task_waker = Waker::from(waker.clone());
+                if let Poll::Ready(output) =
+                    future.as_mut().poll(&mut Context::from_waker(&task_waker))
+                {
+                    return output;
+                }
+            }
+
+            self.run_ready();
+
+            if !waker.is_woken() {
+                self.park(None);
+            }
+        }
+    }
+
+    /// Fires the due timers and polls the woken tasks, then waits for at most `max_wait` for
+    /// something else to happen.
+    pub fn turn(&self, max_wait: Duration) {
+        self.run_ready();
+        self.park(Some(max_wait));
+        self.run_ready();
+    }
+
+    /// Returns the number of tasks that haven't completed yet.
+    #[inline]
+    pub fn task_count(&self) -> usize {
+        self.state.borrow().tasks.len()
+    }
+
+    fn run_ready(&self) {
+        self.fire_timers();
+        self.poll_tasks();
+    }
+
+    fn fire_timers(&self) {
+        let now = Instant::now();
+
+        let due: Vec<u64> = self
+            .state
+            .borrow()
+            .timers
+            .iter()
+            .filter(|t| t.next_tick <= now)
+            .map(|t| t.id)
+            .collect();
+
+        for id in due {
+            // The callback is taken out while it runs, as it may register or unregister timers.
+            let on_tick = {
+                let mut state = self.state.borrow_mut();
+                let Some(timer) = state.timers.iter_mut().find(|t| t.id == id) else {
+                    continue;
+                };
+
+                timer.next_tick += timer.period;
+                if timer.next_tick <= now {
+                    // Ticks that were missed are skipped, instead of being fired in a burst.
+                    timer.next_tick = now + timer.period;
+                }
+
+                timer.on_tick.take()
+            };
+
+            let Some(mut on_tick) = on_tick else {
+                continue;
+            };
+
+            on_tick();
+
+            let mut state = self.state.borrow_mut();
+            if let Some(timer) = state.timers.iter_mut().find(|t| t.id == id) {
+                timer.on_tick = Some(on_tick);
+            }
+        }
+    }
+
+    fn poll_tasks(&self) {
+        let tasks = std::mem::take(&mut self.state.borrow_mut().tasks);
+        let mut remaining = Vec::with_capacity(tasks.len());
+
+        for mut task in tasks {
+            if task.waker.take_woken() {
+                let waker = Waker::from(task.waker.clone());
+                if task
+                    .future
+                    .as_mut()
+                    .poll(&mut Context::from_waker(&waker))
+                    .is_ready()
+                {
+                    continue;
+                }
+            }
+
+            remaining.push(task);
+        }
+
+        // Tasks spawned while polling were pushed to the state in the meantime.
+        let mut state = self.state.borrow_mut();
+        remaining.append(&mut state.tasks);
+        state.tasks = remaining;
+    }
+
+    fn park(&self, max_wait: Option<Duration>) {
+        let state = self.state.borrow();
+
+        if state.tasks.iter().any(|t| t.waker.is_woken()) {
+            return;
+        }
+
+        let now = Instant::now();
+        let until_next_tick = state
+            .timers
+            .iter()
+            .map(|t| t.next_tick.saturating_duration_since(now))
+            .min();
+
+        drop(state);
+
+        match (until_next_tick, max_wait) {
+            (Some(a), Some(b)) => std::thread::park_timeout(a.min(b)),
+            (Some(timeout), None) | (None, Some(timeout)) => std::thread::park_timeout(timeout),
+            (None, None) => std::thread::park(),
+        }
+    }
+}
+
+impl Default for LocalEventLoop {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HostEventLoop for LocalEventLoop {
+    type Timer = LocalTimerHandle;
+    type Fd = Infallible;
+
+    fn spawn_local(&self, task: Pin<Box<dyn Future<Output = ()>>>) {
+        self.state.borrow_mut().tasks.push(LocalTask {
+            future: task,
+            waker: TaskWaker::new(),
+        });
+    }
+
+    fn register_timer(&self, period: Duration, on_tick: Box<dyn FnMut()>) -> LocalTimerHandle {
+        let mut state = self.state.borrow_mut();
+
+        let id = state.next_timer_id;
+        state.next_timer_id += 1;
+
+        state.timers.push(LocalTimer {
+            id,
+            period,
+            next_tick: Instant::now() + period,
+            on_tick: Some(on_tick),
+        });
+
+        LocalTimerHandle(id)
+    }
+
+    fn unregister_timer(&self, timer: LocalTimerHandle) {
+        self.state.borrow_mut().timers.retain(|t| t.id != timer.0);
+    }
+
+    #[cfg(unix)]
+    fn register_fd(
+        &self,
+        _fd: std::os::fd::RawFd,
+        _interest: FdInterest,
+        _on_ready: Box<dyn FnMut(FdInterest)>,
+    ) -> Result<Infallible, HostError> {
+        Err(HostError::Message(
+            "File descriptors are not supported by this event loop",
+        ))
+    }
+
+    fn unregister_fd(&self, fd: Infallible) {
+        match fd {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn callback_requests_are_merged_and_fair() {
+        let mut requests = CallbackRequests::new();
+        let a = requests.add('a');
+        let b = requests.add('b');
+
+        assert_eq!(requests.try_next(), None);
+
+        a.notify();
+        a.notify();
+        b.notify();
+
+        assert_eq!(requests.try_next(), Some('a'));
+        assert_eq!(requests.try_next(), Some('b'));
+        assert_eq!(requests.try_next(), None);
+
+        requests.remove(&'a');
+        a.notify();
+        assert_eq!(requests.try_next(), None);
+    }
+
+    #[test]
+    fn callback_requests_wake_the_event_loop() {
+        let mut requests = CallbackRequests::new();
+        let signal = requests.add(42);
+
+        let notifier = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            signal.notify();
+        });
+
+        let event_loop = LocalEventLoop::new();
+        assert_eq!(event_loop.block_on(requests.next_request()), 42);
+
+        notifier.join().unwrap();
+    }
+
+    #[test]
+    fn timers_tick_until_unregistered() {
+        let event_loop = LocalEventLoop::new();
+        let ticks = Rc::new(Cell::new(0));
+
+        let timer = event_loop.register_timer(Duration::from_millis(1), {
+            let ticks = ticks.clone();
+            Box::new(move || ticks.set(ticks.get() + 1))
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while ticks.get() < 3 && Instant::now() < deadline {
+            event_loop.turn(Duration::from_millis(5));
+        }
+
+        assert!(ticks.get() >= 3);
+
+        event_loop.unregister_timer(timer);
+        let count = ticks.get();
+        event_loop.turn(Duration::from_millis(5));
+        assert_eq!(ticks.get(), count);
+    }
+
+    #[test]
+    fn spawned_tasks_run_while_blocking() {
+        let event_loop = LocalEventLoop::new();
+        let mut requests = CallbackRequests::new();
+        let signal = requests.add(());
+
+        event_loop.spawn_local(Box::pin(async move { signal.notify() }));
+        assert_eq!(event_loop.task_count(), 1);
+
+        event_loop.block_on(requests.next_request());
+        assert_eq!(event_loop.task_count(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn local_event_loop_does_not_support_fds() {
+        let event_loop = LocalEventLoop::new();
+        let result = event_loop.register_fd(0, FdInterest::default(), Box::new(|_| {}));
+        assert!(result.is_err());
+    }
+}
@@ -46,4 +46,40 @@ impl<'a, H: HostHandlers> HostExtensions<'a, H> {
 
         self
     }
+
+    /// Adds a raw, hand-written extension implementation to the list of extensions this host
+    /// supports.
+    ///
+    /// This is an escape hatch for extensions Clack does not support yet (e.g. bleeding-edge
+    /// drafts), or for interoperability with C code and other frameworks. Prefer implementing
+    /// [`ExtensionImplementation`] and using [`register`](Self::register) whenever possible.
+    ///
+    /// Like [`register`](Self::register), the first registered implementation matching the
+    /// requested identifier is the one exposed to the plugin, regardless of how it was registered.
+    ///
+    /// # Safety
+    ///
+    /// The given pointer must point to the C FFI-compatible extension struct (e.g. a
+    /// `clap_host_xxx` struct) that exactly matches the given `identifier`, as expected by the
+    /// plugin.
+    ///
+    /// This struct, as well as all the functions it points to, must remain valid for as long as
+    /// the plugin instance is alive (in practice, it should usually be a `static`). Those functions
+    /// will be called by the plugin with this host's `clap_host` pointer, and are responsible for
+    /// upholding all of the CLAP specification's thread-safety and validity requirements.
+    pub unsafe fn register_raw(
+        &mut self,
+        identifier: &CStr,
+        implementation: NonNull<c_void>,
+    ) -> &mut Self {
+        if self.found.is_some() {
+            return self;
+        }
+
+        if identifier == self.requested {
+            self.found = Some(implementation)
+        }
+
+        self
+    }
 }
@@ -1,3 +1,4 @@
+use clack_common::utils::ClapVersion;
 use clap_sys::host::clap_host;
 use std::ffi::{CStr, CString, NulError};
 use std::pin::Pin;
@@ -9,6 +10,7 @@ struct HostInfoInner {
     vendor: Pin<Box<CStr>>,
     url: Pin<Box<CStr>>,
     version: Pin<Box<CStr>>,
+    clap_version: ClapVersion,
 }
 
 /// Human-readable information and description about the host.
@@ -70,6 +72,7 @@ impl HostInfo {
                 vendor: Pin::new(vendor.into_boxed_c_str()),
                 url: Pin::new(url.into_boxed_c_str()),
                 version: Pin::new(version.into_boxed_c_str()),
+                clap_version: ClapVersion::CURRENT,
             }),
         }
     }
@@ -90,9 +93,46 @@ impl HostInfo {
             from_opt(info.url()),
             from_opt(info.version()),
         )
+        .with_clap_version(info.clap_version())
+    }
+
+    /// Overrides the CLAP version this host advertises to plugins.
+    ///
+    /// By default, hosts advertise the CLAP version implemented by Clack, i.e.
+    /// [`ClapVersion::CURRENT`]. Overriding it is mostly useful to test how plugins behave when
+    /// loaded by older hosts.
+    ///
+    /// Note that plugins whose bundle uses a version that is not
+    /// [compatible](ClapVersion::is_compatible_with) with the advertised one will fail to
+    /// instantiate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_host::host::HostInfo;
+    /// use clack_host::utils::ClapVersion;
+    ///
+    /// let info = HostInfo::new("Legacy host", "Legacy Corp.", "https://example.com", "1.0.0")
+    ///     .unwrap()
+    ///     .with_clap_version(ClapVersion { major: 1, minor: 0, revision: 0 });
+    ///
+    /// assert_eq!(info.clap_version(), ClapVersion { major: 1, minor: 0, revision: 0 });
+    /// ```
+    pub fn with_clap_version(mut self, clap_version: ClapVersion) -> Self {
+        Arc::make_mut(&mut self.inner).clap_version = clap_version;
+        self
+    }
+
+    /// Returns the CLAP version this host advertises to plugins.
+    ///
+    /// See [`with_clap_version`](HostInfo::with_clap_version) to override it.
+    #[inline]
+    pub fn clap_version(&self) -> ClapVersion {
+        self.inner.clap_version
     }
 
     pub(crate) fn write_to_raw(&self, host: &mut clap_host) {
+        host.clap_version = self.inner.clap_version.to_raw();
         host.name = self.inner.name.as_ptr();
         host.vendor = self.inner.vendor.as_ptr();
         host.url = self.inner.url.as_ptr();
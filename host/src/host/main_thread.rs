@@ -0,0 +1,138 @@
+use std::sync::OnceLock;
+use std::thread::ThreadId;
+
+static MAIN_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// A token proving that the host's main thread has been identified.
+///
+/// Many operations on a [`PluginInstance`](crate::plugin::PluginInstance) (such as activation, or
+/// calls to the plugin's main-thread extensions) must only ever be performed on the main thread.
+/// Methods performing those operations take a reference to this token, which turns this
+/// contract into something the compiler can help with.
+///
+/// A token is obtained once per process using [`acquire`](Self::acquire), which records the
+/// calling thread as the main thread. Hosts integrating with an existing event loop, which
+/// already know which thread is their main thread, can also create a token using
+/// [`new_unchecked`](Self::new_unchecked).
+///
+/// In debug builds, methods taking this token also check that they are actually called from the
+/// thread it was created on, and panic otherwise.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::host::MainThreadToken;
+///
+/// let token = MainThreadToken::acquire().expect("Called from another thread");
+/// assert!(token.is_current());
+///
+/// // Acquiring the token again from the main thread returns the same token.
+/// assert_eq!(MainThreadToken::acquire(), Some(token));
+///
+/// // But it cannot be acquired from any other thread.
+/// std::thread::spawn(|| assert!(MainThreadToken::acquire().is_none()))
+///     .join()
+///     .unwrap();
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MainThreadToken {
+    thread_id: ThreadId,
+}
+
+impl MainThreadToken {
+    /// Acquires the main thread token.
+    ///
+    /// The first call to this function records the calling thread as the process' main thread.
+    /// Subsequent calls return the same token if called from that thread, or `None` if called from
+    /// any other thread.
+    pub fn acquire() -> Option<Self> {
+        let current = std::thread::current().id();
+        let thread_id = *MAIN_THREAD.get_or_init(|| current);
+
+        if thread_id == current {
+            Some(Self { thread_id })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a main thread token for the current thread, without checking or recording it as
+    /// the process' main thread.
+    ///
+    /// This is meant for hosts integrating with an existing event loop, which already provides
+    /// its own guarantees about which thread is the main thread.
+    ///
+    /// # Safety
+    ///
+    /// This must only be called on the host's main thread, i.e. the thread all of the plugin
+    /// instances this token is used with were created on.
+    #[inline]
+    pub unsafe fn new_unchecked() -> Self {
+        Self {
+            thread_id: std::thread::current().id(),
+        }
+    }
+
+    /// Returns the token of the thread recorded as the process' main thread by
+    /// [`acquire`](Self::acquire), if it was ever called.
+    #[inline]
+    pub(crate) fn recorded() -> Option<Self> {
+        MAIN_THREAD.get().map(|&thread_id| Self { thread_id })
+    }
+
+    /// Returns the ID of the thread this token was created for.
+    #[inline]
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread_id
+    }
+
+    /// Returns `true` if this is called from the thread this token was created for.
+    #[inline]
+    pub fn is_current(&self) -> bool {
+        std::thread::current().id() == self.thread_id
+    }
+
+    /// In debug builds, panics if this is called from any thread other than the one this token
+    /// was created for, or if that thread is not the given `expected` thread.
+    #[inline]
+    #[track_caller]
+    pub(crate) fn debug_assert_current(&self, expected: ThreadId) {
+        if cfg!(debug_assertions) {
+            assert!(
+                self.is_current() && self.thread_id == expected,
+                "Main-thread-only operation called from another thread than the host's main thread"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unchecked_token_is_bound_to_its_thread() {
+        // SAFETY: this token is only used to test thread checks.
+        let token = unsafe { MainThreadToken::new_unchecked() };
+        let current = std::thread::current().id();
+        assert!(token.is_current());
+        token.debug_assert_current(current);
+
+        std::thread::spawn(move || assert!(!token.is_current()))
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Main-thread-only operation")]
+    fn mismatched_thread_panics() {
+        // SAFETY: this token is only used to test thread checks.
+        let token = unsafe { MainThreadToken::new_unchecked() };
+        let other = std::thread::spawn(|| std::thread::current().id())
+            .join()
+            .unwrap();
+
+        token.debug_assert_current(other);
+    }
+}
@@ -0,0 +1,598 @@
+//! Servicing plugin main-thread requests from a per-frame tick.
+//!
+//! Hosts built on immediate-mode GUI frameworks don't have an event loop with timers to plug
+//! plugins into: instead, they get a callback once per rendered frame. This module provides a
+//! [`TickDriver`], which is meant to be called from that callback, and which performs all of the
+//! main-thread work the plugins requested since the previous frame:
+//!
+//! * calling their `on_main_thread` callback, after they called `request_callback`;
+//! * firing their due timers, which are kept in a [`TimerQueue`];
+//! * flushing their parameters, after they called `request_flush`;
+//! * running the operations that have been deferred until they are deactivated (see
+//!   [`DeferredMainThreadOps`](crate::plugin::deferred::DeferredMainThreadOps)).
+//!
+//! Each tick is given a time budget, so that a frame never stalls on plugin work. Work that
+//! couldn't be performed within the budget is carried over to the next tick, in the same order.
+//! Instances are serviced in a round-robin fashion, so that a single busy instance cannot starve
+//! the others.
+//!
+//! The driver itself doesn't call into the plugins: it hands each piece of [`TickWork`] to a
+//! closure, which performs it using whichever extensions the host supports.
+//!
+//! # Example
+//!
+//! ```
+//! use clack_host::host::tick::*;
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! struct MyHostShared {
+//!     requests: Arc<TickRequests>,
+//! }
+//!
+//! // In SharedHandler::request_callback:
+//! # impl MyHostShared {
+//! fn request_callback(&self) {
+//!     self.requests.request_callback();
+//! }
+//! # }
+//!
+//! let mut driver = TickDriver::new(Duration::from_millis(4));
+//! let shared = MyHostShared {
+//!     requests: driver.add("my-plugin"),
+//! };
+//!
+//! shared.request_callback();
+//!
+//! // In the GUI framework's per-frame callback:
+//! let frame_time = Duration::from_millis(16);
+//! let report = driver.tick(frame_time, |instance, work| match work {
+//!     TickWork::MainThreadCallback => { /* instance.call_on_main_thread_callback() */ }
+//!     TickWork::Timer(_id) => { /* timer.on_timer(&mut handle, TimerId(id)) */ }
+//!     TickWork::ParamFlush => { /* params.flush(&mut handle, &input, &mut output) */ }
+//!     TickWork::DeferredOps => { /* deferred.restart(...) */ }
+//! });
+//!
+//! assert_eq!(report.performed, 1);
+//! assert_eq!(report.remaining, 0);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A single piece of main-thread work to be performed on a plugin instance.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum TickWork {
+    /// The instance's `on_main_thread` callback must be called.
+    MainThreadCallback,
+    /// The instance's timer with the given ID is due, and its `on_timer` callback must be called.
+    Timer(u32),
+    /// The instance's parameters must be flushed.
+    ParamFlush,
+    /// The operations deferred until the instance is deactivated must be run, restarting the
+    /// instance if needed.
+    DeferredOps,
+}
+
+#[derive(Debug)]
+struct QueuedTimer {
+    id: u32,
+    period: Duration,
+    /// This is `None` until the timer is picked up by the driver's clock.
+    next_tick: Option<Duration>,
+}
+
+/// The timers registered by a single plugin instance.
+///
+/// Timers are driven by the clock of a [`TickDriver`], which only advances by the time given to
+/// each [`tick`](TickDriver::tick), rather than by the system clock.
+#[derive(Debug, Default)]
+pub struct TimerQueue {
+    timers: Vec<QueuedTimer>,
+    next_id: u32,
+}
+
+impl TimerQueue {
+    /// Creates a new queue, with no timers.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            timers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Registers a new timer, ticking every `period`, and returns its ID.
+    ///
+    /// The timer first ticks one `period` after the next clock update.
+    pub fn register(&mut self, period: Duration) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        self.timers.push(QueuedTimer {
+            id,
+            period,
+            next_tick: None,
+        });
+
+        id
+    }
+
+    /// Unregisters the timer with the given ID. Returns `false` if there was no such timer.
+    pub fn unregister(&mut self, id: u32) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|t| t.id != id);
+        self.timers.len() != count
+    }
+
+    /// Returns `true` if a timer with the given ID is registered.
+    #[inline]
+    pub fn contains(&self, id: u32) -> bool {
+        self.timers.iter().any(|t| t.id == id)
+    }
+
+    /// Returns the number of registered timers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    /// Returns `true` if no timers are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Advances the clock to `now`, calling `on_due` with the ID of every timer that is due,
+    /// in the order they are due.
+    ///
+    /// Each timer ticks at most once per call: ticks that were missed are skipped, instead of
+    /// being fired in a burst.
+    pub fn advance_to(&mut self, now: Duration, mut on_due: impl FnMut(u32)) {
+        let mut due: Vec<(Duration, u32)> = Vec::new();
+
+        for timer in &mut self.timers {
+            let Some(next_tick) = timer.next_tick else {
+                timer.next_tick = Some(now + timer.period);
+                continue;
+            };
+
+            if next_tick > now {
+                continue;
+            }
+
+            due.push((next_tick, timer.id));
+
+            let mut next_tick = next_tick + timer.period;
+            if next_tick <= now {
+                next_tick = now + timer.period;
+            }
+            timer.next_tick = Some(next_tick);
+        }
+
+        due.sort_by_key(|&(tick, id)| (tick, id));
+        for (_, id) in due {
+            on_due(id);
+        }
+    }
+}
+
+/// The main-thread requests of a single plugin instance, serviced by a [`TickDriver`].
+///
+/// This is obtained from [`TickDriver::add`], and is meant to be stored in the host's
+/// [`SharedHandler`](crate::host::SharedHandler), so that the host's callbacks can mark the
+/// plugin's requests.
+#[derive(Debug)]
+pub struct TickRequests {
+    callback: AtomicBool,
+    flush: AtomicBool,
+    deferred_ops: AtomicBool,
+    timers: Mutex<TimerQueue>,
+}
+
+impl TickRequests {
+    fn new() -> Self {
+        Self {
+            callback: AtomicBool::new(false),
+            flush: AtomicBool::new(false),
+            deferred_ops: AtomicBool::new(false),
+            timers: Mutex::new(TimerQueue::new()),
+        }
+    }
+
+    /// Marks the instance as needing its `on_main_thread` callback to be called.
+    ///
+    /// This is meant to be called from the host's
+    /// [`request_callback`](crate::host::SharedHandler::request_callback) implementation, and can
+    /// be called from any thread.
+    #[inline]
+    pub fn request_callback(&self) {
+        self.callback.store(true, Ordering::Release)
+    }
+
+    /// Marks the instance as needing its parameters to be flushed.
+    ///
+    /// This is meant to be called from the host's `request_flush` implementation, and can be
+    /// called from any thread.
+    #[inline]
+    pub fn request_flush(&self) {
+        self.flush.store(true, Ordering::Release)
+    }
+
+    /// Marks the instance as having operations waiting for it to be deactivated.
+    ///
+    /// This is meant to be called by the host after it enqueued an operation that couldn't be
+    /// run immediately, or when the plugin called `request_restart`.
+    #[inline]
+    pub fn request_deferred_ops(&self) {
+        self.deferred_ops.store(true, Ordering::Release)
+    }
+
+    /// Registers a new timer for this instance, ticking every `period`, and returns its ID.
+    ///
+    /// This is meant to be called from the host's `register_timer` implementation.
+    #[inline]
+    pub fn register_timer(&self, period: Duration) -> u32 {
+        self.timers().register(period)
+    }
+
+    /// Unregisters the timer with the given ID. Returns `false` if there was no such timer.
+    ///
+    /// Once this returns, the timer is never reported as due anymore, even if it was already
+    /// queued in its [`TickDriver`].
+    ///
+    /// This is meant to be called from the host's `unregister_timer` implementation.
+    #[inline]
+    pub fn unregister_timer(&self, id: u32) -> bool {
+        self.timers().unregister(id)
+    }
+
+    /// Returns the number of timers registered by this instance.
+    #[inline]
+    pub fn timer_count(&self) -> usize {
+        self.timers().len()
+    }
+
+    fn timers(&self) -> MutexGuard<'_, TimerQueue> {
+        self.timers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queues all of the new requests to `queue`, merging the ones that are already queued.
+    fn collect(&self, now: Duration, queue: &mut VecDeque<TickWork>) {
+        let mut push = |work| {
+            if !queue.contains(&work) {
+                queue.push_back(work);
+            }
+        };
+
+        if self.callback.swap(false, Ordering::AcqRel) {
+            push(TickWork::MainThreadCallback);
+        }
+
+        self.timers()
+            .advance_to(now, |id| push(TickWork::Timer(id)));
+
+        if self.flush.swap(false, Ordering::AcqRel) {
+            push(TickWork::ParamFlush);
+        }
+
+        if self.deferred_ops.swap(false, Ordering::AcqRel) {
+            push(TickWork::DeferredOps);
+        }
+    }
+
+    /// Returns `true` if the given queued work is still relevant.
+    fn is_live(&self, work: TickWork) -> bool {
+        match work {
+            TickWork::Timer(id) => self.timers().contains(id),
+            _ => true,
+        }
+    }
+}
+
+struct TickedInstance<K> {
+    key: K,
+    requests: Arc<TickRequests>,
+    queue: VecDeque<TickWork>,
+}
+
+/// The outcome of a [`TickDriver::tick`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct TickReport {
+    /// The number of pieces of work that were performed during this tick.
+    pub performed: usize,
+    /// The number of pieces of work that were carried over to the next tick.
+    pub remaining: usize,
+    /// Whether the tick stopped because its budget was exhausted.
+    pub budget_exhausted: bool,
+}
+
+/// Services the main-thread requests of a set of plugin instances, once per frame.
+///
+/// Each instance is identified by a key of type `K`, and is given a [`TickRequests`] handle
+/// through [`add`](Self::add). Every call to [`tick`](Self::tick) then collects the new requests
+/// of every instance, and performs as much of the queued work as the budget allows.
+///
+/// Work is performed in a round-robin fashion across instances, one piece at a time. Within a
+/// single instance, work is always performed in the order it was queued, even when it is carried
+/// over several ticks. Requests made within a single tick are queued in this order: main-thread
+/// callback, due timers, parameter flush, then deferred operations. Requests that are made again
+/// before being serviced are merged.
+pub struct TickDriver<K> {
+    instances: Vec<TickedInstance<K>>,
+    budget: Duration,
+    now: Duration,
+    next_index: usize,
+}
+
+impl<K> TickDriver<K> {
+    /// Creates a new driver, with no instances, which spends at most `budget` per tick.
+    #[inline]
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            instances: Vec::new(),
+            budget,
+            now: Duration::ZERO,
+            next_index: 0,
+        }
+    }
+
+    /// Returns the maximum amount of time spent performing work in a single tick.
+    #[inline]
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Sets the maximum amount of time spent performing work in a single tick.
+    #[inline]
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+    }
+
+    /// Returns the current time of the driver's clock, i.e. the total time given to all the
+    /// previous ticks.
+    #[inline]
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Adds an instance with the given key, returning the handle its requests must be marked on.
+    pub fn add(&mut self, key: K) -> Arc<TickRequests> {
+        let requests = Arc::new(TickRequests::new());
+
+        self.instances.push(TickedInstance {
+            key,
+            requests: requests.clone(),
+            queue: VecDeque::new(),
+        });
+
+        requests
+    }
+
+    /// Removes the instance with the given key, discarding all of its queued work.
+    ///
+    /// Its handle can still be used, but its requests won't be serviced anymore.
+    pub fn remove(&mut self, key: &K)
+    where
+        K: PartialEq,
+    {
+        let Some(index) = self.instances.iter().position(|i| &i.key == key) else {
+            return;
+        };
+
+        self.instances.remove(index);
+
+        if self.next_index > index {
+            self.next_index -= 1;
+        }
+        if self.next_index >= self.instances.len() {
+            self.next_index = 0;
+        }
+    }
+
+    /// Returns the number of instances in this driver.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Returns `true` if this driver contains no instances.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Returns the number of pieces of work that are queued, and waiting for the next tick.
+    ///
+    /// This doesn't include the requests made since the last tick.
+    #[inline]
+    pub fn pending_count(&self) -> usize {
+        self.instances.iter().map(|i| i.queue.len()).sum()
+    }
+
+    /// Advances the driver's clock by `elapsed`, collects the new requests of every instance,
+    /// and performs the queued work, calling `perform` with the key of the instance and the
+    /// work to perform on it.
+    ///
+    /// This stops as soon as the time spent performing work exceeds the budget. At least one
+    /// piece of work is always performed if any is queued, so that progress is always made, even
+    /// with a zero budget.
+    ///
+    /// This must be called on the main thread, as `perform` calls `[main-thread]` plugin
+    /// functions.
+    pub fn tick(&mut self, elapsed: Duration, mut perform: impl FnMut(&K, TickWork)) -> TickReport {
+        let start = Instant::now();
+        self.now += elapsed;
+
+        for instance in &mut self.instances {
+            instance.requests.collect(self.now, &mut instance.queue);
+        }
+
+        let mut report = TickReport::default();
+
+        while let Some(index) = self.next_busy_instance() {
+            if report.performed > 0 && start.elapsed() >= self.budget {
+                report.budget_exhausted = true;
+                break;
+            }
+
+            self.next_index = (index + 1) % self.instances.len();
+            let instance = &mut self.instances[index];
+
+            // PANIC: next_busy_instance only returns instances with queued work.
+            let work = instance.queue.pop_front().unwrap();
+            if !instance.requests.is_live(work) {
+                continue;
+            }
+
+            perform(&instance.key, work);
+            report.performed += 1;
+        }
+
+        report.remaining = self.pending_count();
+        report
+    }
+
+    /// Returns the index of the next instance with queued work, starting from the round-robin
+    /// cursor.
+    fn next_busy_instance(&self) -> Option<usize> {
+        let count = self.instances.len();
+
+        (0..count)
+            .map(|offset| (self.next_index + offset) % count)
+            .find(|&index| !self.instances[index].queue.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timers_tick_on_the_driver_clock() {
+        let mut timers = TimerQueue::new();
+        let fast = timers.register(Duration::from_millis(10));
+        let slow = timers.register(Duration::from_millis(25));
+
+        let mut advance = |now| {
+            let mut due: Vec<u32> = Vec::new();
+            timers.advance_to(Duration::from_millis(now), |id| due.push(id));
+            due
+        };
+
+        // Timers start ticking from the first clock update.
+        assert!(advance(0).is_empty());
+        assert!(advance(9).is_empty());
+        assert_eq!(advance(10), [fast]);
+        assert_eq!(advance(25), [fast, slow]);
+        // Missed ticks are skipped.
+        assert_eq!(advance(100), [fast, slow]);
+        assert!(advance(109).is_empty());
+        assert_eq!(advance(110), [fast]);
+    }
+
+    #[test]
+    fn unregistered_timers_are_not_reported() {
+        let mut driver = TickDriver::new(Duration::from_secs(1));
+        let requests = driver.add(());
+
+        let timer = requests.register_timer(Duration::from_millis(10));
+        assert_eq!(requests.timer_count(), 1);
+
+        driver.tick(Duration::ZERO, |_, _| panic!("Nothing should be due"));
+
+        let mut performed = Vec::new();
+        driver.tick(Duration::from_millis(10), |_, work| performed.push(work));
+        assert_eq!(performed, [TickWork::Timer(timer)]);
+
+        assert!(requests.unregister_timer(timer));
+        assert!(!requests.unregister_timer(timer));
+
+        driver.tick(Duration::from_millis(10), |_, _| {
+            panic!("Timer was unregistered")
+        });
+    }
+
+    #[test]
+    fn requests_are_merged_and_ordered() {
+        let mut driver = TickDriver::new(Duration::from_secs(1));
+        let requests = driver.add('a');
+
+        requests.request_deferred_ops();
+        requests.request_flush();
+        requests.request_callback();
+        requests.request_callback();
+
+        let mut performed = Vec::new();
+        let report = driver.tick(Duration::ZERO, |key, work| performed.push((*key, work)));
+
+        assert_eq!(
+            performed,
+            [
+                ('a', TickWork::MainThreadCallback),
+                ('a', TickWork::ParamFlush),
+                ('a', TickWork::DeferredOps),
+            ]
+        );
+        assert_eq!(
+            report,
+            TickReport {
+                performed: 3,
+                remaining: 0,
+                budget_exhausted: false
+            }
+        );
+    }
+
+    #[test]
+    fn zero_budget_still_makes_progress() {
+        let mut driver = TickDriver::new(Duration::ZERO);
+        let a = driver.add('a');
+        let b = driver.add('b');
+
+        a.request_callback();
+        a.request_flush();
+        b.request_callback();
+
+        let mut performed = Vec::new();
+        for _ in 0..3 {
+            let report = driver.tick(Duration::ZERO, |key, work| performed.push((*key, work)));
+            assert_eq!(report.performed, 1);
+        }
+
+        // Instances take turns, and each instance's work stays in order.
+        assert_eq!(
+            performed,
+            [
+                ('a', TickWork::MainThreadCallback),
+                ('b', TickWork::MainThreadCallback),
+                ('a', TickWork::ParamFlush),
+            ]
+        );
+        assert_eq!(driver.pending_count(), 0);
+    }
+
+    #[test]
+    fn removing_instances_keeps_the_cursor_in_bounds() {
+        let mut driver = TickDriver::new(Duration::ZERO);
+        let a = driver.add('a');
+        let b = driver.add('b');
+        driver.add('c');
+
+        a.request_callback();
+        b.request_callback();
+        b.request_flush();
+
+        driver.tick(Duration::ZERO, |_, _| {});
+        driver.tick(Duration::ZERO, |_, _| {});
+        driver.remove(&'c');
+        driver.remove(&'a');
+        assert_eq!(driver.len(), 1);
+
+        let mut performed = Vec::new();
+        driver.tick(Duration::ZERO, |key, work| performed.push((*key, work)));
+        assert_eq!(performed, [('b', TickWork::ParamFlush)]);
+    }
+}
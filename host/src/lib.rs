@@ -141,6 +141,10 @@
 //! // Information about our totally legit host.
 //! let host_info = HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2")?;
 //!
+//! // This token marks the current thread as our main thread, and is required to call
+//! // main-thread-only methods.
+//! let main_thread = MainThreadToken::acquire().expect("Not on the main thread");
+//!
 //! // Step 1: Load the bundle in memory.
 //! # mod diva { include!("./bundle/diva_stub.rs"); }
 //! # let bundle = unsafe { PluginBundle::load_from_raw(&diva::DIVA_STUB_ENTRY, "/home/user/.clap/u-he/libdiva.so")? };
@@ -179,7 +183,7 @@
 //!     min_frames_count: 4,
 //!     max_frames_count: 4,
 //! };
-//! let audio_processor = plugin_instance.activate(|_, _| (), audio_configuration)?;
+//! let audio_processor = plugin_instance.activate_checked(&main_thread, |_, _| (), audio_configuration)?;
 //!
 //! // Event buffers
 //! // For this example, we'll only have a single input event.
@@ -256,10 +260,12 @@
 //!
 //! // Step 9: stop and deactivate the plugin.
 //!
-//! plugin_instance.deactivate(audio_processor);
+//! plugin_instance.deactivate_checked(&main_thread, audio_processor);
 //! # Ok(()) }
 //! ```
 
+#[cfg(feature = "rt-thread")]
+pub mod audio_thread;
 pub mod bundle;
 pub mod extensions;
 pub mod factory;
@@ -273,16 +279,32 @@ pub use clack_common::stream;
 pub use clack_common::utils;
 
 /// A helpful prelude re-exporting all the types related to host implementation.
+///
+/// This covers bundle loading, the host handler traits, plugin instances and their handles, audio
+/// processors and their buffers, as well as the standard event types.
+///
+/// Some items are intentionally left out:
+///
+/// * raw FFI wrappers and the underlying `clap_sys` types, which have to be imported from their
+///   own module (or from `clap_sys` directly);
+/// * the [`PluginDescriptor`](factory::PluginDescriptor) and
+///   [`PluginAudioProcessor`](process::PluginAudioProcessor) types, as their names would collide
+///   with the plugin-side types of the same name when used along with `clack_plugin`'s prelude,
+///   e.g. in tests.
+///
+/// Types related to extensions are provided by `clack_extensions::prelude`.
 pub mod prelude {
     pub use crate::{
-        bundle::PluginBundle,
+        bundle::{PluginBundle, PluginBundleError},
         events::{
-            io::{EventBuffer, InputEvents, OutputEvents},
-            Event, EventHeader, Pckn, UnknownEvent,
+            event_types::*,
+            io::{EventBuffer, EventIo, InputEvents, OutputEvents},
+            spaces::CoreEventSpace,
+            Event, EventFlags, EventHeader, Match, Pckn, UnknownEvent,
         },
         host::{
             AudioProcessorHandler, HostError, HostExtensions, HostHandlers, HostInfo,
-            MainThreadHandler, SharedHandler,
+            MainThreadHandler, MainThreadToken, SharedHandler,
         },
         plugin::{
             InitializedPluginHandle, InitializingPluginHandle, PluginAudioProcessorHandle,
@@ -294,8 +316,12 @@ pub mod prelude {
                 OutputAudioBuffers,
             },
             AudioPortProcessingInfo, PluginAudioConfiguration, ProcessStatus,
-            StoppedPluginAudioProcessor,
+            StartedPluginAudioProcessor, StoppedPluginAudioProcessor,
+        },
+        stream::{InputStream, OutputStream},
+        utils::{
+            compatibility::{CheckLevel, CompatibilityCheck, CompatibilityPolicy},
+            ClapId, Cookie,
         },
-        utils::ClapId,
     };
 }
@@ -1,27 +1,49 @@
+use crate::extensions::{Extension, PluginExtensionSide};
 use crate::prelude::*;
 use clap_sys::plugin::clap_plugin;
 use std::ffi::CStr;
 use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 use std::sync::Arc;
+use std::thread::ThreadId;
 
+pub mod deferred;
 mod error;
+mod extension_cache;
 mod handle;
 pub(crate) mod instance;
+pub mod parallel;
+pub mod rack;
+pub mod restart;
 
 pub use error::PluginInstanceError;
+use extension_cache::ExtensionCache;
 pub use handle::*;
 use instance::*;
 
 pub use clack_common::plugin::*;
 
 /// A plugin instance.
+///
+/// # Host data
+///
+/// The `host_data` field of the `clap_host` struct given to the plugin always points to the
+/// private state `clack-host` keeps for this instance. It is set before the plugin is created,
+/// and never changes during the lifetime of the instance. Plugins must treat it as opaque: it
+/// cannot be used to pass custom context to a plugin, as changing it would break every callback
+/// the plugin makes to the host. Custom host extensions should be used for that purpose instead.
 pub struct PluginInstance<H: HostHandlers> {
     pub(crate) inner: ManuallyDrop<Arc<PluginInstanceInner<H>>>,
+    pub(crate) main_thread: ThreadId,
+    extensions: ExtensionCache,
     _no_send: PhantomData<*const ()>,
 }
 
 impl<H: HostHandlers> PluginInstance<H> {
+    /// Creates and initializes a new plugin instance, with the default
+    /// [`CompatibilityPolicy`].
+    ///
+    /// See [`new_with_policy`](Self::new_with_policy) to use another policy.
     pub fn new<FS, FH>(
         shared: FS,
         main_thread: FH,
@@ -29,6 +51,35 @@ impl<H: HostHandlers> PluginInstance<H> {
         plugin_id: &CStr,
         host: &HostInfo,
     ) -> Result<Self, PluginInstanceError>
+    where
+        FS: for<'b> FnOnce(&'b ()) -> <H as HostHandlers>::Shared<'b>,
+        FH: for<'b> FnOnce(
+            &'b <H as HostHandlers>::Shared<'b>,
+        ) -> <H as HostHandlers>::MainThread<'b>,
+    {
+        Self::new_with_policy(
+            shared,
+            main_thread,
+            bundle,
+            plugin_id,
+            host,
+            CompatibilityPolicy::default(),
+        )
+    }
+
+    /// Creates and initializes a new plugin instance, which handles the plugin's violations of
+    /// the CLAP specification following the given [`CompatibilityPolicy`].
+    ///
+    /// Only the host-side checks apply: they cover main-thread host functions called from other
+    /// threads, and process statuses unknown to this version of Clack.
+    pub fn new_with_policy<FS, FH>(
+        shared: FS,
+        main_thread: FH,
+        bundle: &PluginBundle,
+        plugin_id: &CStr,
+        host: &HostInfo,
+        policy: CompatibilityPolicy,
+    ) -> Result<Self, PluginInstanceError>
     where
         FS: for<'b> FnOnce(&'b ()) -> <H as HostHandlers>::Shared<'b>,
         FH: for<'b> FnOnce(
@@ -41,19 +92,88 @@ impl<H: HostHandlers> PluginInstance<H> {
             bundle,
             plugin_id,
             host.clone(),
+            policy,
         )?;
 
-        Ok(Self {
+        Ok(Self::from_inner(inner))
+    }
+
+    /// Wraps an initialized instance, binding it to the current thread.
+    pub(crate) fn from_inner(inner: Arc<PluginInstanceInner<H>>) -> Self {
+        Self {
             inner: ManuallyDrop::new(inner),
+            main_thread: std::thread::current().id(),
+            extensions: ExtensionCache::new(),
             _no_send: PhantomData,
-        })
+        }
     }
 
+    /// Activates the plugin with the given audio configuration, and returns its audio processor.
+    ///
+    /// This is the same as [`activate_unchecked`](Self::activate_unchecked).
+    #[inline]
+    #[deprecated(note = "Use `activate_checked` with a `MainThreadToken`, or `activate_unchecked`")]
     pub fn activate<FA>(
         &mut self,
         audio_processor: FA,
         configuration: PluginAudioConfiguration,
     ) -> Result<StoppedPluginAudioProcessor<H>, PluginInstanceError>
+    where
+        FA: for<'a> FnOnce(
+            &'a <H as HostHandlers>::Shared<'a>,
+            &mut <H as HostHandlers>::MainThread<'a>,
+        ) -> <H as HostHandlers>::AudioProcessor<'a>,
+    {
+        self.activate_unchecked(audio_processor, configuration)
+    }
+
+    /// Activates the plugin with the given audio configuration, and returns its audio processor.
+    ///
+    /// This must be called on the main thread, which is enforced by the given [`MainThreadToken`].
+    /// See [`activate_unchecked`](Self::activate_unchecked) for a version of this method that
+    /// doesn't require a token.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`PluginInstanceError::AlreadyActivatedPlugin`] if the plugin is already
+    /// active, even if its audio processor has since been dropped (see
+    /// [`has_active_processor`](Self::has_active_processor)), or
+    /// [`PluginInstanceError::ActivationFailed`] if the plugin failed to activate.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if the token's thread isn't the one this instance was created
+    /// on.
+    #[track_caller]
+    pub fn activate_checked<FA>(
+        &mut self,
+        main_thread: &MainThreadToken,
+        audio_processor: FA,
+        configuration: PluginAudioConfiguration,
+    ) -> Result<StoppedPluginAudioProcessor<H>, PluginInstanceError>
+    where
+        FA: for<'a> FnOnce(
+            &'a <H as HostHandlers>::Shared<'a>,
+            &mut <H as HostHandlers>::MainThread<'a>,
+        ) -> <H as HostHandlers>::AudioProcessor<'a>,
+    {
+        main_thread.debug_assert_current(self.main_thread);
+        self.activate_unchecked(audio_processor, configuration)
+    }
+
+    /// Activates the plugin with the given audio configuration, and returns its audio processor.
+    ///
+    /// Unlike [`activate_checked`](Self::activate_checked), this doesn't require a [`MainThreadToken`]. Callers
+    /// are responsible for only calling this on the thread this instance was created on.
+    ///
+    /// # Errors
+    ///
+    /// See [`activate_checked`](Self::activate_checked).
+    pub fn activate_unchecked<FA>(
+        &mut self,
+        audio_processor: FA,
+        configuration: PluginAudioConfiguration,
+    ) -> Result<StoppedPluginAudioProcessor<H>, PluginInstanceError>
     where
         FA: for<'a> FnOnce(
             &'a <H as HostHandlers>::Shared<'a>,
@@ -68,20 +188,92 @@ impl<H: HostHandlers> PluginInstance<H> {
     }
 
     #[inline]
+    #[deprecated(
+        note = "Use `deactivate_checked` with a `MainThreadToken`, or `deactivate_unchecked`"
+    )]
     pub fn deactivate(&mut self, processor: StoppedPluginAudioProcessor<H>) {
-        self.deactivate_with(processor, |_, _| ())
+        self.deactivate_unchecked(processor)
     }
 
     #[inline]
+    #[track_caller]
+    pub fn deactivate_checked(
+        &mut self,
+        main_thread: &MainThreadToken,
+        processor: StoppedPluginAudioProcessor<H>,
+    ) {
+        main_thread.debug_assert_current(self.main_thread);
+        self.deactivate_unchecked(processor)
+    }
+
+    #[inline]
+    pub fn deactivate_unchecked(&mut self, processor: StoppedPluginAudioProcessor<H>) {
+        self.deactivate_with_unchecked(processor, |_, _| ())
+    }
+
+    #[inline]
+    #[deprecated(
+        note = "Use `try_deactivate_checked` with a `MainThreadToken`, or `try_deactivate_unchecked`"
+    )]
     pub fn try_deactivate(&mut self) -> Result<(), PluginInstanceError> {
-        self.try_deactivate_with(|_, _| ())
+        self.try_deactivate_unchecked()
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn try_deactivate_checked(
+        &mut self,
+        main_thread: &MainThreadToken,
+    ) -> Result<(), PluginInstanceError> {
+        main_thread.debug_assert_current(self.main_thread);
+        self.try_deactivate_unchecked()
     }
 
+    #[inline]
+    pub fn try_deactivate_unchecked(&mut self) -> Result<(), PluginInstanceError> {
+        self.try_deactivate_with_unchecked(|_, _| ())
+    }
+
+    #[inline]
+    #[deprecated(
+        note = "Use `deactivate_with_checked` with a `MainThreadToken`, or `deactivate_with_unchecked`"
+    )]
     pub fn deactivate_with<T, D>(
         &mut self,
         processor: StoppedPluginAudioProcessor<H>,
         drop_with: D,
     ) -> T
+    where
+        D: for<'s> FnOnce(
+            <H as HostHandlers>::AudioProcessor<'s>,
+            &mut <H as HostHandlers>::MainThread<'s>,
+        ) -> T,
+    {
+        self.deactivate_with_unchecked(processor, drop_with)
+    }
+
+    #[track_caller]
+    pub fn deactivate_with_checked<T, D>(
+        &mut self,
+        main_thread: &MainThreadToken,
+        processor: StoppedPluginAudioProcessor<H>,
+        drop_with: D,
+    ) -> T
+    where
+        D: for<'s> FnOnce(
+            <H as HostHandlers>::AudioProcessor<'s>,
+            &mut <H as HostHandlers>::MainThread<'s>,
+        ) -> T,
+    {
+        main_thread.debug_assert_current(self.main_thread);
+        self.deactivate_with_unchecked(processor, drop_with)
+    }
+
+    pub fn deactivate_with_unchecked<T, D>(
+        &mut self,
+        processor: StoppedPluginAudioProcessor<H>,
+        drop_with: D,
+    ) -> T
     where
         D: for<'s> FnOnce(
             <H as HostHandlers>::AudioProcessor<'s>,
@@ -95,10 +287,43 @@ impl<H: HostHandlers> PluginInstance<H> {
         drop(processor);
 
         // PANIC: we dropped the only processor produced, and checked if it matched
-        self.try_deactivate_with(drop_with).unwrap()
+        self.try_deactivate_with_unchecked(drop_with).unwrap()
     }
 
+    #[inline]
+    #[deprecated(
+        note = "Use `try_deactivate_with_checked` with a `MainThreadToken`, or `try_deactivate_with_unchecked`"
+    )]
     pub fn try_deactivate_with<T, D>(&mut self, drop_with: D) -> Result<T, PluginInstanceError>
+    where
+        D: for<'s> FnOnce(
+            <H as HostHandlers>::AudioProcessor<'s>,
+            &mut <H as HostHandlers>::MainThread<'s>,
+        ) -> T,
+    {
+        self.try_deactivate_with_unchecked(drop_with)
+    }
+
+    #[track_caller]
+    pub fn try_deactivate_with_checked<T, D>(
+        &mut self,
+        main_thread: &MainThreadToken,
+        drop_with: D,
+    ) -> Result<T, PluginInstanceError>
+    where
+        D: for<'s> FnOnce(
+            <H as HostHandlers>::AudioProcessor<'s>,
+            &mut <H as HostHandlers>::MainThread<'s>,
+        ) -> T,
+    {
+        main_thread.debug_assert_current(self.main_thread);
+        self.try_deactivate_with_unchecked(drop_with)
+    }
+
+    pub fn try_deactivate_with_unchecked<T, D>(
+        &mut self,
+        drop_with: D,
+    ) -> Result<T, PluginInstanceError>
     where
         D: for<'s> FnOnce(
             <H as HostHandlers>::AudioProcessor<'s>,
@@ -113,8 +338,24 @@ impl<H: HostHandlers> PluginInstance<H> {
 
     // FIXME: this should be on the handle?
     #[inline]
+    #[deprecated(
+        note = "Use `call_on_main_thread_callback_checked` with a `MainThreadToken`, or `call_on_main_thread_callback_unchecked`"
+    )]
     pub fn call_on_main_thread_callback(&mut self) {
-        // SAFETY: this is done on the main thread, and the &mut reference guarantees no aliasing
+        self.call_on_main_thread_callback_unchecked()
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn call_on_main_thread_callback_checked(&mut self, main_thread: &MainThreadToken) {
+        main_thread.debug_assert_current(self.main_thread);
+        self.call_on_main_thread_callback_unchecked()
+    }
+
+    #[inline]
+    pub fn call_on_main_thread_callback_unchecked(&mut self) {
+        // SAFETY: this type is not Send, so this is done on the thread the instance was created on,
+        // and the &mut reference guarantees no aliasing
         unsafe { self.inner.on_main_thread() }
     }
 
@@ -123,11 +364,76 @@ impl<H: HostHandlers> PluginInstance<H> {
         self.inner.raw_instance()
     }
 
+    /// Returns `true` if the plugin is currently activated.
+    ///
+    /// This remains `true` until the plugin is deactivated, even if its audio processor was
+    /// dropped in the meantime.
     #[inline]
     pub fn is_active(&self) -> bool {
         self.inner.is_active()
     }
 
+    /// Returns the audio configuration the plugin was activated with, or `None` if it is not
+    /// currently active.
+    #[inline]
+    pub fn activation_config(&self) -> Option<PluginAudioConfiguration> {
+        self.inner.activation_config()
+    }
+
+    /// Returns `true` if the audio processor returned by [`activate`](Self::activate) is still
+    /// alive.
+    ///
+    /// If the plugin [is active](Self::is_active) but its audio processor was dropped, it can
+    /// still be deactivated using [`try_deactivate`](Self::try_deactivate), after which it can
+    /// be activated again.
+    #[inline]
+    pub fn has_active_processor(&self) -> bool {
+        Arc::strong_count(&self.inner) > 1
+    }
+
+    /// Gives temporary access to this instance's [`Shared`](HostHandlers::Shared) handler.
+    ///
+    /// The shared handler may be concurrently accessed by the plugin from any thread, including
+    /// the audio thread. Any state that needs to be updated after the instance was created (e.g.
+    /// the channel used to send events to the host's engine) must therefore use interior
+    /// mutability, such as a [`Mutex`](std::sync::Mutex) or atomics.
+    ///
+    /// Handlers can't borrow data owned outside of the instance, as they have to live as long
+    /// as the plugin itself. Long-lived engine state should be shared with them using [`Arc`]s
+    /// instead, which can then be swapped using this method or
+    /// [`access_handler_mut`](Self::access_handler_mut).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_host::prelude::*;
+    /// use std::sync::mpsc::{channel, Sender};
+    /// use std::sync::Mutex;
+    ///
+    /// struct MyHostShared {
+    ///     restart_requests: Mutex<Sender<()>>,
+    /// }
+    ///
+    /// impl SharedHandler<'_> for MyHostShared {
+    ///     fn request_restart(&self) {
+    ///         let _ = self.restart_requests.lock().unwrap().send(());
+    ///     }
+    ///     # fn request_process(&self) {}
+    ///     # fn request_callback(&self) {}
+    /// }
+    ///
+    /// # struct MyHost;
+    /// # impl HostHandlers for MyHost {
+    /// #     type Shared<'a> = MyHostShared;
+    /// #     type MainThread<'a> = ();
+    /// #     type AudioProcessor<'a> = ();
+    /// # }
+    /// # fn rewire(instance: &PluginInstance<MyHost>) {
+    /// // The engine's topology changed: route the requests of this plugin to a new place.
+    /// let (sender, receiver) = channel();
+    /// instance.access_shared_handler(|h| *h.restart_requests.lock().unwrap() = sender);
+    /// # }
+    /// ```
     #[inline]
     pub fn access_shared_handler<'s, R>(
         &'s self,
@@ -136,35 +442,237 @@ impl<H: HostHandlers> PluginInstance<H> {
         access(self.inner.wrapper().shared())
     }
 
+    /// Gives temporary access to this instance's [`MainThread`](HostHandlers::MainThread)
+    /// handler.
+    ///
+    /// This is the same as [`access_handler_unchecked`](Self::access_handler_unchecked).
     #[inline]
+    #[deprecated(
+        note = "Use `access_handler_checked` with a `MainThreadToken`, or `access_handler_unchecked`"
+    )]
     pub fn access_handler<'s, R>(
         &'s self,
         access: impl for<'a> FnOnce(&'s <H as HostHandlers>::MainThread<'a>) -> R,
+    ) -> R {
+        self.access_handler_unchecked(access)
+    }
+
+    /// Gives temporary access to this instance's [`MainThread`](HostHandlers::MainThread)
+    /// handler.
+    ///
+    /// This must be called on the main thread, which is enforced by the given [`MainThreadToken`].
+    /// See [`access_handler_unchecked`](Self::access_handler_unchecked) for a version of this
+    /// method that doesn't require a token, and
+    /// [`access_handler_mut_checked`](Self::access_handler_mut_checked) to receive a mutable
+    /// reference instead.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if the token's thread isn't the one this instance was created
+    /// on.
+    #[inline]
+    #[track_caller]
+    pub fn access_handler_checked<'s, R>(
+        &'s self,
+        main_thread: &MainThreadToken,
+        access: impl for<'a> FnOnce(&'s <H as HostHandlers>::MainThread<'a>) -> R,
+    ) -> R {
+        main_thread.debug_assert_current(self.main_thread);
+        self.access_handler_unchecked(access)
+    }
+
+    /// Gives temporary access to this instance's [`MainThread`](HostHandlers::MainThread)
+    /// handler.
+    ///
+    /// Unlike [`access_handler_checked`](Self::access_handler_checked), this doesn't require a
+    /// [`MainThreadToken`]. Callers are responsible for only calling this on the thread this
+    /// instance was created on.
+    #[inline]
+    pub fn access_handler_unchecked<'s, R>(
+        &'s self,
+        access: impl for<'a> FnOnce(&'s <H as HostHandlers>::MainThread<'a>) -> R,
     ) -> R {
         // SAFETY: we take &self, the only reference to the wrapper on the main thread, therefore
         // we can guarantee there are no mutable reference anywhere
         unsafe { access(self.inner.wrapper().main_thread().as_ref()) }
     }
 
+    /// Gives temporary mutable access to this instance's
+    /// [`MainThread`](HostHandlers::MainThread) handler.
+    ///
+    /// This is the same as [`access_handler_mut_unchecked`](Self::access_handler_mut_unchecked).
     #[inline]
+    #[deprecated(
+        note = "Use `access_handler_mut_checked` with a `MainThreadToken`, or `access_handler_mut_unchecked`"
+    )]
     pub fn access_handler_mut<'s, R>(
         &'s mut self,
         access: impl for<'a> FnOnce(&'s mut <H as HostHandlers>::MainThread<'a>) -> R,
+    ) -> R {
+        self.access_handler_mut_unchecked(access)
+    }
+
+    /// Gives temporary mutable access to this instance's
+    /// [`MainThread`](HostHandlers::MainThread) handler.
+    ///
+    /// This is the way to update the handler's state after the instance was created, e.g. to
+    /// replace the [`Arc`] to the engine state it uses when the project's topology changes,
+    /// without having to re-create the whole instance.
+    ///
+    /// This must be called on the main thread, which is enforced by the given [`MainThreadToken`].
+    /// See [`access_handler_mut_unchecked`](Self::access_handler_mut_unchecked) for a version of
+    /// this method that doesn't require a token.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if the token's thread isn't the one this instance was created
+    /// on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_host::prelude::*;
+    /// use std::sync::Arc;
+    ///
+    /// struct Track {
+    ///     name: String,
+    /// }
+    ///
+    /// struct MyHostMainThread {
+    ///     track: Arc<Track>,
+    /// }
+    ///
+    /// impl MainThreadHandler<'_> for MyHostMainThread {}
+    ///
+    /// # struct MyHost;
+    /// # impl HostHandlers for MyHost {
+    /// #     type Shared<'a> = ();
+    /// #     type MainThread<'a> = MyHostMainThread;
+    /// #     type AudioProcessor<'a> = ();
+    /// # }
+    /// # fn move_to(instance: &mut PluginInstance<MyHost>, main_thread: &MainThreadToken) {
+    /// // The plugin was moved to another track.
+    /// let track = Arc::new(Track { name: "Drums".into() });
+    /// instance.access_handler_mut_checked(main_thread, |h| h.track = track);
+    /// # }
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn access_handler_mut_checked<'s, R>(
+        &'s mut self,
+        main_thread: &MainThreadToken,
+        access: impl for<'a> FnOnce(&'s mut <H as HostHandlers>::MainThread<'a>) -> R,
+    ) -> R {
+        main_thread.debug_assert_current(self.main_thread);
+        self.access_handler_mut_unchecked(access)
+    }
+
+    /// Gives temporary mutable access to this instance's
+    /// [`MainThread`](HostHandlers::MainThread) handler.
+    ///
+    /// Unlike [`access_handler_mut_checked`](Self::access_handler_mut_checked), this doesn't require a
+    /// [`MainThreadToken`]. Callers are responsible for only calling this on the thread this
+    /// instance was created on.
+    #[inline]
+    pub fn access_handler_mut_unchecked<'s, R>(
+        &'s mut self,
+        access: impl for<'a> FnOnce(&'s mut <H as HostHandlers>::MainThread<'a>) -> R,
     ) -> R {
         // SAFETY: we take &mut self, the only reference to the wrapper on the main thread, therefore
         // we can guarantee there are no mutable reference anywhere
         unsafe { access(self.inner.wrapper().main_thread().as_mut()) }
     }
 
+    /// Returns the plugin's implementation of the extension `E`, if it supports it.
+    ///
+    /// The first non-null pointer the plugin returns for a given extension is cached, and returned
+    /// by all subsequent calls, even if the plugin would return a different one. This shields the
+    /// host from plugins giving inconsistent answers across calls, which the CLAP specification
+    /// doesn't allow. Extensions the plugin doesn't support are queried again on every call.
+    ///
+    /// See [`set_extension_validation`](Self::set_extension_validation) to detect such plugins,
+    /// and [`requery_extension`](Self::requery_extension) to bypass the cache.
+    pub fn get_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(&self) -> Option<E> {
+        let plugin = self.plugin_shared_handle();
+
+        // SAFETY: the host pointer is the one given to this instance, and is valid for its lifetime.
+        let ext = unsafe {
+            self.extensions
+                .get(plugin, self.inner.raw_host(), E::IDENTIFIER)?
+        };
+
+        // SAFETY: the pointer was returned by the plugin for E::IDENTIFIER.
+        Some(unsafe { plugin.wrap_extension(ext) })
+    }
+
+    /// Queries the plugin for its implementation of the extension `E`, bypassing the cache used
+    /// by [`get_extension`](Self::get_extension).
+    ///
+    /// This is an escape hatch for misbehaving plugins that change their answers over time, e.g.
+    /// only exposing an extension after activation. The cache is left untouched.
+    #[inline]
+    pub fn requery_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(
+        &self,
+    ) -> Option<E> {
+        self.plugin_shared_handle().get_extension()
+    }
+
+    /// Enables or disables the validation of the plugin's extension queries.
+    ///
+    /// When enabled, [`get_extension`](Self::get_extension) also queries the plugin on every call,
+    /// and reports it as misbehaving to the host's log if its answer differs from the cached one.
+    /// This is disabled by default, as it defeats the purpose of the cache.
+    #[inline]
+    pub fn set_extension_validation(&mut self, enabled: bool) {
+        self.extensions.set_validation(enabled);
+    }
+
     #[inline]
     pub fn plugin_shared_handle(&self) -> PluginSharedHandle {
         self.inner.plugin_shared()
     }
 
+    /// Returns a handle to the plugin, which can be used to call its main-thread extensions.
+    ///
+    /// This is the same as [`plugin_handle_unchecked`](Self::plugin_handle_unchecked).
     #[inline]
+    #[deprecated(
+        note = "Use `plugin_handle_checked` with a `MainThreadToken`, or `plugin_handle_unchecked`"
+    )]
     pub fn plugin_handle(&mut self) -> PluginMainThreadHandle {
+        self.plugin_handle_unchecked()
+    }
+
+    /// Returns a handle to the plugin, which can be used to call its main-thread extensions.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if the token's thread isn't the one this instance was created
+    /// on.
+    #[inline]
+    #[track_caller]
+    pub fn plugin_handle_checked(
+        &mut self,
+        main_thread: &MainThreadToken,
+    ) -> PluginMainThreadHandle<'_> {
+        main_thread.debug_assert_current(self.main_thread);
+        self.plugin_handle_unchecked()
+    }
+
+    /// Returns a handle to the plugin, without requiring a [`MainThreadToken`].
+    ///
+    /// Callers are responsible for only using the returned handle on the thread this instance was
+    /// created on.
+    #[inline]
+    pub fn plugin_handle_unchecked(&mut self) -> PluginMainThreadHandle<'_> {
         // SAFETY: this type can only exist on the main thread.
-        unsafe { PluginMainThreadHandle::new(self.inner.raw_instance().into()) }
+        unsafe { PluginMainThreadHandle::new(self.inner.raw_instance_ptr()) }
+    }
+
+    /// Returns the ID of the thread this instance was created on, which is its main thread.
+    #[inline]
+    pub fn main_thread_id(&self) -> ThreadId {
+        self.main_thread
     }
 }
 
@@ -0,0 +1,219 @@
+//! A queue of main-thread operations that require a plugin instance to be deactivated.
+//!
+//! See the [`DeferredMainThreadOps`] type for more information.
+
+use crate::host::{HostHandlers, MainThreadToken};
+use crate::plugin::{PluginInstance, PluginInstanceError};
+use crate::process::StoppedPluginAudioProcessor;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::sync::mpsc::{sync_channel, Receiver, TryRecvError};
+
+type DeferredOp<H> = Box<dyn FnOnce(&mut PluginInstance<H>)>;
+
+/// A queue of operations that can only be performed while a plugin instance is deactivated.
+///
+/// Some plugin calls are only valid while the plugin is deactivated, such as selecting an audio
+/// ports configuration, applying a configurable audio ports request, or handling an audio ports
+/// rescan with the `RESCAN_ALL` flag. This type collects such operations for a single plugin
+/// instance, and runs them the next time that instance passes through the deactivated state.
+///
+/// Operations are [`enqueue`](Self::enqueue)d with a closure. If the instance is already
+/// deactivated, the operation runs immediately. Otherwise, it runs the next time the host calls
+/// [`run_pending`](Self::run_pending) while the instance is deactivated, which hosts should do
+/// whenever they restart the plugin (e.g. after the plugin called `request_restart`). Hosts can
+/// also force such a restart using [`restart`](Self::restart).
+///
+/// Each operation returns a [`DeferredOpHandle`], which can be used to get notified of its
+/// completion and to retrieve its result.
+///
+/// Dropping this queue cancels all of the operations that are still pending.
+pub struct DeferredMainThreadOps<H: HostHandlers> {
+    ops: VecDeque<DeferredOp<H>>,
+}
+
+impl<H: HostHandlers> DeferredMainThreadOps<H> {
+    /// Creates a new, empty queue.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            ops: VecDeque::new(),
+        }
+    }
+
+    /// Enqueues an operation to be performed on the given instance while it is deactivated.
+    ///
+    /// If the instance is currently deactivated, the operation is run immediately. Otherwise, it
+    /// is run at the next call to [`run_pending`](Self::run_pending) or
+    /// [`restart`](Self::restart).
+    ///
+    /// The given instance must be the one this queue was created for.
+    pub fn enqueue<T, F>(&mut self, instance: &mut PluginInstance<H>, op: F) -> DeferredOpHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PluginInstance<H>) -> T + 'static,
+    {
+        let (sender, receiver) = sync_channel(1);
+
+        let op = move |instance: &mut PluginInstance<H>| {
+            // The handle may have been dropped already, in which case the result is just discarded.
+            let _ = sender.send(op(instance));
+        };
+
+        if instance.is_active() {
+            self.ops.push_back(Box::new(op));
+        } else {
+            op(instance);
+        }
+
+        DeferredOpHandle { receiver }
+    }
+
+    /// Returns the number of operations that are waiting for the instance to be deactivated.
+    #[inline]
+    pub fn pending_count(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if some operations are waiting for the instance to be deactivated.
+    ///
+    /// Hosts can use this to decide when to restart the plugin.
+    #[inline]
+    pub fn has_pending(&self) -> bool {
+        !self.ops.is_empty()
+    }
+
+    /// Runs all of the pending operations, in the order they were enqueued, if the given instance
+    /// is deactivated. Returns the number of operations that were run.
+    ///
+    /// If the instance is still active, this does nothing and returns `0`.
+    ///
+    /// The given instance must be the one this queue was created for.
+    pub fn run_pending(&mut self, instance: &mut PluginInstance<H>) -> usize {
+        if instance.is_active() {
+            return 0;
+        }
+
+        let mut count = 0;
+
+        while let Some(op) = self.ops.pop_front() {
+            op(instance);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Cancels all of the pending operations.
+    ///
+    /// The handles of the cancelled operations will report them as
+    /// [`Cancelled`](DeferredOpState::Cancelled).
+    #[inline]
+    pub fn cancel_all(&mut self) {
+        self.ops.clear()
+    }
+
+    /// Forces a restart of the given instance to run all of the pending operations.
+    ///
+    /// This deactivates the instance using the given audio processor, runs all of the pending
+    /// operations, then re-activates the instance with the same audio configuration it had,
+    /// using the given `audio_processor` closure.
+    ///
+    /// The given instance must be the one this queue was created for.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`PluginInstanceError::ActivationFailed`] if the plugin failed to
+    /// re-activate. In that case, the pending operations have still been run, and the instance
+    /// is left deactivated.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the given audio processor does not belong to the given instance. In debug
+    /// builds, this also panics if the token's thread isn't the one the instance was created on.
+    #[track_caller]
+    pub fn restart<FA>(
+        &mut self,
+        main_thread: &MainThreadToken,
+        instance: &mut PluginInstance<H>,
+        processor: StoppedPluginAudioProcessor<H>,
+        audio_processor: FA,
+    ) -> Result<StoppedPluginAudioProcessor<H>, PluginInstanceError>
+    where
+        FA: for<'a> FnOnce(
+            &'a <H as HostHandlers>::Shared<'a>,
+            &mut <H as HostHandlers>::MainThread<'a>,
+        ) -> <H as HostHandlers>::AudioProcessor<'a>,
+    {
+        // PANIC: the instance can't be inactive while one of its processors still exists.
+        let configuration = instance.activation_config().unwrap();
+
+        instance.deactivate_checked(main_thread, processor);
+        self.run_pending(instance);
+        instance.activate_checked(main_thread, audio_processor, configuration)
+    }
+}
+
+impl<H: HostHandlers> Default for DeferredMainThreadOps<H> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<H: HostHandlers> Debug for DeferredMainThreadOps<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeferredMainThreadOps")
+            .field("pending_count", &self.ops.len())
+            .finish()
+    }
+}
+
+/// The state of an operation enqueued in a [`DeferredMainThreadOps`] queue.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DeferredOpState<T> {
+    /// The operation is still waiting for the instance to be deactivated.
+    Pending,
+    /// The operation has completed, and returned the given value.
+    Completed(T),
+    /// The operation was cancelled before it could run, or its result was already retrieved.
+    Cancelled,
+}
+
+/// A handle to an operation enqueued in a [`DeferredMainThreadOps`] queue.
+///
+/// This can be sent to other threads, to be notified of the operation's completion there.
+pub struct DeferredOpHandle<T> {
+    receiver: Receiver<T>,
+}
+
+impl<T> DeferredOpHandle<T> {
+    /// Returns the current state of the operation, without blocking.
+    ///
+    /// The result of a completed operation is only returned once: subsequent calls return
+    /// [`DeferredOpState::Cancelled`].
+    pub fn poll(&self) -> DeferredOpState<T> {
+        match self.receiver.try_recv() {
+            Ok(value) => DeferredOpState::Completed(value),
+            Err(TryRecvError::Empty) => DeferredOpState::Pending,
+            Err(TryRecvError::Disconnected) => DeferredOpState::Cancelled,
+        }
+    }
+
+    /// Blocks the current thread until the operation completes, and returns its result.
+    ///
+    /// This returns `None` if the operation was cancelled, or if its result was already
+    /// retrieved.
+    ///
+    /// This must not be called from the main thread while the operation is still pending, as it
+    /// would never complete.
+    pub fn wait(self) -> Option<T> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<T> Debug for DeferredOpHandle<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DeferredOpHandle")
+    }
+}
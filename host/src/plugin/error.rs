@@ -1,5 +1,6 @@
 use crate::host::HostHandlers;
 use crate::process::ProcessingStartError;
+use clack_common::utils::ClapVersion;
 use clap_sys::ext::log::{clap_log_severity, CLAP_LOG_ERROR, CLAP_LOG_PLUGIN_MISBEHAVING};
 use core::fmt;
 use core::fmt::{Debug, Display, Formatter};
@@ -27,10 +28,27 @@ pub enum PluginInstanceError {
     MissingPluginFactory,
     /// The plugin's instantiation failed.
     InstantiationFailed,
+    /// Tried to instantiate a plugin from a bundle using a CLAP version that is incompatible with
+    /// the one advertised by the host (see [`HostInfo::clap_version`](crate::host::HostInfo::clap_version)).
+    IncompatibleClapVersion {
+        /// The CLAP version used by the plugin's bundle.
+        found: ClapVersion,
+    },
     /// The plugin has already been destroyed.
     PluginDestroyed,
-    /// The plugin's audio processing failed.
+    /// The plugin's audio processing failed, i.e. its `process` function returned
+    /// `CLAP_PROCESS_ERROR`.
+    ///
+    /// The audio processor is still usable afterward: the host may keep calling `process` on it.
     ProcessingFailed,
+    /// The plugin's `process` function returned a status value that isn't a valid CLAP process
+    /// status.
+    ///
+    /// This is a sign of a misbehaving plugin implementation.
+    InvalidProcessStatus {
+        /// The invalid status value returned by the plugin.
+        status: i32,
+    },
     /// Tried to perform or stop processing when the audio processor was not started yet.
     ProcessingStopped,
     /// Tried to start processing when the processing was already started.
@@ -62,8 +80,10 @@ impl PluginInstanceError {
             Self::PluginNotFound => "Specified plugin was not found",
             Self::MissingPluginFactory => "No plugin factory was provided",
             Self::InstantiationFailed => "Could not instantiate",
+            Self::IncompatibleClapVersion { .. } => "Plugin uses an incompatible CLAP version",
             Self::PluginDestroyed => "Plugin was destroyed",
             Self::ProcessingFailed => "Could not process",
+            Self::InvalidProcessStatus { .. } => "Plugin returned an invalid process status",
             Self::ProcessingStopped => "Audio Processor is currently stopped",
             Self::ProcessingStarted => "Audio Processor is currently started",
             Self::NullProcessFunction => "Plugin's process function is null",
@@ -80,6 +100,7 @@ impl PluginInstanceError {
             PluginInstanceError::NullFactoryCreatePluginFunction => CLAP_LOG_PLUGIN_MISBEHAVING,
             PluginInstanceError::NullProcessFunction => CLAP_LOG_PLUGIN_MISBEHAVING,
             PluginInstanceError::NullActivateFunction => CLAP_LOG_PLUGIN_MISBEHAVING,
+            PluginInstanceError::InvalidProcessStatus { .. } => CLAP_LOG_PLUGIN_MISBEHAVING,
             _ => CLAP_LOG_ERROR,
         }
     }
@@ -87,7 +108,13 @@ impl PluginInstanceError {
 
 impl Display for PluginInstanceError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        f.write_str(self.msg())
+        match self {
+            Self::IncompatibleClapVersion { found } => {
+                write!(f, "{}: v{found}", self.msg())
+            }
+            Self::InvalidProcessStatus { status } => write!(f, "{}: {status}", self.msg()),
+            _ => f.write_str(self.msg()),
+        }
     }
 }
 
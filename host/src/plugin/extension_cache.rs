@@ -0,0 +1,102 @@
+use crate::extensions::wrapper::logging::host_log_record;
+use crate::plugin::PluginSharedHandle;
+use clack_common::utils::fallback_log::{LogOrigin, LogRecord};
+use clap_sys::ext::log::CLAP_LOG_PLUGIN_MISBEHAVING;
+use clap_sys::host::clap_host;
+use std::cell::RefCell;
+use std::ffi::{c_void, CStr};
+use std::ptr::NonNull;
+
+/// The extension pointers a plugin instance returned from `get_extension`, by identifier.
+///
+/// Only non-null answers are cached: an extension the plugin didn't provide is queried again on
+/// every call.
+pub(crate) struct ExtensionCache {
+    entries: RefCell<Vec<(&'static CStr, NonNull<c_void>)>>,
+    validate: bool,
+}
+
+impl ExtensionCache {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: RefCell::new(Vec::new()),
+            validate: false,
+        }
+    }
+
+    #[inline]
+    pub fn set_validation(&mut self, enabled: bool) {
+        self.validate = enabled;
+    }
+
+    /// Returns the cached pointer for the given extension, querying the plugin if there is none.
+    ///
+    /// In validation mode, the plugin is queried on every call, and any answer differing from the
+    /// cached one is reported to the host's log. The cached pointer is returned regardless.
+    ///
+    /// # Safety
+    ///
+    /// The host pointer must point to the valid `clap_host` instance given to this plugin.
+    pub unsafe fn get(
+        &self,
+        plugin: PluginSharedHandle,
+        host: *const clap_host,
+        identifier: &'static CStr,
+    ) -> Option<NonNull<c_void>> {
+        let cached = self
+            .entries
+            .borrow()
+            .iter()
+            .find(|(id, _)| *id == identifier)
+            .map(|(_, ptr)| *ptr);
+
+        // The plugin is never called while the cache is borrowed, as it could call back into it.
+        let Some(cached) = cached else {
+            let queried = plugin.get_raw_extension(identifier)?;
+            self.entries.borrow_mut().push((identifier, queried));
+            return Some(queried);
+        };
+
+        if self.validate {
+            let queried = plugin.get_raw_extension(identifier);
+
+            if queried != Some(cached) {
+                report_inconsistency(plugin, host, identifier, cached, queried);
+            }
+        }
+
+        Some(cached)
+    }
+}
+
+/// # Safety
+///
+/// Same as [`ExtensionCache::get`].
+unsafe fn report_inconsistency(
+    plugin: PluginSharedHandle,
+    host: *const clap_host,
+    identifier: &CStr,
+    cached: NonNull<c_void>,
+    queried: Option<NonNull<c_void>>,
+) {
+    let queried = match queried {
+        Some(ptr) => format!("{ptr:p}"),
+        None => "NULL".to_string(),
+    };
+
+    let message = format_args!(
+        "Plugin returned {queried} for extension {identifier:?}, after previously returning \
+        {cached:p}. The first answer is kept.",
+    );
+
+    let record = LogRecord {
+        origin: LogOrigin::Host,
+        severity: CLAP_LOG_PLUGIN_MISBEHAVING,
+        plugin_id: plugin.descriptor().and_then(|d| d.id()),
+        callback: "clap_plugin.get_extension",
+        message: &message,
+    };
+
+    host_log_record(host, &record);
+}
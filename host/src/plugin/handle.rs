@@ -1,6 +1,7 @@
 use crate::factory::PluginDescriptor;
 use clack_common::extensions::{Extension, PluginExtensionSide, RawExtension};
 use clap_sys::plugin::clap_plugin;
+use std::ffi::{c_void, CStr};
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -130,16 +131,43 @@ impl<'a> PluginSharedHandle<'a> {
     }
 
     pub fn get_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(&self) -> Option<E> {
-        // SAFETY: This type ensures the function pointers are valid
-        let ext =
-            unsafe { self.as_raw().get_extension?(self.raw.as_ptr(), E::IDENTIFIER.as_ptr()) };
+        let ext = self.get_raw_extension(E::IDENTIFIER)?;
 
-        let ext = NonNull::new(ext as *mut _)?;
+        // SAFETY: pointer comes from the associated E::IDENTIFIER.
+        unsafe { Some(self.wrap_extension(ext)) }
+    }
+
+    /// Wraps a raw extension pointer returned by this plugin into its typed [`Extension`].
+    ///
+    /// # Safety
+    ///
+    /// The pointer must have been returned by this plugin's `get_extension`, for `E`'s identifier.
+    pub(crate) unsafe fn wrap_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(
+        &self,
+        ext: NonNull<c_void>,
+    ) -> E {
         // SAFETY: The CLAP spec guarantees that the extension lives as long as the instance.
-        let raw = unsafe { RawExtension::from_raw_plugin_extension(ext, self.raw) };
+        let raw = RawExtension::from_raw_plugin_extension(ext.cast(), self.raw);
 
-        // SAFETY: pointer comes from the associated E::IDENTIFIER.
-        unsafe { Some(E::from_raw(raw)) }
+        E::from_raw(raw)
+    }
+
+    /// Retrieves the plugin's raw pointer to the extension matching the given `identifier`.
+    ///
+    /// This is an escape hatch to hand-roll bindings for extensions Clack does not support yet
+    /// (e.g. bleeding-edge drafts). Prefer using [`get_extension`](Self::get_extension) with a
+    /// typed [`Extension`] whenever possible.
+    ///
+    /// This returns `None` if the plugin does not support the given extension.
+    ///
+    /// The returned pointer is only valid for as long as the plugin instance is alive, and is
+    /// expected to point to the C FFI-compatible extension struct matching `identifier`. Actually
+    /// dereferencing and using it is up to the caller, and is therefore unsafe.
+    pub fn get_raw_extension(&self, identifier: &CStr) -> Option<NonNull<c_void>> {
+        // SAFETY: This type ensures the function pointers are valid
+        let ext = unsafe { self.as_raw().get_extension?(self.raw.as_ptr(), identifier.as_ptr()) };
+
+        NonNull::new(ext as *mut _)
     }
 
     /// Safely dereferences a [`RawExtension`] pointer produced by this plugin instance.
@@ -173,6 +201,35 @@ impl Debug for PluginSharedHandle<'_> {
     }
 }
 
+/// A handle to a plugin instance, that can only be used on the audio thread.
+///
+/// This handle is obtained from an active audio processor (e.g. using
+/// [`StartedPluginAudioProcessor::plugin_handle`](crate::process::StartedPluginAudioProcessor::plugin_handle)),
+/// and is what extension methods that must be called from the audio thread take as a parameter,
+/// such as `PluginTail::get` or `PluginParams::flush_active`. Extension methods that must be called
+/// from the main thread take a [`PluginMainThreadHandle`] instead, which cannot be obtained from
+/// an audio processor.
+///
+/// # Caching extensions
+///
+/// Querying an extension from the plugin is thread-safe, but it is still a call into the plugin
+/// that may not be realtime-safe. As extension types are [`Copy`] and remain valid for as long as
+/// the plugin instance is alive, hosts should query all the extensions they need on the main
+/// thread (e.g. at activation time), and send them along with the audio processor to the audio
+/// thread:
+///
+/// ```ignore
+/// // On the main thread, after activating the plugin:
+/// let tail = instance.plugin_handle_checked(&main_thread).get_extension::<PluginTail>();
+/// let params = instance.plugin_handle_checked(&main_thread).get_extension::<PluginParams>();
+///
+/// // Then, on the audio thread:
+/// processor.process(/* ... */)?;
+///
+/// if let Some(tail) = &tail {
+///     let tail_length = tail.get(&processor.plugin_handle());
+/// }
+/// ```
 #[derive(Eq, PartialEq)]
 #[repr(transparent)]
 pub struct PluginAudioProcessorHandle<'a> {
@@ -287,6 +344,21 @@ impl<'a> InitializingPluginHandle<'a> {
     pub fn get_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(&self) -> Option<E> {
         self.inner.get_extension()
     }
+
+    /// Retrieves the plugin's raw pointer to the extension matching the given `identifier`.
+    ///
+    /// This is an escape hatch to hand-roll bindings for extensions Clack does not support yet
+    /// (e.g. bleeding-edge drafts). Prefer using [`get_extension`](Self::get_extension) with a
+    /// typed [`Extension`] whenever possible.
+    ///
+    /// This returns `None` if the plugin does not support the given extension.
+    ///
+    /// The returned pointer is only valid for as long as the plugin instance is alive, and is
+    /// expected to point to the C FFI-compatible extension struct matching `identifier`. Actually
+    /// dereferencing and using it is up to the caller, and is therefore unsafe.
+    pub fn get_raw_extension(&self, identifier: &CStr) -> Option<NonNull<c_void>> {
+        self.inner.get_raw_extension(identifier)
+    }
 }
 
 impl Debug for InitializingPluginHandle<'_> {
@@ -369,6 +441,21 @@ impl<'a> InitializedPluginHandle<'a> {
     pub fn get_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(&self) -> Option<E> {
         self.inner.get_extension()
     }
+
+    /// Retrieves the plugin's raw pointer to the extension matching the given `identifier`.
+    ///
+    /// This is an escape hatch to hand-roll bindings for extensions Clack does not support yet
+    /// (e.g. bleeding-edge drafts). Prefer using [`get_extension`](Self::get_extension) with a
+    /// typed [`Extension`] whenever possible.
+    ///
+    /// This returns `None` if the plugin does not support the given extension.
+    ///
+    /// The returned pointer is only valid for as long as the plugin instance is alive, and is
+    /// expected to point to the C FFI-compatible extension struct matching `identifier`. Actually
+    /// dereferencing and using it is up to the caller, and is therefore unsafe.
+    pub fn get_raw_extension(&self, identifier: &CStr) -> Option<NonNull<c_void>> {
+        self.inner.get_raw_extension(identifier)
+    }
 }
 
 impl Debug for InitializedPluginHandle<'_> {
@@ -397,6 +484,11 @@ impl RemoteHandleInner {
     fn get_extension<E: Extension<ExtensionSide = PluginExtensionSide>>(&self) -> Option<E> {
         self.access(|handle| handle.get_extension())?
     }
+
+    #[inline]
+    fn get_raw_extension(&self, identifier: &CStr) -> Option<NonNull<c_void>> {
+        self.access(|handle| handle.get_raw_extension(identifier))?
+    }
 }
 
 impl PartialEq for RemoteHandleInner {
@@ -1,6 +1,10 @@
+use crate::bundle::InstanceEntryGuard;
 use crate::extensions::wrapper::descriptor::RawHostDescriptor;
 use crate::extensions::wrapper::HostWrapper;
 use crate::prelude::*;
+use clack_common::utils::compatibility::{CheckLevel, CompatibilityCheck, CompatibilityPolicy};
+use clap_sys::ext::log::CLAP_LOG_PLUGIN_MISBEHAVING;
+use clap_sys::host::clap_host;
 use clap_sys::plugin::clap_plugin;
 use std::ffi::CStr;
 use std::pin::Pin;
@@ -12,10 +16,14 @@ pub(crate) struct PluginInstanceInner<H: HostHandlers> {
     host_wrapper: Pin<Arc<HostWrapper<H>>>,
     host_descriptor: Pin<Box<RawHostDescriptor>>,
     plugin_ptr: Option<NonNull<clap_plugin>>,
+    is_initialized: bool,
 
     is_started: AtomicBool,
+    activation_config: Option<PluginAudioConfiguration>,
 
-    _plugin_bundle: PluginBundle, // SAFETY: Keep the DLL/.SO alive while plugin is instantiated
+    // SAFETY: Keep the DLL/.SO alive and its entry initialized while plugin is instantiated.
+    // This is dropped after the plugin is destroyed in Drop, so the entry is deinitialized last.
+    _entry_guard: InstanceEntryGuard,
 }
 
 impl<H: HostHandlers> PluginInstanceInner<H> {
@@ -25,6 +33,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
         plugin_bundle: &PluginBundle,
         plugin_id: &CStr,
         host_info: HostInfo,
+        policy: CompatibilityPolicy,
     ) -> Result<Arc<Self>, PluginInstanceError>
     where
         FS: for<'s> FnOnce(&'s ()) -> <H as HostHandlers>::Shared<'s>,
@@ -32,19 +41,58 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
             &'s <H as HostHandlers>::Shared<'s>,
         ) -> <H as HostHandlers>::MainThread<'s>,
     {
+        let mut instance = Self::create(
+            shared,
+            main_thread,
+            plugin_bundle,
+            plugin_id,
+            host_info,
+            policy,
+        )?;
+
+        // SAFETY: instantiate() is only called on the main thread.
+        unsafe { Self::init(&mut instance)? };
+
+        Ok(instance)
+    }
+
+    /// Creates the plugin instance through the bundle's factory, without initializing it.
+    ///
+    /// This is thread-safe, as the factory's `create_plugin` is.
+    pub(crate) fn create<FH, FS>(
+        shared: FS,
+        main_thread: FH,
+        plugin_bundle: &PluginBundle,
+        plugin_id: &CStr,
+        host_info: HostInfo,
+        policy: CompatibilityPolicy,
+    ) -> Result<Arc<Self>, PluginInstanceError>
+    where
+        FS: for<'s> FnOnce(&'s ()) -> <H as HostHandlers>::Shared<'s>,
+        FH: for<'s> FnOnce(
+            &'s <H as HostHandlers>::Shared<'s>,
+        ) -> <H as HostHandlers>::MainThread<'s>,
+    {
+        let found = plugin_bundle.clap_version();
+        if !found.is_compatible_with(&host_info.clap_version()) {
+            return Err(PluginInstanceError::IncompatibleClapVersion { found });
+        }
+
         let plugin_factory = plugin_bundle
             .get_plugin_factory()
             .ok_or(PluginInstanceError::MissingPluginFactory)?;
 
-        let host_wrapper = HostWrapper::new(shared, main_thread);
+        let host_wrapper = HostWrapper::new(shared, main_thread, policy);
         let host_descriptor = Box::pin(RawHostDescriptor::new::<H>(host_info));
 
         let mut instance = Arc::new(Self {
             host_wrapper,
             host_descriptor,
             plugin_ptr: None,
-            _plugin_bundle: plugin_bundle.clone(),
+            is_initialized: false,
+            _entry_guard: plugin_bundle.instance_guard(),
             is_started: AtomicBool::new(false),
+            activation_config: None,
         });
 
         {
@@ -63,44 +111,111 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
                 instance.host_wrapper.created(plugin_instance_ptr);
             }
 
-            // Now instantiate the plugin
-            // SAFETY: The CLAP spec requires those function pointers to be valid to call
-            unsafe {
-                if let Some(init) = plugin_instance_ptr.as_ref().init {
-                    if !init(plugin_instance_ptr.as_ptr()) {
-                        instance.host_wrapper.start_instance_destroy();
-                        if let Some(destroy) = plugin_instance_ptr.as_ref().destroy {
-                            destroy(plugin_instance_ptr.as_ptr());
-                        }
-
-                        return Err(PluginInstanceError::InstantiationFailed);
-                    }
-                }
-            }
-
-            // SAFETY: The pointer comes from the plugin factory
-            unsafe { instance.host_wrapper.instantiated() };
             instance.plugin_ptr = Some(plugin_instance_ptr);
         }
 
         Ok(instance)
     }
 
+    /// Initializes a plugin instance previously returned by [`create`](Self::create).
+    ///
+    /// If initialization fails, the plugin instance is destroyed.
+    ///
+    /// # Safety
+    /// This must only be called on the main thread, and only once.
+    pub(crate) unsafe fn init(this: &mut Arc<Self>) -> Result<(), PluginInstanceError> {
+        let instance = Arc::get_mut(this).unwrap();
+        // PANIC: create() always sets the plugin pointer.
+        let plugin_instance_ptr = instance.plugin_ptr.unwrap();
+
+        instance
+            .host_wrapper
+            .set_main_thread(std::thread::current().id());
+
+        // SAFETY: The CLAP spec requires those function pointers to be valid to call
+        if let Some(init) = plugin_instance_ptr.as_ref().init {
+            if !init(plugin_instance_ptr.as_ptr()) {
+                instance.host_wrapper.start_instance_destroy();
+                if let Some(destroy) = plugin_instance_ptr.as_ref().destroy {
+                    destroy(plugin_instance_ptr.as_ptr());
+                }
+
+                instance.plugin_ptr = None;
+                return Err(PluginInstanceError::InstantiationFailed);
+            }
+        }
+
+        // SAFETY: The pointer comes from the plugin factory
+        instance.host_wrapper.instantiated();
+        instance.is_initialized = true;
+
+        Ok(())
+    }
+
     #[inline]
     pub fn wrapper(&self) -> &HostWrapper<H> {
         &self.host_wrapper
     }
 
+    #[inline]
+    pub fn raw_host(&self) -> *const clap_host {
+        self.host_descriptor.raw()
+    }
+
     #[inline]
     pub fn raw_instance(&self) -> &clap_plugin {
-        // SAFETY: This can only be None in the middle of instantiate()
-        unsafe { self.plugin_ptr.unwrap_unchecked().as_ref() }
+        // SAFETY: This can only be None in the middle of create(), or after init() failed
+        unsafe { self.raw_instance_ptr().as_ref() }
+    }
+
+    /// Returns the pointer to the plugin instance, as given by the plugin.
+    ///
+    /// Unlike the pointer to [`raw_instance`](Self::raw_instance), this pointer allows the plugin
+    /// to free itself during the call it is given to, e.g. when destruction has been deferred.
+    #[inline]
+    pub fn raw_instance_ptr(&self) -> NonNull<clap_plugin> {
+        // SAFETY: This can only be None in the middle of create(), or after init() failed
+        unsafe { self.plugin_ptr.unwrap_unchecked() }
+    }
+
+    /// Handles a process status the plugin returned that is unknown to this version of Clack,
+    /// following the instance's compatibility policy.
+    ///
+    /// This returns an [`InvalidProcessStatus`](PluginInstanceError::InvalidProcessStatus) error
+    /// if the [`UnknownProcessStatus`](CompatibilityCheck::UnknownProcessStatus) check is denied,
+    /// and [`ProcessStatus::Continue`] otherwise.
+    pub(crate) fn unknown_process_status(
+        &self,
+        status: i32,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        let check = CompatibilityCheck::UnknownProcessStatus;
+        let wrapper = self.wrapper();
+
+        let level = if wrapper.first_unknown_process_status() {
+            // SAFETY: this is the host pointer given to this plugin instance.
+            unsafe {
+                wrapper.report(
+                    self.raw_host(),
+                    "clap_plugin.process",
+                    check,
+                    CLAP_LOG_PLUGIN_MISBEHAVING,
+                    &format_args!("Plugin returned unknown process status {status}"),
+                )
+            }
+        } else {
+            wrapper.compatibility_policy().level(check)
+        };
+
+        match level {
+            CheckLevel::Deny => Err(PluginInstanceError::InvalidProcessStatus { status }),
+            _ => Ok(ProcessStatus::Continue),
+        }
     }
 
     #[inline]
     pub fn plugin_shared(&self) -> PluginSharedHandle {
         // SAFETY: the raw instance is guaranteed to be valid
-        unsafe { PluginSharedHandle::new(self.raw_instance().into()) }
+        unsafe { PluginSharedHandle::new(self.raw_instance_ptr()) }
     }
 
     pub fn activate<FA>(
@@ -127,7 +242,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
         // SAFETY: this type ensures the function pointer is valid
         let success = unsafe {
             activate(
-                self.raw_instance(),
+                self.raw_instance_ptr().as_ptr(),
                 configuration.sample_rate,
                 configuration.min_frames_count,
                 configuration.max_frames_count,
@@ -140,6 +255,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
             return Err(PluginInstanceError::ActivationFailed);
         }
 
+        self.activation_config = Some(configuration);
         Ok(())
     }
 
@@ -148,6 +264,11 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
         self.wrapper().is_active()
     }
 
+    #[inline]
+    pub fn activation_config(&self) -> Option<PluginAudioConfiguration> {
+        self.activation_config
+    }
+
     #[inline]
     pub fn deactivate_with<T>(
         &mut self,
@@ -168,9 +289,11 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
         if let Some(deactivate) = self.raw_instance().deactivate {
             // SAFETY: this type ensures the function pointer is valid.
             // We just checked the instance is in an active state.
-            unsafe { deactivate(self.raw_instance()) };
+            unsafe { deactivate(self.raw_instance_ptr().as_ptr()) };
         }
 
+        self.activation_config = None;
+
         // SAFETY: this method being &mut guarantees nothing can call any other main-thread method
         unsafe { self.host_wrapper.teardown_audio_processor(drop) }
     }
@@ -181,7 +304,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
     #[inline]
     pub unsafe fn start_processing(&self) -> Result<(), PluginInstanceError> {
         if let Some(start_processing) = self.raw_instance().start_processing {
-            if start_processing(self.raw_instance()) {
+            if start_processing(self.raw_instance_ptr().as_ptr()) {
                 self.is_started.store(true, Ordering::Release);
                 return Ok(());
             }
@@ -197,7 +320,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
     #[inline]
     pub unsafe fn reset(&self) {
         if let Some(reset) = self.raw_instance().reset {
-            reset(self.raw_instance())
+            reset(self.raw_instance_ptr().as_ptr())
         }
     }
 
@@ -207,7 +330,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
     #[inline]
     pub unsafe fn stop_processing(&self) {
         if let Some(stop_processing) = self.raw_instance().stop_processing {
-            stop_processing(self.raw_instance());
+            stop_processing(self.raw_instance_ptr().as_ptr());
             self.is_started.store(false, Ordering::Release);
         }
     }
@@ -217,7 +340,7 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
     #[inline]
     pub unsafe fn on_main_thread(&self) {
         if let Some(on_main_thread) = self.raw_instance().on_main_thread {
-            on_main_thread(self.raw_instance())
+            on_main_thread(self.raw_instance_ptr().as_ptr())
         }
     }
 }
@@ -225,13 +348,13 @@ impl<H: HostHandlers> PluginInstanceInner<H> {
 impl<H: HostHandlers> Drop for PluginInstanceInner<H> {
     #[inline]
     fn drop(&mut self) {
-        // Happens only if instantiate didn't complete
+        // Happens only if create didn't complete, or if init failed
         let Some(plugin_ptr) = self.plugin_ptr else {
             return;
         };
 
         // Check if instance hasn't been properly deactivated
-        if self.is_active() {
+        if self.is_initialized && self.is_active() {
             let _ = self.deactivate_with(|_, _| ());
         }
 
@@ -0,0 +1,408 @@
+//! Creating many plugin instances in parallel.
+//!
+//! Loading a project may require instantiating dozens of plugins, which can take a long time if
+//! done serially on the main thread. However, only some of the steps of a plugin's lifecycle are
+//! actually restricted to the main thread by the CLAP specification:
+//!
+//! * Loaded [`PluginBundle`]s, their [`PluginFactory`](crate::factory::PluginFactory) and the
+//!   [`PluginDescriptor`](crate::factory::PluginDescriptor)s it exposes can be used from any
+//!   thread.
+//! * Creating a plugin instance through the factory (`create_plugin`) is thread-safe. This is
+//!   performed by [`PluginInstancePreMain::create`], and results in a
+//!   [`PluginInstancePreMain`], which can be sent across threads.
+//! * Initializing the plugin (`init`) is a main-thread operation, which is performed by
+//!   [`PluginInstancePreMain::init`] and results in a [`PluginInstance`].
+//! * Everything that comes after that (activation, state loading, GUI, destruction) is also
+//!   restricted to the main thread, and is only available on [`PluginInstance`].
+//!
+//! The [`ParallelInstantiator`] type fans out the creation of many plugin instances across a
+//! pool of threads, then initializes them serially on the main thread.
+//!
+//! Note that the speed-up this provides depends on how much work plugins perform when they are
+//! created, rather than when they are initialized.
+
+use crate::bundle::PluginBundle;
+use crate::host::{HostHandlers, HostInfo, MainThreadToken};
+use crate::plugin::instance::PluginInstanceInner;
+use crate::plugin::{PluginInstance, PluginInstanceError};
+use clack_common::utils::compatibility::CompatibilityPolicy;
+use std::ffi::{CStr, CString};
+use std::mem::ManuallyDrop;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A plugin instance that has been created, but not initialized yet.
+///
+/// Unlike [`PluginInstance`], this type can be created and sent from any thread. It must then be
+/// [initialized](Self::init) on the main thread to get a [`PluginInstance`].
+///
+/// Dropping this type destroys the plugin instance. As destroying a plugin is a main-thread
+/// operation, this must only be dropped on the main thread.
+///
+/// If the process' main thread was recorded by [`MainThreadToken::acquire`], dropping this type
+/// on any other thread leaks the plugin instance instead of destroying it, and panics in debug
+/// builds.
+pub struct PluginInstancePreMain<H: HostHandlers> {
+    inner: Arc<PluginInstanceInner<H>>,
+}
+
+impl<H: HostHandlers> PluginInstancePreMain<H> {
+    /// Creates a new plugin instance from the given bundle, without initializing it.
+    ///
+    /// The instance uses the default [`CompatibilityPolicy`].
+    ///
+    /// Unlike [`PluginInstance::new`], this can be called from any thread. However, the host
+    /// handlers are created on the calling thread, and then sent to the main thread alongside
+    /// the instance. This is why the host's [`MainThread`](HostHandlers::MainThread) handler
+    /// type must be [`Send`] here.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if the plugin could not be created, e.g. if the bundle has no
+    /// plugin with the given ID.
+    pub fn create<FS, FH>(
+        shared: FS,
+        main_thread: FH,
+        bundle: &PluginBundle,
+        plugin_id: &CStr,
+        host: &HostInfo,
+    ) -> Result<Self, PluginInstanceError>
+    where
+        FS: for<'b> FnOnce(&'b ()) -> <H as HostHandlers>::Shared<'b>,
+        FH: for<'b> FnOnce(
+            &'b <H as HostHandlers>::Shared<'b>,
+        ) -> <H as HostHandlers>::MainThread<'b>,
+        for<'b> <H as HostHandlers>::MainThread<'b>: Send,
+    {
+        let inner = PluginInstanceInner::<H>::create(
+            shared,
+            main_thread,
+            bundle,
+            plugin_id,
+            host.clone(),
+            CompatibilityPolicy::default(),
+        )?;
+
+        Ok(Self { inner })
+    }
+
+    /// Initializes this plugin instance on the main thread.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`PluginInstanceError::InstantiationFailed`] if the plugin failed to
+    /// initialize. The plugin instance is then destroyed.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if the token's thread isn't the current thread.
+    #[track_caller]
+    pub fn init(
+        self,
+        main_thread: &MainThreadToken,
+    ) -> Result<PluginInstance<H>, PluginInstanceError> {
+        main_thread.debug_assert_current(std::thread::current().id());
+        self.init_unchecked()
+    }
+
+    /// Initializes this plugin instance.
+    ///
+    /// Unlike [`init`](Self::init), this doesn't require a [`MainThreadToken`]. Callers are
+    /// responsible for only calling this on the main thread, which becomes the thread the
+    /// resulting [`PluginInstance`] is bound to.
+    ///
+    /// # Errors
+    ///
+    /// See [`init`](Self::init).
+    pub fn init_unchecked(mut self) -> Result<PluginInstance<H>, PluginInstanceError> {
+        // SAFETY: this type is only ever initialized once, as init consumes it. The caller
+        // ensures this is called on the main thread.
+        unsafe { PluginInstanceInner::init(&mut self.inner)? };
+
+        let this = ManuallyDrop::new(self);
+        // SAFETY: this is never used or dropped again, so the instance is moved out only once.
+        let inner = unsafe { std::ptr::read(&this.inner) };
+
+        Ok(PluginInstance::from_inner(inner))
+    }
+}
+
+impl<H: HostHandlers> Drop for PluginInstancePreMain<H> {
+    fn drop(&mut self) {
+        let Some(main_thread) = MainThreadToken::recorded() else {
+            return;
+        };
+
+        if main_thread.is_current() {
+            return;
+        }
+
+        // Destroying the plugin off the main thread is not allowed: leak it instead.
+        std::mem::forget(self.inner.clone());
+
+        if cfg!(debug_assertions) && !std::thread::panicking() {
+            panic!("Uninitialized plugin instance dropped on another thread than the main thread");
+        }
+    }
+}
+
+// SAFETY: the only non-thread-safe part of the instance are the host's main-thread handlers,
+// which are required to be Send when creating this type. The plugin itself is not initialized yet,
+// and creating it is a thread-safe operation.
+unsafe impl<H: HostHandlers> Send for PluginInstancePreMain<H> {}
+
+/// The stage an instance reached in a [`ParallelInstantiator`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InstantiationStage {
+    /// The instance was created (or failed to be created), on a worker thread.
+    Created,
+    /// The instance was initialized (or failed to be initialized), on the main thread.
+    Initialized,
+}
+
+/// Progress information reported by a [`ParallelInstantiator`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct InstantiationProgress {
+    /// The index of the instance that just completed a stage, in the order of requests.
+    pub index: usize,
+    /// The stage the instance just completed.
+    pub stage: InstantiationStage,
+    /// Whether the instance completed the stage successfully.
+    pub success: bool,
+    /// The number of instances that completed the same stage so far, including this one.
+    pub completed: usize,
+    /// The total number of instances to instantiate.
+    pub total: usize,
+}
+
+struct InstantiationRequest {
+    bundle: PluginBundle,
+    plugin_id: CString,
+}
+
+/// Instantiates many plugins, creating them in parallel before initializing them on the main
+/// thread.
+///
+/// See the [module documentation](self) for more information about which operations are
+/// performed in parallel.
+///
+/// # Example
+///
+/// ```no_run
+/// use clack_host::plugin::parallel::ParallelInstantiator;
+/// use clack_host::prelude::*;
+/// use std::ffi::CString;
+///
+/// # pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let host_info = HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2")?;
+/// let main_thread = MainThreadToken::acquire().expect("Not on the main thread");
+/// let bundle = unsafe { PluginBundle::load("/home/user/.clap/u-he/libdiva.so")? };
+///
+/// let mut instantiator = ParallelInstantiator::<()>::new(&host_info);
+///
+/// for _ in 0..60 {
+///     instantiator.push(&bundle, &CString::new("com.u-he.diva")?);
+/// }
+///
+/// let instances = instantiator.instantiate(
+///     &main_thread,
+///     |_, _| (),
+///     |_, _| (),
+///     |progress| println!("{}/{} {:?}", progress.completed, progress.total, progress.stage),
+/// );
+///
+/// for (index, instance) in instances.iter().enumerate() {
+///     if let Err(e) = instance {
+///         eprintln!("Failed to instantiate plugin #{index}: {e}");
+///     }
+/// }
+/// # Ok(()) }
+/// ```
+pub struct ParallelInstantiator<H: HostHandlers> {
+    host_info: HostInfo,
+    requests: Vec<InstantiationRequest>,
+    thread_count: NonZeroUsize,
+    _handlers: std::marker::PhantomData<fn() -> H>,
+}
+
+impl<H: HostHandlers> ParallelInstantiator<H> {
+    /// Creates a new instantiator, which will pass the given host information to all plugins.
+    ///
+    /// By default, the instantiator uses as many threads as the system's available parallelism.
+    pub fn new(host_info: &HostInfo) -> Self {
+        Self {
+            host_info: host_info.clone(),
+            requests: Vec::new(),
+            thread_count: std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            _handlers: std::marker::PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of threads used to create the instances.
+    #[inline]
+    pub fn with_thread_count(mut self, thread_count: NonZeroUsize) -> Self {
+        self.thread_count = thread_count;
+        self
+    }
+
+    /// Requests an instance of the plugin with the given ID, from the given bundle.
+    ///
+    /// Returns the index of the request, which is also the index of the resulting instance in the
+    /// instantiation results.
+    pub fn push(&mut self, bundle: &PluginBundle, plugin_id: &CStr) -> usize {
+        self.requests.push(InstantiationRequest {
+            bundle: bundle.clone(),
+            plugin_id: plugin_id.to_owned(),
+        });
+
+        self.requests.len() - 1
+    }
+
+    /// Returns the number of requested instances.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns `true` if no instance was requested.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Creates all of the requested instances in parallel, without initializing them.
+    ///
+    /// The `shared` and `main_thread` closures create the host handlers of each instance. They
+    /// receive the index of the instance being created, and are called concurrently from the
+    /// worker threads.
+    ///
+    /// The `progress` callback is also called from the worker threads, every time an instance has
+    /// been created.
+    ///
+    /// This returns the result of each instance's creation, in the order they were requested.
+    /// The successfully created instances must then be [initialized](PluginInstancePreMain::init)
+    /// on the main thread.
+    pub fn create<FS, FH, FP>(
+        &self,
+        shared: FS,
+        main_thread: FH,
+        progress: FP,
+    ) -> Vec<Result<PluginInstancePreMain<H>, PluginInstanceError>>
+    where
+        FS: for<'b> Fn(usize, &'b ()) -> <H as HostHandlers>::Shared<'b> + Sync,
+        FH: for<'b> Fn(
+                usize,
+                &'b <H as HostHandlers>::Shared<'b>,
+            ) -> <H as HostHandlers>::MainThread<'b>
+            + Sync,
+        FP: Fn(InstantiationProgress) + Sync,
+        for<'b> <H as HostHandlers>::MainThread<'b>: Send,
+    {
+        let total = self.requests.len();
+        let results: Vec<_> = (0..total).map(|_| Mutex::new(None)).collect();
+        let next_request = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+
+        let worker = || loop {
+            let index = next_request.fetch_add(1, Ordering::Relaxed);
+            let Some(request) = self.requests.get(index) else {
+                return;
+            };
+
+            let result = PluginInstancePreMain::create(
+                |unit| shared(index, unit),
+                |s| main_thread(index, s),
+                &request.bundle,
+                &request.plugin_id,
+                &self.host_info,
+            );
+
+            let success = result.is_ok();
+            *results[index].lock().unwrap() = Some(result);
+
+            progress(InstantiationProgress {
+                index,
+                stage: InstantiationStage::Created,
+                success,
+                completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                total,
+            });
+        };
+
+        let thread_count = self.thread_count.get().min(total);
+
+        std::thread::scope(|scope| {
+            for _ in 1..thread_count {
+                scope.spawn(worker);
+            }
+
+            // The current thread also takes part in the work.
+            worker();
+        });
+
+        results
+            .into_iter()
+            // PANIC: all requests have been processed once the scope ends.
+            .map(|result| result.into_inner().unwrap().unwrap())
+            .collect()
+    }
+
+    /// Creates all of the requested instances in parallel, then initializes them serially on the
+    /// current thread, which must be the main thread.
+    ///
+    /// See [`create`](Self::create) for more information about the `shared` and `main_thread`
+    /// closures. The `progress` callback is called from the worker threads during creation, and
+    /// from the main thread during initialization.
+    ///
+    /// This returns the result of each instantiation, in the order they were requested. Operations
+    /// that are restricted to the main thread, such as loading the plugins' state or activating
+    /// them, can then be performed on the resulting instances.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if the token's thread isn't the current thread.
+    #[track_caller]
+    pub fn instantiate<FS, FH, FP>(
+        &self,
+        main_thread_token: &MainThreadToken,
+        shared: FS,
+        main_thread: FH,
+        progress: FP,
+    ) -> Vec<Result<PluginInstance<H>, PluginInstanceError>>
+    where
+        FS: for<'b> Fn(usize, &'b ()) -> <H as HostHandlers>::Shared<'b> + Sync,
+        FH: for<'b> Fn(
+                usize,
+                &'b <H as HostHandlers>::Shared<'b>,
+            ) -> <H as HostHandlers>::MainThread<'b>
+            + Sync,
+        FP: Fn(InstantiationProgress) + Sync,
+        for<'b> <H as HostHandlers>::MainThread<'b>: Send,
+    {
+        main_thread_token.debug_assert_current(std::thread::current().id());
+
+        let created = self.create(shared, main_thread, &progress);
+        let total = created.len();
+        let mut completed = 0;
+
+        created
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| {
+                let result = result.and_then(PluginInstancePreMain::init_unchecked);
+                completed += 1;
+
+                progress(InstantiationProgress {
+                    index,
+                    stage: InstantiationStage::Initialized,
+                    success: result.is_ok(),
+                    completed,
+                    total,
+                });
+
+                result
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,458 @@
+//! A collection of plugin instances, which can be torn down safely all at once.
+//!
+//! See the [`Rack`] type for more information.
+
+use crate::bundle::PluginBundle;
+use crate::host::HostHandlers;
+use crate::plugin::{PluginInstance, PluginInstanceError};
+use crate::process::{PluginAudioConfiguration, PluginAudioProcessor, StoppedPluginAudioProcessor};
+use std::fmt::{Display, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The identifier of a plugin instance in a [`Rack`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct RackInstanceId(usize);
+
+impl Display for RackInstanceId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Where the audio processor of an instance currently is.
+enum ProcessorSlot<H: HostHandlers> {
+    /// The instance is not activated.
+    Inactive,
+    /// The instance is activated, and its audio processor is held by the rack.
+    Home(StoppedPluginAudioProcessor<H>),
+    /// The instance is activated, and its audio processor was handed out to the audio thread.
+    Away,
+}
+
+struct RackEntry<H: HostHandlers> {
+    instance: PluginInstance<H>,
+    processor: ProcessorSlot<H>,
+}
+
+type ReturnedProcessor<H> = (RackInstanceId, StoppedPluginAudioProcessor<H>);
+
+/// A collection of plugin instances, and of their audio processors.
+///
+/// Tearing down many plugin instances (e.g. when closing a project) requires doing things in a
+/// specific order: processing must first be stopped on the audio thread, then all audio
+/// processors must be sent back to the main thread to deactivate the plugins, which can then be
+/// destroyed. Only then can the plugin bundles be unloaded.
+///
+/// This type owns plugin instances and their audio processors, and performs all of these steps
+/// in its [`shutdown`](Rack::shutdown) method. Audio processors are handed out to the audio
+/// thread as [`ProcessorToken`]s, which send them back to the rack when dropped.
+///
+/// Audio processors that never come back from the audio thread, and host handlers that panic
+/// while the plugin is being deactivated or destroyed, do not prevent the rest of the instances
+/// from being torn down: they are reported in the [`ShutdownReport`] instead.
+pub struct Rack<H: HostHandlers> {
+    entries: Vec<Option<RackEntry<H>>>,
+    bundles: Vec<PluginBundle>,
+    shutdown_requested: Arc<AtomicBool>,
+    return_sender: Sender<ReturnedProcessor<H>>,
+    return_receiver: Receiver<ReturnedProcessor<H>>,
+}
+
+impl<H: HostHandlers> Rack<H> {
+    /// Creates a new, empty rack.
+    pub fn new() -> Self {
+        let (return_sender, return_receiver) = channel();
+
+        Self {
+            entries: Vec::new(),
+            bundles: Vec::new(),
+            shutdown_requested: Arc::new(AtomicBool::new(false)),
+            return_sender,
+            return_receiver,
+        }
+    }
+
+    /// Keeps the given bundle loaded until all of the rack's instances are destroyed.
+    ///
+    /// Instances already keep their own bundle loaded. This is meant for bundles the host wants
+    /// to keep around for e.g. instantiating more plugins, and which should only be unloaded
+    /// last, once the rack is [`shutdown`](Self::shutdown).
+    pub fn keep_bundle(&mut self, bundle: PluginBundle) {
+        self.bundles.push(bundle);
+    }
+
+    /// Adds the given plugin instance to this rack, and returns its identifier.
+    ///
+    /// The given instance must not be active.
+    ///
+    /// # Errors
+    ///
+    /// This returns the instance back, with [`PluginInstanceError::AlreadyActivatedPlugin`], if
+    /// it was already active.
+    pub fn insert(
+        &mut self,
+        instance: PluginInstance<H>,
+    ) -> Result<RackInstanceId, (PluginInstance<H>, PluginInstanceError)> {
+        if instance.is_active() {
+            return Err((instance, PluginInstanceError::AlreadyActivatedPlugin));
+        }
+
+        self.entries.push(Some(RackEntry {
+            instance,
+            processor: ProcessorSlot::Inactive,
+        }));
+
+        Ok(RackInstanceId(self.entries.len() - 1))
+    }
+
+    /// Returns the number of instances in this rack.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    /// Returns `true` if this rack holds no instance.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the instance with the given identifier, if it exists.
+    #[inline]
+    pub fn instance(&self, id: RackInstanceId) -> Option<&PluginInstance<H>> {
+        Some(&self.entries.get(id.0)?.as_ref()?.instance)
+    }
+
+    /// Returns the instance with the given identifier, if it exists.
+    #[inline]
+    pub fn instance_mut(&mut self, id: RackInstanceId) -> Option<&mut PluginInstance<H>> {
+        Some(&mut self.entries.get_mut(id.0)?.as_mut()?.instance)
+    }
+
+    /// Returns `true` if the audio processor of the given instance is currently out on the audio
+    /// thread.
+    #[inline]
+    pub fn is_processor_away(&self, id: RackInstanceId) -> bool {
+        self.entry(id)
+            .is_ok_and(|e| matches!(e.processor, ProcessorSlot::Away))
+    }
+
+    /// Activates the given instance, and keeps its audio processor until it is
+    /// [taken](Self::take_processor).
+    ///
+    /// # Errors
+    ///
+    /// This returns [`PluginInstanceError::PluginNotFound`] if no instance has the given
+    /// identifier, or any error returned by [`PluginInstance::activate`].
+    pub fn activate<FA>(
+        &mut self,
+        id: RackInstanceId,
+        audio_processor: FA,
+        configuration: PluginAudioConfiguration,
+    ) -> Result<(), PluginInstanceError>
+    where
+        FA: for<'a> FnOnce(
+            &'a <H as HostHandlers>::Shared<'a>,
+            &mut <H as HostHandlers>::MainThread<'a>,
+        ) -> <H as HostHandlers>::AudioProcessor<'a>,
+    {
+        let entry = self.entry_mut(id)?;
+        let processor = entry
+            .instance
+            .activate_unchecked(audio_processor, configuration)?;
+        entry.processor = ProcessorSlot::Home(processor);
+
+        Ok(())
+    }
+
+    /// Hands the audio processor of the given instance out, to be sent to the audio thread.
+    ///
+    /// The processor comes back to the rack when the returned token is dropped, and can then be
+    /// [collected](Self::collect_returned_processors).
+    ///
+    /// Returns `None` if the instance doesn't exist, isn't active, or if its processor is
+    /// already away.
+    pub fn take_processor(&mut self, id: RackInstanceId) -> Option<ProcessorToken<H>> {
+        let entry = self.entry_mut(id).ok()?;
+        if !matches!(entry.processor, ProcessorSlot::Home(_)) {
+            return None;
+        }
+
+        let ProcessorSlot::Home(processor) =
+            std::mem::replace(&mut entry.processor, ProcessorSlot::Away)
+        else {
+            unreachable!()
+        };
+
+        Some(ProcessorToken {
+            id,
+            processor: Some(processor.into()),
+            return_sender: self.return_sender.clone(),
+            shutdown_requested: self.shutdown_requested.clone(),
+        })
+    }
+
+    /// Puts back all the audio processors that were returned from the audio thread so far.
+    ///
+    /// Returns the number of processors that came back.
+    pub fn collect_returned_processors(&mut self) -> usize {
+        let mut count = 0;
+
+        while let Ok(returned) = self.return_receiver.try_recv() {
+            self.put_back(returned);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Deactivates the given instance.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`PluginInstanceError::PluginNotFound`] if no instance has the given
+    /// identifier, [`PluginInstanceError::DeactivatedPlugin`] if it isn't active, or
+    /// [`PluginInstanceError::ProcessingStarted`] if its audio processor is away, and hasn't been
+    /// [collected](Self::collect_returned_processors) back yet.
+    pub fn deactivate(&mut self, id: RackInstanceId) -> Result<(), PluginInstanceError> {
+        let entry = self.entry_mut(id)?;
+
+        match std::mem::replace(&mut entry.processor, ProcessorSlot::Inactive) {
+            ProcessorSlot::Home(processor) => {
+                entry.instance.deactivate_unchecked(processor);
+                Ok(())
+            }
+            ProcessorSlot::Inactive => Err(PluginInstanceError::DeactivatedPlugin),
+            ProcessorSlot::Away => {
+                entry.processor = ProcessorSlot::Away;
+                Err(PluginInstanceError::ProcessingStarted)
+            }
+        }
+    }
+
+    /// Deactivates and removes the given instance from the rack, and returns it.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`deactivate`](Self::deactivate), except that it is fine for the instance to be
+    /// inactive already.
+    pub fn remove(&mut self, id: RackInstanceId) -> Result<PluginInstance<H>, PluginInstanceError> {
+        match self.deactivate(id) {
+            Ok(()) | Err(PluginInstanceError::DeactivatedPlugin) => {}
+            Err(e) => return Err(e),
+        }
+
+        let entry = self.entries[id.0].take();
+        // PANIC: deactivate() already checked the entry exists.
+        Ok(entry.unwrap().instance)
+    }
+
+    /// Tears down all of the rack's instances, in order.
+    ///
+    /// This performs the following steps:
+    ///
+    /// 1. All [`ProcessorToken`]s are notified that a shutdown is requested (see
+    ///    [`ProcessorToken::is_shutdown_requested`]), and the audio processors they hold are
+    ///    awaited for up to the given `timeout`. Tokens stop processing before sending their
+    ///    processor back.
+    /// 2. All active instances are deactivated.
+    /// 3. All instances are destroyed.
+    /// 4. All bundles kept with [`keep_bundle`](Self::keep_bundle) are released.
+    ///
+    /// Instances which audio processor didn't come back in time cannot be safely deactivated nor
+    /// destroyed: they are leaked instead, along with their bundle. Panics raised by host handlers
+    /// while deactivating or destroying an instance are caught, and the teardown continues with
+    /// the remaining instances.
+    ///
+    /// All of these issues are listed in the returned [`ShutdownReport`].
+    pub fn shutdown(mut self, timeout: Duration) -> ShutdownReport {
+        let mut report = ShutdownReport::default();
+
+        // 1. Get all processors back from the audio thread.
+        self.shutdown_requested.store(true, Ordering::Release);
+        self.collect_returned_processors();
+
+        let deadline = Instant::now() + timeout;
+        while self.has_processors_away() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            match self.return_receiver.recv_timeout(remaining) {
+                Ok(returned) => self.put_back(returned),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let mut entries: Vec<_> = std::mem::take(&mut self.entries)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, e)| Some((RackInstanceId(i), e?)))
+            .collect();
+
+        // 2. Deactivate everything that can be.
+        entries.retain_mut(|(id, entry)| {
+            match std::mem::replace(&mut entry.processor, ProcessorSlot::Inactive) {
+                ProcessorSlot::Inactive => true,
+                ProcessorSlot::Home(processor) => {
+                    let instance = &mut entry.instance;
+                    if catch_unwind(AssertUnwindSafe(|| {
+                        instance.deactivate_unchecked(processor)
+                    }))
+                    .is_err()
+                    {
+                        report.panicked.push(*id);
+                    }
+
+                    true
+                }
+                ProcessorSlot::Away => {
+                    // Dropping the instance while its processor is still alive leaks it.
+                    report.leaked.push(*id);
+                    false
+                }
+            }
+        });
+
+        // 3. Destroy all the instances that were deactivated.
+        for (id, entry) in entries {
+            if catch_unwind(AssertUnwindSafe(|| drop(entry.instance))).is_err() {
+                if !report.panicked.contains(&id) {
+                    report.panicked.push(id);
+                }
+            } else {
+                report.destroyed += 1;
+            }
+        }
+
+        // 4. Only unload the bundles once nothing uses them anymore.
+        self.bundles.clear();
+
+        report
+    }
+
+    fn has_processors_away(&self) -> bool {
+        self.entries
+            .iter()
+            .flatten()
+            .any(|e| matches!(e.processor, ProcessorSlot::Away))
+    }
+
+    fn put_back(&mut self, (id, processor): ReturnedProcessor<H>) {
+        if let Ok(entry) = self.entry_mut(id) {
+            entry.processor = ProcessorSlot::Home(processor);
+        }
+    }
+
+    fn entry(&self, id: RackInstanceId) -> Result<&RackEntry<H>, PluginInstanceError> {
+        self.entries
+            .get(id.0)
+            .and_then(Option::as_ref)
+            .ok_or(PluginInstanceError::PluginNotFound)
+    }
+
+    fn entry_mut(&mut self, id: RackInstanceId) -> Result<&mut RackEntry<H>, PluginInstanceError> {
+        self.entries
+            .get_mut(id.0)
+            .and_then(Option::as_mut)
+            .ok_or(PluginInstanceError::PluginNotFound)
+    }
+}
+
+impl<H: HostHandlers> Default for Rack<H> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The audio processor of a [`Rack`] instance, handed out to the audio thread.
+///
+/// When dropped, this token stops processing if needed, and sends the audio processor back to
+/// the rack. If the rack no longer exists at that point, the audio processor is leaked, as it
+/// cannot be safely deactivated from the audio thread.
+pub struct ProcessorToken<H: HostHandlers> {
+    id: RackInstanceId,
+    processor: Option<PluginAudioProcessor<H>>,
+    return_sender: Sender<ReturnedProcessor<H>>,
+    shutdown_requested: Arc<AtomicBool>,
+}
+
+impl<H: HostHandlers> ProcessorToken<H> {
+    /// Returns the identifier of the instance this audio processor belongs to.
+    #[inline]
+    pub fn id(&self) -> RackInstanceId {
+        self.id
+    }
+
+    /// Returns the audio processor held by this token.
+    #[inline]
+    pub fn processor(&mut self) -> &mut PluginAudioProcessor<H> {
+        // PANIC: the processor is only taken out on drop.
+        self.processor.as_mut().unwrap()
+    }
+
+    /// Returns `true` if the rack is shutting down, in which case this token should be dropped as
+    /// soon as possible.
+    #[inline]
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.shutdown_requested.load(Ordering::Acquire)
+    }
+}
+
+impl<H: HostHandlers> Drop for ProcessorToken<H> {
+    fn drop(&mut self) {
+        let Some(processor) = self.processor.take() else {
+            return;
+        };
+
+        let processor = processor.into_stopped();
+        if let Err(returned) = self.return_sender.send((self.id, processor)) {
+            // The rack is gone: the plugin cannot be safely deactivated from this thread.
+            std::mem::forget(returned.0);
+        }
+    }
+}
+
+/// The outcome of a [`Rack::shutdown`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ShutdownReport {
+    /// The number of instances that were successfully destroyed.
+    pub destroyed: usize,
+    /// The instances which audio processor never came back from the audio thread, and which were
+    /// leaked instead of being destroyed.
+    pub leaked: Vec<RackInstanceId>,
+    /// The instances for which a panic occurred while they were deactivated or destroyed.
+    pub panicked: Vec<RackInstanceId>,
+}
+
+impl ShutdownReport {
+    /// Returns `true` if all instances were torn down without any issue.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.leaked.is_empty() && self.panicked.is_empty()
+    }
+}
+
+impl Display for ShutdownReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} plugin instance(s) destroyed", self.destroyed)?;
+
+        if !self.leaked.is_empty() {
+            write!(
+                f,
+                ", {} leaked as their audio processor never came back",
+                self.leaked.len()
+            )?;
+        }
+
+        if !self.panicked.is_empty() {
+            write!(f, ", {} panicked during teardown", self.panicked.len())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+//! Coordinated restarts of plugin instances.
+//!
+//! See the [`RestartCoordinator`] type for more information.
+
+use crate::host::{HostHandlers, MainThreadToken};
+use crate::plugin::{PluginInstance, PluginInstanceError};
+use crate::prelude::PluginAudioConfiguration;
+use crate::process::StoppedPluginAudioProcessor;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// The next restart generation to hand out. This is shared by all coordinators, so that two
+/// restart sequences never share the same generation.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// Restarts plugin instances, letting Clack plugins carry their audio processing state over to
+/// their new audio processor.
+///
+/// Restarting a plugin (e.g. after it called `request_restart`, or to change its audio
+/// configuration) means deactivating it, then activating it again right away. Doing this through
+/// a [`RestartCoordinator`] marks both calls as belonging to the same restart sequence. Plugins
+/// made with Clack use this to save part of their audio state (e.g. delay lines, or smoothed
+/// parameter values) before being deactivated, and to restore it into the audio processor they
+/// are re-activated with, which minimizes audible glitches.
+///
+/// This is done through a small private extension, which only Clack plugins know about. For any
+/// other plugin, this behaves exactly like a plain deactivation followed by an activation.
+#[derive(Debug, Default)]
+pub struct RestartCoordinator {
+    last_generation: Option<u64>,
+}
+
+impl RestartCoordinator {
+    /// Creates a new coordinator.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            last_generation: None,
+        }
+    }
+
+    /// Returns the generation of the last restart sequence this coordinator performed, or `None`
+    /// if it didn't perform any yet.
+    #[inline]
+    pub fn last_generation(&self) -> Option<u64> {
+        self.last_generation
+    }
+
+    /// Restarts the given instance with the given audio configuration.
+    ///
+    /// This deactivates the instance using the given audio processor, then re-activates it with
+    /// the given `configuration`, using the given `audio_processor` closure. Both the
+    /// configuration and the audio processor may differ from the ones the instance was
+    /// previously activated with.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`PluginInstanceError::ActivationFailed`] if the plugin failed to
+    /// re-activate. In that case, the instance is left deactivated.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the given audio processor does not belong to the given instance. In debug
+    /// builds, this also panics if the token's thread isn't the one the instance was created on.
+    #[track_caller]
+    pub fn restart<H, FA>(
+        &mut self,
+        main_thread: &MainThreadToken,
+        instance: &mut PluginInstance<H>,
+        processor: StoppedPluginAudioProcessor<H>,
+        configuration: PluginAudioConfiguration,
+        audio_processor: FA,
+    ) -> Result<StoppedPluginAudioProcessor<H>, PluginInstanceError>
+    where
+        H: HostHandlers,
+        FA: for<'a> FnOnce(
+            &'a <H as HostHandlers>::Shared<'a>,
+            &mut <H as HostHandlers>::MainThread<'a>,
+        ) -> <H as HostHandlers>::AudioProcessor<'a>,
+    {
+        main_thread.debug_assert_current(instance.main_thread);
+        self.restart_unchecked(instance, processor, configuration, audio_processor)
+    }
+
+    /// Restarts the given instance with the given audio configuration.
+    ///
+    /// Unlike [`restart`](Self::restart), this doesn't require a [`MainThreadToken`]. Callers
+    /// are responsible for only calling this on the thread the instance was created on.
+    ///
+    /// # Errors
+    ///
+    /// See [`restart`](Self::restart).
+    ///
+    /// # Panics
+    ///
+    /// This panics if the given audio processor does not belong to the given instance.
+    pub fn restart_unchecked<H, FA>(
+        &mut self,
+        instance: &mut PluginInstance<H>,
+        processor: StoppedPluginAudioProcessor<H>,
+        configuration: PluginAudioConfiguration,
+        audio_processor: FA,
+    ) -> Result<StoppedPluginAudioProcessor<H>, PluginInstanceError>
+    where
+        H: HostHandlers,
+        FA: for<'a> FnOnce(
+            &'a <H as HostHandlers>::Shared<'a>,
+            &mut <H as HostHandlers>::MainThread<'a>,
+        ) -> <H as HostHandlers>::AudioProcessor<'a>,
+    {
+        if !Arc::ptr_eq(&instance.inner, &processor.inner) {
+            panic!("Given plugin audio processor does not match the instance being restarted")
+        }
+
+        let generation = NEXT_GENERATION.fetch_add(1, Ordering::Relaxed);
+        self.last_generation = Some(generation);
+
+        instance.inner.wrapper().set_restart_generation(generation);
+
+        instance.deactivate_unchecked(processor);
+        let result = instance.activate_unchecked(audio_processor, configuration);
+
+        instance.inner.wrapper().set_restart_generation(0);
+
+        result
+    }
+}
@@ -17,21 +17,42 @@ use self::audio_buffers::InputAudioBuffers;
 use crate::host::HostHandlers;
 use crate::plugin::{PluginAudioProcessorHandle, PluginInstanceError, PluginSharedHandle};
 use crate::prelude::{OutputAudioBuffers, PluginInstance};
+use crate::process::trace::{ProcessTrace, TracedTransport};
 use crate::process::PluginAudioProcessor::*;
 use clack_common::events::event_types::TransportEvent;
-use clack_common::events::io::{InputEvents, OutputEvents};
-use clap_sys::process::clap_process;
+use clack_common::events::io::{InputEvents, OutputEventBuffer, OutputEvents, TryPushError};
+use clack_common::events::UnknownEvent;
+use clap_sys::audio_buffer::clap_audio_buffer;
+use clap_sys::ext::params::{clap_plugin_params, CLAP_EXT_PARAMS};
+use clap_sys::process::{clap_process, clap_process_status};
 use std::cell::UnsafeCell;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::plugin::instance::PluginInstanceInner;
 pub use clack_common::process::*;
 
+pub mod actions;
 #[allow(missing_docs)] // TODO: doc this
 pub mod audio_buffers;
+pub mod automation;
+pub mod chain;
+pub mod channel_adapter;
+pub mod delay;
+pub mod error_policy;
+#[cfg(feature = "load-meter")]
+pub mod load;
+pub mod metering;
+pub mod mix;
+pub mod note_ids;
+pub mod recorder;
+pub mod scheduler;
+pub mod trace;
+pub mod transport;
+pub mod wet_dry;
 
 /// A handle to a plugin's audio processor that can be in either its `started` or `stopped` state.
 ///
@@ -58,6 +79,9 @@ impl<H: HostHandlers> PluginAudioProcessor<H> {
     ///
     /// Calling this method allows the `steady_time` parameter passed to [`process`](StartedPluginAudioProcessor::process)
     /// to jump backwards.
+    ///
+    /// The plugin's parameter values are not affected. This can be called whether processing is
+    /// started or not.
     #[inline]
     pub fn reset(&mut self) {
         match self {
@@ -245,16 +269,16 @@ impl<H: HostHandlers> PluginAudioProcessor<H> {
     pub fn start_processing(
         &mut self,
     ) -> Result<&mut StartedPluginAudioProcessor<H>, PluginInstanceError> {
-        let inner = match self {
+        let (inner, trace) = match self {
             Started(_) => return Err(PluginInstanceError::ProcessingStarted),
-            Stopped(a) => a.inner.clone(),
+            Stopped(a) => (a.inner.clone(), a.trace.take()),
         };
 
         let Ok(started) = StoppedPluginAudioProcessor::new(inner).start_processing() else {
             return Err(PluginInstanceError::StartProcessingFailed);
         };
 
-        *self = Started(started);
+        *self = Started(StartedPluginAudioProcessor { trace, ..started });
 
         Ok(match self {
             Started(s) => s,
@@ -273,14 +297,14 @@ impl<H: HostHandlers> PluginAudioProcessor<H> {
     pub fn stop_processing(
         &mut self,
     ) -> Result<&mut StoppedPluginAudioProcessor<H>, PluginInstanceError> {
-        let inner = match self {
+        let (inner, trace) = match self {
             Stopped(_) => return Err(PluginInstanceError::ProcessingStopped),
-            Started(a) => a.inner.clone(),
+            Started(a) => (a.inner.clone(), a.trace.take()),
         };
 
         let stopped = StartedPluginAudioProcessor::new(inner).stop_processing();
 
-        *self = Stopped(stopped);
+        *self = Stopped(StoppedPluginAudioProcessor { trace, ..stopped });
 
         Ok(match self {
             Stopped(s) => s,
@@ -315,14 +339,14 @@ impl<H: HostHandlers> PluginAudioProcessor<H> {
     /// This operation is infallible.
     #[inline]
     pub fn ensure_processing_stopped(&mut self) -> &mut StoppedPluginAudioProcessor<H> {
-        let inner = match self {
+        let (inner, trace) = match self {
             Stopped(s) => return s,
-            Started(a) => a.inner.clone(),
+            Started(a) => (a.inner.clone(), a.trace.take()),
         };
 
         let stopped = StartedPluginAudioProcessor::new(inner).stop_processing();
 
-        *self = Stopped(stopped);
+        *self = Stopped(StoppedPluginAudioProcessor { trace, ..stopped });
 
         match self {
             Stopped(s) => s,
@@ -402,6 +426,7 @@ impl<H: HostHandlers> From<StoppedPluginAudioProcessor<H>> for PluginAudioProces
 /// [`destroy`](PluginInstance::deactivate)
 pub struct StartedPluginAudioProcessor<H: HostHandlers> {
     inner: Arc<PluginInstanceInner<H>>,
+    trace: Option<ProcessTrace>,
     _no_sync: PhantomData<UnsafeCell<()>>,
 }
 
@@ -410,6 +435,7 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
     fn new(inner: Arc<PluginInstanceInner<H>>) -> Self {
         Self {
             inner,
+            trace: None,
             _no_sync: PhantomData,
         }
     }
@@ -457,8 +483,18 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
     /// This function can return [`PluginInstanceError::NullProcessFunction`] if the plugin
     /// implementation did not provide a valid underlying `process` function pointer.
     ///
-    /// This can also return [`PluginInstanceError::ProcessingFailed`] if the `process` function
-    /// failed for any reason.
+    /// This can also return [`PluginInstanceError::ProcessingFailed`] if the plugin reported an
+    /// error, or [`PluginInstanceError::InvalidProcessStatus`] if it returned an unknown status
+    /// value and the instance's compatibility policy denies
+    /// [unknown statuses](crate::utils::compatibility::CompatibilityCheck::UnknownProcessStatus),
+    /// which is the default. In both cases, this audio processor stays usable, and `process` can be called again
+    /// for the next block. See the [`error_policy`] module for helpers to handle repeated errors.
+    ///
+    /// # Plugins without audio ports
+    ///
+    /// If both `audio_inputs` and `audio_outputs` are empty, the block has no frames. Use
+    /// [`process_events`](Self::process_events) to process plugins without audio ports, such as
+    /// note effects, with an explicit frame count.
     ///
     /// [`reset`]: Self::reset
     pub fn process(
@@ -472,10 +508,137 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
     ) -> Result<ProcessStatus, PluginInstanceError> {
         let frames_count = audio_inputs.min_available_frames_with(audio_outputs);
 
-        let audio_inputs = audio_inputs.as_raw_buffers();
-        let audio_outputs = audio_outputs.as_raw_buffers();
+        self.process_raw(
+            frames_count,
+            audio_inputs.as_raw_buffers(),
+            audio_outputs.as_raw_buffers(),
+            input_events,
+            output_events,
+            steady_time,
+            transport,
+        )
+    }
+
+    /// Process a block of events through the plugin, without any audio buffers.
+    ///
+    /// This is meant for plugins that have no audio ports, such as note effects or MIDI
+    /// analyzers. Since there are no audio buffers to derive the frame count from, the length of
+    /// the block is given explicitly by `frames_count`. Event timestamps are relative to the start
+    /// of this block, and must be lower than `frames_count`.
+    ///
+    /// All the other arguments behave the same way as in [`process`](Self::process). Note that
+    /// `steady_time` must still be increased by at least `frames_count` for the next call.
+    ///
+    /// # Errors
+    ///
+    /// This function can return [`PluginInstanceError::NullProcessFunction`] if the plugin
+    /// implementation did not provide a valid underlying `process` function pointer.
+    ///
+    /// This can also return [`PluginInstanceError::ProcessingFailed`] if the plugin reported an
+    /// error, or [`PluginInstanceError::InvalidProcessStatus`] if it returned an unknown status
+    /// value and the instance's compatibility policy denies
+    /// [unknown statuses](crate::utils::compatibility::CompatibilityCheck::UnknownProcessStatus),
+    /// which is the default. In both cases, this audio processor stays usable.
+    pub fn process_events(
+        &mut self,
+        frames_count: u32,
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+        steady_time: Option<u64>,
+        transport: Option<&TransportEvent>,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        self.process_raw(
+            frames_count,
+            &[],
+            &mut [],
+            input_events,
+            output_events,
+            steady_time,
+            transport,
+        )
+    }
+
+    /// Flushes the given parameter changes to the plugin, and receives its own parameter changes,
+    /// without processing any audio.
+    ///
+    /// This calls the plugin's `clap_plugin_params.flush` function directly from the audio
+    /// thread. Plugins handle it with their audio processor's state, the same way they would
+    /// handle parameter events in a `process` call.
+    ///
+    /// Hosts should normally send parameter changes through [`process`](Self::process). This
+    /// method is meant for when the plugin is kept active and started, but isn't being processed,
+    /// e.g. while it is bypassed by the host, or when the host isn't receiving any audio. Unlike a
+    /// [zero-frame `process` call](Self::process_events), this doesn't run the plugin's audio
+    /// processing, and is always valid, even if `input_events` is empty.
+    ///
+    /// This returns `false` if the plugin doesn't support the `params` extension, or doesn't
+    /// implement its `flush` function, in which case nothing was flushed.
+    pub fn flush_active_params(
+        &mut self,
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+    ) -> bool {
+        let Some(params) = self
+            .inner
+            .plugin_shared()
+            .get_raw_extension(CLAP_EXT_PARAMS)
+        else {
+            return false;
+        };
+
+        // SAFETY: the pointer was returned by the plugin for the params extension, and the CLAP
+        // spec guarantees it lives as long as the instance.
+        let params = unsafe { params.cast::<clap_plugin_params>().as_ref() };
+
+        let Some(flush) = params.flush else {
+            return false;
+        };
+
+        // SAFETY: this type ensures the function pointer is valid, and that this is called on the
+        // audio thread, while the plugin is active.
+        unsafe {
+            flush(
+                self.inner.raw_instance_ptr().as_ptr(),
+                input_events.as_raw(),
+                output_events.as_raw_mut(),
+            )
+        };
+
+        true
+    }
+
+    /// Attaches the given [`ProcessTrace`] to this audio processor, replacing and returning the
+    /// previously attached one, if any. Passing [`None`] detaches the current trace.
+    ///
+    /// All subsequent calls to [`process`](Self::process) and
+    /// [`process_events`](Self::process_events) are then recorded into the trace. The trace stays
+    /// attached when this audio processor is stopped and started again.
+    ///
+    /// If the `process-trace` feature is disabled, traces record nothing, and attaching one has no
+    /// effect on process calls.
+    #[inline]
+    pub fn set_trace(&mut self, trace: Option<ProcessTrace>) -> Option<ProcessTrace> {
+        core::mem::replace(&mut self.trace, trace)
+    }
+
+    /// Returns the [`ProcessTrace`] attached to this audio processor, if any.
+    #[inline]
+    pub fn trace(&self) -> Option<&ProcessTrace> {
+        self.trace.as_ref()
+    }
 
-        let process = clap_process {
+    #[allow(clippy::too_many_arguments)]
+    fn process_raw(
+        &mut self,
+        frames_count: u32,
+        audio_inputs: &[clap_audio_buffer],
+        audio_outputs: &mut [clap_audio_buffer],
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+        steady_time: Option<u64>,
+        transport: Option<&TransportEvent>,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        let mut process = clap_process {
             frames_count,
 
             in_events: input_events.as_raw(),
@@ -498,18 +661,58 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
             },
         };
 
-        let instance = self.inner.raw_instance();
+        let instance = self.inner.raw_instance_ptr().as_ptr();
 
-        let process_fn = instance
+        let process_fn = self
+            .inner
+            .raw_instance()
             .process
             .ok_or(PluginInstanceError::NullProcessFunction)?;
 
+        let Some(trace) = self.trace.as_ref().filter(|_| ProcessTrace::is_enabled()) else {
+            // SAFETY: this type ensures the function pointer is valid
+            let status = unsafe { process_fn(instance, &process) };
+
+            return self.check_status(status);
+        };
+
+        let mut counter = CountingOutputEvents {
+            output: output_events,
+            count: 0,
+        };
+        let mut counted_output_events = OutputEvents::from_buffer(&mut counter);
+        process.out_events = counted_output_events.as_raw_mut();
+
+        let start = Instant::now();
         // SAFETY: this type ensures the function pointer is valid
         let status = unsafe { process_fn(instance, &process) };
+        let duration = start.elapsed();
+
+        trace.record(
+            frames_count,
+            process.steady_time,
+            input_events.len(),
+            counter.count,
+            status,
+            duration,
+            TracedTransport::from_transport(transport),
+        );
+
+        self.check_status(status)
+    }
 
-        match ProcessStatus::from_raw(status) {
-            None | Some(Err(())) => Err(PluginInstanceError::ProcessingFailed),
-            Some(Ok(status)) => Ok(status),
+    /// Converts the given raw status, handling unknown ones following the instance's
+    /// compatibility policy.
+    #[inline]
+    fn check_status(
+        &self,
+        status: clap_process_status,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        match process_status_from_raw(status) {
+            Err(PluginInstanceError::InvalidProcessStatus { status }) => {
+                self.inner.unknown_process_status(status)
+            }
+            result => result,
         }
     }
 
@@ -522,7 +725,7 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
     /// to jump backwards.
     #[inline]
     pub fn reset(&mut self) {
-        // SAFETY: This type ensures this can only be called in the main thread.
+        // SAFETY: This type ensures this can only be called on the audio thread.
         unsafe { self.inner.reset() }
     }
 
@@ -540,6 +743,7 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
 
         StoppedPluginAudioProcessor {
             inner,
+            trace: self.trace,
             _no_sync: PhantomData,
         }
     }
@@ -610,7 +814,7 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
     /// Returns this plugin instance's audio processor handle.
     #[inline]
     pub fn plugin_handle(&mut self) -> PluginAudioProcessorHandle {
-        PluginAudioProcessorHandle::new(self.inner.raw_instance().into())
+        PluginAudioProcessorHandle::new(self.inner.raw_instance_ptr())
     }
 
     /// Returns this plugin instance's shared handle.
@@ -626,6 +830,21 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
     }
 }
 
+/// Forwards events to another output event list, while counting them for a [`ProcessTrace`].
+struct CountingOutputEvents<'a, 'b> {
+    output: &'a mut OutputEvents<'b>,
+    count: u32,
+}
+
+impl OutputEventBuffer for CountingOutputEvents<'_, '_> {
+    #[inline]
+    fn try_push(&mut self, event: &UnknownEvent) -> Result<(), TryPushError> {
+        self.output.try_push(event)?;
+        self.count += 1;
+        Ok(())
+    }
+}
+
 /// A handle to a plugin's audio processor that is in the `stopped` state.
 ///
 /// This is the default state the plugin's audio processor will be in after calling [`activate`].
@@ -651,6 +870,7 @@ impl<H: HostHandlers> StartedPluginAudioProcessor<H> {
 /// [audio processor]: crate::prelude::AudioProcessorHandler
 pub struct StoppedPluginAudioProcessor<H: HostHandlers> {
     pub(crate) inner: Arc<PluginInstanceInner<H>>,
+    trace: Option<ProcessTrace>,
     _no_sync: PhantomData<UnsafeCell<()>>,
 }
 
@@ -659,6 +879,7 @@ impl<H: HostHandlers> StoppedPluginAudioProcessor<H> {
     pub(crate) fn new(inner: Arc<PluginInstanceInner<H>>) -> Self {
         Self {
             inner,
+            trace: None,
             _no_sync: PhantomData,
         }
     }
@@ -672,7 +893,7 @@ impl<H: HostHandlers> StoppedPluginAudioProcessor<H> {
     /// to jump backwards.
     #[inline]
     pub fn reset(&mut self) {
-        // SAFETY: This type ensures this can only be called in the main thread.
+        // SAFETY: This type ensures this can only be called on the audio thread.
         unsafe { self.inner.reset() }
     }
 
@@ -699,6 +920,7 @@ impl<H: HostHandlers> StoppedPluginAudioProcessor<H> {
         match unsafe { self.inner.start_processing() } {
             Ok(()) => Ok(StartedPluginAudioProcessor {
                 inner: self.inner,
+                trace: self.trace,
                 _no_sync: PhantomData,
             }),
             Err(_) => Err(ProcessingStartError { processor: self }),
@@ -772,7 +994,7 @@ impl<H: HostHandlers> StoppedPluginAudioProcessor<H> {
     /// Returns this plugin instance's audio processor handle.
     #[inline]
     pub fn plugin_handle(&mut self) -> PluginAudioProcessorHandle {
-        PluginAudioProcessorHandle::new(self.inner.raw_instance().into())
+        PluginAudioProcessorHandle::new(self.inner.raw_instance_ptr())
     }
 
     /// Returns this plugin instance's shared handle.
@@ -818,6 +1040,17 @@ impl<H: HostHandlers> Display for ProcessingStartError<H> {
 
 impl<H: HostHandlers> Error for ProcessingStartError<H> {}
 
+/// Maps the raw status returned by a plugin's `process` function to a result.
+fn process_status_from_raw(
+    status: clap_process_status,
+) -> Result<ProcessStatus, PluginInstanceError> {
+    match ProcessStatus::from_raw(status) {
+        Some(Ok(status)) => Ok(status),
+        Some(Err(())) => Err(PluginInstanceError::ProcessingFailed),
+        None => Err(PluginInstanceError::InvalidProcessStatus { status }),
+    }
+}
+
 #[cfg(test)]
 mod test {
     extern crate static_assertions as sa;
@@ -825,4 +1058,22 @@ mod test {
 
     sa::assert_not_impl_any!(StartedPluginAudioProcessor<()>: Sync);
     sa::assert_not_impl_any!(StoppedPluginAudioProcessor<()>: Sync);
+
+    #[test]
+    fn process_errors_are_distinguished_from_invalid_statuses() {
+        use clap_sys::process::{CLAP_PROCESS_ERROR, CLAP_PROCESS_SLEEP};
+
+        assert_eq!(
+            process_status_from_raw(CLAP_PROCESS_SLEEP),
+            Ok(ProcessStatus::Sleep)
+        );
+        assert_eq!(
+            process_status_from_raw(CLAP_PROCESS_ERROR),
+            Err(PluginInstanceError::ProcessingFailed)
+        );
+        assert_eq!(
+            process_status_from_raw(42),
+            Err(PluginInstanceError::InvalidProcessStatus { status: 42 })
+        );
+    }
 }
@@ -0,0 +1,389 @@
+//! Frame-accurate scheduling of state-affecting actions, such as bypass or preset changes.
+//!
+//! See the [`ActionScheduler`] type for more information.
+
+use crate::process::note_ids::ActiveNoteTracker;
+use clack_common::events::event_types::ParamValueEvent;
+use clack_common::events::io::EventBuffer;
+use clack_common::events::Pckn;
+use clack_common::utils::{ClapId, Cookie};
+
+/// How the notes are ended by [`ScheduledAction::AllNotesOff`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum NotesOffMode {
+    /// All held notes receive a note off event, and may ring out.
+    Release,
+    /// All notes, held or released, receive a choke event, and end immediately.
+    Choke,
+}
+
+/// An action that affects a plugin's state, scheduled at a given sample position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScheduledAction {
+    /// Sets the plugin's bypass parameter, with a value of `1.0` if bypassed, or `0.0` otherwise.
+    SetBypass {
+        /// The ID of the plugin's bypass parameter.
+        param_id: ClapId,
+        /// Whether the plugin is bypassed.
+        bypassed: bool,
+    },
+    /// Loads a preset into the plugin.
+    ///
+    /// Presets are loaded through the main thread, so this action is never turned into events:
+    /// it is returned as a [`DeferredAction`] instead.
+    LoadPreset {
+        /// A host-defined identifier of the preset to load.
+        preset: u64,
+    },
+    /// Ends all the notes tracked by the [`ActiveNoteTracker`], each targeting its note ID.
+    AllNotesOff {
+        /// How the notes are ended.
+        mode: NotesOffMode,
+    },
+}
+
+impl ScheduledAction {
+    /// Returns `true` if this action must be performed on the main thread.
+    #[inline]
+    pub const fn is_main_thread(&self) -> bool {
+        matches!(self, ScheduledAction::LoadPreset { .. })
+    }
+}
+
+/// A main-thread action that became due during a block, and that must now be performed by the
+/// host.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DeferredAction {
+    /// The absolute sample position the action was scheduled at.
+    pub position: u64,
+    /// The offset, in frames, of that position in the block it became due in.
+    ///
+    /// This is `0` if the action was already late when that block started.
+    pub frame: u32,
+    /// The action to perform.
+    pub action: ScheduledAction,
+}
+
+/// Schedules [`ScheduledAction`]s at absolute sample positions, and turns them into the events
+/// to send to a plugin, block by block.
+///
+/// Actions are [scheduled](Self::schedule) with an absolute sample position, e.g. the position a
+/// user toggled bypass at, on the host's timeline. Then, before processing each block,
+/// [`write_block`](Self::write_block) writes the events of all the actions that are due in that
+/// block, at their offset in the block. Actions which were scheduled before the start of the
+/// block are written at its first frame. Actions scheduled at the same position are performed in
+/// the order they were scheduled.
+///
+/// Actions that must be performed on the main thread, such as preset loads, can't be turned into
+/// events: they are kept as [`DeferredAction`]s instead, which the host must then
+/// [drain](Self::drain_deferred) and perform.
+///
+/// All storage is allocated when the scheduler is created: it never allocates afterwards, and can
+/// be used on the audio thread.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::events::io::EventBuffer;
+/// use clack_host::process::actions::*;
+/// use clack_host::process::note_ids::ActiveNoteTracker;
+/// use clack_host::utils::ClapId;
+///
+/// let mut scheduler = ActionScheduler::new(16);
+/// let mut notes = ActiveNoteTracker::new(16);
+/// let mut events = EventBuffer::new();
+///
+/// let bypass = ScheduledAction::SetBypass { param_id: ClapId::new(0), bypassed: true };
+/// scheduler.schedule(1000, bypass).unwrap();
+/// scheduler.schedule(1000, ScheduledAction::LoadPreset { preset: 3 }).unwrap();
+///
+/// // Nothing is due in the first block.
+/// assert_eq!(scheduler.write_block(0, 512, &mut notes, &mut events), 0);
+///
+/// // The bypass change lands 488 frames into the second block.
+/// assert_eq!(scheduler.write_block(512, 512, &mut notes, &mut events), 1);
+/// assert_eq!(events.get(0).unwrap().header().time(), 488);
+///
+/// // The preset must be loaded from the main thread.
+/// let deferred: Vec<_> = scheduler.drain_deferred().collect();
+/// assert_eq!(deferred[0].action, ScheduledAction::LoadPreset { preset: 3 });
+/// ```
+#[derive(Clone, Debug)]
+pub struct ActionScheduler {
+    /// The pending actions, sorted by position.
+    pending: Vec<(u64, ScheduledAction)>,
+    deferred: Vec<DeferredAction>,
+    capacity: usize,
+}
+
+impl ActionScheduler {
+    /// Creates a new scheduler, which can hold up to `capacity` pending and deferred actions at
+    /// the same time.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            pending: Vec::with_capacity(capacity),
+            deferred: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the maximum number of pending and deferred actions this scheduler can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of actions that are scheduled, but not yet due.
+    #[inline]
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns `true` if no action is pending or deferred.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.deferred.is_empty()
+    }
+
+    /// Schedules an action at the given absolute sample position.
+    ///
+    /// # Errors
+    ///
+    /// If the scheduler is full, the action is returned.
+    pub fn schedule(
+        &mut self,
+        position: u64,
+        action: ScheduledAction,
+    ) -> Result<(), ScheduledAction> {
+        if self.pending.len() + self.deferred.len() >= self.capacity {
+            return Err(action);
+        }
+
+        let index = self.pending.partition_point(|(p, _)| *p <= position);
+        self.pending.insert(index, (position, action));
+
+        Ok(())
+    }
+
+    /// Cancels all pending actions, e.g. when the host's playback position jumps.
+    ///
+    /// Deferred actions are kept.
+    #[inline]
+    pub fn cancel_pending(&mut self) {
+        self.pending.clear()
+    }
+
+    /// Writes the events of all the actions that are due in the block starting at the given
+    /// absolute sample position, at their offset in that block.
+    ///
+    /// The notes ended by [`AllNotesOff`](ScheduledAction::AllNotesOff) actions are taken from,
+    /// and updated in, the given tracker. Main-thread actions are kept as deferred actions.
+    ///
+    /// Events are written in order. If `output` already contains other events, it must be
+    /// [sorted](EventBuffer::sort) before being sent to the plugin.
+    ///
+    /// Returns the number of events that were written.
+    pub fn write_block(
+        &mut self,
+        block_start: u64,
+        frames_count: u32,
+        notes: &mut ActiveNoteTracker,
+        output: &mut EventBuffer,
+    ) -> usize {
+        let block_end = block_start.saturating_add(frames_count.into());
+        let due = self.pending.partition_point(|(p, _)| *p < block_end);
+
+        let mut count = 0;
+
+        for (position, action) in self.pending.drain(..due) {
+            // Due actions are always within the block, or before it.
+            let frame = position.saturating_sub(block_start) as u32;
+
+            match action {
+                ScheduledAction::SetBypass { param_id, bypassed } => {
+                    output.push(&ParamValueEvent::new(
+                        frame,
+                        param_id,
+                        Pckn::match_all(),
+                        if bypassed { 1.0 } else { 0.0 },
+                        Cookie::empty(),
+                    ));
+                    count += 1;
+                }
+                ScheduledAction::AllNotesOff {
+                    mode: NotesOffMode::Release,
+                } => count += notes.release_all(frame, 0.0, output),
+                ScheduledAction::AllNotesOff {
+                    mode: NotesOffMode::Choke,
+                } => count += notes.choke_all(frame, output),
+                ScheduledAction::LoadPreset { .. } => self.deferred.push(DeferredAction {
+                    position,
+                    frame,
+                    action,
+                }),
+            }
+        }
+
+        count
+    }
+
+    /// Returns the main-thread actions that became due, and that weren't drained yet.
+    #[inline]
+    pub fn deferred(&self) -> &[DeferredAction] {
+        &self.deferred
+    }
+
+    /// Removes and returns all the main-thread actions that became due, in order.
+    #[inline]
+    pub fn drain_deferred(&mut self) -> impl Iterator<Item = DeferredAction> + '_ {
+        self.deferred.drain(..)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::events::spaces::CoreEventSpace;
+    use clack_common::events::Match;
+
+    const BYPASS: ClapId = ClapId::new(42);
+
+    fn bypass(bypassed: bool) -> ScheduledAction {
+        ScheduledAction::SetBypass {
+            param_id: BYPASS,
+            bypassed,
+        }
+    }
+
+    fn times(events: &EventBuffer) -> Vec<u32> {
+        events.iter().map(|e| e.header().time()).collect()
+    }
+
+    #[test]
+    fn actions_land_at_their_frame() {
+        let mut scheduler = ActionScheduler::new(8);
+        let mut notes = ActiveNoteTracker::new(8);
+        let mut events = EventBuffer::new();
+
+        scheduler.schedule(300, bypass(false)).unwrap();
+        scheduler.schedule(100, bypass(true)).unwrap();
+        scheduler.schedule(256, bypass(true)).unwrap();
+        scheduler.schedule(255, bypass(false)).unwrap();
+
+        assert_eq!(scheduler.write_block(0, 256, &mut notes, &mut events), 2);
+        assert_eq!(times(&events), [100, 255]);
+
+        events.clear();
+        assert_eq!(scheduler.write_block(256, 256, &mut notes, &mut events), 2);
+        assert_eq!(times(&events), [0, 44]);
+
+        let values: Vec<f64> = events
+            .iter()
+            .map(|e| match e.as_core_event() {
+                Some(CoreEventSpace::ParamValue(e)) => {
+                    assert_eq!(e.param_id(), BYPASS);
+                    e.value()
+                }
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(values, [1.0, 0.0]);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn late_actions_land_at_block_start_in_order() {
+        let mut scheduler = ActionScheduler::new(8);
+        let mut notes = ActiveNoteTracker::new(8);
+        let mut events = EventBuffer::new();
+
+        scheduler.schedule(10, bypass(true)).unwrap();
+        scheduler
+            .schedule(10, ScheduledAction::LoadPreset { preset: 7 })
+            .unwrap();
+        scheduler.schedule(10, bypass(false)).unwrap();
+
+        assert_eq!(scheduler.write_block(64, 64, &mut notes, &mut events), 2);
+        assert_eq!(times(&events), [0, 0]);
+        assert_eq!(scheduler.pending_count(), 0);
+
+        assert_eq!(
+            scheduler.deferred(),
+            [DeferredAction {
+                position: 10,
+                frame: 0,
+                action: ScheduledAction::LoadPreset { preset: 7 },
+            }]
+        );
+        assert_eq!(scheduler.drain_deferred().count(), 1);
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn full_scheduler_returns_the_action() {
+        let mut scheduler = ActionScheduler::new(2);
+        let mut notes = ActiveNoteTracker::new(8);
+        let mut events = EventBuffer::new();
+
+        scheduler
+            .schedule(0, ScheduledAction::LoadPreset { preset: 1 })
+            .unwrap();
+        scheduler.schedule(100, bypass(true)).unwrap();
+        assert_eq!(scheduler.schedule(50, bypass(false)), Err(bypass(false)));
+
+        // Deferred actions still take room until they are drained.
+        scheduler.write_block(0, 10, &mut notes, &mut events);
+        assert_eq!(scheduler.schedule(50, bypass(false)), Err(bypass(false)));
+
+        assert_eq!(scheduler.drain_deferred().count(), 1);
+        assert_eq!(scheduler.schedule(50, bypass(false)), Ok(()));
+    }
+
+    #[test]
+    fn all_notes_off_ends_every_tracked_note() {
+        let mut scheduler = ActionScheduler::new(8);
+        let mut notes = ActiveNoteTracker::new(8);
+        let mut events = EventBuffer::new();
+
+        notes.note_on(0, 0, 0, 60, 1.0).unwrap();
+        notes.note_on(0, 0, 1, 64, 1.0).unwrap();
+        notes.note_on(0, 1, 0, 67, 1.0).unwrap();
+        notes.note_off(0, 0, 1, 64, 0.0).unwrap();
+
+        let release = ScheduledAction::AllNotesOff {
+            mode: NotesOffMode::Release,
+        };
+        let choke = ScheduledAction::AllNotesOff {
+            mode: NotesOffMode::Choke,
+        };
+        scheduler.schedule(20, release).unwrap();
+        scheduler.schedule(30, choke).unwrap();
+
+        // Only the two held notes are released.
+        assert_eq!(scheduler.write_block(0, 25, &mut notes, &mut events), 2);
+        assert_eq!(times(&events), [20, 20]);
+        let released: Vec<_> = events
+            .iter()
+            .map(|e| match e.as_core_event() {
+                Some(CoreEventSpace::NoteOff(e)) => e.pckn().note_id,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(released, [0, 2].map(Match::Specific));
+        assert_eq!(notes.len(), 3);
+
+        // All three notes are choked, released or not.
+        events.clear();
+        assert_eq!(scheduler.write_block(25, 25, &mut notes, &mut events), 3);
+        assert_eq!(times(&events), [5, 5, 5]);
+        let choked: Vec<_> = events
+            .iter()
+            .map(|e| match e.as_core_event() {
+                Some(CoreEventSpace::NoteChoke(e)) => e.pckn().note_id,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(choked, [0, 1, 2].map(Match::Specific));
+        assert!(notes.is_empty());
+    }
+}
@@ -3,6 +3,10 @@
 use clack_common::process::AudioPortProcessingInfo;
 use clap_sys::audio_buffer::clap_audio_buffer;
 use core::array::IntoIter;
+use core::cell::{Cell, RefCell, UnsafeCell};
+use core::fmt::{Display, Formatter};
+use core::ops::{Deref, DerefMut};
+use std::error::Error;
 
 pub struct InputChannel<'a, T> {
     pub buffer: &'a mut [T],
@@ -126,6 +130,16 @@ impl AudioPorts {
         self.buffer_configs.len()
     }
 
+    /// Returns a pointer to the channel buffer list starting at the given index.
+    ///
+    /// All pointers are derived from the list's raw pointer, without going through references to
+    /// its contents: this keeps every port's pointer valid while the other ports are written.
+    #[inline]
+    fn channel_list_ptr(&mut self, start: usize) -> *mut *mut f32 {
+        // SAFETY: start is at most the length of the list, so this stays within the allocation.
+        unsafe { self.buffer_lists.as_mut_ptr().add(start) }
+    }
+
     fn resize_buffer_configs(&mut self, new_size: usize) {
         if new_size > self.buffer_configs.len() {
             self.buffer_configs.resize(
@@ -200,20 +214,21 @@ impl AudioPorts {
                 }
             };
 
-            let buffers = self.buffer_lists.get_mut(last..).unwrap_or(&mut []);
+            let channel_count = self.buffer_lists.len() - last;
+            let buffers = self.channel_list_ptr(last);
 
             // PANIC: this can only panic with an invalid implementation of ExactSizeIterator
             let descriptor = &mut self.buffer_configs[i];
-            descriptor.channel_count = buffers.len() as u32;
+            descriptor.channel_count = channel_count as u32;
             descriptor.latency = port.latency;
             descriptor.constant_mask = constant_mask;
 
             if is_f64 {
-                descriptor.data64 = buffers.as_ptr().cast();
+                descriptor.data64 = buffers.cast();
                 descriptor.data32 = core::ptr::null();
             } else {
                 descriptor.data64 = core::ptr::null();
-                descriptor.data32 = buffers.as_ptr() as *const *const _;
+                descriptor.data32 = buffers.cast();
             }
         }
 
@@ -222,18 +237,16 @@ impl AudioPorts {
         // to find them back.
         if has_reallocated {
             let mut last_len = 0;
-            for descriptor in &mut self.buffer_configs[..total] {
-                let channel_count = descriptor.channel_count as usize;
-                let buffers = self
-                    .buffer_lists
-                    .get_mut(last_len..channel_count)
-                    .unwrap_or(&mut []);
-                last_len += channel_count;
+            for i in 0..total {
+                let buffers = self.channel_list_ptr(last_len);
+
+                let descriptor = &mut self.buffer_configs[i];
+                last_len += descriptor.channel_count as usize;
 
                 if descriptor.data32.is_null() {
-                    descriptor.data64 = buffers.as_ptr().cast();
+                    descriptor.data64 = buffers.cast();
                 } else {
-                    descriptor.data32 = buffers.as_ptr().cast();
+                    descriptor.data32 = buffers.cast();
                 }
             }
         }
@@ -298,20 +311,21 @@ impl AudioPorts {
                 }
             };
 
-            let buffers = self.buffer_lists.get_mut(last..).unwrap_or(&mut []);
+            let channel_count = self.buffer_lists.len() - last;
+            let buffers = self.channel_list_ptr(last);
 
             // PANIC: this can only panic with an invalid implementation of ExactSizeIterator
             let descriptor = &mut self.buffer_configs[i];
-            descriptor.channel_count = buffers.len() as u32;
+            descriptor.channel_count = channel_count as u32;
             descriptor.latency = port.latency;
             descriptor.constant_mask = 0;
 
             if is_f64 {
-                descriptor.data64 = buffers.as_mut_ptr().cast();
+                descriptor.data64 = buffers.cast();
                 descriptor.data32 = core::ptr::null();
             } else {
                 descriptor.data64 = core::ptr::null();
-                descriptor.data32 = buffers.as_mut_ptr() as *const *const _;
+                descriptor.data32 = buffers.cast();
             }
         }
 
@@ -320,18 +334,16 @@ impl AudioPorts {
         // to find them back.
         if has_reallocated {
             let mut last_len = 0;
-            for descriptor in &mut self.buffer_configs[..total] {
-                let channel_count = descriptor.channel_count as usize;
-                let buffers = self
-                    .buffer_lists
-                    .get_mut(last_len..channel_count)
-                    .unwrap_or(&mut []);
-                last_len += channel_count;
+            for i in 0..total {
+                let buffers = self.channel_list_ptr(last_len);
+
+                let descriptor = &mut self.buffer_configs[i];
+                last_len += descriptor.channel_count as usize;
 
                 if descriptor.data32.is_null() {
-                    descriptor.data64 = buffers.as_ptr().cast();
+                    descriptor.data64 = buffers.cast();
                 } else {
-                    descriptor.data32 = buffers.as_ptr().cast();
+                    descriptor.data32 = buffers.cast();
                 }
             }
         }
@@ -358,6 +370,9 @@ pub struct InputAudioBuffers<'a> {
 }
 
 impl<'a> InputAudioBuffers<'a> {
+    /// Returns input buffers with no ports, e.g. for plugins that have no audio input.
+    ///
+    /// The [`frames_count`](Self::frames_count) of empty buffers is always `None`.
     #[inline]
     pub const fn empty() -> Self {
         Self {
@@ -387,11 +402,17 @@ impl<'a> InputAudioBuffers<'a> {
     ///
     /// The caller must also ensure `frames_count` is lower than or equal to the sizes of the
     /// channel buffers pointed to by `buffers`.
+    ///
+    /// If `buffers` is empty, the resulting [`frames_count`](Self::frames_count) is `None`.
     #[inline]
     pub unsafe fn from_raw_buffers(buffers: &'a [clap_audio_buffer], frames_count: u32) -> Self {
         Self {
+            frames_count: if buffers.is_empty() {
+                None
+            } else {
+                Some(frames_count)
+            },
             buffers,
-            frames_count: Some(frames_count),
         }
     }
 
@@ -454,6 +475,10 @@ impl<'a> InputAudioBuffers<'a> {
     ///
     /// This is useful to ensure a safe frame count for a `process` batch that would receive those
     /// input and output audio buffers.
+    ///
+    /// If neither have any port, this returns `0`. In that case, the frame count has to be given
+    /// explicitly, e.g. using
+    /// [`StartedPluginAudioProcessor::process_events`](crate::process::StartedPluginAudioProcessor::process_events).
     pub fn min_available_frames_with(&self, outputs: &OutputAudioBuffers) -> u32 {
         match (self.frames_count, outputs.frames_count) {
             (Some(a), Some(b)) => a.min(b),
@@ -463,12 +488,22 @@ impl<'a> InputAudioBuffers<'a> {
     }
 }
 
+impl Default for InputAudioBuffers<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 pub struct OutputAudioBuffers<'a> {
     buffers: &'a mut [clap_audio_buffer],
     frames_count: Option<u32>,
 }
 
 impl<'a> OutputAudioBuffers<'a> {
+    /// Returns output buffers with no ports, e.g. for plugins that have no audio output.
+    ///
+    /// The [`frames_count`](Self::frames_count) of empty buffers is always `None`.
     #[inline]
     pub fn empty() -> Self {
         Self {
@@ -498,14 +533,20 @@ impl<'a> OutputAudioBuffers<'a> {
     ///
     /// The caller must also ensure `frames_count` is lower than or equal to the sizes of the
     /// channel buffers pointed to by `buffers`.
+    ///
+    /// If `buffers` is empty, the resulting [`frames_count`](Self::frames_count) is `None`.
     #[inline]
     pub unsafe fn from_raw_buffers(
         buffers: &'a mut [clap_audio_buffer],
         frames_count: u32,
     ) -> Self {
         Self {
+            frames_count: if buffers.is_empty() {
+                None
+            } else {
+                Some(frames_count)
+            },
             buffers,
-            frames_count: Some(frames_count),
         }
     }
 
@@ -626,6 +667,245 @@ impl<'a> OutputAudioBuffers<'a> {
     }
 }
 
+impl Default for OutputAudioBuffers<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+/// A pool of pre-allocated channel buffers, to be used as temporary buffers during processing.
+///
+/// Hosts with dynamic routing often need scratch buffers that only live for a single block (e.g.
+/// to sum multiple connections into a single plugin input). This pool allocates a fixed amount of
+/// channel buffers of `max_frames` samples each upfront, which can then be checked out from the
+/// audio thread without allocating.
+///
+/// Checked out buffers are handed out as [`PooledBuffer`] guards, which can be used to build
+/// [`InputAudioBuffers`] and [`OutputAudioBuffers`] through [`AudioPorts`], and which return
+/// their buffer to the pool when dropped. Multiple buffers can be checked out at the same time.
+///
+/// The pool is meant to be used from a single thread at a time: it can be sent to the audio
+/// thread, but not shared across threads.
+///
+/// # Realtime Safety
+///
+/// Checking out and returning buffers never allocates. If all buffers are checked out,
+/// [`try_checkout`](Self::try_checkout) returns a [`BufferPoolExhausted`] error instead, which
+/// the host can handle e.g. by falling back to a silent buffer. The pool's
+/// [high-water mark](Self::high_water_mark) can be used to tune its capacity.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::process::audio_buffers::*;
+///
+/// let pool = BufferPool::<f32>::new(4, 256);
+/// let mut ports = AudioPorts::with_capacity(2, 1);
+///
+/// let mut left = pool.try_checkout().unwrap();
+/// let mut right = pool.try_checkout().unwrap();
+///
+/// let outputs = ports.with_output_buffers([AudioPortBuffer {
+///     latency: 0,
+///     channels: AudioPortBufferType::f32_output_only([&mut *left, &mut *right]),
+/// }]);
+///
+/// assert_eq!(outputs.frames_count(), Some(256));
+/// assert_eq!(pool.available(), 2);
+/// ```
+pub struct BufferPool<T> {
+    storage: Box<[UnsafeCell<T>]>,
+    buffer_count: usize,
+    max_frames: usize,
+    free: RefCell<Vec<usize>>,
+    high_water_mark: Cell<usize>,
+}
+
+impl<T: Copy + Default> BufferPool<T> {
+    /// Creates a new pool of `buffer_count` channel buffers, each of `max_frames` samples.
+    ///
+    /// All buffers are initially filled with the default value of `T`, i.e. silence.
+    pub fn new(buffer_count: usize, max_frames: usize) -> Self {
+        let storage = (0..buffer_count * max_frames)
+            .map(|_| UnsafeCell::new(T::default()))
+            .collect();
+
+        let mut free = Vec::with_capacity(buffer_count);
+        free.extend((0..buffer_count).rev());
+
+        Self {
+            storage,
+            buffer_count,
+            max_frames,
+            free: RefCell::new(free),
+            high_water_mark: Cell::new(0),
+        }
+    }
+}
+
+impl<T> BufferPool<T> {
+    /// Returns the total number of buffers in this pool.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer_count
+    }
+
+    /// Returns the length, in samples, of each buffer of this pool.
+    #[inline]
+    pub fn max_frames(&self) -> usize {
+        self.max_frames
+    }
+
+    /// Returns the number of buffers that can currently be checked out.
+    #[inline]
+    pub fn available(&self) -> usize {
+        self.free.borrow().len()
+    }
+
+    /// Returns the number of buffers that are currently checked out.
+    #[inline]
+    pub fn checked_out(&self) -> usize {
+        self.capacity() - self.available()
+    }
+
+    /// Returns the highest number of buffers that were checked out at the same time, since this
+    /// pool was created or since the last call to
+    /// [`reset_high_water_mark`](Self::reset_high_water_mark).
+    #[inline]
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark.get()
+    }
+
+    /// Resets the [high-water mark](Self::high_water_mark) to the number of buffers currently
+    /// checked out.
+    #[inline]
+    pub fn reset_high_water_mark(&self) {
+        self.high_water_mark.set(self.checked_out())
+    }
+
+    /// Checks out a buffer from this pool.
+    ///
+    /// The contents of the returned buffer are unspecified: they are whatever the previous user
+    /// of that buffer left in it. Use [`PooledBuffer::clear`] if the buffer must start silent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BufferPoolExhausted`] if all the buffers of this pool are already checked out.
+    pub fn try_checkout(&self) -> Result<PooledBuffer<'_, T>, BufferPoolExhausted> {
+        let index = self.free.borrow_mut().pop().ok_or(BufferPoolExhausted)?;
+
+        let checked_out = self.checked_out();
+        if checked_out > self.high_water_mark.get() {
+            self.high_water_mark.set(checked_out);
+        }
+
+        let start = index * self.max_frames;
+        let cells = &self.storage[start..start + self.max_frames];
+
+        // SAFETY: The index was just removed from the free list, and is only put back once the
+        // returned guard is dropped. No other reference to this range of the storage can exist
+        // until then. UnsafeCell<T> has the same memory layout as T.
+        let buffer = unsafe {
+            core::slice::from_raw_parts_mut(UnsafeCell::raw_get(cells.as_ptr()), cells.len())
+        };
+
+        Ok(PooledBuffer {
+            buffer,
+            index,
+            pool: self,
+        })
+    }
+
+    /// Returns all the buffers that are still checked out back to the pool, and returns how many
+    /// there were.
+    ///
+    /// As this requires exclusive access to the pool, no [`PooledBuffer`] guard can be alive at
+    /// this point: any buffer still checked out was leaked, e.g. using [`core::mem::forget`].
+    /// Hosts can call this at the end of each block to detect such leaks, and recover the leaked
+    /// buffers.
+    pub fn reclaim_leaked(&mut self) -> usize {
+        let free = self.free.get_mut();
+        let leaked = self.buffer_count - free.len();
+
+        if leaked > 0 {
+            free.clear();
+            free.extend((0..self.buffer_count).rev());
+        }
+
+        leaked
+    }
+}
+
+/// A buffer checked out from a [`BufferPool`].
+///
+/// This dereferences to a slice of the pool's `max_frames` samples. The buffer is returned to the
+/// pool when this guard is dropped.
+pub struct PooledBuffer<'a, T> {
+    buffer: &'a mut [T],
+    index: usize,
+    pool: &'a BufferPool<T>,
+}
+
+impl<T: Copy + Default> PooledBuffer<'_, T> {
+    /// Fills this buffer with the default value of `T`, i.e. silence.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffer.fill(T::default())
+    }
+}
+
+impl<T> Deref for PooledBuffer<'_, T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.buffer
+    }
+}
+
+impl<T> DerefMut for PooledBuffer<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buffer
+    }
+}
+
+impl<T> AsRef<[T]> for PooledBuffer<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.buffer
+    }
+}
+
+impl<T> AsMut<[T]> for PooledBuffer<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T] {
+        self.buffer
+    }
+}
+
+impl<T> Drop for PooledBuffer<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // This never allocates, as the free list has room for all the pool's buffers.
+        self.pool.free.borrow_mut().push(self.index)
+    }
+}
+
+/// An error returned by [`BufferPool::try_checkout`] when all the buffers of the pool are
+/// already checked out.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BufferPoolExhausted;
+
+impl Display for BufferPoolExhausted {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("All buffers of the pool are already checked out")
+    }
+}
+
+impl Error for BufferPoolExhausted {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -745,7 +1025,7 @@ mod test {
         let raw_output_buffers = output_buffers.as_raw_buffers();
         let process = clap_process {
             audio_inputs: raw_input_buffers.as_ptr(),
-            audio_outputs: raw_output_buffers.as_ptr() as *mut _,
+            audio_outputs: raw_output_buffers.as_mut_ptr(),
             audio_inputs_count: raw_input_buffers.len() as u32,
             audio_outputs_count: raw_output_buffers.len() as u32,
 
@@ -777,4 +1057,87 @@ mod test {
             }
         }
     }
+
+    #[test]
+    pub fn buffer_pool_hands_out_distinct_buffers() {
+        let pool = BufferPool::<f32>::new(4, 8);
+        let mut ports = AudioPorts::with_capacity(2, 1);
+
+        let mut buffers: Vec<_> = (0..4).map(|_| pool.try_checkout().unwrap()).collect();
+        assert_eq!(pool.available(), 0);
+        assert_eq!(pool.high_water_mark(), 4);
+
+        for (i, buffer) in buffers.iter_mut().enumerate() {
+            assert_eq!(buffer.len(), 8);
+            assert!(buffer.iter().all(|s| *s == 0.0));
+            buffer.fill(i as f32);
+        }
+
+        for (i, buffer) in buffers.iter().enumerate() {
+            assert!(buffer.iter().all(|s| *s == i as f32));
+        }
+
+        let (inputs, outputs) = buffers.split_at_mut(2);
+        let inputs = ports.with_input_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f32_input_only(
+                inputs.iter_mut().map(InputChannel::variable),
+            ),
+        }]);
+        assert_eq!(inputs.frames_count(), Some(8));
+
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+        let outputs = output_ports.with_output_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f32_output_only(outputs.iter_mut().map(|b| &mut **b)),
+        }]);
+        assert_eq!(outputs.frames_count(), Some(8));
+    }
+
+    #[test]
+    pub fn buffer_pool_exhaustion_does_not_allocate() {
+        let pool = BufferPool::<f64>::new(2, 16);
+
+        let first = pool.try_checkout().unwrap();
+        let second = pool.try_checkout().unwrap();
+        assert_eq!(pool.try_checkout().err(), Some(BufferPoolExhausted));
+        assert_eq!(pool.free.borrow().capacity(), 2);
+
+        drop(first);
+        assert_eq!(pool.available(), 1);
+
+        let mut third = pool.try_checkout().unwrap();
+        third.fill(1.0);
+        third.clear();
+        assert!(third.iter().all(|s| *s == 0.0));
+
+        drop(second);
+        drop(third);
+        assert_eq!(pool.available(), 2);
+        assert_eq!(pool.free.borrow().capacity(), 2);
+        assert_eq!(pool.high_water_mark(), 2);
+
+        pool.reset_high_water_mark();
+        assert_eq!(pool.high_water_mark(), 0);
+    }
+
+    #[test]
+    pub fn buffer_pool_reclaims_leaked_buffers() {
+        let mut pool = BufferPool::<f32>::new(3, 4);
+
+        let kept = pool.try_checkout().unwrap();
+        core::mem::forget(pool.try_checkout().unwrap());
+        drop(kept);
+
+        assert_eq!(pool.checked_out(), 1);
+        assert_eq!(pool.reclaim_leaked(), 1);
+        assert_eq!(pool.available(), 3);
+        assert_eq!(pool.reclaim_leaked(), 0);
+
+        // All buffers can be checked out again, and are distinct.
+        let buffers: Vec<_> = (0..3).map(|_| pool.try_checkout().unwrap()).collect();
+        let mut pointers: Vec<_> = buffers.iter().map(|b| b.as_ptr()).collect();
+        pointers.dedup();
+        assert_eq!(pointers.len(), 3);
+    }
 }
@@ -0,0 +1,688 @@
+//! Recording of the parameter changes output by a plugin into automation curves.
+//!
+//! See the [`ParamAutomationRecorder`] type for more information.
+
+use clack_common::events::io::InputEvents;
+use clack_common::events::spaces::CoreEventSpace;
+use clack_common::utils::ClapId;
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The default maximum number of gestures a [`ParamAutomationRecorder`] can track at once.
+pub const DEFAULT_MAX_OPEN_GESTURES: usize = 32;
+
+/// A single point of an automation curve.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AutomationPoint {
+    /// The absolute position of the point, in samples.
+    pub position: u64,
+    /// The parameter's value at this point.
+    pub value: f64,
+}
+
+/// How an [`AutomationSegment`] was delimited.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SegmentEnd {
+    /// The plugin ended the gesture, with a gesture end event.
+    GestureEnd,
+    /// The plugin never ended the gesture, which was closed after no event was output for its
+    /// parameter during the recorder's gesture timeout.
+    ///
+    /// This is a plugin bug, which hosts may want to report.
+    TimedOut,
+    /// The plugin changed the parameter's value outside of any gesture.
+    ///
+    /// The segment holds that single value change, which should be written as-is.
+    Instant,
+}
+
+/// A completed write to a parameter's automation curve, as recorded by a
+/// [`ParamAutomationRecorder`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AutomationSegment {
+    /// The ID of the parameter that was written.
+    pub param_id: ClapId,
+    /// The absolute position the write started at, in samples.
+    pub start: u64,
+    /// The absolute position the write ended at, in samples.
+    pub end: u64,
+    /// The recorded points, in order. This may be empty if the gesture didn't change the value.
+    pub points: Vec<AutomationPoint>,
+    /// How this write was delimited.
+    pub end_reason: SegmentEnd,
+}
+
+/// A change recorded on the audio thread, sent to the main thread.
+#[derive(Copy, Clone, Debug)]
+enum AutomationRecord {
+    Begin {
+        param_id: ClapId,
+        position: u64,
+    },
+    Point {
+        param_id: ClapId,
+        point: AutomationPoint,
+    },
+    End {
+        param_id: ClapId,
+        position: u64,
+        reason: SegmentEnd,
+    },
+}
+
+/// A single-producer, single-consumer queue of records.
+struct RecordQueue {
+    records: Box<[UnsafeCell<MaybeUninit<AutomationRecord>>]>,
+    /// The total number of records the consumer has read. Only written by the consumer.
+    read: AtomicUsize,
+    /// The total number of records the producer has published. Only written by the producer.
+    write: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+// SAFETY: Access to each record is synchronized through the read and write indexes, so that only
+// one side can access a given record at any time. Records are plain data.
+unsafe impl Send for RecordQueue {}
+// SAFETY: See above.
+unsafe impl Sync for RecordQueue {}
+
+impl RecordQueue {
+    #[inline]
+    fn record(&self, index: usize) -> *mut MaybeUninit<AutomationRecord> {
+        self.records[index % self.records.len()].get()
+    }
+}
+
+/// A gesture currently in progress on the audio thread.
+#[derive(Copy, Clone)]
+struct OpenGesture {
+    param_id: ClapId,
+    /// The position of the last event output for this parameter.
+    last_activity: u64,
+    /// The last point that was sent.
+    last_sent: Option<AutomationPoint>,
+    /// The latest point, which will be sent unless it ends up on a straight line between
+    /// `last_sent` and the next point.
+    pending: Option<AutomationPoint>,
+}
+
+/// Records the parameter changes output by a plugin into automation curves, on the audio thread.
+///
+/// After each process call, the host passes the plugin's output events to
+/// [`record`](Self::record), along with the start position of the processed block. Parameter
+/// value changes are split per parameter, and grouped into [`AutomationSegment`]s, which are sent
+/// to the matching [`AutomationCollector`] through a lock-free queue, to be written into the
+/// host's automation curves on the main thread.
+///
+/// Segments are delimited as follows:
+///
+/// * Value changes between a gesture begin and a gesture end event form a single segment, even if
+///   the gesture spans many blocks. Points that lie on a straight line between their neighbors
+///   (within the recorder's [epsilon](Self::with_epsilon)) are thinned out.
+/// * If the plugin never ends a gesture, it is closed once no event was output for its parameter
+///   for the recorder's gesture timeout, and reported as [`SegmentEnd::TimedOut`]. The timeout
+///   is checked at the start of every recorded block.
+/// * Value changes outside of any gesture are each reported as a single-point
+///   [`SegmentEnd::Instant`] segment.
+///
+/// The recorder never allocates nor blocks. If the main thread doesn't collect the changes fast
+/// enough and the queue is full, or if too many gestures are in progress at once, changes are
+/// dropped instead, and counted (see [`AutomationCollector::dropped_count`]).
+///
+/// # Example
+///
+/// ```
+/// use clack_host::events::event_types::*;
+/// use clack_host::events::io::EventBuffer;
+/// use clack_host::events::Pckn;
+/// use clack_host::process::automation::{ParamAutomationRecorder, SegmentEnd};
+/// use clack_host::utils::{ClapId, Cookie};
+///
+/// let param_id = ClapId::new(1);
+/// let (mut recorder, mut collector) = ParamAutomationRecorder::new(256, 48_000);
+///
+/// // On the audio thread, after processing a block starting at sample 1024:
+/// let mut output_events = EventBuffer::new();
+/// output_events.push(&ParamGestureBeginEvent::new(0, param_id));
+/// output_events.push(&ParamValueEvent::new(4, param_id, Pckn::match_all(), 0.5, Cookie::empty()));
+/// output_events.push(&ParamGestureEndEvent::new(8, param_id));
+/// recorder.record(&output_events.as_input(), 1024);
+///
+/// // Later, on the main thread:
+/// collector.collect();
+/// let segments = collector.take_completed();
+/// assert_eq!(segments.len(), 1);
+/// assert_eq!(segments[0].points[0].position, 1028);
+/// assert_eq!(segments[0].end_reason, SegmentEnd::GestureEnd);
+/// ```
+pub struct ParamAutomationRecorder {
+    queue: Arc<RecordQueue>,
+    gestures: Vec<OpenGesture>,
+    max_open_gestures: usize,
+    gesture_timeout: u64,
+    epsilon: f64,
+}
+
+impl ParamAutomationRecorder {
+    /// Creates a new recorder, and its matching [`AutomationCollector`].
+    ///
+    /// The queue between them can hold up to `queue_capacity` changes. Gestures the plugin never
+    /// ends are closed after `gesture_timeout` samples without any event for their parameter.
+    ///
+    /// The recorder can track up to [`DEFAULT_MAX_OPEN_GESTURES`] gestures at once, and doesn't
+    /// thin out any point by default.
+    pub fn new(queue_capacity: usize, gesture_timeout: u64) -> (Self, AutomationCollector) {
+        let records = (0..queue_capacity.max(1))
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+
+        let queue = Arc::new(RecordQueue {
+            records,
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        });
+
+        (
+            Self {
+                queue: queue.clone(),
+                gestures: Vec::with_capacity(DEFAULT_MAX_OPEN_GESTURES),
+                max_open_gestures: DEFAULT_MAX_OPEN_GESTURES,
+                gesture_timeout,
+                epsilon: 0.0,
+            },
+            AutomationCollector {
+                queue,
+                open: HashMap::new(),
+                completed: Vec::new(),
+            },
+        )
+    }
+
+    /// Sets the maximum difference from a straight line a point can have to be thinned out.
+    ///
+    /// A point is thinned out if its value is within `epsilon` of the line between the previous
+    /// recorded point and the next one. With an `epsilon` of `0.0`, only points that are exactly
+    /// on that line, such as repeated values, are thinned out.
+    #[inline]
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon.max(0.0);
+        self
+    }
+
+    /// Sets the maximum number of gestures this recorder can track at once.
+    ///
+    /// This allocates, and should be called before the recorder is sent to the audio thread.
+    #[inline]
+    pub fn with_max_open_gestures(mut self, max_open_gestures: usize) -> Self {
+        self.max_open_gestures = max_open_gestures;
+        self.gestures
+            .reserve(max_open_gestures.saturating_sub(self.gestures.len()));
+        self
+    }
+
+    /// Returns the number of gestures currently in progress.
+    #[inline]
+    pub fn open_gesture_count(&self) -> usize {
+        self.gestures.len()
+    }
+
+    /// Records all the parameter changes in the given list.
+    ///
+    /// `block_start` is the absolute position of the first sample of the block these events
+    /// were output in, e.g. the `steady_time` passed to the process call. Before recording,
+    /// gestures that timed out by `block_start` are closed.
+    ///
+    /// All the recorded changes are published to the [`AutomationCollector`] before this method
+    /// returns.
+    ///
+    /// # Realtime Safety
+    ///
+    /// This method is realtime-safe: it never allocates, locks nor blocks.
+    pub fn record(&mut self, events: &InputEvents, block_start: u64) {
+        let mut write = self.queue.write.load(Ordering::Relaxed);
+
+        self.close_timed_out(block_start, &mut write);
+
+        for event in events {
+            let position = block_start + event.header().time() as u64;
+
+            match event.as_core_event() {
+                Some(CoreEventSpace::ParamGestureBegin(e)) => {
+                    if let Some(param_id) = e.param_id() {
+                        self.begin(param_id, position, &mut write);
+                    }
+                }
+                Some(CoreEventSpace::ParamValue(e)) => {
+                    if let Some(param_id) = e.param_id() {
+                        let point = AutomationPoint {
+                            position,
+                            value: e.value(),
+                        };
+                        self.point(param_id, point, &mut write);
+                    }
+                }
+                Some(CoreEventSpace::ParamGestureEnd(e)) => {
+                    if let Some(param_id) = e.param_id() {
+                        self.end(param_id, position, SegmentEnd::GestureEnd, &mut write);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.queue.write.store(write, Ordering::Release);
+    }
+
+    fn close_timed_out(&mut self, position: u64, write: &mut usize) {
+        let mut index = 0;
+        while let Some(gesture) = self.gestures.get(index) {
+            if position.saturating_sub(gesture.last_activity) >= self.gesture_timeout {
+                let gesture = self.gestures.swap_remove(index);
+                self.close(gesture, gesture.last_activity, SegmentEnd::TimedOut, write);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    fn begin(&mut self, param_id: ClapId, position: u64, write: &mut usize) {
+        if let Some(gesture) = self.gestures.iter_mut().find(|g| g.param_id == param_id) {
+            // The gesture is already in progress: this is just more activity.
+            gesture.last_activity = position;
+            return;
+        }
+
+        if self.gestures.len() >= self.max_open_gestures {
+            self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        self.gestures.push(OpenGesture {
+            param_id,
+            last_activity: position,
+            last_sent: None,
+            pending: None,
+        });
+
+        self.send(AutomationRecord::Begin { param_id, position }, write);
+    }
+
+    fn point(&mut self, param_id: ClapId, point: AutomationPoint, write: &mut usize) {
+        let Some(index) = self.gestures.iter().position(|g| g.param_id == param_id) else {
+            // Outside of any gesture, this is an instant write.
+            self.send(
+                AutomationRecord::Begin {
+                    param_id,
+                    position: point.position,
+                },
+                write,
+            );
+            self.send(AutomationRecord::Point { param_id, point }, write);
+            self.send(
+                AutomationRecord::End {
+                    param_id,
+                    position: point.position,
+                    reason: SegmentEnd::Instant,
+                },
+                write,
+            );
+            return;
+        };
+
+        let epsilon = self.epsilon;
+        let gesture = &mut self.gestures[index];
+        gesture.last_activity = point.position;
+
+        let Some(last_sent) = gesture.last_sent else {
+            gesture.last_sent = Some(point);
+            self.send(AutomationRecord::Point { param_id, point }, write);
+            return;
+        };
+
+        let to_send = match gesture.pending.replace(point) {
+            Some(pending) if !is_on_line(last_sent, pending, point, epsilon) => pending,
+            _ => return,
+        };
+
+        gesture.last_sent = Some(to_send);
+        self.send(
+            AutomationRecord::Point {
+                param_id,
+                point: to_send,
+            },
+            write,
+        );
+    }
+
+    fn end(&mut self, param_id: ClapId, position: u64, reason: SegmentEnd, write: &mut usize) {
+        // Gesture ends without a matching begin are ignored.
+        if let Some(index) = self.gestures.iter().position(|g| g.param_id == param_id) {
+            let gesture = self.gestures.swap_remove(index);
+            self.close(gesture, position, reason, write);
+        }
+    }
+
+    fn close(
+        &mut self,
+        gesture: OpenGesture,
+        position: u64,
+        reason: SegmentEnd,
+        write: &mut usize,
+    ) {
+        let param_id = gesture.param_id;
+
+        if let Some(point) = gesture.pending {
+            self.send(AutomationRecord::Point { param_id, point }, write);
+        }
+
+        self.send(
+            AutomationRecord::End {
+                param_id,
+                position,
+                reason,
+            },
+            write,
+        );
+    }
+
+    fn send(&self, record: AutomationRecord, write: &mut usize) {
+        let queue = &*self.queue;
+
+        if *write - queue.read.load(Ordering::Acquire) >= queue.records.len() {
+            queue.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        // SAFETY: the record at `write` is not published yet, so only we can access it.
+        unsafe { queue.record(*write).write(MaybeUninit::new(record)) };
+        *write += 1;
+    }
+}
+
+/// Returns `true` if `point` is within `epsilon` of the line between `start` and `end`.
+fn is_on_line(
+    start: AutomationPoint,
+    point: AutomationPoint,
+    end: AutomationPoint,
+    epsilon: f64,
+) -> bool {
+    let expected = if end.position <= start.position {
+        end.value
+    } else {
+        let t = (point.position - start.position) as f64 / (end.position - start.position) as f64;
+        start.value + (end.value - start.value) * t
+    };
+
+    (point.value - expected).abs() <= epsilon
+}
+
+/// The main-thread side of a [`ParamAutomationRecorder`], which assembles its recorded changes
+/// into [`AutomationSegment`]s.
+pub struct AutomationCollector {
+    queue: Arc<RecordQueue>,
+    open: HashMap<ClapId, AutomationSegment>,
+    completed: Vec<AutomationSegment>,
+}
+
+impl AutomationCollector {
+    /// Collects all the changes published by the recorder so far.
+    ///
+    /// Returns the number of segments that were completed.
+    pub fn collect(&mut self) -> usize {
+        let queue = Arc::clone(&self.queue);
+        let read = queue.read.load(Ordering::Relaxed);
+        let write = queue.write.load(Ordering::Acquire);
+        let previous_len = self.completed.len();
+
+        for index in read..write {
+            // SAFETY: the record at `index` is published, so only we can access it until we
+            // update the read index.
+            let record = unsafe { (*queue.record(index)).assume_init() };
+            self.apply(record);
+        }
+
+        queue.read.store(write, Ordering::Release);
+        self.completed.len() - previous_len
+    }
+
+    fn apply(&mut self, record: AutomationRecord) {
+        let new_segment = |param_id, position| AutomationSegment {
+            param_id,
+            start: position,
+            end: position,
+            points: Vec::new(),
+            end_reason: SegmentEnd::GestureEnd,
+        };
+
+        match record {
+            AutomationRecord::Begin { param_id, position } => {
+                self.open.insert(param_id, new_segment(param_id, position));
+            }
+            AutomationRecord::Point { param_id, point } => {
+                // If the matching begin was dropped, the segment starts at its first point.
+                let segment = self
+                    .open
+                    .entry(param_id)
+                    .or_insert_with(|| new_segment(param_id, point.position));
+
+                segment.end = point.position;
+                segment.points.push(point);
+            }
+            AutomationRecord::End {
+                param_id,
+                position,
+                reason,
+            } => {
+                if let Some(mut segment) = self.open.remove(&param_id) {
+                    segment.end = segment.end.max(position);
+                    segment.end_reason = reason;
+                    self.completed.push(segment);
+                }
+            }
+        }
+    }
+
+    /// Returns the segments that were completed and not taken yet, in the order they were
+    /// completed.
+    #[inline]
+    pub fn completed(&self) -> &[AutomationSegment] {
+        &self.completed
+    }
+
+    /// Removes and returns all the completed segments.
+    #[inline]
+    pub fn take_completed(&mut self) -> Vec<AutomationSegment> {
+        core::mem::take(&mut self.completed)
+    }
+
+    /// Returns `true` if a write to the given parameter is still in progress.
+    #[inline]
+    pub fn is_recording(&self, param_id: ClapId) -> bool {
+        self.open.contains_key(&param_id)
+    }
+
+    /// Returns the total number of changes the recorder had to drop, either because its queue
+    /// was full, or because too many gestures were in progress.
+    #[inline]
+    pub fn dropped_count(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::events::event_types::*;
+    use clack_common::events::io::EventBuffer;
+    use clack_common::events::Pckn;
+    use clack_common::utils::Cookie;
+
+    const GAIN: ClapId = ClapId::new(1);
+    const PAN: ClapId = ClapId::new(2);
+
+    fn value(time: u32, param_id: ClapId, value: f64) -> ParamValueEvent {
+        ParamValueEvent::new(time, param_id, Pckn::match_all(), value, Cookie::empty())
+    }
+
+    fn point(position: u64, value: f64) -> AutomationPoint {
+        AutomationPoint { position, value }
+    }
+
+    #[test]
+    pub fn records_gestures_spanning_many_blocks() {
+        let (mut recorder, mut collector) = ParamAutomationRecorder::new(64, 48_000);
+
+        let mut buffer = EventBuffer::new();
+        buffer.push(&ParamGestureBeginEvent::new(10, GAIN));
+        buffer.push(&value(10, GAIN, 0.1));
+        recorder.record(&buffer.as_input(), 0);
+
+        for (block, gain) in [(1, 0.8), (2, 0.2), (3, 0.9)] {
+            buffer.clear();
+            buffer.push(&value(0, GAIN, gain));
+            recorder.record(&buffer.as_input(), block * 100);
+        }
+
+        assert_eq!(collector.collect(), 0);
+        assert!(collector.is_recording(GAIN));
+
+        buffer.clear();
+        buffer.push(&ParamGestureEndEvent::new(5, GAIN));
+        recorder.record(&buffer.as_input(), 400);
+
+        assert_eq!(collector.collect(), 1);
+        assert!(!collector.is_recording(GAIN));
+
+        let segments = collector.take_completed();
+        assert_eq!(
+            segments,
+            [AutomationSegment {
+                param_id: GAIN,
+                start: 10,
+                end: 405,
+                points: vec![
+                    point(10, 0.1),
+                    point(100, 0.8),
+                    point(200, 0.2),
+                    point(300, 0.9)
+                ],
+                end_reason: SegmentEnd::GestureEnd,
+            }]
+        );
+        assert!(collector.completed().is_empty());
+    }
+
+    #[test]
+    pub fn thins_points_on_straight_lines() {
+        let (recorder, mut collector) = ParamAutomationRecorder::new(64, 48_000);
+        let mut recorder = recorder.with_epsilon(0.01);
+
+        let mut buffer = EventBuffer::new();
+        buffer.push(&ParamGestureBeginEvent::new(0, GAIN));
+        for i in 0..=10 {
+            buffer.push(&value(i * 10, GAIN, i as f64 / 10.0));
+        }
+        // A plateau after the ramp.
+        buffer.push(&value(105, GAIN, 1.0));
+        buffer.push(&value(110, GAIN, 1.0));
+        buffer.push(&value(120, GAIN, 1.0));
+        buffer.push(&ParamGestureEndEvent::new(120, GAIN));
+        recorder.record(&buffer.as_input(), 0);
+
+        collector.collect();
+        let segments = collector.take_completed();
+
+        assert_eq!(
+            segments[0].points,
+            [point(0, 0.0), point(100, 1.0), point(120, 1.0)]
+        );
+    }
+
+    #[test]
+    pub fn closes_gestures_without_end_on_timeout() {
+        let (mut recorder, mut collector) = ParamAutomationRecorder::new(64, 1000);
+
+        let mut buffer = EventBuffer::new();
+        buffer.push(&ParamGestureBeginEvent::new(0, GAIN));
+        buffer.push(&value(20, GAIN, 0.5));
+        recorder.record(&buffer.as_input(), 0);
+
+        // Not timed out yet.
+        recorder.record(&EventBuffer::new().as_input(), 1000);
+        assert_eq!(collector.collect(), 0);
+        assert_eq!(recorder.open_gesture_count(), 1);
+
+        recorder.record(&EventBuffer::new().as_input(), 1020);
+        assert_eq!(collector.collect(), 1);
+        assert_eq!(recorder.open_gesture_count(), 0);
+
+        let segment = &collector.completed()[0];
+        assert_eq!(segment.end_reason, SegmentEnd::TimedOut);
+        assert_eq!(segment.start, 0);
+        assert_eq!(segment.end, 20);
+        assert_eq!(segment.points, [point(20, 0.5)]);
+    }
+
+    #[test]
+    pub fn records_changes_without_gesture_as_instant_writes() {
+        let (mut recorder, mut collector) = ParamAutomationRecorder::new(64, 48_000);
+
+        let mut buffer = EventBuffer::new();
+        buffer.push(&value(3, PAN, 0.25));
+        buffer.push(&value(7, PAN, 0.75));
+        // Gesture ends without a matching begin are ignored.
+        buffer.push(&ParamGestureEndEvent::new(8, PAN));
+        recorder.record(&buffer.as_input(), 100);
+
+        assert_eq!(collector.collect(), 2);
+
+        let segments = collector.take_completed();
+        assert_eq!(segments[0].points, [point(103, 0.25)]);
+        assert_eq!(segments[0].end_reason, SegmentEnd::Instant);
+        assert_eq!((segments[1].start, segments[1].end), (107, 107));
+        assert_eq!(segments[1].end_reason, SegmentEnd::Instant);
+    }
+
+    #[test]
+    pub fn separates_interleaved_params() {
+        let (mut recorder, mut collector) = ParamAutomationRecorder::new(64, 48_000);
+
+        let mut buffer = EventBuffer::new();
+        buffer.push(&ParamGestureBeginEvent::new(0, GAIN));
+        buffer.push(&ParamGestureBeginEvent::new(0, PAN));
+        buffer.push(&value(1, GAIN, 0.1));
+        buffer.push(&value(2, PAN, 0.2));
+        buffer.push(&value(3, GAIN, 0.3));
+        buffer.push(&ParamGestureEndEvent::new(4, PAN));
+        buffer.push(&ParamGestureEndEvent::new(5, GAIN));
+        recorder.record(&buffer.as_input(), 0);
+
+        collector.collect();
+        let segments = collector.take_completed();
+
+        assert_eq!(segments[0].param_id, PAN);
+        assert_eq!(segments[0].points, [point(2, 0.2)]);
+        assert_eq!(segments[1].param_id, GAIN);
+        assert_eq!(segments[1].points, [point(1, 0.1), point(3, 0.3)]);
+    }
+
+    #[test]
+    pub fn drops_gestures_beyond_capacity() {
+        let (recorder, mut collector) = ParamAutomationRecorder::new(64, 48_000);
+        let mut recorder = recorder.with_max_open_gestures(1);
+
+        let mut buffer = EventBuffer::new();
+        buffer.push(&ParamGestureBeginEvent::new(0, GAIN));
+        buffer.push(&ParamGestureBeginEvent::new(0, PAN));
+        recorder.record(&buffer.as_input(), 0);
+
+        collector.collect();
+        assert_eq!(collector.dropped_count(), 1);
+        assert!(collector.is_recording(GAIN));
+        assert!(!collector.is_recording(PAN));
+    }
+}
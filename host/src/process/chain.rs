@@ -0,0 +1,705 @@
+//! A minimal processing graph primitive, which runs multiple plugins in series.
+//!
+//! See the [`StoppedPluginChain`] and [`StartedPluginChain`] types for more information.
+
+use crate::host::HostHandlers;
+use crate::plugin::PluginInstanceError;
+use crate::process::audio_buffers::{
+    AudioPortBuffer, AudioPortBufferType, AudioPorts, InputAudioBuffers, InputChannel,
+};
+use crate::process::scheduler::{is_silent, ProcessingScheduler, ScheduleDecision};
+use crate::process::{
+    PluginAudioProcessor, ProcessStatus, StartedPluginAudioProcessor, StoppedPluginAudioProcessor,
+};
+use clack_common::events::event_types::TransportEvent;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use clap_sys::audio_buffer::clap_audio_buffer;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Information about a plugin in a chain, which the chain cannot query by itself.
+///
+/// This information should be retrieved by the host from the plugin's extensions before the plugin
+/// is added to the chain.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ChainedPluginInfo {
+    /// Whether the plugin's main input and output ports can be processed in-place, i.e. using the
+    /// same buffers for both.
+    ///
+    /// This is the case if the main output port reports the main input port as its in-place pair
+    /// in the `audio-ports` extension.
+    pub in_place: bool,
+    /// The plugin's latency, in samples, as reported by the `latency` extension.
+    pub latency: u32,
+    /// The plugin's raw tail length, in samples, as reported by the `tail` extension.
+    ///
+    /// Values of [`ProcessingScheduler::INFINITE_TAIL`] or more mean the tail is infinite. This
+    /// is only used by [automatic scheduling](StoppedPluginChain::set_scheduling), and should be
+    /// `0` if the plugin doesn't implement the `tail` extension.
+    pub tail: u32,
+}
+
+struct ChainSlot<H: HostHandlers> {
+    processor: PluginAudioProcessor<H>,
+    info: ChainedPluginInfo,
+    scheduler: ProcessingScheduler,
+}
+
+struct ChainInner<H: HostHandlers> {
+    slots: Vec<ChainSlot<H>>,
+    scheduling: bool,
+    channel_count: usize,
+    max_frames_count: u32,
+    scratch: [Vec<Vec<f32>>; 2],
+    input_ports: AudioPorts,
+    output_ports: AudioPorts,
+}
+
+impl<H: HostHandlers> ChainInner<H> {
+    fn total_latency(&self) -> u32 {
+        self.slots
+            .iter()
+            .fold(0u32, |total, slot| total.saturating_add(slot.info.latency))
+    }
+}
+
+/// A chain of plugins processing audio in series, in its `stopped` state.
+///
+/// The audio output of each plugin in the chain is fed to the audio input of the next, and the
+/// audio output of the last plugin is the output of the chain. Only the main port of each plugin
+/// is used, and all plugins must accept the same number of 32-bit float channels.
+///
+/// Plugins can only be added to or removed from a chain in this state, which requires their audio
+/// processors to be stopped as well. The chain can then be started using
+/// [`start_processing`](Self::start_processing), which returns a [`StartedPluginChain`] that can
+/// process audio.
+///
+/// All intermediate buffers are allocated when the chain is created, which should be done after
+/// all the plugins were activated with a configuration using the same maximum frame count.
+pub struct StoppedPluginChain<H: HostHandlers> {
+    inner: Box<ChainInner<H>>,
+}
+
+impl<H: HostHandlers> StoppedPluginChain<H> {
+    /// Creates a new, empty plugin chain, processing the given number of channels.
+    ///
+    /// This allocates intermediate buffers for blocks of up to `max_frames_count` frames, which
+    /// must match the maximum frame count the chained plugins have been activated with.
+    pub fn new(channel_count: usize, max_frames_count: u32) -> Self {
+        let scratch = || vec![vec![0.0; max_frames_count as usize]; channel_count];
+
+        Self {
+            inner: Box::new(ChainInner {
+                slots: Vec::new(),
+                scheduling: false,
+                channel_count,
+                max_frames_count,
+                scratch: [scratch(), scratch()],
+                input_ports: AudioPorts::with_capacity(channel_count, 1),
+                output_ports: AudioPorts::with_capacity(channel_count, 1),
+            }),
+        }
+    }
+
+    /// Returns the number of plugins in this chain.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.slots.len()
+    }
+
+    /// Returns `true` if this chain contains no plugins.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.slots.is_empty()
+    }
+
+    /// Returns the number of channels this chain processes.
+    #[inline]
+    pub fn channel_count(&self) -> usize {
+        self.inner.channel_count
+    }
+
+    /// Returns the maximum number of frames this chain can process at once.
+    #[inline]
+    pub fn max_frames_count(&self) -> u32 {
+        self.inner.max_frames_count
+    }
+
+    /// Returns the total latency of this chain, in samples.
+    ///
+    /// This is the sum of the latencies of all the plugins in the chain.
+    #[inline]
+    pub fn total_latency(&self) -> u32 {
+        self.inner.total_latency()
+    }
+
+    /// Returns the information about the plugin at the given index, or `None` if the index is out
+    /// of bounds.
+    #[inline]
+    pub fn plugin_info(&self, index: usize) -> Option<ChainedPluginInfo> {
+        self.inner.slots.get(index).map(|slot| slot.info)
+    }
+
+    /// Updates the information about the plugin at the given index, e.g. after its latency has
+    /// changed.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is out of bounds.
+    #[inline]
+    pub fn set_plugin_info(&mut self, index: usize, info: ChainedPluginInfo) {
+        let slot = &mut self.inner.slots[index];
+        slot.info = info;
+        slot.scheduler.set_tail(info.tail);
+        slot.scheduler.set_latency(info.latency);
+    }
+
+    /// Enables or disables automatic scheduling of the plugins in this chain.
+    ///
+    /// When enabled, a [`ProcessingScheduler`] tracks each plugin, and plugins that went to sleep
+    /// are skipped instead of being processed, with silence being passed to the next plugin.
+    /// Sleeping plugins are woken up as soon as they receive input events, or as soon as their
+    /// input isn't silent anymore. See the [`ProcessingScheduler`] documentation for when exactly
+    /// plugins are put to sleep, depending on the statuses they return.
+    ///
+    /// This requires the [`tail`](ChainedPluginInfo::tail) of each plugin to be known. It also
+    /// adds a silence check of every intermediate buffer to each block.
+    ///
+    /// This is disabled by default, in which case all plugins are processed for every block.
+    #[inline]
+    pub fn set_scheduling(&mut self, enabled: bool) {
+        self.inner.scheduling = enabled;
+    }
+
+    /// Returns `true` if automatic scheduling is enabled. See
+    /// [`set_scheduling`](Self::set_scheduling).
+    #[inline]
+    pub fn is_scheduling(&self) -> bool {
+        self.inner.scheduling
+    }
+
+    /// Adds a plugin at the end of this chain.
+    #[inline]
+    pub fn push(&mut self, processor: StoppedPluginAudioProcessor<H>, info: ChainedPluginInfo) {
+        self.insert(self.len(), processor, info)
+    }
+
+    /// Inserts a plugin at the given position in this chain, shifting all plugins after it.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is greater than the chain's length.
+    pub fn insert(
+        &mut self,
+        index: usize,
+        processor: StoppedPluginAudioProcessor<H>,
+        info: ChainedPluginInfo,
+    ) {
+        self.inner.slots.insert(
+            index,
+            ChainSlot {
+                processor: processor.into(),
+                info,
+                scheduler: ProcessingScheduler::new(info.tail, info.latency),
+            },
+        )
+    }
+
+    /// Removes the plugin at the given position from this chain, and returns its audio processor.
+    ///
+    /// Returns `None` if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<StoppedPluginAudioProcessor<H>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        Some(self.inner.slots.remove(index).processor.into_stopped())
+    }
+
+    /// Removes all plugins from this chain, and returns their audio processors in order.
+    pub fn into_processors(self) -> Vec<StoppedPluginAudioProcessor<H>> {
+        self.inner
+            .slots
+            .into_iter()
+            .map(|slot| slot.processor.into_stopped())
+            .collect()
+    }
+
+    /// Starts processing on all plugins of this chain, and returns the chain in its
+    /// [started](StartedPluginChain) state.
+    ///
+    /// # Errors
+    ///
+    /// If any plugin fails to start processing, all the plugins that were already started are
+    /// stopped again, and a [`PluginChainStartError`] is returned, from which the stopped chain
+    /// can be recovered.
+    pub fn start_processing(mut self) -> Result<StartedPluginChain<H>, PluginChainStartError<H>> {
+        for index in 0..self.inner.slots.len() {
+            if self.inner.slots[index]
+                .processor
+                .start_processing()
+                .is_err()
+            {
+                for slot in &mut self.inner.slots[..index] {
+                    slot.processor.ensure_processing_stopped();
+                }
+
+                return Err(PluginChainStartError { chain: self, index });
+            }
+        }
+
+        for slot in &mut self.inner.slots {
+            slot.scheduler.wake();
+        }
+
+        Ok(StartedPluginChain { inner: self.inner })
+    }
+}
+
+/// A chain of plugins processing audio in series, in its `started` state.
+///
+/// See the [`StoppedPluginChain`] documentation for more information.
+pub struct StartedPluginChain<H: HostHandlers> {
+    inner: Box<ChainInner<H>>,
+}
+
+/// The location of the audio data currently being passed along the chain.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Location {
+    Input,
+    Scratch(usize),
+    Output,
+}
+
+impl<H: HostHandlers> StartedPluginChain<H> {
+    /// Returns the number of plugins in this chain.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.slots.len()
+    }
+
+    /// Returns `true` if this chain contains no plugins.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.slots.is_empty()
+    }
+
+    /// Returns the total latency of this chain, in samples.
+    ///
+    /// This is the sum of the latencies of all the plugins in the chain.
+    #[inline]
+    pub fn total_latency(&self) -> u32 {
+        self.inner.total_latency()
+    }
+
+    /// Returns the scheduler tracking the plugin at the given index, or `None` if the index is
+    /// out of bounds.
+    ///
+    /// This can be used to check whether the plugin is currently asleep. Schedulers are only
+    /// updated if [automatic scheduling](StoppedPluginChain::set_scheduling) is enabled.
+    #[inline]
+    pub fn scheduler(&self, index: usize) -> Option<&ProcessingScheduler> {
+        self.inner.slots.get(index).map(|slot| &slot.scheduler)
+    }
+
+    /// Processes a block of audio through all the plugins in this chain, in order.
+    ///
+    /// The `input` and `output` slices must both contain one buffer per channel of the chain. The
+    /// number of frames processed is the length of the shortest buffer, which must not be
+    /// greater than the chain's maximum frame count.
+    ///
+    /// Each element of `events_per_plugin` contains the input and output event lists for the
+    /// plugin at the same position in the chain. If there are fewer elements than plugins, the
+    /// remaining plugins receive no events, and the events they output are discarded.
+    ///
+    /// Audio is passed between plugins by alternating between the chain's intermediate buffers,
+    /// without copies. Plugins that support in-place processing are given the same buffer for
+    /// their input and output, in which case audio is only copied if such a plugin is the first or
+    /// the last of the chain.
+    ///
+    /// The returned [`ProcessStatus`] is the combination of the statuses returned by all plugins
+    /// (see [`ProcessStatus::combined_with`]), i.e. the chain can only sleep if all of its
+    /// plugins can.
+    ///
+    /// If [automatic scheduling](StoppedPluginChain::set_scheduling) is enabled, sleeping plugins
+    /// are skipped and count as [`ProcessStatus::Sleep`].
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`PluginChainError`] if the given buffers do not match the chain's
+    /// configuration, or if any plugin fails to process. In the latter case, the plugins after
+    /// the failing one are not processed.
+    pub fn process_chain<I: AsMut<[f32]>, O: AsMut<[f32]>>(
+        &mut self,
+        input: &mut [I],
+        output: &mut [O],
+        events_per_plugin: &mut [(&InputEvents<'_>, &mut OutputEvents<'_>)],
+        transport: Option<&TransportEvent>,
+        steady_time: Option<u64>,
+    ) -> Result<ProcessStatus, PluginChainError> {
+        let inner = &mut self.inner;
+
+        if input.len() != inner.channel_count || output.len() != inner.channel_count {
+            return Err(PluginChainError::ChannelCountMismatch);
+        }
+
+        let frames_count = input
+            .iter_mut()
+            .map(|c| c.as_mut().len())
+            .chain(output.iter_mut().map(|c| c.as_mut().len()))
+            .min()
+            .unwrap_or(0);
+
+        if frames_count > inner.max_frames_count as usize {
+            return Err(PluginChainError::TooManyFrames);
+        }
+
+        let [scratch_a, scratch_b] = &mut inner.scratch;
+        let mut buffers = ChainBuffers {
+            input,
+            output,
+            scratch: [scratch_a, scratch_b],
+            frames_count,
+        };
+
+        let no_input_events = InputEvents::empty();
+        let mut status = ProcessStatus::Sleep;
+        let mut current = Location::Input;
+        let last_index = inner.slots.len().saturating_sub(1);
+        let scheduling = inner.scheduling;
+        let mut silent = scheduling && buffers.is_silent(current);
+
+        for (index, slot) in inner.slots.iter_mut().enumerate() {
+            let is_last = index == last_index;
+
+            let processor = slot
+                .processor
+                .as_started_mut()
+                .map_err(|error| PluginChainError::Plugin { index, error })?;
+
+            let mut no_output_events = OutputEvents::void();
+            let (input_events, output_events) = match events_per_plugin.get_mut(index) {
+                Some((input_events, output_events)) => (*input_events, &mut **output_events),
+                None => (&no_input_events, &mut no_output_events),
+            };
+
+            if scheduling {
+                let decision =
+                    slot.scheduler
+                        .decide(frames_count as u32, !input_events.is_empty(), silent);
+
+                if decision == ScheduleDecision::Skip {
+                    let target = match current {
+                        _ if is_last => Location::Output,
+                        Location::Input => Location::Scratch(0),
+                        other => other,
+                    };
+
+                    buffers.fill_silence(target);
+                    current = target;
+                    continue;
+                }
+            }
+
+            let mut stage = Stage {
+                processor,
+                input_ports: &mut inner.input_ports,
+                output_ports: &mut inner.output_ports,
+                input_events,
+                output_events,
+                transport,
+                steady_time,
+            };
+
+            let result = if slot.info.in_place {
+                if current == Location::Input {
+                    let target = if is_last {
+                        Location::Output
+                    } else {
+                        Location::Scratch(0)
+                    };
+
+                    buffers.copy(current, target);
+                    current = target;
+                }
+
+                buffers.with_single(current, |buffers| stage.process_in_place(buffers))
+            } else {
+                let target = match current {
+                    _ if is_last => Location::Output,
+                    Location::Scratch(i) => Location::Scratch(1 - i),
+                    Location::Input | Location::Output => Location::Scratch(0),
+                };
+
+                let result = buffers.with_pair(current, target, |inputs, outputs| {
+                    stage.process(inputs, outputs)
+                });
+
+                current = target;
+                result
+            };
+
+            let plugin_status =
+                result.map_err(|error| PluginChainError::Plugin { index, error })?;
+            status = status.combined_with(plugin_status);
+
+            if scheduling {
+                silent = buffers.is_silent(current);
+                slot.scheduler.record(plugin_status, silent);
+            }
+        }
+
+        if current != Location::Output {
+            buffers.copy(current, Location::Output);
+        }
+
+        Ok(status)
+    }
+
+    /// Stops processing on all plugins of this chain, and returns the chain in its
+    /// [stopped](StoppedPluginChain) state.
+    ///
+    /// This operation is infallible.
+    pub fn stop_processing(mut self) -> StoppedPluginChain<H> {
+        for slot in &mut self.inner.slots {
+            slot.processor.ensure_processing_stopped();
+        }
+
+        StoppedPluginChain { inner: self.inner }
+    }
+}
+
+/// A set of channel buffers, as exposed to a single plugin of the chain.
+type Channels<'a, 'b> = &'a mut dyn Iterator<Item = &'b mut [f32]>;
+
+/// Everything a single plugin of the chain needs to process, apart from the audio buffers.
+struct Stage<'a, 'i, 'o, H: HostHandlers> {
+    processor: &'a mut StartedPluginAudioProcessor<H>,
+    input_ports: &'a mut AudioPorts,
+    output_ports: &'a mut AudioPorts,
+    input_events: &'a InputEvents<'i>,
+    output_events: &'a mut OutputEvents<'o>,
+    transport: Option<&'a TransportEvent>,
+    steady_time: Option<u64>,
+}
+
+impl<H: HostHandlers> Stage<'_, '_, '_, H> {
+    fn process(
+        &mut self,
+        inputs: Channels,
+        outputs: Channels,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        let inputs = self.input_ports.with_input_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_input_only(
+                inputs.map(|c| InputChannel::variable(&mut *c)),
+            ),
+            latency: 0,
+        }]);
+
+        let mut outputs = self.output_ports.with_output_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_output_only(outputs.map(|c| &mut *c)),
+            latency: 0,
+        }]);
+
+        self.processor.process(
+            &inputs,
+            &mut outputs,
+            self.input_events,
+            self.output_events,
+            self.steady_time,
+            self.transport,
+        )
+    }
+
+    fn process_in_place(
+        &mut self,
+        buffers: Channels,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        let mut outputs = self.output_ports.with_output_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_output_only(buffers.map(|c| &mut *c)),
+            latency: 0,
+        }]);
+
+        let frames_count = outputs.frames_count().unwrap_or(0);
+        let raw_inputs: [clap_audio_buffer; 1] = [outputs.as_raw_buffers()[0]];
+
+        // SAFETY: the input buffers point to the same channel buffers as the output buffers,
+        // which are valid for the duration of the process call, and hold at least frames_count
+        // samples.
+        let inputs = unsafe { InputAudioBuffers::from_raw_buffers(&raw_inputs, frames_count) };
+
+        self.processor.process(
+            &inputs,
+            &mut outputs,
+            self.input_events,
+            self.output_events,
+            self.steady_time,
+            self.transport,
+        )
+    }
+}
+
+/// All the buffers audio can be passed through during a single [`StartedPluginChain::process_chain`]
+/// call.
+struct ChainBuffers<'a, I, O> {
+    input: &'a mut [I],
+    output: &'a mut [O],
+    scratch: [&'a mut Vec<Vec<f32>>; 2],
+    frames_count: usize,
+}
+
+impl<I: AsMut<[f32]>, O: AsMut<[f32]>> ChainBuffers<'_, I, O> {
+    fn is_silent(&mut self, location: Location) -> bool {
+        self.with_single(location, |channels| is_silent(channels.map(|c| &*c)))
+    }
+
+    fn fill_silence(&mut self, location: Location) {
+        self.with_single(location, |channels| {
+            for channel in channels {
+                channel.fill(0.0);
+            }
+        })
+    }
+
+    fn copy(&mut self, from: Location, to: Location) {
+        self.with_pair(from, to, |sources, destinations| {
+            for (source, destination) in sources.zip(destinations) {
+                destination.copy_from_slice(source);
+            }
+        })
+    }
+
+    fn with_single<R>(&mut self, location: Location, f: impl FnOnce(Channels) -> R) -> R {
+        let frames_count = self.frames_count;
+
+        match location {
+            Location::Input => f(&mut self
+                .input
+                .iter_mut()
+                .map(|c| &mut c.as_mut()[..frames_count])),
+            Location::Scratch(i) => {
+                f(&mut self.scratch[i].iter_mut().map(|c| &mut c[..frames_count]))
+            }
+            Location::Output => f(&mut self
+                .output
+                .iter_mut()
+                .map(|c| &mut c.as_mut()[..frames_count])),
+        }
+    }
+
+    /// Calls the given closure with the channels of two different locations.
+    ///
+    /// # Panics
+    ///
+    /// This panics if both locations are the same, or if the destination is the chain's input.
+    fn with_pair<R>(
+        &mut self,
+        from: Location,
+        to: Location,
+        f: impl FnOnce(Channels, Channels) -> R,
+    ) -> R {
+        let frames_count = self.frames_count;
+        let [scratch_a, scratch_b] = &mut self.scratch;
+
+        let mut input = self
+            .input
+            .iter_mut()
+            .map(|c| &mut c.as_mut()[..frames_count]);
+        let mut output = self
+            .output
+            .iter_mut()
+            .map(|c| &mut c.as_mut()[..frames_count]);
+        let mut scratch_a = scratch_a.iter_mut().map(|c| &mut c[..frames_count]);
+        let mut scratch_b = scratch_b.iter_mut().map(|c| &mut c[..frames_count]);
+
+        match (from, to) {
+            (Location::Input, Location::Scratch(0)) => f(&mut input, &mut scratch_a),
+            (Location::Input, Location::Scratch(_)) => f(&mut input, &mut scratch_b),
+            (Location::Input, Location::Output) => f(&mut input, &mut output),
+            (Location::Scratch(0), Location::Scratch(1)) => f(&mut scratch_a, &mut scratch_b),
+            (Location::Scratch(1), Location::Scratch(0)) => f(&mut scratch_b, &mut scratch_a),
+            (Location::Scratch(0), Location::Output) => f(&mut scratch_a, &mut output),
+            (Location::Scratch(_), Location::Output) => f(&mut scratch_b, &mut output),
+            (Location::Output, Location::Scratch(0)) => f(&mut output, &mut scratch_a),
+            (Location::Output, Location::Scratch(_)) => f(&mut output, &mut scratch_b),
+            _ => unreachable!("Invalid audio routing in plugin chain"),
+        }
+    }
+}
+
+/// Errors that can occur while processing a [`StartedPluginChain`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PluginChainError {
+    /// The number of given input or output buffers does not match the chain's channel count.
+    ChannelCountMismatch,
+    /// The given buffers are larger than the chain's maximum frame count.
+    TooManyFrames,
+    /// A plugin of the chain failed to process.
+    Plugin {
+        /// The index of the plugin in the chain.
+        index: usize,
+        /// The error returned by the plugin.
+        error: PluginInstanceError,
+    },
+}
+
+impl Display for PluginChainError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChannelCountMismatch => {
+                f.write_str("Buffer channel count does not match the plugin chain's")
+            }
+            Self::TooManyFrames => {
+                f.write_str("Buffers exceed the plugin chain's maximum frame count")
+            }
+            Self::Plugin { index, error } => write!(f, "Plugin #{index} of the chain: {error}"),
+        }
+    }
+}
+
+impl Error for PluginChainError {}
+
+/// An error that occurred when a plugin chain couldn't start processing.
+///
+/// The [`StoppedPluginChain`] can be recovered using the
+/// [`into_stopped_chain`](Self::into_stopped_chain) method.
+pub struct PluginChainStartError<H: HostHandlers> {
+    chain: StoppedPluginChain<H>,
+    index: usize,
+}
+
+impl<H: HostHandlers> PluginChainStartError<H> {
+    /// Returns the index of the plugin that failed to start processing.
+    #[inline]
+    pub fn failed_plugin_index(&self) -> usize {
+        self.index
+    }
+
+    /// Recovers the [`StoppedPluginChain`] that failed to start.
+    #[inline]
+    pub fn into_stopped_chain(self) -> StoppedPluginChain<H> {
+        self.chain
+    }
+}
+
+impl<H: HostHandlers> Debug for PluginChainStartError<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to start processing of plugin #{} in chain",
+            self.index
+        )
+    }
+}
+
+impl<H: HostHandlers> Display for PluginChainStartError<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to start processing of plugin #{} in chain",
+            self.index
+        )
+    }
+}
+
+impl<H: HostHandlers> Error for PluginChainStartError<H> {}
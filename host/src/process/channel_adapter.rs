@@ -0,0 +1,692 @@
+//! Up- and down-mixing between a host track's channel layout and a plugin's main port layout.
+//!
+//! See the [`ChannelAdapter`] type for more information.
+
+use crate::host::HostHandlers;
+use crate::plugin::PluginInstanceError;
+use crate::process::audio_buffers::{
+    AudioPortBuffer, AudioPortBufferType, AudioPorts, InputChannel,
+};
+use crate::process::{ProcessStatus, StartedPluginAudioProcessor};
+use clack_common::events::event_types::TransportEvent;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use std::error::Error;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+
+/// The level at which the center and surround channels are folded down into stereo, as per
+/// ITU-R BS.775 (-3dB).
+const ITU_FOLD_DOWN: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// The channel layout of a host track or of a plugin audio port.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ChannelLayout {
+    /// A single channel.
+    Mono,
+    /// Left and right channels.
+    Stereo,
+    /// A 5.1 surround layout, in the default CLAP surround channel order: front left, front right,
+    /// front center, LFE, back left and back right.
+    Surround51,
+    /// Any other layout, with the given number of channels.
+    ///
+    /// Audio can only be passed between two such layouts if their channel counts are the same,
+    /// in which case it is passed through as-is.
+    Other(usize),
+}
+
+impl ChannelLayout {
+    /// Returns the layout of an audio port, from its port type and channel count, as reported by
+    /// the `audio-ports` extension.
+    ///
+    /// Ports of the `surround` type with 6 channels are assumed to use the default CLAP surround
+    /// channel order. Hosts should check their actual channel map using the `surround` extension,
+    /// and use [`Other`](Self::Other) if it doesn't match.
+    ///
+    /// Ports without a type are considered mono or stereo if they have 1 or 2 channels,
+    /// respectively.
+    pub fn from_port_type(port_type: Option<&CStr>, channel_count: u32) -> Self {
+        let port_type = port_type.map(CStr::to_bytes);
+
+        match (port_type, channel_count) {
+            (Some(b"mono") | None, 1) => Self::Mono,
+            (Some(b"stereo") | None, 2) => Self::Stereo,
+            (Some(b"surround"), 6) => Self::Surround51,
+            (_, channel_count) => Self::Other(channel_count as usize),
+        }
+    }
+
+    /// Returns the number of channels in this layout.
+    #[inline]
+    pub fn channel_count(&self) -> usize {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+            Self::Surround51 => 6,
+            Self::Other(channel_count) => *channel_count,
+        }
+    }
+}
+
+/// A matrix of gains to mix a set of channels into another.
+///
+/// Each destination channel is the sum of all the source channels, each multiplied by the
+/// matching coefficient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelMatrix {
+    source_count: usize,
+    destination_count: usize,
+    coefficients: Vec<f32>,
+}
+
+impl ChannelMatrix {
+    /// Returns the standard matrix to mix audio from the given layout to another, or `None` if
+    /// there is no sensible way to do so.
+    ///
+    /// The following conversions are supported:
+    ///
+    /// * Between identical layouts, which is the identity matrix;
+    /// * Mono to stereo, which duplicates the channel;
+    /// * Stereo to mono, which averages both channels;
+    /// * Mono to 5.1, which feeds the center channel;
+    /// * Stereo to 5.1, which feeds the front left and right channels;
+    /// * 5.1 to stereo, which folds the center and back channels into the front ones at -3dB, as
+    ///   per ITU-R BS.775, and drops the LFE channel;
+    /// * 5.1 to mono, which averages the result of the stereo fold-down.
+    pub fn between(from: ChannelLayout, to: ChannelLayout) -> Option<Self> {
+        use ChannelLayout::*;
+
+        if from == to {
+            return Some(Self::identity(from.channel_count()));
+        }
+
+        let k = ITU_FOLD_DOWN;
+        let matrix = match (from, to) {
+            (Mono, Stereo) => Self::from_rows(1, &[&[1.0], &[1.0]]),
+            (Stereo, Mono) => Self::from_rows(2, &[&[0.5, 0.5]]),
+            (Mono, Surround51) => {
+                Self::from_rows(1, &[&[0.0], &[0.0], &[1.0], &[0.0], &[0.0], &[0.0]])
+            }
+            (Stereo, Surround51) => Self::from_rows(
+                2,
+                &[
+                    &[1.0, 0.0],
+                    &[0.0, 1.0],
+                    &[0.0, 0.0],
+                    &[0.0, 0.0],
+                    &[0.0, 0.0],
+                    &[0.0, 0.0],
+                ],
+            ),
+            (Surround51, Stereo) => Self::from_rows(
+                6,
+                &[&[1.0, 0.0, k, 0.0, k, 0.0], &[0.0, 1.0, k, 0.0, 0.0, k]],
+            ),
+            (Surround51, Mono) => {
+                Self::between(Surround51, Stereo)?.then(&Self::between(Stereo, Mono)?)
+            }
+            _ => return None,
+        };
+
+        Some(matrix)
+    }
+
+    /// Returns the identity matrix for the given number of channels, which leaves them untouched.
+    pub fn identity(channel_count: usize) -> Self {
+        let mut coefficients = vec![0.0; channel_count * channel_count];
+        for channel in 0..channel_count {
+            coefficients[channel * channel_count + channel] = 1.0;
+        }
+
+        Self {
+            source_count: channel_count,
+            destination_count: channel_count,
+            coefficients,
+        }
+    }
+
+    /// Creates a matrix from the coefficients of each destination channel, with one coefficient
+    /// per source channel.
+    ///
+    /// # Panics
+    ///
+    /// This panics if any row doesn't contain exactly `source_count` coefficients.
+    pub fn from_rows(source_count: usize, rows: &[&[f32]]) -> Self {
+        let mut coefficients = Vec::with_capacity(source_count * rows.len());
+
+        for row in rows {
+            assert_eq!(row.len(), source_count, "Invalid channel matrix row length");
+            coefficients.extend_from_slice(row);
+        }
+
+        Self {
+            source_count,
+            destination_count: rows.len(),
+            coefficients,
+        }
+    }
+
+    /// Returns the number of source channels of this matrix.
+    #[inline]
+    pub fn source_count(&self) -> usize {
+        self.source_count
+    }
+
+    /// Returns the number of destination channels of this matrix.
+    #[inline]
+    pub fn destination_count(&self) -> usize {
+        self.destination_count
+    }
+
+    /// Returns the gain applied to the given source channel when mixing it into the given
+    /// destination channel, or `None` if either index is out of bounds.
+    #[inline]
+    pub fn coefficient(&self, destination: usize, source: usize) -> Option<f32> {
+        if destination >= self.destination_count || source >= self.source_count {
+            return None;
+        }
+
+        Some(self.coefficients[destination * self.source_count + source])
+    }
+
+    /// Returns a matrix performing this matrix's mix, followed by the given one.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `next`'s source count doesn't match this matrix's destination count.
+    pub fn then(&self, next: &Self) -> Self {
+        assert_eq!(
+            next.source_count, self.destination_count,
+            "Incompatible channel matrices"
+        );
+
+        let mut coefficients = vec![0.0; next.destination_count * self.source_count];
+
+        for destination in 0..next.destination_count {
+            for source in 0..self.source_count {
+                coefficients[destination * self.source_count + source] = (0..self
+                    .destination_count)
+                    .map(|middle| {
+                        next.coefficients[destination * next.source_count + middle]
+                            * self.coefficients[middle * self.source_count + source]
+                    })
+                    .sum();
+            }
+        }
+
+        Self {
+            source_count: self.source_count,
+            destination_count: next.destination_count,
+            coefficients,
+        }
+    }
+
+    /// Mixes the given source channels into the given destination channels.
+    ///
+    /// The number of frames mixed is the length of the shortest channel. Destination channels
+    /// whose only non-zero coefficient is `1.0` are copied from the matching source channel, and
+    /// are therefore bit-exact.
+    ///
+    /// Any of the channels can be given as slices or as
+    /// [`PooledBuffer`](crate::process::audio_buffers::PooledBuffer)s.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the number of source or destination channels doesn't match this matrix.
+    pub fn apply<S: AsRef<[f32]>, D: AsMut<[f32]>>(&self, sources: &[S], destinations: &mut [D]) {
+        assert_eq!(
+            sources.len(),
+            self.source_count,
+            "Source channel count mismatch"
+        );
+        assert_eq!(
+            destinations.len(),
+            self.destination_count,
+            "Destination channel count mismatch"
+        );
+
+        let frames_count = sources
+            .iter()
+            .map(|c| c.as_ref().len())
+            .chain(destinations.iter_mut().map(|c| c.as_mut().len()))
+            .min()
+            .unwrap_or(0);
+
+        for (row, destination) in self
+            .coefficients
+            .chunks_exact(self.source_count.max(1))
+            .zip(destinations.iter_mut())
+        {
+            let destination = &mut destination.as_mut()[..frames_count];
+            let mut gains = row.iter().enumerate().filter(|(_, gain)| **gain != 0.0);
+
+            match (gains.next(), gains.next()) {
+                (None, _) => destination.fill(0.0),
+                (Some((source, &1.0)), None) => {
+                    destination.copy_from_slice(&sources[source].as_ref()[..frames_count])
+                }
+                _ => {
+                    destination.fill(0.0);
+
+                    for (source, &gain) in row.iter().enumerate().filter(|(_, g)| **g != 0.0) {
+                        let source = &sources[source].as_ref()[..frames_count];
+                        for (destination, source) in destination.iter_mut().zip(source) {
+                            *destination += source * gain;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Adapts the channels of a host track to a plugin whose main ports use a different layout, and
+/// back.
+///
+/// Around each process call, the track's input channels are mixed into the layout of the plugin's
+/// main input port, and the plugin's main output port is mixed back into the track's layout,
+/// using the standard matrices given by [`ChannelMatrix::between`]. When both layouts are the
+/// same, the track's buffers are given to the plugin directly, and audio is passed through
+/// bit-exact.
+///
+/// Creating an adapter fails if there is no sensible adaptation between the two layouts, in
+/// which case the host should refuse to insert the plugin on the track.
+///
+/// All intermediate buffers are allocated when the adapter is created, which should be done when
+/// the plugin is activated, using the same maximum frame count.
+///
+/// # Chains and buffer pools
+///
+/// The adapter only handles a single plugin. To adapt a track to a whole
+/// [`StartedPluginChain`](crate::process::chain::StartedPluginChain), the matrices returned by
+/// [`input_matrix`](Self::input_matrix) and [`output_matrix`](Self::output_matrix) can be
+/// applied before and after processing the chain, e.g. using buffers checked out from a
+/// [`BufferPool`](crate::process::audio_buffers::BufferPool).
+///
+/// # Example
+///
+/// ```
+/// use clack_host::process::channel_adapter::{ChannelAdapter, ChannelLayout};
+///
+/// // A stereo track cannot host a plugin with a 7-channel port.
+/// assert!(ChannelAdapter::new(ChannelLayout::Stereo, ChannelLayout::Other(7), 256).is_err());
+///
+/// let adapter = ChannelAdapter::new(ChannelLayout::Surround51, ChannelLayout::Stereo, 256).unwrap();
+/// assert!(!adapter.is_passthrough());
+/// ```
+///
+/// # Realtime Safety
+///
+/// Processing never allocates, and can be done on the audio thread.
+pub struct ChannelAdapter {
+    track_layout: ChannelLayout,
+    plugin_layout: ChannelLayout,
+    /// The matrices to the plugin's layout and back, or `None` if the layouts are the same.
+    matrices: Option<(ChannelMatrix, ChannelMatrix)>,
+    max_frames_count: u32,
+    plugin_inputs: Vec<Vec<f32>>,
+    plugin_outputs: Vec<Vec<f32>>,
+    input_ports: AudioPorts,
+    output_ports: AudioPorts,
+}
+
+impl ChannelAdapter {
+    /// Creates an adapter between the given track layout and the given plugin main port layout,
+    /// for blocks of up to `max_frames_count` frames.
+    ///
+    /// # Errors
+    ///
+    /// This returns an [`UnsupportedChannelAdaptation`] error if there is no sensible adaptation
+    /// between the two layouts.
+    pub fn new(
+        track_layout: ChannelLayout,
+        plugin_layout: ChannelLayout,
+        max_frames_count: u32,
+    ) -> Result<Self, UnsupportedChannelAdaptation> {
+        let unsupported = UnsupportedChannelAdaptation {
+            track_layout,
+            plugin_layout,
+        };
+
+        let matrices = if track_layout == plugin_layout {
+            None
+        } else {
+            let input = ChannelMatrix::between(track_layout, plugin_layout).ok_or(unsupported)?;
+            let output = ChannelMatrix::between(plugin_layout, track_layout).ok_or(unsupported)?;
+            Some((input, output))
+        };
+
+        let plugin_buffers = || match matrices {
+            Some(_) => vec![vec![0.0; max_frames_count as usize]; plugin_layout.channel_count()],
+            None => Vec::new(),
+        };
+
+        let channel_count = track_layout
+            .channel_count()
+            .max(plugin_layout.channel_count());
+
+        Ok(Self {
+            track_layout,
+            plugin_layout,
+            max_frames_count,
+            plugin_inputs: plugin_buffers(),
+            plugin_outputs: plugin_buffers(),
+            matrices,
+            input_ports: AudioPorts::with_capacity(channel_count, 1),
+            output_ports: AudioPorts::with_capacity(channel_count, 1),
+        })
+    }
+
+    /// Returns the layout of the host track.
+    #[inline]
+    pub fn track_layout(&self) -> ChannelLayout {
+        self.track_layout
+    }
+
+    /// Returns the layout of the plugin's main ports.
+    #[inline]
+    pub fn plugin_layout(&self) -> ChannelLayout {
+        self.plugin_layout
+    }
+
+    /// Returns the maximum number of frames this adapter can process at once.
+    #[inline]
+    pub fn max_frames_count(&self) -> u32 {
+        self.max_frames_count
+    }
+
+    /// Returns `true` if both layouts are the same, in which case audio is passed through as-is.
+    #[inline]
+    pub fn is_passthrough(&self) -> bool {
+        self.matrices.is_none()
+    }
+
+    /// Returns the matrix mixing the track's channels into the plugin's input, or `None` if audio
+    /// is passed through as-is.
+    #[inline]
+    pub fn input_matrix(&self) -> Option<&ChannelMatrix> {
+        self.matrices.as_ref().map(|(input, _)| input)
+    }
+
+    /// Returns the matrix mixing the plugin's output into the track's channels, or `None` if audio
+    /// is passed through as-is.
+    #[inline]
+    pub fn output_matrix(&self) -> Option<&ChannelMatrix> {
+        self.matrices.as_ref().map(|(_, output)| output)
+    }
+
+    /// Processes a block of audio through the given plugin, adapting the channels of the track
+    /// to the plugin's main ports and back.
+    ///
+    /// The `input` and `output` slices must both contain one buffer per channel of the track. The
+    /// number of frames processed is the length of the shortest buffer, which must not be
+    /// greater than the adapter's maximum frame count.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`ChannelAdapterError`] if the given buffers do not match the adapter's
+    /// configuration, or if the plugin fails to process.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process<H: HostHandlers, I, O>(
+        &mut self,
+        processor: &mut StartedPluginAudioProcessor<H>,
+        input: &mut [I],
+        output: &mut [O],
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+        steady_time: Option<u64>,
+        transport: Option<&TransportEvent>,
+    ) -> Result<ProcessStatus, ChannelAdapterError>
+    where
+        I: AsRef<[f32]> + AsMut<[f32]>,
+        O: AsMut<[f32]>,
+    {
+        let channel_count = self.track_layout.channel_count();
+        if input.len() != channel_count || output.len() != channel_count {
+            return Err(ChannelAdapterError::ChannelCountMismatch);
+        }
+
+        let frames_count = input
+            .iter_mut()
+            .map(|c| c.as_mut().len())
+            .chain(output.iter_mut().map(|c| c.as_mut().len()))
+            .min()
+            .unwrap_or(0);
+
+        if frames_count > self.max_frames_count as usize {
+            return Err(ChannelAdapterError::TooManyFrames);
+        }
+
+        let status = match &self.matrices {
+            None => {
+                let inputs = self.input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only(
+                        input
+                            .iter_mut()
+                            .map(|c| InputChannel::variable(&mut c.as_mut()[..frames_count])),
+                    ),
+                    latency: 0,
+                }]);
+
+                let mut outputs = self.output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only(
+                        output.iter_mut().map(|c| &mut c.as_mut()[..frames_count]),
+                    ),
+                    latency: 0,
+                }]);
+
+                processor.process(
+                    &inputs,
+                    &mut outputs,
+                    input_events,
+                    output_events,
+                    steady_time,
+                    transport,
+                )
+            }
+            Some((input_matrix, output_matrix)) => {
+                let plugin_inputs = &mut self.plugin_inputs;
+                let plugin_outputs = &mut self.plugin_outputs;
+
+                input_matrix.apply(input, plugin_inputs);
+
+                let inputs = self.input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only(
+                        plugin_inputs
+                            .iter_mut()
+                            .map(|c| InputChannel::variable(&mut c[..frames_count])),
+                    ),
+                    latency: 0,
+                }]);
+
+                let mut outputs = self.output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only(
+                        plugin_outputs.iter_mut().map(|c| &mut c[..frames_count]),
+                    ),
+                    latency: 0,
+                }]);
+
+                let status = processor.process(
+                    &inputs,
+                    &mut outputs,
+                    input_events,
+                    output_events,
+                    steady_time,
+                    transport,
+                );
+
+                output_matrix.apply(plugin_outputs, output);
+                status
+            }
+        };
+
+        status.map_err(ChannelAdapterError::Plugin)
+    }
+}
+
+/// An error returned when there is no sensible adaptation between a track layout and a plugin
+/// layout.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UnsupportedChannelAdaptation {
+    /// The layout of the host track.
+    pub track_layout: ChannelLayout,
+    /// The layout of the plugin's main ports.
+    pub plugin_layout: ChannelLayout,
+}
+
+impl Display for UnsupportedChannelAdaptation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot adapt a {:?} track to a plugin with a {:?} layout",
+            self.track_layout, self.plugin_layout
+        )
+    }
+}
+
+impl Error for UnsupportedChannelAdaptation {}
+
+/// Errors that can occur while processing through a [`ChannelAdapter`].
+#[derive(Debug)]
+pub enum ChannelAdapterError {
+    /// The number of given input or output buffers does not match the track's channel count.
+    ChannelCountMismatch,
+    /// The given buffers are larger than the adapter's maximum frame count.
+    TooManyFrames,
+    /// The plugin failed to process.
+    Plugin(PluginInstanceError),
+}
+
+impl Display for ChannelAdapterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChannelCountMismatch => {
+                f.write_str("Buffer channel count does not match the track's")
+            }
+            Self::TooManyFrames => f.write_str("Buffers exceed the adapter's maximum frame count"),
+            Self::Plugin(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl Error for ChannelAdapterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Plugin(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ChannelLayout::*;
+
+    const K: f32 = ITU_FOLD_DOWN;
+
+    fn mix(from: ChannelLayout, to: ChannelLayout, sources: &[[f32; 2]]) -> Vec<[f32; 2]> {
+        let mut destinations = vec![[0.0; 2]; to.channel_count()];
+        ChannelMatrix::between(from, to)
+            .unwrap()
+            .apply(sources, &mut destinations);
+        destinations
+    }
+
+    #[test]
+    fn mono_and_stereo() {
+        assert_eq!(
+            mix(Mono, Stereo, &[[0.5, -1.0]]),
+            [[0.5, -1.0], [0.5, -1.0]]
+        );
+        assert_eq!(mix(Stereo, Mono, &[[1.0, 0.5], [0.0, -0.5]]), [[0.5, 0.0]]);
+    }
+
+    #[test]
+    fn surround_fold_down() {
+        // L, R, C, LFE, Ls, Rs
+        let sources = [
+            [1.0, 0.0],
+            [0.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 0.0],
+            [0.0, 1.0],
+        ];
+
+        assert_eq!(
+            mix(Surround51, Stereo, &sources),
+            [[1.0 + K + K, K], [K, 1.0 + K + K]]
+        );
+
+        let mono = mix(Surround51, Mono, &sources);
+        let expected = [0.5 + 1.5 * K, 0.5 + 1.5 * K];
+        assert!((mono[0][0] - expected[0]).abs() < 1e-6);
+        assert!((mono[0][1] - expected[1]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn upmix_to_surround() {
+        assert_eq!(
+            mix(Mono, Surround51, &[[1.0, 0.5]]),
+            [[0.0; 2], [0.0; 2], [1.0, 0.5], [0.0; 2], [0.0; 2], [0.0; 2]]
+        );
+        assert_eq!(
+            mix(Stereo, Surround51, &[[1.0, 0.5], [0.25, 0.0]]),
+            [
+                [1.0, 0.5],
+                [0.25, 0.0],
+                [0.0; 2],
+                [0.0; 2],
+                [0.0; 2],
+                [0.0; 2]
+            ]
+        );
+    }
+
+    #[test]
+    fn composition() {
+        let stereo_to_mono = ChannelMatrix::between(Stereo, Mono).unwrap();
+        let mono_to_stereo = ChannelMatrix::between(Mono, Stereo).unwrap();
+
+        assert_eq!(
+            stereo_to_mono.then(&mono_to_stereo),
+            ChannelMatrix::from_rows(2, &[&[0.5, 0.5], &[0.5, 0.5]])
+        );
+        assert_eq!(
+            ChannelMatrix::identity(2).then(&stereo_to_mono),
+            stereo_to_mono
+        );
+    }
+
+    #[test]
+    fn identity_is_bit_exact() {
+        let sources = [[0.1f32, f32::MIN_POSITIVE / 2.0], [-0.3, 1e30]];
+        assert_eq!(mix(Stereo, Stereo, &sources), sources);
+    }
+
+    #[test]
+    fn unsupported_layouts() {
+        assert_eq!(ChannelMatrix::between(Stereo, Other(4)), None);
+        assert_eq!(ChannelMatrix::between(Other(3), Mono), None);
+        assert!(ChannelMatrix::between(Other(3), Other(3)).is_some());
+
+        let error = ChannelAdapter::new(Surround51, Other(8), 32).err().unwrap();
+        assert_eq!(error.track_layout, Surround51);
+        assert_eq!(error.plugin_layout, Other(8));
+    }
+
+    #[test]
+    fn port_types() {
+        let layout = |port_type: Option<&[u8]>, channel_count| {
+            let port_type = port_type.map(|t| CStr::from_bytes_with_nul(t).unwrap());
+            ChannelLayout::from_port_type(port_type, channel_count)
+        };
+
+        assert_eq!(layout(Some(b"mono\0"), 1), Mono);
+        assert_eq!(layout(None, 2), Stereo);
+        assert_eq!(layout(Some(b"surround\0"), 6), Surround51);
+        assert_eq!(layout(Some(b"surround\0"), 8), Other(8));
+        assert_eq!(layout(Some(b"stereo\0"), 1), Other(1));
+    }
+}
@@ -0,0 +1,382 @@
+//! Latency compensation between the input ports of a plugin.
+//!
+//! See the [`DelayCompensator`] type for more information.
+
+use crate::host::HostHandlers;
+use crate::plugin::PluginInstanceError;
+use crate::process::audio_buffers::{
+    AudioPortBuffer, AudioPortBufferType, AudioPorts, InputChannel,
+};
+use crate::process::{PluginAudioConfiguration, ProcessStatus, StartedPluginAudioProcessor};
+use clack_common::events::event_types::TransportEvent;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The delay line of a single input port.
+struct PortDelay {
+    delay: usize,
+    lines: Vec<Vec<f32>>,
+    position: usize,
+}
+
+impl PortDelay {
+    fn new(channel_count: usize, delay: u32) -> Self {
+        Self {
+            delay: delay as usize,
+            lines: vec![vec![0.0; delay as usize]; channel_count],
+            position: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        for line in &mut self.lines {
+            line.fill(0.0);
+        }
+
+        self.position = 0;
+    }
+
+    /// Delays the given channels in-place.
+    fn process<C: AsMut<[f32]>>(&mut self, channels: &mut [C], frames_count: usize) {
+        if self.delay == 0 {
+            return;
+        }
+
+        let mut position = self.position;
+
+        for (channel, line) in channels.iter_mut().zip(&mut self.lines) {
+            position = self.position;
+
+            for sample in &mut channel.as_mut()[..frames_count] {
+                *sample = core::mem::replace(&mut line[position], *sample);
+                position = (position + 1) % self.delay;
+            }
+        }
+
+        self.position = position;
+    }
+}
+
+/// Aligns the input ports of a plugin, by delaying each of them by a given amount of samples.
+///
+/// This is typically used for sidechain inputs: if the main input of a plugin went through a chain
+/// of plugins with a total latency of e.g. 64 samples, but its sidechain input didn't, then the
+/// sidechain input must be delayed by 64 samples for both streams to stay aligned.
+///
+/// All delay lines are allocated when the compensator is created, or when its delays are
+/// changed, which must happen outside the audio thread. Ports with a delay of zero are never
+/// copied nor touched.
+///
+/// Latency changes require the plugin to be restarted, so new delays should be set using
+/// [`set_port_latencies`](Self::set_port_latencies) or [`set_delays`](Self::set_delays) while
+/// handling the restart, i.e. while the plugin is deactivated. This resets all the delay lines.
+///
+/// Only 32-bit float input ports are supported.
+pub struct DelayCompensator {
+    ports: Vec<PortDelay>,
+    configuration: PluginAudioConfiguration,
+    input_ports: AudioPorts,
+    output_ports: AudioPorts,
+}
+
+impl DelayCompensator {
+    /// Creates a new compensator for input ports with the given channel counts.
+    ///
+    /// `output_channel_count` is the number of channels of the plugin's output port, which is
+    /// only used by [`process_aligned`](Self::process_aligned). All ports are initially not
+    /// delayed.
+    pub fn new(
+        configuration: PluginAudioConfiguration,
+        port_channel_counts: &[usize],
+        output_channel_count: usize,
+    ) -> Self {
+        let total_channel_count = port_channel_counts.iter().sum();
+
+        Self {
+            ports: port_channel_counts
+                .iter()
+                .map(|channel_count| PortDelay::new(*channel_count, 0))
+                .collect(),
+            configuration,
+            input_ports: AudioPorts::with_capacity(total_channel_count, port_channel_counts.len()),
+            output_ports: AudioPorts::with_capacity(output_channel_count, 1),
+        }
+    }
+
+    /// Returns the number of input ports this compensator handles.
+    #[inline]
+    pub fn port_count(&self) -> usize {
+        self.ports.len()
+    }
+
+    /// Returns the delay applied to the given input port, in samples.
+    ///
+    /// This returns `None` if there is no port at the given index.
+    #[inline]
+    pub fn delay(&self, port_index: usize) -> Option<u32> {
+        self.ports.get(port_index).map(|p| p.delay as u32)
+    }
+
+    /// Returns the largest delay applied to any input port, in samples.
+    #[inline]
+    pub fn max_delay(&self) -> u32 {
+        self.ports.iter().map(|p| p.delay as u32).max().unwrap_or(0)
+    }
+
+    /// Returns the audio configuration this compensator was set up with.
+    #[inline]
+    pub fn configuration(&self) -> PluginAudioConfiguration {
+        self.configuration
+    }
+
+    /// Sets the delay of each input port, in samples.
+    ///
+    /// Ports missing from `delays` are not delayed, and extra delays are ignored. This
+    /// (re-)allocates the delay lines and resets them.
+    pub fn set_delays(&mut self, delays: &[u32]) {
+        for (index, port) in self.ports.iter_mut().enumerate() {
+            let delay = delays.get(index).copied().unwrap_or(0);
+            *port = PortDelay::new(port.lines.len(), delay);
+        }
+    }
+
+    /// Sets the delays so that input ports with the given latencies are aligned.
+    ///
+    /// `latencies` contains the latency each input port's signal has already accumulated before
+    /// reaching the plugin (e.g. from the plugins that processed it upstream). Every port is then
+    /// delayed so that its total latency matches the largest one.
+    pub fn set_port_latencies(&mut self, latencies: &[u32]) {
+        let max = latencies.iter().copied().max().unwrap_or(0);
+        let delays: Vec<u32> = latencies.iter().map(|l| max - l).collect();
+
+        self.set_delays(&delays);
+    }
+
+    /// Updates the audio configuration, e.g. after the plugin was re-activated.
+    ///
+    /// If the sample rate changed, all delay lines are reset, as their contents are meaningless at
+    /// the new rate.
+    pub fn set_configuration(&mut self, configuration: PluginAudioConfiguration) {
+        if configuration.sample_rate != self.configuration.sample_rate {
+            self.reset();
+        }
+
+        self.configuration = configuration;
+    }
+
+    /// Clears all delay lines.
+    pub fn reset(&mut self) {
+        for port in &mut self.ports {
+            port.reset();
+        }
+    }
+
+    /// Delays the given input port's channels in-place, by the port's delay.
+    ///
+    /// Only the first `frames_count` samples of each channel are processed. This does nothing if
+    /// the port has no delay, or if there is no port at the given index.
+    pub fn delay_port<C: AsMut<[f32]>>(
+        &mut self,
+        port_index: usize,
+        channels: &mut [C],
+        frames_count: usize,
+    ) {
+        if let Some(port) = self.ports.get_mut(port_index) {
+            port.process(channels, frames_count)
+        }
+    }
+
+    /// Delays all the given input ports, then processes them through the given plugin.
+    ///
+    /// `inputs` must contain one item per input port, each containing one buffer per channel.
+    /// `outputs` must contain the buffers of the plugin's single output port. The input buffers
+    /// are delayed in-place.
+    ///
+    /// The number of frames processed is the length of the shortest buffer, which must not be
+    /// greater than the maximum frame count the plugin was activated with.
+    ///
+    /// See [`StartedPluginAudioProcessor::process`] for more information about the other
+    /// arguments.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`DelayCompensatorError`] if the given buffers do not match the
+    /// compensator's configuration, or if the plugin fails to process.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_aligned<H, P, I, O>(
+        &mut self,
+        processor: &mut StartedPluginAudioProcessor<H>,
+        inputs: &mut [P],
+        outputs: &mut [O],
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+        steady_time: Option<u64>,
+        transport: Option<&TransportEvent>,
+    ) -> Result<ProcessStatus, DelayCompensatorError>
+    where
+        H: HostHandlers,
+        P: AsMut<[I]>,
+        I: AsMut<[f32]>,
+        O: AsMut<[f32]>,
+    {
+        if inputs.len() != self.ports.len() {
+            return Err(DelayCompensatorError::PortCountMismatch);
+        }
+
+        let frames_count = inputs
+            .iter_mut()
+            .flat_map(|p| p.as_mut().iter_mut().map(|c| c.as_mut().len()))
+            .chain(outputs.iter_mut().map(|c| c.as_mut().len()))
+            .min()
+            .unwrap_or(0);
+
+        if frames_count > self.configuration.max_frames_count as usize {
+            return Err(DelayCompensatorError::TooManyFrames);
+        }
+
+        for (port, channels) in self.ports.iter_mut().zip(inputs.iter_mut()) {
+            port.process(channels.as_mut(), frames_count);
+        }
+
+        let input_buffers =
+            self.input_ports
+                .with_input_buffers(inputs.iter_mut().map(|channels| {
+                    AudioPortBuffer {
+                        channels: AudioPortBufferType::f32_input_only(
+                            channels
+                                .as_mut()
+                                .iter_mut()
+                                .map(|c| InputChannel::variable(&mut c.as_mut()[..frames_count])),
+                        ),
+                        latency: 0,
+                    }
+                }));
+
+        let mut output_buffers = self.output_ports.with_output_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_output_only(
+                outputs.iter_mut().map(|c| &mut c.as_mut()[..frames_count]),
+            ),
+            latency: 0,
+        }]);
+
+        processor
+            .process(
+                &input_buffers,
+                &mut output_buffers,
+                input_events,
+                output_events,
+                steady_time,
+                transport,
+            )
+            .map_err(DelayCompensatorError::Plugin)
+    }
+}
+
+/// Errors that can occur while processing through a [`DelayCompensator`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DelayCompensatorError {
+    /// The number of given input ports does not match the compensator's.
+    PortCountMismatch,
+    /// The given buffers are larger than the maximum frame count.
+    TooManyFrames,
+    /// The plugin failed to process.
+    Plugin(PluginInstanceError),
+}
+
+impl Display for DelayCompensatorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PortCountMismatch => {
+                f.write_str("Input port count does not match the compensator's")
+            }
+            Self::TooManyFrames => f.write_str("Buffers exceed the maximum frame count"),
+            Self::Plugin(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl Error for DelayCompensatorError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: 1,
+        max_frames_count: 16,
+    };
+
+    #[test]
+    fn delays_impulses_across_blocks() {
+        let mut compensator = DelayCompensator::new(CONFIGURATION, &[1, 2], 0);
+        compensator.set_port_latencies(&[20, 0]);
+
+        assert_eq!(compensator.delay(0), Some(0));
+        assert_eq!(compensator.delay(1), Some(20));
+        assert_eq!(compensator.max_delay(), 20);
+
+        let mut main_output = Vec::new();
+        let mut sidechain_output = Vec::new();
+
+        for block in 0..4 {
+            let mut main = [vec![0.0f32; 16]];
+            let mut sidechain = [vec![0.0f32; 16], vec![0.0f32; 16]];
+
+            if block == 0 {
+                main[0][0] = 1.0;
+                sidechain[0][0] = 1.0;
+                sidechain[1][3] = 1.0;
+            }
+
+            compensator.delay_port(0, &mut main, 16);
+            compensator.delay_port(1, &mut sidechain, 16);
+
+            main_output.extend_from_slice(&main[0]);
+            sidechain_output.extend_from_slice(&sidechain[0]);
+            sidechain_output.extend_from_slice(&sidechain[1]);
+        }
+
+        let impulses = |s: &[f32]| {
+            s.iter()
+                .enumerate()
+                .filter(|(_, s)| **s != 0.0)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(impulses(&main_output), [0]);
+
+        // Both channels are interleaved by block in sidechain_output.
+        let (left, right): (Vec<_>, Vec<_>) = sidechain_output
+            .chunks(16)
+            .enumerate()
+            .partition(|(i, _)| i % 2 == 0);
+        let left: Vec<f32> = left.into_iter().flat_map(|(_, c)| c.to_vec()).collect();
+        let right: Vec<f32> = right.into_iter().flat_map(|(_, c)| c.to_vec()).collect();
+
+        assert_eq!(impulses(&left), [20]);
+        assert_eq!(impulses(&right), [23]);
+    }
+
+    #[test]
+    fn sample_rate_change_resets_delay_lines() {
+        let mut compensator = DelayCompensator::new(CONFIGURATION, &[1], 0);
+        compensator.set_delays(&[4]);
+
+        let mut block = [vec![1.0f32; 4]];
+        compensator.delay_port(0, &mut block, 4);
+        assert_eq!(block[0], [0.0; 4]);
+
+        compensator.set_configuration(PluginAudioConfiguration {
+            sample_rate: 48_000.0,
+            ..CONFIGURATION
+        });
+
+        let mut block = [vec![0.0f32; 4]];
+        compensator.delay_port(0, &mut block, 4);
+        assert_eq!(block[0], [0.0; 4]);
+        assert_eq!(compensator.delay(0), Some(4));
+    }
+}
@@ -0,0 +1,349 @@
+//! Host-side handling of plugins that report errors while processing.
+//!
+//! See the [`ProcessErrorPolicy`] type for more information.
+
+use crate::host::HostHandlers;
+use crate::plugin::PluginInstanceError;
+use crate::process::wet_dry::{WetDryError, WetDryHandle};
+use crate::process::{ProcessStatus, StartedPluginAudioProcessor};
+use crate::utils::compatibility::CheckLevel;
+
+/// Host callbacks to be notified when a plugin gets suspended by a [`ProcessErrorPolicy`].
+///
+/// This is meant to be implemented by the host's [`Shared`](HostHandlers::Shared) handler, and
+/// is used by [`ProcessErrorPolicy::record_and_notify`].
+pub trait HostProcessErrorHandler {
+    /// Called when a plugin was suspended after failing to process too many consecutive blocks.
+    ///
+    /// This is called from the audio thread, and must therefore be realtime-safe. Hosts will
+    /// typically flag the plugin instance as faulty here, and report it to the user later on the
+    /// main thread.
+    fn plugin_suspended(&self, consecutive_errors: u32);
+}
+
+/// What the host should do after a plugin's `process` call, as decided by a
+/// [`ProcessErrorPolicy`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ProcessErrorAction {
+    /// The plugin can keep being processed normally.
+    Continue,
+    /// The plugin failed to process this block, but it may still recover: the host should discard
+    /// its output for this block, and keep calling `process` for the next ones.
+    Retry,
+    /// The plugin failed too many times, and was suspended: the host should stop calling
+    /// `process` until the policy is [resumed](ProcessErrorPolicy::resume).
+    Suspend,
+}
+
+/// How hosts should handle process status values that are unknown to this version of Clack.
+///
+/// Future CLAP versions may define new process statuses. Plugins built against those versions
+/// may return them to older hosts, which surface them as
+/// [`PluginInstanceError::InvalidProcessStatus`] errors.
+///
+/// This is superseded by the
+/// [`UnknownProcessStatus`](crate::utils::compatibility::CompatibilityCheck::UnknownProcessStatus)
+/// check of the instance's
+/// [`CompatibilityPolicy`](crate::utils::compatibility::CompatibilityPolicy), which handles
+/// unknown statuses before they are returned as errors. See [`level`](Self::level) for the
+/// equivalent check level.
+#[deprecated(
+    note = "Set the `UnknownProcessStatus` check of the instance's `CompatibilityPolicy` instead"
+)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum UnknownStatusHandling {
+    /// Unknown statuses are errors. This is mostly useful for hosts that validate plugins.
+    Strict,
+    /// Unknown statuses are treated as [`ProcessStatus::Continue`], i.e. the plugin keeps being
+    /// processed normally, and is never put to sleep because of them.
+    ///
+    /// `CLAP_PROCESS_ERROR` is still reported as an error.
+    Permissive,
+}
+
+#[allow(deprecated)]
+impl UnknownStatusHandling {
+    /// Returns the level of the `UnknownProcessStatus` compatibility check equivalent to this
+    /// handling.
+    #[inline]
+    pub const fn level(self) -> CheckLevel {
+        match self {
+            Self::Strict => CheckLevel::Deny,
+            Self::Permissive => CheckLevel::Allow,
+        }
+    }
+
+    /// Applies this handling to the result of a `process` call.
+    ///
+    /// In [`Permissive`](Self::Permissive) mode, this turns
+    /// [`InvalidProcessStatus`](PluginInstanceError::InvalidProcessStatus) errors into
+    /// [`ProcessStatus::Continue`]. Any other result is returned as-is.
+    #[inline]
+    pub fn apply(
+        self,
+        result: Result<ProcessStatus, PluginInstanceError>,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        match (self, result) {
+            (Self::Permissive, Err(PluginInstanceError::InvalidProcessStatus { .. })) => {
+                Ok(ProcessStatus::Continue)
+            }
+            (_, result) => result,
+        }
+    }
+}
+
+#[allow(deprecated)]
+impl Default for UnknownStatusHandling {
+    #[inline]
+    fn default() -> Self {
+        Self::Strict
+    }
+}
+
+/// A per-instance policy for handling errors reported by a plugin while processing.
+///
+/// Plugins may fail to process a block by returning `CLAP_PROCESS_ERROR`, which surfaces as
+/// [`PluginInstanceError::ProcessingFailed`]. This doesn't invalidate the audio processor: the
+/// CLAP specification allows hosts to keep calling `process` afterward, and some plugins recover
+/// from transient errors. Plugins that keep failing however should be taken out of the signal
+/// path.
+///
+/// This policy tracks the result of each `process` call, passed to [`record`](Self::record), and
+/// tells the host what to do next through a [`ProcessErrorAction`]:
+///
+/// * After a configurable amount of consecutive errors (see
+///   [`suspend_after`](Self::suspend_after)), the plugin is suspended. It stays suspended until
+///   the host calls [`resume`](Self::resume), and the host's shared handler can be notified of
+///   the suspension (see [`record_and_notify`](Self::record_and_notify)).
+/// * If a [`WetDryHandle`] is given (see [`with_bypass`](Self::with_bypass)), the plugin is
+///   bypassed as soon as it fails, and un-bypassed once it processes successfully again, unless
+///   it got suspended in the meantime. Bypasses engaged by someone else (e.g. the user) are left
+///   untouched. Together with the
+///   [`PluginWetDryWrapper`](crate::process::wet_dry::PluginWetDryWrapper), which outputs the dry
+///   signal on errors, this makes transient failures click-free.
+///
+/// Plugins that return an invalid status value (see
+/// [`PluginInstanceError::InvalidProcessStatus`]) are treated the same way as plugins reporting
+/// an error. Those errors are only returned if the instance's
+/// [`CompatibilityPolicy`](crate::utils::compatibility::CompatibilityPolicy) denies
+/// [unknown process statuses](crate::utils::compatibility::CompatibilityCheck::UnknownProcessStatus).
+/// Plugins without a `process` function are suspended immediately. Other errors are host-side
+/// usage errors, and are ignored by this policy.
+///
+/// # Realtime Safety
+///
+/// This type never allocates, and all of its methods are realtime-safe.
+#[derive(Clone)]
+pub struct ProcessErrorPolicy {
+    max_consecutive_errors: Option<u32>,
+    bypass: Option<WetDryHandle>,
+    tolerates_unknown_statuses: bool,
+    engaged_bypass: bool,
+    consecutive_errors: u32,
+    total_errors: u64,
+    suspended: bool,
+}
+
+impl ProcessErrorPolicy {
+    /// Creates a new policy, which never suspends the plugin and doesn't bypass it.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            max_consecutive_errors: None,
+            bypass: None,
+            tolerates_unknown_statuses: false,
+            engaged_bypass: false,
+            consecutive_errors: 0,
+            total_errors: 0,
+            suspended: false,
+        }
+    }
+
+    /// Suspends the plugin after it failed to process the given number of consecutive blocks.
+    ///
+    /// A value of `0` is treated as `1`, i.e. the plugin is suspended on its first error.
+    #[inline]
+    pub fn suspend_after(mut self, max_consecutive_errors: u32) -> Self {
+        self.max_consecutive_errors = Some(max_consecutive_errors.max(1));
+        self
+    }
+
+    /// Bypasses the plugin using the given wet/dry wrapper handle while it fails to process.
+    #[inline]
+    pub fn with_bypass(mut self, bypass: WetDryHandle) -> Self {
+        self.bypass = Some(bypass);
+        self
+    }
+
+    /// Sets how process statuses unknown to this version of Clack are handled.
+    ///
+    /// By default, they are [strictly](UnknownStatusHandling::Strict) treated as errors.
+    #[deprecated(
+        note = "Set the `UnknownProcessStatus` check of the instance's `CompatibilityPolicy` instead"
+    )]
+    #[allow(deprecated)]
+    #[inline]
+    pub fn with_unknown_statuses(mut self, handling: UnknownStatusHandling) -> Self {
+        self.tolerates_unknown_statuses = handling == UnknownStatusHandling::Permissive;
+        self
+    }
+
+    /// Returns `true` if the plugin is currently suspended.
+    #[inline]
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Returns the number of consecutive blocks the plugin failed to process.
+    #[inline]
+    pub fn consecutive_errors(&self) -> u32 {
+        self.consecutive_errors
+    }
+
+    /// Returns the total number of blocks the plugin failed to process, since this policy was
+    /// created.
+    #[inline]
+    pub fn total_errors(&self) -> u64 {
+        self.total_errors
+    }
+
+    /// Lifts the plugin's suspension, and un-bypasses it if it was bypassed by this policy.
+    ///
+    /// The consecutive error count is reset, but the total error count is kept.
+    pub fn resume(&mut self) {
+        self.set_bypassed(false);
+        self.suspended = false;
+        self.consecutive_errors = 0;
+    }
+
+    /// Records the result of a `process` call, and returns what the host should do next.
+    ///
+    /// Results recorded while the plugin is suspended are ignored.
+    pub fn record<T>(&mut self, result: &Result<T, PluginInstanceError>) -> ProcessErrorAction {
+        match result {
+            Ok(_) => self.record_success(),
+            Err(error) => self.record_error(error),
+        }
+    }
+
+    /// Records the result of a [`PluginWetDryWrapper`]'s `process` call, and returns what the host
+    /// should do next.
+    ///
+    /// Errors that don't come from the plugin are ignored.
+    ///
+    /// [`PluginWetDryWrapper`]: crate::process::wet_dry::PluginWetDryWrapper
+    pub fn record_wet_dry<T>(&mut self, result: &Result<T, WetDryError>) -> ProcessErrorAction {
+        match result {
+            Ok(_) => self.record_success(),
+            Err(WetDryError::Plugin(error)) => self.record_error(error),
+            Err(_) => self.current_action(),
+        }
+    }
+
+    /// Records the result of a `process` call of the given audio processor, like
+    /// [`record`](Self::record) does.
+    ///
+    /// If this suspends the plugin, the host's shared handler is then notified through its
+    /// [`HostProcessErrorHandler`] implementation.
+    pub fn record_and_notify<T, H: HostHandlers>(
+        &mut self,
+        processor: &StartedPluginAudioProcessor<H>,
+        result: &Result<T, PluginInstanceError>,
+    ) -> ProcessErrorAction
+    where
+        for<'a> <H as HostHandlers>::Shared<'a>: HostProcessErrorHandler,
+    {
+        let was_suspended = self.suspended;
+        let action = self.record(result);
+
+        if !was_suspended && self.suspended {
+            let consecutive_errors = self.consecutive_errors;
+            processor.access_shared_handler(|s| s.plugin_suspended(consecutive_errors));
+        }
+
+        action
+    }
+
+    /// Records a successful `process` call.
+    pub fn record_success(&mut self) -> ProcessErrorAction {
+        if self.suspended {
+            return ProcessErrorAction::Suspend;
+        }
+
+        self.consecutive_errors = 0;
+        self.set_bypassed(false);
+
+        ProcessErrorAction::Continue
+    }
+
+    /// Records a failed `process` call, and returns what the host should do next.
+    pub fn record_error(&mut self, error: &PluginInstanceError) -> ProcessErrorAction {
+        if self.suspended {
+            return ProcessErrorAction::Suspend;
+        }
+
+        if self.tolerates_unknown_statuses
+            && matches!(error, PluginInstanceError::InvalidProcessStatus { .. })
+        {
+            return self.record_success();
+        }
+
+        match error {
+            PluginInstanceError::ProcessingFailed
+            | PluginInstanceError::InvalidProcessStatus { .. } => {}
+            PluginInstanceError::NullProcessFunction => {
+                self.suspend();
+                return ProcessErrorAction::Suspend;
+            }
+            _ => return self.current_action(),
+        }
+
+        self.consecutive_errors = self.consecutive_errors.saturating_add(1);
+        self.total_errors = self.total_errors.saturating_add(1);
+        self.set_bypassed(true);
+
+        match self.max_consecutive_errors {
+            Some(max) if self.consecutive_errors >= max => {
+                self.suspend();
+                ProcessErrorAction::Suspend
+            }
+            _ => ProcessErrorAction::Retry,
+        }
+    }
+
+    fn current_action(&self) -> ProcessErrorAction {
+        if self.suspended {
+            ProcessErrorAction::Suspend
+        } else {
+            ProcessErrorAction::Continue
+        }
+    }
+
+    fn suspend(&mut self) {
+        self.suspended = true;
+        self.set_bypassed(true);
+    }
+
+    /// Engages or releases the bypass. A bypass that was already engaged by someone else is
+    /// left untouched.
+    fn set_bypassed(&mut self, bypassed: bool) {
+        let Some(bypass) = &self.bypass else {
+            return;
+        };
+
+        if bypassed && !self.engaged_bypass && !bypass.is_bypassed() {
+            bypass.set_bypassed(true);
+            self.engaged_bypass = true;
+        } else if !bypassed && self.engaged_bypass {
+            bypass.set_bypassed(false);
+            self.engaged_bypass = false;
+        }
+    }
+}
+
+impl Default for ProcessErrorPolicy {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,182 @@
+//! Per-block CPU load measurement around any plugin.
+//!
+//! See the [`PluginLoadMeter`] type for more information.
+
+use crate::host::HostHandlers;
+use crate::plugin::PluginInstanceError;
+use crate::process::audio_buffers::{InputAudioBuffers, OutputAudioBuffers};
+use crate::process::{PluginAudioConfiguration, ProcessStatus, StartedPluginAudioProcessor};
+use clack_common::events::event_types::TransportEvent;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The time constant of the load smoothing, in seconds of processed audio.
+///
+/// After this duration, the smoothed load has moved about 63% of the way towards a new steady
+/// load.
+pub const LOAD_SMOOTHING_DURATION: f64 = 0.3;
+
+struct LoadMeterValues {
+    load: AtomicU32,
+    peak: AtomicU32,
+}
+
+/// A handle to the values measured by a [`PluginLoadMeter`].
+///
+/// This handle can be cloned and sent to any thread, allowing e.g. the UI thread to display the
+/// plugin's CPU load while it is processing on the audio thread.
+///
+/// Loads are expressed as a percentage of the block's duration: a plugin that takes 5ms to
+/// process a 10ms block has a load of `50.0`. Loads above `100.0` mean the plugin can't keep up
+/// with real-time processing.
+#[derive(Clone)]
+pub struct LoadMeterHandle {
+    values: Arc<LoadMeterValues>,
+}
+
+impl LoadMeterHandle {
+    /// Returns the smoothed load of the plugin, in percent.
+    ///
+    /// See [`LOAD_SMOOTHING_DURATION`] for how quickly this value follows load changes.
+    #[inline]
+    pub fn load_percent(&self) -> f32 {
+        f32::from_bits(self.values.load.load(Ordering::Relaxed))
+    }
+
+    /// Returns the highest load of a single block since the meter was created, or since the last
+    /// call to [`reset_peak`](Self::reset_peak), in percent.
+    #[inline]
+    pub fn peak_percent(&self) -> f32 {
+        f32::from_bits(self.values.peak.load(Ordering::Relaxed))
+    }
+
+    /// Resets the peak load value.
+    #[inline]
+    pub fn reset_peak(&self) {
+        self.values.peak.store(0.0f32.to_bits(), Ordering::Relaxed)
+    }
+}
+
+/// A wrapper around a started plugin audio processor, which measures how long each process call
+/// takes compared to the duration of the processed block.
+///
+/// The measured load is both smoothed and peak-held, and can be read from any thread using a
+/// [`LoadMeterHandle`] obtained from [`handle`](Self::handle).
+///
+/// Measurements use a monotonic clock, which is read twice per processed block. Blocks with no
+/// frames are processed normally, but are not measured.
+///
+/// This type is only available when the `load-meter` feature is enabled.
+pub struct PluginLoadMeter<H: HostHandlers> {
+    processor: StartedPluginAudioProcessor<H>,
+    handle: LoadMeterHandle,
+    sample_rate: f64,
+    smoothed: Option<f64>,
+}
+
+impl<H: HostHandlers> PluginLoadMeter<H> {
+    /// Wraps the given started audio processor.
+    ///
+    /// The `configuration` must be the one the plugin was activated with.
+    pub fn new(
+        processor: StartedPluginAudioProcessor<H>,
+        configuration: PluginAudioConfiguration,
+    ) -> Self {
+        Self {
+            processor,
+            handle: LoadMeterHandle {
+                values: Arc::new(LoadMeterValues {
+                    load: AtomicU32::new(0.0f32.to_bits()),
+                    peak: AtomicU32::new(0.0f32.to_bits()),
+                }),
+            },
+            sample_rate: configuration.sample_rate,
+            smoothed: None,
+        }
+    }
+
+    /// Returns a handle to this meter's measured values.
+    #[inline]
+    pub fn handle(&self) -> LoadMeterHandle {
+        self.handle.clone()
+    }
+
+    /// Returns a mutable reference to the wrapped audio processor.
+    #[inline]
+    pub fn processor_mut(&mut self) -> &mut StartedPluginAudioProcessor<H> {
+        &mut self.processor
+    }
+
+    /// Returns the wrapped audio processor.
+    #[inline]
+    pub fn into_inner(self) -> StartedPluginAudioProcessor<H> {
+        self.processor
+    }
+
+    /// Processes a block through the wrapped plugin, and measures its load.
+    ///
+    /// See [`StartedPluginAudioProcessor::process`] for more information about the arguments.
+    ///
+    /// # Errors
+    ///
+    /// This returns the error of the wrapped plugin if it fails to process. Failed process calls
+    /// are not measured.
+    pub fn process(
+        &mut self,
+        audio_inputs: &InputAudioBuffers,
+        audio_outputs: &mut OutputAudioBuffers,
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+        steady_time: Option<u64>,
+        transport: Option<&TransportEvent>,
+    ) -> Result<ProcessStatus, PluginInstanceError> {
+        let frames_count = audio_inputs.min_available_frames_with(audio_outputs);
+
+        let start = Instant::now();
+        let status = self.processor.process(
+            audio_inputs,
+            audio_outputs,
+            input_events,
+            output_events,
+            steady_time,
+            transport,
+        )?;
+        let elapsed = start.elapsed();
+
+        if frames_count > 0 && self.sample_rate > 0.0 {
+            self.record(
+                elapsed.as_secs_f64(),
+                frames_count as f64 / self.sample_rate,
+            );
+        }
+
+        Ok(status)
+    }
+
+    /// Updates the smoothed and peak loads with the measurement of a single block.
+    fn record(&mut self, elapsed: f64, block_duration: f64) {
+        let load = elapsed / block_duration * 100.0;
+
+        let smoothed = match self.smoothed {
+            None => load,
+            Some(previous) => {
+                let amount = 1.0 - (-block_duration / LOAD_SMOOTHING_DURATION).exp();
+                previous + (load - previous) * amount
+            }
+        };
+
+        self.smoothed = Some(smoothed);
+
+        let values = &self.handle.values;
+        values
+            .load
+            .store((smoothed as f32).to_bits(), Ordering::Relaxed);
+
+        // Bit patterns of positive floats are ordered the same way as their values.
+        values
+            .peak
+            .fetch_max((load as f32).to_bits(), Ordering::Relaxed);
+    }
+}
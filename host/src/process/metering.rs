@@ -0,0 +1,713 @@
+//! Peak and RMS level metering of a plugin's audio inputs and outputs.
+//!
+//! See the [`Metering`] type for more information.
+
+use crate::process::audio_buffers::{InputAudioBuffers, OutputAudioBuffers};
+use crate::process::PluginAudioConfiguration;
+use clap_sys::audio_buffer::clap_audio_buffer;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// The default duration of the window RMS levels are computed over, in seconds.
+pub const DEFAULT_RMS_WINDOW: f64 = 0.3;
+
+/// The default duration peaks are held for before they start decaying, in seconds.
+pub const DEFAULT_PEAK_HOLD: f64 = 1.0;
+
+/// The default decay rate of held peaks, in decibels per second.
+pub const DEFAULT_PEAK_DECAY: f64 = 20.0;
+
+/// The number of independent accumulators used when scanning a buffer, which allows the compiler
+/// to vectorize the loops.
+const LANES: usize = 8;
+
+/// Converts a linear gain to decibels.
+///
+/// A gain of `0.0` (silence) is converted to [`f32::NEG_INFINITY`].
+#[inline]
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.log10()
+}
+
+/// A type of audio sample that can be metered, i.e. either `f32` or `f64`.
+pub trait MeterSample: Copy {
+    /// Converts this sample to a 64-bit float.
+    fn to_f64(self) -> f64;
+}
+
+impl MeterSample for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl MeterSample for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// The levels of a single audio channel, as measured by a [`Metering`].
+///
+/// All levels are linear gains, where `1.0` is full scale. Use [`gain_to_db`] or the `*_db`
+/// methods to display them in decibels.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ChannelLevel {
+    /// The highest absolute sample value of the last measured block.
+    pub peak: f32,
+    /// The highest peak of the recent blocks, held then decaying over time.
+    pub held_peak: f32,
+    /// The RMS level over the last RMS window.
+    pub rms: f32,
+}
+
+impl ChannelLevel {
+    /// Returns the [`peak`](Self::peak) level, in decibels.
+    #[inline]
+    pub fn peak_db(&self) -> f32 {
+        gain_to_db(self.peak)
+    }
+
+    /// Returns the [`held_peak`](Self::held_peak) level, in decibels.
+    #[inline]
+    pub fn held_peak_db(&self) -> f32 {
+        gain_to_db(self.held_peak)
+    }
+
+    /// Returns the [`rms`](Self::rms) level, in decibels.
+    #[inline]
+    pub fn rms_db(&self) -> f32 {
+        gain_to_db(self.rms)
+    }
+}
+
+#[derive(Default)]
+struct ChannelValues {
+    peak: AtomicU32,
+    held_peak: AtomicU32,
+    rms: AtomicU32,
+}
+
+impl ChannelValues {
+    fn load(&self) -> ChannelLevel {
+        ChannelLevel {
+            peak: f32::from_bits(self.peak.load(Ordering::Relaxed)),
+            held_peak: f32::from_bits(self.held_peak.load(Ordering::Relaxed)),
+            rms: f32::from_bits(self.rms.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn store(&self, level: ChannelLevel) {
+        self.peak.store(level.peak.to_bits(), Ordering::Relaxed);
+        self.held_peak
+            .store(level.held_peak.to_bits(), Ordering::Relaxed);
+        self.rms.store(level.rms.to_bits(), Ordering::Relaxed);
+    }
+}
+
+struct MeterValues {
+    inputs: Box<[ChannelValues]>,
+    outputs: Box<[ChannelValues]>,
+}
+
+/// A handle to the levels measured by a [`Metering`].
+///
+/// This handle can be cloned and sent to any thread, allowing e.g. the UI thread to display the
+/// plugin's levels while it is processing on the audio thread.
+#[derive(Clone)]
+pub struct MeterHandle {
+    values: Arc<MeterValues>,
+}
+
+impl MeterHandle {
+    /// Returns the number of metered input channels.
+    #[inline]
+    pub fn input_channel_count(&self) -> usize {
+        self.values.inputs.len()
+    }
+
+    /// Returns the number of metered output channels.
+    #[inline]
+    pub fn output_channel_count(&self) -> usize {
+        self.values.outputs.len()
+    }
+
+    /// Returns the levels of the given input channel, or `None` if it isn't metered.
+    #[inline]
+    pub fn input(&self, channel: usize) -> Option<ChannelLevel> {
+        self.values.inputs.get(channel).map(ChannelValues::load)
+    }
+
+    /// Returns the levels of the given output channel, or `None` if it isn't metered.
+    #[inline]
+    pub fn output(&self, channel: usize) -> Option<ChannelLevel> {
+        self.values.outputs.get(channel).map(ChannelValues::load)
+    }
+
+    /// Returns the difference between the RMS levels of the given output and input channels, in
+    /// decibels.
+    ///
+    /// For plugins such as compressors, whose output is expected to be quieter than their input,
+    /// this can be displayed as a gain reduction meter. Hosts should prefer the value reported by
+    /// the plugin itself through the `gain-adjustment-metering` extension when it is available.
+    ///
+    /// This returns `None` if either channel isn't metered, and [`f32::NAN`] if both are silent.
+    pub fn gain_difference_db(&self, channel: usize) -> Option<f32> {
+        let input = self.input(channel)?;
+        let output = self.output(channel)?;
+
+        Some(output.rms_db() - input.rms_db())
+    }
+
+    /// Resets the held peaks of all channels.
+    pub fn reset_peaks(&self) {
+        for channel in self.values.inputs.iter().chain(self.values.outputs.iter()) {
+            channel.held_peak.store(0.0f32.to_bits(), Ordering::Relaxed)
+        }
+    }
+}
+
+/// The audio-thread state of a single metered channel.
+struct ChannelMeter {
+    /// The squares of the last samples, as a ring buffer one RMS window long.
+    squares: Box<[f64]>,
+    position: usize,
+    sum: f64,
+    held_peak: f32,
+    /// The remaining number of frames the held peak is held for.
+    hold_remaining: u64,
+}
+
+impl ChannelMeter {
+    fn new(window_frames: usize) -> Self {
+        Self {
+            squares: vec![0.0; window_frames.max(1)].into_boxed_slice(),
+            position: 0,
+            sum: 0.0,
+            held_peak: 0.0,
+            hold_remaining: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.squares.fill(0.0);
+        self.position = 0;
+        self.sum = 0.0;
+        self.held_peak = 0.0;
+        self.hold_remaining = 0;
+    }
+
+    /// Measures a block of samples, and returns the resulting levels, or `None` if the block is
+    /// empty.
+    fn measure<S: MeterSample>(
+        &mut self,
+        samples: &[S],
+        timing: &PeakTiming,
+    ) -> Option<ChannelLevel> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let peak = peak(samples);
+
+        // Only the last window's worth of samples can contribute to the RMS level.
+        let recent = &samples[samples.len().saturating_sub(self.squares.len())..];
+        let mut remaining = recent;
+
+        while !remaining.is_empty() {
+            let len = remaining.len().min(self.squares.len() - self.position);
+            let (segment, rest) = remaining.split_at(len);
+            let ring = &mut self.squares[self.position..self.position + len];
+
+            self.sum += store_squares(segment, ring);
+            self.position += len;
+
+            // Sum the whole window again once per period, so that rounding errors don't add up.
+            if self.position == self.squares.len() {
+                self.position = 0;
+                self.sum = sum(&self.squares);
+            }
+
+            remaining = rest;
+        }
+
+        let frames = samples.len() as u64;
+
+        if peak >= self.held_peak {
+            self.held_peak = peak;
+            self.hold_remaining = timing.hold_frames;
+        } else if self.hold_remaining >= frames {
+            self.hold_remaining -= frames;
+        } else {
+            let decaying_frames = frames - self.hold_remaining;
+            self.hold_remaining = 0;
+            self.held_peak = (self.held_peak as f64
+                * timing.decay_per_frame.powf(decaying_frames as f64))
+            .max(peak as f64) as f32;
+        }
+
+        Some(ChannelLevel {
+            peak,
+            held_peak: self.held_peak,
+            rms: (self.sum.max(0.0) / self.squares.len() as f64).sqrt() as f32,
+        })
+    }
+}
+
+struct PeakTiming {
+    hold_frames: u64,
+    /// The factor held peaks are multiplied by for every decaying frame.
+    decay_per_frame: f64,
+}
+
+impl PeakTiming {
+    fn new(sample_rate: f64, hold: f64, decay: f64) -> Self {
+        Self {
+            hold_frames: (sample_rate * hold).max(0.0) as u64,
+            decay_per_frame: 10f64.powf(-decay.max(0.0) / 20.0 / sample_rate),
+        }
+    }
+}
+
+/// Returns the highest absolute value of the given samples.
+fn peak<S: MeterSample>(samples: &[S]) -> f32 {
+    let mut lanes = [0.0f64; LANES];
+    let chunks = samples.chunks_exact(LANES);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        for (lane, sample) in lanes.iter_mut().zip(chunk) {
+            *lane = lane.max(sample.to_f64().abs());
+        }
+    }
+
+    for (lane, sample) in lanes.iter_mut().zip(remainder) {
+        *lane = lane.max(sample.to_f64().abs());
+    }
+
+    lanes.into_iter().fold(0.0, f64::max) as f32
+}
+
+/// Stores the squares of the given samples into `ring`, which must have the same length, and
+/// returns the sum of the new squares minus the sum of the old ones.
+fn store_squares<S: MeterSample>(samples: &[S], ring: &mut [f64]) -> f64 {
+    let removed = sum(ring);
+
+    for (square, sample) in ring.iter_mut().zip(samples) {
+        let sample = sample.to_f64();
+        *square = sample * sample;
+    }
+
+    sum(ring) - removed
+}
+
+fn sum(values: &[f64]) -> f64 {
+    let mut lanes = [0.0f64; LANES];
+    let chunks = values.chunks_exact(LANES);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        for (lane, value) in lanes.iter_mut().zip(chunk) {
+            *lane += value;
+        }
+    }
+
+    lanes.iter().sum::<f64>() + remainder.iter().sum::<f64>()
+}
+
+/// A peak and RMS level meter for a plugin's audio outputs and, optionally, inputs.
+///
+/// The meter is fed the audio buffers of each block after the plugin processed them, using
+/// [`measure_output_buffers`](Self::measure_output_buffers) (and
+/// [`measure_input_buffers`](Self::measure_input_buffers)), or the slice-based
+/// [`measure_outputs`](Self::measure_outputs) and [`measure_inputs`](Self::measure_inputs). Both
+/// 32-bit and 64-bit float samples are supported.
+///
+/// For each channel, this measures:
+///
+/// * the peak level of the last block;
+/// * a held peak level, which is held for [`DEFAULT_PEAK_HOLD`] seconds, then decays at
+///   [`DEFAULT_PEAK_DECAY`] decibels per second;
+/// * the RMS level over a sliding window of [`DEFAULT_RMS_WINDOW`] seconds.
+///
+/// These durations can be configured using [`with_rms_window`](Self::with_rms_window),
+/// [`with_peak_hold`](Self::with_peak_hold) and [`with_peak_decay`](Self::with_peak_decay).
+///
+/// The measured levels can be read from any thread using a [`MeterHandle`] obtained from
+/// [`handle`](Self::handle). Metering inputs as well allows displaying the difference between
+/// input and output levels, see [`MeterHandle::gain_difference_db`].
+///
+/// All buffers are allocated when the meter is created or configured, so this should be done on
+/// the main thread, e.g. right after the plugin is activated. Measuring never allocates.
+pub struct Metering {
+    handle: MeterHandle,
+    inputs: Vec<ChannelMeter>,
+    outputs: Vec<ChannelMeter>,
+    sample_rate: f64,
+    peak_hold: f64,
+    peak_decay: f64,
+    timing: PeakTiming,
+}
+
+impl Metering {
+    /// Creates a new meter for the given number of input and output channels.
+    ///
+    /// The `configuration` must be the one the plugin was activated with. Inputs are not metered
+    /// if `input_channel_count` is zero.
+    pub fn new(
+        configuration: PluginAudioConfiguration,
+        input_channel_count: usize,
+        output_channel_count: usize,
+    ) -> Self {
+        let sample_rate = configuration.sample_rate;
+        let window_frames = window_frames(sample_rate, DEFAULT_RMS_WINDOW);
+
+        let channels = |count| -> Box<[ChannelValues]> {
+            (0..count).map(|_| ChannelValues::default()).collect()
+        };
+
+        Self {
+            handle: MeterHandle {
+                values: Arc::new(MeterValues {
+                    inputs: channels(input_channel_count),
+                    outputs: channels(output_channel_count),
+                }),
+            },
+            inputs: (0..input_channel_count)
+                .map(|_| ChannelMeter::new(window_frames))
+                .collect(),
+            outputs: (0..output_channel_count)
+                .map(|_| ChannelMeter::new(window_frames))
+                .collect(),
+            sample_rate,
+            peak_hold: DEFAULT_PEAK_HOLD,
+            peak_decay: DEFAULT_PEAK_DECAY,
+            timing: PeakTiming::new(sample_rate, DEFAULT_PEAK_HOLD, DEFAULT_PEAK_DECAY),
+        }
+    }
+
+    /// Sets the duration of the window RMS levels are computed over, in seconds.
+    ///
+    /// This resets the meter.
+    pub fn with_rms_window(mut self, seconds: f64) -> Self {
+        let window_frames = window_frames(self.sample_rate, seconds);
+
+        for meter in self.inputs.iter_mut().chain(self.outputs.iter_mut()) {
+            *meter = ChannelMeter::new(window_frames);
+        }
+
+        self.publish_reset();
+        self
+    }
+
+    /// Sets the duration peaks are held for before they start decaying, in seconds.
+    pub fn with_peak_hold(mut self, seconds: f64) -> Self {
+        self.peak_hold = seconds;
+        self.timing = PeakTiming::new(self.sample_rate, self.peak_hold, self.peak_decay);
+        self
+    }
+
+    /// Sets the decay rate of held peaks, in decibels per second.
+    pub fn with_peak_decay(mut self, db_per_second: f64) -> Self {
+        self.peak_decay = db_per_second;
+        self.timing = PeakTiming::new(self.sample_rate, self.peak_hold, self.peak_decay);
+        self
+    }
+
+    /// Returns a handle to this meter's measured levels.
+    #[inline]
+    pub fn handle(&self) -> MeterHandle {
+        self.handle.clone()
+    }
+
+    /// Resets all the measured levels to silence, e.g. after the plugin was reset.
+    pub fn reset(&mut self) {
+        for meter in self.inputs.iter_mut().chain(self.outputs.iter_mut()) {
+            meter.reset();
+        }
+
+        self.publish_reset();
+    }
+
+    /// Measures a block of output samples, with one slice per channel.
+    ///
+    /// Channels beyond the number of metered output channels are ignored, as well as empty ones.
+    pub fn measure_outputs<S: MeterSample, C: AsRef<[S]>>(&mut self, channels: &[C]) {
+        for ((meter, values), channel) in self
+            .outputs
+            .iter_mut()
+            .zip(self.handle.values.outputs.iter())
+            .zip(channels)
+        {
+            if let Some(level) = meter.measure(channel.as_ref(), &self.timing) {
+                values.store(level);
+            }
+        }
+    }
+
+    /// Measures a block of input samples, with one slice per channel.
+    ///
+    /// Channels beyond the number of metered input channels are ignored, as well as empty ones.
+    pub fn measure_inputs<S: MeterSample, C: AsRef<[S]>>(&mut self, channels: &[C]) {
+        for ((meter, values), channel) in self
+            .inputs
+            .iter_mut()
+            .zip(self.handle.values.inputs.iter())
+            .zip(channels)
+        {
+            if let Some(level) = meter.measure(channel.as_ref(), &self.timing) {
+                values.store(level);
+            }
+        }
+    }
+
+    /// Measures the given output buffers, e.g. right after they were processed by the plugin.
+    ///
+    /// The channels of all ports are metered in order: the first channel of the second port comes
+    /// right after the last channel of the first port. Missing channel buffers are skipped.
+    pub fn measure_output_buffers(&mut self, outputs: &mut OutputAudioBuffers) {
+        let frames_count = outputs.frames_count().unwrap_or(0) as usize;
+
+        // SAFETY: the OutputAudioBuffers type ensures its buffers are valid for frames_count.
+        unsafe {
+            measure_raw(
+                outputs.as_raw_buffers(),
+                frames_count,
+                &mut self.outputs,
+                &self.handle.values.outputs,
+                &self.timing,
+            )
+        }
+    }
+
+    /// Measures the given input buffers, e.g. right after they were processed by the plugin.
+    ///
+    /// See [`measure_output_buffers`](Self::measure_output_buffers).
+    pub fn measure_input_buffers(&mut self, inputs: &InputAudioBuffers) {
+        let frames_count = inputs.frames_count().unwrap_or(0) as usize;
+
+        // SAFETY: the InputAudioBuffers type ensures its buffers are valid for frames_count.
+        unsafe {
+            measure_raw(
+                inputs.as_raw_buffers(),
+                frames_count,
+                &mut self.inputs,
+                &self.handle.values.inputs,
+                &self.timing,
+            )
+        }
+    }
+
+    fn publish_reset(&self) {
+        let values = &self.handle.values;
+
+        for channel in values.inputs.iter().chain(values.outputs.iter()) {
+            channel.store(ChannelLevel::default());
+        }
+    }
+}
+
+fn window_frames(sample_rate: f64, seconds: f64) -> usize {
+    (sample_rate * seconds).round().max(1.0) as usize
+}
+
+/// Measures all the channels of the given raw buffers, in order.
+///
+/// # Safety
+///
+/// The caller must ensure all the non-null channel pointers of the given buffers are valid for
+/// reads of `frames_count` samples.
+unsafe fn measure_raw(
+    buffers: &[clap_audio_buffer],
+    frames_count: usize,
+    meters: &mut [ChannelMeter],
+    values: &[ChannelValues],
+    timing: &PeakTiming,
+) {
+    let mut channels = meters.iter_mut().zip(values);
+
+    for buffer in buffers {
+        for index in 0..buffer.channel_count as usize {
+            let Some((meter, values)) = channels.next() else {
+                return;
+            };
+
+            let level = if !buffer.data32.is_null() {
+                let channel = *buffer.data32.add(index);
+                if channel.is_null() {
+                    continue;
+                }
+
+                meter.measure(core::slice::from_raw_parts(channel, frames_count), timing)
+            } else if !buffer.data64.is_null() {
+                let channel = *buffer.data64.add(index);
+                if channel.is_null() {
+                    continue;
+                }
+
+                meter.measure(core::slice::from_raw_parts(channel, frames_count), timing)
+            } else {
+                continue;
+            };
+
+            if let Some(level) = level {
+                values.store(level);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::audio_buffers::{
+        AudioPortBuffer, AudioPortBufferType, AudioPorts, InputChannel,
+    };
+    use std::f64::consts::TAU;
+
+    const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 1,
+        max_frames_count: 512,
+    };
+
+    /// A full-scale 1kHz sine, which period is exactly 48 frames.
+    fn sine(frames: usize) -> Vec<f64> {
+        (0..frames)
+            .map(|i| (TAU * 1_000.0 * i as f64 / CONFIGURATION.sample_rate).sin())
+            .collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn assert_full_scale_sine(handle: &MeterHandle) {
+        let level = handle.output(0).unwrap();
+        assert_close(level.rms_db(), -3.0103);
+        assert_close(level.peak_db(), 0.0);
+        assert_close(level.held_peak_db(), 0.0);
+    }
+
+    #[test]
+    fn full_scale_sines_are_measured() {
+        let signal = sine(480 * 4);
+        let signal32: Vec<f32> = signal.iter().map(|&s| s as f32).collect();
+
+        let mut metering = Metering::new(CONFIGURATION, 0, 1).with_rms_window(0.01);
+        let handle = metering.handle();
+        for block in signal.chunks(300) {
+            metering.measure_outputs(&[block]);
+        }
+        assert_full_scale_sine(&handle);
+
+        let mut metering = Metering::new(CONFIGURATION, 0, 1).with_rms_window(0.01);
+        let handle = metering.handle();
+        for block in signal32.chunks(300) {
+            metering.measure_outputs(&[block]);
+        }
+        assert_full_scale_sine(&handle);
+
+        // Empty blocks don't change anything.
+        metering.measure_outputs::<f32, &[f32]>(&[&[]]);
+        assert_full_scale_sine(&handle);
+        assert_eq!(handle.output(1), None);
+    }
+
+    #[test]
+    fn rms_follows_the_window() {
+        let mut metering = Metering::new(CONFIGURATION, 0, 1).with_rms_window(0.01);
+        let handle = metering.handle();
+
+        // Half of the window is at full scale, the other half is silent.
+        metering.measure_outputs(&[[1.0f32; 480]]);
+        metering.measure_outputs(&[[0.0f32; 240]]);
+        assert_close(handle.output(0).unwrap().rms, 0.5f32.sqrt());
+
+        // Old samples leave the window.
+        metering.measure_outputs(&[[0.5f32; 960]]);
+        assert_close(handle.output(0).unwrap().rms, 0.5);
+
+        metering.reset();
+        assert_eq!(handle.output(0).unwrap(), ChannelLevel::default());
+        assert_eq!(handle.output(0).unwrap().rms_db(), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn held_peaks_decay_after_the_hold_time() {
+        let mut metering = Metering::new(CONFIGURATION, 0, 1)
+            .with_peak_hold(0.01)
+            .with_peak_decay(60.0);
+        let handle = metering.handle();
+
+        metering.measure_outputs(&[[-0.5f32, 1.0, 0.25]]);
+        let level = handle.output(0).unwrap();
+        assert_eq!(level.peak, 1.0);
+        assert_eq!(level.held_peak, 1.0);
+
+        // Still held.
+        metering.measure_outputs(&[[0.0f32; 480]]);
+        let level = handle.output(0).unwrap();
+        assert_eq!(level.peak, 0.0);
+        assert_eq!(level.held_peak, 1.0);
+
+        // 100ms at 60dB/s.
+        metering.measure_outputs(&[[0.0f32; 4800]]);
+        assert_close(handle.output(0).unwrap().held_peak_db(), -6.0);
+
+        // New peaks above the decayed one are held again.
+        metering.measure_outputs(&[[0.75f32; 4]]);
+        assert_eq!(handle.output(0).unwrap().held_peak, 0.75);
+
+        handle.reset_peaks();
+        assert_eq!(handle.output(0).unwrap().held_peak, 0.0);
+    }
+
+    #[test]
+    fn audio_buffers_are_measured_across_ports() {
+        let mut metering = Metering::new(CONFIGURATION, 1, 3).with_rms_window(0.001);
+        let handle = metering.handle();
+
+        let mut input = vec![0.5f32; 48];
+        let mut left = vec![1.0f32; 48];
+        let mut right = vec![0.25f32; 48];
+        let mut aux = vec![-0.5f64; 48];
+
+        let mut input_ports = AudioPorts::with_capacity(1, 1);
+        let inputs = input_ports.with_input_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_input_only(
+                [InputChannel::variable(&mut input)].into_iter(),
+            ),
+            latency: 0,
+        }]);
+
+        let mut output_ports = AudioPorts::with_capacity(3, 2);
+        let mut outputs = output_ports.with_output_buffers([
+            AudioPortBuffer {
+                channels: AudioPortBufferType::F32(vec![left.as_mut_slice(), right.as_mut_slice()]),
+                latency: 0,
+            },
+            AudioPortBuffer {
+                channels: AudioPortBufferType::F64(vec![aux.as_mut_slice()]),
+                latency: 0,
+            },
+        ]);
+
+        metering.measure_input_buffers(&inputs);
+        metering.measure_output_buffers(&mut outputs);
+
+        assert_close(handle.output(0).unwrap().rms, 1.0);
+        assert_close(handle.output(1).unwrap().rms, 0.25);
+        assert_close(handle.output(2).unwrap().rms, 0.5);
+        assert_close(handle.input(0).unwrap().rms, 0.5);
+
+        // The first output channel is 6dB louder than the first input channel.
+        assert_close(handle.gain_difference_db(0).unwrap(), 6.0206);
+        assert_eq!(handle.gain_difference_db(1), None);
+    }
+}
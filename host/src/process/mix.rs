@@ -0,0 +1,645 @@
+//! Summing of multiple plugin outputs into a single set of buffers, e.g. for mix buses.
+//!
+//! See the [`MixNode`] type for more information.
+
+use crate::process::audio_buffers::OutputAudioBuffers;
+use clap_sys::audio_buffer::clap_audio_buffer;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// The number of samples processed at once by the mixing loops, which allows the compiler to
+/// vectorize them.
+const LANES: usize = 8;
+
+/// The input level at which the soft clipper reaches full scale.
+const SOFT_CLIP_KNEE: f64 = 1.5;
+
+/// A type of audio sample that can be mixed, i.e. either `f32` or `f64`.
+pub trait MixSample:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    /// The smallest positive normal value of this type. Smaller values are flushed to zero.
+    const MIN_POSITIVE: Self;
+
+    /// Converts a 64-bit float to this sample type.
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts this sample to a 64-bit float.
+    fn to_f64(self) -> f64;
+}
+
+impl MixSample for f32 {
+    const MIN_POSITIVE: Self = f32::MIN_POSITIVE;
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl MixSample for f64 {
+    const MIN_POSITIVE: Self = f64::MIN_POSITIVE;
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// Sums the audio outputs of multiple sources into a single destination, e.g. to build a mix bus
+/// or the return of a send effect.
+///
+/// Each source has its own gain, which is `1.0` by default. The sum can optionally be soft-clipped
+/// (see [`with_soft_clip`](Self::with_soft_clip)), and any denormal value resulting from the mix
+/// is flushed to zero.
+///
+/// Sources can be given either as [`OutputAudioBuffers`], e.g. the outputs of plugins processed
+/// by the host, using [`mix_buffers`](Self::mix_buffers), or as plain channel slices, e.g. the
+/// outputs of a [`StartedPluginChain`](crate::process::chain::StartedPluginChain), using
+/// [`mix`](Self::mix). Both 32-bit and 64-bit float samples are supported.
+///
+/// # Constant channels
+///
+/// When mixing [`OutputAudioBuffers`], the constant masks of the sources are honored: sources
+/// whose channel is constant are only read once, and are skipped entirely if silent. If all the
+/// sources of a destination channel are constant, that channel is filled with the constant sum,
+/// and flagged as constant in the destination's mask.
+///
+/// # Example
+///
+/// A send/return topology, where a dry signal and the output of a reverb chain are summed at
+/// half the reverb's level:
+///
+/// ```
+/// use clack_host::process::mix::MixNode;
+///
+/// let mut mix = MixNode::new(2);
+/// mix.set_gain(1, 0.5);
+///
+/// let dry = [vec![1.0f32; 4], vec![-1.0f32; 4]];
+/// // This would be the output of the reverb's StartedPluginChain.
+/// let reverb = [vec![0.5f32; 4], vec![0.5f32; 4]];
+///
+/// let mut bus = [vec![0.0f32; 4], vec![0.0f32; 4]];
+/// mix.mix(&[&dry[..], &reverb[..]], &mut bus).unwrap();
+///
+/// assert_eq!(bus[0], [1.25; 4]);
+/// assert_eq!(bus[1], [-0.75; 4]);
+/// ```
+///
+/// # Realtime Safety
+///
+/// Mixing never allocates, and can be done on the audio thread.
+#[derive(Clone, Debug)]
+pub struct MixNode {
+    gains: Vec<f32>,
+    soft_clip: bool,
+}
+
+impl MixNode {
+    /// Creates a new mix node summing the given number of sources, all with unity gain.
+    pub fn new(source_count: usize) -> Self {
+        Self {
+            gains: vec![1.0; source_count],
+            soft_clip: false,
+        }
+    }
+
+    /// Sets whether the sum is soft-clipped.
+    ///
+    /// Soft clipping leaves quiet signals mostly untouched, and smoothly saturates louder ones,
+    /// so that the output never exceeds full scale.
+    pub fn with_soft_clip(mut self, soft_clip: bool) -> Self {
+        self.soft_clip = soft_clip;
+        self
+    }
+
+    /// Returns the number of sources this node sums.
+    #[inline]
+    pub fn source_count(&self) -> usize {
+        self.gains.len()
+    }
+
+    /// Returns the gain of the source at the given index, or `None` if the index is out of bounds.
+    #[inline]
+    pub fn gain(&self, source: usize) -> Option<f32> {
+        self.gains.get(source).copied()
+    }
+
+    /// Sets the gain of the source at the given index.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `source` is out of bounds.
+    #[inline]
+    pub fn set_gain(&mut self, source: usize, gain: f32) {
+        self.gains[source] = gain;
+    }
+
+    /// Returns `true` if the sum is soft-clipped.
+    #[inline]
+    pub fn soft_clip(&self) -> bool {
+        self.soft_clip
+    }
+
+    /// Sets whether the sum is soft-clipped.
+    ///
+    /// See [`with_soft_clip`](Self::with_soft_clip).
+    #[inline]
+    pub fn set_soft_clip(&mut self, soft_clip: bool) {
+        self.soft_clip = soft_clip;
+    }
+
+    /// Sums the given sources into the given destination, with one slice per channel.
+    ///
+    /// Each element of `sources` contains the channels of the source at the same index. Sources
+    /// with fewer channels than the destination don't contribute to the extra channels, and
+    /// source channels beyond the destination's channel count are ignored.
+    ///
+    /// The number of frames mixed is the length of the shortest channel.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`MixError::SourceCountMismatch`] if the number of sources does not match
+    /// this node's [`source_count`](Self::source_count).
+    pub fn mix<S: MixSample, C: AsRef<[S]>, D: AsMut<[S]>>(
+        &self,
+        sources: &[&[C]],
+        destination: &mut [D],
+    ) -> Result<(), MixError> {
+        self.check_source_count(sources.len())?;
+
+        let frames_count = sources
+            .iter()
+            .flat_map(|channels| channels.iter().map(|c| c.as_ref().len()))
+            .chain(destination.iter_mut().map(|c| c.as_mut().len()))
+            .min()
+            .unwrap_or(0);
+
+        for (index, destination) in destination.iter_mut().enumerate() {
+            let mut channel = ChannelMix::new(&mut destination.as_mut()[..frames_count]);
+
+            for (source, gain) in sources.iter().zip(&self.gains) {
+                if let Some(samples) = source.get(index) {
+                    channel.add(&samples.as_ref()[..frames_count], false, *gain);
+                }
+            }
+
+            channel.finish(self.soft_clip);
+        }
+
+        Ok(())
+    }
+
+    /// Sums the given source buffers into the given destination buffers.
+    ///
+    /// The channels of all ports are mixed in order: the first channel of the second port comes
+    /// right after the last channel of the first port. Sources with fewer channels than the
+    /// destination don't contribute to the extra channels, and missing channel buffers are
+    /// skipped. Sources and destination may use different sample types.
+    ///
+    /// The number of frames mixed is the smallest [`frames_count`] of the destination and all
+    /// the non-empty sources.
+    ///
+    /// The destination's constant masks are updated to reflect the constant channels of the sum.
+    /// See the [type documentation](Self#constant-channels) for more information.
+    ///
+    /// # Errors
+    ///
+    /// This returns [`MixError::SourceCountMismatch`] if the number of sources does not match
+    /// this node's [`source_count`](Self::source_count).
+    ///
+    /// [`frames_count`]: OutputAudioBuffers::frames_count
+    pub fn mix_buffers(
+        &self,
+        sources: &mut [OutputAudioBuffers],
+        destination: &mut OutputAudioBuffers,
+    ) -> Result<(), MixError> {
+        self.check_source_count(sources.len())?;
+
+        let Some(frames_count) = sources
+            .iter()
+            .filter_map(|source| source.frames_count())
+            .chain(destination.frames_count())
+            .min()
+        else {
+            return Ok(());
+        };
+        let frames_count = frames_count as usize;
+
+        let mut index = 0;
+
+        for port in destination.as_raw_buffers() {
+            let mut constant_mask = 0;
+
+            for channel_index in 0..port.channel_count as usize {
+                // SAFETY: the OutputAudioBuffers type ensures its buffers are valid for
+                // frames_count.
+                let is_constant = unsafe {
+                    match RawChannel::from_raw(port, channel_index) {
+                        Some(RawChannel::F32(ptr)) => self.mix_raw_channel(
+                            core::slice::from_raw_parts_mut(ptr, frames_count),
+                            sources,
+                            index,
+                        ),
+                        Some(RawChannel::F64(ptr)) => self.mix_raw_channel(
+                            core::slice::from_raw_parts_mut(ptr, frames_count),
+                            sources,
+                            index,
+                        ),
+                        None => false,
+                    }
+                };
+
+                if is_constant && channel_index < 64 {
+                    constant_mask |= 1 << channel_index;
+                }
+
+                index += 1;
+            }
+
+            port.constant_mask = constant_mask;
+        }
+
+        Ok(())
+    }
+
+    /// Sums the channel at the given flattened index of all sources into the given destination.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure all the sources are valid for reads of `destination.len()` samples.
+    unsafe fn mix_raw_channel<D: MixSample>(
+        &self,
+        destination: &mut [D],
+        sources: &mut [OutputAudioBuffers],
+        index: usize,
+    ) -> bool {
+        let frames_count = destination.len();
+        let mut channel = ChannelMix::new(destination);
+
+        for (source, gain) in sources.iter_mut().zip(&self.gains) {
+            let Some((raw, is_constant)) = find_channel(source.as_raw_buffers(), index) else {
+                continue;
+            };
+
+            match raw {
+                RawChannel::F32(ptr) => channel.add(
+                    core::slice::from_raw_parts(ptr, frames_count),
+                    is_constant,
+                    *gain,
+                ),
+                RawChannel::F64(ptr) => channel.add(
+                    core::slice::from_raw_parts(ptr, frames_count),
+                    is_constant,
+                    *gain,
+                ),
+            }
+        }
+
+        channel.finish(self.soft_clip)
+    }
+
+    fn check_source_count(&self, source_count: usize) -> Result<(), MixError> {
+        if source_count != self.gains.len() {
+            return Err(MixError::SourceCountMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// A pointer to the samples of a single channel of a raw audio buffer.
+#[derive(Copy, Clone)]
+enum RawChannel {
+    F32(*mut f32),
+    F64(*mut f64),
+}
+
+impl RawChannel {
+    /// Returns the channel at the given index of the given port, or `None` if it is missing.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the given buffer's channel pointer arrays are valid.
+    unsafe fn from_raw(port: &clap_audio_buffer, index: usize) -> Option<Self> {
+        if index >= port.channel_count as usize {
+            return None;
+        }
+
+        if !port.data32.is_null() {
+            let ptr = *port.data32.add(index);
+            (!ptr.is_null()).then_some(Self::F32(ptr as *mut f32))
+        } else if !port.data64.is_null() {
+            let ptr = *port.data64.add(index);
+            (!ptr.is_null()).then_some(Self::F64(ptr as *mut f64))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the channel at the given flattened index of the given ports, along with whether it is
+/// constant.
+///
+/// # Safety
+///
+/// The caller must ensure the given buffers' channel pointer arrays are valid.
+unsafe fn find_channel(
+    ports: &[clap_audio_buffer],
+    mut index: usize,
+) -> Option<(RawChannel, bool)> {
+    for port in ports {
+        let channel_count = port.channel_count as usize;
+
+        if index < channel_count {
+            let is_constant = index < 64 && port.constant_mask & (1 << index) != 0;
+            return RawChannel::from_raw(port, index).map(|raw| (raw, is_constant));
+        }
+
+        index -= channel_count;
+    }
+
+    None
+}
+
+/// The sum of all the sources of a single destination channel.
+struct ChannelMix<'a, D> {
+    destination: &'a mut [D],
+    /// The sum of all constant sources, which is only added once all sources are mixed.
+    constant_sum: f64,
+    /// Whether a non-constant source has been written to the destination yet.
+    has_variable_source: bool,
+}
+
+impl<'a, D: MixSample> ChannelMix<'a, D> {
+    fn new(destination: &'a mut [D]) -> Self {
+        Self {
+            destination,
+            constant_sum: 0.0,
+            has_variable_source: false,
+        }
+    }
+
+    fn add<S: MixSample>(&mut self, source: &[S], is_constant: bool, gain: f32) {
+        if gain == 0.0 || source.is_empty() {
+            return;
+        }
+
+        if is_constant {
+            self.constant_sum += source[0].to_f64() * gain as f64;
+            return;
+        }
+
+        let gain = D::from_f64(gain as f64);
+
+        if self.has_variable_source {
+            for_each_lane(self.destination, source, |d, s| {
+                *d = *d + D::from_f64(s.to_f64()) * gain
+            });
+        } else {
+            for_each_lane(self.destination, source, |d, s| {
+                *d = D::from_f64(s.to_f64()) * gain
+            });
+            self.has_variable_source = true;
+        }
+    }
+
+    /// Finalizes the sum, and returns `true` if the resulting channel is constant.
+    fn finish(self, soft_clip: bool) -> bool {
+        if !self.has_variable_source {
+            let value = process_sample(D::from_f64(self.constant_sum), soft_clip);
+            self.destination.fill(value);
+            return true;
+        }
+
+        let constant = D::from_f64(self.constant_sum);
+        let add_constant = self.constant_sum != 0.0;
+
+        for chunk in self.destination.chunks_mut(LANES) {
+            for sample in chunk {
+                let value = if add_constant {
+                    *sample + constant
+                } else {
+                    *sample
+                };
+
+                *sample = process_sample(value, soft_clip);
+            }
+        }
+
+        false
+    }
+}
+
+/// Calls the given closure on each pair of destination and source samples, in fixed-size chunks
+/// so that the compiler can vectorize it.
+#[inline]
+fn for_each_lane<D: Copy, S: Copy>(
+    destination: &mut [D],
+    source: &[S],
+    mut f: impl FnMut(&mut D, S),
+) {
+    let mut destination_chunks = destination.chunks_exact_mut(LANES);
+    let mut source_chunks = source.chunks_exact(LANES);
+
+    for (destination, source) in (&mut destination_chunks).zip(&mut source_chunks) {
+        for (d, s) in destination.iter_mut().zip(source) {
+            f(d, *s);
+        }
+    }
+
+    let destination = destination_chunks.into_remainder();
+    for (d, s) in destination.iter_mut().zip(source_chunks.remainder()) {
+        f(d, *s);
+    }
+}
+
+/// Applies the optional soft clipping to the given sample, and flushes it to zero if denormal.
+#[inline]
+fn process_sample<D: MixSample>(sample: D, soft_clip: bool) -> D {
+    let sample = if soft_clip {
+        let knee = D::from_f64(SOFT_CLIP_KNEE);
+        let sample = if sample > knee {
+            knee
+        } else if sample < -knee {
+            -knee
+        } else {
+            sample
+        };
+
+        // A cubic curve with unity slope at zero, reaching exactly ±1 with a zero slope at the
+        // knee.
+        let factor = D::from_f64(4.0 / 27.0);
+        sample - sample * sample * sample * factor
+    } else {
+        sample
+    };
+
+    if sample < D::MIN_POSITIVE && sample > -D::MIN_POSITIVE {
+        D::from_f64(0.0)
+    } else {
+        sample
+    }
+}
+
+/// Errors that can occur while mixing with a [`MixNode`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MixError {
+    /// The number of given sources does not match the mix node's source count.
+    SourceCountMismatch,
+}
+
+impl Display for MixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SourceCountMismatch => f.write_str("Source count does not match the mix node's"),
+        }
+    }
+}
+
+impl Error for MixError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::process::audio_buffers::{AudioPortBuffer, AudioPortBufferType, AudioPorts};
+
+    #[test]
+    pub fn sums_sources_with_gains() {
+        let mut mix = MixNode::new(3);
+        mix.set_gain(1, 0.5);
+        mix.set_gain(2, -2.0);
+
+        let a = [vec![1.0f64; 19]];
+        let b = [vec![2.0f64; 19]];
+        let c = [(0..19).map(|i| i as f64).collect::<Vec<_>>()];
+        let mut out = [vec![42.0f64; 19]];
+
+        mix.mix(&[&a[..], &b[..], &c[..]], &mut out).unwrap();
+
+        for (i, sample) in out[0].iter().enumerate() {
+            assert_eq!(*sample, 2.0 - 2.0 * i as f64);
+        }
+
+        assert_eq!(
+            mix.mix(&[&a[..]], &mut out),
+            Err(MixError::SourceCountMismatch)
+        );
+    }
+
+    #[test]
+    pub fn soft_clips_the_sum() {
+        let mix = MixNode::new(2).with_soft_clip(true);
+
+        let a = [vec![0.0f32, 0.01, 1.0, 4.0, -4.0]];
+        let b = [vec![0.0f32, 0.0, 1.0, 4.0, -4.0]];
+        let mut out = [vec![0.0f32; 5]];
+
+        mix.mix(&[&a[..], &b[..]], &mut out).unwrap();
+
+        assert_eq!(out[0][0], 0.0);
+        assert!((out[0][1] - 0.01).abs() < 1e-6);
+        assert!(out[0][2] <= 1.0 && out[0][2] > 0.9);
+        assert_eq!(out[0][3], 1.0);
+        assert_eq!(out[0][4], -1.0);
+    }
+
+    #[test]
+    pub fn flushes_denormals_to_zero() {
+        let mut mix = MixNode::new(2);
+        mix.set_gain(1, 0.5);
+
+        let denormal = f32::MIN_POSITIVE / 4.0;
+        assert!(denormal.is_subnormal());
+
+        let a = [vec![denormal; 16]];
+        let b = [vec![f32::MIN_POSITIVE; 16]];
+        let mut out = [vec![1.0f32; 16]];
+
+        mix.mix(&[&a[..], &b[..]], &mut out).unwrap();
+
+        assert!(out[0].iter().all(|s| *s == 0.0 && !s.is_subnormal()));
+    }
+
+    #[test]
+    pub fn propagates_constant_channels() {
+        let mix = MixNode::new(2);
+
+        let mut a_left = [0.5f32; 8];
+        let mut a_right = [0.0f32; 8];
+        let mut b_left = [0.25f32; 8];
+        let mut b_right: Vec<f32> = (0..8).map(|i| i as f32).collect();
+        let mut out_left = [0.0f32; 8];
+        let mut out_right = [0.0f32; 8];
+
+        let mut ports_a = AudioPorts::with_capacity(2, 1);
+        let mut ports_b = AudioPorts::with_capacity(2, 1);
+        let mut ports_out = AudioPorts::with_capacity(2, 1);
+
+        let a = ports_a.with_output_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f32_output_only([&mut a_left[..], &mut a_right[..]]),
+        }]);
+        let b = ports_b.with_output_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f32_output_only([&mut b_left[..], &mut b_right[..]]),
+        }]);
+        let mut out = ports_out.with_output_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f32_output_only([&mut out_left[..], &mut out_right[..]]),
+        }]);
+
+        let mut sources = [a, b];
+        // Both left channels are constant, and the right channel of A is constant silence.
+        sources[0].as_raw_buffers()[0].constant_mask = 0b11;
+        sources[1].as_raw_buffers()[0].constant_mask = 0b01;
+
+        mix.mix_buffers(&mut sources, &mut out).unwrap();
+
+        assert_eq!(out.as_raw_buffers()[0].constant_mask, 0b01);
+        assert_eq!(out_left, [0.75; 8]);
+        assert_eq!(out_right, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    pub fn mixes_f64_sources_into_f32_destination() {
+        let mix = MixNode::new(1);
+
+        let mut source = [0.5f64; 4];
+        let mut destination = [0.0f32; 4];
+
+        let mut source_ports = AudioPorts::with_capacity(1, 1);
+        let mut destination_ports = AudioPorts::with_capacity(1, 1);
+
+        let source = source_ports.with_output_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f64_output_only([&mut source[..]]),
+        }]);
+        let mut out = destination_ports.with_output_buffers([AudioPortBuffer {
+            latency: 0,
+            channels: AudioPortBufferType::f32_output_only([&mut destination[..]]),
+        }]);
+
+        mix.mix_buffers(&mut [source], &mut out).unwrap();
+
+        assert_eq!(out.as_raw_buffers()[0].constant_mask, 0);
+        assert_eq!(destination, [0.5; 4]);
+    }
+}
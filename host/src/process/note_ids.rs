@@ -0,0 +1,558 @@
+//! Generation and lifecycle tracking of the note IDs the host sends to plugins.
+//!
+//! See the [`ActiveNoteTracker`] type for more information.
+
+use clack_common::events::event_types::*;
+use clack_common::events::io::{EventBuffer, InputEvents};
+use clack_common::events::spaces::CoreEventSpace;
+use clack_common::events::{Match, Pckn};
+use clack_common::utils::{ClapId, Cookie};
+
+/// The highest note ID that can be sent to a plugin.
+///
+/// Note IDs are 32-bit signed integers in CLAP, and negative values are reserved for wildcards.
+pub const MAX_NOTE_ID: u32 = i32::MAX as u32;
+
+/// Generates monotonically increasing note IDs.
+///
+/// IDs start at `0`, and wrap around back to `0` after [`MAX_NOTE_ID`]. IDs that are still in use
+/// are skipped, so that two live notes never share the same ID.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct NoteIdAllocator {
+    next: u32,
+}
+
+impl NoteIdAllocator {
+    /// Creates a new allocator, whose first ID is `0`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { next: 0 }
+    }
+
+    /// Creates a new allocator, whose first ID is the given one.
+    ///
+    /// IDs greater than [`MAX_NOTE_ID`] wrap around to `0`.
+    #[inline]
+    pub const fn starting_at(first_id: u32) -> Self {
+        Self {
+            next: if first_id > MAX_NOTE_ID { 0 } else { first_id },
+        }
+    }
+
+    /// Returns the ID that will be tried first on the next allocation.
+    #[inline]
+    pub const fn peek(&self) -> u32 {
+        self.next
+    }
+
+    /// Allocates the next ID for which `is_in_use` returns `false`.
+    ///
+    /// This returns `None` if every single ID is in use.
+    pub fn allocate(&mut self, mut is_in_use: impl FnMut(u32) -> bool) -> Option<u32> {
+        let start = self.next;
+
+        loop {
+            let id = self.next;
+            self.next = if id == MAX_NOTE_ID { 0 } else { id + 1 };
+
+            if !is_in_use(id) {
+                return Some(id);
+            }
+
+            if self.next == start {
+                return None;
+            }
+        }
+    }
+}
+
+/// Whether an [`ActiveNote`] is still held, or was released by the host.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NoteState {
+    /// The note is held, i.e. no note off was sent yet.
+    Held,
+    /// The note was released, but the plugin hasn't ended it yet.
+    Released {
+        /// How many blocks were processed since the note was released.
+        blocks: u32,
+    },
+}
+
+/// A note that was started by the host, and hasn't ended yet.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ActiveNote {
+    pckn: Pckn,
+    note_id: u32,
+    state: NoteState,
+}
+
+impl ActiveNote {
+    /// Returns the port, channel, key and note ID of this note. All of them are specific values.
+    #[inline]
+    pub const fn pckn(&self) -> Pckn {
+        self.pckn
+    }
+
+    /// Returns the note ID that was assigned to this note.
+    #[inline]
+    pub const fn note_id(&self) -> u32 {
+        self.note_id
+    }
+
+    /// Returns whether this note is still held.
+    #[inline]
+    pub const fn state(&self) -> NoteState {
+        self.state
+    }
+
+    /// Returns a note expression event targeting this note only.
+    #[inline]
+    pub const fn expression_event(
+        &self,
+        time: u32,
+        expression_type: NoteExpressionType,
+        value: f64,
+    ) -> NoteExpressionEvent {
+        NoteExpressionEvent::new(time, self.pckn, expression_type, value)
+    }
+
+    /// Returns a parameter modulation event targeting this note only.
+    #[inline]
+    pub const fn param_mod_event(&self, time: u32, param_id: ClapId, amount: f64) -> ParamModEvent {
+        ParamModEvent::new(time, param_id, self.pckn, amount, Cookie::empty())
+    }
+}
+
+/// Assigns note IDs to the notes the host sends to a plugin, and tracks them until they end.
+///
+/// CLAP note IDs allow hosts to address a single sounding note, e.g. to apply per-note expressions
+/// or modulations. Notes are started by the host with [`note_on`](Self::note_on), which assigns
+/// them a unique ID, and are then tracked until the plugin reports they ended, by sending a
+/// [`NoteEndEvent`] (or a [`NoteOffEvent`]) on its output. Output events must be fed to the
+/// tracker using [`observe_output_events`](Self::observe_output_events).
+///
+/// Some plugins never report note ends. To avoid leaking those notes, released notes can also be
+/// retired after a given number of blocks, using
+/// [`with_release_timeout`](Self::with_release_timeout). [`end_block`](Self::end_block) must then
+/// be called after each processed block.
+///
+/// Live notes can be looked up using [`find`](Self::find), and per-note events can be generated
+/// for all live notes matching a [`Pckn`] using
+/// [`write_expression`](Self::write_expression) and
+/// [`write_param_mod`](Self::write_param_mod). This allows e.g. automation curves drawn for a
+/// key to be sent to the exact note currently playing it.
+///
+/// All storage is allocated when the tracker is created: it never allocates afterwards, and can
+/// be used on the audio thread.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::events::event_types::NoteEndEvent;
+/// use clack_host::events::io::EventBuffer;
+/// use clack_host::process::note_ids::ActiveNoteTracker;
+///
+/// let mut tracker = ActiveNoteTracker::new(16);
+///
+/// let note_on = tracker.note_on(0, 0, 0, 60, 1.0).unwrap();
+/// assert_eq!(tracker.note_id_for(0, 0, 60), Some(0));
+///
+/// // Send note_on to the plugin... which later reports the note ended.
+/// let mut plugin_output = EventBuffer::new();
+/// plugin_output.push(&NoteEndEvent::new(0, note_on.pckn()));
+///
+/// tracker.observe_output_events(&plugin_output.as_input());
+/// assert!(tracker.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ActiveNoteTracker {
+    allocator: NoteIdAllocator,
+    notes: Vec<ActiveNote>,
+    capacity: usize,
+    release_timeout: Option<u32>,
+}
+
+impl ActiveNoteTracker {
+    /// Creates a new tracker, which can track up to `capacity` notes at the same time.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            allocator: NoteIdAllocator::new(),
+            notes: Vec::with_capacity(capacity),
+            capacity,
+            release_timeout: None,
+        }
+    }
+
+    /// Sets the number of blocks after which released notes are considered ended, even if the
+    /// plugin did not report it.
+    ///
+    /// By default, notes are only ended by the plugin, or when the tracker is full.
+    #[inline]
+    pub fn with_release_timeout(mut self, blocks: u32) -> Self {
+        self.release_timeout = Some(blocks);
+        self
+    }
+
+    /// Returns the allocator used to generate note IDs.
+    #[inline]
+    pub fn allocator(&self) -> &NoteIdAllocator {
+        &self.allocator
+    }
+
+    /// Returns the maximum number of notes that can be tracked at the same time.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of live notes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Returns `true` if no note is currently live.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Returns all the live notes, from oldest to newest.
+    #[inline]
+    pub fn active_notes(&self) -> &[ActiveNote] {
+        &self.notes
+    }
+
+    /// Returns `true` if a live note has the given ID.
+    #[inline]
+    pub fn is_live(&self, note_id: u32) -> bool {
+        self.notes.iter().any(|n| n.note_id == note_id)
+    }
+
+    /// Returns all the live notes matching the given [`Pckn`], which may contain wildcards.
+    #[inline]
+    pub fn find<'a>(&'a self, pckn: &'a Pckn) -> impl Iterator<Item = &'a ActiveNote> + 'a {
+        self.notes.iter().filter(|n| pckn.matches(&n.pckn))
+    }
+
+    /// Returns the ID of the most recent live note playing the given key, if any.
+    pub fn note_id_for(&self, port_index: u16, channel: u16, key: u16) -> Option<u32> {
+        let pckn = Pckn::new(port_index, channel, key, Match::All);
+        self.find(&pckn).last().map(|n| n.note_id)
+    }
+
+    /// Starts tracking a new note, and returns the note on event to send to the plugin, with a
+    /// newly assigned note ID.
+    ///
+    /// If the tracker is full, the oldest released note is forgotten to make room for the new
+    /// one. If all tracked notes are still held, this returns `None`.
+    pub fn note_on(
+        &mut self,
+        time: u32,
+        port_index: u16,
+        channel: u16,
+        key: u16,
+        velocity: f64,
+    ) -> Option<NoteOnEvent> {
+        if self.notes.len() >= self.capacity {
+            let oldest_released = self
+                .notes
+                .iter()
+                .position(|n| matches!(n.state, NoteState::Released { .. }))?;
+
+            self.notes.remove(oldest_released);
+        }
+
+        let notes = &self.notes;
+        let note_id = self
+            .allocator
+            .allocate(|id| notes.iter().any(|n| n.note_id == id))?;
+
+        let pckn = Pckn::new(port_index, channel, key, note_id);
+        self.notes.push(ActiveNote {
+            pckn,
+            note_id,
+            state: NoteState::Held,
+        });
+
+        Some(NoteOnEvent::new(time, pckn, velocity))
+    }
+
+    /// Releases the oldest held note playing the given key, and returns the note off event to
+    /// send to the plugin, targeting that note's ID.
+    ///
+    /// The note stays live until the plugin ends it. This returns `None` if no note is held on
+    /// the given key.
+    pub fn note_off(
+        &mut self,
+        time: u32,
+        port_index: u16,
+        channel: u16,
+        key: u16,
+        velocity: f64,
+    ) -> Option<NoteOffEvent> {
+        let pckn = Pckn::new(port_index, channel, key, Match::All);
+
+        let note = self
+            .notes
+            .iter_mut()
+            .find(|n| n.state == NoteState::Held && pckn.matches(&n.pckn))?;
+
+        note.state = NoteState::Released { blocks: 0 };
+        Some(NoteOffEvent::new(time, note.pckn, velocity))
+    }
+
+    /// Stops tracking all notes matching the given [`Pckn`], and returns the choke event to send
+    /// to the plugin.
+    pub fn choke(&mut self, time: u32, pckn: Pckn) -> NoteChokeEvent {
+        self.retire(&pckn);
+        NoteChokeEvent::new(time, pckn)
+    }
+
+    /// Releases all held notes, and writes a note off event for each of them, targeting that
+    /// note's ID.
+    ///
+    /// Released notes stay live until the plugin ends them. Returns the number of events that
+    /// were written.
+    pub fn release_all(&mut self, time: u32, velocity: f64, output: &mut EventBuffer) -> usize {
+        let mut count = 0;
+
+        for note in &mut self.notes {
+            if note.state == NoteState::Held {
+                note.state = NoteState::Released { blocks: 0 };
+                output.push(&NoteOffEvent::new(time, note.pckn, velocity));
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Stops tracking all notes, held or released, and writes a choke event for each of them,
+    /// targeting that note's ID.
+    ///
+    /// Returns the number of events that were written.
+    pub fn choke_all(&mut self, time: u32, output: &mut EventBuffer) -> usize {
+        for note in &self.notes {
+            output.push(&NoteChokeEvent::new(time, note.pckn));
+        }
+
+        let count = self.notes.len();
+        self.notes.clear();
+        count
+    }
+
+    /// Updates the tracked notes from the plugin's output events.
+    ///
+    /// Notes matching any [`NoteEndEvent`] or [`NoteOffEvent`] are retired, and their IDs can
+    /// be reused.
+    pub fn observe_output_events(&mut self, events: &InputEvents) {
+        for event in events {
+            match event.as_core_event() {
+                Some(CoreEventSpace::NoteEnd(e)) => self.retire(&e.pckn()),
+                Some(CoreEventSpace::NoteOff(e)) => self.retire(&e.pckn()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Marks the end of a processed block.
+    ///
+    /// This ages released notes, and retires those which exceeded the release timeout, if any.
+    pub fn end_block(&mut self) {
+        for note in &mut self.notes {
+            if let NoteState::Released { blocks } = &mut note.state {
+                *blocks = blocks.saturating_add(1);
+            }
+        }
+
+        if let Some(timeout) = self.release_timeout {
+            self.notes.retain(|n| match n.state {
+                NoteState::Held => true,
+                NoteState::Released { blocks } => blocks < timeout,
+            });
+        }
+    }
+
+    /// Stops tracking all notes, e.g. when the plugin is reset or deactivated.
+    ///
+    /// The note ID allocator keeps going from where it was.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.notes.clear()
+    }
+
+    /// Writes a note expression event for every live note matching the given [`Pckn`], each
+    /// targeting that note's specific ID.
+    ///
+    /// Returns the number of events that were written.
+    pub fn write_expression(
+        &self,
+        pckn: &Pckn,
+        time: u32,
+        expression_type: NoteExpressionType,
+        value: f64,
+        output: &mut EventBuffer,
+    ) -> usize {
+        let mut count = 0;
+
+        for note in self.find(pckn) {
+            output.push(&note.expression_event(time, expression_type, value));
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Writes a parameter modulation event for every live note matching the given [`Pckn`], each
+    /// targeting that note's specific ID.
+    ///
+    /// Returns the number of events that were written.
+    pub fn write_param_mod(
+        &self,
+        pckn: &Pckn,
+        time: u32,
+        param_id: ClapId,
+        amount: f64,
+        output: &mut EventBuffer,
+    ) -> usize {
+        let mut count = 0;
+
+        for note in self.find(pckn) {
+            output.push(&note.param_mod_event(time, param_id, amount));
+            count += 1;
+        }
+
+        count
+    }
+
+    fn retire(&mut self, pckn: &Pckn) {
+        self.notes.retain(|n| !pckn.matches(&n.pckn));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn allocator_wraps_around_and_skips_used_ids() {
+        let mut allocator = NoteIdAllocator::starting_at(MAX_NOTE_ID - 1);
+
+        assert_eq!(allocator.allocate(|_| false), Some(MAX_NOTE_ID - 1));
+        assert_eq!(allocator.allocate(|_| false), Some(MAX_NOTE_ID));
+        assert_eq!(allocator.allocate(|id| id < 3), Some(3));
+        assert_eq!(allocator.peek(), 4);
+
+        assert_eq!(NoteIdAllocator::starting_at(u32::MAX).peek(), 0);
+    }
+
+    #[test]
+    fn notes_are_retired_by_plugin_output() {
+        let mut tracker = ActiveNoteTracker::new(4);
+
+        let first = tracker.note_on(0, 0, 0, 60, 1.0).unwrap();
+        let second = tracker.note_on(0, 0, 0, 60, 1.0).unwrap();
+        tracker.note_on(0, 0, 0, 64, 1.0).unwrap();
+
+        assert_eq!(first.pckn().note_id, Match::Specific(0));
+        assert_eq!(second.pckn().note_id, Match::Specific(1));
+        assert_eq!(tracker.note_id_for(0, 0, 60), Some(1));
+
+        // The oldest held note is released first.
+        let off = tracker.note_off(5, 0, 0, 60, 0.0).unwrap();
+        assert_eq!(off.pckn(), first.pckn());
+        assert_eq!(tracker.len(), 3);
+
+        let mut output = EventBuffer::new();
+        output.push(&NoteEndEvent::new(0, first.pckn()));
+        tracker.observe_output_events(&output.as_input());
+
+        assert_eq!(tracker.len(), 2);
+        assert!(!tracker.is_live(0));
+
+        // Wildcard ends retire all matching notes.
+        output.clear();
+        output.push(&NoteEndEvent::new(
+            0,
+            Pckn::new(0u16, 0u16, 60u16, Match::All),
+        ));
+        tracker.observe_output_events(&output.as_input());
+
+        assert_eq!(tracker.len(), 1);
+        assert_eq!(tracker.note_id_for(0, 0, 60), None);
+        assert_eq!(tracker.note_id_for(0, 0, 64), Some(2));
+    }
+
+    #[test]
+    fn released_notes_time_out() {
+        let mut tracker = ActiveNoteTracker::new(4).with_release_timeout(2);
+
+        tracker.note_on(0, 0, 0, 60, 1.0).unwrap();
+        tracker.note_on(0, 0, 0, 62, 1.0).unwrap();
+        tracker.note_off(0, 0, 0, 60, 0.0).unwrap();
+
+        tracker.end_block();
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(
+            tracker.active_notes()[0].state(),
+            NoteState::Released { blocks: 1 }
+        );
+
+        tracker.end_block();
+        assert_eq!(tracker.len(), 1);
+        assert_eq!(tracker.active_notes()[0].state(), NoteState::Held);
+    }
+
+    #[test]
+    fn full_tracker_forgets_oldest_released_note() {
+        let mut tracker = ActiveNoteTracker::new(2);
+
+        tracker.note_on(0, 0, 0, 60, 1.0).unwrap();
+        tracker.note_on(0, 0, 0, 62, 1.0).unwrap();
+        assert!(tracker.note_on(0, 0, 0, 64, 1.0).is_none());
+
+        tracker.note_off(0, 0, 0, 62, 0.0).unwrap();
+        let note = tracker.note_on(0, 0, 0, 64, 1.0).unwrap();
+
+        assert_eq!(note.pckn().note_id, Match::Specific(2));
+        assert_eq!(tracker.note_id_for(0, 0, 62), None);
+        assert_eq!(tracker.len(), 2);
+    }
+
+    #[test]
+    fn per_note_events_target_live_notes() {
+        let mut tracker = ActiveNoteTracker::new(4);
+
+        tracker.note_on(0, 0, 0, 60, 1.0).unwrap();
+        tracker.note_on(0, 0, 1, 60, 1.0).unwrap();
+        tracker.note_on(0, 0, 0, 67, 1.0).unwrap();
+
+        let mut output = EventBuffer::new();
+        let key = Pckn::new(Match::All, Match::All, 60u16, Match::All);
+
+        assert_eq!(
+            tracker.write_expression(&key, 3, NoteExpressionType::Tuning, 0.5, &mut output),
+            2
+        );
+        assert_eq!(
+            tracker.write_param_mod(&key, 3, ClapId::new(7), 0.25, &mut output),
+            2
+        );
+
+        let ids: Vec<_> = output
+            .iter()
+            .map(|e| match e.as_core_event() {
+                Some(CoreEventSpace::NoteExpression(e)) => e.pckn().note_id,
+                Some(CoreEventSpace::ParamMod(e)) => e.pckn().note_id,
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(
+            ids,
+            [0, 1, 0, 1].map(Match::Specific),
+            "Events must target note IDs"
+        );
+    }
+}
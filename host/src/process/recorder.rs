@@ -0,0 +1,417 @@
+//! Recording of the note and parameter events output by a plugin.
+//!
+//! See the [`EventRecorder`] type for more information.
+
+use clack_common::events::event_types::*;
+use clack_common::events::io::InputEvents;
+use clack_common::events::spaces::CoreEventSpace;
+use clack_common::events::{Event, Match, UnknownEvent};
+use clack_common::utils::{KeyName, NoteNaming};
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The maximum number of events a single chunk of the recorder's queue can hold.
+pub const RECORDER_CHUNK_SIZE: usize = 64;
+
+/// An owned copy of a note or parameter event, as recorded by an [`EventRecorder`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RecordedEvent {
+    /// A note on event.
+    NoteOn(NoteOnEvent),
+    /// A note off event.
+    NoteOff(NoteOffEvent),
+    /// A note choke event.
+    NoteChoke(NoteChokeEvent),
+    /// A note end event.
+    NoteEnd(NoteEndEvent),
+    /// A note expression event.
+    NoteExpression(NoteExpressionEvent),
+    /// A parameter value event.
+    ParamValue(ParamValueEvent),
+    /// A parameter modulation event.
+    ParamMod(ParamModEvent),
+    /// A parameter gesture begin event.
+    ParamGestureBegin(ParamGestureBeginEvent),
+    /// A parameter gesture end event.
+    ParamGestureEnd(ParamGestureEndEvent),
+    /// A MIDI 1.0 event.
+    Midi(MidiEvent),
+    /// A MIDI 2.0 event.
+    Midi2(Midi2Event),
+}
+
+impl RecordedEvent {
+    /// Copies the given event, if it is a note, parameter or MIDI event.
+    ///
+    /// MIDI SysEx events are not supported, as they reference data that is only valid for the
+    /// duration of a single process call.
+    pub fn from_unknown(event: &UnknownEvent) -> Option<Self> {
+        Some(match event.as_core_event()? {
+            CoreEventSpace::NoteOn(e) => Self::NoteOn(*e),
+            CoreEventSpace::NoteOff(e) => Self::NoteOff(*e),
+            CoreEventSpace::NoteChoke(e) => Self::NoteChoke(*e),
+            CoreEventSpace::NoteEnd(e) => Self::NoteEnd(*e),
+            CoreEventSpace::NoteExpression(e) => Self::NoteExpression(*e),
+            CoreEventSpace::ParamValue(e) => Self::ParamValue(*e),
+            CoreEventSpace::ParamMod(e) => Self::ParamMod(*e),
+            CoreEventSpace::ParamGestureBegin(e) => Self::ParamGestureBegin(*e),
+            CoreEventSpace::ParamGestureEnd(e) => Self::ParamGestureEnd(*e),
+            CoreEventSpace::Midi(e) => Self::Midi(*e),
+            CoreEventSpace::Midi2(e) => Self::Midi2(*e),
+            CoreEventSpace::Transport(_) | CoreEventSpace::MidiSysEx(_) => return None,
+        })
+    }
+
+    /// Returns this event as an [`UnknownEvent`], e.g. to push it into an event buffer.
+    ///
+    /// Note the event's time is still relative to the block it was recorded in.
+    pub fn as_unknown(&self) -> &UnknownEvent {
+        match self {
+            Self::NoteOn(e) => e.as_unknown(),
+            Self::NoteOff(e) => e.as_unknown(),
+            Self::NoteChoke(e) => e.as_unknown(),
+            Self::NoteEnd(e) => e.as_unknown(),
+            Self::NoteExpression(e) => e.as_unknown(),
+            Self::ParamValue(e) => e.as_unknown(),
+            Self::ParamMod(e) => e.as_unknown(),
+            Self::ParamGestureBegin(e) => e.as_unknown(),
+            Self::ParamGestureEnd(e) => e.as_unknown(),
+            Self::Midi(e) => e.as_unknown(),
+            Self::Midi2(e) => e.as_unknown(),
+        }
+    }
+
+    /// Returns the name of the key this event targets, in the given naming convention.
+    ///
+    /// This is supported for note events targeting a specific key, as well as MIDI 1.0 note on
+    /// and note off messages. For all other events, this returns `None`.
+    pub fn key_name(&self, naming: NoteNaming) -> Option<KeyName> {
+        let key = match self {
+            Self::NoteOn(e) => e.key(),
+            Self::NoteOff(e) => e.key(),
+            Self::NoteChoke(e) => e.key(),
+            Self::NoteEnd(e) => e.key(),
+            Self::NoteExpression(e) => e.key(),
+            Self::Midi(e) => match e.data() {
+                [status, key, _] if matches!(status & 0xF0, 0x80 | 0x90) => {
+                    Match::Specific(key as u16)
+                }
+                _ => Match::All,
+            },
+            _ => Match::All,
+        };
+
+        naming.name(key.into_specific()?)
+    }
+}
+
+impl AsRef<UnknownEvent> for RecordedEvent {
+    #[inline]
+    fn as_ref(&self) -> &UnknownEvent {
+        self.as_unknown()
+    }
+}
+
+/// A recorded event, with the absolute sample position it occurred at.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimedEvent {
+    /// The absolute position of the event, in samples.
+    ///
+    /// This is the start position of the block the event was recorded in, plus the event's time.
+    pub position: u64,
+    /// The recorded event.
+    pub event: RecordedEvent,
+}
+
+struct Chunk {
+    len: usize,
+    events: [MaybeUninit<TimedEvent>; RECORDER_CHUNK_SIZE],
+}
+
+impl Chunk {
+    #[inline]
+    fn new() -> Self {
+        Self {
+            len: 0,
+            events: [MaybeUninit::uninit(); RECORDER_CHUNK_SIZE],
+        }
+    }
+
+    #[inline]
+    fn events(&self) -> &[TimedEvent] {
+        let events = &self.events[..self.len];
+
+        // SAFETY: the first `len` events are always initialized.
+        unsafe { &*(events as *const [MaybeUninit<TimedEvent>] as *const [TimedEvent]) }
+    }
+}
+
+/// A single-producer, single-consumer queue of chunks.
+///
+/// Chunks in `read..write` are published and owned by the consumer. All the other chunks are
+/// owned by the producer, which fills the chunk at `write` before publishing it.
+struct ChunkQueue {
+    chunks: Box<[UnsafeCell<Chunk>]>,
+    /// The total number of chunks the consumer has read. Only written by the consumer.
+    read: AtomicUsize,
+    /// The total number of chunks the producer has published. Only written by the producer.
+    write: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+// SAFETY: Access to each chunk is synchronized through the read and write indexes, so that only
+// one side can access a given chunk at any time. The recorded events are plain data: the only
+// pointers they may contain are parameter cookies, which are opaque and never dereferenced.
+unsafe impl Send for ChunkQueue {}
+// SAFETY: See above.
+unsafe impl Sync for ChunkQueue {}
+
+impl ChunkQueue {
+    #[inline]
+    fn chunk(&self, index: usize) -> *mut Chunk {
+        self.chunks[index % self.chunks.len()].get()
+    }
+}
+
+/// Records the note and parameter events output by a plugin, on the audio thread.
+///
+/// After each process call, the host passes the plugin's output events to
+/// [`record`](Self::record), along with the start position of the processed block. Events are
+/// copied into fixed-size chunks, which are sent to the matching [`EventTimeline`] through a
+/// lock-free queue, so that they can be collected on the main thread (e.g. to implement MIDI
+/// capture).
+///
+/// The recorder never allocates nor blocks: if the main thread doesn't collect the events fast
+/// enough and the queue is full, new events are dropped instead, and counted (see
+/// [`EventTimeline::dropped_count`]).
+///
+/// # Example
+///
+/// ```
+/// use clack_host::events::event_types::NoteOnEvent;
+/// use clack_host::events::io::EventBuffer;
+/// use clack_host::events::Pckn;
+/// use clack_host::process::recorder::{EventRecorder, RecordedEvent};
+///
+/// let (mut recorder, mut timeline) = EventRecorder::new(16);
+///
+/// // On the audio thread, after processing a block starting at sample 1024:
+/// let mut output_events = EventBuffer::new();
+/// output_events.push(&NoteOnEvent::new(5, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0));
+/// recorder.record(&output_events.as_input(), 1024);
+///
+/// // Later, on the main thread:
+/// timeline.collect();
+/// assert_eq!(timeline.events().len(), 1);
+/// assert_eq!(timeline.events()[0].position, 1029);
+/// ```
+pub struct EventRecorder {
+    queue: Arc<ChunkQueue>,
+}
+
+impl EventRecorder {
+    /// Creates a new recorder, and its matching [`EventTimeline`].
+    ///
+    /// The queue between them can hold up to `chunk_count` chunks of [`RECORDER_CHUNK_SIZE`]
+    /// events each. At least two chunks are always allocated.
+    pub fn new(chunk_count: usize) -> (Self, EventTimeline) {
+        let chunks = (0..chunk_count.max(2))
+            .map(|_| UnsafeCell::new(Chunk::new()))
+            .collect();
+
+        let queue = Arc::new(ChunkQueue {
+            chunks,
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            dropped: AtomicU64::new(0),
+        });
+
+        (
+            Self {
+                queue: queue.clone(),
+            },
+            EventTimeline {
+                queue,
+                events: Vec::new(),
+            },
+        )
+    }
+
+    /// Records all the note and parameter events in the given list.
+    ///
+    /// `block_start` is the absolute position of the first sample of the block these events
+    /// were output in, e.g. the `steady_time` passed to the process call.
+    ///
+    /// All recorded events are published to the [`EventTimeline`] before this method returns.
+    ///
+    /// # Realtime Safety
+    ///
+    /// This method is realtime-safe: it never allocates, locks nor blocks.
+    pub fn record(&mut self, events: &InputEvents, block_start: u64) {
+        let queue = &*self.queue;
+        let write = queue.write.load(Ordering::Relaxed);
+        let mut current = write;
+        let mut len = 0;
+
+        for event in events {
+            let Some(recorded) = RecordedEvent::from_unknown(event) else {
+                continue;
+            };
+
+            if len == RECORDER_CHUNK_SIZE {
+                current += 1;
+                len = 0;
+            }
+
+            // The chunk is only free if the consumer isn't holding it.
+            if current - queue.read.load(Ordering::Acquire) >= queue.chunks.len() {
+                queue.dropped.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
+            // SAFETY: the chunk at `current` is not published yet, so only we can access it.
+            let chunk = unsafe { &mut *queue.chunk(current) };
+            chunk.events[len] = MaybeUninit::new(TimedEvent {
+                position: block_start + event.header().time() as u64,
+                event: recorded,
+            });
+            len += 1;
+            chunk.len = len;
+        }
+
+        let published = if len > 0 { current + 1 } else { current };
+        if published != write {
+            queue.write.store(published, Ordering::Release);
+        }
+    }
+}
+
+/// The main-thread side of an [`EventRecorder`], which collects its recorded events into a
+/// timeline.
+pub struct EventTimeline {
+    queue: Arc<ChunkQueue>,
+    events: Vec<TimedEvent>,
+}
+
+impl EventTimeline {
+    /// Collects all the events published by the recorder so far into this timeline.
+    ///
+    /// Returns the number of events that were collected.
+    pub fn collect(&mut self) -> usize {
+        let queue = &*self.queue;
+        let read = queue.read.load(Ordering::Relaxed);
+        let write = queue.write.load(Ordering::Acquire);
+        let previous_len = self.events.len();
+
+        for index in read..write {
+            // SAFETY: the chunk at `index` is published, so only we can access it until we update
+            // the read index.
+            let chunk = unsafe { &*queue.chunk(index) };
+            self.events.extend_from_slice(chunk.events());
+        }
+
+        queue.read.store(write, Ordering::Release);
+        self.events.len() - previous_len
+    }
+
+    /// Returns all the events collected in this timeline, in the order they were recorded.
+    #[inline]
+    pub fn events(&self) -> &[TimedEvent] {
+        &self.events
+    }
+
+    /// Removes and returns all the collected events that occurred before the given absolute
+    /// position, i.e. a completed span of the timeline.
+    ///
+    /// Events at or after `position` are kept in this timeline.
+    pub fn drain_before(&mut self, position: u64) -> Vec<TimedEvent> {
+        let split = self.events.partition_point(|e| e.position < position);
+        let remaining = self.events.split_off(split);
+
+        core::mem::replace(&mut self.events, remaining)
+    }
+
+    /// Returns the total number of events the recorder had to drop because its queue was full.
+    #[inline]
+    pub fn dropped_count(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::events::io::EventBuffer;
+    use clack_common::events::Pckn;
+
+    fn note_on(time: u32, key: u16) -> NoteOnEvent {
+        NoteOnEvent::new(time, Pckn::new(0u16, 0u16, key, 0u32), 1.0)
+    }
+
+    #[test]
+    pub fn records_across_chunks() {
+        let (mut recorder, mut timeline) = EventRecorder::new(4);
+
+        let mut buffer = EventBuffer::new();
+        for i in 0..100 {
+            buffer.push(&note_on(i, i as u16));
+        }
+        buffer.push(&MidiSysExEvent::new(0, 0, &[0xF0, 0xF7]));
+
+        recorder.record(&buffer.as_input(), 1000);
+
+        assert_eq!(timeline.collect(), 100);
+        assert_eq!(timeline.dropped_count(), 0);
+
+        for (i, event) in timeline.events().iter().enumerate() {
+            assert_eq!(event.position, 1000 + i as u64);
+            assert_eq!(
+                event.event,
+                RecordedEvent::NoteOn(note_on(i as u32, i as u16))
+            );
+        }
+
+        let span = timeline.drain_before(1050);
+        assert_eq!(span.len(), 50);
+        assert_eq!(timeline.events().len(), 50);
+        assert_eq!(timeline.events()[0].position, 1050);
+    }
+
+    #[test]
+    pub fn drops_events_when_full() {
+        let (mut recorder, mut timeline) = EventRecorder::new(2);
+
+        let mut buffer = EventBuffer::new();
+        for i in 0..(RECORDER_CHUNK_SIZE * 3) as u32 {
+            buffer.push(&note_on(i, 60));
+        }
+
+        recorder.record(&buffer.as_input(), 0);
+
+        assert_eq!(timeline.collect(), RECORDER_CHUNK_SIZE * 2);
+        assert_eq!(timeline.dropped_count(), RECORDER_CHUNK_SIZE as u64);
+
+        // Once collected, the recorder can record again.
+        recorder.record(&buffer.as_input(), 0);
+        assert_eq!(timeline.collect(), RECORDER_CHUNK_SIZE * 2);
+    }
+
+    #[test]
+    pub fn names_recorded_keys() {
+        let naming = NoteNaming::SCIENTIFIC;
+
+        let note = RecordedEvent::NoteOn(note_on(0, 61));
+        assert_eq!(note.key_name(naming).unwrap().to_string(), "C#4");
+
+        let midi = RecordedEvent::Midi(MidiEvent::new(0, 0, [0x91, 69, 100]));
+        assert_eq!(midi.key_name(naming).unwrap().to_string(), "A4");
+
+        let all_keys = NoteOnEvent::new(0, Pckn::match_all(), 1.0);
+        assert_eq!(RecordedEvent::NoteOn(all_keys).key_name(naming), None);
+
+        let control_change = RecordedEvent::Midi(MidiEvent::new(0, 0, [0xB0, 1, 0]));
+        assert_eq!(control_change.key_name(naming), None);
+    }
+}
@@ -0,0 +1,288 @@
+//! Automatic sleep and wake-up of plugins, based on the status they return.
+//!
+//! See the [`ProcessingScheduler`] type for more information.
+
+use crate::process::ProcessStatus;
+
+/// The absolute sample value under which audio is considered silent, about -120 dBFS.
+pub const SILENCE_THRESHOLD: f32 = 1e-6;
+
+/// Returns `true` if all the samples of the given channels are silent, i.e. their absolute value
+/// is at most [`SILENCE_THRESHOLD`].
+pub fn is_silent<'a>(channels: impl IntoIterator<Item = &'a [f32]>) -> bool {
+    channels
+        .into_iter()
+        .all(|channel| channel.iter().all(|s| s.abs() <= SILENCE_THRESHOLD))
+}
+
+/// What should be done with a plugin for the current block, as decided by a
+/// [`ProcessingScheduler`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScheduleDecision {
+    /// The plugin must be processed normally.
+    Process,
+    /// The plugin must be processed, but its inputs are silent: the host may pass them as
+    /// constant buffers.
+    ProcessSilence,
+    /// The plugin is asleep, and doesn't need to be processed at all. Its outputs must be filled
+    /// with silence (and may be flagged as constant) instead.
+    Skip,
+}
+
+/// Decides, block by block, whether a plugin needs to be processed, from the last
+/// [`ProcessStatus`] it returned, its input events and audio, and its tail and latency.
+///
+/// A scheduler is used in two steps for every block: [`decide`](Self::decide) is called before
+/// processing, and returns whether the plugin must be processed. If it was, the status it
+/// returned is then given to [`record`](Self::record), along with whether its outputs were
+/// silent.
+///
+/// Plugins are put to sleep as follows, depending on the last status they returned:
+///
+/// * [`Continue`](ProcessStatus::Continue): the plugin never sleeps.
+/// * [`ContinueIfNotQuiet`](ProcessStatus::ContinueIfNotQuiet): the plugin sleeps once both its
+///   inputs and outputs are silent, and its inputs have been silent for at least its latency.
+/// * [`Tail`](ProcessStatus::Tail): the plugin sleeps once its inputs have been silent for at
+///   least its tail length plus its latency. Any non-silent input restarts the tail. Infinite
+///   tails never end.
+/// * [`Sleep`](ProcessStatus::Sleep): the plugin sleeps right away.
+///
+/// A sleeping plugin is woken up as soon as it receives input events, or as soon as its inputs
+/// aren't silent anymore.
+///
+/// # Example
+///
+/// ```
+/// use clack_host::process::scheduler::{ProcessingScheduler, ScheduleDecision};
+/// use clack_host::process::ProcessStatus;
+///
+/// let mut scheduler = ProcessingScheduler::new(0, 0);
+///
+/// // Silent input, no events: a plugin that returned Sleep can be skipped.
+/// assert_eq!(scheduler.decide(64, false, true), ScheduleDecision::ProcessSilence);
+/// scheduler.record(ProcessStatus::Sleep, true);
+/// assert_eq!(scheduler.decide(64, false, true), ScheduleDecision::Skip);
+///
+/// // Incoming events wake it up.
+/// assert_eq!(scheduler.decide(64, true, true), ScheduleDecision::ProcessSilence);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProcessingScheduler {
+    tail: u32,
+    latency: u32,
+    last_status: Option<ProcessStatus>,
+    sleeping: bool,
+    /// The number of frames of silent input since the last non-silent one, including the
+    /// current block.
+    silent_frames: u64,
+    input_silent: bool,
+}
+
+impl ProcessingScheduler {
+    /// The raw tail length from which a tail is considered infinite, as defined by the `tail`
+    /// extension.
+    pub const INFINITE_TAIL: u32 = i32::MAX as u32;
+
+    /// Creates a new scheduler for a plugin with the given raw tail length and latency, in
+    /// samples.
+    ///
+    /// The raw tail length is the value reported by the plugin's `tail` extension: values of
+    /// [`INFINITE_TAIL`](Self::INFINITE_TAIL) or more are considered infinite. Plugins that
+    /// don't implement this extension have no tail.
+    ///
+    /// The plugin starts awake.
+    pub fn new(tail: u32, latency: u32) -> Self {
+        Self {
+            tail,
+            latency,
+            last_status: None,
+            sleeping: false,
+            silent_frames: 0,
+            input_silent: false,
+        }
+    }
+
+    /// Updates the plugin's raw tail length, e.g. after it notified the host it changed.
+    #[inline]
+    pub fn set_tail(&mut self, tail: u32) {
+        self.tail = tail;
+    }
+
+    /// Updates the plugin's latency, in samples.
+    #[inline]
+    pub fn set_latency(&mut self, latency: u32) {
+        self.latency = latency;
+    }
+
+    /// Returns `true` if the plugin is currently asleep.
+    #[inline]
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Returns the status the plugin returned the last time it was processed, if any.
+    #[inline]
+    pub fn last_status(&self) -> Option<ProcessStatus> {
+        self.last_status
+    }
+
+    /// Returns the number of frames left in the plugin's tail, or [`None`] if its tail is
+    /// infinite.
+    ///
+    /// This is only relevant if the plugin last returned [`ProcessStatus::Tail`].
+    pub fn tail_remaining(&self) -> Option<u64> {
+        if self.tail >= Self::INFINITE_TAIL {
+            return None;
+        }
+
+        let length = self.tail as u64 + self.latency as u64;
+        Some(length.saturating_sub(self.silent_frames))
+    }
+
+    /// Wakes the plugin up, and forgets the last status it returned.
+    ///
+    /// This should be called whenever the plugin's state was reset, or its processing restarted.
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.last_status = None;
+        self.silent_frames = 0;
+    }
+
+    /// Decides what should be done with the plugin for the next block.
+    ///
+    /// `has_input_events` is whether the plugin receives any input event in this block, and
+    /// `input_silent` whether its inputs are silent (see [`is_silent`]). Plugins without audio
+    /// inputs should be considered as having silent inputs.
+    pub fn decide(
+        &mut self,
+        frames_count: u32,
+        has_input_events: bool,
+        input_silent: bool,
+    ) -> ScheduleDecision {
+        self.input_silent = input_silent;
+
+        if input_silent {
+            self.silent_frames = self.silent_frames.saturating_add(frames_count as u64);
+        } else {
+            self.silent_frames = 0;
+        }
+
+        if has_input_events || !input_silent {
+            self.sleeping = false;
+        }
+
+        match (self.sleeping, input_silent) {
+            (true, _) => ScheduleDecision::Skip,
+            (false, true) => ScheduleDecision::ProcessSilence,
+            (false, false) => ScheduleDecision::Process,
+        }
+    }
+
+    /// Records the status the plugin returned after being processed, as decided by the last call
+    /// to [`decide`](Self::decide), and whether its outputs were silent.
+    pub fn record(&mut self, status: ProcessStatus, output_silent: bool) {
+        self.last_status = Some(status);
+
+        let past_latency = self.input_silent && self.silent_frames >= self.latency as u64;
+
+        self.sleeping = match status {
+            ProcessStatus::Continue => false,
+            ProcessStatus::ContinueIfNotQuiet => past_latency && output_silent,
+            ProcessStatus::Tail => self.input_silent && self.tail_remaining() == Some(0),
+            ProcessStatus::Sleep => true,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn continue_never_sleeps() {
+        let mut scheduler = ProcessingScheduler::new(0, 0);
+
+        for _ in 0..10 {
+            assert_eq!(
+                scheduler.decide(32, false, true),
+                ScheduleDecision::ProcessSilence
+            );
+            scheduler.record(ProcessStatus::Continue, true);
+        }
+    }
+
+    #[test]
+    fn quiet_output_sleeps_after_latency() {
+        let mut scheduler = ProcessingScheduler::new(0, 48);
+
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::ContinueIfNotQuiet, true);
+        assert!(!scheduler.is_sleeping());
+
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::ContinueIfNotQuiet, false);
+        assert!(!scheduler.is_sleeping());
+
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::ContinueIfNotQuiet, true);
+        assert!(scheduler.is_sleeping());
+
+        assert_eq!(scheduler.decide(32, false, true), ScheduleDecision::Skip);
+        assert_eq!(
+            scheduler.decide(32, false, false),
+            ScheduleDecision::Process
+        );
+    }
+
+    #[test]
+    fn tail_restarts_on_new_input() {
+        let mut scheduler = ProcessingScheduler::new(64, 0);
+
+        scheduler.decide(32, false, false);
+        scheduler.record(ProcessStatus::Tail, false);
+        assert_eq!(scheduler.tail_remaining(), Some(64));
+
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::Tail, false);
+        assert_eq!(scheduler.tail_remaining(), Some(32));
+
+        scheduler.decide(32, false, false);
+        scheduler.record(ProcessStatus::Tail, false);
+        assert_eq!(scheduler.tail_remaining(), Some(64));
+
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::Tail, false);
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::Tail, false);
+        assert!(scheduler.is_sleeping());
+        assert_eq!(scheduler.tail_remaining(), Some(0));
+    }
+
+    #[test]
+    fn infinite_tails_never_end() {
+        let mut scheduler = ProcessingScheduler::new(ProcessingScheduler::INFINITE_TAIL, 0);
+
+        for _ in 0..10 {
+            scheduler.decide(u32::MAX, false, true);
+            scheduler.record(ProcessStatus::Tail, true);
+        }
+
+        assert!(!scheduler.is_sleeping());
+        assert_eq!(scheduler.tail_remaining(), None);
+    }
+
+    #[test]
+    fn events_wake_sleeping_plugins() {
+        let mut scheduler = ProcessingScheduler::new(0, 0);
+
+        scheduler.decide(32, false, true);
+        scheduler.record(ProcessStatus::Sleep, true);
+        assert_eq!(scheduler.decide(32, false, true), ScheduleDecision::Skip);
+
+        assert_eq!(
+            scheduler.decide(32, true, true),
+            ScheduleDecision::ProcessSilence
+        );
+        assert!(!scheduler.is_sleeping());
+    }
+}
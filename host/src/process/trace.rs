@@ -0,0 +1,369 @@
+//! A record of the most recent process calls of a plugin, for debugging purposes.
+//!
+//! See the [`ProcessTrace`] type for more information.
+
+use clack_common::events::event_types::{TransportEvent, TransportFlags};
+use clack_common::process::ProcessStatus;
+use clap_sys::process::clap_process_status;
+use std::fmt::{Debug, Formatter, Write};
+use std::time::Duration;
+
+#[cfg(feature = "process-trace")]
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+#[cfg(feature = "process-trace")]
+use std::sync::Arc;
+
+/// The state of the transport given to a traced process call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TracedTransport {
+    /// No transport information was given: the host was free-running.
+    None,
+    /// The transport was stopped.
+    Stopped,
+    /// The transport was playing.
+    Playing,
+    /// The transport was playing and recording.
+    Recording,
+}
+
+impl TracedTransport {
+    /// Returns the state of the given transport.
+    pub fn from_transport(transport: Option<&TransportEvent>) -> Self {
+        match transport {
+            None => Self::None,
+            Some(t) if t.flags.contains(TransportFlags::IS_RECORDING) => Self::Recording,
+            Some(t) if t.flags.contains(TransportFlags::IS_PLAYING) => Self::Playing,
+            Some(_) => Self::Stopped,
+        }
+    }
+
+    #[cfg(feature = "process-trace")]
+    fn to_bits(self) -> u64 {
+        match self {
+            Self::None => 0,
+            Self::Stopped => 1,
+            Self::Playing => 2,
+            Self::Recording => 3,
+        }
+    }
+
+    #[cfg(feature = "process-trace")]
+    fn from_bits(bits: u64) -> Self {
+        match bits {
+            1 => Self::Stopped,
+            2 => Self::Playing,
+            3 => Self::Recording,
+            _ => Self::None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::None => "-",
+            Self::Stopped => "stopped",
+            Self::Playing => "playing",
+            Self::Recording => "recording",
+        }
+    }
+}
+
+/// The metadata of a single traced process call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ProcessTraceEntry {
+    /// The index of this block, counting from the first block recorded by the trace.
+    pub block: u64,
+    /// The number of frames of this block.
+    pub frames_count: u32,
+    /// The steady time given to the plugin, if any.
+    pub steady_time: Option<u64>,
+    /// The number of input events given to the plugin.
+    pub input_events: u32,
+    /// The number of events the plugin successfully pushed to its output events.
+    pub output_events: u32,
+    /// The raw status returned by the plugin.
+    pub raw_status: clap_process_status,
+    /// How long the plugin took to process this block.
+    pub duration: Duration,
+    /// The state of the transport given to the plugin.
+    pub transport: TracedTransport,
+}
+
+impl ProcessTraceEntry {
+    /// Returns the status returned by the plugin, or [`None`] if it returned an error, or an
+    /// unknown status value.
+    #[inline]
+    pub fn status(&self) -> Option<ProcessStatus> {
+        ProcessStatus::from_raw(self.raw_status).and_then(Result::ok)
+    }
+
+    fn status_label(&self) -> &'static str {
+        match self.status() {
+            Some(ProcessStatus::Continue) => "continue",
+            Some(ProcessStatus::ContinueIfNotQuiet) => "if-not-quiet",
+            Some(ProcessStatus::Tail) => "tail",
+            Some(ProcessStatus::Sleep) => "sleep",
+            None => "error",
+        }
+    }
+}
+
+/// A fixed-size ring buffer recording the metadata of the most recent process calls of a plugin.
+///
+/// A trace is attached to a started audio processor using
+/// [`StartedPluginAudioProcessor::set_trace`](crate::process::StartedPluginAudioProcessor::set_trace).
+/// Every subsequent `process` call then records its frame count, steady time, number of input
+/// and output events, returned status, duration and transport state. Only the last
+/// [`CAPACITY`](Self::CAPACITY) blocks are kept.
+///
+/// Traces can be cloned and sent to any thread: all clones share the same recorded blocks,
+/// which can be copied at any time using [`snapshot`](Self::snapshot), or formatted in a compact
+/// text table for bug reports using [`dump`](Self::dump).
+///
+/// Recording a block doesn't lock nor allocate, and only amounts to a few atomic stores and two
+/// reads of a monotonic clock. Blocks that are being overwritten while a snapshot is taken are
+/// skipped from that snapshot.
+///
+/// A trace should only be attached to a single audio processor at a time. Otherwise, blocks
+/// recorded concurrently by multiple processors may be lost or mixed up.
+///
+/// # Feature flag
+///
+/// Blocks are only recorded when the `process-trace` feature is enabled. Without it, this type
+/// is still available but does nothing: it never allocates, attaching it has no effect on
+/// process calls, and its snapshots are always empty. This allows hosts to use it
+/// unconditionally.
+///
+/// # Example
+///
+/// ```no_run
+/// use clack_host::process::trace::ProcessTrace;
+/// # use clack_host::prelude::*;
+/// # fn example<H: HostHandlers>(processor: &mut StartedPluginAudioProcessor<H>) {
+///
+/// let trace = ProcessTrace::new();
+/// processor.set_trace(Some(trace.clone()));
+///
+/// // ... later, e.g. on the main thread, when the plugin misbehaves:
+/// eprintln!("{}", trace.dump());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ProcessTrace {
+    #[cfg(feature = "process-trace")]
+    ring: Arc<TraceRing>,
+}
+
+impl ProcessTrace {
+    /// The number of blocks kept by a trace.
+    pub const CAPACITY: usize = 256;
+
+    /// Creates a new, empty trace.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "process-trace")]
+            ring: Arc::new(TraceRing::new()),
+        }
+    }
+
+    /// Returns `true` if blocks are actually recorded, i.e. if the `process-trace` feature is
+    /// enabled.
+    #[inline]
+    pub const fn is_enabled() -> bool {
+        cfg!(feature = "process-trace")
+    }
+
+    /// Returns the total number of blocks recorded since this trace was created, including the
+    /// ones that are no longer kept.
+    #[inline]
+    pub fn recorded_blocks(&self) -> u64 {
+        #[cfg(feature = "process-trace")]
+        {
+            self.ring.head.load(Ordering::Acquire)
+        }
+        #[cfg(not(feature = "process-trace"))]
+        {
+            0
+        }
+    }
+
+    /// Returns a copy of the recorded blocks, from the oldest to the most recent.
+    pub fn snapshot(&self) -> Vec<ProcessTraceEntry> {
+        #[cfg(feature = "process-trace")]
+        {
+            self.ring.snapshot()
+        }
+        #[cfg(not(feature = "process-trace"))]
+        {
+            Vec::new()
+        }
+    }
+
+    /// Formats the recorded blocks in a compact text table, one line per block, from the oldest
+    /// to the most recent.
+    pub fn dump(&self) -> String {
+        let mut output = String::new();
+
+        if !Self::is_enabled() {
+            output.push_str("process tracing is disabled (requires the `process-trace` feature)\n");
+            return output;
+        }
+
+        let _ = writeln!(
+            output,
+            "{:>8} {:>6} {:>12} {:>5} {:>5} {:>12} {:>10} {:>9}",
+            "block", "frames", "steady_time", "in", "out", "status", "time_us", "transport"
+        );
+
+        for entry in self.snapshot() {
+            let steady_time = match entry.steady_time {
+                Some(steady_time) => steady_time.to_string(),
+                None => "-".into(),
+            };
+
+            let _ = writeln!(
+                output,
+                "{:>8} {:>6} {:>12} {:>5} {:>5} {:>12} {:>10.1} {:>9}",
+                entry.block,
+                entry.frames_count,
+                steady_time,
+                entry.input_events,
+                entry.output_events,
+                entry.status_label(),
+                entry.duration.as_secs_f64() * 1_000_000.0,
+                entry.transport.label()
+            );
+        }
+
+        output
+    }
+
+    /// Records a block. This does nothing if the `process-trace` feature is disabled.
+    #[inline]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    pub(crate) fn record(
+        &self,
+        frames_count: u32,
+        steady_time: i64,
+        input_events: u32,
+        output_events: u32,
+        raw_status: clap_process_status,
+        duration: Duration,
+        transport: TracedTransport,
+    ) {
+        #[cfg(feature = "process-trace")]
+        self.ring.record(
+            frames_count as u64 | (input_events as u64) << 32,
+            output_events as u64 | (raw_status as u32 as u64) << 32,
+            steady_time as u64,
+            (duration.as_nanos() as u64 & DURATION_MASK) | transport.to_bits() << 56,
+        );
+    }
+}
+
+impl Default for ProcessTrace {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for ProcessTrace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessTrace")
+            .field("enabled", &Self::is_enabled())
+            .field("recorded_blocks", &self.recorded_blocks())
+            .finish()
+    }
+}
+
+/// The durations are stored in the lower 56 bits of a word, which is enough for a couple years.
+#[cfg(feature = "process-trace")]
+const DURATION_MASK: u64 = (1 << 56) - 1;
+
+/// A single recorded block, guarded by a sequence number.
+///
+/// The sequence number is odd while the slot is being written, and is `2 * (block + 1)` once
+/// the given block has been fully written into it.
+#[cfg(feature = "process-trace")]
+struct TraceSlot {
+    sequence: AtomicU64,
+    data: [AtomicU64; 4],
+}
+
+#[cfg(feature = "process-trace")]
+struct TraceRing {
+    head: AtomicU64,
+    slots: Box<[TraceSlot]>,
+}
+
+#[cfg(feature = "process-trace")]
+impl TraceRing {
+    fn new() -> Self {
+        Self {
+            head: AtomicU64::new(0),
+            slots: (0..ProcessTrace::CAPACITY)
+                .map(|_| TraceSlot {
+                    sequence: AtomicU64::new(0),
+                    data: Default::default(),
+                })
+                .collect(),
+        }
+    }
+
+    fn record(&self, a: u64, b: u64, c: u64, d: u64) {
+        // Only the audio thread writes to the ring, so there is no need to synchronize with
+        // other writers here.
+        let block = self.head.load(Ordering::Relaxed);
+        let slot = &self.slots[(block % ProcessTrace::CAPACITY as u64) as usize];
+
+        slot.sequence.store(block * 2 + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        slot.data[0].store(a, Ordering::Relaxed);
+        slot.data[1].store(b, Ordering::Relaxed);
+        slot.data[2].store(c, Ordering::Relaxed);
+        slot.data[3].store(d, Ordering::Relaxed);
+
+        slot.sequence.store(block * 2 + 2, Ordering::Release);
+        self.head.store(block + 1, Ordering::Release);
+    }
+
+    fn snapshot(&self) -> Vec<ProcessTraceEntry> {
+        let head = self.head.load(Ordering::Acquire);
+        let first = head.saturating_sub(ProcessTrace::CAPACITY as u64);
+
+        let mut entries = Vec::with_capacity((head - first) as usize);
+
+        for block in first..head {
+            let slot = &self.slots[(block % ProcessTrace::CAPACITY as u64) as usize];
+            let expected = block * 2 + 2;
+
+            if slot.sequence.load(Ordering::Acquire) != expected {
+                continue;
+            }
+
+            let [a, b, c, d] = [0, 1, 2, 3].map(|i| slot.data[i].load(Ordering::Relaxed));
+
+            fence(Ordering::Acquire);
+            if slot.sequence.load(Ordering::Relaxed) != expected {
+                continue;
+            }
+
+            let steady_time = c as i64;
+
+            entries.push(ProcessTraceEntry {
+                block,
+                frames_count: a as u32,
+                input_events: (a >> 32) as u32,
+                output_events: b as u32,
+                raw_status: (b >> 32) as u32 as clap_process_status,
+                steady_time: (steady_time >= 0).then_some(steady_time as u64),
+                duration: Duration::from_nanos(d & DURATION_MASK),
+                transport: TracedTransport::from_bits(d >> 56),
+            });
+        }
+
+        entries
+    }
+}
@@ -0,0 +1,759 @@
+//! Synthesis of transport information, for hosts that do not have a real transport.
+//!
+//! See the [`TransportSynthesizer`] type for more information.
+
+use clack_common::events::event_types::{TransportEvent, TransportFlags};
+use clack_common::events::{EventFlags, EventHeader};
+use clack_common::utils::{BeatTime, SecondsTime};
+use std::ops::Range;
+
+/// The tempo used by [`TransportSynthesizer::new`], in beats per minute.
+pub const DEFAULT_TEMPO: f64 = 120.0;
+
+/// A section of the timeline during which the tempo follows a single (possibly empty) linear
+/// ramp.
+///
+/// Positions are always computed from the number of frames elapsed since the start of the
+/// segment, so that no error accumulates from one block to the next.
+#[derive(Copy, Clone, Debug)]
+struct Segment {
+    start_beats: f64,
+    start_seconds: f64,
+    tempo: f64,
+    /// The tempo reached at the end of the ramp, or `tempo` if there is none.
+    end_tempo: f64,
+    /// The tempo increment per frame, while the ramp is running.
+    tempo_inc: f64,
+    /// The length of the tempo ramp, in frames. `0` if there is no ramp.
+    ramp_frames: u64,
+}
+
+impl Segment {
+    #[inline]
+    fn tempo_at(&self, frames: u64) -> f64 {
+        if frames < self.ramp_frames {
+            self.tempo + self.tempo_inc * frames as f64
+        } else {
+            self.end_tempo
+        }
+    }
+
+    #[inline]
+    fn tempo_inc_at(&self, frames: u64) -> f64 {
+        if frames < self.ramp_frames {
+            self.tempo_inc
+        } else {
+            0.0
+        }
+    }
+
+    /// Integrates the tempo over the given number of frames, in beats per minute times frames.
+    #[inline]
+    fn beat_minutes_frames(&self, frames: u64) -> f64 {
+        let ramp = frames.min(self.ramp_frames) as f64;
+        let after_ramp = frames.saturating_sub(self.ramp_frames) as f64;
+
+        self.tempo * ramp + 0.5 * self.tempo_inc * ramp * ramp + self.end_tempo * after_ramp
+    }
+
+    #[inline]
+    fn beats_at(&self, frames: u64, sample_rate: f64) -> f64 {
+        self.start_beats + self.beat_minutes_frames(frames) / (60.0 * sample_rate)
+    }
+
+    #[inline]
+    fn seconds_at(&self, frames: u64, sample_rate: f64) -> f64 {
+        self.start_seconds + frames as f64 / sample_rate
+    }
+
+    /// Returns the segment starting the given number of frames into this one.
+    fn split_at(&self, frames: u64, sample_rate: f64) -> Segment {
+        Segment {
+            start_beats: self.beats_at(frames, sample_rate),
+            start_seconds: self.seconds_at(frames, sample_rate),
+            tempo: self.tempo_at(frames),
+            end_tempo: self.end_tempo,
+            tempo_inc: self.tempo_inc_at(frames),
+            ramp_frames: self.ramp_frames.saturating_sub(frames),
+        }
+    }
+}
+
+/// The transport information produced by [`TransportSynthesizer::next_block`] for a single block.
+#[derive(Copy, Clone, Debug)]
+pub struct SynthesizedTransport<'a> {
+    /// The transport information at the start of the block.
+    ///
+    /// This is meant to be given as the `transport` parameter of
+    /// [`StartedPluginAudioProcessor::process`](crate::process::StartedPluginAudioProcessor::process).
+    pub transport: &'a TransportEvent,
+    /// Additional transport events happening within the block, sorted by time.
+    ///
+    /// These are emitted when a tempo ramp ends or when the loop region wraps around in the middle
+    /// of the block. They need to be added to the plugin's input events.
+    pub events: &'a [TransportEvent],
+    /// Whether the song position jumped since the previous block, or jumps within this one.
+    ///
+    /// This is set after a call to [`seek`](TransportSynthesizer::seek), and when the loop region
+    /// wraps around.
+    pub discontinuity: bool,
+}
+
+/// Produces a fresh [`TransportEvent`] for every processed block, for hosts without a timeline.
+///
+/// The synthesizer is configured with a tempo, a time signature, an optional loop region and a
+/// play state, and advances the song position by the number of frames processed while playing.
+/// Positions are always derived from the number of frames processed, rather than accumulated
+/// block by block, so they do not drift over long sessions.
+///
+/// The tempo can also be ramped linearly over a given number of frames, in which case every block
+/// reports the current tempo and its per-sample increment. Ramps only progress while playing.
+/// Ramps are continuous: plugins can follow them exactly using a
+/// [`TempoIntegrator`](clack_common::utils::TempoIntegrator) with the
+/// [`Ramped`](clack_common::utils::TempoCurve::Ramped) curve.
+///
+/// As there is no tempo map, the seconds timeline of the loop region and of [`seek`](Self::seek)
+/// positions is derived from the tempo at the time they are used. Bars are always counted from
+/// the start of the song, using the current time signature.
+///
+/// # Example
+///
+/// ```
+/// use clack_common::events::io::EventBuffer;
+/// use clack_common::utils::BeatTime;
+/// use clack_host::process::transport::TransportSynthesizer;
+///
+/// let mut transport = TransportSynthesizer::new(48_000.0);
+/// transport.set_loop(Some(BeatTime::from_int(0)..BeatTime::from_int(4)));
+/// transport.play();
+///
+/// let mut input_events = EventBuffer::new();
+///
+/// for _ in 0..100 {
+///     let block = transport.next_block(512);
+///
+///     input_events.clear();
+///     input_events.push_all(block.events);
+///     input_events.sort();
+///
+///     // audio_processor.process(.., &input_events.as_input(), .., None, Some(block.transport))
+///     assert!(block.transport.song_pos_beats < BeatTime::from_int(4));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TransportSynthesizer {
+    sample_rate: f64,
+    segment: Segment,
+    /// The number of frames played since the start of the current segment.
+    elapsed: u64,
+    time_signature: (i16, i16),
+    loop_region: Option<Range<BeatTime>>,
+    playing: bool,
+    pending_discontinuity: bool,
+    transport: TransportEvent,
+    events: Vec<TransportEvent>,
+}
+
+impl TransportSynthesizer {
+    /// Creates a new, stopped transport at the start of the song, at [`DEFAULT_TEMPO`] and in 4/4.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `sample_rate` is not strictly positive.
+    pub fn new(sample_rate: f64) -> Self {
+        assert!(sample_rate > 0.0, "Sample rate must be strictly positive");
+
+        let mut synthesizer = Self {
+            sample_rate,
+            segment: Segment {
+                start_beats: 0.0,
+                start_seconds: 0.0,
+                tempo: DEFAULT_TEMPO,
+                end_tempo: DEFAULT_TEMPO,
+                tempo_inc: 0.0,
+                ramp_frames: 0,
+            },
+            elapsed: 0,
+            time_signature: (4, 4),
+            loop_region: None,
+            playing: false,
+            pending_discontinuity: false,
+            transport: TransportEvent {
+                header: EventHeader::new_core(0, EventFlags::empty()),
+                flags: TransportFlags::empty(),
+                song_pos_beats: BeatTime::from_int(0),
+                song_pos_seconds: SecondsTime::from_int(0),
+                tempo: DEFAULT_TEMPO,
+                tempo_inc: 0.0,
+                loop_start_beats: BeatTime::from_int(0),
+                loop_end_beats: BeatTime::from_int(0),
+                loop_start_seconds: SecondsTime::from_int(0),
+                loop_end_seconds: SecondsTime::from_int(0),
+                bar_start: BeatTime::from_int(0),
+                bar_number: 0,
+                time_signature_numerator: 4,
+                time_signature_denominator: 4,
+            },
+            events: Vec::with_capacity(4),
+        };
+
+        synthesizer.transport = synthesizer.event_at(0, 0);
+        synthesizer
+    }
+
+    /// Returns the sample rate this synthesizer was created with.
+    #[inline]
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Returns the current tempo, in beats per minute.
+    #[inline]
+    pub fn tempo(&self) -> f64 {
+        self.segment.tempo_at(self.elapsed)
+    }
+
+    /// Sets the tempo, in beats per minute, starting from the next block.
+    ///
+    /// This cancels any running tempo ramp.
+    ///
+    /// # Panics
+    ///
+    /// This panics if `tempo` is not strictly positive.
+    pub fn set_tempo(&mut self, tempo: f64) {
+        assert!(tempo > 0.0, "Tempo must be strictly positive");
+
+        self.split();
+        self.segment.tempo = tempo;
+        self.segment.end_tempo = tempo;
+        self.segment.tempo_inc = 0.0;
+        self.segment.ramp_frames = 0;
+    }
+
+    /// Linearly ramps the tempo from its current value to `target`, over the given number of
+    /// played frames, starting from the next block.
+    ///
+    /// If `frames` is `0`, this is the same as [`set_tempo`](Self::set_tempo).
+    ///
+    /// # Panics
+    ///
+    /// This panics if `target` is not strictly positive.
+    pub fn ramp_tempo(&mut self, target: f64, frames: u64) {
+        if frames == 0 {
+            return self.set_tempo(target);
+        }
+
+        assert!(target > 0.0, "Tempo must be strictly positive");
+
+        self.split();
+        self.segment.end_tempo = target;
+        self.segment.tempo_inc = (target - self.segment.tempo) / frames as f64;
+        self.segment.ramp_frames = frames;
+    }
+
+    /// Returns the current time signature, as a `(numerator, denominator)` pair.
+    #[inline]
+    pub fn time_signature(&self) -> (i16, i16) {
+        self.time_signature
+    }
+
+    /// Sets the time signature, starting from the next block.
+    ///
+    /// # Panics
+    ///
+    /// This panics if either `numerator` or `denominator` is not strictly positive.
+    pub fn set_time_signature(&mut self, numerator: i16, denominator: i16) {
+        assert!(
+            numerator > 0 && denominator > 0,
+            "Time signature must be strictly positive"
+        );
+
+        self.time_signature = (numerator, denominator);
+    }
+
+    /// Returns the active loop region, in beats, if any.
+    #[inline]
+    pub fn loop_region(&self) -> Option<Range<BeatTime>> {
+        self.loop_region.clone()
+    }
+
+    /// Sets or clears the loop region, in beats, starting from the next block.
+    ///
+    /// The song position wraps back to the start of the region when it reaches its end, but only
+    /// if it crosses the end while playing: a position already past the region is not affected.
+    /// Empty regions are ignored.
+    pub fn set_loop(&mut self, region: Option<Range<BeatTime>>) {
+        self.loop_region = region.filter(|r| r.start < r.end);
+    }
+
+    /// Returns `true` if the transport is playing.
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Starts playing from the current position, starting from the next block.
+    #[inline]
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Stops playing, keeping the current position, starting from the next block.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    /// Returns the current song position, in beats.
+    #[inline]
+    pub fn position(&self) -> BeatTime {
+        BeatTime::from_float(self.segment.beats_at(self.elapsed, self.sample_rate))
+    }
+
+    /// Moves the song position to the given beat, starting from the next block.
+    ///
+    /// The next block is reported as a [discontinuity](SynthesizedTransport::discontinuity).
+    pub fn seek(&mut self, position: BeatTime) {
+        self.split();
+
+        let beats = position.to_float();
+        self.segment.start_beats = beats;
+        self.segment.start_seconds = self.beats_to_seconds(beats);
+        self.pending_discontinuity = true;
+    }
+
+    /// Returns the transport information of the last block returned by
+    /// [`next_block`](Self::next_block).
+    #[inline]
+    pub fn transport(&self) -> &TransportEvent {
+        &self.transport
+    }
+
+    /// Produces the transport information for the next block of `frames_count` frames, and
+    /// advances the song position past it if playing.
+    ///
+    /// This does not allocate, unless the loop region is short enough to wrap around more times
+    /// within a single block than ever before.
+    pub fn next_block(&mut self, frames_count: u32) -> SynthesizedTransport<'_> {
+        self.events.clear();
+
+        let mut discontinuity = std::mem::take(&mut self.pending_discontinuity);
+        self.transport = self.event_at(0, self.elapsed);
+
+        if self.playing {
+            discontinuity |= self.advance(frames_count);
+        }
+
+        SynthesizedTransport {
+            transport: &self.transport,
+            events: &self.events,
+            discontinuity,
+        }
+    }
+
+    /// Advances the position by the given number of frames, emitting the events happening within
+    /// the block. Returns `true` if the loop region wrapped around within the block.
+    fn advance(&mut self, frames_count: u32) -> bool {
+        let frames_count = frames_count as u64;
+        let mut wrapped = false;
+        // The number of frames of the block that were already played.
+        let mut offset = 0;
+
+        loop {
+            let end = self.elapsed + (frames_count - offset);
+            let wrap = self.find_wrap(end);
+
+            let ramp_end = self.segment.ramp_frames;
+            if ramp_end > self.elapsed && ramp_end < wrap.unwrap_or(end) {
+                let time = offset + (ramp_end - self.elapsed);
+                self.events.push(self.event_at(time as u32, ramp_end));
+            }
+
+            let Some(wrap) = wrap else {
+                self.elapsed = end;
+                return wrapped;
+            };
+
+            offset += wrap - self.elapsed;
+            self.wrap_at(wrap);
+
+            // Wrapping exactly at the end of the block is reported at the start of the next one.
+            if offset == frames_count {
+                self.pending_discontinuity = true;
+                return wrapped;
+            }
+
+            wrapped = true;
+            self.events.push(self.event_at(offset as u32, 0));
+        }
+    }
+
+    /// Returns the first frame in `(elapsed, end]` at which the loop region's end is reached.
+    fn find_wrap(&self, end: u64) -> Option<u64> {
+        let loop_end = self.loop_region.as_ref()?.end.to_float();
+
+        if self.beats_at(self.elapsed) >= loop_end || self.beats_at(end) < loop_end {
+            return None;
+        }
+
+        let (mut low, mut high) = (self.elapsed, end);
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if self.beats_at(middle) >= loop_end {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+
+        Some(high)
+    }
+
+    /// Starts a new segment at the start of the loop region, `frames` into the current one.
+    fn wrap_at(&mut self, frames: u64) {
+        let Some(region) = &self.loop_region else {
+            return;
+        };
+
+        let mut segment = self.segment.split_at(frames, self.sample_rate);
+
+        // Keep the part of the frame that overshot the loop end, so that the average tempo stays
+        // exact when the loop length is not a whole number of frames.
+        let overshoot = segment.start_beats - region.end.to_float();
+        let start = region.start.to_float() + overshoot;
+
+        segment.start_beats = start;
+        segment.start_seconds = start * 60.0 / segment.tempo;
+
+        self.segment = segment;
+        self.elapsed = 0;
+    }
+
+    /// Starts a new segment at the current position.
+    fn split(&mut self) {
+        self.segment = self.segment.split_at(self.elapsed, self.sample_rate);
+        self.elapsed = 0;
+    }
+
+    #[inline]
+    fn beats_at(&self, frames: u64) -> f64 {
+        self.segment.beats_at(frames, self.sample_rate)
+    }
+
+    #[inline]
+    fn beats_to_seconds(&self, beats: f64) -> f64 {
+        beats * 60.0 / self.segment.tempo_at(self.elapsed)
+    }
+
+    /// Builds the transport event at the given time of the block, `frames` into the current
+    /// segment.
+    fn event_at(&self, time: u32, frames: u64) -> TransportEvent {
+        let beats = self.segment.beats_at(frames, self.sample_rate);
+        let (numerator, denominator) = self.time_signature;
+
+        let mut flags = TransportFlags::HAS_TEMPO
+            | TransportFlags::HAS_BEATS_TIMELINE
+            | TransportFlags::HAS_SECONDS_TIMELINE
+            | TransportFlags::HAS_TIME_SIGNATURE;
+
+        if self.playing {
+            flags |= TransportFlags::IS_PLAYING;
+        }
+
+        let tempo = self.segment.tempo_at(frames);
+        let (loop_start_beats, loop_end_beats) = match &self.loop_region {
+            Some(region) => {
+                flags |= TransportFlags::IS_LOOP_ACTIVE;
+                (region.start, region.end)
+            }
+            None => (BeatTime::from_int(0), BeatTime::from_int(0)),
+        };
+
+        let bar_length = numerator as f64 * 4.0 / denominator as f64;
+        let bar_number = (beats / bar_length).floor();
+
+        TransportEvent {
+            header: EventHeader::new_core(time, EventFlags::empty()),
+            flags,
+            song_pos_beats: BeatTime::from_float(beats),
+            song_pos_seconds: SecondsTime::from_float(
+                self.segment.seconds_at(frames, self.sample_rate),
+            ),
+            tempo,
+            tempo_inc: self.segment.tempo_inc_at(frames),
+            loop_start_beats,
+            loop_end_beats,
+            loop_start_seconds: SecondsTime::from_float(loop_start_beats.to_float() * 60.0 / tempo),
+            loop_end_seconds: SecondsTime::from_float(loop_end_beats.to_float() * 60.0 / tempo),
+            bar_start: BeatTime::from_float(bar_number * bar_length),
+            bar_number: bar_number as i32,
+            time_signature_numerator: numerator,
+            time_signature_denominator: denominator,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::utils::{TempoCurve, TempoIntegrator};
+
+    fn beats(value: f64) -> BeatTime {
+        BeatTime::from_float(value)
+    }
+
+    /// Checks the position against a hand-computed one, allowing for the last bit to be rounded
+    /// differently.
+    #[track_caller]
+    fn assert_position(actual: BeatTime, expected: f64) {
+        let difference = (actual.to_bits() - beats(expected).to_bits()).abs();
+        assert!(
+            difference <= 1,
+            "expected {expected} beats, got {}",
+            actual.to_float()
+        );
+    }
+
+    #[test]
+    fn stays_still_while_stopped() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+
+        for _ in 0..10 {
+            let block = transport.next_block(512);
+            assert_eq!(block.transport.song_pos_beats, beats(0.0));
+            assert!(!block.transport.flags.contains(TransportFlags::IS_PLAYING));
+            assert!(block.events.is_empty());
+        }
+    }
+
+    #[test]
+    fn advances_without_drifting_over_long_runs() {
+        // 120 BPM at 48kHz is exactly 24000 frames per beat.
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.play();
+
+        // One hour, in blocks of 512 frames.
+        for _ in 0..337_500 {
+            transport.next_block(512);
+        }
+
+        let block = transport.next_block(512);
+        assert_eq!(block.transport.song_pos_beats, BeatTime::from_int(7200));
+        assert_eq!(
+            block.transport.song_pos_seconds,
+            SecondsTime::from_int(3600)
+        );
+        assert_eq!(block.transport.bar_number, 1800);
+        assert_eq!(block.transport.bar_start, BeatTime::from_int(7200));
+        assert!(block.transport.flags.contains(TransportFlags::IS_PLAYING));
+    }
+
+    #[test]
+    fn advances_with_uneven_blocks() {
+        // 100 BPM at 44.1kHz is 26460 frames per beat.
+        let mut transport = TransportSynthesizer::new(44_100.0);
+        transport.set_tempo(100.0);
+        transport.play();
+
+        let mut played = 0u64;
+        for i in 0..100_000u64 {
+            let frames_count = 1 + (i * 7919 % 1021);
+            let block = transport.next_block(frames_count as u32);
+            assert_position(block.transport.song_pos_beats, played as f64 / 26_460.0);
+
+            played += frames_count;
+        }
+    }
+
+    #[test]
+    fn counts_bars_with_time_signature() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_time_signature(6, 8);
+        transport.seek(beats(10.0));
+
+        let block = transport.next_block(512);
+        assert!(block.discontinuity);
+
+        // 6/8 bars are 3 quarter notes long.
+        assert_eq!(block.transport.bar_number, 3);
+        assert_eq!(block.transport.bar_start, BeatTime::from_int(9));
+        assert_eq!(block.transport.song_pos_seconds, SecondsTime::from_int(5));
+
+        assert!(!transport.next_block(512).discontinuity);
+    }
+
+    #[test]
+    fn ramps_tempo_across_blocks() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_tempo(60.0);
+        transport.ramp_tempo(120.0, 48_000);
+        transport.play();
+
+        let mut ramp_end_events = 0;
+        for _ in 0..100 {
+            let block = transport.next_block(500);
+            ramp_end_events += block.events.len();
+        }
+
+        // The ramp fits exactly in 96 blocks, so it ends on a block boundary.
+        assert_eq!(ramp_end_events, 0);
+
+        // Over the ramp the average tempo is 90 BPM, then 2000 more frames are played at 120 BPM.
+        let block = transport.next_block(500);
+        assert_eq!(block.transport.tempo, 120.0);
+        assert_eq!(block.transport.tempo_inc, 0.0);
+        assert_position(block.transport.song_pos_beats, 1.5 + 2000.0 / 24_000.0);
+    }
+
+    #[test]
+    fn reports_ramp_ending_within_a_block() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_tempo(60.0);
+        transport.ramp_tempo(120.0, 1000);
+        transport.play();
+
+        let block = transport.next_block(512);
+        assert_eq!(block.transport.tempo, 60.0);
+        assert_eq!(block.transport.tempo_inc, 60.0 / 1000.0);
+        assert!(block.events.is_empty());
+
+        let block = transport.next_block(512);
+        assert_eq!(block.transport.tempo, 60.0 + 0.06 * 512.0);
+        assert_eq!(block.events.len(), 1);
+
+        let ramp_end = block.events[0];
+        assert_eq!(ramp_end.header.time(), 1000 - 512);
+        assert_eq!(ramp_end.tempo, 120.0);
+        assert_eq!(ramp_end.tempo_inc, 0.0);
+        // The ramp lasts 1000 frames, at an average of 90 BPM.
+        assert_position(ramp_end.song_pos_beats, 1000.0 * 90.0 / 60.0 / 48_000.0);
+    }
+
+    #[test]
+    fn ramped_blocks_match_tempo_integration() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_tempo(70.0);
+        transport.ramp_tempo(170.0, 100_000);
+        transport.play();
+
+        let mut integrator = TempoIntegrator::new(48_000.0, TempoCurve::Ramped);
+        let mut beats = 0.0;
+
+        for i in 0..300u32 {
+            let frames_count = 300 + i * 7 % 500;
+            let block = transport.next_block(frames_count);
+            assert_position(block.transport.song_pos_beats, beats);
+
+            // Follow the transport events within the block, if any.
+            integrator.update(block.transport);
+            let mut time = 0;
+            for event in block.events {
+                beats += integrator.advance(event.header.time() - time);
+                integrator.update(event);
+                time = event.header.time();
+            }
+
+            beats += integrator.advance(frames_count - time);
+        }
+
+        assert_eq!(transport.tempo(), 170.0);
+        assert_eq!(integrator.tempo(), Some(170.0));
+    }
+
+    #[test]
+    fn wraps_around_loop_region() {
+        // 120 BPM at 48kHz: the 4-beat loop lasts 96000 frames, which is not a multiple of 700.
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_loop(Some(BeatTime::from_int(2)..BeatTime::from_int(6)));
+        transport.play();
+
+        let mut played = 0u64;
+        let mut wraps = 0;
+
+        for _ in 0..10_000 {
+            let block = transport.next_block(700);
+
+            // The loop end is first reached after 144000 frames, then every 96000 frames.
+            let expected = if played < 144_000 {
+                played as f64 / 24_000.0
+            } else {
+                2.0 + ((played - 144_000) % 96_000) as f64 / 24_000.0
+            };
+
+            assert_position(block.transport.song_pos_beats, expected);
+            assert!(block
+                .transport
+                .flags
+                .contains(TransportFlags::IS_LOOP_ACTIVE));
+
+            if let Some(event) = block.events.first() {
+                assert!(block.discontinuity);
+                assert_position(event.song_pos_beats, 2.0);
+                assert_eq!(
+                    played + event.header.time() as u64,
+                    144_000 + wraps * 96_000
+                );
+                wraps += 1;
+            } else if block.discontinuity {
+                // The loop end was reached exactly at the end of the previous block.
+                assert_eq!(played, 144_000 + wraps * 96_000);
+                wraps += 1;
+            }
+
+            played += 700;
+        }
+
+        assert_eq!(wraps, (7_000_000 - 144_000 - 1) / 96_000 + 1);
+    }
+
+    #[test]
+    fn wraps_at_block_boundary_on_next_block() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_loop(Some(BeatTime::from_int(0)..BeatTime::from_int(1)));
+        transport.play();
+
+        for _ in 0..(24_000 / 480) {
+            let block = transport.next_block(480);
+            assert!(block.events.is_empty());
+        }
+
+        let block = transport.next_block(480);
+        assert!(block.discontinuity);
+        assert!(block.events.is_empty());
+        assert_eq!(block.transport.song_pos_beats, BeatTime::from_int(0));
+    }
+
+    #[test]
+    fn does_not_wrap_past_loop_region() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.set_loop(Some(BeatTime::from_int(0)..BeatTime::from_int(1)));
+        transport.seek(BeatTime::from_int(8));
+        transport.play();
+
+        transport.next_block(48_000);
+        let block = transport.next_block(512);
+        assert!(!block.discontinuity);
+        assert_eq!(block.transport.song_pos_beats, BeatTime::from_int(10));
+    }
+
+    #[test]
+    fn resumes_after_stopping() {
+        let mut transport = TransportSynthesizer::new(48_000.0);
+        transport.play();
+        transport.next_block(24_000);
+        transport.stop();
+
+        let block = transport.next_block(24_000);
+        assert!(!block.transport.flags.contains(TransportFlags::IS_PLAYING));
+        assert_eq!(block.transport.song_pos_beats, BeatTime::from_int(1));
+
+        transport.next_block(24_000);
+        transport.play();
+
+        let block = transport.next_block(24_000);
+        assert!(block.transport.flags.contains(TransportFlags::IS_PLAYING));
+        assert!(!block.discontinuity);
+        assert_eq!(block.transport.song_pos_beats, BeatTime::from_int(1));
+        assert_eq!(transport.position(), BeatTime::from_int(2));
+    }
+}
@@ -0,0 +1,377 @@
+//! Host-side dry/wet mixing and bypass around any plugin.
+//!
+//! See the [`PluginWetDryWrapper`] type for more information.
+
+use crate::host::HostHandlers;
+use crate::plugin::PluginInstanceError;
+use crate::process::audio_buffers::{
+    AudioPortBuffer, AudioPortBufferType, AudioPorts, InputChannel,
+};
+use crate::process::{PluginAudioConfiguration, ProcessStatus, StartedPluginAudioProcessor};
+use clack_common::events::event_types::TransportEvent;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// The duration of the ramp applied when the mix amount or the bypass state changes, in seconds.
+pub const MIX_RAMP_DURATION: f64 = 0.01;
+
+struct WetDryControls {
+    mix: AtomicU32,
+    bypassed: AtomicBool,
+}
+
+/// A handle to the mix and bypass controls of a [`PluginWetDryWrapper`].
+///
+/// This handle can be cloned and sent to any thread, allowing e.g. the UI or main thread to
+/// control the wrapper while it is processing on the audio thread.
+#[derive(Clone)]
+pub struct WetDryHandle {
+    controls: Arc<WetDryControls>,
+}
+
+impl WetDryHandle {
+    /// Sets the wet amount of the mix, between `0.0` (dry only) and `1.0` (wet only).
+    ///
+    /// The given value is clamped to this range. `NaN` values are ignored.
+    #[inline]
+    pub fn set_mix(&self, mix: f32) {
+        if mix.is_nan() {
+            return;
+        }
+
+        let mix = mix.clamp(0.0, 1.0);
+        self.controls.mix.store(mix.to_bits(), Ordering::Relaxed)
+    }
+
+    /// Returns the current wet amount of the mix.
+    #[inline]
+    pub fn mix(&self) -> f32 {
+        f32::from_bits(self.controls.mix.load(Ordering::Relaxed))
+    }
+
+    /// Sets whether the plugin is bypassed, i.e. if only the dry signal is output.
+    #[inline]
+    pub fn set_bypassed(&self, bypassed: bool) {
+        self.controls.bypassed.store(bypassed, Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the plugin is currently bypassed.
+    #[inline]
+    pub fn is_bypassed(&self) -> bool {
+        self.controls.bypassed.load(Ordering::Relaxed)
+    }
+}
+
+/// A wrapper around a started plugin audio processor, which mixes its output with its dry input.
+///
+/// This allows hosts to provide dry/wet mixing and bypass for any plugin, without needing the
+/// plugin's cooperation. Only the plugin's main port is used, and its input and output must have
+/// the same number of 32-bit float channels.
+///
+/// The dry signal is delayed by the plugin's latency, so that it stays aligned with the plugin's
+/// output. Changes to the mix amount or the bypass state are smoothed over
+/// [`MIX_RAMP_DURATION`] to avoid clicks.
+///
+/// Note the wrapped plugin keeps processing while bypassed, so that un-bypassing it is also
+/// click-free.
+///
+/// # Plugin bypass parameters
+///
+/// Some plugins expose their own bypass parameter (flagged with `IS_BYPASS` in the `params`
+/// extension), which they handle themselves while processing. Hosts should prefer driving that
+/// parameter when it exists, and leave this wrapper's bypass for plugins that don't have one.
+///
+/// Both bypasses are independent: un-bypassing this wrapper doesn't un-bypass the plugin if its
+/// own bypass parameter is still enabled, and vice-versa. A well-behaved plugin outputs its input
+/// delayed by its latency when bypassed, which is the same as this wrapper's dry signal, so
+/// enabling both at once is harmless, but the host should track which one it actually used.
+///
+/// The mix amount and bypass state can be changed from any thread, using either the
+/// [`set_mix`](Self::set_mix) and [`set_bypassed`](Self::set_bypassed) methods, or a
+/// [`WetDryHandle`] obtained from [`handle`](Self::handle).
+pub struct PluginWetDryWrapper<H: HostHandlers> {
+    processor: StartedPluginAudioProcessor<H>,
+    controls: WetDryHandle,
+    channel_count: usize,
+    max_frames_count: u32,
+    delay_lines: Vec<Vec<f32>>,
+    delay_position: usize,
+    dry: Vec<Vec<f32>>,
+    input_ports: AudioPorts,
+    output_ports: AudioPorts,
+    ramp_step: f32,
+    current_wet: Option<f32>,
+}
+
+impl<H: HostHandlers> PluginWetDryWrapper<H> {
+    /// Wraps the given started audio processor.
+    ///
+    /// The `configuration` must be the one the plugin was activated with, and `latency` must be
+    /// the plugin's latency in samples, as reported by its `latency` extension. All buffers are
+    /// allocated here, so this should be called on the main thread, right after activation.
+    ///
+    /// The mix is initially fully wet, and the plugin isn't bypassed.
+    pub fn new(
+        processor: StartedPluginAudioProcessor<H>,
+        configuration: PluginAudioConfiguration,
+        channel_count: usize,
+        latency: u32,
+    ) -> Self {
+        let ramp_frames = (configuration.sample_rate * MIX_RAMP_DURATION).max(1.0);
+
+        Self {
+            processor,
+            controls: WetDryHandle {
+                controls: Arc::new(WetDryControls {
+                    mix: AtomicU32::new(1.0f32.to_bits()),
+                    bypassed: AtomicBool::new(false),
+                }),
+            },
+            channel_count,
+            max_frames_count: configuration.max_frames_count,
+            delay_lines: vec![vec![0.0; latency as usize]; channel_count],
+            delay_position: 0,
+            dry: vec![vec![0.0; configuration.max_frames_count as usize]; channel_count],
+            input_ports: AudioPorts::with_capacity(channel_count, 1),
+            output_ports: AudioPorts::with_capacity(channel_count, 1),
+            ramp_step: (1.0 / ramp_frames) as f32,
+            current_wet: None,
+        }
+    }
+
+    /// Returns a handle to this wrapper's mix and bypass controls.
+    #[inline]
+    pub fn handle(&self) -> WetDryHandle {
+        self.controls.clone()
+    }
+
+    /// Sets the wet amount of the mix. See [`WetDryHandle::set_mix`].
+    #[inline]
+    pub fn set_mix(&self, mix: f32) {
+        self.controls.set_mix(mix)
+    }
+
+    /// Sets whether the plugin is bypassed. See [`WetDryHandle::set_bypassed`].
+    #[inline]
+    pub fn set_bypassed(&self, bypassed: bool) {
+        self.controls.set_bypassed(bypassed)
+    }
+
+    /// Returns the latency the dry signal is delayed by, in samples.
+    #[inline]
+    pub fn latency(&self) -> u32 {
+        self.delay_lines.first().map_or(0, |l| l.len() as u32)
+    }
+
+    /// Returns a mutable reference to the wrapped audio processor.
+    #[inline]
+    pub fn processor_mut(&mut self) -> &mut StartedPluginAudioProcessor<H> {
+        &mut self.processor
+    }
+
+    /// Resets the wrapped plugin's audio processing state, as well as the dry signal's delay line.
+    ///
+    /// Any ongoing mix ramp is also completed immediately.
+    pub fn reset(&mut self) {
+        self.processor.reset();
+
+        for delay_line in &mut self.delay_lines {
+            delay_line.fill(0.0);
+        }
+
+        self.delay_position = 0;
+        self.current_wet = None;
+    }
+
+    /// Returns the wrapped audio processor.
+    #[inline]
+    pub fn into_inner(self) -> StartedPluginAudioProcessor<H> {
+        self.processor
+    }
+
+    /// Processes a block of audio through the wrapped plugin, and mixes its output with the dry
+    /// input.
+    ///
+    /// The `input` and `output` slices must both contain one buffer per channel. The number of
+    /// frames processed is the length of the shortest buffer, which must not be greater than the
+    /// maximum frame count the plugin was activated with.
+    ///
+    /// See [`StartedPluginAudioProcessor::process`] for more information about the other
+    /// arguments.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`WetDryError`] if the given buffers do not match the wrapper's
+    /// configuration, or if the plugin fails to process. In the latter case, the output buffers
+    /// are filled with the dry signal, and the wrapped audio processor can still be used for the
+    /// next blocks.
+    pub fn process<I: AsMut<[f32]>, O: AsMut<[f32]>>(
+        &mut self,
+        input: &mut [I],
+        output: &mut [O],
+        input_events: &InputEvents,
+        output_events: &mut OutputEvents,
+        steady_time: Option<u64>,
+        transport: Option<&TransportEvent>,
+    ) -> Result<ProcessStatus, WetDryError> {
+        if input.len() != self.channel_count || output.len() != self.channel_count {
+            return Err(WetDryError::ChannelCountMismatch);
+        }
+
+        let frames_count = input
+            .iter_mut()
+            .map(|c| c.as_mut().len())
+            .chain(output.iter_mut().map(|c| c.as_mut().len()))
+            .min()
+            .unwrap_or(0);
+
+        if frames_count > self.max_frames_count as usize {
+            return Err(WetDryError::TooManyFrames);
+        }
+
+        self.delay_dry_input(input, frames_count);
+
+        let status = {
+            let inputs = self.input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only(
+                    input
+                        .iter_mut()
+                        .map(|c| InputChannel::variable(&mut c.as_mut()[..frames_count])),
+                ),
+                latency: 0,
+            }]);
+
+            let mut outputs = self.output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only(
+                    output.iter_mut().map(|c| &mut c.as_mut()[..frames_count]),
+                ),
+                latency: 0,
+            }]);
+
+            self.processor.process(
+                &inputs,
+                &mut outputs,
+                input_events,
+                output_events,
+                steady_time,
+                transport,
+            )
+        };
+
+        let status = match status {
+            Ok(status) => status,
+            Err(error) => {
+                self.output_dry(output, frames_count);
+                return Err(WetDryError::Plugin(error));
+            }
+        };
+
+        let target = if self.controls.is_bypassed() {
+            0.0
+        } else {
+            self.controls.mix()
+        };
+
+        let start = self.current_wet.unwrap_or(target);
+        let mut end = start;
+
+        for (dry, wet) in self.dry.iter().zip(output.iter_mut()) {
+            let dry = &dry[..frames_count];
+            let wet = &mut wet.as_mut()[..frames_count];
+
+            let mut amount = start;
+            for (dry, wet) in dry.iter().zip(wet) {
+                amount = ramp(amount, target, self.ramp_step);
+                *wet = *wet * amount + *dry * (1.0 - amount);
+            }
+
+            end = amount;
+        }
+
+        self.current_wet = Some(end);
+
+        // Keep processing until the ramp is completed.
+        if end != target {
+            return Ok(status.combined_with(ProcessStatus::Continue));
+        }
+
+        Ok(status)
+    }
+
+    /// Replaces the output with the dry signal, e.g. when the plugin failed to process.
+    ///
+    /// The next successful block ramps back to the current mix from there.
+    fn output_dry<O: AsMut<[f32]>>(&mut self, output: &mut [O], frames_count: usize) {
+        for (dry, output) in self.dry.iter().zip(output.iter_mut()) {
+            output.as_mut()[..frames_count].copy_from_slice(&dry[..frames_count]);
+        }
+
+        self.current_wet = Some(0.0);
+    }
+
+    /// Pushes the given input through the delay line, and stores its delayed output as the dry
+    /// signal.
+    fn delay_dry_input<I: AsMut<[f32]>>(&mut self, input: &mut [I], frames_count: usize) {
+        let mut position = self.delay_position;
+
+        for ((input, delay_line), dry) in input
+            .iter_mut()
+            .zip(&mut self.delay_lines)
+            .zip(&mut self.dry)
+        {
+            let input = &input.as_mut()[..frames_count];
+            let dry = &mut dry[..frames_count];
+
+            if delay_line.is_empty() {
+                dry.copy_from_slice(input);
+                continue;
+            }
+
+            position = self.delay_position;
+            for (input, dry) in input.iter().zip(dry) {
+                *dry = core::mem::replace(&mut delay_line[position], *input);
+                position = (position + 1) % delay_line.len();
+            }
+        }
+
+        self.delay_position = position;
+    }
+}
+
+/// Moves `current` towards `target` by at most `step`.
+#[inline]
+fn ramp(current: f32, target: f32, step: f32) -> f32 {
+    if current < target {
+        (current + step).min(target)
+    } else {
+        (current - step).max(target)
+    }
+}
+
+/// Errors that can occur while processing a [`PluginWetDryWrapper`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WetDryError {
+    /// The number of given input or output buffers does not match the wrapper's channel count.
+    ChannelCountMismatch,
+    /// The given buffers are larger than the maximum frame count.
+    TooManyFrames,
+    /// The wrapped plugin failed to process.
+    Plugin(PluginInstanceError),
+}
+
+impl Display for WetDryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ChannelCountMismatch => {
+                f.write_str("Buffer channel count does not match the wrapper's")
+            }
+            Self::TooManyFrames => f.write_str("Buffers exceed the maximum frame count"),
+            Self::Plugin(error) => Display::fmt(error, f),
+        }
+    }
+}
+
+impl Error for WetDryError {}
@@ -0,0 +1,208 @@
+use clack_host::prelude::*;
+use clack_host::process::StartedPluginAudioProcessor;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+pub struct NoopPlugin;
+
+/// Checks that the configuration it receives after activation is the one it was activated with.
+pub struct NoopPluginAudioProcessor {
+    audio_config: PluginAudioConfiguration,
+}
+
+impl NoopPluginAudioProcessor {
+    fn check_config(&self, audio_config: PluginAudioConfiguration) -> Result<(), PluginError> {
+        if audio_config != self.audio_config {
+            return Err(PluginError::Message("Unexpected audio configuration"));
+        }
+
+        Ok(())
+    }
+}
+
+impl Plugin for NoopPlugin {
+    type AudioProcessor<'a> = NoopPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for NoopPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.noop", "Noop")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for NoopPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { audio_config })
+    }
+
+    fn process(
+        &mut self,
+        process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.check_config(process.audio_config)?;
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static NOOP_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<NoopPlugin>);
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 256,
+};
+
+fn instantiate() -> PluginInstance<()> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.noop\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn activation_state_is_tracked() {
+    let mut instance = instantiate();
+
+    assert!(!instance.is_active());
+    assert!(!instance.has_active_processor());
+    assert_eq!(instance.activation_config(), None);
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+
+    assert!(instance.is_active());
+    assert!(instance.has_active_processor());
+    assert_eq!(instance.activation_config(), Some(CONFIGURATION));
+
+    instance.deactivate_unchecked(processor);
+
+    assert!(!instance.is_active());
+    assert!(!instance.has_active_processor());
+    assert_eq!(instance.activation_config(), None);
+}
+
+#[test]
+pub fn activating_twice_fails() {
+    let mut instance = instantiate();
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+
+    assert_eq!(
+        instance.activate_unchecked(|_, _| (), CONFIGURATION).err(),
+        Some(PluginInstanceError::AlreadyActivatedPlugin)
+    );
+
+    // The instance's state must not have been touched by the failed activation.
+    assert_eq!(instance.activation_config(), Some(CONFIGURATION));
+    instance.deactivate_unchecked(processor);
+}
+
+#[test]
+pub fn dropped_processor_can_be_recovered() {
+    let mut instance = instantiate();
+
+    drop(
+        instance
+            .activate_unchecked(|_, _| (), CONFIGURATION)
+            .unwrap(),
+    );
+
+    assert!(instance.is_active());
+    assert!(!instance.has_active_processor());
+    assert_eq!(instance.activation_config(), Some(CONFIGURATION));
+
+    assert_eq!(
+        instance.activate_unchecked(|_, _| (), CONFIGURATION).err(),
+        Some(PluginInstanceError::AlreadyActivatedPlugin)
+    );
+
+    instance.try_deactivate_unchecked().unwrap();
+    assert!(!instance.is_active());
+    assert_eq!(instance.activation_config(), None);
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+    assert!(instance.has_active_processor());
+    instance.deactivate_unchecked(processor);
+}
+
+fn process_block(processor: &mut StartedPluginAudioProcessor<()>) -> bool {
+    let mut buffer = [0.0f32; 16];
+    let mut input_ports = AudioPorts::with_capacity(1, 1);
+    let mut output_ports = AudioPorts::with_capacity(1, 1);
+    let mut output = [0.0f32; 16];
+
+    processor
+        .process(
+            &input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only([InputChannel::variable(
+                    &mut buffer,
+                )]),
+                latency: 0,
+            }]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([&mut output[..]]),
+                latency: 0,
+            }]),
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .is_ok()
+}
+
+#[test]
+pub fn plugin_receives_configuration_after_activation() {
+    let mut instance = instantiate();
+
+    let other_configuration = PluginAudioConfiguration {
+        sample_rate: 96_000.0,
+        ..CONFIGURATION
+    };
+
+    for configuration in [CONFIGURATION, other_configuration] {
+        let mut processor = instance
+            .activate_unchecked(|_, _| (), configuration)
+            .unwrap()
+            .start_processing()
+            .unwrap();
+
+        assert!(process_block(&mut processor));
+
+        instance.deactivate_unchecked(processor.stop_processing());
+    }
+}
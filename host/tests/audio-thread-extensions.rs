@@ -0,0 +1,199 @@
+use clack_extensions::params::*;
+use clack_extensions::tail::*;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const TAIL_LENGTH: ClapId = ClapId::new(0);
+
+/// A plugin which tail length is set by its single parameter.
+pub struct TailPlugin;
+
+pub struct TailPluginMainThread;
+
+impl PluginMainThread<'_, ()> for TailPluginMainThread {}
+
+impl PluginMainThreadParams for TailPluginMainThread {
+    fn count(&mut self) -> u32 {
+        1
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        if param_index == 0 {
+            info.set(&ParamInfo {
+                id: TAIL_LENGTH,
+                flags: ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_STEPPED,
+                cookie: Default::default(),
+                name: b"Tail Length",
+                module: b"",
+                min_value: 0.0,
+                max_value: 48_000.0,
+                default_value: 0.0,
+            })
+        }
+    }
+
+    fn get_value(&mut self, _param_id: ClapId) -> Option<f64> {
+        None
+    }
+
+    fn value_to_text(
+        &mut self,
+        _param_id: ClapId,
+        _value: f64,
+        _writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        Err(std::fmt::Error)
+    }
+
+    fn text_to_value(&mut self, _param_id: ClapId, _text: &CStr) -> Option<f64> {
+        None
+    }
+
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {
+        unreachable!("This test only flushes while the plugin is active")
+    }
+}
+
+pub struct TailPluginAudioProcessor {
+    tail_length: u32,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), TailPluginMainThread> for TailPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut TailPluginMainThread,
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { tail_length: 0 })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Tail)
+    }
+}
+
+impl PluginAudioProcessorParams for TailPluginAudioProcessor {
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        for event in input {
+            if let Some(event) = event.as_event::<ParamValueEvent>() {
+                self.tail_length = event.value() as u32;
+            }
+        }
+    }
+}
+
+impl PluginTailImpl for TailPluginAudioProcessor {
+    fn get(&self) -> TailLength {
+        TailLength::Finite(self.tail_length)
+    }
+}
+
+impl Plugin for TailPlugin {
+    type AudioProcessor<'a> = TailPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = TailPluginMainThread;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder.register::<PluginParams>().register::<PluginTail>();
+    }
+}
+
+impl DefaultPluginFactory for TailPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.tail", "Tail")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(TailPluginMainThread)
+    }
+}
+
+pub static TAIL_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<TailPlugin>);
+
+#[test]
+pub fn extensions_can_be_used_from_audio_thread() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&TAIL_ENTRY, "/tail.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.tail\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 1,
+        max_frames_count: 256,
+    };
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap();
+
+    // Extensions are queried on the main thread, then sent to the audio thread.
+    let tail = instance
+        .plugin_handle_unchecked()
+        .get_extension::<PluginTail>()
+        .unwrap();
+    let params = instance
+        .plugin_handle_unchecked()
+        .get_extension::<PluginParams>()
+        .unwrap();
+
+    let processor = std::thread::scope(|s| {
+        s.spawn(move || {
+            let mut processor = processor;
+            assert_eq!(tail.get(&processor.plugin_handle()), TailLength::Finite(0));
+
+            let mut events = EventBuffer::new();
+            events.push(&ParamValueEvent::new(
+                0,
+                TAIL_LENGTH,
+                Pckn::match_all(),
+                480.0,
+                Cookie::empty(),
+            ));
+
+            params.flush_active(
+                &mut processor.plugin_handle(),
+                &events.as_input(),
+                &mut OutputEvents::void(),
+            );
+
+            assert_eq!(
+                tail.get(&processor.plugin_handle()),
+                TailLength::Finite(480)
+            );
+            processor
+        })
+        .join()
+        .unwrap()
+    });
+
+    instance.deactivate_unchecked(processor);
+}
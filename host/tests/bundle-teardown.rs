@@ -0,0 +1,153 @@
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::entry::{Entry, EntryFactories, EntryLoadError};
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+static INITS: AtomicUsize = AtomicUsize::new(0);
+static DEINITS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_INSTANCES: AtomicUsize = AtomicUsize::new(0);
+static ORDERING_VIOLATED: AtomicBool = AtomicBool::new(false);
+
+/// A plugin which keeps track of how many of its instances are alive.
+struct TrackedPlugin;
+
+struct TrackedPluginShared;
+
+impl PluginShared<'_> for TrackedPluginShared {}
+
+impl Drop for TrackedPluginShared {
+    fn drop(&mut self) {
+        LIVE_INSTANCES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct TrackedPluginMainThread;
+
+impl PluginMainThread<'_, TrackedPluginShared> for TrackedPluginMainThread {}
+
+impl Plugin for TrackedPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = TrackedPluginShared;
+    type MainThread<'a> = TrackedPluginMainThread;
+}
+
+impl DefaultPluginFactory for TrackedPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.tracked", "Tracked")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        LIVE_INSTANCES.fetch_add(1, Ordering::SeqCst);
+        Ok(TrackedPluginShared)
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(TrackedPluginMainThread)
+    }
+}
+
+/// An entry which counts its initializations, and checks it is never deinitialized while some of
+/// its instances are still alive.
+struct TrackedEntry(SinglePluginEntry<TrackedPlugin>);
+
+impl Entry for TrackedEntry {
+    fn new(plugin_path: &CStr) -> Result<Self, EntryLoadError> {
+        // The entry must never be initialized twice without being deinitialized in between.
+        if INITS.fetch_add(1, Ordering::SeqCst) != DEINITS.load(Ordering::SeqCst) {
+            ORDERING_VIOLATED.store(true, Ordering::SeqCst);
+        }
+
+        Ok(Self(SinglePluginEntry::new(plugin_path)?))
+    }
+
+    fn declare_factories<'a>(&'a self, builder: &mut EntryFactories<'a>) {
+        self.0.declare_factories(builder)
+    }
+}
+
+impl Drop for TrackedEntry {
+    fn drop(&mut self) {
+        if LIVE_INSTANCES.load(Ordering::SeqCst) != 0 {
+            ORDERING_VIOLATED.store(true, Ordering::SeqCst);
+        }
+
+        DEINITS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+static TRACKED_ENTRY: EntryDescriptor = clack_entry!(TrackedEntry);
+
+fn load_bundle() -> PluginBundle {
+    // SAFETY: the entry is a Clack plugin, which is sound to load multiple times.
+    unsafe { PluginBundle::load_from_raw(&TRACKED_ENTRY, "/tracked.clap") }.unwrap()
+}
+
+fn instantiate(bundle: &PluginBundle) -> PluginInstance<()> {
+    let host = HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.tracked\0").unwrap(),
+        &host,
+    )
+    .unwrap()
+}
+
+#[test]
+fn entry_is_deinitialized_once_after_the_last_instance() {
+    const THREADS: usize = 4;
+    const ITERATIONS: usize = if cfg!(miri) { 4 } else { 200 };
+
+    thread::scope(|s| {
+        for thread_index in 0..THREADS {
+            s.spawn(move || {
+                for i in 0..ITERATIONS {
+                    let bundle = load_bundle();
+                    let instance = instantiate(&bundle);
+
+                    // Half of the time, the last handle is dropped before the instance.
+                    if (thread_index + i) % 2 == 0 {
+                        drop(bundle);
+                        drop(instance);
+                    } else {
+                        drop(instance);
+                        drop(bundle);
+                    }
+                }
+            });
+        }
+    });
+
+    assert!(!ORDERING_VIOLATED.load(Ordering::SeqCst));
+    assert_eq!(LIVE_INSTANCES.load(Ordering::SeqCst), 0);
+    assert!(INITS.load(Ordering::SeqCst) > 0);
+    assert_eq!(INITS.load(Ordering::SeqCst), DEINITS.load(Ordering::SeqCst));
+
+    // Instances are counted across all handles, and outlive them.
+    let bundle = load_bundle();
+    let other_handle = bundle.clone();
+    let first = instantiate(&bundle);
+    let second = instantiate(&other_handle);
+    assert_eq!(bundle.instance_count(), 2);
+    assert_eq!(other_handle.instance_count(), 2);
+
+    drop(first);
+    assert_eq!(bundle.instance_count(), 1);
+
+    let inits = INITS.load(Ordering::SeqCst);
+    drop(bundle);
+    drop(other_handle);
+    assert_eq!(DEINITS.load(Ordering::SeqCst), inits - 1);
+
+    drop(second);
+    assert_eq!(DEINITS.load(Ordering::SeqCst), inits);
+    assert!(!ORDERING_VIOLATED.load(Ordering::SeqCst));
+}
@@ -0,0 +1,181 @@
+//! A `ChannelAdapter` mixes a track's channels into a plugin's layout and back around each
+//! process call.
+
+use clack_host::prelude::*;
+use clack_host::process::channel_adapter::{ChannelAdapter, ChannelLayout};
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const BLOCK_SIZE: usize = 4;
+
+/// A plugin doubling the level of all of its channels, whatever their count.
+pub struct DoublerPlugin;
+
+pub struct DoublerPluginAudioProcessor;
+
+impl Plugin for DoublerPlugin {
+    type AudioProcessor<'a> = DoublerPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for DoublerPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.doubler", "Doubler")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for DoublerPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let mut port_pair = audio.port_pair(0).unwrap();
+        let mut channels = port_pair.channels()?.into_f32().unwrap();
+
+        for pair in channels.iter_mut() {
+            if let ChannelPair::InputOutput(input, output) = pair {
+                for (input, output) in input.iter().zip(output) {
+                    *output = input * 2.0;
+                }
+            }
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static DOUBLER_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<DoublerPlugin>);
+
+struct DoublerHostShared;
+
+impl SharedHandler<'_> for DoublerHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct DoublerHost;
+
+impl HostHandlers for DoublerHost {
+    type Shared<'a> = DoublerHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn process_through(
+    track_layout: ChannelLayout,
+    plugin_layout: ChannelLayout,
+    input: &mut [[f32; BLOCK_SIZE]],
+) -> Vec<[f32; BLOCK_SIZE]> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&DOUBLER_ENTRY, "/doubler.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<DoublerHost>::new(
+        |_| DoublerHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.doubler\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE as u32,
+        max_frames_count: BLOCK_SIZE as u32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut adapter = ChannelAdapter::new(track_layout, plugin_layout, BLOCK_SIZE as u32).unwrap();
+    let mut output = vec![[0.0; BLOCK_SIZE]; track_layout.channel_count()];
+
+    adapter
+        .process(
+            &mut processor,
+            input,
+            &mut output,
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    instance.deactivate_unchecked(processor.stop_processing());
+    output
+}
+
+#[test]
+pub fn matching_layouts_are_passed_through() {
+    let mut input = [[0.1, -0.2, 0.3, f32::MIN_POSITIVE], [1e-30, 0.0, -1.0, 0.5]];
+    let output = process_through(ChannelLayout::Stereo, ChannelLayout::Stereo, &mut input);
+
+    let expected: Vec<_> = input.iter().map(|c| c.map(|s| s * 2.0)).collect();
+    assert_eq!(output, expected);
+}
+
+#[test]
+pub fn stereo_track_is_adapted_to_mono_plugin() {
+    let mut input = [[1.0, 0.5, 0.0, -1.0], [0.0, 0.5, 1.0, -1.0]];
+    let output = process_through(ChannelLayout::Stereo, ChannelLayout::Mono, &mut input);
+
+    // Downmixed to mono, doubled, then duplicated on both channels.
+    assert_eq!(output, [[1.0, 1.0, 1.0, -2.0], [1.0, 1.0, 1.0, -2.0]]);
+}
+
+#[test]
+pub fn surround_track_is_adapted_to_stereo_plugin() {
+    let k = std::f32::consts::FRAC_1_SQRT_2;
+    let mut input = [
+        [1.0; BLOCK_SIZE],
+        [0.0; BLOCK_SIZE],
+        [1.0; BLOCK_SIZE],
+        [1.0; BLOCK_SIZE],
+        [0.0; BLOCK_SIZE],
+        [1.0; BLOCK_SIZE],
+    ];
+    let output = process_through(ChannelLayout::Surround51, ChannelLayout::Stereo, &mut input);
+
+    // Folded down to stereo, doubled, then sent to the front channels only.
+    assert_eq!(
+        output,
+        [
+            [(1.0 + k) * 2.0; BLOCK_SIZE],
+            [(k + k) * 2.0; BLOCK_SIZE],
+            [0.0; BLOCK_SIZE],
+            [0.0; BLOCK_SIZE],
+            [0.0; BLOCK_SIZE],
+            [0.0; BLOCK_SIZE],
+        ]
+    );
+}
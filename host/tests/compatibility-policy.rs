@@ -0,0 +1,373 @@
+//! Violations of the CLAP specification caught by the compatibility policies of both the host and
+//! the plugin, and reported through their diagnostics handlers.
+
+use clack_extensions::latency::{HostLatency, HostLatencyImpl};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clack_plugin::utils::compatibility::Diagnostic;
+use clap_sys::ext::latency::{clap_host_latency, CLAP_EXT_LATENCY};
+use clap_sys::host::clap_host;
+use clap_sys::process::{clap_process, CLAP_PROCESS_CONTINUE, CLAP_PROCESS_ERROR};
+use std::ffi::CStr;
+use std::ptr::{null, null_mut};
+use std::sync::Mutex;
+use std::thread;
+
+/// A diagnostic, as received by [`collect`].
+#[derive(Debug, PartialEq)]
+struct Collected {
+    check: CompatibilityCheck,
+    level: CheckLevel,
+    callback: &'static str,
+    message: String,
+}
+
+static DIAGNOSTICS: Mutex<Vec<Collected>> = Mutex::new(Vec::new());
+
+fn collect(diagnostic: &Diagnostic) {
+    DIAGNOSTICS.lock().unwrap().push(Collected {
+        check: diagnostic.check,
+        level: diagnostic.level,
+        callback: diagnostic.callback,
+        message: diagnostic.to_string(),
+    });
+}
+
+/// Takes all the diagnostics collected so far for the given check.
+///
+/// Tests run in parallel: each of them only looks at the checks it triggers.
+fn take_diagnostics(check: CompatibilityCheck) -> Vec<Collected> {
+    let mut diagnostics = DIAGNOSTICS.lock().unwrap();
+    let (taken, kept) = diagnostics.drain(..).partition(|d| d.check == check);
+    *diagnostics = kept;
+    taken
+}
+
+/// Notifies the host that the latency changed, regardless of the current thread.
+///
+/// # Safety
+///
+/// The given host must be valid, and support the latency extension.
+unsafe fn latency_changed(host: *const clap_host) {
+    let latency = (*host).get_extension.unwrap()(host, CLAP_EXT_LATENCY.as_ptr())
+        .cast::<clap_host_latency>()
+        .as_ref()
+        .unwrap();
+
+    latency.changed.unwrap()(host);
+}
+
+/// A plugin which notifies the host of latency changes both when activated and when processing.
+pub struct LatencyPlugin<const STRICT: bool>;
+
+pub struct LatencyPluginAudioProcessor<'a> {
+    host: HostAudioProcessorHandle<'a>,
+}
+
+impl<const STRICT: bool> Plugin for LatencyPlugin<STRICT> {
+    type AudioProcessor<'a> = LatencyPluginAudioProcessor<'a>;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    const COMPATIBILITY: CompatibilityPolicy = if STRICT {
+        CompatibilityPolicy::STRICT.with_diagnostics_handler(collect)
+    } else {
+        CompatibilityPolicy::COMPATIBLE.with_diagnostics_handler(collect)
+    };
+}
+
+impl<const STRICT: bool> DefaultPluginFactory for LatencyPlugin<STRICT> {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.latency", "Latency")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for LatencyPluginAudioProcessor<'a> {
+    fn activate(
+        host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        // SAFETY: this is called on the main thread, and the host supports the latency extension.
+        unsafe { latency_changed(host.as_raw_ptr()) };
+        Ok(Self { host })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        // SAFETY: the host supports the latency extension. This is a main-thread function,
+        // called from the audio thread on purpose.
+        unsafe { latency_changed(self.host.as_raw_ptr()) };
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+/// A [`LatencyPlugin`] which only warns about blocks with a number of frames out of range.
+pub struct LenientFramesPlugin;
+
+impl Plugin for LenientFramesPlugin {
+    type AudioProcessor<'a> = LatencyPluginAudioProcessor<'a>;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    const COMPATIBILITY: CompatibilityPolicy = CompatibilityPolicy::COMPATIBLE
+        .with(CompatibilityCheck::FramesOutOfRange, CheckLevel::Warn)
+        .with_diagnostics_handler(collect);
+}
+
+impl DefaultPluginFactory for LenientFramesPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        LatencyPlugin::<false>::get_descriptor()
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub static STRICT_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<LatencyPlugin<true>>);
+pub static COMPATIBLE_ENTRY: EntryDescriptor =
+    clack_entry!(SinglePluginEntry<LatencyPlugin<false>>);
+pub static LENIENT_FRAMES_ENTRY: EntryDescriptor =
+    clack_entry!(SinglePluginEntry<LenientFramesPlugin>);
+
+struct LatencyHost;
+
+#[derive(Default)]
+struct LatencyHostMainThread {
+    changes: u32,
+}
+
+impl MainThreadHandler<'_> for LatencyHostMainThread {}
+
+impl HostLatencyImpl for LatencyHostMainThread {
+    fn changed(&mut self) {
+        self.changes += 1;
+    }
+}
+
+impl HostHandlers for LatencyHost {
+    type Shared<'a> = ();
+    type MainThread<'a> = LatencyHostMainThread;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _: &Self::Shared<'_>) {
+        builder.register::<HostLatency>();
+    }
+}
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 4,
+};
+
+fn instantiate(
+    entry: &'static EntryDescriptor,
+    policy: CompatibilityPolicy,
+) -> PluginInstance<LatencyHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(entry, "/latency.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<LatencyHost>::new_with_policy(
+        |_| (),
+        |_| LatencyHostMainThread::default(),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.latency\0").unwrap(),
+        &host_info,
+        policy,
+    )
+    .unwrap()
+}
+
+/// Calls `process` on the given processor through a hand-built raw `clap_process` struct, which
+/// allows using any number of frames. No audio ports are provided.
+fn process_raw(processor: &mut StartedPluginAudioProcessor<LatencyHost>, frames_count: u32) -> i32 {
+    let input_events = InputEvents::empty();
+    let mut output_events = OutputEvents::void();
+
+    let process = clap_process {
+        steady_time: -1,
+        frames_count,
+        transport: null(),
+        audio_inputs: null(),
+        audio_outputs: null_mut(),
+        audio_inputs_count: 0,
+        audio_outputs_count: 0,
+        in_events: input_events.as_raw(),
+        out_events: output_events.as_raw_mut(),
+    };
+
+    let plugin = processor.plugin_handle().as_raw();
+    unsafe { (plugin.process.unwrap())(plugin, &process) }
+}
+
+/// Activates the plugin, then processes a block on another thread, during which the plugin calls
+/// a main-thread host function.
+///
+/// Returns how many times the host's main-thread handler was called.
+fn process_on_other_thread(policy: CompatibilityPolicy) -> u32 {
+    let mut instance = instantiate(&COMPATIBLE_ENTRY, policy);
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    processor = thread::spawn(move || {
+        assert_eq!(process_raw(&mut processor, 4), CLAP_PROCESS_CONTINUE);
+        processor
+    })
+    .join()
+    .unwrap();
+
+    instance.deactivate_unchecked(processor.stop_processing());
+    instance.access_handler_unchecked(|h| h.changes)
+}
+
+#[test]
+pub fn wrong_thread_host_calls_follow_the_host_policy() {
+    const CHECK: CompatibilityCheck = CompatibilityCheck::WrongThreadCallbacks;
+
+    // Only the call made on the main thread, while activating, goes through.
+    let strict = CompatibilityPolicy::STRICT.with_diagnostics_handler(collect);
+    assert_eq!(process_on_other_thread(strict), 1);
+    assert_eq!(
+        take_diagnostics(CHECK),
+        [Collected {
+            check: CHECK,
+            level: CheckLevel::Deny,
+            callback: "clap_host_latency.changed",
+            message: "Plugin called a main-thread host function from another thread \
+                (wrong-thread-callbacks, denied)"
+                .into(),
+        }]
+    );
+
+    let compatible = CompatibilityPolicy::COMPATIBLE.with_diagnostics_handler(collect);
+    assert_eq!(process_on_other_thread(compatible), 2);
+
+    let diagnostics = take_diagnostics(CHECK);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, CheckLevel::Warn);
+
+    let allowing = compatible.with(CHECK, CheckLevel::Allow);
+    assert_eq!(process_on_other_thread(allowing), 2);
+    assert!(take_diagnostics(CHECK).is_empty());
+}
+
+/// Processes blocks of the given frame counts, once per activation.
+///
+/// Returns the raw process statuses.
+fn process_frames(entry: &'static EntryDescriptor, activations: &[&[u32]]) -> Vec<i32> {
+    let mut instance = instantiate(entry, CompatibilityPolicy::default());
+    let mut statuses = Vec::new();
+
+    for blocks in activations {
+        let mut processor = instance
+            .activate_unchecked(|_, _| (), CONFIGURATION)
+            .unwrap()
+            .start_processing()
+            .unwrap();
+
+        for &frames_count in *blocks {
+            statuses.push(process_raw(&mut processor, frames_count));
+        }
+
+        instance.deactivate_unchecked(processor.stop_processing());
+    }
+
+    statuses
+}
+
+#[test]
+pub fn frames_out_of_range_follow_the_plugin_policy() {
+    const CHECK: CompatibilityCheck = CompatibilityCheck::FramesOutOfRange;
+
+    let statuses = process_frames(&STRICT_ENTRY, &[&[8, 0, 8, 4], &[16]]);
+    assert_eq!(
+        statuses,
+        [
+            CLAP_PROCESS_ERROR,
+            CLAP_PROCESS_CONTINUE,
+            CLAP_PROCESS_ERROR,
+            CLAP_PROCESS_CONTINUE,
+            CLAP_PROCESS_ERROR
+        ]
+    );
+
+    // Violations are reported once per activation.
+    let messages: Vec<_> = take_diagnostics(CHECK)
+        .into_iter()
+        .map(|d| (d.level, d.callback, d.message))
+        .collect();
+
+    assert_eq!(
+        messages,
+        [8, 16].map(|frames| (
+            CheckLevel::Deny,
+            "clap_plugin.process",
+            format!(
+                "Host processed {frames} frames, outside of the 1..=4 range given at activation \
+                (frames-out-of-range, denied)"
+            )
+        ))
+    );
+
+    // Blocks out of range are denied by the compatible preset as well.
+    let statuses = process_frames(&COMPATIBLE_ENTRY, &[&[8, 8]]);
+    assert_eq!(statuses, [CLAP_PROCESS_ERROR; 2]);
+
+    let diagnostics = take_diagnostics(CHECK);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, CheckLevel::Deny);
+
+    // If only warned about, blocks under the minimum are worked around, but blocks over the
+    // maximum still fail.
+    let configuration = PluginAudioConfiguration {
+        min_frames_count: 2,
+        ..CONFIGURATION
+    };
+
+    let mut instance = instantiate(&LENIENT_FRAMES_ENTRY, CompatibilityPolicy::default());
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let statuses = [1, 8].map(|frames_count| process_raw(&mut processor, frames_count));
+    assert_eq!(statuses, [CLAP_PROCESS_CONTINUE, CLAP_PROCESS_ERROR]);
+
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    let diagnostics = take_diagnostics(CHECK);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, CheckLevel::Warn);
+}
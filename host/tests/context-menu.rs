@@ -0,0 +1,277 @@
+//! A plugin asks the host to show a context menu, which the host fills with the plugin's items
+//! and shows later, sending the picked action back to the plugin.
+
+use clack_extensions::context_menu::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::{CStr, CString};
+use std::sync::Mutex;
+
+const GAIN: ClapId = ClapId::new(5);
+
+const RESET: ClapId = ClapId::new(1);
+const BYPASS: ClapId = ClapId::new(2);
+const LOCKED: ClapId = ClapId::new(3);
+const HOST_ACTION: ClapId = ClapId::new(100);
+
+fn cstr(bytes: &[u8]) -> &CStr {
+    CStr::from_bytes_with_nul(bytes).unwrap()
+}
+
+/// The actions the plugin performed.
+static PERFORMED: Mutex<Vec<(ContextMenuTarget, ClapId)>> = Mutex::new(Vec::new());
+
+/// The host items the plugin collected.
+static HOST_ITEMS: Mutex<Vec<MenuItem>> = Mutex::new(Vec::new());
+
+pub struct MenuPlugin;
+
+pub struct MenuPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+}
+
+impl<'a> PluginMainThread<'a, ()> for MenuPluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        let Some(context_menu) = self.host.get_extension::<HostContextMenu>() else {
+            return;
+        };
+
+        let mut host_menu = MenuModel::new();
+        if context_menu.populate(
+            &mut self.host,
+            ContextMenuTarget::Param(GAIN),
+            &mut host_menu,
+        ) {
+            *HOST_ITEMS.lock().unwrap() = host_menu.items().to_vec();
+        }
+
+        if context_menu.can_popup(&mut self.host) {
+            context_menu.popup(&mut self.host, ContextMenuTarget::Param(GAIN), 1, 10, 20);
+        }
+    }
+}
+
+impl PluginContextMenuImpl for MenuPluginMainThread<'_> {
+    fn populate(
+        &mut self,
+        target: ContextMenuTarget,
+        builder: &mut ContextMenuBuilder,
+    ) -> Result<(), PluginError> {
+        if target != ContextMenuTarget::Param(GAIN) {
+            return Ok(());
+        }
+
+        builder.add(ContextMenuItem::Title {
+            title: cstr(b"Gain\0"),
+            is_enabled: true,
+        });
+        builder.add(ContextMenuItem::Entry {
+            label: cstr(b"Reset\0"),
+            is_enabled: true,
+            action_id: RESET,
+        });
+        builder.add(ContextMenuItem::CheckEntry {
+            label: cstr(b"Bypass\0"),
+            is_enabled: true,
+            is_checked: true,
+            action_id: BYPASS,
+        });
+        builder.add(ContextMenuItem::Separator);
+        builder.add(ContextMenuItem::BeginSubmenu {
+            label: cstr(b"More\0"),
+            is_enabled: true,
+        });
+        builder.add(ContextMenuItem::Entry {
+            label: cstr(b"Locked\0"),
+            is_enabled: false,
+            action_id: LOCKED,
+        });
+        builder.add(ContextMenuItem::EndSubmenu);
+
+        Ok(())
+    }
+
+    fn perform(&mut self, target: ContextMenuTarget, action_id: ClapId) -> Result<(), PluginError> {
+        PERFORMED.lock().unwrap().push((target, action_id));
+        Ok(())
+    }
+}
+
+impl Plugin for MenuPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = MenuPluginMainThread<'a>;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginContextMenu>();
+    }
+}
+
+impl DefaultPluginFactory for MenuPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.context-menu", "Context Menu")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(MenuPluginMainThread { host })
+    }
+}
+
+pub static MENU_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<MenuPlugin>);
+
+struct MenuHost;
+
+struct MenuHostShared;
+
+impl SharedHandler<'_> for MenuHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct MenuHostMainThread {
+    popups: ContextMenuPopups,
+}
+
+impl MainThreadHandler<'_> for MenuHostMainThread {}
+
+impl HostContextMenuImpl for MenuHostMainThread {
+    fn populate(
+        &mut self,
+        _target: ContextMenuTarget,
+        builder: &mut ContextMenuBuilder,
+    ) -> Result<(), HostError> {
+        builder.add(ContextMenuItem::Entry {
+            label: cstr(b"Show automation\0"),
+            is_enabled: true,
+            action_id: HOST_ACTION,
+        });
+
+        Ok(())
+    }
+
+    fn perform(&mut self, _target: ContextMenuTarget, _action_id: ClapId) -> Result<(), HostError> {
+        Ok(())
+    }
+
+    fn can_popup(&mut self) -> bool {
+        self.popups.can_popup()
+    }
+
+    fn popup(
+        &mut self,
+        target: ContextMenuTarget,
+        screen_index: i32,
+        x: i32,
+        y: i32,
+    ) -> Result<(), HostError> {
+        self.popups.request(target, screen_index, x, y)
+    }
+}
+
+impl HostHandlers for MenuHost {
+    type Shared<'a> = MenuHostShared;
+    type MainThread<'a> = MenuHostMainThread;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostContextMenu>();
+    }
+}
+
+fn instantiate(can_popup: bool) -> PluginInstance<MenuHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&MENU_ENTRY, "/context-menu.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut popups = ContextMenuPopups::new();
+    popups.set_can_popup(can_popup);
+
+    PluginInstance::<MenuHost>::new(
+        |_| MenuHostShared,
+        move |_| MenuHostMainThread { popups },
+        &bundle,
+        cstr(b"org.rust-audio.clack.context-menu\0"),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn shows_plugin_items_and_performs_picked_action() {
+    let mut instance = instantiate(true);
+    instance.call_on_main_thread_callback_unchecked();
+
+    assert_eq!(
+        *HOST_ITEMS.lock().unwrap(),
+        [MenuItem::Entry {
+            label: CString::from(cstr(b"Show automation\0")),
+            is_enabled: true,
+            action_id: HOST_ACTION
+        }]
+    );
+
+    let request = instance
+        .access_handler_mut_unchecked(|h| h.popups.take_pending())
+        .unwrap();
+
+    assert_eq!(
+        request,
+        PopupRequest {
+            target: ContextMenuTarget::Param(GAIN),
+            screen_index: 1,
+            x: 10,
+            y: 20
+        }
+    );
+
+    let mut plugin = instance.plugin_handle_unchecked();
+
+    let mut shown = None;
+    assert!(request.show(&mut plugin, |popup| shown = Some(popup)));
+    let popup = shown.unwrap();
+
+    let items = popup.menu().items();
+    assert_eq!(items.len(), 5);
+    assert!(matches!(items[0], MenuItem::Title { .. }));
+    assert_eq!(
+        items[2],
+        MenuItem::CheckEntry {
+            label: CString::from(cstr(b"Bypass\0")),
+            is_enabled: true,
+            is_checked: true,
+            action_id: BYPASS
+        }
+    );
+    assert_eq!(items[3], MenuItem::Separator);
+    assert!(matches!(&items[4], MenuItem::Submenu { items, .. } if items.len() == 1));
+
+    assert!(popup.menu().action(LOCKED).is_some());
+    assert!(!popup.menu().is_action_enabled(LOCKED));
+    assert!(popup.menu().is_action_enabled(RESET));
+
+    assert!(popup.perform(&mut plugin, RESET));
+
+    assert_eq!(
+        *PERFORMED.lock().unwrap(),
+        [(ContextMenuTarget::Param(GAIN), RESET)]
+    );
+}
+
+#[test]
+pub fn refuses_popups_when_disabled() {
+    let mut instance = instantiate(false);
+    instance.call_on_main_thread_callback_unchecked();
+
+    assert!(instance
+        .access_handler_mut_unchecked(|h| h.popups.take_pending())
+        .is_none());
+}
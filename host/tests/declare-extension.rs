@@ -0,0 +1,263 @@
+//! Vendor extensions declared through `declare_extension!` cross the CLAP ABI in both directions,
+//! on every thread.
+
+use clack_extensions::declare_extension;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::host::clap_host;
+use clap_sys::plugin::clap_plugin;
+use std::ffi::CStr;
+
+const EXT_VENDOR_GAIN: &CStr = match CStr::from_bytes_with_nul(b"com.example.vendor-gain\0") {
+    Ok(id) => id,
+    Err(_) => panic!(),
+};
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[allow(non_camel_case_types)]
+pub struct clap_plugin_vendor_gain {
+    pub api_version: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> u32>,
+    pub get_gain: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> f64>,
+    pub set_gain: Option<unsafe extern "C" fn(plugin: *const clap_plugin, gain: f64)>,
+    pub is_clipping: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> bool>,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[allow(non_camel_case_types)]
+pub struct clap_host_vendor_gain {
+    pub gain_changed: Option<unsafe extern "C" fn(host: *const clap_host, gain: f64)>,
+}
+
+declare_extension! {
+    pub struct PluginVendorGain: plugin(clap_plugin_vendor_gain) = EXT_VENDOR_GAIN;
+
+    pub trait PluginVendorGainShared: any_thread {
+        fn api_version() -> u32 = 0;
+    }
+
+    pub trait PluginVendorGainImpl: main_thread {
+        fn get_gain() -> f64 = 1.0;
+        fn set_gain(gain: f64);
+    }
+
+    pub trait PluginVendorGainAudioProcessor: audio_thread {
+        fn is_clipping() -> bool = false;
+    }
+}
+
+declare_extension! {
+    pub struct HostVendorGain: host(clap_host_vendor_gain) = EXT_VENDOR_GAIN;
+
+    pub trait HostVendorGainImpl: main_thread {
+        fn gain_changed(gain: f64);
+    }
+}
+
+pub struct GainPlugin;
+
+pub struct GainPluginShared;
+
+impl PluginShared<'_> for GainPluginShared {}
+
+impl PluginVendorGainShared for GainPluginShared {
+    fn api_version(&self) -> u32 {
+        3
+    }
+}
+
+pub struct GainPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    gain: f64,
+}
+
+impl<'a> PluginMainThread<'a, GainPluginShared> for GainPluginMainThread<'a> {}
+
+impl PluginVendorGainImpl for GainPluginMainThread<'_> {
+    fn get_gain(&mut self) -> f64 {
+        assert!(self.gain <= 1.0, "Gain is out of range");
+        self.gain
+    }
+
+    fn set_gain(&mut self, gain: f64) {
+        assert!(gain.is_finite(), "Gain must be finite");
+        self.gain = gain;
+
+        if let Some(vendor_gain) = self.host.get_extension::<HostVendorGain>() {
+            vendor_gain.gain_changed(&mut self.host, gain);
+        }
+    }
+}
+
+pub struct GainPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, GainPluginShared, GainPluginMainThread<'a>>
+    for GainPluginAudioProcessor
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut GainPluginMainThread<'a>,
+        _shared: &'a GainPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginVendorGainAudioProcessor for GainPluginAudioProcessor {
+    fn is_clipping(&mut self) -> bool {
+        true
+    }
+}
+
+impl Plugin for GainPlugin {
+    type AudioProcessor<'a> = GainPluginAudioProcessor;
+    type Shared<'a> = GainPluginShared;
+    type MainThread<'a> = GainPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder.register::<PluginVendorGain>();
+    }
+}
+
+impl DefaultPluginFactory for GainPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.vendor-gain", "Vendor Gain")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(GainPluginShared)
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(GainPluginMainThread { host, gain: 0.5 })
+    }
+}
+
+pub static GAIN_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<GainPlugin>);
+
+struct GainHostShared;
+
+impl SharedHandler<'_> for GainHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+#[derive(Default)]
+struct GainHostMainThread {
+    changes: Vec<f64>,
+}
+
+impl MainThreadHandler<'_> for GainHostMainThread {}
+
+impl HostVendorGainImpl for GainHostMainThread {
+    fn gain_changed(&mut self, gain: f64) {
+        self.changes.push(gain);
+    }
+}
+
+struct GainHost;
+
+impl HostHandlers for GainHost {
+    type Shared<'a> = GainHostShared;
+    type MainThread<'a> = GainHostMainThread;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostVendorGain>();
+    }
+}
+
+fn instantiate() -> PluginInstance<GainHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&GAIN_ENTRY, "/vendor-gain.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<GainHost>::new(
+        |_| GainHostShared,
+        |_| GainHostMainThread::default(),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.vendor-gain\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn functions_are_called_on_every_thread() {
+    let mut instance = instantiate();
+    let vendor_gain: PluginVendorGain = instance.plugin_shared_handle().get_extension().unwrap();
+
+    assert_eq!(vendor_gain.api_version(&instance.plugin_shared_handle()), 3);
+
+    assert_eq!(
+        vendor_gain.get_gain(&mut instance.plugin_handle_unchecked()),
+        0.5
+    );
+    vendor_gain.set_gain(&mut instance.plugin_handle_unchecked(), 0.25);
+    assert_eq!(
+        vendor_gain.get_gain(&mut instance.plugin_handle_unchecked()),
+        0.25
+    );
+
+    // The plugin notified the host through the host side of the extension.
+    assert_eq!(
+        instance.access_handler_unchecked(|h| h.changes.clone()),
+        [0.25]
+    );
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    assert!(vendor_gain.is_clipping(&mut processor.plugin_handle()));
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
+
+#[test]
+pub fn failed_calls_return_the_declared_default() {
+    let mut instance = instantiate();
+    let vendor_gain: PluginVendorGain = instance.plugin_shared_handle().get_extension().unwrap();
+
+    // The plugin panics on non-finite gains: the panic is caught and logged by the wrapper.
+    vendor_gain.set_gain(&mut instance.plugin_handle_unchecked(), f64::NAN);
+    assert!(instance.access_handler_unchecked(|h| h.changes.is_empty()));
+
+    // The plugin panics when asked for an out-of-range gain, so the default value is returned.
+    vendor_gain.set_gain(&mut instance.plugin_handle_unchecked(), 2.0);
+    assert_eq!(
+        instance.access_handler_unchecked(|h| h.changes.clone()),
+        [2.0]
+    );
+    assert_eq!(
+        vendor_gain.get_gain(&mut instance.plugin_handle_unchecked()),
+        1.0
+    );
+}
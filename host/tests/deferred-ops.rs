@@ -0,0 +1,155 @@
+use clack_host::plugin::deferred::{DeferredMainThreadOps, DeferredOpState};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+pub struct NoopPlugin;
+
+impl Plugin for NoopPlugin {
+    type AudioProcessor<'a> = NoopPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for NoopPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.noop", "Noop")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub struct NoopPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for NoopPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static NOOP_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<NoopPlugin>);
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 256,
+};
+
+fn instantiate() -> PluginInstance<()> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.noop\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn ops_run_immediately_when_deactivated() {
+    let mut instance = instantiate();
+    let mut ops = DeferredMainThreadOps::new();
+
+    let handle = ops.enqueue(&mut instance, |instance| instance.is_active());
+
+    assert!(!ops.has_pending());
+    assert_eq!(handle.poll(), DeferredOpState::Completed(false));
+    // The result is only returned once.
+    assert_eq!(handle.poll(), DeferredOpState::Cancelled);
+}
+
+#[test]
+pub fn ops_are_deferred_until_deactivation() {
+    let mut instance = instantiate();
+    let mut ops = DeferredMainThreadOps::new();
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+
+    let first = ops.enqueue(&mut instance, |instance| instance.is_active());
+    let second = ops.enqueue(&mut instance, |_| 42);
+
+    assert_eq!(ops.pending_count(), 2);
+    assert_eq!(first.poll(), DeferredOpState::Pending);
+
+    // Nothing can run while the instance is still active.
+    assert_eq!(ops.run_pending(&mut instance), 0);
+    assert_eq!(second.poll(), DeferredOpState::Pending);
+
+    instance.deactivate_unchecked(processor);
+    assert_eq!(ops.run_pending(&mut instance), 2);
+
+    assert!(!ops.has_pending());
+    assert_eq!(first.poll(), DeferredOpState::Completed(false));
+    assert_eq!(second.poll(), DeferredOpState::Completed(42));
+}
+
+#[test]
+pub fn restart_flushes_pending_ops() {
+    let mut instance = instantiate();
+    let mut ops = DeferredMainThreadOps::new();
+    // SAFETY: the instance was created on this thread.
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+
+    let processor = instance
+        .activate_checked(&main_thread, |_, _| (), CONFIGURATION)
+        .unwrap();
+
+    let handle = ops.enqueue(&mut instance, |instance| instance.activation_config());
+    let waiter = std::thread::spawn(move || handle.wait());
+
+    let processor = ops
+        .restart(&main_thread, &mut instance, processor, |_, _| ())
+        .unwrap();
+
+    assert_eq!(waiter.join().unwrap(), Some(None));
+    assert_eq!(instance.activation_config(), Some(CONFIGURATION));
+
+    instance.deactivate_checked(&main_thread, processor);
+}
+
+#[test]
+pub fn dropped_queue_cancels_pending_ops() {
+    let mut instance = instantiate();
+    let mut ops = DeferredMainThreadOps::new();
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+
+    let handle = ops.enqueue(&mut instance, |_| ());
+    drop(ops);
+
+    assert_eq!(handle.poll(), DeferredOpState::Cancelled);
+    instance.deactivate_unchecked(processor);
+}
@@ -0,0 +1,233 @@
+//! Drives a plugin through process calls that the safe host API cannot produce, such as missing
+//! buffers or empty blocks, using hand-built raw `clap_process` structs.
+
+use clack_host::events::event_types::MidiEvent;
+use clack_host::prelude::*;
+use clack_host::process::StartedPluginAudioProcessor;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::audio_buffer::clap_audio_buffer;
+use clap_sys::process::{clap_process, CLAP_PROCESS_CONTINUE, CLAP_PROCESS_ERROR};
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ptr::{null, null_mut};
+
+/// What the plugin observed of a single input port.
+#[derive(Debug, PartialEq)]
+struct SeenPort {
+    constant_mask: u64,
+    channels: Vec<Vec<f32>>,
+}
+
+thread_local! {
+    /// The input ports seen by the plugin in each process call, on this thread.
+    static SEEN: RefCell<Vec<Vec<SeenPort>>> = const { RefCell::new(Vec::new()) };
+}
+
+pub struct RecordingPlugin;
+
+pub struct RecordingPluginAudioProcessor;
+
+impl Plugin for RecordingPlugin {
+    type AudioProcessor<'a> = RecordingPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for RecordingPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.recording", "Recording")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for RecordingPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let mut ports = Vec::new();
+
+        for port in audio.input_ports() {
+            let channels = port.channels()?.into_f32().unwrap();
+
+            ports.push(SeenPort {
+                constant_mask: port.constant_mask().to_bits(),
+                channels: channels.iter().map(|c| c.to_vec()).collect(),
+            });
+        }
+
+        SEEN.with(|seen| seen.borrow_mut().push(ports));
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static RECORDING_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<RecordingPlugin>);
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 4,
+};
+
+fn input_buffer(data32: *const *const f32, channel_count: u32) -> clap_audio_buffer {
+    clap_audio_buffer {
+        data32,
+        data64: null(),
+        channel_count,
+        latency: 0,
+        constant_mask: 0,
+    }
+}
+
+/// Activates the plugin, calls `process` on it with the given raw inputs and frame count, and
+/// returns the raw process status along with the ports the plugin saw, if it was called.
+fn process_raw(
+    inputs: &[clap_audio_buffer],
+    frames_count: u32,
+    input_events: &InputEvents,
+) -> (i32, Option<Vec<SeenPort>>) {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&RECORDING_ENTRY, "/recording.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.recording\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let mut processor: StartedPluginAudioProcessor<()> = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut output_events = OutputEvents::void();
+
+    let process = clap_process {
+        steady_time: -1,
+        frames_count,
+        transport: null(),
+        audio_inputs: if inputs.is_empty() {
+            null()
+        } else {
+            inputs.as_ptr()
+        },
+        audio_outputs: null_mut(),
+        audio_inputs_count: inputs.len() as u32,
+        audio_outputs_count: 0,
+        in_events: input_events.as_raw(),
+        out_events: output_events.as_raw_mut(),
+    };
+
+    SEEN.with(|seen| seen.borrow_mut().clear());
+
+    let plugin = processor.plugin_handle().as_raw();
+    let status = unsafe { (plugin.process.unwrap())(plugin, &process) };
+
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    let seen = SEEN.with(|seen| seen.borrow_mut().pop());
+    (status, seen)
+}
+
+#[test]
+pub fn zero_ports_yield_empty_iterators() {
+    let (status, seen) = process_raw(&[], 4, &InputEvents::empty());
+
+    assert_eq!(status, CLAP_PROCESS_CONTINUE);
+    assert_eq!(seen, Some(vec![]));
+}
+
+#[test]
+pub fn null_channel_pointers_are_synthesized_as_constant_silence() {
+    let data = [1.0f32; 4];
+    let channels = [data.as_ptr(), null()];
+
+    let mut buffer = input_buffer(channels.as_ptr(), 2);
+    buffer.constant_mask = 0b10;
+
+    let (status, seen) = process_raw(&[buffer], 4, &InputEvents::empty());
+
+    assert_eq!(status, CLAP_PROCESS_CONTINUE);
+    assert_eq!(
+        seen,
+        Some(vec![SeenPort {
+            constant_mask: 0b10,
+            channels: vec![vec![1.0; 4], vec![0.0; 4]],
+        }])
+    );
+}
+
+#[test]
+pub fn null_data_pointers_are_synthesized_as_constant_silence() {
+    let mut buffer = input_buffer(null(), 2);
+    buffer.constant_mask = 0b11;
+
+    let (status, seen) = process_raw(&[buffer], 4, &InputEvents::empty());
+
+    assert_eq!(status, CLAP_PROCESS_CONTINUE);
+    assert_eq!(
+        seen,
+        Some(vec![SeenPort {
+            constant_mask: u64::MAX,
+            channels: vec![vec![0.0; 4], vec![0.0; 4]],
+        }])
+    );
+}
+
+#[test]
+pub fn oversized_blocks_are_not_synthesized() {
+    // Hosts must not exceed the maximum frame count: missing buffers can't be synthesized then.
+    let buffer = input_buffer(null(), 2);
+    let (status, seen) = process_raw(&[buffer], 8, &InputEvents::empty());
+
+    assert_eq!(status, CLAP_PROCESS_ERROR);
+    assert_eq!(seen, None);
+}
+
+#[test]
+pub fn empty_blocks_are_skipped() {
+    let buffer = input_buffer(null(), 2);
+    let (status, seen) = process_raw(&[buffer], 0, &InputEvents::empty());
+
+    assert_eq!(status, CLAP_PROCESS_CONTINUE);
+    assert_eq!(seen, None);
+}
+
+#[test]
+pub fn empty_blocks_with_events_are_processed() {
+    let mut events = EventBuffer::new();
+    events.push(&MidiEvent::new(0, 0, [0x90, 60, 100]));
+
+    let (status, seen) = process_raw(&[], 0, &events.as_input());
+
+    assert_eq!(status, CLAP_PROCESS_CONTINUE);
+    assert_eq!(seen, Some(vec![]));
+}
@@ -0,0 +1,232 @@
+use clack_host::prelude::*;
+use clack_host::process::delay::{DelayCompensator, DelayCompensatorError};
+use clack_host::process::StartedPluginAudioProcessor;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const BLOCK_SIZE: usize = 16;
+
+/// A plugin that sums the first channel of all of its input ports into its single output channel.
+pub struct SumPlugin;
+
+pub struct SumPluginAudioProcessor;
+
+impl Plugin for SumPlugin {
+    type AudioProcessor<'a> = SumPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for SumPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.sum", "Sum")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for SumPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let mut sum = [0.0f32; BLOCK_SIZE];
+
+        for port in audio.input_ports() {
+            let channels = port
+                .channels()?
+                .into_f32()
+                .ok_or(PluginError::Message("Expected f32 input"))?;
+
+            if let Some(channel) = channels.channel(0) {
+                for (sum, sample) in sum.iter_mut().zip(channel) {
+                    *sum += *sample;
+                }
+            }
+        }
+
+        let mut output = audio
+            .output_port(0)
+            .ok_or(PluginError::Message("No output port"))?;
+        let mut channels = output
+            .channels()?
+            .into_f32()
+            .ok_or(PluginError::Message("Expected f32 output"))?;
+
+        if let Some(channel) = channels.channel_mut(0) {
+            let len = channel.len();
+            channel.copy_from_slice(&sum[..len]);
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static SUM_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<SumPlugin>);
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: BLOCK_SIZE as u32,
+};
+
+fn instantiate() -> PluginInstance<()> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&SUM_ENTRY, "/sum.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.sum\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+/// Sends an impulse at the given positions through each input port, and returns the positions of
+/// all the non-zero output samples, along with their values.
+fn process_impulses(
+    compensator: &mut DelayCompensator,
+    processor: &mut StartedPluginAudioProcessor<()>,
+    impulses: &[usize],
+    block_count: usize,
+) -> Vec<(usize, f32)> {
+    let mut result = Vec::new();
+
+    for block in 0..block_count {
+        let block_start = block * BLOCK_SIZE;
+
+        let mut inputs: Vec<Vec<Vec<f32>>> = impulses
+            .iter()
+            .map(|position| {
+                let mut channel = vec![0.0f32; BLOCK_SIZE];
+                if (block_start..block_start + BLOCK_SIZE).contains(position) {
+                    channel[position - block_start] = 1.0;
+                }
+                vec![channel]
+            })
+            .collect();
+
+        let mut output = [vec![0.0f32; BLOCK_SIZE]];
+
+        compensator
+            .process_aligned(
+                processor,
+                &mut inputs,
+                &mut output,
+                &InputEvents::empty(),
+                &mut OutputEvents::void(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        result.extend(
+            output[0]
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| **s != 0.0)
+                .map(|(i, s)| (block_start + i, *s)),
+        );
+    }
+
+    result
+}
+
+#[test]
+pub fn sidechain_is_aligned_with_main_input() {
+    let mut instance = instantiate();
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    // The main input went through 21 samples of latency upstream, the sidechain 0.
+    let mut compensator = DelayCompensator::new(CONFIGURATION, &[1, 1], 1);
+    compensator.set_port_latencies(&[21, 0]);
+
+    // Both impulses were emitted at the same time, so the main one arrives 21 samples late.
+    let output = process_impulses(&mut compensator, &mut processor, &[21, 0], 3);
+    assert_eq!(output, [(21, 2.0)]);
+
+    // Without compensation, both impulses are misaligned.
+    compensator.set_delays(&[0, 0]);
+    let output = process_impulses(&mut compensator, &mut processor, &[21, 0], 3);
+    assert_eq!(output, [(0, 1.0), (21, 1.0)]);
+
+    assert_eq!(
+        compensator.process_aligned(
+            &mut processor,
+            &mut [[vec![0.0f32; BLOCK_SIZE]]],
+            &mut [vec![0.0f32; BLOCK_SIZE]],
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        ),
+        Err(DelayCompensatorError::PortCountMismatch)
+    );
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
+
+#[test]
+pub fn latency_change_is_applied_on_restart() {
+    let mut instance = instantiate();
+    let mut compensator = DelayCompensator::new(CONFIGURATION, &[1, 1], 1);
+    compensator.set_port_latencies(&[5, 0]);
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let output = process_impulses(&mut compensator, &mut processor, &[5, 0], 2);
+    assert_eq!(output, [(5, 2.0)]);
+
+    // The upstream latency changed: restart the plugin with new delays.
+    instance.deactivate_unchecked(processor.stop_processing());
+    compensator.set_port_latencies(&[0, 12]);
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        ..CONFIGURATION
+    };
+    compensator.set_configuration(configuration);
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let output = process_impulses(&mut compensator, &mut processor, &[3, 15], 3);
+    assert_eq!(output, [(15, 2.0)]);
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
@@ -152,8 +152,8 @@ pub fn handles_normal_deactivate() {
         max_frames_count: 5,
     };
 
-    let processor = instance.activate(|_, _| (), config).unwrap();
-    instance.deactivate(processor);
+    let processor = instance.activate_unchecked(|_, _| (), config).unwrap();
+    instance.deactivate_unchecked(processor);
 }
 
 #[test]
@@ -165,12 +165,12 @@ pub fn handles_try_deactivate() {
         max_frames_count: 5,
     };
 
-    let processor = instance.activate(|_, _| (), config).unwrap();
+    let processor = instance.activate_unchecked(|_, _| (), config).unwrap();
 
-    assert!(instance.try_deactivate().is_err());
+    assert!(instance.try_deactivate_unchecked().is_err());
     drop(processor);
 
-    instance.try_deactivate().unwrap();
+    instance.try_deactivate_unchecked().unwrap();
 }
 
 #[test]
@@ -182,7 +182,7 @@ pub fn stops_when_dropping() {
         max_frames_count: 5,
     };
 
-    let processor = instance.activate(|_, _| (), config).unwrap();
+    let processor = instance.activate_unchecked(|_, _| (), config).unwrap();
     let processor = processor.start_processing().unwrap();
 
     drop(processor);
@@ -198,7 +198,7 @@ pub fn works_with_reverse_drop() {
         max_frames_count: 5,
     };
 
-    let processor = instance.activate(|_, _| (), config).unwrap();
+    let processor = instance.activate_unchecked(|_, _| (), config).unwrap();
     let processor = processor.start_processing().unwrap();
 
     drop(instance);
@@ -214,7 +214,7 @@ pub fn works_with_forgotten_audio_processor() {
         max_frames_count: 5,
     };
 
-    let processor = instance.activate(|_, _| (), config).unwrap();
+    let processor = instance.activate_unchecked(|_, _| (), config).unwrap();
 
     let t = thread::spawn(move || {
         let processor = processor.start_processing().unwrap();
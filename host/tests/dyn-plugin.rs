@@ -0,0 +1,298 @@
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::entry::{Entry, EntryFactories, EntryLoadError};
+use clack_plugin::factory::plugin::{PluginFactory, PluginFactoryWrapper};
+use clack_plugin::plugin::dynamic::*;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const GAIN: ClapId = ClapId::new(0);
+
+/// A "script" only known at runtime, which decides which extensions and processor the plugin uses.
+#[derive(Copy, Clone)]
+struct Script {
+    gain: Option<f32>,
+}
+
+struct ScriptShared {
+    script: Script,
+}
+
+impl<'a> PluginSharedDyn<'a> for ScriptShared {
+    fn declare_extensions(&self, builder: &mut PluginExtensions<DynPlugin>) {
+        if self.script.gain.is_some() {
+            builder.register::<PluginParams>();
+        }
+    }
+}
+
+struct ScriptMainThread {
+    script: Script,
+}
+
+impl<'a> PluginMainThreadDyn<'a> for ScriptMainThread {
+    fn activate(
+        &mut self,
+        _host: HostAudioProcessorHandle<'a>,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Box<dyn PluginAudioProcessorDyn<'a>>, PluginError> {
+        match self.script.gain {
+            Some(gain) => Ok(Box::new(GainProcessor { gain })),
+            None => Ok(Box::new(PassthroughProcessor)),
+        }
+    }
+
+    fn provide<'r>(&'r mut self, request: &mut DynInterfaceRequest<'r, 'a>) {
+        if self.script.gain.is_some() {
+            request.provide::<dyn PluginMainThreadParams>(self);
+        }
+    }
+}
+
+impl PluginMainThreadParams for ScriptMainThread {
+    fn count(&mut self) -> u32 {
+        1
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        if param_index == 0 {
+            info.set(&ParamInfo {
+                id: GAIN,
+                flags: ParamInfoFlags::IS_AUTOMATABLE,
+                cookie: Default::default(),
+                name: b"Gain",
+                module: b"",
+                min_value: 0.0,
+                max_value: 1.0,
+                default_value: 0.5,
+            })
+        }
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        match param_id {
+            GAIN => self.script.gain.map(|gain| gain as f64),
+            _ => None,
+        }
+    }
+
+    fn value_to_text(
+        &mut self,
+        _param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        use std::fmt::Write;
+        write!(writer, "{value:.2}")
+    }
+
+    fn text_to_value(&mut self, _param_id: ClapId, _text: &CStr) -> Option<f64> {
+        None
+    }
+
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+fn process_channels(
+    audio: &mut Audio,
+    mut f: impl FnMut(f32) -> f32,
+) -> Result<ProcessStatus, PluginError> {
+    let mut port_pair = audio.port_pair(0).ok_or(PluginError::Message("No ports"))?;
+    let mut channels = port_pair
+        .channels()?
+        .into_f32()
+        .ok_or(PluginError::Message("Expected f32 buffers"))?;
+
+    for pair in channels.iter_mut() {
+        let ChannelPair::InputOutput(input, output) = pair else {
+            return Err(PluginError::Message("Expected separate buffers"));
+        };
+
+        for (input, output) in input.iter().zip(output.iter_mut()) {
+            *output = f(*input);
+        }
+    }
+
+    Ok(ProcessStatus::Continue)
+}
+
+struct GainProcessor {
+    gain: f32,
+}
+
+impl<'a> PluginAudioProcessorDyn<'a> for GainProcessor {
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        process_channels(&mut audio, |sample| sample * self.gain)
+    }
+
+    fn provide<'r>(&'r mut self, request: &mut DynInterfaceRequest<'r, 'a>) {
+        request.provide::<dyn PluginAudioProcessorParams>(self);
+    }
+}
+
+impl PluginAudioProcessorParams for GainProcessor {
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+struct PassthroughProcessor;
+
+impl PluginAudioProcessorDyn<'_> for PassthroughProcessor {
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        process_channels(&mut audio, |sample| sample)
+    }
+}
+
+pub struct ScriptEntry {
+    plugin_factory: PluginFactoryWrapper<ScriptFactory>,
+}
+
+impl Entry for ScriptEntry {
+    fn new(_plugin_path: &CStr) -> Result<Self, EntryLoadError> {
+        Ok(Self {
+            plugin_factory: PluginFactoryWrapper::new(ScriptFactory {
+                descriptors: [
+                    PluginDescriptor::new("org.rust-audio.clack.dyn-gain", "Dyn Gain"),
+                    PluginDescriptor::new("org.rust-audio.clack.dyn-thru", "Dyn Passthrough"),
+                ],
+                scripts: [Script { gain: Some(0.5) }, Script { gain: None }],
+            }),
+        })
+    }
+
+    fn declare_factories<'a>(&'a self, builder: &mut EntryFactories<'a>) {
+        builder.register_factory(&self.plugin_factory);
+    }
+}
+
+struct ScriptFactory {
+    descriptors: [PluginDescriptor; 2],
+    scripts: [Script; 2],
+}
+
+impl PluginFactory for ScriptFactory {
+    fn plugin_count(&self) -> u32 {
+        self.descriptors.len() as u32
+    }
+
+    fn plugin_descriptor(&self, index: u32) -> Option<&PluginDescriptor> {
+        self.descriptors.get(index as usize)
+    }
+
+    fn create_plugin<'a>(
+        &'a self,
+        host_info: clack_plugin::host::HostInfo<'a>,
+        plugin_id: &CStr,
+    ) -> Option<clack_plugin::plugin::PluginInstance<'a>> {
+        let index = self.descriptors.iter().position(|d| d.id() == plugin_id)?;
+        let script = self.scripts[index];
+
+        Some(clack_plugin::plugin::PluginInstance::new::<DynPlugin>(
+            host_info,
+            &self.descriptors[index],
+            move |_| Ok(DynPluginShared::new(Box::new(ScriptShared { script }))),
+            move |_, _| {
+                Ok(DynPluginMainThread::new(Box::new(ScriptMainThread {
+                    script,
+                })))
+            },
+        ))
+    }
+}
+
+pub static SCRIPT_ENTRY: EntryDescriptor = clack_entry!(ScriptEntry);
+
+fn instantiate(id: &[u8]) -> PluginInstance<()> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&SCRIPT_ENTRY, "/script.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(id).unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+fn process_block(instance: &mut PluginInstance<()>) -> Vec<f32> {
+    let mut input = vec![1.0f32, 0.5, -0.5, -1.0];
+    let mut output = vec![0.0f32; input.len()];
+    let mut input_ports = AudioPorts::with_capacity(1, 1);
+    let mut output_ports = AudioPorts::with_capacity(1, 1);
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 1,
+        max_frames_count: 4,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    processor
+        .process(
+            &input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only([InputChannel::variable(&mut input)]),
+                latency: 0,
+            }]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([&mut output[..]]),
+                latency: 0,
+            }]),
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    instance.deactivate_unchecked(processor.stop_processing());
+    output
+}
+
+#[test]
+pub fn dyn_plugin_declares_runtime_extensions() {
+    let mut instance = instantiate(b"org.rust-audio.clack.dyn-gain\0");
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    assert_eq!(params.count(&mut handle), 1);
+    assert_eq!(params.get_value(&mut handle, GAIN), Some(0.5));
+
+    let mut buffer = ParamInfoBuffer::new();
+    let info = params.get_info(&mut handle, 0, &mut buffer).unwrap();
+    assert_eq!(info.id, GAIN);
+}
+
+#[test]
+pub fn dyn_plugin_omits_undeclared_extensions() {
+    let mut instance = instantiate(b"org.rust-audio.clack.dyn-thru\0");
+    let handle = instance.plugin_handle_unchecked();
+
+    assert!(handle.get_extension::<PluginParams>().is_none());
+}
+
+#[test]
+pub fn dyn_plugin_picks_processor_at_activation() {
+    let mut gain = instantiate(b"org.rust-audio.clack.dyn-gain\0");
+    assert_eq!(process_block(&mut gain), [0.5, 0.25, -0.25, -0.5]);
+
+    let mut passthrough = instantiate(b"org.rust-audio.clack.dyn-thru\0");
+    assert_eq!(process_block(&mut passthrough), [1.0, 0.5, -0.5, -1.0]);
+}
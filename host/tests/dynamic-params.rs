@@ -0,0 +1,403 @@
+//! Plugins may register the params extension without any parameter, and add or remove them at
+//! runtime (e.g. samplers loading a file). Hosts must follow them through full rescans.
+
+use clack_extensions::params::generic_ui::GenericUiModel;
+use clack_extensions::params::info_snapshot::ParamInfoSnapshot;
+use clack_extensions::params::set::{ParamDef, ParamSet};
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+
+const VOLUME: ClapId = ClapId::new(0);
+const PITCH: ClapId = ClapId::new(1);
+const START: ClapId = ClapId::new(2);
+const LOOP: ClapId = ClapId::new(3);
+
+/// What the plugin does on its next main thread callback.
+enum Command {
+    Load(Vec<(ClapId, ParamDef)>),
+    Add(ClapId, ParamDef),
+    Remove(ClapId),
+}
+
+thread_local! {
+    static COMMAND: RefCell<Option<Command>> = const { RefCell::new(None) };
+    /// Incremented every time the plugin's parameter list changes, and used as the cookie of all
+    /// of its parameters.
+    static GENERATION: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A sampler-like plugin, which has no parameters until a sample is loaded.
+pub struct SamplerPlugin;
+
+pub struct SamplerPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for SamplerPluginShared {}
+
+pub struct SamplerPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    shared: &'a SamplerPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, SamplerPluginShared> for SamplerPluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        let params = &self.shared.params;
+        let host = &mut self.host;
+
+        let changed = match COMMAND.with(|c| c.borrow_mut().take()) {
+            Some(Command::Load(defs)) => params.replace_params(host, defs).is_ok(),
+            Some(Command::Add(id, def)) => params.add_param(host, id, def).is_ok(),
+            Some(Command::Remove(id)) => params.remove_param(host, id).is_some(),
+            None => false,
+        };
+
+        if changed {
+            GENERATION.with(|g| g.set(g.get() + 1));
+        }
+    }
+}
+
+impl PluginMainThreadParams for SamplerPluginMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        let Some(&id) = self.shared.params.ids().get(param_index as usize) else {
+            return;
+        };
+        let def = self.shared.params.def(id).unwrap();
+
+        info.set(&ParamInfo {
+            id,
+            flags: def.flags(),
+            cookie: Cookie::from_raw(GENERATION.with(Cell::get) as *mut _),
+            name: def.name().as_bytes(),
+            module: b"",
+            min_value: def.min_value(),
+            max_value: def.max_value(),
+            default_value: def.default_value(),
+        })
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input)
+    }
+}
+
+pub struct SamplerPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, SamplerPluginShared, SamplerPluginMainThread<'a>>
+    for SamplerPluginAudioProcessor
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut SamplerPluginMainThread<'a>,
+        _shared: &'a SamplerPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for SamplerPluginAudioProcessor {
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+impl Plugin for SamplerPlugin {
+    type AudioProcessor<'a> = SamplerPluginAudioProcessor;
+    type Shared<'a> = SamplerPluginShared;
+    type MainThread<'a> = SamplerPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&SamplerPluginShared>,
+    ) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for SamplerPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.sampler", "Sampler")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(SamplerPluginShared {
+            params: ParamSet::new(),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(SamplerPluginMainThread { host, shared })
+    }
+}
+
+pub static SAMPLER_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<SamplerPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = MyHostMainThread;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostParams>();
+    }
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostParamsImplShared for MyHostShared {
+    fn request_flush(&self) {}
+}
+
+#[derive(Default)]
+struct MyHostMainThread {
+    rescans: Vec<ParamRescanFlags>,
+}
+
+impl MainThreadHandler<'_> for MyHostMainThread {}
+
+impl HostParamsImplMainThread for MyHostMainThread {
+    fn rescan(&mut self, flags: ParamRescanFlags) {
+        self.rescans.push(flags);
+    }
+
+    fn clear(&mut self, _param_id: ClapId, _flags: ParamClearFlags) {}
+}
+
+/// A host following a plugin's parameters.
+struct Session {
+    instance: PluginInstance<MyHost>,
+    cache: ParamValueCache,
+    snapshot: ParamInfoSnapshot,
+    model: GenericUiModel,
+}
+
+impl Session {
+    fn new() -> Self {
+        let bundle =
+            unsafe { PluginBundle::load_from_raw(&SAMPLER_ENTRY, "/sampler.clap").unwrap() };
+        let host_info =
+            HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+        let instance = PluginInstance::<MyHost>::new(
+            |_| MyHostShared,
+            |_| MyHostMainThread::default(),
+            &bundle,
+            CStr::from_bytes_with_nul(b"org.rust-audio.clack.sampler\0").unwrap(),
+            &host_info,
+        )
+        .unwrap();
+
+        let mut session = Self {
+            instance,
+            cache: ParamValueCache::new(),
+            snapshot: ParamInfoSnapshot::from_entries(Vec::new()),
+            model: GenericUiModel::new(),
+        };
+
+        session.rescan(ParamRescanFlags::ALL);
+        session
+    }
+
+    /// Has the plugin run the given command, and returns the IDs the generic UI model reports as
+    /// changed following the rescan it requested, if any.
+    fn run(&mut self, command: Command) -> Option<Vec<ClapId>> {
+        COMMAND.with(|c| *c.borrow_mut() = Some(command));
+        self.instance.call_on_main_thread_callback_unchecked();
+
+        let rescans = self
+            .instance
+            .access_handler_mut_unchecked(|h| std::mem::take(&mut h.rescans));
+
+        match rescans[..] {
+            [] => None,
+            [flags] => {
+                assert_eq!(flags, ParamRescanFlags::ALL);
+                Some(self.rescan(flags))
+            }
+            _ => panic!("Expected a single rescan request, got {rescans:?}"),
+        }
+    }
+
+    fn rescan(&mut self, flags: ParamRescanFlags) -> Vec<ClapId> {
+        let mut handle = self.instance.plugin_handle_unchecked();
+        let params = handle.get_extension::<PluginParams>().unwrap();
+
+        self.cache.rescan(&params, &mut handle);
+        self.snapshot = ParamInfoSnapshot::capture(&params, &mut handle);
+        self.model.on_rescan(flags, &self.cache)
+    }
+
+    fn ids(&self) -> Vec<ClapId> {
+        self.cache.params().iter().map(|p| p.id).collect()
+    }
+
+    fn set_value(&mut self, param_id: ClapId, value: f64) {
+        let mut events = EventIo::new();
+        events.push_input(&ParamValueEvent::new(
+            0,
+            param_id,
+            Pckn::match_all(),
+            value,
+            self.cache.get(param_id).unwrap().cookie,
+        ));
+
+        let mut handle = self.instance.plugin_handle_unchecked();
+        let params = handle.get_extension::<PluginParams>().unwrap();
+        let (input, mut output) = events.split();
+        params.flush(&mut handle, &input, &mut output);
+
+        self.cache.update_from_events(&input);
+    }
+}
+
+fn sample_params() -> Vec<(ClapId, ParamDef)> {
+    vec![
+        (VOLUME, ParamDef::new("Volume", 0.0, 1.0, 0.8)),
+        (PITCH, ParamDef::new("Pitch", -24.0, 24.0, 0.0)),
+    ]
+}
+
+fn other_sample_params() -> Vec<(ClapId, ParamDef)> {
+    vec![
+        (START, ParamDef::new("Start", 0.0, 1.0, 0.0)),
+        (PITCH, ParamDef::new("Pitch", -12.0, 12.0, 0.0)),
+        (
+            LOOP,
+            ParamDef::enumeration("Loop", &["Off", "Forward", "Ping-Pong"]),
+        ),
+    ]
+}
+
+#[test]
+pub fn plugins_without_parameters_are_valid() {
+    let session = Session::new();
+
+    assert!(session.cache.params().is_empty());
+    assert!(session.snapshot.params().is_empty());
+    assert!(session.model.controls().is_empty());
+    assert_eq!(session.cache.value(VOLUME), None);
+}
+
+#[test]
+pub fn parameters_are_followed_from_zero_to_many_and_back() {
+    let mut session = Session::new();
+
+    // 0 -> N
+    let changed = session.run(Command::Load(sample_params())).unwrap();
+    assert_eq!(changed, [VOLUME, PITCH]);
+    assert_eq!(session.ids(), [VOLUME, PITCH]);
+    assert_eq!(session.snapshot.params().len(), 2);
+    assert_eq!(session.cache.value(VOLUME), Some(0.8));
+    assert_eq!(session.model.control(PITCH).unwrap().value(), Some(0.0));
+
+    session.set_value(PITCH, 18.0);
+    assert_eq!(session.cache.value(PITCH), Some(18.0));
+
+    // N -> M: the parameter that is still there keeps its ID and value, clamped to its new range.
+    let changed = session.run(Command::Load(other_sample_params())).unwrap();
+    assert_eq!(changed, [START, PITCH, LOOP, VOLUME]);
+    assert_eq!(session.ids(), [START, PITCH, LOOP]);
+    assert_eq!(session.cache.value(PITCH), Some(12.0));
+    assert!(session.model.control(VOLUME).is_none());
+
+    // M -> M + 1 -> M, one parameter at a time.
+    let changed = session
+        .run(Command::Add(VOLUME, ParamDef::new("Volume", 0.0, 1.0, 0.5)))
+        .unwrap();
+    assert_eq!(changed, [START, PITCH, LOOP, VOLUME]);
+    assert_eq!(session.cache.value(VOLUME), Some(0.5));
+
+    let changed = session.run(Command::Remove(START)).unwrap();
+    assert_eq!(changed, [PITCH, LOOP, VOLUME, START]);
+    assert_eq!(session.ids(), [PITCH, LOOP, VOLUME]);
+    assert_eq!(session.cache.value(PITCH), Some(12.0));
+
+    // M -> 0
+    let changed = session.run(Command::Load(Vec::new())).unwrap();
+    assert_eq!(changed, [PITCH, LOOP, VOLUME]);
+    assert!(session.cache.params().is_empty());
+    assert!(session.snapshot.params().is_empty());
+    assert!(session.model.controls().is_empty());
+
+    // 0 -> N again: previous values are forgotten once the parameters are gone.
+    session.run(Command::Load(sample_params())).unwrap();
+    assert_eq!(session.cache.value(PITCH), Some(0.0));
+}
+
+#[test]
+pub fn full_rescans_refresh_cookies() {
+    let mut session = Session::new();
+
+    session.run(Command::Load(sample_params())).unwrap();
+    let before = session.cache.get(VOLUME).unwrap().cookie.as_raw();
+
+    session.run(Command::Remove(PITCH)).unwrap();
+    let after = session.cache.get(VOLUME).unwrap().cookie.as_raw();
+
+    assert_ne!(before, after);
+    assert_eq!(after as usize, GENERATION.with(Cell::get));
+}
+
+#[test]
+pub fn invalid_changes_do_not_request_rescans() {
+    let mut session = Session::new();
+    session.run(Command::Load(sample_params())).unwrap();
+
+    assert!(session
+        .run(Command::Add(VOLUME, ParamDef::new("Volume", 0.0, 1.0, 0.5)))
+        .is_none());
+    assert!(session.run(Command::Remove(LOOP)).is_none());
+
+    let duplicated = vec![
+        (LOOP, ParamDef::bypass("Loop")),
+        (LOOP, ParamDef::bypass("Loop")),
+    ];
+    assert!(session.run(Command::Load(duplicated)).is_none());
+    assert_eq!(session.ids(), [VOLUME, PITCH]);
+}
@@ -0,0 +1,176 @@
+//! Hosts querying extensions before calling `init`, through the raw plugin vtable.
+
+use clack_extensions::latency::{PluginLatency, PluginLatencyImpl};
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::ext::latency::{clap_plugin_latency, CLAP_EXT_LATENCY};
+use clap_sys::factory::plugin_factory::{clap_plugin_factory, CLAP_PLUGIN_FACTORY_ID};
+use clap_sys::host::clap_host;
+use clap_sys::plugin::clap_plugin;
+use clap_sys::version::CLAP_VERSION;
+use std::ffi::{c_void, CStr};
+use std::ptr::null_mut;
+
+pub struct LatencyPlugin<const COMPATIBLE: bool>;
+
+pub struct LatencyPluginMainThread;
+
+impl PluginMainThread<'_, ()> for LatencyPluginMainThread {}
+
+impl PluginLatencyImpl for LatencyPluginMainThread {
+    fn get(&mut self) -> u32 {
+        42
+    }
+}
+
+impl<const COMPATIBLE: bool> Plugin for LatencyPlugin<COMPATIBLE> {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = LatencyPluginMainThread;
+
+    const COMPATIBILITY: CompatibilityPolicy = if COMPATIBLE {
+        CompatibilityPolicy::COMPATIBLE
+            .with(CompatibilityCheck::EarlyGetExtension, CheckLevel::Allow)
+    } else {
+        CompatibilityPolicy::STRICT
+    };
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginLatency>();
+    }
+}
+
+impl<const COMPATIBLE: bool> DefaultPluginFactory for LatencyPlugin<COMPATIBLE> {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.early-queries", "Early Queries")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(LatencyPluginMainThread)
+    }
+}
+
+pub static STRICT_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<LatencyPlugin<false>>);
+pub static COMPATIBLE_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<LatencyPlugin<true>>);
+
+const PLUGIN_ID: &[u8] = b"org.rust-audio.clack.early-queries\0";
+const UNKNOWN_EXTENSION: &[u8] = b"org.rust-audio.clack.unknown\0";
+
+fn raw_host() -> clap_host {
+    clap_host {
+        clap_version: CLAP_VERSION,
+        host_data: null_mut(),
+        name: b"Legit Studio\0".as_ptr().cast(),
+        vendor: b"Legit Ltd.\0".as_ptr().cast(),
+        url: b"https://example.com\0".as_ptr().cast(),
+        version: b"4.3.2\0".as_ptr().cast(),
+        get_extension: None,
+        request_restart: None,
+        request_process: None,
+        request_callback: None,
+    }
+}
+
+/// Creates a plugin from the given entry, without initializing it.
+///
+/// # Safety
+///
+/// The given host must outlive the returned plugin, and the entry must be de-initialized once the
+/// plugin is destroyed.
+unsafe fn create_plugin(entry: &EntryDescriptor, host: &clap_host) -> *const clap_plugin {
+    assert!(entry.init.unwrap()(
+        b"/early-queries.clap\0".as_ptr().cast()
+    ));
+
+    // The factory pointer is kept as-is, as the plugin reads its own data past the raw factory.
+    let factory =
+        entry.get_factory.unwrap()(CLAP_PLUGIN_FACTORY_ID.as_ptr()).cast::<clap_plugin_factory>();
+    assert!(!factory.is_null());
+
+    let plugin = (*factory).create_plugin.unwrap()(factory, host, PLUGIN_ID.as_ptr().cast());
+    assert!(!plugin.is_null());
+    plugin
+}
+
+/// # Safety
+///
+/// The given plugin must be valid.
+unsafe fn get_extension(plugin: *const clap_plugin, id: &CStr) -> *const c_void {
+    (*plugin).get_extension.unwrap()(plugin, id.as_ptr())
+}
+
+/// # Safety
+///
+/// The given plugin must be valid, and must support the latency extension.
+unsafe fn latency(plugin: *const clap_plugin) -> u32 {
+    let latency = get_extension(plugin, CLAP_EXT_LATENCY)
+        .cast::<clap_plugin_latency>()
+        .as_ref()
+        .unwrap();
+
+    latency.get.unwrap()(plugin)
+}
+
+#[test]
+pub fn strict_plugins_reject_queries_before_init() {
+    let host = raw_host();
+
+    unsafe {
+        let plugin = create_plugin(&STRICT_ENTRY, &host);
+
+        assert!(get_extension(plugin, CLAP_EXT_LATENCY).is_null());
+
+        assert!((*plugin).init.unwrap()(plugin));
+        assert!(!get_extension(plugin, CLAP_EXT_LATENCY).is_null());
+        assert_eq!(latency(plugin), 42);
+
+        (*plugin).destroy.unwrap()(plugin);
+        STRICT_ENTRY.deinit.unwrap()();
+    }
+}
+
+#[test]
+pub fn compatible_plugins_answer_queries_before_init() {
+    let host = raw_host();
+    let unknown = CStr::from_bytes_with_nul(UNKNOWN_EXTENSION).unwrap();
+
+    unsafe {
+        let plugin = create_plugin(&COMPATIBLE_ENTRY, &host);
+
+        let early_latency = get_extension(plugin, CLAP_EXT_LATENCY);
+        assert!(!early_latency.is_null());
+        assert!(get_extension(plugin, unknown).is_null());
+
+        // The extension itself is still guarded until the plugin is initialized.
+        assert_eq!(latency(plugin), 0);
+        assert!(!(*plugin).activate.unwrap()(plugin, 48_000.0, 32, 32));
+
+        assert!((*plugin).init.unwrap()(plugin));
+        assert_eq!(get_extension(plugin, CLAP_EXT_LATENCY), early_latency);
+        assert_eq!(latency(plugin), 42);
+
+        (*plugin).destroy.unwrap()(plugin);
+        COMPATIBLE_ENTRY.deinit.unwrap()();
+    }
+}
+
+#[test]
+pub fn compatible_plugins_can_be_destroyed_before_init() {
+    let host = raw_host();
+
+    unsafe {
+        let plugin = create_plugin(&COMPATIBLE_ENTRY, &host);
+
+        assert!(!get_extension(plugin, CLAP_EXT_LATENCY).is_null());
+
+        (*plugin).destroy.unwrap()(plugin);
+        COMPATIBLE_ENTRY.deinit.unwrap()();
+    }
+}
@@ -0,0 +1,384 @@
+//! A host adds sidechain inputs to a plugin that accepts up to 4 of them, and keeps its audio
+//! port layout up to date as they are added and removed.
+
+use clack_extensions::audio_ports::snapshot::{AudioPortChange, AudioPortLayoutSnapshot};
+use clack_extensions::audio_ports::{
+    AudioPortInfo, AudioPortInfoWriter, AudioPortType, PluginAudioPorts, PluginAudioPortsImpl,
+};
+use clack_extensions::extensible_audio_ports::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const MAIN: ClapId = ClapId::new(0);
+const MAX_SIDECHAINS: usize = 4;
+
+pub struct SidechainPlugin;
+
+pub struct SidechainPluginMainThread {
+    sidechains: Vec<(ClapId, String)>,
+    next_id: u32,
+}
+
+impl Plugin for SidechainPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = SidechainPluginMainThread;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder
+            .register::<PluginAudioPorts>()
+            .register::<PluginExtensibleAudioPorts>();
+    }
+}
+
+impl DefaultPluginFactory for SidechainPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.sidechains", "Sidechains")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(SidechainPluginMainThread {
+            sidechains: Vec::new(),
+            next_id: 1,
+        })
+    }
+}
+
+impl PluginMainThread<'_, ()> for SidechainPluginMainThread {}
+
+impl PluginAudioPortsImpl for SidechainPluginMainThread {
+    fn count(&mut self, is_input: bool) -> u32 {
+        if is_input {
+            1 + self.sidechains.len() as u32
+        } else {
+            1
+        }
+    }
+
+    fn get(&mut self, index: u32, is_input: bool, writer: &mut AudioPortInfoWriter) {
+        if index == 0 {
+            writer.set(&AudioPortInfo::stereo(MAIN, b"main").main());
+            return;
+        }
+
+        if !is_input {
+            return;
+        }
+
+        if let Some((id, name)) = self.sidechains.get(index as usize - 1) {
+            writer.set(&AudioPortInfo::stereo(*id, name.as_bytes()));
+        }
+    }
+}
+
+impl PluginExtensibleAudioPortsImpl for SidechainPluginMainThread {
+    fn add_port(
+        &mut self,
+        is_input: bool,
+        channel_count: u32,
+        port_type: Option<AudioPortType>,
+        _details: PortDetails,
+    ) -> Result<ClapId, PluginError> {
+        if !is_input || self.sidechains.len() >= MAX_SIDECHAINS {
+            return Err(PluginError::Message("Cannot add more sidechains"));
+        }
+
+        if channel_count != 2 || port_type != Some(AudioPortType::STEREO) {
+            return Err(PluginError::Message("Only stereo sidechains are supported"));
+        }
+
+        let id = ClapId::new(self.next_id);
+        self.next_id += 1;
+        self.sidechains
+            .push((id, format!("sidechain {}", id.get())));
+
+        Ok(id)
+    }
+
+    fn remove_port(&mut self, is_input: bool, port_id: ClapId) -> Result<(), PluginError> {
+        let position = self
+            .sidechains
+            .iter()
+            .position(|(id, _)| *id == port_id)
+            .filter(|_| is_input)
+            .ok_or(PluginError::Message("Only sidechains can be removed"))?;
+
+        self.sidechains.remove(position);
+        Ok(())
+    }
+}
+
+pub static SIDECHAINS_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<SidechainPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 256,
+};
+
+fn instantiate() -> PluginInstance<MyHost> {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&SIDECHAINS_ENTRY, "/sidechains.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.sidechains\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+fn capture(
+    instance: &mut PluginInstance<MyHost>,
+    main_thread: &MainThreadToken,
+) -> (PluginExtensibleAudioPorts, AudioPortLayoutSnapshot) {
+    let mut plugin = instance.plugin_handle_checked(main_thread);
+    let audio_ports = plugin.get_extension::<PluginAudioPorts>().unwrap();
+    let extensible = plugin
+        .get_extension::<PluginExtensibleAudioPorts>()
+        .unwrap();
+
+    (
+        extensible,
+        AudioPortLayoutSnapshot::capture(&audio_ports, &mut plugin),
+    )
+}
+
+fn input_ids(layout: &AudioPortLayoutSnapshot) -> Vec<u32> {
+    layout.inputs().iter().map(|p| p.id.get()).collect()
+}
+
+#[test]
+fn sidechains_can_be_added_up_to_the_limit() {
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+    let mut instance = instantiate();
+    let (extensible, mut layout) = capture(&mut instance, &main_thread);
+
+    assert_eq!(input_ids(&layout), [0]);
+
+    for expected_id in 1..=4 {
+        let update = extensible
+            .add_port(
+                &mut instance,
+                &main_thread,
+                &mut layout,
+                true,
+                2,
+                Some(AudioPortType::STEREO),
+            )
+            .unwrap();
+
+        assert_eq!(update.port_id, ClapId::new(expected_id));
+        assert_eq!(
+            update.changes,
+            [AudioPortChange::Added {
+                is_input: true,
+                id: ClapId::new(expected_id),
+            }]
+        );
+    }
+
+    assert_eq!(input_ids(&layout), [0, 1, 2, 3, 4]);
+    assert_eq!(layout.inputs()[4].name, "sidechain 4");
+    assert_eq!(layout.inputs()[4].channel_count, 2);
+
+    let error = extensible
+        .add_port(
+            &mut instance,
+            &main_thread,
+            &mut layout,
+            true,
+            2,
+            Some(AudioPortType::STEREO),
+        )
+        .unwrap_err();
+
+    assert_eq!(error, ExtensibleAudioPortsError::Rejected);
+    assert_eq!(input_ids(&layout), [0, 1, 2, 3, 4]);
+    assert_eq!(layout, capture(&mut instance, &main_thread).1);
+}
+
+#[test]
+fn unsupported_ports_are_rejected() {
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+    let mut instance = instantiate();
+    let (extensible, mut layout) = capture(&mut instance, &main_thread);
+
+    let outputs = extensible.add_port(
+        &mut instance,
+        &main_thread,
+        &mut layout,
+        false,
+        2,
+        Some(AudioPortType::STEREO),
+    );
+    assert_eq!(outputs, Err(ExtensibleAudioPortsError::Rejected));
+
+    let mono = extensible.add_port(
+        &mut instance,
+        &main_thread,
+        &mut layout,
+        true,
+        1,
+        Some(AudioPortType::MONO),
+    );
+    assert_eq!(mono, Err(ExtensibleAudioPortsError::Rejected));
+
+    assert_eq!(input_ids(&layout), [0]);
+}
+
+#[test]
+fn sidechains_can_be_removed() {
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+    let mut instance = instantiate();
+    let (extensible, mut layout) = capture(&mut instance, &main_thread);
+
+    for _ in 0..MAX_SIDECHAINS {
+        extensible
+            .add_port(
+                &mut instance,
+                &main_thread,
+                &mut layout,
+                true,
+                2,
+                Some(AudioPortType::STEREO),
+            )
+            .unwrap();
+    }
+
+    let update = extensible
+        .remove_port(
+            &mut instance,
+            &main_thread,
+            &mut layout,
+            true,
+            ClapId::new(2),
+        )
+        .unwrap();
+
+    assert_eq!(update.port_id, ClapId::new(2));
+    assert_eq!(
+        update.changes,
+        [
+            AudioPortChange::Removed {
+                is_input: true,
+                id: ClapId::new(2),
+            },
+            AudioPortChange::Moved {
+                is_input: true,
+                id: ClapId::new(3),
+                old: 3,
+                new: 2,
+            },
+            AudioPortChange::Moved {
+                is_input: true,
+                id: ClapId::new(4),
+                old: 4,
+                new: 3,
+            },
+        ]
+    );
+    assert_eq!(input_ids(&layout), [0, 1, 3, 4]);
+
+    // The main port can't be removed, and removed ports aren't known anymore.
+    assert_eq!(
+        extensible.remove_port(&mut instance, &main_thread, &mut layout, true, MAIN),
+        Err(ExtensibleAudioPortsError::Rejected)
+    );
+    assert_eq!(
+        extensible.remove_port(
+            &mut instance,
+            &main_thread,
+            &mut layout,
+            true,
+            ClapId::new(2)
+        ),
+        Err(ExtensibleAudioPortsError::UnknownPort(ClapId::new(2)))
+    );
+
+    // A slot was freed up for a new sidechain.
+    let update = extensible
+        .add_port(
+            &mut instance,
+            &main_thread,
+            &mut layout,
+            true,
+            2,
+            Some(AudioPortType::STEREO),
+        )
+        .unwrap();
+
+    assert_eq!(update.port_id, ClapId::new(5));
+    assert_eq!(input_ids(&layout), [0, 1, 3, 4, 5]);
+}
+
+#[test]
+fn ports_cannot_be_changed_while_active() {
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+    let mut instance = instantiate();
+    let (extensible, mut layout) = capture(&mut instance, &main_thread);
+
+    let processor = instance
+        .activate_checked(&main_thread, |_, _| (), CONFIGURATION)
+        .unwrap();
+
+    assert_eq!(
+        extensible.add_port(
+            &mut instance,
+            &main_thread,
+            &mut layout,
+            true,
+            2,
+            Some(AudioPortType::STEREO),
+        ),
+        Err(ExtensibleAudioPortsError::PluginActive)
+    );
+    assert_eq!(
+        extensible.remove_port(&mut instance, &main_thread, &mut layout, true, MAIN),
+        Err(ExtensibleAudioPortsError::PluginActive)
+    );
+
+    instance.deactivate_checked(&main_thread, processor);
+
+    let update = extensible
+        .add_port(
+            &mut instance,
+            &main_thread,
+            &mut layout,
+            true,
+            2,
+            Some(AudioPortType::STEREO),
+        )
+        .unwrap();
+
+    assert_eq!(update.port_id, ClapId::new(1));
+}
@@ -0,0 +1,218 @@
+//! Plugin instances cache the extension pointers plugins return, and can detect plugins changing
+//! their answers over time.
+
+use clack_extensions::latency::PluginLatency;
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::ext::latency::{clap_plugin_latency, CLAP_EXT_LATENCY};
+use clap_sys::plugin::clap_plugin;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::ptr::NonNull;
+use std::sync::Mutex;
+
+/// What the plugin answers to a query for the latency extension.
+#[derive(Copy, Clone)]
+enum Answer {
+    First,
+    Second,
+    Null,
+}
+
+thread_local! {
+    /// The plugin's next answers. Once exhausted, the plugin keeps answering [`Answer::First`].
+    static SCRIPT: RefCell<VecDeque<Answer>> = const { RefCell::new(VecDeque::new()) };
+}
+
+fn script(answers: &[Answer]) {
+    SCRIPT.with(|s| *s.borrow_mut() = answers.iter().copied().collect());
+}
+
+fn remaining_answers() -> usize {
+    SCRIPT.with(|s| s.borrow().len())
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn first_latency(_plugin: *const clap_plugin) -> u32 {
+    1
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn second_latency(_plugin: *const clap_plugin) -> u32 {
+    2
+}
+
+static FIRST: clap_plugin_latency = clap_plugin_latency {
+    get: Some(first_latency),
+};
+
+static SECOND: clap_plugin_latency = clap_plugin_latency {
+    get: Some(second_latency),
+};
+
+/// A plugin that answers queries for the latency extension following a script.
+pub struct FlippingPlugin;
+
+impl Plugin for FlippingPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, shared: Option<&()>) {
+        // Only script answers given to the host, once the instance is created.
+        if shared.is_none() {
+            return;
+        }
+
+        let answer = SCRIPT.with(|s| s.borrow_mut().pop_front().unwrap_or(Answer::First));
+        let latency = match answer {
+            Answer::First => &FIRST,
+            Answer::Second => &SECOND,
+            Answer::Null => return,
+        };
+
+        // SAFETY: both statics are latency extension structs.
+        unsafe {
+            builder.register_raw(CLAP_EXT_LATENCY, NonNull::from(latency).cast());
+        }
+    }
+}
+
+impl DefaultPluginFactory for FlippingPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.flipping", "Flipping")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub static FLIPPING_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<FlippingPlugin>);
+
+#[derive(Default)]
+struct CacheHostShared {
+    logs: Mutex<Vec<(LogSeverity, String)>>,
+}
+
+impl SharedHandler<'_> for CacheHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostLogImpl for CacheHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.logs
+            .lock()
+            .unwrap()
+            .push((severity, message.to_owned()));
+    }
+}
+
+struct CacheHost;
+
+impl HostHandlers for CacheHost {
+    type Shared<'a> = CacheHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostLog>();
+    }
+}
+
+fn instantiate() -> PluginInstance<CacheHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&FLIPPING_ENTRY, "/flipping.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<CacheHost>::new(
+        |_| CacheHostShared::default(),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.flipping\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+fn latency_of(instance: &mut PluginInstance<CacheHost>, latency: Option<PluginLatency>) -> u32 {
+    latency
+        .unwrap()
+        .get(&mut instance.plugin_handle_unchecked())
+}
+
+fn take_logs(instance: &PluginInstance<CacheHost>) -> Vec<(LogSeverity, String)> {
+    instance.access_shared_handler(|h| std::mem::take(&mut *h.logs.lock().unwrap()))
+}
+
+#[test]
+pub fn first_answer_is_cached() {
+    let mut instance = instantiate();
+    script(&[Answer::First, Answer::Second, Answer::Null]);
+
+    let latency = instance.get_extension::<PluginLatency>();
+    assert_eq!(latency_of(&mut instance, latency), 1);
+
+    // The plugin isn't queried again.
+    let latency = instance.get_extension::<PluginLatency>();
+    assert_eq!(latency_of(&mut instance, latency), 1);
+    assert_eq!(remaining_answers(), 2);
+
+    // Requerying bypasses the cache, without changing it.
+    let latency = instance.requery_extension::<PluginLatency>();
+    assert_eq!(latency_of(&mut instance, latency), 2);
+    assert!(instance.requery_extension::<PluginLatency>().is_none());
+
+    let latency = instance.get_extension::<PluginLatency>();
+    assert_eq!(latency_of(&mut instance, latency), 1);
+    assert!(take_logs(&instance).is_empty());
+}
+
+#[test]
+pub fn null_answers_are_not_cached() {
+    let mut instance = instantiate();
+    script(&[Answer::Null, Answer::Second]);
+
+    assert!(instance.get_extension::<PluginLatency>().is_none());
+
+    let latency = instance.get_extension::<PluginLatency>();
+    assert_eq!(latency_of(&mut instance, latency), 2);
+}
+
+#[test]
+pub fn inconsistent_answers_are_reported_in_validation_mode() {
+    let mut instance = instantiate();
+    instance.set_extension_validation(true);
+    script(&[Answer::First, Answer::First, Answer::Second, Answer::Null]);
+
+    for _ in 0..4 {
+        let latency = instance.get_extension::<PluginLatency>();
+        assert_eq!(latency_of(&mut instance, latency), 1);
+    }
+
+    let logs = take_logs(&instance);
+    assert_eq!(logs.len(), 2, "{logs:?}");
+
+    for (severity, message) in &logs {
+        assert_eq!(*severity, LogSeverity::PluginMisbehaving);
+        assert!(
+            message.contains("org.rust-audio.clack.flipping"),
+            "{message}"
+        );
+        assert!(message.contains("clap.latency"), "{message}");
+    }
+
+    assert!(logs[1].1.contains("NULL"), "{}", logs[1].1);
+}
@@ -0,0 +1,384 @@
+use clack_extensions::params::generic_ui::*;
+use clack_extensions::params::set::{ParamDef, ParamSet};
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+const CUTOFF: ClapId = ClapId::new(0);
+const FILTER_LEVEL: ClapId = ClapId::new(1);
+const MODE: ClapId = ClapId::new(2);
+const AMP_LEVEL: ClapId = ClapId::new(3);
+const VOICES: ClapId = ClapId::new(4);
+const METER: ClapId = ClapId::new(5);
+const HIDDEN: ClapId = ClapId::new(6);
+const BYPASS: ClapId = ClapId::new(7);
+
+thread_local! {
+    /// The number of times the plugin's `value_to_text` was called, on this thread.
+    static VALUE_TO_TEXT_CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A plugin with a bit of every kind of parameter, spread across a couple of modules.
+pub struct UiPlugin;
+
+pub struct UiPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for UiPluginShared {}
+
+pub struct UiPluginMainThread<'a> {
+    shared: &'a UiPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, UiPluginShared> for UiPluginMainThread<'a> {}
+
+impl PluginMainThreadParams for UiPluginMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.shared.params.get_info(param_index, info)
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        VALUE_TO_TEXT_CALLS.with(|c| c.set(c.get() + 1));
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input)
+    }
+}
+
+pub struct UiPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, UiPluginShared, UiPluginMainThread<'a>>
+    for UiPluginAudioProcessor
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut UiPluginMainThread<'a>,
+        _shared: &'a UiPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for UiPluginAudioProcessor {
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+impl Plugin for UiPlugin {
+    type AudioProcessor<'a> = UiPluginAudioProcessor;
+    type Shared<'a> = UiPluginShared;
+    type MainThread<'a> = UiPluginMainThread<'a>;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&UiPluginShared>) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for UiPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.generic-ui", "Generic UI")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        let stepped = ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_STEPPED;
+
+        Ok(UiPluginShared {
+            params: ParamSet::new()
+                .with_param(
+                    CUTOFF,
+                    ParamDef::new("Cutoff", 20.0, 20_000.0, 1_000.0).with_module("Filter"),
+                )
+                .with_param(
+                    FILTER_LEVEL,
+                    ParamDef::new("Level", 0.0, 1.0, 0.5).with_module("Filter"),
+                )
+                .with_param(
+                    MODE,
+                    ParamDef::enumeration("Mode", &["LP", "HP", "BP"]).with_module("Filter"),
+                )
+                .with_param(
+                    AMP_LEVEL,
+                    ParamDef::new("Level", 0.0, 1.0, 1.0).with_module("Amp"),
+                )
+                .with_param(
+                    VOICES,
+                    ParamDef::new("Voices", 1.0, 8.0, 4.0).with_flags(stepped),
+                )
+                .with_param(
+                    METER,
+                    ParamDef::new("Meter", 0.0, 1.0, 0.0).with_flags(ParamInfoFlags::IS_READONLY),
+                )
+                .with_param(
+                    HIDDEN,
+                    ParamDef::new("Hidden", 0.0, 1.0, 0.0).with_flags(ParamInfoFlags::IS_HIDDEN),
+                )
+                .with_bypass_param(BYPASS),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(UiPluginMainThread { shared })
+    }
+}
+
+pub static UI_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<UiPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+fn instantiate() -> (PluginInstance<MyHost>, ParamValueCache) {
+    let bundle = unsafe { PluginBundle::load_from_raw(&UI_ENTRY, "/generic-ui.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.generic-ui\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let mut cache = ParamValueCache::new();
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    cache.rescan(&params, &mut handle);
+
+    (instance, cache)
+}
+
+/// Sends the given events to the (inactive) plugin.
+fn flush(instance: &mut PluginInstance<MyHost>, events: &EventBuffer) {
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    params.flush(&mut handle, &events.as_input(), &mut OutputEvents::void());
+}
+
+fn plugin_value(instance: &mut PluginInstance<MyHost>, param_id: ClapId) -> Option<f64> {
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    params.get_value(&mut handle, param_id)
+}
+
+#[test]
+pub fn controls_are_classified_and_grouped() {
+    let (_instance, cache) = instantiate();
+    let model = GenericUiModel::from_cache(&cache);
+
+    let controls: Vec<_> = model
+        .controls()
+        .iter()
+        .map(|c| (c.id(), c.kind(), c.display_name()))
+        .collect();
+
+    assert_eq!(
+        controls,
+        [
+            (CUTOFF, ControlKind::Knob, "Cutoff"),
+            (FILTER_LEVEL, ControlKind::Knob, "Filter / Level"),
+            (MODE, ControlKind::Enum, "Mode"),
+            (AMP_LEVEL, ControlKind::Knob, "Amp / Level"),
+            (VOICES, ControlKind::Stepper, "Voices"),
+            (METER, ControlKind::Knob, "Meter"),
+            (BYPASS, ControlKind::Toggle, "Bypass"),
+        ]
+    );
+
+    assert!(model.control(METER).unwrap().is_readonly());
+    assert!(model.control(HIDDEN).is_none());
+    assert_eq!(model.control(AMP_LEVEL).unwrap().module().as_str(), "Amp");
+    assert_eq!(model.control(CUTOFF).unwrap().value(), Some(1_000.0));
+
+    let root = model.modules().root();
+    let modules: Vec<_> = root.children().iter().map(|m| m.name()).collect();
+    assert_eq!(modules, ["Filter", "Amp"]);
+    assert_eq!(root.children()[0].params(), [0, 1, 2]);
+    assert_eq!(root.children()[1].params(), [3]);
+    assert_eq!(root.params(), [4, 5, 6]);
+}
+
+fn value_to_text_calls() -> usize {
+    VALUE_TO_TEXT_CALLS.with(|c| c.get())
+}
+
+#[test]
+pub fn value_texts_are_cached() {
+    let (mut instance, mut cache) = instantiate();
+    let mut model = GenericUiModel::from_cache(&cache);
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    let calls = value_to_text_calls();
+
+    assert_eq!(model.value_text(MODE, &params, &mut handle), Some("LP"));
+    assert_eq!(model.value_text(MODE, &params, &mut handle), Some("LP"));
+    assert_eq!(model.value_text(VOICES, &params, &mut handle), Some("4"));
+    assert_eq!(model.value_text(HIDDEN, &params, &mut handle), None);
+    assert_eq!(value_to_text_calls(), calls + 2);
+
+    let labels = model.enum_labels(MODE, &params, &mut handle).unwrap();
+    assert_eq!(labels, ["LP", "HP", "BP"]);
+
+    // Changing the value invalidates its text.
+    let mut events = EventBuffer::new();
+    assert!(model
+        .set_value(MODE, 1.0, &mut cache, &mut events.as_output())
+        .unwrap());
+    assert_eq!(model.value_text(MODE, &params, &mut handle), Some("HP"));
+
+    // So does a text rescan.
+    let calls = value_to_text_calls();
+    model.on_rescan(ParamRescanFlags::TEXT, &cache);
+    assert_eq!(model.value_text(VOICES, &params, &mut handle), Some("4"));
+    assert_eq!(value_to_text_calls(), calls + 1);
+}
+
+#[test]
+pub fn user_changes_are_sent_to_the_plugin() {
+    let (mut instance, mut cache) = instantiate();
+    let mut model = GenericUiModel::from_cache(&cache);
+    let revision = model.revision();
+
+    let mut events = EventBuffer::new();
+    let mut output = events.as_output();
+
+    assert!(model.begin_gesture(CUTOFF, &mut output).unwrap());
+    assert!(model
+        .set_value(CUTOFF, 50_000.0, &mut cache, &mut output)
+        .unwrap());
+    assert!(model.end_gesture(CUTOFF, &mut output).unwrap());
+    assert!(model
+        .set_normalized_value(VOICES, 0.5, &mut cache, &mut output)
+        .unwrap());
+    assert!(model
+        .set_value(BYPASS, 1.0, &mut cache, &mut output)
+        .unwrap());
+
+    // Read-only, hidden and unknown parameters can't be changed.
+    assert!(!model
+        .set_value(METER, 1.0, &mut cache, &mut output)
+        .unwrap());
+    assert!(!model.begin_gesture(METER, &mut output).unwrap());
+    assert!(!model
+        .set_value(HIDDEN, 1.0, &mut cache, &mut output)
+        .unwrap());
+    assert!(!model
+        .set_value(ClapId::new(42), 1.0, &mut cache, &mut output)
+        .unwrap());
+
+    assert_eq!(events.len(), 5);
+    assert!(model.revision() > revision);
+
+    // Values are constrained to their range, and rounded for stepped parameters.
+    assert_eq!(model.control(CUTOFF).unwrap().value(), Some(20_000.0));
+    assert_eq!(model.control(VOICES).unwrap().value(), Some(5.0));
+    assert_eq!(cache.value(CUTOFF), Some(20_000.0));
+    assert_eq!(cache.value(VOICES), Some(5.0));
+
+    flush(&mut instance, &events);
+    assert_eq!(plugin_value(&mut instance, CUTOFF), Some(20_000.0));
+    assert_eq!(plugin_value(&mut instance, VOICES), Some(5.0));
+    assert_eq!(plugin_value(&mut instance, BYPASS), Some(1.0));
+    assert_eq!(plugin_value(&mut instance, METER), Some(0.0));
+
+    events.clear();
+    assert!(model
+        .reset(CUTOFF, &mut cache, &mut events.as_output())
+        .unwrap());
+    flush(&mut instance, &events);
+    assert_eq!(plugin_value(&mut instance, CUTOFF), Some(1_000.0));
+}
+
+#[test]
+pub fn rescans_update_the_model() {
+    let (mut instance, mut cache) = instantiate();
+    let mut model = GenericUiModel::from_cache(&cache);
+
+    // The plugin changes a value on its own.
+    let mut events = EventBuffer::new();
+    events.push(&ParamValueEvent::new(
+        0,
+        AMP_LEVEL,
+        Pckn::match_all(),
+        0.25,
+        Cookie::empty(),
+    ));
+    flush(&mut instance, &events);
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    cache.rescan(&params, &mut handle);
+
+    let revision = model.revision();
+    assert_eq!(
+        model.on_rescan(ParamRescanFlags::VALUES, &cache),
+        [AMP_LEVEL]
+    );
+    assert_eq!(model.control(AMP_LEVEL).unwrap().value(), Some(0.25));
+    assert_eq!(
+        model.control(AMP_LEVEL).unwrap().normalized_value(),
+        Some(0.25)
+    );
+    assert!(model.revision() > revision);
+
+    // Nothing changed since.
+    let revision = model.revision();
+    assert!(model.sync_values(&cache).is_empty());
+    assert_eq!(model.revision(), revision);
+
+    // A full rescan rebuilds everything.
+    assert_eq!(
+        model.on_rescan(ParamRescanFlags::ALL, &cache).len(),
+        model.controls().len()
+    );
+    assert!(model.revision() > revision);
+}
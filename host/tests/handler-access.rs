@@ -0,0 +1,150 @@
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// A plugin that requests a restart every time its main-thread callback is called.
+pub struct RestartingPlugin;
+
+pub struct RestartingPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+}
+
+impl<'a> PluginMainThread<'a, ()> for RestartingPluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        self.host.request_restart();
+    }
+}
+
+impl Plugin for RestartingPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = RestartingPluginMainThread<'a>;
+}
+
+impl DefaultPluginFactory for RestartingPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.restarting", "Restarting")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(RestartingPluginMainThread { host })
+    }
+}
+
+pub static RESTARTING_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<RestartingPlugin>);
+
+/// Engine state owned outside the plugin instance.
+struct Track {
+    name: &'static str,
+}
+
+struct EngineHost;
+
+impl HostHandlers for EngineHost {
+    type Shared<'a> = EngineHostShared;
+    type MainThread<'a> = EngineHostMainThread;
+    type AudioProcessor<'a> = ();
+}
+
+struct EngineHostShared {
+    restart_requests: Mutex<Sender<&'static str>>,
+    track_name: Mutex<&'static str>,
+}
+
+impl SharedHandler<'_> for EngineHostShared {
+    fn request_restart(&self) {
+        let track_name = *self.track_name.lock().unwrap();
+        let _ = self.restart_requests.lock().unwrap().send(track_name);
+    }
+
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct EngineHostMainThread {
+    track: Arc<Track>,
+}
+
+impl MainThreadHandler<'_> for EngineHostMainThread {}
+
+fn instantiate(track: Arc<Track>) -> (PluginInstance<EngineHost>, Receiver<&'static str>) {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&RESTARTING_ENTRY, "/restarting.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let (sender, receiver) = channel();
+    let track_name = track.name;
+
+    let instance = PluginInstance::<EngineHost>::new(
+        move |_| EngineHostShared {
+            restart_requests: Mutex::new(sender),
+            track_name: Mutex::new(track_name),
+        },
+        move |_| EngineHostMainThread { track },
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.restarting\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    (instance, receiver)
+}
+
+#[test]
+pub fn main_thread_handlers_can_be_rewired() {
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+
+    let bass = Arc::new(Track { name: "Bass" });
+    let (mut instance, _receiver) = instantiate(bass.clone());
+
+    assert_eq!(Arc::strong_count(&bass), 2);
+    assert_eq!(
+        instance.access_handler_checked(&main_thread, |h| h.track.name),
+        "Bass"
+    );
+
+    let drums = Arc::new(Track { name: "Drums" });
+    let previous = instance.access_handler_mut_checked(&main_thread, |h| {
+        std::mem::replace(&mut h.track, drums.clone())
+    });
+
+    assert!(Arc::ptr_eq(&previous, &bass));
+    drop(previous);
+
+    // The instance doesn't hold the previous engine state anymore.
+    assert_eq!(Arc::strong_count(&bass), 1);
+    assert_eq!(Arc::strong_count(&drums), 2);
+    assert_eq!(instance.access_handler_unchecked(|h| h.track.name), "Drums");
+}
+
+#[test]
+pub fn shared_handlers_can_be_rewired() {
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+
+    let (mut instance, first_receiver) = instantiate(Arc::new(Track { name: "Bass" }));
+
+    instance.call_on_main_thread_callback_checked(&main_thread);
+    assert_eq!(first_receiver.try_recv(), Ok("Bass"));
+
+    // The plugin was moved to another track, which is handled by a different part of the engine.
+    let (sender, second_receiver) = channel();
+    instance.access_shared_handler(|h| {
+        *h.restart_requests.lock().unwrap() = sender;
+        *h.track_name.lock().unwrap() = "Drums";
+    });
+
+    instance.call_on_main_thread_callback_checked(&main_thread);
+    assert_eq!(second_receiver.try_recv(), Ok("Drums"));
+    assert!(first_receiver.try_recv().is_err());
+}
@@ -0,0 +1,210 @@
+//! Host callbacks that panic must never unwind into the plugin: the panic is caught, reported,
+//! and the plugin receives a failure value instead.
+
+use clack_common::utils::fallback_log::{set_fallback_logger, LogRecord};
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::{Arc, Mutex};
+
+/// A plugin that calls into the host as soon as it is instantiated.
+pub struct ChattyPlugin;
+
+pub struct ChattyPluginMainThread;
+
+impl PluginMainThread<'_, ()> for ChattyPluginMainThread {}
+
+impl Plugin for ChattyPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ChattyPluginMainThread;
+}
+
+impl DefaultPluginFactory for ChattyPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.chatty", "Chatty")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        let host = host.shared();
+        host.request_restart();
+
+        if let Some(log) = host.get_extension::<HostLog>() {
+            log.log(
+                &host,
+                LogSeverity::Info,
+                CStr::from_bytes_with_nul(b"Hello\0").unwrap(),
+            );
+        }
+
+        Ok(ChattyPluginMainThread)
+    }
+}
+
+pub static CHATTY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<ChattyPlugin>);
+
+type LogRecords = Arc<Mutex<Vec<(LogSeverity, String)>>>;
+
+#[derive(Copy, Clone, Default)]
+struct Panics {
+    request_restart: bool,
+    log: bool,
+    declare_extensions: bool,
+}
+
+struct PanickingHost;
+
+struct PanickingHostShared {
+    records: LogRecords,
+    panics: Panics,
+}
+
+impl SharedHandler<'_> for PanickingHostShared {
+    fn request_restart(&self) {
+        if self.panics.request_restart {
+            panic!("request_restart panicked");
+        }
+    }
+
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostLogImpl for PanickingHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        if self.panics.log {
+            panic!("log panicked");
+        }
+
+        self.records
+            .lock()
+            .unwrap()
+            .push((severity, message.to_string()));
+    }
+}
+
+impl HostHandlers for PanickingHost {
+    type Shared<'a> = PanickingHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, shared: &Self::Shared<'_>) {
+        if shared.panics.declare_extensions {
+            panic!("declare_extensions panicked");
+        }
+
+        builder.register::<HostLog>();
+    }
+}
+
+static FALLBACK_RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record_fallback(record: &LogRecord) {
+    FALLBACK_RECORDS.lock().unwrap().push(record.to_string());
+}
+
+fn fallback_records_containing(pattern: &str) -> usize {
+    FALLBACK_RECORDS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|r| r.contains(pattern))
+        .count()
+}
+
+fn instantiate(panics: Panics) -> (PluginInstance<PanickingHost>, LogRecords) {
+    set_fallback_logger(Some(record_fallback));
+
+    let bundle = unsafe { PluginBundle::load_from_raw(&CHATTY_ENTRY, "/chatty.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let records = LogRecords::default();
+    let shared_records = records.clone();
+
+    let instance = PluginInstance::<PanickingHost>::new(
+        move |_| PanickingHostShared {
+            records: shared_records,
+            panics,
+        },
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.chatty\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    (instance, records)
+}
+
+#[test]
+pub fn panicking_callbacks_are_reported_through_the_host_log() {
+    let (_instance, records) = instantiate(Panics {
+        request_restart: true,
+        ..Panics::default()
+    });
+
+    assert_eq!(
+        *records.lock().unwrap(),
+        [
+            (
+                LogSeverity::HostMisbehaving,
+                "[org.rust-audio.clack.chatty] [clap_host.request_restart] Host callback panicked"
+                    .to_string()
+            ),
+            (LogSeverity::Info, "Hello".to_string())
+        ]
+    );
+}
+
+#[test]
+pub fn panicking_log_is_reported_to_the_fallback_logger() {
+    let (_instance, records) = instantiate(Panics {
+        log: true,
+        ..Panics::default()
+    });
+
+    assert!(records.lock().unwrap().is_empty());
+    assert!(fallback_records_containing("[clap_host_log.log] Host callback panicked") > 0);
+    assert!(fallback_records_containing("Log handler failed when writing message: Hello") > 0);
+}
+
+#[test]
+pub fn failing_to_report_a_panic_does_not_recurse() {
+    let (_instance, records) = instantiate(Panics {
+        request_restart: true,
+        log: true,
+        ..Panics::default()
+    });
+
+    assert!(records.lock().unwrap().is_empty());
+    assert!(
+        fallback_records_containing(
+            "Log handler failed when writing message: [org.rust-audio.clack.chatty] [clap_host.request_restart] Host callback panicked"
+        ) > 0
+    );
+}
+
+#[test]
+pub fn panicking_extension_lookups_return_null() {
+    let (instance, records) = instantiate(Panics {
+        request_restart: true,
+        declare_extensions: true,
+        ..Panics::default()
+    });
+
+    assert!(records.lock().unwrap().is_empty());
+    assert!(fallback_records_containing("[clap_host.get_extension] Host callback panicked") > 0);
+    assert!(fallback_records_containing("[clap_host.request_restart] Host callback panicked") > 0);
+
+    drop(instance);
+}
@@ -0,0 +1,179 @@
+//! A plugin captures the host's capabilities once, and picks a degradation path depending on
+//! whether the host supports timers and thread pools.
+
+use clack_extensions::capabilities::HostCapabilities;
+use clack_extensions::thread_pool::{HostThreadPool, HostThreadPoolImpl};
+use clack_extensions::timer::{HostTimer, HostTimerImpl, TimerId};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum RefreshPath {
+    Timer(TimerId),
+    GuiIdle,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum RenderPath {
+    Parallel,
+    Serial,
+}
+
+thread_local! {
+    /// The way the plugin picked to refresh its meters.
+    static REFRESH_PATH: Cell<Option<RefreshPath>> = const { Cell::new(None) };
+    /// The way the plugin picked to render its voices.
+    static RENDER_PATH: Cell<Option<RenderPath>> = const { Cell::new(None) };
+}
+
+pub struct AdaptivePlugin;
+
+pub struct AdaptivePluginShared {
+    capabilities: HostCapabilities,
+}
+
+impl PluginShared<'_> for AdaptivePluginShared {}
+
+impl AdaptivePluginShared {
+    fn render_path(&self) -> RenderPath {
+        self.capabilities
+            .thread_pool()
+            .map_or_else(|| RenderPath::Serial, |_| RenderPath::Parallel)
+    }
+}
+
+pub struct AdaptivePluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    shared: &'a AdaptivePluginShared,
+}
+
+impl<'a> PluginMainThread<'a, AdaptivePluginShared> for AdaptivePluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        let timer = self
+            .shared
+            .capabilities
+            .timer()
+            .and_then(|timer| timer.register_timer(&mut self.host, 30).ok());
+
+        REFRESH_PATH.with(|p| p.set(Some(timer.map_or(RefreshPath::GuiIdle, RefreshPath::Timer))));
+        RENDER_PATH.with(|p| p.set(Some(self.shared.render_path())));
+    }
+}
+
+impl Plugin for AdaptivePlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = AdaptivePluginShared;
+    type MainThread<'a> = AdaptivePluginMainThread<'a>;
+}
+
+impl DefaultPluginFactory for AdaptivePlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.adaptive", "Adaptive")
+    }
+
+    fn new_shared(host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(AdaptivePluginShared {
+            capabilities: HostCapabilities::query(&host),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(AdaptivePluginMainThread { host, shared })
+    }
+}
+
+pub static ADAPTIVE_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<AdaptivePlugin>);
+
+struct AdaptiveHostShared {
+    supports_extensions: bool,
+}
+
+impl SharedHandler<'_> for AdaptiveHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct AdaptiveHostMainThread;
+
+impl MainThreadHandler<'_> for AdaptiveHostMainThread {}
+
+impl HostTimerImpl for AdaptiveHostMainThread {
+    fn register_timer(&mut self, _period_ms: u32) -> Result<TimerId, HostError> {
+        Ok(TimerId(7))
+    }
+
+    fn unregister_timer(&mut self, _timer_id: TimerId) -> Result<(), HostError> {
+        Ok(())
+    }
+}
+
+struct AdaptiveHostAudioProcessor;
+
+impl AudioProcessorHandler<'_> for AdaptiveHostAudioProcessor {}
+
+impl HostThreadPoolImpl for AdaptiveHostAudioProcessor {
+    fn request_exec(&mut self, _task_count: u32) -> Result<(), HostError> {
+        Ok(())
+    }
+}
+
+struct AdaptiveHost;
+
+impl HostHandlers for AdaptiveHost {
+    type Shared<'a> = AdaptiveHostShared;
+    type MainThread<'a> = AdaptiveHostMainThread;
+    type AudioProcessor<'a> = AdaptiveHostAudioProcessor;
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, shared: &Self::Shared<'_>) {
+        if shared.supports_extensions {
+            builder.register::<HostTimer>().register::<HostThreadPool>();
+        }
+    }
+}
+
+fn picked_paths(supports_extensions: bool) -> (Option<RefreshPath>, Option<RenderPath>) {
+    let bundle = unsafe { PluginBundle::load_from_raw(&ADAPTIVE_ENTRY, "/adaptive.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<AdaptiveHost>::new(
+        |_| AdaptiveHostShared {
+            supports_extensions,
+        },
+        |_| AdaptiveHostMainThread,
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.adaptive\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    instance.call_on_main_thread_callback_unchecked();
+
+    (REFRESH_PATH.with(Cell::get), RENDER_PATH.with(Cell::get))
+}
+
+#[test]
+pub fn uses_host_timer_and_thread_pool_when_supported() {
+    assert_eq!(
+        picked_paths(true),
+        (
+            Some(RefreshPath::Timer(TimerId(7))),
+            Some(RenderPath::Parallel)
+        )
+    );
+}
+
+#[test]
+pub fn degrades_when_host_lacks_extensions() {
+    assert_eq!(
+        picked_paths(false),
+        (Some(RefreshPath::GuiIdle), Some(RenderPath::Serial))
+    );
+}
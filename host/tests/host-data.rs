@@ -0,0 +1,78 @@
+//! The `host_data` pointer clack-host gives to plugins is set before they are created, and stays
+//! the same for the lifetime of the instance.
+
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::{c_void, CStr};
+use std::sync::Mutex;
+
+/// The host data the plugin observed, at each step of its lifetime.
+static OBSERVED: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+fn observe(host_data: *mut c_void) {
+    OBSERVED.lock().unwrap().push(host_data as usize);
+}
+
+pub struct HostDataPlugin;
+
+pub struct HostDataPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+}
+
+impl<'a> PluginMainThread<'a, ()> for HostDataPluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        observe(self.host.shared().host_data());
+    }
+}
+
+impl Plugin for HostDataPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = HostDataPluginMainThread<'a>;
+}
+
+impl DefaultPluginFactory for HostDataPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.host-data", "Host Data")
+    }
+
+    fn new_shared(host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        observe(host.host_data());
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        observe(host.shared().host_data());
+        Ok(HostDataPluginMainThread { host })
+    }
+}
+
+pub static HOST_DATA_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<HostDataPlugin>);
+
+#[test]
+pub fn host_data_is_stable_for_the_instance_lifetime() {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&HOST_DATA_ENTRY, "/host-data.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.host-data\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    instance.call_on_main_thread_callback_unchecked();
+
+    let observed = OBSERVED.lock().unwrap().clone();
+    assert_eq!(observed.len(), 3);
+    assert_ne!(observed[0], 0);
+    assert!(observed.iter().all(|&data| data == observed[0]));
+}
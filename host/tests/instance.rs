@@ -4,6 +4,7 @@ use clack_plugin::prelude::*;
 use std::ffi::CStr;
 
 use clack_host::prelude::*;
+use clack_host::utils::ClapVersion;
 use clack_plugin::clack_entry;
 
 pub struct DivaPluginStubAudioProcessor;
@@ -140,3 +141,34 @@ pub fn it_works_concurrently_with_static_entrypoint() {
         }
     })
 }
+
+#[test]
+pub fn refuses_incompatible_advertised_clap_version() {
+    let bundle = unsafe {
+        PluginBundle::load_from_raw(&DIVA_STUB_ENTRY, "/home/user/.clap/u-he/libdiva.so").unwrap()
+    };
+    assert_eq!(bundle.clap_version(), ClapVersion::CURRENT);
+
+    let host_info = HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2")
+        .unwrap()
+        .with_clap_version(ClapVersion {
+            major: 0,
+            minor: 26,
+            revision: 0,
+        });
+
+    let plugin_instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"com.u-he.diva\0").unwrap(),
+        &host_info,
+    );
+
+    assert_eq!(
+        plugin_instance.err(),
+        Some(PluginInstanceError::IncompatibleClapVersion {
+            found: ClapVersion::CURRENT
+        })
+    );
+}
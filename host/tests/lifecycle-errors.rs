@@ -0,0 +1,193 @@
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::{Arc, Mutex};
+
+pub struct UnlicensedPlugin;
+
+impl Plugin for UnlicensedPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for UnlicensedPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.unlicensed", "Unlicensed")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Err(PluginError::Message("No valid license found"))
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub struct PickyPlugin;
+
+impl Plugin for PickyPlugin {
+    type AudioProcessor<'a> = PickyPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for PickyPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.picky", "Picky")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub struct PickyPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for PickyPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        if audio_config.sample_rate != 48_000.0 {
+            return Err(PluginError::Message("Unsupported sample rate"));
+        }
+
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static UNLICENSED_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<UnlicensedPlugin>);
+pub static PICKY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<PickyPlugin>);
+
+type LogRecords = Arc<Mutex<Vec<(LogSeverity, String)>>>;
+
+struct LoggingHost;
+
+struct LoggingHostShared {
+    records: LogRecords,
+}
+
+impl SharedHandler<'_> for LoggingHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostLogImpl for LoggingHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((severity, message.to_string()));
+    }
+}
+
+impl HostHandlers for LoggingHost {
+    type Shared<'a> = LoggingHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostLog>();
+    }
+}
+
+/// Instantiates the given plugin, with a host that records all of the messages logged by the
+/// plugin, even if instantiation fails.
+fn instantiate(
+    entry: &'static EntryDescriptor,
+    id: &[u8],
+) -> (
+    Result<PluginInstance<LoggingHost>, PluginInstanceError>,
+    LogRecords,
+) {
+    let bundle = unsafe { PluginBundle::load_from_raw(entry, "/errors.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let records = LogRecords::default();
+    let shared_records = records.clone();
+
+    let instance = PluginInstance::<LoggingHost>::new(
+        move |_| LoggingHostShared {
+            records: shared_records,
+        },
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(id).unwrap(),
+        &host_info,
+    );
+
+    (instance, records)
+}
+
+#[test]
+pub fn initialization_errors_are_logged() {
+    let (instance, records) = instantiate(&UNLICENSED_ENTRY, b"org.rust-audio.clack.unlicensed\0");
+
+    assert_eq!(
+        instance.err(),
+        Some(PluginInstanceError::InstantiationFailed)
+    );
+    assert_eq!(
+        *records.lock().unwrap(),
+        [(
+            LogSeverity::Error,
+            "[org.rust-audio.clack.unlicensed] [clap_plugin.init] \
+            Plugin failed to initialize: No valid license found"
+                .to_string()
+        )]
+    );
+}
+
+#[test]
+pub fn activation_errors_are_logged_with_configuration() {
+    let (instance, records) = instantiate(&PICKY_ENTRY, b"org.rust-audio.clack.picky\0");
+    let mut instance = instance.unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: 1,
+        max_frames_count: 256,
+    };
+
+    assert_eq!(
+        instance.activate_unchecked(|_, _| (), configuration).err(),
+        Some(PluginInstanceError::ActivationFailed)
+    );
+    assert_eq!(
+        *records.lock().unwrap(),
+        [(
+            LogSeverity::Error,
+            "[org.rust-audio.clack.picky] [clap_plugin.activate] \
+            Plugin failed to activate (sample rate: 44100 Hz, frames: 1 to 256): \
+            Unsupported sample rate"
+                .to_string()
+        )]
+    );
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "load-meter")]
+
+use clack_host::prelude::*;
+use clack_host::process::load::PluginLoadMeter;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+const SAMPLE_RATE: f64 = 48_000.0;
+const BLOCK_SIZE: u32 = 480; // 10ms
+
+/// How long the plugin sleeps for in each process call, in microseconds.
+static SLEEP_DURATION: AtomicU64 = AtomicU64::new(0);
+
+pub struct SleepyPlugin;
+
+pub struct SleepyPluginAudioProcessor;
+
+impl Plugin for SleepyPlugin {
+    type AudioProcessor<'a> = SleepyPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for SleepyPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.sleepy", "Sleepy")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for SleepyPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let sleep = SLEEP_DURATION.load(Ordering::Relaxed);
+        if sleep > 0 {
+            std::thread::sleep(Duration::from_micros(sleep));
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static SLEEPY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<SleepyPlugin>);
+
+struct SleepyHost;
+
+impl HostHandlers for SleepyHost {
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn process_blocks(meter: &mut PluginLoadMeter<SleepyHost>, count: usize) {
+    let mut input = vec![0.0f32; BLOCK_SIZE as usize];
+    let mut output = vec![0.0f32; BLOCK_SIZE as usize];
+    let mut input_ports = AudioPorts::with_capacity(1, 1);
+    let mut output_ports = AudioPorts::with_capacity(1, 1);
+
+    for _ in 0..count {
+        meter
+            .process(
+                &input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only([InputChannel::variable(
+                        &mut input,
+                    )]),
+                    latency: 0,
+                }]),
+                &mut output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only([&mut output[..]]),
+                    latency: 0,
+                }]),
+                &InputEvents::empty(),
+                &mut OutputEvents::void(),
+                None,
+                None,
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri is too slow for the measured load to be meaningful
+pub fn smoothed_load_converges() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&SLEEPY_ENTRY, "/sleepy.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<SleepyHost>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.sleepy\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: SAMPLE_RATE,
+        min_frames_count: BLOCK_SIZE,
+        max_frames_count: BLOCK_SIZE,
+    };
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut meter = PluginLoadMeter::new(processor, configuration);
+    let handle = meter.handle();
+
+    assert_eq!(handle.load_percent(), 0.0);
+    assert_eq!(handle.peak_percent(), 0.0);
+
+    // Without sleeping, processing is near-instant.
+    process_blocks(&mut meter, 10);
+    assert!(handle.load_percent() < 20.0, "{}", handle.load_percent());
+
+    // Sleeping 2ms out of each 10ms block is at least a 20% load. After 1s of processed audio,
+    // the smoothed load must have converged to (at least) within 5% of it.
+    SLEEP_DURATION.store(2_000, Ordering::Relaxed);
+    process_blocks(&mut meter, 100);
+
+    let load = handle.load_percent();
+    assert!(load >= 19.0, "{load}");
+    assert!(handle.peak_percent() >= load);
+
+    handle.reset_peak();
+    assert_eq!(handle.peak_percent(), 0.0);
+
+    process_blocks(&mut meter, 1);
+    assert!(handle.peak_percent() >= 20.0, "{}", handle.peak_percent());
+
+    SLEEP_DURATION.store(0, Ordering::Relaxed);
+    instance.deactivate_unchecked(meter.into_inner().stop_processing());
+}
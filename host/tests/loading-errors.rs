@@ -0,0 +1,211 @@
+use clack_host::bundle::{EntryDescriptor, PluginBundle, PluginBundleError};
+use clap_sys::version::{clap_version, CLAP_VERSION};
+use std::ffi::{c_char, c_void, CStr};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::ptr::null;
+
+fn gain_library_path() -> PathBuf {
+    PathBuf::from(format!(
+        "{}/../target/debug/{}clack_plugin_gain{}",
+        env!("CARGO_MANIFEST_DIR"),
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ))
+}
+
+/// Creates an empty directory to hold the fixtures of a given test.
+fn fixtures_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "clack-loading-errors-{}-{name}",
+        std::process::id()
+    ));
+
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_plist(bundle_path: &Path, executable: &str) {
+    let contents = bundle_path.join("Contents");
+    std::fs::create_dir_all(contents.join("MacOS")).unwrap();
+
+    let plist = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <plist version=\"1.0\">\n<dict>\n\
+         \t<key>CFBundleExecutable</key>\n\t<string>{executable}</string>\n\
+         </dict>\n</plist>\n"
+    );
+
+    std::fs::write(contents.join("Info.plist"), plist).unwrap();
+}
+
+/// Tries to load every bundle in the given directory, in alphabetical order.
+fn scan(dir: &Path) -> Vec<(String, Result<PluginBundle, PluginBundleError>)> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, unsafe { PluginBundle::load(&path) })
+        })
+        .collect()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support calling foreign function (dlopen)
+pub fn broken_fixtures_produce_matching_errors() {
+    let dir = fixtures_dir("scan");
+
+    std::fs::write(dir.join("empty.clap"), b"").unwrap();
+    std::fs::write(dir.join("garbage.clap"), b"definitely not a shared library").unwrap();
+
+    std::fs::create_dir(dir.join("no-plist.clap")).unwrap();
+    write_plist(&dir.join("missing-executable.clap"), "Missing");
+
+    let gain_bundle = dir.join("valid.clap");
+    write_plist(&gain_bundle, "Gain");
+    std::fs::copy(gain_library_path(), gain_bundle.join("Contents/MacOS/Gain")).unwrap();
+
+    let results = scan(&dir);
+    let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "empty.clap",
+            "garbage.clap",
+            "missing-executable.clap",
+            "no-plist.clap",
+            "valid.clap"
+        ]
+    );
+
+    for (name, result) in results {
+        let path = dir.join(&name);
+
+        match (name.as_str(), result) {
+            ("empty.clap" | "garbage.clap", Err(PluginBundleError::LibraryLoadingError { .. })) => {
+            }
+            (
+                "missing-executable.clap",
+                Err(PluginBundleError::MissingBundleExecutable {
+                    executable_path, ..
+                }),
+            ) => assert_eq!(executable_path, path.join("Contents/MacOS/Missing")),
+            (
+                "no-plist.clap",
+                Err(PluginBundleError::MissingBundleExecutable {
+                    executable_path, ..
+                }),
+            ) => assert_eq!(executable_path, path.join("Contents/MacOS/no-plist")),
+            ("valid.clap", Ok(bundle)) => {
+                assert!(bundle.get_plugin_factory().is_some());
+                continue;
+            }
+            (name, result) => panic!("Unexpected result for {name}: {:?}", result.err()),
+        }
+
+        let error = unsafe { PluginBundle::load(&path) }.err().unwrap();
+        assert_eq!(error.path(), path);
+        assert!(error.to_string().contains(&*path.to_string_lossy()));
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support reading the current directory in isolation
+pub fn missing_files_are_reported_with_absolute_paths() {
+    let relative = Path::new("definitely-missing.clap");
+    let error = unsafe { PluginBundle::load(relative) }.err().unwrap();
+
+    let PluginBundleError::FileAccess { path, error } = error else {
+        panic!("Unexpected error: {error:?}")
+    };
+
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert!(path.is_absolute());
+    assert_eq!(path, std::env::current_dir().unwrap().join(relative));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support calling foreign function (dlopen)
+pub fn missing_entry_symbols_are_reported() {
+    let path = gain_library_path();
+    let library = unsafe { libloading::Library::new(&path) }.unwrap();
+    let symbol_name = CStr::from_bytes_with_nul(b"not_clap_entry\0").unwrap();
+
+    let error = unsafe { PluginBundle::load_from_symbol_in_library(&path, library, symbol_name) }
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        error,
+        PluginBundleError::MissingEntrySymbol { .. }
+    ));
+    assert_eq!(error.path(), path);
+}
+
+extern "C" fn init_ok(_plugin_path: *const c_char) -> bool {
+    true
+}
+
+extern "C" fn init_failing(_plugin_path: *const c_char) -> bool {
+    false
+}
+
+extern "C" fn deinit() {}
+
+extern "C" fn get_factory(_factory_id: *const c_char) -> *const c_void {
+    null()
+}
+
+static OUTDATED_ENTRY: EntryDescriptor = EntryDescriptor {
+    clap_version: clap_version {
+        major: 0,
+        minor: 26,
+        revision: 0,
+    },
+    init: Some(init_ok),
+    deinit: Some(deinit),
+    get_factory: Some(get_factory),
+};
+
+static FAILING_ENTRY: EntryDescriptor = EntryDescriptor {
+    clap_version: CLAP_VERSION,
+    init: Some(init_failing),
+    deinit: Some(deinit),
+    get_factory: Some(get_factory),
+};
+
+#[test]
+pub fn entry_errors_are_reported() {
+    let error = unsafe { PluginBundle::load_from_raw(&OUTDATED_ENTRY, "/outdated.clap") }
+        .err()
+        .unwrap();
+
+    let PluginBundleError::IncompatibleClapVersion {
+        path,
+        plugin_version,
+    } = error
+    else {
+        panic!("Unexpected error: {error:?}")
+    };
+
+    assert_eq!(path, Path::new("/outdated.clap"));
+    assert_eq!(plugin_version.major, 0);
+
+    let error = unsafe { PluginBundle::load_from_raw(&FAILING_ENTRY, "/failing.clap") }
+        .err()
+        .unwrap();
+
+    assert!(matches!(
+        &error,
+        PluginBundleError::EntryInitFailed { path } if path == Path::new("/failing.clap")
+    ));
+}
@@ -0,0 +1,104 @@
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+pub struct NoopPlugin;
+
+impl Plugin for NoopPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for NoopPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.noop", "Noop")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub static NOOP_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<NoopPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 256,
+};
+
+fn instantiate() -> PluginInstance<MyHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.noop\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+fn can_use_main_thread_methods_with_token() {
+    let main_thread = MainThreadToken::acquire().unwrap();
+    let mut instance = instantiate();
+    assert_eq!(instance.main_thread_id(), main_thread.thread_id());
+
+    let processor = instance
+        .activate_checked(&main_thread, |_, _| (), CONFIGURATION)
+        .unwrap();
+    instance.call_on_main_thread_callback_checked(&main_thread);
+    let _ = instance.plugin_handle_checked(&main_thread);
+
+    instance.deactivate_checked(&main_thread, processor);
+    assert!(!instance.is_active());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Main-thread-only operation")]
+fn using_token_from_another_thread_panics() {
+    // SAFETY: this token is misused on purpose, but the debug check panics before the plugin is
+    // ever called with it.
+    let token = unsafe { MainThreadToken::new_unchecked() };
+
+    // The instance is created on another thread, which is therefore its main thread.
+    let result = std::thread::spawn(move || {
+        let mut instance = instantiate();
+        let _ = instance.plugin_handle_checked(&token);
+    })
+    .join();
+
+    if let Err(panic) = result {
+        std::panic::resume_unwind(panic)
+    }
+}
@@ -212,7 +212,7 @@ fn it_works() {
     // Initialize host
     let mut host = TestHost::instantiate(&clap_entry);
 
-    host.activate();
+    host.activate_unchecked();
 
     host.inputs_mut()[0].fill(69f32);
     host.inputs_mut()[1].fill(69f32);
@@ -228,6 +228,6 @@ fn it_works() {
         }
     }
 
-    host.deactivate();
+    host.deactivate_unchecked();
 }
 */
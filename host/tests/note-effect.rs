@@ -0,0 +1,201 @@
+use clack_host::events::event_types::{NoteOffEvent, NoteOnEvent};
+use clack_host::events::Match;
+use clack_host::prelude::*;
+use clack_host::process::StartedPluginAudioProcessor;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::RefCell;
+use std::ffi::CStr;
+
+const BLOCK_SIZE: u32 = 64;
+
+thread_local! {
+    /// The frame count of every block the plugin processed, on this thread.
+    static FRAMES_COUNTS: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A note effect with no audio ports, which delays all note events by exactly one block.
+pub struct DelayPlugin;
+
+pub struct DelayPluginAudioProcessor {
+    pending: EventBuffer,
+}
+
+impl Plugin for DelayPlugin {
+    type AudioProcessor<'a> = DelayPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    const COMPATIBILITY: CompatibilityPolicy =
+        CompatibilityPolicy::COMPATIBLE.with(CompatibilityCheck::UnsortedEvents, CheckLevel::Warn);
+}
+
+impl DefaultPluginFactory for DelayPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.note-delay", "Note Delay")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for DelayPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            pending: EventBuffer::with_capacity(16),
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        FRAMES_COUNTS.with(|f| f.borrow_mut().push(audio.frames_count()));
+
+        for event in &self.pending {
+            let _ = events.output.try_push(event);
+        }
+
+        self.pending.clear();
+
+        for event in events.input {
+            if event.as_event::<NoteOnEvent>().is_some()
+                || event.as_event::<NoteOffEvent>().is_some()
+            {
+                self.pending.push(event);
+            }
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static DELAY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<DelayPlugin>);
+
+fn process_block(
+    processor: &mut StartedPluginAudioProcessor<()>,
+    input_events: &EventBuffer,
+) -> EventBuffer {
+    let mut output_events = EventBuffer::new();
+
+    processor
+        .process_events(
+            BLOCK_SIZE,
+            &input_events.as_input(),
+            &mut output_events.as_output(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    output_events
+}
+
+fn keys_and_times(events: &EventBuffer) -> Vec<(Match<u16>, u32)> {
+    events
+        .iter()
+        .map(|e| {
+            if let Some(e) = e.as_event::<NoteOnEvent>() {
+                (e.key(), e.header().time())
+            } else {
+                let e = e.as_event::<NoteOffEvent>().unwrap();
+                (e.key(), e.header().time())
+            }
+        })
+        .collect()
+}
+
+#[test]
+pub fn empty_audio_buffers_have_no_frame_count() {
+    assert_eq!(InputAudioBuffers::empty().frames_count(), None);
+    assert_eq!(OutputAudioBuffers::empty().frames_count(), None);
+    assert_eq!(InputAudioBuffers::default().frames_count(), None);
+    assert_eq!(OutputAudioBuffers::default().frames_count(), None);
+
+    // SAFETY: there are no buffers to be invalid.
+    let (inputs, outputs) = unsafe {
+        (
+            InputAudioBuffers::from_raw_buffers(&[], BLOCK_SIZE),
+            OutputAudioBuffers::from_raw_buffers(&mut [], BLOCK_SIZE),
+        )
+    };
+
+    assert_eq!(inputs.frames_count(), None);
+    assert_eq!(outputs.frames_count(), None);
+    assert_eq!(inputs.min_available_frames_with(&outputs), 0);
+}
+
+#[test]
+pub fn note_events_flow_through_plugin_without_audio_ports() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&DELAY_ENTRY, "/note-delay.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.note-delay\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE,
+        max_frames_count: BLOCK_SIZE,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    FRAMES_COUNTS.with(|f| f.borrow_mut().clear());
+
+    let mut notes = EventBuffer::new();
+    notes.push(&NoteOnEvent::new(
+        3,
+        Pckn::new(0u16, 0u16, 60u16, 0u32),
+        1.0,
+    ));
+    notes.push(&NoteOffEvent::new(
+        40,
+        Pckn::new(0u16, 0u16, 60u16, 0u32),
+        0.0,
+    ));
+
+    // Nothing comes out on the first block: the notes are held for one block.
+    let first = process_block(&mut processor, &notes);
+    assert!(first.is_empty());
+
+    let second = process_block(&mut processor, &EventBuffer::new());
+    assert_eq!(
+        keys_and_times(&second),
+        [(Match::Specific(60), 3), (Match::Specific(60), 40)]
+    );
+
+    let third = process_block(&mut processor, &EventBuffer::new());
+    assert!(third.is_empty());
+
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    let frames_counts = FRAMES_COUNTS.with(|f| core::mem::take(&mut *f.borrow_mut()));
+    assert_eq!(frames_counts, [BLOCK_SIZE; 3]);
+}
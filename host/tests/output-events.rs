@@ -0,0 +1,222 @@
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::events::event_types::MidiEvent;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::Mutex;
+
+const BLOCK_SIZE: u32 = 32;
+
+/// A plugin that echoes every input MIDI event as an output MIDI event, using the first byte
+/// of its data as the output event's time. This allows tests to make it push any event stream.
+pub struct EchoPlugin;
+
+pub struct EchoPluginAudioProcessor;
+
+impl Plugin for EchoPlugin {
+    type AudioProcessor<'a> = EchoPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    const COMPATIBILITY: CompatibilityPolicy =
+        CompatibilityPolicy::COMPATIBLE.with(CompatibilityCheck::UnsortedEvents, CheckLevel::Warn);
+}
+
+impl DefaultPluginFactory for EchoPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.echo", "Echo")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for EchoPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        for event in events.input {
+            if let Some(event) = event.as_event::<MidiEvent>() {
+                let data = event.data();
+                let _ = events
+                    .output
+                    .try_push(MidiEvent::new(data[0] as u32, 0, data));
+            }
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static ECHO_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<EchoPlugin>);
+
+struct EchoHostShared {
+    logs: Mutex<Vec<(LogSeverity, String)>>,
+}
+
+impl SharedHandler<'_> for EchoHostShared {
+    fn request_restart(&self) {
+        unreachable!()
+    }
+    fn request_process(&self) {
+        unreachable!()
+    }
+    fn request_callback(&self) {
+        unreachable!()
+    }
+}
+
+impl HostLogImpl for EchoHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.logs.lock().unwrap().push((severity, message.into()));
+    }
+}
+
+struct EchoHost;
+
+impl HostHandlers for EchoHost {
+    type Shared<'a> = EchoHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _: &Self::Shared<'_>) {
+        builder.register::<HostLog>();
+    }
+}
+
+/// Makes the echo plugin push output events at the given times, and returns the output events
+/// as well as everything the plugin logged.
+fn echo(times: &[u8]) -> (EventBuffer, Vec<(LogSeverity, String)>) {
+    let bundle = unsafe { PluginBundle::load_from_raw(&ECHO_ENTRY, "/echo.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<EchoHost>::new(
+        |_| EchoHostShared {
+            logs: Mutex::new(Vec::new()),
+        },
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.echo\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE,
+        max_frames_count: BLOCK_SIZE,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut input_events = EventBuffer::new();
+    for (i, time) in times.iter().enumerate() {
+        input_events.push(&MidiEvent::new(0, 0, [*time, i as u8, 0]));
+    }
+
+    let mut output_events = EventBuffer::new();
+
+    let mut input = vec![0.0f32; BLOCK_SIZE as usize];
+    let mut output = vec![0.0f32; BLOCK_SIZE as usize];
+    let mut input_ports = AudioPorts::with_capacity(1, 1);
+    let mut output_ports = AudioPorts::with_capacity(1, 1);
+
+    processor
+        .process(
+            &input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only([InputChannel::variable(&mut input)]),
+                latency: 0,
+            }]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([&mut output[..]]),
+                latency: 0,
+            }]),
+            &input_events.as_input(),
+            &mut output_events.as_output(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    let logs = instance.access_shared_handler(|h| core::mem::take(&mut *h.logs.lock().unwrap()));
+
+    (output_events, logs)
+}
+
+fn times_and_indexes(events: &EventBuffer) -> Vec<(u32, u8)> {
+    events
+        .iter()
+        .map(|e| {
+            let e = e.as_event::<MidiEvent>().unwrap();
+            (e.header().time(), e.data()[1])
+        })
+        .collect()
+}
+
+#[test]
+pub fn valid_output_events_are_not_reported() {
+    let (mut events, logs) = echo(&[0, 4, 4, 31]);
+
+    assert!(logs.is_empty(), "Unexpected logs: {logs:?}");
+    assert!(events.sanitize(BLOCK_SIZE).is_clean());
+    assert_eq!(
+        times_and_indexes(&events),
+        [(0, 0), (4, 1), (4, 2), (31, 3)]
+    );
+}
+
+#[test]
+pub fn unordered_output_events_are_reported_and_sanitized() {
+    let (mut events, logs) = echo(&[10, 5, 12]);
+
+    assert_eq!(logs.len(), 1, "Unexpected logs: {logs:?}");
+    assert_eq!(logs[0].0, LogSeverity::PluginMisbehaving);
+    assert!(logs[0].1.contains("out of order"));
+
+    let report = events.sanitize(BLOCK_SIZE);
+    assert_eq!(report.out_of_order, 1);
+    assert_eq!(report.out_of_block, 0);
+    assert_eq!(times_and_indexes(&events), [(5, 1), (10, 0), (12, 2)]);
+}
+
+#[test]
+pub fn out_of_block_output_events_are_reported_and_sanitized() {
+    let (mut events, logs) = echo(&[3, 32, 200]);
+
+    assert_eq!(logs.len(), 1, "Unexpected logs: {logs:?}");
+    assert_eq!(logs[0].0, LogSeverity::PluginMisbehaving);
+    assert!(logs[0].1.contains("beyond the current block"));
+
+    let report = events.sanitize(BLOCK_SIZE);
+    assert_eq!(report.out_of_order, 0);
+    assert_eq!(report.out_of_block, 2);
+    assert_eq!(times_and_indexes(&events), [(3, 0), (31, 1), (31, 2)]);
+}
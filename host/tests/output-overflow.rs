@@ -0,0 +1,217 @@
+//! A plugin generating more output events than the host's queue can hold spills them into an
+//! `OverflowBuffer`, and delivers them in the following blocks.
+
+use clack_host::events::event_types::MidiEvent;
+use clack_host::events::io::{EventBuffer, OutputEventBuffer, TryPushError};
+use clack_host::events::UnknownEvent;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clack_plugin::process::overflow::{OutputOverflow, OverflowBuffer};
+use std::cell::Cell;
+use std::ffi::CStr;
+
+const BLOCK_SIZE: u32 = 32;
+
+thread_local! {
+    /// The overflow status the plugin saw at the end of each block.
+    static OVERFLOW: Cell<OutputOverflow> = Cell::new(OutputOverflow::default());
+}
+
+/// A plugin that turns every input MIDI event into as many output MIDI events as its first data
+/// byte, one frame apart.
+pub struct ArpPlugin;
+
+pub struct ArpPluginAudioProcessor {
+    overflow: OverflowBuffer,
+    next_note: u8,
+}
+
+impl Plugin for ArpPlugin {
+    type AudioProcessor<'a> = ArpPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for ArpPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.arp", "Arp")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for ArpPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            overflow: OverflowBuffer::new(8),
+            next_note: 0,
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let mut output = self.overflow.begin(events.output);
+
+        for event in events.input {
+            let Some(event) = event.as_event::<MidiEvent>() else {
+                continue;
+            };
+
+            for time in 0..event.data()[0] as u32 {
+                let note = self.next_note;
+                self.next_note += 1;
+                let _ = output.try_push(MidiEvent::new(time, 0, [note, 0, 0]));
+            }
+        }
+
+        OVERFLOW.with(|o| o.set(output.overflow()));
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static ARP_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<ArpPlugin>);
+
+/// A host output event queue which refuses events past a fixed capacity.
+struct CappedSink {
+    events: EventBuffer,
+    capacity: usize,
+}
+
+impl OutputEventBuffer for CappedSink {
+    fn try_push(&mut self, event: &UnknownEvent) -> Result<(), TryPushError> {
+        if self.events.len() >= self.capacity {
+            return Err(TryPushError::new());
+        }
+
+        self.events.push(event);
+        Ok(())
+    }
+}
+
+struct ArpHostShared;
+
+impl SharedHandler<'_> for ArpHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct ArpHost;
+
+impl HostHandlers for ArpHost {
+    type Shared<'a> = ArpHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+#[test]
+pub fn overflowing_events_are_delivered_in_later_blocks() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&ARP_ENTRY, "/arp.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<ArpHost>::new(
+        |_| ArpHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.arp\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE,
+        max_frames_count: BLOCK_SIZE,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut input = vec![0.0f32; BLOCK_SIZE as usize];
+    let mut output = vec![0.0f32; BLOCK_SIZE as usize];
+    let mut input_ports = AudioPorts::with_capacity(1, 1);
+    let mut output_ports = AudioPorts::with_capacity(1, 1);
+
+    let mut sink = CappedSink {
+        events: EventBuffer::with_capacity(4),
+        capacity: 4,
+    };
+
+    let mut process_block = |note_count: u8| {
+        let mut input_events = EventBuffer::new();
+        if note_count > 0 {
+            input_events.push(&MidiEvent::new(0, 0, [note_count, 0, 0]));
+        }
+
+        sink.events.clear();
+
+        processor
+            .process(
+                &input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only([InputChannel::variable(
+                        &mut input,
+                    )]),
+                    latency: 0,
+                }]),
+                &mut output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only([&mut output[..]]),
+                    latency: 0,
+                }]),
+                &input_events.as_input(),
+                &mut OutputEvents::from_buffer(&mut sink),
+                None,
+                None,
+            )
+            .unwrap();
+
+        let delivered: Vec<_> = sink
+            .events
+            .iter()
+            .map(|e| {
+                let e = e.as_event::<MidiEvent>().unwrap();
+                (e.header().time(), e.data()[0])
+            })
+            .collect();
+
+        (delivered, OVERFLOW.with(Cell::get))
+    };
+
+    let (delivered, overflow) = process_block(10);
+    assert_eq!(delivered, [(0, 0), (1, 1), (2, 2), (3, 3)]);
+    assert!(overflow.has_overflowed());
+    assert_eq!(overflow.spilled, 6);
+
+    let (delivered, overflow) = process_block(0);
+    assert_eq!(delivered, [(0, 4), (0, 5), (0, 6), (0, 7)]);
+    assert_eq!(overflow.retried, 4);
+
+    let (delivered, overflow) = process_block(1);
+    assert_eq!(delivered, [(0, 8), (0, 9), (0, 10)]);
+    assert!(!overflow.has_overflowed());
+    assert_eq!(overflow.retried, 2);
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
@@ -0,0 +1,190 @@
+use clack_host::plugin::parallel::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+
+pub struct NoopPlugin;
+
+impl Plugin for NoopPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for NoopPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.noop", "Noop")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub struct BrokenPlugin;
+
+impl Plugin for BrokenPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for BrokenPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.broken", "Broken")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Err(PluginError::Message("Broken"))
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub static NOOP_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<NoopPlugin>);
+pub static BROKEN_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<BrokenPlugin>);
+
+fn host_info() -> HostInfo {
+    HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap()
+}
+
+fn noop_id() -> &'static CStr {
+    CStr::from_bytes_with_nul(b"org.rust-audio.clack.noop\0").unwrap()
+}
+
+#[test]
+pub fn instances_are_created_in_parallel_and_initialized_in_order() {
+    let noop = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+    let broken = unsafe { PluginBundle::load_from_raw(&BROKEN_ENTRY, "/broken.clap").unwrap() };
+
+    let mut instantiator = ParallelInstantiator::<()>::new(&host_info())
+        .with_thread_count(NonZeroUsize::new(4).unwrap());
+
+    for _ in 0..16 {
+        instantiator.push(&noop, noop_id());
+    }
+
+    let missing = instantiator.push(
+        &noop,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.missing\0").unwrap(),
+    );
+    let failing = instantiator.push(
+        &broken,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.broken\0").unwrap(),
+    );
+
+    assert_eq!(instantiator.len(), 18);
+
+    let reports = Mutex::new(Vec::new());
+    // SAFETY: this test's thread is the main thread of all the instances it creates.
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+
+    let instances = instantiator.instantiate(
+        &main_thread,
+        |_, _| (),
+        |_, _| (),
+        |progress| reports.lock().unwrap().push(progress),
+    );
+
+    assert_eq!(instances.len(), 18);
+
+    for (index, instance) in instances.iter().enumerate() {
+        match index {
+            i if i == missing => {
+                assert_eq!(
+                    instance.as_ref().err(),
+                    Some(&PluginInstanceError::PluginNotFound)
+                )
+            }
+            i if i == failing => assert_eq!(
+                instance.as_ref().err(),
+                Some(&PluginInstanceError::InstantiationFailed)
+            ),
+            _ => assert!(instance.is_ok()),
+        }
+    }
+
+    let reports = reports.into_inner().unwrap();
+    let (created, initialized): (Vec<&InstantiationProgress>, Vec<_>) = reports
+        .iter()
+        .partition(|p| p.stage == InstantiationStage::Created);
+
+    assert_eq!(created.len(), 18);
+    assert_eq!(initialized.len(), 18);
+    // All instances are created before any of them is initialized.
+    assert!(reports[..18]
+        .iter()
+        .all(|p| p.stage == InstantiationStage::Created));
+
+    assert!(!created.iter().find(|p| p.index == missing).unwrap().success);
+    assert!(created.iter().find(|p| p.index == failing).unwrap().success);
+    assert!(
+        !initialized
+            .iter()
+            .find(|p| p.index == failing)
+            .unwrap()
+            .success
+    );
+
+    // Initialization happens serially, in the order of requests.
+    for (i, progress) in initialized.iter().enumerate() {
+        assert_eq!(progress.index, i);
+        assert_eq!(progress.completed, i + 1);
+        assert_eq!(progress.total, 18);
+    }
+}
+
+#[test]
+pub fn pre_main_instance_can_be_created_on_another_thread() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+
+    let created = std::thread::scope(|s| {
+        s.spawn(|| {
+            PluginInstancePreMain::<()>::create(|_| (), |_| (), &bundle, noop_id(), &host_info())
+        })
+        .join()
+        .unwrap()
+    })
+    .unwrap();
+
+    let mut instance = created.init_unchecked().unwrap();
+    assert_eq!(instance.main_thread_id(), std::thread::current().id());
+
+    let processor = instance
+        .activate_unchecked(
+            |_, _| (),
+            PluginAudioConfiguration {
+                sample_rate: 48_000.0,
+                min_frames_count: 1,
+                max_frames_count: 256,
+            },
+        )
+        .unwrap();
+
+    instance.deactivate_unchecked(processor);
+}
+
+#[test]
+pub fn uninitialized_instances_can_be_dropped() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+
+    let created =
+        PluginInstancePreMain::<()>::create(|_| (), |_| (), &bundle, noop_id(), &host_info());
+
+    drop(created.unwrap());
+}
@@ -0,0 +1,184 @@
+#[cfg(debug_assertions)]
+use clack_common::utils::fallback_log::{set_fallback_logger, LogRecord};
+use clack_extensions::params::*;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::mem::MaybeUninit;
+#[cfg(debug_assertions)]
+use std::sync::Mutex;
+
+const GAIN: ClapId = ClapId::new(0);
+
+/// A plugin that only implements the required methods of the params extension.
+pub struct MinimalParamsPlugin;
+
+pub struct MinimalParamsPluginMainThread;
+
+impl PluginMainThread<'_, ()> for MinimalParamsPluginMainThread {}
+
+impl PluginMainThreadParams for MinimalParamsPluginMainThread {
+    fn count(&mut self) -> u32 {
+        1
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        if param_index == 0 {
+            info.set(&ParamInfo {
+                id: GAIN,
+                flags: ParamInfoFlags::IS_AUTOMATABLE,
+                cookie: Default::default(),
+                name: b"Gain",
+                module: b"",
+                min_value: 0.0,
+                max_value: 1.0,
+                default_value: 0.5,
+            })
+        }
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        (param_id == GAIN).then_some(0.5)
+    }
+}
+
+pub struct MinimalParamsPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, (), MinimalParamsPluginMainThread>
+    for MinimalParamsPluginAudioProcessor
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut MinimalParamsPluginMainThread,
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for MinimalParamsPluginAudioProcessor {}
+
+impl Plugin for MinimalParamsPlugin {
+    type AudioProcessor<'a> = MinimalParamsPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = MinimalParamsPluginMainThread;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for MinimalParamsPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.minimal-params", "Minimal Params")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(MinimalParamsPluginMainThread)
+    }
+}
+
+pub static MINIMAL_PARAMS_ENTRY: EntryDescriptor =
+    clack_entry!(SinglePluginEntry<MinimalParamsPlugin>);
+
+fn instantiate() -> PluginInstance<()> {
+    let bundle = unsafe {
+        PluginBundle::load_from_raw(&MINIMAL_PARAMS_ENTRY, "/minimal-params.clap").unwrap()
+    };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.minimal-params\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn text_conversions_default_to_plain_numbers() {
+    let mut instance = instantiate();
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    let mut buffer = [MaybeUninit::uninit(); 32];
+    let text = params
+        .value_to_text(&mut handle, GAIN, 0.25, &mut buffer)
+        .unwrap();
+    assert_eq!(text, b"0.25");
+
+    let text = CStr::from_bytes_with_nul(b" 0.75 \0").unwrap();
+    assert_eq!(params.text_to_value(&mut handle, GAIN, text), Some(0.75));
+
+    let text = CStr::from_bytes_with_nul(b"loud\0").unwrap();
+    assert_eq!(params.text_to_value(&mut handle, GAIN, text), None);
+}
+
+#[cfg(debug_assertions)]
+static FALLBACK_RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[cfg(debug_assertions)]
+fn record_fallback(record: &LogRecord) {
+    FALLBACK_RECORDS.lock().unwrap().push(record.to_string());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn default_flush_reports_dropped_changes() {
+    set_fallback_logger(Some(record_fallback));
+
+    let mut instance = instantiate();
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    // Nothing is reported if there was nothing to drop.
+    params.flush(
+        &mut handle,
+        &InputEvents::empty(),
+        &mut OutputEvents::void(),
+    );
+
+    let mut input = EventBuffer::new();
+    input.push(&ParamValueEvent::new(
+        0,
+        GAIN,
+        Pckn::match_all(),
+        0.75,
+        Cookie::empty(),
+    ));
+    params.flush(&mut handle, &input.as_input(), &mut OutputEvents::void());
+
+    set_fallback_logger(None);
+
+    let records = FALLBACK_RECORDS.lock().unwrap();
+    let dropped: Vec<_> = records.iter().filter(|r| r.contains("flush")).collect();
+    assert_eq!(dropped.len(), 1, "{records:?}");
+    assert!(
+        dropped[0].contains("Dropped 1 parameter event(s)"),
+        "{records:?}"
+    );
+}
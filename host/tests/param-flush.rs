@@ -0,0 +1,480 @@
+use clack_extensions::params::flush::*;
+use clack_extensions::params::set::{ParamDef, ParamSet};
+use clack_extensions::params::*;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const GAIN: ClapId = ClapId::new(0);
+const LEVEL: ClapId = ClapId::new(1);
+
+thread_local! {
+    static MAIN_THREAD_FLUSHES: Cell<u32> = const { Cell::new(0) };
+    static AUDIO_THREAD_FLUSHES: Cell<u32> = const { Cell::new(0) };
+}
+
+/// A plugin which gain parameter is changed from its (imaginary) GUI when its `on_main_thread`
+/// callback is called. It then requests a flush to report that change to the host, a few times.
+pub struct KnobPlugin;
+
+pub struct KnobPluginShared {
+    params: ParamSet,
+    gui_changed: AtomicBool,
+}
+
+impl PluginShared<'_> for KnobPluginShared {}
+
+impl KnobPluginShared {
+    fn flush(&self, input: &InputEvents, output: &mut OutputEvents) {
+        self.params.flush(input);
+
+        if self.gui_changed.swap(false, Ordering::SeqCst) {
+            let value = self.params.value(GAIN).unwrap();
+            output
+                .try_push(ParamValueEvent::new(
+                    0,
+                    GAIN,
+                    Pckn::match_all(),
+                    value,
+                    Cookie::empty(),
+                ))
+                .unwrap();
+        }
+    }
+}
+
+pub struct KnobPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    shared: &'a KnobPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, KnobPluginShared> for KnobPluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        self.shared.params.set_value(GAIN, 0.25);
+        self.shared.gui_changed.store(true, Ordering::SeqCst);
+
+        let host = self.host.shared();
+        let params = host.get_extension::<HostParams>().unwrap();
+        for _ in 0..3 {
+            params.request_flush(&host);
+        }
+    }
+}
+
+impl PluginMainThreadParams for KnobPluginMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.shared.params.get_info(param_index, info)
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input: &InputEvents, output: &mut OutputEvents) {
+        MAIN_THREAD_FLUSHES.with(|f| f.set(f.get() + 1));
+        self.shared.flush(input, output)
+    }
+}
+
+pub struct KnobPluginAudioProcessor<'a> {
+    shared: &'a KnobPluginShared,
+}
+
+impl<'a> PluginAudioProcessor<'a, KnobPluginShared, KnobPluginMainThread<'a>>
+    for KnobPluginAudioProcessor<'a>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut KnobPluginMainThread<'a>,
+        shared: &'a KnobPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { shared })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.shared.flush(events.input, events.output);
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for KnobPluginAudioProcessor<'_> {
+    fn flush(&mut self, input: &InputEvents, output: &mut OutputEvents) {
+        AUDIO_THREAD_FLUSHES.with(|f| f.set(f.get() + 1));
+        self.shared.flush(input, output)
+    }
+}
+
+impl Plugin for KnobPlugin {
+    type AudioProcessor<'a> = KnobPluginAudioProcessor<'a>;
+    type Shared<'a> = KnobPluginShared;
+    type MainThread<'a> = KnobPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&KnobPluginShared>,
+    ) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for KnobPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.knob", "Knob")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(KnobPluginShared {
+            params: ParamSet::new()
+                .with_param(GAIN, ParamDef::new("Gain", 0.0, 1.0, 1.0))
+                .with_param(
+                    LEVEL,
+                    ParamDef::new("Level", 0.0, 1.0, 0.0).with_flags(ParamInfoFlags::IS_READONLY),
+                ),
+            gui_changed: AtomicBool::new(false),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(KnobPluginMainThread { host, shared })
+    }
+}
+
+pub static KNOB_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<KnobPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = MyHostMainThread;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostParams>();
+    }
+}
+
+#[derive(Default)]
+struct MyHostShared {
+    flush_requests: FlushRequestQueue,
+}
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostParamsImplShared for MyHostShared {
+    fn request_flush(&self) {
+        self.flush_requests.request()
+    }
+}
+
+impl HostParamsFlushQueue for MyHostShared {
+    fn flush_request_queue(&self) -> &FlushRequestQueue {
+        &self.flush_requests
+    }
+}
+
+struct MyHostMainThread;
+
+impl MainThreadHandler<'_> for MyHostMainThread {}
+
+impl HostParamsImplMainThread for MyHostMainThread {
+    fn rescan(&mut self, _flags: ParamRescanFlags) {}
+    fn clear(&mut self, _param_id: ClapId, _flags: ParamClearFlags) {}
+}
+
+fn instantiate() -> (PluginInstance<MyHost>, ParamValueCache) {
+    let bundle = unsafe { PluginBundle::load_from_raw(&KNOB_ENTRY, "/knob.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared::default(),
+        |_| MyHostMainThread,
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.knob\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let mut cache = ParamValueCache::new();
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    cache.rescan(&params, &mut handle);
+
+    (instance, cache)
+}
+
+fn is_pending(instance: &PluginInstance<MyHost>) -> bool {
+    instance.access_shared_handler(|s| s.flush_requests.is_pending())
+}
+
+#[test]
+pub fn repeated_flush_requests_are_coalesced() {
+    let (mut instance, mut cache) = instantiate();
+    let mut events = EventIo::new();
+
+    assert!(!is_pending(&instance));
+    assert_eq!(
+        run_pending_flushes(
+            &mut [FlushTarget::new(&mut instance, &mut cache)],
+            &mut events
+        ),
+        0
+    );
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 0);
+
+    instance.call_on_main_thread_callback_unchecked();
+    assert!(is_pending(&instance));
+    assert_eq!(cache.value(GAIN), Some(1.0));
+
+    assert_eq!(
+        run_pending_flushes(
+            &mut [FlushTarget::new(&mut instance, &mut cache)],
+            &mut events
+        ),
+        1
+    );
+
+    // The plugin requested thrice, but was only flushed once.
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 1);
+    assert!(!is_pending(&instance));
+    assert_eq!(cache.value(GAIN), Some(0.25));
+
+    assert_eq!(
+        run_pending_flushes(
+            &mut [FlushTarget::new(&mut instance, &mut cache)],
+            &mut events
+        ),
+        0
+    );
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 1);
+}
+
+#[test]
+pub fn queued_input_events_are_sent_on_flush() {
+    let (mut instance, mut cache) = instantiate();
+    let mut events = EventIo::new();
+
+    let mut queued = EventBuffer::new();
+    queued.push(&ParamValueEvent::new(
+        0,
+        GAIN,
+        Pckn::match_all(),
+        0.5,
+        Cookie::empty(),
+    ));
+    let queued = queued.as_input();
+
+    // Without a pending request, queued events are kept for later.
+    let target = FlushTarget::new(&mut instance, &mut cache).with_input_events(&queued);
+    assert_eq!(run_pending_flushes(&mut [target], &mut events), 0);
+
+    instance.access_shared_handler(|s| s.flush_requests.request());
+    let target = FlushTarget::new(&mut instance, &mut cache).with_input_events(&queued);
+    assert_eq!(run_pending_flushes(&mut [target], &mut events), 1);
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    assert_eq!(params.get_value(&mut handle, GAIN), Some(0.5));
+}
+
+#[test]
+pub fn active_plugins_are_only_flushed_from_the_audio_thread() {
+    let (mut instance, mut cache) = instantiate();
+    let mut events = EventIo::new();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap();
+
+    instance.call_on_main_thread_callback_unchecked();
+    assert!(is_pending(&instance));
+
+    // The plugin is active: the main thread must leave the request to the audio thread.
+    assert_eq!(
+        run_pending_flushes(
+            &mut [FlushTarget::new(&mut instance, &mut cache)],
+            &mut events
+        ),
+        0
+    );
+    assert!(is_pending(&instance));
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 0);
+
+    let mut handle = processor.plugin_handle();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    events.reset();
+
+    let flushed = instance.access_shared_handler(|s| {
+        let (input, mut output) = events.split();
+        s.flush_requests
+            .flush_active(&params, &mut handle, &input, &mut output)
+    });
+
+    assert!(flushed);
+    assert_eq!(AUDIO_THREAD_FLUSHES.with(Cell::get), 1);
+    assert!(!is_pending(&instance));
+
+    // The host forwards the output events to the main thread's cache itself.
+    cache.update_from_events(&events.output_buffer().as_input());
+    assert_eq!(cache.value(GAIN), Some(0.25));
+
+    instance.deactivate_unchecked(processor);
+}
+
+#[test]
+pub fn started_plugins_can_be_flushed_without_processing() {
+    let (mut instance, mut cache) = instantiate();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut events = EventIo::new();
+    events.push_input(&ParamValueEvent::new(
+        0,
+        GAIN,
+        Pckn::match_all(),
+        0.5,
+        Cookie::empty(),
+    ));
+
+    let (input, mut output) = events.split();
+    assert!(processor.flush_active_params(&input, &mut output));
+    assert_eq!(AUDIO_THREAD_FLUSHES.with(Cell::get), 1);
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 0);
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    assert_eq!(params.get_value(&mut handle, GAIN), Some(0.5));
+
+    // Flushing with no input events is still valid, and lets the plugin report its own changes.
+    instance.call_on_main_thread_callback_unchecked();
+    events.reset();
+    let (input, mut output) = events.split();
+    assert!(processor.flush_active_params(&input, &mut output));
+    assert_eq!(AUDIO_THREAD_FLUSHES.with(Cell::get), 2);
+
+    cache.update_from_events(&events.output_buffer().as_input());
+    assert_eq!(cache.value(GAIN), Some(0.25));
+
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    assert_eq!(params.get_value(&mut handle, GAIN), Some(0.25));
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 0);
+}
+
+#[test]
+pub fn inactive_values_are_set_in_a_single_flush() {
+    let (mut instance, mut cache) = instantiate();
+    let mut events = EventIo::new();
+
+    let report = set_inactive_values(
+        &mut instance,
+        &mut cache,
+        &[(GAIN, 0.5), (LEVEL, 0.5), (ClapId::new(42), 0.5)],
+        &mut events,
+    )
+    .unwrap();
+
+    // Read-only and unknown parameters are never sent.
+    assert_eq!(report.rejected(), [LEVEL, ClapId::new(42)]);
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 1);
+    assert_eq!(cache.value(GAIN), Some(0.5));
+    assert_eq!(cache.value(LEVEL), Some(0.0));
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    assert_eq!(params.get_value(&mut handle, LEVEL), Some(0.0));
+
+    // The plugin clamps out-of-range values, which is read back as a rejection.
+    let report =
+        set_inactive_values(&mut instance, &mut cache, &[(GAIN, 2.0)], &mut events).unwrap();
+
+    assert_eq!(report.rejected(), [GAIN]);
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 2);
+
+    let report =
+        set_inactive_values(&mut instance, &mut cache, &[(GAIN, 0.75)], &mut events).unwrap();
+
+    assert!(report.all_accepted());
+    assert_eq!(cache.value(GAIN), Some(0.75));
+}
+
+#[test]
+pub fn inactive_values_are_not_set_on_active_plugins() {
+    let (mut instance, mut cache) = instantiate();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap();
+
+    let error = set_inactive_values(
+        &mut instance,
+        &mut cache,
+        &[(GAIN, 0.5)],
+        &mut EventIo::new(),
+    )
+    .unwrap_err();
+
+    assert_eq!(error, PluginInstanceError::AlreadyActivatedPlugin);
+    assert_eq!(MAIN_THREAD_FLUSHES.with(Cell::get), 0);
+
+    instance.deactivate_unchecked(processor);
+}
@@ -0,0 +1,308 @@
+use clack_extensions::params::set::{ParamDef, ParamSet};
+use clack_extensions::params::snapshot::*;
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const CUTOFF: ClapId = ClapId::new(0);
+const MODE: ClapId = ClapId::new(1);
+const VOICES: ClapId = ClapId::new(2);
+const METER: ClapId = ClapId::new(3);
+const BYPASS: ClapId = ClapId::new(4);
+
+/// A plugin with a bit of every kind of parameter.
+pub struct MorphPlugin;
+
+pub struct MorphPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for MorphPluginShared {}
+
+pub struct MorphPluginMainThread<'a> {
+    shared: &'a MorphPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, MorphPluginShared> for MorphPluginMainThread<'a> {}
+
+impl PluginMainThreadParams for MorphPluginMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.shared.params.get_info(param_index, info)
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input)
+    }
+}
+
+pub struct MorphPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, MorphPluginShared, MorphPluginMainThread<'a>>
+    for MorphPluginAudioProcessor
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut MorphPluginMainThread<'a>,
+        _shared: &'a MorphPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for MorphPluginAudioProcessor {
+    fn flush(&mut self, _input: &InputEvents, _output: &mut OutputEvents) {}
+}
+
+impl Plugin for MorphPlugin {
+    type AudioProcessor<'a> = MorphPluginAudioProcessor;
+    type Shared<'a> = MorphPluginShared;
+    type MainThread<'a> = MorphPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&MorphPluginShared>,
+    ) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for MorphPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.morph", "Morph")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        let stepped = ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_STEPPED;
+
+        Ok(MorphPluginShared {
+            params: ParamSet::new()
+                .with_param(CUTOFF, ParamDef::new("Cutoff", 20.0, 20_000.0, 1_000.0))
+                .with_param(MODE, ParamDef::enumeration("Mode", &["LP", "HP", "BP"]))
+                .with_param(
+                    VOICES,
+                    ParamDef::new("Voices", 1.0, 8.0, 4.0).with_flags(stepped),
+                )
+                .with_param(
+                    METER,
+                    ParamDef::new("Meter", 0.0, 1.0, 0.0).with_flags(ParamInfoFlags::IS_READONLY),
+                )
+                .with_bypass_param(BYPASS),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(MorphPluginMainThread { shared })
+    }
+}
+
+pub static MORPH_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<MorphPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+fn instantiate() -> (PluginInstance<MyHost>, ParamValueCache) {
+    let bundle = unsafe { PluginBundle::load_from_raw(&MORPH_ENTRY, "/morph.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.morph\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let mut cache = ParamValueCache::new();
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    cache.rescan(&params, &mut handle);
+
+    (instance, cache)
+}
+
+/// Applies the given snapshot to the (inactive) plugin, and returns the number of events sent.
+fn apply(
+    instance: &mut PluginInstance<MyHost>,
+    cache: &mut ParamValueCache,
+    snapshot: &ParamSnapshot,
+) -> usize {
+    let mut events = EventBuffer::new();
+    let pushed = snapshot.apply(cache, &mut events.as_output(), 0).unwrap();
+    assert_eq!(events.len(), pushed);
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    params.flush(&mut handle, &events.as_input(), &mut OutputEvents::void());
+
+    pushed
+}
+
+fn assert_plugin_matches(instance: &mut PluginInstance<MyHost>, snapshot: &ParamSnapshot) {
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    for param in snapshot.params() {
+        assert_eq!(
+            params.get_value(&mut handle, param.id()),
+            Some(param.value())
+        );
+    }
+}
+
+/// A small, deterministic pseudo-random number generator.
+fn rng() -> impl FnMut() -> f64 {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+
+    move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[test]
+pub fn snapshots_skip_readonly_and_bypass_params() {
+    let (_instance, cache) = instantiate();
+    let snapshot = ParamSnapshot::capture(&cache);
+
+    let ids: Vec<_> = snapshot.params().iter().map(|p| p.id()).collect();
+    assert_eq!(ids, [CUTOFF, MODE, VOICES]);
+    assert_eq!(snapshot.value(CUTOFF), Some(1_000.0));
+    assert_eq!(snapshot.value(METER), None);
+}
+
+#[test]
+pub fn randomized_snapshots_are_applied() {
+    let (mut instance, mut cache) = instantiate();
+    let original = ParamSnapshot::capture(&cache);
+
+    let random = original.randomize(rng(), 1.0, |_| true);
+    assert_ne!(random, original);
+
+    for param in random.params() {
+        assert!(param.value() >= param.min_value() && param.value() <= param.max_value());
+        if param.is_stepped() {
+            assert_eq!(param.value().fract(), 0.0);
+        }
+    }
+
+    let changed = random
+        .params()
+        .iter()
+        .filter(|p| original.value(p.id()) != Some(p.value()))
+        .count();
+    assert_eq!(apply(&mut instance, &mut cache, &random), changed);
+    assert_plugin_matches(&mut instance, &random);
+
+    // The cache was updated: applying the same snapshot again is a no-op.
+    assert_eq!(apply(&mut instance, &mut cache, &random), 0);
+    assert_eq!(ParamSnapshot::capture(&cache), random);
+}
+
+#[test]
+pub fn randomization_can_be_filtered_and_scaled() {
+    let (_instance, cache) = instantiate();
+    let original = ParamSnapshot::capture(&cache);
+
+    let continuous_only = original.randomize(rng(), 1.0, |p| !p.is_stepped());
+    assert_ne!(continuous_only.value(CUTOFF), original.value(CUTOFF));
+    assert_eq!(continuous_only.value(MODE), original.value(MODE));
+    assert_eq!(continuous_only.value(VOICES), original.value(VOICES));
+
+    assert_eq!(original.randomize(rng(), 0.0, |_| true), original);
+
+    let full = original
+        .randomize(rng(), 1.0, |_| true)
+        .value(CUTOFF)
+        .unwrap();
+    let half = original
+        .randomize(rng(), 0.5, |_| true)
+        .value(CUTOFF)
+        .unwrap();
+    assert_eq!(half, (full + 1_000.0) / 2.0);
+}
+
+#[test]
+pub fn morphing_switches_stepped_params_halfway() {
+    let (mut instance, mut cache) = instantiate();
+
+    let a = ParamSnapshot::capture(&cache);
+    let mut b = a.clone();
+    assert!(b.set_value(CUTOFF, 3_000.0));
+    assert!(b.set_value(MODE, 2.0));
+    assert!(b.set_value(VOICES, 7.6));
+    assert_eq!(b.value(VOICES), Some(8.0));
+    assert!(!b.set_value(METER, 1.0));
+
+    assert_eq!(ParamSnapshot::interpolate(&a, &b, 0.0), a);
+    assert_eq!(ParamSnapshot::interpolate(&a, &b, 1.0), b);
+
+    let before = ParamSnapshot::interpolate(&a, &b, 0.25);
+    assert_eq!(before.value(CUTOFF), Some(1_500.0));
+    assert_eq!(before.value(MODE), Some(0.0));
+    assert_eq!(before.value(VOICES), Some(4.0));
+
+    let after = ParamSnapshot::interpolate(&a, &b, 0.5);
+    assert_eq!(after.value(CUTOFF), Some(2_000.0));
+    assert_eq!(after.value(MODE), Some(2.0));
+    assert_eq!(after.value(VOICES), Some(8.0));
+
+    // Only the cutoff changes before the halfway point.
+    assert_eq!(apply(&mut instance, &mut cache, &before), 1);
+    assert_plugin_matches(&mut instance, &before);
+
+    assert_eq!(apply(&mut instance, &mut cache, &after), 3);
+    assert_plugin_matches(&mut instance, &after);
+}
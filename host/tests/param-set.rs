@@ -0,0 +1,163 @@
+use clack_extensions::params::set::{ParamDef, ParamSet, ParamSetProvider};
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const GAIN: ClapId = ClapId::new(0);
+const FILTER_TYPE: ClapId = ClapId::new(1);
+
+pub struct ParamSetPlugin;
+
+pub struct ParamSetPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for ParamSetPluginShared {}
+
+pub struct ParamSetPluginMainThread<'a> {
+    shared: &'a ParamSetPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, ParamSetPluginShared> for ParamSetPluginMainThread<'a> {}
+
+// The whole params extension is implemented by the parameter set.
+impl ParamSetProvider for ParamSetPluginMainThread<'_> {
+    fn param_set(&self) -> &ParamSet {
+        &self.shared.params
+    }
+}
+
+pub struct ParamSetPluginAudioProcessor;
+
+impl<'a> PluginAudioProcessor<'a, ParamSetPluginShared, ParamSetPluginMainThread<'a>>
+    for ParamSetPluginAudioProcessor
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut ParamSetPluginMainThread<'a>,
+        _shared: &'a ParamSetPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for ParamSetPluginAudioProcessor {}
+
+impl Plugin for ParamSetPlugin {
+    type AudioProcessor<'a> = ParamSetPluginAudioProcessor;
+    type Shared<'a> = ParamSetPluginShared;
+    type MainThread<'a> = ParamSetPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for ParamSetPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.param-set", "Param Set")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(ParamSetPluginShared {
+            params: ParamSet::new()
+                .with_param(GAIN, ParamDef::new("Gain", 0.0, 1.0, 0.5))
+                .with_param(
+                    FILTER_TYPE,
+                    ParamDef::enumeration("Filter Type", &["LP", "HP", "BP"]),
+                ),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(ParamSetPluginMainThread { shared })
+    }
+}
+
+pub static PARAM_SET_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<ParamSetPlugin>);
+
+fn instantiate() -> PluginInstance<()> {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&PARAM_SET_ENTRY, "/param-set.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.param-set\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn enum_labels_are_read_from_plugin() {
+    let mut instance = instantiate();
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    assert_eq!(params.count(&mut handle), 2);
+
+    let mut buffer = ParamInfoBuffer::new();
+    let gain_info = params.get_info(&mut handle, 0, &mut buffer).unwrap();
+    assert!(!gain_info.is_enum());
+    assert_eq!(gain_info.enum_labels(&params, &mut handle), None);
+
+    let mut buffer = ParamInfoBuffer::new();
+    let filter_info = params.get_info(&mut handle, 1, &mut buffer).unwrap();
+    assert_eq!(filter_info.id, FILTER_TYPE);
+    assert!(filter_info.is_enum() && filter_info.is_stepped());
+    assert!(filter_info.validate().is_empty());
+
+    let mut cache = EnumLabelCache::new();
+    assert_eq!(
+        cache.get(&filter_info, &params, &mut handle),
+        Some(&["LP".to_string(), "HP".to_string(), "BP".to_string()][..])
+    );
+}
+
+#[test]
+pub fn enum_text_to_value_accepts_labels_and_numbers() {
+    let mut instance = instantiate();
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+
+    let text_to_value = |handle: &mut PluginMainThreadHandle, text: &[u8]| {
+        let text = CStr::from_bytes_with_nul(text).unwrap();
+        params.text_to_value(handle, FILTER_TYPE, text)
+    };
+
+    assert_eq!(text_to_value(&mut handle, b"BP\0"), Some(2.0));
+    assert_eq!(text_to_value(&mut handle, b"1\0"), Some(1.0));
+    assert_eq!(text_to_value(&mut handle, b"Notch\0"), None);
+    assert_eq!(text_to_value(&mut handle, b"5\0"), None);
+
+    let mut buffer = [core::mem::MaybeUninit::uninit(); 32];
+    let text = params
+        .value_to_text(&mut handle, FILTER_TYPE, 1.0, &mut buffer)
+        .unwrap();
+    assert_eq!(text, b"HP");
+
+    assert_eq!(params.get_value(&mut handle, FILTER_TYPE), Some(0.0));
+    assert_eq!(params.get_value(&mut handle, GAIN), Some(0.5));
+}
@@ -0,0 +1,138 @@
+use clack_host::bundle::index::{PluginIndex, ResolutionReason, SessionResolution};
+use clack_host::factory::PluginCompatibilityFactory;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::path::Path;
+
+macro_rules! compatible_plugin {
+    ($name:ident, $entry:ident, $id:literal, [$($compatible:literal),*]) => {
+        pub struct $name;
+
+        impl Plugin for $name {
+            type AudioProcessor<'a> = ();
+            type Shared<'a> = ();
+            type MainThread<'a> = ();
+        }
+
+        impl DefaultPluginFactory for $name {
+            fn get_descriptor() -> PluginDescriptor {
+                PluginDescriptor::new($id, "Compatible")
+                    .with_version("2.0.0")
+                    .with_compatible_ids([$($compatible),*] as [&str; _])
+            }
+
+            fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+                Ok(())
+            }
+
+            fn new_main_thread<'a>(
+                _host: HostMainThreadHandle<'a>,
+                _shared: &'a Self::Shared<'a>,
+            ) -> Result<Self::MainThread<'a>, PluginError> {
+                Ok(())
+            }
+        }
+
+        pub static $entry: EntryDescriptor = clack_entry!(SinglePluginEntry<$name>);
+    };
+}
+
+compatible_plugin!(
+    ReverbPlugin,
+    REVERB_ENTRY,
+    "org.rust-audio.clack.reverb",
+    [
+        "org.rust-audio.clack.old-reverb",
+        "org.rust-audio.clack.older-reverb"
+    ]
+);
+compatible_plugin!(DelayPlugin, DELAY_ENTRY, "org.rust-audio.clack.delay", []);
+
+const REVERB: &str = "org.rust-audio.clack.reverb";
+const OLD_REVERB: &str = "org.rust-audio.clack.old-reverb";
+const DELAY: &str = "org.rust-audio.clack.delay";
+
+fn load(entry: &'static EntryDescriptor, path: &str) -> PluginBundle {
+    unsafe { PluginBundle::load_from_raw(entry, path).unwrap() }
+}
+
+fn index() -> PluginIndex {
+    let mut index = PluginIndex::new();
+    index.add_bundle_with_modified_time("/reverb.clap", None, &load(&REVERB_ENTRY, "/reverb.clap"));
+    index.add_bundle_with_modified_time("/delay.clap", None, &load(&DELAY_ENTRY, "/delay.clap"));
+
+    index
+}
+
+#[test]
+fn exposes_compatible_ids_through_factory() {
+    let reverb = load(&REVERB_ENTRY, "/reverb.clap");
+    let factory = reverb.get_factory::<PluginCompatibilityFactory>().unwrap();
+
+    let ids: Vec<_> = factory
+        .compatible_ids(CStr::from_bytes_with_nul(b"org.rust-audio.clack.reverb\0").unwrap())
+        .map(|id| id.to_str().unwrap())
+        .collect();
+    assert_eq!(ids, [OLD_REVERB, "org.rust-audio.clack.older-reverb"]);
+
+    let unknown = CStr::from_bytes_with_nul(b"org.rust-audio.clack.unknown\0").unwrap();
+    assert_eq!(factory.compatible_ids(unknown).count(), 0);
+
+    // Plugins without compatible IDs don't expose the factory at all.
+    let delay = load(&DELAY_ENTRY, "/delay.clap");
+    assert!(delay.get_factory::<PluginCompatibilityFactory>().is_none());
+}
+
+#[test]
+fn indexes_compatible_ids() {
+    let index = index();
+
+    assert_eq!(
+        index.get(REVERB)[0].compatible_ids,
+        [OLD_REVERB, "org.rust-audio.clack.older-reverb"]
+    );
+    assert!(index.get(DELAY)[0].compatible_ids.is_empty());
+}
+
+#[test]
+fn resolves_installed_plugins_directly() {
+    let index = index();
+
+    let resolution = index.resolve_for_session(REVERB).unwrap();
+    assert!(!resolution.is_substitute());
+    assert!(matches!(resolution, SessionResolution::Direct(_)));
+    assert_eq!(
+        resolution.resolution().plugin.bundle_path,
+        Path::new("/reverb.clap")
+    );
+}
+
+#[test]
+fn resolves_missing_plugins_to_compatible_substitutes() {
+    let index = index();
+
+    let resolution = index.resolve_for_session(OLD_REVERB).unwrap();
+    assert!(resolution.is_substitute());
+
+    let SessionResolution::Compatible {
+        original,
+        substitute,
+        resolution,
+    } = resolution
+    else {
+        panic!("Expected a compatible resolution");
+    };
+
+    assert_eq!(original, OLD_REVERB);
+    assert_eq!(substitute, REVERB);
+    assert_eq!(resolution.plugin.bundle_path, Path::new("/reverb.clap"));
+    assert_eq!(resolution.reason, ResolutionReason::Unique);
+
+    // The plain resolution doesn't consider compatible plugins.
+    assert!(index.resolve(OLD_REVERB).is_none());
+    assert!(index
+        .resolve_for_session("org.rust-audio.clack.missing")
+        .is_none());
+}
@@ -0,0 +1,127 @@
+use clack_host::bundle::index::{PluginIndex, ResolutionPreference, ResolutionReason};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+macro_rules! versioned_plugin {
+    ($name:ident, $entry:ident, $version:literal) => {
+        pub struct $name;
+
+        impl Plugin for $name {
+            type AudioProcessor<'a> = ();
+            type Shared<'a> = ();
+            type MainThread<'a> = ();
+        }
+
+        impl DefaultPluginFactory for $name {
+            fn get_descriptor() -> PluginDescriptor {
+                PluginDescriptor::new("org.rust-audio.clack.versioned", "Versioned")
+                    .with_vendor("Clack")
+                    .with_version($version)
+            }
+
+            fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+                Ok(())
+            }
+
+            fn new_main_thread<'a>(
+                _host: HostMainThreadHandle<'a>,
+                _shared: &'a Self::Shared<'a>,
+            ) -> Result<Self::MainThread<'a>, PluginError> {
+                Ok(())
+            }
+        }
+
+        pub static $entry: EntryDescriptor = clack_entry!(SinglePluginEntry<$name>);
+    };
+}
+
+versioned_plugin!(OldPlugin, OLD_ENTRY, "1.2.3");
+versioned_plugin!(NewPlugin, NEW_ENTRY, "1.10.0");
+
+const ID: &str = "org.rust-audio.clack.versioned";
+
+fn index_with(preference: ResolutionPreference) -> PluginIndex {
+    let old = unsafe { PluginBundle::load_from_raw(&OLD_ENTRY, "/old.clap").unwrap() };
+    let new = unsafe { PluginBundle::load_from_raw(&NEW_ENTRY, "/new.clap").unwrap() };
+
+    let mut index = PluginIndex::new().with_preference(preference);
+
+    // The older version was installed more recently.
+    let earlier = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+    let later = earlier + Duration::from_secs(60);
+    assert_eq!(
+        index.add_bundle_with_modified_time("/old.clap", Some(later), &old),
+        1
+    );
+    assert_eq!(
+        index.add_bundle_with_modified_time("/new.clap", Some(earlier), &new),
+        1
+    );
+
+    index
+}
+
+#[test]
+fn resolves_highest_version_by_default() {
+    let index = index_with(ResolutionPreference::default());
+    assert_eq!(index.len(), 1);
+    assert_eq!(index.get(ID).len(), 2);
+
+    let resolution = index.resolve(ID).unwrap();
+    assert_eq!(resolution.plugin.bundle_path, Path::new("/new.clap"));
+    assert_eq!(resolution.plugin.version.as_deref(), Some("1.10.0"));
+    assert_eq!(resolution.plugin.vendor.as_deref(), Some("Clack"));
+    assert_eq!(
+        resolution.reason,
+        ResolutionReason::Preference(ResolutionPreference::HighestVersion)
+    );
+    assert!(resolution.is_conflict());
+    assert_eq!(resolution.alternatives.len(), 1);
+    assert_eq!(
+        resolution.alternatives[0].bundle_path,
+        Path::new("/old.clap")
+    );
+
+    let conflicts: Vec<_> = index.conflicts().collect();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].0, ID);
+
+    assert!(index.resolve("org.rust-audio.clack.missing").is_none());
+}
+
+#[test]
+fn resolves_newest_file() {
+    let index = index_with(ResolutionPreference::NewestFile);
+
+    let resolution = index.resolve(ID).unwrap();
+    assert_eq!(resolution.plugin.bundle_path, Path::new("/old.clap"));
+    assert_eq!(
+        resolution.reason,
+        ResolutionReason::Preference(ResolutionPreference::NewestFile)
+    );
+}
+
+#[test]
+fn pins_take_precedence() {
+    let mut index = index_with(ResolutionPreference::HighestVersion);
+    index.pin(ID, "/old.clap");
+
+    let resolution = index.resolve(ID).unwrap();
+    assert_eq!(resolution.plugin.bundle_path, Path::new("/old.clap"));
+    assert_eq!(resolution.reason, ResolutionReason::Pinned);
+
+    // Pins to bundles the plugin wasn't found in are ignored.
+    index.pin(ID, "/elsewhere.clap");
+    let resolution = index.resolve(ID).unwrap();
+    assert_eq!(resolution.plugin.bundle_path, Path::new("/new.clap"));
+
+    index.remove_bundle("/new.clap");
+    let resolution = index.resolve(ID).unwrap();
+    assert_eq!(resolution.plugin.bundle_path, Path::new("/old.clap"));
+    assert_eq!(resolution.reason, ResolutionReason::Unique);
+    assert!(!resolution.is_conflict());
+    assert_eq!(index.conflicts().count(), 0);
+}
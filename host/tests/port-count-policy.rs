@@ -0,0 +1,339 @@
+//! Drives plugins denying and working around port count mismatches through hand-built raw `clap_process` structs
+//! that provide a different number of audio ports than the plugins declared.
+
+use clack_extensions::audio_ports::{
+    AudioPortInfo, AudioPortInfoWriter, PluginAudioPorts, PluginAudioPortsImpl,
+};
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::audio_buffer::clap_audio_buffer;
+use clap_sys::process::{clap_process, CLAP_PROCESS_CONTINUE, CLAP_PROCESS_ERROR};
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ptr::null;
+use std::sync::{Arc, Mutex};
+
+/// What the plugin observed of a single process call.
+#[derive(Debug, PartialEq)]
+struct SeenAudio {
+    input_count: usize,
+    output_count: usize,
+    undeclared_inputs: Vec<usize>,
+    undeclared_outputs: Vec<usize>,
+}
+
+thread_local! {
+    /// The audio seen by the plugin in each process call, on this thread.
+    static SEEN: RefCell<Vec<SeenAudio>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A plugin declaring a single input and a single output port.
+pub struct PortsPlugin<const STRICT: bool>;
+
+pub struct PortsPluginMainThread;
+
+pub struct PortsPluginAudioProcessor;
+
+impl<const STRICT: bool> Plugin for PortsPlugin<STRICT> {
+    type AudioProcessor<'a> = PortsPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = PortsPluginMainThread;
+
+    const COMPATIBILITY: CompatibilityPolicy = CompatibilityPolicy::COMPATIBLE.with(
+        CompatibilityCheck::PortCountMismatch,
+        if STRICT {
+            CheckLevel::Deny
+        } else {
+            CheckLevel::Warn
+        },
+    );
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginAudioPorts>();
+    }
+}
+
+impl<const STRICT: bool> DefaultPluginFactory for PortsPlugin<STRICT> {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.ports", "Ports")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(PortsPluginMainThread)
+    }
+}
+
+impl PluginMainThread<'_, ()> for PortsPluginMainThread {}
+
+impl PluginAudioPortsImpl for PortsPluginMainThread {
+    fn count(&mut self, _is_input: bool) -> u32 {
+        1
+    }
+
+    fn get(&mut self, index: u32, _is_input: bool, writer: &mut AudioPortInfoWriter) {
+        if index == 0 {
+            writer.set(&AudioPortInfo::stereo(ClapId::new(0), b"main"));
+        }
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), PortsPluginMainThread> for PortsPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut PortsPluginMainThread,
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let seen = SeenAudio {
+            input_count: audio.input_ports().count(),
+            output_count: audio.output_port_count(),
+            undeclared_inputs: audio.undeclared_ports().inputs().collect(),
+            undeclared_outputs: audio.undeclared_ports().outputs().collect(),
+        };
+
+        SEEN.with(|s| s.borrow_mut().push(seen));
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static STRICT_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<PortsPlugin<true>>);
+pub static PERMISSIVE_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<PortsPlugin<false>>);
+
+type LogRecords = Arc<Mutex<Vec<(LogSeverity, String)>>>;
+
+struct LoggingHost;
+
+struct LoggingHostShared {
+    records: LogRecords,
+}
+
+impl SharedHandler<'_> for LoggingHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostLogImpl for LoggingHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((severity, message.to_string()));
+    }
+}
+
+impl HostHandlers for LoggingHost {
+    type Shared<'a> = LoggingHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostLog>();
+    }
+}
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 4,
+};
+
+const EMPTY_BUFFER: clap_audio_buffer = clap_audio_buffer {
+    data32: null(),
+    data64: null(),
+    channel_count: 0,
+    latency: 0,
+    constant_mask: 0,
+};
+
+/// Process calls to make in a single activation, as (input port count, output port count).
+type Blocks<'a> = &'a [(usize, usize)];
+
+/// Instantiates the given plugin, then for each activation, calls `process` on it with every given
+/// number of (empty) input and output ports.
+///
+/// Returns the raw process statuses, the audio the plugin saw, and the logged messages.
+fn process_raw(
+    entry: &'static EntryDescriptor,
+    activations: &[Blocks],
+) -> (Vec<i32>, Vec<SeenAudio>, Vec<(LogSeverity, String)>) {
+    let bundle = unsafe { PluginBundle::load_from_raw(entry, "/ports.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let records = LogRecords::default();
+    let shared_records = records.clone();
+
+    let mut instance = PluginInstance::<LoggingHost>::new(
+        move |_| LoggingHostShared {
+            records: shared_records,
+        },
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.ports\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    SEEN.with(|s| s.borrow_mut().clear());
+    let mut statuses = Vec::new();
+
+    for blocks in activations {
+        let mut processor = instance
+            .activate_unchecked(|_, _| (), CONFIGURATION)
+            .unwrap()
+            .start_processing()
+            .unwrap();
+
+        for &(input_count, output_count) in *blocks {
+            let inputs = vec![EMPTY_BUFFER; input_count];
+            let mut outputs = vec![EMPTY_BUFFER; output_count];
+            let input_events = InputEvents::empty();
+            let mut output_events = OutputEvents::void();
+
+            let process = clap_process {
+                steady_time: -1,
+                frames_count: 4,
+                transport: null(),
+                audio_inputs: inputs.as_ptr(),
+                audio_outputs: outputs.as_mut_ptr(),
+                audio_inputs_count: input_count as u32,
+                audio_outputs_count: output_count as u32,
+                in_events: input_events.as_raw(),
+                out_events: output_events.as_raw_mut(),
+            };
+
+            let plugin = processor.plugin_handle().as_raw();
+            statuses.push(unsafe { (plugin.process.unwrap())(plugin, &process) });
+        }
+
+        instance.deactivate_unchecked(processor.stop_processing());
+    }
+
+    let seen = SEEN.with(|s| s.borrow_mut().drain(..).collect());
+    let records = records.lock().unwrap().clone();
+    (statuses, seen, records)
+}
+
+fn mismatch_log(inputs: usize, outputs: usize, action: &str) -> (LogSeverity, String) {
+    (
+        LogSeverity::HostMisbehaving,
+        format!(
+            "[org.rust-audio.clack.ports] [clap_plugin.process] \
+            Host provided {inputs} input and {outputs} output audio ports, \
+            but the plugin declared 1 input and 1 output ports \
+            (port-count-mismatch, {action})"
+        ),
+    )
+}
+
+#[test]
+pub fn matching_port_counts_are_accepted() {
+    let (statuses, seen, logs) = process_raw(&STRICT_ENTRY, &[&[(1, 1), (1, 1)]]);
+
+    assert_eq!(statuses, [CLAP_PROCESS_CONTINUE; 2]);
+    assert_eq!(seen.len(), 2);
+    assert_eq!(
+        seen[0],
+        SeenAudio {
+            input_count: 1,
+            output_count: 1,
+            undeclared_inputs: vec![],
+            undeclared_outputs: vec![],
+        }
+    );
+    assert!(logs.is_empty());
+}
+
+#[test]
+pub fn strict_plugins_reject_mismatched_port_counts() {
+    let (statuses, seen, logs) = process_raw(&STRICT_ENTRY, &[&[(2, 1), (0, 1), (1, 1)]]);
+
+    assert_eq!(
+        statuses,
+        [
+            CLAP_PROCESS_ERROR,
+            CLAP_PROCESS_ERROR,
+            CLAP_PROCESS_CONTINUE
+        ]
+    );
+    // Only the matching block reached the audio processor.
+    assert_eq!(seen.len(), 1);
+    // Only the first mismatch is reported.
+    assert_eq!(logs, [mismatch_log(2, 1, "denied")]);
+}
+
+#[test]
+pub fn permissive_plugins_flag_undeclared_ports() {
+    let (statuses, seen, logs) = process_raw(&PERMISSIVE_ENTRY, &[&[(3, 1), (1, 2)]]);
+
+    assert_eq!(statuses, [CLAP_PROCESS_CONTINUE; 2]);
+    assert_eq!(
+        seen,
+        [
+            SeenAudio {
+                input_count: 3,
+                output_count: 1,
+                undeclared_inputs: (1..3).collect(),
+                undeclared_outputs: vec![],
+            },
+            SeenAudio {
+                input_count: 1,
+                output_count: 2,
+                undeclared_inputs: vec![],
+                undeclared_outputs: (1..2).collect(),
+            }
+        ]
+    );
+
+    if cfg!(debug_assertions) {
+        assert_eq!(logs, [mismatch_log(3, 1, "worked around")]);
+    } else {
+        assert!(logs.is_empty());
+    }
+}
+
+#[test]
+pub fn permissive_plugins_cope_with_missing_ports() {
+    let (statuses, seen, _) = process_raw(&PERMISSIVE_ENTRY, &[&[(0, 0)]]);
+
+    assert_eq!(statuses, [CLAP_PROCESS_CONTINUE]);
+    assert_eq!(
+        seen,
+        [SeenAudio {
+            input_count: 0,
+            output_count: 0,
+            undeclared_inputs: vec![],
+            undeclared_outputs: vec![],
+        }]
+    );
+}
+
+#[test]
+pub fn mismatches_are_reported_once_per_activation() {
+    let (statuses, _, logs) = process_raw(&STRICT_ENTRY, &[&[(2, 2), (2, 2)], &[(0, 0), (0, 0)]]);
+
+    assert_eq!(statuses, [CLAP_PROCESS_ERROR; 4]);
+    assert_eq!(
+        logs,
+        [mismatch_log(2, 2, "denied"), mismatch_log(0, 0, "denied")]
+    );
+}
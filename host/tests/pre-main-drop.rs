@@ -0,0 +1,62 @@
+//! Uninitialized plugin instances can be sent across threads, but must still be destroyed on the
+//! main thread.
+//!
+//! This lives in its own test binary, as it records the test's thread as the process' main thread.
+
+use clack_host::plugin::parallel::PluginInstancePreMain;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+pub struct NoopPlugin;
+
+impl Plugin for NoopPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for NoopPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.noop", "Noop")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub static NOOP_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<NoopPlugin>);
+
+fn create(bundle: &PluginBundle) -> PluginInstancePreMain<()> {
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+    let id = CStr::from_bytes_with_nul(b"org.rust-audio.clack.noop\0").unwrap();
+
+    PluginInstancePreMain::<()>::create(|_| (), |_| (), bundle, id, &host_info).unwrap()
+}
+
+#[test]
+pub fn dropping_off_the_main_thread_is_refused() {
+    let _main_thread = MainThreadToken::acquire().unwrap();
+    let bundle = unsafe { PluginBundle::load_from_raw(&NOOP_ENTRY, "/noop.clap").unwrap() };
+
+    // Dropping on the main thread destroys the instance as usual.
+    drop(create(&bundle));
+
+    let dropped_elsewhere = std::thread::scope(|s| s.spawn(|| drop(create(&bundle))).join());
+
+    if cfg!(debug_assertions) {
+        assert!(dropped_elsewhere.is_err());
+    } else {
+        assert!(dropped_elsewhere.is_ok());
+    }
+}
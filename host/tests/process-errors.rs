@@ -0,0 +1,345 @@
+use clack_host::prelude::*;
+use clack_host::process::error_policy::*;
+use clack_host::process::wet_dry::PluginWetDryWrapper;
+use clack_host::process::StartedPluginAudioProcessor;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+const BLOCK_SIZE: usize = 32;
+
+thread_local! {
+    /// For each block, whether the plugin fails to process it. Blocks past the end succeed.
+    static SCRIPT: Cell<&'static [bool]> = const { Cell::new(&[]) };
+}
+
+/// A plugin that halves its input, but fails to process some blocks, as scripted by [`SCRIPT`].
+pub struct FlakyPlugin;
+
+pub struct FlakyPluginAudioProcessor {
+    block: usize,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for FlakyPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { block: 0 })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let block = self.block;
+        self.block += 1;
+
+        if SCRIPT.with(|s| s.get().get(block).copied().unwrap_or(false)) {
+            return Err(PluginError::Message("Scripted failure"));
+        }
+
+        for mut port_pair in &mut audio {
+            let Some(mut channels) = port_pair.channels()?.into_f32() else {
+                continue;
+            };
+
+            for pair in channels.iter_mut() {
+                if let ChannelPair::InputOutput(input, output) = pair {
+                    for (input, output) in input.iter().zip(output.iter_mut()) {
+                        *output = *input * 0.5;
+                    }
+                }
+            }
+        }
+
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    }
+}
+
+impl Plugin for FlakyPlugin {
+    type AudioProcessor<'a> = FlakyPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for FlakyPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.flaky", "Flaky")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub static FLAKY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<FlakyPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+#[derive(Default)]
+struct MyHostShared {
+    suspensions: AtomicU32,
+    last_error_count: AtomicU32,
+}
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostProcessErrorHandler for MyHostShared {
+    fn plugin_suspended(&self, consecutive_errors: u32) {
+        self.suspensions.fetch_add(1, Ordering::SeqCst);
+        self.last_error_count
+            .store(consecutive_errors, Ordering::SeqCst);
+    }
+}
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: BLOCK_SIZE as u32,
+    max_frames_count: BLOCK_SIZE as u32,
+};
+
+fn start(script: &'static [bool]) -> (PluginInstance<MyHost>, StartedPluginAudioProcessor<MyHost>) {
+    SCRIPT.with(|s| s.set(script));
+
+    let bundle = unsafe { PluginBundle::load_from_raw(&FLAKY_ENTRY, "/flaky.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared::default(),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.flaky\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    (instance, processor)
+}
+
+fn process_block(
+    processor: &mut StartedPluginAudioProcessor<MyHost>,
+) -> Result<ProcessStatus, PluginInstanceError> {
+    processor.process_events(
+        BLOCK_SIZE as u32,
+        &InputEvents::empty(),
+        &mut OutputEvents::void(),
+        None,
+        None,
+    )
+}
+
+#[test]
+pub fn processor_stays_usable_after_errors() {
+    let (mut instance, mut processor) = start(&[false, true, true, false]);
+
+    assert_eq!(
+        process_block(&mut processor),
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    );
+    assert_eq!(
+        process_block(&mut processor),
+        Err(PluginInstanceError::ProcessingFailed)
+    );
+    assert_eq!(
+        process_block(&mut processor),
+        Err(PluginInstanceError::ProcessingFailed)
+    );
+    assert_eq!(
+        process_block(&mut processor),
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    );
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
+
+#[test]
+pub fn policy_suspends_after_consecutive_errors() {
+    let (mut instance, mut processor) = start(&[true, false, true, true, true, true]);
+    let mut policy = ProcessErrorPolicy::new().suspend_after(3);
+
+    let mut actions = Vec::new();
+    for _ in 0..5 {
+        let result = process_block(&mut processor);
+        actions.push(policy.record_and_notify(&processor, &result));
+    }
+
+    use ProcessErrorAction::*;
+    assert_eq!(actions, [Retry, Continue, Retry, Retry, Suspend]);
+    assert!(policy.is_suspended());
+    assert_eq!(policy.consecutive_errors(), 3);
+    assert_eq!(policy.total_errors(), 4);
+
+    // Further results are ignored while suspended, and the host was only notified once.
+    let result = process_block(&mut processor);
+    assert_eq!(policy.record_and_notify(&processor, &result), Suspend);
+    processor.access_shared_handler(|s| {
+        assert_eq!(s.suspensions.load(Ordering::SeqCst), 1);
+        assert_eq!(s.last_error_count.load(Ordering::SeqCst), 3);
+    });
+
+    // Once resumed, the plugin is back to normal.
+    policy.resume();
+    assert!(!policy.is_suspended());
+    let result = process_block(&mut processor);
+    assert_eq!(policy.record(&result), Continue);
+    assert_eq!(policy.total_errors(), 4);
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
+
+#[test]
+pub fn wet_dry_wrapper_is_bypassed_while_plugin_fails() {
+    let (mut instance, processor) = start(&[false, true, false]);
+
+    let mut wrapper = PluginWetDryWrapper::new(processor, CONFIGURATION, 2, 0);
+    let mut policy = ProcessErrorPolicy::new()
+        .suspend_after(2)
+        .with_bypass(wrapper.handle());
+    let handle = wrapper.handle();
+
+    let mut run_block = |wrapper: &mut PluginWetDryWrapper<MyHost>| {
+        let mut input = [vec![1.0f32; BLOCK_SIZE], vec![1.0f32; BLOCK_SIZE]];
+        let mut output = [vec![0.0f32; BLOCK_SIZE], vec![0.0f32; BLOCK_SIZE]];
+
+        let result = wrapper.process(
+            &mut input,
+            &mut output,
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        );
+
+        (
+            policy.record_wet_dry(&result),
+            output,
+            policy.is_suspended(),
+        )
+    };
+
+    let (action, output, _) = run_block(&mut wrapper);
+    assert_eq!(action, ProcessErrorAction::Continue);
+    assert!(output[0].iter().all(|s| *s == 0.5));
+    assert!(!handle.is_bypassed());
+
+    // The failed block outputs the dry signal, and the wrapper gets bypassed.
+    let (action, output, _) = run_block(&mut wrapper);
+    assert_eq!(action, ProcessErrorAction::Retry);
+    assert!(output.iter().flatten().all(|s| *s == 1.0));
+    assert!(handle.is_bypassed());
+
+    // The plugin recovers: the bypass is released once that block is processed...
+    let (action, output, suspended) = run_block(&mut wrapper);
+    assert_eq!(action, ProcessErrorAction::Continue);
+    assert!(!suspended);
+    assert!(!handle.is_bypassed());
+    assert!(output.iter().flatten().all(|s| *s == 1.0));
+
+    // ... and the output then ramps back to the wet signal.
+    let (_, output, _) = run_block(&mut wrapper);
+    assert!(output[0][0] < 1.0 && output[0][0] > 0.5);
+    assert!(output[0][BLOCK_SIZE - 1] < output[0][0]);
+
+    instance.deactivate_unchecked(wrapper.into_inner().stop_processing());
+}
+
+#[test]
+pub fn user_bypass_is_left_untouched() {
+    let (mut instance, processor) = start(&[true, false]);
+
+    let wrapper = PluginWetDryWrapper::new(processor, CONFIGURATION, 2, 0);
+    let mut policy = ProcessErrorPolicy::new().with_bypass(wrapper.handle());
+
+    wrapper.set_bypassed(true);
+    policy.record_error(&PluginInstanceError::ProcessingFailed);
+    policy.record_success();
+    assert!(wrapper.handle().is_bypassed());
+
+    // Host-side usage errors aren't counted.
+    assert_eq!(
+        policy.record_error(&PluginInstanceError::ProcessingStopped),
+        ProcessErrorAction::Continue
+    );
+    assert_eq!(policy.total_errors(), 1);
+
+    instance.deactivate_unchecked(wrapper.into_inner().stop_processing());
+}
+
+#[test]
+#[allow(deprecated)]
+pub fn unknown_statuses_can_be_tolerated() {
+    let unknown = PluginInstanceError::InvalidProcessStatus { status: 42 };
+
+    assert_eq!(
+        UnknownStatusHandling::Permissive.apply(Err(unknown)),
+        Ok(ProcessStatus::Continue)
+    );
+    assert_eq!(
+        UnknownStatusHandling::Strict.apply(Err(unknown)),
+        Err(unknown)
+    );
+    assert_eq!(
+        UnknownStatusHandling::Permissive.apply(Err(PluginInstanceError::ProcessingFailed)),
+        Err(PluginInstanceError::ProcessingFailed)
+    );
+
+    let mut strict = ProcessErrorPolicy::new().suspend_after(1);
+    assert_eq!(strict.record_error(&unknown), ProcessErrorAction::Suspend);
+
+    let mut permissive = ProcessErrorPolicy::new()
+        .suspend_after(1)
+        .with_unknown_statuses(UnknownStatusHandling::Permissive);
+    assert_eq!(
+        permissive.record_error(&unknown),
+        ProcessErrorAction::Continue
+    );
+    assert_eq!(permissive.total_errors(), 0);
+    assert_eq!(
+        permissive.record_error(&PluginInstanceError::ProcessingFailed),
+        ProcessErrorAction::Suspend
+    );
+}
+
+#[test]
+pub fn denied_unknown_statuses_are_treated_as_errors() {
+    let unknown = PluginInstanceError::InvalidProcessStatus { status: 42 };
+
+    let mut policy = ProcessErrorPolicy::new().suspend_after(2);
+    assert_eq!(policy.record_error(&unknown), ProcessErrorAction::Retry);
+    assert_eq!(policy.total_errors(), 1);
+    assert_eq!(
+        policy.record_error(&PluginInstanceError::ProcessingFailed),
+        ProcessErrorAction::Suspend
+    );
+}
@@ -0,0 +1,297 @@
+use clack_host::events::event_types::{NoteOnEvent, TransportEvent, TransportFlags};
+use clack_host::events::{EventFlags, EventHeader, Pckn};
+use clack_host::prelude::*;
+use clack_host::process::trace::ProcessTrace;
+use clack_host::utils::{BeatTime, SecondsTime};
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+/// A plugin that copies its input events to its output events, and then goes to sleep.
+pub struct EchoPlugin;
+
+pub struct EchoPluginAudioProcessor;
+
+impl Plugin for EchoPlugin {
+    type AudioProcessor<'a> = EchoPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for EchoPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.echo", "Echo")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for EchoPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        for event in events.input {
+            events.output.try_push(event).unwrap();
+        }
+
+        Ok(ProcessStatus::Sleep)
+    }
+}
+
+pub static ECHO_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<EchoPlugin>);
+
+struct EchoHost;
+
+impl HostHandlers for EchoHost {
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn instantiate() -> PluginInstance<EchoHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&ECHO_ENTRY, "/echo.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<EchoHost>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.echo\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+fn start(instance: &mut PluginInstance<EchoHost>) -> StartedPluginAudioProcessor<EchoHost> {
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 1,
+        max_frames_count: 256,
+    };
+
+    instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap()
+}
+
+fn note_on(time: u32) -> NoteOnEvent {
+    NoteOnEvent::new(time, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0)
+}
+
+fn playing_transport() -> TransportEvent {
+    TransportEvent {
+        header: EventHeader::new_core(0, EventFlags::empty()),
+        flags: TransportFlags::IS_PLAYING,
+        song_pos_beats: BeatTime::from_int(0),
+        song_pos_seconds: SecondsTime::from_int(0),
+        tempo: 120.0,
+        tempo_inc: 0.0,
+        loop_start_beats: BeatTime::from_int(0),
+        loop_end_beats: BeatTime::from_int(0),
+        loop_start_seconds: SecondsTime::from_int(0),
+        loop_end_seconds: SecondsTime::from_int(0),
+        bar_start: BeatTime::from_int(0),
+        bar_number: 0,
+        time_signature_numerator: 4,
+        time_signature_denominator: 4,
+    }
+}
+
+#[test]
+pub fn untraced_processors_have_no_trace() {
+    let mut instance = instantiate();
+    let mut processor = start(&mut instance);
+
+    assert!(processor.trace().is_none());
+
+    let trace = ProcessTrace::new();
+    assert!(processor.set_trace(Some(trace)).is_none());
+    assert!(processor.trace().is_some());
+    assert!(processor.set_trace(None).is_some());
+    assert!(processor.trace().is_none());
+}
+
+#[test]
+#[cfg(not(feature = "process-trace"))]
+pub fn disabled_traces_record_nothing() {
+    let mut instance = instantiate();
+    let mut processor = start(&mut instance);
+
+    let trace = ProcessTrace::new();
+    processor.set_trace(Some(trace.clone()));
+
+    let mut events = EventIo::new();
+    events.push_input(&note_on(0));
+    let (input, mut output) = events.split();
+    processor
+        .process_events(64, &input, &mut output, Some(0), Some(&playing_transport()))
+        .unwrap();
+
+    // The plugin is still processed normally.
+    assert_eq!(events.drain_output().count(), 1);
+
+    assert!(!ProcessTrace::is_enabled());
+    assert_eq!(trace.recorded_blocks(), 0);
+    assert!(trace.snapshot().is_empty());
+    assert!(trace.dump().contains("disabled"));
+}
+
+#[test]
+#[cfg(feature = "process-trace")]
+pub fn blocks_are_recorded() {
+    use clack_host::process::trace::TracedTransport;
+
+    let mut instance = instantiate();
+    let mut processor = start(&mut instance);
+
+    let trace = ProcessTrace::new();
+    processor.set_trace(Some(trace.clone()));
+
+    let mut events = EventIo::new();
+    events.push_input(&note_on(0));
+    events.push_input(&note_on(3));
+
+    let (input, mut output) = events.split();
+    let status = processor
+        .process_events(64, &input, &mut output, Some(128), None)
+        .unwrap();
+    assert_eq!(status, ProcessStatus::Sleep);
+
+    // Events pushed by the plugin still reach the host's output events.
+    assert_eq!(events.drain_output().count(), 2);
+    events.reset();
+
+    let transport = playing_transport();
+
+    let (input, mut output) = events.split();
+    processor
+        .process_events(32, &input, &mut output, None, Some(&transport))
+        .unwrap();
+
+    let entries = trace.snapshot();
+    assert_eq!(trace.recorded_blocks(), 2);
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].block, 0);
+    assert_eq!(entries[0].frames_count, 64);
+    assert_eq!(entries[0].steady_time, Some(128));
+    assert_eq!(entries[0].input_events, 2);
+    assert_eq!(entries[0].output_events, 2);
+    assert_eq!(entries[0].status(), Some(ProcessStatus::Sleep));
+    assert_eq!(entries[0].transport, TracedTransport::None);
+
+    assert_eq!(entries[1].block, 1);
+    assert_eq!(entries[1].frames_count, 32);
+    assert_eq!(entries[1].steady_time, None);
+    assert_eq!(entries[1].input_events, 0);
+    assert_eq!(entries[1].output_events, 0);
+    assert_eq!(entries[1].transport, TracedTransport::Playing);
+
+    let dump = trace.dump();
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("steady_time"));
+    assert!(lines[1].contains("sleep"));
+    assert!(lines[2].contains("playing"));
+}
+
+#[test]
+#[cfg(feature = "process-trace")]
+pub fn only_the_most_recent_blocks_are_kept() {
+    let mut instance = instantiate();
+    let mut processor = start(&mut instance);
+
+    let trace = ProcessTrace::new();
+    processor.set_trace(Some(trace.clone()));
+
+    let block_count = ProcessTrace::CAPACITY as u64 + 10;
+    for block in 0..block_count {
+        processor
+            .process_events(
+                16,
+                &InputEvents::empty(),
+                &mut OutputEvents::void(),
+                Some(block * 16),
+                None,
+            )
+            .unwrap();
+    }
+
+    let entries = trace.snapshot();
+    assert_eq!(trace.recorded_blocks(), block_count);
+    assert_eq!(entries.len(), ProcessTrace::CAPACITY);
+    assert_eq!(entries[0].block, 10);
+    assert_eq!(entries[0].steady_time, Some(160));
+    assert_eq!(entries.last().unwrap().block, block_count - 1);
+}
+
+#[test]
+#[cfg(feature = "process-trace")]
+pub fn traces_stay_attached_across_stop_and_start() {
+    let mut instance = instantiate();
+    let processor = start(&mut instance);
+
+    let mut processor = clack_host::process::PluginAudioProcessor::from(processor);
+    let trace = ProcessTrace::new();
+    processor
+        .as_started_mut()
+        .unwrap()
+        .set_trace(Some(trace.clone()));
+
+    processor.stop_processing().unwrap();
+    let started = processor.start_processing().unwrap();
+    started
+        .process_events(
+            8,
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(trace.recorded_blocks(), 1);
+
+    // The trace is also kept by the type-state stop and start methods.
+    let processor = processor.into_started().unwrap();
+    let stopped = processor.stop_processing();
+    let Ok(mut started) = stopped.start_processing() else {
+        panic!("Failed to restart processing");
+    };
+
+    started
+        .process_events(
+            8,
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(trace.recorded_blocks(), 2);
+}
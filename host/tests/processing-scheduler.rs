@@ -0,0 +1,432 @@
+//! Plugin chains automatically putting plugins to sleep, and waking them up.
+
+use clack_host::events::event_types::{NoteOffEvent, NoteOnEvent};
+use clack_host::events::io::EventBuffer;
+use clack_host::events::Pckn;
+use clack_host::prelude::*;
+use clack_host::process::chain::{ChainedPluginInfo, StartedPluginChain, StoppedPluginChain};
+use clack_host::process::scheduler::SILENCE_THRESHOLD;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+const BLOCK_SIZE: usize = 32;
+const REVERB_TAIL: u32 = 100;
+
+thread_local! {
+    static REVERB_CALLS: Cell<u32> = const { Cell::new(0) };
+    static SYNTH_CALLS: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Returns the mutable output buffer of the first channel, after copying the input into it.
+fn mono_output<'a>(audio: &'a mut Audio) -> Result<&'a mut [f32], PluginError> {
+    let mut port_pair = audio.port_pair(0).ok_or(PluginError::Message("No port"))?;
+    let channels = port_pair
+        .channels()?
+        .into_f32()
+        .ok_or(PluginError::Message("Expected f32"))?;
+
+    match channels.into_iter().next() {
+        Some(ChannelPair::InPlace(buffer)) => Ok(buffer),
+        Some(ChannelPair::InputOutput(input, output)) => {
+            output.copy_from_slice(input);
+            Ok(output)
+        }
+        _ => Err(PluginError::Message("Expected an output channel")),
+    }
+}
+
+/// A "reverb", which adds a constant ring of `REVERB_TAIL` samples after any non-silent input.
+pub struct ReverbPlugin;
+
+pub struct ReverbAudioProcessor {
+    ring_remaining: u32,
+}
+
+impl Plugin for ReverbPlugin {
+    type AudioProcessor<'a> = ReverbAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for ReverbPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.reverb", "Reverb")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for ReverbAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { ring_remaining: 0 })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        REVERB_CALLS.with(|c| c.set(c.get() + 1));
+
+        for sample in mono_output(&mut audio)? {
+            if sample.abs() > SILENCE_THRESHOLD {
+                self.ring_remaining = REVERB_TAIL;
+                *sample += 0.5;
+            } else if self.ring_remaining > 0 {
+                *sample += 0.5;
+                self.ring_remaining -= 1;
+            }
+        }
+
+        Ok(ProcessStatus::Tail)
+    }
+}
+
+pub static REVERB_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<ReverbPlugin>);
+
+/// A single-voice "synth", whose release either decays, or hangs at a constant level.
+pub struct SynthPlugin<const HANGING_RELEASE: bool>;
+
+pub struct SynthAudioProcessor<const HANGING_RELEASE: bool> {
+    level: f32,
+    releasing: bool,
+}
+
+impl<const HANGING_RELEASE: bool> Plugin for SynthPlugin<HANGING_RELEASE> {
+    type AudioProcessor<'a> = SynthAudioProcessor<HANGING_RELEASE>;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl<const HANGING_RELEASE: bool> DefaultPluginFactory for SynthPlugin<HANGING_RELEASE> {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.synth", "Synth")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a, const HANGING_RELEASE: bool> PluginAudioProcessor<'a, (), ()>
+    for SynthAudioProcessor<HANGING_RELEASE>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            level: 0.0,
+            releasing: false,
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        SYNTH_CALLS.with(|c| c.set(c.get() + 1));
+
+        // Events are applied at the start of the block, for simplicity.
+        for event in events.input {
+            if event.as_event::<NoteOnEvent>().is_some() {
+                self.level = 0.25;
+                self.releasing = false;
+            } else if event.as_event::<NoteOffEvent>().is_some() {
+                self.releasing = true;
+            }
+        }
+
+        for sample in mono_output(&mut audio)? {
+            if self.releasing && !HANGING_RELEASE {
+                self.level *= 0.5;
+            }
+
+            *sample = self.level;
+        }
+
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    }
+}
+
+pub static SYNTH_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<SynthPlugin<false>>);
+pub static HANGING_SYNTH_ENTRY: EntryDescriptor =
+    clack_entry!(SinglePluginEntry<SynthPlugin<true>>);
+
+struct TestHost;
+
+impl HostHandlers for TestHost {
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+/// The plugin instances must outlive the chain.
+struct Session {
+    _instances: Vec<PluginInstance<TestHost>>,
+    chain: StartedPluginChain<TestHost>,
+}
+
+impl Session {
+    fn new(plugins: &[(&'static EntryDescriptor, &[u8], u32)]) -> Self {
+        let host_info =
+            HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+        let configuration = PluginAudioConfiguration {
+            sample_rate: 48_000.0,
+            min_frames_count: 1,
+            max_frames_count: BLOCK_SIZE as u32,
+        };
+
+        let mut instances = Vec::new();
+        let mut chain = StoppedPluginChain::new(1, BLOCK_SIZE as u32);
+        chain.set_scheduling(true);
+
+        for (entry, id, tail) in plugins {
+            let bundle = unsafe { PluginBundle::load_from_raw(entry, "/plugin.clap").unwrap() };
+            let mut instance = PluginInstance::<TestHost>::new(
+                |_| (),
+                |_| (),
+                &bundle,
+                CStr::from_bytes_with_nul(id).unwrap(),
+                &host_info,
+            )
+            .unwrap();
+
+            let processor = instance
+                .activate_unchecked(|_, _| (), configuration)
+                .unwrap();
+
+            chain.push(
+                processor,
+                ChainedPluginInfo {
+                    in_place: false,
+                    latency: 0,
+                    tail: *tail,
+                },
+            );
+            instances.push(instance);
+        }
+
+        Self {
+            _instances: instances,
+            chain: chain.start_processing().unwrap(),
+        }
+    }
+
+    /// Processes a block with the given constant input, and events for the first plugin.
+    fn process(&mut self, input: f32, events: &EventBuffer) -> (Vec<f32>, ProcessStatus) {
+        let mut input = [vec![input; BLOCK_SIZE]];
+        let mut output = [vec![-1.0; BLOCK_SIZE]];
+
+        let input_events = events.as_input();
+        let mut output_events = OutputEvents::void();
+
+        let status = self
+            .chain
+            .process_chain(
+                &mut input,
+                &mut output,
+                &mut [(&input_events, &mut output_events)],
+                None,
+                None,
+            )
+            .unwrap();
+
+        let [output] = output;
+        (output, status)
+    }
+
+    fn is_sleeping(&self, index: usize) -> bool {
+        self.chain.scheduler(index).unwrap().is_sleeping()
+    }
+}
+
+const REVERB: (&EntryDescriptor, &[u8], u32) =
+    (&REVERB_ENTRY, b"org.rust-audio.clack.reverb\0", REVERB_TAIL);
+const SYNTH: (&EntryDescriptor, &[u8], u32) = (&SYNTH_ENTRY, b"org.rust-audio.clack.synth\0", 0);
+const HANGING_SYNTH: (&EntryDescriptor, &[u8], u32) =
+    (&HANGING_SYNTH_ENTRY, b"org.rust-audio.clack.synth\0", 0);
+
+fn calls(counter: &'static std::thread::LocalKey<Cell<u32>>) -> u32 {
+    counter.with(Cell::get)
+}
+
+fn note(on: bool) -> EventBuffer {
+    let mut events = EventBuffer::new();
+    let pckn = Pckn::new(0u16, 0u16, 60u16, 0u32);
+
+    if on {
+        events.push(&NoteOnEvent::new(0, pckn, 1.0));
+    } else {
+        events.push(&NoteOffEvent::new(0, pckn, 1.0));
+    }
+
+    events
+}
+
+#[test]
+pub fn reverb_rings_out_then_sleeps() {
+    let mut session = Session::new(&[REVERB]);
+    let no_events = EventBuffer::new();
+
+    let (output, _) = session.process(1.0, &no_events);
+    assert!(output.iter().all(|s| *s == 1.5));
+
+    // The whole tail must be heard before the reverb goes to sleep.
+    let mut ring = 0;
+    for _ in 0..4 {
+        let (output, _) = session.process(0.0, &no_events);
+        ring += output.iter().filter(|s| **s == 0.5).count();
+        assert!(!session.is_sleeping(0) || ring == REVERB_TAIL as usize);
+    }
+
+    assert_eq!(ring, REVERB_TAIL as usize);
+    assert!(session.is_sleeping(0));
+
+    let processed = calls(&REVERB_CALLS);
+    for _ in 0..10 {
+        let (output, status) = session.process(0.0, &no_events);
+        assert!(output.iter().all(|s| *s == 0.0));
+        assert_eq!(status, ProcessStatus::Sleep);
+    }
+    assert_eq!(calls(&REVERB_CALLS), processed);
+
+    // New input wakes the reverb up.
+    let (output, status) = session.process(1.0, &no_events);
+    assert!(output.iter().all(|s| *s == 1.5));
+    assert_eq!(status, ProcessStatus::Tail);
+    assert_eq!(calls(&REVERB_CALLS), processed + 1);
+}
+
+#[test]
+pub fn new_input_restarts_the_tail() {
+    let mut session = Session::new(&[REVERB]);
+    let no_events = EventBuffer::new();
+
+    for _ in 0..3 {
+        session.process(1.0, &no_events);
+        session.process(0.0, &no_events);
+        session.process(0.0, &no_events);
+        assert!(!session.is_sleeping(0));
+    }
+
+    session.process(0.0, &no_events);
+    session.process(0.0, &no_events);
+    assert!(session.is_sleeping(0));
+}
+
+#[test]
+pub fn synth_sleeps_once_its_release_is_quiet() {
+    let mut session = Session::new(&[SYNTH]);
+    let no_events = EventBuffer::new();
+
+    session.process(0.0, &no_events);
+    assert!(session.is_sleeping(0));
+    let processed = calls(&SYNTH_CALLS);
+    session.process(0.0, &no_events);
+    assert_eq!(calls(&SYNTH_CALLS), processed);
+
+    // A note wakes the synth up, and keeps it awake while it's held.
+    let (output, status) = session.process(0.0, &note(true));
+    assert!(output.iter().all(|s| *s == 0.25));
+    assert_eq!(status, ProcessStatus::ContinueIfNotQuiet);
+
+    for _ in 0..10 {
+        session.process(0.0, &no_events);
+        assert!(!session.is_sleeping(0));
+    }
+
+    // The release isn't quiet during the note-off block, but is right after.
+    session.process(0.0, &note(false));
+    assert!(!session.is_sleeping(0));
+    session.process(0.0, &no_events);
+    assert!(session.is_sleeping(0));
+
+    let processed = calls(&SYNTH_CALLS);
+    for _ in 0..10 {
+        let (output, _) = session.process(0.0, &no_events);
+        assert!(output.iter().all(|s| *s == 0.0));
+    }
+    assert_eq!(calls(&SYNTH_CALLS), processed);
+}
+
+#[test]
+pub fn hanging_release_keeps_the_synth_awake() {
+    let mut session = Session::new(&[HANGING_SYNTH]);
+    let no_events = EventBuffer::new();
+
+    session.process(0.0, &note(true));
+    session.process(0.0, &note(false));
+
+    let processed = calls(&SYNTH_CALLS);
+    for _ in 0..10 {
+        let (output, _) = session.process(0.0, &no_events);
+        assert!(output.iter().all(|s| *s == 0.25));
+        assert!(!session.is_sleeping(0));
+    }
+    assert_eq!(calls(&SYNTH_CALLS), processed + 10);
+}
+
+#[test]
+pub fn whole_chain_goes_to_sleep() {
+    let mut session = Session::new(&[SYNTH, REVERB]);
+    let no_events = EventBuffer::new();
+
+    session.process(0.0, &note(true));
+    session.process(0.0, &note(false));
+
+    // The synth sleeps first, while the reverb keeps ringing.
+    session.process(0.0, &no_events);
+    assert!(session.is_sleeping(0));
+    assert!(!session.is_sleeping(1));
+
+    let mut status = ProcessStatus::Continue;
+    for _ in 0..5 {
+        (_, status) = session.process(0.0, &no_events);
+    }
+
+    assert!(session.is_sleeping(0));
+    assert!(session.is_sleeping(1));
+    assert_eq!(status, ProcessStatus::Sleep);
+
+    let processed = (calls(&SYNTH_CALLS), calls(&REVERB_CALLS));
+    let (output, _) = session.process(0.0, &no_events);
+    assert!(output.iter().all(|s| *s == 0.0));
+    assert_eq!((calls(&SYNTH_CALLS), calls(&REVERB_CALLS)), processed);
+
+    // A new note wakes up both plugins.
+    let (output, _) = session.process(0.0, &note(true));
+    assert!(output.iter().all(|s| *s == 0.75));
+    assert_eq!(
+        (calls(&SYNTH_CALLS), calls(&REVERB_CALLS)),
+        (processed.0 + 1, processed.1 + 1)
+    );
+}
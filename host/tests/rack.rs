@@ -0,0 +1,286 @@
+use clack_host::plugin::rack::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::thread;
+use std::time::Duration;
+
+thread_local! {
+    static DESTROYED_INSTANCES: Cell<u32> = const { Cell::new(0) };
+}
+
+pub struct RackPlugin;
+
+pub struct RackPluginMainThread {
+    active: bool,
+}
+
+impl PluginMainThread<'_, ()> for RackPluginMainThread {}
+
+impl Drop for RackPluginMainThread {
+    fn drop(&mut self) {
+        assert!(!self.active);
+        DESTROYED_INSTANCES.with(|d| d.set(d.get() + 1));
+    }
+}
+
+pub struct RackPluginAudioProcessor {
+    processing: bool,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), RackPluginMainThread> for RackPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        main_thread: &mut RackPluginMainThread,
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        main_thread.active = true;
+        Ok(Self { processing: false })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        assert!(self.processing);
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn deactivate(self, main_thread: &mut RackPluginMainThread) {
+        assert!(!self.processing);
+        main_thread.active = false;
+    }
+
+    fn start_processing(&mut self) -> Result<(), PluginError> {
+        self.processing = true;
+        Ok(())
+    }
+
+    fn stop_processing(&mut self) {
+        self.processing = false;
+    }
+}
+
+impl Plugin for RackPlugin {
+    type AudioProcessor<'a> = RackPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = RackPluginMainThread;
+}
+
+impl DefaultPluginFactory for RackPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.rack", "Rack")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(RackPluginMainThread { active: false })
+    }
+}
+
+pub static RACK_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<RackPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = MyHostAudioProcessor;
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+/// A host audio processor handler that can be made to panic when it is dropped, i.e. when the
+/// plugin is deactivated.
+struct MyHostAudioProcessor {
+    panic_on_drop: bool,
+}
+
+impl AudioProcessorHandler<'_> for MyHostAudioProcessor {}
+
+impl Drop for MyHostAudioProcessor {
+    fn drop(&mut self) {
+        if self.panic_on_drop {
+            panic!("Host audio processor handler panicked");
+        }
+    }
+}
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 32,
+    max_frames_count: 32,
+};
+
+fn load_bundle() -> PluginBundle {
+    unsafe { PluginBundle::load_from_raw(&RACK_ENTRY, "/rack.clap").unwrap() }
+}
+
+fn insert_instance(rack: &mut Rack<MyHost>, bundle: &PluginBundle) -> RackInstanceId {
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.rack\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    rack.insert(instance).ok().unwrap()
+}
+
+fn activate(rack: &mut Rack<MyHost>, id: RackInstanceId, panic_on_drop: bool) {
+    rack.activate(
+        id,
+        |_, _| MyHostAudioProcessor { panic_on_drop },
+        CONFIGURATION,
+    )
+    .unwrap();
+}
+
+#[test]
+pub fn shutdown_tears_down_all_instances_in_order() {
+    let mut rack = Rack::new();
+    let bundle = load_bundle();
+
+    let inactive = insert_instance(&mut rack, &bundle);
+    let idle = insert_instance(&mut rack, &bundle);
+    let processing = insert_instance(&mut rack, &bundle);
+    rack.keep_bundle(bundle);
+
+    activate(&mut rack, idle, false);
+    activate(&mut rack, processing, false);
+
+    let mut token = rack.take_processor(processing).unwrap();
+    assert_eq!(token.id(), processing);
+    assert!(rack.is_processor_away(processing));
+    assert!(rack.take_processor(processing).is_none());
+    assert!(rack.take_processor(inactive).is_none());
+
+    let audio_thread = thread::spawn(move || {
+        token.processor().start_processing().unwrap();
+
+        while !token.is_shutdown_requested() {
+            token
+                .processor()
+                .as_started_mut()
+                .unwrap()
+                .process_events(
+                    32,
+                    &InputEvents::empty(),
+                    &mut OutputEvents::void(),
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // Dropping the token stops processing, and sends the processor back.
+    });
+
+    let report = rack.shutdown(Duration::from_secs(10));
+    audio_thread.join().unwrap();
+
+    assert!(report.is_clean(), "{report}");
+    assert_eq!(report.destroyed, 3);
+    assert_eq!(DESTROYED_INSTANCES.with(Cell::get), 3);
+}
+
+#[test]
+pub fn processors_that_never_come_back_are_leaked() {
+    let mut rack = Rack::new();
+    let bundle = load_bundle();
+
+    let stuck = insert_instance(&mut rack, &bundle);
+    let idle = insert_instance(&mut rack, &bundle);
+
+    activate(&mut rack, stuck, false);
+    activate(&mut rack, idle, false);
+
+    let token = rack.take_processor(stuck).unwrap();
+
+    let report = rack.shutdown(Duration::from_millis(10));
+
+    assert_eq!(report.destroyed, 1);
+    assert_eq!(report.leaked, vec![stuck]);
+    assert!(report.panicked.is_empty());
+    assert!(!report.is_clean());
+    assert_eq!(DESTROYED_INSTANCES.with(Cell::get), 1);
+
+    // The rack is gone: the processor can't be sent back, and must not be destroyed either.
+    assert!(token.is_shutdown_requested());
+    drop(token);
+    assert_eq!(DESTROYED_INSTANCES.with(Cell::get), 1);
+}
+
+#[test]
+pub fn panics_during_deactivation_do_not_stop_the_teardown() {
+    let mut rack = Rack::new();
+    let bundle = load_bundle();
+
+    let first = insert_instance(&mut rack, &bundle);
+    let faulty = insert_instance(&mut rack, &bundle);
+    let last = insert_instance(&mut rack, &bundle);
+
+    activate(&mut rack, first, false);
+    activate(&mut rack, faulty, true);
+    activate(&mut rack, last, false);
+
+    let report = rack.shutdown(Duration::from_secs(1));
+
+    assert_eq!(report.panicked, vec![faulty]);
+    assert!(report.leaked.is_empty());
+    assert_eq!(report.destroyed, 3);
+    assert_eq!(DESTROYED_INSTANCES.with(Cell::get), 3);
+}
+
+#[test]
+pub fn removed_instances_are_deactivated() {
+    let mut rack = Rack::new();
+    let bundle = load_bundle();
+
+    let id = insert_instance(&mut rack, &bundle);
+    activate(&mut rack, id, false);
+    assert_eq!(rack.len(), 1);
+
+    let token = rack.take_processor(id).unwrap();
+    assert!(matches!(
+        rack.remove(id),
+        Err(PluginInstanceError::ProcessingStarted)
+    ));
+
+    drop(token);
+    assert_eq!(rack.collect_returned_processors(), 1);
+
+    let instance = rack.remove(id).unwrap();
+    assert!(!instance.is_active());
+    assert!(rack.is_empty());
+    assert!(rack.instance(id).is_none());
+
+    drop(instance);
+    assert_eq!(DESTROYED_INSTANCES.with(Cell::get), 1);
+
+    assert!(rack.shutdown(Duration::from_secs(1)).is_clean());
+}
@@ -0,0 +1,112 @@
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::plugin::clap_plugin;
+use std::ffi::{c_void, CStr};
+use std::ptr::NonNull;
+
+const DRAFT_EXT_ID: &[u8] = b"org.example.draft-ext\0";
+
+/// A hand-written extension struct, for an extension Clack doesn't know about.
+#[repr(C)]
+struct clap_plugin_draft_ext {
+    answer: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> u32>,
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn answer(_plugin: *const clap_plugin) -> u32 {
+    42
+}
+
+static DRAFT_EXT: clap_plugin_draft_ext = clap_plugin_draft_ext {
+    answer: Some(answer),
+};
+
+fn draft_ext_id() -> &'static CStr {
+    CStr::from_bytes_with_nul(DRAFT_EXT_ID).unwrap()
+}
+
+pub struct DivaPluginStub;
+
+impl Plugin for DivaPluginStub {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        // SAFETY: DRAFT_EXT is a static matching DRAFT_EXT_ID.
+        unsafe {
+            builder.register_raw(draft_ext_id(), NonNull::from(&DRAFT_EXT).cast());
+        }
+    }
+}
+
+impl DefaultPluginFactory for DivaPluginStub {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("com.u-he.diva", "Diva")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        assert_eq!(host.as_raw_ptr(), host.as_raw() as *const _);
+        Ok(())
+    }
+}
+
+pub static DIVA_STUB_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<DivaPluginStub>);
+
+struct MyHostShared;
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {
+        unimplemented!()
+    }
+    fn request_process(&self) {
+        unimplemented!()
+    }
+    fn request_callback(&self) {
+        unimplemented!()
+    }
+}
+
+struct MyHost;
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+#[test]
+pub fn can_use_raw_extensions() {
+    let bundle = unsafe {
+        PluginBundle::load_from_raw(&DIVA_STUB_ENTRY, "/home/user/.clap/u-he/libdiva.so").unwrap()
+    };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"com.u-he.diva\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let handle = instance.plugin_handle_unchecked();
+    let unknown = CStr::from_bytes_with_nul(b"org.example.unknown\0").unwrap();
+    assert!(handle.get_raw_extension(unknown).is_none());
+
+    let ext: NonNull<c_void> = handle.get_raw_extension(draft_ext_id()).unwrap();
+    let ext = ext.cast::<clap_plugin_draft_ext>();
+
+    // SAFETY: the plugin registered this exact struct for this identifier.
+    let answer = unsafe { ext.as_ref().answer.unwrap()(handle.as_raw_ptr()) };
+    assert_eq!(answer, 42);
+}
@@ -0,0 +1,249 @@
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use clap_sys::plugin::clap_plugin;
+use std::cell::Cell;
+use std::ffi::CStr;
+use std::ptr::null;
+use std::sync::Mutex;
+
+thread_local! {
+    /// The raw instance the host destroys from within its callbacks.
+    static PLUGIN: Cell<*const clap_plugin> = const { Cell::new(null()) };
+    static MAIN_THREAD_DROPPED: Cell<bool> = const { Cell::new(false) };
+    static AUDIO_PROCESSOR_DROPPED: Cell<bool> = const { Cell::new(false) };
+    /// Whether the plugin was still alive after the host callback that destroyed it returned.
+    static ALIVE_AFTER_HOST_CALLBACK: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/// A plugin that calls back into the host from its `on_main_thread` and `process` callbacks,
+/// then checks it hasn't been freed under its feet.
+pub struct CallbackPlugin;
+
+pub struct CallbackPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    callbacks: u32,
+}
+
+impl<'a> PluginMainThread<'a, ()> for CallbackPluginMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        self.host.request_callback();
+
+        self.callbacks += 1;
+        ALIVE_AFTER_HOST_CALLBACK.with(|c| c.set(Some(!MAIN_THREAD_DROPPED.with(Cell::get))));
+    }
+}
+
+impl Drop for CallbackPluginMainThread<'_> {
+    fn drop(&mut self) {
+        MAIN_THREAD_DROPPED.with(|c| c.set(true));
+    }
+}
+
+pub struct CallbackPluginAudioProcessor<'a> {
+    host: HostAudioProcessorHandle<'a>,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), CallbackPluginMainThread<'a>>
+    for CallbackPluginAudioProcessor<'a>
+{
+    fn activate(
+        host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut CallbackPluginMainThread<'a>,
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { host })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.host.request_process();
+
+        ALIVE_AFTER_HOST_CALLBACK.with(|c| c.set(Some(!AUDIO_PROCESSOR_DROPPED.with(Cell::get))));
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl Drop for CallbackPluginAudioProcessor<'_> {
+    fn drop(&mut self) {
+        AUDIO_PROCESSOR_DROPPED.with(|c| c.set(true));
+    }
+}
+
+impl Plugin for CallbackPlugin {
+    type AudioProcessor<'a> = CallbackPluginAudioProcessor<'a>;
+    type Shared<'a> = ();
+    type MainThread<'a> = CallbackPluginMainThread<'a>;
+}
+
+impl DefaultPluginFactory for CallbackPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.callback", "Callback")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(CallbackPluginMainThread { host, callbacks: 0 })
+    }
+}
+
+pub static CALLBACK_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<CallbackPlugin>);
+
+/// A faulty host, which destroys the plugin from any of its callbacks, and then keeps calling it.
+struct FaultyHostShared {
+    logs: Mutex<Vec<(LogSeverity, String)>>,
+}
+
+impl FaultyHostShared {
+    fn destroy_and_call_again(&self) {
+        let plugin = PLUGIN.with(Cell::get);
+
+        // SAFETY: this is exactly the bad call sequence under test. The plugin must defer its
+        // destruction, so that the pointer stays valid until the current plugin callback returns.
+        unsafe {
+            (*plugin).destroy.unwrap()(plugin);
+            (*plugin).on_main_thread.unwrap()(plugin);
+            (*plugin).destroy.unwrap()(plugin);
+        }
+    }
+}
+
+impl SharedHandler<'_> for FaultyHostShared {
+    fn request_restart(&self) {}
+
+    fn request_process(&self) {
+        self.destroy_and_call_again()
+    }
+
+    fn request_callback(&self) {
+        self.destroy_and_call_again()
+    }
+}
+
+impl HostLogImpl for FaultyHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.logs.lock().unwrap().push((severity, message.into()));
+    }
+}
+
+struct FaultyHost;
+
+impl HostHandlers for FaultyHost {
+    type Shared<'a> = FaultyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _: &Self::Shared<'_>) {
+        builder.register::<HostLog>();
+    }
+}
+
+fn instantiate() -> PluginInstance<FaultyHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&CALLBACK_ENTRY, "/callback.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let instance = PluginInstance::<FaultyHost>::new(
+        |_| FaultyHostShared {
+            logs: Mutex::new(Vec::new()),
+        },
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.callback\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    PLUGIN.with(|c| c.set(instance.raw_instance()));
+    instance
+}
+
+fn logs(instance: &PluginInstance<FaultyHost>) -> Vec<String> {
+    instance.access_shared_handler(|s| {
+        let logs = s.logs.lock().unwrap();
+        logs.iter().map(|(_, message)| message.clone()).collect()
+    })
+}
+
+fn assert_logged_reentrant_destroy(logs: &[String]) {
+    assert_eq!(logs.len(), 3, "{logs:?}");
+    assert!(logs[0].starts_with(
+        "[org.rust-audio.clack.callback] [clap_plugin.destroy] \
+        Host destroyed the plugin while one of its callbacks"
+    ));
+    // Callbacks arriving after destroy was called, including destroy itself, are rejected.
+    assert_eq!(
+        logs[1],
+        "[org.rust-audio.clack.callback] [clap_plugin.on_main_thread] \
+        Plugin instance was already destroyed"
+    );
+    assert_eq!(
+        logs[2],
+        "[org.rust-audio.clack.callback] [clap_plugin.destroy] \
+        Plugin instance was already destroyed"
+    );
+}
+
+#[test]
+pub fn destroy_from_main_thread_callback_is_deferred() {
+    let mut instance = instantiate();
+
+    instance.call_on_main_thread_callback_unchecked();
+
+    assert_eq!(ALIVE_AFTER_HOST_CALLBACK.with(Cell::get), Some(true));
+    assert!(MAIN_THREAD_DROPPED.with(Cell::get));
+    assert_logged_reentrant_destroy(&logs(&instance));
+
+    // The plugin was already destroyed by the host: it must not be destroyed a second time.
+    std::mem::forget(instance);
+}
+
+#[test]
+pub fn destroy_from_process_is_deferred() {
+    let mut instance = instantiate();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    processor
+        .process_events(
+            32,
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    // The audio processor was only dropped once process() returned, as part of the deferred
+    // deactivation.
+    assert_eq!(ALIVE_AFTER_HOST_CALLBACK.with(Cell::get), Some(true));
+    assert!(AUDIO_PROCESSOR_DROPPED.with(Cell::get));
+    assert!(MAIN_THREAD_DROPPED.with(Cell::get));
+    assert_logged_reentrant_destroy(&logs(&instance));
+
+    // The plugin was already destroyed by the host: it must not be destroyed a second time.
+    std::mem::forget(processor);
+    std::mem::forget(instance);
+}
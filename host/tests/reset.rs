@@ -0,0 +1,240 @@
+//! Resetting a plugin clears its processing state, but keeps its parameter values.
+
+use clack_extensions::params::set::{ParamDef, ParamSet, ParamSetProvider};
+use clack_extensions::params::*;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::events::Pckn;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const GAIN: ClapId = ClapId::new(0);
+const DELAY: usize = 4;
+const BLOCK_SIZE: usize = 8;
+
+/// A mono plugin delaying its input by a few samples, and applying a gain to it.
+pub struct DelayPlugin;
+
+pub struct DelayPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for DelayPluginShared {}
+
+pub struct DelayPluginMainThread<'a> {
+    shared: &'a DelayPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, DelayPluginShared> for DelayPluginMainThread<'a> {}
+
+impl ParamSetProvider for DelayPluginMainThread<'_> {
+    fn param_set(&self) -> &ParamSet {
+        &self.shared.params
+    }
+}
+
+pub struct DelayPluginAudioProcessor<'a> {
+    shared: &'a DelayPluginShared,
+    line: [f32; DELAY],
+    position: usize,
+}
+
+impl<'a> PluginAudioProcessor<'a, DelayPluginShared, DelayPluginMainThread<'a>>
+    for DelayPluginAudioProcessor<'a>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut DelayPluginMainThread<'a>,
+        shared: &'a DelayPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            shared,
+            line: [0.0; DELAY],
+            position: 0,
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.shared.params.flush(events.input);
+        let gain = self.shared.params.value(GAIN).unwrap() as f32;
+
+        let mut port_pair = audio.port_pair(0).unwrap();
+        let mut channels = port_pair.channels()?.into_f32().unwrap();
+
+        if let Some(ChannelPair::InputOutput(input, output)) = channels.channel_pair(0) {
+            for (input, output) in input.iter().zip(output) {
+                *output = self.line[self.position] * gain;
+                self.line[self.position] = *input;
+                self.position = (self.position + 1) % DELAY;
+            }
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn reset(&mut self) {
+        self.line = [0.0; DELAY];
+        self.position = 0;
+    }
+}
+
+impl PluginAudioProcessorParams for DelayPluginAudioProcessor<'_> {
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input);
+    }
+}
+
+impl Plugin for DelayPlugin {
+    type AudioProcessor<'a> = DelayPluginAudioProcessor<'a>;
+    type Shared<'a> = DelayPluginShared;
+    type MainThread<'a> = DelayPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder.register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for DelayPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.delay", "Delay")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(DelayPluginShared {
+            params: ParamSet::new().with_param(GAIN, ParamDef::new("Gain", 0.0, 1.0, 1.0)),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(DelayPluginMainThread { shared })
+    }
+}
+
+pub static DELAY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<DelayPlugin>);
+
+struct DelayHostShared;
+
+impl SharedHandler<'_> for DelayHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct DelayHost;
+
+impl HostHandlers for DelayHost {
+    type Shared<'a> = DelayHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn process(
+    processor: &mut StartedPluginAudioProcessor<DelayHost>,
+    input: [f32; BLOCK_SIZE],
+    events: &EventBuffer,
+) -> [f32; BLOCK_SIZE] {
+    let mut input = input;
+    let mut output = [0.0f32; BLOCK_SIZE];
+    let mut input_ports = AudioPorts::with_capacity(1, 1);
+    let mut output_ports = AudioPorts::with_capacity(1, 1);
+
+    processor
+        .process(
+            &input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only([InputChannel::variable(&mut input)]),
+                latency: 0,
+            }]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([&mut output[..]]),
+                latency: 0,
+            }]),
+            &events.as_input(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    output
+}
+
+#[test]
+pub fn reset_clears_audio_state_but_keeps_param_values() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&DELAY_ENTRY, "/delay.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<DelayHost>::new(
+        |_| DelayHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.delay\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let params: PluginParams = instance.plugin_shared_handle().get_extension().unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE as u32,
+        max_frames_count: BLOCK_SIZE as u32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut gain = EventBuffer::new();
+    gain.push(&ParamValueEvent::new(
+        0,
+        GAIN,
+        Pckn::match_all(),
+        0.5,
+        Cookie::empty(),
+    ));
+
+    let output = process(&mut processor, [1.0; BLOCK_SIZE], &gain);
+    assert_eq!(output, [0.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.5, 0.5]);
+
+    // Resetting is allowed while processing is started.
+    processor.reset();
+
+    // The delay line was cleared, but the gain is still applied.
+    let output = process(&mut processor, [1.0; BLOCK_SIZE], &EventBuffer::new());
+    assert_eq!(output, [0.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.5, 0.5]);
+    assert_eq!(
+        params.get_value(&mut instance.plugin_handle_unchecked(), GAIN),
+        Some(0.5)
+    );
+
+    // Resetting is also allowed while processing is stopped.
+    let mut stopped = processor.stop_processing();
+    stopped.reset();
+    let mut processor = stopped.start_processing().unwrap();
+
+    let output = process(&mut processor, [0.0; BLOCK_SIZE], &EventBuffer::new());
+    assert_eq!(output, [0.0; BLOCK_SIZE]);
+    assert_eq!(
+        params.get_value(&mut instance.plugin_handle_unchecked(), GAIN),
+        Some(0.5)
+    );
+
+    instance.deactivate_unchecked(processor.stop_processing());
+}
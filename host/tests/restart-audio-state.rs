@@ -0,0 +1,155 @@
+//! A Clack plugin carries its audio state over to its new audio processor when restarted through
+//! a `RestartCoordinator`, but not through a plain deactivation and activation.
+
+use clack_host::plugin::restart::RestartCoordinator;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+
+thread_local! {
+    /// The number of audio processors activated so far.
+    static ACTIVATIONS: Cell<u32> = const { Cell::new(0) };
+    /// The state each audio processor had when it was deactivated.
+    static DEACTIVATED: RefCell<Vec<u32>> = const { RefCell::new(Vec::new()) };
+}
+
+pub struct StatefulPlugin;
+
+impl Plugin for StatefulPlugin {
+    type AudioProcessor<'a> = StatefulPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for StatefulPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.stateful", "Stateful")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+pub struct StatefulPluginAudioProcessor {
+    state: u32,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for StatefulPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        let state = ACTIVATIONS.with(|a| {
+            a.set(a.get() + 1);
+            a.get()
+        });
+
+        Ok(Self { state })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn deactivate(self, _main_thread: &mut ()) {
+        DEACTIVATED.with(|d| d.borrow_mut().push(self.state));
+    }
+
+    fn save_audio_state(&mut self) -> Option<AudioStateBlob> {
+        Some(AudioStateBlob::new(self.state))
+    }
+
+    fn restore_audio_state(&mut self, blob: AudioStateBlob) {
+        self.state = blob.downcast().unwrap();
+    }
+}
+
+pub static STATEFUL_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<StatefulPlugin>);
+
+const CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 1,
+    max_frames_count: 256,
+};
+
+const NEW_CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 96_000.0,
+    min_frames_count: 1,
+    max_frames_count: 512,
+};
+
+fn instantiate() -> PluginInstance<()> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&STATEFUL_ENTRY, "/stateful.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.stateful\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+fn deactivated() -> Vec<u32> {
+    DEACTIVATED.with(|d| d.borrow().clone())
+}
+
+#[test]
+pub fn coordinated_restart_carries_audio_state_over() {
+    let mut instance = instantiate();
+    let mut coordinator = RestartCoordinator::new();
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+
+    let processor = coordinator
+        .restart_unchecked(&mut instance, processor, NEW_CONFIGURATION, |_, _| ())
+        .unwrap();
+
+    assert!(coordinator.last_generation().is_some());
+    assert_eq!(instance.activation_config(), Some(NEW_CONFIGURATION));
+
+    instance.deactivate_unchecked(processor);
+
+    // The second audio processor took the state of the first one.
+    assert_eq!(ACTIVATIONS.with(Cell::get), 2);
+    assert_eq!(deactivated(), [1, 1]);
+}
+
+#[test]
+pub fn plain_reactivation_starts_from_fresh_audio_state() {
+    let mut instance = instantiate();
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+    instance.deactivate_unchecked(processor);
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), CONFIGURATION)
+        .unwrap();
+    instance.deactivate_unchecked(processor);
+
+    assert_eq!(deactivated(), [1, 2]);
+}
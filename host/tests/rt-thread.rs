@@ -0,0 +1,77 @@
+#![cfg(feature = "rt-thread")]
+//! Real-time audio threads run their closure whether or not their priority could be elevated.
+
+#[cfg(target_os = "linux")]
+use clack_host::audio_thread::PromotionErrorKind;
+use clack_host::audio_thread::{spawn_realtime, MIN_STACK_SIZE};
+
+#[cfg(target_os = "linux")]
+fn current_policy() -> std::ffi::c_int {
+    extern "C" {
+        fn sched_getscheduler(pid: std::ffi::c_int) -> std::ffi::c_int;
+    }
+
+    // SAFETY: a pid of 0 designates the calling thread.
+    unsafe { sched_getscheduler(0) }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support calling foreign functions (thread scheduling)
+pub fn closure_runs_on_named_thread() {
+    let thread = spawn_realtime("clack-rt-test", 0, || {
+        std::thread::current().name().map(str::to_owned)
+    })
+    .unwrap();
+
+    assert_eq!(thread.thread().name(), Some("clack-rt-test"));
+    assert_eq!(thread.join().unwrap().as_deref(), Some("clack-rt-test"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support calling foreign functions (thread scheduling)
+pub fn stack_is_usable_after_prefaulting() {
+    let thread = spawn_realtime("clack-rt-stack", MIN_STACK_SIZE, || {
+        let buffer = [1u8; 16 * 1024];
+        std::hint::black_box(&buffer)
+            .iter()
+            .map(|&b| b as u32)
+            .sum::<u32>()
+    })
+    .unwrap();
+
+    assert_eq!(thread.join().unwrap(), 16 * 1024);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support calling foreign functions (thread scheduling)
+pub fn promotion_failure_is_not_fatal() {
+    let thread = spawn_realtime("clack-rt-promotion", 0, || 42).unwrap();
+
+    // Without the required privileges, the thread falls back to the default priority, but still
+    // runs: the failure is reported, not fatal.
+    if let Err(e) = thread.promotion() {
+        assert!(!e.to_string().is_empty());
+    }
+
+    assert_eq!(thread.join().unwrap(), 42);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri does not support calling foreign functions (thread scheduling)
+pub fn reported_promotion_matches_scheduling_policy() {
+    const SCHED_OTHER: std::ffi::c_int = 0;
+    const SCHED_FIFO: std::ffi::c_int = 1;
+
+    let thread = spawn_realtime("clack-rt-policy", 0, current_policy).unwrap();
+    let promotion = thread.promotion().map_err(|e| e.kind());
+    let policy = thread.join().unwrap();
+
+    match promotion {
+        Ok(()) => assert_eq!(policy, SCHED_FIFO),
+        Err(kind) => {
+            assert_eq!(kind, PromotionErrorKind::Os);
+            assert_eq!(policy, SCHED_OTHER);
+        }
+    }
+}
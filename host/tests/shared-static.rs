@@ -0,0 +1,147 @@
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::entry::SharedStatic;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+static INITIALIZED_TABLES: AtomicUsize = AtomicUsize::new(0);
+static DROPPED_TABLES: AtomicUsize = AtomicUsize::new(0);
+
+static WAVETABLES: SharedStatic<Wavetables> = SharedStatic::new();
+
+pub struct Wavetables(Vec<f32>);
+
+impl Wavetables {
+    fn load() -> Self {
+        INITIALIZED_TABLES.fetch_add(1, Ordering::SeqCst);
+        Self(vec![0.0; 2048])
+    }
+}
+
+impl Drop for Wavetables {
+    fn drop(&mut self) {
+        DROPPED_TABLES.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+pub struct SynthPluginStubShared {
+    wavetables: Arc<Wavetables>,
+}
+
+impl PluginShared<'_> for SynthPluginStubShared {}
+
+pub struct SynthPluginStub;
+pub struct SynthPluginStubMainThread;
+
+impl PluginMainThread<'_, SynthPluginStubShared> for SynthPluginStubMainThread {}
+
+impl Plugin for SynthPluginStub {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = SynthPluginStubShared;
+    type MainThread<'a> = SynthPluginStubMainThread;
+}
+
+impl DefaultPluginFactory for SynthPluginStub {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.example.synth", "Synth")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        let plugin_id = CStr::from_bytes_with_nul(b"org.example.synth\0").unwrap();
+        let wavetables = WAVETABLES.get_or_init(plugin_id, Wavetables::load);
+
+        Ok(SynthPluginStubShared { wavetables })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        assert_eq!(shared.wavetables.0.len(), 2048);
+        Ok(SynthPluginStubMainThread)
+    }
+}
+
+pub static SYNTH_STUB_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<SynthPluginStub>);
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {
+        unimplemented!()
+    }
+    fn request_process(&self) {
+        unimplemented!()
+    }
+    fn request_callback(&self) {
+        unimplemented!()
+    }
+}
+
+struct MyHost;
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn load_bundle() -> PluginBundle {
+    unsafe { PluginBundle::load_from_raw(&SYNTH_STUB_ENTRY, "/home/user/.clap/synth.so").unwrap() }
+}
+
+fn instantiate(bundle: &PluginBundle) -> PluginInstance<MyHost> {
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| (),
+        bundle,
+        CStr::from_bytes_with_nul(b"org.example.synth\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn shares_static_between_concurrent_instances() {
+    let bundle = load_bundle();
+
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..50 {
+                    let first = instantiate(&bundle);
+                    let second = instantiate(&bundle);
+                    drop(first);
+                    drop(second);
+                }
+            });
+        }
+    });
+
+    // The value is retained while the entry is initialized, even if no instances are alive.
+    assert_eq!(INITIALIZED_TABLES.load(Ordering::SeqCst), 1);
+    assert_eq!(DROPPED_TABLES.load(Ordering::SeqCst), 0);
+
+    // The last instance keeps the bundle (and the entry) alive.
+    let instance = instantiate(&bundle);
+    drop(bundle);
+    assert_eq!(DROPPED_TABLES.load(Ordering::SeqCst), 0);
+
+    // Destroying the last instance de-initializes the entry, which releases the value.
+    drop(instance);
+    assert_eq!(DROPPED_TABLES.load(Ordering::SeqCst), 1);
+
+    // Loading the bundle again initializes a new value.
+    let bundle = load_bundle();
+    let instance = instantiate(&bundle);
+    assert_eq!(INITIALIZED_TABLES.load(Ordering::SeqCst), 2);
+
+    drop(instance);
+    drop(bundle);
+    assert_eq!(DROPPED_TABLES.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,288 @@
+use clack_extensions::params::set::{ParamDef, ParamSet};
+use clack_extensions::params::*;
+use clack_extensions::state::session::*;
+use clack_extensions::state::*;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::io::{Read, Write};
+
+const GAIN: ClapId = ClapId::new(0);
+
+/// A plugin which marks its state dirty every time its parameters are changed by the host.
+pub struct DirtyPlugin;
+
+pub struct DirtyPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for DirtyPluginShared {}
+
+pub struct DirtyPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    shared: &'a DirtyPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, DirtyPluginShared> for DirtyPluginMainThread<'a> {}
+
+impl PluginMainThreadParams for DirtyPluginMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.shared.params.get_info(param_index, info)
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input);
+
+        if !input.is_empty() {
+            let mut state: HostState = self.host.get_extension().unwrap();
+            state.mark_dirty(&self.host);
+        }
+    }
+}
+
+pub struct DirtyPluginAudioProcessor<'a> {
+    shared: &'a DirtyPluginShared,
+}
+
+impl<'a> PluginAudioProcessor<'a, DirtyPluginShared, DirtyPluginMainThread<'a>>
+    for DirtyPluginAudioProcessor<'a>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut DirtyPluginMainThread<'a>,
+        shared: &'a DirtyPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { shared })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.shared.params.flush(events.input);
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for DirtyPluginAudioProcessor<'_> {
+    fn flush(&mut self, input: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input)
+    }
+}
+
+impl PluginStateImpl for DirtyPluginMainThread<'_> {
+    fn save(&mut self, output: &mut OutputStream) -> Result<(), PluginError> {
+        let gain = self.shared.params.value(GAIN).unwrap();
+        output.write_all(&gain.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn load(&mut self, input: &mut InputStream) -> Result<(), PluginError> {
+        let mut gain = [0; 8];
+        input.read_exact(&mut gain)?;
+        self.shared.params.set_value(GAIN, f64::from_le_bytes(gain));
+        Ok(())
+    }
+}
+
+impl Plugin for DirtyPlugin {
+    type AudioProcessor<'a> = DirtyPluginAudioProcessor<'a>;
+    type Shared<'a> = DirtyPluginShared;
+    type MainThread<'a> = DirtyPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&DirtyPluginShared>,
+    ) {
+        builder.register::<PluginParams>().register::<PluginState>();
+    }
+}
+
+impl DefaultPluginFactory for DirtyPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.dirty", "Dirty")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(DirtyPluginShared {
+            params: ParamSet::new().with_param(GAIN, ParamDef::new("Gain", 0.0, 1.0, 1.0)),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(DirtyPluginMainThread { host, shared })
+    }
+}
+
+pub static DIRTY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<DirtyPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = MyHostMainThread;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostState>();
+    }
+}
+
+struct MyHostShared;
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+#[derive(Default)]
+struct MyHostMainThread {
+    dirtiness: StateDirtiness,
+}
+
+impl MainThreadHandler<'_> for MyHostMainThread {}
+
+impl HostStateImpl for MyHostMainThread {
+    fn mark_dirty(&mut self) {
+        self.dirtiness.mark_dirty()
+    }
+}
+
+impl HostStateDirtiness for MyHostMainThread {
+    fn state_dirtiness(&self) -> &StateDirtiness {
+        &self.dirtiness
+    }
+
+    fn state_dirtiness_mut(&mut self) -> &mut StateDirtiness {
+        &mut self.dirtiness
+    }
+}
+
+fn instantiate() -> PluginInstance<MyHost> {
+    let bundle = unsafe { PluginBundle::load_from_raw(&DIRTY_ENTRY, "/dirty.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<MyHost>::new(
+        |_| MyHostShared,
+        |_| MyHostMainThread::default(),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.dirty\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+fn set_gain(instance: &mut PluginInstance<MyHost>, value: f64) {
+    let mut events = EventBuffer::new();
+    events.push(&ParamValueEvent::new(
+        0,
+        GAIN,
+        Pckn::match_all(),
+        value,
+        Cookie::empty(),
+    ));
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    params.flush(&mut handle, &events.as_input(), &mut OutputEvents::void());
+}
+
+fn is_dirty(instance: &PluginInstance<MyHost>) -> bool {
+    instance.access_handler_unchecked(|h| h.dirtiness.is_dirty())
+}
+
+/// Returns the indices of the saved instances.
+fn saved_indices(saved: &[(usize, Result<Vec<u8>, StateError>)]) -> Vec<usize> {
+    saved.iter().map(|(index, _)| *index).collect()
+}
+
+#[test]
+pub fn param_changes_mark_the_state_dirty() {
+    let mut instance = instantiate();
+    instance.access_handler_mut_unchecked(|h| h.dirtiness.set_policy(DirtinessPolicy::Trusted));
+    assert!(!is_dirty(&instance));
+
+    set_gain(&mut instance, 0.5);
+    set_gain(&mut instance, 0.25);
+    assert!(is_dirty(&instance));
+    assert_eq!(
+        instance.access_handler_unchecked(|h| h.dirtiness.change_count()),
+        2
+    );
+
+    let saved = save_all_dirty(&mut [&mut instance]);
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].1.as_ref().unwrap(), &0.25f64.to_le_bytes());
+    assert!(!is_dirty(&instance));
+
+    // Nothing changed since the last save.
+    assert!(save_all_dirty(&mut [&mut instance]).is_empty());
+}
+
+#[test]
+pub fn only_dirty_instances_are_saved() {
+    let mut first = instantiate();
+    let mut second = instantiate();
+
+    // Neither plugin called mark_dirty yet: they can't be trusted to do so.
+    let saved = save_all_dirty(&mut [&mut first, &mut second]);
+    assert_eq!(saved_indices(&saved), [0, 1]);
+    assert!(is_dirty(&first) && is_dirty(&second));
+
+    set_gain(&mut second, 0.5);
+    let saved = save_all_dirty(&mut [&mut first, &mut second]);
+    assert_eq!(saved_indices(&saved), [0, 1]);
+    assert_eq!(saved[1].1.as_ref().unwrap(), &0.5f64.to_le_bytes());
+
+    // The second plugin called mark_dirty: its calls are trusted from now on.
+    assert!(is_dirty(&first));
+    assert!(!is_dirty(&second));
+    let saved = save_all_dirty(&mut [&mut first, &mut second]);
+    assert_eq!(saved_indices(&saved), [0]);
+
+    // The host knows the first plugin is trustworthy.
+    first.access_handler_mut_unchecked(|h| h.dirtiness.set_policy(DirtinessPolicy::Trusted));
+    assert!(save_all_dirty(&mut [&mut first, &mut second]).is_empty());
+
+    set_gain(&mut first, 0.75);
+    let saved = save_all_dirty(&mut [&mut first, &mut second]);
+    assert_eq!(saved_indices(&saved), [0]);
+    assert_eq!(saved[0].1.as_ref().unwrap(), &0.75f64.to_le_bytes());
+
+    // The host knows the second plugin isn't.
+    second.access_handler_mut_unchecked(|h| h.dirtiness.set_policy(DirtinessPolicy::AlwaysDirty));
+    let saved = save_all_dirty(&mut [&mut first, &mut second]);
+    assert_eq!(saved_indices(&saved), [1]);
+}
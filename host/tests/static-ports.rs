@@ -0,0 +1,263 @@
+use clack_extensions::audio_ports::labels::{ChannelLabels, CustomChannelLabels};
+use clack_extensions::audio_ports::*;
+use clack_extensions::note_ports::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const STEREO_PORT: AudioPortInfo<'static> = AudioPortInfo {
+    id: ClapId::new(0),
+    name: b"main",
+    channel_count: 2,
+    flags: AudioPortFlags::IS_MAIN,
+    port_type: Some(AudioPortType::STEREO),
+    in_place_pair: None,
+};
+
+const SIDECHAIN_PORT: AudioPortInfo<'static> = AudioPortInfo {
+    id: ClapId::new(1),
+    name: b"sidechain",
+    channel_count: 1,
+    flags: AudioPortFlags::empty(),
+    port_type: Some(AudioPortType::MONO),
+    in_place_pair: None,
+};
+
+const NOTE_PORT: NotePortInfo<'static> = NotePortInfo {
+    id: ClapId::new(0),
+    name: b"notes",
+    supported_dialects: NoteDialects::CLAP.union(NoteDialects::MIDI),
+    preferred_dialect: Some(NoteDialect::Clap),
+};
+
+pub struct StaticPortsPlugin;
+
+pub struct StaticPortsPluginAudioProcessor;
+
+impl Plugin for StaticPortsPlugin {
+    type AudioProcessor<'a> = StaticPortsPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder
+            .register::<StaticAudioPorts<Self>>()
+            .register::<StaticNotePorts<Self>>();
+    }
+}
+
+impl AudioPortLayoutProvider for StaticPortsPlugin {
+    const AUDIO_PORTS: AudioPortLayout =
+        AudioPortLayout::new(&[STEREO_PORT, SIDECHAIN_PORT], &[STEREO_PORT]).with_channel_labels(
+            &[CustomChannelLabels {
+                is_input: false,
+                port_id: STEREO_PORT.id,
+                labels: &[b"Dry", b"Wet"],
+            }],
+        );
+}
+
+impl NotePortLayoutProvider for StaticPortsPlugin {
+    const NOTE_PORTS: NotePortLayout = NotePortLayout::new(&[NOTE_PORT], &[]);
+}
+
+impl DefaultPluginFactory for StaticPortsPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.static-ports", "Static Ports")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for StaticPortsPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        if !StaticPortsPlugin::AUDIO_PORTS.matches(&audio) {
+            return Err(PluginError::Message(
+                "Audio buffers do not match the declared layout",
+            ));
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+pub static STATIC_PORTS_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<StaticPortsPlugin>);
+
+struct StaticPortsHost;
+
+impl HostHandlers for StaticPortsHost {
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn instantiate() -> PluginInstance<StaticPortsHost> {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&STATIC_PORTS_ENTRY, "/static-ports.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<StaticPortsHost>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.static-ports\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn static_layouts_are_exposed_to_host() {
+    let mut instance = instantiate();
+    let mut handle = instance.plugin_handle_unchecked();
+
+    let audio_ports = handle.get_extension::<PluginAudioPorts>().unwrap();
+    let layout = StaticPortsPlugin::AUDIO_PORTS;
+
+    for is_input in [true, false] {
+        assert_eq!(
+            audio_ports.count(&mut handle, is_input),
+            layout.count(is_input)
+        );
+
+        for (index, expected) in layout.ports(is_input).iter().enumerate() {
+            let mut buffer = AudioPortInfoBuffer::new();
+            let port = audio_ports
+                .get(&mut handle, index as u32, is_input, &mut buffer)
+                .unwrap();
+
+            assert_eq!(port.id, expected.id);
+            assert_eq!(
+                ChannelLabels::new(&port, None).port_name().as_bytes(),
+                expected.name
+            );
+            assert_eq!(port.channel_count, expected.channel_count);
+            assert_eq!(port.flags, expected.flags);
+            assert_eq!(port.port_type, expected.port_type);
+        }
+    }
+
+    let mut buffer = AudioPortInfoBuffer::new();
+    assert!(audio_ports.get(&mut handle, 2, true, &mut buffer).is_none());
+
+    let note_ports = handle.get_extension::<PluginNotePorts>().unwrap();
+    assert_eq!(note_ports.count(&mut handle, true), 1);
+    assert_eq!(note_ports.count(&mut handle, false), 0);
+
+    let mut buffer = NotePortInfoBuffer::new();
+    let port = note_ports.get(&mut handle, 0, true, &mut buffer).unwrap();
+    assert_eq!(port.name, b"notes");
+    assert_eq!(port.supported_dialects, NOTE_PORT.supported_dialects);
+    assert_eq!(port.preferred_dialect, Some(NoteDialect::Clap));
+}
+
+#[test]
+pub fn channel_labels_are_exposed_to_host() {
+    let mut instance = instantiate();
+    let mut handle = instance.plugin_handle_unchecked();
+    let audio_ports = handle.get_extension::<PluginAudioPorts>().unwrap();
+
+    let mut buffer = AudioPortInfoBuffer::new();
+    let output = audio_ports.get(&mut handle, 0, false, &mut buffer).unwrap();
+    assert_eq!(output.name, b"main [Dry|Wet]");
+
+    let labels = ChannelLabels::new(&output, None);
+    assert_eq!(labels.port_name(), "main");
+    assert_eq!(labels.long_labels().collect::<Vec<_>>(), ["Dry", "Wet"]);
+
+    // Ports without custom labels are labelled from their port type.
+    let mut buffer = AudioPortInfoBuffer::new();
+    let input = audio_ports.get(&mut handle, 0, true, &mut buffer).unwrap();
+    assert_eq!(input.name, b"main");
+    assert_eq!(
+        ChannelLabels::new(&input, None)
+            .short_labels()
+            .collect::<Vec<_>>(),
+        ["L", "R"]
+    );
+}
+
+fn process_with_channels(
+    instance: &mut PluginInstance<StaticPortsHost>,
+    input_channels: &[usize],
+) -> bool {
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 16,
+        max_frames_count: 16,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut inputs: Vec<Vec<Vec<f32>>> = input_channels
+        .iter()
+        .map(|count| vec![vec![0.0; 16]; *count])
+        .collect();
+    let mut output = [vec![0.0f32; 16], vec![0.0f32; 16]];
+
+    let mut input_ports = AudioPorts::with_capacity(3, inputs.len());
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+    let result = processor.process(
+        &input_ports.with_input_buffers(inputs.iter_mut().map(|port| AudioPortBuffer {
+            channels: AudioPortBufferType::f32_input_only(
+                port.iter_mut().map(InputChannel::variable),
+            ),
+            latency: 0,
+        })),
+        &mut output_ports.with_output_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_output_only(
+                output.iter_mut().map(|c| c.as_mut_slice()),
+            ),
+            latency: 0,
+        }]),
+        &InputEvents::empty(),
+        &mut OutputEvents::void(),
+        None,
+        None,
+    );
+
+    instance.deactivate_unchecked(processor.stop_processing());
+    result.is_ok()
+}
+
+#[test]
+pub fn layout_matches_processed_buffers() {
+    let mut instance = instantiate();
+
+    assert!(process_with_channels(&mut instance, &[2, 1]));
+    assert!(!process_with_channels(&mut instance, &[2]));
+    assert!(!process_with_channels(&mut instance, &[2, 2]));
+}
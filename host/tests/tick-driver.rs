@@ -0,0 +1,250 @@
+use clack_extensions::timer::{HostTimer, HostTimerImpl, PluginTimer, PluginTimerImpl, TimerId};
+use clack_host::host::tick::*;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A plugin which polls something on a 10ms timer, and asks for a main-thread callback every
+/// time it does. It stops polling after its second timer tick.
+struct PollingPlugin;
+
+#[derive(Default)]
+struct PollingPluginShared {
+    timer_ticks: AtomicU32,
+}
+
+impl PluginShared<'_> for PollingPluginShared {}
+
+struct PollingPluginMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    shared: &'a PollingPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, PollingPluginShared> for PollingPluginMainThread<'a> {}
+
+impl PluginTimerImpl for PollingPluginMainThread<'_> {
+    fn on_timer(&mut self, timer_id: TimerId) {
+        let ticks = self.shared.timer_ticks.fetch_add(1, Ordering::SeqCst) + 1;
+        self.host.request_callback();
+
+        if ticks == 2 {
+            let timer: HostTimer = self.host.get_extension().unwrap();
+            timer.unregister_timer(&mut self.host, timer_id).unwrap();
+        }
+    }
+}
+
+impl Plugin for PollingPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = PollingPluginShared;
+    type MainThread<'a> = PollingPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder.register::<PluginTimer>();
+    }
+}
+
+impl DefaultPluginFactory for PollingPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.polling", "Polling")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(PollingPluginShared::default())
+    }
+
+    fn new_main_thread<'a>(
+        mut host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        let timer: HostTimer = host.get_extension().unwrap();
+        timer.register_timer(&mut host, 10).unwrap();
+
+        Ok(PollingPluginMainThread { host, shared })
+    }
+}
+
+static POLLING_PLUGIN_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<PollingPlugin>);
+
+struct MyHost;
+
+impl HostHandlers for MyHost {
+    type Shared<'a> = MyHostShared;
+    type MainThread<'a> = MyHostMainThread<'a>;
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostTimer>();
+    }
+}
+
+struct MyHostShared {
+    requests: Arc<TickRequests>,
+}
+
+impl SharedHandler<'_> for MyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {
+        self.requests.request_callback()
+    }
+}
+
+struct MyHostMainThread<'a> {
+    shared: &'a MyHostShared,
+}
+
+impl<'a> MainThreadHandler<'a> for MyHostMainThread<'a> {}
+
+impl HostTimerImpl for MyHostMainThread<'_> {
+    fn register_timer(&mut self, period_ms: u32) -> Result<TimerId, HostError> {
+        let id = self
+            .shared
+            .requests
+            .register_timer(Duration::from_millis(period_ms.into()));
+
+        Ok(TimerId(id))
+    }
+
+    fn unregister_timer(&mut self, timer_id: TimerId) -> Result<(), HostError> {
+        if self.shared.requests.unregister_timer(timer_id.0) {
+            Ok(())
+        } else {
+            Err(HostError::Message("Unknown timer"))
+        }
+    }
+}
+
+fn instantiate(driver: &mut TickDriver<usize>, key: usize) -> PluginInstance<MyHost> {
+    let host = HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+    // SAFETY: the entry is a Clack plugin, which is sound to load multiple times.
+    let bundle = unsafe { PluginBundle::load_from_raw(&POLLING_PLUGIN_ENTRY, "") }.unwrap();
+    let requests = driver.add(key);
+
+    PluginInstance::<MyHost>::new(
+        |_| MyHostShared { requests },
+        |shared| MyHostMainThread { shared },
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.polling\0").unwrap(),
+        &host,
+    )
+    .unwrap()
+}
+
+/// Performs the given work on the given instance.
+fn perform(instance: &mut PluginInstance<MyHost>, work: TickWork) {
+    // SAFETY: tests only run on the thread they were started on.
+    let main_thread = unsafe { MainThreadToken::new_unchecked() };
+
+    match work {
+        TickWork::MainThreadCallback => instance.call_on_main_thread_callback_checked(&main_thread),
+        TickWork::Timer(id) => {
+            let mut handle = instance.plugin_handle_checked(&main_thread);
+            let timer = handle.get_extension::<PluginTimer>().unwrap();
+            timer.on_timer(&mut handle, TimerId(id));
+        }
+        // This plugin doesn't have any parameters, or anything to do while deactivated.
+        TickWork::ParamFlush | TickWork::DeferredOps => {}
+    }
+}
+
+#[test]
+fn timers_and_callbacks_are_serviced_from_ticks() {
+    let mut driver = TickDriver::new(Duration::from_secs(1));
+    let mut instance = instantiate(&mut driver, 0);
+    let requests = instance.access_shared_handler(|h| h.requests.clone());
+    assert_eq!(requests.timer_count(), 1);
+
+    let mut performed = Vec::new();
+    let mut tick = |elapsed| {
+        performed.clear();
+        driver.tick(Duration::from_millis(elapsed), |_, work| {
+            performed.push(work);
+            perform(&mut instance, work)
+        });
+        performed.clone()
+    };
+
+    // The timer starts counting from the first tick.
+    assert_eq!(tick(16), []);
+    // The callback the timer requests is only serviced at the next tick.
+    assert_eq!(tick(16), [TickWork::Timer(0)]);
+    assert_eq!(tick(0), [TickWork::MainThreadCallback]);
+    // The timer unregisters itself on its second tick.
+    assert_eq!(tick(16), [TickWork::Timer(0)]);
+    assert_eq!(requests.timer_count(), 0);
+    assert_eq!(tick(16), [TickWork::MainThreadCallback]);
+    assert_eq!(tick(16), []);
+}
+
+#[test]
+fn budget_exhaustion_preserves_ordering_per_instance() {
+    const WORK_TIME: Duration = Duration::from_millis(3);
+
+    let mut driver = TickDriver::new(Duration::from_millis(5));
+    let mut instances = [instantiate(&mut driver, 0), instantiate(&mut driver, 1)];
+
+    // Start the timers' clock.
+    assert_eq!(driver.tick(Duration::ZERO, |_, _| {}).performed, 0);
+
+    for instance in &instances {
+        instance.access_shared_handler(|h| {
+            h.requests.request_deferred_ops();
+            h.requests.request_flush();
+            h.requests.request_callback();
+        });
+    }
+
+    let mut performed = Vec::new();
+    let mut ticks = 0;
+    let mut elapsed = Duration::from_millis(10);
+
+    while ticks == 0 || driver.pending_count() > 0 {
+        let report = driver.tick(elapsed, |&key, work| {
+            performed.push((key, work));
+            perform(&mut instances[key], work);
+            std::thread::sleep(WORK_TIME);
+        });
+
+        // The budget only ever allows for at most two pieces of work.
+        assert!(report.performed >= 1 && report.performed <= 2);
+        assert_eq!(report.remaining, driver.pending_count());
+        assert_eq!(report.budget_exhausted, report.remaining > 0);
+
+        ticks += 1;
+        elapsed = Duration::ZERO;
+    }
+
+    assert!(ticks >= 5);
+
+    // The work of both instances is interleaved, across ticks.
+    let keys: Vec<usize> = performed.iter().map(|(key, _)| *key).collect();
+    assert_eq!(keys, [0, 1, 0, 1, 0, 1, 0, 1, 0, 1]);
+
+    for key in 0..2 {
+        let work: Vec<TickWork> = performed
+            .iter()
+            .filter(|(k, _)| *k == key)
+            .map(|(_, work)| *work)
+            .collect();
+
+        // The callbacks requested by the timers are queued behind the work that was carried over.
+        assert_eq!(
+            work,
+            [
+                TickWork::MainThreadCallback,
+                TickWork::Timer(0),
+                TickWork::ParamFlush,
+                TickWork::DeferredOps,
+                TickWork::MainThreadCallback,
+            ]
+        );
+    }
+}
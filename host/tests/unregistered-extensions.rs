@@ -0,0 +1,110 @@
+//! Plugins only expose the extensions they register, whatever extensions are enabled in
+//! `clack-extensions`.
+
+use clack_extensions::audio_ports::PluginAudioPorts;
+use clack_extensions::latency::{PluginLatency, PluginLatencyImpl};
+use clack_extensions::note_ports::PluginNotePorts;
+use clack_extensions::params::PluginParams;
+use clack_extensions::state::PluginState;
+use clack_extensions::tail::PluginTail;
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+/// A plugin that only supports the latency extension.
+pub struct LatencyOnlyPlugin;
+
+pub struct LatencyOnlyPluginMainThread;
+
+impl PluginMainThread<'_, ()> for LatencyOnlyPluginMainThread {}
+
+impl PluginLatencyImpl for LatencyOnlyPluginMainThread {
+    fn get(&mut self) -> u32 {
+        42
+    }
+}
+
+impl Plugin for LatencyOnlyPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = LatencyOnlyPluginMainThread;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginLatency>();
+    }
+}
+
+impl DefaultPluginFactory for LatencyOnlyPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.latency-only", "Latency Only")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(LatencyOnlyPluginMainThread)
+    }
+}
+
+pub static LATENCY_ONLY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<LatencyOnlyPlugin>);
+
+struct LatencyOnlyHostShared;
+
+impl SharedHandler<'_> for LatencyOnlyHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct LatencyOnlyHost;
+
+impl HostHandlers for LatencyOnlyHost {
+    type Shared<'a> = LatencyOnlyHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn instantiate() -> PluginInstance<LatencyOnlyHost> {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&LATENCY_ONLY_ENTRY, "/latency-only.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<LatencyOnlyHost>::new(
+        |_| LatencyOnlyHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.latency-only\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn registered_extension_is_exposed() {
+    let mut instance = instantiate();
+
+    let latency = instance.get_extension::<PluginLatency>().unwrap();
+    assert_eq!(latency.get(&mut instance.plugin_handle_unchecked()), 42);
+}
+
+#[test]
+pub fn unregistered_extensions_are_null() {
+    let mut instance = instantiate();
+
+    assert!(instance.get_extension::<PluginAudioPorts>().is_none());
+    assert!(instance.get_extension::<PluginNotePorts>().is_none());
+    assert!(instance.get_extension::<PluginParams>().is_none());
+    assert!(instance.get_extension::<PluginState>().is_none());
+    assert!(instance.get_extension::<PluginTail>().is_none());
+
+    let handle = instance.plugin_handle_unchecked();
+    let unknown = CStr::from_bytes_with_nul(b"org.rust-audio.clack.unknown\0").unwrap();
+    assert!(handle.get_raw_extension(unknown).is_none());
+}
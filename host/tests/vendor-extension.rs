@@ -0,0 +1,323 @@
+//! Implements a made-up vendor extension with the thread-specific `PluginWrapper` helpers, and
+//! calls it from the host side through its raw extension struct.
+
+use clack_extensions::log::{HostLog, HostLogImpl, LogSeverity};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::extensions::prelude::{
+    clap_plugin, Extension, ExtensionImplementation, PluginExtensionSide, PluginWrapper,
+    RawExtension, RawExtensionImplementation,
+};
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+const EXAMPLE_METER_ID: &[u8] = b"com.example.meter/1\0";
+
+fn example_meter_id() -> &'static CStr {
+    CStr::from_bytes_with_nul(EXAMPLE_METER_ID).unwrap()
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[allow(non_camel_case_types)]
+struct clap_plugin_example_meter {
+    // [main-thread]
+    channel_count: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> u32>,
+    // [audio-thread]
+    reset: Option<unsafe extern "C" fn(plugin: *const clap_plugin)>,
+    // [thread-safe]
+    level: Option<unsafe extern "C" fn(plugin: *const clap_plugin, channel: u32) -> f32>,
+}
+
+#[derive(Copy, Clone)]
+struct PluginMeter(
+    #[allow(dead_code)] RawExtension<PluginExtensionSide, clap_plugin_example_meter>,
+);
+
+// SAFETY: clap_plugin_example_meter is the plugin-side struct matching the identifier.
+unsafe impl Extension for PluginMeter {
+    const IDENTIFIER: &'static CStr = match CStr::from_bytes_with_nul(EXAMPLE_METER_ID) {
+        Ok(id) => id,
+        Err(_) => panic!(),
+    };
+    type ExtensionSide = PluginExtensionSide;
+
+    #[inline]
+    unsafe fn from_raw(raw: RawExtension<Self::ExtensionSide>) -> Self {
+        Self(raw.cast())
+    }
+}
+
+trait PluginMeterMainThreadImpl {
+    fn channel_count(&mut self) -> u32;
+}
+
+trait PluginMeterAudioProcessorImpl {
+    fn reset_meter(&mut self);
+}
+
+trait PluginMeterSharedImpl {
+    fn level(&self, channel: u32) -> f32;
+}
+
+// SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+unsafe impl<P: Plugin> ExtensionImplementation<P> for PluginMeter
+where
+    for<'a> P::MainThread<'a>: PluginMeterMainThreadImpl,
+    for<'a> P::AudioProcessor<'a>: PluginMeterAudioProcessorImpl,
+    for<'a> P::Shared<'a>: PluginMeterSharedImpl,
+{
+    const IMPLEMENTATION: RawExtensionImplementation =
+        RawExtensionImplementation::new(&clap_plugin_example_meter {
+            channel_count: Some(channel_count::<P>),
+            reset: Some(reset::<P>),
+            level: Some(level::<P>),
+        });
+}
+
+/// # Safety
+///
+/// Must be called by the host with a valid plugin instance.
+unsafe extern "C" fn channel_count<P: Plugin>(plugin: *const clap_plugin) -> u32
+where
+    for<'a> P::MainThread<'a>: PluginMeterMainThreadImpl,
+{
+    PluginWrapper::<P>::handle_main_thread(
+        plugin,
+        "clap_plugin_example_meter.channel_count",
+        |main_thread, _, _| Ok(main_thread.channel_count()),
+    )
+    .unwrap_or(0)
+}
+
+/// # Safety
+///
+/// Must be called by the host with a valid plugin instance.
+unsafe extern "C" fn reset<P: Plugin>(plugin: *const clap_plugin)
+where
+    for<'a> P::AudioProcessor<'a>: PluginMeterAudioProcessorImpl,
+{
+    PluginWrapper::<P>::handle_audio_processor(
+        plugin,
+        "clap_plugin_example_meter.reset",
+        |audio_processor, _, _| {
+            audio_processor.reset_meter();
+            Ok(())
+        },
+    );
+}
+
+/// # Safety
+///
+/// Must be called by the host with a valid plugin instance.
+unsafe extern "C" fn level<P: Plugin>(plugin: *const clap_plugin, channel: u32) -> f32
+where
+    for<'a> P::Shared<'a>: PluginMeterSharedImpl,
+{
+    PluginWrapper::<P>::handle_shared(plugin, "clap_plugin_example_meter.level", |shared, _| {
+        Ok(shared.level(channel))
+    })
+    .unwrap_or(-1.0)
+}
+
+/// A plugin implementing the made-up meter extension.
+pub struct MeterPlugin;
+
+pub struct MeterPluginShared {
+    level: AtomicU32,
+    resets: AtomicUsize,
+}
+
+impl PluginShared<'_> for MeterPluginShared {}
+
+impl PluginMeterSharedImpl for MeterPluginShared {
+    fn level(&self, channel: u32) -> f32 {
+        if channel == 0 {
+            f32::from_bits(self.level.load(Ordering::Relaxed))
+        } else {
+            0.0
+        }
+    }
+}
+
+pub struct MeterPluginMainThread;
+
+impl PluginMainThread<'_, MeterPluginShared> for MeterPluginMainThread {}
+
+impl PluginMeterMainThreadImpl for MeterPluginMainThread {
+    fn channel_count(&mut self) -> u32 {
+        2
+    }
+}
+
+pub struct MeterPluginAudioProcessor<'a> {
+    shared: &'a MeterPluginShared,
+}
+
+impl<'a> PluginAudioProcessor<'a, MeterPluginShared, MeterPluginMainThread>
+    for MeterPluginAudioProcessor<'a>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut MeterPluginMainThread,
+        shared: &'a MeterPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        shared.level.store(0.5f32.to_bits(), Ordering::Relaxed);
+        Ok(Self { shared })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginMeterAudioProcessorImpl for MeterPluginAudioProcessor<'_> {
+    fn reset_meter(&mut self) {
+        self.shared.resets.fetch_add(1, Ordering::Relaxed);
+        self.shared.level.store(0.0f32.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl Plugin for MeterPlugin {
+    type AudioProcessor<'a> = MeterPluginAudioProcessor<'a>;
+    type Shared<'a> = MeterPluginShared;
+    type MainThread<'a> = MeterPluginMainThread;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&MeterPluginShared>,
+    ) {
+        builder.register::<PluginMeter>();
+    }
+}
+
+impl DefaultPluginFactory for MeterPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.meter", "Meter")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(MeterPluginShared {
+            level: AtomicU32::new(0.0f32.to_bits()),
+            resets: AtomicUsize::new(0),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(MeterPluginMainThread)
+    }
+}
+
+pub static METER_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<MeterPlugin>);
+
+type LogRecords = Arc<Mutex<Vec<(LogSeverity, String)>>>;
+
+struct LoggingHost;
+
+struct LoggingHostShared {
+    records: LogRecords,
+}
+
+impl SharedHandler<'_> for LoggingHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+impl HostLogImpl for LoggingHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((severity, message.to_string()));
+    }
+}
+
+impl HostHandlers for LoggingHost {
+    type Shared<'a> = LoggingHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder.register::<HostLog>();
+    }
+}
+
+#[test]
+pub fn vendor_extensions_reach_every_thread() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&METER_ENTRY, "/meter.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let records = LogRecords::default();
+    let shared_records = records.clone();
+
+    let mut instance = PluginInstance::<LoggingHost>::new(
+        move |_| LoggingHostShared {
+            records: shared_records,
+        },
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.meter\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let handle = instance.plugin_handle_unchecked();
+    let ext = handle.get_raw_extension(example_meter_id()).unwrap();
+    // SAFETY: the plugin registered this exact struct for this identifier.
+    let ext = unsafe { *ext.cast::<clap_plugin_example_meter>().as_ref() };
+    let plugin = handle.as_raw_ptr();
+
+    // SAFETY: all of these are called with the right plugin instance, on the right threads.
+    unsafe {
+        assert_eq!(ext.channel_count.unwrap()(plugin), 2);
+        assert_eq!(ext.level.unwrap()(plugin, 0), 0.0);
+
+        // The plugin isn't active: the audio processor can't be reached.
+        ext.reset.unwrap()(plugin);
+    }
+
+    assert_eq!(
+        *records.lock().unwrap(),
+        [(
+            LogSeverity::HostMisbehaving,
+            "[org.rust-audio.clack.meter] [clap_plugin_example_meter.reset] \
+            Plugin was not activated before calling an audio-thread method"
+                .to_string()
+        )]
+    );
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap();
+
+    // SAFETY: level is thread-safe.
+    let level = unsafe { ext.level.unwrap()(plugin, 0) };
+    assert_eq!(level, 0.5);
+
+    unsafe {
+        ext.reset.unwrap()(plugin);
+        assert_eq!(ext.level.unwrap()(plugin, 0), 0.0);
+    }
+
+    assert_eq!(records.lock().unwrap().len(), 1);
+    instance.deactivate_unchecked(processor);
+}
@@ -0,0 +1,139 @@
+//! Plugins can leave the optional functions of their `clap_plugin` table null.
+
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+thread_local! {
+    /// The number of audio processors that were deactivated.
+    static DEACTIVATED: Cell<u32> = const { Cell::new(0) };
+}
+
+pub struct MinimalPlugin;
+
+pub struct MinimalPluginAudioProcessor;
+
+impl Plugin for MinimalPlugin {
+    type AudioProcessor<'a> = MinimalPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    const VTABLE: PluginVTableConfig = PluginVTableConfig {
+        deactivate: false,
+        reset: false,
+        on_main_thread: false,
+        ..PluginVTableConfig::ALL
+    };
+}
+
+impl DefaultPluginFactory for MinimalPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.minimal", "Minimal")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for MinimalPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn deactivate(self, _main_thread: &mut ()) {
+        DEACTIVATED.with(|d| d.set(d.get() + 1));
+    }
+}
+
+pub static MINIMAL_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<MinimalPlugin>);
+
+struct MinimalHostShared;
+
+impl SharedHandler<'_> for MinimalHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct MinimalHost;
+
+impl HostHandlers for MinimalHost {
+    type Shared<'a> = MinimalHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+#[test]
+pub fn disabled_functions_are_null() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&MINIMAL_ENTRY, "/minimal.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<MinimalHost>::new(
+        |_| MinimalHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.minimal\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let raw = instance.raw_instance();
+    assert!(raw.deactivate.is_none());
+    assert!(raw.reset.is_none());
+    assert!(raw.on_main_thread.is_none());
+
+    assert!(raw.init.is_some());
+    assert!(raw.destroy.is_some());
+    assert!(raw.activate.is_some());
+    assert!(raw.process.is_some());
+    assert!(raw.get_extension.is_some());
+    assert!(raw.start_processing.is_some());
+    assert!(raw.stop_processing.is_some());
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: 32,
+        max_frames_count: 32,
+    };
+
+    // The host can't deactivate the plugin, so re-activating it replaces the audio processor.
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap();
+    instance.deactivate_unchecked(processor);
+    assert_eq!(DEACTIVATED.with(Cell::get), 0);
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap();
+    assert_eq!(DEACTIVATED.with(Cell::get), 1);
+    instance.deactivate_unchecked(processor);
+
+    drop(instance);
+    assert_eq!(DEACTIVATED.with(Cell::get), 2);
+}
@@ -0,0 +1,395 @@
+use clack_extensions::latency::{PluginLatency, PluginLatencyImpl};
+use clack_extensions::params::set::ParamSet;
+use clack_extensions::params::*;
+use clack_host::prelude::*;
+use clack_host::process::wet_dry::PluginWetDryWrapper;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+const LATENCY: usize = 24;
+const BLOCK_SIZE: usize = 32;
+const BYPASS_PARAM_ID: ClapId = ClapId::new(0);
+
+/// A plugin that delays its input by [`LATENCY`] samples, and halves its volume unless its bypass
+/// parameter is enabled.
+pub struct DelayPlugin;
+
+pub struct DelayPluginShared {
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for DelayPluginShared {}
+
+pub struct DelayPluginMainThread<'a> {
+    shared: &'a DelayPluginShared,
+}
+
+impl<'a> PluginMainThread<'a, DelayPluginShared> for DelayPluginMainThread<'a> {}
+
+impl PluginLatencyImpl for DelayPluginMainThread<'_> {
+    fn get(&mut self) -> u32 {
+        LATENCY as u32
+    }
+}
+
+impl PluginMainThreadParams for DelayPluginMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.shared.params.get_info(param_index, info)
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input_parameter_changes: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input_parameter_changes)
+    }
+}
+
+pub struct DelayPluginAudioProcessor<'a> {
+    shared: &'a DelayPluginShared,
+    delay_lines: [Vec<f32>; 2],
+    position: usize,
+}
+
+impl PluginAudioProcessorParams for DelayPluginAudioProcessor<'_> {
+    fn flush(&mut self, input_parameter_changes: &InputEvents, _output: &mut OutputEvents) {
+        self.shared.params.flush(input_parameter_changes)
+    }
+}
+
+impl Plugin for DelayPlugin {
+    type AudioProcessor<'a> = DelayPluginAudioProcessor<'a>;
+    type Shared<'a> = DelayPluginShared;
+    type MainThread<'a> = DelayPluginMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&DelayPluginShared>,
+    ) {
+        builder
+            .register::<PluginLatency>()
+            .register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for DelayPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.delay", "Delay")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(DelayPluginShared {
+            params: ParamSet::new().with_bypass_param(BYPASS_PARAM_ID),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(DelayPluginMainThread { shared })
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, DelayPluginShared, DelayPluginMainThread<'a>>
+    for DelayPluginAudioProcessor<'a>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut DelayPluginMainThread<'a>,
+        shared: &'a DelayPluginShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            shared,
+            delay_lines: [vec![0.0; LATENCY], vec![0.0; LATENCY]],
+            position: 0,
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.shared.params.flush(events.input);
+
+        // When bypassed, the input is still delayed, to stay aligned with the non-bypassed output.
+        let volume = if self.shared.params.is_bypassed() {
+            1.0
+        } else {
+            0.5
+        };
+
+        let mut port_pair = audio.port_pair(0).ok_or(PluginError::Message("No ports"))?;
+        let mut channels = port_pair
+            .channels()?
+            .into_f32()
+            .ok_or(PluginError::Message("Expected f32 buffers"))?;
+
+        let mut position = self.position;
+
+        for (pair, delay_line) in channels.iter_mut().zip(&mut self.delay_lines) {
+            let ChannelPair::InputOutput(input, output) = pair else {
+                return Err(PluginError::Message("Expected separate buffers"));
+            };
+
+            position = self.position;
+            for (input, output) in input.iter().zip(output.iter_mut()) {
+                *output = core::mem::replace(&mut delay_line[position], *input) * volume;
+                position = (position + 1) % LATENCY;
+            }
+        }
+
+        self.position = position;
+
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    }
+}
+
+pub static DELAY_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<DelayPlugin>);
+
+fn signal(index: usize, channel: usize) -> f32 {
+    let value = ((index * 7) % 13) as f32 - 6.0;
+
+    if channel == 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+fn dry(index: usize, channel: usize) -> f32 {
+    match index.checked_sub(LATENCY) {
+        Some(index) => signal(index, channel),
+        None => 0.0,
+    }
+}
+
+fn wet(index: usize, channel: usize) -> f32 {
+    dry(index, channel) * 0.5
+}
+
+/// Processes the given number of blocks of the test signal through a wrapped delay plugin, and
+/// returns the output and the process status of each block.
+///
+/// Before each block, `before_block` is called with the block index, the wrapper, the plugin's
+/// parameter cache, and a list of events to send to the plugin in that block.
+fn process_blocks(
+    block_count: usize,
+    mut before_block: impl FnMut(
+        usize,
+        &PluginWetDryWrapper<()>,
+        &mut ParamValueCache,
+        &mut OutputEvents,
+    ),
+) -> (Vec<[Vec<f32>; 2]>, Vec<ProcessStatus>) {
+    let bundle = unsafe { PluginBundle::load_from_raw(&DELAY_ENTRY, "/delay.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    let mut instance = PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.delay\0").unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let mut handle = instance.plugin_handle_unchecked();
+    let latency = handle
+        .get_extension::<PluginLatency>()
+        .unwrap()
+        .get(&mut handle);
+
+    assert_eq!(latency, LATENCY as u32);
+
+    let mut param_cache = ParamValueCache::new();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    param_cache.rescan(&params, &mut handle);
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE as u32,
+        max_frames_count: BLOCK_SIZE as u32,
+    };
+
+    let processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut wrapper = PluginWetDryWrapper::new(processor, configuration, 2, latency);
+
+    let mut outputs = Vec::new();
+    let mut statuses = Vec::new();
+
+    let mut events = EventBuffer::new();
+
+    for block in 0..block_count {
+        events.clear();
+        before_block(block, &wrapper, &mut param_cache, &mut events.as_output());
+
+        let start = block * BLOCK_SIZE;
+        let mut input: [Vec<f32>; 2] =
+            [0, 1].map(|c| (start..start + BLOCK_SIZE).map(|i| signal(i, c)).collect());
+        let mut output = [vec![0.0; BLOCK_SIZE], vec![0.0; BLOCK_SIZE]];
+
+        let status = wrapper
+            .process(
+                &mut input,
+                &mut output,
+                &events.as_input(),
+                &mut OutputEvents::void(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        outputs.push(output);
+        statuses.push(status);
+    }
+
+    instance.deactivate_unchecked(wrapper.into_inner().stop_processing());
+
+    (outputs, statuses)
+}
+
+fn assert_block_matches(
+    block: usize,
+    output: &[Vec<f32>; 2],
+    expected: impl Fn(usize, usize) -> f32,
+) {
+    for (channel, output) in output.iter().enumerate() {
+        for (i, sample) in output.iter().enumerate() {
+            let index = block * BLOCK_SIZE + i;
+            assert_eq!(*sample, expected(index, channel), "At sample {index}");
+        }
+    }
+}
+
+#[test]
+pub fn full_wet_mix_nulls_with_plugin_output() {
+    let (outputs, statuses) = process_blocks(4, |_, _, _, _| {});
+
+    for (block, output) in outputs.iter().enumerate() {
+        assert_block_matches(block, output, wet);
+    }
+
+    assert!(statuses
+        .iter()
+        .all(|s| *s == ProcessStatus::ContinueIfNotQuiet));
+}
+
+#[test]
+pub fn zero_mix_outputs_exact_delayed_dry_signal() {
+    let (outputs, _) = process_blocks(4, |block, wrapper, _, _| {
+        if block == 0 {
+            wrapper.set_mix(0.0);
+        }
+    });
+
+    for (block, output) in outputs.iter().enumerate() {
+        assert_block_matches(block, output, dry);
+    }
+}
+
+#[test]
+pub fn half_mix_is_aligned_with_plugin_latency() {
+    let (outputs, _) = process_blocks(4, |block, wrapper, _, _| {
+        if block == 0 {
+            wrapper.set_mix(0.5);
+        }
+    });
+
+    for (block, output) in outputs.iter().enumerate() {
+        assert_block_matches(block, output, |i, c| dry(i, c) * 0.75);
+    }
+}
+
+#[test]
+pub fn bypass_ramps_to_dry_signal() {
+    // The ramp lasts 480 samples at 48kHz, i.e. 15 blocks.
+    let (outputs, statuses) = process_blocks(20, |block, wrapper, _, _| {
+        if block == 1 {
+            wrapper.handle().set_bypassed(true);
+        }
+    });
+
+    assert_block_matches(0, &outputs[0], wet);
+    assert_eq!(statuses[0], ProcessStatus::ContinueIfNotQuiet);
+
+    // Halfway through the ramp, the output is somewhere between wet and dry.
+    let index = 8 * BLOCK_SIZE;
+    let (dry, wet) = (dry(index, 0), wet(index, 0));
+    let sample = outputs[8][0][0];
+    assert!(sample > dry.min(wet) && sample < dry.max(wet));
+    assert_eq!(statuses[8], ProcessStatus::Continue);
+
+    for (block, output) in outputs.iter().enumerate().skip(17) {
+        assert_block_matches(block, output, self::dry);
+        assert_eq!(statuses[block], ProcessStatus::ContinueIfNotQuiet);
+    }
+}
+
+#[test]
+pub fn plugin_bypass_param_is_detected() {
+    process_blocks(1, |_, _, param_cache, _| {
+        assert_eq!(param_cache.bypass_param_id(), Some(BYPASS_PARAM_ID));
+        assert!(param_cache
+            .get(BYPASS_PARAM_ID)
+            .unwrap()
+            .flags
+            .contains(ParamInfoFlags::IS_BYPASS | ParamInfoFlags::IS_STEPPED));
+        assert!(!param_cache.is_bypassed());
+    });
+}
+
+#[test]
+pub fn plugin_bypass_and_wrapper_bypass_do_not_double_bypass() {
+    let (outputs, _) = process_blocks(24, |block, wrapper, param_cache, events| {
+        match block {
+            // Bypass the plugin itself first, through its bypass parameter.
+            0 => assert!(param_cache.set_bypassed(true, events).unwrap()),
+            // Then also bypass the wrapper, while the plugin is still bypassed.
+            2 => wrapper.set_bypassed(true),
+            // Releasing the wrapper's bypass only doesn't un-bypass the plugin.
+            20 => wrapper.set_bypassed(false),
+            _ => {}
+        }
+    });
+
+    // Since the plugin's bypassed output is aligned with the wrapper's dry signal, the output
+    // stays the same no matter which bypass is active, save for rounding errors during the ramps.
+    for (block, output) in outputs.iter().enumerate() {
+        for (channel, output) in output.iter().enumerate() {
+            for (i, sample) in output.iter().enumerate() {
+                let index = block * BLOCK_SIZE + i;
+                let expected = dry(index, channel);
+                assert!((sample - expected).abs() < 1e-5, "At sample {index}");
+            }
+        }
+    }
+}
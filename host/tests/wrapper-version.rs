@@ -0,0 +1,120 @@
+//! Plugin instances refuse to be driven through a `plugin_data` pointer created by an incompatible
+//! version of Clack.
+
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::prelude::*;
+use std::cell::Cell;
+use std::ffi::CStr;
+
+thread_local! {
+    static CALLBACKS: Cell<u32> = const { Cell::new(0) };
+}
+
+pub struct VersionedPlugin;
+
+pub struct VersionedPluginMainThread;
+
+impl PluginMainThread<'_, ()> for VersionedPluginMainThread {
+    fn on_main_thread(&mut self) {
+        CALLBACKS.with(|c| c.set(c.get() + 1));
+    }
+}
+
+impl Plugin for VersionedPlugin {
+    type AudioProcessor<'a> = ();
+    type Shared<'a> = ();
+    type MainThread<'a> = VersionedPluginMainThread;
+}
+
+impl DefaultPluginFactory for VersionedPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new("org.rust-audio.clack.versioned", "Versioned")
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(VersionedPluginMainThread)
+    }
+}
+
+pub static VERSIONED_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<VersionedPlugin>);
+
+struct VersionedHostShared;
+
+impl SharedHandler<'_> for VersionedHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct VersionedHost;
+
+impl HostHandlers for VersionedHost {
+    type Shared<'a> = VersionedHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+fn instantiate() -> PluginInstance<VersionedHost> {
+    let bundle =
+        unsafe { PluginBundle::load_from_raw(&VERSIONED_ENTRY, "/versioned.clap").unwrap() };
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+
+    PluginInstance::<VersionedHost>::new(
+        |_| VersionedHostShared,
+        |_| (),
+        &bundle,
+        CStr::from_bytes_with_nul(b"org.rust-audio.clack.versioned\0").unwrap(),
+        &host_info,
+    )
+    .unwrap()
+}
+
+/// Flips a byte of the `plugin_data` header at the given offset, then calls the plugin while it is
+/// corrupted.
+fn call_with_corrupted_header(instance: &mut PluginInstance<VersionedHost>, offset: usize) {
+    let raw = instance.raw_instance();
+    let header = raw.plugin_data.cast::<u8>();
+
+    CALLBACKS.with(|c| c.set(0));
+
+    // SAFETY: the header is at least 12 bytes long, and is restored before the instance is used
+    // through Clack again.
+    unsafe {
+        *header.add(offset) ^= 0xFF;
+
+        raw.on_main_thread.unwrap()(raw);
+        assert!(!raw.activate.unwrap()(raw, 48_000.0, 32, 32));
+        // Not freeing an instance with an unknown layout is the only safe option.
+        raw.destroy.unwrap()(raw);
+
+        assert!(!raw.plugin_data.is_null());
+        assert_eq!(CALLBACKS.with(Cell::get), 0);
+
+        *header.add(offset) ^= 0xFF;
+    }
+
+    // The instance is usable again once the header is restored.
+    instance.call_on_main_thread_callback_unchecked();
+    assert_eq!(CALLBACKS.with(Cell::get), 1);
+}
+
+#[test]
+pub fn corrupted_magic_is_rejected() {
+    let mut instance = instantiate();
+    call_with_corrupted_header(&mut instance, 0);
+}
+
+#[test]
+pub fn mismatched_layout_version_is_rejected() {
+    let mut instance = instantiate();
+    call_with_corrupted_header(&mut instance, 8);
+}
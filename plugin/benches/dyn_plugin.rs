@@ -0,0 +1,232 @@
+//! Compares the per-block processing overhead of a statically dispatched plugin with the same
+//! plugin wrapped in a [`DynPlugin`].
+//!
+//! Run with `cargo bench -p clack-plugin --bench dyn_plugin`.
+
+use clack_host::prelude::*;
+use clack_plugin::entry::{Entry, EntryFactories, EntryLoadError};
+use clack_plugin::factory::plugin::{PluginFactory, PluginFactoryWrapper};
+use clack_plugin::plugin::dynamic::*;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+use std::hint::black_box;
+use std::time::Instant;
+
+const BLOCK_SIZE: usize = 64;
+const ITERATIONS: u32 = 1_000_000;
+
+fn apply_gain(audio: &mut Audio) -> Result<ProcessStatus, PluginError> {
+    let mut port_pair = audio.port_pair(0).ok_or(PluginError::Message("No ports"))?;
+    let mut channels = port_pair
+        .channels()?
+        .into_f32()
+        .ok_or(PluginError::Message("Expected f32 buffers"))?;
+
+    for pair in channels.iter_mut() {
+        if let ChannelPair::InputOutput(input, output) = pair {
+            for (input, output) in input.iter().zip(output.iter_mut()) {
+                *output = *input * 0.5;
+            }
+        }
+    }
+
+    Ok(ProcessStatus::Continue)
+}
+
+pub struct StaticGain;
+
+impl Plugin for StaticGain {
+    type AudioProcessor<'a> = StaticGainProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+pub struct StaticGainProcessor;
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for StaticGainProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self)
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        apply_gain(&mut audio)
+    }
+}
+
+struct DynGainShared;
+
+impl PluginSharedDyn<'_> for DynGainShared {}
+
+struct DynGainMainThread;
+
+impl<'a> PluginMainThreadDyn<'a> for DynGainMainThread {
+    fn activate(
+        &mut self,
+        _host: HostAudioProcessorHandle<'a>,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Box<dyn PluginAudioProcessorDyn<'a>>, PluginError> {
+        Ok(Box::new(DynGainProcessor))
+    }
+}
+
+struct DynGainProcessor;
+
+impl PluginAudioProcessorDyn<'_> for DynGainProcessor {
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        apply_gain(&mut audio)
+    }
+}
+
+pub struct BenchEntry {
+    plugin_factory: PluginFactoryWrapper<BenchFactory>,
+}
+
+impl Entry for BenchEntry {
+    fn new(_plugin_path: &CStr) -> Result<Self, EntryLoadError> {
+        Ok(Self {
+            plugin_factory: PluginFactoryWrapper::new(BenchFactory {
+                descriptors: [
+                    PluginDescriptor::new("org.rust-audio.clack.static-gain", "Static Gain"),
+                    PluginDescriptor::new("org.rust-audio.clack.dyn-gain", "Dyn Gain"),
+                ],
+            }),
+        })
+    }
+
+    fn declare_factories<'a>(&'a self, builder: &mut EntryFactories<'a>) {
+        builder.register_factory(&self.plugin_factory);
+    }
+}
+
+struct BenchFactory {
+    descriptors: [PluginDescriptor; 2],
+}
+
+impl PluginFactory for BenchFactory {
+    fn plugin_count(&self) -> u32 {
+        self.descriptors.len() as u32
+    }
+
+    fn plugin_descriptor(&self, index: u32) -> Option<&PluginDescriptor> {
+        self.descriptors.get(index as usize)
+    }
+
+    fn create_plugin<'a>(
+        &'a self,
+        host_info: clack_plugin::host::HostInfo<'a>,
+        plugin_id: &CStr,
+    ) -> Option<clack_plugin::plugin::PluginInstance<'a>> {
+        if plugin_id == self.descriptors[0].id() {
+            Some(clack_plugin::plugin::PluginInstance::new::<StaticGain>(
+                host_info,
+                &self.descriptors[0],
+                |_| Ok(()),
+                |_, _| Ok(()),
+            ))
+        } else if plugin_id == self.descriptors[1].id() {
+            Some(clack_plugin::plugin::PluginInstance::new::<DynPlugin>(
+                host_info,
+                &self.descriptors[1],
+                |_| Ok(DynPluginShared::new(Box::new(DynGainShared))),
+                |_, _| Ok(DynPluginMainThread::new(Box::new(DynGainMainThread))),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+static BENCH_ENTRY: EntryDescriptor = clack_plugin::clack_entry!(BenchEntry);
+
+/// Processes [`ITERATIONS`] blocks with the given plugin, and returns the average time per block.
+fn bench(bundle: &PluginBundle, plugin_id: &[u8]) -> f64 {
+    let host_info =
+        HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+    let mut instance = PluginInstance::<()>::new(
+        |_| (),
+        |_| (),
+        bundle,
+        CStr::from_bytes_with_nul(plugin_id).unwrap(),
+        &host_info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 48_000.0,
+        min_frames_count: BLOCK_SIZE as u32,
+        max_frames_count: BLOCK_SIZE as u32,
+    };
+
+    let mut processor = instance
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut input = [[0.25f32; BLOCK_SIZE]; 2];
+    let mut output = [[0.0f32; BLOCK_SIZE]; 2];
+    let mut input_ports = AudioPorts::with_capacity(2, 1);
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        let [left_in, right_in] = &mut input;
+        let [left_out, right_out] = &mut output;
+
+        let status = processor.process(
+            &input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only([
+                    InputChannel::variable(left_in),
+                    InputChannel::variable(right_in),
+                ]),
+                latency: 0,
+            }]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([&mut left_out[..], right_out]),
+                latency: 0,
+            }]),
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        );
+
+        black_box(status.unwrap());
+    }
+
+    let elapsed = start.elapsed();
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    elapsed.as_nanos() as f64 / ITERATIONS as f64
+}
+
+fn main() {
+    let bundle = unsafe { PluginBundle::load_from_raw(&BENCH_ENTRY, "/bench.clap").unwrap() };
+
+    let static_ns = bench(&bundle, b"org.rust-audio.clack.static-gain\0");
+    let dyn_ns = bench(&bundle, b"org.rust-audio.clack.dyn-gain\0");
+
+    println!("static plugin: {static_ns:>8.1} ns/block");
+    println!("dyn plugin:    {dyn_ns:>8.1} ns/block");
+    println!(
+        "overhead:      {:>8.1} ns/block ({:+.1}%)",
+        dyn_ns - static_ns,
+        (dyn_ns / static_ns - 1.0) * 100.0
+    );
+}
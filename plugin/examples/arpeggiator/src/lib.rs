@@ -0,0 +1,271 @@
+#![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs, clippy::missing_docs_in_private_items, unsafe_code)]
+
+use crate::scheduler::{
+    EventScheduler, JumpPolicy, PendingEvents, ScheduledEvent, TimelineSegment,
+};
+use clack_extensions::prelude::*;
+use clack_plugin::prelude::*;
+use clack_plugin::utils::BeatTime;
+
+pub mod scheduler;
+
+/// The length of a single arpeggiator step, in beats: a sixteenth note.
+pub const STEP: BeatTime = BeatTime::from_ratio(1, 4);
+
+/// How long each arpeggiated note is held, in beats: half a step.
+pub const GATE: BeatTime = BeatTime::from_ratio(1, 8);
+
+/// The maximum number of notes waiting to be emitted.
+const SCHEDULER_CAPACITY: usize = 256;
+
+/// The maximum number of notes that can be held at once.
+const MAX_HELD_NOTES: usize = 32;
+
+/// The type that represents our plugin in Clack.
+///
+/// This is what implements the [`Plugin`] trait, and where all the other subtypes are attached.
+pub struct ArpeggiatorPlugin;
+
+impl Plugin for ArpeggiatorPlugin {
+    type AudioProcessor<'a> = ArpeggiatorAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ArpeggiatorMainThread;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<PluginNotePorts>();
+    }
+}
+
+impl DefaultPluginFactory for ArpeggiatorPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        use features::*;
+
+        PluginDescriptor::new(
+            "org.rust-audio.clack.arpeggiator",
+            "Clack Arpeggiator Example",
+        )
+        .with_features([NOTE_EFFECT])
+    }
+
+    fn new_shared(_host: HostSharedHandle) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a (),
+    ) -> Result<ArpeggiatorMainThread, PluginError> {
+        Ok(ArpeggiatorMainThread)
+    }
+}
+
+/// A note event emitted by the arpeggiator.
+#[derive(Copy, Clone, Debug)]
+pub enum ArpeggiatorEvent {
+    /// A note starts.
+    NoteOn(NoteOnEvent),
+    /// A note ends.
+    NoteOff(NoteOffEvent),
+}
+
+impl ScheduledEvent for ArpeggiatorEvent {
+    fn at_frame(self, frame: u32) -> Self {
+        match self {
+            Self::NoteOn(e) => Self::NoteOn(e.with_time(frame)),
+            Self::NoteOff(e) => Self::NoteOff(e.with_time(frame)),
+        }
+    }
+
+    fn as_unknown_event(&self) -> &UnknownEvent {
+        match self {
+            Self::NoteOn(e) => e.as_unknown(),
+            Self::NoteOff(e) => e.as_unknown(),
+        }
+    }
+}
+
+/// A note currently held by the user.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct HeldNote {
+    /// The note's channel.
+    channel: u16,
+    /// The note's key.
+    key: u16,
+}
+
+/// Our plugin's audio processor. It lives in the audio thread.
+///
+/// It keeps track of the notes the user is holding, and plays them one at a time, in ascending
+/// order, on every step.
+pub struct ArpeggiatorAudioProcessor {
+    /// The scheduler that emits the arpeggiated notes at the right time.
+    scheduler: EventScheduler<ArpeggiatorEvent>,
+    /// The notes currently held, sorted by key.
+    held: Vec<HeldNote>,
+    /// The position of the next step to play, if the transport was playing.
+    next_step: Option<BeatTime>,
+    /// The index of the next held note to play.
+    next_note: usize,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ArpeggiatorMainThread> for ArpeggiatorAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut ArpeggiatorMainThread,
+        _shared: &'a (),
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        // We only schedule notes from the audio thread: the sender isn't needed.
+        let (scheduler, _sender) = EventScheduler::new(
+            audio_config.sample_rate,
+            SCHEDULER_CAPACITY,
+            // Pending note off events are moved along with the playhead, so that no note is
+            // left hanging when the transport jumps.
+            JumpPolicy::Remap,
+        );
+
+        Ok(Self {
+            scheduler,
+            held: Vec::with_capacity(MAX_HELD_NOTES),
+            next_step: None,
+            next_note: 0,
+        })
+    }
+
+    fn process(
+        &mut self,
+        process: Process,
+        audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        // Held notes are only updated once per block: the arpeggiator picks them up on its next
+        // step anyway.
+        for event in events.input {
+            self.handle_event(event);
+        }
+
+        let Self {
+            scheduler,
+            held,
+            next_step,
+            next_note,
+        } = self;
+
+        scheduler.process(
+            process.transport,
+            events.input,
+            audio.frames_count(),
+            events.output,
+            |segment, pending| Self::schedule_steps(held, next_step, next_note, segment, pending),
+        );
+
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn reset(&mut self) {
+        self.next_step = None;
+        self.scheduler.reset_position();
+    }
+}
+
+impl ArpeggiatorAudioProcessor {
+    /// Handles an incoming event.
+    fn handle_event(&mut self, event: &UnknownEvent) {
+        match event.as_core_event() {
+            Some(CoreEventSpace::NoteOn(event)) => {
+                let pckn = event.pckn();
+                let (Some(&channel), Some(&key)) =
+                    (pckn.channel.as_specific(), pckn.key.as_specific())
+                else {
+                    return;
+                };
+
+                let note = HeldNote { channel, key };
+                if self.held.len() < MAX_HELD_NOTES && !self.held.contains(&note) {
+                    let index = self.held.partition_point(|n| n.key <= key);
+                    self.held.insert(index, note);
+                }
+            }
+            Some(CoreEventSpace::NoteOff(event)) => {
+                let pckn = event.pckn();
+                self.held
+                    .retain(|n| !(pckn.channel.matches(n.channel) && pckn.key.matches(n.key)));
+            }
+            _ => {}
+        }
+    }
+
+    /// Schedules all the steps that start within the given segment.
+    fn schedule_steps(
+        held: &[HeldNote],
+        next_step: &mut Option<BeatTime>,
+        next_note: &mut usize,
+        segment: &TimelineSegment,
+        pending: &mut PendingEvents<ArpeggiatorEvent>,
+    ) {
+        let step = match *next_step {
+            Some(step) if !segment.jumped => step,
+            // Start again from the first step after the playhead.
+            _ => first_step_from(segment.beats.start),
+        };
+
+        let mut step = step;
+        while step < segment.beats.end {
+            if !held.is_empty() {
+                let note = held[*next_note % held.len()];
+                let pckn = Pckn::new(0u16, note.channel, note.key, Match::All);
+
+                pending.schedule(
+                    step,
+                    ArpeggiatorEvent::NoteOn(NoteOnEvent::new(0, pckn, 1.0)),
+                );
+                pending.schedule(
+                    step + GATE,
+                    ArpeggiatorEvent::NoteOff(NoteOffEvent::new(0, pckn, 1.0)),
+                );
+
+                *next_note = (*next_note + 1) % held.len();
+            }
+
+            step += STEP;
+        }
+
+        *next_step = Some(step);
+    }
+}
+
+/// Returns the position of the first step at or after the given position.
+fn first_step_from(position: BeatTime) -> BeatTime {
+    let step = STEP.to_bits();
+    let steps = (position.to_bits() + step - 1).div_euclid(step);
+
+    BeatTime::from_bits(steps * step)
+}
+
+/// The data that belongs to the main thread of our plugin.
+pub struct ArpeggiatorMainThread;
+
+impl PluginMainThread<'_, ()> for ArpeggiatorMainThread {}
+
+impl PluginNotePortsImpl for ArpeggiatorMainThread {
+    fn count(&mut self, _is_input: bool) -> u32 {
+        1
+    }
+
+    fn get(&mut self, index: u32, is_input: bool, writer: &mut NotePortInfoWriter) {
+        if index != 0 {
+            return;
+        }
+
+        writer.set(&NotePortInfo {
+            id: ClapId::new(if is_input { 1 } else { 2 }),
+            name: if is_input { b"notes in" } else { b"notes out" },
+            preferred_dialect: Some(NoteDialect::Clap),
+            supported_dialects: NoteDialects::CLAP,
+        })
+    }
+}
+
+clack_export_entry!(SinglePluginEntry<ArpeggiatorPlugin>);
@@ -0,0 +1,549 @@
+//! A helper to schedule events at musical positions, and emit them at the right frame of the
+//! right block.
+//!
+//! See the [`EventScheduler`] type for more information.
+
+use clack_plugin::events::event_types::{TransportEvent, TransportFlags};
+use clack_plugin::events::io::{InputEvents, OutputEvents};
+use clack_plugin::events::{Event, UnknownEvent};
+use clack_plugin::utils::{BeatTime, Timebase};
+use std::ops::Range;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+
+/// An event that can be scheduled by an [`EventScheduler`].
+///
+/// This is implemented for all the standard event types. Plugins that need to schedule events of
+/// different types (e.g. both note on and note off events) can implement it on an `enum` of
+/// them.
+pub trait ScheduledEvent: Copy + Send + 'static {
+    /// Returns this event, with its time set to the given frame offset in the current block.
+    fn at_frame(self, frame: u32) -> Self;
+
+    /// Returns this event as an [`UnknownEvent`], to be pushed to the output events.
+    fn as_unknown_event(&self) -> &UnknownEvent;
+}
+
+impl<E: Event + Copy + Send> ScheduledEvent for E {
+    #[inline]
+    fn at_frame(self, frame: u32) -> Self {
+        self.with_time(frame)
+    }
+
+    #[inline]
+    fn as_unknown_event(&self) -> &UnknownEvent {
+        self.as_unknown()
+    }
+}
+
+/// What an [`EventScheduler`] does with its pending events when the transport jumps to another
+/// position, e.g. when the user relocates the playhead, or when a loop wraps around.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum JumpPolicy {
+    /// All pending events are dropped. The plugin is expected to schedule new events from the new
+    /// position.
+    Flush,
+    /// All pending events are moved by the same amount as the playhead, so that they keep their
+    /// distance to it. For instance, events scheduled past the end of a loop are moved back to
+    /// its start when it wraps around.
+    Remap,
+}
+
+/// A contiguous part of a block, during which the transport is playing and its tempo doesn't
+/// change.
+///
+/// Blocks are split in multiple segments when the host sends transport events in the middle of
+/// them, e.g. on tempo changes or when a loop wraps around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimelineSegment {
+    /// The frames of the block this segment covers.
+    pub frames: Range<u32>,
+    /// The song positions this segment covers, in beats.
+    pub beats: Range<BeatTime>,
+    /// Whether the transport jumped at the start of this segment.
+    ///
+    /// The scheduler's [`JumpPolicy`] has already been applied to the pending events when
+    /// this is set.
+    pub jumped: bool,
+}
+
+/// The events waiting to be emitted by an [`EventScheduler`], sorted by song position.
+pub struct PendingEvents<E> {
+    /// The pending events, along with their song position.
+    events: Vec<(BeatTime, E)>,
+    /// The maximum number of pending events.
+    capacity: usize,
+    /// The number of events that were dropped because there was no room left for them.
+    dropped: u64,
+}
+
+impl<E: ScheduledEvent> PendingEvents<E> {
+    /// Schedules an event to be emitted at the given song position, in beats.
+    ///
+    /// Events scheduled at the same position are emitted in the order they were scheduled.
+    /// Events scheduled at a position that was already played are emitted at the start of the
+    /// next processed segment.
+    ///
+    /// If there is no room left for this event, it is dropped, and this returns `false`.
+    pub fn schedule(&mut self, position: BeatTime, event: E) -> bool {
+        if self.events.len() >= self.capacity {
+            self.dropped += 1;
+            return false;
+        }
+
+        let index = self.events.partition_point(|(p, _)| *p <= position);
+        self.events.insert(index, (position, event));
+        true
+    }
+
+    /// Returns the number of pending events.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if there are no pending events.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Applies the given jump policy, for a jump of the given distance.
+    fn apply_jump(&mut self, policy: JumpPolicy, distance: BeatTime) {
+        match policy {
+            JumpPolicy::Flush => self.events.clear(),
+            JumpPolicy::Remap => {
+                for (position, _) in &mut self.events {
+                    *position += distance;
+                }
+            }
+        }
+    }
+}
+
+/// A handle to schedule events on an [`EventScheduler`] from any thread, e.g. from the plugin's
+/// main thread.
+///
+/// Events are sent through a bounded channel, using non-blocking operations only. They are
+/// picked up by the scheduler at the start of the next processed block.
+#[derive(Clone)]
+pub struct ScheduleSender<E> {
+    /// The sending side of the scheduler's queue.
+    sender: SyncSender<(BeatTime, E)>,
+}
+
+impl<E: ScheduledEvent> ScheduleSender<E> {
+    /// Schedules an event to be emitted at the given song position, in beats.
+    ///
+    /// If the queue is full, or if the scheduler was dropped, the event is dropped, and this
+    /// returns `false`.
+    #[inline]
+    pub fn schedule(&self, position: BeatTime, event: E) -> bool {
+        self.sender.try_send((position, event)).is_ok()
+    }
+}
+
+/// Emits events scheduled at musical positions, at the right frame of the right block.
+///
+/// Events can be scheduled at any song position (in beats), either from the audio thread with
+/// [`schedule`](Self::schedule), or from any other thread through the [`ScheduleSender`] that
+/// is returned along with the scheduler.
+///
+/// In every process call, [`process`](Self::process) follows the transport to emit all the
+/// events that are due in the block, with their frame offset computed from the tempo. Blocks are
+/// split into [`TimelineSegment`]s wherever the host sends a transport event in the middle of
+/// them, so that tempo changes and loops are honored with frame accuracy.
+///
+/// The scheduler handles the following transport changes:
+///
+/// * **Jumps and loops:** if the song position at the start of a segment doesn't match the end of
+///   the previous one, the pending events are either dropped or moved, according to the
+///   scheduler's [`JumpPolicy`].
+/// * **Stops:** while the transport is stopped (or if the host doesn't provide a beat timeline),
+///   nothing is emitted, and all pending events are held until playback resumes.
+/// * **Tempo changes:** each segment uses its own tempo and tempo increment.
+///
+/// # Example
+///
+/// ```
+/// use clack_plugin::events::event_types::NoteOnEvent;
+/// use clack_plugin::events::Pckn;
+/// use clack_plugin::utils::BeatTime;
+/// use clack_plugin_arpeggiator::scheduler::{EventScheduler, JumpPolicy};
+///
+/// let (mut scheduler, sender) = EventScheduler::new(48_000.0, 64, JumpPolicy::Remap);
+/// let note = NoteOnEvent::new(0, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0);
+///
+/// // From the audio thread:
+/// scheduler.schedule(BeatTime::from_int(4), note);
+///
+/// // From any other thread:
+/// std::thread::spawn(move || sender.schedule(BeatTime::from_int(8), note))
+///     .join()
+///     .unwrap();
+/// ```
+pub struct EventScheduler<E> {
+    /// The events waiting to be emitted.
+    pending: PendingEvents<E>,
+    /// The receiving side of the queue of events scheduled from other threads.
+    receiver: Receiver<(BeatTime, E)>,
+    /// The current sample rate, in Hertz.
+    sample_rate: f64,
+    /// What to do with the pending events when the transport jumps.
+    policy: JumpPolicy,
+    /// The song position at the end of the last segment that was played, if any.
+    expected_position: Option<BeatTime>,
+}
+
+impl<E: ScheduledEvent> EventScheduler<E> {
+    /// Creates a new scheduler, and its matching [`ScheduleSender`].
+    ///
+    /// The scheduler can hold up to `capacity` pending events, and the queue of the sender can
+    /// hold up to `capacity` events that have not been picked up by the scheduler yet. Both are
+    /// allocated upfront.
+    pub fn new(sample_rate: f64, capacity: usize, policy: JumpPolicy) -> (Self, ScheduleSender<E>) {
+        let (sender, receiver) = sync_channel(capacity.max(1));
+
+        let scheduler = Self {
+            pending: PendingEvents {
+                events: Vec::with_capacity(capacity),
+                capacity,
+                dropped: 0,
+            },
+            receiver,
+            sample_rate,
+            policy,
+            expected_position: None,
+        };
+
+        (scheduler, ScheduleSender { sender })
+    }
+
+    /// Schedules an event to be emitted at the given song position, in beats.
+    ///
+    /// See [`PendingEvents::schedule`].
+    #[inline]
+    pub fn schedule(&mut self, position: BeatTime, event: E) -> bool {
+        self.pending.schedule(position, event)
+    }
+
+    /// Returns the events waiting to be emitted.
+    #[inline]
+    pub fn pending(&self) -> &PendingEvents<E> {
+        &self.pending
+    }
+
+    /// Returns the number of events that were dropped, either because there was no room left to
+    /// schedule them, or because the output event list was full.
+    ///
+    /// Events dropped by the [`JumpPolicy::Flush`] policy are not counted.
+    #[inline]
+    pub fn dropped_count(&self) -> u64 {
+        self.pending.dropped
+    }
+
+    /// Forgets the song position of the last played block, so that the next one is never
+    /// considered as a jump.
+    ///
+    /// This should be called e.g. when the plugin is reset.
+    #[inline]
+    pub fn reset_position(&mut self) {
+        self.expected_position = None;
+    }
+
+    /// Emits all the events that are due in the current block to `output`.
+    ///
+    /// `transport` is the transport at the start of the block, and `input` the block's input
+    /// events, from which transport changes happening in the middle of the block are read.
+    ///
+    /// Before emitting the events of each segment of the block, `on_segment` is called with that
+    /// segment and the pending events, which allows the plugin to schedule events just in time
+    /// (e.g. all the events that fall within the segment). It is only called for segments where
+    /// the transport is playing.
+    pub fn process(
+        &mut self,
+        transport: Option<&TransportEvent>,
+        input: &InputEvents,
+        frames_count: u32,
+        output: &mut OutputEvents,
+        mut on_segment: impl FnMut(&TimelineSegment, &mut PendingEvents<E>),
+    ) {
+        while let Ok((position, event)) = self.receiver.try_recv() {
+            self.pending.schedule(position, event);
+        }
+
+        let changes = input
+            .iter()
+            .filter_map(|e| e.as_event::<TransportEvent>())
+            .map(|t| (t.header.time().min(frames_count), Some(*t)));
+
+        let mut current = transport.copied();
+        let mut start = 0;
+
+        for (time, next) in changes.chain(Some((frames_count, None))) {
+            if time > start {
+                self.process_segment(current.as_ref(), start..time, output, &mut on_segment);
+                start = time;
+            }
+
+            if let Some(next) = next {
+                current = Some(next);
+            }
+        }
+    }
+
+    /// Emits the events that are due in a single segment, using the given transport.
+    fn process_segment(
+        &mut self,
+        transport: Option<&TransportEvent>,
+        frames: Range<u32>,
+        output: &mut OutputEvents,
+        on_segment: &mut impl FnMut(&TimelineSegment, &mut PendingEvents<E>),
+    ) {
+        if !transport.is_some_and(|t| t.flags.contains(TransportFlags::IS_PLAYING)) {
+            return;
+        }
+
+        let timebase = Timebase::new(self.sample_rate, transport);
+        let length = frames.end - frames.start;
+
+        let (Some(start), Some(end)) = (
+            timebase.beat_position_at(0),
+            timebase.beat_position_at(length),
+        ) else {
+            return;
+        };
+
+        // A difference of less than a sample is only a rounding error.
+        let tolerance = timebase.samples_to_beats(1.0).unwrap_or(0.0);
+        let jump = self
+            .expected_position
+            .map(|expected| start - expected)
+            .filter(|distance| distance.to_float().abs() > tolerance);
+
+        if let Some(distance) = jump {
+            self.pending.apply_jump(self.policy, distance);
+        }
+
+        self.expected_position = Some(end);
+
+        let segment = TimelineSegment {
+            frames: frames.clone(),
+            beats: start..end,
+            jumped: jump.is_some(),
+        };
+
+        on_segment(&segment, &mut self.pending);
+
+        let mut due = 0;
+        for (position, event) in &self.pending.events {
+            // Events that should have been played already are emitted right away.
+            let Some(offset) = timebase.beats_to_samples((*position - start).to_float()) else {
+                break;
+            };
+
+            let offset = offset.round().max(0.0);
+            if offset >= length as f64 {
+                break;
+            }
+
+            let event = event.at_frame(frames.start + offset as u32);
+            if output.try_push(event.as_unknown_event()).is_err() {
+                self.pending.dropped += 1;
+            }
+
+            due += 1;
+        }
+
+        self.pending.events.drain(..due);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_plugin::events::event_types::NoteOnEvent;
+    use clack_plugin::events::io::EventBuffer;
+    use clack_plugin::events::{EventFlags, EventHeader, Pckn};
+    use clack_plugin::utils::SecondsTime;
+
+    /// Returns a playing transport at the given position and tempo.
+    fn transport(time: u32, position: f64, tempo: f64) -> TransportEvent {
+        TransportEvent {
+            header: EventHeader::new_core(time, EventFlags::empty()),
+            flags: TransportFlags::IS_PLAYING
+                | TransportFlags::HAS_TEMPO
+                | TransportFlags::HAS_BEATS_TIMELINE,
+            song_pos_beats: BeatTime::from_float(position),
+            song_pos_seconds: SecondsTime::from_int(0),
+            tempo,
+            tempo_inc: 0.0,
+            loop_start_beats: BeatTime::from_int(0),
+            loop_end_beats: BeatTime::from_int(0),
+            loop_start_seconds: SecondsTime::from_int(0),
+            loop_end_seconds: SecondsTime::from_int(0),
+            bar_start: BeatTime::from_int(0),
+            bar_number: 0,
+            time_signature_numerator: 4,
+            time_signature_denominator: 4,
+        }
+    }
+
+    /// Returns a note on event for the given key.
+    fn note(key: u16) -> NoteOnEvent {
+        NoteOnEvent::new(0, Pckn::new(0u16, 0u16, key, 0u32), 1.0)
+    }
+
+    /// Processes a block, and returns the emitted events as (time, key) pairs.
+    fn process(
+        scheduler: &mut EventScheduler<NoteOnEvent>,
+        transport: Option<&TransportEvent>,
+        input: &EventBuffer,
+        frames_count: u32,
+    ) -> Vec<(u32, u16)> {
+        let mut output = EventBuffer::new();
+        scheduler.process(
+            transport,
+            &input.as_input(),
+            frames_count,
+            &mut output.as_output(),
+            |_, _| {},
+        );
+
+        output
+            .iter()
+            .map(|e| {
+                let e = e.as_event::<NoteOnEvent>().unwrap();
+                (e.time(), *e.pckn().key.as_specific().unwrap())
+            })
+            .collect()
+    }
+
+    // At 120 BPM and 48kHz, a beat lasts 24000 frames.
+
+    #[test]
+    fn events_are_emitted_at_their_frame() {
+        let (mut scheduler, _) = EventScheduler::new(48_000.0, 16, JumpPolicy::Flush);
+        scheduler.schedule(BeatTime::from_float(0.25), note(61));
+        scheduler.schedule(BeatTime::from_float(0.5), note(62));
+        scheduler.schedule(BeatTime::from_int(1), note(63));
+
+        let empty = EventBuffer::new();
+        let block = |position| transport(0, position, 120.0);
+
+        assert_eq!(
+            process(&mut scheduler, Some(&block(0.0)), &empty, 8192),
+            [(6000, 61)]
+        );
+        assert_eq!(
+            process(&mut scheduler, Some(&block(8192.0 / 24000.0)), &empty, 8192),
+            [(12000 - 8192, 62)]
+        );
+        assert_eq!(
+            process(
+                &mut scheduler,
+                Some(&block(16384.0 / 24000.0)),
+                &empty,
+                8192
+            ),
+            [(24000 - 16384, 63)]
+        );
+        assert!(scheduler.pending().is_empty());
+    }
+
+    #[test]
+    fn mid_block_tempo_changes_are_honored() {
+        let (mut scheduler, _) = EventScheduler::new(48_000.0, 16, JumpPolicy::Flush);
+        scheduler.schedule(BeatTime::from_float(0.25), note(61));
+        scheduler.schedule(BeatTime::from_float(0.75), note(62));
+
+        // The tempo doubles after half a beat.
+        let mut input = EventBuffer::new();
+        input.push(&transport(12000, 0.5, 240.0));
+
+        assert_eq!(
+            process(
+                &mut scheduler,
+                Some(&transport(0, 0.0, 120.0)),
+                &input,
+                24000
+            ),
+            [(6000, 61), (12000 + 3000, 62)]
+        );
+    }
+
+    #[test]
+    fn stopped_transport_holds_events() {
+        let (mut scheduler, sender) = EventScheduler::new(48_000.0, 16, JumpPolicy::Flush);
+        assert!(sender.schedule(BeatTime::from_float(0.25), note(61)));
+
+        let empty = EventBuffer::new();
+        let mut stopped = transport(0, 0.0, 120.0);
+        stopped.flags.remove(TransportFlags::IS_PLAYING);
+
+        assert_eq!(process(&mut scheduler, Some(&stopped), &empty, 8192), []);
+        assert_eq!(process(&mut scheduler, None, &empty, 8192), []);
+        assert_eq!(scheduler.pending().len(), 1);
+
+        assert_eq!(
+            process(
+                &mut scheduler,
+                Some(&transport(0, 0.0, 120.0)),
+                &empty,
+                8192
+            ),
+            [(6000, 61)]
+        );
+    }
+
+    #[test]
+    fn jumps_flush_or_remap_pending_events() {
+        let empty = EventBuffer::new();
+
+        for (policy, expected) in [
+            (JumpPolicy::Flush, vec![]),
+            (JumpPolicy::Remap, vec![(6000, 61)]),
+        ] {
+            let (mut scheduler, _) = EventScheduler::new(48_000.0, 16, policy);
+            scheduler.schedule(BeatTime::from_float(4.25), note(61));
+
+            assert_eq!(
+                process(
+                    &mut scheduler,
+                    Some(&transport(0, 3.0, 120.0)),
+                    &empty,
+                    24000
+                ),
+                []
+            );
+
+            // The loop wraps around, from beat 4 back to beat 0.
+            assert_eq!(
+                process(
+                    &mut scheduler,
+                    Some(&transport(0, 0.0, 120.0)),
+                    &empty,
+                    24000
+                ),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn late_events_are_emitted_right_away() {
+        let (mut scheduler, _) = EventScheduler::new(48_000.0, 1, JumpPolicy::Flush);
+        assert!(scheduler.schedule(BeatTime::from_int(0), note(61)));
+        assert!(!scheduler.schedule(BeatTime::from_int(0), note(62)));
+        assert_eq!(scheduler.dropped_count(), 1);
+
+        assert_eq!(
+            process(
+                &mut scheduler,
+                Some(&transport(0, 2.0, 120.0)),
+                &EventBuffer::new(),
+                64
+            ),
+            [(0, 61)]
+        );
+    }
+}
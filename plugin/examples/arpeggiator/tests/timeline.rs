@@ -0,0 +1,202 @@
+use clack_host::events::event_types::*;
+use clack_host::events::{EventFlags, EventHeader, Match, Pckn};
+use clack_host::prelude::*;
+use clack_host::utils::{BeatTime, SecondsTime};
+use std::ffi::CStr;
+
+use clack_plugin_arpeggiator::clap_entry;
+
+const SAMPLE_RATE: f64 = 48_000.0;
+
+/// A note played by the arpeggiator, as seen by the host.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Played {
+    On(u32, u16),
+    Off(u32, u16),
+}
+
+fn transport(time: u32, position: f64, tempo: f64) -> TransportEvent {
+    TransportEvent {
+        header: EventHeader::new_core(time, EventFlags::empty()),
+        flags: TransportFlags::IS_PLAYING
+            | TransportFlags::HAS_TEMPO
+            | TransportFlags::HAS_BEATS_TIMELINE,
+        song_pos_beats: BeatTime::from_float(position),
+        song_pos_seconds: SecondsTime::from_int(0),
+        tempo,
+        tempo_inc: 0.0,
+        loop_start_beats: BeatTime::from_int(0),
+        loop_end_beats: BeatTime::from_int(0),
+        loop_start_seconds: SecondsTime::from_int(0),
+        loop_end_seconds: SecondsTime::from_int(0),
+        bar_start: BeatTime::from_int(0),
+        bar_number: 0,
+        time_signature_numerator: 4,
+        time_signature_denominator: 4,
+    }
+}
+
+fn note_on(key: u16) -> NoteOnEvent {
+    NoteOnEvent::new(0, Pckn::new(0u16, 0u16, key, Match::All), 1.0)
+}
+
+struct Arpeggiator {
+    _instance: PluginInstance<()>,
+    processor: StartedPluginAudioProcessor<()>,
+}
+
+impl Arpeggiator {
+    fn new() -> Self {
+        let info = HostInfo::new("test", "", "", "").unwrap();
+
+        // SAFETY: only called this once here
+        let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+        let id = CStr::from_bytes_with_nul(b"org.rust-audio.clack.arpeggiator\0").unwrap();
+
+        let mut instance = PluginInstance::<()>::new(|_| (), |_| (), &bundle, id, &info).unwrap();
+
+        let processor = instance
+            .activate_unchecked(
+                |_, _| (),
+                PluginAudioConfiguration {
+                    sample_rate: SAMPLE_RATE,
+                    min_frames_count: 1,
+                    max_frames_count: 24_000,
+                },
+            )
+            .unwrap()
+            .start_processing()
+            .unwrap();
+
+        Self {
+            _instance: instance,
+            processor,
+        }
+    }
+
+    /// Processes a block, and returns the notes the arpeggiator played in it.
+    fn process(
+        &mut self,
+        frames_count: u32,
+        transport: &TransportEvent,
+        input: &EventBuffer,
+    ) -> Vec<Played> {
+        let mut output = EventBuffer::new();
+
+        self.processor
+            .process_events(
+                frames_count,
+                &input.as_input(),
+                &mut output.as_output(),
+                None,
+                Some(transport),
+            )
+            .unwrap();
+
+        output
+            .iter()
+            .filter_map(|event| match event.as_core_event()? {
+                CoreEventSpace::NoteOn(e) => {
+                    Some(Played::On(e.time(), *e.pckn().key.as_specific()?))
+                }
+                CoreEventSpace::NoteOff(e) => {
+                    Some(Played::Off(e.time(), *e.pckn().key.as_specific()?))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn held_chord() -> EventBuffer {
+    let mut input = EventBuffer::new();
+    input.push(&note_on(67));
+    input.push(&note_on(60));
+    input.push(&note_on(64));
+    input
+}
+
+// At 120 BPM and 48kHz, a step (a sixteenth note) lasts 6000 frames. At 240 BPM, it lasts 3000.
+
+#[test]
+pub fn steps_follow_tempo_changes() {
+    let mut arp = Arpeggiator::new();
+
+    assert_eq!(
+        arp.process(12_000, &transport(0, 0.0, 120.0), &held_chord()),
+        [
+            Played::On(0, 60),
+            Played::Off(3000, 60),
+            Played::On(6000, 64),
+            Played::Off(9000, 64),
+        ]
+    );
+
+    // The tempo doubles in the middle of the next block, on beat 0.75.
+    let mut input = EventBuffer::new();
+    input.push(&transport(6000, 0.75, 240.0));
+
+    assert_eq!(
+        arp.process(12_000, &transport(0, 0.5, 120.0), &input),
+        [
+            Played::On(0, 67),
+            Played::Off(3000, 67),
+            Played::On(6000, 60),
+            Played::Off(7500, 60),
+            Played::On(9000, 64),
+            Played::Off(10_500, 64),
+        ]
+    );
+
+    // The next block starts on beat 1.25, at the new tempo.
+    assert_eq!(
+        arp.process(3000, &transport(0, 1.25, 240.0), &EventBuffer::new()),
+        [Played::On(0, 67), Played::Off(1500, 67)]
+    );
+}
+
+#[test]
+pub fn loops_restart_steps_without_hanging_notes() {
+    let mut arp = Arpeggiator::new();
+
+    // The block ends right after the note on, before its note off.
+    assert_eq!(
+        arp.process(1500, &transport(0, 1.75, 120.0), &held_chord()),
+        [Played::On(0, 60)]
+    );
+
+    // The loop wraps around in the middle of the next block, from beat 1.8125 to beat 0.
+    let mut input = EventBuffer::new();
+    input.push(&transport(750, 0.0, 120.0));
+
+    assert_eq!(
+        arp.process(12_000, &transport(0, 1.8125, 120.0), &input),
+        [
+            Played::On(750, 64),
+            Played::Off(1500, 60),
+            Played::Off(750 + 3000, 64),
+            Played::On(750 + 6000, 67),
+            Played::Off(750 + 9000, 67),
+        ]
+    );
+}
+
+#[test]
+pub fn stopped_transport_holds_notes() {
+    let mut arp = Arpeggiator::new();
+
+    assert_eq!(
+        arp.process(1500, &transport(0, 0.0, 120.0), &held_chord()),
+        [Played::On(0, 60)]
+    );
+
+    let mut stopped = transport(0, 0.0625, 120.0);
+    stopped.flags.remove(TransportFlags::IS_PLAYING);
+    assert_eq!(arp.process(6000, &stopped, &EventBuffer::new()), []);
+
+    // Playback resumes where it stopped.
+    assert_eq!(
+        arp.process(6000, &transport(0, 0.0625, 120.0), &EventBuffer::new()),
+        [Played::Off(1500, 60), Played::On(4500, 64)]
+    );
+}
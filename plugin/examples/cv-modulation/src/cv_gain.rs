@@ -0,0 +1,109 @@
+//! The CV gain plugin, applying the signal of a CV input port to its audio.
+
+use clack_extensions::prelude::*;
+use clack_plugin::prelude::*;
+
+/// Returns the descriptor of the [`CvGainPlugin`].
+pub(crate) fn descriptor() -> PluginDescriptor {
+    use features::*;
+
+    PluginDescriptor::new("org.rust-audio.clack.cv-gain", "Clack CV Gain Example")
+        .with_features([AUDIO_EFFECT, STEREO])
+}
+
+/// An effect applying the signal of its CV input port as a gain to its stereo audio.
+pub struct CvGainPlugin;
+
+impl Plugin for CvGainPlugin {
+    type AudioProcessor<'a> = CvGainAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<StaticAudioPorts<Self>>();
+    }
+}
+
+impl AudioPortLayoutProvider for CvGainPlugin {
+    const AUDIO_PORTS: AudioPortLayout = AudioPortLayout::new(
+        &[
+            AudioPortInfo::stereo(ClapId::new(0), b"main").main(),
+            AudioPortInfo::cv(ClapId::new(1), b"gain"),
+        ],
+        &[AudioPortInfo::stereo(ClapId::new(0), b"main")
+            .main()
+            .with_in_place_pair(ClapId::new(0))],
+    );
+}
+
+/// The CV gain's audio processor. It lives in the audio thread.
+pub struct CvGainAudioProcessor {
+    /// A copy of the CV input of the current block.
+    ///
+    /// The CV has to be copied, as it can't be read while the audio buffers are being written.
+    cv: Vec<f32>,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for CvGainAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            cv: vec![1.0; audio_config.max_frames_count as usize],
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let frames_count = audio.frames_count() as usize;
+        let cv = self
+            .cv
+            .get_mut(..frames_count)
+            .ok_or(PluginError::Message("Block is larger than the maximum"))?;
+
+        // If no CV is connected, pass the audio through unchanged.
+        let connected_cv = audio
+            .input_port(1)
+            .and_then(|port| port.channels().ok()?.into_f32())
+            .and_then(|channels| channels.channel(0));
+
+        match connected_cv {
+            Some(input) => cv.copy_from_slice(input),
+            None => cv.fill(1.0),
+        }
+
+        let mut port_pair = audio
+            .port_pair(0)
+            .ok_or(PluginError::Message("No input/output ports found"))?;
+
+        let mut channels = port_pair
+            .channels()?
+            .into_f32()
+            .ok_or(PluginError::Message("Expected f32 input/output"))?;
+
+        for pair in channels.iter_mut() {
+            match pair {
+                ChannelPair::InputOnly(_) | ChannelPair::OutputOnly(_) => {}
+                ChannelPair::InPlace(buf) => {
+                    for (sample, gain) in buf.iter_mut().zip(cv.iter()) {
+                        *sample *= gain;
+                    }
+                }
+                ChannelPair::InputOutput(input, output) => {
+                    for ((output, input), gain) in output.iter_mut().zip(input).zip(cv.iter()) {
+                        *output = input * gain;
+                    }
+                }
+            }
+        }
+
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    }
+}
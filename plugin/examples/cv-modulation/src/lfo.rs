@@ -0,0 +1,87 @@
+//! The LFO plugin, a modulation source outputting its signal on a CV port.
+
+use clack_extensions::prelude::*;
+use clack_plugin::prelude::*;
+use std::f32::consts::TAU;
+
+/// The frequency of the LFO, in Hz.
+const LFO_FREQUENCY: f32 = 2.0;
+
+/// Returns the descriptor of the [`LfoPlugin`].
+pub(crate) fn descriptor() -> PluginDescriptor {
+    use features::*;
+
+    PluginDescriptor::new("org.rust-audio.clack.cv-lfo", "Clack CV LFO Example")
+        .with_features([UTILITY, MONO])
+}
+
+/// A modulator plugin, outputting a sine LFO between `0.0` and `1.0` on a single CV port.
+pub struct LfoPlugin;
+
+impl Plugin for LfoPlugin {
+    type AudioProcessor<'a> = LfoAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<StaticAudioPorts<Self>>();
+    }
+}
+
+impl AudioPortLayoutProvider for LfoPlugin {
+    const AUDIO_PORTS: AudioPortLayout =
+        AudioPortLayout::new(&[], &[AudioPortInfo::cv(ClapId::new(0), b"LFO")]);
+}
+
+/// The LFO's audio processor. It lives in the audio thread.
+pub struct LfoAudioProcessor {
+    /// The current phase of the LFO, between `0.0` and `1.0`.
+    phase: f32,
+    /// How much the phase advances for every sample.
+    phase_increment: f32,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for LfoAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            phase: 0.0,
+            phase_increment: LFO_FREQUENCY / audio_config.sample_rate as f32,
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        _events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let mut port = audio
+            .output_port(0)
+            .ok_or(PluginError::Message("No CV output port found"))?;
+
+        let mut channels = port
+            .channels()?
+            .into_f32()
+            .ok_or(PluginError::Message("Expected f32 CV output"))?;
+
+        let cv = channels
+            .channel_mut(0)
+            .ok_or(PluginError::Message("Expected a mono CV output"))?;
+
+        for sample in cv {
+            *sample = 0.5 + 0.5 * (self.phase * TAU).sin();
+            self.phase = (self.phase + self.phase_increment).fract();
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+}
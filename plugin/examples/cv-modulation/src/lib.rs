@@ -0,0 +1,89 @@
+#![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs, clippy::missing_docs_in_private_items, unsafe_code)]
+
+use clack_plugin::entry::prelude::*;
+use clack_plugin::prelude::*;
+use std::ffi::CStr;
+
+mod cv_gain;
+mod lfo;
+
+pub use cv_gain::CvGainPlugin;
+pub use lfo::LfoPlugin;
+
+/// The entry of this bundle, which exposes both of our plugins through a single factory.
+pub struct CvModulationEntry {
+    /// The plugin factory of this bundle.
+    plugin_factory: PluginFactoryWrapper<CvModulationPluginFactory>,
+}
+
+impl Entry for CvModulationEntry {
+    fn new(_bundle_path: &CStr) -> Result<Self, EntryLoadError> {
+        Ok(Self {
+            plugin_factory: PluginFactoryWrapper::new(CvModulationPluginFactory::new()),
+        })
+    }
+
+    fn declare_factories<'a>(&'a self, builder: &mut EntryFactories<'a>) {
+        builder.register_factory(&self.plugin_factory);
+    }
+}
+
+/// The plugin factory of this bundle, which holds the descriptors of both plugins.
+pub struct CvModulationPluginFactory {
+    /// The descriptor of the [`LfoPlugin`].
+    lfo: PluginDescriptor,
+    /// The descriptor of the [`CvGainPlugin`].
+    cv_gain: PluginDescriptor,
+}
+
+impl CvModulationPluginFactory {
+    /// Creates the factory, and the descriptors of all the plugins it exposes.
+    fn new() -> Self {
+        Self {
+            lfo: lfo::descriptor(),
+            cv_gain: cv_gain::descriptor(),
+        }
+    }
+}
+
+impl PluginFactory for CvModulationPluginFactory {
+    fn plugin_count(&self) -> u32 {
+        2
+    }
+
+    fn plugin_descriptor(&self, index: u32) -> Option<&PluginDescriptor> {
+        match index {
+            0 => Some(&self.lfo),
+            1 => Some(&self.cv_gain),
+            _ => None,
+        }
+    }
+
+    fn create_plugin<'a>(
+        &'a self,
+        host_info: HostInfo<'a>,
+        plugin_id: &CStr,
+    ) -> Option<PluginInstance<'a>> {
+        if plugin_id == self.lfo.id() {
+            Some(PluginInstance::new::<LfoPlugin>(
+                host_info,
+                &self.lfo,
+                |_host| Ok(()),
+                |_host, _shared| Ok(()),
+            ))
+        } else if plugin_id == self.cv_gain.id() {
+            Some(PluginInstance::new::<CvGainPlugin>(
+                host_info,
+                &self.cv_gain,
+                |_host| Ok(()),
+                |_host, _shared| Ok(()),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+clack_export_entry!(CvModulationEntry);
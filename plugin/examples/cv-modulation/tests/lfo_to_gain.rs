@@ -0,0 +1,142 @@
+use clack_extensions::prelude::*;
+use clack_host::factory::PluginFactory;
+use clack_host::prelude::*;
+use std::ffi::CStr;
+
+use clack_plugin_cv_modulation::clap_entry;
+
+const FRAMES: u32 = 256;
+
+fn instantiate(bundle: &PluginBundle, id: &CStr) -> PluginInstance<()> {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+    PluginInstance::<()>::new(|_| (), |_| (), bundle, id, &info).unwrap()
+}
+
+/// Returns the channel count, main flag and CV-ness of the plugin's port at the given index.
+fn port_info(plugin: &mut PluginInstance<()>, index: u32, is_input: bool) -> (u32, bool, bool) {
+    let mut handle = plugin.plugin_handle_unchecked();
+    let ports = handle.get_extension::<PluginAudioPorts>().unwrap();
+
+    let mut buffer = AudioPortInfoBuffer::new();
+    let info = ports
+        .get(&mut handle, index, is_input, &mut buffer)
+        .unwrap();
+
+    (
+        info.channel_count,
+        info.flags.contains(AudioPortFlags::IS_MAIN),
+        info.is_cv(),
+    )
+}
+
+#[test]
+pub fn lfo_modulates_gain_through_cv_ports() {
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+    let factory = bundle.get_factory::<PluginFactory>().unwrap();
+
+    assert_eq!(factory.plugin_count(), 2);
+    let lfo_id = factory.plugin_descriptor(0).unwrap().id().unwrap();
+    let gain_id = factory.plugin_descriptor(1).unwrap().id().unwrap();
+
+    let mut lfo = instantiate(&bundle, lfo_id);
+    let mut gain = instantiate(&bundle, gain_id);
+
+    // The host recognizes CV ports as such: mono, and never main.
+    assert_eq!(port_info(&mut lfo, 0, false), (1, false, true));
+    assert_eq!(port_info(&mut gain, 0, true), (2, true, false));
+    assert_eq!(port_info(&mut gain, 1, true), (1, false, true));
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 1_000.0,
+        min_frames_count: FRAMES,
+        max_frames_count: FRAMES,
+    };
+
+    let mut lfo_processor = lfo
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut gain_processor = gain
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    // First, run the LFO into a CV buffer.
+    let mut cv = vec![-1.0f32; FRAMES as usize];
+    let mut cv_ports = AudioPorts::with_capacity(1, 1);
+
+    lfo_processor
+        .process(
+            &InputAudioBuffers::empty(),
+            &mut cv_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([&mut cv[..]]),
+                latency: 0,
+            }]),
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert!(cv.iter().all(|s| (0.0..=1.0).contains(s)));
+    assert_eq!(cv[0], 0.5);
+    // At 2Hz and 1kHz, the LFO peaks after 125 samples.
+    assert!(cv[125] > 0.99);
+
+    // Then, feed that CV into the gain's CV input port.
+    let mut audio = [
+        vec![1.0f32; FRAMES as usize],
+        vec![-0.5f32; FRAMES as usize],
+    ];
+    let mut output = [vec![0.0f32; FRAMES as usize], vec![0.0f32; FRAMES as usize]];
+    let mut input_ports = AudioPorts::with_capacity(3, 2);
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+    let [audio_l, audio_r] = &mut audio;
+    let [output_l, output_r] = &mut output;
+
+    gain_processor
+        .process(
+            &input_ports.with_input_buffers([
+                AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only(vec![
+                        InputChannel::variable(&mut audio_l[..]),
+                        InputChannel::variable(&mut audio_r[..]),
+                    ]),
+                    latency: 0,
+                },
+                AudioPortBuffer {
+                    // Ports can have different channel counts, hence the Vecs.
+                    channels: AudioPortBufferType::f32_input_only(vec![InputChannel::variable(
+                        &mut cv[..],
+                    )]),
+                    latency: 0,
+                },
+            ]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only([
+                    &mut output_l[..],
+                    &mut output_r[..],
+                ]),
+                latency: 0,
+            }]),
+            &InputEvents::empty(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    for ((l, r), gain) in output[0].iter().zip(&output[1]).zip(&cv) {
+        assert_eq!(*l, *gain);
+        assert_eq!(*r, -0.5 * gain);
+    }
+
+    lfo.deactivate_unchecked(lfo_processor.stop_processing());
+    gain.deactivate_unchecked(gain_processor.stop_processing());
+}
@@ -3,8 +3,7 @@
 #![doc = include_str!("../README.md")]
 
 use crate::params::GainParams;
-use clack_extensions::state::PluginState;
-use clack_extensions::{audio_ports::*, params::*};
+use clack_extensions::prelude::*;
 use clack_plugin::prelude::*;
 
 mod params;
@@ -25,6 +24,7 @@ impl Plugin for GainPlugin {
     ) {
         builder
             .register::<PluginAudioPorts>()
+            .register::<PluginNotePorts>()
             .register::<PluginParams>()
             .register::<PluginState>();
     }
@@ -32,7 +32,7 @@ impl Plugin for GainPlugin {
 
 impl DefaultPluginFactory for GainPlugin {
     fn get_descriptor() -> PluginDescriptor {
-        use clack_plugin::plugin::features::*;
+        use features::*;
 
         PluginDescriptor::new("org.rust-audio.clack.gain", "Clack Gain Example")
             .with_features([AUDIO_EFFECT, STEREO])
@@ -112,13 +112,18 @@ impl<'a> PluginAudioProcessor<'a, GainPluginShared, GainPluginMainThread<'a>>
         // sample-accurate event.
 
         for event_batch in events.input.batch() {
-            // Process all param events in this batch
+            // Process all param events in this batch, and pass all note events through.
             for event in event_batch.events() {
-                self.shared.params.handle_event(event)
+                self.shared.params.handle_event(event);
+
+                if is_note_event(event) {
+                    // If the host's output queue is full, there's nothing more we can do.
+                    let _ = events.output.try_push(event);
+                }
             }
 
             // Get the volume value after all parameter changes have been handled.
-            let volume = self.shared.params.get_volume();
+            let volume = self.shared.params.get_modulated_volume();
 
             for buf in channel_buffers.iter_mut().flatten() {
                 for sample in buf.iter_mut() {
@@ -150,6 +155,41 @@ impl PluginAudioPortsImpl for GainPluginMainThread<'_> {
     }
 }
 
+impl PluginNotePortsImpl for GainPluginMainThread<'_> {
+    fn count(&mut self, _is_input: bool) -> u32 {
+        1
+    }
+
+    fn get(&mut self, index: u32, _is_input: bool, writer: &mut NotePortInfoWriter) {
+        if index == 0 {
+            writer.set(&NotePortInfo {
+                id: ClapId::new(0),
+                name: b"main",
+                preferred_dialect: Some(NoteDialect::Clap),
+                supported_dialects: NoteDialects::CLAP | NoteDialects::MIDI,
+            })
+        }
+    }
+}
+
+/// Returns `true` if the given event is a note or MIDI event, which this plugin passes through
+/// untouched.
+fn is_note_event(event: &UnknownEvent) -> bool {
+    matches!(
+        event.as_core_event(),
+        Some(
+            CoreEventSpace::NoteOn(_)
+                | CoreEventSpace::NoteOff(_)
+                | CoreEventSpace::NoteChoke(_)
+                | CoreEventSpace::NoteEnd(_)
+                | CoreEventSpace::NoteExpression(_)
+                | CoreEventSpace::Midi(_)
+                | CoreEventSpace::Midi2(_)
+                | CoreEventSpace::MidiSysEx(_)
+        )
+    )
+}
+
 /// The plugin data that gets shared between the Main Thread and the Audio Thread.
 pub struct GainPluginShared {
     /// The plugin's parameter values.
@@ -1,11 +1,8 @@
 //! Contains all types and implementations related to parameter management.
 
 use crate::{GainPluginAudioProcessor, GainPluginMainThread};
-use clack_extensions::params::*;
-use clack_extensions::state::PluginStateImpl;
-use clack_plugin::events::spaces::CoreEventSpace;
+use clack_extensions::prelude::*;
 use clack_plugin::prelude::*;
-use clack_plugin::stream::{InputStream, OutputStream};
 use std::ffi::CStr;
 use std::fmt::Write as _;
 use std::io::{Read, Write as _};
@@ -27,6 +24,8 @@ const DEFAULT_VOLUME: f32 = 1.0;
 pub struct GainParams {
     /// The current value of the volume parameter.
     volume: AtomicF32,
+    /// The current modulation amount of the volume parameter, set by the host.
+    volume_modulation: AtomicF32,
 }
 
 impl GainParams {
@@ -34,6 +33,7 @@ impl GainParams {
     pub fn new() -> Self {
         Self {
             volume: AtomicF32::new(DEFAULT_VOLUME),
+            volume_modulation: AtomicF32::new(0.0),
         }
     }
 
@@ -43,6 +43,16 @@ impl GainParams {
         self.volume.load(Ordering::SeqCst)
     }
 
+    /// Returns the current volume, with the host's modulation applied.
+    ///
+    /// This is what should be applied to the audio, while [`get_volume`](Self::get_volume) is
+    /// what should be reported to the host.
+    #[inline]
+    pub fn get_modulated_volume(&self) -> f32 {
+        let modulation = self.volume_modulation.load(Ordering::SeqCst);
+        (self.get_volume() + modulation).clamp(0., 1.)
+    }
+
     /// Sets a new value for the value parameter.
     /// The value is clamped, as it should only be in the `0..=1` range.
     #[inline]
@@ -53,13 +63,17 @@ impl GainParams {
 
     /// Handles incoming events.
     ///
-    /// If the given event is a matching parameter change event, the volume parameter will be
-    /// updated accordingly.
+    /// If the given event is a matching parameter change or modulation event, the volume
+    /// parameter will be updated accordingly.
     pub fn handle_event(&self, event: &UnknownEvent) {
-        if let Some(CoreEventSpace::ParamValue(event)) = event.as_core_event() {
-            if event.param_id() == PARAM_VOLUME_ID {
+        match event.as_core_event() {
+            Some(CoreEventSpace::ParamValue(event)) if event.param_id() == PARAM_VOLUME_ID => {
                 self.set_volume(event.value() as f32)
             }
+            Some(CoreEventSpace::ParamMod(event)) if event.param_id() == PARAM_VOLUME_ID => self
+                .volume_modulation
+                .store(event.amount() as f32, Ordering::SeqCst),
+            _ => {}
         }
     }
 }
@@ -96,7 +110,7 @@ impl PluginMainThreadParams for GainPluginMainThread<'_> {
         }
         info.set(&ParamInfo {
             id: 1.into(),
-            flags: ParamInfoFlags::IS_AUTOMATABLE,
+            flags: ParamInfoFlags::IS_AUTOMATABLE | ParamInfoFlags::IS_MODULATABLE,
             cookie: Default::default(),
             name: b"Volume",
             module: b"",
@@ -0,0 +1,163 @@
+use clack_host::factory::PluginFactory;
+use clack_host::prelude::*;
+use clack_host::process::chain::{ChainedPluginInfo, PluginChainError, StoppedPluginChain};
+
+use clack_plugin_gain::clap_entry;
+
+const MAX_FRAMES: u32 = 32;
+
+#[test]
+pub fn chain_applies_all_gains_in_order() {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+    let plugin_id = bundle
+        .get_factory::<PluginFactory>()
+        .unwrap()
+        .plugin_descriptor(0)
+        .unwrap()
+        .id()
+        .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: 1,
+        max_frames_count: MAX_FRAMES,
+    };
+
+    let mut plugins: Vec<_> = (0..3)
+        .map(|_| {
+            PluginInstance::<TestHostHandlers>::new(
+                |_| TestHostShared,
+                |_| TestHostMainThread,
+                &bundle,
+                plugin_id,
+                &info,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let mut chain = StoppedPluginChain::new(2, MAX_FRAMES);
+    let slots = [(true, 10), (false, 5), (true, 3)];
+
+    for (plugin, (in_place, latency)) in plugins.iter_mut().zip(slots) {
+        let processor = plugin
+            .activate_unchecked(|_, _| TestHostAudioProcessor, configuration)
+            .unwrap();
+
+        chain.push(
+            processor,
+            ChainedPluginInfo {
+                in_place,
+                latency,
+                tail: 0,
+            },
+        );
+    }
+
+    assert_eq!(chain.len(), 3);
+    assert_eq!(chain.total_latency(), 18);
+
+    let mut chain = chain.start_processing().unwrap();
+
+    let mut input = [vec![1.0f32; 32], vec![-1.0f32; 32]];
+    let mut output = [vec![0.0f32; 32], vec![0.0f32; 32]];
+
+    let mut input_events: Vec<_> = [0.5, 0.5, 0.25]
+        .into_iter()
+        .map(|volume| {
+            let mut buffer = EventBuffer::with_capacity(1);
+            buffer.push(&ParamValueEvent::new(
+                0,
+                ClapId::new(1),
+                Pckn::match_all(),
+                volume,
+                Cookie::empty(),
+            ));
+            buffer
+        })
+        .collect();
+
+    let [events_0, events_1, events_2] = &mut input_events[..] else {
+        unreachable!()
+    };
+
+    let status = chain
+        .process_chain(
+            &mut input,
+            &mut output,
+            &mut [
+                (&events_0.as_input(), &mut OutputEvents::void()),
+                (&events_1.as_input(), &mut OutputEvents::void()),
+                (&events_2.as_input(), &mut OutputEvents::void()),
+            ],
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(status, ProcessStatus::ContinueIfNotQuiet);
+    assert!(output[0].iter().all(|s| *s == 0.0625));
+    assert!(output[1].iter().all(|s| *s == -0.0625));
+
+    // The input must not have been touched.
+    assert!(input[0].iter().all(|s| *s == 1.0));
+
+    assert_eq!(
+        chain.process_chain(&mut input[..1], &mut output, &mut [], None, None),
+        Err(PluginChainError::ChannelCountMismatch)
+    );
+
+    // Remove the middle plugin. The others keep their volume from the previous block.
+    let mut chain = chain.stop_processing();
+    let removed = chain.remove(1).unwrap();
+    plugins[1].deactivate_unchecked(removed);
+
+    assert_eq!(chain.total_latency(), 13);
+
+    let mut chain = chain.start_processing().unwrap();
+    chain
+        .process_chain(&mut input, &mut output, &mut [], None, Some(32))
+        .unwrap();
+
+    assert!(output[0].iter().all(|s| *s == 0.125));
+    assert!(output[1].iter().all(|s| *s == -0.125));
+
+    plugins.remove(1);
+
+    let processors = chain.stop_processing().into_processors();
+    for (plugin, processor) in plugins.iter_mut().zip(processors) {
+        plugin.deactivate_unchecked(processor);
+    }
+}
+
+struct TestHostMainThread;
+struct TestHostShared;
+struct TestHostAudioProcessor;
+struct TestHostHandlers;
+
+impl SharedHandler<'_> for TestHostShared {
+    fn request_restart(&self) {
+        unimplemented!()
+    }
+
+    fn request_process(&self) {
+        unimplemented!()
+    }
+
+    fn request_callback(&self) {
+        unimplemented!()
+    }
+}
+
+impl AudioProcessorHandler<'_> for TestHostAudioProcessor {}
+
+impl MainThreadHandler<'_> for TestHostMainThread {}
+
+impl HostHandlers for TestHostHandlers {
+    type Shared<'a> = TestHostShared;
+    type MainThread<'a> = TestHostMainThread;
+    type AudioProcessor<'a> = TestHostAudioProcessor;
+}
@@ -0,0 +1,161 @@
+use clack_extensions::params::modulation::ModulationRouter;
+use clack_extensions::prelude::*;
+use clack_host::factory::PluginFactory;
+use clack_host::prelude::*;
+
+use clack_plugin_gain::clap_entry;
+
+const BLOCK_SIZE: usize = 32;
+const LFO_PERIOD: f64 = (BLOCK_SIZE * 8) as f64;
+
+fn lfo(steady_time: u64) -> f64 {
+    (steady_time as f64 / LFO_PERIOD * std::f64::consts::TAU).sin()
+}
+
+#[test]
+pub fn lfo_modulates_gain() {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+    let descriptor = bundle
+        .get_factory::<PluginFactory>()
+        .unwrap()
+        .plugin_descriptor(0)
+        .unwrap();
+
+    let mut plugin = PluginInstance::<TestHostHandlers>::new(
+        |_| TestHostShared,
+        |_| TestHostMainThread,
+        &bundle,
+        descriptor.id().unwrap(),
+        &info,
+    )
+    .unwrap();
+
+    let mut handle = plugin.plugin_handle_unchecked();
+    let params = handle.get_extension::<PluginParams>().unwrap();
+    let mut buffer = ParamInfoBuffer::new();
+    let volume_info = params.get_info(&mut handle, 0, &mut buffer).unwrap();
+
+    assert!(volume_info.flags.contains(ParamInfoFlags::IS_MODULATABLE));
+    assert!(!volume_info.is_stepped());
+    assert!(volume_info.validate().is_empty());
+
+    let mut router = ModulationRouter::new();
+    let lfo_source = router.add_source(|steady_time, _| lfo(steady_time));
+    router
+        .route(lfo_source, &volume_info, Pckn::match_all(), 0.25)
+        .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: BLOCK_SIZE as u32,
+        max_frames_count: BLOCK_SIZE as u32,
+    };
+
+    let mut processor = plugin
+        .activate_unchecked(|_, _| TestHostAudioProcessor, configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut input_events = EventBuffer::with_capacity(10);
+    let mut inputs_descriptors = AudioPorts::with_capacity(2, 1);
+    let mut outputs_descriptors = AudioPorts::with_capacity(2, 1);
+
+    // Set the base volume to the middle of its range.
+    input_events.push(&ParamValueEvent::new(
+        0,
+        volume_info.id,
+        Pckn::match_all(),
+        0.5,
+        Cookie::empty(),
+    ));
+
+    let mut amplitudes = Vec::new();
+
+    for block in 0..16 {
+        let steady_time = (block * BLOCK_SIZE) as u64;
+        router.process(
+            steady_time,
+            BLOCK_SIZE as u32,
+            &mut input_events.as_output(),
+        );
+
+        let mut input_buffers = [vec![1f32; BLOCK_SIZE], vec![1f32; BLOCK_SIZE]];
+        let mut output_buffers = [vec![0f32; BLOCK_SIZE], vec![0f32; BLOCK_SIZE]];
+
+        let input_channels = inputs_descriptors.with_input_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_input_only(
+                input_buffers.iter_mut().map(InputChannel::variable),
+            ),
+            latency: 0,
+        }]);
+
+        let mut output_channels = outputs_descriptors.with_output_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_output_only(
+                output_buffers.iter_mut().map(|b| b.as_mut_slice()),
+            ),
+            latency: 0,
+        }]);
+
+        processor
+            .process(
+                &input_channels,
+                &mut output_channels,
+                &input_events.as_input(),
+                &mut OutputEvents::void(),
+                Some(steady_time),
+                None,
+            )
+            .unwrap();
+
+        input_events.clear();
+
+        let amplitude = output_buffers[0][0];
+        assert!(output_buffers.iter().flatten().all(|s| *s == amplitude));
+
+        let expected = 0.5 + 0.25 * lfo(steady_time);
+        assert!((amplitude as f64 - expected).abs() < 1e-6);
+
+        amplitudes.push(amplitude);
+    }
+
+    // The amplitude wobbles around the base volume, following the LFO.
+    let min = amplitudes.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = amplitudes.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    assert_eq!(min, 0.25);
+    assert_eq!(max, 0.75);
+
+    plugin.deactivate_unchecked(processor.stop_processing());
+}
+
+struct TestHostMainThread;
+struct TestHostShared;
+struct TestHostAudioProcessor;
+struct TestHostHandlers;
+
+impl SharedHandler<'_> for TestHostShared {
+    fn request_restart(&self) {
+        unimplemented!()
+    }
+
+    fn request_process(&self) {
+        unimplemented!()
+    }
+
+    fn request_callback(&self) {
+        unimplemented!()
+    }
+}
+
+impl AudioProcessorHandler<'_> for TestHostAudioProcessor {}
+
+impl MainThreadHandler<'_> for TestHostMainThread {}
+
+impl HostHandlers for TestHostHandlers {
+    type Shared<'a> = TestHostShared;
+    type MainThread<'a> = TestHostMainThread;
+    type AudioProcessor<'a> = TestHostAudioProcessor;
+}
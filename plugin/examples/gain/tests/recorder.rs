@@ -0,0 +1,176 @@
+use clack_extensions::prelude::*;
+use clack_host::factory::PluginFactory;
+use clack_host::prelude::*;
+use clack_host::process::recorder::{EventRecorder, RecordedEvent};
+
+use clack_plugin_gain::clap_entry;
+
+const BLOCK_SIZE: u32 = 32;
+
+#[test]
+pub fn recorder_captures_note_passthrough() {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+    let plugin_id = bundle
+        .get_factory::<PluginFactory>()
+        .unwrap()
+        .plugin_descriptor(0)
+        .unwrap()
+        .id()
+        .unwrap();
+
+    let mut plugin = PluginInstance::<TestHostHandlers>::new(
+        |_| TestHostShared,
+        |_| TestHostMainThread,
+        &bundle,
+        plugin_id,
+        &info,
+    )
+    .unwrap();
+
+    let mut handle = plugin.plugin_handle_unchecked();
+    let note_ports = handle.get_extension::<PluginNotePorts>().unwrap();
+    assert_eq!(note_ports.count(&mut handle, true), 1);
+    assert_eq!(note_ports.count(&mut handle, false), 1);
+
+    let mut buffer = NotePortInfoBuffer::new();
+    let port = note_ports.get(&mut handle, 0, false, &mut buffer).unwrap();
+    assert_eq!(port.name, b"main");
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: 1,
+        max_frames_count: BLOCK_SIZE,
+    };
+
+    let mut processor = plugin
+        .activate_unchecked(|_, _| TestHostAudioProcessor, configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    // A short sequence of notes, as (absolute position, key, is note on).
+    let sequence = [
+        (3, 60, true),
+        (20, 64, true),
+        (31, 60, false),
+        (40, 67, true),
+        (70, 64, false),
+        (95, 67, false),
+    ];
+
+    let (mut recorder, mut timeline) = EventRecorder::new(4);
+
+    let mut input = [
+        vec![1.0f32; BLOCK_SIZE as usize],
+        vec![1.0f32; BLOCK_SIZE as usize],
+    ];
+    let mut output = [
+        vec![0.0f32; BLOCK_SIZE as usize],
+        vec![0.0f32; BLOCK_SIZE as usize],
+    ];
+    let mut input_ports = AudioPorts::with_capacity(2, 1);
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+    let mut events = EventIo::new();
+
+    for block in 0..3u64 {
+        let block_start = block * BLOCK_SIZE as u64;
+        let block_end = block_start + BLOCK_SIZE as u64;
+
+        events.reset();
+
+        for (position, key, is_on) in sequence {
+            if !(block_start..block_end).contains(&position) {
+                continue;
+            }
+
+            let time = (position - block_start) as u32;
+            let pckn = Pckn::new(0u16, 0u16, key, 0u32);
+
+            if is_on {
+                events.push_input(&NoteOnEvent::new(time, pckn, 1.0));
+            } else {
+                events.push_input(&NoteOffEvent::new(time, pckn, 0.0));
+            }
+        }
+
+        let (input_events, mut output_events) = events.split();
+
+        processor
+            .process(
+                &input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only(
+                        input.iter_mut().map(InputChannel::variable),
+                    ),
+                    latency: 0,
+                }]),
+                &mut output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only(
+                        output.iter_mut().map(|b| b.as_mut_slice()),
+                    ),
+                    latency: 0,
+                }]),
+                &input_events,
+                &mut output_events,
+                Some(block_start),
+                None,
+            )
+            .unwrap();
+
+        recorder.record(&events.output_buffer().as_input(), block_start);
+    }
+
+    plugin.deactivate_unchecked(processor.stop_processing());
+
+    assert_eq!(timeline.collect(), sequence.len());
+    assert_eq!(timeline.dropped_count(), 0);
+
+    let reconstructed: Vec<_> = timeline
+        .events()
+        .iter()
+        .map(|e| match e.event {
+            RecordedEvent::NoteOn(n) => (e.position, n.key().into_specific().unwrap(), true),
+            RecordedEvent::NoteOff(n) => (e.position, n.key().into_specific().unwrap(), false),
+            other => panic!("Unexpected recorded event: {other:?}"),
+        })
+        .collect();
+
+    assert_eq!(reconstructed, sequence);
+
+    // Only the events of the completed first block are drained.
+    let first_block = timeline.drain_before(BLOCK_SIZE as u64);
+    assert_eq!(first_block.len(), 3);
+    assert_eq!(timeline.events().len(), 3);
+}
+
+struct TestHostMainThread;
+struct TestHostShared;
+struct TestHostAudioProcessor;
+struct TestHostHandlers;
+
+impl SharedHandler<'_> for TestHostShared {
+    fn request_restart(&self) {
+        unimplemented!()
+    }
+
+    fn request_process(&self) {
+        unimplemented!()
+    }
+
+    fn request_callback(&self) {
+        unimplemented!()
+    }
+}
+
+impl AudioProcessorHandler<'_> for TestHostAudioProcessor {}
+
+impl MainThreadHandler<'_> for TestHostMainThread {}
+
+impl HostHandlers for TestHostHandlers {
+    type Shared<'a> = TestHostShared;
+    type MainThread<'a> = TestHostMainThread;
+    type AudioProcessor<'a> = TestHostAudioProcessor;
+}
@@ -0,0 +1,141 @@
+use clack_host::factory::PluginFactory;
+use clack_host::prelude::*;
+use clack_host::process::chain::{ChainedPluginInfo, StoppedPluginChain};
+use clack_host::process::mix::MixNode;
+
+use clack_plugin_gain::clap_entry;
+
+const MAX_FRAMES: u32 = 32;
+
+#[test]
+pub fn send_return_sums_both_chains() {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+    let plugin_id = bundle
+        .get_factory::<PluginFactory>()
+        .unwrap()
+        .plugin_descriptor(0)
+        .unwrap()
+        .id()
+        .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: 1,
+        max_frames_count: MAX_FRAMES,
+    };
+
+    let mut plugins: Vec<_> = (0..2)
+        .map(|_| {
+            PluginInstance::<TestHostHandlers>::new(
+                |_| TestHostShared,
+                |_| TestHostMainThread,
+                &bundle,
+                plugin_id,
+                &info,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    // The main chain and the send's return chain, each with a single gain plugin.
+    let mut chains: Vec<_> = plugins
+        .iter_mut()
+        .map(|plugin| {
+            let processor = plugin
+                .activate_unchecked(|_, _| TestHostAudioProcessor, configuration)
+                .unwrap();
+
+            let mut chain = StoppedPluginChain::new(2, MAX_FRAMES);
+            chain.push(processor, ChainedPluginInfo::default());
+            chain.start_processing().unwrap()
+        })
+        .collect();
+
+    let volume = |value| {
+        let mut buffer = EventBuffer::with_capacity(1);
+        buffer.push(&ParamValueEvent::new(
+            0,
+            ClapId::new(1),
+            Pckn::match_all(),
+            value,
+            Cookie::empty(),
+        ));
+        buffer
+    };
+
+    let main_events = volume(0.5);
+    let send_events = volume(0.25);
+
+    let mut input = [vec![1.0f32; 32], vec![-1.0f32; 32]];
+    let mut main_output = [vec![0.0f32; 32], vec![0.0f32; 32]];
+    let mut send_output = [vec![0.0f32; 32], vec![0.0f32; 32]];
+
+    chains[0]
+        .process_chain(
+            &mut input,
+            &mut main_output,
+            &mut [(&main_events.as_input(), &mut OutputEvents::void())],
+            None,
+            None,
+        )
+        .unwrap();
+
+    chains[1]
+        .process_chain(
+            &mut input,
+            &mut send_output,
+            &mut [(&send_events.as_input(), &mut OutputEvents::void())],
+            None,
+            None,
+        )
+        .unwrap();
+
+    // Return the send at half its level.
+    let mut mix = MixNode::new(2);
+    mix.set_gain(1, 0.5);
+
+    let mut bus = [vec![0.0f32; 32], vec![0.0f32; 32]];
+    mix.mix(&[&main_output[..], &send_output[..]], &mut bus)
+        .unwrap();
+
+    assert!(bus[0].iter().all(|s| *s == 0.625));
+    assert!(bus[1].iter().all(|s| *s == -0.625));
+
+    for (plugin, chain) in plugins.iter_mut().zip(chains) {
+        for processor in chain.stop_processing().into_processors() {
+            plugin.deactivate_unchecked(processor);
+        }
+    }
+}
+
+struct TestHostMainThread;
+struct TestHostShared;
+struct TestHostAudioProcessor;
+struct TestHostHandlers;
+
+impl SharedHandler<'_> for TestHostShared {
+    fn request_restart(&self) {
+        unimplemented!()
+    }
+
+    fn request_process(&self) {
+        unimplemented!()
+    }
+
+    fn request_callback(&self) {
+        unimplemented!()
+    }
+}
+
+impl AudioProcessorHandler<'_> for TestHostAudioProcessor {}
+
+impl MainThreadHandler<'_> for TestHostMainThread {}
+
+impl HostHandlers for TestHostHandlers {
+    type Shared<'a> = TestHostShared;
+    type MainThread<'a> = TestHostMainThread;
+    type AudioProcessor<'a> = TestHostAudioProcessor;
+}
@@ -1,8 +1,6 @@
-use clack_extensions::audio_ports::{AudioPortInfoBuffer, PluginAudioPorts};
-use clack_host::events::event_types::ParamValueEvent;
+use clack_extensions::prelude::*;
 use clack_host::factory::PluginFactory;
 use clack_host::prelude::*;
-use clack_host::utils::Cookie;
 
 use clack_plugin_gain::clap_entry;
 
@@ -54,7 +52,7 @@ pub fn it_works() {
     )
     .unwrap();
 
-    let mut plugin_main_thread = plugin.plugin_handle();
+    let mut plugin_main_thread = plugin.plugin_handle_unchecked();
     let ports_ext = plugin_main_thread
         .get_extension::<PluginAudioPorts>()
         .unwrap();
@@ -77,15 +75,14 @@ pub fn it_works() {
     };
 
     let processor = plugin
-        .activate(|_, _| TestHostAudioProcessor, configuration)
+        .activate_unchecked(|_, _| TestHostAudioProcessor, configuration)
         .unwrap();
 
     assert!(plugin.is_active());
 
-    let mut input_events = EventBuffer::with_capacity(10);
-    let mut output_events = EventBuffer::with_capacity(10);
+    let mut events = EventIo::with_capacity(10);
 
-    input_events.push(&ParamValueEvent::new(
+    events.push_input(&ParamValueEvent::new(
         0,
         ClapId::new(1),
         Pckn::match_all(),
@@ -115,12 +112,14 @@ pub fn it_works() {
         latency: 0,
     }]);
 
+    let (input_events, mut output_events) = events.split();
+
     processor
         .process(
             &input_channels,
             &mut output_channels,
-            &input_events.as_input(),
-            &mut output_events.as_output(),
+            &input_events,
+            &mut output_events,
             None,
             None,
         )
@@ -135,7 +134,7 @@ pub fn it_works() {
         }
     }
 
-    plugin.deactivate(processor.stop_processing());
+    plugin.deactivate_unchecked(processor.stop_processing());
 }
 
 struct TestHostMainThread;
@@ -0,0 +1,107 @@
+#![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
+#![deny(missing_docs, clippy::missing_docs_in_private_items, unsafe_code)]
+#![doc = include_str!("../README.md")]
+#![no_std]
+
+use clack_plugin::prelude::*;
+
+/// The ID of the volume parameter.
+pub const VOLUME: ClapId = ClapId::new(1);
+
+/// The type that represents our plugin in Clack.
+pub struct NoStdGainPlugin;
+
+impl Plugin for NoStdGainPlugin {
+    type AudioProcessor<'a> = NoStdGainPluginAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+}
+
+impl DefaultPluginFactory for NoStdGainPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        use features::*;
+
+        PluginDescriptor::new(
+            "org.rust-audio.clack.no-std-gain",
+            "Clack no_std Gain Example",
+        )
+        .with_features([AUDIO_EFFECT, STEREO])
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(())
+    }
+}
+
+/// Our plugin's audio processor. It lives in the audio thread.
+pub struct NoStdGainPluginAudioProcessor {
+    /// The current volume, as last received from a parameter event.
+    volume: f32,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for NoStdGainPluginAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self { volume: 1.0 })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let mut port_pair = audio
+            .port_pair(0)
+            .ok_or(PluginError::Message("No input/output ports found"))?;
+
+        // Errors from Clack's own types can still be converted into a PluginError with `?`.
+        let mut channels = port_pair
+            .channels()?
+            .into_f32()
+            .ok_or(PluginError::Message("Expected f32 input/output"))?;
+
+        for event_batch in events.input.batch() {
+            for event in event_batch.events() {
+                if let Some(CoreEventSpace::ParamValue(event)) = event.as_core_event() {
+                    if event.param_id() == Some(VOLUME) {
+                        self.volume = event.value() as f32;
+                    }
+                }
+            }
+
+            let bounds = event_batch.sample_bounds();
+
+            for pair in channels.iter_mut() {
+                match pair {
+                    ChannelPair::InPlace(buf) => {
+                        for sample in &mut buf[bounds] {
+                            *sample *= self.volume
+                        }
+                    }
+                    ChannelPair::InputOutput(input, output) => {
+                        for (input, output) in input[bounds].iter().zip(&mut output[bounds]) {
+                            *output = input * self.volume
+                        }
+                    }
+                    ChannelPair::InputOnly(_) | ChannelPair::OutputOnly(_) => {}
+                }
+            }
+        }
+
+        Ok(ProcessStatus::ContinueIfNotQuiet)
+    }
+}
+
+clack_export_entry!(SinglePluginEntry<NoStdGainPlugin>);
@@ -0,0 +1,88 @@
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+
+use clack_plugin_no_std_gain::{clap_entry, VOLUME};
+
+struct TestHostShared;
+
+impl SharedHandler<'_> for TestHostShared {
+    fn request_restart(&self) {}
+    fn request_process(&self) {}
+    fn request_callback(&self) {}
+}
+
+struct TestHostHandlers;
+
+impl HostHandlers for TestHostHandlers {
+    type Shared<'a> = TestHostShared;
+    type MainThread<'a> = ();
+    type AudioProcessor<'a> = ();
+}
+
+#[test]
+pub fn volume_is_applied() {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+
+    let mut plugin = PluginInstance::<TestHostHandlers>::new(
+        |_| TestHostShared,
+        |_| (),
+        &bundle,
+        std::ffi::CStr::from_bytes_with_nul(b"org.rust-audio.clack.no-std-gain\0").unwrap(),
+        &info,
+    )
+    .unwrap();
+
+    let configuration = PluginAudioConfiguration {
+        sample_rate: 44_100.0,
+        min_frames_count: 4,
+        max_frames_count: 4,
+    };
+
+    let mut processor = plugin
+        .activate_unchecked(|_, _| (), configuration)
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut input = [[1.0f32; 4]; 2];
+    let mut output = [[0.0f32; 4]; 2];
+    let mut input_ports = AudioPorts::with_capacity(2, 1);
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+    let mut events = EventBuffer::new();
+    events.push(&ParamValueEvent::new(
+        2,
+        VOLUME,
+        Pckn::match_all(),
+        0.5,
+        Cookie::empty(),
+    ));
+
+    processor
+        .process(
+            &input_ports.with_input_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only(
+                    input.iter_mut().map(InputChannel::variable),
+                ),
+                latency: 0,
+            }]),
+            &mut output_ports.with_output_buffers([AudioPortBuffer {
+                channels: AudioPortBufferType::f32_output_only(
+                    output.iter_mut().map(|c| &mut c[..]),
+                ),
+                latency: 0,
+            }]),
+            &events.as_input(),
+            &mut OutputEvents::void(),
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(output, [[1.0, 1.0, 0.5, 0.5]; 2]);
+
+    plugin.deactivate_unchecked(processor.stop_processing());
+}
@@ -4,9 +4,7 @@
 
 use crate::params::{PolySynthParamModulations, PolySynthParams};
 use crate::poly_oscillator::PolyOscillator;
-use clack_extensions::state::PluginState;
-use clack_extensions::{audio_ports::*, note_ports::*, params::*};
-use clack_plugin::events::spaces::CoreEventSpace;
+use clack_extensions::prelude::*;
 use clack_plugin::prelude::*;
 
 mod oscillator;
@@ -37,7 +35,7 @@ impl Plugin for PolySynthPlugin {
 
 impl DefaultPluginFactory for PolySynthPlugin {
     fn get_descriptor() -> PluginDescriptor {
-        use clack_plugin::plugin::features::*;
+        use features::*;
 
         PluginDescriptor::new("org.rust-audio.clack.polysynth", "Clack PolySynth Example")
             .with_features([SYNTHESIZER, MONO, INSTRUMENT])
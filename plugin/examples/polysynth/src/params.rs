@@ -1,12 +1,8 @@
 //! Contains all types and implementations related to parameter management.
 
 use crate::{PolySynthAudioProcessor, PolySynthPluginMainThread};
-use clack_extensions::params::*;
-use clack_extensions::state::PluginStateImpl;
-use clack_plugin::events::event_types::{ParamModEvent, ParamValueEvent};
-use clack_plugin::events::spaces::CoreEventSpace;
+use clack_extensions::prelude::*;
 use clack_plugin::prelude::*;
-use clack_plugin::stream::{InputStream, OutputStream};
 use std::ffi::CStr;
 use std::fmt::Write as _;
 use std::io::{Read, Write as _};
@@ -0,0 +1,88 @@
+//! A minimal radix-2 FFT, to keep this example free of dependencies.
+
+use std::f32::consts::PI;
+
+/// A complex number.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Complex {
+    /// The real part.
+    pub re: f32,
+    /// The imaginary part.
+    pub im: f32,
+}
+
+impl Complex {
+    /// Multiplies two complex numbers.
+    #[inline]
+    fn mul(self, other: Self) -> Self {
+        Self {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+/// Transforms the given buffer in-place, using the iterative Cooley-Tukey algorithm.
+///
+/// If `inverse` is `true`, this computes the inverse transform, including its `1 / N` scaling.
+///
+/// # Panics
+///
+/// This panics if the buffer's length isn't a power of two.
+pub fn fft(buffer: &mut [Complex], inverse: bool) {
+    let len = buffer.len();
+    assert!(len.is_power_of_two(), "FFT size must be a power of two");
+
+    // Bit-reversal permutation.
+    let mut j = 0;
+    for i in 1..len {
+        let mut bit = len >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+
+    let mut size = 2;
+    while size <= len {
+        let angle = sign * 2.0 * PI / size as f32;
+
+        for start in (0..len).step_by(size) {
+            for k in 0..size / 2 {
+                let twiddle = Complex {
+                    re: (angle * k as f32).cos(),
+                    im: (angle * k as f32).sin(),
+                };
+
+                let even = buffer[start + k];
+                let odd = buffer[start + k + size / 2].mul(twiddle);
+
+                buffer[start + k] = Complex {
+                    re: even.re + odd.re,
+                    im: even.im + odd.im,
+                };
+                buffer[start + k + size / 2] = Complex {
+                    re: even.re - odd.re,
+                    im: even.im - odd.im,
+                };
+            }
+        }
+
+        size *= 2;
+    }
+
+    if inverse {
+        let scale = 1.0 / len as f32;
+        for value in buffer {
+            value.re *= scale;
+            value.im *= scale;
+        }
+    }
+}
@@ -0,0 +1,201 @@
+#![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs, clippy::missing_docs_in_private_items, unsafe_code)]
+
+use crate::overlap_add::{OverlapAdd, HOP_SIZE};
+use clack_extensions::params::set::{ParamDef, ParamSet, ParamSetProvider};
+use clack_extensions::prelude::*;
+use clack_plugin::events::io::InputEvents;
+use clack_plugin::prelude::*;
+
+mod fft;
+mod overlap_add;
+
+pub use overlap_add::{FFT_SIZE, LATENCY};
+
+/// The unique identifier for the Gain parameter.
+pub const PARAM_GAIN_ID: ClapId = ClapId::new(0);
+
+/// The type that represents our plugin in Clack.
+///
+/// This is what implements the [`Plugin`] trait, and where all the other subtypes are attached.
+pub struct SpectralGainPlugin;
+
+impl Plugin for SpectralGainPlugin {
+    type AudioProcessor<'a> = SpectralGainAudioProcessor<'a>;
+    type Shared<'a> = SpectralGainShared;
+    type MainThread<'a> = SpectralGainMainThread<'a>;
+
+    fn declare_extensions(
+        builder: &mut PluginExtensions<Self>,
+        _shared: Option<&Self::Shared<'_>>,
+    ) {
+        builder
+            .register::<StaticAudioPorts<Self>>()
+            .register::<PluginLatency>()
+            .register::<PluginParams>();
+    }
+}
+
+impl DefaultPluginFactory for SpectralGainPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        use features::*;
+
+        PluginDescriptor::new(
+            "org.rust-audio.clack.spectral-gain",
+            "Clack Spectral Gain Example",
+        )
+        .with_features([AUDIO_EFFECT, STEREO])
+    }
+
+    fn new_shared(_host: HostSharedHandle<'_>) -> Result<Self::Shared<'_>, PluginError> {
+        Ok(SpectralGainShared {
+            params: ParamSet::new().with_param(PARAM_GAIN_ID, ParamDef::new("Gain", 0.0, 1.0, 1.0)),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        shared: &'a Self::Shared<'a>,
+    ) -> Result<Self::MainThread<'a>, PluginError> {
+        Ok(SpectralGainMainThread { shared })
+    }
+}
+
+impl AudioPortLayoutProvider for SpectralGainPlugin {
+    const AUDIO_PORTS: AudioPortLayout = AudioPortLayout::new(
+        &[AudioPortInfo::stereo(ClapId::new(0), b"main").main()],
+        &[AudioPortInfo::stereo(ClapId::new(0), b"main")
+            .main()
+            .with_in_place_pair(ClapId::new(0))],
+    );
+}
+
+/// The plugin data that gets shared between the Main Thread and the Audio Thread.
+pub struct SpectralGainShared {
+    /// The plugin's parameters.
+    params: ParamSet,
+}
+
+impl PluginShared<'_> for SpectralGainShared {}
+
+/// The data that belongs to the main thread of our plugin.
+pub struct SpectralGainMainThread<'a> {
+    /// A reference to the plugin's shared data.
+    shared: &'a SpectralGainShared,
+}
+
+impl<'a> PluginMainThread<'a, SpectralGainShared> for SpectralGainMainThread<'a> {}
+
+impl ParamSetProvider for SpectralGainMainThread<'_> {
+    fn param_set(&self) -> &ParamSet {
+        &self.shared.params
+    }
+}
+
+impl PluginLatencyImpl for SpectralGainMainThread<'_> {
+    fn get(&mut self) -> u32 {
+        LATENCY as u32
+    }
+}
+
+/// Our plugin's audio processor. It lives in the audio thread.
+///
+/// It processes each channel of its stereo input in the frequency domain, one hop at a time.
+pub struct SpectralGainAudioProcessor<'a> {
+    /// A reference to the plugin's shared data.
+    shared: &'a SpectralGainShared,
+    /// The frequency-domain processing of each channel.
+    channels: [OverlapAdd; 2],
+}
+
+impl<'a> PluginAudioProcessor<'a, SpectralGainShared, SpectralGainMainThread<'a>>
+    for SpectralGainAudioProcessor<'a>
+{
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut SpectralGainMainThread<'a>,
+        shared: &'a SpectralGainShared,
+        _audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        Ok(Self {
+            shared,
+            channels: [OverlapAdd::new(), OverlapAdd::new()],
+        })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        mut audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        // Blocks are split in hops, so that each chunk completes at most one FFT frame per
+        // channel. Blocks don't have to be a multiple of the hop size: the remaining frames are
+        // buffered by the overlap-add, and completed by the next block.
+        let mut chunks = audio.chunks_exact(HOP_SIZE as u32);
+
+        for mut chunk in &mut chunks {
+            self.process_chunk(&mut chunk, events.input)?;
+        }
+
+        if let Some(mut remainder) = chunks.into_remainder() {
+            self.process_chunk(&mut remainder, events.input)?;
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn reset(&mut self) {
+        for channel in &mut self.channels {
+            channel.reset();
+        }
+    }
+}
+
+impl SpectralGainAudioProcessor<'_> {
+    /// Processes a single chunk of the block, alongside the events that happen during it.
+    fn process_chunk(
+        &mut self,
+        chunk: &mut AudioChunk,
+        events: &InputEvents,
+    ) -> Result<(), PluginError> {
+        // The batches' sample indexes are relative to the start of the chunk.
+        for batch in chunk.batch_events(events) {
+            for event in batch.events() {
+                self.shared.params.handle_event(event);
+            }
+
+            let gain = self.shared.params.value(PARAM_GAIN_ID).unwrap_or(1.0) as f32;
+            let end = batch
+                .next_batch_first_sample()
+                .map_or(chunk.frames_count(), |end| end as u32);
+
+            for frame in batch.first_sample() as u32..end {
+                for (index, channel) in self.channels.iter_mut().enumerate() {
+                    // The input is read before the output is written, so this also works if the
+                    // host processes in-place.
+                    let input = chunk
+                        .input_sample::<f32>(0, index as u32, frame)
+                        .ok_or(PluginError::Message("Expected f32 stereo input"))?;
+
+                    let output = chunk
+                        .output_sample_mut::<f32>(0, index as u32, frame)
+                        .ok_or(PluginError::Message("Expected f32 stereo output"))?;
+
+                    *output = channel.tick(input, gain);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ParamSetProvider for SpectralGainAudioProcessor<'_> {
+    fn param_set(&self) -> &ParamSet {
+        &self.shared.params
+    }
+}
+
+clack_export_entry!(SinglePluginEntry<SpectralGainPlugin>);
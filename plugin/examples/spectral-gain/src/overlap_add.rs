@@ -0,0 +1,117 @@
+//! A streaming short-time Fourier transform, using overlap-add.
+
+use crate::fft::{fft, Complex};
+use std::f32::consts::PI;
+
+/// The size of each FFT frame, in samples.
+pub const FFT_SIZE: usize = 512;
+
+/// The number of samples between the start of two consecutive FFT frames.
+pub const HOP_SIZE: usize = FFT_SIZE / 4;
+
+/// The delay between the input and the output, in samples.
+///
+/// A frame can only be transformed once all of its samples have been received, and its first
+/// samples can only be output once all the frames overlapping them have been added.
+pub const LATENCY: usize = FFT_SIZE;
+
+/// The scaling that compensates for windowing every sample twice (when analyzing, then when
+/// synthesizing), for each of the overlapping frames.
+///
+/// With a Hann window and a hop of a quarter of the frame, the squared windows of the overlapping
+/// frames always sum to `1.5`.
+const OVERLAP_SCALE: f32 = 1.0 / 1.5;
+
+/// Processes a single channel, frame by frame, in the frequency domain.
+///
+/// Samples are fed one at a time, and every [`HOP_SIZE`] samples, the last [`FFT_SIZE`] samples
+/// are windowed, transformed, processed, transformed back, and added to the output.
+pub struct OverlapAdd {
+    /// The analysis and synthesis window.
+    window: Vec<f32>,
+    /// The last [`FFT_SIZE`] input samples.
+    input: Vec<f32>,
+    /// The sum of the processed frames that still overlap with upcoming ones.
+    output: Vec<f32>,
+    /// The output samples that are complete, and are being played back.
+    ready: Vec<f32>,
+    /// The index of the next sample in the current hop.
+    position: usize,
+    /// The spectrum of the frame being processed.
+    spectrum: Vec<Complex>,
+}
+
+impl OverlapAdd {
+    /// Creates a new, silent processor.
+    pub fn new() -> Self {
+        let window = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+
+        Self {
+            window,
+            input: vec![0.0; FFT_SIZE],
+            output: vec![0.0; FFT_SIZE],
+            ready: vec![0.0; HOP_SIZE],
+            position: 0,
+            spectrum: vec![Complex::default(); FFT_SIZE],
+        }
+    }
+
+    /// Feeds a single input sample, and returns the matching output sample, [`LATENCY`] samples
+    /// later.
+    ///
+    /// If this sample completes a frame, `gain` is applied to all of its frequency bins.
+    #[inline]
+    pub fn tick(&mut self, sample: f32, gain: f32) -> f32 {
+        let output = self.ready[self.position];
+
+        self.input[FFT_SIZE - HOP_SIZE + self.position] = sample;
+        self.position += 1;
+
+        if self.position == HOP_SIZE {
+            self.process_frame(gain);
+            self.position = 0;
+        }
+
+        output
+    }
+
+    /// Processes the current frame, and makes the next hop of output samples ready.
+    fn process_frame(&mut self, gain: f32) {
+        for ((bin, input), window) in self.spectrum.iter_mut().zip(&self.input).zip(&self.window) {
+            *bin = Complex {
+                re: input * window,
+                im: 0.0,
+            };
+        }
+
+        fft(&mut self.spectrum, false);
+
+        for bin in &mut self.spectrum {
+            bin.re *= gain;
+            bin.im *= gain;
+        }
+
+        fft(&mut self.spectrum, true);
+
+        for ((output, bin), window) in self.output.iter_mut().zip(&self.spectrum).zip(&self.window)
+        {
+            *output += bin.re * window * OVERLAP_SCALE;
+        }
+
+        self.ready.copy_from_slice(&self.output[..HOP_SIZE]);
+
+        self.output.copy_within(HOP_SIZE.., 0);
+        self.output[FFT_SIZE - HOP_SIZE..].fill(0.0);
+        self.input.copy_within(HOP_SIZE.., 0);
+    }
+
+    /// Clears all the buffered samples.
+    pub fn reset(&mut self) {
+        self.input.fill(0.0);
+        self.output.fill(0.0);
+        self.ready.fill(0.0);
+        self.position = 0;
+    }
+}
@@ -0,0 +1,139 @@
+use clack_extensions::latency::PluginLatency;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::prelude::*;
+use std::ffi::CStr;
+
+use clack_plugin_spectral_gain::{clap_entry, FFT_SIZE, LATENCY, PARAM_GAIN_ID};
+
+/// Block sizes that are not multiples of the hop size, so that chunk remainders are processed.
+const BLOCK_SIZES: [usize; 4] = [100, 333, 64, 512];
+
+fn load() -> PluginBundle {
+    // SAFETY: only called this once per test here
+    unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap()
+}
+
+fn instantiate(bundle: &PluginBundle) -> PluginInstance<()> {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+    let id = CStr::from_bytes_with_nul(b"org.rust-audio.clack.spectral-gain\0").unwrap();
+
+    PluginInstance::<()>::new(|_| (), |_| (), bundle, id, &info).unwrap()
+}
+
+/// Processes the given stereo input in blocks of varying sizes, with the gain parameter set to
+/// `gain` at the start, and returns the left and right outputs.
+fn run(input: &[f32], gain: f64) -> [Vec<f32>; 2] {
+    let bundle = load();
+    let mut instance = instantiate(&bundle);
+    let mut processor = instance
+        .activate_unchecked(
+            |_, _| (),
+            PluginAudioConfiguration {
+                sample_rate: 48_000.0,
+                min_frames_count: 1,
+                max_frames_count: 512,
+            },
+        )
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut output = [vec![0.0f32; input.len()], vec![0.0f32; input.len()]];
+
+    let mut input_ports = AudioPorts::with_capacity(2, 1);
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+    let mut input_events = EventBuffer::new();
+
+    let mut start = 0;
+    for block_size in BLOCK_SIZES.iter().cycle() {
+        if start >= input.len() {
+            break;
+        }
+
+        let end = (start + block_size).min(input.len());
+
+        input_events.clear();
+        if start == 0 {
+            input_events.push(&ParamValueEvent::new(
+                0,
+                PARAM_GAIN_ID,
+                Pckn::match_all(),
+                gain,
+                Cookie::empty(),
+            ));
+        }
+
+        let mut left = input[start..end].to_vec();
+        let mut right: Vec<f32> = left.iter().map(|s| -s).collect();
+        let [output_l, output_r] = &mut output;
+
+        processor
+            .process(
+                &input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only([
+                        InputChannel::variable(&mut left[..]),
+                        InputChannel::variable(&mut right[..]),
+                    ]),
+                    latency: 0,
+                }]),
+                &mut output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only([
+                        &mut output_l[start..end],
+                        &mut output_r[start..end],
+                    ]),
+                    latency: 0,
+                }]),
+                &input_events.as_input(),
+                &mut OutputEvents::void(),
+                None,
+                None,
+            )
+            .unwrap();
+
+        start = end;
+    }
+
+    instance.deactivate_unchecked(processor.stop_processing());
+    output
+}
+
+#[test]
+pub fn latency_is_reported() {
+    let bundle = load();
+    let mut instance = instantiate(&bundle);
+    let mut handle = instance.plugin_handle_unchecked();
+
+    let latency = handle
+        .get_extension::<PluginLatency>()
+        .unwrap()
+        .get(&mut handle);
+
+    assert_eq!(latency, LATENCY as u32);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Processing this many frames is too slow under Miri
+pub fn output_is_delayed_and_scaled_input() {
+    let input: Vec<f32> = (0..FFT_SIZE * 8)
+        .map(|i| (i as f32 * 0.05).sin() * 0.5 + (i as f32 * 0.31).cos() * 0.25)
+        .collect();
+
+    let [left, right] = run(&input, 0.5);
+
+    for (frame, (left, right)) in left.iter().zip(&right).enumerate() {
+        // Nothing comes out until the first input sample went through all of its frames.
+        let expected = frame
+            .checked_sub(LATENCY)
+            .map_or(0.0, |delayed| input[delayed] * 0.5);
+
+        assert!(
+            (left - expected).abs() < 1e-4,
+            "Left output at frame {frame} is {left}, expected {expected}"
+        );
+        assert!(
+            (right + expected).abs() < 1e-4,
+            "Right output at frame {frame} is {right}, expected {}",
+            -expected
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! A delay line whose length is measured in beats rather than in time.
+
+use clack_plugin::events::event_types::TransportEvent;
+use clack_plugin::utils::{TempoCurve, TempoIntegrator};
+
+/// The tempo the delay follows when the host doesn't provide one, in beats per minute.
+const FALLBACK_TEMPO: f64 = 120.0;
+
+/// Where a delayed sample is read from, in the delay's history.
+#[derive(Copy, Clone, Debug)]
+struct Tap {
+    /// The index of the frame right before the delayed position.
+    before: usize,
+    /// The index of the frame right after the delayed position.
+    after: usize,
+    /// How far the delayed position is between the two frames, from `0.0` to `1.0`.
+    fraction: f32,
+}
+
+/// A stereo delay line, which delays its input by a constant number of beats.
+///
+/// The delay keeps its own beat clock, which counts the beats elapsed since it was created by
+/// integrating the host's tempo, including its ramps. Every frame of the history is stamped with
+/// the clock, and delayed frames are looked up by beat, rather than by a length in frames
+/// derived from the current tempo. This keeps the echoes on the beat grid, even when the tempo
+/// changes while they are still in the history.
+pub struct BeatDelay {
+    /// The length of the delay, in beats.
+    delay_beats: f64,
+    /// How much of the delayed signal is fed back into the delay.
+    feedback: f32,
+    /// Integrates the host's tempo into the beat clock.
+    integrator: TempoIntegrator,
+    /// The beat clock at the start of the next block.
+    clock: f64,
+    /// The beat clock value of every frame in the history.
+    beats: Vec<f64>,
+    /// The history of every channel, with the feedback already applied.
+    history: [Vec<f32>; 2],
+    /// The total number of frames written to the history.
+    written: u64,
+    /// The absolute index of the frame the delayed position was last found after.
+    read: u64,
+    /// The taps of every frame of the current block.
+    taps: Vec<Option<Tap>>,
+}
+
+impl BeatDelay {
+    /// Creates a new delay of the given length in beats, which keeps up to `history_frames`
+    /// frames of history.
+    pub fn new(
+        delay_beats: f64,
+        feedback: f32,
+        sample_rate: f64,
+        history_frames: usize,
+        max_frames_count: u32,
+    ) -> Self {
+        let mut integrator = TempoIntegrator::new(sample_rate, TempoCurve::Ramped);
+        integrator.set_tempo(FALLBACK_TEMPO);
+
+        Self {
+            delay_beats,
+            feedback,
+            integrator,
+            clock: 0.0,
+            beats: vec![0.0; history_frames],
+            history: [vec![0.0; history_frames], vec![0.0; history_frames]],
+            written: 0,
+            read: 0,
+            taps: Vec::with_capacity(max_frames_count as usize),
+        }
+    }
+
+    /// Clears the history and the beat clock.
+    pub fn reset(&mut self) {
+        self.clock = 0.0;
+        self.written = 0;
+        self.read = 0;
+
+        for channel in &mut self.history {
+            channel.fill(0.0);
+        }
+    }
+
+    /// Stamps the frames of the next block with the beat clock, and finds where their delayed
+    /// samples are in the history.
+    ///
+    /// `transport` is the transport at the start of the block, and `changes` are the transport
+    /// events happening within it, sorted by time.
+    pub fn prepare<'a>(
+        &mut self,
+        frames_count: u32,
+        transport: Option<&TransportEvent>,
+        changes: impl IntoIterator<Item = &'a TransportEvent>,
+    ) {
+        self.follow(transport);
+        self.taps.clear();
+
+        let mut changes = changes.into_iter().peekable();
+        // The beat clock and frame at which the integrator was last updated.
+        let mut segment = (self.clock, 0);
+
+        for frame in 0..frames_count {
+            while let Some(change) = changes.next_if(|c| c.header.time() <= frame) {
+                let (clock, start) = segment;
+                segment = (clock + self.integrator.advance(frame - start), frame);
+                self.follow(Some(change));
+            }
+
+            let (clock, start) = segment;
+            let beat = clock + self.integrator.beats_in(frame - start);
+
+            let index = self.written + frame as u64;
+            let position = self.wrap(index);
+            self.beats[position] = beat;
+
+            let tap = self.find_tap(index, beat - self.delay_beats);
+            self.taps.push(tap);
+        }
+
+        let (clock, start) = segment;
+        self.clock = clock + self.integrator.advance(frames_count - start);
+    }
+
+    /// Delays the given channel of the block prepared by [`prepare`](Self::prepare), in place.
+    ///
+    /// Each sample is replaced with the dry sample, mixed with the delayed signal.
+    pub fn process_channel(&mut self, channel: usize, samples: &mut [f32], mix: f32) {
+        self.process_with(channel, samples.len(), |frame, delayed| {
+            let input = samples[frame];
+            samples[frame] = input + delayed * mix;
+            input
+        });
+    }
+
+    /// Delays the given channel of the block prepared by [`prepare`](Self::prepare), from
+    /// `input` to `output`.
+    pub fn process_channel_to(
+        &mut self,
+        channel: usize,
+        input: &[f32],
+        output: &mut [f32],
+        mix: f32,
+    ) {
+        let frames = input.len().min(output.len());
+        self.process_with(channel, frames, |frame, delayed| {
+            output[frame] = input[frame] + delayed * mix;
+            input[frame]
+        });
+    }
+
+    /// Marks the block prepared by [`prepare`](Self::prepare) as processed.
+    pub fn finish(&mut self, frames_count: u32) {
+        self.written += frames_count as u64;
+    }
+
+    /// Runs the delay over the given number of frames of a channel.
+    ///
+    /// For every frame, `mix_frame` receives the delayed sample, and returns the input sample
+    /// that is written to the history.
+    fn process_with(
+        &mut self,
+        channel: usize,
+        frames: usize,
+        mut mix_frame: impl FnMut(usize, f32) -> f32,
+    ) {
+        let Some(history) = self.history.get_mut(channel) else {
+            return;
+        };
+
+        let length = history.len() as u64;
+
+        for (frame, tap) in self.taps.iter().enumerate().take(frames) {
+            let delayed = match tap {
+                Some(tap) => {
+                    history[tap.before] * (1.0 - tap.fraction) + history[tap.after] * tap.fraction
+                }
+                None => 0.0,
+            };
+
+            let input = mix_frame(frame, delayed);
+            history[((self.written + frame as u64) % length) as usize] =
+                input + delayed * self.feedback;
+        }
+    }
+
+    /// Finds where the given beat is in the history, before the frame at the given index.
+    fn find_tap(&mut self, index: u64, target: f64) -> Option<Tap> {
+        // The frame being written can't be read yet, and the oldest ones may be overwritten.
+        let oldest = (index + 1).saturating_sub(self.beats.len() as u64);
+        let latest = index.checked_sub(1)?;
+
+        self.read = self.read.clamp(oldest, latest);
+        while self.read < latest && self.beats[self.wrap(self.read + 1)] <= target {
+            self.read += 1;
+        }
+
+        if self.read >= latest {
+            return None;
+        }
+
+        let before = self.beats[self.wrap(self.read)];
+        let after = self.beats[self.wrap(self.read + 1)];
+        if target < before || after <= before {
+            return None;
+        }
+
+        Some(Tap {
+            before: self.wrap(self.read),
+            after: self.wrap(self.read + 1),
+            fraction: ((target - before) / (after - before)) as f32,
+        })
+    }
+
+    /// Follows the tempo of the given transport, or the fallback tempo if it has none.
+    fn follow(&mut self, transport: Option<&TransportEvent>) {
+        if let Some(transport) = transport {
+            self.integrator.update(transport);
+        }
+
+        if self.integrator.tempo().is_none() {
+            self.integrator.set_tempo(FALLBACK_TEMPO);
+        }
+    }
+
+    /// Returns the position in the history of the frame at the given absolute index.
+    #[inline]
+    fn wrap(&self, index: u64) -> usize {
+        (index % self.beats.len() as u64) as usize
+    }
+}
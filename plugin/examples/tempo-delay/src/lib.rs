@@ -0,0 +1,160 @@
+#![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs, clippy::missing_docs_in_private_items, unsafe_code)]
+
+use crate::delay::BeatDelay;
+use clack_extensions::prelude::*;
+use clack_plugin::events::event_types::TransportEvent;
+use clack_plugin::prelude::*;
+
+mod delay;
+
+/// The length of the delay, in beats: a dotted eighth note.
+pub const DELAY_BEATS: f64 = 0.75;
+
+/// How much of the delayed signal is fed back into the delay.
+pub const FEEDBACK: f32 = 0.4;
+
+/// How much of the delayed signal is mixed with the dry signal.
+pub const MIX: f32 = 0.5;
+
+/// How much history the delay keeps, in seconds. This is enough for the delay to stay in sync
+/// down to 12 BPM.
+const HISTORY_SECONDS: f64 = 4.0;
+
+/// The type that represents our plugin in Clack.
+///
+/// This is what implements the [`Plugin`] trait, and where all the other subtypes are attached.
+pub struct TempoDelayPlugin;
+
+impl Plugin for TempoDelayPlugin {
+    type AudioProcessor<'a> = TempoDelayAudioProcessor;
+    type Shared<'a> = ();
+    type MainThread<'a> = ();
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&()>) {
+        builder.register::<StaticAudioPorts<Self>>();
+    }
+}
+
+impl DefaultPluginFactory for TempoDelayPlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        use features::*;
+
+        PluginDescriptor::new(
+            "org.rust-audio.clack.tempo-delay",
+            "Clack Tempo Delay Example",
+        )
+        .with_features([AUDIO_EFFECT, DELAY, STEREO])
+    }
+
+    fn new_shared(_host: HostSharedHandle) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    fn new_main_thread<'a>(
+        _host: HostMainThreadHandle<'a>,
+        _shared: &'a (),
+    ) -> Result<(), PluginError> {
+        Ok(())
+    }
+}
+
+impl AudioPortLayoutProvider for TempoDelayPlugin {
+    const AUDIO_PORTS: AudioPortLayout = AudioPortLayout::new(
+        &[AudioPortInfo::stereo(ClapId::new(0), b"main").main()],
+        &[AudioPortInfo::stereo(ClapId::new(0), b"main")
+            .main()
+            .with_in_place_pair(ClapId::new(0))],
+    );
+}
+
+/// Our plugin's audio processor. It lives in the audio thread.
+///
+/// It delays its stereo input by [`DELAY_BEATS`] beats, following the host's tempo, and its
+/// ramps, with sample accuracy.
+pub struct TempoDelayAudioProcessor {
+    /// The delay line.
+    delay: BeatDelay,
+    /// The transport events of the current block.
+    ///
+    /// These have to be copied, as the input events can't be kept while audio is processed.
+    transport_changes: Vec<TransportEvent>,
+}
+
+impl<'a> PluginAudioProcessor<'a, (), ()> for TempoDelayAudioProcessor {
+    fn activate(
+        _host: HostAudioProcessorHandle<'a>,
+        _main_thread: &mut (),
+        _shared: &'a (),
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        let history_frames = (audio_config.sample_rate * HISTORY_SECONDS).ceil() as usize;
+
+        Ok(Self {
+            delay: BeatDelay::new(
+                DELAY_BEATS,
+                FEEDBACK,
+                audio_config.sample_rate,
+                history_frames,
+                audio_config.max_frames_count,
+            ),
+            transport_changes: Vec::with_capacity(16),
+        })
+    }
+
+    fn process(
+        &mut self,
+        process: Process,
+        mut audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        let frames_count = audio.frames_count();
+
+        // The tempo can change in the middle of the block. Changes past the capacity are dropped,
+        // and picked up on the next block.
+        self.transport_changes.clear();
+        for change in events
+            .input
+            .iter()
+            .filter_map(|e| e.as_event::<TransportEvent>())
+        {
+            if self.transport_changes.len() < self.transport_changes.capacity() {
+                self.transport_changes.push(*change);
+            }
+        }
+
+        self.delay
+            .prepare(frames_count, process.transport, &self.transport_changes);
+
+        let mut port_pair = audio
+            .port_pair(0)
+            .ok_or(PluginError::Message("No input/output ports found"))?;
+
+        let mut channels = port_pair
+            .channels()?
+            .into_f32()
+            .ok_or(PluginError::Message("Expected f32 input/output"))?;
+
+        for (channel, pair) in channels.iter_mut().enumerate() {
+            match pair {
+                ChannelPair::InputOnly(_) | ChannelPair::OutputOnly(_) => {}
+                ChannelPair::InPlace(buf) => self.delay.process_channel(channel, buf, MIX),
+                ChannelPair::InputOutput(input, output) => {
+                    self.delay.process_channel_to(channel, input, output, MIX)
+                }
+            }
+        }
+
+        self.delay.finish(frames_count);
+
+        // The echoes keep going after the input stops.
+        Ok(ProcessStatus::Continue)
+    }
+
+    fn reset(&mut self) {
+        self.delay.reset();
+    }
+}
+
+clack_export_entry!(SinglePluginEntry<TempoDelayPlugin>);
@@ -0,0 +1,178 @@
+use clack_host::prelude::*;
+use clack_host::process::transport::TransportSynthesizer;
+use std::ffi::CStr;
+
+use clack_plugin_tempo_delay::{clap_entry, DELAY_BEATS, FEEDBACK, MIX};
+
+const SAMPLE_RATE: f64 = 48_000.0;
+const BLOCK_SIZE: u32 = 512;
+
+/// Feeds a single impulse at the given frame to the delay, and returns its left output.
+///
+/// The transport is given by the synthesizer, or omitted entirely if there is none.
+fn run(mut transport: Option<TransportSynthesizer>, impulse: usize, frames: usize) -> Vec<f32> {
+    let info = HostInfo::new("test", "", "", "").unwrap();
+
+    // SAFETY: only called this once here
+    let bundle = unsafe { PluginBundle::load_from_raw(&clap_entry, "") }.unwrap();
+    let id = CStr::from_bytes_with_nul(b"org.rust-audio.clack.tempo-delay\0").unwrap();
+
+    let mut instance = PluginInstance::<()>::new(|_| (), |_| (), &bundle, id, &info).unwrap();
+    let mut processor = instance
+        .activate_unchecked(
+            |_, _| (),
+            PluginAudioConfiguration {
+                sample_rate: SAMPLE_RATE,
+                min_frames_count: 1,
+                max_frames_count: BLOCK_SIZE,
+            },
+        )
+        .unwrap()
+        .start_processing()
+        .unwrap();
+
+    let mut input = vec![0.0f32; frames];
+    input[impulse] = 1.0;
+    let mut output = [vec![0.0f32; frames], vec![0.0f32; frames]];
+
+    let mut input_ports = AudioPorts::with_capacity(2, 1);
+    let mut output_ports = AudioPorts::with_capacity(2, 1);
+    let mut input_events = EventBuffer::new();
+
+    for start in (0..frames).step_by(BLOCK_SIZE as usize) {
+        let end = (start + BLOCK_SIZE as usize).min(frames);
+        let block = transport
+            .as_mut()
+            .map(|t| t.next_block((end - start) as u32));
+
+        input_events.clear();
+        if let Some(block) = &block {
+            input_events.push_all(block.events);
+        }
+
+        let mut left = input[start..end].to_vec();
+        let mut right = input[start..end].to_vec();
+        let [output_l, output_r] = &mut output;
+
+        processor
+            .process(
+                &input_ports.with_input_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_input_only([
+                        InputChannel::variable(&mut left[..]),
+                        InputChannel::variable(&mut right[..]),
+                    ]),
+                    latency: 0,
+                }]),
+                &mut output_ports.with_output_buffers([AudioPortBuffer {
+                    channels: AudioPortBufferType::f32_output_only([
+                        &mut output_l[start..end],
+                        &mut output_r[start..end],
+                    ]),
+                    latency: 0,
+                }]),
+                &input_events.as_input(),
+                &mut OutputEvents::void(),
+                None,
+                block.map(|b| b.transport),
+            )
+            .unwrap();
+    }
+
+    instance.deactivate_unchecked(processor.stop_processing());
+
+    let [left, right] = output;
+    assert_eq!(left, right);
+    left
+}
+
+/// Returns the song position of every frame, in beats, as seen by the host.
+fn positions(mut transport: TransportSynthesizer, frames: usize) -> Vec<f64> {
+    (0..frames)
+        .map(|_| transport.next_block(1).transport.song_pos_beats.to_float())
+        .collect()
+}
+
+/// Returns the frame at which the song position is the closest to the given beat.
+fn frame_at(positions: &[f64], beat: f64) -> usize {
+    let distance = |frame: &usize| (positions[*frame] - beat).abs();
+
+    (0..positions.len())
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+        .unwrap()
+}
+
+/// Returns the loudest frame of the output after the given one.
+fn loudest_after(output: &[f32], after: usize) -> usize {
+    (after + 1..output.len())
+        .max_by(|a, b| output[*a].total_cmp(&output[*b]))
+        .unwrap()
+}
+
+fn ramp() -> TransportSynthesizer {
+    let mut transport = TransportSynthesizer::new(SAMPLE_RATE);
+    transport.set_tempo(60.0);
+    // The ramp ends in the middle of a block.
+    transport.ramp_tempo(180.0, 96_000);
+    transport.play();
+    transport
+}
+
+/// Returns the tempo of the [`ramp`] at the given frame.
+fn ramp_tempo(frame: usize) -> f64 {
+    (60.0 + (180.0 - 60.0) * frame as f64 / 96_000.0).min(180.0)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Processing this many frames is too slow under Miri
+pub fn echoes_at_constant_tempo() {
+    let mut transport = TransportSynthesizer::new(SAMPLE_RATE);
+    transport.play();
+
+    // At 120 BPM and 48kHz, a dotted eighth note lasts 18000 frames.
+    let output = run(Some(transport), 1000, 40_000);
+    assert_eq!(output[1000], 1.0);
+    assert_eq!(output[1000 + 18_000], MIX);
+    assert_eq!(output[1000 + 36_000], MIX * FEEDBACK);
+
+    // Without a tempo from the host, the delay runs at 120 BPM too.
+    assert_eq!(run(None, 1000, 40_000), output);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Processing this many frames is too slow under Miri
+pub fn echoes_follow_tempo_ramps() {
+    const FRAMES: usize = 160_000;
+    let positions = positions(ramp(), FRAMES);
+
+    for impulse in [1000, 30_000, 80_000] {
+        let output = run(Some(ramp()), impulse, FRAMES);
+
+        let expected = frame_at(&positions, positions[impulse] + DELAY_BEATS);
+        let echo = loudest_after(&output, impulse);
+
+        assert!(
+            echo.abs_diff(expected) <= 1,
+            "Echo of the impulse at frame {impulse} is at frame {echo}, expected {expected}"
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Processing this many frames is too slow under Miri
+pub fn ramps_move_echoes_off_the_current_tempo() {
+    const FRAMES: usize = 100_000;
+    let impulse = 30_000;
+
+    let tempo = ramp_tempo(impulse);
+
+    let output = run(Some(ramp()), impulse, FRAMES);
+    let echo = loudest_after(&output, impulse);
+
+    // A delay using the tempo at the time of the impulse would echo much later, as the tempo
+    // keeps increasing during the delay.
+    let naive = impulse + (DELAY_BEATS * 60.0 * SAMPLE_RATE / tempo) as usize;
+    assert!(
+        naive > echo + 1000,
+        "Echo at frame {echo}, naive at {naive}"
+    );
+}
@@ -20,17 +20,29 @@
 
 use crate::extensions::wrapper::handle_panic;
 use crate::factory::Factory;
-use std::error::Error;
-use std::ffi::{c_void, CStr};
-use std::fmt::{Display, Formatter};
-use std::panic::{AssertUnwindSafe, UnwindSafe};
-use std::ptr::NonNull;
-use std::sync::Mutex;
+use clack_common::error::Error;
+use core::ffi::{c_void, CStr};
+use core::fmt::{Display, Formatter};
+use core::panic::{AssertUnwindSafe, UnwindSafe};
+use core::ptr::NonNull;
 
 pub use clack_common::entry::*;
 
+#[cfg(feature = "std")]
+mod shared_static;
 mod single;
 
+#[cfg(feature = "std")]
+pub use shared_static::SharedStatic;
+
+#[cfg(feature = "std")]
+type EntryMutex<T> = std::sync::Mutex<T>;
+#[cfg(feature = "std")]
+type EntryMutexGuard<'a, T> = std::sync::MutexGuard<'a, T>;
+#[cfg(not(feature = "std"))]
+type EntryMutex<T> = crate::internal_utils::SpinMutex<T>;
+#[cfg(not(feature = "std"))]
+type EntryMutexGuard<'a, T> = crate::internal_utils::SpinMutexGuard<'a, T>;
 pub use single::{DefaultPluginFactory, SinglePluginEntry};
 
 /// A prelude that's helpful for implementing custom [`Entry`] and [`PluginFactory`](crate::factory::plugin::PluginFactory) types.
@@ -63,7 +75,7 @@ pub mod prelude {
 /// two different plugins.
 ///
 /// ```
-/// use std::ffi::CStr;
+/// use core::ffi::CStr;
 /// use clack_plugin::entry::prelude::*;
 /// use clack_plugin::prelude::*;
 ///
@@ -185,7 +197,7 @@ pub trait Entry: Sized + Send + Sync + 'static {
 pub struct EntryLoadError;
 
 impl Display for EntryLoadError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str("Entry failed to load.")
     }
 }
@@ -206,7 +218,7 @@ impl Error for EntryLoadError {}
 /// an example of how to actually implement it.
 ///
 /// ```
-/// use std::ffi::CStr;
+/// use core::ffi::CStr;
 /// use clack_plugin::entry::prelude::*;
 /// use clack_plugin::prelude::*;
 ///
@@ -366,7 +378,7 @@ enum EntryHolderInner<E> {
 
 #[doc(hidden)]
 pub struct EntryHolder<E: Entry> {
-    inner: Mutex<EntryHolderInner<E>>,
+    inner: EntryMutex<EntryHolderInner<E>>,
 }
 
 use crate::entry::EntryHolderInner::*;
@@ -376,10 +388,19 @@ impl<E: Entry> EntryHolder<E> {
     #[allow(clippy::new_without_default)] // This is actually a private type
     pub const fn new() -> Self {
         Self {
-            inner: Mutex::new(Uninitialized),
+            inner: EntryMutex::new(Uninitialized),
         }
     }
 
+    /// Locks the entry, or returns `None` if the lock is poisoned, which means `init` panicked.
+    #[inline]
+    fn lock(&self) -> Option<EntryMutexGuard<'_, EntryHolderInner<E>>> {
+        #[cfg(feature = "std")]
+        return self.inner.lock().ok();
+        #[cfg(not(feature = "std"))]
+        return Some(self.inner.lock());
+    }
+
     /// # Safety
     ///
     /// Users *must* ensure this is called at least once before any other method, and that
@@ -398,7 +419,7 @@ impl<E: Entry> EntryHolder<E> {
         plugin_path: *const core::ffi::c_char,
         entry_factory: impl FnOnce(&CStr) -> Result<E, EntryLoadError> + UnwindSafe,
     ) -> bool {
-        let Ok(Ok(mut inner)) = handle_panic(|| self.inner.lock()) else {
+        let Ok(Some(mut inner)) = handle_panic(|| self.lock()) else {
             // A poisoned lock means init() panicked, so we consider the entry unusable.
             // Same if lock() itself panicked.
             return false;
@@ -420,6 +441,8 @@ impl<E: Entry> EntryHolder<E> {
                         entry,
                         reference_count: 1,
                     };
+                    #[cfg(feature = "std")]
+                    shared_static::registry::entry_initialized();
                     true
                 } else {
                     false
@@ -432,7 +455,7 @@ impl<E: Entry> EntryHolder<E> {
     ///
     /// Users must *only* call this after they are done with the whole entry.
     pub unsafe fn de_init(&self) {
-        let Ok(mut inner) = self.inner.lock() else {
+        let Some(mut inner) = self.lock() else {
             return;
         };
 
@@ -444,6 +467,10 @@ impl<E: Entry> EntryHolder<E> {
                 *reference_count -= 1;
             } else {
                 let _ = handle_panic(AssertUnwindSafe(|| *inner = Uninitialized));
+                drop(inner);
+
+                #[cfg(feature = "std")]
+                shared_static::registry::entry_deinitialized();
             }
         }
     }
@@ -457,7 +484,7 @@ impl<E: Entry> EntryHolder<E> {
             return core::ptr::null();
         }
 
-        let Ok(inner) = self.inner.lock() else {
+        let Some(inner) = self.lock() else {
             return core::ptr::null();
         };
 
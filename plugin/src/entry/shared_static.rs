@@ -0,0 +1,224 @@
+#![deny(unsafe_code)]
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::ffi::CStr;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+/// A process-wide, lazily-initialized resource that is shared between all instances of a plugin.
+///
+/// Hosts commonly load many instances of the same plugin in a single process. This type allows
+/// those instances to share expensive, immutable resources (e.g. wavetables or impulse responses),
+/// instead of each instance loading its own copy.
+///
+/// Values are keyed by plugin ID, so that a single [`SharedStatic`] can be used by multiple plugin
+/// types exposed by the same bundle. The first instance to call [`get_or_init`](Self::get_or_init)
+/// with a given plugin ID initializes the value, and all subsequent calls return a clone of the
+/// same [`Arc`], which is then usually stored into the plugin's [`Shared`](crate::plugin::Plugin::Shared)
+/// struct.
+///
+/// # Lifetime
+///
+/// While the bundle's entry is initialized, the value is kept alive even if no plugin instances
+/// currently use it, so that instantiating a new plugin doesn't need to initialize it again.
+///
+/// Once all Clack entries in the process have been de-initialized by the host, the value is dropped
+/// as soon as the last plugin instance using it is destroyed. If the host never de-initializes the
+/// entry, the value is simply kept alive until the process exits.
+///
+/// # Example
+///
+/// ```
+/// use clack_plugin::entry::SharedStatic;
+/// use clack_plugin::prelude::*;
+/// use core::ffi::CStr;
+/// use std::sync::Arc;
+///
+/// pub struct Wavetables(Vec<f32>);
+///
+/// static WAVETABLES: SharedStatic<Wavetables> = SharedStatic::new();
+///
+/// pub struct MySynthShared {
+///     wavetables: Arc<Wavetables>,
+/// }
+///
+/// impl<'a> PluginShared<'a> for MySynthShared {}
+///
+/// fn new_shared(_host: HostSharedHandle) -> Result<MySynthShared, PluginError> {
+///     let plugin_id = CStr::from_bytes_with_nul(b"org.example.synth\0").unwrap();
+///     let wavetables = WAVETABLES.get_or_init(plugin_id, || {
+///         Wavetables(vec![0.0; 2048]) // Load the wavetables, only once.
+///     });
+///
+///     Ok(MySynthShared { wavetables })
+/// }
+/// ```
+pub struct SharedStatic<T> {
+    values: Mutex<Vec<SharedStaticValue<T>>>,
+}
+
+struct SharedStaticValue<T> {
+    plugin_id: Box<CStr>,
+    weak: Weak<T>,
+    // Kept while the entry is initialized, so that the value outlives individual instances.
+    retained: Option<Arc<T>>,
+}
+
+impl<T: Send + Sync + 'static> SharedStatic<T> {
+    /// Creates a new, empty [`SharedStatic`].
+    #[allow(clippy::new_without_default)] // This is meant to be used in a static.
+    pub const fn new() -> Self {
+        Self {
+            values: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the value shared by all the instances of the plugin with the given ID, initializing
+    /// it using the given closure if it isn't alive yet.
+    ///
+    /// The closure is called at most once while the value is alive, even if multiple instances
+    /// are created concurrently.
+    ///
+    /// # Panics
+    ///
+    /// The given closure must not call any method on this same [`SharedStatic`], or it may
+    /// deadlock or panic.
+    pub fn get_or_init(&'static self, plugin_id: &CStr, init: impl FnOnce() -> T) -> Arc<T> {
+        match self.get_or_try_init(plugin_id, || Ok::<_, Infallible>(init())) {
+            Ok(value) => value,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Returns the value shared by all the instances of the plugin with the given ID, initializing
+    /// it using the given fallible closure if it isn't alive yet.
+    ///
+    /// If the closure fails, its error is returned, and the next call will attempt to initialize
+    /// the value again.
+    ///
+    /// See [`get_or_init`](Self::get_or_init) for more information.
+    pub fn get_or_try_init<E>(
+        &'static self,
+        plugin_id: &CStr,
+        init: impl FnOnce() -> Result<T, E>,
+    ) -> Result<Arc<T>, E> {
+        let mut values = self.lock();
+
+        let index = match values.iter().position(|v| *v.plugin_id == *plugin_id) {
+            Some(index) => index,
+            None => {
+                values.push(SharedStaticValue {
+                    plugin_id: plugin_id.into(),
+                    weak: Weak::new(),
+                    retained: None,
+                });
+                values.len() - 1
+            }
+        };
+
+        let value = &mut values[index];
+
+        let arc = match value.weak.upgrade() {
+            Some(arc) => arc,
+            None => {
+                let arc = Arc::new(init()?);
+                value.weak = Arc::downgrade(&arc);
+                arc
+            }
+        };
+
+        if value.retained.is_none() && registry::retain(self) {
+            value.retained = Some(arc.clone());
+        }
+
+        Ok(arc)
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, Vec<SharedStaticValue<T>>> {
+        // The values are always left in a consistent state, even if init() panicked.
+        self.values.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Send + Sync + 'static> registry::Release for SharedStatic<T> {
+    fn release(&self) {
+        let released: Vec<_> = self
+            .lock()
+            .iter_mut()
+            .filter_map(|v| v.retained.take())
+            .collect();
+
+        // Drop the values outside the lock, in case their destructor does something funny.
+        drop(released);
+    }
+}
+
+pub(crate) mod registry {
+    use std::sync::{Mutex, MutexGuard};
+
+    pub trait Release: Send + Sync {
+        fn release(&self);
+    }
+
+    struct Registry {
+        initialized_entries: usize,
+        shared_statics: Vec<&'static dyn Release>,
+    }
+
+    static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+        initialized_entries: 0,
+        shared_statics: Vec::new(),
+    });
+
+    #[inline]
+    fn lock() -> MutexGuard<'static, Registry> {
+        REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Registers the given shared static for release on entry de-initialization, and returns
+    /// whether its values should be retained (i.e. if any entry is currently initialized).
+    pub fn retain<R: Release>(shared_static: &'static R) -> bool {
+        let mut registry = lock();
+
+        if registry.initialized_entries == 0 {
+            return false;
+        }
+
+        let shared_static: &'static dyn Release = shared_static;
+        let address = (shared_static as *const dyn Release).cast::<()>();
+
+        if !registry
+            .shared_statics
+            .iter()
+            .any(|s| (*s as *const dyn Release).cast::<()>() == address)
+        {
+            registry.shared_statics.push(shared_static);
+        }
+
+        true
+    }
+
+    pub fn entry_initialized() {
+        lock().initialized_entries += 1;
+    }
+
+    pub fn entry_deinitialized() {
+        let to_release = {
+            let mut registry = lock();
+            registry.initialized_entries = registry.initialized_entries.saturating_sub(1);
+
+            if registry.initialized_entries > 0 {
+                return;
+            }
+
+            core::mem::take(&mut registry.shared_statics)
+        };
+
+        // Released outside the registry lock, as shared statics lock the registry while locked.
+        for shared_static in to_release {
+            shared_static.release();
+        }
+    }
+}
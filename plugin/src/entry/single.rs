@@ -1,9 +1,10 @@
 #![deny(unsafe_code)]
 
 use crate::entry::prelude::*;
+use crate::factory::compatibility::PluginCompatibilityFactory;
 use crate::prelude::*;
-use std::ffi::CStr;
-use std::marker::PhantomData;
+use core::ffi::CStr;
+use core::marker::PhantomData;
 
 /// An [`Entry`] that only exposes a single plugin type to the host.
 ///
@@ -54,13 +55,17 @@ use std::marker::PhantomData;
 /// ```
 pub struct SinglePluginEntry<P: DefaultPluginFactory> {
     plugin_factory: PluginFactoryWrapper<SinglePluginFactory<P>>,
+    compatibility_factory: PluginCompatibilityFactory,
 }
 
 impl<P: DefaultPluginFactory> Entry for SinglePluginEntry<P> {
     fn new(_plugin_path: &CStr) -> Result<Self, EntryLoadError> {
+        let descriptor = P::get_descriptor();
+
         Ok(Self {
+            compatibility_factory: PluginCompatibilityFactory::new([&descriptor]),
             plugin_factory: PluginFactoryWrapper::new(SinglePluginFactory {
-                descriptor: P::get_descriptor(),
+                descriptor,
                 _plugin: PhantomData,
             }),
         })
@@ -69,6 +74,10 @@ impl<P: DefaultPluginFactory> Entry for SinglePluginEntry<P> {
     #[inline]
     fn declare_factories<'a>(&'a self, builder: &mut EntryFactories<'a>) {
         builder.register_factory(&self.plugin_factory);
+
+        if !self.compatibility_factory.is_empty() {
+            builder.register_factory(&self.compatibility_factory);
+        }
     }
 }
 
@@ -131,7 +140,7 @@ pub trait DefaultPluginFactory: Plugin {
     ///
     /// # Errors
     /// This operation may fail for any reason, in which case `Err` is returned and the plugin is
-    /// not instantiated.
+    /// not instantiated. The error is logged to the host.
     fn new_shared(host: HostSharedHandle) -> Result<Self::Shared<'_>, PluginError>;
 
     /// Creates a new instance of the plugin's main thread.
@@ -140,7 +149,7 @@ pub trait DefaultPluginFactory: Plugin {
     ///
     /// # Errors
     /// This operation may fail for any reason, in which case `Err` is returned and the plugin is
-    /// not instantiated.
+    /// not instantiated. The error is logged to the host.
     fn new_main_thread<'a>(
         host: HostMainThreadHandle<'a>,
         shared: &'a Self::Shared<'a>,
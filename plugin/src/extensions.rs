@@ -13,7 +13,7 @@
 //! information.
 //!
 //! ```
-//! use std::ffi::CStr;
+//! use core::ffi::CStr;
 //! use clap_sys::ext::state::{CLAP_EXT_STATE, clap_plugin_state};
 //! use clack_plugin::extensions::prelude::*;
 //!
@@ -71,23 +71,150 @@
 //! where
 //!     for<'a> P::MainThread<'a>: PluginStateImplementation,
 //! {
-//!     PluginWrapper::<P>::handle(plugin, |p| {
+//!     // clap_plugin_state.load is a main-thread function: the handler receives the plugin's main
+//!     // thread struct, as well as its shared struct and the host's main-thread handle.
+//!     PluginWrapper::<P>::handle_main_thread(plugin, "clap_plugin_state.load", |main_thread, _shared, _host| {
 //!         let input = InputStream::from_raw_mut(&mut *(stream as *mut _));
-//!         // Retrieve the plugin's main thread struct, and call load() on it
-//!         p.main_thread().as_mut().load(input)?;
+//!         main_thread.load(input)?;
 //!         Ok(())
 //!     })
 //!     .is_some()
 //! }
 //! ```
 //!
+//! # Accessing the plugin from each thread
 //!
+//! Each function of a CLAP extension is documented as being called from a given thread. The
+//! [`PluginWrapper`](wrapper::PluginWrapper) provides a helper for each of them, which hands the
+//! matching parts of the plugin to the implementation:
+//!
+//! * [`handle_main_thread`](wrapper::PluginWrapper::handle_main_thread), for `[main-thread]`
+//!   functions, gives access to the plugin's [`MainThread`](Plugin::MainThread) struct;
+//! * [`handle_audio_processor`](wrapper::PluginWrapper::handle_audio_processor), for
+//!   `[audio-thread]` functions, gives access to the plugin's
+//!   [`AudioProcessor`](Plugin::AudioProcessor) struct, and fails if the plugin isn't active;
+//! * [`handle_shared`](wrapper::PluginWrapper::handle_shared), for `[thread-safe]` functions,
+//!   only gives access to the plugin's [`Shared`](Plugin::Shared) struct.
+//!
+//! All of them also give access to the plugin's `Shared` struct and to the matching host handle.
+//! Functions that need to behave differently depending on the plugin's state (e.g. being called
+//! from the main thread while the plugin is inactive, and from the audio thread while it is
+//! active) can use the more general [`handle_named`](wrapper::PluginWrapper::handle_named) instead.
+//!
+//! The following example implements the plugin side of a made-up vendor extension, which has a
+//! function for each thread. This is meant to be used as a template for third-party extension
+//! crates.
+//!
+//! ```
+//! use clack_plugin::extensions::prelude::*;
+//! use core::ffi::CStr;
+//!
+//! // The C FFI-compatible extension struct, as defined by the vendor's C header.
+//! pub const CLAP_EXT_EXAMPLE_METER: &CStr =
+//!     match CStr::from_bytes_with_nul(b"com.example.meter/1\0") {
+//!         Ok(id) => id,
+//!         Err(_) => panic!(),
+//!     };
+//!
+//! #[repr(C)]
+//! #[derive(Copy, Clone)]
+//! #[allow(non_camel_case_types)]
+//! pub struct clap_plugin_example_meter {
+//!     // [main-thread]
+//!     pub channel_count: Option<unsafe extern "C" fn(plugin: *const clap_plugin) -> u32>,
+//!     // [audio-thread]
+//!     pub reset: Option<unsafe extern "C" fn(plugin: *const clap_plugin)>,
+//!     // [thread-safe]
+//!     pub level: Option<unsafe extern "C" fn(plugin: *const clap_plugin, channel: u32) -> f32>,
+//! }
+//!
+//! // The extension type itself.
+//! #[derive(Copy, Clone)]
+//! pub struct PluginMeter(RawExtension<PluginExtensionSide, clap_plugin_example_meter>);
+//!
+//! // SAFETY: clap_plugin_example_meter is the plugin-side struct matching the identifier.
+//! unsafe impl Extension for PluginMeter {
+//!     const IDENTIFIER: &'static CStr = CLAP_EXT_EXAMPLE_METER;
+//!     type ExtensionSide = PluginExtensionSide;
+//!
+//!     #[inline]
+//!     unsafe fn from_raw(raw: RawExtension<Self::ExtensionSide>) -> Self {
+//!         Self(raw.cast())
+//!     }
+//! }
+//!
+//! // One trait per thread the extension's functions are called on.
+//! pub trait PluginMeterMainThreadImpl {
+//!     fn channel_count(&mut self) -> u32;
+//! }
+//!
+//! pub trait PluginMeterAudioProcessorImpl {
+//!     fn reset(&mut self);
+//! }
+//!
+//! pub trait PluginMeterSharedImpl {
+//!     fn level(&self, channel: u32) -> f32;
+//! }
+//!
+//! // SAFETY: The given struct is the CLAP extension struct for the matching side of this extension.
+//! unsafe impl<P: Plugin> ExtensionImplementation<P> for PluginMeter
+//! where
+//!     for<'a> P::MainThread<'a>: PluginMeterMainThreadImpl,
+//!     for<'a> P::AudioProcessor<'a>: PluginMeterAudioProcessorImpl,
+//!     for<'a> P::Shared<'a>: PluginMeterSharedImpl,
+//! {
+//!     const IMPLEMENTATION: RawExtensionImplementation =
+//!         RawExtensionImplementation::new(&clap_plugin_example_meter {
+//!             channel_count: Some(channel_count::<P>),
+//!             reset: Some(reset::<P>),
+//!             level: Some(level::<P>),
+//!         });
+//! }
+//!
+//! unsafe extern "C" fn channel_count<P: Plugin>(plugin: *const clap_plugin) -> u32
+//! where
+//!     for<'a> P::MainThread<'a>: PluginMeterMainThreadImpl,
+//! {
+//!     PluginWrapper::<P>::handle_main_thread(plugin, "clap_plugin_example_meter.channel_count", |main_thread, _, _| {
+//!         Ok(main_thread.channel_count())
+//!     })
+//!     // If anything went wrong, the error was logged: return a safe default value to the host.
+//!     .unwrap_or(0)
+//! }
+//!
+//! unsafe extern "C" fn reset<P: Plugin>(plugin: *const clap_plugin)
+//! where
+//!     for<'a> P::AudioProcessor<'a>: PluginMeterAudioProcessorImpl,
+//! {
+//!     // If the plugin isn't active, the handler isn't called and the error is logged.
+//!     PluginWrapper::<P>::handle_audio_processor(plugin, "clap_plugin_example_meter.reset", |audio_processor, _, _| {
+//!         audio_processor.reset();
+//!         Ok(())
+//!     });
+//! }
+//!
+//! unsafe extern "C" fn level<P: Plugin>(plugin: *const clap_plugin, channel: u32) -> f32
+//! where
+//!     for<'a> P::Shared<'a>: PluginMeterSharedImpl,
+//! {
+//!     PluginWrapper::<P>::handle_shared(plugin, "clap_plugin_example_meter.level", |shared, _| {
+//!         Ok(shared.level(channel))
+//!     })
+//!     .unwrap_or(0.0)
+//! }
+//! ```
+//!
+//! Plugins then implement those three traits, and register the extension in their
+//! [`declare_extensions`](Plugin::declare_extensions) implementation, like any other extension:
+//! `builder.register::<PluginMeter>();`.
 
 use crate::plugin::Plugin;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
 use core::ffi::c_void;
-use std::ffi::CStr;
-use std::marker::PhantomData;
-use std::ptr::NonNull;
+use core::ffi::CStr;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
 
 pub mod wrapper;
 
@@ -98,9 +225,12 @@ pub use clack_common::extensions::*;
 /// Plugins can declare the different extensions they support by using the
 /// [`register`](PluginExtensions::register) method on this struct, during a call to
 /// [`declare_extensions`](Plugin::declare_extensions).
+///
+/// The C wrapper functions of an extension are only compiled into the plugin if its
+/// [`register`](PluginExtensions::register) method is called for it: enabling more extensions in
+/// `clack-extensions` than the plugin actually registers doesn't increase its binary size.
 pub struct PluginExtensions<'a, P: ?Sized> {
-    found: Option<NonNull<c_void>>,
-    requested: &'a CStr,
+    query: ExtensionQuery<'a>,
     plugin_type: PhantomData<P>,
 }
 
@@ -108,15 +238,33 @@ impl<'a, P: Plugin> PluginExtensions<'a, P> {
     #[inline]
     pub(crate) fn new(requested: &'a CStr) -> Self {
         Self {
-            found: None,
-            requested,
+            query: ExtensionQuery {
+                found: None,
+                requested: Some(requested),
+                declared: None,
+            },
+            plugin_type: PhantomData,
+        }
+    }
+
+    /// Creates a builder that doesn't answer any query, but records the identifiers of all the
+    /// registered extensions into `declared` instead.
+    #[inline]
+    pub(crate) fn declaring(declared: &'a mut Vec<CString>) -> Self {
+        Self {
+            query: ExtensionQuery {
+                found: None,
+                requested: None,
+                declared: Some(declared),
+            },
             plugin_type: PhantomData,
         }
     }
 
     #[inline]
     pub(crate) fn found(&self) -> *const c_void {
-        self.found
+        self.query
+            .found
             .map(|p| p.as_ptr())
             .unwrap_or(core::ptr::null_mut())
     }
@@ -125,15 +273,65 @@ impl<'a, P: Plugin> PluginExtensions<'a, P> {
     pub fn register<E: ExtensionImplementation<P, ExtensionSide = PluginExtensionSide>>(
         &mut self,
     ) -> &mut Self {
-        if self.found.is_some() {
-            return self;
-        }
+        // Only the extension's identifier and vtable are specific to E: matching them against
+        // the requested identifier is shared by all extensions and plugin types.
+        self.query.offer(E::IDENTIFIER, E::IMPLEMENTATION.as_ptr());
+        self
+    }
+
+    /// Adds a raw, hand-written extension implementation to the list of extensions this plugin
+    /// supports.
+    ///
+    /// This is an escape hatch for extensions Clack does not support yet (e.g. bleeding-edge
+    /// drafts), or for interoperability with C code and other frameworks. Prefer implementing
+    /// [`ExtensionImplementation`] and using [`register`](Self::register) whenever possible.
+    ///
+    /// Like [`register`](Self::register), the first registered implementation matching the
+    /// requested identifier is the one exposed to the host, regardless of how it was registered.
+    ///
+    /// # Safety
+    ///
+    /// The given pointer must point to the C FFI-compatible extension struct (e.g. a
+    /// `clap_plugin_xxx` struct) that exactly matches the given `identifier`, as expected by the
+    /// host.
+    ///
+    /// This struct, as well as all the functions it points to, must remain valid for as long as
+    /// the plugin instance is alive (in practice, it should usually be a `static`). Those functions
+    /// will be called by the host with this plugin's `clap_plugin` pointer, and are responsible
+    /// for upholding all of the CLAP specification's thread-safety and validity requirements.
+    pub unsafe fn register_raw(
+        &mut self,
+        identifier: &CStr,
+        implementation: NonNull<c_void>,
+    ) -> &mut Self {
+        self.query.offer(identifier, implementation);
+        self
+    }
+}
 
-        if E::IDENTIFIER == self.requested {
-            self.found = Some(E::IMPLEMENTATION.as_ptr())
+/// The state of a single `get_extension` query, independent of the plugin type.
+struct ExtensionQuery<'a> {
+    found: Option<NonNull<c_void>>,
+    requested: Option<&'a CStr>,
+    declared: Option<&'a mut Vec<CString>>,
+}
+
+impl ExtensionQuery<'_> {
+    /// Keeps the given implementation if it matches the requested identifier, and no other
+    /// implementation was found before.
+    ///
+    /// If this query records declared extensions instead, the identifier is recorded unless it
+    /// was already registered before.
+    fn offer(&mut self, identifier: &CStr, implementation: NonNull<c_void>) {
+        if let Some(declared) = &mut self.declared {
+            if !declared.iter().any(|d| d.as_c_str() == identifier) {
+                declared.push(identifier.into());
+            }
         }
 
-        self
+        if self.found.is_none() && self.requested == Some(identifier) {
+            self.found = Some(implementation)
+        }
     }
 }
 
@@ -148,6 +346,7 @@ pub mod prelude {
             RawExtension, RawExtensionImplementation,
         },
         host::{HostAudioProcessorHandle, HostMainThreadHandle, HostSharedHandle},
+        plugin::dynamic::{DynInterface, DynPluginAudioProcessor, DynPluginMainThread},
         plugin::{Plugin, PluginError},
         utils::ClapId,
     };
@@ -3,30 +3,77 @@
 //! These unsafe utilities are targeted at extension implementors. Most `clack-plugin` users do not
 //! have to use those utilities to use extensions, see `clack-extensions` instead.
 
-use crate::host::HostSharedHandle;
-use crate::internal_utils::UnsafeOptionCell;
-use crate::plugin::{logging, Plugin, PluginAudioProcessor, PluginBoxInner, PluginError};
+use crate::extensions::PluginExtensions;
+use crate::host::{HostAudioProcessorHandle, HostMainThreadHandle, HostSharedHandle};
+use crate::internal_utils::{AliasableBox, OnceBox, UnsafeOptionCell};
+use crate::plugin::{
+    logging, AudioStateBlob, Plugin, PluginAudioProcessor, PluginBoxInner, PluginError,
+};
+use crate::process::audio::{InputSanitizer, PortCountCheck, PortCountChecker, PortCountMismatch};
 use crate::process::PluginAudioConfiguration;
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use clack_common::error::Error;
+use clack_common::extensions::restart_generation::*;
+use clap_sys::audio_buffer::clap_audio_buffer;
 use clap_sys::ext::log::*;
 use clap_sys::plugin::clap_plugin;
-use std::cell::UnsafeCell;
-use std::error::Error;
-use std::fmt::{Display, Formatter};
-use std::panic::AssertUnwindSafe;
-use std::pin::Pin;
-use std::ptr::NonNull;
-
-#[cfg(not(test))]
+use core::cell::UnsafeCell;
+use core::ffi::CStr;
+use core::fmt::{Display, Formatter};
+use core::panic::AssertUnwindSafe;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+#[cfg(all(feature = "std", not(test)))]
 #[allow(unused)]
 pub(crate) use std::panic::catch_unwind as handle_panic;
 
-#[cfg(test)]
+/// Without the standard library, panics can't be caught: they are expected to abort.
+#[cfg(any(not(feature = "std"), test))]
 #[inline]
 #[allow(unused)]
-pub(crate) fn handle_panic<F: FnOnce() -> R, R>(f: F) -> std::thread::Result<R> {
+pub(crate) fn handle_panic<F: FnOnce() -> R, R>(
+    f: F,
+) -> Result<R, alloc::boxed::Box<dyn core::any::Any + Send>> {
     Ok(f())
 }
 
+/// Holds the audio configuration of an active plugin, so that it can be read from any thread.
+struct AudioConfigurationCell {
+    sample_rate: AtomicU64,
+    min_frames_count: AtomicU32,
+    max_frames_count: AtomicU32,
+}
+
+impl AudioConfigurationCell {
+    fn new() -> Self {
+        Self {
+            sample_rate: AtomicU64::new(0),
+            min_frames_count: AtomicU32::new(0),
+            max_frames_count: AtomicU32::new(0),
+        }
+    }
+
+    fn set(&self, config: PluginAudioConfiguration) {
+        self.sample_rate
+            .store(config.sample_rate.to_bits(), Ordering::Relaxed);
+        self.min_frames_count
+            .store(config.min_frames_count, Ordering::Relaxed);
+        self.max_frames_count
+            .store(config.max_frames_count, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> PluginAudioConfiguration {
+        PluginAudioConfiguration {
+            sample_rate: f64::from_bits(self.sample_rate.load(Ordering::Relaxed)),
+            min_frames_count: self.min_frames_count.load(Ordering::Relaxed),
+            max_frames_count: self.max_frames_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
 /// A wrapper around a `clack` plugin of a given type.
 ///
 /// This wrapper allows access to a plugin's [`Shared`](Plugin::Shared),
@@ -34,12 +81,22 @@ pub(crate) fn handle_panic<F: FnOnce() -> R, R>(f: F) -> std::thread::Result<R>
 /// also handling common FFI issues, such as error management and unwind safety.
 ///
 /// The only way to access an instance of `PluginWrapper` is through the
-/// [`handle`](PluginWrapper::handle) function.
+/// [`handle_named`](PluginWrapper::handle_named) function. Extension implementations can also use
+/// the [`handle_main_thread`](PluginWrapper::handle_main_thread),
+/// [`handle_audio_processor`](PluginWrapper::handle_audio_processor) and
+/// [`handle_shared`](PluginWrapper::handle_shared) shorthands, which directly give access to the
+/// parts of the plugin matching the thread they are called on.
 pub struct PluginWrapper<'a, P: Plugin> {
     audio_processor: UnsafeOptionCell<P::AudioProcessor<'a>>,
+    audio_config: AudioConfigurationCell,
+    input_sanitizer: UnsafeOptionCell<InputSanitizer>,
+    port_count_checker: UnsafeOptionCell<PortCountChecker>,
+    frames_out_of_range: AtomicBool,
+    saved_audio_state: UnsafeOptionCell<Box<(u64, AudioStateBlob)>>,
     main_thread: UnsafeCell<P::MainThread<'a>>,
-    shared: Pin<Box<P::Shared<'a>>>,
+    shared: AliasableBox<P::Shared<'a>>,
     host: HostSharedHandle<'a>,
+    declared_extensions: OnceBox<Vec<CString>>,
 }
 
 impl<'a, P: Plugin> PluginWrapper<'a, P> {
@@ -48,36 +105,71 @@ impl<'a, P: Plugin> PluginWrapper<'a, P> {
     /// `shared` and `main_thread` must be related and correctly initialized.
     pub(crate) unsafe fn new(
         host: HostSharedHandle<'a>,
-        shared: Pin<Box<P::Shared<'a>>>,
+        shared: AliasableBox<P::Shared<'a>>,
         main_thread: P::MainThread<'a>,
     ) -> Self {
         Self {
             host,
             shared,
+            declared_extensions: OnceBox::new(),
             main_thread: UnsafeCell::new(main_thread),
             audio_processor: UnsafeOptionCell::new(),
+            audio_config: AudioConfigurationCell::new(),
+            input_sanitizer: UnsafeOptionCell::new(),
+            port_count_checker: UnsafeOptionCell::new(),
+            frames_out_of_range: AtomicBool::new(false),
+            saved_audio_state: UnsafeOptionCell::new(),
         }
     }
 
+    /// Activates the plugin with the given configuration.
+    ///
+    /// The `declared_ports` are the numbers of input and output ports the plugin declared, if
+    /// known, which process calls are checked against.
+    ///
     /// # Safety
     /// Caller must ensure this method is only called on main thread and has exclusivity
     pub(crate) unsafe fn activate(
         &self,
         audio_config: PluginAudioConfiguration,
+        declared_ports: Option<(u32, u32)>,
     ) -> Result<(), PluginWrapperError> {
         if self.is_active() {
-            return Err(PluginWrapperError::ActivatedPlugin);
+            if P::VTABLE.deactivate {
+                return Err(PluginWrapperError::ActivatedPlugin);
+            }
+
+            // The host has no way to deactivate this plugin: the previous audio processor is
+            // deactivated here instead.
+            self.deactivate()?;
         }
 
-        let shared = &*(self.shared() as *const _);
+        // SAFETY: the shared data outlives the audio processor.
+        let shared = self.shared.as_ptr().as_ref();
         let host = self.host;
 
-        let processor = P::AudioProcessor::activate(
+        let mut processor = P::AudioProcessor::activate(
             host.as_audio_processor_unchecked(),
             self.main_thread().as_mut(),
             shared,
             audio_config,
-        )?;
+        )
+        .map_err(|e| PluginWrapperError::ActivationFailed(audio_config, e))?;
+
+        // State saved during another restart sequence (or outside of any) is just dropped.
+        if let Some(saved) = self.saved_audio_state.take() {
+            let (generation, blob) = *saved;
+            if generation == self.restart_generation() {
+                processor.restore_audio_state(blob);
+            }
+        }
+
+        self.audio_config.set(audio_config);
+        self.input_sanitizer
+            .put(InputSanitizer::new(audio_config.max_frames_count));
+        self.port_count_checker
+            .put(PortCountChecker::new(declared_ports));
+        self.frames_out_of_range.store(false, Ordering::Relaxed);
 
         // SAFETY: It is up to the caller to ensure this is never called simultaneously with deactivate()
         self.audio_processor.put(processor);
@@ -90,20 +182,93 @@ impl<'a, P: Plugin> PluginWrapper<'a, P> {
     pub(crate) unsafe fn deactivate(&self) -> Result<(), PluginWrapperError> {
         match self.audio_processor.take() {
             None => Err(PluginWrapperError::DeactivatedPlugin),
-            Some(audio_processor) => {
+            Some(mut audio_processor) => {
+                self.saved_audio_state.take();
+
+                let generation = self.restart_generation();
+                if generation != 0 {
+                    if let Some(blob) = audio_processor.save_audio_state() {
+                        self.saved_audio_state.put(Box::new((generation, blob)));
+                    }
+                }
+
                 audio_processor.deactivate(self.main_thread().as_mut());
+                self.input_sanitizer.take();
+                self.port_count_checker.take();
 
                 Ok(())
             }
         }
     }
 
+    /// Returns the generation of the restart sequence the host is currently performing, or `0` if
+    /// it isn't restarting the plugin, or doesn't support Clack's restart generation extension.
+    ///
+    /// # Safety
+    /// Caller must ensure this method is only called on main thread.
+    unsafe fn restart_generation(&self) -> u64 {
+        let host = self.host.as_raw();
+
+        let Some(get_extension) = host.get_extension else {
+            return 0;
+        };
+
+        let extension = get_extension(host, CLACK_EXT_RESTART_GENERATION.as_ptr())
+            .cast::<clack_host_restart_generation>();
+
+        // SAFETY: the extension pointer comes from the matching identifier, and the CLAP spec
+        // guarantees it lives as long as the host.
+        match extension.as_ref().and_then(|e| e.get) {
+            Some(get) => get(host),
+            None => 0,
+        }
+    }
+
     /// Returns if the current plugin has been activated or not.
     #[inline]
     pub fn is_active(&self) -> bool {
         self.audio_processor.is_some()
     }
 
+    /// Returns the [`PluginAudioConfiguration`] the plugin is currently activated with, or `None`
+    /// if it is not active.
+    ///
+    /// This is the same configuration that was given to [`PluginAudioProcessor::activate`], and
+    /// is also available to the audio processor through the [`Process`](crate::process::Process)
+    /// struct it receives in every `process` call.
+    ///
+    /// This can be called from any thread. However, if it is called concurrently with the plugin's
+    /// activation or deactivation on the main thread, either the previous or the new state may
+    /// be observed.
+    #[inline]
+    pub fn audio_config(&self) -> Option<PluginAudioConfiguration> {
+        if self.is_active() {
+            Some(self.audio_config.get())
+        } else {
+            None
+        }
+    }
+
+    /// Returns the identifiers of all the extensions the plugin declared, in the order they were
+    /// registered in [`declare_extensions`](Plugin::declare_extensions).
+    ///
+    /// Extensions are only collected the first time this is called, by running
+    /// [`declare_extensions`](Plugin::declare_extensions) once more. Identifiers registered more
+    /// than once are only listed once.
+    pub fn declared_extensions(&self) -> impl Iterator<Item = &CStr> + '_ {
+        self.declared_extensions
+            .get_or_init(|| {
+                let mut declared = Vec::new();
+                P::declare_extensions(
+                    &mut PluginExtensions::declaring(&mut declared),
+                    Some(self.shared()),
+                );
+                declared
+            })
+            .iter()
+            .map(CString::as_c_str)
+    }
+
     /// Returns a reference to a plugin's [`Shared`](Plugin::Shared) struct.
     ///
     /// This is always safe to call in any context, since the `Shared` struct is required to
@@ -153,6 +318,57 @@ impl<'a, P: Plugin> PluginWrapper<'a, P> {
             .ok_or(PluginWrapperError::DeactivatedPlugin)
     }
 
+    /// Returns the given input buffers, with any missing channel buffer replaced by a silent,
+    /// constant buffer.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure this method is only called on the audio thread, and that the given
+    /// buffers are valid (except for missing channel buffers).
+    #[inline]
+    pub(crate) unsafe fn sanitize_inputs<'p>(
+        &'p self,
+        inputs: &'p [clap_audio_buffer],
+        frames_count: u32,
+    ) -> &'p [clap_audio_buffer] {
+        match self.input_sanitizer.as_ptr() {
+            Some(mut sanitizer) => sanitizer.as_mut().sanitize(inputs, frames_count),
+            None => inputs,
+        }
+    }
+
+    /// Checks the given numbers of input and output ports provided by the host against the ones
+    /// the plugin declared when it was activated.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure this method is only called on the audio thread.
+    #[inline]
+    pub(crate) unsafe fn check_port_counts(&self, inputs: usize, outputs: usize) -> PortCountCheck {
+        match self.port_count_checker.as_ptr() {
+            Some(mut checker) => checker.as_mut().check(inputs, outputs),
+            None => PortCountCheck::Matching,
+        }
+    }
+
+    /// Checks the given number of frames of a process call against the range the plugin was
+    /// activated with. Empty blocks are always considered to be in range.
+    ///
+    /// This returns `None` if the number of frames is in range. Otherwise, this returns whether this
+    /// is the first block out of range since the plugin was activated.
+    #[inline]
+    pub(crate) fn check_frames_count(&self, frames_count: u32) -> Option<bool> {
+        let config = self.audio_config.get();
+
+        if frames_count == 0
+            || (config.min_frames_count..=config.max_frames_count).contains(&frames_count)
+        {
+            return None;
+        }
+
+        Some(!self.frames_out_of_range.swap(true, Ordering::Relaxed))
+    }
+
     /// Provides a shared reference to a plugin wrapper of a given type, to the given handler
     /// closure.
     ///
@@ -161,11 +377,18 @@ impl<'a, P: Plugin> PluginWrapper<'a, P> {
     /// * The given `clap_plugin` pointer is null-checked, as well as some other host-provided
     ///   pointers;
     /// * The handler is wrapped in [`std::panic::catch_unwind`];
-    /// * Any [`PluginWrapperError`] returned by the handler is caught.
+    /// * Any [`PluginWrapperError`] returned by the handler is caught;
+    /// * The handler is tracked as in flight, so that if the host destroys the plugin while it is
+    ///   running (e.g. re-entrantly, from a host callback), the plugin is only actually freed once
+    ///   the handler has completed.
     ///
     /// If any of the above safety check fails, an error message is logged (using the standard CLAP
     /// logging extension). If logging is unavailable or fails for any reason, the error message is
-    /// written to `stderr` as a fallback.
+    /// sent to the [fallback logger](clack_common::utils::fallback_log) instead, which writes it to
+    /// `stderr` by default.
+    ///
+    /// The given `callback` name (e.g. `"clap_plugin.activate"`) is only used to identify which
+    /// CLAP callback failed in those error messages.
     ///
     /// Note that some safety checks (e.g. the `clap_plugin` pointer null-checks) may result in the
     /// closure never being called, and an error being returned only. Users of this function must
@@ -202,57 +425,209 @@ impl<'a, P: Plugin> PluginWrapper<'a, P> {
     /// use clack_plugin::extensions::wrapper::PluginWrapper;
     ///
     /// unsafe extern "C" fn on_main_thread<P: Plugin>(plugin: *const clap_plugin) {
-    ///   PluginWrapper::<P>::handle(plugin, |p| {
+    ///   PluginWrapper::<P>::handle_named(plugin, "clap_plugin.on_main_thread", |p| {
     ///     p.main_thread().as_mut().on_main_thread();
     ///     Ok(())
     ///   });
     /// }
     /// ```
-    pub unsafe fn handle<T, F>(plugin: *const clap_plugin, handler: F) -> Option<T>
+    pub unsafe fn handle_named<T, F>(
+        plugin: *const clap_plugin,
+        callback: &'static str,
+        handler: F,
+    ) -> Option<T>
     where
         F: FnOnce(&PluginWrapper<'a, P>) -> Result<T, PluginWrapperError>,
     {
-        match Self::from_raw(plugin).and_then(|p| Self::handle_panic(p, handler)) {
+        let data = match Self::plugin_data_from_raw(plugin) {
+            Ok(data) => data,
+            Err(e) => {
+                logging::plugin_log::<P>(plugin, callback, &e);
+                return None;
+            }
+        };
+
+        // The guard must outlive the logging below, which still needs the plugin data.
+        let _guard = PluginBoxInner::enter(plugin, data);
+
+        match data
+            .as_ref()
+            .wrapper()
+            .and_then(|p| Self::handle_panic(p, handler))
+        {
             Ok(value) => Some(value),
             Err(e) => {
-                logging::plugin_log::<P>(plugin, &e);
+                logging::plugin_log::<P>(plugin, callback, &e);
 
                 None
             }
         }
     }
 
+    /// Same as [`handle_named`](Self::handle_named), but without a callback name: error messages
+    /// don't tell which CLAP callback failed.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`handle_named`](Self::handle_named).
+    #[deprecated(note = "Use `handle_named`, which identifies the callback in error messages")]
+    #[inline]
+    pub unsafe fn handle<T, F>(plugin: *const clap_plugin, handler: F) -> Option<T>
+    where
+        F: FnOnce(&PluginWrapper<'a, P>) -> Result<T, PluginWrapperError>,
+    {
+        Self::handle_named(plugin, "", handler)
+    }
+
+    /// Handles a main-thread callback from the host, giving the handler access to the plugin's
+    /// [`MainThread`](Plugin::MainThread) and [`Shared`](Plugin::Shared) structs, as well as to
+    /// the host's main-thread handle.
+    ///
+    /// This is a shorthand for [`handle_named`](Self::handle_named), which is meant to implement extension
+    /// functions that the CLAP specification marks as `[main-thread]`. All the safety checks of
+    /// [`handle_named`](Self::handle_named) apply.
+    ///
+    /// # Errors
+    ///
+    /// If any safety check failed, or any error or panic occurred inside the handler closure, this
+    /// function returns `None`, and the error message is logged.
+    ///
+    /// # Safety
+    ///
+    /// On top of the requirements of [`handle_named`](Self::handle_named), the caller must ensure this is
+    /// only called on the main thread.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clap_sys::plugin::clap_plugin;
+    /// use clack_plugin::plugin::{Plugin, PluginMainThread};
+    /// use clack_plugin::extensions::wrapper::PluginWrapper;
+    ///
+    /// unsafe extern "C" fn on_main_thread<P: Plugin>(plugin: *const clap_plugin) {
+    ///   PluginWrapper::<P>::handle_main_thread(plugin, "clap_plugin.on_main_thread", |main_thread, _shared, _host| {
+    ///     main_thread.on_main_thread();
+    ///     Ok(())
+    ///   });
+    /// }
+    /// ```
+    pub unsafe fn handle_main_thread<T, F>(
+        plugin: *const clap_plugin,
+        callback: &'static str,
+        handler: F,
+    ) -> Option<T>
+    where
+        F: FnOnce(
+            &mut P::MainThread<'a>,
+            &P::Shared<'a>,
+            HostMainThreadHandle<'a>,
+        ) -> Result<T, PluginWrapperError>,
+    {
+        Self::handle_named(plugin, callback, |p| {
+            // SAFETY: the caller ensures this is called on the main thread.
+            let host = p.host.as_main_thread_unchecked();
+            handler(p.main_thread().as_mut(), p.shared(), host)
+        })
+    }
+
+    /// Handles an audio-thread callback from the host, giving the handler access to the plugin's
+    /// [`AudioProcessor`](Plugin::AudioProcessor) and [`Shared`](Plugin::Shared) structs, as well
+    /// as to the host's audio-thread handle.
+    ///
+    /// This is a shorthand for [`handle_named`](Self::handle_named), which is meant to implement extension
+    /// functions that the CLAP specification marks as `[audio-thread]`. All the safety checks of
+    /// [`handle_named`](Self::handle_named) apply.
+    ///
+    /// If the plugin isn't active, the handler isn't called, and a
+    /// [`PluginWrapperError::DeactivatedPlugin`] error is logged.
+    ///
+    /// # Errors
+    ///
+    /// If any safety check failed, if the plugin isn't active, or if any error or panic occurred
+    /// inside the handler closure, this function returns `None`, and the error message is logged.
+    ///
+    /// # Safety
+    ///
+    /// On top of the requirements of [`handle_named`](Self::handle_named), the caller must ensure this is
+    /// only called on the audio thread.
+    pub unsafe fn handle_audio_processor<T, F>(
+        plugin: *const clap_plugin,
+        callback: &'static str,
+        handler: F,
+    ) -> Option<T>
+    where
+        F: FnOnce(
+            &mut P::AudioProcessor<'a>,
+            &P::Shared<'a>,
+            HostAudioProcessorHandle<'a>,
+        ) -> Result<T, PluginWrapperError>,
+    {
+        Self::handle_named(plugin, callback, |p| {
+            let mut audio_processor = p.audio_processor()?;
+            // SAFETY: the caller ensures this is called on the audio thread.
+            let host = p.host.as_audio_processor_unchecked();
+            handler(audio_processor.as_mut(), p.shared(), host)
+        })
+    }
+
+    /// Handles a thread-safe callback from the host, giving the handler access to the plugin's
+    /// [`Shared`](Plugin::Shared) struct, as well as to the host's shared handle.
+    ///
+    /// This is a shorthand for [`handle_named`](Self::handle_named), which is meant to implement extension
+    /// functions that the CLAP specification marks as `[thread-safe]`. All the safety checks of
+    /// [`handle_named`](Self::handle_named) apply.
+    ///
+    /// # Errors
+    ///
+    /// If any safety check failed, or any error or panic occurred inside the handler closure, this
+    /// function returns `None`, and the error message is logged.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as [`handle_named`](Self::handle_named) apply. This can be called from any
+    /// thread.
+    pub unsafe fn handle_shared<T, F>(
+        plugin: *const clap_plugin,
+        callback: &'static str,
+        handler: F,
+    ) -> Option<T>
+    where
+        F: FnOnce(&P::Shared<'a>, HostSharedHandle<'a>) -> Result<T, PluginWrapperError>,
+    {
+        Self::handle_named(plugin, callback, |p| handler(p.shared(), p.host))
+    }
+
     /// # Safety
     /// The plugin pointer must be valid
     pub(crate) unsafe fn handle_plugin_data<T, F>(
         plugin: *const clap_plugin,
+        callback: &'static str,
         handler: F,
     ) -> Option<T>
     where
         F: FnOnce(NonNull<PluginBoxInner<'a, P>>) -> Result<T, PluginWrapperError>,
     {
-        match Self::plugin_data_from_raw(plugin).and_then(|p| Self::handle_panic(p, handler)) {
+        let data = match Self::plugin_data_from_raw(plugin) {
+            Ok(data) => data,
+            Err(e) => {
+                logging::plugin_log::<P>(plugin, callback, &e);
+                return None;
+            }
+        };
+
+        // The guard must outlive the logging below, which still needs the plugin data.
+        let _guard = PluginBoxInner::enter(plugin, data);
+
+        match Self::handle_panic(data, handler) {
             Ok(value) => Some(value),
             Err(e) => {
-                logging::plugin_log::<P>(plugin, &e);
+                logging::plugin_log::<P>(plugin, callback, &e);
 
                 None
             }
         }
     }
 
-    /// # Safety
-    /// The plugin pointer must be valid
-    unsafe fn from_raw<'p>(raw: *const clap_plugin) -> Result<&'p Self, PluginWrapperError> {
-        raw.as_ref()
-            .ok_or(PluginWrapperError::NullPluginInstance)?
-            .plugin_data
-            .cast::<PluginBoxInner<'a, P>>()
-            .as_ref()
-            .ok_or(PluginWrapperError::AlreadyDestroyed)?
-            .wrapper()
-    }
-
     /// # Safety
     /// The plugin pointer must be valid
     unsafe fn plugin_data_from_raw(
@@ -261,10 +636,9 @@ impl<'a, P: Plugin> PluginWrapper<'a, P> {
         let data = raw
             .as_ref()
             .ok_or(PluginWrapperError::NullPluginInstance)?
-            .plugin_data
-            .cast::<PluginBoxInner<'a, P>>();
+            .plugin_data;
 
-        NonNull::new(data).ok_or(PluginWrapperError::AlreadyDestroyed)
+        PluginBoxInner::from_plugin_data(data)
     }
 
     #[inline]
@@ -285,13 +659,24 @@ unsafe impl<P: Plugin> Send for PluginWrapper<'_, P> {}
 unsafe impl<P: Plugin> Sync for PluginWrapper<'_, P> {}
 
 /// Errors raised by a [`PluginWrapper`].
+///
+/// New variants may be added in the future: use [`kind`](Self::kind) to handle specific errors
+/// programmatically.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum PluginWrapperError {
     /// The `clap_plugin` raw pointer was null.
     NullPluginInstance,
     /// The `clap_plugin.plugin_data` raw pointer was null, which indicates the instance was already
-    /// destroyed.
+    /// destroyed, or `destroy` was already called while other callbacks were still running.
     AlreadyDestroyed,
+    /// The `clap_plugin.plugin_data` raw pointer doesn't point to an instance created by this
+    /// version of Clack.
+    ///
+    /// The layout of `plugin_data` is private to Clack, and may change between versions. It starts
+    /// with a header recording its layout version, which allows detecting this instead of
+    /// misinterpreting it.
+    IncompatibleWrapperVersion,
     /// An unexpectedly null raw pointer was encountered.
     ///
     /// The given string may contain more information about which pointer was found to be null.
@@ -306,6 +691,11 @@ pub enum PluginWrapperError {
     PluginCalledDuringInitialization,
     /// The host tried to call a plugin method while `destroy` is running.
     Destroying,
+    /// The host called `destroy` while another of the plugin's callbacks was still running, e.g.
+    /// re-entrantly from a host callback.
+    ///
+    /// The plugin is only actually destroyed once all of its running callbacks have completed.
+    DestroyedDuringCallback,
     /// The plugin's initialization (`init`) has failed.
     InitializationAlreadyFailed,
     /// The plugin is already initialized (i.e. a second call to `init` was attempted).
@@ -322,15 +712,75 @@ pub enum PluginWrapperError {
     Panic,
     /// A given [`PluginError`] was raised during a function call.
     Plugin(PluginError),
+    /// The plugin returned the given [`PluginError`] while it was being initialized (in `init`).
+    InitializationFailed(PluginError),
+    /// The plugin returned the given [`PluginError`] while being activated with the given
+    /// audio configuration.
+    ActivationFailed(PluginAudioConfiguration, PluginError),
+    /// The host provided a different number of audio ports in a `process` call than the plugin
+    /// declared.
+    PortCountMismatch(PortCountMismatch),
     /// Bad UTF-8.
-    StringEncoding(std::str::Utf8Error),
+    StringEncoding(core::str::Utf8Error),
     /// Plugin returned a malformed C string.
-    InvalidCString(std::ffi::NulError),
+    InvalidCString(alloc::ffi::NulError),
     /// A generic or custom error of a given severity.
     Error(clap_log_severity, Box<dyn Error>),
 }
 
 impl PluginWrapperError {
+    /// Returns the kind of this error, without any of its associated data.
+    ///
+    /// Unlike the error itself, the returned [`PluginWrapperErrorKind`] is `Copy` and can be
+    /// compared, which is useful to handle specific errors programmatically.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_plugin::extensions::wrapper::{PluginWrapperError, PluginWrapperErrorKind};
+    /// let error = PluginWrapperError::NulPtr("clap_process");
+    ///
+    /// assert_eq!(error.kind(), PluginWrapperErrorKind::NulPtr);
+    /// ```
+    pub fn kind(&self) -> PluginWrapperErrorKind {
+        match self {
+            PluginWrapperError::NullPluginInstance => PluginWrapperErrorKind::NullPluginInstance,
+            PluginWrapperError::AlreadyDestroyed => PluginWrapperErrorKind::AlreadyDestroyed,
+            PluginWrapperError::IncompatibleWrapperVersion => {
+                PluginWrapperErrorKind::IncompatibleWrapperVersion
+            }
+            PluginWrapperError::NulPtr(_) => PluginWrapperErrorKind::NulPtr,
+            PluginWrapperError::InvalidParameter(_) => PluginWrapperErrorKind::InvalidParameter,
+            PluginWrapperError::UninitializedPlugin => PluginWrapperErrorKind::UninitializedPlugin,
+            PluginWrapperError::PluginCalledDuringInitialization => {
+                PluginWrapperErrorKind::PluginCalledDuringInitialization
+            }
+            PluginWrapperError::Destroying => PluginWrapperErrorKind::Destroying,
+            PluginWrapperError::DestroyedDuringCallback => {
+                PluginWrapperErrorKind::DestroyedDuringCallback
+            }
+            PluginWrapperError::InitializationAlreadyFailed => {
+                PluginWrapperErrorKind::InitializationAlreadyFailed
+            }
+            PluginWrapperError::AlreadyInitialized => PluginWrapperErrorKind::AlreadyInitialized,
+            PluginWrapperError::ActivatedPlugin => PluginWrapperErrorKind::ActivatedPlugin,
+            PluginWrapperError::DeactivatedPlugin => PluginWrapperErrorKind::DeactivatedPlugin,
+            PluginWrapperError::DeactivationRequiredForFunction(_) => {
+                PluginWrapperErrorKind::DeactivationRequiredForFunction
+            }
+            PluginWrapperError::Panic => PluginWrapperErrorKind::Panic,
+            PluginWrapperError::Plugin(_) => PluginWrapperErrorKind::Plugin,
+            PluginWrapperError::InitializationFailed(_) => {
+                PluginWrapperErrorKind::InitializationFailed
+            }
+            PluginWrapperError::ActivationFailed(_, _) => PluginWrapperErrorKind::ActivationFailed,
+            PluginWrapperError::PortCountMismatch(_) => PluginWrapperErrorKind::PortCountMismatch,
+            PluginWrapperError::StringEncoding(_) => PluginWrapperErrorKind::StringEncoding,
+            PluginWrapperError::InvalidCString(_) => PluginWrapperErrorKind::InvalidCString,
+            PluginWrapperError::Error(_, _) => PluginWrapperErrorKind::Error,
+        }
+    }
+
     /// Returns the severity of this error.
     ///
     /// This is mainly useful for logging.
@@ -346,8 +796,12 @@ impl PluginWrapperError {
     /// ```
     pub fn severity(&self) -> clap_log_severity {
         match self {
-            PluginWrapperError::Plugin(_) => CLAP_LOG_ERROR,
-            PluginWrapperError::Panic => CLAP_LOG_PLUGIN_MISBEHAVING,
+            PluginWrapperError::Plugin(_)
+            | PluginWrapperError::InitializationFailed(_)
+            | PluginWrapperError::ActivationFailed(_, _) => CLAP_LOG_ERROR,
+            PluginWrapperError::Panic | PluginWrapperError::InvalidCString(_) => {
+                CLAP_LOG_PLUGIN_MISBEHAVING
+            }
             PluginWrapperError::Error(s, _) => *s,
             _ => CLAP_LOG_HOST_MISBEHAVING,
         }
@@ -384,14 +838,17 @@ impl From<PluginError> for PluginWrapperError {
 }
 
 impl Display for PluginWrapperError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             PluginWrapperError::NullPluginInstance => {
                 f.write_str("Plugin method was called with null clap_plugin pointer")
             }
-            PluginWrapperError::AlreadyDestroyed => f.write_str(
-                "Plugin instance was already destroyed (clap_plugin.plugin_data pointer is null)",
-            ),
+            PluginWrapperError::AlreadyDestroyed => {
+                f.write_str("Plugin instance was already destroyed")
+            }
+            PluginWrapperError::IncompatibleWrapperVersion => {
+                f.write_str("Plugin instance was not created by a compatible version of Clack")
+            }
             PluginWrapperError::PluginCalledDuringInitialization => {
                 f.write_str("Host tried to call plugin function during initialization")
             }
@@ -400,6 +857,10 @@ impl Display for PluginWrapperError {
             }
             PluginWrapperError::AlreadyInitialized => f.write_str("Plugin is already initialized"),
             PluginWrapperError::Destroying => f.write_str("Plugin is being destroyed"),
+            PluginWrapperError::DestroyedDuringCallback => f.write_str(
+                "Host destroyed the plugin while one of its callbacks was still running. \
+                Destruction is deferred until all of them complete",
+            ),
             PluginWrapperError::NulPtr(ptr_name) => {
                 write!(f, "Plugin method was called with null {ptr_name} pointer")
             }
@@ -411,12 +872,13 @@ impl Display for PluginWrapperError {
             }
             PluginWrapperError::ActivatedPlugin => f.write_str("Plugin was already activated"),
             PluginWrapperError::DeactivatedPlugin => {
-                f.write_str("Plugin was not activated before calling a audio-thread method")
+                f.write_str("Plugin was not activated before calling an audio-thread method")
             }
             PluginWrapperError::DeactivationRequiredForFunction(function) => write!(
                 f,
                 "Host attempted to call '{function}' while plugin was still active"
             ),
+            PluginWrapperError::PortCountMismatch(mismatch) => Display::fmt(mismatch, f),
             PluginWrapperError::StringEncoding(e) => {
                 write!(
                     f,
@@ -431,11 +893,129 @@ impl Display for PluginWrapperError {
                     e.nul_position()
                 )
             }
-            PluginWrapperError::Plugin(e) => std::fmt::Display::fmt(&e, f),
-            PluginWrapperError::Error(_, e) => std::fmt::Display::fmt(e, f),
+            PluginWrapperError::Plugin(e) => write!(f, "Plugin returned an error: {e}"),
+            PluginWrapperError::InitializationFailed(e) => {
+                write!(f, "Plugin failed to initialize: {e}")
+            }
+            PluginWrapperError::ActivationFailed(config, e) => write!(
+                f,
+                "Plugin failed to activate (sample rate: {} Hz, frames: {} to {}): {e}",
+                config.sample_rate, config.min_frames_count, config.max_frames_count
+            ),
+            PluginWrapperError::Error(_, e) => core::fmt::Display::fmt(e, f),
             PluginWrapperError::Panic => f.write_str("Plugin panicked"),
         }
     }
 }
 
-impl Error for PluginWrapperError {}
+impl Error for PluginWrapperError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PluginWrapperError::Plugin(e)
+            | PluginWrapperError::InitializationFailed(e)
+            | PluginWrapperError::ActivationFailed(_, e) => e.source(),
+            PluginWrapperError::StringEncoding(e) => Some(e),
+            PluginWrapperError::InvalidCString(e) => Some(e),
+            PluginWrapperError::Error(_, e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The kind of a [`PluginWrapperError`], without any of its associated data.
+///
+/// See [`PluginWrapperError::kind`]. Each kind matches the [`PluginWrapperError`] variant of the
+/// same name.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PluginWrapperErrorKind {
+    /// The `clap_plugin` raw pointer was null.
+    NullPluginInstance,
+    /// The plugin instance was already destroyed.
+    AlreadyDestroyed,
+    /// The plugin instance was not created by a compatible version of Clack.
+    IncompatibleWrapperVersion,
+    /// An unexpectedly null raw pointer was encountered.
+    NulPtr,
+    /// An invalid parameter value was encountered.
+    InvalidParameter,
+    /// The plugin was not properly initialized.
+    UninitializedPlugin,
+    /// The host tried to call a plugin method while `init` is running.
+    PluginCalledDuringInitialization,
+    /// The host tried to call a plugin method while `destroy` is running.
+    Destroying,
+    /// The host called `destroy` while another of the plugin's callbacks was still running.
+    DestroyedDuringCallback,
+    /// The plugin's initialization has failed.
+    InitializationAlreadyFailed,
+    /// The plugin is already initialized.
+    AlreadyInitialized,
+    /// The plugin was already activated.
+    ActivatedPlugin,
+    /// The plugin was not activated.
+    DeactivatedPlugin,
+    /// A function requiring the plugin to be deactivated was called while it was active.
+    DeactivationRequiredForFunction,
+    /// The plugin panicked.
+    Panic,
+    /// The plugin returned an error.
+    Plugin,
+    /// The plugin returned an error during initialization.
+    InitializationFailed,
+    /// The plugin returned an error during activation.
+    ActivationFailed,
+    /// The host provided a different number of audio ports than the plugin declared.
+    PortCountMismatch,
+    /// Bad UTF-8.
+    StringEncoding,
+    /// A malformed C string was encountered.
+    InvalidCString,
+    /// A generic or custom error.
+    Error,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn source_exposes_wrapped_errors() {
+        let error = PluginWrapperError::Plugin(PluginError::from(std::env::VarError::NotPresent));
+        assert_eq!(error.kind(), PluginWrapperErrorKind::Plugin);
+
+        let source = error.source().unwrap();
+        assert!(source.downcast_ref::<std::env::VarError>().is_some());
+
+        let error = PluginWrapperError::Plugin(PluginError::Message("Some error"));
+        assert!(error.source().is_none());
+        assert_eq!(error.to_string(), "Plugin returned an error: Some error");
+
+        let error = PluginWrapperError::with_severity(CLAP_LOG_ERROR)(core::fmt::Error);
+        assert_eq!(error.kind(), PluginWrapperErrorKind::Error);
+        assert!(error.source().unwrap().is::<core::fmt::Error>());
+
+        assert!(PluginWrapperError::Panic.source().is_none());
+    }
+
+    #[test]
+    fn lifecycle_errors_include_context() {
+        let error = PluginWrapperError::InitializationFailed(PluginError::Message("No license"));
+        assert_eq!(error.kind(), PluginWrapperErrorKind::InitializationFailed);
+        assert_eq!(error.severity(), CLAP_LOG_ERROR);
+        assert_eq!(error.to_string(), "Plugin failed to initialize: No license");
+
+        let config = PluginAudioConfiguration {
+            sample_rate: 44_100.0,
+            min_frames_count: 32,
+            max_frames_count: 512,
+        };
+
+        let error = PluginWrapperError::ActivationFailed(config, PluginError::Message("Too fast"));
+        assert_eq!(error.kind(), PluginWrapperErrorKind::ActivationFailed);
+        assert_eq!(
+            error.to_string(),
+            "Plugin failed to activate (sample rate: 44100 Hz, frames: 32 to 512): Too fast"
+        );
+    }
+}
@@ -16,9 +16,10 @@
 //! custom entry and plugin factory.
 
 use core::ffi::c_void;
-use std::ffi::CStr;
-use std::ptr::NonNull;
+use core::ffi::CStr;
+use core::ptr::NonNull;
 
+pub mod compatibility;
 pub mod plugin;
 
 /// A base trait for plugin-side factory implementations.
@@ -44,7 +45,7 @@ pub mod plugin;
 /// ```
 /// use clack_plugin::factory::Factory;
 /// use clap_sys::factory::plugin_factory::{clap_plugin_factory, CLAP_PLUGIN_FACTORY_ID};
-/// use std::ffi::CStr;
+/// use core::ffi::CStr;
 ///
 /// #[repr(C)]
 /// pub struct MyPluginFactory(clap_plugin_factory);
@@ -0,0 +1,132 @@
+//! The plugin compatibility factory type.
+//!
+//! This factory exposes the [compatible IDs](crate::plugin::PluginDescriptor::compatible_ids) of
+//! the plugins in a bundle to the host. It is not part of the CLAP specification, and is only
+//! understood by hosts made with Clack.
+//!
+//! [`SinglePluginEntry`](crate::entry::SinglePluginEntry) exposes it automatically. Custom
+//! entries can create one from their plugin descriptors, and register it alongside their
+//! plugin factory.
+//!
+//! See the [`factory` module documentation](crate::factory) to learn more about factories.
+
+use crate::extensions::wrapper::handle_panic;
+use crate::factory::Factory;
+use crate::plugin::PluginDescriptor;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use clack_common::plugin::compatibility::*;
+use core::ffi::{c_char, CStr};
+use core::panic::AssertUnwindSafe;
+
+/// The compatible IDs of a single plugin.
+struct CompatibleIds {
+    plugin_id: Box<CStr>,
+    ids: Vec<Box<CStr>>,
+    ids_array: Vec<*const c_char>,
+}
+
+/// A factory exposing the [compatible IDs](PluginDescriptor::compatible_ids) of a set of plugins.
+///
+/// # Example
+///
+/// ```
+/// use clack_plugin::entry::prelude::*;
+/// use clack_plugin::factory::compatibility::PluginCompatibilityFactory;
+///
+/// let descriptor = PluginDescriptor::new("org.rust-audio.clack.reverb", "Reverb")
+///     .with_compatible_ids(["org.rust-audio.clack.old-reverb"]);
+///
+/// let factory = PluginCompatibilityFactory::new([&descriptor]);
+/// assert!(!factory.is_empty());
+/// ```
+#[repr(C)]
+pub struct PluginCompatibilityFactory {
+    raw: clack_plugin_compatibility_factory,
+    plugins: Vec<CompatibleIds>,
+}
+
+// SAFETY: PluginCompatibilityFactory is fully self-contained, the pointers refer to data owned by it.
+unsafe impl Send for PluginCompatibilityFactory {}
+
+// SAFETY: PluginCompatibilityFactory does not have any interior mutability.
+unsafe impl Sync for PluginCompatibilityFactory {}
+
+impl PluginCompatibilityFactory {
+    /// Creates a new factory, exposing the compatible IDs declared by the given descriptors.
+    ///
+    /// Descriptors that don't declare any compatible ID are skipped.
+    pub fn new<'a>(descriptors: impl IntoIterator<Item = &'a PluginDescriptor>) -> Self {
+        let plugins = descriptors
+            .into_iter()
+            .filter(|d| !d.compatible_ids().is_empty())
+            .map(|d| {
+                let ids = d.compatible_ids().to_vec();
+                let mut ids_array: Vec<_> = ids.iter().map(|id| id.as_ptr()).collect();
+                ids_array.push(core::ptr::null());
+
+                CompatibleIds {
+                    plugin_id: d.id().into(),
+                    ids,
+                    ids_array,
+                }
+            })
+            .collect();
+
+        Self {
+            raw: clack_plugin_compatibility_factory {
+                get_compatible_ids: Some(get_compatible_ids),
+            },
+            plugins,
+        }
+    }
+
+    /// Returns `true` if none of the plugins declared any compatible ID.
+    ///
+    /// Entries don't need to register an empty factory.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Returns the IDs the plugin with the given ID is compatible with.
+    pub fn compatible_ids(&self, plugin_id: &CStr) -> &[Box<CStr>] {
+        self.find(plugin_id)
+            .map(|p| p.ids.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn find(&self, plugin_id: &CStr) -> Option<&CompatibleIds> {
+        self.plugins.iter().find(|p| *p.plugin_id == *plugin_id)
+    }
+}
+
+// SAFETY: PluginCompatibilityFactory is #[repr(C)] with clack_plugin_compatibility_factory as its
+// first field, and matches CLACK_PLUGIN_COMPATIBILITY_FACTORY_ID.
+unsafe impl Factory for PluginCompatibilityFactory {
+    const IDENTIFIER: &'static CStr = CLACK_PLUGIN_COMPATIBILITY_FACTORY_ID;
+}
+
+#[allow(clippy::missing_safety_doc)]
+unsafe extern "C" fn get_compatible_ids(
+    factory: *const clack_plugin_compatibility_factory,
+    plugin_id: *const c_char,
+) -> *const *const c_char {
+    if plugin_id.is_null() {
+        return core::ptr::null();
+    }
+
+    // SAFETY: the factory pointer is the first field of a PluginCompatibilityFactory.
+    let Some(factory) = (unsafe { factory.cast::<PluginCompatibilityFactory>().as_ref() }) else {
+        return core::ptr::null();
+    };
+
+    // SAFETY: the host guarantees the plugin ID is a valid C string.
+    let plugin_id = unsafe { CStr::from_ptr(plugin_id) };
+
+    handle_panic(AssertUnwindSafe(|| match factory.find(plugin_id) {
+        Some(plugin) => plugin.ids_array.as_ptr(),
+        None => core::ptr::null(),
+    }))
+    .unwrap_or(core::ptr::null())
+}
@@ -12,14 +12,18 @@ use crate::extensions::wrapper::handle_panic;
 use crate::factory::Factory;
 use crate::host::HostInfo;
 use crate::plugin::{PluginDescriptor, PluginInstance};
+use clack_common::error::Error;
+use clack_common::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
+use clap_sys::ext::log::{
+    clap_log_severity, CLAP_LOG_HOST_MISBEHAVING, CLAP_LOG_PLUGIN_MISBEHAVING,
+};
 use clap_sys::factory::plugin_factory::{clap_plugin_factory, CLAP_PLUGIN_FACTORY_ID};
 use clap_sys::host::clap_host;
 use clap_sys::plugin::{clap_plugin, clap_plugin_descriptor};
-use std::error::Error;
-use std::ffi::CStr;
-use std::fmt::{Display, Formatter};
-use std::panic::AssertUnwindSafe;
-use std::ptr::NonNull;
+use core::ffi::CStr;
+use core::fmt::{Display, Formatter};
+use core::panic::AssertUnwindSafe;
+use core::ptr::NonNull;
 
 /// A wrapper around a given [`PluginFactory`] implementation.
 ///
@@ -60,6 +64,7 @@ impl<F: PluginFactory> PluginFactoryWrapper<F> {
     /// The plugin factory pointer must be valid
     unsafe fn handle<T>(
         raw: *const clap_plugin_factory,
+        callback: &'static str,
         handler: impl FnOnce(&F) -> Result<T, PluginFactoryError>,
     ) -> Option<T> {
         let factory = Self::from_raw(raw);
@@ -74,7 +79,13 @@ impl<F: PluginFactory> PluginFactoryWrapper<F> {
         match result {
             Ok(value) => Some(value),
             Err(e) => {
-                eprintln!("[CLAP_PLUGIN_FACTORY_ERROR] {e}");
+                fallback_log(&LogRecord {
+                    origin: LogOrigin::PluginFactory,
+                    severity: e.severity(),
+                    plugin_id: None,
+                    callback,
+                    message: &e,
+                });
 
                 None
             }
@@ -93,7 +104,10 @@ impl<F: PluginFactory> PluginFactoryWrapper<F> {
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn get_plugin_count(factory: *const clap_plugin_factory) -> u32 {
-        Self::handle(factory, |factory| Ok(factory.plugin_count())).unwrap_or(0)
+        Self::handle(factory, "clap_plugin_factory.get_plugin_count", |factory| {
+            Ok(factory.plugin_count())
+        })
+        .unwrap_or(0)
     }
 
     #[allow(clippy::missing_safety_doc)]
@@ -101,10 +115,14 @@ impl<F: PluginFactory> PluginFactoryWrapper<F> {
         factory: *const clap_plugin_factory,
         index: u32,
     ) -> *const clap_plugin_descriptor {
-        Self::handle(factory, |factory| match factory.plugin_descriptor(index) {
-            None => Ok(core::ptr::null()),
-            Some(d) => Ok(d.as_raw()),
-        })
+        Self::handle(
+            factory,
+            "clap_plugin_factory.get_plugin_descriptor",
+            |factory| match factory.plugin_descriptor(index) {
+                None => Ok(core::ptr::null()),
+                Some(d) => Ok(d.as_raw()),
+            },
+        )
         .unwrap_or(core::ptr::null())
     }
 
@@ -112,9 +130,9 @@ impl<F: PluginFactory> PluginFactoryWrapper<F> {
     unsafe extern "C" fn create_plugin(
         factory: *const clap_plugin_factory,
         clap_host: *const clap_host,
-        plugin_id: *const std::os::raw::c_char,
+        plugin_id: *const core::ffi::c_char,
     ) -> *const clap_plugin {
-        Self::handle(factory, |factory| {
+        Self::handle(factory, "clap_plugin_factory.create_plugin", |factory| {
             let plugin_id = CStr::from_ptr(plugin_id);
             let clap_host =
                 NonNull::new(clap_host as *mut _).ok_or(PluginFactoryError::NulPtr("clap_host"))?;
@@ -145,7 +163,7 @@ unsafe impl<F> Factory for PluginFactoryWrapper<F> {
 /// The following example shows how to implement a basic, single-plugin factory.
 ///
 /// ```
-/// use std::ffi::CStr;
+/// use core::ffi::CStr;
 /// use clack_plugin::entry::prelude::*;
 /// use clack_plugin::prelude::*;
 ///
@@ -223,8 +241,17 @@ enum PluginFactoryError {
     Panic,
 }
 
+impl PluginFactoryError {
+    fn severity(&self) -> clap_log_severity {
+        match self {
+            PluginFactoryError::Panic => CLAP_LOG_PLUGIN_MISBEHAVING,
+            _ => CLAP_LOG_HOST_MISBEHAVING,
+        }
+    }
+}
+
 impl Display for PluginFactoryError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             PluginFactoryError::NullFactoryInstance => f.write_str(
                 "Plugin factory method was called with null clap_plugin_factory pointer",
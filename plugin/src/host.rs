@@ -3,10 +3,10 @@
 use clack_common::extensions::{Extension, HostExtensionSide, RawExtension};
 use clack_common::utils::ClapVersion;
 use clap_sys::host::clap_host;
-use std::ffi::CStr;
-use std::marker::PhantomData;
-use std::ops::Deref;
-use std::ptr::NonNull;
+use core::ffi::{c_void, CStr};
+use core::marker::PhantomData;
+use core::ops::Deref;
+use core::ptr::NonNull;
 
 /// Various information about the host, provided at plugin instantiation time.
 #[derive(Copy, Clone)]
@@ -30,7 +30,23 @@ impl<'a> HostInfo<'a> {
         }
     }
 
-    /// The [`ClapVersion`] the host uses.
+    /// The [`ClapVersion`] the host advertises.
+    ///
+    /// Plugins can use this to gate features that depend on a newer CLAP version than the one the
+    /// host implements.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_plugin::host::HostInfo;
+    /// use clack_plugin::utils::ClapVersion;
+    ///
+    /// # fn foo(info: HostInfo) {
+    /// let info: HostInfo = /* ... */
+    /// # info;
+    /// let supports_1_2 = info.clap_version() >= ClapVersion { major: 1, minor: 2, revision: 0 };
+    /// # }
+    /// ```
     #[inline]
     pub fn clap_version(&self) -> ClapVersion {
         ClapVersion::from_raw(self.as_raw().clap_version)
@@ -120,6 +136,47 @@ impl<'a> HostInfo<'a> {
         // SAFETY: this type ensures the raw pointer is valid
         unsafe { self.raw.as_ref() }
     }
+
+    /// Returns a raw pointer to the C FFI-compatible host struct, without dereferencing it.
+    ///
+    /// This is mostly useful to pass the host handle to C helper libraries. If you need to safely
+    /// access the host struct through a shared reference, use [`as_raw`](Self::as_raw) instead.
+    #[inline]
+    pub fn as_raw_ptr(&self) -> *const clap_host {
+        self.raw.as_ptr()
+    }
+
+    /// Returns the raw `host_data` pointer of the host struct.
+    ///
+    /// This pointer is reserved for the host, and is opaque to the plugin: the CLAP specification
+    /// makes no guarantees about what it points to, or if it is even valid. Hosts written with
+    /// `clack-host` use it to point to their own internal state, for example.
+    ///
+    /// However, plugins that know which host they are embedded in (e.g. a host which loads
+    /// plugins it bundles itself) can use it to retrieve some context the host stored there. See
+    /// also [`host_data_as`](Self::host_data_as).
+    #[inline]
+    pub fn host_data(&self) -> *mut c_void {
+        self.as_raw().host_data
+    }
+
+    /// Returns a reference to the data the `host_data` pointer points to, interpreted as a `T`.
+    ///
+    /// This returns `None` if the `host_data` pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure the host stored a pointer to a valid, properly aligned `T` in
+    /// `host_data`, and that it remains valid and is not mutated for the `'a` lifetime. As the
+    /// `host_data` pointer is opaque to the plugin, this can only be known if the plugin knows
+    /// exactly which host it is running in: calling this in any other host is Undefined Behavior.
+    ///
+    /// In particular, this must never be used with hosts written with `clack-host`, which store
+    /// a pointer to their own private data there.
+    #[inline]
+    pub unsafe fn host_data_as<T>(&self) -> Option<&'a T> {
+        self.host_data().cast::<T>().as_ref()
+    }
 }
 
 /// A thread-safe handle to the host.
@@ -155,6 +212,15 @@ impl<'a> HostSharedHandle<'a> {
         unsafe { self.raw.as_ref() }
     }
 
+    /// Returns a raw pointer to the C FFI-compatible host struct, without dereferencing it.
+    ///
+    /// This is mostly useful to pass the host handle to C helper libraries. If you need to safely
+    /// access the host struct through a shared reference, use [`as_raw`](Self::as_raw) instead.
+    #[inline]
+    pub fn as_raw_ptr(&self) -> *const clap_host {
+        self.raw.as_ptr()
+    }
+
     /// Returns this handle as a reference to the host's information.
     #[inline]
     pub fn as_info(&self) -> &HostInfo<'a> {
@@ -289,6 +355,15 @@ impl<'a> HostMainThreadHandle<'a> {
         // SAFETY: this type enforces the pointer is valid for 'a
         unsafe { self.raw.as_ref() }
     }
+
+    /// Returns a raw pointer to the C FFI-compatible host struct, without dereferencing it.
+    ///
+    /// This is mostly useful to pass the host handle to C helper libraries. If you need to safely
+    /// access the host struct through a shared reference, use [`as_raw`](Self::as_raw) instead.
+    #[inline]
+    pub fn as_raw_ptr(&self) -> *const clap_host {
+        self.raw.as_ptr()
+    }
 }
 
 impl<'a> From<HostMainThreadHandle<'a>> for HostSharedHandle<'a> {
@@ -336,6 +411,15 @@ impl<'a> HostAudioProcessorHandle<'a> {
         unsafe { self.raw.as_ref() }
     }
 
+    /// Returns a raw pointer to the C FFI-compatible host struct, without dereferencing it.
+    ///
+    /// This is mostly useful to pass the host handle to C helper libraries. If you need to safely
+    /// access the host struct through a shared reference, use [`as_raw`](Self::as_raw) instead.
+    #[inline]
+    pub fn as_raw_ptr(&self) -> *const clap_host {
+        self.raw.as_ptr()
+    }
+
     /// Returns this handle as a reference to a thread-safe host handle from this handle.
     #[inline]
     pub fn as_shared(&self) -> &HostSharedHandle<'a> {
@@ -363,3 +447,48 @@ impl<'a> Deref for HostAudioProcessorHandle<'a> {
 fn mismatched_instance() -> ! {
     panic!("Given host handle doesn't match the extension pointer it was used on.")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap_sys::version::CLAP_VERSION;
+
+    fn raw_host(host_data: *mut c_void) -> clap_host {
+        clap_host {
+            clap_version: CLAP_VERSION,
+            host_data,
+            name: core::ptr::null(),
+            vendor: core::ptr::null(),
+            url: core::ptr::null(),
+            version: core::ptr::null(),
+            get_extension: None,
+            request_restart: None,
+            request_process: None,
+            request_callback: None,
+        }
+    }
+
+    #[test]
+    fn reads_typed_host_data() {
+        let context = 42u32;
+        let raw = raw_host(&context as *const u32 as *mut c_void);
+
+        // SAFETY: the raw host lives for the whole test.
+        let info = unsafe { HostInfo::from_raw(NonNull::from(&raw)) };
+
+        assert_eq!(info.host_data(), raw.host_data);
+        // SAFETY: host_data points to a valid u32.
+        assert_eq!(unsafe { info.host_data_as::<u32>() }, Some(&42));
+    }
+
+    #[test]
+    fn null_host_data_reads_as_none() {
+        let raw = raw_host(core::ptr::null_mut());
+
+        // SAFETY: the raw host lives for the whole test.
+        let info = unsafe { HostInfo::from_raw(NonNull::from(&raw)) };
+
+        // SAFETY: host_data is null.
+        assert_eq!(unsafe { info.host_data_as::<u32>() }, None);
+    }
+}
@@ -1,8 +1,9 @@
-use std::cell::UnsafeCell;
-use std::mem::MaybeUninit;
-use std::ptr;
-use std::ptr::NonNull;
-use std::sync::atomic::{AtomicBool, Ordering};
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
 
 /// A safer form of [`core::slice::from_raw_parts`] that returns a properly aligned slice in case
 /// the length is 0.
@@ -40,6 +41,108 @@ pub(crate) unsafe fn slice_from_external_parts_mut<'a, T>(data: *mut T, len: usi
     core::slice::from_raw_parts_mut(data, len)
 }
 
+/// A heap allocation that, unlike [`Box`], may be moved around while references to its contents
+/// are still alive.
+///
+/// Moving a `Box` asserts unique access to its contents, which invalidates any reference that
+/// was previously handed out to them, even through a `Pin`. This type only holds a raw pointer,
+/// which makes it suitable for self-referential structures, such as the plugin's main thread
+/// borrowing its shared data.
+pub(crate) struct AliasableBox<T> {
+    ptr: NonNull<T>,
+}
+
+impl<T> AliasableBox<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            ptr: NonNull::from(Box::leak(Box::new(value))),
+        }
+    }
+
+    /// Returns a pointer to the contents, which stays valid until this box is dropped, even if it
+    /// is moved.
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> NonNull<T> {
+        self.ptr
+    }
+}
+
+impl<T> core::ops::Deref for AliasableBox<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the pointer is valid for as long as this box is alive, and is never mutated.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T> Drop for AliasableBox<T> {
+    fn drop(&mut self) {
+        // SAFETY: the pointer was created from a Box, and is never used again after this.
+        drop(unsafe { Box::from_raw(self.ptr.as_ptr()) })
+    }
+}
+
+/// A heap allocation that is lazily initialized on first access, from any thread.
+///
+/// If multiple threads race to initialize it, all of them compute a value, but only the first one
+/// is kept. The value is never modified nor moved after it is set.
+pub(crate) struct OnceBox<T> {
+    ptr: AtomicPtr<T>,
+}
+
+impl<T> OnceBox<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            ptr: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the value, initializing it with `init` first if it wasn't already.
+    pub(crate) fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+        let mut current = self.ptr.load(Ordering::Acquire);
+
+        if current.is_null() {
+            let new = Box::into_raw(Box::new(init()));
+
+            current = match self.ptr.compare_exchange(
+                ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new,
+                Err(existing) => {
+                    // SAFETY: the new pointer was never shared with anyone.
+                    drop(unsafe { Box::from_raw(new) });
+                    existing
+                }
+            };
+        }
+
+        // SAFETY: the pointer was set once from a Box, and is only freed when this is dropped.
+        unsafe { &*current }
+    }
+}
+
+impl<T> Drop for OnceBox<T> {
+    fn drop(&mut self) {
+        let ptr = *self.ptr.get_mut();
+        if !ptr.is_null() {
+            // SAFETY: the pointer was created from a Box, and is never used again after this.
+            drop(unsafe { Box::from_raw(ptr) })
+        }
+    }
+}
+
+// SAFETY: the value is only ever shared immutably once it is set, but it can be set from any
+// thread, and dropped on another.
+unsafe impl<T: Send + Sync> Sync for OnceBox<T> {}
+
+// SAFETY: this owns the value, like a Box would.
+unsafe impl<T: Send> Send for OnceBox<T> {}
+
 /// Equivalent in spirit to `UnsafeCell<Option<T>>`, except you can read if the cell is set or not
 /// without invalidating potential active &mut references to the data.
 pub(crate) struct UnsafeOptionCell<T> {
@@ -110,3 +213,72 @@ impl<T> Drop for UnsafeOptionCell<T> {
         *is_some = false;
     }
 }
+
+/// A minimal spin lock, standing in for [`std::sync::Mutex`] when the standard library is not
+/// available.
+///
+/// This is only used to guard the rare, short critical sections of the bundle entry.
+#[cfg(not(feature = "std"))]
+pub(crate) struct SpinMutex<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: the value is only ever accessed by a single thread at a time, through the lock.
+#[cfg(not(feature = "std"))]
+unsafe impl<T: Send> Sync for SpinMutex<T> {}
+
+#[cfg(not(feature = "std"))]
+impl<T> SpinMutex<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub(crate) fn lock(&self) -> SpinMutexGuard<T> {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+
+        SpinMutexGuard { mutex: self }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) struct SpinMutexGuard<'a, T> {
+    mutex: &'a SpinMutex<T>,
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> core::ops::Deref for SpinMutexGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the lock is held for as long as this guard is alive.
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> core::ops::DerefMut for SpinMutexGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the lock is held for as long as this guard is alive.
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<T> Drop for SpinMutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.mutex.locked.store(false, Ordering::Release);
+    }
+}
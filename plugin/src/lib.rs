@@ -2,7 +2,9 @@
 #![doc(html_logo_url = "https://raw.githubusercontent.com/prokopyl/clack/main/logo.svg")]
 #![deny(clippy::undocumented_unsafe_blocks)]
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
 extern crate core;
 
 #[macro_use]
@@ -15,30 +17,53 @@ pub mod process;
 
 pub(crate) mod internal_utils;
 
+pub use clack_common::error;
 pub use clack_common::events;
 pub use clack_common::stream;
 pub use clack_common::utils;
 
 /// A helpful prelude re-exporting all the types related to plugin implementation.
+///
+/// This covers the plugin traits and descriptors, the entry types most plugins need, the host
+/// handles, audio and event processing types, as well as the standard event types.
+///
+/// Some items are intentionally left out:
+///
+/// * raw FFI wrappers and the underlying `clap_sys` types, which have to be imported from their
+///   own module (or from `clap_sys` directly);
+/// * types only needed to implement custom entries or factories, which are provided by the
+///   [`entry::prelude`](crate::entry::prelude) instead;
+/// * [`HostInfo`](crate::host::HostInfo), as its name would collide with the host-side type of
+///   the same name when used along with `clack_host`'s prelude, e.g. in tests.
+///
+/// Types related to extensions are provided by `clack_extensions::prelude`.
 pub mod prelude {
     pub use crate::{
-        clack_export_entry,
+        clack_entry, clack_export_entry,
         entry::{DefaultPluginFactory, Entry, EntryDescriptor, SinglePluginEntry},
         events::{
+            event_types::*,
             io::{InputEvents, OutputEvents},
-            Event, EventHeader, Pckn, UnknownEvent,
+            spaces::CoreEventSpace,
+            Event, EventFlags, EventHeader, Match, Pckn, UnknownEvent,
         },
         extensions::PluginExtensions,
         host::{HostAudioProcessorHandle, HostMainThreadHandle, HostSharedHandle},
         plugin::{
-            Plugin, PluginAudioProcessor, PluginDescriptor, PluginError, PluginMainThread,
-            PluginShared,
+            features, AudioStateBlob, Plugin, PluginAudioProcessor, PluginDescriptor, PluginError,
+            PluginMainThread, PluginShared, PluginVTableConfig,
         },
         process::{
-            audio::{ChannelPair, SampleType},
+            audio::{
+                AudioChunk, BufferError, ChannelPair, InputPort, OutputPort, PortPair, SampleType,
+            },
             Audio, Events, PluginAudioConfiguration, Process, ProcessStatus,
         },
-        utils::ClapId,
+        stream::{InputStream, OutputStream},
+        utils::{
+            compatibility::{CheckLevel, CompatibilityCheck, CompatibilityPolicy},
+            ClapId, Cookie,
+        },
     };
 }
 
@@ -49,15 +49,21 @@
 use crate::extensions::PluginExtensions;
 use crate::host::HostAudioProcessorHandle;
 use crate::process::{Audio, Events, PluginAudioConfiguration, Process, ProcessStatus};
+use crate::utils::compatibility::CompatibilityPolicy;
 
+mod audio_state;
 mod descriptor;
+pub mod dynamic;
 mod error;
 mod instance;
 pub(crate) mod logging;
+mod vtable;
 
+pub use audio_state::AudioStateBlob;
 pub use descriptor::*;
 pub use error::PluginError;
 pub use instance::*;
+pub use vtable::PluginVTableConfig;
 
 pub use clack_common::plugin::*;
 
@@ -138,6 +144,32 @@ pub trait Plugin: 'static {
     /// See the [module documentation](crate::plugin) for more information on the thread model.
     type MainThread<'a>: PluginMainThread<'a, Self::Shared<'a>>;
 
+    /// Whether process calls for empty blocks should skip this plugin's audio processor.
+    ///
+    /// Some hosts call `process` with a frame count of zero. If this is set to `true`, such calls
+    /// return [`ProcessStatus::Continue`](crate::process::ProcessStatus::Continue) directly,
+    /// without calling [`PluginAudioProcessor::process`], unless they also carry input events.
+    ///
+    /// This is `true` by default. Plugins that need to observe every process call can set this
+    /// to `false`, in which case they must handle zero-length audio buffers.
+    const SKIP_EMPTY_BLOCKS: bool = true;
+
+    /// Which of the optional functions of the raw `clap_plugin` table are exposed to the host.
+    ///
+    /// See [`PluginVTableConfig`] for more information. This is [`PluginVTableConfig::ALL`] by
+    /// default.
+    const VTABLE: PluginVTableConfig = PluginVTableConfig::ALL;
+
+    /// How this plugin handles the host's violations of the CLAP specification, and its own.
+    ///
+    /// See [`CompatibilityPolicy`] for the available checks and presets. Only the plugin-side
+    /// checks apply: they cover extension queries made before `init`, `process` calls with an
+    /// unexpected number of frames or audio ports, and the ordering of this plugin's own output
+    /// events.
+    ///
+    /// This is [`CompatibilityPolicy::COMPATIBLE`] by default.
+    const COMPATIBILITY: CompatibilityPolicy = CompatibilityPolicy::COMPATIBLE;
+
     /// Declares the extensions this plugin supports.
     ///
     /// This Implemented by calling [`register`] on the given [`PluginExtensions`]
@@ -191,7 +223,8 @@ pub trait PluginAudioProcessor<'a, S: PluginShared<'a>, M: PluginMainThread<'a,
     /// # Errors
     ///
     /// This operation may fail for any reason, in which case `Err` is returned
-    /// and the plugin is not activated.
+    /// and the plugin is not activated. The error is logged to the host, along with the audio
+    /// configuration the plugin failed to activate with.
     ///
     /// # Realtime Safety
     ///
@@ -257,6 +290,16 @@ pub trait PluginAudioProcessor<'a, S: PluginShared<'a>, M: PluginMainThread<'a,
     ///
     /// Calling this method allows the `steady_time` parameter passed to [`process`](Self::process)
     /// to jump backwards.
+    ///
+    /// This is only meant to reset the state derived from the audio and events processed so far.
+    /// Parameter values are part of the plugin's state, not its processing state, and must
+    /// therefore be left untouched: after a reset, the plugin must keep processing with the same
+    /// parameter values as before.
+    ///
+    /// This method is called on the audio thread, whether processing is
+    /// [started](Self::start_processing) or not. It is never called while the plugin is inactive.
+    ///
+    /// The default implementation does nothing.
     #[allow(unused)]
     #[inline]
     fn reset(&mut self) {}
@@ -279,6 +322,35 @@ pub trait PluginAudioProcessor<'a, S: PluginShared<'a>, M: PluginMainThread<'a,
     /// and events it receives (if any) is contiguous to the last one it [processed](Self::process).
     #[inline]
     fn stop_processing(&mut self) {}
+
+    /// Saves the part of the audio processor's state that should survive a host restart.
+    ///
+    /// This is called on the main thread, right before the audio processor is
+    /// [deactivated](Self::deactivate), but only if the host is deactivating the plugin to
+    /// immediately re-activate it (e.g. to change its audio configuration). The returned blob is
+    /// then given to [`restore_audio_state`](Self::restore_audio_state) on the re-activated
+    /// audio processor.
+    ///
+    /// This allows plugins to carry over state such as delay lines, envelopes or smoothed
+    /// parameter values, minimizing audible glitches when the host restarts them. Plugins must
+    /// not rely on this being called, as only Clack hosts can signal a restart.
+    ///
+    /// The default implementation saves nothing and returns `None`.
+    #[inline]
+    fn save_audio_state(&mut self) -> Option<AudioStateBlob> {
+        None
+    }
+
+    /// Restores the state previously saved by [`save_audio_state`](Self::save_audio_state).
+    ///
+    /// This is called on the main thread, right after the audio processor was
+    /// [activated](Self::activate) as part of the same host restart in which the state was saved.
+    /// Note that the new audio configuration may differ from the previous one.
+    ///
+    /// The default implementation discards the given state.
+    #[allow(unused)]
+    #[inline]
+    fn restore_audio_state(&mut self, blob: AudioStateBlob) {}
 }
 
 impl<'a, M: PluginMainThread<'a, S>, S: PluginShared<'a>> PluginAudioProcessor<'a, S, M> for () {
@@ -0,0 +1,67 @@
+use alloc::boxed::Box;
+use core::any::Any;
+use core::fmt::{Debug, Formatter};
+
+/// A piece of audio processing state, carried over from a deactivated audio processor to the
+/// next one when the host restarts the plugin.
+///
+/// This is produced by [`PluginAudioProcessor::save_audio_state`] and consumed by
+/// [`PluginAudioProcessor::restore_audio_state`]. It can hold any [`Send`] value, which can be
+/// retrieved using [`downcast`](Self::downcast).
+///
+/// [`PluginAudioProcessor::save_audio_state`]: crate::plugin::PluginAudioProcessor::save_audio_state
+/// [`PluginAudioProcessor::restore_audio_state`]: crate::plugin::PluginAudioProcessor::restore_audio_state
+pub struct AudioStateBlob(Box<dyn Any + Send>);
+
+impl AudioStateBlob {
+    /// Wraps the given state into a blob.
+    #[inline]
+    pub fn new<T: Any + Send>(state: T) -> Self {
+        Self(Box::new(state))
+    }
+
+    /// Returns `true` if this blob holds a value of type `T`.
+    #[inline]
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+
+    /// Returns a reference to the held state, if it is of type `T`.
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    /// Returns the held state, if it is of type `T`. Otherwise, this blob is returned unchanged.
+    #[inline]
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        if self.is::<T>() {
+            // PANIC: we just checked the type matches.
+            Ok(*self.0.downcast().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Debug for AudioStateBlob {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("AudioStateBlob { .. }")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn downcasts_to_the_held_type_only() {
+        let blob = AudioStateBlob::new(42u64);
+
+        assert!(blob.is::<u64>());
+        assert_eq!(blob.downcast_ref::<u32>(), None);
+
+        let blob = blob.downcast::<String>().unwrap_err();
+        assert_eq!(blob.downcast::<u64>().unwrap(), 42);
+    }
+}
@@ -1,8 +1,13 @@
+use crate::plugin::features::{validate_features, FeatureWarning};
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::vec;
+use alloc::vec::Vec;
 use clap_sys::plugin::clap_plugin_descriptor;
 use clap_sys::version::CLAP_VERSION;
-use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
-use std::pin::Pin;
+use core::ffi::c_char;
+use core::ffi::CStr;
+use core::ops::Deref;
 
 /// Represents a type that can provide metadata about a given Plugin, such as its ID, name, version,
 /// and more.
@@ -27,19 +32,21 @@ use std::pin::Pin;
 /// }
 /// ```
 pub struct PluginDescriptor {
-    id: Pin<Box<CStr>>,
-    name: Pin<Box<CStr>>,
+    id: OwnedCStr,
+    name: OwnedCStr,
 
-    vendor: Option<Pin<Box<CStr>>>,
-    url: Option<Pin<Box<CStr>>>,
-    manual_url: Option<Pin<Box<CStr>>>,
-    support_url: Option<Pin<Box<CStr>>>,
-    version: Option<Pin<Box<CStr>>>,
-    description: Option<Pin<Box<CStr>>>,
+    vendor: Option<OwnedCStr>,
+    url: Option<OwnedCStr>,
+    manual_url: Option<OwnedCStr>,
+    support_url: Option<OwnedCStr>,
+    version: Option<OwnedCStr>,
+    description: Option<OwnedCStr>,
 
     features: Vec<Box<CStr>>,
     features_array: Vec<*const c_char>,
 
+    compatible_ids: Vec<Box<CStr>>,
+
     raw_descriptor: clap_plugin_descriptor,
 }
 
@@ -49,6 +56,31 @@ unsafe impl Send for PluginDescriptor {}
 // SAFETY: PluginDescriptor does not have any interior mutability.
 unsafe impl Sync for PluginDescriptor {}
 
+/// An owned C string, which can be moved around without invalidating the pointers to its contents.
+///
+/// Moving a `Box<CStr>` asserts unique access to the string, which would invalidate the pointers
+/// the raw descriptor holds to it every time the descriptor is moved.
+#[derive(Clone)]
+struct OwnedCStr(Vec<u8>);
+
+impl OwnedCStr {
+    /// Creates a new C string from the given string, panicking with the given message if it
+    /// contains NULL-byte characters.
+    fn new(string: &str, error: &str) -> Self {
+        Self(CString::new(string).expect(error).into_bytes_with_nul())
+    }
+}
+
+impl Deref for OwnedCStr {
+    type Target = CStr;
+
+    #[inline]
+    fn deref(&self) -> &CStr {
+        // SAFETY: the bytes always come from a CString, and are thus valid.
+        unsafe { CStr::from_bytes_with_nul_unchecked(&self.0) }
+    }
+}
+
 // SAFETY: there is a null byte in this string.
 const EMPTY: &CStr = unsafe { CStr::from_bytes_with_nul_unchecked(b"\0") };
 const EMPTY_FEATURES: &[*const c_char] = &[core::ptr::null()];
@@ -75,17 +107,9 @@ impl PluginDescriptor {
             panic!("Plugin Name must not be blank!");
         }
 
-        let id = Pin::new(
-            CString::new(id)
-                .expect("Invalid Plugin ID")
-                .into_boxed_c_str(),
-        );
+        let id = OwnedCStr::new(id, "Invalid Plugin ID");
 
-        let name = Pin::new(
-            CString::new(name)
-                .expect("Invalid Plugin Name")
-                .into_boxed_c_str(),
-        );
+        let name = OwnedCStr::new(name, "Invalid Plugin Name");
 
         Self {
             raw_descriptor: clap_plugin_descriptor {
@@ -113,6 +137,8 @@ impl PluginDescriptor {
 
             features: vec![],
             features_array: vec![],
+
+            compatible_ids: vec![],
         }
     }
 
@@ -141,11 +167,7 @@ impl PluginDescriptor {
             panic!("Plugin ID must not be blank!");
         }
 
-        let id = Pin::new(
-            CString::new(id)
-                .expect("Invalid Plugin ID")
-                .into_boxed_c_str(),
-        );
+        let id = OwnedCStr::new(id, "Invalid Plugin ID");
 
         self.raw_descriptor.id = id.as_ptr();
         self.id = id;
@@ -178,11 +200,7 @@ impl PluginDescriptor {
             panic!("Plugin name must not be blank!");
         }
 
-        let name = Pin::new(
-            CString::new(name)
-                .expect("Invalid Plugin name")
-                .into_boxed_c_str(),
-        );
+        let name = OwnedCStr::new(name, "Invalid Plugin name");
 
         self.raw_descriptor.name = name.as_ptr();
         self.name = name;
@@ -214,11 +232,7 @@ impl PluginDescriptor {
             self.raw_descriptor.vendor = EMPTY.as_ptr();
             self.vendor = None;
         } else {
-            let vendor = Pin::new(
-                CString::new(vendor)
-                    .expect("Invalid Plugin vendor")
-                    .into_boxed_c_str(),
-            );
+            let vendor = OwnedCStr::new(vendor, "Invalid Plugin vendor");
 
             self.raw_descriptor.vendor = vendor.as_ptr();
             self.vendor = Some(vendor);
@@ -251,11 +265,7 @@ impl PluginDescriptor {
             self.raw_descriptor.url = EMPTY.as_ptr();
             self.url = None;
         } else {
-            let url = Pin::new(
-                CString::new(url)
-                    .expect("Invalid Plugin URL")
-                    .into_boxed_c_str(),
-            );
+            let url = OwnedCStr::new(url, "Invalid Plugin URL");
 
             self.raw_descriptor.url = url.as_ptr();
             self.url = Some(url);
@@ -288,11 +298,7 @@ impl PluginDescriptor {
             self.raw_descriptor.manual_url = EMPTY.as_ptr();
             self.manual_url = None;
         } else {
-            let manual_url = Pin::new(
-                CString::new(manual_url)
-                    .expect("Invalid Plugin Manual URL")
-                    .into_boxed_c_str(),
-            );
+            let manual_url = OwnedCStr::new(manual_url, "Invalid Plugin Manual URL");
 
             self.raw_descriptor.manual_url = manual_url.as_ptr();
             self.manual_url = Some(manual_url);
@@ -325,11 +331,7 @@ impl PluginDescriptor {
             self.raw_descriptor.support_url = EMPTY.as_ptr();
             self.support_url = None;
         } else {
-            let support_url = Pin::new(
-                CString::new(support_url)
-                    .expect("Invalid Plugin Support URL")
-                    .into_boxed_c_str(),
-            );
+            let support_url = OwnedCStr::new(support_url, "Invalid Plugin Support URL");
 
             self.raw_descriptor.support_url = support_url.as_ptr();
             self.support_url = Some(support_url);
@@ -365,11 +367,7 @@ impl PluginDescriptor {
             self.raw_descriptor.version = EMPTY.as_ptr();
             self.version = None;
         } else {
-            let version = Pin::new(
-                CString::new(version)
-                    .expect("Invalid Plugin version")
-                    .into_boxed_c_str(),
-            );
+            let version = OwnedCStr::new(version, "Invalid Plugin version");
 
             self.raw_descriptor.version = version.as_ptr();
             self.version = Some(version);
@@ -402,11 +400,7 @@ impl PluginDescriptor {
             self.raw_descriptor.description = EMPTY.as_ptr();
             self.description = None;
         } else {
-            let description = Pin::new(
-                CString::new(description)
-                    .expect("Invalid Plugin description")
-                    .into_boxed_c_str(),
-            );
+            let description = OwnedCStr::new(description, "Invalid Plugin description");
 
             self.raw_descriptor.description = description.as_ptr();
             self.description = Some(description);
@@ -427,11 +421,27 @@ impl PluginDescriptor {
 
     /// Sets the plugin's feature list.
     ///
+    /// Features can be given either as raw C strings (e.g. the constants in the
+    /// [`features`](super::features) module), or as [`PluginFeature`](super::features::PluginFeature)
+    /// values.
+    ///
     /// See the [`features`](PluginDescriptor::features) method documentation for more information.
-    pub fn with_features<'a>(mut self, features: impl IntoIterator<Item = &'a CStr>) -> Self {
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_plugin::plugin::features::{PluginFeature, STEREO};
+    /// use clack_plugin::prelude::PluginDescriptor;
+    ///
+    /// let descriptor = PluginDescriptor::new("org.rust-audio.clack.gain", "Clack Gain")
+    ///     .with_features([PluginFeature::AudioEffect, PluginFeature::Stereo]);
+    ///
+    /// assert_eq!(descriptor.features()[1].as_ref(), STEREO);
+    /// ```
+    pub fn with_features<F: AsRef<CStr>>(mut self, features: impl IntoIterator<Item = F>) -> Self {
         self.features = features
             .into_iter()
-            .map(|s| CString::from(s).into_boxed_c_str())
+            .map(|s| CString::from(s.as_ref()).into_boxed_c_str())
             .collect();
 
         self.features_array = self.features.iter().map(|f| f.as_ptr()).collect();
@@ -442,6 +452,48 @@ impl PluginDescriptor {
         self
     }
 
+    /// Checks this descriptor's feature list for unknown features or inconsistent combinations.
+    ///
+    /// See [`validate_features`] for more information.
+    pub fn validate_features(&self) -> Vec<FeatureWarning> {
+        validate_features(self.features.iter().map(|f| f.as_ref()))
+    }
+
+    /// The IDs of other plugins this plugin is compatible with.
+    ///
+    /// Hosts can use this plugin in place of any of those, e.g. when loading a session that
+    /// references one of them. This is typically used to replace a deprecated plugin ID.
+    ///
+    /// This isn't part of the CLAP descriptor: it is exposed to the host through the
+    /// [`PluginCompatibilityFactory`](crate::factory::compatibility::PluginCompatibilityFactory).
+    #[inline]
+    pub fn compatible_ids(&self) -> &[Box<CStr>] {
+        &self.compatible_ids
+    }
+
+    /// Sets the IDs of other plugins this plugin is compatible with.
+    ///
+    /// See the [`compatible_ids`](PluginDescriptor::compatible_ids) method documentation for more
+    /// information. Empty IDs are ignored.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the given IDs contains NULL-byte characters, which are
+    /// invalid.
+    pub fn with_compatible_ids<S: AsRef<str>>(mut self, ids: impl IntoIterator<Item = S>) -> Self {
+        self.compatible_ids = ids
+            .into_iter()
+            .filter(|id| !id.as_ref().is_empty())
+            .map(|id| {
+                CString::new(id.as_ref())
+                    .expect("Invalid compatible Plugin ID")
+                    .into_boxed_c_str()
+            })
+            .collect();
+
+        self
+    }
+
     /// Returns the plugin descriptor as a reference to the C-FFI compatible CLAP struct.
     #[inline]
     pub fn as_raw(&self) -> &clap_plugin_descriptor {
@@ -462,6 +514,7 @@ impl Clone for PluginDescriptor {
         let description = self.description.clone();
 
         let features = self.features.clone();
+        let compatible_ids = self.compatible_ids.clone();
         let mut features_array: Vec<_> = features.iter().map(|f| f.as_ptr()).collect();
 
         if !features_array.is_empty() {
@@ -500,6 +553,8 @@ impl Clone for PluginDescriptor {
 
             features,
             features_array,
+
+            compatible_ids,
         }
     }
 }
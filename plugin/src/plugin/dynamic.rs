@@ -0,0 +1,413 @@
+//! Support for plugins whose implementation is only known at runtime.
+//!
+//! The [`Plugin`] trait and its associated types are resolved statically, which allows the
+//! plugin wrapper to call into the plugin's implementation without any indirection. However, some
+//! plugins (e.g. script-based ones, where the DSP graph is only loaded at runtime) cannot know
+//! their concrete implementation types ahead of time.
+//!
+//! This module provides the [`DynPlugin`] type, which implements [`Plugin`] once and for all, and
+//! forwards all of the plugin's callbacks to trait objects:
+//!
+//! * [`PluginSharedDyn`], for the thread-safe part of the plugin, which also declares the
+//!   extensions the plugin supports;
+//! * [`PluginMainThreadDyn`], for the main-thread part of the plugin, which also creates the
+//!   audio processor on activation;
+//! * [`PluginAudioProcessorDyn`], for the audio processor.
+//!
+//! Each callback costs a single virtual call on top of the static path.
+//!
+//! # Extensions
+//!
+//! Extensions are declared at runtime by [`PluginSharedDyn::declare_extensions`], depending on
+//! what the plugin's implementation actually supports.
+//!
+//! Extension implementations are then retrieved from the main thread and audio processor trait
+//! objects using their `provide` methods, by providing a trait object of the extension's
+//! implementation trait to a [`DynInterfaceRequest`]. Extensions that support dynamic plugins
+//! (such as `clack_extensions`' params, audio ports, note ports, state and latency extensions)
+//! implement [`DynInterface`] on their implementation traits' trait objects for this purpose.
+//!
+//! # Example
+//!
+//! ```
+//! use clack_plugin::prelude::*;
+//! use clack_plugin::plugin::dynamic::*;
+//!
+//! struct ScriptShared;
+//! impl PluginSharedDyn<'_> for ScriptShared {}
+//!
+//! struct ScriptMainThread;
+//!
+//! impl<'a> PluginMainThreadDyn<'a> for ScriptMainThread {
+//!     fn activate(
+//!         &mut self,
+//!         _host: HostAudioProcessorHandle<'a>,
+//!         _audio_config: PluginAudioConfiguration,
+//!     ) -> Result<Box<dyn PluginAudioProcessorDyn<'a>>, PluginError> {
+//!         // The actual processor could be picked depending on the loaded script.
+//!         Ok(Box::new(Silence))
+//!     }
+//! }
+//!
+//! struct Silence;
+//!
+//! impl PluginAudioProcessorDyn<'_> for Silence {
+//!     fn process(
+//!         &mut self,
+//!         _process: Process,
+//!         _audio: Audio,
+//!         _events: Events,
+//!     ) -> Result<ProcessStatus, PluginError> {
+//!         Ok(ProcessStatus::Sleep)
+//!     }
+//! }
+//!
+//! // In a custom PluginFactory implementation:
+//! fn create_plugin<'a>(
+//!     host_info: HostInfo<'a>,
+//!     descriptor: &'a PluginDescriptor,
+//! ) -> PluginInstance<'a> {
+//!     PluginInstance::new::<DynPlugin>(
+//!         host_info,
+//!         descriptor,
+//!         |_host| Ok(DynPluginShared::new(Box::new(ScriptShared))),
+//!         |_host, _shared| Ok(DynPluginMainThread::new(Box::new(ScriptMainThread))),
+//!     )
+//! }
+//! # use clack_plugin::host::HostInfo;
+//! # use clack_plugin::plugin::PluginInstance;
+//! ```
+
+use crate::extensions::PluginExtensions;
+use crate::host::HostAudioProcessorHandle;
+use crate::plugin::{Plugin, PluginAudioProcessor, PluginError, PluginMainThread, PluginShared};
+use crate::process::{Audio, Events, PluginAudioConfiguration, Process, ProcessStatus};
+use alloc::boxed::Box;
+use core::any::TypeId;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+/// A [`Plugin`] implementation that forwards all of its callbacks to trait objects.
+///
+/// See the [module documentation](self) for more information.
+pub struct DynPlugin;
+
+impl Plugin for DynPlugin {
+    type AudioProcessor<'a> = DynPluginAudioProcessor<'a>;
+    type Shared<'a> = DynPluginShared<'a>;
+    type MainThread<'a> = DynPluginMainThread<'a>;
+
+    #[inline]
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, shared: Option<&Self::Shared<'_>>) {
+        if let Some(shared) = shared {
+            shared.inner.declare_extensions(builder)
+        }
+    }
+}
+
+/// An object-safe version of the [`PluginShared`] trait, used by [`DynPlugin`].
+pub trait PluginSharedDyn<'a>: Send + Sync + 'a {
+    /// Declares the extensions this plugin supports.
+    ///
+    /// The extensions registered here must be provided by the main thread and audio processor
+    /// trait objects, depending on the extension. See the [module documentation](self) for more
+    /// information.
+    #[inline]
+    #[allow(unused_variables)]
+    fn declare_extensions(&self, builder: &mut PluginExtensions<DynPlugin>) {}
+}
+
+/// An object-safe version of the [`PluginMainThread`] trait, used by [`DynPlugin`].
+///
+/// Implementations of this trait also create the plugin's audio processor on activation.
+pub trait PluginMainThreadDyn<'a>: 'a {
+    /// Activates the plugin, and returns its audio processor.
+    ///
+    /// See [`PluginAudioProcessor::activate`].
+    fn activate(
+        &mut self,
+        host: HostAudioProcessorHandle<'a>,
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Box<dyn PluginAudioProcessorDyn<'a>>, PluginError>;
+
+    /// Deactivates the plugin, consuming its audio processor.
+    ///
+    /// See [`PluginAudioProcessor::deactivate`].
+    #[inline]
+    #[allow(unused_variables)]
+    fn deactivate(&mut self, audio_processor: Box<dyn PluginAudioProcessorDyn<'a>>) {}
+
+    /// See [`PluginMainThread::on_main_thread`].
+    #[inline]
+    fn on_main_thread(&mut self) {}
+
+    /// Provides the main-thread implementations of the extensions this plugin supports.
+    ///
+    /// See the [module documentation](self) for more information.
+    #[inline]
+    #[allow(unused_variables)]
+    fn provide<'r>(&'r mut self, request: &mut DynInterfaceRequest<'r, 'a>) {}
+}
+
+/// An object-safe version of the [`PluginAudioProcessor`] trait, used by [`DynPlugin`].
+pub trait PluginAudioProcessorDyn<'a>: Send + 'a {
+    /// See [`PluginAudioProcessor::process`].
+    fn process(
+        &mut self,
+        process: Process,
+        audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError>;
+
+    /// See [`PluginAudioProcessor::reset`].
+    #[inline]
+    fn reset(&mut self) {}
+
+    /// See [`PluginAudioProcessor::start_processing`].
+    #[inline]
+    fn start_processing(&mut self) -> Result<(), PluginError> {
+        Ok(())
+    }
+
+    /// See [`PluginAudioProcessor::stop_processing`].
+    #[inline]
+    fn stop_processing(&mut self) {}
+
+    /// Provides the audio-thread implementations of the extensions this plugin supports.
+    ///
+    /// See the [module documentation](self) for more information.
+    #[inline]
+    #[allow(unused_variables)]
+    fn provide<'r>(&'r mut self, request: &mut DynInterfaceRequest<'r, 'a>) {}
+}
+
+/// An interface that can be requested from a dynamic plugin's trait objects.
+///
+/// This is implemented by extensions on the trait objects of their implementation traits (e.g.
+/// `dyn PluginStateImpl`), which are both the key used to request an interface, and the type of
+/// the provided implementation.
+pub trait DynInterface: 'static {
+    /// The type of the interface's implementation, usually `dyn Trait + 'a`.
+    type Target<'a>: ?Sized + 'a;
+}
+
+/// A request for an interface implementation, made to a dynamic plugin's trait objects.
+///
+/// See the [module documentation](self) for more information.
+pub struct DynInterfaceRequest<'r, 'a> {
+    type_id: TypeId,
+    slot: NonNull<()>,
+    // Invariant in both lifetimes: the slot's type depends on them.
+    _lifetimes: PhantomData<fn(&'r mut &'a ()) -> &'r mut &'a ()>,
+}
+
+impl<'r, 'a> DynInterfaceRequest<'r, 'a> {
+    /// Provides an implementation of the interface `I`, if it is the one being requested.
+    ///
+    /// Providing an implementation for an interface that is not requested does nothing.
+    #[inline]
+    pub fn provide<I: DynInterface + ?Sized>(
+        &mut self,
+        implementation: &'r mut I::Target<'a>,
+    ) -> &mut Self {
+        if self.type_id == TypeId::of::<I>() {
+            // SAFETY: the slot's type was created from the same interface type as its TypeId, and
+            // with the same lifetimes, which are invariant.
+            unsafe {
+                *self.slot.cast::<Option<&'r mut I::Target<'a>>>().as_ptr() = Some(implementation)
+            };
+        }
+
+        self
+    }
+
+    /// Returns `true` if the interface `I` is the one being requested.
+    #[inline]
+    pub fn is<I: DynInterface + ?Sized>(&self) -> bool {
+        self.type_id == TypeId::of::<I>()
+    }
+
+    #[inline]
+    fn request<I: DynInterface + ?Sized>(
+        provider: impl FnOnce(&mut DynInterfaceRequest<'r, 'a>),
+    ) -> Option<&'r mut I::Target<'a>> {
+        let mut slot: Option<&'r mut I::Target<'a>> = None;
+        let mut request = DynInterfaceRequest {
+            type_id: TypeId::of::<I>(),
+            slot: NonNull::from(&mut slot).cast(),
+            _lifetimes: PhantomData,
+        };
+
+        provider(&mut request);
+        slot
+    }
+}
+
+/// The [`Shared`](Plugin::Shared) type of a [`DynPlugin`].
+pub struct DynPluginShared<'a> {
+    inner: Box<dyn PluginSharedDyn<'a>>,
+}
+
+impl<'a> DynPluginShared<'a> {
+    /// Wraps the given trait object.
+    #[inline]
+    pub fn new(inner: Box<dyn PluginSharedDyn<'a>>) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the wrapped trait object.
+    #[inline]
+    pub fn get(&self) -> &dyn PluginSharedDyn<'a> {
+        &*self.inner
+    }
+}
+
+impl<'a> PluginShared<'a> for DynPluginShared<'a> {}
+
+/// The [`MainThread`](Plugin::MainThread) type of a [`DynPlugin`].
+pub struct DynPluginMainThread<'a> {
+    inner: Box<dyn PluginMainThreadDyn<'a>>,
+}
+
+impl<'a> DynPluginMainThread<'a> {
+    /// Wraps the given trait object.
+    #[inline]
+    pub fn new(inner: Box<dyn PluginMainThreadDyn<'a>>) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a mutable reference to the wrapped trait object.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut dyn PluginMainThreadDyn<'a> {
+        &mut *self.inner
+    }
+
+    /// Returns the main-thread implementation of the interface `I`, if the plugin provides it.
+    #[inline]
+    pub fn interface<I: DynInterface + ?Sized>(&mut self) -> Option<&mut I::Target<'a>> {
+        DynInterfaceRequest::request::<I>(|request| self.inner.provide(request))
+    }
+}
+
+impl<'a> PluginMainThread<'a, DynPluginShared<'a>> for DynPluginMainThread<'a> {
+    #[inline]
+    fn on_main_thread(&mut self) {
+        self.inner.on_main_thread()
+    }
+}
+
+/// The [`AudioProcessor`](Plugin::AudioProcessor) type of a [`DynPlugin`].
+pub struct DynPluginAudioProcessor<'a> {
+    inner: Box<dyn PluginAudioProcessorDyn<'a>>,
+}
+
+impl<'a> DynPluginAudioProcessor<'a> {
+    /// Returns a mutable reference to the wrapped trait object.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut dyn PluginAudioProcessorDyn<'a> {
+        &mut *self.inner
+    }
+
+    /// Returns the audio-thread implementation of the interface `I`, if the plugin provides it.
+    #[inline]
+    pub fn interface<I: DynInterface + ?Sized>(&mut self) -> Option<&mut I::Target<'a>> {
+        DynInterfaceRequest::request::<I>(|request| self.inner.provide(request))
+    }
+}
+
+impl<'a> PluginAudioProcessor<'a, DynPluginShared<'a>, DynPluginMainThread<'a>>
+    for DynPluginAudioProcessor<'a>
+{
+    #[inline]
+    fn activate(
+        host: HostAudioProcessorHandle<'a>,
+        main_thread: &mut DynPluginMainThread<'a>,
+        _shared: &'a DynPluginShared<'a>,
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        let inner = main_thread.inner.activate(host, audio_config)?;
+        Ok(Self { inner })
+    }
+
+    #[inline]
+    fn process(
+        &mut self,
+        process: Process,
+        audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.inner.process(process, audio, events)
+    }
+
+    #[inline]
+    fn deactivate(self, main_thread: &mut DynPluginMainThread<'a>) {
+        main_thread.inner.deactivate(self.inner)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.inner.reset()
+    }
+
+    #[inline]
+    fn start_processing(&mut self) -> Result<(), PluginError> {
+        self.inner.start_processing()
+    }
+
+    #[inline]
+    fn stop_processing(&mut self) {
+        self.inner.stop_processing()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    trait Greet {
+        fn greet(&mut self) -> &'static str;
+    }
+
+    impl DynInterface for dyn Greet {
+        type Target<'a> = dyn Greet + 'a;
+    }
+
+    trait Count {}
+
+    impl DynInterface for dyn Count {
+        type Target<'a> = dyn Count + 'a;
+    }
+
+    struct Greeter;
+
+    impl Greet for Greeter {
+        fn greet(&mut self) -> &'static str {
+            "Hello"
+        }
+    }
+
+    impl<'a> PluginMainThreadDyn<'a> for Greeter {
+        fn activate(
+            &mut self,
+            _host: HostAudioProcessorHandle<'a>,
+            _audio_config: PluginAudioConfiguration,
+        ) -> Result<Box<dyn PluginAudioProcessorDyn<'a>>, PluginError> {
+            Err(PluginError::Message("Cannot activate"))
+        }
+
+        fn provide<'r>(&'r mut self, request: &mut DynInterfaceRequest<'r, 'a>) {
+            request.provide::<dyn Greet>(self);
+        }
+    }
+
+    #[test]
+    fn provides_requested_interfaces_only() {
+        let mut main_thread = DynPluginMainThread::new(Box::new(Greeter));
+
+        assert_eq!(
+            main_thread.interface::<dyn Greet>().map(|g| g.greet()),
+            Some("Hello")
+        );
+        assert!(main_thread.interface::<dyn Count>().is_none());
+    }
+}
@@ -1,5 +1,6 @@
+use alloc::boxed::Box;
+use clack_common::error::Error;
 use core::fmt::{self, Debug, Display, Formatter};
-use std::error::Error;
 
 /// A generic, type-erased error type for plugin-originating errors.
 ///
@@ -45,6 +46,20 @@ pub enum PluginError {
     Message(&'static str),
 }
 
+impl PluginError {
+    /// Returns the underlying error, if this wraps one.
+    ///
+    /// This type cannot implement [`Error`] itself (as it can be converted from any [`Error`]),
+    /// so this method allows to walk its error chain instead.
+    #[inline]
+    pub fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PluginError::Error(e) => Some(e.as_ref()),
+            PluginError::Message(_) => None,
+        }
+    }
+}
+
 impl Display for PluginError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -1,18 +1,31 @@
 use crate::extensions::wrapper::{handle_panic, PluginWrapper, PluginWrapperError};
 use crate::extensions::PluginExtensions;
 use crate::host::{HostInfo, HostMainThreadHandle, HostSharedHandle};
+use crate::internal_utils::{
+    slice_from_external_parts, slice_from_external_parts_mut, AliasableBox,
+};
 use crate::plugin::instance::WrapperData::*;
+use crate::plugin::logging::plugin_report;
 use crate::plugin::{Plugin, PluginAudioProcessor, PluginError, PluginMainThread};
 use crate::prelude::PluginDescriptor;
-use crate::process::{Audio, Events, PluginAudioConfiguration, Process};
+use crate::process::audio::{PortCountCheck, UndeclaredPorts};
+use crate::process::output_check::OutputEventsChecker;
+use crate::process::{Audio, Events, PluginAudioConfiguration, Process, ProcessStatus};
+use alloc::boxed::Box;
+use clack_common::events::io::{InputEvents, OutputEvents};
+use clack_common::utils::compatibility::{CheckLevel, CompatibilityCheck};
+use clap_sys::ext::audio_ports::{clap_plugin_audio_ports, CLAP_EXT_AUDIO_PORTS};
+use clap_sys::ext::log::{CLAP_LOG_HOST_MISBEHAVING, CLAP_LOG_PLUGIN_MISBEHAVING};
 use clap_sys::plugin::{clap_plugin, clap_plugin_descriptor};
 use clap_sys::process::{clap_process, clap_process_status, CLAP_PROCESS_ERROR};
+use core::cell::UnsafeCell;
 use core::ffi::c_void;
-use std::cell::UnsafeCell;
-use std::ffi::CStr;
-use std::marker::PhantomData;
-use std::mem::ManuallyDrop;
-use std::sync::atomic::{AtomicU8, Ordering};
+use core::ffi::CStr;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::panic::AssertUnwindSafe;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 
 pub(crate) trait PluginInitializer<'a, P: Plugin>: 'a {
     fn init(
@@ -35,10 +48,10 @@ where
     ) -> Result<PluginWrapper<'a, P>, PluginError> {
         let (shared_initializer, main_thread_initializer) = *self;
         let shared_handle = host.shared();
-        let shared = Box::pin(shared_initializer(shared_handle)?);
+        let shared = AliasableBox::new(shared_initializer(shared_handle)?);
 
-        // SAFETY: this lives long enough
-        let shared_ref = unsafe { &*(shared.as_ref().get_ref() as *const _) };
+        // SAFETY: this lives as long as the wrapper, which also owns the main thread.
+        let shared_ref = unsafe { shared.as_ptr().as_ref() };
         let main_thread = main_thread_initializer(host, shared_ref)?;
 
         // SAFETY: we just created the shared and main_thread together
@@ -57,10 +70,10 @@ where
     ) -> Result<PluginWrapper<'a, P>, PluginError> {
         let shared_handle = host.shared();
         let (shared, main_thread_initializer) = self(host)?;
-        let shared = Box::pin(shared);
+        let shared = AliasableBox::new(shared);
 
-        // SAFETY: this lives long enough
-        let shared_ref = unsafe { &*(shared.as_ref().get_ref() as *const _) };
+        // SAFETY: this lives as long as the wrapper, which also owns the main thread.
+        let shared_ref = unsafe { shared.as_ptr().as_ref() };
         let main_thread = main_thread_initializer(shared_ref)?;
 
         // SAFETY: we just created the shared and main_thread together
@@ -74,9 +87,43 @@ pub(crate) enum WrapperData<'a, P: Plugin> {
     Uninitialized(Box<dyn PluginInitializer<'a, P>>),
 }
 
+/// The header every `clap_plugin.plugin_data` pointer created by Clack starts with.
+///
+/// The layout of `plugin_data` is private to Clack and may change in any release. This header is
+/// its only stable part: a host process may load plugins built against different versions of
+/// Clack, and the header allows detecting an instance with an incompatible layout instead of
+/// blindly misinterpreting it.
+///
+/// Its layout must never change: `magic` is at offset 0, and `layout_version` at offset 8.
+#[derive(Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub(crate) struct WrapperHeader {
+    magic: u64,
+    layout_version: u32,
+}
+
+impl WrapperHeader {
+    const MAGIC: u64 = u64::from_be_bytes(*b"CLACKPLG");
+    /// Must be bumped whenever the layout of [`PluginBoxInner`] changes.
+    const LAYOUT_VERSION: u32 = 1;
+
+    const CURRENT: Self = Self {
+        magic: Self::MAGIC,
+        layout_version: Self::LAYOUT_VERSION,
+    };
+}
+
+#[repr(C)]
 pub(crate) struct PluginBoxInner<'a, P: Plugin> {
+    /// Must stay the first field, see [`WrapperHeader`].
+    header: WrapperHeader,
     host: HostSharedHandle<'a>,
     state: AtomicU8,
+    /// The number of callbacks currently running on this instance.
+    ///
+    /// This is only used to detect the host destroying the plugin from within one of its own
+    /// callbacks, which happens on the same thread, hence the relaxed ordering.
+    in_flight: AtomicUsize,
     plugin_data: UnsafeCell<WrapperData<'a, P>>,
 }
 
@@ -85,8 +132,52 @@ const INITIALIZED: u8 = 1;
 const INITIALIZING: u8 = 2;
 const INITIALIZATION_FAILED: u8 = 3;
 const DESTROYING: u8 = 4;
+/// `destroy` was called while other callbacks were running: the last one to complete frees the
+/// instance.
+const DESTROY_DEFERRED: u8 = 5;
+
+/// Marks a callback as running on a plugin instance, for as long as it is alive.
+///
+/// See [`PluginBoxInner::enter`].
+pub(crate) struct CallbackGuard<'a, P: Plugin> {
+    plugin: *const clap_plugin,
+    data: NonNull<PluginBoxInner<'a, P>>,
+}
+
+impl<P: Plugin> Drop for CallbackGuard<'_, P> {
+    fn drop(&mut self) {
+        // SAFETY: the instance cannot be freed while a callback is still in flight.
+        let data = unsafe { self.data.as_ref() };
+
+        if data.in_flight.fetch_sub(1, Ordering::Relaxed) != 1 {
+            return;
+        }
+
+        // The last callback has completed: perform the destruction that was deferred, if any.
+        if data.state.load(Ordering::Acquire) == DESTROY_DEFERRED {
+            // SAFETY: no other callback is running, and the host already called destroy(), so
+            // it won't use this instance ever again.
+            unsafe { PluginBoxInner::<P>::destroy_deferred(self.plugin.cast_mut()) }
+        }
+    }
+}
 
 impl<'a, P: Plugin> PluginBoxInner<'a, P> {
+    /// Marks a callback as running on the given plugin instance, until the returned guard is
+    /// dropped.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be the `plugin_data` of the given, valid `plugin` instance.
+    #[inline]
+    pub(crate) unsafe fn enter(
+        plugin: *const clap_plugin,
+        data: NonNull<Self>,
+    ) -> CallbackGuard<'a, P> {
+        data.as_ref().in_flight.fetch_add(1, Ordering::Relaxed);
+        CallbackGuard { plugin, data }
+    }
+
     #[inline]
     pub(crate) fn wrapper_uninit(
         &self,
@@ -96,6 +187,7 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
             UNINITIALIZED => Err(PluginWrapperError::UninitializedPlugin),
             INITIALIZATION_FAILED => Err(PluginWrapperError::InitializationAlreadyFailed),
             DESTROYING => Err(PluginWrapperError::Destroying),
+            DESTROY_DEFERRED => Err(PluginWrapperError::AlreadyDestroyed),
 
             // SAFETY: when in the initialized state, it is guarantee that plugin_data is never written to again.
             INITIALIZED => match unsafe { &*self.plugin_data.get() } {
@@ -114,6 +206,7 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
             UNINITIALIZED => Err(PluginWrapperError::UninitializedPlugin),
             INITIALIZATION_FAILED => Err(PluginWrapperError::InitializationAlreadyFailed),
             DESTROYING => Err(PluginWrapperError::Destroying),
+            DESTROY_DEFERRED => Err(PluginWrapperError::AlreadyDestroyed),
 
             // SAFETY: when in the initialized state, it is guarantee that plugin_data is never written to again.
             INITIALIZED => match unsafe { &*self.plugin_data.get() } {
@@ -132,25 +225,49 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
         desc: &'a clap_plugin_descriptor,
         initializer: Box<dyn PluginInitializer<'a, P>>,
     ) -> clap_plugin {
+        let vtable = P::VTABLE;
+
         clap_plugin {
             desc,
             plugin_data: Box::into_raw(Box::new(Self {
+                header: WrapperHeader::CURRENT,
                 host,
                 plugin_data: UnsafeCell::new(Uninitialized(initializer)),
                 state: AtomicU8::new(UNINITIALIZED),
+                in_flight: AtomicUsize::new(0),
             }))
             .cast(),
             init: Some(Self::init),
             destroy: Some(Self::destroy),
             activate: Some(Self::activate),
-            deactivate: Some(Self::deactivate),
-            reset: Some(Self::reset),
-            start_processing: Some(Self::start_processing),
-            stop_processing: Some(Self::stop_processing),
+            deactivate: vtable.deactivate.then_some(Self::deactivate as _),
+            reset: vtable.reset.then_some(Self::reset as _),
+            start_processing: vtable
+                .start_processing
+                .then_some(Self::start_processing as _),
+            stop_processing: vtable.stop_processing.then_some(Self::stop_processing as _),
             process: Some(Self::process),
             get_extension: Some(Self::get_extension),
-            on_main_thread: Some(Self::on_main_thread),
+            on_main_thread: vtable.on_main_thread.then_some(Self::on_main_thread as _),
+        }
+    }
+
+    /// Returns the instance the given `plugin_data` pointer points to, after checking it was
+    /// created with this exact layout.
+    ///
+    /// # Safety
+    ///
+    /// The given pointer must be null, or point to a `plugin_data` created by any version of Clack.
+    pub(crate) unsafe fn from_plugin_data(
+        plugin_data: *mut c_void,
+    ) -> Result<NonNull<Self>, PluginWrapperError> {
+        let data = NonNull::new(plugin_data).ok_or(PluginWrapperError::AlreadyDestroyed)?;
+
+        if data.cast::<WrapperHeader>().as_ptr().read_unaligned() != WrapperHeader::CURRENT {
+            return Err(PluginWrapperError::IncompatibleWrapperVersion);
         }
+
+        Ok(data.cast())
     }
 
     #[inline]
@@ -160,7 +277,7 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn init(plugin: *const clap_plugin) -> bool {
-        PluginWrapper::<P>::handle_plugin_data(plugin, |data| {
+        PluginWrapper::<P>::handle_plugin_data(plugin, "clap_plugin.init", |data| {
             // We can only keep a shared reference in here, now that init() is called.
             let data = data.as_ref();
 
@@ -176,6 +293,7 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
                 Ok(s) | Err(s) => match s {
                     INITIALIZED => return Err(PluginWrapperError::AlreadyInitialized),
                     DESTROYING => return Err(PluginWrapperError::Destroying),
+                    DESTROY_DEFERRED => return Err(PluginWrapperError::AlreadyDestroyed),
                     INITIALIZATION_FAILED | INITIALIZING => {
                         return Err(PluginWrapperError::InitializationAlreadyFailed)
                     }
@@ -194,12 +312,25 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
                     // We now guaranteed that the current state is INITIALIZING, so there is nothing to drop.
                     data.plugin_data.get().write(Initialized(wrapper));
                     // The write operation completed, we can now inform other threads that initialization is complete.
-                    data.state.store(INITIALIZED, Ordering::Release);
+                    // If the host destroyed the plugin during init(), the deferred destruction takes care of the wrapper.
+                    data.state
+                        .compare_exchange(
+                            INITIALIZING,
+                            INITIALIZED,
+                            Ordering::Release,
+                            Ordering::Relaxed,
+                        )
+                        .map_err(|_| PluginWrapperError::AlreadyDestroyed)?;
                     Ok(())
                 }
                 Err(e) => {
-                    data.state.store(INITIALIZATION_FAILED, Ordering::Release);
-                    Err(e.into())
+                    let _ = data.state.compare_exchange(
+                        INITIALIZING,
+                        INITIALIZATION_FAILED,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    );
+                    Err(PluginWrapperError::InitializationFailed(e))
                 }
             }
         })
@@ -210,32 +341,84 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
     unsafe extern "C" fn destroy(plugin: *const clap_plugin) {
         // Deactivate the plugin, in case the host didn't call deactivate() first.
         // This also handles all kinds of logging in case things are already wrong (double free, etc.)
-        PluginWrapper::<P>::handle_plugin_data(plugin, |data| {
-            use PluginWrapperError::*;
-
-            let wrapper = match data.as_ref().wrapper() {
-                Ok(w) => w,
-                // Silence those errors: it is normal to call destroy in these states, no need to log warnings.
-                Err(
-                    PluginCalledDuringInitialization
-                    | UninitializedPlugin
-                    | InitializationAlreadyFailed,
-                ) => return Ok(()),
-                Err(e) => return Err(e),
-            };
+        let can_free =
+            PluginWrapper::<P>::handle_plugin_data(plugin, "clap_plugin.destroy", |data| {
+                use PluginWrapperError::*;
+                let data = data.as_ref();
+
+                // This call is in flight too: any other one means the host is destroying the plugin
+                // from within one of its callbacks. Freeing it now would pull the rug from under that
+                // callback, so this is left to the last one to complete instead.
+                if data.in_flight.load(Ordering::Relaxed) > 1 {
+                    return match data.state.load(Ordering::Acquire) {
+                        DESTROYING => Err(Destroying),
+                        DESTROY_DEFERRED => Err(AlreadyDestroyed),
+                        _ => {
+                            data.state.store(DESTROY_DEFERRED, Ordering::SeqCst);
+                            Err(DestroyedDuringCallback)
+                        }
+                    };
+                }
+
+                let wrapper = match data.wrapper() {
+                    Ok(w) => w,
+                    // Silence those errors: it is normal to call destroy in these states, no need to log warnings.
+                    Err(
+                        PluginCalledDuringInitialization
+                        | UninitializedPlugin
+                        | InitializationAlreadyFailed,
+                    ) => return Ok(()),
+                    Err(e) => return Err(e),
+                };
+
+                if wrapper.is_active() {
+                    wrapper.deactivate()
+                } else {
+                    Ok(())
+                }
+            });
 
-            if wrapper.is_active() {
-                wrapper.deactivate()
-            } else {
-                Ok(())
+        if can_free.is_none() {
+            return;
+        }
+
+        Self::free(plugin.cast_mut());
+    }
+
+    /// Performs a destruction that was deferred until the last running callback completed.
+    ///
+    /// # Safety
+    ///
+    /// The given plugin must be valid, and no callback may be running on it.
+    #[cold]
+    unsafe fn destroy_deferred(plugin: *mut clap_plugin) {
+        if let Some(data) = plugin
+            .as_ref()
+            .and_then(|p| Self::from_plugin_data(p.plugin_data).ok())
+            .map(|data| data.as_ref())
+        {
+            if let Initialized(wrapper) = &*data.plugin_data.get() {
+                if wrapper.is_active() {
+                    let _ = handle_panic(AssertUnwindSafe(|| wrapper.deactivate()));
+                }
             }
-        });
+        }
 
-        if let Some(plugin) = plugin.cast_mut().as_mut() {
-            // Try our best to guard against double-free
-            let Some(plugin_data) = plugin.plugin_data.cast::<PluginBoxInner<P>>().as_mut() else {
+        Self::free(plugin);
+    }
+
+    /// Frees the given plugin instance, unless it is already being freed.
+    ///
+    /// # Safety
+    ///
+    /// The given plugin pointer must be valid (or null), and must not be used afterwards.
+    unsafe fn free(plugin: *mut clap_plugin) {
+        if let Some(plugin) = plugin.as_mut() {
+            // Try our best to guard against double-free, and never free what we didn't allocate.
+            let Ok(mut plugin_data) = Self::from_plugin_data(plugin.plugin_data) else {
                 return;
             };
+            let plugin_data = plugin_data.as_mut();
 
             // We could use direct &mut access for the swap, but let's use atomic operations just in case...
             if plugin_data.state.swap(DESTROYING, Ordering::SeqCst) != DESTROYING {
@@ -262,26 +445,44 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
         min_sample_count: u32,
         max_sample_count: u32,
     ) -> bool {
-        PluginWrapper::<P>::handle(plugin, |p| {
+        // This is queried before entering the wrapper, as it calls back into the plugin.
+        let declared_ports = Self::declared_port_counts(plugin);
+
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.activate", |p| {
             let config = PluginAudioConfiguration {
                 sample_rate,
                 min_frames_count: min_sample_count,
                 max_frames_count: max_sample_count,
             };
 
-            p.activate(config)
+            p.activate(config, declared_ports)
         })
         .is_some()
     }
 
+    /// Returns the numbers of input and output audio ports the plugin currently declares, if it
+    /// implements the audio ports extension.
+    ///
+    /// # Safety
+    ///
+    /// The given plugin must be valid, and this must only be called on the main thread.
+    unsafe fn declared_port_counts(plugin: *const clap_plugin) -> Option<(u32, u32)> {
+        let audio_ports = Self::get_extension(plugin, CLAP_EXT_AUDIO_PORTS.as_ptr())
+            .cast::<clap_plugin_audio_ports>()
+            .as_ref()?;
+
+        let count = audio_ports.count?;
+        Some((count(plugin, true), count(plugin, false)))
+    }
+
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn deactivate(plugin: *const clap_plugin) {
-        PluginWrapper::<P>::handle(plugin, |p| p.deactivate());
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.deactivate", |p| p.deactivate());
     }
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn reset(plugin: *const clap_plugin) {
-        PluginWrapper::<P>::handle(plugin, |p| {
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.reset", |p| {
             p.audio_processor()?.as_mut().reset();
             Ok(())
         });
@@ -289,7 +490,7 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn start_processing(plugin: *const clap_plugin) -> bool {
-        PluginWrapper::<P>::handle(plugin, |p| {
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.start_processing", |p| {
             Ok(p.audio_processor()?.as_mut().start_processing()?)
         })
         .is_some()
@@ -297,7 +498,7 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn stop_processing(plugin: *const clap_plugin) {
-        PluginWrapper::<P>::handle(plugin, |p| {
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.stop_processing", |p| {
             p.audio_processor()?.as_mut().stop_processing();
             Ok(())
         });
@@ -307,29 +508,122 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
     unsafe extern "C" fn process(
         plugin: *const clap_plugin,
         process: *const clap_process,
+    ) -> clap_process_status {
+        let Some(process) = process.as_ref() else {
+            return CLAP_PROCESS_ERROR;
+        };
+
+        if cfg!(debug_assertions)
+            && P::COMPATIBILITY.level(CompatibilityCheck::UnsortedEvents) != CheckLevel::Allow
+        {
+            return Self::process_checked(plugin, process);
+        }
+
+        Self::process_block(plugin, process, Events::from_raw(process))
+    }
+
+    /// Same as [`process`](Self::process), but checks the output events pushed by the plugin.
+    ///
+    /// This is only done in debug builds. See [`CompatibilityCheck::UnsortedEvents`].
+    ///
+    /// # Safety
+    ///
+    /// Same as [`process`](Self::process).
+    #[cold]
+    unsafe fn process_checked(
+        plugin: *const clap_plugin,
+        process: &clap_process,
+    ) -> clap_process_status {
+        let deny = P::COMPATIBILITY.level(CompatibilityCheck::UnsortedEvents) == CheckLevel::Deny;
+        let checker = OutputEventsChecker::new(process.out_events, process.frames_count, deny);
+        let mut checked_output = checker.as_raw();
+
+        let events = Events {
+            input: InputEvents::from_raw(&*process.in_events),
+            output: OutputEvents::from_raw_mut(&mut checked_output),
+        };
+
+        let status = Self::process_block(plugin, process, events);
+
+        for violation in checker.violations() {
+            plugin_report::<P>(
+                plugin,
+                "clap_plugin.process",
+                CompatibilityCheck::UnsortedEvents,
+                CLAP_LOG_PLUGIN_MISBEHAVING,
+                &violation,
+            );
+        }
+
+        status
+    }
+
+    /// Processes a single block, with the given events.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`process`](Self::process).
+    #[inline]
+    unsafe fn process_block(
+        plugin: *const clap_plugin,
+        process: &clap_process,
+        events: Events,
     ) -> clap_process_status {
         // SAFETY: process ptr is never accessed later, and is guaranteed to be valid and unique by the host
-        PluginWrapper::<P>::handle(plugin, |p| {
-            Ok(p.audio_processor()?.as_mut().process(
-                Process::from_raw(&*process),
-                Audio::from_raw(&*process),
-                Events::from_raw(&*process),
-            )?)
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.process", |p| {
+            let audio_config = p
+                .audio_config()
+                .ok_or(PluginWrapperError::DeactivatedPlugin)?;
+
+            if P::SKIP_EMPTY_BLOCKS && is_empty_block(process) {
+                return Ok(Some(ProcessStatus::Continue));
+            }
+
+            let Some(audio) = checked_audio(plugin, p, process)? else {
+                return Ok(None);
+            };
+
+            Ok(Some(p.audio_processor()?.as_mut().process(
+                Process::from_raw(process, audio_config),
+                audio,
+                events,
+            )?))
         })
-        .map(|s| s as clap_process_status)
+        .flatten()
+        .map(ProcessStatus::as_raw)
         .unwrap_or(CLAP_PROCESS_ERROR)
     }
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn get_extension(
         plugin: *const clap_plugin,
-        identifier: *const std::os::raw::c_char,
+        identifier: *const core::ffi::c_char,
     ) -> *const c_void {
         let identifier = CStr::from_ptr(identifier);
         let mut builder = PluginExtensions::new(identifier);
 
-        PluginWrapper::<P>::handle_plugin_data(plugin, |data| {
-            let p = data.as_ref().wrapper_uninit()?;
+        PluginWrapper::<P>::handle_plugin_data(plugin, "clap_plugin.get_extension", |data| {
+            let p = match data.as_ref().wrapper_uninit() {
+                Err(PluginWrapperError::UninitializedPlugin) => {
+                    let level = plugin_report::<P>(
+                        plugin,
+                        "clap_plugin.get_extension",
+                        CompatibilityCheck::EarlyGetExtension,
+                        CLAP_LOG_HOST_MISBEHAVING,
+                        &format_args!(
+                            "Extension {} was queried before the plugin was initialized",
+                            identifier.to_string_lossy()
+                        ),
+                    );
+
+                    if level == CheckLevel::Deny {
+                        return Ok(());
+                    }
+
+                    None
+                }
+                result => result?,
+            };
             P::declare_extensions(&mut builder, p.map(|p| p.shared()));
             Ok(())
         });
@@ -338,13 +632,110 @@ impl<'a, P: Plugin> PluginBoxInner<'a, P> {
 
     #[allow(clippy::missing_safety_doc)]
     unsafe extern "C" fn on_main_thread(plugin: *const clap_plugin) {
-        PluginWrapper::<P>::handle(plugin, |p| {
+        PluginWrapper::<P>::handle_named(plugin, "clap_plugin.on_main_thread", |p| {
             p.main_thread().as_mut().on_main_thread();
             Ok(())
         });
     }
 }
 
+/// Returns `true` if the given process call has no frames to process, and no input events.
+///
+/// # Safety
+///
+/// The given process struct must be valid.
+#[inline]
+unsafe fn is_empty_block(process: &clap_process) -> bool {
+    process.frames_count == 0
+        && (process.in_events.is_null() || InputEvents::from_raw(&*process.in_events).is_empty())
+}
+
+/// Creates the [`Audio`] of the given process call, with any missing input channel buffer replaced
+/// by a silent one.
+///
+/// The number of frames and ports provided by the host are also checked against the plugin's
+/// activation parameters and declared ports, following the plugin's
+/// [`COMPATIBILITY`](Plugin::COMPATIBILITY) policy. Violations are reported once per activation.
+/// If the block must be rejected, this returns `None`.
+///
+/// # Safety
+///
+/// The given plugin and process struct must be valid, and this must only be called on the audio
+/// thread.
+#[inline]
+unsafe fn checked_audio<'p, P: Plugin>(
+    plugin: *const clap_plugin,
+    wrapper: &'p PluginWrapper<P>,
+    process: &'p clap_process,
+) -> Result<Option<Audio<'p>>, PluginWrapperError> {
+    let inputs =
+        slice_from_external_parts(process.audio_inputs, process.audio_inputs_count as usize);
+    let outputs =
+        slice_from_external_parts_mut(process.audio_outputs, process.audio_outputs_count as usize);
+
+    if let Some(first) = wrapper.check_frames_count(process.frames_count) {
+        let check = CompatibilityCheck::FramesOutOfRange;
+        let config = wrapper
+            .audio_config()
+            .ok_or(PluginWrapperError::DeactivatedPlugin)?;
+
+        let level = if first {
+            plugin_report::<P>(
+                plugin,
+                "clap_plugin.process",
+                check,
+                CLAP_LOG_HOST_MISBEHAVING,
+                &format_args!(
+                    "Host processed {} frames, outside of the {}..={} range given at activation",
+                    process.frames_count, config.min_frames_count, config.max_frames_count
+                ),
+            )
+        } else {
+            P::COMPATIBILITY.level(check)
+        };
+
+        // The plugin may only have allocated enough memory for the maximum: longer blocks are
+        // never forwarded, regardless of the policy.
+        if level == CheckLevel::Deny || process.frames_count > config.max_frames_count {
+            return Ok(None);
+        }
+    }
+
+    let mut undeclared = UndeclaredPorts::default();
+
+    if let PortCountCheck::Mismatched { mismatch, first } =
+        wrapper.check_port_counts(inputs.len(), outputs.len())
+    {
+        let check = CompatibilityCheck::PortCountMismatch;
+        let level = P::COMPATIBILITY.level(check);
+
+        // Worked around mismatches are only reported in debug builds.
+        if first && (level == CheckLevel::Deny || cfg!(debug_assertions)) {
+            plugin_report::<P>(
+                plugin,
+                "clap_plugin.process",
+                check,
+                CLAP_LOG_HOST_MISBEHAVING,
+                &mismatch,
+            );
+        }
+
+        if level == CheckLevel::Deny {
+            return Ok(None);
+        }
+
+        undeclared = mismatch.undeclared_ports();
+    }
+
+    let audio = Audio::from_raw_buffers(
+        wrapper.sanitize_inputs(inputs, process.frames_count),
+        outputs,
+        process.frames_count,
+    );
+
+    Ok(Some(audio.with_undeclared_ports(undeclared)))
+}
+
 /// A wrapper around a [`Plugin`] instance.
 ///
 /// This type is created with its [`new`](PluginInstance::new) method when the host wants to
@@ -1,10 +1,14 @@
 use crate::extensions::wrapper::PluginWrapperError;
-use crate::plugin::{Plugin, PluginBoxInner};
+use crate::plugin::{Plugin, PluginBoxInner, PluginError};
+use alloc::ffi::CString;
+use alloc::string::String;
+use clack_common::utils::compatibility::{CheckLevel, CompatibilityCheck, Diagnostic};
+use clack_common::utils::fallback_log::{fallback_log, LogOrigin, LogRecord};
 use clap_sys::ext::log::{clap_host_log, clap_log_severity, CLAP_EXT_LOG};
 use clap_sys::host::clap_host;
 use clap_sys::plugin::clap_plugin;
-use std::os::raw::c_char;
-use std::{error::Error, ffi::CString, fmt::Display, fmt::Write};
+use core::ffi::c_char;
+use core::{ffi::CStr, fmt::Display, fmt::Write};
 
 pub type ClapLoggingFn =
     unsafe extern "C" fn(host: *const clap_host, severity: clap_log_severity, msg: *const c_char);
@@ -16,40 +20,127 @@ pub type ClapLoggingFn =
 unsafe fn get_logger<P: Plugin>(
     plugin: *const clap_plugin,
 ) -> Option<(*const clap_host, ClapLoggingFn)> {
-    let host = plugin
-        .as_ref()?
-        .plugin_data
-        .cast::<PluginBoxInner<P>>()
-        .as_ref()?
-        .host()
-        .as_raw();
+    let data = PluginBoxInner::<P>::from_plugin_data(plugin.as_ref()?.plugin_data).ok()?;
+    let host = data.as_ref().host().as_raw();
 
     let log = host.get_extension?(host, CLAP_EXT_LOG.as_ptr()) as *mut clap_host_log;
     Some((host, log.as_ref()?.log?))
 }
 
-fn log_display<D: Display>(message: &D) -> Result<CString, Box<dyn Error>> {
+fn log_display<D: Display>(message: &D) -> Result<CString, PluginError> {
     let mut buf = String::new();
     write!(buf, "{message}")?;
     Ok(CString::new(buf)?)
 }
 
+/// # Safety
+///
+/// Plugin pointer must be non-dangling (but can be NULL).
+unsafe fn get_plugin_id<'a>(plugin: *const clap_plugin) -> Option<&'a CStr> {
+    let id = plugin.as_ref()?.desc.as_ref()?.id;
+
+    if id.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(id))
+    }
+}
+
 /// # Safety
 ///
 /// Plugin pointer must be non-dangling (but can be NULL).
 /// It *must* point to a plugin instance created by Clack.
-pub unsafe fn plugin_log<P: Plugin>(plugin: *const clap_plugin, e: &PluginWrapperError) {
+pub unsafe fn plugin_log<P: Plugin>(
+    plugin: *const clap_plugin,
+    callback: &'static str,
+    e: &PluginWrapperError,
+) {
+    let plugin_id = get_plugin_id(plugin);
+
+    // The plugin ID and callback name are prepended to the message, the same way the fallback
+    // logger does, so that these errors can be found in the host's log files.
+    let record = LogRecord {
+        origin: LogOrigin::Plugin,
+        severity: e.severity(),
+        plugin_id,
+        callback,
+        message: e,
+    };
+
     if let Some((host, logger)) = get_logger::<P>(plugin) {
-        match log_display(e) {
+        match log_display(&record) {
             Ok(cstr) => {
                 logger(host, e.severity(), cstr.as_ptr());
                 return;
             }
-            Err(e) => {
-                eprintln!("[CLAP_PLUGIN_ERROR] Failed to serialize error message for host: {e}")
-            }
+            Err(serialization_error) => fallback_log(&LogRecord {
+                origin: LogOrigin::Plugin,
+                severity: e.severity(),
+                plugin_id,
+                callback,
+                message: &format_args!(
+                    "Failed to serialize error message for host: {serialization_error}"
+                ),
+            }),
         };
     }
 
-    eprintln!("[CLAP_PLUGIN_ERROR] {e}");
+    fallback_log(&record);
+}
+
+/// Reports a violation detected by the given check, following the plugin's
+/// [`COMPATIBILITY`](Plugin::COMPATIBILITY) policy.
+///
+/// Unless the policy has a diagnostics handler, the violation is logged through the host's `log`
+/// extension, or the fallback logger if it is unavailable. Nothing is reported if the check is
+/// allowed.
+///
+/// This returns the level of the check, so that the caller can act on it.
+///
+/// # Safety
+///
+/// Plugin pointer must be non-dangling (but can be NULL).
+/// It *must* point to a plugin instance created by Clack.
+pub unsafe fn plugin_report<P: Plugin>(
+    plugin: *const clap_plugin,
+    callback: &'static str,
+    check: CompatibilityCheck,
+    severity: clap_log_severity,
+    message: &dyn Display,
+) -> CheckLevel {
+    let level = P::COMPATIBILITY.level(check);
+    if level == CheckLevel::Allow {
+        return level;
+    }
+
+    let diagnostic = Diagnostic {
+        check,
+        level,
+        severity,
+        instance: plugin,
+        plugin_id: get_plugin_id(plugin),
+        callback,
+        message,
+    };
+
+    P::COMPATIBILITY.report(&diagnostic, |diagnostic| {
+        let record = LogRecord {
+            origin: LogOrigin::Plugin,
+            severity,
+            plugin_id: diagnostic.plugin_id,
+            callback,
+            message: diagnostic,
+        };
+
+        if let Some((host, logger)) = get_logger::<P>(plugin) {
+            if let Ok(cstr) = log_display(&record) {
+                logger(host, severity, cstr.as_ptr());
+                return;
+            }
+        }
+
+        fallback_log(&record);
+    });
+
+    level
 }
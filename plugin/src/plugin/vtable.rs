@@ -0,0 +1,94 @@
+/// Which of the optional functions of the raw `clap_plugin` function table are exposed to the
+/// host.
+///
+/// By default, Clack populates every function pointer of a plugin's `clap_plugin` table, and
+/// forwards them to the matching trait methods, even if they are left to their default, empty
+/// implementations. Some hosts however behave differently when a function pointer is null than
+/// when it is present but does nothing. Setting a field of this configuration to `false` makes
+/// the matching function pointer null instead, and the matching trait method is then never called.
+///
+/// This is set for a plugin type using the [`Plugin::VTABLE`](crate::plugin::Plugin::VTABLE)
+/// constant.
+///
+/// The `init`, `destroy`, `activate`, `process` and `get_extension` functions are required for
+/// Clack to work, and are therefore always exposed: this type has no field to disable them.
+///
+/// ```compile_fail
+/// use clack_plugin::prelude::PluginVTableConfig;
+///
+/// const VTABLE: PluginVTableConfig = PluginVTableConfig {
+///     process: false,
+///     ..PluginVTableConfig::ALL
+/// };
+/// ```
+///
+/// Note that this only covers the `clap_plugin` table itself. Extensions are only exposed if they
+/// are registered in [`Plugin::declare_extensions`](crate::plugin::Plugin::declare_extensions).
+///
+/// # Example
+///
+/// ```
+/// use clack_plugin::prelude::*;
+///
+/// # pub struct MyPluginAudioProcessor;
+/// # impl<'a> PluginAudioProcessor<'a, (), ()> for MyPluginAudioProcessor {
+/// #     fn activate(_: HostAudioProcessorHandle<'a>, _: &mut (), _: &'a (), _: PluginAudioConfiguration) -> Result<Self, PluginError> { Ok(Self) }
+/// #     fn process(&mut self, _: Process, _: Audio, _: Events) -> Result<ProcessStatus, PluginError> { Ok(ProcessStatus::Continue) }
+/// # }
+/// pub struct MyPlugin;
+///
+/// impl Plugin for MyPlugin {
+///     type AudioProcessor<'a> = MyPluginAudioProcessor;
+///     type Shared<'a> = ();
+///     type MainThread<'a> = ();
+///
+///     // This plugin has no state to reset, and no use for main thread callbacks.
+///     const VTABLE: PluginVTableConfig = PluginVTableConfig {
+///         reset: false,
+///         on_main_thread: false,
+///         ..PluginVTableConfig::ALL
+///     };
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PluginVTableConfig {
+    /// Whether the `deactivate` function is exposed.
+    ///
+    /// If it isn't, the audio processor is only deactivated when the plugin is destroyed.
+    pub deactivate: bool,
+    /// Whether the `start_processing` function is exposed.
+    pub start_processing: bool,
+    /// Whether the `stop_processing` function is exposed.
+    pub stop_processing: bool,
+    /// Whether the `reset` function is exposed.
+    pub reset: bool,
+    /// Whether the `on_main_thread` function is exposed.
+    pub on_main_thread: bool,
+}
+
+impl PluginVTableConfig {
+    /// Exposes all the functions of the `clap_plugin` table.
+    pub const ALL: Self = Self {
+        deactivate: true,
+        start_processing: true,
+        stop_processing: true,
+        reset: true,
+        on_main_thread: true,
+    };
+
+    /// Only exposes the functions of the `clap_plugin` table that are required by Clack.
+    pub const REQUIRED_ONLY: Self = Self {
+        deactivate: false,
+        start_processing: false,
+        stop_processing: false,
+        reset: false,
+        on_main_thread: false,
+    };
+}
+
+impl Default for PluginVTableConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::ALL
+    }
+}
@@ -7,13 +7,51 @@ use clack_common::events::event_types::TransportEvent;
 use clack_common::events::io::{InputEvents, OutputEvents};
 use clap_sys::audio_buffer::clap_audio_buffer;
 use clap_sys::process::clap_process;
-use std::ops::RangeBounds;
+use core::ops::{Bound, RangeBounds};
 
 pub use clack_common::process::*;
 pub mod audio;
+pub(crate) mod output_check;
+pub mod overflow;
 use crate::internal_utils::{slice_from_external_parts, slice_from_external_parts_mut};
 use audio::*;
 
+/// Checks that the given raw buffers actually contain channel buffers for every channel they
+/// declare, so that channel count mismatches are caught early in debug builds.
+///
+/// # Panics
+///
+/// This panics if any port declares channels but has no channel buffers, or has a null channel
+/// buffer.
+///
+/// # Safety
+///
+/// The buffer structs, and the channel buffer arrays they point to, must be valid.
+unsafe fn debug_check_buffers(buffers: &[clap_audio_buffer], is_input: bool) {
+    let direction = if is_input { "input" } else { "output" };
+
+    for (index, buffer) in buffers.iter().enumerate() {
+        let channel_count = buffer.channel_count as usize;
+        if channel_count == 0 {
+            continue;
+        }
+
+        let has_f32 = !buffer.data32.is_null()
+            && slice_from_external_parts(buffer.data32, channel_count)
+                .iter()
+                .all(|c| !c.is_null());
+        let has_f64 = !buffer.data64.is_null()
+            && slice_from_external_parts(buffer.data64, channel_count)
+                .iter()
+                .all(|c| !c.is_null());
+
+        assert!(
+            has_f32 || has_f64,
+            "Audio {direction} port #{index} declares {channel_count} channels, but doesn't provide a buffer for all of them"
+        );
+    }
+}
+
 /// Metadata about the current process call.
 ///
 /// This exposes [transport information](Process::transport) (in the form of a [`TransportEvent`]), as well as a
@@ -37,6 +75,12 @@ pub struct Process<'a> {
     /// Note that this counter's maximum value is actually [`i64::MAX`], due to how it is
     /// implemented in the CLAP specification.
     pub steady_time: Option<u64>,
+    /// The audio configuration the plugin was activated with.
+    ///
+    /// This is the same configuration that was given to
+    /// [`PluginAudioProcessor::activate`](crate::plugin::PluginAudioProcessor::activate), so that
+    /// audio processors don't need to store it themselves.
+    pub audio_config: PluginAudioConfiguration,
 }
 
 impl<'a> Process<'a> {
@@ -44,11 +88,15 @@ impl<'a> Process<'a> {
     ///
     /// The user must ensure the given process struct is fully valid, and for the lifetime `'a`.
     #[inline]
-    pub(crate) unsafe fn from_raw(raw: *const clap_process) -> Process<'a> {
+    pub(crate) unsafe fn from_raw(
+        raw: *const clap_process,
+        audio_config: PluginAudioConfiguration,
+    ) -> Process<'a> {
         let transport = (*raw).transport;
         let steady_time = (*raw).steady_time;
 
         Self {
+            audio_config,
             steady_time: if steady_time < 0 {
                 None
             } else {
@@ -64,6 +112,10 @@ impl<'a> Process<'a> {
 }
 
 /// Input and output events that occurred during this processing block.
+///
+/// Both sides can be used independently: the input events can be iterated as many times as
+/// needed (e.g. once for notes and once for parameters) while events are pushed to the output,
+/// and the whole struct can be [`split`](Self::split) to move each side into separate helpers.
 pub struct Events<'a> {
     /// The input event buffer, for the plugin to read events from.
     pub input: &'a InputEvents<'a>,
@@ -71,6 +123,44 @@ pub struct Events<'a> {
     pub output: &'a mut OutputEvents<'a>,
 }
 
+impl<'a> Events<'a> {
+    /// Creates a new set of events from the given input and output event buffers.
+    ///
+    /// This is mostly useful to test processing helpers outside of an actual `process` call.
+    #[inline]
+    pub fn new(input: &'a InputEvents<'a>, output: &'a mut OutputEvents<'a>) -> Self {
+        Self { input, output }
+    }
+
+    /// Splits these events into their input and output sides.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_plugin::events::io::{InputEvents, OutputEvents};
+    /// use clack_plugin::process::Events;
+    ///
+    /// struct VoiceManager;
+    ///
+    /// impl VoiceManager {
+    ///     fn handle(&mut self, input: &InputEvents, output: &mut OutputEvents) {
+    ///         for event in input {
+    ///             let _ = output.try_push(event);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn process(voices: &mut VoiceManager, events: Events) {
+    ///     let (input, output) = events.split();
+    ///     voices.handle(input, output);
+    /// }
+    /// ```
+    #[inline]
+    pub fn split(self) -> (&'a InputEvents<'a>, &'a mut OutputEvents<'a>) {
+        (self.input, self.output)
+    }
+}
+
 impl Events<'_> {
     /// # Safety
     ///
@@ -200,6 +290,7 @@ pub struct Audio<'a> {
     inputs: &'a [clap_audio_buffer],
     outputs: &'a mut [clap_audio_buffer],
     frames_count: u32,
+    undeclared: UndeclaredPorts,
 }
 
 impl<'a> Audio<'a> {
@@ -212,17 +303,16 @@ impl<'a> Audio<'a> {
     /// it points to stay valid for the given lifetime.
     #[inline]
     pub unsafe fn from_raw(raw_process: &clap_process) -> Audio {
-        Audio {
-            frames_count: raw_process.frames_count,
-            inputs: slice_from_external_parts(
-                raw_process.audio_inputs,
-                raw_process.audio_inputs_count as usize,
-            ),
-            outputs: slice_from_external_parts_mut(
-                raw_process.audio_outputs,
-                raw_process.audio_outputs_count as usize,
-            ),
-        }
+        let inputs = slice_from_external_parts(
+            raw_process.audio_inputs,
+            raw_process.audio_inputs_count as usize,
+        );
+        let outputs = slice_from_external_parts_mut(
+            raw_process.audio_outputs,
+            raw_process.audio_outputs_count as usize,
+        );
+
+        Audio::from_raw_buffers(inputs, outputs, raw_process.frames_count)
     }
 
     /// Create a new [`Audio`] from raw buffer structs.
@@ -240,13 +330,41 @@ impl<'a> Audio<'a> {
         outputs: &'a mut [clap_audio_buffer],
         frames_count: u32,
     ) -> Self {
+        if cfg!(debug_assertions) {
+            debug_check_buffers(inputs, true);
+            debug_check_buffers(outputs, false);
+        }
+
         Self {
             inputs,
             outputs,
             frames_count,
+            undeclared: UndeclaredPorts::default(),
         }
     }
 
+    /// Flags the given ports as undeclared by the plugin.
+    #[inline]
+    pub(crate) fn with_undeclared_ports(mut self, undeclared: UndeclaredPorts) -> Self {
+        self.undeclared = undeclared;
+        self
+    }
+
+    /// Returns the ports the host provided in this block that were not declared by the plugin.
+    ///
+    /// Hosts are supposed to provide exactly the audio ports the plugin declared through the audio
+    /// ports extension. However, some hosts may provide more: in that case, unless the plugin's
+    /// [`COMPATIBILITY`](crate::plugin::Plugin::COMPATIBILITY) policy denies
+    /// [`PortCountMismatch`](crate::utils::compatibility::CompatibilityCheck::PortCountMismatch),
+    /// the extra ports are still exposed by this struct, and their indexes are returned by this
+    /// method.
+    ///
+    /// This is always empty if the plugin doesn't implement the audio ports extension.
+    #[inline]
+    pub fn undeclared_ports(&self) -> &UndeclaredPorts {
+        &self.undeclared
+    }
+
     /// Returns the raw input and output buffers structs, respectively.
     #[inline]
     pub fn raw_buffers(&mut self) -> (&'a [clap_audio_buffer], &mut [clap_audio_buffer]) {
@@ -434,7 +552,16 @@ impl<'a> Audio<'a> {
             .get_mut((range.start_bound().cloned(), range.end_bound().cloned()))
             .unwrap_or(&mut []);
 
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
         Audio {
+            undeclared: self
+                .undeclared
+                .sub_range(start, inputs.len(), outputs.len()),
             inputs,
             outputs,
             frames_count: self.frames_count,
@@ -448,6 +575,133 @@ impl<'a> Audio<'a> {
     pub fn frames_count(&self) -> u32 {
         self.frames_count
     }
+
+    /// Returns the value of the given input sample.
+    ///
+    /// This returns [`None`] if the port or channel doesn't exist, or if the port doesn't hold
+    /// samples of type `S`.
+    ///
+    /// This is mostly useful for processing that doesn't read samples linearly. Iterating over
+    /// the port's [channels](InputPort::channels) is more efficient otherwise.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if `frame_index` is out of bounds. In release builds, this
+    /// returns [`None`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_plugin::prelude::*;
+    ///
+    /// # fn process(audio: Audio) {
+    /// let audio: Audio = /* ... */
+    /// # audio;
+    /// let last_frame = audio.frames_count() - 1;
+    ///
+    /// if let Some(sample) = audio.input_sample::<f32>(0, 1, last_frame) {
+    ///     // Read the last sample of the second channel of the first input port.
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn input_sample<S: Sample>(
+        &self,
+        port_index: usize,
+        channel_index: u32,
+        frame_index: u32,
+    ) -> Option<S> {
+        // SAFETY: this type ensures the buffers are valid and of length frames_count, and that no
+        // mutable reference to output buffers is alive while &self is borrowed.
+        unsafe {
+            chunks::sample_ptr::<S>(
+                self.inputs,
+                port_index,
+                channel_index,
+                0,
+                self.frames_count,
+                frame_index,
+            )
+            .map(|sample| sample.read())
+        }
+    }
+
+    /// Returns a mutable reference to the given output sample.
+    ///
+    /// This returns [`None`] if the port or channel doesn't exist, or if the port doesn't hold
+    /// samples of type `S`.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if `frame_index` is out of bounds. In release builds, this
+    /// returns [`None`] instead.
+    #[inline]
+    pub fn output_sample_mut<S: Sample>(
+        &mut self,
+        port_index: usize,
+        channel_index: u32,
+        frame_index: u32,
+    ) -> Option<&mut S> {
+        // SAFETY: this type ensures the buffers are valid and of length frames_count, and &mut
+        // ensures there is no input being read concurrently.
+        unsafe {
+            chunks::sample_ptr::<S>(
+                self.outputs,
+                port_index,
+                channel_index,
+                0,
+                self.frames_count,
+                frame_index,
+            )
+            .map(|sample| &mut *sample)
+        }
+    }
+
+    /// Returns an iterator over chunks of `chunk_size` frames of this block, e.g. to process
+    /// audio in fixed-size hops.
+    ///
+    /// If the block's frame count isn't a multiple of `chunk_size`, the last frames are left out,
+    /// and can be retrieved using [`AudioChunks::into_remainder`].
+    ///
+    /// Input events can be batched alongside each chunk using [`AudioChunk::batch_events`].
+    ///
+    /// # Panics
+    ///
+    /// This panics if `chunk_size` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clack_plugin::prelude::*;
+    ///
+    /// # fn process(mut audio: Audio, events: Events) {
+    /// let mut audio: Audio = /* ... */
+    /// # audio;
+    /// let events: Events = /* ... */
+    /// # events;
+    ///
+    /// let mut chunks = audio.chunks_exact(64);
+    /// for mut chunk in &mut chunks {
+    ///     for batch in chunk.batch_events(events.input) {
+    ///         // Event and sample indexes are relative to the start of the chunk.
+    ///     }
+    ///
+    ///     if let Some(sample) = chunk.output_sample_mut::<f32>(0, 0, 63) {
+    ///         *sample = 0.0;
+    ///     }
+    /// }
+    ///
+    /// if let Some(remainder) = chunks.into_remainder() {
+    ///     // Buffer the remaining frames until the next block.
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn chunks_exact(&mut self, chunk_size: u32) -> AudioChunks<'_> {
+        // SAFETY: this type ensures the buffers are valid and of length frames_count, and &mut
+        // ensures no other reference to the buffers is alive while the chunks are.
+        unsafe { AudioChunks::from_raw(self.inputs, self.outputs, self.frames_count, chunk_size) }
+    }
 }
 
 impl<'buf: 'a, 'a> IntoIterator for &'a mut Audio<'buf> {
@@ -461,3 +715,111 @@ impl<'buf: 'a, 'a> IntoIterator for &'a mut Audio<'buf> {
         self.port_pairs()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::events::event_types::{NoteOnEvent, ParamValueEvent};
+    use clack_common::events::io::EventBuffer;
+    use clack_common::events::spaces::CoreEventSpace;
+    use clack_common::events::Pckn;
+    use clack_common::utils::{ClapId, Cookie};
+
+    fn input_events() -> EventBuffer {
+        let mut buffer = EventBuffer::new();
+        buffer.push(&NoteOnEvent::new(
+            0,
+            Pckn::new(0u16, 0u16, 60u16, 1u32),
+            1.0,
+        ));
+        buffer.push(&ParamValueEvent::new(
+            1,
+            ClapId::new(0),
+            Pckn::match_all(),
+            0.5,
+            Cookie::empty(),
+        ));
+        buffer.push(&NoteOnEvent::new(
+            2,
+            Pckn::new(0u16, 0u16, 64u16, 2u32),
+            1.0,
+        ));
+        buffer
+    }
+
+    /// A helper that only ever reads input events.
+    struct VoiceManager {
+        active_voices: usize,
+    }
+
+    impl VoiceManager {
+        fn handle_notes(&mut self, input: &InputEvents) {
+            for event in input {
+                if let Some(CoreEventSpace::NoteOn(_)) = event.as_core_event() {
+                    self.active_voices += 1;
+                }
+            }
+        }
+    }
+
+    /// A helper that keeps hold of the output events.
+    struct Echo<'a, 'o> {
+        output: &'a mut OutputEvents<'o>,
+    }
+
+    impl Echo<'_, '_> {
+        fn echo(&mut self, input: &InputEvents) {
+            for event in input {
+                self.output.try_push(event).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn input_can_be_iterated_multiple_times_while_pushing_outputs() {
+        let input_buffer = input_events();
+        let input = input_buffer.as_input();
+        let mut output_buffer = EventBuffer::new();
+        let mut output = output_buffer.as_output();
+
+        let events = Events::new(&input, &mut output);
+
+        // First pass: notes, echoed to the output while iterating.
+        for event in events.input {
+            if let Some(CoreEventSpace::NoteOn(_)) = event.as_core_event() {
+                events.output.try_push(event).unwrap();
+            }
+        }
+
+        // Second pass: params.
+        let params = events
+            .input
+            .iter()
+            .filter(|e| matches!(e.as_core_event(), Some(CoreEventSpace::ParamValue(_))))
+            .count();
+
+        assert_eq!(params, 1);
+        assert_eq!(output_buffer.len(), 2);
+    }
+
+    #[test]
+    fn split_events_can_be_moved_into_separate_helpers() {
+        let input_buffer = input_events();
+        let input = input_buffer.as_input();
+        let mut output_buffer = EventBuffer::new();
+        let mut output = output_buffer.as_output();
+
+        let (input, output) = Events::new(&input, &mut output).split();
+
+        let mut voices = VoiceManager { active_voices: 0 };
+        let mut echo = Echo { output };
+
+        // The input can be lent to the voice manager while the output is mutably held elsewhere.
+        voices.handle_notes(input);
+        echo.echo(input);
+        voices.handle_notes(input);
+
+        assert_eq!(voices.active_voices, 4);
+        assert_eq!(output_buffer.len(), 3);
+    }
+}
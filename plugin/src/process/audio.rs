@@ -1,16 +1,24 @@
 //! Various types related to accessing [`Audio`](super::Audio) buffers.
 
+pub(crate) mod chunks;
 mod error;
 mod input;
 mod output;
 mod pair;
+mod port_count;
 mod sample_type;
+mod sanitize;
 
+pub use chunks::{AudioChunk, AudioChunks};
 pub use error::BufferError;
 pub use input::*;
 pub use output::*;
 pub use pair::*;
-pub use sample_type::SampleType;
+pub use port_count::{PortCountMismatch, UndeclaredPorts};
+pub use sample_type::{Sample, SampleType};
+
+pub(crate) use port_count::{PortCountCheck, PortCountChecker};
+pub(crate) use sanitize::InputSanitizer;
 
 #[cfg(test)]
 #[allow(missing_docs)]
@@ -46,6 +54,7 @@ pub mod tests {
             inputs: input_buffers.as_raw_buffers(),
             frames_count,
             outputs: output_buffers.into_raw_buffers(),
+            undeclared: UndeclaredPorts::default(),
         }
     }
 
@@ -207,4 +216,179 @@ pub mod tests {
 
         assert_eq!(ins, outs);
     }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Audio input port #0 declares 2 channels")]
+    fn missing_channel_buffers_are_caught() {
+        let buffer = clap_sys::audio_buffer::clap_audio_buffer {
+            data32: core::ptr::null_mut(),
+            data64: core::ptr::null_mut(),
+            channel_count: 2,
+            latency: 0,
+            constant_mask: 0,
+        };
+
+        // SAFETY: the buffer struct is valid, and its null pointers are never dereferenced.
+        let _ = unsafe { Audio::from_raw_buffers(core::slice::from_ref(&buffer), &mut [], 4) };
+    }
+
+    #[test]
+    fn undeclared_ports_follow_sub_ranges() {
+        let mismatch = PortCountMismatch {
+            declared_inputs: 1,
+            declared_outputs: 2,
+            inputs: 3,
+            outputs: 1,
+        };
+
+        let undeclared = mismatch.undeclared_ports();
+        assert_eq!(undeclared.inputs(), 1..3);
+        assert!(undeclared.outputs().is_empty());
+
+        let sub_range = undeclared.sub_range(2, 1, 0);
+        assert_eq!(sub_range.inputs(), 0..1);
+        assert!(sub_range.outputs().is_empty());
+
+        assert!(undeclared.sub_range(0, 1, 1).is_empty());
+    }
+
+    #[test]
+    fn can_access_samples_with_indexes() {
+        let mut ins = [[1f32, 2., 3., 4.], [5., 6., 7., 8.]];
+        let mut outs = [[0f32; 4]; 2];
+
+        let mut input_ports = AudioPorts::with_capacity(2, 1);
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+        let mut audio = get_audio(&mut ins, &mut outs, &mut input_ports, &mut output_ports);
+        assert_eq!(audio.frames_count(), 4);
+
+        assert_eq!(audio.input_sample::<f32>(0, 1, 2), Some(7.0));
+        assert_eq!(audio.input_sample::<f32>(0, 2, 2), None);
+        assert_eq!(audio.input_sample::<f32>(1, 0, 2), None);
+        assert_eq!(audio.input_sample::<f64>(0, 0, 2), None);
+
+        *audio.output_sample_mut::<f32>(0, 1, 3).unwrap() = 42.0;
+        assert!(audio.output_sample_mut::<f64>(0, 1, 3).is_none());
+
+        assert_eq!(outs, [[0.0; 4], [0.0, 0.0, 0.0, 42.0]]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Frame index 4 is out of bounds for 4 frames")]
+    fn out_of_bounds_samples_panic_in_debug() {
+        let mut ins = [[1f32; 4]; 2];
+        let mut outs = [[0f32; 4]; 2];
+
+        let mut input_ports = AudioPorts::with_capacity(2, 1);
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+        let audio = get_audio(&mut ins, &mut outs, &mut input_ports, &mut output_ports);
+        audio.input_sample::<f32>(0, 0, 4);
+    }
+
+    #[test]
+    fn can_iterate_on_exact_chunks() {
+        let mut ins = [[0f32, 1., 2., 3., 4., 5., 6., 7., 8., 9.]; 2];
+        let mut outs = [[0f32; 10]; 2];
+
+        let mut input_ports = AudioPorts::with_capacity(2, 1);
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+        let mut audio = get_audio(&mut ins, &mut outs, &mut input_ports, &mut output_ports);
+
+        let mut chunks = audio.chunks_exact(4);
+        assert_eq!(chunks.len(), 2);
+
+        let mut chunk_views: Vec<_> = (&mut chunks).collect();
+        let remainder = chunks.into_remainder().unwrap();
+        assert_eq!((remainder.start_frame(), remainder.frames_count()), (8, 2));
+
+        chunk_views.push(remainder);
+
+        // Chunks don't overlap, so they can be processed in any order.
+        for chunk in chunk_views.iter_mut().rev() {
+            let input: Vec<f32> = chunk.input_channel::<f32>(0, 1).unwrap().to_vec();
+            assert_eq!(input[0], chunk.start_frame() as f32);
+
+            let output = chunk.output_channel_mut::<f32>(0, 1).unwrap();
+            for (output, input) in output.iter_mut().zip(input) {
+                *output = input * 2.0;
+            }
+
+            let last = chunk.frames_count() - 1;
+            *chunk.output_sample_mut::<f32>(0, 0, last).unwrap() = 1.0;
+        }
+
+        assert_eq!(
+            outs,
+            [
+                [0., 0., 0., 1., 0., 0., 0., 1., 0., 1.],
+                [0., 2., 4., 6., 8., 10., 12., 14., 16., 18.]
+            ]
+        );
+    }
+
+    #[test]
+    fn exact_chunks_have_no_remainder_on_multiples() {
+        let mut ins = [[0f32; 8]; 2];
+        let mut outs = [[0f32; 8]; 2];
+
+        let mut input_ports = AudioPorts::with_capacity(2, 1);
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+        let mut audio = get_audio(&mut ins, &mut outs, &mut input_ports, &mut output_ports);
+
+        let chunks = audio.chunks_exact(4);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.into_remainder().is_none());
+    }
+
+    #[test]
+    fn chunks_batch_their_events() {
+        use clack_common::events::event_types::ParamGestureBeginEvent;
+        use clack_common::events::io::InputEvents;
+        use clack_common::utils::ClapId;
+
+        let buf = [1, 4, 6, 9].map(|time| ParamGestureBeginEvent::new(time, ClapId::new(0)));
+        let events = InputEvents::from_buffer(&buf);
+
+        let mut ins = [[0f32; 10]; 2];
+        let mut outs = [[0f32; 10]; 2];
+
+        let mut input_ports = AudioPorts::with_capacity(2, 1);
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+
+        let mut audio = get_audio(&mut ins, &mut outs, &mut input_ports, &mut output_ports);
+        let mut chunks = audio.chunks_exact(4);
+
+        let mut batches = Vec::new();
+        for chunk in &mut chunks {
+            batches.push(
+                chunk
+                    .batch_events(&events)
+                    .map(|b| (b.first_sample(), b.events().count()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let remainder = chunks.into_remainder().unwrap();
+        batches.push(
+            remainder
+                .batch_events(&events)
+                .map(|b| (b.first_sample(), b.events().count()))
+                .collect(),
+        );
+
+        assert_eq!(
+            batches,
+            [
+                vec![(0, 0), (1, 1)],
+                vec![(0, 1), (2, 1)],
+                vec![(0, 0), (1, 1)]
+            ]
+        );
+    }
 }
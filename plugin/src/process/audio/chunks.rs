@@ -0,0 +1,323 @@
+use crate::internal_utils::{slice_from_external_parts, slice_from_external_parts_mut};
+use crate::process::audio::Sample;
+use clack_common::events::io::{EventBatcher, InputEvents};
+use clap_sys::audio_buffer::clap_audio_buffer;
+
+/// Returns a pointer to the first sample of the given channel of the given port, if the port
+/// exists, has that channel, and holds samples of type `S`.
+///
+/// # Safety
+///
+/// All the given buffer structs must be valid, as well as the channel arrays they point to.
+#[inline]
+unsafe fn channel_ptr<S: Sample>(
+    buffers: &[clap_audio_buffer],
+    port_index: usize,
+    channel_index: u32,
+) -> Option<*mut S> {
+    let buffer = buffers.get(port_index)?;
+    let channels = S::raw_channels(buffer);
+
+    if channels.is_null() || channel_index >= buffer.channel_count {
+        return None;
+    }
+
+    let channel = *channels.add(channel_index as usize);
+    (!channel.is_null()).then_some(channel)
+}
+
+/// Returns a pointer to the given sample, `frame_index` being relative to `start`.
+///
+/// In debug builds, this panics if the frame index is out of bounds.
+///
+/// # Safety
+///
+/// All the given buffer structs must be valid, as well as the channel arrays they point to. The
+/// channel buffers must be at least `start + frames_count` samples long.
+#[inline]
+pub(crate) unsafe fn sample_ptr<S: Sample>(
+    buffers: &[clap_audio_buffer],
+    port_index: usize,
+    channel_index: u32,
+    start: u32,
+    frames_count: u32,
+    frame_index: u32,
+) -> Option<*mut S> {
+    debug_assert!(
+        frame_index < frames_count,
+        "Frame index {frame_index} is out of bounds for {frames_count} frames"
+    );
+
+    if frame_index >= frames_count {
+        return None;
+    }
+
+    let channel = channel_ptr::<S>(buffers, port_index, channel_index)?;
+    Some(channel.add((start + frame_index) as usize))
+}
+
+/// A view over a contiguous range of frames of an [`Audio`](crate::process::Audio) block.
+///
+/// This is produced by the [`AudioChunks`] iterator returned by
+/// [`Audio::chunks_exact`](crate::process::Audio::chunks_exact).
+///
+/// Samples can be accessed either one at a time, using frame indexes relative to the start of this
+/// chunk, or as whole channel slices. Input events can be batched alongside this chunk with
+/// [`batch_events`](AudioChunk::batch_events).
+///
+/// Like for [`Audio`](crate::process::Audio), input and output buffers may be the same if the
+/// host processes in-place: output samples can only be borrowed mutably while no input sample is
+/// borrowed.
+pub struct AudioChunk<'a> {
+    inputs: &'a [clap_audio_buffer],
+    outputs: &'a [clap_audio_buffer],
+    start: u32,
+    frames_count: u32,
+}
+
+impl<'a> AudioChunk<'a> {
+    /// # Safety
+    ///
+    /// All the given buffer structs must be valid for `'a`, as well as all the buffers they point
+    /// to, which must be at least `start + frames_count` samples long. No other references to the
+    /// `start..start + frames_count` range of these buffers may exist for `'a`.
+    #[inline]
+    unsafe fn from_raw(
+        inputs: &'a [clap_audio_buffer],
+        outputs: &'a [clap_audio_buffer],
+        start: u32,
+        frames_count: u32,
+    ) -> Self {
+        Self {
+            inputs,
+            outputs,
+            start,
+            frames_count,
+        }
+    }
+
+    /// Returns the index of the first frame of this chunk, within the whole block.
+    #[inline]
+    pub fn start_frame(&self) -> u32 {
+        self.start
+    }
+
+    /// Returns the number of frames in this chunk.
+    #[inline]
+    pub fn frames_count(&self) -> u32 {
+        self.frames_count
+    }
+
+    /// Returns the number of available input ports.
+    #[inline]
+    pub fn input_port_count(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Returns the number of available output ports.
+    #[inline]
+    pub fn output_port_count(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Returns the value of the given input sample, `frame_index` being relative to the start of
+    /// this chunk.
+    ///
+    /// This returns [`None`] if the port or channel doesn't exist, or if the port doesn't hold
+    /// samples of type `S`.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if `frame_index` is out of bounds. In release builds, this
+    /// returns [`None`] instead.
+    #[inline]
+    pub fn input_sample<S: Sample>(
+        &self,
+        port_index: usize,
+        channel_index: u32,
+        frame_index: u32,
+    ) -> Option<S> {
+        // SAFETY: this type ensures the buffers are valid and long enough, and that no mutable
+        // reference to this chunk's samples is alive while &self is borrowed.
+        unsafe {
+            sample_ptr::<S>(
+                self.inputs,
+                port_index,
+                channel_index,
+                self.start,
+                self.frames_count,
+                frame_index,
+            )
+            .map(|sample| sample.read())
+        }
+    }
+
+    /// Returns a mutable reference to the given output sample, `frame_index` being relative to
+    /// the start of this chunk.
+    ///
+    /// This returns [`None`] if the port or channel doesn't exist, or if the port doesn't hold
+    /// samples of type `S`.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, this panics if `frame_index` is out of bounds. In release builds, this
+    /// returns [`None`] instead.
+    #[inline]
+    pub fn output_sample_mut<S: Sample>(
+        &mut self,
+        port_index: usize,
+        channel_index: u32,
+        frame_index: u32,
+    ) -> Option<&mut S> {
+        // SAFETY: this type ensures the buffers are valid and long enough, and &mut self ensures
+        // no other reference to this chunk's samples is alive.
+        unsafe {
+            sample_ptr::<S>(
+                self.outputs,
+                port_index,
+                channel_index,
+                self.start,
+                self.frames_count,
+                frame_index,
+            )
+            .map(|sample| &mut *sample)
+        }
+    }
+
+    /// Returns the samples of the given input channel within this chunk.
+    ///
+    /// This returns [`None`] if the port or channel doesn't exist, or if the port doesn't hold
+    /// samples of type `S`.
+    #[inline]
+    pub fn input_channel<S: Sample>(&self, port_index: usize, channel_index: u32) -> Option<&[S]> {
+        // SAFETY: this type ensures the buffers are valid and long enough, and that no mutable
+        // reference to this chunk's samples is alive while &self is borrowed.
+        unsafe {
+            channel_ptr::<S>(self.inputs, port_index, channel_index).map(|channel| {
+                slice_from_external_parts(
+                    channel.add(self.start as usize),
+                    self.frames_count as usize,
+                )
+            })
+        }
+    }
+
+    /// Returns the samples of the given output channel within this chunk.
+    ///
+    /// This returns [`None`] if the port or channel doesn't exist, or if the port doesn't hold
+    /// samples of type `S`.
+    #[inline]
+    pub fn output_channel_mut<S: Sample>(
+        &mut self,
+        port_index: usize,
+        channel_index: u32,
+    ) -> Option<&mut [S]> {
+        // SAFETY: this type ensures the buffers are valid and long enough, and &mut self ensures
+        // no other reference to this chunk's samples is alive.
+        unsafe {
+            channel_ptr::<S>(self.outputs, port_index, channel_index).map(|channel| {
+                slice_from_external_parts_mut(
+                    channel.add(self.start as usize),
+                    self.frames_count as usize,
+                )
+            })
+        }
+    }
+
+    /// Returns an iterator that batches the given input events happening within this chunk.
+    ///
+    /// The sample indexes of the batches are relative to the start of this chunk, and can be used
+    /// to index the slices returned by [`input_channel`](Self::input_channel) and
+    /// [`output_channel_mut`](Self::output_channel_mut) directly.
+    ///
+    /// See [`InputEvents::batch_frames`] for more information.
+    #[inline]
+    pub fn batch_events<'e>(&self, events: &'e InputEvents<'e>) -> EventBatcher<'e> {
+        events.batch_frames(self.start..self.start + self.frames_count)
+    }
+}
+
+/// An iterator over fixed-size [`AudioChunk`]s of an [`Audio`](crate::process::Audio) block.
+///
+/// This is returned by [`Audio::chunks_exact`](crate::process::Audio::chunks_exact). Like
+/// [`slice::chunks_exact`], the last frames of the block are left out if they can't fill a whole
+/// chunk: they can be retrieved with [`into_remainder`](AudioChunks::into_remainder).
+pub struct AudioChunks<'a> {
+    inputs: &'a [clap_audio_buffer],
+    outputs: &'a [clap_audio_buffer],
+    chunk_size: u32,
+    next_start: u32,
+    chunks_end: u32,
+    frames_count: u32,
+}
+
+impl<'a> AudioChunks<'a> {
+    /// # Safety
+    ///
+    /// All the given buffer structs must be valid for `'a`, as well as all the buffers they point
+    /// to, which must be at least `frames_count` samples long. No other references to these
+    /// buffers may exist for `'a`.
+    #[inline]
+    pub(crate) unsafe fn from_raw(
+        inputs: &'a [clap_audio_buffer],
+        outputs: &'a [clap_audio_buffer],
+        frames_count: u32,
+        chunk_size: u32,
+    ) -> Self {
+        assert!(chunk_size != 0, "Chunk size must be non-zero");
+
+        Self {
+            inputs,
+            outputs,
+            chunk_size,
+            next_start: 0,
+            chunks_end: frames_count - frames_count % chunk_size,
+            frames_count,
+        }
+    }
+
+    /// Returns the last frames of the block, that couldn't fill a whole chunk.
+    ///
+    /// This returns [`None`] if the block's frame count is a multiple of the chunk size.
+    #[inline]
+    pub fn into_remainder(self) -> Option<AudioChunk<'a>> {
+        if self.chunks_end == self.frames_count {
+            return None;
+        }
+
+        // SAFETY: the remainder doesn't overlap with any of the chunks.
+        Some(unsafe {
+            AudioChunk::from_raw(
+                self.inputs,
+                self.outputs,
+                self.chunks_end,
+                self.frames_count - self.chunks_end,
+            )
+        })
+    }
+}
+
+impl<'a> Iterator for AudioChunks<'a> {
+    type Item = AudioChunk<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_start >= self.chunks_end {
+            return None;
+        }
+
+        let start = self.next_start;
+        self.next_start += self.chunk_size;
+
+        // SAFETY: each chunk covers a different range of frames.
+        Some(unsafe { AudioChunk::from_raw(self.inputs, self.outputs, start, self.chunk_size) })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = ((self.chunks_end - self.next_start) / self.chunk_size) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for AudioChunks<'_> {}
@@ -1,5 +1,5 @@
-use std::error::Error;
-use std::fmt::{Display, Formatter};
+use clack_common::error::Error;
+use core::fmt::{Display, Formatter};
 
 /// Errors that can occur when accessing [`Audio`](crate::process::Audio) buffers.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -23,7 +23,7 @@ pub enum BufferError {
 }
 
 impl Display for BufferError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             BufferError::InvalidChannelBuffer => {
                 f.write_str("Invalid port channels buffers: both the data32 and data64 pointers were null")
@@ -3,7 +3,7 @@ use crate::prelude::Audio;
 use crate::process::audio::{BufferError, SampleType};
 use clack_common::process::ConstantMask;
 use clap_sys::audio_buffer::clap_audio_buffer;
-use std::slice::Iter;
+use core::slice::Iter;
 
 /// An iterator of all the available [`InputPort`]s from an [`Audio`] struct.
 pub struct InputPortsIter<'a> {
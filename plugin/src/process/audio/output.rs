@@ -4,7 +4,7 @@ use crate::process::audio::{BufferError, SampleType};
 use crate::process::InputChannelsIter;
 use clack_common::process::ConstantMask;
 use clap_sys::audio_buffer::clap_audio_buffer;
-use std::slice::IterMut;
+use core::slice::IterMut;
 
 /// An iterator of all the available [`OutputPort`]s from an [`Audio`] struct.
 pub struct OutputPortsIter<'a> {
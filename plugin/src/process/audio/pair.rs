@@ -4,7 +4,7 @@ use crate::process::audio::{BufferError, InputPort, OutputPort, SampleType};
 use crate::process::Audio;
 use clack_common::process::{AudioPortProcessingInfo, ConstantMask};
 use clap_sys::audio_buffer::clap_audio_buffer;
-use std::slice::{Iter, IterMut};
+use core::slice::{Iter, IterMut};
 
 /// A pair of Input and Output ports.
 ///
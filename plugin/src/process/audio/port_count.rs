@@ -0,0 +1,133 @@
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+
+/// The audio ports provided by the host in a `process` call, which were not declared by the plugin.
+///
+/// Undeclared ports are always the last ones: they are those whose indexes are greater than or
+/// equal to the number of ports declared by the plugin.
+///
+/// See [`Audio::undeclared_ports`](crate::process::Audio::undeclared_ports).
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct UndeclaredPorts {
+    inputs: Range<usize>,
+    outputs: Range<usize>,
+}
+
+impl UndeclaredPorts {
+    /// Returns the indexes of the input ports that were not declared by the plugin.
+    #[inline]
+    pub fn inputs(&self) -> Range<usize> {
+        self.inputs.clone()
+    }
+
+    /// Returns the indexes of the output ports that were not declared by the plugin.
+    #[inline]
+    pub fn outputs(&self) -> Range<usize> {
+        self.outputs.clone()
+    }
+
+    /// Returns `true` if all the ports provided by the host were declared by the plugin.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.outputs.is_empty()
+    }
+
+    /// Returns the undeclared ports of a sub-range of ports starting at `start`, with the given
+    /// number of input and output ports.
+    pub(crate) fn sub_range(&self, start: usize, inputs: usize, outputs: usize) -> Self {
+        fn shift(range: &Range<usize>, start: usize, len: usize) -> Range<usize> {
+            range.start.saturating_sub(start).min(len)..range.end.saturating_sub(start).min(len)
+        }
+
+        Self {
+            inputs: shift(&self.inputs, start, inputs),
+            outputs: shift(&self.outputs, start, outputs),
+        }
+    }
+}
+
+/// A mismatch between the number of audio ports declared by a plugin, and the number of ports
+/// actually provided by the host in a `process` call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PortCountMismatch {
+    /// The number of input ports declared by the plugin.
+    pub declared_inputs: usize,
+    /// The number of output ports declared by the plugin.
+    pub declared_outputs: usize,
+    /// The number of input ports provided by the host.
+    pub inputs: usize,
+    /// The number of output ports provided by the host.
+    pub outputs: usize,
+}
+
+impl PortCountMismatch {
+    /// Returns the ports provided by the host that were not declared by the plugin.
+    #[inline]
+    pub fn undeclared_ports(&self) -> UndeclaredPorts {
+        UndeclaredPorts {
+            inputs: self.declared_inputs.min(self.inputs)..self.inputs,
+            outputs: self.declared_outputs.min(self.outputs)..self.outputs,
+        }
+    }
+}
+
+impl Display for PortCountMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Host provided {} input and {} output audio ports, but the plugin declared {} input and {} output ports",
+            self.inputs, self.outputs, self.declared_inputs, self.declared_outputs
+        )
+    }
+}
+
+/// The result of a [`PortCountChecker::check`].
+pub(crate) enum PortCountCheck {
+    Matching,
+    Mismatched {
+        mismatch: PortCountMismatch,
+        /// Whether this is the first mismatch since activation, which hasn't been reported yet.
+        first: bool,
+    },
+}
+
+/// Checks the port counts of every `process` call against the ones the plugin declared when it
+/// was activated.
+pub(crate) struct PortCountChecker {
+    declared: Option<(usize, usize)>,
+    reported: bool,
+}
+
+impl PortCountChecker {
+    /// Creates a new checker for the given declared input and output port counts, or a checker
+    /// that accepts everything if they are unknown.
+    pub fn new(declared: Option<(u32, u32)>) -> Self {
+        Self {
+            declared: declared.map(|(inputs, outputs)| (inputs as usize, outputs as usize)),
+            reported: false,
+        }
+    }
+
+    pub fn check(&mut self, inputs: usize, outputs: usize) -> PortCountCheck {
+        let Some((declared_inputs, declared_outputs)) = self.declared else {
+            return PortCountCheck::Matching;
+        };
+
+        if declared_inputs == inputs && declared_outputs == outputs {
+            return PortCountCheck::Matching;
+        }
+
+        let first = !self.reported;
+        self.reported = true;
+
+        PortCountCheck::Mismatched {
+            mismatch: PortCountMismatch {
+                declared_inputs,
+                declared_outputs,
+                inputs,
+                outputs,
+            },
+            first,
+        }
+    }
+}
@@ -2,6 +2,42 @@ use crate::internal_utils::{slice_from_external_parts, slice_from_external_parts
 use crate::process::audio::BufferError;
 use clap_sys::audio_buffer::clap_audio_buffer;
 
+/// A sample type audio buffers can hold, i.e. either [`f32`] or [`f64`].
+///
+/// This is used by methods that access individual samples regardless of the buffer type, such as
+/// [`Audio::input_sample`](crate::process::Audio::input_sample).
+///
+/// This trait is sealed, and cannot be implemented outside of this crate.
+pub trait Sample: sealed::Sample + Copy + 'static {}
+
+impl Sample for f32 {}
+impl Sample for f64 {}
+
+pub(crate) mod sealed {
+    use clap_sys::audio_buffer::clap_audio_buffer;
+
+    pub trait Sample {
+        /// Returns the pointer to the array of channel buffers of this type in the given buffer.
+        ///
+        /// This may be null if the buffer doesn't hold samples of this type.
+        fn raw_channels(buffer: &clap_audio_buffer) -> *const *mut Self;
+    }
+
+    impl Sample for f32 {
+        #[inline]
+        fn raw_channels(buffer: &clap_audio_buffer) -> *const *mut Self {
+            buffer.data32 as *const *mut f32
+        }
+    }
+
+    impl Sample for f64 {
+        #[inline]
+        fn raw_channels(buffer: &clap_audio_buffer) -> *const *mut Self {
+            buffer.data64 as *const *mut f64
+        }
+    }
+}
+
 /// A generic enum to discriminate between buffers containing [`f32`] and [`f64`] sample types.
 ///
 /// This enum is actually just a generic container for two different types, called `F32` and `F64`,
@@ -0,0 +1,268 @@
+use crate::internal_utils::slice_from_external_parts;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use clap_sys::audio_buffer::clap_audio_buffer;
+
+/// The number of input ports the sanitizer preallocates room for.
+const PREALLOCATED_PORTS: usize = 16;
+/// The number of input channels the sanitizer preallocates room for, for each sample type.
+const PREALLOCATED_CHANNELS: usize = 64;
+
+/// Replaces missing input channel buffers provided by the host with silent, constant buffers.
+///
+/// Some hosts provide null channel pointers for constant input channels, or a null `data32` and
+/// `data64` for a port that has channels. This fixes those input buffers up before they are
+/// exposed to the plugin, so that reading them is always safe.
+///
+/// All buffers are allocated upon activation. More may be allocated if the host provides more
+/// ports or channels than preallocated, but only when missing buffers actually have to be
+/// replaced.
+pub(crate) struct InputSanitizer {
+    silence32: Box<[f32]>,
+    silence64: Box<[f64]>,
+    buffers: Vec<clap_audio_buffer>,
+    channels32: Vec<*mut f32>,
+    channels64: Vec<*mut f64>,
+}
+
+// SAFETY: the raw pointers this type holds only ever point to its own silence buffers, or to the
+// host-provided buffers of the current process call, which are only accessed on the audio thread.
+unsafe impl Send for InputSanitizer {}
+
+/// What needs to be done to a single input buffer to sanitize it.
+enum Fix {
+    None,
+    /// Both `data32` and `data64` are null: synthesize all channels as 32-bit silence.
+    Synthesize,
+    /// Some channel pointers are null: replace them.
+    ReplaceNull {
+        in32: bool,
+        in64: bool,
+    },
+}
+
+impl InputSanitizer {
+    pub fn new(max_frames_count: u32) -> Self {
+        let max_frames_count = max_frames_count as usize;
+
+        Self {
+            silence32: vec![0.0; max_frames_count].into_boxed_slice(),
+            silence64: vec![0.0; max_frames_count].into_boxed_slice(),
+            buffers: Vec::with_capacity(PREALLOCATED_PORTS),
+            channels32: Vec::with_capacity(PREALLOCATED_CHANNELS),
+            channels64: Vec::with_capacity(PREALLOCATED_CHANNELS),
+        }
+    }
+
+    /// Returns the given input buffers, with all missing channel buffers replaced by silent
+    /// buffers, which are also flagged as constant.
+    ///
+    /// If no buffer is missing, the given input buffers are returned as-is, without copying. They
+    /// are also returned as-is if `frames_count` exceeds the maximum frame count this sanitizer was
+    /// created with.
+    ///
+    /// # Safety
+    ///
+    /// The given buffers must be valid, except for the missing channel buffers described above.
+    pub unsafe fn sanitize<'a>(
+        &'a mut self,
+        inputs: &'a [clap_audio_buffer],
+        frames_count: u32,
+    ) -> &'a [clap_audio_buffer] {
+        if frames_count as usize > self.silence32.len() {
+            return inputs;
+        }
+
+        if inputs.iter().all(|b| matches!(Self::fix_for(b), Fix::None)) {
+            return inputs;
+        }
+
+        self.buffers.clear();
+        self.channels32.clear();
+        self.channels64.clear();
+
+        let silence32 = self.silence32.as_ptr() as *mut f32;
+        let silence64 = self.silence64.as_ptr() as *mut f64;
+
+        // First, fill the channel pointer tables, so that they don't move anymore.
+        let mut offsets = [0usize; 2];
+        for buffer in inputs {
+            let channel_count = buffer.channel_count as usize;
+
+            match Self::fix_for(buffer) {
+                Fix::None => {}
+                Fix::Synthesize => self
+                    .channels32
+                    .extend(core::iter::repeat(silence32).take(channel_count)),
+                Fix::ReplaceNull { in32, in64 } => {
+                    if in32 {
+                        let channels = slice_from_external_parts(
+                            buffer.data32 as *const *mut f32,
+                            channel_count,
+                        );
+                        self.channels32.extend(channels.iter().map(|&c| {
+                            if c.is_null() {
+                                silence32
+                            } else {
+                                c
+                            }
+                        }));
+                    }
+
+                    if in64 {
+                        let channels = slice_from_external_parts(
+                            buffer.data64 as *const *mut f64,
+                            channel_count,
+                        );
+                        self.channels64.extend(channels.iter().map(|&c| {
+                            if c.is_null() {
+                                silence64
+                            } else {
+                                c
+                            }
+                        }));
+                    }
+                }
+            }
+        }
+
+        // Then, point the copied buffers to them.
+        let channels32 = self.channels32.as_ptr();
+        let channels64 = self.channels64.as_ptr();
+
+        for buffer in inputs {
+            let mut buffer = *buffer;
+            let channel_count = buffer.channel_count as usize;
+
+            match Self::fix_for(&buffer) {
+                Fix::None => {}
+                Fix::Synthesize => {
+                    buffer.data32 = channels32.add(offsets[0]) as *const *const f32;
+                    buffer.constant_mask = u64::MAX;
+                    offsets[0] += channel_count;
+                }
+                Fix::ReplaceNull { in32, in64 } => {
+                    if in32 {
+                        buffer.constant_mask |=
+                            Self::null_mask(buffer.data32 as *const *mut f32, channel_count);
+                        buffer.data32 = channels32.add(offsets[0]) as *const *const f32;
+                        offsets[0] += channel_count;
+                    }
+
+                    if in64 {
+                        buffer.constant_mask |=
+                            Self::null_mask(buffer.data64 as *const *mut f64, channel_count);
+                        buffer.data64 = channels64.add(offsets[1]) as *const *const f64;
+                        offsets[1] += channel_count;
+                    }
+                }
+            }
+
+            self.buffers.push(buffer);
+        }
+
+        &self.buffers
+    }
+
+    /// # Safety
+    ///
+    /// The buffer's channel pointer tables must be valid, if they are not null.
+    unsafe fn fix_for(buffer: &clap_audio_buffer) -> Fix {
+        let channel_count = buffer.channel_count as usize;
+        if channel_count == 0 {
+            return Fix::None;
+        }
+
+        match (buffer.data32.is_null(), buffer.data64.is_null()) {
+            (true, true) => Fix::Synthesize,
+            (null32, null64) => {
+                let in32 = !null32
+                    && slice_from_external_parts(buffer.data32 as *const *mut f32, channel_count)
+                        .iter()
+                        .any(|c| c.is_null());
+
+                let in64 = !null64
+                    && slice_from_external_parts(buffer.data64 as *const *mut f64, channel_count)
+                        .iter()
+                        .any(|c| c.is_null());
+
+                if in32 || in64 {
+                    Fix::ReplaceNull { in32, in64 }
+                } else {
+                    Fix::None
+                }
+            }
+        }
+    }
+
+    /// Returns a constant mask with the bits of all the null channels set.
+    ///
+    /// # Safety
+    ///
+    /// The channel pointer table must be valid.
+    unsafe fn null_mask<T>(channels: *const *mut T, channel_count: usize) -> u64 {
+        slice_from_external_parts(channels, channel_count)
+            .iter()
+            .take(64)
+            .enumerate()
+            .filter(|(_, c)| c.is_null())
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::ptr::null;
+
+    fn buffer(data32: *const *const f32, channel_count: u32) -> clap_audio_buffer {
+        clap_audio_buffer {
+            data32,
+            data64: null(),
+            channel_count,
+            latency: 0,
+            constant_mask: 0,
+        }
+    }
+
+    #[test]
+    fn valid_buffers_are_not_copied() {
+        let mut sanitizer = InputSanitizer::new(4);
+        let data = [1.0f32; 4];
+        let channels = [data.as_ptr()];
+        let inputs = [buffer(channels.as_ptr(), 1), buffer(null(), 0)];
+
+        // SAFETY: all buffers are valid and 4 frames long.
+        let sanitized = unsafe { sanitizer.sanitize(&inputs, 4) };
+        assert_eq!(sanitized.as_ptr(), inputs.as_ptr());
+    }
+
+    #[test]
+    fn missing_buffers_are_replaced_with_silence() {
+        let mut sanitizer = InputSanitizer::new(4);
+        let data = [1.0f32; 4];
+        let channels = [data.as_ptr(), null::<f32>()];
+        let inputs = [buffer(channels.as_ptr(), 2), buffer(null(), 3)];
+
+        // SAFETY: all non-null buffers are valid and 4 frames long.
+        let sanitized = unsafe { sanitizer.sanitize(&inputs, 4) };
+        assert_eq!(sanitized.len(), 2);
+
+        // SAFETY: sanitized buffers have valid channel pointer tables, of 4-frame long buffers.
+        let (first, silence, second) = unsafe {
+            let first = core::slice::from_raw_parts(sanitized[0].data32, 2);
+            let silence = core::slice::from_raw_parts(first[1], 4);
+            let second = core::slice::from_raw_parts(sanitized[1].data32, 3);
+
+            (first, silence, second)
+        };
+
+        assert_eq!(first[0], data.as_ptr());
+        assert_eq!(silence, [0.0; 4]);
+        assert_eq!(sanitized[0].constant_mask, 0b10);
+
+        assert!(second.iter().all(|c| !c.is_null()));
+        assert_eq!(sanitized[1].constant_mask, u64::MAX);
+    }
+}
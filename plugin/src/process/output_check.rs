@@ -0,0 +1,103 @@
+//! Debug checks for the output events pushed by plugins.
+//!
+//! See [`CompatibilityCheck::UnsortedEvents`](crate::utils::compatibility::CompatibilityCheck::UnsortedEvents).
+
+use clap_sys::events::{clap_event_header, clap_output_events};
+use core::cell::Cell;
+use core::ffi::c_void;
+
+/// An output event list that checks every event pushed to it against the CLAP specification,
+/// before forwarding it to the host's list.
+///
+/// If `deny` is set, the offending events are not forwarded to the host.
+pub(crate) struct OutputEventsChecker {
+    inner: *const clap_output_events,
+    frames_count: u32,
+    deny: bool,
+    last_time: Cell<u32>,
+    out_of_order: Cell<bool>,
+    out_of_block: Cell<bool>,
+}
+
+impl OutputEventsChecker {
+    /// # Safety
+    ///
+    /// The given pointer must be valid for the lifetime of this checker.
+    #[inline]
+    pub unsafe fn new(inner: *const clap_output_events, frames_count: u32, deny: bool) -> Self {
+        Self {
+            inner,
+            frames_count,
+            deny,
+            last_time: Cell::new(0),
+            out_of_order: Cell::new(false),
+            out_of_block: Cell::new(false),
+        }
+    }
+
+    /// Returns a raw output event list which pushes into this checker.
+    ///
+    /// The returned list must not outlive this checker, and this checker must not be moved while
+    /// the list is in use.
+    #[inline]
+    pub fn as_raw(&self) -> clap_output_events {
+        clap_output_events {
+            ctx: self as *const Self as *mut c_void,
+            try_push: Some(Self::try_push),
+        }
+    }
+
+    /// Returns a message describing each of the violations that were found, if any.
+    pub fn violations(&self) -> impl Iterator<Item = &'static str> {
+        let out_of_order = self.out_of_order.get().then_some(
+            "Plugin pushed output events out of order: event times must never decrease.",
+        );
+
+        let out_of_block = self.out_of_block.get().then_some(
+            "Plugin pushed output events beyond the current block: event times must be less than the block's frame count.",
+        );
+
+        out_of_order.into_iter().chain(out_of_block)
+    }
+
+    #[allow(clippy::missing_safety_doc)]
+    unsafe extern "C" fn try_push(
+        list: *const clap_output_events,
+        event: *const clap_event_header,
+    ) -> bool {
+        let Some(checker) = list
+            .as_ref()
+            .and_then(|list| (list.ctx as *const Self).as_ref())
+        else {
+            return false;
+        };
+
+        if let Some(event) = event.as_ref() {
+            let out_of_order = event.time < checker.last_time.get();
+            let out_of_block = event.time >= checker.frames_count;
+
+            if out_of_order {
+                checker.out_of_order.set(true);
+            }
+
+            if out_of_block {
+                checker.out_of_block.set(true);
+            }
+
+            if checker.deny && (out_of_order || out_of_block) {
+                return false;
+            }
+
+            checker.last_time.set(event.time);
+        }
+
+        let Some(inner) = checker.inner.as_ref() else {
+            return false;
+        };
+
+        match inner.try_push {
+            Some(try_push) => try_push(inner, event),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! Handling of output events the host couldn't accept.
+//!
+//! See the [`OverflowBuffer`] type for more information.
+
+use clack_common::events::io::{EventBuffer, OutputEventBuffer, OutputEvents, TryPushError};
+use clack_common::events::UnknownEvent;
+
+/// A report of what happened to the output events of a single `process` call.
+///
+/// This is returned by [`OverflowBuffer::last_overflow`] and [`SpillingOutput::overflow`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct OutputOverflow {
+    /// The number of events the host refused to accept during this call.
+    pub rejected: u32,
+    /// The number of events that were stored to be sent again during the next call, either
+    /// because the host refused them, or to preserve their order after a refused event.
+    pub spilled: u32,
+    /// The number of events that were lost, because the [`OverflowBuffer`] itself was full.
+    pub dropped: u32,
+    /// The number of events from previous calls that the host accepted at the start of this call.
+    pub retried: u32,
+}
+
+impl OutputOverflow {
+    /// Returns `true` if the host's output event queue overflowed during this call.
+    ///
+    /// Plugins may react to this by e.g. generating fewer events, or requesting smaller blocks.
+    #[inline]
+    pub fn has_overflowed(&self) -> bool {
+        self.rejected > 0
+    }
+
+    /// Returns `true` if some events were lost during this call.
+    #[inline]
+    pub fn has_dropped_events(&self) -> bool {
+        self.dropped > 0
+    }
+}
+
+/// Plugin-owned storage for the output events the host's queue couldn't accept.
+///
+/// Hosts usually size their output event queue relative to the input events they sent, and may
+/// refuse events once it is full. This is a problem for plugins that generate many events, such
+/// as arpeggiators.
+///
+/// Instead of pushing events directly to the host's [`OutputEvents`], plugins can push them
+/// through the [`SpillingOutput`] returned by [`begin`](Self::begin). Events the host refuses are
+/// then stored in this buffer, and sent again at the start of the next `process` call, with their
+/// time set to `0`. Once an event has been stored, all the following events of the same call are
+/// stored as well, so that events are always delivered in the order they were pushed.
+///
+/// What happened during the last call is reported as an [`OutputOverflow`], which can be queried
+/// at the end of `process` using [`last_overflow`](Self::last_overflow).
+///
+/// # Realtime Safety
+///
+/// All storage is allocated when the buffer is created. If more than `max_pending` events have to
+/// be stored, the extra ones are dropped instead, and counted in [`OutputOverflow::dropped`].
+///
+/// # Example
+///
+/// ```
+/// use clack_plugin::prelude::*;
+/// use clack_plugin::process::overflow::OverflowBuffer;
+///
+/// struct Arpeggiator {
+///     overflow: OverflowBuffer,
+/// }
+///
+/// impl Arpeggiator {
+///     fn process(&mut self, events: Events) {
+///         let mut output = self.overflow.begin(events.output);
+///
+///         for note in 0..16 {
+///             let event = NoteOnEvent::new(note, Pckn::new(0u16, 0u16, 60u16, 0u32), 1.0);
+///             let _ = output.try_push(event);
+///         }
+///
+///         if output.overflow().has_overflowed() {
+///             // Generate fewer notes next time.
+///         }
+///     }
+/// }
+/// ```
+pub struct OverflowBuffer {
+    pending: EventBuffer,
+    retrying: EventBuffer,
+    max_pending: usize,
+    last_overflow: OutputOverflow,
+}
+
+impl OverflowBuffer {
+    /// Creates a new overflow buffer, which can store up to `max_pending` events.
+    ///
+    /// # Realtime Safety
+    ///
+    /// This method allocates, and should be called e.g. when the plugin is activated.
+    pub fn new(max_pending: usize) -> Self {
+        Self {
+            pending: EventBuffer::with_capacity(max_pending),
+            retrying: EventBuffer::with_capacity(max_pending),
+            max_pending,
+            last_overflow: OutputOverflow::default(),
+        }
+    }
+
+    /// Returns the maximum number of events this buffer can store.
+    #[inline]
+    pub fn max_pending(&self) -> usize {
+        self.max_pending
+    }
+
+    /// Returns the number of events that are waiting to be sent to the host.
+    #[inline]
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns what happened to the output events of the last `process` call.
+    #[inline]
+    pub fn last_overflow(&self) -> OutputOverflow {
+        self.last_overflow
+    }
+
+    /// Discards all the pending events, e.g. when the plugin is reset.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Starts a new `process` call, pushing events to the given host output events.
+    ///
+    /// This first sends the events that are still pending from previous calls, at time `0`.
+    /// Events the host refuses again are kept for the next call.
+    pub fn begin<'a, 'o>(&'a mut self, output: &'a mut OutputEvents<'o>) -> SpillingOutput<'a, 'o> {
+        self.last_overflow = OutputOverflow::default();
+
+        core::mem::swap(&mut self.pending, &mut self.retrying);
+        self.retrying.retime(0);
+
+        for event in &self.retrying {
+            if self.pending.is_empty() && output.try_push(event).is_ok() {
+                self.last_overflow.retried += 1;
+            } else {
+                if self.pending.is_empty() {
+                    self.last_overflow.rejected += 1;
+                }
+
+                // This cannot overflow, as these events were already stored before.
+                self.pending.push(event);
+            }
+        }
+
+        self.retrying.clear();
+
+        SpillingOutput {
+            buffer: self,
+            output,
+        }
+    }
+}
+
+/// The output events of a single `process` call, which stores the events the host refuses into
+/// an [`OverflowBuffer`].
+///
+/// This is obtained from [`OverflowBuffer::begin`]. It can also be wrapped into an
+/// [`OutputEvents`] using [`OutputEvents::from_buffer`], to be given to helpers expecting one.
+pub struct SpillingOutput<'a, 'o> {
+    buffer: &'a mut OverflowBuffer,
+    output: &'a mut OutputEvents<'o>,
+}
+
+impl SpillingOutput<'_, '_> {
+    /// Pushes the given event to the host, or stores it for the next call if the host refuses it.
+    ///
+    /// # Errors
+    ///
+    /// This returns a [`TryPushError`] if the host refused the event, and the [`OverflowBuffer`]
+    /// was full. The event is then lost.
+    pub fn try_push<E: AsRef<UnknownEvent>>(&mut self, event: E) -> Result<(), TryPushError> {
+        let event = event.as_ref();
+        let buffer = &mut *self.buffer;
+
+        if buffer.pending.is_empty() {
+            if self.output.try_push(event).is_ok() {
+                return Ok(());
+            }
+
+            buffer.last_overflow.rejected += 1;
+        }
+
+        if buffer.pending.len() >= buffer.max_pending {
+            buffer.last_overflow.dropped += 1;
+            return Err(TryPushError::new());
+        }
+
+        buffer.pending.push(event);
+        buffer.last_overflow.spilled += 1;
+        Ok(())
+    }
+
+    /// Returns what happened to the output events of this call so far.
+    #[inline]
+    pub fn overflow(&self) -> OutputOverflow {
+        self.buffer.last_overflow
+    }
+}
+
+impl OutputEventBuffer for SpillingOutput<'_, '_> {
+    #[inline]
+    fn try_push(&mut self, event: &UnknownEvent) -> Result<(), TryPushError> {
+        SpillingOutput::try_push(self, event)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clack_common::events::event_types::MidiEvent;
+    use clack_common::events::Event;
+
+    /// A host output queue which refuses events past a fixed capacity.
+    struct CappedSink {
+        events: EventBuffer,
+        capacity: usize,
+    }
+
+    impl CappedSink {
+        fn new(capacity: usize) -> Self {
+            Self {
+                events: EventBuffer::with_capacity(capacity),
+                capacity,
+            }
+        }
+
+        fn take(&mut self) -> Vec<(u32, u8)> {
+            let events = self
+                .events
+                .iter()
+                .map(|e| {
+                    let e = e.as_event::<MidiEvent>().unwrap();
+                    (e.header().time(), e.data()[0])
+                })
+                .collect();
+
+            self.events.clear();
+            events
+        }
+    }
+
+    impl OutputEventBuffer for CappedSink {
+        fn try_push(&mut self, event: &UnknownEvent) -> Result<(), TryPushError> {
+            if self.events.len() >= self.capacity {
+                return Err(TryPushError::new());
+            }
+
+            self.events.push(event);
+            Ok(())
+        }
+    }
+
+    fn push_notes(buffer: &mut OverflowBuffer, sink: &mut CappedSink, notes: &[u8]) {
+        let mut output = OutputEvents::from_buffer(sink);
+        let mut output = buffer.begin(&mut output);
+
+        for note in notes {
+            output
+                .try_push(MidiEvent::new(*note as u32, 0, [*note, 0, 0]))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn spills_and_retries_at_time_zero() {
+        let mut buffer = OverflowBuffer::new(16);
+        let mut sink = CappedSink::new(4);
+
+        push_notes(&mut buffer, &mut sink, &[1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(sink.take(), [(1, 1), (2, 2), (3, 3), (4, 4)]);
+        assert_eq!(buffer.pending_len(), 2);
+        assert_eq!(
+            buffer.last_overflow(),
+            OutputOverflow {
+                rejected: 1,
+                spilled: 2,
+                dropped: 0,
+                retried: 0
+            }
+        );
+        assert!(buffer.last_overflow().has_overflowed());
+
+        push_notes(&mut buffer, &mut sink, &[7]);
+
+        assert_eq!(sink.take(), [(0, 5), (0, 6), (7, 7)]);
+        assert_eq!(buffer.pending_len(), 0);
+        assert_eq!(
+            buffer.last_overflow(),
+            OutputOverflow {
+                retried: 2,
+                ..Default::default()
+            }
+        );
+        assert!(!buffer.last_overflow().has_overflowed());
+    }
+
+    #[test]
+    fn keeps_order_while_events_are_pending() {
+        let mut buffer = OverflowBuffer::new(16);
+        let mut sink = CappedSink::new(4);
+
+        push_notes(&mut buffer, &mut sink, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(sink.take().len(), 4);
+        assert_eq!(buffer.pending_len(), 6);
+
+        // The host still can't take all pending events, so new ones must queue behind them.
+        push_notes(&mut buffer, &mut sink, &[11]);
+        assert_eq!(sink.take(), [(0, 5), (0, 6), (0, 7), (0, 8)]);
+        assert_eq!(buffer.pending_len(), 3);
+        assert_eq!(buffer.last_overflow().retried, 4);
+        assert_eq!(buffer.last_overflow().spilled, 1);
+
+        push_notes(&mut buffer, &mut sink, &[]);
+        assert_eq!(sink.take(), [(0, 9), (0, 10), (0, 11)]);
+        assert_eq!(buffer.pending_len(), 0);
+    }
+
+    #[test]
+    fn drops_events_when_full() {
+        let mut buffer = OverflowBuffer::new(2);
+        let mut sink = CappedSink::new(4);
+
+        let mut output = OutputEvents::from_buffer(&mut sink);
+        let mut output = buffer.begin(&mut output);
+
+        for note in 1..=7 {
+            let result = output.try_push(MidiEvent::new(0, 0, [note, 0, 0]));
+            assert_eq!(result.is_ok(), note <= 6);
+        }
+
+        let overflow = output.overflow();
+        assert_eq!(overflow.spilled, 2);
+        assert_eq!(overflow.dropped, 1);
+        assert!(overflow.has_dropped_events());
+    }
+}
@@ -0,0 +1,483 @@
+//! The fixture host, which implements the host side of every extension.
+//!
+//! Everything the fixture host receives from the plugin is recorded as a [`HostEvent`], to be
+//! compared with what the plugin sent.
+
+use clack_extensions::audio_ports::*;
+use clack_extensions::audio_ports_config::*;
+use clack_extensions::context_menu::*;
+use clack_extensions::event_registry::*;
+use clack_extensions::gui::*;
+use clack_extensions::latency::*;
+use clack_extensions::log::*;
+use clack_extensions::note_name::*;
+use clack_extensions::note_ports::*;
+use clack_extensions::params::*;
+#[cfg(unix)]
+use clack_extensions::posix_fd::*;
+use clack_extensions::state::*;
+use clack_extensions::tail::*;
+use clack_extensions::thread_check::*;
+use clack_extensions::thread_pool::*;
+use clack_extensions::timer::*;
+use clack_extensions::voice_info::*;
+use clack_host::events::spaces::EventSpaceId;
+use clack_host::prelude::*;
+use std::ffi::CStr;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+/// The action ID of the "Learn MIDI" entry the fixture host adds to the plugin's context menus.
+pub const LEARN_MIDI_ACTION: ClapId = ClapId::new(50);
+
+/// Something the fixture host received from the plugin.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HostEvent {
+    /// The plugin requested a restart.
+    RestartRequested,
+    /// The plugin requested to be processed.
+    ProcessRequested,
+    /// The plugin requested a main thread callback.
+    CallbackRequested,
+    /// The plugin requested a parameter rescan.
+    ParamsRescanned(ParamRescanFlags),
+    /// The plugin requested references to a parameter to be cleared.
+    ParamCleared(ClapId, ParamClearFlags),
+    /// The plugin requested a parameter flush.
+    ParamFlushRequested,
+    /// The plugin marked its state as dirty.
+    StateMarkedDirty,
+    /// The plugin's latency changed.
+    LatencyChanged,
+    /// The plugin's tail length changed.
+    TailChanged,
+    /// The plugin registered a timer.
+    TimerRegistered {
+        /// The ID the host gave to the timer.
+        id: TimerId,
+        /// The timer's period, in milliseconds.
+        period_ms: u32,
+    },
+    /// The plugin unregistered a timer.
+    TimerUnregistered(TimerId),
+    /// The plugin logged a message.
+    Logged(LogSeverity, String),
+    /// The plugin's voice info changed.
+    VoiceInfoChanged,
+    /// The plugin requested an audio ports rescan.
+    AudioPortsRescanned(RescanType),
+    /// The plugin requested a note ports rescan.
+    NotePortsRescanned(NotePortRescanFlags),
+    /// The plugin's GUI resize hints changed.
+    GuiResizeHintsChanged,
+    /// The plugin requested its GUI to be resized.
+    GuiResizeRequested(GuiSize),
+    /// The plugin requested its GUI to be shown.
+    GuiShowRequested,
+    /// The plugin requested its GUI to be hidden.
+    GuiHideRequested,
+    /// The plugin's GUI was closed.
+    GuiClosed {
+        /// Whether the GUI was destroyed.
+        was_destroyed: bool,
+    },
+    /// The plugin performed the action of one of the host's context menu items.
+    ContextMenuPerformed(ContextMenuTarget, ClapId),
+    /// The plugin asked the host to show its context menu.
+    ContextMenuPopupRequested {
+        /// The target of the menu.
+        target: ContextMenuTarget,
+        /// The index of the screen to show the menu on.
+        screen_index: i32,
+        /// The horizontal position of the menu.
+        x: i32,
+        /// The vertical position of the menu.
+        y: i32,
+    },
+    /// The plugin requested an audio ports configurations rescan.
+    AudioPortsConfigsRescanned,
+    /// The plugin's note names changed.
+    NoteNamesChanged,
+    /// The plugin queried the ID of an event space, by name.
+    EventSpaceQueried(String),
+    /// The plugin requested the given number of tasks to be run in the thread pool.
+    TasksRequested(u32),
+    /// The plugin registered a file descriptor.
+    #[cfg(unix)]
+    FdRegistered(RawFd, FdFlags),
+    /// The plugin changed the events a registered file descriptor is watched for.
+    #[cfg(unix)]
+    FdModified(RawFd, FdFlags),
+    /// The plugin unregistered a file descriptor.
+    #[cfg(unix)]
+    FdUnregistered(RawFd),
+}
+
+/// A host implementing the host side of every extension covered by the integration tests.
+pub struct FixtureHost;
+
+impl HostHandlers for FixtureHost {
+    type Shared<'a> = FixtureHostShared;
+    type MainThread<'a> = FixtureHostMainThread<'a>;
+    type AudioProcessor<'a> = FixtureHostAudioProcessor<'a>;
+
+    fn declare_extensions(builder: &mut HostExtensions<Self>, _shared: &Self::Shared<'_>) {
+        builder
+            .register::<HostAudioPorts>()
+            .register::<HostAudioPortsConfig>()
+            .register::<HostContextMenu>()
+            .register::<HostEventRegistry>()
+            .register::<HostGui>()
+            .register::<HostLatency>()
+            .register::<HostLog>()
+            .register::<HostNoteName>()
+            .register::<HostNotePorts>()
+            .register::<HostParams>()
+            .register::<HostState>()
+            .register::<HostTail>()
+            .register::<HostThreadCheck>()
+            .register::<HostThreadPool>()
+            .register::<HostTimer>()
+            .register::<HostVoiceInfo>();
+
+        // This extension is only available on Unix.
+        #[cfg(unix)]
+        builder.register::<HostPosixFd>();
+    }
+}
+
+/// The fixture host's thread-safe state.
+pub struct FixtureHostShared {
+    events: Mutex<Vec<HostEvent>>,
+    main_thread: ThreadId,
+}
+
+impl Default for FixtureHostShared {
+    /// Creates the fixture host's thread-safe state, with the current thread as its main thread.
+    fn default() -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            main_thread: thread::current().id(),
+        }
+    }
+}
+
+impl FixtureHostShared {
+    /// Returns everything the fixture host received so far, and clears it.
+    pub fn take_events(&self) -> Vec<HostEvent> {
+        std::mem::take(&mut self.events.lock().unwrap())
+    }
+
+    fn record(&self, event: HostEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+impl SharedHandler<'_> for FixtureHostShared {
+    fn request_restart(&self) {
+        self.record(HostEvent::RestartRequested);
+    }
+
+    fn request_process(&self) {
+        self.record(HostEvent::ProcessRequested);
+    }
+
+    fn request_callback(&self) {
+        self.record(HostEvent::CallbackRequested);
+    }
+}
+
+impl HostParamsImplShared for FixtureHostShared {
+    fn request_flush(&self) {
+        self.record(HostEvent::ParamFlushRequested);
+    }
+}
+
+impl HostLogImpl for FixtureHostShared {
+    fn log(&self, severity: LogSeverity, message: &str) {
+        self.record(HostEvent::Logged(severity, message.to_owned()));
+    }
+}
+
+impl HostGuiImpl for FixtureHostShared {
+    fn resize_hints_changed(&self) {
+        self.record(HostEvent::GuiResizeHintsChanged);
+    }
+
+    fn request_resize(&self, new_size: GuiSize) -> Result<(), HostError> {
+        self.record(HostEvent::GuiResizeRequested(new_size));
+        Ok(())
+    }
+
+    fn request_show(&self) -> Result<(), HostError> {
+        self.record(HostEvent::GuiShowRequested);
+        Ok(())
+    }
+
+    fn request_hide(&self) -> Result<(), HostError> {
+        self.record(HostEvent::GuiHideRequested);
+        Ok(())
+    }
+
+    fn closed(&self, was_destroyed: bool) {
+        self.record(HostEvent::GuiClosed { was_destroyed });
+    }
+}
+
+impl HostThreadCheckImpl for FixtureHostShared {
+    fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.main_thread
+    }
+
+    /// The fixture host has no dedicated audio thread: any thread but the main thread counts as
+    /// one.
+    fn is_audio_thread(&self) -> bool {
+        !self.is_main_thread()
+    }
+}
+
+/// The fixture host's main thread state.
+pub struct FixtureHostMainThread<'a> {
+    shared: &'a FixtureHostShared,
+    plugin: Option<InitializedPluginHandle<'a>>,
+    timers: Vec<(TimerId, u32)>,
+    next_timer_id: u32,
+}
+
+impl<'a> FixtureHostMainThread<'a> {
+    /// Creates the fixture host's main thread state.
+    pub fn new(shared: &'a FixtureHostShared) -> Self {
+        Self {
+            shared,
+            plugin: None,
+            timers: Vec::new(),
+            next_timer_id: 1,
+        }
+    }
+
+    /// Returns the timers the plugin currently has registered, with their period in milliseconds.
+    pub fn timers(&self) -> &[(TimerId, u32)] {
+        &self.timers
+    }
+}
+
+impl<'a> MainThreadHandler<'a> for FixtureHostMainThread<'a> {
+    fn initialized(&mut self, instance: InitializedPluginHandle<'a>) {
+        self.plugin = Some(instance);
+    }
+}
+
+impl HostParamsImplMainThread for FixtureHostMainThread<'_> {
+    fn rescan(&mut self, flags: ParamRescanFlags) {
+        self.shared.record(HostEvent::ParamsRescanned(flags));
+    }
+
+    fn clear(&mut self, param_id: ClapId, flags: ParamClearFlags) {
+        self.shared.record(HostEvent::ParamCleared(param_id, flags));
+    }
+}
+
+impl HostStateImpl for FixtureHostMainThread<'_> {
+    fn mark_dirty(&mut self) {
+        self.shared.record(HostEvent::StateMarkedDirty);
+    }
+}
+
+impl HostLatencyImpl for FixtureHostMainThread<'_> {
+    fn changed(&mut self) {
+        self.shared.record(HostEvent::LatencyChanged);
+    }
+}
+
+impl HostTimerImpl for FixtureHostMainThread<'_> {
+    fn register_timer(&mut self, period_ms: u32) -> Result<TimerId, HostError> {
+        let id = TimerId(self.next_timer_id);
+        self.next_timer_id += 1;
+
+        self.timers.push((id, period_ms));
+        self.shared
+            .record(HostEvent::TimerRegistered { id, period_ms });
+
+        Ok(id)
+    }
+
+    fn unregister_timer(&mut self, timer_id: TimerId) -> Result<(), HostError> {
+        let index = self
+            .timers
+            .iter()
+            .position(|(id, _)| *id == timer_id)
+            .ok_or(HostError::Message("Unknown timer"))?;
+
+        self.timers.remove(index);
+        self.shared.record(HostEvent::TimerUnregistered(timer_id));
+        Ok(())
+    }
+}
+
+impl HostVoiceInfoImpl for FixtureHostMainThread<'_> {
+    fn changed(&mut self) {
+        self.shared.record(HostEvent::VoiceInfoChanged);
+    }
+}
+
+impl HostAudioPortsImpl for FixtureHostMainThread<'_> {
+    fn is_rescan_flag_supported(&self, _flag: RescanType) -> bool {
+        true
+    }
+
+    fn rescan(&mut self, flag: RescanType) {
+        self.shared.record(HostEvent::AudioPortsRescanned(flag));
+    }
+}
+
+impl HostNotePortsImpl for FixtureHostMainThread<'_> {
+    fn supported_dialects(&self) -> NoteDialects {
+        NoteDialects::CLAP | NoteDialects::MIDI
+    }
+
+    fn rescan(&mut self, flags: NotePortRescanFlags) {
+        self.shared.record(HostEvent::NotePortsRescanned(flags));
+    }
+}
+
+impl HostContextMenuImpl for FixtureHostMainThread<'_> {
+    fn populate(
+        &mut self,
+        _target: ContextMenuTarget,
+        builder: &mut ContextMenuBuilder,
+    ) -> Result<(), HostError> {
+        builder.add(ContextMenuItem::Entry {
+            label: CStr::from_bytes_with_nul(b"Learn MIDI\0").unwrap(),
+            is_enabled: true,
+            action_id: LEARN_MIDI_ACTION,
+        });
+
+        Ok(())
+    }
+
+    fn perform(&mut self, target: ContextMenuTarget, action_id: ClapId) -> Result<(), HostError> {
+        if action_id != LEARN_MIDI_ACTION {
+            return Err(HostError::Message("Unknown context menu action"));
+        }
+
+        self.shared
+            .record(HostEvent::ContextMenuPerformed(target, action_id));
+        Ok(())
+    }
+
+    fn can_popup(&mut self) -> bool {
+        true
+    }
+
+    fn popup(
+        &mut self,
+        target: ContextMenuTarget,
+        screen_index: i32,
+        x: i32,
+        y: i32,
+    ) -> Result<(), HostError> {
+        self.shared.record(HostEvent::ContextMenuPopupRequested {
+            target,
+            screen_index,
+            x,
+            y,
+        });
+        Ok(())
+    }
+}
+
+impl HostAudioPortsConfigImpl for FixtureHostMainThread<'_> {
+    fn rescan(&mut self) {
+        self.shared.record(HostEvent::AudioPortsConfigsRescanned);
+    }
+}
+
+impl HostNoteNameImpl for FixtureHostMainThread<'_> {
+    fn changed(&mut self) {
+        self.shared.record(HostEvent::NoteNamesChanged);
+    }
+}
+
+// SAFETY: The fixture host only knows about the core event space, whose ID never changes.
+unsafe impl HostEventRegistryImpl for FixtureHostMainThread<'_> {
+    fn query(&self, space_name: &CStr) -> Option<EventSpaceId> {
+        self.shared.record(HostEvent::EventSpaceQueried(
+            space_name.to_string_lossy().into_owned(),
+        ));
+
+        // The core event space is the one with an empty name.
+        space_name
+            .to_bytes()
+            .is_empty()
+            .then(|| EventSpaceId::core().into())
+    }
+}
+
+#[cfg(unix)]
+impl HostPosixFdImpl for FixtureHostMainThread<'_> {
+    fn register_fd(&mut self, fd: RawFd, flags: FdFlags) -> Result<(), HostError> {
+        self.shared.record(HostEvent::FdRegistered(fd, flags));
+        Ok(())
+    }
+
+    fn modify_fd(&mut self, fd: RawFd, flags: FdFlags) -> Result<(), HostError> {
+        self.shared.record(HostEvent::FdModified(fd, flags));
+        Ok(())
+    }
+
+    fn unregister_fd(&mut self, fd: RawFd) -> Result<(), HostError> {
+        self.shared.record(HostEvent::FdUnregistered(fd));
+        Ok(())
+    }
+}
+
+/// The fixture host's audio processor state.
+pub struct FixtureHostAudioProcessor<'a> {
+    shared: &'a FixtureHostShared,
+    plugin: Option<InitializedPluginHandle<'a>>,
+}
+
+impl<'a> FixtureHostAudioProcessor<'a> {
+    /// Creates the fixture host's audio processor state.
+    pub fn new(shared: &'a FixtureHostShared, main_thread: &FixtureHostMainThread<'a>) -> Self {
+        Self {
+            shared,
+            plugin: main_thread.plugin.clone(),
+        }
+    }
+}
+
+impl<'a> AudioProcessorHandler<'a> for FixtureHostAudioProcessor<'a> {}
+
+impl HostTailImpl for FixtureHostAudioProcessor<'_> {
+    fn changed(&mut self) {
+        self.shared.record(HostEvent::TailChanged);
+    }
+}
+
+impl HostThreadPoolImpl for FixtureHostAudioProcessor<'_> {
+    /// The fixture host has no thread pool: it runs the requested tasks on the calling thread.
+    fn request_exec(&mut self, task_count: u32) -> Result<(), HostError> {
+        self.shared.record(HostEvent::TasksRequested(task_count));
+
+        let plugin = self
+            .plugin
+            .as_ref()
+            .ok_or(HostError::Message("Plugin is not initialized"))?;
+        let thread_pool = plugin
+            .get_extension::<PluginThreadPool>()
+            .ok_or(HostError::Message(
+                "Plugin does not support the thread pool",
+            ))?;
+
+        plugin
+            .access(|plugin| {
+                for task_index in 0..task_count {
+                    thread_pool.exec(&plugin, task_index);
+                }
+            })
+            .ok_or(HostError::Message("Plugin is being destroyed"))
+    }
+}
@@ -0,0 +1,237 @@
+//! Fixtures for Clack's in-process integration test suite.
+//!
+//! The tests in this crate load a [fixture plugin](FixturePlugin) built with `clack-plugin` into a
+//! [fixture host](FixtureHost) built with `clack-host`, without going through a dynamic library,
+//! and check that the data of each covered extension crosses the CLAP ABI intact in both
+//! directions. The covered extensions are the ones enabled in this crate's `Cargo.toml`.
+//!
+//! Each test covers a single extension in a single direction: `host_to_plugin` tests check that
+//! what the host sends or queries is what the plugin sees, and `plugin_to_host` tests check the
+//! opposite. A failing test therefore points at both the extension and the direction at fault.
+//!
+//! # Adding an extension
+//!
+//! Every new extension added to Clack must be covered by this suite. When adding one:
+//!
+//! * enable its feature in this crate's `Cargo.toml`;
+//! * implement and register its plugin side on the [`FixturePlugin`], and add the
+//!   [`PluginCommand`]s and [`PluginEvent`]s needed to drive and observe it;
+//! * implement and register its host side on the [`FixtureHost`], and record what it receives as
+//!   [`HostEvent`]s;
+//! * add a module for it in `tests/extensions.rs`, with one test per direction.
+
+#![deny(missing_docs)]
+
+use crate::host::*;
+use crate::plugin::*;
+use clack_host::extensions::{Extension, PluginExtensionSide};
+use clack_host::prelude::*;
+use clack_plugin::clack_entry;
+use clack_plugin::entry::{EntryDescriptor, SinglePluginEntry};
+use clack_plugin::extensions::wrapper::PluginWrapper;
+use std::ffi::{CStr, CString};
+
+pub mod host;
+pub mod plugin;
+
+/// The entry of the fixture plugin.
+pub static FIXTURE_ENTRY: EntryDescriptor = clack_entry!(SinglePluginEntry<FixturePlugin>);
+
+/// The audio configuration the fixture plugin is activated with.
+pub const AUDIO_CONFIGURATION: PluginAudioConfiguration = PluginAudioConfiguration {
+    sample_rate: 48_000.0,
+    min_frames_count: 32,
+    max_frames_count: 32,
+};
+
+/// An instance of the fixture plugin, loaded in the fixture host.
+///
+/// Test hosts must be created, used and dropped on a single thread, which is considered to be the
+/// main thread.
+pub struct TestHost {
+    instance: PluginInstance<FixtureHost>,
+    processor: Option<StartedPluginAudioProcessor<FixtureHost>>,
+}
+
+impl TestHost {
+    /// Loads and instantiates the fixture plugin.
+    ///
+    /// Everything recorded on either side during instantiation is discarded.
+    pub fn new() -> Self {
+        let bundle = unsafe { PluginBundle::load_from_raw(&FIXTURE_ENTRY, "/fixture.clap") }
+            .expect("Failed to load the fixture bundle");
+        let host_info =
+            HostInfo::new("Legit Studio", "Legit Ltd.", "https://example.com", "4.3.2").unwrap();
+        let plugin_id = CString::new(PLUGIN_ID).unwrap();
+
+        let instance = PluginInstance::<FixtureHost>::new(
+            |_| FixtureHostShared::default(),
+            |shared| FixtureHostMainThread::new(shared),
+            &bundle,
+            &plugin_id,
+            &host_info,
+        )
+        .expect("Failed to instantiate the fixture plugin");
+
+        let host = Self {
+            instance,
+            processor: None,
+        };
+
+        host.take_host_events();
+        take_plugin_events();
+        host
+    }
+
+    /// Returns the plugin instance.
+    pub fn instance(&mut self) -> &mut PluginInstance<FixtureHost> {
+        &mut self.instance
+    }
+
+    /// Returns a main thread handle to the plugin.
+    pub fn plugin(&mut self) -> PluginMainThreadHandle<'_> {
+        self.instance.plugin_handle_unchecked()
+    }
+
+    /// Returns the given plugin extension.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin doesn't expose the extension.
+    pub fn extension<E: Extension<ExtensionSide = PluginExtensionSide>>(&self) -> E {
+        self.instance
+            .plugin_shared_handle()
+            .get_extension()
+            .expect("The fixture plugin doesn't expose the requested extension")
+    }
+
+    /// Returns the identifiers of all the extensions the plugin declared, as seen from the plugin
+    /// side, in the order they were registered.
+    pub fn declared_extensions(&self) -> Vec<CString> {
+        let plugin = self.instance.plugin_shared_handle().as_raw_ptr();
+
+        // SAFETY: the instance was created from the fixture plugin's entry, so it is a
+        // FixturePlugin created by Clack.
+        unsafe {
+            PluginWrapper::<FixturePlugin>::handle_named(
+                plugin,
+                "TestHost::declared_extensions",
+                |p| Ok(p.declared_extensions().map(CStr::to_owned).collect()),
+            )
+        }
+        .expect("Failed to access the fixture plugin")
+    }
+
+    /// Returns everything the host received so far, and clears it.
+    pub fn take_host_events(&self) -> Vec<HostEvent> {
+        self.instance
+            .access_shared_handler(FixtureHostShared::take_events)
+    }
+
+    /// Makes the plugin run the given command on the main thread.
+    pub fn send(&mut self, command: PluginCommand) {
+        queue_command(command);
+        self.instance.call_on_main_thread_callback_unchecked();
+    }
+
+    /// Activates the plugin and starts processing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin is already active.
+    pub fn activate(&mut self) {
+        assert!(self.processor.is_none(), "The plugin is already active");
+
+        let processor = self
+            .instance
+            .activate_unchecked(
+                |shared, main_thread| FixtureHostAudioProcessor::new(shared, main_thread),
+                AUDIO_CONFIGURATION,
+            )
+            .expect("Failed to activate the fixture plugin")
+            .start_processing()
+            .expect("Failed to start processing");
+
+        self.processor = Some(processor);
+    }
+
+    /// Stops processing and deactivates the plugin, if it was active.
+    pub fn deactivate(&mut self) {
+        if let Some(processor) = self.processor.take() {
+            self.instance
+                .deactivate_unchecked(processor.stop_processing());
+        }
+    }
+
+    /// Returns the started audio processor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the plugin isn't active.
+    pub fn processor(&mut self) -> &mut StartedPluginAudioProcessor<FixtureHost> {
+        self.processor
+            .as_mut()
+            .expect("The fixture plugin isn't active")
+    }
+
+    /// Processes a block of silence with the input events of `events`. The events the plugin
+    /// outputs are appended to its output events.
+    ///
+    /// Buffers are provided for all the plugin's audio ports.
+    pub fn process(&mut self, events: &mut EventIo) {
+        const FRAMES: usize = AUDIO_CONFIGURATION.max_frames_count as usize;
+
+        let mut main_in = [[0.0f32; FRAMES]; 2];
+        let mut sidechain = [0.0f32; FRAMES];
+        let mut main_out = [[0.0f32; FRAMES]; 2];
+
+        let mut input_ports = AudioPorts::with_capacity(3, 2);
+        let mut output_ports = AudioPorts::with_capacity(2, 1);
+        let [main_in_l, main_in_r] = &mut main_in;
+        let input_buffers = input_ports.with_input_buffers([
+            AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only(vec![
+                    InputChannel::constant(main_in_l),
+                    InputChannel::constant(main_in_r),
+                ]),
+                latency: 0,
+            },
+            AudioPortBuffer {
+                channels: AudioPortBufferType::f32_input_only(vec![InputChannel::constant(
+                    &mut sidechain,
+                )]),
+                latency: 0,
+            },
+        ]);
+
+        let mut output_buffers = output_ports.with_output_buffers([AudioPortBuffer {
+            channels: AudioPortBufferType::f32_output_only(main_out.iter_mut().map(|c| &mut c[..])),
+            latency: 0,
+        }]);
+
+        let (input_events, mut output_events) = events.split();
+        self.processor()
+            .process(
+                &input_buffers,
+                &mut output_buffers,
+                &input_events,
+                &mut output_events,
+                None,
+                None,
+            )
+            .expect("Failed to process");
+    }
+}
+
+impl Default for TestHost {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TestHost {
+    fn drop(&mut self) {
+        self.deactivate();
+    }
+}
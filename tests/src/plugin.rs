@@ -0,0 +1,1032 @@
+//! The fixture plugin, which implements the plugin side of every extension.
+//!
+//! The plugin is driven from the tests by sending it [`PluginCommand`]s, which it executes in its
+//! `on_main_thread` callback. Everything it receives from the host is recorded as a
+//! [`PluginEvent`], to be compared with what the host sent.
+
+use clack_extensions::audio_ports::*;
+use clack_extensions::audio_ports_config::*;
+use clack_extensions::context_menu::*;
+use clack_extensions::event_registry::*;
+use clack_extensions::extensible_audio_ports::*;
+use clack_extensions::gui::*;
+use clack_extensions::latency::*;
+use clack_extensions::log::*;
+use clack_extensions::note_name::*;
+use clack_extensions::note_ports::*;
+use clack_extensions::params::set::{ParamDef, ParamSet};
+use clack_extensions::params::*;
+#[cfg(unix)]
+use clack_extensions::posix_fd::*;
+use clack_extensions::render::*;
+use clack_extensions::state::*;
+use clack_extensions::tail::*;
+use clack_extensions::thread_check::*;
+use clack_extensions::thread_pool::*;
+use clack_extensions::timer::*;
+use clack_extensions::voice_info::*;
+use clack_plugin::events::event_types::ParamValueEvent;
+use clack_plugin::events::spaces::CoreEventSpace;
+use clack_plugin::prelude::*;
+use clack_plugin::utils::Cookie;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// The fixture plugin's gain parameter.
+pub const GAIN: ClapId = ClapId::new(1);
+/// The fixture plugin's mode parameter, an enumeration.
+pub const MODE: ClapId = ClapId::new(2);
+/// The labels of the fixture plugin's mode parameter.
+pub const MODE_LABELS: [&str; 3] = ["Clean", "Warm", "Crushed"];
+
+/// The ID of the fixture plugin.
+pub const PLUGIN_ID: &str = "org.rust-audio.clack.fixture";
+
+/// The period of the timer the fixture plugin registers when it is created.
+pub const TIMER_PERIOD_MS: u32 = 10;
+
+/// The voice capacity reported by the fixture plugin.
+pub const VOICE_CAPACITY: u32 = 16;
+
+/// The size of the fixture plugin's GUI when it is created.
+pub const GUI_SIZE: GuiSize = GuiSize {
+    width: 400,
+    height: 300,
+};
+
+/// The input audio ports declared by the fixture plugin.
+pub const AUDIO_INPUTS: [AudioPortInfo<'static>; 2] = [
+    AudioPortInfo {
+        id: ClapId::new(10),
+        name: b"Main In",
+        channel_count: 2,
+        flags: AudioPortFlags::IS_MAIN,
+        port_type: Some(AudioPortType::STEREO),
+        in_place_pair: Some(ClapId::new(20)),
+    },
+    AudioPortInfo {
+        id: ClapId::new(11),
+        name: b"Sidechain",
+        channel_count: 1,
+        flags: AudioPortFlags::empty(),
+        port_type: Some(AudioPortType::MONO),
+        in_place_pair: None,
+    },
+];
+
+/// The output audio ports declared by the fixture plugin.
+pub const AUDIO_OUTPUTS: [AudioPortInfo<'static>; 1] = [AudioPortInfo {
+    id: ClapId::new(20),
+    name: b"Main Out",
+    channel_count: 2,
+    flags: AudioPortFlags::IS_MAIN,
+    port_type: Some(AudioPortType::STEREO),
+    in_place_pair: Some(ClapId::new(10)),
+}];
+
+/// The ID of the first input audio port added to the fixture plugin by the host.
+pub const FIRST_ADDED_AUDIO_INPUT: ClapId = ClapId::new(100);
+
+/// The input note ports declared by the fixture plugin. It has no output note ports.
+pub const NOTE_INPUTS: [NotePortInfo<'static>; 1] = [NotePortInfo {
+    id: ClapId::new(30),
+    name: b"Notes",
+    supported_dialects: NoteDialects::CLAP.union(NoteDialects::MIDI),
+    preferred_dialect: Some(NoteDialect::Clap),
+}];
+
+/// The action ID of the "Reset" entry the fixture plugin adds to its context menus.
+pub const RESET_ACTION: ClapId = ClapId::new(40);
+
+/// The audio ports configurations declared by the fixture plugin.
+pub const AUDIO_PORTS_CONFIGS: [AudioPortsConfiguration<'static>; 2] = [
+    AudioPortsConfiguration {
+        id: ClapId::new(60),
+        name: b"Stereo",
+        input_port_count: 2,
+        output_port_count: 1,
+        main_input: Some(MainPortInfo {
+            channel_count: 2,
+            port_type: Some(AudioPortType::STEREO),
+        }),
+        main_output: Some(MainPortInfo {
+            channel_count: 2,
+            port_type: Some(AudioPortType::STEREO),
+        }),
+    },
+    AudioPortsConfiguration {
+        id: ClapId::new(61),
+        name: b"Mono, no sidechain",
+        input_port_count: 1,
+        output_port_count: 1,
+        main_input: Some(MainPortInfo {
+            channel_count: 1,
+            port_type: Some(AudioPortType::MONO),
+        }),
+        main_output: Some(MainPortInfo {
+            channel_count: 1,
+            port_type: None,
+        }),
+    },
+];
+
+/// The note names declared by the fixture plugin.
+pub const NOTE_NAMES: [NoteName<'static>; 2] = [
+    NoteName {
+        name: b"Kick",
+        port: Match::Specific(0),
+        channel: Match::All,
+        key: Match::Specific(36),
+    },
+    NoteName {
+        name: b"Snare",
+        port: Match::All,
+        channel: Match::Specific(9),
+        key: Match::Specific(38),
+    },
+];
+
+/// Whether the fixture plugin reports a hard realtime requirement.
+pub const HARD_REALTIME_REQUIREMENT: bool = true;
+
+/// An action the fixture plugin performs towards the host on its next `on_main_thread` callback.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PluginCommand {
+    /// Changes a parameter as if from the plugin's GUI, then requests a flush to report it.
+    SetParamFromGui(ClapId, f64),
+    /// Asks the host to rescan the parameters.
+    RescanParams(ParamRescanFlags),
+    /// Tells the host the plugin's state changed.
+    MarkDirty,
+    /// Changes the latency the plugin applies when it is next activated, and requests a restart.
+    SetLatency(u32),
+    /// Changes the plugin's tail length, which is reported in the next `process` call.
+    SetTail(u32),
+    /// Changes the plugin's voice count, and tells the host about it.
+    SetVoiceCount(u32),
+    /// Asks the host to rescan the audio ports.
+    RescanAudioPorts(RescanType),
+    /// Asks the host to rescan the note ports.
+    RescanNotePorts(NotePortRescanFlags),
+    /// Queries the note dialects supported by the host.
+    QueryNoteDialects,
+    /// Sends a message to the host's log.
+    Log(LogSeverity, String),
+    /// Asks the host to resize the GUI.
+    RequestGuiResize(GuiSize),
+    /// Tells the host the GUI was closed.
+    CloseGui {
+        /// Whether the GUI was destroyed.
+        was_destroyed: bool,
+    },
+    /// Unregisters the timer the plugin registered when it was created.
+    UnregisterTimer,
+    /// Collects the host's context menu items for the given target.
+    PopulateHostContextMenu(ContextMenuTarget),
+    /// Asks the host to perform the action of one of its context menu items.
+    PerformHostContextMenuAction(ContextMenuTarget, ClapId),
+    /// Asks the host to show its context menu for the given target, if it can.
+    PopupHostContextMenu {
+        /// The target of the menu.
+        target: ContextMenuTarget,
+        /// The index of the screen to show the menu on.
+        screen_index: i32,
+        /// The horizontal position of the menu.
+        x: i32,
+        /// The vertical position of the menu.
+        y: i32,
+    },
+    /// Asks the host to rescan the audio ports configurations.
+    RescanAudioPortsConfigs,
+    /// Tells the host the note names changed.
+    ChangeNoteNames,
+    /// Asks the host whether the main thread and another thread are its main or audio thread.
+    CheckThreads,
+    /// Queries the ID the host gave to the core event space.
+    QueryCoreEventSpace,
+    /// Requests the given number of tasks to be run in the host's thread pool, during the next
+    /// `process` call.
+    RequestTasks(u32),
+    /// Registers a file descriptor into the host's event reactor.
+    #[cfg(unix)]
+    RegisterFd(RawFd, FdFlags),
+    /// Changes the events a registered file descriptor is watched for.
+    #[cfg(unix)]
+    ModifyFd(RawFd, FdFlags),
+    /// Removes a file descriptor from the host's event reactor.
+    #[cfg(unix)]
+    UnregisterFd(RawFd),
+}
+
+/// Something the fixture plugin received from the host.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PluginEvent {
+    /// The host accepted the timer the plugin registered when it was created.
+    TimerRegistered(TimerId),
+    /// The host fired a timer.
+    TimerFired(TimerId),
+    /// The plugin was activated with the given sample rate.
+    Activated(f64),
+    /// The plugin queried the note dialects supported by the host.
+    HostNoteDialects(NoteDialects),
+    /// The plugin loaded a state, with the given parameter values.
+    StateLoaded(Vec<(ClapId, f64)>),
+    /// The host created the GUI.
+    GuiCreated {
+        /// The windowing API.
+        api: String,
+        /// Whether the GUI is floating.
+        is_floating: bool,
+    },
+    /// The host set the GUI's scale.
+    GuiScaleSet(f64),
+    /// The host set the GUI's size.
+    GuiSizeSet(GuiSize),
+    /// The host suggested a title for the GUI window.
+    GuiTitleSuggested(String),
+    /// The host showed the GUI.
+    GuiShown,
+    /// The host hid the GUI.
+    GuiHidden,
+    /// The host destroyed the GUI.
+    GuiDestroyed,
+    /// The host asked for the plugin's context menu items for the given target.
+    ContextMenuPopulated(ContextMenuTarget),
+    /// The host performed the action of one of the plugin's context menu items.
+    ContextMenuPerformed(ContextMenuTarget, ClapId),
+    /// The plugin collected the host's context menu items.
+    HostContextMenu(MenuModel),
+    /// The host added an audio port.
+    AudioPortAdded {
+        /// Whether the port is an input port.
+        is_input: bool,
+        /// The number of channels of the port.
+        channel_count: u32,
+        /// The type of the port, if any.
+        port_type: Option<String>,
+    },
+    /// The host removed an audio port.
+    AudioPortRemoved {
+        /// Whether the port is an input port.
+        is_input: bool,
+        /// The ID of the port.
+        port_id: ClapId,
+    },
+    /// The host selected an audio ports configuration.
+    AudioPortsConfigSelected(ClapId),
+    /// The host set the render mode.
+    RenderModeSet(RenderMode),
+    /// The host told whether a thread of the plugin is its main thread or audio thread.
+    ThreadChecked {
+        /// Whether the plugin asked from its main thread.
+        from_main_thread: bool,
+        /// Whether the host considers the thread to be its main thread.
+        is_main_thread: Option<bool>,
+        /// Whether the host considers the thread to be its audio thread.
+        is_audio_thread: Option<bool>,
+    },
+    /// The plugin queried the ID of the core event space.
+    CoreEventSpaceQueried(Option<u16>),
+    /// The host ran the tasks the plugin requested, with the given indices.
+    TasksExecuted(Vec<u32>),
+    /// The host reported events on a registered file descriptor.
+    #[cfg(unix)]
+    FdEvent(RawFd, FdFlags),
+}
+
+thread_local! {
+    static COMMANDS: RefCell<VecDeque<PluginCommand>> = const { RefCell::new(VecDeque::new()) };
+    static EVENTS: RefCell<Vec<PluginEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Queues a command, to be run by the fixture plugin on its next `on_main_thread` callback.
+///
+/// The fixture plugin must live on the current thread.
+pub fn queue_command(command: PluginCommand) {
+    COMMANDS.with(|c| c.borrow_mut().push_back(command));
+}
+
+/// Returns everything the fixture plugin living on the current thread received so far, and
+/// clears it.
+pub fn take_plugin_events() -> Vec<PluginEvent> {
+    EVENTS.with(|e| e.take())
+}
+
+fn record(event: PluginEvent) {
+    EVENTS.with(|e| e.borrow_mut().push(event));
+}
+
+/// A plugin implementing the plugin side of every extension covered by the integration tests.
+pub struct FixturePlugin;
+
+impl Plugin for FixturePlugin {
+    type AudioProcessor<'a> = FixtureAudioProcessor<'a>;
+    type Shared<'a> = FixtureShared;
+    type MainThread<'a> = FixtureMainThread<'a>;
+
+    fn declare_extensions(builder: &mut PluginExtensions<Self>, _shared: Option<&FixtureShared>) {
+        builder
+            .register::<PluginAudioPorts>()
+            .register::<PluginAudioPortsConfig>()
+            .register::<PluginContextMenu>()
+            .register::<PluginExtensibleAudioPorts>()
+            .register::<PluginGui>()
+            .register::<PluginLatency>()
+            .register::<PluginNoteName>()
+            .register::<PluginNotePorts>()
+            .register::<PluginParams>()
+            .register::<PluginRender>()
+            .register::<PluginState>()
+            .register::<PluginTail>()
+            .register::<PluginThreadPool>()
+            .register::<PluginTimer>()
+            .register::<PluginVoiceInfo>();
+
+        // This extension is only available on Unix.
+        #[cfg(unix)]
+        builder.register::<PluginPosixFd>();
+    }
+}
+
+impl DefaultPluginFactory for FixturePlugin {
+    fn get_descriptor() -> PluginDescriptor {
+        PluginDescriptor::new(PLUGIN_ID, "Fixture")
+    }
+
+    fn new_shared(host: HostSharedHandle<'_>) -> Result<FixtureShared, PluginError> {
+        Ok(FixtureShared {
+            host_log: host.get_extension(),
+            host_tail: host.get_extension(),
+            params: ParamSet::new()
+                .with_param(GAIN, ParamDef::new("Gain", 0.0, 1.0, 1.0))
+                .with_param(
+                    MODE,
+                    ParamDef::enumeration("Mode", &MODE_LABELS).with_module("Character"),
+                ),
+            gui_changes: Mutex::new(Vec::new()),
+            latency: AtomicU32::new(0),
+            pending_latency: AtomicU32::new(0),
+            tail: AtomicU32::new(0),
+            tail_changed: AtomicBool::new(false),
+            requested_tasks: AtomicU32::new(0),
+            executed_tasks: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn new_main_thread<'a>(
+        mut host: HostMainThreadHandle<'a>,
+        shared: &'a FixtureShared,
+    ) -> Result<FixtureMainThread<'a>, PluginError> {
+        let host_timer: Option<HostTimer> = host.shared().get_extension();
+
+        let timer = host_timer
+            .as_ref()
+            .and_then(|timer| timer.register_timer(&mut host, TIMER_PERIOD_MS).ok());
+
+        if let Some(timer) = timer {
+            record(PluginEvent::TimerRegistered(timer));
+        }
+
+        Ok(FixtureMainThread {
+            host,
+            shared,
+            host_timer,
+            timer,
+            voice_count: 0,
+            gui_size: None,
+            added_audio_inputs: Vec::new(),
+            next_audio_port_id: FIRST_ADDED_AUDIO_INPUT.get(),
+        })
+    }
+}
+
+/// The fixture plugin's thread-safe state.
+pub struct FixtureShared {
+    host_log: Option<HostLog>,
+    host_tail: Option<HostTail>,
+    params: ParamSet,
+    /// Parameter changes from the GUI, to be reported to the host in the next flush.
+    gui_changes: Mutex<Vec<(ClapId, f64)>>,
+    latency: AtomicU32,
+    pending_latency: AtomicU32,
+    tail: AtomicU32,
+    tail_changed: AtomicBool,
+    /// The number of tasks to request from the host's thread pool in the next `process` call.
+    requested_tasks: AtomicU32,
+    /// The indices of the tasks the host's thread pool ran so far.
+    executed_tasks: Mutex<Vec<u32>>,
+}
+
+impl PluginShared<'_> for FixtureShared {}
+
+impl PluginThreadPoolImpl for FixtureShared {
+    fn exec(&self, task_index: u32) {
+        self.executed_tasks.lock().unwrap().push(task_index);
+    }
+}
+
+impl FixtureShared {
+    fn flush(&self, input: &InputEvents, output: &mut OutputEvents) {
+        self.params.flush(input);
+
+        for (param_id, value) in self.gui_changes.lock().unwrap().drain(..) {
+            let event =
+                ParamValueEvent::new(0, param_id, Pckn::match_all(), value, Cookie::empty());
+            let _ = output.try_push(event);
+        }
+    }
+
+    fn log(&self, host: &HostSharedHandle, severity: LogSeverity, message: &str) {
+        if let Some(log) = &self.host_log {
+            log.log(host, severity, &CString::new(message).unwrap());
+        }
+    }
+}
+
+/// The fixture plugin's main thread state.
+pub struct FixtureMainThread<'a> {
+    host: HostMainThreadHandle<'a>,
+    shared: &'a FixtureShared,
+    host_timer: Option<HostTimer>,
+    timer: Option<TimerId>,
+    voice_count: u32,
+    gui_size: Option<GuiSize>,
+    /// The input audio ports added by the host, after the declared ones.
+    added_audio_inputs: Vec<AudioPortInfo<'static>>,
+    next_audio_port_id: u32,
+}
+
+impl FixtureMainThread<'_> {
+    fn run(&mut self, command: PluginCommand) {
+        let host = self.host.shared();
+
+        match command {
+            PluginCommand::SetParamFromGui(param_id, value) => {
+                self.shared.params.set_value(param_id, value);
+                self.shared
+                    .gui_changes
+                    .lock()
+                    .unwrap()
+                    .push((param_id, value));
+
+                if let Some(params) = host.get_extension::<HostParams>() {
+                    params.request_flush(&host);
+                }
+            }
+            PluginCommand::RescanParams(flags) => {
+                if let Some(params) = host.get_extension::<HostParams>() {
+                    params.rescan(&mut self.host, flags);
+                }
+            }
+            PluginCommand::MarkDirty => {
+                if let Some(mut state) = host.get_extension::<HostState>() {
+                    state.mark_dirty(&self.host);
+                }
+            }
+            PluginCommand::SetLatency(latency) => {
+                self.shared.pending_latency.store(latency, Ordering::SeqCst);
+                host.request_restart();
+            }
+            PluginCommand::SetTail(tail) => {
+                self.shared.tail.store(tail, Ordering::SeqCst);
+                self.shared.tail_changed.store(true, Ordering::SeqCst);
+            }
+            PluginCommand::SetVoiceCount(voice_count) => {
+                self.voice_count = voice_count;
+
+                if let Some(voice_info) = host.get_extension::<HostVoiceInfo>() {
+                    voice_info.changed(&mut self.host);
+                }
+            }
+            PluginCommand::RescanAudioPorts(flags) => {
+                if let Some(ports) = host.get_extension::<HostAudioPorts>() {
+                    ports.rescan(&mut self.host, flags);
+                }
+            }
+            PluginCommand::RescanNotePorts(flags) => {
+                if let Some(ports) = host.get_extension::<HostNotePorts>() {
+                    ports.rescan(&mut self.host, flags);
+                }
+            }
+            PluginCommand::QueryNoteDialects => {
+                if let Some(ports) = host.get_extension::<HostNotePorts>() {
+                    record(PluginEvent::HostNoteDialects(
+                        ports.supported_dialects(&self.host),
+                    ));
+                }
+            }
+            PluginCommand::Log(severity, message) => self.shared.log(&host, severity, &message),
+            PluginCommand::RequestGuiResize(size) => {
+                if let Some(gui) = host.get_extension::<HostGui>() {
+                    let _ = gui.request_resize(&host, size.width, size.height);
+                }
+            }
+            PluginCommand::CloseGui { was_destroyed } => {
+                if let Some(gui) = host.get_extension::<HostGui>() {
+                    gui.closed(&host, was_destroyed);
+                }
+            }
+            PluginCommand::UnregisterTimer => {
+                if let (Some(host_timer), Some(timer)) = (&self.host_timer, self.timer.take()) {
+                    let _ = host_timer.unregister_timer(&mut self.host, timer);
+                }
+            }
+            PluginCommand::PopulateHostContextMenu(target) => {
+                if let Some(context_menu) = host.get_extension::<HostContextMenu>() {
+                    let mut menu = MenuModel::new();
+                    context_menu.populate(&mut self.host, target, &mut menu);
+                    record(PluginEvent::HostContextMenu(menu));
+                }
+            }
+            PluginCommand::PerformHostContextMenuAction(target, action_id) => {
+                if let Some(context_menu) = host.get_extension::<HostContextMenu>() {
+                    context_menu.perform(&mut self.host, target, action_id);
+                }
+            }
+            PluginCommand::PopupHostContextMenu {
+                target,
+                screen_index,
+                x,
+                y,
+            } => {
+                if let Some(context_menu) = host.get_extension::<HostContextMenu>() {
+                    if context_menu.can_popup(&mut self.host) {
+                        context_menu.popup(&mut self.host, target, screen_index, x, y);
+                    }
+                }
+            }
+            PluginCommand::RescanAudioPortsConfigs => {
+                if let Some(configs) = host.get_extension::<HostAudioPortsConfig>() {
+                    configs.rescan(&mut self.host);
+                }
+            }
+            PluginCommand::ChangeNoteNames => {
+                if let Some(note_name) = host.get_extension::<HostNoteName>() {
+                    note_name.changed(&mut self.host);
+                }
+            }
+            PluginCommand::CheckThreads => {
+                if let Some(thread_check) = host.get_extension::<HostThreadCheck>() {
+                    let check = |from_main_thread| PluginEvent::ThreadChecked {
+                        from_main_thread,
+                        is_main_thread: thread_check.is_main_thread(&host),
+                        is_audio_thread: thread_check.is_audio_thread(&host),
+                    };
+
+                    record(check(true));
+                    record(thread::scope(|s| s.spawn(|| check(false)).join().unwrap()));
+                }
+            }
+            PluginCommand::QueryCoreEventSpace => {
+                if let Some(registry) = host.get_extension::<HostEventRegistry>() {
+                    let id = registry.query::<CoreEventSpace>(&self.host);
+                    record(PluginEvent::CoreEventSpaceQueried(id.map(|id| id.id())));
+                }
+            }
+            PluginCommand::RequestTasks(task_count) => {
+                self.shared
+                    .requested_tasks
+                    .store(task_count, Ordering::SeqCst);
+            }
+            #[cfg(unix)]
+            PluginCommand::RegisterFd(fd, flags) => {
+                if let Some(posix_fd) = host.get_extension::<HostPosixFd>() {
+                    let _ = posix_fd.register_fd(&mut self.host, fd, flags);
+                }
+            }
+            #[cfg(unix)]
+            PluginCommand::ModifyFd(fd, flags) => {
+                if let Some(posix_fd) = host.get_extension::<HostPosixFd>() {
+                    let _ = posix_fd.modify_fd(&mut self.host, fd, flags);
+                }
+            }
+            #[cfg(unix)]
+            PluginCommand::UnregisterFd(fd) => {
+                if let Some(posix_fd) = host.get_extension::<HostPosixFd>() {
+                    let _ = posix_fd.unregister_fd(&mut self.host, fd);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> PluginMainThread<'a, FixtureShared> for FixtureMainThread<'a> {
+    fn on_main_thread(&mut self) {
+        while let Some(command) = COMMANDS.with(|c| c.borrow_mut().pop_front()) {
+            self.run(command);
+        }
+    }
+}
+
+impl PluginMainThreadParams for FixtureMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        self.shared.params.count()
+    }
+
+    fn get_info(&mut self, param_index: u32, info: &mut ParamInfoWriter) {
+        self.shared.params.get_info(param_index, info)
+    }
+
+    fn get_value(&mut self, param_id: ClapId) -> Option<f64> {
+        self.shared.params.value(param_id)
+    }
+
+    fn value_to_text(
+        &mut self,
+        param_id: ClapId,
+        value: f64,
+        writer: &mut ParamDisplayWriter,
+    ) -> std::fmt::Result {
+        self.shared.params.value_to_text(param_id, value, writer)
+    }
+
+    fn text_to_value(&mut self, param_id: ClapId, text: &CStr) -> Option<f64> {
+        self.shared.params.text_to_value(param_id, text)
+    }
+
+    fn flush(&mut self, input: &InputEvents, output: &mut OutputEvents) {
+        self.shared.flush(input, output)
+    }
+}
+
+impl PluginStateImpl for FixtureMainThread<'_> {
+    fn save(&mut self, output: &mut OutputStream) -> Result<(), PluginError> {
+        for param_id in [GAIN, MODE] {
+            let value = self.shared.params.value(param_id).unwrap_or_default();
+            output.write_all(&param_id.get().to_le_bytes())?;
+            output.write_all(&value.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    fn load(&mut self, input: &mut InputStream) -> Result<(), PluginError> {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+
+        let mut loaded = Vec::new();
+        for entry in data.chunks_exact(12) {
+            let param_id = u32::from_le_bytes(entry[..4].try_into().unwrap());
+            let value = f64::from_le_bytes(entry[4..].try_into().unwrap());
+
+            let param_id = ClapId::from_raw(param_id).ok_or(PluginError::Message("Bad ID"))?;
+            self.shared.params.set_value(param_id, value);
+            loaded.push((param_id, value));
+        }
+
+        record(PluginEvent::StateLoaded(loaded));
+        Ok(())
+    }
+}
+
+impl PluginLatencyImpl for FixtureMainThread<'_> {
+    fn get(&mut self) -> u32 {
+        self.shared.latency.load(Ordering::SeqCst)
+    }
+}
+
+impl PluginTimerImpl for FixtureMainThread<'_> {
+    fn on_timer(&mut self, timer_id: TimerId) {
+        record(PluginEvent::TimerFired(timer_id));
+    }
+}
+
+impl PluginVoiceInfoImpl for FixtureMainThread<'_> {
+    fn get(&self) -> Option<VoiceInfo> {
+        Some(VoiceInfo {
+            voice_count: self.voice_count,
+            voice_capacity: VOICE_CAPACITY,
+            flags: VoiceInfoFlags::SUPPORTS_OVERLAPPING_NOTES,
+        })
+    }
+}
+
+impl PluginAudioPortsImpl for FixtureMainThread<'_> {
+    fn count(&mut self, is_input: bool) -> u32 {
+        if is_input {
+            (AUDIO_INPUTS.len() + self.added_audio_inputs.len()) as u32
+        } else {
+            AUDIO_OUTPUTS.len() as u32
+        }
+    }
+
+    fn get(&mut self, index: u32, is_input: bool, writer: &mut AudioPortInfoWriter) {
+        let port = if is_input {
+            AUDIO_INPUTS
+                .iter()
+                .chain(&self.added_audio_inputs)
+                .nth(index as usize)
+        } else {
+            AUDIO_OUTPUTS.get(index as usize)
+        };
+
+        if let Some(port) = port {
+            writer.set(port);
+        }
+    }
+}
+
+impl PluginExtensibleAudioPortsImpl for FixtureMainThread<'_> {
+    fn add_port(
+        &mut self,
+        is_input: bool,
+        channel_count: u32,
+        port_type: Option<AudioPortType>,
+        _details: PortDetails,
+    ) -> Result<ClapId, PluginError> {
+        record(PluginEvent::AudioPortAdded {
+            is_input,
+            channel_count,
+            port_type: port_type.map(|t| t.0.to_string_lossy().into_owned()),
+        });
+
+        let default_type = AudioPortType::from_channel_count(channel_count);
+        if !is_input || port_type.is_some_and(|t| Some(t) != default_type) {
+            return Err(PluginError::Message(
+                "Only plain mono or stereo inputs can be added",
+            ));
+        }
+
+        let id = ClapId::new(self.next_audio_port_id);
+        self.next_audio_port_id += 1;
+
+        self.added_audio_inputs.push(AudioPortInfo {
+            id,
+            name: b"Added In",
+            channel_count,
+            flags: AudioPortFlags::empty(),
+            port_type: default_type,
+            in_place_pair: None,
+        });
+
+        Ok(id)
+    }
+
+    fn remove_port(&mut self, is_input: bool, port_id: ClapId) -> Result<(), PluginError> {
+        record(PluginEvent::AudioPortRemoved { is_input, port_id });
+
+        let index = self
+            .added_audio_inputs
+            .iter()
+            .position(|port| is_input && port.id == port_id)
+            .ok_or(PluginError::Message("Only added ports can be removed"))?;
+
+        self.added_audio_inputs.remove(index);
+        Ok(())
+    }
+}
+
+impl PluginNotePortsImpl for FixtureMainThread<'_> {
+    fn count(&mut self, is_input: bool) -> u32 {
+        if is_input {
+            NOTE_INPUTS.len() as u32
+        } else {
+            0
+        }
+    }
+
+    fn get(&mut self, index: u32, is_input: bool, writer: &mut NotePortInfoWriter) {
+        if let Some(port) = NOTE_INPUTS.get(index as usize).filter(|_| is_input) {
+            writer.set(port);
+        }
+    }
+}
+
+impl PluginGuiImpl for FixtureMainThread<'_> {
+    fn is_api_supported(&mut self, configuration: GuiConfiguration) -> bool {
+        // This GUI has no window to embed.
+        configuration.is_floating
+    }
+
+    fn get_preferred_api(&mut self) -> Option<GuiConfiguration<'_>> {
+        Some(GuiConfiguration {
+            api_type: GuiApiType::default_for_current_platform()?,
+            is_floating: true,
+        })
+    }
+
+    fn create(&mut self, configuration: GuiConfiguration) -> Result<(), PluginError> {
+        if !configuration.is_floating {
+            return Err(PluginError::Message("Only floating GUIs are supported"));
+        }
+
+        self.gui_size = Some(GUI_SIZE);
+        record(PluginEvent::GuiCreated {
+            api: configuration.api_type.0.to_string_lossy().into_owned(),
+            is_floating: configuration.is_floating,
+        });
+
+        Ok(())
+    }
+
+    fn destroy(&mut self) {
+        self.gui_size = None;
+        record(PluginEvent::GuiDestroyed);
+    }
+
+    fn set_scale(&mut self, scale: f64) -> Result<(), PluginError> {
+        record(PluginEvent::GuiScaleSet(scale));
+        Ok(())
+    }
+
+    fn get_size(&mut self) -> Option<GuiSize> {
+        self.gui_size
+    }
+
+    fn can_resize(&mut self) -> bool {
+        true
+    }
+
+    fn set_size(&mut self, size: GuiSize) -> Result<(), PluginError> {
+        self.gui_size = Some(size);
+        record(PluginEvent::GuiSizeSet(size));
+        Ok(())
+    }
+
+    fn set_parent(&mut self, _window: Window) -> Result<(), PluginError> {
+        Err(PluginError::Message("Only floating GUIs are supported"))
+    }
+
+    fn set_transient(&mut self, _window: Window) -> Result<(), PluginError> {
+        Err(PluginError::Message("Only floating GUIs are supported"))
+    }
+
+    fn suggest_title(&mut self, title: &str) {
+        record(PluginEvent::GuiTitleSuggested(title.to_owned()));
+    }
+
+    fn show(&mut self) -> Result<(), PluginError> {
+        record(PluginEvent::GuiShown);
+        Ok(())
+    }
+
+    fn hide(&mut self) -> Result<(), PluginError> {
+        record(PluginEvent::GuiHidden);
+        Ok(())
+    }
+}
+
+impl PluginContextMenuImpl for FixtureMainThread<'_> {
+    fn populate(
+        &mut self,
+        target: ContextMenuTarget,
+        builder: &mut ContextMenuBuilder,
+    ) -> Result<(), PluginError> {
+        record(PluginEvent::ContextMenuPopulated(target));
+
+        builder.add(ContextMenuItem::Title {
+            title: CStr::from_bytes_with_nul(b"Fixture\0").unwrap(),
+            is_enabled: true,
+        });
+        builder.add(ContextMenuItem::Entry {
+            label: CStr::from_bytes_with_nul(b"Reset\0").unwrap(),
+            is_enabled: true,
+            action_id: RESET_ACTION,
+        });
+
+        Ok(())
+    }
+
+    fn perform(&mut self, target: ContextMenuTarget, action_id: ClapId) -> Result<(), PluginError> {
+        if action_id != RESET_ACTION {
+            return Err(PluginError::Message("Unknown context menu action"));
+        }
+
+        record(PluginEvent::ContextMenuPerformed(target, action_id));
+        Ok(())
+    }
+}
+
+impl PluginAudioPortsConfigImpl for FixtureMainThread<'_> {
+    fn count(&mut self) -> u32 {
+        AUDIO_PORTS_CONFIGS.len() as u32
+    }
+
+    fn get(&mut self, index: u32, writer: &mut AudioPortConfigWriter) {
+        if let Some(config) = AUDIO_PORTS_CONFIGS.get(index as usize) {
+            writer.write(config);
+        }
+    }
+
+    fn select(&mut self, config_id: ClapId) -> Result<(), PluginError> {
+        if !AUDIO_PORTS_CONFIGS
+            .iter()
+            .any(|config| config.id == config_id)
+        {
+            return Err(PluginError::Message("Unknown audio ports configuration"));
+        }
+
+        record(PluginEvent::AudioPortsConfigSelected(config_id));
+        Ok(())
+    }
+}
+
+impl PluginNoteNameImpl for FixtureMainThread<'_> {
+    fn count(&mut self) -> usize {
+        NOTE_NAMES.len()
+    }
+
+    fn get(&mut self, index: usize, writer: &mut NoteNameWriter) {
+        if let Some(note_name) = NOTE_NAMES.get(index) {
+            writer.write(note_name);
+        }
+    }
+}
+
+impl PluginRenderImpl for FixtureMainThread<'_> {
+    fn has_hard_realtime_requirement(&self) -> bool {
+        HARD_REALTIME_REQUIREMENT
+    }
+
+    fn set(&mut self, mode: RenderMode) -> Result<(), PluginError> {
+        record(PluginEvent::RenderModeSet(mode));
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+impl PluginPosixFdImpl for FixtureMainThread<'_> {
+    fn on_fd(&mut self, fd: RawFd, flags: FdFlags) {
+        record(PluginEvent::FdEvent(fd, flags));
+    }
+}
+
+/// The fixture plugin's audio processor.
+pub struct FixtureAudioProcessor<'a> {
+    host: HostAudioProcessorHandle<'a>,
+    shared: &'a FixtureShared,
+}
+
+impl<'a> PluginAudioProcessor<'a, FixtureShared, FixtureMainThread<'a>>
+    for FixtureAudioProcessor<'a>
+{
+    fn activate(
+        host: HostAudioProcessorHandle<'a>,
+        main_thread: &mut FixtureMainThread<'a>,
+        shared: &'a FixtureShared,
+        audio_config: PluginAudioConfiguration,
+    ) -> Result<Self, PluginError> {
+        let latency = shared.pending_latency.load(Ordering::SeqCst);
+        if shared.latency.swap(latency, Ordering::SeqCst) != latency {
+            if let Some(host_latency) = main_thread.host.shared().get_extension::<HostLatency>() {
+                host_latency.changed(&mut main_thread.host);
+            }
+        }
+
+        record(PluginEvent::Activated(audio_config.sample_rate));
+        shared.log(
+            &host.shared(),
+            LogSeverity::Info,
+            &format!("Activated at {} Hz", audio_config.sample_rate),
+        );
+
+        Ok(Self { host, shared })
+    }
+
+    fn process(
+        &mut self,
+        _process: Process,
+        _audio: Audio,
+        events: Events,
+    ) -> Result<ProcessStatus, PluginError> {
+        self.shared.flush(events.input, events.output);
+
+        if self.shared.tail_changed.swap(false, Ordering::SeqCst) {
+            if let Some(tail) = &self.shared.host_tail {
+                tail.changed(&mut self.host);
+            }
+        }
+
+        let task_count = self.shared.requested_tasks.swap(0, Ordering::SeqCst);
+        if task_count > 0 {
+            let thread_pool = self.host.shared().get_extension::<HostThreadPool>();
+            if let Some(thread_pool) = thread_pool {
+                if thread_pool.request_exec(&mut self.host, task_count).is_ok() {
+                    let executed = std::mem::take(&mut *self.shared.executed_tasks.lock().unwrap());
+                    record(PluginEvent::TasksExecuted(executed));
+                }
+            }
+        }
+
+        Ok(ProcessStatus::Continue)
+    }
+}
+
+impl PluginAudioProcessorParams for FixtureAudioProcessor<'_> {
+    fn flush(&mut self, input: &InputEvents, output: &mut OutputEvents) {
+        self.shared.flush(input, output)
+    }
+}
+
+impl PluginTailImpl for FixtureAudioProcessor<'_> {
+    fn get(&self) -> TailLength {
+        TailLength::from_raw(self.shared.tail.load(Ordering::SeqCst))
+    }
+}
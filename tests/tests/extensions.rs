@@ -0,0 +1,1086 @@
+//! Drives the fixture plugin from the fixture host through every extension.
+//!
+//! Each module covers one extension. See the crate documentation for how to add one.
+
+use clack_extensions::params::*;
+use clack_host::events::event_types::ParamValueEvent;
+use clack_host::events::io::OwnedEvent;
+use clack_host::events::Pckn;
+use clack_host::prelude::*;
+use clack_host::utils::Cookie;
+use clack_tests::host::HostEvent;
+use clack_tests::plugin::*;
+use clack_tests::TestHost;
+
+fn param_value(param_id: ClapId, value: f64) -> EventIo {
+    let mut events = EventIo::new();
+    events.push_input(&ParamValueEvent::new(
+        0,
+        param_id,
+        Pckn::match_all(),
+        value,
+        Cookie::empty(),
+    ));
+    events
+}
+
+fn param_values(events: &mut EventIo) -> Vec<(ClapId, f64)> {
+    events
+        .drain_output()
+        .filter_map(|e| match e {
+            OwnedEvent::ParamValue(e) => Some((e.param_id().unwrap(), e.value())),
+            _ => None,
+        })
+        .collect()
+}
+
+mod params {
+    use super::*;
+    use clack_extensions::params::info_snapshot::{ParamInfoEntry, ParamInfoSnapshot};
+
+    #[test]
+    fn plugin_to_host_info() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+
+        let snapshot = ParamInfoSnapshot::capture(&params, &mut session.plugin());
+        assert_eq!(
+            snapshot.params(),
+            [
+                ParamInfoEntry {
+                    id: GAIN,
+                    flags: ParamInfoFlags::IS_AUTOMATABLE,
+                    name: "Gain".into(),
+                    module: "".into(),
+                    min_value: 0.0,
+                    max_value: 1.0,
+                    default_value: 1.0,
+                },
+                ParamInfoEntry {
+                    id: MODE,
+                    flags: ParamInfoFlags::IS_AUTOMATABLE
+                        | ParamInfoFlags::IS_STEPPED
+                        | ParamInfoFlags::IS_ENUM,
+                    name: "Mode".into(),
+                    module: "Character".into(),
+                    min_value: 0.0,
+                    max_value: 2.0,
+                    default_value: 0.0,
+                }
+            ],
+            "The host didn't receive the parameter info the plugin declared"
+        );
+
+        let mut plugin = session.plugin();
+        assert_eq!(params.get_value(&mut plugin, GAIN), Some(1.0));
+
+        let mut buffer = [std::mem::MaybeUninit::uninit(); 64];
+        let text = params.value_to_text(&mut plugin, MODE, 1.0, &mut buffer);
+        assert_eq!(text.unwrap(), MODE_LABELS[1].as_bytes());
+    }
+
+    #[test]
+    fn host_to_plugin_text() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+
+        let text = std::ffi::CString::new(MODE_LABELS[2]).unwrap();
+        assert_eq!(
+            params.text_to_value(&mut session.plugin(), MODE, &text),
+            Some(2.0)
+        );
+    }
+
+    #[test]
+    fn host_to_plugin_flush_inactive() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+
+        params.flush(
+            &mut session.plugin(),
+            &param_value(GAIN, 0.25).input(),
+            &mut OutputEvents::void(),
+        );
+
+        assert_eq!(
+            params.get_value(&mut session.plugin(), GAIN),
+            Some(0.25),
+            "The plugin didn't apply the value flushed by the host while inactive"
+        );
+    }
+
+    #[test]
+    fn host_to_plugin_flush_active() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+        session.activate();
+
+        params.flush_active(
+            &mut session.processor().plugin_handle(),
+            &param_value(GAIN, 0.5).input(),
+            &mut OutputEvents::void(),
+        );
+
+        assert_eq!(
+            params.get_value(&mut session.plugin(), GAIN),
+            Some(0.5),
+            "The plugin didn't apply the value flushed by the host while active"
+        );
+    }
+
+    #[test]
+    fn host_to_plugin_process() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+        session.activate();
+
+        session.process(&mut param_value(MODE, 2.0));
+
+        assert_eq!(
+            params.get_value(&mut session.plugin(), MODE),
+            Some(2.0),
+            "The plugin didn't apply the value sent by the host while processing"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host_gui_change() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+
+        session.send(PluginCommand::SetParamFromGui(GAIN, 0.75));
+        assert_eq!(
+            session.take_host_events(),
+            [HostEvent::ParamFlushRequested],
+            "The plugin's flush request didn't reach the host"
+        );
+
+        let mut events = EventIo::new();
+        let (input, mut output) = events.split();
+        params.flush(&mut session.plugin(), &input, &mut output);
+
+        assert_eq!(
+            param_values(&mut events),
+            [(GAIN, 0.75)],
+            "The host didn't receive the value changed from the plugin's GUI"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host_rescan() {
+        let mut session = TestHost::new();
+
+        for flags in [ParamRescanFlags::VALUES, ParamRescanFlags::ALL] {
+            session.send(PluginCommand::RescanParams(flags));
+            assert_eq!(
+                session.take_host_events(),
+                [HostEvent::ParamsRescanned(flags)]
+            );
+        }
+    }
+}
+
+mod state {
+    use super::*;
+    use clack_extensions::state::PluginState;
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let params: PluginParams = session.extension();
+        let state: PluginState = session.extension();
+
+        params.flush(
+            &mut session.plugin(),
+            &param_value(GAIN, 0.125).input(),
+            &mut OutputEvents::void(),
+        );
+
+        let mut saved = Vec::new();
+        state.save(&mut session.plugin(), &mut saved).unwrap();
+
+        params.flush(
+            &mut session.plugin(),
+            &param_value(GAIN, 0.875).input(),
+            &mut OutputEvents::void(),
+        );
+
+        state.load(&mut session.plugin(), &mut &saved[..]).unwrap();
+
+        assert_eq!(
+            take_plugin_events(),
+            [PluginEvent::StateLoaded(vec![(GAIN, 0.125), (MODE, 0.0)])],
+            "The plugin didn't load the state it saved"
+        );
+        assert_eq!(params.get_value(&mut session.plugin(), GAIN), Some(0.125));
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::MarkDirty);
+        assert_eq!(session.take_host_events(), [HostEvent::StateMarkedDirty]);
+    }
+}
+
+mod latency {
+    use super::*;
+    use clack_extensions::latency::PluginLatency;
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+        let latency: PluginLatency = session.extension();
+        session.activate();
+        session.take_host_events();
+        assert_eq!(latency.get(&mut session.plugin()), 0);
+
+        session.send(PluginCommand::SetLatency(64));
+        assert_eq!(
+            session.take_host_events(),
+            [HostEvent::RestartRequested],
+            "The plugin's restart request didn't reach the host"
+        );
+
+        session.deactivate();
+        session.activate();
+
+        let events = session.take_host_events();
+        assert!(
+            events.contains(&HostEvent::LatencyChanged),
+            "The plugin's latency change didn't reach the host: {events:?}"
+        );
+        assert_eq!(
+            latency.get(&mut session.plugin()),
+            64,
+            "The host didn't receive the plugin's new latency"
+        );
+    }
+}
+
+mod tail {
+    use super::*;
+    use clack_extensions::tail::{PluginTail, TailLength};
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+        let tail: PluginTail = session.extension();
+        session.activate();
+        session.take_host_events();
+
+        assert_eq!(
+            tail.get(&session.processor().plugin_handle()),
+            TailLength::Finite(0)
+        );
+
+        session.send(PluginCommand::SetTail(480));
+        session.process(&mut EventIo::new());
+
+        assert_eq!(
+            session.take_host_events(),
+            [HostEvent::TailChanged],
+            "The plugin's tail change didn't reach the host"
+        );
+        assert_eq!(
+            tail.get(&session.processor().plugin_handle()),
+            TailLength::Finite(480),
+            "The host didn't receive the plugin's new tail length"
+        );
+
+        session.send(PluginCommand::SetTail(u32::MAX));
+        assert_eq!(
+            tail.get(&session.processor().plugin_handle()),
+            TailLength::Infinite
+        );
+    }
+}
+
+mod audio_ports {
+    use super::*;
+    use clack_extensions::audio_ports::snapshot::{AudioPortEntry, AudioPortLayoutSnapshot};
+    use clack_extensions::audio_ports::{PluginAudioPorts, RescanType};
+
+    #[test]
+    fn plugin_to_host_enumeration() {
+        let mut session = TestHost::new();
+        let ports: PluginAudioPorts = session.extension();
+
+        let snapshot = AudioPortLayoutSnapshot::capture(&ports, &mut session.plugin());
+
+        let inputs: Vec<_> = AUDIO_INPUTS.iter().map(AudioPortEntry::from_info).collect();
+        let outputs: Vec<_> = AUDIO_OUTPUTS
+            .iter()
+            .map(AudioPortEntry::from_info)
+            .collect();
+        assert_eq!(
+            snapshot.inputs(),
+            inputs,
+            "The host didn't receive the input ports the plugin declared"
+        );
+        assert_eq!(
+            snapshot.outputs(),
+            outputs,
+            "The host didn't receive the output ports the plugin declared"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host_rescan() {
+        let mut session = TestHost::new();
+
+        for flags in [RescanType::NAMES, RescanType::LIST] {
+            session.send(PluginCommand::RescanAudioPorts(flags));
+            assert_eq!(
+                session.take_host_events(),
+                [HostEvent::AudioPortsRescanned(flags)]
+            );
+        }
+    }
+}
+
+mod extensible_audio_ports {
+    use super::*;
+    use clack_extensions::audio_ports::snapshot::{
+        AudioPortChange, AudioPortEntry, AudioPortLayoutSnapshot,
+    };
+    use clack_extensions::audio_ports::{AudioPortFlags, AudioPortType, PluginAudioPorts};
+    use clack_extensions::extensible_audio_ports::{
+        ExtensibleAudioPortsError, PluginExtensibleAudioPorts,
+    };
+
+    fn capture(session: &mut TestHost) -> AudioPortLayoutSnapshot {
+        let ports: PluginAudioPorts = session.extension();
+        AudioPortLayoutSnapshot::capture(&ports, &mut session.plugin())
+    }
+
+    #[test]
+    fn host_to_plugin() {
+        let main_thread = unsafe { MainThreadToken::new_unchecked() };
+        let mut session = TestHost::new();
+        let extensible: PluginExtensibleAudioPorts = session.extension();
+        let mut layout = capture(&mut session);
+
+        let update = extensible
+            .add_port(
+                session.instance(),
+                &main_thread,
+                &mut layout,
+                true,
+                2,
+                Some(AudioPortType::STEREO),
+            )
+            .unwrap();
+        let rejected = extensible.add_port(
+            session.instance(),
+            &main_thread,
+            &mut layout,
+            false,
+            1,
+            None,
+        );
+        assert_eq!(rejected, Err(ExtensibleAudioPortsError::Rejected));
+
+        extensible
+            .remove_port(
+                session.instance(),
+                &main_thread,
+                &mut layout,
+                true,
+                update.port_id,
+            )
+            .unwrap();
+
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::AudioPortAdded {
+                    is_input: true,
+                    channel_count: 2,
+                    port_type: Some("stereo".into())
+                },
+                PluginEvent::AudioPortAdded {
+                    is_input: false,
+                    channel_count: 1,
+                    port_type: None
+                },
+                PluginEvent::AudioPortRemoved {
+                    is_input: true,
+                    port_id: update.port_id
+                },
+            ],
+            "The plugin didn't receive the port changes the host made"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let main_thread = unsafe { MainThreadToken::new_unchecked() };
+        let mut session = TestHost::new();
+        let extensible: PluginExtensibleAudioPorts = session.extension();
+        let mut layout = capture(&mut session);
+
+        let update = extensible
+            .add_port(session.instance(), &main_thread, &mut layout, true, 1, None)
+            .unwrap();
+
+        assert_eq!(update.port_id, FIRST_ADDED_AUDIO_INPUT);
+        assert_eq!(
+            update.changes,
+            [AudioPortChange::Added {
+                is_input: true,
+                id: FIRST_ADDED_AUDIO_INPUT
+            }],
+            "The host didn't receive the port the plugin added"
+        );
+        assert_eq!(
+            layout.inputs().last(),
+            Some(&AudioPortEntry {
+                id: FIRST_ADDED_AUDIO_INPUT,
+                name: "Added In".into(),
+                channel_count: 1,
+                flags: AudioPortFlags::empty(),
+                port_type: Some("mono".into()),
+                in_place_pair: None,
+            }),
+            "The host didn't receive the information of the port the plugin added"
+        );
+
+        let update = extensible
+            .remove_port(
+                session.instance(),
+                &main_thread,
+                &mut layout,
+                true,
+                FIRST_ADDED_AUDIO_INPUT,
+            )
+            .unwrap();
+
+        assert_eq!(
+            update.changes,
+            [AudioPortChange::Removed {
+                is_input: true,
+                id: FIRST_ADDED_AUDIO_INPUT
+            }],
+            "The host didn't see the port the plugin removed"
+        );
+        assert_eq!(layout, capture(&mut session));
+    }
+}
+
+mod note_ports {
+    use super::*;
+    use clack_extensions::note_ports::snapshot::{NotePortEntry, NotePortLayoutSnapshot};
+    use clack_extensions::note_ports::{NoteDialects, NotePortRescanFlags, PluginNotePorts};
+
+    #[test]
+    fn plugin_to_host_enumeration() {
+        let mut session = TestHost::new();
+        let ports: PluginNotePorts = session.extension();
+
+        let snapshot = NotePortLayoutSnapshot::capture(&ports, &mut session.plugin());
+
+        let inputs: Vec<_> = NOTE_INPUTS.iter().map(NotePortEntry::from_info).collect();
+        assert_eq!(
+            snapshot.inputs(),
+            inputs,
+            "The host didn't receive the input ports the plugin declared"
+        );
+        assert_eq!(snapshot.outputs(), []);
+    }
+
+    #[test]
+    fn plugin_to_host_rescan() {
+        let mut session = TestHost::new();
+
+        for flags in [NotePortRescanFlags::NAMES, NotePortRescanFlags::ALL] {
+            session.send(PluginCommand::RescanNotePorts(flags));
+            assert_eq!(
+                session.take_host_events(),
+                [HostEvent::NotePortsRescanned(flags)]
+            );
+        }
+    }
+
+    #[test]
+    fn host_to_plugin_dialects() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::QueryNoteDialects);
+        assert_eq!(
+            take_plugin_events(),
+            [PluginEvent::HostNoteDialects(
+                NoteDialects::CLAP | NoteDialects::MIDI
+            )],
+            "The plugin didn't receive the note dialects the host supports"
+        );
+    }
+}
+
+mod timer {
+    use super::*;
+    use clack_extensions::timer::PluginTimer;
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        let timers = session
+            .instance()
+            .access_handler_unchecked(|h| h.timers().to_vec());
+        assert_eq!(
+            timers.iter().map(|&(_, period)| period).collect::<Vec<_>>(),
+            [TIMER_PERIOD_MS],
+            "The host didn't receive the timer the plugin registered"
+        );
+        let (timer_id, _) = timers[0];
+
+        session.send(PluginCommand::UnregisterTimer);
+        assert_eq!(
+            session.take_host_events(),
+            [HostEvent::TimerUnregistered(timer_id)]
+        );
+        assert!(session
+            .instance()
+            .access_handler_unchecked(|h| h.timers().is_empty()));
+    }
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let timer: PluginTimer = session.extension();
+
+        let (timer_id, _) = session
+            .instance()
+            .access_handler_unchecked(|h| h.timers()[0]);
+        timer.on_timer(&mut session.plugin(), timer_id);
+        timer.on_timer(&mut session.plugin(), timer_id);
+
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::TimerFired(timer_id),
+                PluginEvent::TimerFired(timer_id)
+            ],
+            "The plugin didn't receive the timer ticks the host fired"
+        );
+    }
+}
+
+mod log {
+    use super::*;
+    use clack_extensions::log::LogSeverity;
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        for severity in [LogSeverity::Debug, LogSeverity::Warning, LogSeverity::Error] {
+            session.send(PluginCommand::Log(
+                severity,
+                format!("{severity:?} message"),
+            ));
+            assert_eq!(
+                session.take_host_events(),
+                [HostEvent::Logged(severity, format!("{severity:?} message"))],
+                "The host didn't receive the message the plugin logged"
+            );
+        }
+
+        session.activate();
+        assert!(session.take_host_events().contains(&HostEvent::Logged(
+            LogSeverity::Info,
+            "Activated at 48000 Hz".into()
+        )));
+    }
+}
+
+mod voice_info {
+    use super::*;
+    use clack_extensions::voice_info::{PluginVoiceInfo, VoiceInfoFlags};
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+        let voice_info: PluginVoiceInfo = session.extension();
+
+        let info = voice_info.get(&mut session.plugin()).unwrap();
+        assert_eq!(info.voice_count, 0);
+        assert_eq!(info.voice_capacity, VOICE_CAPACITY);
+        assert_eq!(info.flags, VoiceInfoFlags::SUPPORTS_OVERLAPPING_NOTES);
+
+        session.send(PluginCommand::SetVoiceCount(3));
+        assert_eq!(session.take_host_events(), [HostEvent::VoiceInfoChanged]);
+
+        let info = voice_info.get(&mut session.plugin()).unwrap();
+        assert_eq!(
+            info.voice_count, 3,
+            "The host didn't receive the plugin's new voice count"
+        );
+    }
+}
+
+mod gui {
+    use super::*;
+    use clack_extensions::gui::{GuiApiType, GuiConfiguration, GuiSize, PluginGui};
+    use std::ffi::CStr;
+
+    #[test]
+    fn host_to_plugin_lifecycle() {
+        let mut session = TestHost::new();
+        let gui: PluginGui = session.extension();
+        let mut plugin = session.plugin();
+
+        let Some(api_type) = GuiApiType::default_for_current_platform() else {
+            return;
+        };
+        let embedded = GuiConfiguration {
+            api_type,
+            is_floating: false,
+        };
+        let floating = GuiConfiguration {
+            api_type,
+            is_floating: true,
+        };
+
+        assert_eq!(gui.get_preferred_api(&mut plugin), Some(floating));
+        assert!(!gui.is_api_supported(&mut plugin, embedded));
+        assert!(gui.is_api_supported(&mut plugin, floating));
+        assert!(gui.create(&mut plugin, embedded).is_err());
+
+        gui.create(&mut plugin, floating).unwrap();
+        gui.set_scale(&mut plugin, 2.0).unwrap();
+        assert_eq!(gui.get_size(&mut plugin), Some(GUI_SIZE));
+        assert!(gui.can_resize(&mut plugin));
+
+        let size = GuiSize {
+            width: 640,
+            height: 480,
+        };
+        gui.set_size(&mut plugin, size).unwrap();
+        assert_eq!(gui.get_size(&mut plugin), Some(size));
+
+        let title = CStr::from_bytes_with_nul(b"Fixture (Track 1)\0").unwrap();
+        gui.suggest_title(&mut plugin, title);
+        gui.show(&mut plugin).unwrap();
+        gui.hide(&mut plugin).unwrap();
+        gui.destroy(&mut plugin);
+
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::GuiCreated {
+                    api: api_type.0.to_string_lossy().into_owned(),
+                    is_floating: true
+                },
+                PluginEvent::GuiScaleSet(2.0),
+                PluginEvent::GuiSizeSet(size),
+                PluginEvent::GuiTitleSuggested("Fixture (Track 1)".into()),
+                PluginEvent::GuiShown,
+                PluginEvent::GuiHidden,
+                PluginEvent::GuiDestroyed,
+            ],
+            "The plugin didn't receive the GUI calls the host made"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        let size = GuiSize {
+            width: 800,
+            height: 600,
+        };
+        session.send(PluginCommand::RequestGuiResize(size));
+        session.send(PluginCommand::CloseGui {
+            was_destroyed: true,
+        });
+
+        assert_eq!(
+            session.take_host_events(),
+            [
+                HostEvent::GuiResizeRequested(size),
+                HostEvent::GuiClosed {
+                    was_destroyed: true
+                }
+            ],
+            "The host didn't receive the GUI requests the plugin made"
+        );
+    }
+}
+
+mod context_menu {
+    use super::*;
+    use clack_extensions::context_menu::{
+        ContextMenuItem, ContextMenuTarget, MenuItem, MenuModel, PluginContextMenu,
+    };
+    use clack_tests::host::LEARN_MIDI_ACTION;
+    use std::ffi::CString;
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let context_menu: PluginContextMenu = session.extension();
+        let mut plugin = session.plugin();
+        let target = ContextMenuTarget::Param(GAIN);
+
+        let mut menu = MenuModel::new();
+        assert!(context_menu.populate(&mut plugin, target, &mut menu));
+        assert_eq!(
+            menu.items(),
+            [
+                MenuItem::Title {
+                    title: CString::new("Fixture").unwrap(),
+                    is_enabled: true
+                },
+                MenuItem::Entry {
+                    label: CString::new("Reset").unwrap(),
+                    is_enabled: true,
+                    action_id: RESET_ACTION
+                },
+            ],
+            "The host didn't receive the menu items the plugin added"
+        );
+
+        assert!(context_menu.perform(&mut plugin, target, RESET_ACTION));
+        assert!(!context_menu.perform(&mut plugin, target, ClapId::new(999)));
+
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::ContextMenuPopulated(target),
+                PluginEvent::ContextMenuPerformed(target, RESET_ACTION),
+            ],
+            "The plugin didn't receive the context menu calls the host made"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+        let target = ContextMenuTarget::Global;
+
+        session.send(PluginCommand::PopulateHostContextMenu(target));
+        session.send(PluginCommand::PerformHostContextMenuAction(
+            target,
+            LEARN_MIDI_ACTION,
+        ));
+        session.send(PluginCommand::PopupHostContextMenu {
+            target,
+            screen_index: 1,
+            x: 120,
+            y: -40,
+        });
+
+        let mut expected_menu = MenuModel::new();
+        expected_menu.push(ContextMenuItem::Entry {
+            label: &CString::new("Learn MIDI").unwrap(),
+            is_enabled: true,
+            action_id: LEARN_MIDI_ACTION,
+        });
+
+        assert_eq!(
+            take_plugin_events(),
+            [PluginEvent::HostContextMenu(expected_menu)],
+            "The plugin didn't receive the menu items the host added"
+        );
+        assert_eq!(
+            session.take_host_events(),
+            [
+                HostEvent::ContextMenuPerformed(target, LEARN_MIDI_ACTION),
+                HostEvent::ContextMenuPopupRequested {
+                    target,
+                    screen_index: 1,
+                    x: 120,
+                    y: -40
+                },
+            ],
+            "The host didn't receive the context menu calls the plugin made"
+        );
+    }
+}
+
+mod audio_ports_config {
+    use super::*;
+    use clack_extensions::audio_ports_config::{AudioPortsConfigBuffer, PluginAudioPortsConfig};
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let configs: PluginAudioPortsConfig = session.extension();
+        let mut plugin = session.plugin();
+
+        assert_eq!(configs.count(&mut plugin), AUDIO_PORTS_CONFIGS.len());
+        for (index, expected) in AUDIO_PORTS_CONFIGS.iter().enumerate() {
+            let mut buffer = AudioPortsConfigBuffer::new();
+            let config = configs.get(&mut plugin, index, &mut buffer).unwrap();
+
+            assert_eq!(config.id, expected.id);
+            assert_eq!(
+                config.name, expected.name,
+                "The host didn't receive the configuration the plugin declared"
+            );
+            assert_eq!(config.input_port_count, expected.input_port_count);
+            assert_eq!(config.output_port_count, expected.output_port_count);
+            assert_eq!(config.main_input, expected.main_input);
+            assert_eq!(config.main_output, expected.main_output);
+        }
+
+        let mut buffer = AudioPortsConfigBuffer::new();
+        assert!(configs
+            .get(&mut plugin, AUDIO_PORTS_CONFIGS.len(), &mut buffer)
+            .is_none());
+
+        let selected = AUDIO_PORTS_CONFIGS[1].id;
+        assert!(configs.select(&mut plugin, selected).is_ok());
+        assert!(configs.select(&mut plugin, ClapId::new(999)).is_err());
+
+        assert_eq!(
+            take_plugin_events(),
+            [PluginEvent::AudioPortsConfigSelected(selected)],
+            "The plugin didn't receive the configuration the host selected"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::RescanAudioPortsConfigs);
+        assert_eq!(
+            session.take_host_events(),
+            [HostEvent::AudioPortsConfigsRescanned]
+        );
+    }
+}
+
+mod note_name {
+    use super::*;
+    use clack_extensions::note_name::{NoteNameBuffer, PluginNoteName};
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let note_name: PluginNoteName = session.extension();
+        let mut plugin = session.plugin();
+
+        assert_eq!(note_name.count(&mut plugin), NOTE_NAMES.len());
+        for (index, expected) in NOTE_NAMES.iter().enumerate() {
+            let mut buffer = NoteNameBuffer::new();
+            let name = note_name.get(&mut plugin, index, &mut buffer).unwrap();
+
+            assert_eq!(
+                name.name, expected.name,
+                "The host didn't receive the note name the plugin declared"
+            );
+            assert_eq!(name.port, expected.port);
+            assert_eq!(name.channel, expected.channel);
+            assert_eq!(name.key, expected.key);
+        }
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::ChangeNoteNames);
+        assert_eq!(session.take_host_events(), [HostEvent::NoteNamesChanged]);
+    }
+}
+
+mod render {
+    use super::*;
+    use clack_extensions::render::{PluginRender, RenderMode};
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let render: PluginRender = session.extension();
+        let mut plugin = session.plugin();
+
+        assert_eq!(
+            render.has_realtime_requirement(&mut plugin),
+            HARD_REALTIME_REQUIREMENT
+        );
+
+        for mode in [RenderMode::Offline, RenderMode::Realtime] {
+            assert!(render.set(&mut plugin, mode).is_ok());
+        }
+
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::RenderModeSet(RenderMode::Offline),
+                PluginEvent::RenderModeSet(RenderMode::Realtime)
+            ],
+            "The plugin didn't receive the render modes the host set"
+        );
+    }
+}
+
+mod thread_check {
+    use super::*;
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::CheckThreads);
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::ThreadChecked {
+                    from_main_thread: true,
+                    is_main_thread: Some(true),
+                    is_audio_thread: Some(false)
+                },
+                PluginEvent::ThreadChecked {
+                    from_main_thread: false,
+                    is_main_thread: Some(false),
+                    is_audio_thread: Some(true)
+                },
+            ],
+            "The plugin didn't receive the host's view of its threads"
+        );
+    }
+}
+
+mod thread_pool {
+    use super::*;
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+        session.activate();
+        session.take_host_events();
+        take_plugin_events();
+
+        session.send(PluginCommand::RequestTasks(3));
+        session.process(&mut EventIo::new());
+
+        assert_eq!(session.take_host_events(), [HostEvent::TasksRequested(3)]);
+        assert_eq!(
+            take_plugin_events(),
+            [PluginEvent::TasksExecuted(vec![0, 1, 2])],
+            "The plugin didn't receive the tasks the host ran"
+        );
+    }
+}
+
+mod event_registry {
+    use super::*;
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::QueryCoreEventSpace);
+        assert_eq!(
+            session.take_host_events(),
+            [HostEvent::EventSpaceQueried(String::new())]
+        );
+        assert_eq!(
+            take_plugin_events(),
+            [PluginEvent::CoreEventSpaceQueried(Some(0))],
+            "The plugin didn't receive the ID the host gave to the core event space"
+        );
+    }
+}
+
+#[cfg(unix)]
+mod posix_fd {
+    use super::*;
+    use clack_extensions::posix_fd::{FdFlags, PluginPosixFd};
+
+    #[test]
+    fn host_to_plugin() {
+        let mut session = TestHost::new();
+        let posix_fd: PluginPosixFd = session.extension();
+
+        posix_fd.on_fd(&mut session.plugin(), 5, FdFlags::READ);
+        posix_fd.on_fd(&mut session.plugin(), 5, FdFlags::READ | FdFlags::ERROR);
+
+        assert_eq!(
+            take_plugin_events(),
+            [
+                PluginEvent::FdEvent(5, FdFlags::READ),
+                PluginEvent::FdEvent(5, FdFlags::READ | FdFlags::ERROR)
+            ],
+            "The plugin didn't receive the file descriptor events the host reported"
+        );
+    }
+
+    #[test]
+    fn plugin_to_host() {
+        let mut session = TestHost::new();
+
+        session.send(PluginCommand::RegisterFd(5, FdFlags::READ));
+        session.send(PluginCommand::ModifyFd(5, FdFlags::READ | FdFlags::WRITE));
+        session.send(PluginCommand::UnregisterFd(5));
+
+        assert_eq!(
+            session.take_host_events(),
+            [
+                HostEvent::FdRegistered(5, FdFlags::READ),
+                HostEvent::FdModified(5, FdFlags::READ | FdFlags::WRITE),
+                HostEvent::FdUnregistered(5)
+            ],
+            "The host didn't receive the file descriptors the plugin registered"
+        );
+    }
+}
+
+mod declared_extensions {
+    use super::*;
+    use clack_extensions::audio_ports::PluginAudioPorts;
+    use clack_extensions::audio_ports_config::PluginAudioPortsConfig;
+    use clack_extensions::context_menu::PluginContextMenu;
+    use clack_extensions::extensible_audio_ports::PluginExtensibleAudioPorts;
+    use clack_extensions::gui::PluginGui;
+    use clack_extensions::latency::PluginLatency;
+    use clack_extensions::note_name::PluginNoteName;
+    use clack_extensions::note_ports::PluginNotePorts;
+    #[cfg(unix)]
+    use clack_extensions::posix_fd::PluginPosixFd;
+    use clack_extensions::render::PluginRender;
+    use clack_extensions::state::PluginState;
+    use clack_extensions::tail::PluginTail;
+    use clack_extensions::thread_pool::PluginThreadPool;
+    use clack_extensions::timer::PluginTimer;
+    use clack_extensions::voice_info::PluginVoiceInfo;
+    use clack_host::extensions::Extension;
+
+    #[test]
+    fn plugin_lists_registered_extensions() {
+        let session = TestHost::new();
+
+        #[allow(unused_mut)]
+        let mut expected = vec![
+            PluginAudioPorts::IDENTIFIER,
+            PluginAudioPortsConfig::IDENTIFIER,
+            PluginContextMenu::IDENTIFIER,
+            PluginExtensibleAudioPorts::IDENTIFIER,
+            PluginGui::IDENTIFIER,
+            PluginLatency::IDENTIFIER,
+            PluginNoteName::IDENTIFIER,
+            PluginNotePorts::IDENTIFIER,
+            PluginParams::IDENTIFIER,
+            PluginRender::IDENTIFIER,
+            PluginState::IDENTIFIER,
+            PluginTail::IDENTIFIER,
+            PluginThreadPool::IDENTIFIER,
+            PluginTimer::IDENTIFIER,
+            PluginVoiceInfo::IDENTIFIER,
+        ];
+        #[cfg(unix)]
+        expected.push(PluginPosixFd::IDENTIFIER);
+
+        assert_eq!(
+            session.declared_extensions(),
+            expected,
+            "The plugin didn't list the extensions it registered"
+        );
+    }
+}